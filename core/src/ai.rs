@@ -0,0 +1,446 @@
+//! Клиент модели ИИ.
+//!
+//! [`AiClient`] оборачивает поставщика ([`AiProvider`]) — например,
+//! OpenAI-совместимый HTTP-сервис из секции `[ai]` ([`HttpProvider`], флаг
+//! `ai`):
+//!
+//! - перед отправкой из запроса вырезаются секреты по выражениям `redact`
+//!   ([`Redactor`]), так что код с паролями не уходит наружу;
+//! - ответы запоминаются по отпечатку модели и очищенного запроса, и
+//!   повторный запрос не обращается к поставщику;
+//! - число одновременных запросов и их частота ограничены ([`Limits`]):
+//!   лишние запросы ждут своей очереди;
+//! - ответ передаётся по частям функции `on_token` по мере получения, чтобы
+//!   его можно было показывать постепенно.
+//!
+//! Ошибки делятся на отсутствие связи и отказ ([`OpError`]), как у других
+//! обращений к серверу.
+
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::config::AiConfig;
+use crate::offline_queue::OpError;
+
+/// Замена вырезанного секрета.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Запрос к модели.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct AiRequest {
+    /// Системная инструкция.
+    pub system: Option<String>,
+    pub prompt: String,
+    /// Наибольшая длина ответа в токенах.
+    pub max_tokens: Option<u32>,
+}
+
+impl AiRequest {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_system(mut self, system: impl Into<String>) -> Self {
+        self.system = Some(system.into());
+        self
+    }
+}
+
+/// Поставщик ответов модели.
+pub trait AiProvider: Send + Sync {
+    /// Имя модели; входит в ключ кэша.
+    fn model(&self) -> &str;
+    /// Ответ на `request`. Части ответа по мере получения передаются
+    /// `on_token`; возвращается весь ответ.
+    fn complete(
+        &self,
+        request: &AiRequest,
+        on_token: &mut dyn FnMut(&str),
+    ) -> Result<String, OpError>;
+}
+
+/// Вырезает секреты из текста по регулярным выражениям.
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    pub fn new(patterns: &[String]) -> Result<Self, regex::Error> {
+        let patterns = patterns
+            .iter()
+            .map(|p| Regex::new(p))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Текст с секретами, заменёнными на [`REDACTED`]: если в выражении
+    /// есть группа, заменяется первая группа, иначе всё совпадение.
+    pub fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut out = Cow::Borrowed(text);
+        for re in &self.patterns {
+            if !re.is_match(&out) {
+                continue;
+            }
+            let replaced = re
+                .replace_all(&out, |caps: &regex::Captures| {
+                    let whole = caps.get(0).expect("group 0 always matches");
+                    match caps.get(1) {
+                        Some(group) => {
+                            let start = group.start() - whole.start();
+                            let end = group.end() - whole.start();
+                            let text = whole.as_str();
+                            format!("{}{REDACTED}{}", &text[..start], &text[end..])
+                        }
+                        None => REDACTED.to_string(),
+                    }
+                })
+                .into_owned();
+            out = Cow::Owned(replaced);
+        }
+        out
+    }
+}
+
+/// Ограничения клиента.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Сколько запросов выполняется одновременно; `0` считается за `1`.
+    pub max_concurrent: usize,
+    /// Не больше стольких запросов за `window`.
+    pub max_requests: Option<u32>,
+    pub window: Duration,
+    /// Сколько ответов помнить; `0` отключает кэш.
+    pub cache_size: usize,
+}
+
+impl From<&AiConfig> for Limits {
+    fn from(config: &AiConfig) -> Self {
+        Self {
+            max_concurrent: config.max_concurrent,
+            max_requests: config.requests_per_minute,
+            window: Duration::from_secs(60),
+            cache_size: config.cache_size,
+        }
+    }
+}
+
+#[derive(Default)]
+struct LimiterState {
+    active: usize,
+    /// Время начала запросов за последнее окно.
+    started: VecDeque<Instant>,
+}
+
+/// Допуск запросов по [`Limits`].
+struct Limiter {
+    limits: Limits,
+    state: Mutex<LimiterState>,
+    released: Condvar,
+}
+
+/// Место выполняющегося запроса; освобождается при удалении.
+struct Permit<'a>(&'a Limiter);
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        let mut state = self.0.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.active -= 1;
+        self.0.released.notify_all();
+    }
+}
+
+impl Limiter {
+    fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            state: Mutex::default(),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Ждёт, пока запрос можно будет выполнить.
+    fn acquire(&self) -> Permit<'_> {
+        let max_concurrent = self.limits.max_concurrent.max(1);
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            let now = Instant::now();
+            while state
+                .started
+                .front()
+                .is_some_and(|t| now.duration_since(*t) >= self.limits.window)
+            {
+                state.started.pop_front();
+            }
+            let rate_wait = match self.limits.max_requests {
+                Some(max) if state.started.len() >= max.max(1) as usize => state
+                    .started
+                    .front()
+                    .map(|t| self.limits.window.saturating_sub(now.duration_since(*t))),
+                _ => None,
+            };
+            if state.active < max_concurrent && rate_wait.is_none() {
+                state.active += 1;
+                state.started.push_back(now);
+                return Permit(self);
+            }
+            state = match rate_wait {
+                Some(wait) if state.active < max_concurrent => {
+                    self.released
+                        .wait_timeout(state, wait)
+                        .unwrap_or_else(|e| e.into_inner())
+                        .0
+                }
+                _ => self.released.wait(state).unwrap_or_else(|e| e.into_inner()),
+            };
+        }
+    }
+}
+
+/// Ответ клиента.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AiResponse {
+    pub text: String,
+    /// Ответ взят из кэша.
+    pub cached: bool,
+}
+
+#[derive(Default)]
+struct Cache {
+    answers: HashMap<u64, String>,
+    /// Ключи в порядке добавления; первым вытесняется самый старый.
+    order: VecDeque<u64>,
+}
+
+/// Клиент модели с очисткой запросов, кэшем и ограничениями.
+pub struct AiClient {
+    provider: Box<dyn AiProvider>,
+    redactor: Redactor,
+    limiter: Limiter,
+    cache: Mutex<Cache>,
+}
+
+impl std::fmt::Debug for AiClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AiClient")
+            .field("model", &self.provider.model())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AiClient {
+    pub fn new(provider: Box<dyn AiProvider>, redactor: Redactor, limits: Limits) -> Self {
+        Self {
+            provider,
+            redactor,
+            limiter: Limiter::new(limits),
+            cache: Mutex::default(),
+        }
+    }
+
+    /// Клиент по секции `[ai]`; ошибка, если `endpoint` не задан или
+    /// выражение `redact` некорректно.
+    #[cfg(feature = "ai")]
+    pub fn from_config(config: &AiConfig) -> Result<Self, String> {
+        let provider = HttpProvider::from_config(config)?;
+        let redactor = Redactor::new(&config.redact).map_err(|e| e.to_string())?;
+        Ok(Self::new(
+            Box::new(provider),
+            redactor,
+            Limits::from(config),
+        ))
+    }
+
+    /// Запрос в том виде, в котором он уйдёт поставщику.
+    pub fn redacted(&self, request: &AiRequest) -> AiRequest {
+        AiRequest {
+            system: request
+                .system
+                .as_deref()
+                .map(|s| self.redactor.redact(s).into_owned()),
+            prompt: self.redactor.redact(&request.prompt).into_owned(),
+            max_tokens: request.max_tokens,
+        }
+    }
+
+    fn cache_key(&self, request: &AiRequest) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.provider.model().hash(&mut hasher);
+        request.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Ответ модели на `request`. Части ответа передаются `on_token` по
+    /// мере получения; ответ из кэша передаётся одной частью. Если
+    /// ограничения исчерпаны, вызов ждёт.
+    pub fn complete(
+        &self,
+        request: &AiRequest,
+        mut on_token: impl FnMut(&str),
+    ) -> Result<AiResponse, OpError> {
+        let request = self.redacted(request);
+        let key = self.cache_key(&request);
+        let cached = self
+            .cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .answers
+            .get(&key)
+            .cloned();
+        if let Some(text) = cached {
+            on_token(&text);
+            return Ok(AiResponse { text, cached: true });
+        }
+        let text = {
+            let _permit = self.limiter.acquire();
+            self.provider.complete(&request, &mut on_token)?
+        };
+        let cache_size = self.limiter.limits.cache_size;
+        if cache_size > 0 {
+            let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+            if cache.answers.insert(key, text.clone()).is_none() {
+                cache.order.push_back(key);
+            }
+            while cache.order.len() > cache_size {
+                if let Some(old) = cache.order.pop_front() {
+                    cache.answers.remove(&old);
+                }
+            }
+        }
+        Ok(AiResponse {
+            text,
+            cached: false,
+        })
+    }
+}
+
+/// Строка потока ответа `chat/completions` (server-sent events).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamLine {
+    /// Очередная часть ответа.
+    Token(String),
+    /// Ответ закончен.
+    Done,
+    /// Строка без текста ответа.
+    Skip,
+}
+
+/// Разбирает строку потока ответа: `data: {...}` с
+/// `choices[0].delta.content` или `data: [DONE]`.
+pub fn parse_stream_line(line: &str) -> StreamLine {
+    let Some(data) = line.strip_prefix("data:") else {
+        return StreamLine::Skip;
+    };
+    let data = data.trim();
+    if data == "[DONE]" {
+        return StreamLine::Done;
+    }
+    serde_json::from_str::<Value>(data)
+        .ok()
+        .and_then(|v| {
+            v.pointer("/choices/0/delta/content")
+                .and_then(Value::as_str)
+                .filter(|s| !s.is_empty())
+                .map(|s| StreamLine::Token(s.to_string()))
+        })
+        .unwrap_or(StreamLine::Skip)
+}
+
+/// OpenAI-совместимый поставщик: `POST {endpoint}` с
+/// `{"model", "messages", "stream": true}`, ответ — поток строк
+/// `data: {...}` ([`parse_stream_line`]).
+#[cfg(feature = "ai")]
+pub struct HttpProvider {
+    endpoint: String,
+    model: String,
+    token: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "ai")]
+impl HttpProvider {
+    /// Поставщик по секции `[ai]`; ошибка, если `endpoint` не задан.
+    pub fn from_config(config: &AiConfig) -> Result<Self, String> {
+        let endpoint = config
+            .endpoint
+            .clone()
+            .ok_or("в секции [ai] не задан endpoint")?;
+        let token = config
+            .token_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok());
+        let client = reqwest::blocking::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            endpoint,
+            model: config.model.clone().unwrap_or_default(),
+            token,
+            client,
+        })
+    }
+}
+
+#[cfg(feature = "ai")]
+impl AiProvider for HttpProvider {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn complete(
+        &self,
+        request: &AiRequest,
+        on_token: &mut dyn FnMut(&str),
+    ) -> Result<String, OpError> {
+        use std::io::BufRead;
+
+        use crate::offline_queue::http_error;
+
+        let mut messages = Vec::new();
+        if let Some(system) = &request.system {
+            messages.push(serde_json::json!({ "role": "system", "content": system }));
+        }
+        messages.push(serde_json::json!({ "role": "user", "content": request.prompt }));
+        let mut body = serde_json::json!({
+            "model": self.model,
+            "messages": messages,
+            "stream": true,
+        });
+        if let Some(max_tokens) = request.max_tokens {
+            body["max_tokens"] = max_tokens.into();
+        }
+        let mut http = self.client.post(&self.endpoint).json(&body);
+        if let Some(token) = &self.token {
+            http = http.bearer_auth(token);
+        }
+        let response = http
+            .send()
+            .and_then(|r| r.error_for_status())
+            .map_err(http_error)?;
+        let mut text = String::new();
+        for line in std::io::BufReader::new(response).lines() {
+            let line = line.map_err(|e| OpError::Offline(e.to_string()))?;
+            match parse_stream_line(&line) {
+                StreamLine::Token(token) => {
+                    on_token(&token);
+                    text.push_str(&token);
+                }
+                StreamLine::Done => break,
+                StreamLine::Skip => {}
+            }
+        }
+        Ok(text)
+    }
+}
@@ -0,0 +1,5 @@
+//! Анализ кода по синтаксическому дереву.
+
+pub mod dead_code;
+pub mod metrics;
+pub mod security;
@@ -0,0 +1,477 @@
+//! Мёртвый код и записи метаданных без блоков.
+//!
+//! Функция считается неиспользуемой, если её имя не встречается в
+//! проекте нигде, кроме её собственного определения: ни в вызовах, ни в
+//! импортах, ни в обращениях к методам. Имена сравниваются без учёта
+//! области видимости, поэтому одноимённая функция в другом файле тоже
+//! считается использованием. Точки входа не проверяются: `main`, `pub`
+//! функции Rust, `export` в JavaScript, функции с атрибутами и
+//! декораторами, методы реализаций трейтов, тесты и методы вида
+//! `__init__`.
+//!
+//! Запись `@VISUAL_META` считается осиротевшей, если в файле нет блока с
+//! её идентификатором. Сводки, шаблоны и базы `extends` с блоками не
+//! связаны и не проверяются.
+//!
+//! [`clean`] удаляет осиротевшие записи и по запросу закомментирует
+//! неиспользуемые функции, предварительно сохранив файлы в резервную копию.
+
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tree_sitter::Node;
+
+use crate::blocks::{lang_from_extension, parse_blocks_with_limits, source_files};
+use crate::config::ParseLimits;
+use crate::file_io;
+use crate::lint::{Finding, Severity, Span};
+use crate::meta::{backup, comment_detector, summary, templates, VisualMeta};
+use crate::parser::{parse, Lang};
+use crate::text::PositionIndex;
+
+/// Функция нигде не используется.
+pub const UNUSED_FUNCTION: &str = "code/unused-function";
+/// Запись `@VISUAL_META` не связана ни с одним блоком.
+pub const ORPHAN_META: &str = "meta/orphan";
+
+/// Имена функций, которые вызываются извне проекта.
+const ENTRY_POINTS: &[&str] = &["main", "constructor"];
+
+/// Неиспользуемая функция.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnusedFunction {
+    pub file: PathBuf,
+    /// `visual_id` блока функции.
+    pub id: String,
+    pub name: String,
+    /// Строка начала функции, с нуля.
+    pub line: usize,
+    /// Байтовый диапазон функции.
+    pub range: (usize, usize),
+    /// Положение имени функции.
+    pub span: Span,
+}
+
+/// Запись `@VISUAL_META` без блока.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OrphanMeta {
+    pub file: PathBuf,
+    pub id: String,
+    /// Байтовый диапазон комментария записи.
+    pub range: (usize, usize),
+    pub span: Span,
+}
+
+/// Итог поиска мёртвого кода.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DeadCodeReport {
+    pub functions: Vec<UnusedFunction>,
+    pub orphans: Vec<OrphanMeta>,
+}
+
+impl DeadCodeReport {
+    /// Замечания проверки для `multicode lint` и SARIF.
+    pub fn findings(&self) -> Vec<Finding> {
+        let functions = self.functions.iter().map(|f| Finding {
+            rule: UNUSED_FUNCTION,
+            severity: Severity::Warning,
+            message: format!("функция {} нигде не используется", f.name),
+            file: f.file.clone(),
+            span: Some(f.span),
+        });
+        let orphans = self.orphans.iter().map(|o| Finding {
+            rule: ORPHAN_META,
+            severity: Severity::Warning,
+            message: format!("запись {} не связана ни с одним блоком", o.id),
+            file: o.file.clone(),
+            span: Some(o.span),
+        });
+        functions.chain(orphans).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.orphans.is_empty()
+    }
+}
+
+/// Сборщик: файлы добавляются по одному, а неиспользуемые функции
+/// определяются после просмотра всего проекта.
+#[derive(Debug, Default)]
+pub struct DeadCode {
+    /// Число упоминаний каждого имени в проекте.
+    references: HashMap<String, usize>,
+    /// Кандидаты и число упоминаний имени внутри самой функции.
+    candidates: Vec<(UnusedFunction, usize)>,
+    orphans: Vec<OrphanMeta>,
+}
+
+fn is_identifier(node: Node) -> bool {
+    node.kind().ends_with("identifier")
+}
+
+/// Имена, упомянутые в дереве: идентификаторы, а в HTML — слова в
+/// значениях атрибутов и во встроенных скриптах, например `onclick`.
+fn mentions<'a>(node: Node, source: &'a str, out: &mut Vec<(&'a str, usize)>) {
+    if node.kind().contains("comment") {
+        return;
+    }
+    if is_identifier(node) && node.child_count() == 0 {
+        out.push((&source[node.byte_range()], node.start_byte()));
+        return;
+    }
+    if matches!(node.kind(), "attribute_value" | "raw_text") {
+        let text = &source[node.byte_range()];
+        let mut start = None;
+        for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+            let word = c.is_alphanumeric() || c == '_' || c == '$';
+            match (word, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    out.push((&text[s..i], node.start_byte() + s));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        mentions(child, source, out);
+    }
+}
+
+/// Вызывается ли функция извне: точка входа, публичное API, тест или
+/// метод, который вызывает язык или фреймворк.
+fn is_entry_point(node: Node, name: &str, source: &str) -> bool {
+    if ENTRY_POINTS.contains(&name) || name.starts_with("test") {
+        return true;
+    }
+    if name.starts_with("__") && name.ends_with("__") {
+        return true;
+    }
+    let mut cursor = node.walk();
+    let public = node
+        .children(&mut cursor)
+        .any(|c| c.kind() == "visibility_modifier" && &source[c.byte_range()] == "pub");
+    if public {
+        return true;
+    }
+    if node
+        .prev_named_sibling()
+        .is_some_and(|p| p.kind() == "attribute_item")
+    {
+        return true;
+    }
+    let mut parent = node.parent();
+    while let Some(p) = parent {
+        match p.kind() {
+            "export_statement" | "decorated_definition" => return true,
+            "impl_item" if p.child_by_field_name("trait").is_some() => return true,
+            _ => {}
+        }
+        parent = p.parent();
+    }
+    false
+}
+
+/// Диапазон комментария без пробелов, которые захватывает шаблон.
+fn trimmed(content: &str, range: std::ops::Range<usize>) -> (usize, usize) {
+    let text = &content[range.clone()];
+    let start = range.start + (text.len() - text.trim_start().len());
+    (start, range.start + text.trim_end().len())
+}
+
+/// Идентификаторы записей, которые не обязаны совпадать с блоком.
+fn is_detached(meta: &VisualMeta, bases: &HashSet<String>) -> bool {
+    meta.id.starts_with(summary::FILE_PREFIX)
+        || meta.id.starts_with(summary::MODULE_PREFIX)
+        || meta.id.starts_with(templates::TEMPLATE_ID_PREFIX)
+        || bases.contains(&meta.id)
+}
+
+impl DeadCode {
+    /// Добавляет содержимое `content` файла `file`. Файлы неподдерживаемых
+    /// языков пропускаются.
+    pub fn add_file(&mut self, file: &Path, content: &str) {
+        let Some(lang) = file
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension)
+        else {
+            return;
+        };
+        let Ok(parsed_lang) = lang.parse::<Lang>() else {
+            return;
+        };
+        let Some(tree) = parse(content, parsed_lang, None) else {
+            return;
+        };
+        let root = tree.root_node();
+        let index = PositionIndex::new(content);
+        let mut names = Vec::new();
+        mentions(root, content, &mut names);
+        for (name, _) in &names {
+            *self.references.entry(name.to_string()).or_default() += 1;
+        }
+        let Some(parsed) =
+            parse_blocks_with_limits(content.to_string(), lang.into(), &ParseLimits::default())
+        else {
+            return;
+        };
+
+        let mut starts = HashSet::new();
+        for block in &parsed.blocks {
+            let (start, end) = block.range;
+            if block.kind != "Function/Define" || !starts.insert(start) {
+                continue;
+            }
+            let Some(node) = root
+                .descendant_for_byte_range(start, end)
+                .filter(|n| n.byte_range() == (start..end))
+            else {
+                continue;
+            };
+            let Some(name_node) = node.child_by_field_name("name") else {
+                continue;
+            };
+            let name = &content[name_node.byte_range()];
+            if is_entry_point(node, name, content) {
+                continue;
+            }
+            let inside = names
+                .iter()
+                .filter(|(n, offset)| *n == name && (start..end).contains(offset))
+                .count();
+            let function = UnusedFunction {
+                file: file.to_path_buf(),
+                id: block.visual_id.clone(),
+                name: name.to_string(),
+                line: node.start_position().row,
+                range: (start, end),
+                span: Span::of(&index, name_node.byte_range()),
+            };
+            self.candidates.push((function, inside));
+        }
+
+        // В упрощённом разборе нет вложенных блоков, и записи вложенных
+        // блоков выглядели бы осиротевшими.
+        if parsed.simplified {
+            return;
+        }
+        let ids: HashSet<&str> = parsed.blocks.iter().map(|b| b.visual_id.as_str()).collect();
+        let metas: Vec<(std::ops::Range<usize>, VisualMeta)> =
+            comment_detector::extract_with_ranges(content)
+                .into_iter()
+                .filter_map(|(range, json)| {
+                    let meta = serde_json::from_str::<VisualMeta>(&json?).ok()?;
+                    Some((range, meta))
+                })
+                .collect();
+        let bases: HashSet<String> = metas
+            .iter()
+            .filter_map(|(_, m)| m.extends.clone())
+            .collect();
+        for (range, meta) in metas {
+            if ids.contains(meta.id.as_str()) || is_detached(&meta, &bases) {
+                continue;
+            }
+            let (start, end) = trimmed(content, range);
+            self.orphans.push(OrphanMeta {
+                file: file.to_path_buf(),
+                id: meta.id,
+                range: (start, end),
+                span: Span::of(&index, start..end),
+            });
+        }
+    }
+
+    /// Итог по всем добавленным файлам.
+    pub fn finish(self) -> DeadCodeReport {
+        let functions = self
+            .candidates
+            .into_iter()
+            .filter(|(f, inside)| self.references.get(&f.name).copied().unwrap_or(0) <= *inside)
+            .map(|(f, _)| f)
+            .collect();
+        DeadCodeReport {
+            functions,
+            orphans: self.orphans,
+        }
+    }
+}
+
+/// Ищет мёртвый код во всех исходных файлах проекта `root`. Пути
+/// указываются относительно `root`.
+pub fn analyze(root: &Path) -> DeadCodeReport {
+    let mut dead = DeadCode::default();
+    for path in source_files(root) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        dead.add_file(path.strip_prefix(root).unwrap_or(&path), &content);
+    }
+    dead.finish()
+}
+
+/// Начало строкового комментария языка `lang`.
+fn line_comment(lang: &str) -> Option<&'static str> {
+    match lang {
+        "rust" | "javascript" => Some("// "),
+        "python" => Some("# "),
+        _ => None,
+    }
+}
+
+/// Начало строки, содержащей `offset`.
+fn line_start(content: &str, offset: usize) -> usize {
+    content[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Конец строки, содержащей `offset`, вместе с переводом строки.
+fn line_end(content: &str, offset: usize) -> usize {
+    content[offset..]
+        .find('\n')
+        .map_or(content.len(), |i| offset + i + 1)
+}
+
+/// Замена для удаления записи: строка целиком, если кроме комментария
+/// на ней ничего нет.
+fn remove_meta(content: &str, (start, end): (usize, usize)) -> ((usize, usize), String) {
+    let from = line_start(content, start);
+    let to = line_end(content, end);
+    if content[from..start].trim().is_empty() && content[end..to].trim().is_empty() {
+        ((from, to), String::new())
+    } else {
+        ((start, end), String::new())
+    }
+}
+
+/// Замена, закомментирующая строки функции `range` с общим отступом.
+fn comment_out(content: &str, range: (usize, usize), prefix: &str) -> ((usize, usize), String) {
+    let from = line_start(content, range.0);
+    let to = line_end(content, range.1.saturating_sub(1).max(range.0));
+    let lines: Vec<&str> = content[from..to].split_inclusive('\n').collect();
+    let indent = lines
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut out = String::with_capacity(to - from + lines.len() * prefix.len());
+    for line in lines {
+        if line.trim().is_empty() {
+            out.push_str(line);
+        } else {
+            out.push_str(&line[..indent]);
+            out.push_str(prefix);
+            out.push_str(&line[indent..]);
+        }
+    }
+    ((from, to), out)
+}
+
+/// Применяет к `content` очистку из отчёта `report` для файла `file`:
+/// удаляет осиротевшие записи и, если `comment_functions`, закомментирует
+/// неиспользуемые функции. Возвращает новый текст и число изменений.
+pub fn clean_content(
+    file: &Path,
+    content: &str,
+    report: &DeadCodeReport,
+    comment_functions: bool,
+) -> (String, usize) {
+    let mut edits: Vec<((usize, usize), String)> = report
+        .orphans
+        .iter()
+        .filter(|o| o.file == file)
+        .map(|o| remove_meta(content, o.range))
+        .collect();
+    let prefix = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(lang_from_extension)
+        .and_then(line_comment);
+    if let (true, Some(prefix)) = (comment_functions, prefix) {
+        edits.extend(
+            report
+                .functions
+                .iter()
+                .filter(|f| f.file == file)
+                .map(|f| comment_out(content, f.range, prefix)),
+        );
+    }
+    // Из пересекающихся замен остаётся внешняя: вложенная функция или
+    // запись закомментирована вместе с внешней функцией.
+    edits.sort_by_key(|(range, _)| (range.0, std::cmp::Reverse(range.1)));
+    let mut kept: Vec<((usize, usize), String)> = Vec::new();
+    for edit in edits {
+        if kept.last().is_some_and(|(range, _)| edit.0 .0 < range.1) {
+            continue;
+        }
+        kept.push(edit);
+    }
+    let mut out = content.to_string();
+    for ((start, end), text) in kept.iter().rev() {
+        out.replace_range(*start..*end, text);
+    }
+    (out, kept.len())
+}
+
+/// Итог очистки проекта.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CleanReport {
+    /// Изменённые файлы относительно корня проекта.
+    pub files: Vec<PathBuf>,
+    /// Число удалённых записей и закомментированных функций.
+    pub changes: usize,
+    /// Резервная копия изменённых файлов; `None`, если ничего не менялось.
+    pub backup: Option<String>,
+}
+
+/// Очищает проект `root` по отчёту [`analyze`]: удаляет осиротевшие
+/// записи и, если `comment_functions`, закомментирует неиспользуемые
+/// функции. Изменённые файлы предварительно сохраняются в резервную копию.
+pub fn clean(root: &Path, comment_functions: bool) -> io::Result<CleanReport> {
+    let report = analyze(root);
+    let mut files: Vec<&Path> = report
+        .orphans
+        .iter()
+        .map(|o| o.file.as_path())
+        .chain(
+            report
+                .functions
+                .iter()
+                .filter(|_| comment_functions)
+                .map(|f| f.file.as_path()),
+        )
+        .collect();
+    files.sort();
+    files.dedup();
+
+    let mut changed = Vec::new();
+    let mut changes = 0;
+    for file in files {
+        let path = root.join(file);
+        let content = file_io::read_to_string(&path)?;
+        let (cleaned, applied) = clean_content(file, &content, &report, comment_functions);
+        if applied > 0 {
+            changed.push((file.to_path_buf(), path, cleaned));
+            changes += applied;
+        }
+    }
+    if changed.is_empty() {
+        return Ok(CleanReport::default());
+    }
+    let paths: Vec<&Path> = changed.iter().map(|(_, path, _)| path.as_path()).collect();
+    let backup = backup::backup_files(root, &paths)?;
+    for (_, path, cleaned) in &changed {
+        file_io::write_preserving(path, cleaned)?;
+    }
+    Ok(CleanReport {
+        files: changed.into_iter().map(|(file, _, _)| file).collect(),
+        changes,
+        backup: Some(backup),
+    })
+}
@@ -0,0 +1,378 @@
+//! Метрики блоков по синтаксическому дереву.
+//!
+//! Для каждого блока считаются:
+//!
+//! - строки кода — строки, на которых есть что-то кроме пробелов и
+//!   комментариев, в том числе `@VISUAL_META`;
+//! - цикломатическая сложность — единица плюс число ветвлений: условий,
+//!   циклов, ветвей `match`/`switch`/`case`, обработчиков исключений,
+//!   тернарных операторов и логических `&&`, `||`, `??`, `and`, `or`;
+//! - глубина вложенности управляющих конструкций; `else if` и `elif`
+//!   вложенностью не считаются;
+//! - число параметров функции без `self` и `cls`.
+//!
+//! Ограничения вида `complexity > 15` ([`MetricLimit`]) используются в
+//! политиках `[[policy]]`, см. [`crate::policy`].
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde::Serialize;
+use tree_sitter::Node;
+
+use crate::blocks::symbols::symbol_name;
+use crate::blocks::{lang_from_extension, parse_blocks, source_files};
+use crate::file_io;
+use crate::parser::{parse, Lang};
+use crate::BlockInfo;
+
+/// Узлы-ветвления, каждый добавляет единицу к сложности.
+const DECISIONS: &[&str] = &[
+    "if_expression",
+    "if_statement",
+    "elif_clause",
+    "while_expression",
+    "while_statement",
+    "for_expression",
+    "for_statement",
+    "for_in_statement",
+    "enhanced_for_statement",
+    "foreach_statement",
+    "do_statement",
+    "match_arm",
+    "case_clause",
+    "switch_case",
+    "switch_label",
+    "switch_section",
+    "case_statement",
+    "expression_case",
+    "type_case",
+    "communication_case",
+    "catch_clause",
+    "except_clause",
+    "conditional_expression",
+    "ternary_expression",
+    "if_clause",
+    "boolean_operator",
+];
+
+/// Логические операторы, которые ветвят вычисление.
+const SHORT_CIRCUIT: &[&str] = &["&&", "||", "??"];
+
+/// Управляющие конструкции, которые увеличивают вложенность.
+const NESTING: &[&str] = &[
+    "if_expression",
+    "if_statement",
+    "while_expression",
+    "while_statement",
+    "for_expression",
+    "for_statement",
+    "for_in_statement",
+    "enhanced_for_statement",
+    "foreach_statement",
+    "do_statement",
+    "loop_expression",
+    "match_expression",
+    "match_statement",
+    "switch_statement",
+    "switch_expression",
+    "expression_switch_statement",
+    "type_switch_statement",
+    "select_statement",
+    "try_statement",
+    "with_statement",
+];
+
+/// Метрики одного блока.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BlockMetrics {
+    /// Строки кода без пустых строк и комментариев.
+    pub loc: usize,
+    /// Цикломатическая сложность.
+    pub complexity: usize,
+    /// Наибольшая глубина вложенности управляющих конструкций.
+    pub depth: usize,
+    /// Число параметров.
+    pub params: usize,
+}
+
+/// Метрика, которую можно ограничить в политике.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Loc,
+    Complexity,
+    Depth,
+    Params,
+}
+
+impl Metric {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Metric::Loc => "loc",
+            Metric::Complexity => "complexity",
+            Metric::Depth => "depth",
+            Metric::Params => "params",
+        }
+    }
+
+    /// Значение метрики у блока.
+    pub fn of(self, metrics: &BlockMetrics) -> usize {
+        match self {
+            Metric::Loc => metrics.loc,
+            Metric::Complexity => metrics.complexity,
+            Metric::Depth => metrics.depth,
+            Metric::Params => metrics.params,
+        }
+    }
+}
+
+impl FromStr for Metric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "loc" => Ok(Metric::Loc),
+            "complexity" => Ok(Metric::Complexity),
+            "depth" => Ok(Metric::Depth),
+            "params" => Ok(Metric::Params),
+            _ => Err(format!("неизвестная метрика: {s}")),
+        }
+    }
+}
+
+/// Ограничение метрики: `complexity > 15` нарушается при сложности 16 и
+/// больше, `params >= 5` — при пяти параметрах и больше.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MetricLimit {
+    pub metric: Metric,
+    /// Наибольшее допустимое значение.
+    pub max: usize,
+}
+
+impl MetricLimit {
+    /// Значение метрики блока, если оно нарушает ограничение.
+    pub fn exceeded(&self, metrics: &BlockMetrics) -> Option<usize> {
+        let value = self.metric.of(metrics);
+        (value > self.max).then_some(value)
+    }
+}
+
+impl FromStr for MetricLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, inclusive, value) = if let Some((name, value)) = s.split_once(">=") {
+            (name, true, value)
+        } else if let Some((name, value)) = s.split_once('>') {
+            (name, false, value)
+        } else {
+            return Err(format!("ожидается `<метрика> > <число>`: {s}"));
+        };
+        let metric: Metric = name.trim().parse()?;
+        let value: usize = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("не число: {}", value.trim()))?;
+        let max = if inclusive {
+            value
+                .checked_sub(1)
+                .ok_or_else(|| format!("{s} нарушается всегда"))?
+        } else {
+            value
+        };
+        Ok(Self { metric, max })
+    }
+}
+
+impl fmt::Display for MetricLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} > {}", self.metric.as_str(), self.max)
+    }
+}
+
+fn is_comment(node: Node) -> bool {
+    node.kind().contains("comment")
+}
+
+/// Строки с листьями дерева, кроме комментариев.
+fn code_lines(node: Node, lines: &mut HashSet<usize>) {
+    if is_comment(node) {
+        return;
+    }
+    if node.child_count() == 0 {
+        if node.start_byte() < node.end_byte() {
+            lines.extend(node.start_position().row..=node.end_position().row);
+        }
+        return;
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        code_lines(child, lines);
+    }
+}
+
+fn decisions(node: Node, source: &str) -> usize {
+    let own = if DECISIONS.contains(&node.kind()) {
+        1
+    } else if node.kind() == "binary_expression" {
+        node.child_by_field_name("operator")
+            .and_then(|op| source.get(op.byte_range()))
+            .map_or(0, |op| usize::from(SHORT_CIRCUIT.contains(&op)))
+    } else {
+        0
+    };
+    let mut cursor = node.walk();
+    let nested: usize = node
+        .children(&mut cursor)
+        .map(|child| decisions(child, source))
+        .sum();
+    own + nested
+}
+
+/// `else if`: условие в ветви `else` другого условия.
+fn is_else_if(node: Node) -> bool {
+    let Some(parent) = node.parent() else {
+        return false;
+    };
+    parent.kind() == "else_clause"
+        || parent
+            .child_by_field_name("alternative")
+            .is_some_and(|alt| alt.id() == node.id())
+}
+
+fn depth(node: Node) -> usize {
+    let own = usize::from(NESTING.contains(&node.kind()) && !is_else_if(node));
+    let mut cursor = node.walk();
+    let nested = node.children(&mut cursor).map(depth).max().unwrap_or(0);
+    own + nested
+}
+
+fn params(node: Node, source: &str) -> usize {
+    let Some(list) = node
+        .child_by_field_name("parameters")
+        .or_else(|| node.child_by_field_name("parameter"))
+    else {
+        return 0;
+    };
+    if list.named_child_count() == 0 {
+        // Единственный параметр стрелочной функции без скобок.
+        return usize::from(list.kind() == "identifier");
+    }
+    let mut cursor = list.walk();
+    let count = list
+        .named_children(&mut cursor)
+        .filter(|p| !is_comment(*p) && p.kind() != "self_parameter")
+        .filter(|p| !matches!(source.get(p.byte_range()), Some("self" | "cls")))
+        .count();
+    count
+}
+
+/// Метрики узла `node` файла с текстом `source`.
+pub fn node_metrics(node: Node, source: &str) -> BlockMetrics {
+    let mut lines = HashSet::new();
+    code_lines(node, &mut lines);
+    BlockMetrics {
+        loc: lines.len(),
+        complexity: 1 + decisions(node, source),
+        depth: depth(node),
+        params: params(node, source),
+    }
+}
+
+/// Метрики каждого блока `blocks` файла `content`. Блок, которому не
+/// соответствует узел дерева, например ссылка на файл, получает `None`.
+pub fn block_metrics(content: &str, lang: Lang, blocks: &[BlockInfo]) -> Vec<Option<BlockMetrics>> {
+    let Some(tree) = parse(content, lang, None) else {
+        return vec![None; blocks.len()];
+    };
+    let root = tree.root_node();
+    blocks
+        .iter()
+        .map(|b| {
+            let (start, end) = b.range;
+            let node = root
+                .descendant_for_byte_range(start, end)
+                .filter(|n| n.byte_range() == (start..end))?;
+            Some(node_metrics(node, content))
+        })
+        .collect()
+}
+
+/// Метрики функции проекта.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MeasuredBlock {
+    /// Файл относительно корня проекта.
+    pub file: PathBuf,
+    pub id: String,
+    /// Строка начала блока, с нуля.
+    pub line: usize,
+    /// Имя функции, если оно есть.
+    pub name: Option<String>,
+    pub metrics: BlockMetrics,
+}
+
+/// Метрики функций в содержимом `content` файла `file`: определений с
+/// телом, без вложенных узлов с тем же началом.
+pub fn measure_file(file: &Path, content: &str, lang: Lang) -> Vec<MeasuredBlock> {
+    let Some(tree) = parse(content, lang, None) else {
+        return Vec::new();
+    };
+    let root = tree.root_node();
+    let blocks = parse_blocks(content.to_string(), lang.to_string()).unwrap_or_default();
+    let mut starts = HashSet::new();
+    let mut out = Vec::new();
+    for block in &blocks {
+        let (start, end) = block.range;
+        if block.kind != "Function/Define" || !starts.insert(start) {
+            continue;
+        }
+        let Some(node) = root
+            .descendant_for_byte_range(start, end)
+            .filter(|n| n.byte_range() == (start..end))
+            .filter(|n| n.child_by_field_name("body").is_some())
+        else {
+            continue;
+        };
+        out.push(MeasuredBlock {
+            file: file.to_path_buf(),
+            id: block.visual_id.clone(),
+            line: node.start_position().row,
+            name: symbol_name(&content[start..end]).map(str::to_string),
+            metrics: node_metrics(node, content),
+        });
+    }
+    out
+}
+
+/// Метрики функций всех исходных файлов проекта `root`. Пути указываются
+/// относительно `root`.
+pub fn measure(root: &Path) -> Vec<MeasuredBlock> {
+    let mut out = Vec::new();
+    for path in source_files(root) {
+        let Some(lang) = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension)
+            .and_then(|l| l.parse::<Lang>().ok())
+        else {
+            continue;
+        };
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        out.extend(measure_file(rel, &content, lang));
+    }
+    out
+}
+
+/// Измеряет функции проекта `root` и сохраняет метрики в базу `pool`
+/// вместо прежних. Возвращает число измеренных функций.
+#[cfg(feature = "db")]
+pub async fn store(root: &Path, pool: &sqlx::SqlitePool) -> Result<usize, sqlx::Error> {
+    let blocks = measure(root);
+    crate::meta::db::replace_metrics(pool, &blocks).await?;
+    Ok(blocks.len())
+}
@@ -0,0 +1,325 @@
+//! Встроенные правила безопасности.
+//!
+//! Проверки ищут в исходном тексте:
+//!
+//! - секреты, записанные в коде: строковые значения переменных и ключей с
+//!   именами вроде `password`, `api_key`, `token`, а также ключи доступа
+//!   AWS, токены GitHub и Slack и закрытые ключи PEM в любом файле;
+//! - вызовы `eval`/`exec` в Python и `eval`/`new Function` в JavaScript и
+//!   TypeScript;
+//! - SQL-запросы, собранные из строк: конкатенацией, `%`, `.format`,
+//!   f-строками, шаблонными строками и `format!`;
+//! - блоки `unsafe` в Rust без комментария перед блоком или в его первой
+//!   строке.
+//!
+//! Секреты ищутся регулярными выражениями, остальные правила — запросами
+//! tree-sitter. Замечания выдаёт [`crate::lint::lint_content`], поэтому
+//! они попадают в `multicode lint`, SARIF и проверку перед коммитом.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tree_sitter::{Node, Query, QueryCursor};
+
+use crate::lint::Severity;
+use crate::parser::{language, parse, Lang};
+
+/// В коде записан пароль, токен или ключ доступа.
+pub const HARDCODED_SECRET: &str = "security/hardcoded-secret";
+/// Вызов `eval`, `exec` или `new Function` выполняет произвольный код.
+pub const DYNAMIC_EVAL: &str = "security/eval";
+/// SQL-запрос собирается из строк, а не передаётся с параметрами.
+pub const SQL_CONCAT: &str = "security/sql-concat";
+/// Блок `unsafe` не объяснён комментарием.
+pub const UNSAFE_WITHOUT_COMMENT: &str = "security/unsafe-without-comment";
+
+/// Замечание правила безопасности; `range` — байтовый диапазон в тексте.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityIssue {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub range: Range<usize>,
+}
+
+/// Строковое значение переменной или ключа, имя которых говорит о секрете:
+/// `password = "…"`, `API_KEY: &str = "…"`, `"token": "…"`.
+static SECRET_ASSIGNMENT: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r#"(?i)\b([a-z0-9_]*(?:password|passwd|secret|api_?key|access_?key|auth_?token|private_?key|token)[a-z0-9_]*)["']?\s*(?::\s*[&'\w ]{0,24}?)?\s*(?:=|:|=>)\s*[bru]?["']([^"'\s]{6,})["']"#,
+    )
+    .unwrap()
+});
+
+/// Ключи с узнаваемым форматом и их названия для сообщений.
+static SECRET_TOKENS: Lazy<Vec<(Regex, &'static str)>> = Lazy::new(|| {
+    [
+        (r"\b(?:AKIA|ASIA)[0-9A-Z]{16}\b", "ключ доступа AWS"),
+        (r"\bgh[pousr]_[A-Za-z0-9]{36}\b", "токен GitHub"),
+        (r"\bxox[abprs]-[A-Za-z0-9-]{10,}", "токен Slack"),
+        (r"-----BEGIN (?:[A-Z]+ )*PRIVATE KEY-----", "закрытый ключ"),
+    ]
+    .into_iter()
+    .map(|(re, name)| (Regex::new(re).unwrap(), name))
+    .collect()
+});
+
+/// Окончания имён, которые описывают секрет, а не хранят его:
+/// `token_type`, `password_field`, `secret_path`.
+const NOT_SECRET_SUFFIXES: &[&str] = &[
+    "_type", "_name", "_field", "_label", "_url", "_path", "_file", "_env", "_header", "_key_id",
+];
+
+/// Значения-заглушки, которые не считаются секретами.
+const PLACEHOLDERS: &[&str] = &[
+    "example",
+    "changeme",
+    "placeholder",
+    "your",
+    "xxx",
+    "***",
+    "dummy",
+];
+
+/// Начало SQL-запроса или его условия.
+static SQL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:select\s.*?\bfrom|insert\s+into|update\s+\w+\s+set|delete\s+from|drop\s+table|create\s+table|alter\s+table|where\s+\w+\s*(?:=|<|>|like\b|in\b))",
+    )
+    .unwrap()
+});
+
+/// Собранные запросы правил; `None`, если для языка правил нет или запрос
+/// не собрался.
+static QUERIES: Lazy<Mutex<HashMap<Lang, Option<&'static Query>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Захваты запросов: `@eval` — опасный вызов, `@unsafe` — блок `unsafe`,
+/// `@sql` — строка, похожая на запрос, внутри выражения `@expr`, которое
+/// её собирает. Захваты с `_` нужны только условиям.
+fn query_source(lang: Lang) -> Option<&'static str> {
+    match lang {
+        Lang::Rust => Some(
+            r#"
+(unsafe_block) @unsafe
+((macro_invocation
+   macro: (identifier) @_macro
+   (token_tree . (string_literal) @sql)) @expr
+ (#eq? @_macro "format"))
+"#,
+        ),
+        Lang::Python => Some(
+            r#"
+((call function: (identifier) @_name) @eval
+ (#match? @_name "^(eval|exec)$"))
+(binary_operator left: (string) @sql) @expr
+(binary_operator right: (string) @sql) @expr
+((string (interpolation)) @sql @expr)
+((call
+   function: (attribute object: (string) @sql attribute: (identifier) @_method)) @expr
+ (#eq? @_method "format"))
+"#,
+        ),
+        Lang::JavaScript | Lang::TypeScript => Some(
+            r#"
+((call_expression function: (identifier) @_name) @eval
+ (#eq? @_name "eval"))
+((new_expression constructor: (identifier) @_name) @eval
+ (#eq? @_name "Function"))
+(binary_expression left: [(string) (template_string)] @sql operator: "+") @expr
+(binary_expression operator: "+" right: [(string) (template_string)] @sql) @expr
+((template_string (template_substitution)) @sql @expr)
+"#,
+        ),
+        _ => None,
+    }
+}
+
+/// Запрос правил для `lang`, собранный при первом обращении.
+fn security_query(lang: Lang) -> Option<&'static Query> {
+    let mut queries = QUERIES.lock().ok()?;
+    *queries.entry(lang).or_insert_with(|| {
+        let source = query_source(lang)?;
+        match Query::new(&language(lang), source) {
+            Ok(query) => Some(Box::leak(Box::new(query))),
+            Err(e) => {
+                tracing::error!("запрос правил безопасности для {lang} не собран: {e}");
+                None
+            }
+        }
+    })
+}
+
+/// Проверяет текст `content` на языке `lang` правилами безопасности.
+/// Без языка ищутся только секреты. Замечания идут по возрастанию начала.
+pub fn check(content: &str, lang: Option<Lang>) -> Vec<SecurityIssue> {
+    let mut issues = hardcoded_secrets(content);
+    if let Some(lang) = lang {
+        issues.extend(query_issues(content, lang));
+    }
+    issues.sort_by_key(|i| (i.range.start, i.range.end));
+    issues
+}
+
+fn hardcoded_secrets(content: &str) -> Vec<SecurityIssue> {
+    let mut issues: Vec<SecurityIssue> = Vec::new();
+    for (re, kind) in SECRET_TOKENS.iter() {
+        for m in re.find_iter(content) {
+            issues.push(SecurityIssue {
+                rule: HARDCODED_SECRET,
+                severity: Severity::Error,
+                message: format!("в коде записан {kind}"),
+                range: m.range(),
+            });
+        }
+    }
+    for caps in SECRET_ASSIGNMENT.captures_iter(content) {
+        let (name, value) = (&caps[1], caps.get(2).unwrap());
+        let lower = name.to_lowercase();
+        if NOT_SECRET_SUFFIXES.iter().any(|s| lower.ends_with(s))
+            || is_placeholder(value.as_str())
+            || issues.iter().any(|i| overlaps(&i.range, &value.range()))
+            || in_visual_meta(content, value.start())
+        {
+            continue;
+        }
+        issues.push(SecurityIssue {
+            rule: HARDCODED_SECRET,
+            severity: Severity::Error,
+            message: format!(
+                "значение {name} записано в коде; храните секреты в переменных окружения"
+            ),
+            range: value.range(),
+        });
+    }
+    issues
+}
+
+fn is_placeholder(value: &str) -> bool {
+    let lower = value.to_lowercase();
+    value.starts_with(['$', '%', '{', '<'])
+        || value
+            .chars()
+            .all(|c| c == value.chars().next().unwrap_or_default())
+        || PLACEHOLDERS.iter().any(|p| lower.contains(p))
+}
+
+fn overlaps(a: &Range<usize>, b: &Range<usize>) -> bool {
+    a.start < b.end && b.start < a.end
+}
+
+/// Позиция `offset` стоит в строке с `@VISUAL_META`.
+fn in_visual_meta(content: &str, offset: usize) -> bool {
+    let start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[offset..]
+        .find('\n')
+        .map_or(content.len(), |i| offset + i);
+    content[start..end].contains("@VISUAL_META")
+}
+
+fn query_issues(content: &str, lang: Lang) -> Vec<SecurityIssue> {
+    let (Some(query), Some(tree)) = (security_query(lang), parse(content, lang, None)) else {
+        return Vec::new();
+    };
+    let names = query.capture_names();
+    let mut issues = Vec::new();
+    let mut sql: Vec<Range<usize>> = Vec::new();
+    let mut cursor = QueryCursor::new();
+    for m in cursor.matches(query, tree.root_node(), content.as_bytes()) {
+        let node = |name: &str| {
+            m.captures
+                .iter()
+                .find(|c| names[c.index as usize] == name)
+                .map(|c| c.node)
+        };
+        if let Some(call) = node("eval") {
+            let name = node("_name").map_or("", |n| &content[n.byte_range()]);
+            let name = if name == "Function" {
+                "new Function"
+            } else {
+                name
+            };
+            issues.push(SecurityIssue {
+                rule: DYNAMIC_EVAL,
+                severity: Severity::Warning,
+                message: format!("{name} выполняет произвольный код"),
+                range: call.byte_range(),
+            });
+        } else if let Some(block) = node("unsafe") {
+            if !has_comment(block) {
+                issues.push(SecurityIssue {
+                    rule: UNSAFE_WITHOUT_COMMENT,
+                    severity: Severity::Warning,
+                    message: "блок unsafe без комментария о том, почему он безопасен".into(),
+                    range: block.byte_range(),
+                });
+            }
+        } else if let (Some(string), Some(expr)) = (node("sql"), node("expr")) {
+            if builds_sql(string, content, lang) {
+                sql.push(expr.byte_range());
+            }
+        }
+    }
+    // Вложенные выражения одной склейки считаются одним замечанием.
+    sql.sort_by_key(|r| (r.start, std::cmp::Reverse(r.end)));
+    let mut last: Option<Range<usize>> = None;
+    for range in sql {
+        if last.as_ref().is_some_and(|l| range.end <= l.end) {
+            continue;
+        }
+        issues.push(SecurityIssue {
+            rule: SQL_CONCAT,
+            severity: Severity::Warning,
+            message: "SQL-запрос собирается из строк; передавайте значения параметрами".into(),
+            range: range.clone(),
+        });
+        last = Some(range);
+    }
+    issues
+}
+
+/// Строка `string` похожа на SQL-запрос и в неё подставляются значения.
+fn builds_sql(string: Node, content: &str, lang: Lang) -> bool {
+    let text = &content[string.byte_range()];
+    if !SQL.is_match(text) {
+        return false;
+    }
+    match lang {
+        // Строка формата без подстановок `{}` ничего не склеивает.
+        Lang::Rust => text.replace("{{", "").contains('{'),
+        // Шаблон с тегом, например sql`…`, передаёт значения параметрами.
+        Lang::JavaScript | Lang::TypeScript => {
+            string.parent().map(|p| p.kind()) != Some("call_expression")
+        }
+        _ => true,
+    }
+}
+
+fn is_comment(node: Node) -> bool {
+    matches!(node.kind(), "line_comment" | "block_comment")
+}
+
+/// Перед блоком `unsafe` — на предыдущей строке или в той же строке — или
+/// первой строкой внутри блока стоит комментарий.
+fn has_comment(block: Node) -> bool {
+    let first = block
+        .named_child(block.named_child_count().saturating_sub(1))
+        .and_then(|body| body.named_child(0));
+    if first.is_some_and(is_comment) {
+        return true;
+    }
+    let mut statement = block;
+    while let Some(parent) = statement.parent() {
+        if matches!(parent.kind(), "block" | "source_file" | "declaration_list") {
+            break;
+        }
+        statement = parent;
+    }
+    let start = statement.start_position().row;
+    statement
+        .prev_sibling()
+        .is_some_and(|prev| is_comment(prev) && prev.end_position().row + 1 >= start)
+}
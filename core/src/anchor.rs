@@ -0,0 +1,251 @@
+//! Якоря блоков.
+//!
+//! Якорь блока ([`Anchor`]) указывает на диапазон исходника, якорь
+//! метаданных ([`NamedAnchor`]) хранится в `@VISUAL_META` под своим именем.
+//! Оба могут нести подпись и смысловой вид ([`AnchorKind`]). Старые формы —
+//! пара смещений `[start, end]` и строка с именем — по-прежнему читаются и
+//! записываются для якорей без подписи и вида, поэтому существующие файлы
+//! и снимки не меняются.
+
+use std::fmt;
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::Schema;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Смысловой вид якоря.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum AnchorKind {
+    /// Параметр функции.
+    Param,
+    /// Возвращаемое значение.
+    Returns,
+    /// Место, на которое стоит обратить внимание.
+    Hotspot,
+}
+
+impl AnchorKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AnchorKind::Param => "param",
+            AnchorKind::Returns => "returns",
+            AnchorKind::Hotspot => "hotspot",
+        }
+    }
+}
+
+impl fmt::Display for AnchorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Якорь блока: диапазон байтов исходника.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(from = "AnchorRepr", into = "AnchorRepr")]
+pub struct Anchor {
+    pub range: (usize, usize),
+    pub label: Option<String>,
+    pub kind: Option<AnchorKind>,
+}
+
+impl Anchor {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self {
+            range: (start, end),
+            label: None,
+            kind: None,
+        }
+    }
+
+    pub fn with_kind(mut self, kind: AnchorKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+impl From<(usize, usize)> for Anchor {
+    fn from((start, end): (usize, usize)) -> Self {
+        Self::new(start, end)
+    }
+}
+
+impl PartialEq<(usize, usize)> for Anchor {
+    fn eq(&self, other: &(usize, usize)) -> bool {
+        self.range == *other && self.label.is_none() && self.kind.is_none()
+    }
+}
+
+/// Форма [`Anchor`] в JSON: пара смещений или объект.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum AnchorRepr {
+    Range(usize, usize),
+    Full {
+        range: (usize, usize),
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        kind: Option<AnchorKind>,
+    },
+}
+
+impl From<AnchorRepr> for Anchor {
+    fn from(repr: AnchorRepr) -> Self {
+        match repr {
+            AnchorRepr::Range(start, end) => Self::new(start, end),
+            AnchorRepr::Full { range, label, kind } => Self { range, label, kind },
+        }
+    }
+}
+
+impl From<Anchor> for AnchorRepr {
+    fn from(a: Anchor) -> Self {
+        match a {
+            Anchor {
+                range: (start, end),
+                label: None,
+                kind: None,
+            } => AnchorRepr::Range(start, end),
+            Anchor { range, label, kind } => AnchorRepr::Full { range, label, kind },
+        }
+    }
+}
+
+impl JsonSchema for Anchor {
+    fn schema_name() -> String {
+        "Anchor".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        AnchorRepr::json_schema(gen)
+    }
+}
+
+/// Якорь метаданных: имя и необязательные подпись и вид.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(from = "NamedAnchorRepr", into = "NamedAnchorRepr")]
+pub struct NamedAnchor {
+    pub name: String,
+    pub label: Option<String>,
+    pub kind: Option<AnchorKind>,
+}
+
+impl NamedAnchor {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            label: None,
+            kind: None,
+        }
+    }
+
+    pub fn with_kind(mut self, kind: AnchorKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+}
+
+impl From<&str> for NamedAnchor {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for NamedAnchor {
+    fn from(name: String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl PartialEq<&str> for NamedAnchor {
+    fn eq(&self, other: &&str) -> bool {
+        self.name == *other && self.label.is_none() && self.kind.is_none()
+    }
+}
+
+impl fmt::Display for NamedAnchor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Форма [`NamedAnchor`] в JSON: строка с именем или объект.
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum NamedAnchorRepr {
+    Name(String),
+    Full {
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        label: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        kind: Option<AnchorKind>,
+    },
+}
+
+impl From<NamedAnchorRepr> for NamedAnchor {
+    fn from(repr: NamedAnchorRepr) -> Self {
+        match repr {
+            NamedAnchorRepr::Name(name) => Self::new(name),
+            NamedAnchorRepr::Full { name, label, kind } => Self { name, label, kind },
+        }
+    }
+}
+
+impl From<NamedAnchor> for NamedAnchorRepr {
+    fn from(a: NamedAnchor) -> Self {
+        match a {
+            NamedAnchor {
+                name,
+                label: None,
+                kind: None,
+            } => NamedAnchorRepr::Name(name),
+            NamedAnchor { name, label, kind } => NamedAnchorRepr::Full { name, label, kind },
+        }
+    }
+}
+
+impl JsonSchema for NamedAnchor {
+    fn schema_name() -> String {
+        "NamedAnchor".into()
+    }
+
+    fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+        NamedAnchorRepr::json_schema(gen)
+    }
+}
+
+/// Объединяет якоря `first` и `second` по имени, сохраняя порядок первого
+/// появления. Подпись и вид из `second` заменяют заданные в `first`.
+pub fn merge_named(first: &[NamedAnchor], second: &[NamedAnchor]) -> Vec<NamedAnchor> {
+    let mut out: Vec<NamedAnchor> = Vec::with_capacity(first.len() + second.len());
+    for anchor in first.iter().chain(second) {
+        match out.iter_mut().find(|a| a.name == anchor.name) {
+            Some(existing) => {
+                if anchor.label.is_some() {
+                    existing.label = anchor.label.clone();
+                }
+                if anchor.kind.is_some() {
+                    existing.kind = anchor.kind;
+                }
+            }
+            None => out.push(anchor.clone()),
+        }
+    }
+    out
+}
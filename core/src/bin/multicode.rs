@@ -0,0 +1,1094 @@
+//! Командная строка для работы с метаданными проекта.
+//!
+//! ```text
+//! multicode meta remove <файл>...    удалить комментарии @VISUAL_META
+//! multicode meta fix <файл>...       исправить дубликаты идентификаторов
+//! multicode meta fix --check [--staged | <файл>...]
+//!                                    только найти дубликаты, без исправления
+//! multicode meta rename <id> <новый id>
+//!                                    переименовать запись и ссылки на неё
+//! multicode meta template <имя> <запрос>...
+//!                                    подключить шаблон из .multicode/templates.toml
+//!                                    к найденным блокам
+//! multicode meta export-csv [файл]   выгрузить метаданные в CSV (по умолчанию
+//!                                    в stdout)
+//! multicode meta import-csv <файл> [--dry-run]
+//!                                    загрузить исправленный CSV; с --dry-run
+//!                                    только показать изменения
+//! multicode meta convert --to <json|zstd> <файл>...
+//!                                    перекодировать комментарии @VISUAL_META
+//!                                    на месте, сохраняя их стиль и расположение
+//! multicode restore                  список резервных копий
+//! multicode restore <id> [файл]      восстановить копию или один файл
+//! multicode search <запрос>...       найти блоки, например `connect tag:net`
+//! multicode search --saved <имя>     выполнить поиск из .multicode/searches.toml
+//! multicode search --structural <язык> <шаблон>...
+//!                                    структурный поиск по шаблону вроде
+//!                                    `foo($A, $$$REST)` с значениями `$A`
+//! multicode replace [--apply] <язык> <шаблон> <замена>
+//!                                    разница по файлам для замены по шаблону;
+//!                                    с --apply — запись с резервной копией
+//! multicode tasks [--json]          задачи TODO/FIXME и блоки с тегом todo
+//! multicode metrics [--json] [--store]
+//!                                    строки, сложность, вложенность и параметры
+//!                                    функций; с --store — запись в базу
+//!                                    [embeddings] db
+//! multicode deadcode [--json]       неиспользуемые функции и записи
+//!                                    @VISUAL_META без блоков
+//! multicode deadcode clean [--comment-out]
+//!                                    удалить записи без блоков; с --comment-out
+//!                                    закомментировать и неиспользуемые функции
+//! multicode lint [--sarif] [--staged]
+//!                                    проверка @viz и @VISUAL_META, отчёт SARIF 2.1
+//! multicode license [fix]            файлы без заголовка лицензии из [license];
+//!                                    с fix — вставить или обновить заголовки
+//! multicode install-hooks [--force]  хук pre-commit с этими проверками
+//! multicode l10n [--json]            полнота переводов по каталогам и языкам
+//! multicode l10n fill [--dry-run]    машинный перевод недостающих переводов
+//!                                    через [translate] endpoint
+//! multicode policy [--json]          проверка политик [[policy]] из multicode.toml
+//!                                    с итогами по каждой политике
+//! multicode cochange [--commits <n>] [--min <n>]
+//!                                    блоки, меняющиеся вместе, по истории git
+//! multicode schema [meta|block|document|proto]
+//!                                    JSON-схема @VISUAL_META, блоков, экспорта
+//!                                    или protobuf-схема графа блоков
+//! multicode graph <файл>             граф блоков проекта в protobuf
+//! multicode snapshot [--check] [--lang <язык>] <исходник> <эталон.json>
+//!                                    записать эталонный снимок блоков; с
+//!                                    --check — сравнить разбор с эталоном
+//! multicode run <файл> [<id> [имя]]  конфигурации запуска блоков файла или
+//!                                    запуск одной из них, по умолчанию первой
+//! multicode sync [--prefer-local | --prefer-remote]
+//!                                    обмен метаданными с сервером [team_sync];
+//!                                    без связи обмен ставится в очередь
+//! multicode queue [--replay]         операции, ожидающие связи с сервером,
+//!                                    или их немедленный повтор
+//! multicode ask [-k <n>] <вопрос>... блоки, ближе всего подходящие к вопросу,
+//!                                    по векторам [embeddings]
+//! multicode summarize [--ai]         пересоздать сводки файлов и модулей из
+//!                                    описаний блоков; с --ai — пересказ моделью
+//! multicode report [файл]            HTML-отчёт со сводками и описаниями блоков
+//!                                    (по умолчанию в stdout)
+//! ```
+//!
+//! С `--staged` проверяются файлы из индекса git, а код возврата
+//! определяется правилами секции `[precommit]` файла `multicode.toml`.
+//!
+//! Перед изменением файлов создаётся резервная копия в
+//! `.multicode/backups/`. Корнем проекта считается текущий каталог.
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use core::analysis::{dead_code, metrics};
+use core::blocks::{check_snapshot, lang_from_extension, snapshot};
+use core::config::{PrecommitConfig, TranslateConfig};
+use core::file_io;
+use core::license::{self, LicenseHeader};
+use core::lint;
+use core::meta::backup::{fix_all_in_file, list_backups, remove_all_in_file, restore};
+use core::meta::{
+    convert_encoding, csv, duplicate_ids, read_all, rename_id, summary, templates, translation,
+    MetaEncoding,
+};
+use core::offline_queue::{self, OfflineQueue};
+use core::parser::Lang;
+use core::policy::{self, Policies};
+use core::runconfig;
+use core::saved_search::{self, SavedSearch};
+use core::tasks;
+
+const USAGE: &str = "usage:
+  multicode meta remove <file>...
+  multicode meta fix <file>...
+  multicode meta fix --check [--staged | <file>...]
+  multicode meta rename <id> <new-id>
+  multicode meta template <name> <query>...
+  multicode meta export-csv [file]
+  multicode meta import-csv <file> [--dry-run]
+  multicode meta convert --to <json|zstd> <file>...
+  multicode restore [<id> [file]]
+  multicode search <query>...
+  multicode search --saved <name>
+  multicode search --structural <lang> <pattern>...
+  multicode replace [--apply] <lang> <pattern> <template>
+  multicode tasks [--json]
+  multicode metrics [--json] [--store]
+  multicode deadcode [--json]
+  multicode deadcode clean [--comment-out]
+  multicode lint [--sarif] [--staged]
+  multicode license [fix]
+  multicode install-hooks [--force]
+  multicode l10n [--json]
+  multicode l10n fill [--dry-run]
+  multicode policy [--json]
+  multicode cochange [--commits <n>] [--min <n>]
+  multicode schema [meta|block|document|proto]
+  multicode graph <file>
+  multicode snapshot [--check] [--lang <lang>] <source> <golden.json>
+  multicode run <file> [<id> [name]]
+  multicode sync [--prefer-local | --prefer-remote]
+  multicode queue [--replay]
+  multicode ask [-k <n>] <question>...
+  multicode summarize [--ai]
+  multicode report [file]";
+
+/// Текст исходных файлов из индекса git.
+#[cfg(feature = "git")]
+fn staged(root: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    core::precommit::staged_sources(root).map_err(|e| e.message().to_string())
+}
+
+#[cfg(not(feature = "git"))]
+fn staged(_root: &Path) -> Result<Vec<(PathBuf, String)>, String> {
+    Err("--staged requires the `git` feature".into())
+}
+
+/// `meta fix --check`: печатает повторяющиеся идентификаторы, не меняя
+/// файлы. Без `--staged` любой повтор — ошибка.
+fn fix_check(root: &Path, args: &[String]) -> Result<(), String> {
+    let (sources, blocking) = match args {
+        [flag] if flag == "--staged" => (
+            staged(root)?,
+            PrecommitConfig::load(root).is_blocking(lint::META_DUPLICATE_ID),
+        ),
+        [] => return Err(USAGE.into()),
+        files => {
+            let mut sources = Vec::new();
+            for file in files {
+                let content =
+                    file_io::read_to_string(root.join(file)).map_err(|e| format!("{file}: {e}"))?;
+                sources.push((PathBuf::from(file), content));
+            }
+            (sources, true)
+        }
+    };
+    let mut found = 0;
+    for (file, content) in &sources {
+        let ids = duplicate_ids(content);
+        if !ids.is_empty() {
+            found += 1;
+            println!(
+                "{}: [{}] duplicate ids: {}",
+                file.display(),
+                lint::META_DUPLICATE_ID,
+                ids.join(", ")
+            );
+        }
+    }
+    if found > 0 && blocking {
+        return Err(format!(
+            "{found} files have duplicate ids, run `multicode meta fix`"
+        ));
+    }
+    Ok(())
+}
+
+fn meta(root: &Path, args: &[String]) -> Result<(), String> {
+    let (op, files) = args.split_first().ok_or(USAGE)?;
+    if let Some(("fix", rest)) = files
+        .split_first()
+        .filter(|(flag, _)| *flag == "--check")
+        .map(|(_, rest)| (op.as_str(), rest))
+    {
+        return fix_check(root, rest);
+    }
+    if op == "rename" {
+        let [old, new] = files else {
+            return Err(USAGE.into());
+        };
+        let changed = rename_id(root, old, new).map_err(|e| e.to_string())?;
+        for path in changed {
+            let path = path.strip_prefix(root).unwrap_or(&path);
+            println!("{}: updated", path.display());
+        }
+        return Ok(());
+    }
+    if op == "export-csv" {
+        let data = csv::export_csv(root);
+        return match files {
+            [] => {
+                print!("{data}");
+                Ok(())
+            }
+            [file] => std::fs::write(file, data).map_err(|e| format!("{file}: {e}")),
+            _ => Err(USAGE.into()),
+        };
+    }
+    if op == "import-csv" {
+        let (file, dry_run) = match files {
+            [file] => (file, false),
+            [file, flag] if flag == "--dry-run" => (file, true),
+            _ => return Err(USAGE.into()),
+        };
+        let data = file_io::read_to_string(file).map_err(|e| format!("{file}: {e}"))?;
+        let plan = csv::plan_import(root, &data).map_err(|e| e.to_string())?;
+        for change in &plan.changes {
+            println!("{change}");
+        }
+        if dry_run {
+            println!("{} changes, nothing written", plan.changes.len());
+            return Ok(());
+        }
+        let changed = csv::apply_import(root, plan).map_err(|e| e.to_string())?;
+        for path in changed {
+            let path = path.strip_prefix(root).unwrap_or(&path);
+            println!("{}: updated", path.display());
+        }
+        return Ok(());
+    }
+    if op == "convert" {
+        let (target, files) = match files {
+            [flag, encoding, files @ ..] if flag == "--to" && !files.is_empty() => {
+                let target = match encoding.as_str() {
+                    "json" => MetaEncoding::Json,
+                    "zstd" => MetaEncoding::Zstd,
+                    other => return Err(format!("unknown encoding: {other}")),
+                };
+                (target, files)
+            }
+            _ => return Err(USAGE.into()),
+        };
+        for file in files {
+            let path = root.join(file);
+            let content = file_io::read_to_string(&path).map_err(|e| format!("{file}: {e}"))?;
+            let converted = convert_encoding(&content, target);
+            let changed = converted != content;
+            if changed {
+                file_io::write_preserving(&path, &converted).map_err(|e| format!("{file}: {e}"))?;
+            }
+            println!("{file}: {}", if changed { "updated" } else { "unchanged" });
+        }
+        return Ok(());
+    }
+    if op == "template" {
+        let (name, query) = files.split_first().ok_or(USAGE)?;
+        if query.is_empty() {
+            return Err(USAGE.into());
+        }
+        let template = templates::find(root, name)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("template `{name}` not found"))?;
+        let search = SavedSearch::parse("", &query.join(" "));
+        let changed =
+            templates::apply_matching(root, &template, &search).map_err(|e| e.to_string())?;
+        for path in changed {
+            let path = path.strip_prefix(root).unwrap_or(&path);
+            println!("{}: updated", path.display());
+        }
+        return Ok(());
+    }
+    let op: fn(&Path, &Path) -> std::io::Result<bool> = match op.as_str() {
+        "remove" => remove_all_in_file,
+        "fix" => fix_all_in_file,
+        _ => return Err(USAGE.into()),
+    };
+    if files.is_empty() {
+        return Err(USAGE.into());
+    }
+    for file in files {
+        let changed = op(root, Path::new(file)).map_err(|e| format!("{file}: {e}"))?;
+        println!("{file}: {}", if changed { "updated" } else { "unchanged" });
+    }
+    Ok(())
+}
+
+fn restore_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    match args {
+        [] => {
+            let backups = list_backups(root).map_err(|e| e.to_string())?;
+            if backups.is_empty() {
+                println!("no backups");
+            }
+            for backup in backups {
+                println!("{}", backup.id);
+                for file in backup.files {
+                    println!("  {}", file.display());
+                }
+            }
+            Ok(())
+        }
+        [id, rest @ ..] if rest.len() <= 1 => {
+            let file = rest.first().map(Path::new);
+            let restored = restore(root, id, file).map_err(|e| e.to_string())?;
+            for path in restored {
+                println!("restored {}", path.display());
+            }
+            Ok(())
+        }
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn search_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let search = match args {
+        [flag, lang, pattern @ ..] if flag == "--structural" && !pattern.is_empty() => {
+            return structural_cmd(root, lang, &pattern.join(" "));
+        }
+        [flag, name] if flag == "--saved" => saved_search::find(root, name)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("saved search not found: {name}"))?,
+        [] => return Err(USAGE.into()),
+        query => SavedSearch::parse("", &query.join(" ")),
+    };
+    for hit in search.run(root) {
+        let path = hit.file.strip_prefix(root).unwrap_or(&hit.file);
+        let label = hit.block.translations.get("en").unwrap_or(&hit.block.kind);
+        println!(
+            "{}:{}: {} {}",
+            path.display(),
+            hit.line + 1,
+            hit.block.kind,
+            label
+        );
+    }
+    Ok(())
+}
+
+fn structural_cmd(root: &Path, lang: &str, pattern: &str) -> Result<(), String> {
+    let found = core::search::structural(root, pattern, lang).map_err(|e| e.to_string())?;
+    for m in &found {
+        let path = m.file.strip_prefix(root).unwrap_or(&m.file);
+        let first = m.text.lines().next().unwrap_or_default();
+        println!("{}:{}: {first}", path.display(), m.line + 1);
+        for (name, value) in &m.bindings {
+            println!("  ${name} = {value}");
+        }
+    }
+    println!("{} matches", found.len());
+    Ok(())
+}
+
+fn replace_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    use core::search::replace;
+
+    let (apply, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--apply" => (true, rest),
+        _ => (false, args),
+    };
+    let [lang, pattern, template] = args else {
+        return Err(USAGE.into());
+    };
+    let replacements =
+        replace::preview(root, pattern, template, lang).map_err(|e| e.to_string())?;
+    for r in &replacements {
+        let path = r.file.strip_prefix(root).unwrap_or(&r.file);
+        println!("--- {}", path.display());
+        print!("{}", r.diff());
+        for edit in &r.rejected {
+            println!("! {}: replacement does not parse, skipped", edit.line + 1);
+        }
+    }
+    let edits: usize = replacements.iter().map(|r| r.edits.len()).sum();
+    if !apply || edits == 0 {
+        println!("{edits} replacements");
+        return Ok(());
+    }
+    let id = replace::apply(root, &replacements).map_err(|e| e.to_string())?;
+    println!("{edits} replacements applied, undo with `multicode restore {id}`");
+    Ok(())
+}
+
+fn tasks_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let json = match args {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        _ => return Err(USAGE.into()),
+    };
+    let mut tasks = tasks::collect(root);
+    for task in &mut tasks {
+        if let Ok(rel) = task.file.strip_prefix(root) {
+            task.file = rel.to_path_buf();
+        }
+    }
+    if json {
+        let out = serde_json::to_string_pretty(&tasks).map_err(|e| e.to_string())?;
+        println!("{out}");
+        return Ok(());
+    }
+    for task in tasks {
+        let assignee = task.assignee.map(|a| format!(" @{a}")).unwrap_or_default();
+        println!(
+            "{}:{}: [{}]{} {}",
+            task.file.display(),
+            task.line + 1,
+            task.status,
+            assignee,
+            task.text
+        );
+    }
+    Ok(())
+}
+
+fn metrics_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let (mut json, mut store) = (false, false);
+    for arg in args {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--store" => store = true,
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let blocks = metrics::measure(root);
+    if json {
+        let out = serde_json::to_string_pretty(&blocks).map_err(|e| e.to_string())?;
+        println!("{out}");
+    } else {
+        for b in &blocks {
+            let m = &b.metrics;
+            println!(
+                "{}:{} {} loc={} complexity={} depth={} params={}",
+                b.file.display(),
+                b.line + 1,
+                b.name.as_deref().unwrap_or(&b.id),
+                m.loc,
+                m.complexity,
+                m.depth,
+                m.params
+            );
+        }
+    }
+    if store {
+        let stored = store_metrics(root)?;
+        eprintln!("{stored} functions stored");
+    }
+    Ok(())
+}
+
+/// Сохраняет метрики в базу `db` секции `[embeddings]`.
+#[cfg(feature = "db")]
+fn store_metrics(root: &Path) -> Result<usize, String> {
+    let config = core::config::EmbeddingsConfig::load(root);
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    runtime
+        .block_on(async {
+            let pool = core::meta::db::open(&root.join(&config.db)).await?;
+            metrics::store(root, &pool).await
+        })
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "db"))]
+fn store_metrics(_root: &Path) -> Result<usize, String> {
+    Err("--store requires the `db` feature".into())
+}
+
+fn deadcode_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    match args {
+        [] => {}
+        [flag] if flag == "--json" => {
+            let report = dead_code::analyze(root);
+            let out = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+            println!("{out}");
+            return Ok(());
+        }
+        [cmd, rest @ ..] if cmd == "clean" => {
+            let comment_out = match rest {
+                [] => false,
+                [flag] if flag == "--comment-out" => true,
+                _ => return Err(USAGE.into()),
+            };
+            let report = dead_code::clean(root, comment_out).map_err(|e| e.to_string())?;
+            for file in &report.files {
+                println!("{}", file.display());
+            }
+            match report.backup {
+                Some(id) => println!(
+                    "{} changes, undo with `multicode restore {id}`",
+                    report.changes
+                ),
+                None => println!("nothing to clean"),
+            }
+            return Ok(());
+        }
+        _ => return Err(USAGE.into()),
+    }
+    let report = dead_code::analyze(root);
+    for f in report.findings() {
+        print_finding(&f);
+    }
+    println!(
+        "{} unused functions, {} orphaned meta entries",
+        report.functions.len(),
+        report.orphans.len()
+    );
+    Ok(())
+}
+
+fn print_finding(f: &lint::Finding) {
+    let (line, column) = f.span.map_or((1, 1), |s| (s.start_line, s.start_column));
+    println!(
+        "{}:{}:{}: {} [{}] {}",
+        f.file.display(),
+        line,
+        column,
+        f.severity.as_str(),
+        f.rule,
+        f.message
+    );
+}
+
+fn lint_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let (mut sarif, mut staged_only) = (false, false);
+    for arg in args {
+        match arg.as_str() {
+            "--sarif" => sarif = true,
+            "--staged" => staged_only = true,
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let findings = if staged_only {
+        let policies = Policies::load(root);
+        let license = LicenseHeader::load(root);
+        staged(root)?
+            .iter()
+            .flat_map(|(file, content)| {
+                let mut findings = lint::lint_content(file, content);
+                findings.extend(policies.check_content(file, content));
+                if let Some(license) = &license {
+                    findings.extend(license.check_content(file, content));
+                }
+                findings
+            })
+            .collect()
+    } else {
+        lint::lint_project(root)
+    };
+    if sarif {
+        let out =
+            serde_json::to_string_pretty(&lint::to_sarif(&findings)).map_err(|e| e.to_string())?;
+        println!("{out}");
+        return Ok(());
+    }
+    for f in &findings {
+        print_finding(f);
+    }
+    let errors = if staged_only {
+        let config = PrecommitConfig::load(root);
+        findings
+            .iter()
+            .filter(|f| config.is_blocking(f.rule))
+            .count()
+    } else {
+        findings
+            .iter()
+            .filter(|f| f.severity == lint::Severity::Error)
+            .count()
+    };
+    if errors > 0 {
+        return Err(format!("{errors} errors"));
+    }
+    Ok(())
+}
+
+/// `license`: файлы без заголовка лицензии из секции `[license]`; с `fix`
+/// заголовки вставляются и обновляются.
+fn license_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    if LicenseHeader::load(root).is_none() {
+        return Err("no [license] header in multicode.toml".into());
+    }
+    match args {
+        [] => {
+            let findings = license::check_project(root);
+            for f in &findings {
+                print_finding(f);
+            }
+            if !findings.is_empty() {
+                return Err(format!("{} files without license header", findings.len()));
+            }
+            Ok(())
+        }
+        [cmd] if cmd == "fix" => {
+            let report = license::fix(root).map_err(|e| e.to_string())?;
+            for file in &report.files {
+                println!("{}", file.display());
+            }
+            match report.backup {
+                Some(id) => println!(
+                    "{} files updated, undo with `multicode restore {id}`",
+                    report.files.len()
+                ),
+                None => println!("all headers are up to date"),
+            }
+            Ok(())
+        }
+        _ => Err(USAGE.into()),
+    }
+}
+
+/// `policy`: нарушения политик и итог по каждой; ошибкой завершается,
+/// если есть нарушения политик с `severity = "error"`.
+fn policy_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let json = match args {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        _ => return Err(USAGE.into()),
+    };
+    let report = policy::check_project(root);
+    if json {
+        let out = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        println!("{out}");
+    } else {
+        for f in &report.findings {
+            print_finding(f);
+        }
+        for p in &report.policies {
+            println!("{}: {} blocks, {} failed", p.name, p.checked, p.failed);
+        }
+    }
+    let errors = report
+        .findings
+        .iter()
+        .filter(|f| f.severity == lint::Severity::Error)
+        .count();
+    if errors > 0 {
+        return Err(format!("{errors} errors"));
+    }
+    Ok(())
+}
+
+/// `l10n`: доля переведённых записей по каталогам для языков из
+/// `[translate]` или всех встреченных.
+fn l10n_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let json = match args {
+        [] => false,
+        [flag] if flag == "--json" => true,
+        [cmd, rest @ ..] if cmd == "fill" => return l10n_fill(root, rest),
+        _ => return Err(USAGE.into()),
+    };
+    let config = TranslateConfig::load(root);
+    let report = translation::coverage(root, &config.languages);
+    if json {
+        let out = serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?;
+        println!("{out}");
+        return Ok(());
+    }
+    let rows: Vec<(&str, &translation::DirCoverage)> = report
+        .dirs
+        .iter()
+        .map(|d| (d.dir.as_str(), d))
+        .chain(std::iter::once(("total", &report.total)))
+        .collect();
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    print!("{:width$}  {:>6}", "dir", "blocks");
+    for lang in &report.languages {
+        print!("  {lang:>5}");
+    }
+    println!();
+    for (name, dir) in rows {
+        print!("{name:width$}  {:>6}", dir.total);
+        for lang in &report.languages {
+            print!("  {:>4.0}%", dir.percent(lang));
+        }
+        println!();
+    }
+    Ok(())
+}
+
+#[cfg(feature = "translate")]
+fn l10n_fill(root: &Path, args: &[String]) -> Result<(), String> {
+    let dry_run = match args {
+        [] => false,
+        [flag] if flag == "--dry-run" => true,
+        _ => return Err(USAGE.into()),
+    };
+    let config = TranslateConfig::load(root);
+    if config.languages.is_empty() {
+        return Err("no languages in [translate] section of multicode.toml".into());
+    }
+    let translator = translation::HttpTranslator::from_config(&config)?;
+    let plan = translation::plan_fill(
+        root,
+        &config.languages,
+        config.source.as_deref(),
+        &translator,
+    )
+    .map_err(|e| e.to_string())?;
+    for f in &plan.filled {
+        println!(
+            "{} {}: {} → {}: {}",
+            f.file, f.id, f.source, f.target, f.text
+        );
+    }
+    if dry_run {
+        println!("{} translations, nothing written", plan.filled.len());
+        return Ok(());
+    }
+    let changed = translation::apply_fill(root, plan).map_err(|e| e.to_string())?;
+    for path in changed {
+        let path = path.strip_prefix(root).unwrap_or(&path);
+        println!("{}: updated", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "translate"))]
+fn l10n_fill(_root: &Path, _args: &[String]) -> Result<(), String> {
+    Err("l10n fill requires the `translate` feature".into())
+}
+
+#[cfg(feature = "team-sync")]
+fn sync_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    use core::config::TeamSyncConfig;
+    use core::meta::team_sync::{self, ConflictPolicy, HttpRemote};
+    use core::offline_queue::OpError;
+
+    let config = TeamSyncConfig::load(root);
+    let policy = match args {
+        [] => config.conflict,
+        [flag] if flag == "--prefer-local" => ConflictPolicy::Local,
+        [flag] if flag == "--prefer-remote" => ConflictPolicy::Remote,
+        _ => return Err(USAGE.into()),
+    };
+    let remote = HttpRemote::from_config(root, &config)?;
+    let reports = match team_sync::sync_project(root, &remote, policy) {
+        Ok(reports) => reports,
+        Err(OpError::Offline(e)) => {
+            team_sync::enqueue(root, policy).map_err(|e| e.to_string())?;
+            println!("server unavailable ({e}); sync queued, run `multicode queue --replay` later");
+            return Ok(());
+        }
+        Err(e) => return Err(e.to_string()),
+    };
+    for r in &reports {
+        let mut line = format!("{}:", r.file);
+        if r.pushed {
+            line.push_str(" pushed");
+        }
+        if r.pulled > 0 {
+            line.push_str(&format!(" {} pulled", r.pulled));
+        }
+        if !r.conflicts.is_empty() {
+            line.push_str(&format!(" conflicts: {}", r.conflicts.join(", ")));
+        }
+        println!("{line}");
+    }
+    println!("{} files synced", reports.len());
+    Ok(())
+}
+
+#[cfg(not(feature = "team-sync"))]
+fn sync_cmd(_root: &Path, _args: &[String]) -> Result<(), String> {
+    Err("sync requires the `team-sync` feature".into())
+}
+
+fn queue_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let replay = match args {
+        [] => false,
+        [flag] if flag == "--replay" => true,
+        _ => return Err(USAGE.into()),
+    };
+    if replay {
+        let (report, _) = offline_queue::replay_project(root, chrono::Utc::now(), true)
+            .map_err(|e| e.to_string())?;
+        for (kind, e) in &report.failed {
+            eprintln!("{kind}: {e}");
+        }
+        println!(
+            "{} done, {} postponed, {} failed",
+            report.done,
+            report.postponed,
+            report.failed.len()
+        );
+    }
+    let queue = OfflineQueue::load(root).map_err(|e| e.to_string())?;
+    for op in &queue.ops {
+        let mut line = format!(
+            "#{} {} attempts: {} next: {}",
+            op.id,
+            op.kind,
+            op.attempts,
+            op.next_attempt.to_rfc3339()
+        );
+        if let Some(e) = &op.last_error {
+            line.push_str(&format!(" error: {e}"));
+        }
+        println!("{line}");
+    }
+    println!("{} pending", queue.ops.len());
+    Ok(())
+}
+
+/// `ask`: обновляет векторы блоков и печатает ближайшие к вопросу.
+#[cfg(feature = "embeddings")]
+fn ask_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    use std::sync::Arc;
+
+    use core::config::EmbeddingsConfig;
+    use core::search::semantic::{self, HttpEmbedder};
+
+    let (k, words) = match args {
+        [flag, k, words @ ..] if flag == "-k" => {
+            (k.parse().map_err(|_| format!("invalid -k: {k}"))?, words)
+        }
+        words => (10, words),
+    };
+    if words.is_empty() {
+        return Err(USAGE.into());
+    }
+    let query = words.join(" ");
+    let config = EmbeddingsConfig::load(root);
+    let embedder = Arc::new(HttpEmbedder::from_config(&config)?);
+    let runtime = tokio::runtime::Runtime::new().map_err(|e| e.to_string())?;
+    let hits = runtime
+        .block_on(async {
+            let pool = semantic::open(root, &config).await?;
+            semantic::index(root, &pool, embedder.clone(), config.batch_size).await?;
+            semantic::semantic(&pool, embedder, &query, k).await
+        })
+        .map_err(|e| e.to_string())?;
+    for hit in &hits {
+        println!(
+            "{}:{} {:.3} {}",
+            hit.file.display(),
+            hit.line + 1,
+            hit.score,
+            hit.label
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "embeddings"))]
+fn ask_cmd(_root: &Path, _args: &[String]) -> Result<(), String> {
+    Err("ask requires the `embeddings` feature".into())
+}
+
+#[cfg(feature = "git")]
+fn install_hooks_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let force = match args {
+        [] => false,
+        [flag] if flag == "--force" => true,
+        _ => return Err(USAGE.into()),
+    };
+    let path = core::precommit::install_hook(root, force).map_err(|e| match e.kind() {
+        std::io::ErrorKind::AlreadyExists => format!("{e}; use --force to replace it"),
+        _ => e.to_string(),
+    })?;
+    println!("installed {}", path.display());
+    Ok(())
+}
+
+#[cfg(feature = "git")]
+fn cochange_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    use core::cochange::{analyze, BlockRef, CoChangeOptions};
+
+    let mut opts = CoChangeOptions::default();
+    for pair in args.chunks(2) {
+        let value = pair.get(1).and_then(|v| v.parse().ok()).ok_or(USAGE)?;
+        match pair[0].as_str() {
+            "--commits" => opts.max_commits = value,
+            "--min" => opts.min_together = value,
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let report = analyze(root, &opts).map_err(|e| e.to_string())?;
+    let show = |b: &BlockRef| format!("{}:{} {}", b.file.display(), b.line + 1, b.label);
+    println!("{} commits analyzed", report.commits);
+    for s in &report.suggestions {
+        println!(
+            "link   {} <-> {} ({} commits, {:.0}%)",
+            show(&s.a),
+            show(&s.b),
+            s.together,
+            s.confidence * 100.0
+        );
+    }
+    for link in &report.stale_links {
+        println!("stale  {} -> {}", show(&link.from), show(&link.to));
+    }
+    Ok(())
+}
+
+fn schema_cmd(args: &[String]) -> Result<(), String> {
+    let schema = match args.first().map(String::as_str) {
+        None | Some("meta") => core::meta::schema(),
+        Some("block") => core::block_schema(),
+        #[cfg(feature = "export")]
+        Some("document") => core::export::schema(),
+        #[cfg(feature = "export")]
+        Some("proto") => {
+            print!("{}", core::export::binary::PROTO);
+            return Ok(());
+        }
+        _ => return Err(USAGE.into()),
+    };
+    let out = serde_json::to_string_pretty(&schema).map_err(|e| e.to_string())?;
+    println!("{out}");
+    Ok(())
+}
+
+#[cfg(feature = "export")]
+fn graph_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let [out] = args else {
+        return Err(USAGE.into());
+    };
+    let file = std::fs::File::create(out).map_err(|e| e.to_string())?;
+    let count = core::export::binary::export_dir(root, std::io::BufWriter::new(file))
+        .map_err(|e| e.to_string())?;
+    println!("{count} blocks written to {out}");
+    Ok(())
+}
+
+#[cfg(feature = "export")]
+fn report_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let title = root
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "multicode".into());
+    let html = core::export::report::html(root, &title);
+    match args {
+        [] => print!("{html}"),
+        [out] => {
+            std::fs::write(out, html).map_err(|e| e.to_string())?;
+            println!("report written to {out}");
+        }
+        _ => return Err(USAGE.into()),
+    }
+    Ok(())
+}
+
+fn snapshot_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let mut check = false;
+    let mut lang = None;
+    let mut paths = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" => check = true,
+            "--lang" => {
+                let value = args.next().ok_or(USAGE)?;
+                lang = Some(
+                    value
+                        .parse::<Lang>()
+                        .map_err(|_| format!("unsupported language: {value}"))?,
+                );
+            }
+            _ => paths.push(arg),
+        }
+    }
+    let [source, golden] = paths.as_slice() else {
+        return Err(USAGE.into());
+    };
+    let lang = lang
+        .or_else(|| {
+            Path::new(source)
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(lang_from_extension)
+                .and_then(|l| l.parse().ok())
+        })
+        .ok_or_else(|| format!("cannot detect language of {source}"))?;
+    let content =
+        file_io::read_to_string(root.join(source)).map_err(|e| format!("{source}: {e}"))?;
+    if check {
+        let expected =
+            std::fs::read_to_string(root.join(golden)).map_err(|e| format!("{golden}: {e}"))?;
+        check_snapshot(&content, lang, &expected).map_err(|e| format!("{golden}: {e}"))
+    } else {
+        let out = snapshot(&content, lang).ok_or("failed to parse source")?;
+        std::fs::write(root.join(golden), out).map_err(|e| format!("{golden}: {e}"))
+    }
+}
+
+fn summarize_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let client = match args {
+        [] => None,
+        [flag] if flag == "--ai" => Some(ai_client(root)?),
+        _ => return Err(USAGE.into()),
+    };
+    let summaries = summary::regenerate(root, client.as_ref())?;
+    for s in &summaries {
+        println!("{} -> {}", s.id(), s.file.display());
+    }
+    println!("{} summaries", summaries.len());
+    Ok(())
+}
+
+/// Клиент модели по секции `[ai]`.
+#[cfg(feature = "ai")]
+fn ai_client(root: &Path) -> Result<core::ai::AiClient, String> {
+    core::ai::AiClient::from_config(&core::config::AiConfig::load(root))
+}
+
+#[cfg(not(feature = "ai"))]
+fn ai_client(_root: &Path) -> Result<core::ai::AiClient, String> {
+    Err("--ai requires the `ai` feature".into())
+}
+
+fn run_cmd(root: &Path, args: &[String]) -> Result<(), String> {
+    let (file, rest) = args.split_first().ok_or(USAGE)?;
+    let content = file_io::read_to_string(root.join(file)).map_err(|e| format!("{file}: {e}"))?;
+    let metas = read_all(&content);
+    let (id, name) = match rest {
+        [] => {
+            for meta in &metas {
+                for config in runconfig::resolve(meta) {
+                    println!("{}: {} — {}", meta.id, config.label(), config.command);
+                }
+            }
+            return Ok(());
+        }
+        [id] => (id, None),
+        [id, name] => (id, Some(name.as_str())),
+        _ => return Err(USAGE.into()),
+    };
+    let meta = metas
+        .iter()
+        .find(|m| &m.id == id)
+        .ok_or_else(|| format!("block not found: {id}"))?;
+    let configs = runconfig::resolve(meta);
+    let config = runconfig::find(&configs, name)
+        .ok_or_else(|| format!("no run configuration for block {id}"))?;
+    println!("$ {}", config.command);
+    let status = config.command(root).status().map_err(|e| e.to_string())?;
+    if !status.success() {
+        return Err(format!("command failed: {status}"));
+    }
+    Ok(())
+}
+
+fn run() -> Result<(), String> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let root = std::env::current_dir().map_err(|e| e.to_string())?;
+    match args.split_first() {
+        Some((cmd, rest)) if cmd == "meta" => meta(&root, rest),
+        Some((cmd, rest)) if cmd == "restore" => restore_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "search" => search_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "replace" => replace_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "tasks" => tasks_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "metrics" => metrics_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "deadcode" => deadcode_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "lint" => lint_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "license" => license_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "policy" => policy_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "l10n" => l10n_cmd(&root, rest),
+        #[cfg(feature = "git")]
+        Some((cmd, rest)) if cmd == "install-hooks" => install_hooks_cmd(&root, rest),
+        #[cfg(feature = "git")]
+        Some((cmd, rest)) if cmd == "cochange" => cochange_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "schema" && rest.len() <= 1 => schema_cmd(rest),
+        #[cfg(feature = "export")]
+        Some((cmd, rest)) if cmd == "graph" => graph_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "snapshot" => snapshot_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "run" => run_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "sync" => sync_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "queue" => queue_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "ask" => ask_cmd(&root, rest),
+        Some((cmd, rest)) if cmd == "summarize" => summarize_cmd(&root, rest),
+        #[cfg(feature = "export")]
+        Some((cmd, rest)) if cmd == "report" => report_cmd(&root, rest),
+        _ => Err(USAGE.into()),
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -3,10 +3,12 @@ use std::hash::{Hash, Hasher};
 
 use crate::{get_cached_blocks, parser::Block, update_block_cache, BlockInfo};
 
-/// Генерирует стабильный ключ кэша на основе содержимого файла `content`.
-pub fn key(content: &str) -> String {
+/// Генерирует стабильный ключ кэша на основе содержимого файла `content`
+/// и этапов обогащения `stages`.
+pub fn key(content: &str, stages: &[String]) -> String {
     let mut hasher = DefaultHasher::new();
     content.hash(&mut hasher);
+    stages.hash(&mut hasher);
     hasher.finish().to_string()
 }
 
@@ -1,49 +1,381 @@
-use std::collections::HashMap;
+//! Обогащение блоков переводами, метаданными и документацией.
+//!
+//! Разобранные [`Block`] превращаются в [`BlockInfo`] и проходят по
+//! упорядоченному конвейеру этапов [`EnrichStage`]. Набор и порядок этапов
+//! задаются секцией `[enrich]` в `multicode.toml`
+//! (см. [`EnrichConfig`](crate::config::EnrichConfig)), а плагины могут
+//! добавлять свои этапы через [`register_stage`].
 
-use crate::{i18n, meta::read_all, parser::Block, BlockInfo};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
-/// Объединяет исходные `blocks` с метаданными, извлечёнными из `content`.
-///
-/// Каждый блок получает базовые переводы в зависимости от своего типа и
-/// дополняется позиционными и пользовательскими метаданными, если они есть.
+use once_cell::sync::Lazy;
+
+use super::{naming, BlockLocator};
+use crate::{
+    i18n,
+    meta::{file_ref, read_all, style, VisualMeta},
+    parser::Block,
+    BlockInfo,
+};
+
+/// Базовые переводы по типу блока.
+pub const TRANSLATIONS: &str = "translations";
+/// Координаты, переводы, теги, ссылки и оформление из `@VISUAL_META`.
+pub const META: &str = "meta";
+/// Теги охватывающих блоков переходят на вложенные.
+pub const TAGS: &str = "tags";
+/// Документирующий комментарий перед блоком.
+pub const DOCS: &str = "docs";
+/// Подписи из исходного кода для блоков без переводов в метаданных.
+pub const NAMES: &str = "names";
+
+/// Этапы, выполняемые без настройки `[enrich]`.
+pub const DEFAULT_STAGES: &[&str] = &[TRANSLATIONS, META, NAMES, DOCS];
+
+/// Данные файла, доступные этапам обогащения.
+pub struct EnrichContext<'a> {
+    pub content: &'a str,
+    /// Метаданные файла по идентификатору блока.
+    pub metas: &'a HashMap<String, VisualMeta>,
+}
+
+/// Этап конвейера обогащения.
+pub trait EnrichStage: Send + Sync {
+    /// Имя этапа в списке `stages` секции `[enrich]`.
+    fn name(&self) -> &str;
+    fn apply(&self, ctx: &EnrichContext, blocks: &mut [BlockInfo]);
+}
+
+/// Время выполнения этапа при последнем разборе.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageTiming {
+    pub stage: String,
+    pub duration: Duration,
+}
+
+static STAGES: Lazy<Mutex<Vec<Arc<dyn EnrichStage>>>> = Lazy::new(|| {
+    Mutex::new(vec![
+        Arc::new(TranslationsStage),
+        Arc::new(MetaStage),
+        Arc::new(TagsStage),
+        Arc::new(NamesStage),
+        Arc::new(DocsStage),
+    ])
+});
+
+/// Регистрирует этап, который можно включить по имени в `[enrich]`.
+/// Этап с уже зарегистрированным именем заменяет прежний.
+pub fn register_stage<S: EnrichStage + 'static>(stage: S) {
+    if let Ok(mut stages) = STAGES.lock() {
+        stages.retain(|s| s.name() != stage.name());
+        stages.push(Arc::new(stage));
+    }
+}
+
+/// Имена всех зарегистрированных этапов.
+pub fn stage_names() -> Vec<String> {
+    STAGES
+        .lock()
+        .map(|stages| stages.iter().map(|s| s.name().to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// Находит этапы по именам в заданном порядке; неизвестные имена
+/// пропускаются с предупреждением.
+fn resolve(names: &[String]) -> Vec<Arc<dyn EnrichStage>> {
+    let Ok(stages) = STAGES.lock() else {
+        return Vec::new();
+    };
+    names
+        .iter()
+        .filter_map(|name| {
+            let stage = stages.iter().find(|s| s.name() == name).cloned();
+            if stage.is_none() {
+                tracing::warn!(stage = %name, "неизвестный этап обогащения");
+            }
+            stage
+        })
+        .collect()
+}
+
+/// Объединяет исходные `blocks` с метаданными, извлечёнными из `content`,
+/// этапами по умолчанию.
 pub fn enrich_blocks(blocks: Vec<Block>, content: &str) -> Vec<BlockInfo> {
+    let stages: Vec<String> = DEFAULT_STAGES.iter().map(|s| s.to_string()).collect();
+    run_pipeline(blocks, content, &stages).0
+}
+
+/// Прогоняет `blocks` через этапы `stages` по порядку.
+///
+/// Записи-ссылки на файлы добавляются в конец как блоки
+/// [`FileRef`](file_ref::FILE_REF_KIND). Вместе с блоками возвращается
+/// время каждого этапа.
+pub fn run_pipeline(
+    blocks: Vec<Block>,
+    content: &str,
+    stages: &[String],
+) -> (Vec<BlockInfo>, Vec<StageTiming>) {
     let metas = read_all(content);
-    let map: HashMap<_, _> = metas.into_iter().map(|m| (m.id.clone(), m)).collect();
+    let refs: Vec<BlockInfo> = metas
+        .iter()
+        .filter(|m| file_ref::is_file_ref(m))
+        .map(file_ref::file_ref_block)
+        .collect();
+    let metas: HashMap<_, _> = metas.into_iter().map(|m| (m.id.clone(), m)).collect();
+    let ctx = EnrichContext {
+        content,
+        metas: &metas,
+    };
+
+    let mut infos: Vec<BlockInfo> = blocks.into_iter().map(base_info).collect();
+    let mut timings = Vec::new();
+    for stage in resolve(stages) {
+        let start = Instant::now();
+        stage.apply(&ctx, &mut infos);
+        let duration = start.elapsed();
+        tracing::debug!(stage = stage.name(), ?duration, "этап обогащения");
+        timings.push(StageTiming {
+            stage: stage.name().to_string(),
+            duration,
+        });
+    }
+    infos.extend(refs);
+    (infos, timings)
+}
+
+/// [`BlockInfo`] без переводов и метаданных.
+fn base_info(b: Block) -> BlockInfo {
+    BlockInfo {
+        visual_id: b.visual_id,
+        node_id: Some(b.node_id),
+        kind: normalize_kind(&b.kind),
+        translations: HashMap::new(),
+        range: (b.range.start, b.range.end),
+        anchors: b.anchors,
+        x: 0.0,
+        y: 0.0,
+        width: None,
+        height: None,
+        ports: Vec::new(),
+        ai: None,
+        tags: Vec::new(),
+        links: Vec::new(),
+        color: None,
+        icon: None,
+        doc: None,
+    }
+}
+
+struct TranslationsStage;
+
+impl EnrichStage for TranslationsStage {
+    fn name(&self) -> &str {
+        TRANSLATIONS
+    }
+
+    /// Переводы, уже заданные метаданными, не перезаписываются.
+    fn apply(&self, _ctx: &EnrichContext, blocks: &mut [BlockInfo]) {
+        for b in blocks {
+            let base = i18n::lookup(&b.kind).unwrap_or_else(|| {
+                i18n::languages()
+                    .into_iter()
+                    .map(|lang| (lang, b.kind.clone()))
+                    .collect()
+            });
+            for (lang, text) in base {
+                b.translations.entry(lang).or_insert(text);
+            }
+        }
+    }
+}
+
+struct MetaStage;
+
+impl EnrichStage for MetaStage {
+    fn name(&self) -> &str {
+        META
+    }
 
+    fn apply(&self, ctx: &EnrichContext, blocks: &mut [BlockInfo]) {
+        for b in blocks {
+            let Some(meta) = ctx.metas.get(&b.visual_id) else {
+                continue;
+            };
+            b.translations.extend(meta.translations.clone());
+            b.x = meta.x;
+            b.y = meta.y;
+            b.width = meta.width;
+            b.height = meta.height;
+            b.ai = meta.ai.clone();
+            b.tags = meta.tags.clone();
+            b.links = meta.links.clone();
+            b.color = style::color(meta).map(str::to_string);
+            b.icon = style::icon(meta).map(str::to_string);
+        }
+    }
+}
+
+struct TagsStage;
+
+impl EnrichStage for TagsStage {
+    fn name(&self) -> &str {
+        TAGS
+    }
+
+    fn apply(&self, _ctx: &EnrichContext, blocks: &mut [BlockInfo]) {
+        let locator = BlockLocator::new(blocks);
+        for index in locator.outer_first() {
+            let Some(parent) = locator.parent(index) else {
+                continue;
+            };
+            let inherited: Vec<String> = blocks[parent]
+                .tags
+                .iter()
+                .filter(|t| !blocks[index].tags.contains(t))
+                .cloned()
+                .collect();
+            blocks[index].tags.extend(inherited);
+        }
+    }
+}
+
+struct NamesStage;
+
+impl EnrichStage for NamesStage {
+    fn name(&self) -> &str {
+        NAMES
+    }
+
+    /// Подпись из кода ([`naming::default_name`]) заменяет подпись по виду
+    /// блока на всех языках, для которых в метаданных нет перевода.
+    fn apply(&self, ctx: &EnrichContext, blocks: &mut [BlockInfo]) {
+        for b in blocks {
+            let text = ctx.content.get(b.range.0..b.range.1).unwrap_or("");
+            let Some(name) = naming::default_name(&b.kind, text) else {
+                continue;
+            };
+            let named = ctx.metas.get(&b.visual_id).map(|m| &m.translations);
+            let languages = if b.translations.is_empty() {
+                i18n::languages()
+            } else {
+                b.translations.keys().cloned().collect()
+            };
+            for lang in languages {
+                if !named.is_some_and(|t| t.contains_key(&lang)) {
+                    b.translations.insert(lang, name.clone());
+                }
+            }
+        }
+    }
+}
+
+struct DocsStage;
+
+impl EnrichStage for DocsStage {
+    fn name(&self) -> &str {
+        DOCS
+    }
+
+    /// Документация достаётся самому внешнему блоку, начинающемуся в
+    /// данной позиции.
+    fn apply(&self, ctx: &EnrichContext, blocks: &mut [BlockInfo]) {
+        let mut seen = HashSet::new();
+        for b in blocks {
+            if !seen.insert(b.range.0) || b.kind.to_lowercase().contains("comment") {
+                continue;
+            }
+            b.doc = doc_comment(ctx.content, b.range.0);
+        }
+    }
+}
+
+/// Документирующий комментарий (`///`, `/** */` или `#`) в строках
+/// непосредственно перед позицией `start`. Атрибуты `#[...]` и
+/// комментарии `@VISUAL_META` между комментарием и блоком пропускаются.
+pub fn doc_comment(content: &str, start: usize) -> Option<String> {
+    let start = start.min(content.len());
+    let line_start = content[..start].rfind('\n').map_or(0, |i| i + 1);
+    if !content[line_start..start].trim().is_empty() {
+        return None;
+    }
+    let mut lines: Vec<&str> = Vec::new();
+    let mut block: Option<Vec<&str>> = None;
+    for line in content[..line_start].lines().rev() {
+        let t = line.trim();
+        if let Some(inner) = block.as_mut() {
+            if let Some(first) = t.strip_prefix("/**") {
+                inner.push(first.trim());
+                lines.append(inner);
+                block = None;
+                continue;
+            }
+            if t.starts_with("/*") {
+                return None;
+            }
+            inner.push(t.trim_start_matches('*').trim());
+            continue;
+        }
+        if t.contains("@VISUAL_META") || t.starts_with("#[") {
+            continue;
+        }
+        if let Some(doc) = t.strip_prefix("///") {
+            lines.push(doc.strip_prefix(' ').unwrap_or(doc));
+        } else if let Some(rest) = t.strip_suffix("*/") {
+            if let Some(single) = rest.strip_prefix("/**") {
+                lines.push(single.trim());
+            } else if rest.contains("/*") {
+                break;
+            } else {
+                block = Some(vec![rest.trim_start_matches('*').trim()]);
+            }
+        } else if let Some(doc) = t.strip_prefix('#').filter(|_| !t.starts_with("#!")) {
+            lines.push(doc.strip_prefix(' ').unwrap_or(doc));
+        } else {
+            break;
+        }
+    }
+    if block.is_some() {
+        return None;
+    }
+    lines.reverse();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// Преобразует `blocks` в [`BlockInfo`] без чтения метаданных и переводов.
+///
+/// Используется для упрощённого представления больших файлов.
+pub fn simplified_blocks(blocks: Vec<Block>) -> Vec<BlockInfo> {
     blocks
         .into_iter()
         .map(|b| {
             let label = normalize_kind(&b.kind);
-            let mut translations = i18n::lookup(&label).unwrap_or_else(|| {
-                let mut m = HashMap::new();
-                for lang in i18n::languages() {
-                    m.insert(lang, label.clone());
-                }
-                m
-            });
-            if let Some(meta) = map.get(&b.visual_id) {
-                translations.extend(meta.translations.clone());
-            }
-            let pos = map.get(&b.visual_id);
             BlockInfo {
                 visual_id: b.visual_id,
                 node_id: Some(b.node_id),
+                translations: HashMap::from([("en".to_string(), label.clone())]),
                 kind: label,
-                translations,
                 range: (b.range.start, b.range.end),
-                anchors: b.anchors.clone(),
-                x: pos.map(|m| m.x).unwrap_or(0.0),
-                y: pos.map(|m| m.y).unwrap_or(0.0),
+                anchors: Vec::new(),
+                x: 0.0,
+                y: 0.0,
+                width: None,
+                height: None,
                 ports: Vec::new(),
-                ai: pos.and_then(|m| m.ai.clone()),
-                tags: pos.map(|m| m.tags.clone()).unwrap_or_default(),
-                links: pos.map(|m| m.links.clone()).unwrap_or_default(),
+                ai: None,
+                tags: Vec::new(),
+                links: Vec::new(),
+                color: None,
+                icon: None,
+                doc: None,
             }
         })
         .collect()
 }
 
-fn normalize_kind(kind: &str) -> String {
+pub(super) fn normalize_kind(kind: &str) -> String {
     let lower = kind.to_lowercase();
     if lower == "function/define" {
         "Function/Define".into()
@@ -105,4 +437,22 @@ mod tests {
         assert_eq!(b.y, 2.0);
         assert_eq!(b.translations.get("en").unwrap(), "Test");
     }
+
+    #[test]
+    fn doc_comments_before_block() {
+        let content = "/// Складывает.\n/// Два числа.\n#[inline]\nfn add() {}\n";
+        let start = content.find("fn").unwrap();
+        assert_eq!(
+            doc_comment(content, start).as_deref(),
+            Some("Складывает.\nДва числа.")
+        );
+
+        let content = "/**\n * Сумма.\n */\nfunction add() {}\n";
+        let start = content.find("function").unwrap();
+        assert_eq!(doc_comment(content, start).as_deref(), Some("Сумма."));
+
+        let content = "/* обычный */\nfn f() {}\nlet x = 1; fn g() {}\n";
+        assert_eq!(doc_comment(content, content.find("fn f").unwrap()), None);
+        assert_eq!(doc_comment(content, content.find("fn g").unwrap()), None);
+    }
 }
@@ -0,0 +1,57 @@
+//! Автоматическая раскладка блоков на холсте.
+//!
+//! Свободные блоки расставляются по ячейкам сетки в порядке их кода,
+//! строка за строкой. Замороженные блоки — закреплённые и вложенные в
+//! закреплённые — остаются на месте, а ячейки, которые они занимают,
+//! пропускаются.
+
+use std::collections::HashSet;
+
+use super::BlockLocator;
+use crate::BlockInfo;
+
+/// Заморожен ли каждый блок `blocks`: закреплён сам или вложен в
+/// закреплённый блок. `pinned` — идентификаторы закреплённых блоков.
+pub fn frozen(blocks: &[BlockInfo], pinned: &HashSet<String>) -> Vec<bool> {
+    let locator = BlockLocator::new(blocks);
+    let mut frozen = vec![false; blocks.len()];
+    for index in locator.outer_first() {
+        frozen[index] = pinned.contains(&blocks[index].visual_id)
+            || locator.parent(index).is_some_and(|p| frozen[p]);
+    }
+    frozen
+}
+
+/// Позиции блоков `blocks` после раскладки по сетке с ячейкой `cell`
+/// (ширина и высота с промежутком). Замороженные по `frozen` блоки
+/// сохраняют свои координаты.
+pub fn auto_layout(blocks: &[BlockInfo], frozen: &[bool], cell: (f64, f64)) -> Vec<(f64, f64)> {
+    let is_frozen = |i: usize| frozen.get(i).copied().unwrap_or(false);
+    let mut free: Vec<usize> = (0..blocks.len()).filter(|&i| !is_frozen(i)).collect();
+    free.sort_by_key(|&i| (blocks[i].range.0, i));
+    let occupied: Vec<(f64, f64)> = (0..blocks.len())
+        .filter(|&i| is_frozen(i))
+        .map(|i| (blocks[i].x, blocks[i].y))
+        .collect();
+    let overlaps = |x: f64, y: f64| {
+        occupied
+            .iter()
+            .any(|&(ox, oy)| (ox - x).abs() < cell.0 && (oy - y).abs() < cell.1)
+    };
+    let columns = (free.len() as f64).sqrt().ceil().max(1.0) as usize;
+
+    let mut positions: Vec<(f64, f64)> = blocks.iter().map(|b| (b.x, b.y)).collect();
+    let mut slot = 0;
+    for index in free {
+        loop {
+            let x = (slot % columns) as f64 * cell.0;
+            let y = (slot / columns) as f64 * cell.1;
+            slot += 1;
+            if !overlaps(x, y) {
+                positions[index] = (x, y);
+                break;
+            }
+        }
+    }
+    positions
+}
@@ -0,0 +1,117 @@
+//! Поиск блоков по смещению и диапазону в тексте.
+//!
+//! Блоки повторяют узлы дерева разбора, поэтому их диапазоны вложены
+//! друг в друга. [`BlockLocator`] сортирует диапазоны и запоминает для
+//! каждого блока родителя, так что вопрос «в каком блоке курсор»
+//! решается двоичным поиском и подъёмом по предкам, без просмотра всего
+//! списка.
+
+use std::ops::Range;
+
+use crate::BlockInfo;
+
+/// Индекс диапазонов блоков. Возвращает индексы в исходном списке.
+#[derive(Debug, Clone, Default)]
+pub struct BlockLocator {
+    /// Диапазоны и индексы блоков, по возрастанию начала и убыванию конца.
+    sorted: Vec<(usize, usize, usize)>,
+    /// Позиция родителя в `sorted` для каждой позиции в `sorted`.
+    parents: Vec<Option<usize>>,
+    /// Позиция в `sorted` для каждого индекса исходного списка.
+    positions: Vec<usize>,
+}
+
+impl BlockLocator {
+    pub fn new(blocks: &[BlockInfo]) -> Self {
+        let mut sorted: Vec<_> = blocks
+            .iter()
+            .enumerate()
+            .map(|(i, b)| (b.range.0, b.range.1, i))
+            .collect();
+        sorted.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)).then(a.2.cmp(&b.2)));
+
+        let mut parents = Vec::with_capacity(sorted.len());
+        let mut stack: Vec<usize> = Vec::new();
+        for (pos, &(start, end, _)) in sorted.iter().enumerate() {
+            while let Some(&top) = stack.last() {
+                let (top_start, top_end, _) = sorted[top];
+                if top_start <= start && end <= top_end {
+                    break;
+                }
+                stack.pop();
+            }
+            parents.push(stack.last().copied());
+            stack.push(pos);
+        }
+        let mut positions = vec![0; sorted.len()];
+        for (pos, &(_, _, index)) in sorted.iter().enumerate() {
+            positions[index] = pos;
+        }
+        Self {
+            sorted,
+            parents,
+            positions,
+        }
+    }
+
+    /// Индекс ближайшего блока, охватывающего блок `index`.
+    pub fn parent(&self, index: usize) -> Option<usize> {
+        let pos = self.parents[*self.positions.get(index)?]?;
+        Some(self.sorted[pos].2)
+    }
+
+    /// Индексы всех блоков так, что охватывающий блок идёт раньше вложенных.
+    pub fn outer_first(&self) -> impl Iterator<Item = usize> + '_ {
+        self.sorted.iter().map(|&(_, _, index)| index)
+    }
+
+    /// Индекс самого вложенного блока, содержащего `offset`. Конец блока
+    /// включается: курсор сразу за блоком относится к нему, если с этой
+    /// позиции не начинается другой блок.
+    pub fn block_at(&self, offset: usize) -> Option<usize> {
+        let last = self
+            .sorted
+            .partition_point(|&(start, _, _)| start <= offset);
+        let mut pos = last.checked_sub(1);
+        while let Some(p) = pos {
+            let (start, end, index) = self.sorted[p];
+            if start <= offset && offset <= end {
+                return Some(index);
+            }
+            pos = self.parents[p];
+        }
+        None
+    }
+
+    /// Индексы блоков, целиком лежащих в `range`, по порядку начала.
+    pub fn blocks_in_range(&self, range: Range<usize>) -> Vec<usize> {
+        let first = self
+            .sorted
+            .partition_point(|&(start, _, _)| start < range.start);
+        self.sorted[first..]
+            .iter()
+            .take_while(|&&(start, _, _)| start < range.end)
+            .filter(|&&(_, end, _)| end <= range.end)
+            .map(|&(_, _, index)| index)
+            .collect()
+    }
+}
+
+/// Самый вложенный блок `content`, содержащий смещение `offset`.
+///
+/// Блоки берутся из кэша разбора, при его промахе дерево разбирается
+/// инкрементально от сохранённого дерева документа.
+pub fn block_at(content: &str, lang: &str, offset: usize) -> Option<BlockInfo> {
+    let blocks = super::parse_blocks(content.to_string(), lang.to_string())?;
+    let index = BlockLocator::new(&blocks).block_at(offset)?;
+    blocks.into_iter().nth(index)
+}
+
+/// Блоки `content`, целиком лежащие в `range`, по порядку начала.
+pub fn blocks_in_range(content: &str, lang: &str, range: Range<usize>) -> Vec<BlockInfo> {
+    let Some(blocks) = super::parse_blocks(content.to_string(), lang.to_string()) else {
+        return Vec::new();
+    };
+    let found = BlockLocator::new(&blocks).blocks_in_range(range);
+    found.into_iter().map(|i| blocks[i].clone()).collect()
+}
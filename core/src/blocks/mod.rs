@@ -1,20 +1,70 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 
 use chrono::Utc;
 use syn::{File, Item};
+use walkdir::WalkDir;
 
 use crate::{
-    meta::{read_all, remove_all, upsert, VisualMeta},
+    config::{EnrichConfig, ParseLimits},
+    file_io,
+    ignore_rules::IgnoreRules,
+    meta::{canonical, pin, read_all, reinsert, remove_all, VisualMeta},
     parser::{parse, parse_to_blocks, Lang},
     BlockInfo,
 };
 
 mod cache;
-mod enrich;
+pub mod enrich;
+pub mod layout;
+mod locate;
+pub mod naming;
 mod parsing;
+pub mod profile;
+pub mod snapshot;
+pub mod symbols;
+
+pub use locate::{block_at, blocks_in_range, BlockLocator};
+pub use snapshot::{check_snapshot, snapshot};
 
 pub fn parse_blocks(content: String, lang: String) -> Option<Vec<BlockInfo>> {
+    parse_blocks_with_limits(content, lang, &ParseLimits::default()).map(|p| p.blocks)
+}
+
+/// Результат разбора с учётом ограничений [`ParseLimits`].
+#[derive(Debug, Clone, Default)]
+pub struct ParsedBlocks {
+    pub blocks: Vec<BlockInfo>,
+    /// Файл превысил пороги: построены только блоки верхнего уровня
+    /// без якорей и метаданных.
+    pub simplified: bool,
+    /// Время этапов обогащения; пусто, если блоки взяты из кэша или
+    /// разбор упрощён.
+    pub timings: Vec<enrich::StageTiming>,
+}
+
+/// Разбирает `content` на блоки, упрощая результат для слишком больших файлов.
+///
+/// Если размер файла больше `limits.max_file_size` или дерево разбора
+/// содержит больше `limits.max_nodes` узлов, обогащение метаданными и
+/// якоря пропускаются, а в результат попадают только блоки верхнего уровня.
+pub fn parse_blocks_with_limits(
+    content: String,
+    lang: String,
+    limits: &ParseLimits,
+) -> Option<ParsedBlocks> {
+    parse_blocks_with_config(content, lang, limits, &EnrichConfig::default())
+}
+
+/// То же, что [`parse_blocks_with_limits`], но с этапами обогащения из
+/// `enrich`.
+pub fn parse_blocks_with_config(
+    content: String,
+    lang: String,
+    limits: &ParseLimits,
+    enrich: &EnrichConfig,
+) -> Option<ParsedBlocks> {
     let lang = match to_lang(&lang) {
         Some(l) => l,
         None => {
@@ -23,16 +73,96 @@ pub fn parse_blocks(content: String, lang: String) -> Option<Vec<BlockInfo>> {
         }
     };
 
-    let key = cache::key(&content);
+    let key = cache::key(&content, &enrich.stages);
     if let Some(blocks) = cache::get(&key, &content) {
-        return Some(blocks);
+        return Some(ParsedBlocks {
+            blocks,
+            ..Default::default()
+        });
     }
 
-    let mut blocks = parsing::parse(&content, lang)?;
+    let tree = parsing::parse_tree(&content, lang)?;
+    let nodes = tree.root_node().descendant_count();
+    if content.len() > limits.max_file_size || nodes > limits.max_nodes {
+        tracing::warn!(
+            size = content.len(),
+            nodes,
+            "файл превышает пороги разбора, построено упрощённое представление"
+        );
+        let mut blocks = parsing::top_level(&tree);
+        cache::assign_ids(&content, &mut blocks);
+        return Some(ParsedBlocks {
+            blocks: enrich::simplified_blocks(blocks),
+            simplified: true,
+            timings: Vec::new(),
+        });
+    }
+
+    let mut blocks = parse_to_blocks(&tree, None);
     cache::assign_ids(&content, &mut blocks);
-    let result = enrich::enrich_blocks(blocks, &content);
+    let (result, timings) = enrich::run_pipeline(blocks, &content, &enrich.stages);
     cache::store(key, content, result.clone());
-    Some(result)
+    Some(ParsedBlocks {
+        blocks: result,
+        simplified: false,
+        timings,
+    })
+}
+
+/// Рекурсивно разбирает все поддерживаемые исходники в каталоге `root`.
+///
+/// Файлы и каталоги, исключённые правилами `.multicode/ignore`, пропускаются.
+/// Результат упорядочен по пути файла.
+pub fn parse_dir(root: &Path) -> Vec<(PathBuf, Vec<BlockInfo>)> {
+    let rules = IgnoreRules::load(root);
+    let mut out: Vec<(PathBuf, Vec<BlockInfo>)> = WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !rules.is_ignored(e.path(), e.file_type().is_dir()))
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let lang = lang_from_extension(e.path().extension()?.to_str()?)?;
+            let content = file_io::read_to_string(e.path()).ok()?;
+            let blocks = parse_blocks(content, lang.into())?;
+            Some((e.into_path(), blocks))
+        })
+        .collect();
+    out.sort_by(|a, b| a.0.cmp(&b.0));
+    out
+}
+
+/// Поддерживаемые исходные файлы проекта с учётом правил
+/// `.multicode/ignore`, упорядоченные по пути.
+pub(crate) fn source_files(root: &Path) -> Vec<PathBuf> {
+    let rules = IgnoreRules::load(root);
+    WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !rules.is_ignored(e.path(), e.file_type().is_dir()))
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            e.path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .and_then(lang_from_extension)
+                .is_some()
+        })
+        .map(|e| e.into_path())
+        .collect()
+}
+
+/// Язык разбора по расширению файла.
+pub fn lang_from_extension(ext: &str) -> Option<&'static str> {
+    match ext {
+        "rs" => Some("rust"),
+        "py" => Some("python"),
+        "js" => Some("javascript"),
+        "css" => Some("css"),
+        "html" => Some("html"),
+        _ => None,
+    }
 }
 
 pub fn upsert_meta(
@@ -41,11 +171,9 @@ pub fn upsert_meta(
     lang: String,
     files: Vec<String>,
 ) -> HashMap<String, String> {
-    use std::fs;
-
-    meta.updated_at = Utc::now();
     let mut metas = read_all(&content);
-    if let Some(existing) = metas.iter().find(|m| m.id == meta.id) {
+    let previous = metas.iter().find(|m| m.id == meta.id).cloned();
+    if let Some(existing) = previous.as_ref() {
         if meta.version == 0 {
             meta.version = existing.version;
         }
@@ -65,6 +193,10 @@ pub fn upsert_meta(
     if meta.version == 0 {
         meta.version = 1;
     }
+    meta.updated_at = match previous {
+        Some(existing) if canonical::same_content(&existing, &meta) => existing.updated_at,
+        _ => Utc::now(),
+    };
     metas.retain(|m| m.id != meta.id);
     metas.push(meta);
 
@@ -76,30 +208,30 @@ pub fn upsert_meta(
             let updated = metas
                 .clone()
                 .into_iter()
-                .fold(cleaned.clone(), |acc, m| upsert(&acc, &m, false));
+                .fold(cleaned.clone(), |acc, m| reinsert(&acc, &m));
             let mut result = HashMap::new();
             if let Some(id) = files.first() {
                 result.insert(id.clone(), updated);
             }
             for fid in files.iter().skip(1) {
-                if let Ok(src) = fs::read_to_string(fid) {
+                if let Ok(src) = file_io::read_to_string(fid) {
                     let metas = read_all(&src);
                     let cleaned = remove_all(&src);
                     let updated = metas
                         .into_iter()
-                        .fold(cleaned, |acc, m| upsert(&acc, &m, false));
+                        .fold(cleaned, |acc, m| reinsert(&acc, &m));
                     result.insert(fid.clone(), updated);
                 }
             }
             return result;
         }
     };
-    let regenerated = regenerate_code(&cleaned, lang, &metas).unwrap_or(cleaned);
+    let regenerated = regenerate_code(&cleaned, &content, lang, &metas).unwrap_or(cleaned);
 
     let current = metas
         .clone()
         .into_iter()
-        .fold(regenerated, |acc, m| upsert(&acc, &m, false));
+        .fold(regenerated, |acc, m| reinsert(&acc, &m));
 
     let mut result = HashMap::new();
     if let Some(id) = files.first() {
@@ -107,11 +239,11 @@ pub fn upsert_meta(
     }
 
     for fid in files.iter().skip(1) {
-        if let Ok(src) = fs::read_to_string(fid) {
+        if let Ok(src) = file_io::read_to_string(fid) {
             let metas = read_all(&src);
             let cleaned = remove_all(&src);
-            let regen = regenerate_code(&cleaned, lang, &metas).unwrap_or(cleaned);
-            let updated = metas.into_iter().fold(regen, |acc, m| upsert(&acc, &m, false));
+            let regen = regenerate_code(&cleaned, &src, lang, &metas).unwrap_or(cleaned);
+            let updated = metas.into_iter().fold(regen, |acc, m| reinsert(&acc, &m));
             result.insert(fid.clone(), updated);
         }
     }
@@ -119,47 +251,82 @@ pub fn upsert_meta(
     result
 }
 
-fn regenerate_code(content: &str, lang: Lang, metas: &[VisualMeta]) -> Option<String> {
+/// Перегенерирует очищенный от метаданных код `content`. `original` —
+/// исходный текст с комментариями метаданных, по которому вычислены
+/// идентификаторы блоков.
+fn regenerate_code(
+    content: &str,
+    original: &str,
+    lang: Lang,
+    metas: &[VisualMeta],
+) -> Option<String> {
     match lang {
-        Lang::Rust => regenerate_rust(content, metas),
+        Lang::Rust => regenerate_rust(content, original, metas),
         _ => Some(content.to_string()),
     }
 }
 
-fn regenerate_rust(content: &str, metas: &[VisualMeta]) -> Option<String> {
-    let mut file: File = syn::parse_file(content).ok()?;
-    let tree = parse(content, Lang::Rust, None)?;
-    let blocks = parse_to_blocks(&tree, None);
-    let map: HashMap<_, _> = blocks
+/// Идентификаторы блоков верхнего уровня `original` по тексту элемента в
+/// порядке следования. Идентификатор зависит от смещения элемента, а оно
+/// меняется при удалении комментариев метаданных, поэтому элементы
+/// очищенного кода сопоставляются с блоками по тексту.
+fn top_level_ids(original: &str) -> Option<HashMap<String, VecDeque<String>>> {
+    let tree = parse(original, Lang::Rust, None)?;
+    let mut blocks = parse_to_blocks(&tree, None);
+    cache::assign_ids(original, &mut blocks);
+    let ids: HashMap<_, _> = blocks
         .into_iter()
         .map(|b| (b.node_id, b.visual_id))
         .collect();
+    let mut result: HashMap<String, VecDeque<String>> = HashMap::new();
+    let mut cursor = tree.root_node().walk();
+    for node in tree.root_node().children(&mut cursor) {
+        if let Some(id) = ids.get(&(node.id() as u32)) {
+            let text = original[node.byte_range()].trim().to_string();
+            result.entry(text).or_default().push_back(id.clone());
+        }
+    }
+    Some(result)
+}
+
+fn regenerate_rust(content: &str, original: &str, metas: &[VisualMeta]) -> Option<String> {
+    let mut file: File = syn::parse_file(content).ok()?;
+    let tree = parse(content, Lang::Rust, None)?;
+    let mut ids = top_level_ids(original)?;
 
     let mut cursor = tree.root_node().walk();
     let mut roots = Vec::new();
     if cursor.goto_first_child() {
         loop {
-            roots.push(cursor.node().id());
+            roots.push(cursor.node().byte_range());
             if !cursor.goto_next_sibling() {
                 break;
             }
         }
     }
 
-    let mut items: Vec<(Item, (f64, f64))> = file
-        .items
-        .into_iter()
-        .zip(roots.into_iter())
-        .map(|(it, id)| {
-            let vid = map.get(&(id as u32)).cloned().unwrap_or_default();
-            let pos = metas
-                .iter()
-                .find(|m| m.id == vid)
-                .map(|m| (m.y, m.x))
-                .unwrap_or((0.0, 0.0));
-            (it, pos)
-        })
-        .collect();
+    let pinned = pin::pinned_ids(metas);
+    let mut slots: Vec<Option<Item>> = Vec::new();
+    let mut items: Vec<(Item, (f64, f64))> = Vec::new();
+    for (it, range) in file.items.into_iter().zip(roots) {
+        let vid = ids
+            .get_mut(content[range].trim())
+            .and_then(VecDeque::pop_front)
+            .unwrap_or_default();
+        // Закреплённые элементы остаются на своих местах, остальные
+        // переставляются по координатам в оставшихся позициях.
+        if pinned.contains(&vid) {
+            slots.push(Some(it));
+            continue;
+        }
+        let pos = metas
+            .iter()
+            .find(|m| m.id == vid)
+            .map(|m| (m.y, m.x))
+            .unwrap_or((0.0, 0.0));
+        slots.push(None);
+        items.push((it, pos));
+    }
 
     items.sort_by(|a, b| {
         a.1 .0
@@ -168,7 +335,11 @@ fn regenerate_rust(content: &str, metas: &[VisualMeta]) -> Option<String> {
             .then_with(|| a.1 .1.partial_cmp(&b.1 .1).unwrap_or(Ordering::Equal))
     });
 
-    file.items = items.into_iter().map(|(it, _)| it).collect();
+    let mut sorted = items.into_iter().map(|(it, _)| it);
+    file.items = slots
+        .into_iter()
+        .filter_map(|slot| slot.or_else(|| sorted.next()))
+        .collect();
     Some(prettyplease::unparse(&file))
 }
 
@@ -0,0 +1,113 @@
+//! Подписи блоков по исходному коду.
+//!
+//! Без переводов блок подписан видом узла tree-sitter вроде
+//! `let_declaration` или `rule_set`. [`default_name`] выводит из текста
+//! блока понятную подпись: имя функции или класса, имя переменной, текст
+//! условия или заголовок цикла. Синтаксис Rust, Python, JavaScript, CSS и
+//! HTML различается по самому тексту, поэтому язык файла не нужен.
+
+use super::symbols::{symbol_kind, symbol_name};
+
+/// Наибольшая длина подписи в символах, включая многоточие.
+pub const MAX_NAME_CHARS: usize = 40;
+
+/// Ключевые слова перед именем объявляемой переменной.
+const DECLARATION_KEYWORDS: &[&str] = &["let", "mut", "const", "var", "static", "ref"];
+
+/// Текст в одну строку с одиночными пробелами, укороченный до
+/// [`MAX_NAME_CHARS`].
+fn truncate(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if text.chars().count() <= MAX_NAME_CHARS {
+        return text;
+    }
+    let mut out: String = text.chars().take(MAX_NAME_CHARS - 1).collect();
+    out.truncate(out.trim_end().len());
+    out.push('…');
+    out
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Заголовок конструкции: первая строка без открывающей скобки тела
+/// (`{`) или двоеточия Python.
+fn header(text: &str) -> &str {
+    let line = text.lines().next().unwrap_or("").trim();
+    let line = line.strip_suffix('{').unwrap_or(line).trim_end();
+    let line = line.find(" {").map_or(line, |i| &line[..i]);
+    line.strip_suffix(':').unwrap_or(line).trim_end()
+}
+
+/// Условие `if`, `elif` или `else if` с ключевым словом, без скобок
+/// JavaScript вокруг выражения.
+fn condition(text: &str) -> Option<String> {
+    let keyword = ["else if", "elif", "if"].into_iter().find(|k| {
+        text.strip_prefix(k)
+            .is_some_and(|rest| rest.starts_with([' ', '(', '\t', '\n']))
+    })?;
+    let head = header(text)[keyword.len()..].trim();
+    let head = head
+        .strip_prefix('(')
+        .and_then(|h| h.strip_suffix(')'))
+        .unwrap_or(head);
+    Some(format!("{keyword} {}", head.trim()))
+}
+
+/// Имя объявляемой или присваиваемой переменной: `let mut total = 0`,
+/// `const x = 1`, `count += 1` и `name: str = ""` дают имя слева.
+fn variable(text: &str) -> Option<String> {
+    let left = text.split(['=', ':', ';']).next()?;
+    let mut words = left
+        .split(|c: char| !is_ident(c))
+        .filter(|w| !w.is_empty())
+        .skip_while(|w| DECLARATION_KEYWORDS.contains(w));
+    let name = words.next()?;
+    (words.next().is_none() && !name.starts_with(|c: char| c.is_ascii_digit()))
+        .then(|| name.to_string())
+}
+
+/// Подпись блока вида `kind` с текстом `text`; `None`, если вывести
+/// подпись нечем и блок остаётся подписан видом.
+pub fn default_name(kind: &str, text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let name = match kind {
+        "Function/Call" => {
+            let callee = text[..text.find('(')?].trim_end();
+            (!callee.is_empty()).then(|| format!("{}()", truncate(callee)))
+        }
+        "Condition" => condition(text),
+        "Loop" => Some(header(text).to_string()),
+        "Return" => Some(header(text).trim_end_matches(';').to_string()),
+        "rule_set" | "media_statement" | "keyframes_statement" | "at_rule" => {
+            Some(text[..text.find('{')?].trim().to_string())
+        }
+        "element" | "script_element" | "style_element" => {
+            let tag: String = text
+                .strip_prefix('<')?
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '-')
+                .collect();
+            (!tag.is_empty()).then(|| format!("<{tag}>"))
+        }
+        _ if symbol_kind(kind).is_some() => symbol_name(text).map(str::to_string),
+        "Variable"
+        | "let_declaration"
+        | "lexical_declaration"
+        | "variable_declaration"
+        | "assignment"
+        | "augmented_assignment"
+        | "const_item"
+        | "static_item" => variable(text),
+        _ => None,
+    };
+    // Прочие узлы в одну строку, как `x + 1` или ключевое слово `for`,
+    // подписываются своим текстом.
+    let name = name.or_else(|| (!text.contains('\n')).then(|| text.to_string()))?;
+    let name = truncate(&name);
+    (!name.is_empty()).then_some(name)
+}
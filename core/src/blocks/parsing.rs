@@ -1,29 +1,27 @@
-use tree_sitter::{InputEdit, Point};
+use tree_sitter::{InputEdit, Point, Tree};
 
+use crate::parser::{check_tree, map_kind, parse as ts_parse, Block, Lang};
+use crate::text::position_index;
 use crate::{get_document_tree, update_document_tree};
-use crate::parser::{parse as ts_parse, parse_to_blocks, Block, Lang};
 
-/// Преобразует исходное `content` языка `lang` в синтаксические `Block`.
+/// Строит дерево разбора для `content`.
 ///
 /// Повторно использует ранее сохранённое дерево разбора для
 /// инкрементального парсинга, обновляя кэшированное дерево после разбора.
-pub fn parse(content: &str, lang: Lang) -> Option<Vec<Block>> {
-    let old = get_document_tree("current");
+/// Деревья хранятся отдельно для каждого языка, поэтому смена языка не
+/// считается расхождением грамматик; дерево, построенное другой версией
+/// грамматики, отбрасывается.
+pub fn parse_tree(content: &str, lang: Lang) -> Option<Tree> {
+    let id = format!("current:{lang}");
+    let old = get_document_tree(&id).filter(|t| check_tree(t, lang).is_none());
     let tree = if let Some(mut old_tree) = old {
         let old_root = old_tree.root_node();
         let old_end_byte = old_root.end_byte();
         let old_end_position = old_root.end_position();
-        let new_end_byte = content.as_bytes().len();
-        let mut row = 0;
-        let mut column = 0;
-        for b in content.bytes() {
-            if b == b'\n' {
-                row += 1;
-                column = 0;
-            } else {
-                column += 1;
-            }
-        }
+        let new_end_byte = content.len();
+        let index = position_index("current", content);
+        let row = index.line_of(new_end_byte);
+        let column = new_end_byte - index.line_start(row).unwrap_or(0);
         let new_end_position = Point { row, column };
         let edit = InputEdit {
             start_byte: 0,
@@ -38,6 +36,23 @@ pub fn parse(content: &str, lang: Lang) -> Option<Vec<Block>> {
     } else {
         ts_parse(content, lang, None)?
     };
-    update_document_tree("current".to_string(), tree.clone());
-    Some(parse_to_blocks(&tree, None))
+    update_document_tree(id, tree.clone());
+    Some(tree)
+}
+
+/// Блоки только для именованных узлов верхнего уровня, без якорей.
+///
+/// Используется для упрощённого представления слишком больших файлов.
+pub fn top_level(tree: &Tree) -> Vec<Block> {
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    root.named_children(&mut cursor)
+        .map(|node| Block {
+            visual_id: String::new(),
+            node_id: node.id() as u32,
+            kind: map_kind(node.kind()),
+            range: node.byte_range(),
+            anchors: Vec::new(),
+        })
+        .collect()
 }
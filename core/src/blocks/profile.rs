@@ -0,0 +1,25 @@
+//! Время разбора блоков для профилирования холста.
+//!
+//! Исходный текст каждого блока разбирается отдельно от файла, так что
+//! время показывает, во что обходится разбор самого блока, без соседей.
+
+use std::time::{Duration, Instant};
+
+use crate::parser::{self, Lang};
+use crate::BlockInfo;
+
+/// Время разбора исходного текста каждого блока `blocks` файла `content`.
+/// Блоки без текста, например ссылки на файлы, получают нулевое время.
+pub fn parse_costs(content: &str, lang: Lang, blocks: &[BlockInfo]) -> Vec<Duration> {
+    blocks
+        .iter()
+        .map(|b| {
+            let Some(source) = content.get(b.range.0..b.range.1).filter(|s| !s.is_empty()) else {
+                return Duration::ZERO;
+            };
+            let start = Instant::now();
+            let _ = parser::parse(source, lang, None);
+            start.elapsed()
+        })
+        .collect()
+}
@@ -0,0 +1,126 @@
+use serde::Serialize;
+
+use crate::anchor::Anchor;
+use crate::i18n;
+use crate::meta::read_all;
+use crate::parser::{parse, parse_to_blocks, Lang};
+
+/// Запись о блоке в снимке.
+///
+/// Поля объявлены в алфавитном порядке, поэтому ключи JSON всегда
+/// отсортированы. Идентификаторы узлов tree-sitter и хеши `visual_id`
+/// в снимок не попадают: они зависят от версии компилятора и парсера,
+/// а не от структуры кода.
+#[derive(Debug, Serialize)]
+struct SnapshotBlock {
+    anchors: Vec<Anchor>,
+    end: usize,
+    kind: String,
+    label: String,
+    links: Vec<String>,
+    start: usize,
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Snapshot {
+    blocks: Vec<SnapshotBlock>,
+    lang: String,
+}
+
+/// Строит нормализованный JSON-снимок блоков `content` для эталонных файлов.
+///
+/// Разбор выполняется без кэшей и сохранённых деревьев, блоки упорядочены по
+/// диапазону, а меток времени в выводе нет, поэтому результат одинаков
+/// между запусками. Возвращает `None`, если исходник не удалось разобрать.
+pub fn snapshot(content: &str, lang: Lang) -> Option<String> {
+    let tree = parse(content, lang, None)?;
+    let metas = read_all(content);
+    let mut blocks: Vec<SnapshotBlock> = parse_to_blocks(&tree, None)
+        .into_iter()
+        .map(|b| {
+            let id = super::cache::stable_id(content, (b.range.start, b.range.end));
+            let meta = metas.iter().find(|m| m.id == id);
+            let kind = super::enrich::normalize_kind(&b.kind);
+            let label = i18n::lookup(&kind)
+                .and_then(|t| t.get("en").cloned())
+                .unwrap_or_else(|| kind.clone());
+            let mut anchors = b.anchors;
+            anchors.sort_unstable();
+            let mut tags = meta.map(|m| m.tags.clone()).unwrap_or_default();
+            tags.sort();
+            let mut links = meta.map(|m| m.links.clone()).unwrap_or_default();
+            links.sort();
+            SnapshotBlock {
+                anchors,
+                end: b.range.end,
+                kind,
+                label,
+                links,
+                start: b.range.start,
+                tags,
+            }
+        })
+        .collect();
+    blocks.sort_by(|a, b| (a.start, a.end, &a.kind).cmp(&(b.start, b.end, &b.kind)));
+    let snapshot = Snapshot {
+        blocks,
+        lang: lang.to_string(),
+    };
+    let mut out = serde_json::to_string_pretty(&snapshot).ok()?;
+    out.push('\n');
+    Some(out)
+}
+
+/// Сравнивает текущий снимок `content` с эталоном `expected`.
+///
+/// Различия в окончаниях строк и завершающих пробелах эталона не учитываются.
+pub fn check_snapshot(content: &str, lang: Lang, expected: &str) -> Result<(), String> {
+    let actual =
+        snapshot(content, lang).ok_or_else(|| "не удалось разобрать исходник".to_string())?;
+    let expected = expected.replace("\r\n", "\n");
+    if actual.trim_end() == expected.trim_end() {
+        return Ok(());
+    }
+    let line = actual
+        .lines()
+        .zip(expected.lines())
+        .position(|(a, e)| a != e)
+        .unwrap_or_else(|| actual.lines().count().min(expected.lines().count()));
+    Err(format!(
+        "снимок отличается от эталона начиная со строки {}",
+        line + 1
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_is_stable_and_sorted() {
+        let src = "fn main() {\n    let x = 1;\n}\n";
+        let first = snapshot(src, Lang::Rust).unwrap();
+        let second = snapshot(src, Lang::Rust).unwrap();
+        assert_eq!(first, second);
+        assert!(!first.contains("updated_at"));
+        let value: serde_json::Value = serde_json::from_str(&first).unwrap();
+        let blocks = value["blocks"].as_array().unwrap();
+        assert!(!blocks.is_empty());
+        let starts: Vec<u64> = blocks
+            .iter()
+            .map(|b| b["start"].as_u64().unwrap())
+            .collect();
+        let mut sorted = starts.clone();
+        sorted.sort();
+        assert_eq!(starts, sorted);
+    }
+
+    #[test]
+    fn check_detects_drift() {
+        let src = "fn main() {}\n";
+        let golden = snapshot(src, Lang::Rust).unwrap();
+        assert!(check_snapshot(src, Lang::Rust, &golden).is_ok());
+        assert!(check_snapshot("fn main() { let y = 2; }\n", Lang::Rust, &golden).is_err());
+    }
+}
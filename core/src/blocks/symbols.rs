@@ -0,0 +1,156 @@
+//! Символы кода рабочего пространства.
+//!
+//! Функции и классы проекта собираются из блоков разбора: у каждого
+//! символа есть имя из исходника, переводы имени из метаданных блока и
+//! положение в файле. По этому списку редактор ищет символ и переходит к
+//! его блоку.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::{lang_from_extension, parse_blocks, source_files};
+use crate::file_io;
+use crate::meta::read_all;
+use crate::text::PositionIndex;
+use crate::BlockInfo;
+
+/// Ключевые слова, после которых в заголовке стоит имя символа.
+const DEFINING: &[&str] = &[
+    "fn", "def", "function", "class", "struct", "enum", "trait", "union",
+];
+
+/// Модификаторы перед именем метода без ключевого слова, как в JavaScript.
+const MODIFIERS: &[&str] = &["static", "async", "get", "set"];
+
+/// Вид символа.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeSymbolKind {
+    /// Функция или метод.
+    Function,
+    /// Класс, структура, перечисление или трейт.
+    Class,
+}
+
+/// Функция или класс проекта.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeSymbol {
+    /// Имя из исходного кода.
+    pub name: String,
+    pub kind: CodeSymbolKind,
+    pub path: PathBuf,
+    /// Строка заголовка, с нуля.
+    pub line: usize,
+    /// Идентификатор блока символа.
+    pub visual_id: String,
+    /// Переводы имени из метаданных блока.
+    pub translations: HashMap<String, String>,
+}
+
+impl CodeSymbol {
+    /// Имя на языке `lang`, если оно переведено, иначе имя из исходника.
+    pub fn label(&self, lang: &str) -> &str {
+        self.translations.get(lang).unwrap_or(&self.name)
+    }
+}
+
+/// Вид символа для блока типа `kind`; остальные блоки символами не считаются.
+pub fn symbol_kind(kind: &str) -> Option<CodeSymbolKind> {
+    let kind = kind.to_lowercase();
+    if kind == "function/define" {
+        Some(CodeSymbolKind::Function)
+    } else if kind.contains("class")
+        || ["struct_item", "enum_item", "trait_item", "union_item"].contains(&kind.as_str())
+    {
+        Some(CodeSymbolKind::Class)
+    } else {
+        None
+    }
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Идентификаторы строки `line` с их байтовыми смещениями.
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut out = Vec::new();
+    let mut start = None;
+    for (i, c) in line.char_indices().chain([(line.len(), ' ')]) {
+        match (start, is_ident(c)) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                out.push((s, &line[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Имя символа по первой строке его блока; у анонимных функций имени нет.
+pub fn symbol_name(header: &str) -> Option<&str> {
+    let line = header.lines().next()?;
+    let words = words(line);
+    if let Some(i) = words.iter().position(|(_, w)| DEFINING.contains(w)) {
+        let (start, keyword) = words[i];
+        let (next, name) = *words.get(i + 1)?;
+        let gap = &line[start + keyword.len()..next];
+        return gap
+            .chars()
+            .all(|c| c.is_whitespace() || c == '*')
+            .then_some(name);
+    }
+    // Метод класса JavaScript: `name(...) { ... }`.
+    let (start, name) = *words.iter().find(|(_, w)| !MODIFIERS.contains(w))?;
+    line[start + name.len()..]
+        .trim_start()
+        .starts_with('(')
+        .then_some(name)
+}
+
+/// Символы файла `path` по его содержимому и блокам разбора.
+pub fn file_symbols(path: &Path, content: &str, blocks: &[BlockInfo]) -> Vec<CodeSymbol> {
+    let translations: HashMap<String, HashMap<String, String>> = read_all(content)
+        .into_iter()
+        .map(|m| (m.id, m.translations))
+        .collect();
+    let index = PositionIndex::new(content);
+    blocks
+        .iter()
+        .filter_map(|b| {
+            let kind = symbol_kind(&b.kind)?;
+            let header = content.get(b.range.0..b.range.1)?;
+            Some(CodeSymbol {
+                name: symbol_name(header)?.to_string(),
+                kind,
+                path: path.to_path_buf(),
+                line: index.line_of(b.range.0),
+                visual_id: b.visual_id.clone(),
+                translations: translations.get(&b.visual_id).cloned().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+/// Функции и классы всех исходников проекта `root`, упорядоченные по файлу
+/// и строке.
+pub fn workspace_symbols(root: &Path) -> Vec<CodeSymbol> {
+    let mut symbols = Vec::new();
+    for path in source_files(root) {
+        let Some(lang) = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension)
+        else {
+            continue;
+        };
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(blocks) = parse_blocks(content.clone(), lang.into()) {
+            symbols.extend(file_symbols(&path, &content, &blocks));
+        }
+    }
+    symbols
+}
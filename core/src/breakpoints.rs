@@ -0,0 +1,171 @@
+//! Точки останова, привязанные к блокам.
+//!
+//! Точки хранятся в `.multicode/breakpoints.toml` в корне проекта:
+//!
+//! ```toml
+//! [[breakpoint]]
+//! file = "src/net.rs"
+//! visual_id = "connect"
+//! offset = 2
+//! ```
+//!
+//! Точка задана блоком и номером строки от начала блока, а не номером
+//! строки файла, поэтому правки выше блока её не сдвигают. Конкретные
+//! строки вычисляются при запуске отладки по текущему тексту файлов.
+//! Точка, блока которой больше нет, пропускается.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::blocks::{lang_from_extension, parse_blocks};
+use crate::debugger::line_span;
+use crate::meta;
+use crate::text::PositionIndex;
+use crate::BlockInfo;
+
+/// Файл точек останова относительно корня проекта.
+pub const BREAKPOINTS_FILE: &str = ".multicode/breakpoints.toml";
+
+/// Точка останова.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    /// Путь файла относительно корня проекта.
+    pub file: PathBuf,
+    /// Блок, в котором стоит точка.
+    pub visual_id: String,
+    /// Строка точки от первой строки блока, с нуля.
+    pub offset: usize,
+}
+
+/// Точки останова проекта.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Breakpoints {
+    #[serde(default, rename = "breakpoint")]
+    pub entries: Vec<Breakpoint>,
+}
+
+impl Breakpoints {
+    /// Загружает точки проекта `root`; отсутствующий файл даёт пустой список.
+    pub fn load(root: &Path) -> io::Result<Self> {
+        let data = match fs::read_to_string(root.join(BREAKPOINTS_FILE)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        toml::from_str(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// Записывает точки в файл проекта `root`.
+    pub fn save(&self, root: &Path) -> io::Result<()> {
+        let path = root.join(BREAKPOINTS_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = toml::to_string(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        fs::write(path, data)
+    }
+
+    /// Ставит точку `breakpoint` или снимает уже стоящую. Возвращает
+    /// `true`, если точка поставлена.
+    pub fn toggle(&mut self, breakpoint: Breakpoint) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|b| *b != breakpoint);
+        if self.entries.len() == len {
+            self.entries.push(breakpoint);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Точки файла `file` (относительно корня проекта).
+    pub fn in_file<'a>(&'a self, file: &'a Path) -> impl Iterator<Item = &'a Breakpoint> {
+        self.entries.iter().filter(move |b| b.file == file)
+    }
+
+    /// Строки (с единицы) точек файла `file` с содержимым `content` и
+    /// блоками `blocks`, по возрастанию.
+    pub fn lines(&self, file: &Path, content: &str, blocks: &[BlockInfo]) -> Vec<usize> {
+        let mut lines: Vec<usize> = self
+            .in_file(file)
+            .filter_map(|b| line_of(b, content, blocks))
+            .collect();
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    }
+
+    /// Строки всех точек проекта `root` по текущему тексту файлов: путь
+    /// файла относительно корня и строка с единицы. Файлы, которые не
+    /// читаются или не разбираются, пропускаются.
+    pub fn resolve(&self, root: &Path) -> Vec<(PathBuf, usize)> {
+        let mut files: Vec<&Path> = self.entries.iter().map(|b| b.file.as_path()).collect();
+        files.sort();
+        files.dedup();
+        files
+            .into_iter()
+            .flat_map(|file| {
+                let Some(lang) = file
+                    .extension()
+                    .and_then(|e| e.to_str())
+                    .and_then(lang_from_extension)
+                else {
+                    return Vec::new();
+                };
+                let Ok(content) = fs::read_to_string(root.join(file)) else {
+                    return Vec::new();
+                };
+                let blocks = parse_blocks(content.clone(), lang.into()).unwrap_or_default();
+                self.lines(file, &content, &blocks)
+                    .into_iter()
+                    .map(|line| (file.to_path_buf(), line))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Точка останова на строке `line` (с единицы) файла `file`: в самом
+/// вложенном блоке с метаданными, содержащем строку, а без такого — в
+/// самом вложенном блоке вообще. Идентификатор блока из метаданных не
+/// меняется при правках, поэтому такие блоки предпочтительнее.
+pub fn at_line(
+    file: &Path,
+    content: &str,
+    blocks: &[BlockInfo],
+    line: usize,
+) -> Option<Breakpoint> {
+    let (start, end) = line_span(content, line)?;
+    let ids: Vec<String> = meta::read_all(content).into_iter().map(|m| m.id).collect();
+    let innermost = |with_meta: bool| {
+        blocks
+            .iter()
+            .filter(|b| b.range.0 <= start && end <= b.range.1)
+            .filter(|b| !with_meta || ids.contains(&b.visual_id))
+            .min_by_key(|b| b.range.1 - b.range.0)
+    };
+    let block = innermost(true).or_else(|| innermost(false))?;
+    let first = PositionIndex::new(content).line_of(block.range.0) + 1;
+    Some(Breakpoint {
+        file: file.to_path_buf(),
+        visual_id: block.visual_id.clone(),
+        offset: line - first,
+    })
+}
+
+/// Строка (с единицы) точки `breakpoint` в файле `content` с блоками
+/// `blocks`; `None`, если блока точки нет или блок стал короче точки.
+pub fn line_of(breakpoint: &Breakpoint, content: &str, blocks: &[BlockInfo]) -> Option<usize> {
+    let block = blocks
+        .iter()
+        .find(|b| b.visual_id == breakpoint.visual_id)?;
+    let index = PositionIndex::new(content);
+    let first = index.line_of(block.range.0);
+    let last = index.line_of(block.range.1.saturating_sub(1).max(block.range.0));
+    let line = first + breakpoint.offset;
+    (line <= last).then_some(line + 1)
+}
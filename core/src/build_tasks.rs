@@ -0,0 +1,271 @@
+//! Задачи сборки проекта и разбор их вывода.
+//!
+//! Задачи хранятся в `.multicode/tasks.toml` в корне проекта:
+//!
+//! ```toml
+//! [[task]]
+//! name = "build"
+//! command = "cargo build"
+//! cwd = "core"
+//! matchers = ["rustc"]
+//!
+//! [[task]]
+//! name = "lint"
+//! command = "eslint -f unix src"
+//! matchers = [{ pattern = '^(?P<file>[^:\n]+):(?P<line>\d+):(?P<column>\d+): (?P<message>.+)$', severity = "warning" }]
+//! ```
+//!
+//! Сопоставитель ошибок (problem matcher) — встроенный по названию
+//! ([`BUILTIN_MATCHERS`]) или регулярное выражение с именованными группами
+//! `file`, `line`, `message` и необязательными `column` и `severity`.
+//! Выражение применяется ко всему выводу в многострочном режиме, поэтому
+//! может захватывать несколько строк. Без `matchers` применяются все
+//! встроенные сопоставители. Относительные пути из вывода отсчитываются от
+//! рабочего каталога задачи.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::lint::Severity;
+use crate::project_env;
+use crate::shell::Shell;
+
+/// Файл задач сборки относительно корня проекта.
+pub const BUILD_TASKS_FILE: &str = ".multicode/tasks.toml";
+
+/// Встроенные сопоставители: название и регулярное выражение.
+pub const BUILTIN_MATCHERS: &[(&str, &str)] = &[
+    // error[E0425]: cannot find value `x` in this scope
+    //   --> src/main.rs:2:5
+    (
+        "rustc",
+        r"^(?P<severity>error|warning)(?:\[\w+\])?: (?P<message>.+)\n\s*--> (?P<file>[^:\n]+):(?P<line>\d+):(?P<column>\d+)",
+    ),
+    // src/main.c:3:5: error: expected ';'
+    (
+        "gcc",
+        r"^(?P<file>[^:\s][^:\n]*):(?P<line>\d+):(?P<column>\d+): (?:fatal )?(?P<severity>error|warning|note): (?P<message>.+)$",
+    ),
+    // src/app.ts(3,5): error TS2304: Cannot find name 'x'.
+    (
+        "tsc",
+        r"^(?P<file>[^(\n]+)\((?P<line>\d+),(?P<column>\d+)\): (?P<severity>error|warning) (?P<message>.+)$",
+    ),
+];
+
+/// Сопоставитель ошибок в описании задачи.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MatcherSpec {
+    /// Название встроенного сопоставителя.
+    Builtin(String),
+    Custom(ProblemMatcher),
+}
+
+/// Регулярное выражение, выделяющее ошибки из вывода.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProblemMatcher {
+    pub pattern: String,
+    /// Уровень ошибок, у которых нет группы `severity`.
+    #[serde(default = "default_severity")]
+    pub severity: Severity,
+}
+
+fn default_severity() -> Severity {
+    Severity::Error
+}
+
+impl ProblemMatcher {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            severity: default_severity(),
+        }
+    }
+
+    /// Встроенный сопоставитель `name`.
+    pub fn builtin(name: &str) -> Option<Self> {
+        BUILTIN_MATCHERS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, pattern)| Self::new(*pattern))
+    }
+
+    fn regex(&self) -> Result<Regex, regex::Error> {
+        RegexBuilder::new(&self.pattern).multi_line(true).build()
+    }
+}
+
+/// Задача сборки.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildTask {
+    pub name: String,
+    /// Команда оболочки.
+    pub command: String,
+    /// Рабочий каталог относительно корня проекта.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matchers: Vec<MatcherSpec>,
+}
+
+impl BuildTask {
+    /// Рабочий каталог задачи в проекте `root`.
+    pub fn working_dir(&self, root: &Path) -> PathBuf {
+        match &self.cwd {
+            Some(cwd) => root.join(cwd),
+            None => root.to_path_buf(),
+        }
+    }
+
+    /// Сопоставители задачи; без заданных — все встроенные.
+    pub fn problem_matchers(&self) -> Result<Vec<ProblemMatcher>, String> {
+        if self.matchers.is_empty() {
+            return Ok(BUILTIN_MATCHERS
+                .iter()
+                .map(|(_, pattern)| ProblemMatcher::new(*pattern))
+                .collect());
+        }
+        self.matchers
+            .iter()
+            .map(|spec| match spec {
+                MatcherSpec::Builtin(name) => ProblemMatcher::builtin(name)
+                    .ok_or_else(|| format!("unknown problem matcher '{name}'")),
+                MatcherSpec::Custom(matcher) => Ok(matcher.clone()),
+            })
+            .collect()
+    }
+}
+
+/// Задачи сборки проекта.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildTasks {
+    #[serde(default, rename = "task")]
+    pub tasks: Vec<BuildTask>,
+}
+
+impl BuildTasks {
+    /// Загружает задачи проекта `root`; отсутствующий файл даёт пустой
+    /// список. Неизвестные встроенные сопоставители и ошибочные выражения
+    /// считаются ошибкой файла.
+    pub fn load(root: &Path) -> io::Result<Self> {
+        let data = match fs::read_to_string(root.join(BUILD_TASKS_FILE)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        let tasks: Self = toml::from_str(&data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        for task in &tasks.tasks {
+            for matcher in task.problem_matchers().map_err(invalid)? {
+                matcher.regex().map_err(|e| invalid(e.to_string()))?;
+            }
+        }
+        Ok(tasks)
+    }
+
+    /// Задача с названием `name`.
+    pub fn get(&self, name: &str) -> Option<&BuildTask> {
+        self.tasks.iter().find(|t| t.name == name)
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Ошибка, найденная в выводе задачи.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Problem {
+    pub file: PathBuf,
+    /// Строка с единицы.
+    pub line: usize,
+    /// Столбец с единицы; `1`, если сопоставитель его не выделяет.
+    pub column: usize,
+    pub message: String,
+    pub severity: Severity,
+}
+
+/// Ошибки из вывода `output` по сопоставителям `matchers`, в порядке
+/// появления в выводе. Одна и та же ошибка, найденная несколькими
+/// сопоставителями, попадает в список один раз.
+pub fn match_problems(output: &str, matchers: &[ProblemMatcher]) -> Vec<Problem> {
+    let mut found: Vec<(usize, Problem)> = Vec::new();
+    for matcher in matchers {
+        let Ok(re) = matcher.regex() else {
+            continue;
+        };
+        for caps in re.captures_iter(output) {
+            let number = |group: &str| caps.name(group).and_then(|m| m.as_str().parse().ok());
+            let (Some(file), Some(line), Some(message)) =
+                (caps.name("file"), number("line"), caps.name("message"))
+            else {
+                continue;
+            };
+            let severity = match caps.name("severity").map(|m| m.as_str()) {
+                Some("error") => Severity::Error,
+                Some("warning") => Severity::Warning,
+                Some(_) => Severity::Note,
+                None => matcher.severity,
+            };
+            let problem = Problem {
+                file: PathBuf::from(file.as_str().trim()),
+                line,
+                column: number("column").unwrap_or(1),
+                message: message.as_str().trim().to_string(),
+                severity,
+            };
+            if !found.iter().any(|(_, p)| *p == problem) {
+                found.push((caps.get(0).map_or(0, |m| m.start()), problem));
+            }
+        }
+    }
+    found.sort_by_key(|(pos, _)| *pos);
+    found.into_iter().map(|(_, p)| p).collect()
+}
+
+/// Результат выполнения задачи.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskRun {
+    pub task: String,
+    /// Ошибки из вывода; пути файлов от корня проекта.
+    pub problems: Vec<Problem>,
+    /// Команда завершилась успешно.
+    pub success: bool,
+}
+
+/// Выполняет задачу `task` в проекте `root` и разбирает её вывод.
+pub fn run(root: &Path, task: &BuildTask) -> io::Result<TaskRun> {
+    let matchers = task.problem_matchers().map_err(invalid)?;
+    let dir = task.working_dir(root);
+    let output = Shell::system()
+        .command(&task.command)
+        .current_dir(&dir)
+        .envs(project_env::vars(root))
+        .output()?;
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let problems = match_problems(&text, &matchers)
+        .into_iter()
+        .map(|mut p| {
+            let file = dir.join(&p.file);
+            p.file = file
+                .strip_prefix(root)
+                .map(Path::to_path_buf)
+                .unwrap_or(file);
+            p
+        })
+        .collect();
+    Ok(TaskRun {
+        task: task.name.clone(),
+        problems,
+        success: output.status.success(),
+    })
+}
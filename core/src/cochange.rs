@@ -0,0 +1,311 @@
+//! Анализ совместных изменений блоков по истории git.
+//!
+//! Блоки, которые часто меняются в одних и тех же коммитах, скорее всего
+//! связаны по смыслу. [`analyze`] обходит историю репозитория, находит
+//! такие пары и предлагает связать их, а также отмечает существующие
+//! связи между блоками, которые ни разу не менялись вместе.
+//!
+//! Идентификаторы блоков зависят от их содержимого и меняются при правке,
+//! поэтому в истории блок узнаётся по файлу, виду и первой строке кода, а
+//! идентификатор берётся из текущего состояния рабочего каталога.
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use git2::{DiffOptions, Repository, Sort};
+use serde::Serialize;
+
+use crate::blocks::{lang_from_extension, parse_blocks, parse_dir};
+use crate::file_io;
+use crate::meta::file_ref::FILE_REF_KIND;
+use crate::BlockInfo;
+
+/// Параметры анализа совместных изменений.
+#[derive(Debug, Clone)]
+pub struct CoChangeOptions {
+    /// Сколько последних коммитов просматривать.
+    pub max_commits: usize,
+    /// Минимальное число общих коммитов для предложения связи.
+    pub min_together: usize,
+    /// Минимальная доля общих коммитов от числа изменений более
+    /// редко меняющегося блока пары.
+    pub min_confidence: f64,
+    /// Коммиты, затронувшие больше блоков, пропускаются: массовое
+    /// форматирование или переименование не говорит о связи блоков.
+    pub max_blocks_per_commit: usize,
+}
+
+impl Default for CoChangeOptions {
+    fn default() -> Self {
+        Self {
+            max_commits: 500,
+            min_together: 2,
+            min_confidence: 0.5,
+            max_blocks_per_commit: 30,
+        }
+    }
+}
+
+/// Блок рабочего каталога, участвующий в анализе.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockRef {
+    /// Путь к файлу относительно корня репозитория.
+    pub file: PathBuf,
+    pub id: String,
+    pub kind: String,
+    /// Номер первой строки блока, с нуля.
+    pub line: usize,
+    /// Первая строка кода блока.
+    pub label: String,
+}
+
+/// Пара блоков, которые часто меняются вместе, но ещё не связаны.
+#[derive(Debug, Clone, Serialize)]
+pub struct CoChange {
+    pub a: BlockRef,
+    pub b: BlockRef,
+    /// Число коммитов, изменивших оба блока.
+    pub together: usize,
+    /// Доля общих коммитов от числа изменений более редкого блока.
+    pub confidence: f64,
+}
+
+/// Связь между блоками, которые ни разу не менялись в одном коммите.
+#[derive(Debug, Clone, Serialize)]
+pub struct StaleLink {
+    pub from: BlockRef,
+    pub to: BlockRef,
+}
+
+/// Результат анализа: предложения связей по убыванию силы и
+/// подозрительные существующие связи.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CoChangeReport {
+    pub suggestions: Vec<CoChange>,
+    pub stale_links: Vec<StaleLink>,
+    /// Сколько коммитов учтено.
+    pub commits: usize,
+    /// Рабочий каталог репозитория, относительно которого указаны файлы.
+    pub root: PathBuf,
+}
+
+/// Ключ блока, устойчивый к правкам его тела.
+type Key = (PathBuf, String, String);
+
+fn first_line(content: &str, block: &BlockInfo) -> String {
+    content
+        .get(block.range.0..block.range.1)
+        .and_then(|s| s.lines().next())
+        .unwrap_or("")
+        .trim()
+        .to_string()
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content.get(..offset).map_or(0, |s| s.matches('\n').count())
+}
+
+fn contains(outer: &BlockInfo, inner: &BlockInfo) -> bool {
+    outer.range.0 <= inner.range.0 && inner.range.1 <= outer.range.1
+}
+
+/// Блоки верхнего уровня файла: не вложенные ни в какие блоки, кроме
+/// корневого узла, охватывающего весь файл.
+fn outer_blocks(blocks: &[BlockInfo]) -> Vec<&BlockInfo> {
+    let blocks: Vec<&BlockInfo> = blocks.iter().filter(|b| b.kind != FILE_REF_KIND).collect();
+    let is_root = |b: &BlockInfo| blocks.iter().all(|o| contains(b, o));
+    let blocks: Vec<&BlockInfo> = blocks.iter().copied().filter(|b| !is_root(b)).collect();
+    blocks
+        .iter()
+        .enumerate()
+        .filter(|(i, b)| {
+            !blocks
+                .iter()
+                .enumerate()
+                .any(|(j, o)| j != *i && contains(o, b) && (o.range != b.range || j < *i))
+        })
+        .map(|(_, b)| *b)
+        .collect()
+}
+
+/// Ключи блоков файла `content`, затронутых изменёнными строками `hunks`
+/// (номера строк с единицы, включительно).
+fn changed_blocks(file: &Path, content: &str, hunks: &[(usize, usize)]) -> Vec<Key> {
+    let Some(lang) = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(lang_from_extension)
+    else {
+        return Vec::new();
+    };
+    let blocks = parse_blocks(content.to_string(), lang.into()).unwrap_or_default();
+    outer_blocks(&blocks)
+        .into_iter()
+        .filter(|b| {
+            let start = line_of(content, b.range.0) + 1;
+            let end = line_of(content, b.range.1) + 1;
+            hunks.iter().any(|&(s, e)| s <= end && start <= e)
+        })
+        .map(|b| (file.to_path_buf(), b.kind.clone(), first_line(content, b)))
+        .collect()
+}
+
+/// Анализирует историю репозитория, содержащего `root`.
+pub fn analyze(root: &Path, opts: &CoChangeOptions) -> Result<CoChangeReport, git2::Error> {
+    let repo = Repository::discover(root)?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| git2::Error::from_str("репозиторий не имеет рабочего каталога"))?
+        .to_path_buf();
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.set_sorting(Sort::TIME)?;
+
+    let mut changes: HashMap<Key, usize> = HashMap::new();
+    let mut together: HashMap<(Key, Key), usize> = HashMap::new();
+    let mut commits = 0;
+    for oid in revwalk.take(opts.max_commits) {
+        let commit = repo.find_commit(oid?)?;
+        // Первый коммит добавляет всё сразу, а слияния повторяют изменения
+        // своих веток, поэтому учитываются только обычные коммиты.
+        if commit.parent_count() != 1 {
+            continue;
+        }
+        let tree = commit.tree()?;
+        let parent_tree = commit.parent(0)?.tree()?;
+        // Без строк контекста фрагменты указывают ровно на изменённые строки.
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.context_lines(0);
+        let diff = repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), Some(&mut diff_opts))?;
+        let mut hunks: HashMap<PathBuf, Vec<(usize, usize)>> = HashMap::new();
+        diff.foreach(
+            &mut |_, _| true,
+            None,
+            Some(&mut |delta, hunk| {
+                if let Some(path) = delta.new_file().path() {
+                    let start = hunk.new_start() as usize;
+                    let end = start + (hunk.new_lines() as usize).max(1) - 1;
+                    hunks
+                        .entry(path.to_path_buf())
+                        .or_default()
+                        .push((start, end));
+                }
+                true
+            }),
+            None,
+        )?;
+
+        let mut changed = BTreeSet::new();
+        for (file, file_hunks) in &hunks {
+            let Ok(entry) = tree.get_path(file) else {
+                continue;
+            };
+            let Ok(blob) = repo.find_blob(entry.id()) else {
+                continue;
+            };
+            let content = String::from_utf8_lossy(blob.content());
+            changed.extend(changed_blocks(file, &content, file_hunks));
+        }
+        commits += 1;
+        if changed.len() > opts.max_blocks_per_commit {
+            continue;
+        }
+        let changed: Vec<Key> = changed.into_iter().collect();
+        for (i, a) in changed.iter().enumerate() {
+            *changes.entry(a.clone()).or_insert(0) += 1;
+            for b in &changed[i + 1..] {
+                *together.entry((a.clone(), b.clone())).or_insert(0) += 1;
+            }
+        }
+    }
+
+    // Текущие блоки рабочего каталога: по ключу и по идентификатору.
+    let mut current: HashMap<Key, (BlockRef, Vec<String>)> = HashMap::new();
+    for (path, blocks) in parse_dir(&workdir) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let file = path.strip_prefix(&workdir).unwrap_or(&path).to_path_buf();
+        for block in outer_blocks(&blocks) {
+            let label = first_line(&content, block);
+            let key = (file.clone(), block.kind.clone(), label.clone());
+            let block_ref = BlockRef {
+                file: file.clone(),
+                id: block.visual_id.clone(),
+                kind: block.kind.clone(),
+                line: line_of(&content, block.range.0),
+                label,
+            };
+            current.insert(key, (block_ref, block.links.clone()));
+        }
+    }
+    let by_id: HashMap<&str, &Key> = current
+        .iter()
+        .map(|(key, (block, _))| (block.id.as_str(), key))
+        .collect();
+
+    let linked = |a: &Key, b: &Key| {
+        let (Some((ra, la)), Some((rb, lb))) = (current.get(a), current.get(b)) else {
+            return false;
+        };
+        la.contains(&rb.id) || lb.contains(&ra.id)
+    };
+
+    let mut suggestions = Vec::new();
+    for ((a, b), &count) in &together {
+        if count < opts.min_together || linked(a, b) {
+            continue;
+        }
+        let (Some((ra, _)), Some((rb, _))) = (current.get(a), current.get(b)) else {
+            continue;
+        };
+        let rarest = changes[a].min(changes[b]);
+        let confidence = count as f64 / rarest as f64;
+        if confidence < opts.min_confidence {
+            continue;
+        }
+        suggestions.push(CoChange {
+            a: ra.clone(),
+            b: rb.clone(),
+            together: count,
+            confidence,
+        });
+    }
+    suggestions.sort_by(|x, y| {
+        y.together
+            .cmp(&x.together)
+            .then(y.confidence.total_cmp(&x.confidence))
+            .then_with(|| (&x.a.file, x.a.line).cmp(&(&y.a.file, y.a.line)))
+    });
+
+    let mut stale_links = Vec::new();
+    let mut seen = BTreeSet::new();
+    for (key, (from, links)) in &current {
+        for link in links {
+            let Some(&target) = by_id.get(link.as_str()) else {
+                continue;
+            };
+            let pair = if key <= target {
+                (key.clone(), target.clone())
+            } else {
+                (target.clone(), key.clone())
+            };
+            if together.contains_key(&pair) || !seen.insert(pair) {
+                continue;
+            }
+            stale_links.push(StaleLink {
+                from: from.clone(),
+                to: current[target].0.clone(),
+            });
+        }
+    }
+    stale_links.sort_by(|x, y| (&x.from.file, x.from.line).cmp(&(&y.from.file, y.from.line)));
+
+    Ok(CoChangeReport {
+        suggestions,
+        stale_links,
+        commits,
+        root: workdir,
+    })
+}
@@ -0,0 +1,106 @@
+//! Владельцы файлов из `CODEOWNERS`.
+//!
+//! Файл ищется там же, где его ищет GitHub: в `.github/`, в корне проекта и
+//! в `docs/`. Каждая строка — шаблон в синтаксисе `.gitignore` и список
+//! владельцев (`@user`, `@org/team` или адрес почты). Для пути действует
+//! последняя подходящая строка; строка без владельцев снимает владельцев,
+//! назначенных выше. Блоки принадлежат владельцам своего файла.
+
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use tracing::warn;
+
+use crate::file_io;
+
+/// Расположения `CODEOWNERS` относительно корня проекта в порядке поиска.
+pub const LOCATIONS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+#[derive(Debug, Clone)]
+struct Rule {
+    matcher: Gitignore,
+    owners: Vec<String>,
+}
+
+/// Правила `CODEOWNERS` проекта.
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwners {
+    root: PathBuf,
+    rules: Vec<Rule>,
+}
+
+impl CodeOwners {
+    /// Загружает первый найденный `CODEOWNERS` проекта `root`. Без файла
+    /// владельцев нет ни у одного пути.
+    pub fn load(root: &Path) -> Self {
+        LOCATIONS
+            .iter()
+            .find_map(|loc| file_io::read_to_string(root.join(loc)).ok())
+            .map(|text| Self::parse(root, &text))
+            .unwrap_or_else(|| Self {
+                root: root.to_path_buf(),
+                rules: Vec::new(),
+            })
+    }
+
+    /// Разбирает текст `CODEOWNERS` проекта `root`. Некорректные шаблоны
+    /// пропускаются с предупреждением в журнал; отрицания `!`, которые
+    /// GitHub не поддерживает, тоже пропускаются.
+    pub fn parse(root: &Path, text: &str) -> Self {
+        let mut rules = Vec::new();
+        for line in text.lines() {
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            if pattern.starts_with('#') || pattern.starts_with('!') {
+                continue;
+            }
+            let owners: Vec<String> = parts
+                .take_while(|p| !p.starts_with('#'))
+                .map(str::to_string)
+                .collect();
+            let mut builder = GitignoreBuilder::new(root);
+            let matcher = builder
+                .add_line(None, pattern)
+                .map_err(|e| e.to_string())
+                .and_then(|b| b.build().map_err(|e| e.to_string()));
+            match matcher {
+                Ok(matcher) => rules.push(Rule { matcher, owners }),
+                Err(e) => warn!("CODEOWNERS: {pattern}: {e}"),
+            }
+        }
+        Self {
+            root: root.to_path_buf(),
+            rules,
+        }
+    }
+
+    /// Нет ни одного правила.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Владельцы файла или каталога `path`: абсолютного внутри корня
+    /// проекта или относительного корня.
+    pub fn owners(&self, path: &Path) -> &[String] {
+        let rel = match path.strip_prefix(&self.root) {
+            Ok(rel) => rel,
+            Err(_) if path.is_relative() => path,
+            Err(_) => return &[],
+        };
+        if rel.as_os_str().is_empty() {
+            return &[];
+        }
+        let is_dir = self.root.join(rel).is_dir();
+        self.rules
+            .iter()
+            .rev()
+            .find(|r| {
+                r.matcher
+                    .matched_path_or_any_parents(rel, is_dir)
+                    .is_ignore()
+            })
+            .map_or(&[], |r| r.owners.as_slice())
+    }
+}
@@ -1,5 +1,20 @@
-use ::config::{Config, Environment};
-use serde::Deserialize;
+use ::config::{Config, ConfigError, Environment, File, FileFormat};
+use crate::blocks::enrich::DEFAULT_STAGES;
+use crate::lint::{Severity, META_DUPLICATE_ID, META_INVALID_JSON};
+use crate::meta::issue_link::{IssueLink, GITHUB};
+use crate::meta::team_sync::ConflictPolicy;
+use crate::meta::MetaEncoding;
+use crate::project_env;
+use crate::shell::Shell;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+
+/// Имя файла конфигурации проекта, который ищется в корне рабочей папки.
+pub const PROJECT_CONFIG_FILE: &str = "multicode.toml";
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ServerConfig {
@@ -43,3 +58,783 @@ impl ServerConfig {
             .unwrap_or_default()
     }
 }
+
+/// Команды, выполняемые при событиях редактора (секция `[hooks]`).
+///
+/// ```toml
+/// [hooks]
+/// on_save = "cargo fmt --check"
+/// on_lint_error = "notify-send lint"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct HooksConfig {
+    /// Команда, запускаемая после сохранения файла.
+    #[serde(default)]
+    pub on_save: Option<String>,
+    /// Команда, запускаемая после разбора файла на блоки.
+    #[serde(default)]
+    pub on_parse: Option<String>,
+    /// Команда, запускаемая при обнаружении ошибок линтера.
+    #[serde(default)]
+    pub on_lint_error: Option<String>,
+}
+
+/// Событие, передаваемое хуку в виде JSON через stdin.
+///
+/// `command` посылают пользовательские команды палитры редактора; такие
+/// хуки запускаются командой напрямую, а не через секцию `[hooks]`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HookEvent {
+    Save { path: String },
+    Parse { path: String, blocks: usize },
+    LintError { path: String, errors: Vec<String> },
+    Command { id: String, path: Option<String> },
+}
+
+/// Результат выполнения команды хука.
+#[derive(Debug, Clone)]
+pub struct HookOutput {
+    /// Код завершения процесса, если он доступен.
+    pub status: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl HookOutput {
+    pub fn success(&self) -> bool {
+        self.status == Some(0)
+    }
+}
+
+/// Пороги, выше которых разбор файла упрощается (секция `[limits]`).
+///
+/// Для слишком больших файлов строятся только блоки верхнего уровня без
+/// якорей и метаданных, чтобы интерфейс не зависал.
+///
+/// ```toml
+/// [limits]
+/// max_file_size = 2097152
+/// max_nodes = 50000
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ParseLimits {
+    /// Максимальный размер файла в байтах для полного разбора.
+    pub max_file_size: usize,
+    /// Максимальное количество узлов синтаксического дерева для полного разбора.
+    pub max_nodes: usize,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self {
+            max_file_size: 2 * 1024 * 1024,
+            max_nodes: 50_000,
+        }
+    }
+}
+
+impl ParseLimits {
+    /// Загружает секцию `[limits]` из `multicode.toml` в каталоге `root`.
+    ///
+    /// Отсутствующий или некорректный файл даёт значения по умолчанию.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).limits
+    }
+}
+
+/// Этапы обогащения блоков (секция `[enrich]`).
+///
+/// Этапы выполняются в указанном порядке; этап, не попавший в список,
+/// отключён. Встроенные этапы перечислены в [`enrich`](crate::blocks::enrich),
+/// плагины добавляют свои через
+/// [`register_stage`](crate::blocks::enrich::register_stage).
+///
+/// ```toml
+/// [enrich]
+/// stages = ["translations", "meta", "tags", "names", "docs"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct EnrichConfig {
+    pub stages: Vec<String>,
+}
+
+impl Default for EnrichConfig {
+    fn default() -> Self {
+        Self {
+            stages: DEFAULT_STAGES.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl EnrichConfig {
+    /// Загружает секцию `[enrich]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).enrich
+    }
+}
+
+/// Настройки хранения метаданных (секция `[meta]`).
+///
+/// ```toml
+/// [meta]
+/// encoding = "zstd"
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct MetaConfig {
+    /// Кодировка новых комментариев `@VISUAL_META`.
+    pub encoding: MetaEncoding,
+}
+
+impl MetaConfig {
+    /// Загружает секцию `[meta]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).meta
+    }
+}
+
+/// Политика хранения резервных копий (секция `[backups]`).
+///
+/// ```toml
+/// [backups]
+/// keep = 20
+/// max_age_days = 30
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct BackupConfig {
+    /// Сколько последних копий хранить.
+    pub keep: usize,
+    /// Удалять копии старше указанного числа дней (самая новая сохраняется всегда).
+    pub max_age_days: Option<u32>,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            keep: 20,
+            max_age_days: None,
+        }
+    }
+}
+
+impl BackupConfig {
+    /// Загружает секцию `[backups]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).backups
+    }
+}
+
+/// Отчёты о покрытии кода тестами (секция `[coverage]`).
+///
+/// Пути указываются относительно корня проекта; используются все
+/// существующие файлы из списка. Формат (lcov или Cobertura) определяется
+/// по содержимому.
+///
+/// ```toml
+/// [coverage]
+/// reports = ["target/coverage/lcov.info"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct CoverageConfig {
+    pub reports: Vec<String>,
+}
+
+impl Default for CoverageConfig {
+    fn default() -> Self {
+        Self {
+            reports: [
+                "lcov.info",
+                "coverage/lcov.info",
+                "coverage.xml",
+                "coverage/cobertura.xml",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+        }
+    }
+}
+
+impl CoverageConfig {
+    /// Загружает секцию `[coverage]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).coverage
+    }
+}
+
+/// Шаблоны адресов для ссылок на задачи (секция `[issue_links]`).
+///
+/// Ключ — имя трекера из ссылки, значение — адрес с подстановками
+/// `{key}`, `{project}`, `{owner}`, `{repo}` и `{number}`
+/// (см. [`IssueLink::fields`]). Для `gh` по умолчанию используется GitHub.
+///
+/// ```toml
+/// [issue_links]
+/// jira = "https://example.atlassian.net/browse/{key}"
+/// gh = "https://github.com/{owner}/{repo}/issues/{number}"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct IssueLinksConfig {
+    pub templates: BTreeMap<String, String>,
+}
+
+impl Default for IssueLinksConfig {
+    fn default() -> Self {
+        Self {
+            templates: BTreeMap::from([(
+                GITHUB.to_string(),
+                "https://github.com/{owner}/{repo}/issues/{number}".to_string(),
+            )]),
+        }
+    }
+}
+
+impl IssueLinksConfig {
+    /// Загружает секцию `[issue_links]` из `multicode.toml` в каталоге `root`.
+    ///
+    /// Шаблоны проекта дополняют и переопределяют шаблоны по умолчанию.
+    pub fn load(root: &Path) -> Self {
+        let mut config = Self::default();
+        config.templates.extend(load_project(root).issue_links);
+        config
+    }
+
+    /// Адрес для ссылки `link`, если это корректная ссылка на задачу
+    /// трекера с настроенным шаблоном.
+    pub fn resolve(&self, link: &str) -> Option<String> {
+        let link = IssueLink::parse(link)?.ok()?;
+        let template = self.templates.get(&link.tracker)?;
+        Some(link.expand(template))
+    }
+}
+
+/// Команды форматтеров по языкам (секция `[formatters]`).
+///
+/// Ключ — имя языка (`rust`, `python`, `javascript`, `css`, `html`),
+/// значение — команда оболочки, читающая код из stdin и пишущая результат
+/// в stdout. Подстановка `{file}` заменяется путём к файлу.
+///
+/// ```toml
+/// [formatters]
+/// rust = "rustfmt --edition 2021"
+/// python = "black -q -"
+/// javascript = "prettier --stdin-filepath {file}"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct FormattersConfig {
+    pub commands: BTreeMap<String, String>,
+}
+
+impl Default for FormattersConfig {
+    fn default() -> Self {
+        let prettier = "prettier --stdin-filepath {file}";
+        Self {
+            commands: BTreeMap::from([
+                ("rust".to_string(), "rustfmt --edition 2021".to_string()),
+                ("python".to_string(), "black -q -".to_string()),
+                ("javascript".to_string(), prettier.to_string()),
+                ("css".to_string(), prettier.to_string()),
+                ("html".to_string(), prettier.to_string()),
+            ]),
+        }
+    }
+}
+
+impl FormattersConfig {
+    /// Загружает секцию `[formatters]` из `multicode.toml` в каталоге `root`.
+    ///
+    /// Команды проекта дополняют и переопределяют команды по умолчанию;
+    /// пустая строка отключает форматтер для языка.
+    pub fn load(root: &Path) -> Self {
+        let mut config = Self::default();
+        config.commands.extend(load_project(root).formatters);
+        config
+    }
+
+    /// Команда форматтера для языка `lang`.
+    pub fn command_for(&self, lang: &str) -> Option<&str> {
+        self.commands
+            .get(lang)
+            .map(String::as_str)
+            .filter(|c| !c.trim().is_empty())
+    }
+}
+
+/// Оболочка встроенного терминала (секция `[terminal]`).
+///
+/// Без `shell` запускается оболочка пользователя: `$SHELL` в Unix,
+/// `%COMSPEC%` в Windows.
+///
+/// ```toml
+/// [terminal]
+/// shell = "bash"
+/// args = ["--login"]
+/// env = { RUST_BACKTRACE = "1" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TerminalConfig {
+    pub shell: Option<String>,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+}
+
+impl TerminalConfig {
+    /// Загружает секцию `[terminal]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).terminal
+    }
+}
+
+/// Проверки перед коммитом (секция `[precommit]`).
+///
+/// Хук `pre-commit`, установленный `multicode install-hooks`, проверяет
+/// файлы из индекса и отменяет коммит, только если нашлись замечания
+/// правил из `blocking`; остальные замечания лишь печатаются.
+///
+/// ```toml
+/// [precommit]
+/// blocking = ["meta/duplicate-id", "meta/invalid-json", "meta/invalid-field"]
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PrecommitConfig {
+    /// Правила, замечания которых отменяют коммит.
+    pub blocking: Vec<String>,
+}
+
+impl Default for PrecommitConfig {
+    fn default() -> Self {
+        Self {
+            blocking: vec![META_DUPLICATE_ID.to_string(), META_INVALID_JSON.to_string()],
+        }
+    }
+}
+
+impl PrecommitConfig {
+    /// Загружает секцию `[precommit]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).precommit
+    }
+
+    /// Отменяют ли коммит замечания правила `rule`.
+    pub fn is_blocking(&self, rule: &str) -> bool {
+        self.blocking.iter().any(|r| r == rule)
+    }
+}
+
+/// Правило обязательных метаданных (секции `[[policy]]`), см.
+/// [`crate::policy`].
+///
+/// Правило относится к блокам подходящих видов в файлах, совпавших с
+/// `paths`, и требует от их записей `@VISUAL_META` перечисленного, а от
+/// самих блоков — метрик в пределах `metrics`.
+///
+/// ```toml
+/// [[policy]]
+/// name = "api"
+/// paths = ["src/api/**"]
+/// kinds = ["Function"]
+/// public = true
+/// translations = ["en"]
+/// min_tags = 1
+/// metrics = ["complexity > 15", "params > 5"]
+/// severity = "error"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct PolicyRule {
+    pub name: String,
+    /// Шаблоны путей в синтаксисе `.gitignore`; пустой список — все файлы.
+    pub paths: Vec<String>,
+    /// Начала видов блоков, как `kind:` в поиске.
+    pub kinds: Vec<String>,
+    /// Проверять только публичные блоки.
+    pub public: bool,
+    /// Языки, на которые блок должен быть переведён.
+    pub translations: Vec<String>,
+    /// Наименьшее число тегов.
+    pub min_tags: usize,
+    /// Нужно ли описание `ai.description`.
+    pub description: bool,
+    /// Ограничения метрик блока вроде `complexity > 15`, см.
+    /// [`crate::analysis::metrics::MetricLimit`].
+    pub metrics: Vec<String>,
+    pub severity: Severity,
+}
+
+impl Default for PolicyRule {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            paths: Vec::new(),
+            kinds: vec!["Function".to_string()],
+            public: false,
+            translations: Vec::new(),
+            min_tags: 0,
+            description: false,
+            metrics: Vec::new(),
+            severity: Severity::Warning,
+        }
+    }
+}
+
+impl PolicyRule {
+    /// Загружает секции `[[policy]]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Vec<Self> {
+        load_project(root).policy
+    }
+}
+
+/// Переводы метаданных (секция `[translate]`), см.
+/// [`crate::meta::translation`].
+///
+/// `languages` — языки отчёта о полноте переводов и языки, которые
+/// заполняет машинный перевод. `endpoint` — адрес сервиса перевода; без
+/// него машинный перевод недоступен. Ключ доступа берётся из переменной
+/// окружения `token_env` и передаётся в заголовке `Authorization: Bearer`.
+///
+/// ```toml
+/// [translate]
+/// languages = ["en", "ru"]
+/// source = "en"
+/// endpoint = "http://localhost:5000/translate"
+/// token_env = "MT_TOKEN"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TranslateConfig {
+    pub languages: Vec<String>,
+    /// Язык, с которого переводить; по умолчанию — первый заполненный.
+    pub source: Option<String>,
+    pub endpoint: Option<String>,
+    pub token_env: Option<String>,
+}
+
+impl TranslateConfig {
+    /// Загружает секцию `[translate]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).translate
+    }
+}
+
+/// Обмен метаданными с сервером команды (секция `[team_sync]`), см.
+/// [`crate::meta::team_sync`].
+///
+/// `server` — адрес сервера, `project` — имя проекта на нём (по умолчанию
+/// имя каталога проекта). Ключ доступа берётся из переменной окружения
+/// `token_env`. `conflict` — чьё значение поля брать, если его изменили с
+/// обеих сторон.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TeamSyncConfig {
+    pub server: Option<String>,
+    pub project: Option<String>,
+    pub token_env: Option<String>,
+    pub conflict: ConflictPolicy,
+}
+
+impl TeamSyncConfig {
+    /// Загружает секцию `[team_sync]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).team_sync
+    }
+}
+
+/// Клиент модели ИИ (секция `[ai]`), см. [`crate::ai`].
+///
+/// `endpoint` — адрес OpenAI-совместимого метода `chat/completions`; без
+/// него обращения к модели недоступны. Ключ доступа берётся из переменной
+/// окружения `token_env`. `max_concurrent` ограничивает число одновременных
+/// запросов, `requests_per_minute` — их частоту. Совпадения регулярных
+/// выражений `redact` заменяются перед отправкой кода; если в выражении
+/// есть группа, заменяется только она. `cache_size` — сколько ответов
+/// помнить; `0` отключает кэш.
+///
+/// ```toml
+/// [ai]
+/// endpoint = "https://api.openai.com/v1/chat/completions"
+/// model = "gpt-4o-mini"
+/// token_env = "OPENAI_API_KEY"
+/// max_concurrent = 2
+/// requests_per_minute = 30
+/// redact = ['(?i)password\s*=\s*"([^"]*)"', 'sk-[A-Za-z0-9]{20,}']
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct AiConfig {
+    pub endpoint: Option<String>,
+    pub model: Option<String>,
+    pub token_env: Option<String>,
+    pub max_concurrent: usize,
+    pub requests_per_minute: Option<u32>,
+    pub redact: Vec<String>,
+    pub cache_size: usize,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            model: None,
+            token_env: None,
+            max_concurrent: 2,
+            requests_per_minute: None,
+            redact: Vec::new(),
+            cache_size: 256,
+        }
+    }
+}
+
+impl AiConfig {
+    /// Загружает секцию `[ai]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).ai
+    }
+}
+
+/// Семантический поиск по блокам (секция `[embeddings]`), см.
+/// [`crate::search::semantic`].
+///
+/// `endpoint` — адрес OpenAI-совместимого метода `embeddings`; без него
+/// поиск недоступен. Ключ доступа берётся из переменной окружения
+/// `token_env`. Блоки отправляются пачками по `batch_size`. Векторы
+/// хранятся в базе метаданных `db` относительно корня проекта.
+///
+/// ```toml
+/// [embeddings]
+/// endpoint = "https://api.openai.com/v1/embeddings"
+/// model = "text-embedding-3-small"
+/// token_env = "OPENAI_API_KEY"
+/// batch_size = 32
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct EmbeddingsConfig {
+    pub endpoint: Option<String>,
+    pub model: Option<String>,
+    pub token_env: Option<String>,
+    pub batch_size: usize,
+    pub db: String,
+}
+
+impl Default for EmbeddingsConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: None,
+            model: None,
+            token_env: None,
+            batch_size: 32,
+            db: ".multicode/meta.db".into(),
+        }
+    }
+}
+
+impl EmbeddingsConfig {
+    /// Загружает секцию `[embeddings]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).embeddings
+    }
+}
+
+/// Заголовок лицензии исходных файлов (секция `[license]`), см.
+/// [`crate::license`].
+///
+/// `header` — текст заголовка без знаков комментария, `{year}` и `{owner}`
+/// в нём заменяются годом `year` (по умолчанию текущим) и владельцем
+/// `owner`. Без `header` заголовки не проверяются. `on_save` — вставлять
+/// или обновлять заголовок при сохранении файла в редакторе.
+///
+/// ```toml
+/// [license]
+/// header = "Copyright (c) {year} {owner}\nSPDX-License-Identifier: MIT"
+/// owner = "Multicode contributors"
+/// on_save = true
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct LicenseConfig {
+    pub header: Option<String>,
+    pub owner: String,
+    pub year: Option<u32>,
+    pub on_save: bool,
+}
+
+impl LicenseConfig {
+    /// Загружает секцию `[license]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).license
+    }
+}
+
+/// Упорядочивание импортов (секция `[imports]`), см.
+/// [`crate::format::imports`].
+///
+/// `remove_unused` — удалять неиспользуемые импорты (по умолчанию да).
+/// `on_format` — упорядочивать импорты перед запуском форматтера, чтобы
+/// форматтер получил окончательный порядок и обе правки отменялись вместе.
+///
+/// ```toml
+/// [imports]
+/// remove_unused = true
+/// on_format = true
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct ImportsConfig {
+    pub remove_unused: bool,
+    pub on_format: bool,
+}
+
+impl Default for ImportsConfig {
+    fn default() -> Self {
+        Self {
+            remove_unused: true,
+            on_format: false,
+        }
+    }
+}
+
+impl ImportsConfig {
+    /// Загружает секцию `[imports]` из `multicode.toml` в каталоге `root`.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).imports
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProjectConfig {
+    hooks: HooksConfig,
+    limits: ParseLimits,
+    enrich: EnrichConfig,
+    meta: MetaConfig,
+    backups: BackupConfig,
+    coverage: CoverageConfig,
+    issue_links: BTreeMap<String, String>,
+    formatters: BTreeMap<String, String>,
+    terminal: TerminalConfig,
+    precommit: PrecommitConfig,
+    policy: Vec<PolicyRule>,
+    translate: TranslateConfig,
+    team_sync: TeamSyncConfig,
+    ai: AiConfig,
+    embeddings: EmbeddingsConfig,
+    license: LicenseConfig,
+    imports: ImportsConfig,
+}
+
+/// Читает `multicode.toml` проекта `root`. Секции разбираются по
+/// отдельности: ошибка в одной секции пишется в журнал и заменяет только её
+/// значениями по умолчанию.
+fn load_project(root: &Path) -> ProjectConfig {
+    let path = root.join(PROJECT_CONFIG_FILE);
+    let config = match Config::builder()
+        .add_source(
+            File::from(path.as_path())
+                .format(FileFormat::Toml)
+                .required(false),
+        )
+        .build()
+    {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::warn!(path = %path.display(), "не удалось прочитать настройки проекта: {e}");
+            return ProjectConfig::default();
+        }
+    };
+    ProjectConfig {
+        hooks: section(&config, &path, "hooks"),
+        limits: section(&config, &path, "limits"),
+        enrich: section(&config, &path, "enrich"),
+        meta: section(&config, &path, "meta"),
+        backups: section(&config, &path, "backups"),
+        coverage: section(&config, &path, "coverage"),
+        issue_links: section(&config, &path, "issue_links"),
+        formatters: section(&config, &path, "formatters"),
+        terminal: section(&config, &path, "terminal"),
+        precommit: section(&config, &path, "precommit"),
+        policy: section(&config, &path, "policy"),
+        translate: section(&config, &path, "translate"),
+        team_sync: section(&config, &path, "team_sync"),
+        ai: section(&config, &path, "ai"),
+        embeddings: section(&config, &path, "embeddings"),
+        license: section(&config, &path, "license"),
+        imports: section(&config, &path, "imports"),
+    }
+}
+
+/// Секция `key` настроек; при отсутствии или ошибке разбора — значение по
+/// умолчанию.
+fn section<T: DeserializeOwned + Default>(config: &Config, path: &Path, key: &str) -> T {
+    match config.get(key) {
+        Ok(value) => value,
+        Err(ConfigError::NotFound(_)) => T::default(),
+        Err(e) => {
+            tracing::warn!(path = %path.display(), section = key, "ошибка в секции настроек: {e}");
+            T::default()
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Загружает секцию `[hooks]` из `multicode.toml` в каталоге `root`.
+    ///
+    /// Отсутствующий или некорректный файл даёт пустую конфигурацию.
+    pub fn load(root: &Path) -> Self {
+        load_project(root).hooks
+    }
+
+    /// Возвращает команду, настроенную для указанного события.
+    pub fn command_for(&self, event: &HookEvent) -> Option<&str> {
+        let cmd = match event {
+            HookEvent::Save { .. } => self.on_save.as_deref(),
+            HookEvent::Parse { .. } => self.on_parse.as_deref(),
+            HookEvent::LintError { .. } => self.on_lint_error.as_deref(),
+            HookEvent::Command { .. } => None,
+        };
+        cmd.filter(|c| !c.trim().is_empty())
+    }
+
+    /// Выполняет хук для события в каталоге `dir`.
+    ///
+    /// Возвращает `Ok(None)`, если для события не настроена команда.
+    pub fn run(&self, event: &HookEvent, dir: &Path) -> Result<Option<HookOutput>, String> {
+        match self.command_for(event) {
+            Some(cmd) => run_hook(cmd, event, dir).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Запускает `cmd` через системную оболочку с переменными окружения
+/// проекта `dir` и передаёт событие в stdin.
+pub fn run_hook(cmd: &str, event: &HookEvent, dir: &Path) -> Result<HookOutput, String> {
+    let payload = serde_json::to_vec(event).map_err(|e| e.to_string())?;
+    let mut child = Shell::system()
+        .command(cmd)
+        .current_dir(dir)
+        .envs(project_env::vars(dir))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("не удалось запустить хук '{cmd}': {e}"))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Команда может не читать stdin и завершиться раньше; это не ошибка.
+        let _ = stdin.write_all(&payload);
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    tracing::debug!(command = cmd, status = ?output.status.code(), "хук выполнен");
+    Ok(HookOutput {
+        status: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+    })
+}
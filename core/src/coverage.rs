@@ -0,0 +1,249 @@
+//! Покрытие блоков тестами по отчётам lcov и Cobertura.
+//!
+//! [`CoverageReport`] хранит число выполнений каждой строки по файлам,
+//! [`block_coverage`] переводит строки в доли покрытых строк блоков, а
+//! [`uncovered_tests`] находит блоки, для которых в метаданных указаны
+//! тесты (`tests`), хотя ни одна их строка не выполнялась.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::CoverageConfig;
+use crate::file_io;
+use crate::meta;
+use crate::text::PositionIndex;
+use crate::BlockInfo;
+
+/// Число выполнений строк (с единицы) по файлам отчёта.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    files: HashMap<PathBuf, BTreeMap<usize, u64>>,
+}
+
+impl CoverageReport {
+    /// Разбирает отчёт, определяя формат по содержимому: XML считается
+    /// отчётом Cobertura, остальное — трассировкой lcov.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if text.trim_start().starts_with('<') {
+            Self::parse_cobertura(text)
+        } else {
+            Ok(Self::parse_lcov(text))
+        }
+    }
+
+    /// Разбирает трассировку lcov (`SF:`, `DA:`, `end_of_record`).
+    /// Некорректные строки пропускаются.
+    pub fn parse_lcov(text: &str) -> Self {
+        let mut report = Self::default();
+        let mut current: Option<PathBuf> = None;
+        for line in text.lines() {
+            let line = line.trim();
+            if let Some(path) = line.strip_prefix("SF:") {
+                current = Some(PathBuf::from(path));
+            } else if let Some(data) = line.strip_prefix("DA:") {
+                let Some(file) = &current else {
+                    continue;
+                };
+                let mut parts = data.split(',');
+                let (Some(Ok(number)), Some(Ok(hits))) = (
+                    parts.next().map(str::parse::<usize>),
+                    parts.next().map(str::parse::<u64>),
+                ) else {
+                    continue;
+                };
+                report.add(file, number, hits);
+            } else if line == "end_of_record" {
+                current = None;
+            }
+        }
+        report
+    }
+
+    /// Разбирает XML-отчёт Cobertura. Строки методов не учитываются
+    /// отдельно: они повторяют строки класса.
+    pub fn parse_cobertura(xml: &str) -> Result<Self, String> {
+        let doc = roxmltree::Document::parse(xml).map_err(|e| e.to_string())?;
+        let mut report = Self::default();
+        for class in doc.descendants().filter(|n| n.has_tag_name("class")) {
+            let Some(filename) = class.attribute("filename") else {
+                continue;
+            };
+            let file = PathBuf::from(filename);
+            let lines = class
+                .children()
+                .filter(|n| n.has_tag_name("lines"))
+                .flat_map(|n| n.children())
+                .filter(|n| n.has_tag_name("line"));
+            for line in lines {
+                let number = line.attribute("number").and_then(|v| v.parse().ok());
+                let hits = line.attribute("hits").and_then(|v| v.parse().ok());
+                if let (Some(number), Some(hits)) = (number, hits) {
+                    report.add(&file, number, hits);
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Читает отчёт из файла `path`.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = file_io::read_to_string(path)?;
+        Self::parse(&text).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Добавляет выполнения строк из `other`.
+    pub fn merge(&mut self, other: CoverageReport) {
+        for (file, lines) in other.files {
+            for (number, hits) in lines {
+                self.add(&file, number, hits);
+            }
+        }
+    }
+
+    fn add(&mut self, file: &Path, number: usize, hits: u64) {
+        let lines = self.files.entry(file.to_path_buf()).or_default();
+        *lines.entry(number).or_default() += hits;
+    }
+
+    /// Файлы отчёта в том виде, в каком они указаны в нём.
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+
+    /// Строки файла `file` с числом выполнений.
+    ///
+    /// Пути в отчётах бывают абсолютными или относительными к разным
+    /// каталогам, поэтому, если точного совпадения нет, берётся файл
+    /// отчёта с самым длинным общим окончанием пути.
+    pub fn lines(&self, file: &Path) -> Option<&BTreeMap<usize, u64>> {
+        if let Some(lines) = self.files.get(file) {
+            return Some(lines);
+        }
+        self.files
+            .iter()
+            .filter(|(path, _)| file.ends_with(path) || path.ends_with(file))
+            .max_by_key(|(path, _)| path.components().count())
+            .map(|(_, lines)| lines)
+    }
+}
+
+/// Покрытие одного блока.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockCoverage {
+    /// Строки блока, выполненные хотя бы раз.
+    pub covered: usize,
+    /// Строки блока, попавшие в отчёт.
+    pub total: usize,
+}
+
+impl BlockCoverage {
+    /// Доля покрытых строк, от 0 до 1.
+    pub fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.covered as f64 / self.total as f64
+        }
+    }
+
+    /// Процент покрытых строк, округлённый вниз.
+    pub fn percent(&self) -> u32 {
+        (self.covered * 100).checked_div(self.total).unwrap_or(0) as u32
+    }
+}
+
+/// Покрытие блоков `blocks` файла `file` с содержимым `content`, по
+/// индексу блока. `None` — в отчёте нет ни одной строки блока.
+pub fn block_coverage(
+    report: &CoverageReport,
+    file: &Path,
+    content: &str,
+    blocks: &[BlockInfo],
+) -> Vec<Option<BlockCoverage>> {
+    let Some(lines) = report.lines(file) else {
+        return vec![None; blocks.len()];
+    };
+    let index = PositionIndex::new(content);
+    blocks
+        .iter()
+        .map(|b| {
+            let (start, end) = b.range;
+            let first = index.line_of(start) + 1;
+            let last = index.line_of(end.saturating_sub(1).max(start)) + 1;
+            let mut coverage = BlockCoverage {
+                covered: 0,
+                total: 0,
+            };
+            for (_, hits) in lines.range(first..=last) {
+                coverage.total += 1;
+                if *hits > 0 {
+                    coverage.covered += 1;
+                }
+            }
+            (coverage.total > 0).then_some(coverage)
+        })
+        .collect()
+}
+
+/// Число выполнений блоков `blocks` файла `file` с содержимым `content`:
+/// наибольшее число выполнений строки блока. `None` — в отчёте нет ни
+/// одной строки блока.
+pub fn block_hits(
+    report: &CoverageReport,
+    file: &Path,
+    content: &str,
+    blocks: &[BlockInfo],
+) -> Vec<Option<u64>> {
+    let Some(lines) = report.lines(file) else {
+        return vec![None; blocks.len()];
+    };
+    let index = PositionIndex::new(content);
+    blocks
+        .iter()
+        .map(|b| {
+            let (start, end) = b.range;
+            let first = index.line_of(start) + 1;
+            let last = index.line_of(end.saturating_sub(1).max(start)) + 1;
+            lines.range(first..=last).map(|(_, hits)| *hits).max()
+        })
+        .collect()
+}
+
+/// Идентификаторы блоков, у которых в метаданных перечислены тесты, но
+/// по отчёту не выполнилась ни одна строка.
+pub fn uncovered_tests(
+    content: &str,
+    blocks: &[BlockInfo],
+    coverage: &[Option<BlockCoverage>],
+) -> Vec<String> {
+    let metas = meta::read_all(content);
+    blocks
+        .iter()
+        .zip(coverage)
+        .filter(|(_, c)| c.is_some_and(|c| c.covered == 0))
+        .filter(|(b, _)| {
+            metas
+                .iter()
+                .any(|m| m.id == b.visual_id && !m.tests.is_empty())
+        })
+        .map(|(b, _)| b.visual_id.clone())
+        .collect()
+}
+
+/// Загружает и объединяет отчёты проекта `root` из секции `[coverage]`.
+/// Возвращает `None`, если ни одного отчёта нет.
+pub fn load_project(root: &Path) -> io::Result<Option<CoverageReport>> {
+    let mut report: Option<CoverageReport> = None;
+    for path in CoverageConfig::load(root).reports {
+        let path = root.join(path);
+        if !path.is_file() {
+            continue;
+        }
+        let loaded = CoverageReport::load(&path)?;
+        report
+            .get_or_insert_with(CoverageReport::default)
+            .merge(loaded);
+    }
+    Ok(report)
+}
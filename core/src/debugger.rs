@@ -1,7 +1,58 @@
+//! Отладчик.
+//!
+//! Действия отладчика записываются в журнал `debug.log`. Место остановки
+//! [`StoppedLocation`] с переменными кадра переводится в блок файла через
+//! [`stopped_block`], чтобы холст мог подсветить выполняемый блок.
+
 use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::path::PathBuf;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use serde::{Deserialize, Serialize};
+
+use crate::text::PositionIndex;
+use crate::BlockInfo;
+
+/// Переменная кадра, в котором остановился отладчик.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Variable {
+    pub name: String,
+    pub value: String,
+}
+
+/// Место, где остановился отладчик.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoppedLocation {
+    pub file: PathBuf,
+    /// Строка остановки с единицы, как в протоколе отладки.
+    pub line: usize,
+    #[serde(default)]
+    pub variables: Vec<Variable>,
+}
+
+/// Номер блока `blocks` файла `content`, в котором находится строка
+/// `line` (с единицы): самый вложенный блок, целиком содержащий текст
+/// строки без отступов.
+pub fn stopped_block(content: &str, blocks: &[BlockInfo], line: usize) -> Option<usize> {
+    let (start, end) = line_span(content, line)?;
+    blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| b.range.0 <= start && end <= b.range.1)
+        .min_by_key(|(_, b)| b.range.1 - b.range.0)
+        .map(|(i, _)| i)
+}
+
+/// Смещения начала и конца текста строки `line` (с единицы) без отступов.
+pub(crate) fn line_span(content: &str, line: usize) -> Option<(usize, usize)> {
+    let index = PositionIndex::new(content);
+    let line = line.checked_sub(1)?;
+    let text = index.line_text(line)?;
+    let start = index.line_start(line)? + (text.len() - text.trim_start().len());
+    Some((start, start + text.trim().len()))
+}
+
 fn log_action(action: &str) {
     if let Some(mut path) = dirs::data_dir() {
         path.push("multicode");
@@ -23,10 +74,24 @@ pub fn debug_run() {
     log_action("запуск");
 }
 
+/// Запускает отладку с точками останова `breakpoints`: путь файла и
+/// строка с единицы.
+pub fn debug_start(breakpoints: &[(PathBuf, usize)]) {
+    let points: Vec<String> = breakpoints
+        .iter()
+        .map(|(file, line)| format!("{}:{line}", file.display()))
+        .collect();
+    log_action(&format!("запуск, точки останова: {}", points.join(", ")));
+}
+
 pub fn debug_step() {
     log_action("шаг");
 }
 
+pub fn debug_continue() {
+    log_action("продолжение");
+}
+
 pub fn debug_break() {
     log_action("остановка");
 }
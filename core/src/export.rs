@@ -1,15 +1,24 @@
+pub mod binary;
+pub mod report;
+
 use crate::meta;
 use once_cell::sync::Lazy;
 use regex::{Error as RegexError, Regex};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 /// Набор визуальных метаданных, связанных с исходным файлом.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct VizDocument {
     /// Записи визуальных метаданных, извлечённые из файла.
     pub nodes: Vec<meta::VisualMeta>,
 }
 
+/// JSON-схема [`VizDocument`], в котором метаданные экспортируются.
+pub fn schema() -> schemars::schema::RootSchema {
+    schemars::schema_for!(VizDocument)
+}
+
 /// Сериализует все комментарии `@VISUAL_META` из `content` в JSON‑строку `VizDocument`.
 pub fn serialize_viz_document(content: &str) -> Option<String> {
     let metas = meta::read_all(content);
@@ -0,0 +1,390 @@
+//! Двоичный экспорт графа блоков в формате protobuf.
+//!
+//! Для больших репозиториев JSON получается слишком объёмным и медленным,
+//! поэтому граф блоков с метаданными можно выгрузить в protobuf по схеме
+//! [`PROTO`]. Кодирование написано вручную и не требует генерации кода:
+//! сообщения читаются любым декодером protobuf по этой схеме.
+//!
+//! [`GraphWriter`] пишет файлы по одному, не собирая весь граф в памяти;
+//! результат остаётся одним сообщением `BlockGraph`, так как повторяющиеся
+//! поля protobuf можно записывать по частям.
+
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::blocks::{lang_from_extension, parse_blocks, source_files};
+use crate::codeowners::CodeOwners;
+use crate::file_io;
+use crate::BlockInfo;
+
+/// Версия схемы, записываемая в поле `BlockGraph.version`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Схема protobuf экспортируемого графа.
+pub const PROTO: &str = include_str!("block_graph.proto");
+
+const VARINT: u8 = 0;
+const FIXED64: u8 = 1;
+const LEN: u8 = 2;
+const FIXED32: u8 = 5;
+
+/// Граф блоков: сообщение `BlockGraph`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BlockGraph {
+    pub version: u32,
+    pub files: Vec<GraphFile>,
+}
+
+/// Блоки одного файла: сообщение `File`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphFile {
+    pub path: String,
+    pub blocks: Vec<GraphBlock>,
+    /// Владельцы файла из `CODEOWNERS`.
+    pub owners: Vec<String>,
+}
+
+/// Блок с метаданными: сообщение `Block`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphBlock {
+    pub id: String,
+    pub kind: String,
+    pub start: u64,
+    pub end: u64,
+    pub x: f64,
+    pub y: f64,
+    pub tags: Vec<String>,
+    pub links: Vec<String>,
+    pub translations: BTreeMap<String, String>,
+    pub color: Option<String>,
+    pub icon: Option<String>,
+    pub description: Option<String>,
+    pub node_id: Option<u32>,
+}
+
+impl From<&BlockInfo> for GraphBlock {
+    fn from(block: &BlockInfo) -> Self {
+        Self {
+            id: block.visual_id.clone(),
+            kind: block.kind.clone(),
+            start: block.range.0 as u64,
+            end: block.range.1 as u64,
+            x: block.x,
+            y: block.y,
+            tags: block.tags.clone(),
+            links: block.links.clone(),
+            translations: block
+                .translations
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+            color: block.color.clone(),
+            icon: block.icon.clone(),
+            description: block.ai.as_ref().and_then(|ai| ai.description.clone()),
+            node_id: block.node_id,
+        }
+    }
+}
+
+fn put_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn put_key(buf: &mut Vec<u8>, field: u32, wire: u8) {
+    put_varint(buf, (u64::from(field) << 3) | u64::from(wire));
+}
+
+fn put_bytes(buf: &mut Vec<u8>, field: u32, bytes: &[u8]) {
+    put_key(buf, field, LEN);
+    put_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+/// Поля proto3 со значением по умолчанию не записываются.
+fn put_str(buf: &mut Vec<u8>, field: u32, value: &str) {
+    if !value.is_empty() {
+        put_bytes(buf, field, value.as_bytes());
+    }
+}
+
+fn put_u64(buf: &mut Vec<u8>, field: u32, value: u64) {
+    if value != 0 {
+        put_key(buf, field, VARINT);
+        put_varint(buf, value);
+    }
+}
+
+fn put_f64(buf: &mut Vec<u8>, field: u32, value: f64) {
+    if value != 0.0 {
+        put_key(buf, field, FIXED64);
+        buf.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+impl GraphBlock {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        put_str(buf, 1, &self.id);
+        put_str(buf, 2, &self.kind);
+        put_u64(buf, 3, self.start);
+        put_u64(buf, 4, self.end);
+        put_f64(buf, 5, self.x);
+        put_f64(buf, 6, self.y);
+        for tag in &self.tags {
+            put_bytes(buf, 7, tag.as_bytes());
+        }
+        for link in &self.links {
+            put_bytes(buf, 8, link.as_bytes());
+        }
+        for (lang, label) in &self.translations {
+            let mut entry = Vec::new();
+            put_str(&mut entry, 1, lang);
+            put_str(&mut entry, 2, label);
+            put_bytes(buf, 9, &entry);
+        }
+        for (field, value) in [(10, &self.color), (11, &self.icon), (12, &self.description)] {
+            if let Some(value) = value {
+                put_bytes(buf, field, value.as_bytes());
+            }
+        }
+        if let Some(node_id) = self.node_id {
+            put_key(buf, 13, VARINT);
+            put_varint(buf, u64::from(node_id));
+        }
+    }
+}
+
+impl GraphFile {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        put_str(buf, 1, &self.path);
+        let mut block = Vec::new();
+        for b in &self.blocks {
+            block.clear();
+            b.encode(&mut block);
+            put_bytes(buf, 2, &block);
+        }
+        for owner in &self.owners {
+            put_bytes(buf, 3, owner.as_bytes());
+        }
+    }
+}
+
+/// Кодирует граф целиком.
+pub fn encode(graph: &BlockGraph) -> Vec<u8> {
+    let mut buf = Vec::new();
+    put_u64(&mut buf, 1, u64::from(graph.version));
+    let mut file = Vec::new();
+    for f in &graph.files {
+        file.clear();
+        f.encode(&mut file);
+        put_bytes(&mut buf, 2, &file);
+    }
+    buf
+}
+
+/// Потоковая запись сообщения `BlockGraph` по одному файлу.
+pub struct GraphWriter<W: Write> {
+    out: W,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> GraphWriter<W> {
+    /// Начинает граф и записывает версию схемы.
+    pub fn new(mut out: W) -> io::Result<Self> {
+        let mut buf = Vec::new();
+        put_u64(&mut buf, 1, u64::from(SCHEMA_VERSION));
+        out.write_all(&buf)?;
+        Ok(Self { out, buf })
+    }
+
+    /// Дописывает блоки одного файла.
+    pub fn write_file(&mut self, file: &GraphFile) -> io::Result<()> {
+        let mut body = Vec::new();
+        file.encode(&mut body);
+        self.buf.clear();
+        put_bytes(&mut self.buf, 2, &body);
+        self.out.write_all(&self.buf)
+    }
+
+    /// Завершает запись и возвращает приёмник.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Выгружает блоки всех исходных файлов проекта `root` в `out`.
+/// Возвращает число записанных блоков.
+pub fn export_dir<W: Write>(root: &Path, out: W) -> io::Result<usize> {
+    let mut writer = GraphWriter::new(out)?;
+    let owners = CodeOwners::load(root);
+    let mut count = 0;
+    for path in source_files(root) {
+        let Some(lang) = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension)
+        else {
+            continue;
+        };
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let blocks = parse_blocks(content, lang.into()).unwrap_or_default();
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        let file = GraphFile {
+            path: rel
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+            blocks: blocks.iter().map(GraphBlock::from).collect(),
+            owners: owners.owners(rel).to_vec(),
+        };
+        count += file.blocks.len();
+        writer.write_file(&file)?;
+    }
+    writer.finish()?;
+    Ok(count)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+/// Чтение полей сообщения protobuf.
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn varint(&mut self) -> io::Result<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let (&byte, rest) = self
+                .buf
+                .split_first()
+                .ok_or_else(|| invalid("обрезанное число"))?;
+            self.buf = rest;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(invalid("слишком длинное число"))
+    }
+
+    fn take(&mut self, len: usize) -> io::Result<&'a [u8]> {
+        if len > self.buf.len() {
+            return Err(invalid("обрезанное поле"));
+        }
+        let (head, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    /// Следующее поле: номер и тип.
+    fn key(&mut self) -> io::Result<Option<(u32, u8)>> {
+        if self.buf.is_empty() {
+            return Ok(None);
+        }
+        let key = self.varint()?;
+        Ok(Some(((key >> 3) as u32, (key & 7) as u8)))
+    }
+
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let len = self.varint()? as usize;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> io::Result<String> {
+        String::from_utf8(self.bytes()?.to_vec()).map_err(|_| invalid("строка не в UTF-8"))
+    }
+
+    fn f64(&mut self) -> io::Result<f64> {
+        let bytes = self.take(8)?;
+        Ok(f64::from_le_bytes(bytes.try_into().expect("8 байт")))
+    }
+
+    /// Пропускает поле неизвестного номера, чтобы читать графы более
+    /// новых версий схемы.
+    fn skip(&mut self, wire: u8) -> io::Result<()> {
+        match wire {
+            VARINT => self.varint().map(drop),
+            FIXED64 => self.take(8).map(drop),
+            LEN => self.bytes().map(drop),
+            FIXED32 => self.take(4).map(drop),
+            _ => Err(invalid("неизвестный тип поля")),
+        }
+    }
+}
+
+fn decode_block(buf: &[u8]) -> io::Result<GraphBlock> {
+    let mut r = Reader { buf };
+    let mut block = GraphBlock::default();
+    while let Some((field, wire)) = r.key()? {
+        match (field, wire) {
+            (1, LEN) => block.id = r.string()?,
+            (2, LEN) => block.kind = r.string()?,
+            (3, VARINT) => block.start = r.varint()?,
+            (4, VARINT) => block.end = r.varint()?,
+            (5, FIXED64) => block.x = r.f64()?,
+            (6, FIXED64) => block.y = r.f64()?,
+            (7, LEN) => block.tags.push(r.string()?),
+            (8, LEN) => block.links.push(r.string()?),
+            (9, LEN) => {
+                let mut entry = Reader { buf: r.bytes()? };
+                let (mut key, mut value) = (String::new(), String::new());
+                while let Some((field, wire)) = entry.key()? {
+                    match (field, wire) {
+                        (1, LEN) => key = entry.string()?,
+                        (2, LEN) => value = entry.string()?,
+                        _ => entry.skip(wire)?,
+                    }
+                }
+                block.translations.insert(key, value);
+            }
+            (10, LEN) => block.color = Some(r.string()?),
+            (11, LEN) => block.icon = Some(r.string()?),
+            (12, LEN) => block.description = Some(r.string()?),
+            (13, VARINT) => block.node_id = Some(r.varint()? as u32),
+            _ => r.skip(wire)?,
+        }
+    }
+    Ok(block)
+}
+
+fn decode_file(buf: &[u8]) -> io::Result<GraphFile> {
+    let mut r = Reader { buf };
+    let mut file = GraphFile::default();
+    while let Some((field, wire)) = r.key()? {
+        match (field, wire) {
+            (1, LEN) => file.path = r.string()?,
+            (2, LEN) => file.blocks.push(decode_block(r.bytes()?)?),
+            (3, LEN) => file.owners.push(r.string()?),
+            _ => r.skip(wire)?,
+        }
+    }
+    Ok(file)
+}
+
+/// Декодирует граф, записанный [`encode`] или [`GraphWriter`].
+pub fn decode(buf: &[u8]) -> io::Result<BlockGraph> {
+    let mut r = Reader { buf };
+    let mut graph = BlockGraph::default();
+    while let Some((field, wire)) = r.key()? {
+        match (field, wire) {
+            (1, VARINT) => graph.version = r.varint()? as u32,
+            (2, LEN) => graph.files.push(decode_file(r.bytes()?)?),
+            _ => r.skip(wire)?,
+        }
+    }
+    if graph.version > SCHEMA_VERSION {
+        return Err(invalid("граф записан более новой версией схемы"));
+    }
+    Ok(graph)
+}
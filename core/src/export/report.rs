@@ -0,0 +1,102 @@
+//! HTML-отчёт о проекте: сводки модулей и файлов ([`crate::meta::summary`])
+//! и описания блоков.
+//!
+//! Сводки берутся из файлов проекта такими, какими их записал последний
+//! [`summary::regenerate`]; сам отчёт их не пересоздаёт.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::blocks::parse_dir;
+use crate::file_io;
+use crate::meta::summary::{self, Scope};
+
+/// Стили отчёта.
+const STYLE: &str = "body{font-family:sans-serif;max-width:60em;margin:2em auto;}\
+    .summary{white-space:pre-wrap;}\
+    code{background:#f3f3f3;padding:0 .2em;}";
+
+/// Экранирует `text` для вставки в HTML.
+pub fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Отчёт о проекте `root` с заголовком `title`: сводки модулей, затем по
+/// каждому файлу со сводкой или описанными блоками — сводка и описания.
+pub fn html(root: &Path, title: &str) -> String {
+    let summaries = summary::stored(root);
+    let mut out = String::new();
+    let title = escape(title);
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+    );
+    let modules: Vec<_> = summaries
+        .iter()
+        .filter(|s| s.scope == Scope::Module)
+        .collect();
+    if !modules.is_empty() {
+        out.push_str("<h2>Модули</h2>\n");
+        for module in modules {
+            let _ = writeln!(
+                out,
+                "<section>\n<h3>{}</h3>\n<div class=\"summary\">{}</div>\n</section>",
+                escape(&module.path.to_string_lossy()),
+                escape(&module.text)
+            );
+        }
+    }
+    let mut files = String::new();
+    for (path, blocks) in parse_dir(root) {
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let notes = summary::block_notes(&content, &blocks);
+        let file_summary = summaries
+            .iter()
+            .find(|s| s.scope == Scope::File && s.path == rel);
+        if notes.is_empty() && file_summary.is_none() {
+            continue;
+        }
+        let _ = writeln!(
+            files,
+            "<section>\n<h3>{}</h3>",
+            escape(&rel.to_string_lossy())
+        );
+        if let Some(s) = file_summary {
+            let _ = writeln!(files, "<div class=\"summary\">{}</div>", escape(&s.text));
+        }
+        if !notes.is_empty() {
+            files.push_str("<ul>\n");
+            for (label, description) in &notes {
+                let _ = writeln!(
+                    files,
+                    "<li><code>{}</code> — {}</li>",
+                    escape(label),
+                    escape(description)
+                );
+            }
+            files.push_str("</ul>\n");
+        }
+        files.push_str("</section>\n");
+    }
+    if !files.is_empty() {
+        out.push_str("<h2>Файлы</h2>\n");
+        out.push_str(&files);
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
@@ -0,0 +1,186 @@
+//! Чтение и запись исходников с учётом кодировки.
+//!
+//! Старые файлы проекта бывают в CP1251 или UTF-16, и
+//! [`fs::read_to_string`] на них падает. [`read_text`] определяет
+//! кодировку по BOM, по нулевым байтам UTF-16 или с помощью `chardetng` и
+//! перекодирует содержимое в UTF-8, а [`write_preserving`] сохраняет текст
+//! обратно в исходной кодировке файла.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// Сколько байт начала файла просматривается в поисках признаков UTF-16.
+const UTF16_SNIFF_LEN: usize = 4096;
+
+/// Кодировка файла и наличие в нём BOM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileEncoding {
+    pub encoding: &'static Encoding,
+    pub bom: bool,
+}
+
+impl FileEncoding {
+    /// UTF-8 без BOM — кодировка новых файлов.
+    pub const UTF8: Self = Self {
+        encoding: UTF_8,
+        bom: false,
+    };
+
+    /// Имя кодировки по WHATWG, например `windows-1251`.
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    /// Кодировка по имени или метке WHATWG (`cp1251`, `utf-16le`, ...).
+    pub fn for_label(label: &str) -> Option<Self> {
+        Encoding::for_label(label.trim().as_bytes()).map(|encoding| Self {
+            encoding,
+            bom: false,
+        })
+    }
+}
+
+impl Default for FileEncoding {
+    fn default() -> Self {
+        Self::UTF8
+    }
+}
+
+/// Текст файла в UTF-8 и его исходная кодировка.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextFile {
+    pub text: String,
+    pub encoding: FileEncoding,
+    /// При перекодировании встретились недопустимые последовательности,
+    /// заменённые на `U+FFFD`.
+    pub lossy: bool,
+}
+
+/// Определяет кодировку `bytes` и перекодирует их в UTF-8.
+pub fn decode(bytes: &[u8]) -> TextFile {
+    let (encoding, bom_len) = match Encoding::for_bom(bytes) {
+        Some((encoding, len)) => (
+            FileEncoding {
+                encoding,
+                bom: true,
+            },
+            len,
+        ),
+        None => (detect(bytes), 0),
+    };
+    let (text, lossy) = encoding
+        .encoding
+        .decode_without_bom_handling(&bytes[bom_len..]);
+    TextFile {
+        text: text.into_owned(),
+        encoding,
+        lossy,
+    }
+}
+
+/// Кодировка файла без BOM.
+fn detect(bytes: &[u8]) -> FileEncoding {
+    let encoding = if let Some(utf16) = sniff_utf16(bytes) {
+        utf16
+    } else if std::str::from_utf8(bytes).is_ok() {
+        UTF_8
+    } else {
+        let mut detector = EncodingDetector::new();
+        detector.feed(bytes, true);
+        detector.guess(None, true)
+    };
+    FileEncoding {
+        encoding,
+        bom: false,
+    }
+}
+
+/// UTF-16 без BOM узнаётся по нулевым старшим байтам латиницы и
+/// пунктуации, которыми полон любой исходник.
+fn sniff_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(UTF16_SNIFF_LEN) & !1];
+    if sample.is_empty() {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let even = sample.iter().step_by(2).filter(|b| **b == 0).count();
+    let odd = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|b| **b == 0)
+        .count();
+    if odd * 2 > pairs && even * 10 < pairs {
+        Some(UTF_16LE)
+    } else if even * 2 > pairs && odd * 10 < pairs {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Кодирует `text` в `encoding`. Возвращает ошибку, если в кодировке нет
+/// какого-либо символа текста.
+pub fn encode(text: &str, encoding: FileEncoding) -> io::Result<Vec<u8>> {
+    let enc = encoding.encoding;
+    let mut out = Vec::with_capacity(text.len() + 3);
+    if encoding.bom {
+        if enc == UTF_16LE {
+            out.extend_from_slice(&[0xFF, 0xFE]);
+        } else if enc == UTF_16BE {
+            out.extend_from_slice(&[0xFE, 0xFF]);
+        } else if enc == UTF_8 {
+            out.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+        }
+    }
+    // encoding_rs не кодирует в UTF-16, поэтому эти случаи обрабатываются здесь.
+    if enc == UTF_16LE {
+        out.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+    } else if enc == UTF_16BE {
+        out.extend(text.encode_utf16().flat_map(u16::to_be_bytes));
+    } else {
+        let (bytes, _, unmappable) = enc.encode(text);
+        if unmappable {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "текст содержит символы, которых нет в кодировке {}",
+                    enc.name()
+                ),
+            ));
+        }
+        out.extend_from_slice(&bytes);
+    }
+    Ok(out)
+}
+
+/// Читает файл `path` в любой поддерживаемой кодировке.
+pub fn read_text<P: AsRef<Path>>(path: P) -> io::Result<TextFile> {
+    fs::read(path).map(|bytes| decode(&bytes))
+}
+
+/// Замена [`fs::read_to_string`], понимающая кодировки помимо UTF-8.
+pub fn read_to_string<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    read_text(path).map(|f| f.text)
+}
+
+/// Записывает `text` в файл `path` в кодировке `encoding`.
+pub fn write_text<P: AsRef<Path>>(path: P, text: &str, encoding: FileEncoding) -> io::Result<()> {
+    fs::write(path, encode(text, encoding)?)
+}
+
+/// Записывает `text` в файл `path` в той кодировке, в которой файл
+/// сохранён сейчас. Новые файлы записываются в UTF-8.
+pub fn write_preserving<P: AsRef<Path>>(path: P, text: &str) -> io::Result<()> {
+    let path = path.as_ref();
+    let encoding = match fs::read(path) {
+        Ok(bytes) => decode(&bytes).encoding,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => FileEncoding::UTF8,
+        Err(e) => return Err(e),
+    };
+    write_text(path, text, encoding)
+}
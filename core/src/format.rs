@@ -0,0 +1,110 @@
+//! Форматирование кода внешними программами.
+//!
+//! Команда форматтера выбирается по языку файла (см.
+//! [`FormattersConfig`](crate::config::FormattersConfig)), получает код в
+//! stdin и возвращает отформатированный код в stdout. Строки с
+//! комментариями `@VISUAL_META` перед запуском вынимаются из кода и после
+//! форматирования возвращаются без изменений: записи в начале файла — в
+//! начало, остальные — в конец. Импорты упорядочивает модуль [`imports`].
+
+pub mod imports;
+
+use std::io::Write;
+use std::path::Path;
+use std::process::Stdio;
+
+use crate::shell::Shell;
+
+const MARKER: &str = "@VISUAL_META";
+
+/// Код без строк метаданных и сами строки метаданных: ведущие и остальные.
+struct Split<'a> {
+    code: String,
+    leading: Vec<&'a str>,
+    trailing: Vec<&'a str>,
+}
+
+fn split_meta(content: &str) -> Split<'_> {
+    let mut split = Split {
+        code: String::new(),
+        leading: Vec::new(),
+        trailing: Vec::new(),
+    };
+    let mut in_code = false;
+    for line in content.lines() {
+        if line.contains(MARKER) {
+            if in_code {
+                split.trailing.push(line);
+            } else {
+                split.leading.push(line);
+            }
+        } else {
+            in_code |= !line.trim().is_empty();
+            split.code.push_str(line);
+            split.code.push('\n');
+        }
+    }
+    split
+}
+
+fn run(command: &str, input: String, dir: &Path) -> Result<String, String> {
+    let mut child = Shell::system()
+        .command(command)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("не удалось запустить форматтер '{command}': {e}"))?;
+    // Пишем в stdin из отдельного потока, чтобы большой вывод форматтера
+    // не заблокировал обе стороны канала.
+    let writer = child.stdin.take().map(|mut stdin| {
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        })
+    });
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    tracing::debug!(command, status = ?output.status.code(), "форматтер выполнен");
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        tracing::warn!(command, stderr = %stderr.trim(), "ошибка форматтера");
+        return Err(format!(
+            "форматтер '{command}' завершился с ошибкой: {}",
+            stderr.trim()
+        ));
+    }
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+/// Форматирует `content` командой `command`.
+///
+/// Подстановка `{file}` в команде заменяется путём к файлу `file`,
+/// экранированным для оболочки; команда запускается через системную
+/// оболочку в каталоге файла.
+pub fn format_code(content: &str, command: &str, file: &Path) -> Result<String, String> {
+    let split = split_meta(content);
+    let command = command.replace("{file}", &Shell::system().quote_path(file));
+    let dir = file
+        .parent()
+        .filter(|p| p.is_dir())
+        .unwrap_or_else(|| Path::new("."));
+    let formatted = run(&command, split.code, dir)?;
+
+    let mut out = String::new();
+    for line in split.leading {
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str(&formatted);
+    if !split.trailing.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+    for line in split.trailing {
+        out.push_str(line);
+        out.push('\n');
+    }
+    Ok(out)
+}
@@ -0,0 +1,712 @@
+//! Упорядочивание импортов: `use` в Rust, `import`/`from … import` в
+//! Python и `import` в JavaScript и TypeScript.
+//!
+//! Импорты верхнего уровня, идущие подряд от первого из них, делятся на
+//! группы и сортируются внутри группы, группы разделяются пустой строкой:
+//!
+//! - Rust — `std`/`core`/`alloc`, внешние крейты, `crate`/`self`/`super`,
+//!   затем `pub use`; порядок внутри группы совпадает с порядком `rustfmt`,
+//!   поэтому форматтер не переставляет импорты обратно;
+//! - Python — `__future__`, стандартная библиотека, сторонние пакеты,
+//!   относительные импорты; `import x` идут перед `from x import y`;
+//! - JavaScript и TypeScript — пакеты, затем относительные пути.
+//!
+//! Комментарии и атрибуты прямо над импортом переносятся вместе с ним.
+//! Неиспользуемые имена удаляются из импортов, а импорт без имён — целиком.
+//! Не удаляются `pub use`, импорты `*`, строки с `noqa` и имена Rust с
+//! заглавной буквы: типажи используются через методы без упоминания имени.
+//! Участок заканчивается на первом узле, который не является импортом, в том
+//! числе на импорте ради побочного эффекта (`import "./polyfill"`) и на
+//! комментарии `@VISUAL_META`; код после него не меняется.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::path::Path;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use tree_sitter::Node;
+
+use crate::parser::{parse, Lang};
+
+/// Модули стандартной библиотеки Python, которые чаще всего импортируются.
+const PYTHON_STDLIB: &[&str] = &[
+    "abc",
+    "argparse",
+    "array",
+    "ast",
+    "asyncio",
+    "base64",
+    "binascii",
+    "bisect",
+    "builtins",
+    "calendar",
+    "collections",
+    "concurrent",
+    "configparser",
+    "contextlib",
+    "copy",
+    "csv",
+    "ctypes",
+    "dataclasses",
+    "datetime",
+    "decimal",
+    "difflib",
+    "email",
+    "enum",
+    "errno",
+    "fnmatch",
+    "fractions",
+    "functools",
+    "gc",
+    "getpass",
+    "glob",
+    "gzip",
+    "hashlib",
+    "heapq",
+    "hmac",
+    "html",
+    "http",
+    "importlib",
+    "inspect",
+    "io",
+    "ipaddress",
+    "itertools",
+    "json",
+    "logging",
+    "math",
+    "multiprocessing",
+    "operator",
+    "os",
+    "pathlib",
+    "pickle",
+    "platform",
+    "pprint",
+    "queue",
+    "random",
+    "re",
+    "secrets",
+    "select",
+    "shlex",
+    "shutil",
+    "signal",
+    "socket",
+    "sqlite3",
+    "ssl",
+    "statistics",
+    "string",
+    "struct",
+    "subprocess",
+    "sys",
+    "tempfile",
+    "textwrap",
+    "threading",
+    "time",
+    "timeit",
+    "tomllib",
+    "traceback",
+    "types",
+    "typing",
+    "unittest",
+    "urllib",
+    "uuid",
+    "warnings",
+    "weakref",
+    "xml",
+    "zipfile",
+    "zlib",
+];
+
+static WORD: Lazy<Regex> = Lazy::new(|| Regex::new(r"[A-Za-z_][A-Za-z0-9_]*").unwrap());
+
+fn lang_of(file: &Path) -> Option<Lang> {
+    match file.extension()?.to_str()? {
+        "rs" => Some(Lang::Rust),
+        "py" => Some(Lang::Python),
+        "js" | "mjs" | "cjs" | "jsx" => Some(Lang::JavaScript),
+        "ts" | "mts" | "cts" => Some(Lang::TypeScript),
+        _ => None,
+    }
+}
+
+/// Имя, которое импорт вводит в файл.
+#[derive(Debug)]
+struct Name {
+    /// Имя в коде файла: псевдоним или последний сегмент пути.
+    name: String,
+    /// Текст элемента списка импорта, например `b as c`.
+    text: String,
+    removable: bool,
+}
+
+/// Разобранный импорт, который можно пересобрать без части имён.
+#[derive(Debug)]
+enum Shape {
+    /// Импорт целиком: удаляется, только если не использовано ни одно имя.
+    Whole(Vec<Name>),
+    /// `use path::{a, b};`
+    RustList { path: String, items: Vec<Name> },
+    /// `import a, b.c as d`
+    PythonImport(Vec<Name>),
+    /// `from module import a, b as c`
+    PythonFrom { module: String, names: Vec<Name> },
+    /// `import def, * as ns, { a, b as c } from "source";`
+    JsImport {
+        keyword: String,
+        default: Option<Name>,
+        namespace: Option<Name>,
+        named: Vec<Name>,
+        source: String,
+        semicolon: bool,
+    },
+}
+
+/// Импорт вместе с прилегающими комментариями и атрибутами.
+#[derive(Debug)]
+struct Import {
+    group: u8,
+    /// Путь или модуль, по которому сортируется импорт.
+    key: String,
+    /// Текст от начала первой прилегающей строки до начала импорта.
+    before: String,
+    node: String,
+    /// Остаток строки после импорта, например комментарий, с переводом строки.
+    after: String,
+    shape: Shape,
+}
+
+/// Текст файла `file` с упорядоченными импортами; `None`, если язык не
+/// поддерживается или менять нечего. С `remove_unused` неиспользуемые
+/// импорты удаляются.
+pub fn organize_imports(file: &Path, content: &str, remove_unused: bool) -> Option<String> {
+    let lang = lang_of(file)?;
+    let tree = parse(content, lang, None)?;
+    let root = tree.root_node();
+    let mut cursor = root.walk();
+    let nodes: Vec<Node> = root.children(&mut cursor).collect();
+    let first = nodes
+        .iter()
+        .position(|n| parse_import(*n, content, lang).is_some())?;
+
+    let mut imports = Vec::new();
+    let mut pending: Option<usize> = None;
+    let mut last_row = None;
+    let mut start = line_start(content, nodes[first].start_byte());
+    let mut end = start;
+    for node in &nodes[first..] {
+        let row = node.start_position().row;
+        if is_comment(*node) && last_row == Some(row) {
+            continue;
+        }
+        if is_attached(*node, content) {
+            let next_row = node.end_position().row + 1;
+            let adjacent = node
+                .next_sibling()
+                .is_some_and(|n| n.start_position().row == next_row);
+            if !adjacent {
+                break;
+            }
+            pending.get_or_insert(line_start(content, node.start_byte()));
+            continue;
+        }
+        let Some((group, key, shape)) = parse_import(*node, content, lang) else {
+            break;
+        };
+        let node_start = line_start(content, node.start_byte());
+        let line_end = line_end(content, node.end_byte());
+        let rest = content[node.end_byte()..line_end].trim();
+        let own_line = content[node_start..node.start_byte()].trim().is_empty();
+        if !own_line || !(rest.is_empty() || is_comment_text(rest, lang)) {
+            break;
+        }
+        let from = pending.take().unwrap_or(node_start);
+        if imports.is_empty() {
+            start = from;
+        }
+        imports.push(Import {
+            group,
+            key,
+            before: content[from..node.start_byte()].to_string(),
+            node: content[node.byte_range()].to_string(),
+            after: content[node.end_byte()..line_end].to_string(),
+            shape,
+        });
+        last_row = Some(node.end_position().row);
+        end = line_end;
+    }
+
+    if imports.is_empty() {
+        return None;
+    }
+    if remove_unused {
+        let used = used_names(root, content, lang, start..end);
+        imports.retain_mut(|import| prune(import, &used));
+    }
+    for import in &mut imports {
+        if !import.after.ends_with('\n') {
+            import.after.push('\n');
+        }
+    }
+    imports.sort_by(|a, b| {
+        a.group
+            .cmp(&b.group)
+            .then_with(|| compare_keys(&a.key, &b.key, lang))
+            .then_with(|| a.node.cmp(&b.node))
+    });
+    imports.dedup_by(|later, first| later.node == first.node && later.before == first.before);
+
+    let mut region = String::new();
+    for (i, import) in imports.iter().enumerate() {
+        if i > 0 && imports[i - 1].group != import.group {
+            region.push('\n');
+        }
+        region.push_str(&import.before);
+        region.push_str(&import.node);
+        region.push_str(&import.after);
+    }
+    if !content[..end].ends_with('\n') {
+        region.pop();
+    }
+    let mut result = String::with_capacity(content.len());
+    result.push_str(&content[..start]);
+    result.push_str(&region);
+    result.push_str(&content[end..]);
+    // Если импортов не осталось, не оставляем пустую строку на их месте.
+    if region.is_empty() {
+        let rest = result[start..].trim_start_matches(['\n', '\r']).to_string();
+        result.truncate(start);
+        result.push_str(&rest);
+    }
+    (result != content).then_some(result)
+}
+
+fn line_start(content: &str, offset: usize) -> usize {
+    content[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+fn line_end(content: &str, offset: usize) -> usize {
+    content[offset..]
+        .find('\n')
+        .map_or(content.len(), |i| offset + i + 1)
+}
+
+fn is_comment(node: Node) -> bool {
+    node.kind().contains("comment")
+}
+
+fn is_comment_text(text: &str, lang: Lang) -> bool {
+    match lang {
+        Lang::Python => text.starts_with('#'),
+        _ => text.starts_with("//") || text.starts_with("/*"),
+    }
+}
+
+/// Комментарий или атрибут, который переносится вместе со следующим
+/// импортом. Записи `@VISUAL_META` остаются на месте.
+fn is_attached(node: Node, content: &str) -> bool {
+    (is_comment(node) || node.kind() == "attribute_item")
+        && !content[node.byte_range()].contains("@VISUAL_META")
+}
+
+fn text<'a>(node: Node, content: &'a str) -> &'a str {
+    &content[node.byte_range()]
+}
+
+/// Группа, ключ сортировки и устройство импорта `node`; `None`, если узел
+/// не импорт или импорт нельзя переставлять.
+fn parse_import(node: Node, content: &str, lang: Lang) -> Option<(u8, String, Shape)> {
+    match lang {
+        Lang::Rust => rust_import(node, content),
+        Lang::Python => python_import(node, content),
+        _ => js_import(node, content),
+    }
+}
+
+fn rust_import(node: Node, content: &str) -> Option<(u8, String, Shape)> {
+    if node.kind() != "use_declaration" {
+        return None;
+    }
+    let argument = node.child_by_field_name("argument")?;
+    let visibility = node
+        .named_child(0)
+        .filter(|n| n.kind() == "visibility_modifier")
+        .map(|n| text(n, content).to_string());
+    let key = text(argument, content)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    let root = key.split("::").next().unwrap_or_default();
+    let group = match root {
+        _ if visibility.is_some() => 3,
+        "std" | "core" | "alloc" => 0,
+        "crate" | "self" | "super" => 2,
+        _ => 1,
+    };
+    let public = visibility.is_some();
+    let shape = match argument.kind() {
+        "scoped_use_list" if !public => {
+            let path = argument.child_by_field_name("path")?;
+            let list = argument.child_by_field_name("list")?;
+            let path = text(path, content).to_string();
+            let module = path.rsplit("::").next().unwrap_or_default().to_string();
+            let mut cursor = list.walk();
+            let items = list
+                .named_children(&mut cursor)
+                .filter(|n| !is_comment(*n))
+                .map(|item| {
+                    let name = match item.kind() {
+                        "self" => Some(module.clone()),
+                        _ => rust_name(item, content),
+                    };
+                    rust_item(name, text(item, content))
+                })
+                .collect();
+            Shape::RustList { path, items }
+        }
+        _ => {
+            let name = (!public).then(|| rust_name(argument, content)).flatten();
+            Shape::Whole(vec![rust_item(name, text(argument, content))])
+        }
+    };
+    Some((group, key, shape))
+}
+
+/// Имя, которое вводит элемент `use`; `None` для `*` и вложенных списков.
+fn rust_name(node: Node, content: &str) -> Option<String> {
+    match node.kind() {
+        "identifier" => Some(text(node, content).to_string()),
+        "scoped_identifier" => Some(text(node.child_by_field_name("name")?, content).to_string()),
+        "use_as_clause" => {
+            let alias = text(node.child_by_field_name("alias")?, content);
+            (alias != "_").then(|| alias.to_string())
+        }
+        _ => None,
+    }
+}
+
+fn rust_item(name: Option<String>, text: &str) -> Name {
+    let removable = name
+        .as_deref()
+        .is_some_and(|n| !n.starts_with(char::is_uppercase));
+    Name {
+        name: name.unwrap_or_default(),
+        text: text.to_string(),
+        removable,
+    }
+}
+
+fn python_import(node: Node, content: &str) -> Option<(u8, String, Shape)> {
+    let noqa = {
+        let end = line_end(content, node.end_byte());
+        content[node.end_byte()..end].contains("noqa")
+    };
+    let names = |field: &str| -> Vec<Name> {
+        let mut cursor = node.walk();
+        node.children_by_field_name(field, &mut cursor)
+            .map(|n| {
+                let (name, full) = match n.kind() {
+                    "aliased_import" => (
+                        n.child_by_field_name("alias")
+                            .map_or("", |a| text(a, content)),
+                        true,
+                    ),
+                    _ => (text(n, content), false),
+                };
+                // `import os.path` вводит имя `os`.
+                let name = if full {
+                    name
+                } else {
+                    name.split('.').next().unwrap_or(name)
+                };
+                Name {
+                    name: name.to_string(),
+                    text: text(n, content).to_string(),
+                    removable: !noqa,
+                }
+            })
+            .collect()
+    };
+    match node.kind() {
+        "future_import_statement" => Some((0, String::new(), Shape::Whole(Vec::new()))),
+        "import_statement" => {
+            let names = names("name");
+            let key = names.first()?.text.to_lowercase();
+            let group = python_group(&key);
+            Some((group, format!("0 {key}"), Shape::PythonImport(names)))
+        }
+        "import_from_statement" => {
+            let module = text(node.child_by_field_name("module_name")?, content).to_string();
+            let group = python_group(&module);
+            let key = format!("1 {}", module.to_lowercase());
+            let mut cursor = node.walk();
+            let wildcard = node
+                .children(&mut cursor)
+                .any(|n| n.kind() == "wildcard_import");
+            let shape = if wildcard {
+                Shape::Whole(Vec::new())
+            } else {
+                Shape::PythonFrom {
+                    module,
+                    names: names("name"),
+                }
+            };
+            Some((group, key, shape))
+        }
+        _ => None,
+    }
+}
+
+fn python_group(module: &str) -> u8 {
+    let root = module.split('.').next().unwrap_or_default();
+    if module.starts_with('.') {
+        3
+    } else if PYTHON_STDLIB.contains(&root) {
+        1
+    } else {
+        2
+    }
+}
+
+fn js_import(node: Node, content: &str) -> Option<(u8, String, Shape)> {
+    if node.kind() != "import_statement" {
+        return None;
+    }
+    let source = node.child_by_field_name("source")?;
+    let mut cursor = node.walk();
+    let clause = node
+        .children(&mut cursor)
+        .find(|n| n.kind() == "import_clause")?;
+    let path = text(source, content).trim_matches(['"', '\'']).to_string();
+    let group = u8::from(path.starts_with('.') || path.starts_with('/'));
+
+    let name = |n: Node, name: Node| Name {
+        name: text(name, content).to_string(),
+        text: text(n, content).to_string(),
+        removable: true,
+    };
+    let (mut default, mut namespace, mut named) = (None, None, Vec::new());
+    let mut cursor = clause.walk();
+    for part in clause.named_children(&mut cursor) {
+        match part.kind() {
+            "identifier" => default = Some(name(part, part)),
+            "namespace_import" => namespace = part.named_child(0).map(|n| name(part, n)),
+            "named_imports" => {
+                let mut cursor = part.walk();
+                named = part
+                    .named_children(&mut cursor)
+                    .filter(|s| s.kind() == "import_specifier")
+                    .filter_map(|s| {
+                        let local = s
+                            .child_by_field_name("alias")
+                            .or_else(|| s.child_by_field_name("name"))?;
+                        Some(name(s, local))
+                    })
+                    .collect();
+            }
+            _ => {}
+        }
+    }
+    let keyword = content[node.start_byte()..clause.start_byte()]
+        .trim_end()
+        .to_string();
+    let shape = Shape::JsImport {
+        keyword,
+        default,
+        namespace,
+        named,
+        source: text(source, content).to_string(),
+        semicolon: text(node, content).ends_with(';'),
+    };
+    Some((group, path, shape))
+}
+
+/// Имена, которые встречаются в файле вне участка импортов `region`.
+fn used_names(root: Node, content: &str, lang: Lang, region: Range<usize>) -> HashSet<String> {
+    let mut used = HashSet::new();
+    let mut jsx = false;
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.start_byte() >= region.start && node.end_byte() <= region.end {
+            continue;
+        }
+        jsx |= node.kind().starts_with("jsx_");
+        if node.child_count() == 0 {
+            let text = text(node, content);
+            if lang == Lang::Python && node.kind() == "string_content" {
+                used.extend(WORD.find_iter(text).map(|m| m.as_str().to_string()));
+            } else {
+                used.insert(text.to_string());
+            }
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    // JSX без нового преобразования обращается к React неявно.
+    if jsx {
+        used.insert("React".into());
+    }
+    used
+}
+
+/// Убирает из импорта неиспользуемые имена; `false`, если импорт больше не
+/// нужен.
+fn prune(import: &mut Import, used: &HashSet<String>) -> bool {
+    let unused = |n: &Name| n.removable && !used.contains(&n.name);
+    let node = match &mut import.shape {
+        Shape::Whole(names) => return names.is_empty() || !names.iter().all(unused),
+        Shape::RustList { path, items } => {
+            if !items.iter().any(unused) {
+                return true;
+            }
+            items.retain(|n| !unused(n));
+            match items.as_slice() {
+                [] => return false,
+                [item] if item.text == "self" => format!("use {path};"),
+                [item] if !item.text.contains('{') => format!("use {path}::{};", item.text),
+                _ => format!("use {path}::{{{}}};", join(items)),
+            }
+        }
+        Shape::PythonImport(names) => {
+            if !names.iter().any(unused) {
+                return true;
+            }
+            names.retain(|n| !unused(n));
+            if names.is_empty() {
+                return false;
+            }
+            format!("import {}", join(names))
+        }
+        Shape::PythonFrom { module, names } => {
+            if !names.iter().any(unused) {
+                return true;
+            }
+            names.retain(|n| !unused(n));
+            if names.is_empty() {
+                return false;
+            }
+            format!("from {module} import {}", join(names))
+        }
+        Shape::JsImport {
+            keyword,
+            default,
+            namespace,
+            named,
+            source,
+            semicolon,
+        } => {
+            let any_unused =
+                default.iter().chain(namespace.iter()).any(unused) || named.iter().any(unused);
+            if !any_unused {
+                return true;
+            }
+            default.take_if(|n| unused(n));
+            namespace.take_if(|n| unused(n));
+            named.retain(|n| !unused(n));
+            let mut parts: Vec<String> = default
+                .iter()
+                .chain(namespace.iter())
+                .map(|n| n.text.clone())
+                .collect();
+            if !named.is_empty() {
+                parts.push(format!("{{ {} }}", join(named)));
+            }
+            if parts.is_empty() {
+                return false;
+            }
+            let semicolon = if *semicolon { ";" } else { "" };
+            format!("{keyword} {} from {source}{semicolon}", parts.join(", "))
+        }
+    };
+    import.node = node;
+    true
+}
+
+fn join(names: &[Name]) -> String {
+    names
+        .iter()
+        .map(|n| n.text.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn compare_keys(a: &str, b: &str, lang: Lang) -> Ordering {
+    match lang {
+        Lang::Rust => compare_rust_paths(a, b),
+        _ => a.cmp(b),
+    }
+}
+
+/// Сегменты пути `use` верхнего уровня: `std::io::{self, Read}` →
+/// `std`, `io`, `{self, Read}`.
+fn rust_segments(path: &str) -> Vec<&str> {
+    let mut segments = Vec::new();
+    let (mut depth, mut start) = (0, 0);
+    let bytes = path.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => depth -= 1,
+            b':' if depth == 0 && bytes.get(i + 1) == Some(&b':') => {
+                segments.push(&path[start..i]);
+                start = i + 2;
+                i += 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    segments.push(&path[start..]);
+    segments
+}
+
+/// Порядок путей `use`, как у `rustfmt`: `self`, `super`, `crate`, затем
+/// имена (snake_case, CamelCase, UPPER_SNAKE_CASE), `*` и списки.
+fn compare_rust_paths(a: &str, b: &str) -> Ordering {
+    let (a, b) = (rust_segments(a), rust_segments(b));
+    for (a, b) in a.iter().zip(&b) {
+        let ord = compare_rust_segments(a, b);
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+fn compare_rust_segments(a: &str, b: &str) -> Ordering {
+    fn rank(segment: &str) -> u8 {
+        let ident = segment.split(" as ").next().unwrap_or_default();
+        match ident {
+            "self" => 0,
+            "super" => 1,
+            "crate" => 2,
+            "*" => 4,
+            _ if segment.starts_with('{') => 5,
+            _ => 3,
+        }
+    }
+    fn case(ident: &str) -> u8 {
+        if ident.starts_with(char::is_lowercase) {
+            0
+        } else if ident
+            .chars()
+            .all(|c| c.is_uppercase() || c == '_' || c.is_numeric())
+        {
+            2
+        } else {
+            1
+        }
+    }
+    let ord = rank(a).cmp(&rank(b));
+    if ord != Ordering::Equal || rank(a) != 3 {
+        return ord.then_with(|| a.cmp(b));
+    }
+    let (ia, aa) = a.split_once(" as ").unwrap_or((a, ""));
+    let (ib, ab) = b.split_once(" as ").unwrap_or((b, ""));
+    let (ia, ib) = (ia.trim_start_matches("r#"), ib.trim_start_matches("r#"));
+    case(ia)
+        .cmp(&case(ib))
+        .then_with(|| ia.cmp(ib))
+        .then_with(|| aa.cmp(ab))
+}
@@ -1,13 +1,31 @@
-use git2::{BlameOptions, BranchType, Commit, DiffOptions, IndexAddOption, Repository};
-use std::path::Path;
+use git2::build::CheckoutBuilder;
+use git2::{
+    ApplyLocation, ApplyOptions, BlameOptions, Branch, BranchType, CheckoutNotificationType,
+    Commit, Diff, DiffOptions, ErrorCode, IndexAddOption, ObjectType, Patch, Repository, Sort,
+    Status, StatusOptions,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use tracing::error;
 
-pub fn commit(message: &str) -> Result<(), git2::Error> {
+use crate::meta::{read_all, VisualMeta};
+
+fn require_message(message: &str) -> Result<(), git2::Error> {
     if message.trim().is_empty() {
-        return Err(git2::Error::from_str("сообщение коммита не может быть пустым"));
+        return Err(git2::Error::from_str(
+            "сообщение коммита не может быть пустым",
+        ));
     }
+    Ok(())
+}
 
-    let repo = Repository::discover(".")?;
+/// Коммитит каталоги `backend`, `frontend` и `docs` репозитория, которому
+/// принадлежит `root`.
+pub fn commit(root: &Path, message: &str) -> Result<(), git2::Error> {
+    require_message(message)?;
+
+    let (repo, _) = locate(root)?;
     let mut index = repo.index()?;
     // Ограничиваем пути, которые могут быть добавлены в индекс, чтобы
     // случайно не закоммитить большие или несвязанные каталоги.
@@ -16,7 +34,11 @@ pub fn commit(message: &str) -> Result<(), git2::Error> {
     index.write()?;
     let tree_id = index.write_tree()?;
     let tree = repo.find_tree(tree_id)?;
+    write_commit(&repo, &tree, message)
+}
 
+/// Подпись коммита из конфигурации репозитория.
+fn signature(repo: &Repository) -> Result<git2::Signature<'static>, git2::Error> {
     // Получаем подпись коммита из конфигурации репозитория. В некоторых
     // средах (например, в тестах или CI) у пользователя может не быть глобальной
     // конфигурации git. Ранее это приводило к ошибке `config value 'user.name' was not found`
@@ -29,7 +51,12 @@ pub fn commit(message: &str) -> Result<(), git2::Error> {
     let email = cfg
         .get_string("user.email")
         .unwrap_or_else(|_| "unknown@example.com".to_string());
-    let sig = git2::Signature::now(&name, &email)?;
+    git2::Signature::now(&name, &email)
+}
+
+/// Создаёт коммит с деревом `tree` поверх `HEAD`.
+fn write_commit(repo: &Repository, tree: &git2::Tree, message: &str) -> Result<(), git2::Error> {
+    let sig = signature(repo)?;
     // Определяем, есть ли в репозитории ссылка HEAD. В только что
     // инициализированном репозитории `head()` завершится ошибкой, что раньше
     // приводило к сбою `commit`. Вместо того чтобы передавать ошибку дальше,
@@ -40,9 +67,9 @@ pub fn commit(message: &str) -> Result<(), git2::Error> {
     let parents: Vec<&Commit> = parent.iter().collect();
 
     if head.is_ok() {
-        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)?;
+        repo.commit(Some("HEAD"), &sig, &sig, message, tree, &parents)?;
     } else {
-        let oid = repo.commit(None, &sig, &sig, message, &tree, &parents)?;
+        let oid = repo.commit(None, &sig, &sig, message, tree, &parents)?;
         // При первом коммите создаём ветку "main", указывающую на новый
         // коммит, и переводим `HEAD` на неё. Это не позволяет оставить
         // репозиторий в состоянии отсоединённого `HEAD`, что может запутать
@@ -53,12 +80,16 @@ pub fn commit(message: &str) -> Result<(), git2::Error> {
     Ok(())
 }
 
-pub fn diff() -> Result<String, git2::Error> {
+/// Неиндексированные изменения файла или каталога `path` в виде патча.
+pub fn diff(path: &Path) -> Result<String, git2::Error> {
     const MAX_DIFF_LEN: usize = 100_000; // Ограничение вывода diff примерно 100 КБ
 
-    let repo = Repository::discover(".")?;
+    let (repo, rel) = locate(path)?;
     let mut opts = DiffOptions::new();
     opts.include_untracked(true);
+    if !rel.as_os_str().is_empty() {
+        opts.pathspec(&rel);
+    }
     let diff = repo.diff_index_to_workdir(None, Some(&mut opts))?;
     let mut out = String::new();
 
@@ -85,8 +116,9 @@ pub fn diff() -> Result<String, git2::Error> {
     Ok(out)
 }
 
-pub fn branches() -> Result<Vec<String>, git2::Error> {
-    let repo = Repository::discover(".")?;
+/// Локальные ветки репозитория, которому принадлежит `path`.
+pub fn branches(path: &Path) -> Result<Vec<String>, git2::Error> {
+    let (repo, _) = locate(path)?;
     let mut names = Vec::new();
     for branch in repo.branches(Some(BranchType::Local))? {
         let (b, _) = branch?;
@@ -97,20 +129,42 @@ pub fn branches() -> Result<Vec<String>, git2::Error> {
     Ok(names)
 }
 
-pub fn log() -> Result<Vec<String>, git2::Error> {
-    let repo = Repository::discover(".")?;
+/// Последние 20 коммитов репозитория, которому принадлежит `path`. Если
+/// `path` — не корень рабочего каталога, остаются только коммиты, которые
+/// меняют `path`.
+pub fn log(path: &Path) -> Result<Vec<String>, git2::Error> {
+    let (repo, rel) = locate(path)?;
     let mut revwalk = repo.revwalk()?;
     revwalk.push_head()?;
     let mut entries = Vec::new();
-    for oid_res in revwalk.take(20) {
+    for oid_res in revwalk {
+        if entries.len() == 20 {
+            break;
+        }
         let oid = oid_res?;
         let commit = repo.find_commit(oid)?;
+        if !rel.as_os_str().is_empty() && !touches(&repo, &commit, &rel)? {
+            continue;
+        }
         let msg = commit.summary().unwrap_or("").to_string();
         entries.push(format!("{} {}", &oid.to_string()[..7], msg));
     }
     Ok(entries)
 }
 
+/// Меняет ли коммит `commit` файлы внутри `rel` относительно первого
+/// родителя.
+fn touches(repo: &Repository, commit: &Commit, rel: &Path) -> Result<bool, git2::Error> {
+    let parent = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let mut opts = DiffOptions::new();
+    opts.pathspec(rel);
+    let diff = repo.diff_tree_to_tree(parent.as_ref(), Some(&commit.tree()?), Some(&mut opts))?;
+    Ok(diff.deltas().len() > 0)
+}
+
 #[derive(Debug, serde::Serialize)]
 pub struct BlameLine {
     pub line: usize,
@@ -119,10 +173,12 @@ pub struct BlameLine {
     pub time: i64,
 }
 
-pub fn blame(path: &str) -> Result<Vec<BlameLine>, git2::Error> {
-    let repo = Repository::discover(".")?;
+/// Авторы строк файла `path` по истории репозитория, которому он
+/// принадлежит.
+pub fn blame(path: &Path) -> Result<Vec<BlameLine>, git2::Error> {
+    let (repo, rel) = locate(path)?;
     let mut opts = BlameOptions::new();
-    let blame = repo.blame_file(Path::new(path), Some(&mut opts))?;
+    let blame = repo.blame_file(&rel, Some(&mut opts))?;
     let mut lines = Vec::new();
     for hunk in blame.iter() {
         let sig = hunk.final_signature();
@@ -140,3 +196,721 @@ pub fn blame(path: &str) -> Result<Vec<BlameLine>, git2::Error> {
     }
     Ok(lines)
 }
+
+/// Изменение метаданных блока между соседними точками истории.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub enum MetaChange {
+    /// Метаданные блока появились в файле.
+    Added,
+    /// Метаданные блока исчезли из файла.
+    Removed,
+    /// Блок перемещён на холсте.
+    Moved { from: (f64, f64), to: (f64, f64) },
+    /// Изменился набор тегов.
+    Tags {
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+    /// Изменилась заметка блока (`ai.description`).
+    Note {
+        from: Option<String>,
+        to: Option<String>,
+    },
+}
+
+/// Точка истории метаданных блока: коммит и состояние блока в нём.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MetaHistoryEntry {
+    /// Сокращённый идентификатор коммита.
+    pub commit: String,
+    pub summary: String,
+    pub author: String,
+    /// Секунды с начала эпохи Unix
+    pub time: i64,
+    /// Метаданные блока; `None`, если в этом коммите их нет.
+    pub meta: Option<VisualMeta>,
+    /// Отличия от предыдущей точки истории.
+    pub changes: Vec<MetaChange>,
+}
+
+fn note(meta: &VisualMeta) -> Option<String> {
+    meta.ai.as_ref().and_then(|ai| ai.description.clone())
+}
+
+/// Отличия метаданных `new` от `old` по положению, тегам и заметке.
+pub fn meta_changes(old: Option<&VisualMeta>, new: Option<&VisualMeta>) -> Vec<MetaChange> {
+    let (old, new) = match (old, new) {
+        (None, None) => return Vec::new(),
+        (None, Some(_)) => return vec![MetaChange::Added],
+        (Some(_), None) => return vec![MetaChange::Removed],
+        (Some(old), Some(new)) => (old, new),
+    };
+    let mut changes = Vec::new();
+    if (old.x, old.y) != (new.x, new.y) {
+        changes.push(MetaChange::Moved {
+            from: (old.x, old.y),
+            to: (new.x, new.y),
+        });
+    }
+    let added: Vec<String> = new
+        .tags
+        .iter()
+        .filter(|t| !old.tags.contains(t))
+        .cloned()
+        .collect();
+    let removed: Vec<String> = old
+        .tags
+        .iter()
+        .filter(|t| !new.tags.contains(t))
+        .cloned()
+        .collect();
+    if !added.is_empty() || !removed.is_empty() {
+        changes.push(MetaChange::Tags { added, removed });
+    }
+    let (from, to) = (note(old), note(new));
+    if from != to {
+        changes.push(MetaChange::Note { from, to });
+    }
+    changes
+}
+
+/// История метаданных блока `id` из файла `path` по коммитам git.
+///
+/// Обходит первую родительскую линию от `HEAD`, от старых коммитов к новым,
+/// и оставляет только коммиты, в которых положение, теги или заметка блока
+/// изменились.
+pub fn meta_history(path: &Path, id: &str) -> Result<Vec<MetaHistoryEntry>, git2::Error> {
+    let (repo, rel) = locate(path)?;
+
+    let mut revwalk = repo.revwalk()?;
+    revwalk.push_head()?;
+    revwalk.simplify_first_parent()?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)?;
+
+    let mut entries = Vec::new();
+    let mut previous: Option<VisualMeta> = None;
+    for oid in revwalk {
+        let commit = repo.find_commit(oid?)?;
+        let meta = match commit.tree()?.get_path(&rel) {
+            Ok(entry) if entry.kind() == Some(ObjectType::Blob) => {
+                let blob = repo.find_blob(entry.id())?;
+                let content = String::from_utf8_lossy(blob.content());
+                read_all(&content).into_iter().find(|m| m.id == id)
+            }
+            _ => None,
+        };
+        let changes = meta_changes(previous.as_ref(), meta.as_ref());
+        if changes.is_empty() {
+            continue;
+        }
+        let author = commit.author();
+        entries.push(MetaHistoryEntry {
+            commit: commit.id().to_string()[..7].to_string(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            author: author.name().unwrap_or("Неизвестно").to_string(),
+            time: author.when().seconds(),
+            meta: meta.clone(),
+            changes,
+        });
+        previous = meta;
+    }
+    Ok(entries)
+}
+
+/// Состояние файла по `git status`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct FileStatus {
+    /// В индексе есть изменения относительно `HEAD`.
+    pub staged: bool,
+    /// Рабочая копия отличается от индекса.
+    pub modified: bool,
+    /// Файл не отслеживается.
+    pub untracked: bool,
+    /// Файл содержит неразрешённый конфликт слияния.
+    pub conflicted: bool,
+}
+
+impl FileStatus {
+    fn from_git(status: Status) -> Self {
+        Self {
+            staged: status.intersects(
+                Status::INDEX_NEW
+                    | Status::INDEX_MODIFIED
+                    | Status::INDEX_DELETED
+                    | Status::INDEX_RENAMED
+                    | Status::INDEX_TYPECHANGE,
+            ),
+            modified: status.intersects(
+                Status::WT_MODIFIED
+                    | Status::WT_DELETED
+                    | Status::WT_RENAMED
+                    | Status::WT_TYPECHANGE,
+            ),
+            untracked: status.contains(Status::WT_NEW),
+            conflicted: status.contains(Status::CONFLICTED),
+        }
+    }
+
+    /// Объединение состояний, например всех файлов каталога.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            staged: self.staged || other.staged,
+            modified: self.modified || other.modified,
+            untracked: self.untracked || other.untracked,
+            conflicted: self.conflicted || other.conflicted,
+        }
+    }
+
+    pub fn is_clean(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn io_error(e: std::io::Error) -> git2::Error {
+    git2::Error::from_str(&e.to_string())
+}
+
+fn workdir(repo: &Repository) -> Result<PathBuf, git2::Error> {
+    repo.workdir()
+        .ok_or_else(|| git2::Error::from_str("репозиторий не имеет рабочего каталога"))?
+        .canonicalize()
+        .map_err(io_error)
+}
+
+/// Репозиторий файла или каталога `path` и путь к нему относительно
+/// рабочего каталога. Сам `path` может не существовать, например после
+/// удаления файла.
+///
+/// Поиск идёт вверх от `path`, а не от текущего каталога, поэтому для файла
+/// подмодуля находится репозиторий подмодуля, а для файла связанного
+/// рабочего дерева (`git worktree`) — это рабочее дерево, а не основное.
+pub fn locate(path: &Path) -> Result<(Repository, PathBuf), git2::Error> {
+    let full = match path.canonicalize() {
+        Ok(full) => full,
+        Err(_) => {
+            let name = path
+                .file_name()
+                .ok_or_else(|| git2::Error::from_str("некорректный путь"))?;
+            let parent = path
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            parent.canonicalize().map_err(io_error)?.join(name)
+        }
+    };
+    let repo = Repository::discover(if full.is_dir() {
+        &full
+    } else {
+        full.parent().unwrap_or(&full)
+    })?;
+    let rel = full
+        .strip_prefix(workdir(&repo)?)
+        .map_err(|_| git2::Error::from_str("файл находится вне репозитория"))?
+        .to_path_buf();
+    Ok((repo, rel))
+}
+
+/// Состояния изменённых, индексированных и неотслеживаемых файлов каталога
+/// `root`. Ключи — пути вида `root.join(...)`; файлы без изменений и
+/// игнорируемые файлы в результат не попадают. Файлы подмодулей внутри
+/// `root` получают состояние из своих репозиториев.
+pub fn status(root: &Path) -> Result<HashMap<PathBuf, FileStatus>, git2::Error> {
+    let base = root.canonicalize().map_err(io_error)?;
+    let repo = Repository::discover(&base)?;
+    let mut result = HashMap::new();
+    collect_status(&repo, &base, root, &mut result)?;
+    Ok(result)
+}
+
+fn collect_status(
+    repo: &Repository,
+    base: &Path,
+    root: &Path,
+    result: &mut HashMap<PathBuf, FileStatus>,
+) -> Result<(), git2::Error> {
+    let dir = workdir(repo)?;
+    let mut opts = StatusOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    for entry in repo.statuses(Some(&mut opts))?.iter() {
+        let Some(path) = entry.path() else {
+            continue;
+        };
+        let full = dir.join(path);
+        if let Ok(rel) = full.strip_prefix(base) {
+            let status = FileStatus::from_git(entry.status());
+            if !status.is_clean() {
+                result.insert(root.join(rel), status);
+            }
+        }
+    }
+    // В надпроекте подмодуль виден одной записью, поэтому его файлы
+    // читаются из репозитория подмодуля. Неинициализированные и
+    // повреждённые подмодули пропускаются.
+    for submodule in repo.submodules()? {
+        let Ok(sub) = submodule.open() else {
+            continue;
+        };
+        if workdir(&sub).is_ok_and(|dir| dir.starts_with(base)) {
+            let _ = collect_status(&sub, base, root, result);
+        }
+    }
+    Ok(())
+}
+
+/// Добавляет изменения файла или каталога `path` в индекс, включая
+/// удаление файлов.
+pub fn stage(path: &Path) -> Result<(), git2::Error> {
+    let (repo, rel) = locate(path)?;
+    let mut index = repo.index()?;
+    if rel.as_os_str().is_empty() {
+        index.add_all(["*"], IndexAddOption::DEFAULT, None)?;
+        index.update_all(["*"], None)?;
+    } else {
+        index.add_all([&rel], IndexAddOption::DEFAULT, None)?;
+        index.update_all([&rel], None)?;
+    }
+    index.write()
+}
+
+/// Убирает изменения `path` из индекса, оставляя рабочую копию как есть.
+pub fn unstage(path: &Path) -> Result<(), git2::Error> {
+    let (repo, rel) = locate(path)?;
+    let spec = if rel.as_os_str().is_empty() {
+        PathBuf::from("*")
+    } else {
+        rel
+    };
+    // До первого коммита `HEAD` нет, и пути просто удаляются из индекса.
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|h| h.peel(ObjectType::Commit).ok());
+    repo.reset_default(head.as_ref(), [&spec])
+}
+
+/// Отменяет неиндексированные изменения `path`, восстанавливая рабочую
+/// копию из индекса. Неотслеживаемые файлы не удаляются: для них
+/// возвращается ошибка.
+pub fn discard(path: &Path) -> Result<(), git2::Error> {
+    let (repo, rel) = locate(path)?;
+    let tracked = repo
+        .index()?
+        .iter()
+        .any(|e| Path::new(&*String::from_utf8_lossy(&e.path)).starts_with(&rel));
+    if !tracked {
+        return Err(git2::Error::from_str("файл не отслеживается"));
+    }
+    let mut checkout = CheckoutBuilder::new();
+    checkout.force();
+    if !rel.as_os_str().is_empty() {
+        checkout.path(&rel);
+    }
+    repo.checkout_index(None, Some(&mut checkout))
+}
+
+/// Строка участка изменений.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct HunkLine {
+    /// `+` — добавленная строка, `-` — удалённая, ` ` — контекст.
+    pub origin: char,
+    /// Текст строки без перевода строки.
+    pub content: String,
+}
+
+/// Участок изменений файла.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct Hunk {
+    /// Заголовок вида `@@ -1,3 +1,4 @@`.
+    pub header: String,
+    pub lines: Vec<HunkLine>,
+}
+
+/// Изменения одного файла.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+pub struct FileChanges {
+    /// Путь вида `root.join(...)`, как у [`status`].
+    pub path: PathBuf,
+    pub hunks: Vec<Hunk>,
+    /// Двоичный файл: участков нет, добавить его можно только целиком.
+    pub binary: bool,
+}
+
+/// Изменения рабочего каталога: ещё не добавленные в индекс и уже
+/// добавленные, которые войдут в следующий коммит.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct WorkingChanges {
+    pub unstaged: Vec<FileChanges>,
+    pub staged: Vec<FileChanges>,
+}
+
+fn diff_options(pathspec: Option<&Path>) -> DiffOptions {
+    let mut opts = DiffOptions::new();
+    opts.include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .show_untracked_content(true);
+    if let Some(path) = pathspec {
+        opts.pathspec(path).disable_pathspec_match(true);
+    }
+    opts
+}
+
+fn head_tree(repo: &Repository) -> Option<git2::Tree<'_>> {
+    repo.head().ok().and_then(|h| h.peel_to_tree().ok())
+}
+
+fn file_changes(diff: &Diff, workdir: &Path, base: &Path, root: &Path) -> Vec<FileChanges> {
+    let mut files = Vec::new();
+    for idx in 0..diff.deltas().len() {
+        let Ok(Some(patch)) = Patch::from_diff(diff, idx) else {
+            continue;
+        };
+        let delta = patch.delta();
+        let Some(path) = delta.new_file().path().or_else(|| delta.old_file().path()) else {
+            continue;
+        };
+        let Ok(rel) = workdir.join(path).strip_prefix(base).map(Path::to_path_buf) else {
+            continue;
+        };
+        let mut hunks = Vec::new();
+        for h in 0..patch.num_hunks() {
+            let Ok((hunk, count)) = patch.hunk(h) else {
+                continue;
+            };
+            let lines = (0..count)
+                .filter_map(|l| patch.line_in_hunk(h, l).ok())
+                .map(|line| HunkLine {
+                    origin: line.origin(),
+                    content: String::from_utf8_lossy(line.content())
+                        .trim_end_matches(['\r', '\n'])
+                        .to_string(),
+                })
+                .collect();
+            hunks.push(Hunk {
+                header: String::from_utf8_lossy(hunk.header())
+                    .trim_end()
+                    .to_string(),
+                lines,
+            });
+        }
+        files.push(FileChanges {
+            path: root.join(rel),
+            hunks,
+            binary: delta.flags().is_binary(),
+        });
+    }
+    files
+}
+
+/// Изменения каталога `root` по участкам.
+pub fn changes(root: &Path) -> Result<WorkingChanges, git2::Error> {
+    let base = root.canonicalize().map_err(io_error)?;
+    let repo = Repository::discover(&base)?;
+    let workdir = workdir(&repo)?;
+    let unstaged = repo.diff_index_to_workdir(None, Some(&mut diff_options(None)))?;
+    let staged = repo.diff_tree_to_index(
+        head_tree(&repo).as_ref(),
+        None,
+        Some(&mut diff_options(None)),
+    )?;
+    Ok(WorkingChanges {
+        unstaged: file_changes(&unstaged, &workdir, &base, root),
+        staged: file_changes(&staged, &workdir, &base, root),
+    })
+}
+
+/// Применяет к индексу только участки `hunks` (номера по порядку в файле).
+fn apply_hunks(repo: &Repository, diff: &Diff, hunks: &[usize]) -> Result<(), git2::Error> {
+    let mut next = 0;
+    let mut opts = ApplyOptions::new();
+    opts.hunk_callback(|_| {
+        let selected = hunks.contains(&next);
+        next += 1;
+        selected
+    });
+    repo.apply(diff, ApplyLocation::Index, Some(&mut opts))
+}
+
+/// Добавляет в индекс участки `hunks` неиндексированных изменений файла
+/// `path`, как `git add -p`. Неотслеживаемый файл состоит из одного
+/// участка и добавляется целиком.
+pub fn stage_hunks(path: &Path, hunks: &[usize]) -> Result<(), git2::Error> {
+    let (repo, rel) = locate(path)?;
+    if repo.index()?.get_path(&rel, 0).is_none() {
+        return if hunks.contains(&0) {
+            stage(path)
+        } else {
+            Ok(())
+        };
+    }
+    let diff = repo.diff_index_to_workdir(None, Some(&mut diff_options(Some(&rel))))?;
+    apply_hunks(&repo, &diff, hunks)
+}
+
+/// Убирает из индекса участки `hunks` индексированных изменений файла
+/// `path`; рабочая копия не меняется.
+pub fn unstage_hunks(path: &Path, hunks: &[usize]) -> Result<(), git2::Error> {
+    let (repo, rel) = locate(path)?;
+    let mut opts = diff_options(Some(&rel));
+    // Обратный diff индекса к `HEAD` содержит те же участки, что и
+    // показанный пользователю, но возвращает их к версии из `HEAD`.
+    opts.reverse(true);
+    let diff = repo.diff_tree_to_index(head_tree(&repo).as_ref(), None, Some(&mut opts))?;
+    apply_hunks(&repo, &diff, hunks)
+}
+
+/// Сообщение последнего коммита, если он есть.
+pub fn head_message(root: &Path) -> Result<Option<String>, git2::Error> {
+    let repo = Repository::discover(root)?;
+    let head = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    Ok(head.and_then(|c| c.message().map(str::to_string)))
+}
+
+/// Создаёт коммит из текущего индекса репозитория каталога `root`. С
+/// `amend` заменяет последний коммит, сохраняя его автора.
+pub fn commit_index(root: &Path, message: &str, amend: bool) -> Result<(), git2::Error> {
+    require_message(message)?;
+    let repo = Repository::discover(root)?;
+    let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+    let head = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    if amend {
+        let head = head.ok_or_else(|| git2::Error::from_str("нет коммита для исправления"))?;
+        let committer = signature(&repo)?;
+        head.amend(
+            Some("HEAD"),
+            None,
+            Some(&committer),
+            None,
+            Some(message),
+            Some(&tree),
+        )?;
+        return Ok(());
+    }
+    if head.is_some_and(|c| c.tree_id() == tree.id()) {
+        return Err(git2::Error::from_str("в индексе нет изменений для коммита"));
+    }
+    write_commit(&repo, &tree, message)
+}
+
+/// Файлы каталога `root`, добавленные или изменённые в индексе относительно
+/// `HEAD`, с текстом из индекса. Пути указываются относительно `root`;
+/// удалённые, двоичные и не UTF-8 файлы пропускаются.
+pub fn staged_files(root: &Path) -> Result<Vec<(PathBuf, String)>, git2::Error> {
+    let repo = Repository::discover(root)?;
+    let workdir = workdir(&repo)?;
+    let base = root.canonicalize().map_err(io_error)?;
+    let index = repo.index()?;
+    let diff = repo.diff_tree_to_index(head_tree(&repo).as_ref(), Some(&index), None)?;
+    let mut files = Vec::new();
+    for delta in diff.deltas() {
+        if delta.status() == git2::Delta::Deleted {
+            continue;
+        }
+        let Some(path) = delta.new_file().path() else {
+            continue;
+        };
+        let full = workdir.join(path);
+        let Ok(rel) = full.strip_prefix(&base) else {
+            continue;
+        };
+        let blob = repo.find_blob(delta.new_file().id())?;
+        if blob.is_binary() {
+            continue;
+        }
+        if let Ok(content) = String::from_utf8(blob.content().to_vec()) {
+            files.push((rel.to_path_buf(), content));
+        }
+    }
+    Ok(files)
+}
+
+/// Ошибка переключения веток и работы с отложенными изменениями.
+#[derive(Debug)]
+pub enum GitError {
+    /// Ветки с таким именем нет.
+    BranchNotFound(String),
+    /// Ветка с таким именем уже есть.
+    BranchExists(String),
+    /// Имя не подходит для ветки git.
+    InvalidBranchName(String),
+    /// Переключение перезаписало бы незакоммиченные изменения этих файлов.
+    Conflicts(Vec<PathBuf>),
+    /// Нет изменений, которые можно отложить.
+    NothingToStash,
+    /// Список отложенных изменений пуст.
+    NoStash,
+    /// Отложенные изменения конфликтуют с рабочей копией и не применены.
+    StashConflict,
+    Git(git2::Error),
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitError::BranchNotFound(name) => write!(f, "ветка {name} не найдена"),
+            GitError::BranchExists(name) => write!(f, "ветка {name} уже существует"),
+            GitError::InvalidBranchName(name) => write!(f, "некорректное имя ветки: {name}"),
+            GitError::Conflicts(paths) => {
+                let paths: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+                write!(
+                    f,
+                    "незакоммиченные изменения будут перезаписаны: {}",
+                    paths.join(", ")
+                )
+            }
+            GitError::NothingToStash => write!(f, "нет изменений, которые можно отложить"),
+            GitError::NoStash => write!(f, "нет отложенных изменений"),
+            GitError::StashConflict => {
+                write!(f, "отложенные изменения конфликтуют с рабочей копией")
+            }
+            GitError::Git(e) => write!(f, "{}", e.message()),
+        }
+    }
+}
+
+impl std::error::Error for GitError {}
+
+impl From<git2::Error> for GitError {
+    fn from(e: git2::Error) -> Self {
+        GitError::Git(e)
+    }
+}
+
+/// Ветки и отложенные изменения репозитория.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+pub struct BranchInfo {
+    /// Текущая ветка; `None`, если `HEAD` отсоединён.
+    pub current: Option<String>,
+    /// Коммит `HEAD`; `None` до первого коммита.
+    pub head: Option<String>,
+    /// Локальные ветки по алфавиту.
+    pub branches: Vec<String>,
+    /// Сообщения отложенных изменений, начиная с последних.
+    pub stashes: Vec<String>,
+}
+
+/// Ветки и отложенные изменения репозитория каталога `root`.
+pub fn branch_info(root: &Path) -> Result<BranchInfo, GitError> {
+    let mut repo = Repository::discover(root)?;
+    let current = repo
+        .find_reference("HEAD")?
+        .symbolic_target()
+        .and_then(|t| t.strip_prefix("refs/heads/"))
+        .map(str::to_string);
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|h| h.target())
+        .map(|oid| oid.to_string());
+    let mut branches = Vec::new();
+    for branch in repo.branches(Some(BranchType::Local))? {
+        if let Some(name) = branch?.0.name()? {
+            branches.push(name.to_string());
+        }
+    }
+    branches.sort();
+    let mut stashes = Vec::new();
+    repo.stash_foreach(|_, message, _| {
+        stashes.push(message.to_string());
+        true
+    })?;
+    Ok(BranchInfo {
+        current,
+        head,
+        branches,
+        stashes,
+    })
+}
+
+/// Переключается на локальную ветку `name`. Незакоммиченные изменения
+/// переносятся, если не мешают переключению; иначе возвращается
+/// [`GitError::Conflicts`] и рабочая копия не меняется.
+pub fn checkout_branch(root: &Path, name: &str) -> Result<(), GitError> {
+    let repo = Repository::discover(root)?;
+    let branch = match repo.find_branch(name, BranchType::Local) {
+        Ok(branch) => branch,
+        Err(e) if e.code() == ErrorCode::NotFound => {
+            return Err(GitError::BranchNotFound(name.to_string()))
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let refname = branch
+        .get()
+        .name()
+        .ok_or_else(|| GitError::InvalidBranchName(name.to_string()))?
+        .to_string();
+    let tree = branch.get().peel_to_tree()?;
+    let mut conflicts = Vec::new();
+    let result = {
+        let mut checkout = CheckoutBuilder::new();
+        checkout
+            .safe()
+            .notify_on(CheckoutNotificationType::CONFLICT)
+            .notify(|_, path, _, _, _| {
+                conflicts.extend(path.map(Path::to_path_buf));
+                true
+            });
+        repo.checkout_tree(tree.as_object(), Some(&mut checkout))
+    };
+    match result {
+        Err(_) if !conflicts.is_empty() => return Err(GitError::Conflicts(conflicts)),
+        Err(e) => return Err(e.into()),
+        Ok(()) => {}
+    }
+    repo.set_head(&refname)?;
+    Ok(())
+}
+
+/// Создаёт ветку `name` от `HEAD` и, если `checkout`, переключается на неё.
+pub fn create_branch(root: &Path, name: &str, checkout: bool) -> Result<(), GitError> {
+    if !Branch::name_is_valid(name)? {
+        return Err(GitError::InvalidBranchName(name.to_string()));
+    }
+    let repo = Repository::discover(root)?;
+    if repo.find_branch(name, BranchType::Local).is_ok() {
+        return Err(GitError::BranchExists(name.to_string()));
+    }
+    let head = repo.head()?.peel_to_commit()?;
+    let branch = repo.branch(name, &head, false)?;
+    if checkout {
+        // Новая ветка указывает на тот же коммит, поэтому рабочую копию
+        // менять не нужно.
+        if let Some(refname) = branch.get().name() {
+            repo.set_head(refname)?;
+        }
+    }
+    Ok(())
+}
+
+/// Откладывает изменения отслеживаемых файлов, как `git stash`.
+pub fn stash(root: &Path, message: Option<&str>) -> Result<(), GitError> {
+    let mut repo = Repository::discover(root)?;
+    let sig = signature(&repo)?;
+    match repo.stash_save2(&sig, message, None) {
+        Ok(_) => Ok(()),
+        Err(e) if e.code() == ErrorCode::NotFound => Err(GitError::NothingToStash),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Применяет последние отложенные изменения и удаляет их из списка, как
+/// `git stash pop`. При конфликте изменения остаются в списке.
+pub fn stash_pop(root: &Path) -> Result<(), GitError> {
+    let mut repo = Repository::discover(root)?;
+    let mut count = 0;
+    repo.stash_foreach(|_, _, _| {
+        count += 1;
+        false
+    })?;
+    if count == 0 {
+        return Err(GitError::NoStash);
+    }
+    match repo.stash_pop(0, None) {
+        Ok(()) => Ok(()),
+        Err(e) if matches!(e.code(), ErrorCode::Conflict | ErrorCode::MergeConflict) => {
+            Err(GitError::StashConflict)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
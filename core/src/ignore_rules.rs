@@ -0,0 +1,120 @@
+//! Правила исключения файлов проекта.
+//!
+//! Шаблоны читаются из файла [`IGNORE_FILE`] в корне проекта и используют
+//! синтаксис `.gitignore`. Исключённые файлы и каталоги не разбираются,
+//! не индексируются поиском и не отслеживаются наблюдателем.
+//!
+//! ```text
+//! # .multicode/ignore
+//! target/
+//! vendor/
+//! *.min.js
+//! !vendor/keep.rs
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use tracing::warn;
+
+use crate::meta::backup::BACKUP_DIR;
+
+/// Путь к файлу правил относительно корня проекта.
+pub const IGNORE_FILE: &str = ".multicode/ignore";
+
+/// Набор правил исключения для одного корня проекта.
+#[derive(Debug, Clone)]
+pub struct IgnoreRules {
+    root: PathBuf,
+    matcher: Gitignore,
+}
+
+impl IgnoreRules {
+    /// Загружает правила из `root/.multicode/ignore`.
+    ///
+    /// Отсутствующий файл даёт пустой набор правил, некорректные строки
+    /// пропускаются с предупреждением в журнал.
+    pub fn load(root: &Path) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+        let path = root.join(IGNORE_FILE);
+        if path.exists() {
+            if let Some(e) = builder.add(&path) {
+                warn!("{}: {e}", path.display());
+            }
+        }
+        Self::build(root, builder)
+    }
+
+    /// Создаёт правила из списка шаблонов в синтаксисе `.gitignore`.
+    pub fn from_patterns(root: &Path, patterns: &[&str]) -> Self {
+        let mut builder = GitignoreBuilder::new(root);
+        for pattern in patterns {
+            if let Err(e) = builder.add_line(None, pattern) {
+                warn!("{pattern}: {e}");
+            }
+        }
+        Self::build(root, builder)
+    }
+
+    fn build(root: &Path, builder: GitignoreBuilder) -> Self {
+        let matcher = builder.build().unwrap_or_else(|e| {
+            warn!("{e}");
+            Gitignore::empty()
+        });
+        Self {
+            root: root.to_path_buf(),
+            matcher,
+        }
+    }
+
+    /// Нет ни одного правила.
+    pub fn is_empty(&self) -> bool {
+        self.matcher.is_empty()
+    }
+
+    /// Проверяет, исключён ли путь `path` самим правилом или через один из
+    /// родительских каталогов. Пути вне корня проекта не исключаются,
+    /// каталог резервных копий исключается всегда.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let path = match path.strip_prefix(&self.root) {
+            Ok(rel) => rel,
+            Err(_) if path.is_relative() => path,
+            Err(_) => return false,
+        };
+        // Резервные копии всегда исключаются, иначе поиск находил бы дубликаты.
+        if path.starts_with(BACKUP_DIR) {
+            return true;
+        }
+        if self.matcher.is_empty() || path.as_os_str().is_empty() {
+            return false;
+        }
+        self.matcher
+            .matched_path_or_any_parents(path, is_dir)
+            .is_ignore()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_directories_and_negations() {
+        let root = Path::new("/project");
+        let rules = IgnoreRules::from_patterns(root, &["target/", "*.min.js", "!keep.min.js"]);
+        assert!(rules.is_ignored(Path::new("/project/target"), true));
+        assert!(rules.is_ignored(Path::new("/project/target/debug/a.rs"), false));
+        assert!(rules.is_ignored(Path::new("/project/web/app.min.js"), false));
+        assert!(!rules.is_ignored(Path::new("/project/web/keep.min.js"), false));
+        assert!(!rules.is_ignored(Path::new("/project/src/main.rs"), false));
+        assert!(!rules.is_ignored(Path::new("/elsewhere/target/a.rs"), false));
+    }
+
+    #[test]
+    fn missing_file_gives_empty_rules() {
+        let dir = std::env::temp_dir().join("multicode-ignore-missing");
+        let rules = IgnoreRules::load(&dir);
+        assert!(rules.is_empty());
+        assert!(!rules.is_ignored(&dir.join("a.rs"), false));
+    }
+}
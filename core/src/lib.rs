@@ -1,36 +1,65 @@
 //! Ядро библиотеки, предоставляющее парсинг языков, работу с метаданными и другие утилиты.
 
+pub mod ai;
+pub mod analysis;
+pub mod anchor;
 pub mod blocks;
+pub mod breakpoints;
+pub mod build_tasks;
+#[cfg(feature = "git")]
+pub mod cochange;
+pub mod codeowners;
 pub mod config;
+pub mod coverage;
 pub mod debugger;
 #[cfg(feature = "export")]
 pub mod export;
+pub mod file_io;
+pub mod format;
 #[cfg(feature = "git")]
 pub mod git;
 pub mod i18n;
+pub mod ignore_rules;
+pub mod license;
+pub mod lint;
 pub mod meta;
+pub mod offline_queue;
 pub mod parser;
+pub mod policy;
+#[cfg(feature = "git")]
+pub mod precommit;
+pub mod project_env;
+pub mod recent_blocks;
+pub mod runconfig;
+pub mod saved_search;
 pub mod search;
+pub mod shell;
+pub mod tasks;
+pub mod testrun;
+pub mod text;
 pub mod viz_lint;
+pub mod watch;
 
-pub use blocks::{parse_blocks, upsert_meta};
+pub use anchor::{Anchor, AnchorKind, NamedAnchor};
+pub use blocks::{parse_blocks, parse_dir, upsert_meta};
 
 use crate::meta::AiNote;
 use once_cell::sync::Lazy;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Mutex;
 use tree_sitter::Tree;
 
 /// Порт блока на холсте.
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct Port {
     pub x: f64,
     pub y: f64,
 }
 
 /// Информация о блоке, дополненная визуальными метаданными.
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, JsonSchema)]
 pub struct BlockInfo {
     pub visual_id: String,
     #[serde(default)]
@@ -39,9 +68,15 @@ pub struct BlockInfo {
     pub translations: HashMap<String, String>,
     pub range: (usize, usize),
     #[serde(default)]
-    pub anchors: Vec<(usize, usize)>,
+    pub anchors: Vec<Anchor>,
     pub x: f64,
     pub y: f64,
+    /// Ширина блока на холсте из метаданных; `None` — ширина по умолчанию.
+    #[serde(default)]
+    pub width: Option<f64>,
+    /// Высота блока на холсте из метаданных; `None` — высота по умолчанию.
+    #[serde(default)]
+    pub height: Option<f64>,
     #[serde(default)]
     pub ports: Vec<Port>,
     pub ai: Option<AiNote>,
@@ -50,6 +85,20 @@ pub struct BlockInfo {
     pub tags: Vec<String>,
     #[serde(default)]
     pub links: Vec<String>,
+    /// Цвет блока из метаданных, см. [`meta::style`].
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Значок блока из метаданных, см. [`meta::style`].
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Документирующий комментарий перед блоком, см. [`blocks::enrich::DOCS`].
+    #[serde(default)]
+    pub doc: Option<String>,
+}
+
+/// JSON-схема [`BlockInfo`] — формата, в котором блоки отдаются наружу.
+pub fn block_schema() -> schemars::schema::RootSchema {
+    schemars::schema_for!(BlockInfo)
 }
 
 /// Сохранённые деревья разбора для открытых документов.
@@ -75,6 +124,13 @@ pub fn update_document_tree(id: String, tree: Tree) {
     }
 }
 
+/// Удаляет сохранённое [`Tree`] закрытого документа.
+pub fn remove_document_tree(id: &str) {
+    if let Ok(mut trees) = DOCUMENT_TREES.lock() {
+        trees.remove(id);
+    }
+}
+
 /// Возвращает кэшированные блоки, если содержимое совпадает.
 pub fn get_cached_blocks(key: &str, content: &str) -> Option<Vec<BlockInfo>> {
     if let Ok(cache) = BLOCK_CACHE.lock() {
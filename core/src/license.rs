@@ -0,0 +1,324 @@
+//! Заголовок лицензии в начале исходных файлов.
+//!
+//! Текст заголовка задаёт секция `[license]` файла `multicode.toml`
+//! ([`LicenseConfig`]). Заголовок записывается комментариями языка файла
+//! после строки `#!`, объявления кодировки Python и записей
+//! `@VISUAL_META` в начале файла, которые остаются на своих местах.
+//!
+//! Заголовок считается актуальным, если совпадает с шаблоном с любым годом
+//! или диапазоном лет. Первый комментарий файла со словами `copyright`,
+//! `license` или `SPDX` считается устаревшим заголовком: [`LicenseHeader::apply`]
+//! заменяет его, сохраняя указанный год, а если такого комментария нет —
+//! вставляет новый заголовок. Файлы без заголовка отмечает
+//! `multicode lint` по правилу [`LICENSE_HEADER`].
+
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::blocks::{lang_from_extension, source_files};
+use crate::config::LicenseConfig;
+use crate::file_io;
+use crate::lint::{Finding, Severity, Span};
+use crate::meta::backup;
+use crate::text::PositionIndex;
+
+/// В начале файла нет заголовка лицензии или он отличается от шаблона.
+pub const LICENSE_HEADER: &str = "license/header";
+
+/// Год или диапазон лет в заголовке: `2023`, `2019-2024`.
+const YEAR_PATTERN: &str = r"\d{4}(?:\s*[-–]\s*\d{4})?";
+
+static YEAR: Lazy<Regex> = Lazy::new(|| Regex::new(&format!(r"\b{YEAR_PATTERN}\b")).unwrap());
+
+/// Состояние заголовка лицензии в файле.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderState {
+    Current,
+    Missing,
+    Outdated,
+}
+
+/// Начало и конец строки комментария языка.
+type CommentStyle = (&'static str, &'static str);
+
+fn comment_style(file: &Path) -> Option<CommentStyle> {
+    let lang = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(lang_from_extension)?;
+    Some(match lang {
+        "python" => ("#", ""),
+        "css" => ("/*", "*/"),
+        "html" => ("<!--", "-->"),
+        _ => ("//", ""),
+    })
+}
+
+/// Шаблон заголовка с подставленным владельцем.
+#[derive(Debug, Clone)]
+pub struct LicenseHeader {
+    lines: Vec<String>,
+    year: String,
+}
+
+/// Разметка начала файла: строки, которые заголовок пропускает, и первый
+/// комментарий после них.
+struct Prologue {
+    /// Байтовое смещение, с которого начинается заголовок.
+    start: usize,
+    /// Первый комментарий до пустой строки и его текст без знаков
+    /// комментария.
+    comment: Range<usize>,
+    text: Vec<String>,
+    /// После комментария или места вставки идёт непустая строка.
+    followed: bool,
+}
+
+impl LicenseHeader {
+    /// Заголовок по настройкам `config`; `None`, если текст не задан.
+    pub fn new(config: &LicenseConfig) -> Option<Self> {
+        let header = config.header.as_deref()?.trim_end();
+        let year = config.year.unwrap_or_else(|| Utc::now().year() as u32);
+        Some(Self {
+            lines: header
+                .lines()
+                .map(|l| l.replace("{owner}", &config.owner))
+                .collect(),
+            year: year.to_string(),
+        })
+    }
+
+    /// Заголовок из секции `[license]` файла `multicode.toml` проекта `root`.
+    pub fn load(root: &Path) -> Option<Self> {
+        Self::new(&LicenseConfig::load(root))
+    }
+
+    /// Строки заголовка с годом `year`.
+    fn text(&self, year: &str) -> Vec<String> {
+        self.lines
+            .iter()
+            .map(|l| l.replace("{year}", year))
+            .collect()
+    }
+
+    /// Совпадают ли строки `text` с шаблоном при любом годе.
+    fn matches(&self, text: &[String]) -> bool {
+        text.len() >= self.lines.len()
+            && self.lines.iter().zip(text).all(|(line, actual)| {
+                let pattern = regex::escape(line).replace(r"\{year\}", YEAR_PATTERN);
+                Regex::new(&format!("^{pattern}$")).is_ok_and(|re| re.is_match(actual))
+            })
+    }
+
+    fn state_of(&self, prologue: &Prologue) -> HeaderState {
+        if self.matches(&prologue.text) {
+            HeaderState::Current
+        } else if is_license(&prologue.text) {
+            HeaderState::Outdated
+        } else {
+            HeaderState::Missing
+        }
+    }
+
+    /// Состояние заголовка в файле `file`; `None`, если для файлов этого
+    /// вида заголовок не пишется.
+    pub fn state(&self, file: &Path, content: &str) -> Option<HeaderState> {
+        let prologue = prologue(content, comment_style(file)?);
+        Some(self.state_of(&prologue))
+    }
+
+    /// Текст файла с вставленным или обновлённым заголовком; `None`, если
+    /// заголовок актуален или файл не поддерживается.
+    pub fn apply(&self, file: &Path, content: &str) -> Option<String> {
+        let style = comment_style(file)?;
+        let prologue = prologue(content, style);
+        let state = self.state_of(&prologue);
+        if state == HeaderState::Current {
+            return None;
+        }
+        let newline = if content.contains("\r\n") {
+            "\r\n"
+        } else {
+            "\n"
+        };
+        // Устаревший заголовок сохраняет свой год или диапазон лет.
+        let year = match state {
+            HeaderState::Outdated => prologue.text.iter().find_map(|l| YEAR.find(l)),
+            _ => None,
+        }
+        .map_or(self.year.clone(), |m| m.as_str().to_string());
+        let mut header: String = self
+            .text(&year)
+            .iter()
+            .map(|line| comment_line(style, line) + newline)
+            .collect();
+        let range = if state == HeaderState::Outdated {
+            prologue.comment
+        } else {
+            if prologue.followed {
+                header.push_str(newline);
+            }
+            if prologue.start == content.len() && !content.is_empty() && !content.ends_with('\n') {
+                header.insert_str(0, newline);
+            }
+            prologue.start..prologue.start
+        };
+        let mut result = content.to_string();
+        result.replace_range(range, &header);
+        Some(result)
+    }
+
+    /// Замечание о заголовке файла `file` для `multicode lint`.
+    pub fn check_content(&self, file: &Path, content: &str) -> Option<Finding> {
+        let prologue = prologue(content, comment_style(file)?);
+        let (message, range) = match self.state_of(&prologue) {
+            HeaderState::Current => return None,
+            HeaderState::Missing => ("нет заголовка лицензии", prologue.start..prologue.start),
+            HeaderState::Outdated => ("заголовок лицензии отличается от шаблона", prologue.comment),
+        };
+        Some(Finding {
+            rule: LICENSE_HEADER,
+            severity: Severity::Warning,
+            message: message.into(),
+            file: file.to_path_buf(),
+            span: Some(Span::of(&PositionIndex::new(content), range)),
+        })
+    }
+}
+
+fn comment_line((open, close): CommentStyle, line: &str) -> String {
+    match (line.is_empty(), close.is_empty()) {
+        (true, true) => open.to_string(),
+        (true, false) => format!("{open} {close}"),
+        (_, true) => format!("{open} {line}"),
+        _ => format!("{open} {line} {close}"),
+    }
+}
+
+/// Текст строки комментария без знаков комментария; `None`, если строка
+/// не комментарий или документирующий комментарий Rust.
+fn comment_text((open, close): CommentStyle, line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.starts_with("///") || line.starts_with("//!") || line.contains("@VISUAL_META") {
+        return None;
+    }
+    let text = line.strip_prefix(open)?;
+    let text = text.strip_suffix(close).unwrap_or(text);
+    Some(text.trim().to_string())
+}
+
+fn is_license(text: &[String]) -> bool {
+    let text = text.join("\n").to_lowercase();
+    ["copyright", "license", "licence", "spdx"]
+        .iter()
+        .any(|w| text.contains(w))
+}
+
+fn prologue(content: &str, style: CommentStyle) -> Prologue {
+    let mut lines = content
+        .split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset;
+            *offset += line.len();
+            Some((start, line.trim_end_matches(['\r', '\n'])))
+        })
+        .enumerate()
+        .peekable();
+    let mut start = content.len();
+    while let Some(&(i, (offset, line))) = lines.peek() {
+        let skipped = (i == 0 && line.starts_with("#!"))
+            || (i < 2 && style.0 == "#" && line.starts_with('#') && line.contains("coding"))
+            || line.contains("@VISUAL_META");
+        if !skipped {
+            start = offset;
+            break;
+        }
+        lines.next();
+    }
+    let mut end = start;
+    let mut text = Vec::new();
+    let mut followed = false;
+    for (_, (offset, line)) in lines {
+        match comment_text(style, line) {
+            Some(t) => {
+                text.push(t);
+                end = offset + line.len();
+            }
+            None => {
+                followed = !line.trim().is_empty();
+                break;
+            }
+        }
+    }
+    // Строка комментария заменяется вместе с переводом строки.
+    let end = if end > start {
+        content[end..]
+            .find('\n')
+            .map_or(content.len(), |i| end + i + 1)
+    } else {
+        end
+    };
+    Prologue {
+        start,
+        comment: start..end,
+        text,
+        followed,
+    }
+}
+
+/// Итог проверки или исправления заголовков проекта.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LicenseReport {
+    /// Файлы без актуального заголовка относительно корня проекта.
+    pub files: Vec<PathBuf>,
+    /// Резервная копия изменённых файлов; `None`, если ничего не менялось.
+    pub backup: Option<String>,
+}
+
+/// Замечания о заголовках всех исходных файлов проекта `root`. Без
+/// секции `[license]` список пуст.
+pub fn check_project(root: &Path) -> Vec<Finding> {
+    let Some(header) = LicenseHeader::load(root) else {
+        return Vec::new();
+    };
+    source_files(root)
+        .iter()
+        .filter_map(|path| {
+            let content = file_io::read_to_string(path).ok()?;
+            header.check_content(path.strip_prefix(root).unwrap_or(path), &content)
+        })
+        .collect()
+}
+
+/// Вставляет или обновляет заголовки во всех исходных файлах проекта
+/// `root`. Изменённые файлы предварительно сохраняются в резервную копию.
+pub fn fix(root: &Path) -> io::Result<LicenseReport> {
+    let Some(header) = LicenseHeader::load(root) else {
+        return Ok(LicenseReport::default());
+    };
+    let mut changed = Vec::new();
+    for path in source_files(root) {
+        let content = file_io::read_to_string(&path)?;
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        if let Some(fixed) = header.apply(&rel, &content) {
+            changed.push((rel, path, fixed));
+        }
+    }
+    if changed.is_empty() {
+        return Ok(LicenseReport::default());
+    }
+    let paths: Vec<&Path> = changed.iter().map(|(_, path, _)| path.as_path()).collect();
+    let backup = backup::backup_files(root, &paths)?;
+    for (_, path, fixed) in &changed {
+        file_io::write_preserving(path, fixed)?;
+    }
+    Ok(LicenseReport {
+        files: changed.into_iter().map(|(file, _, _)| file).collect(),
+        backup: Some(backup),
+    })
+}
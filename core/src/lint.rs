@@ -0,0 +1,369 @@
+//! Проверка проекта: комментарии `@viz`, метаданные `@VISUAL_META` и
+//! правила безопасности ([`security`]).
+//!
+//! Замечания [`Finding`] содержат идентификатор правила, уровень и
+//! положение в файле, поэтому их можно выгрузить в SARIF 2.1
+//! ([`to_sarif`]) для GitHub code scanning и других инструментов.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::analysis::dead_code::{self, DeadCode};
+use crate::analysis::security;
+use crate::blocks::{lang_from_extension, source_files};
+use crate::file_io;
+use crate::license::{self, LicenseHeader};
+use crate::meta::{self, comment_detector, VisualMeta};
+use crate::parser::viz_comments::{parse_viz_comments, viz_comment_spans};
+use crate::parser::Lang;
+use crate::policy::{self, Policies};
+use crate::text::PositionIndex;
+use crate::viz_lint;
+
+/// Содержимое `@VISUAL_META` не удалось разобрать.
+pub const META_INVALID_JSON: &str = "meta/invalid-json";
+/// Запись метаданных не прошла [`meta::validate`].
+pub const META_INVALID_FIELD: &str = "meta/invalid-field";
+/// Идентификатор `@VISUAL_META` повторяется в файле
+/// ([`meta::duplicate_ids`]). Такие замечания выдаёт не [`lint_content`],
+/// а `multicode meta fix --check`.
+pub const META_DUPLICATE_ID: &str = "meta/duplicate-id";
+/// Записи `@VISUAL_META` наследуют друг друга через `extends` по кругу
+/// ([`meta::inheritance::extends_cycles`]).
+pub const META_EXTENDS_CYCLE: &str = "meta/extends-cycle";
+
+/// Уровень замечания; совпадает с уровнями SARIF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+        }
+    }
+}
+
+/// Правило проверки.
+#[derive(Debug, Clone, Copy)]
+pub struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+}
+
+/// Все правила, которые могут встретиться в замечаниях.
+pub const RULES: &[Rule] = &[
+    Rule {
+        id: viz_lint::UNKNOWN_OP,
+        severity: Severity::Error,
+        description: "Операция узла @viz не поддерживается",
+    },
+    Rule {
+        id: viz_lint::MISSING_OP,
+        severity: Severity::Error,
+        description: "У узла @viz не указана операция",
+    },
+    Rule {
+        id: viz_lint::INVALID_NODE,
+        severity: Severity::Error,
+        description: "Значение node узла @viz не является числом",
+    },
+    Rule {
+        id: viz_lint::DUPLICATE_NODE,
+        severity: Severity::Error,
+        description: "Значение node повторяется в нескольких узлах @viz",
+    },
+    Rule {
+        id: viz_lint::MISSING_NODE,
+        severity: Severity::Error,
+        description: "У узла @viz не указан node",
+    },
+    Rule {
+        id: viz_lint::UNKNOWN_INPUT,
+        severity: Severity::Warning,
+        description: "Вход узла @viz ссылается на неизвестный узел",
+    },
+    Rule {
+        id: viz_lint::UNKNOWN_OUTPUT,
+        severity: Severity::Warning,
+        description: "Выход узла @viz ссылается на неизвестный узел",
+    },
+    Rule {
+        id: META_INVALID_JSON,
+        severity: Severity::Error,
+        description: "Комментарий @VISUAL_META содержит некорректный JSON",
+    },
+    Rule {
+        id: META_INVALID_FIELD,
+        severity: Severity::Error,
+        description: "Поле записи @VISUAL_META имеет недопустимое значение",
+    },
+    Rule {
+        id: META_EXTENDS_CYCLE,
+        severity: Severity::Error,
+        description: "Записи @VISUAL_META наследуют друг друга через extends по кругу",
+    },
+    Rule {
+        id: policy::REQUIRED_META,
+        severity: Severity::Warning,
+        description: "Блоку не хватает метаданных, которых требует политика [[policy]]",
+    },
+    Rule {
+        id: policy::METRIC_LIMIT,
+        severity: Severity::Warning,
+        description: "Метрика блока превышает ограничение политики [[policy]]",
+    },
+    Rule {
+        id: dead_code::UNUSED_FUNCTION,
+        severity: Severity::Warning,
+        description: "Функция нигде в проекте не используется",
+    },
+    Rule {
+        id: dead_code::ORPHAN_META,
+        severity: Severity::Warning,
+        description: "Запись @VISUAL_META не связана ни с одним блоком файла",
+    },
+    Rule {
+        id: security::HARDCODED_SECRET,
+        severity: Severity::Error,
+        description: "В коде записан пароль, токен или ключ доступа",
+    },
+    Rule {
+        id: security::DYNAMIC_EVAL,
+        severity: Severity::Warning,
+        description: "Вызов eval, exec или new Function выполняет произвольный код",
+    },
+    Rule {
+        id: security::SQL_CONCAT,
+        severity: Severity::Warning,
+        description: "SQL-запрос собирается из строк вместо параметров",
+    },
+    Rule {
+        id: security::UNSAFE_WITHOUT_COMMENT,
+        severity: Severity::Warning,
+        description: "Блок unsafe не объяснён комментарием",
+    },
+    Rule {
+        id: license::LICENSE_HEADER,
+        severity: Severity::Warning,
+        description: "В начале файла нет заголовка лицензии из секции [license]",
+    },
+];
+
+/// Положение замечания: строки и столбцы с единицы, столбцы в символах,
+/// конец не включается.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl Span {
+    /// Положение диапазона байтов `range` по индексу `index`.
+    pub(crate) fn of(index: &PositionIndex, range: std::ops::Range<usize>) -> Self {
+        let (start, end) = index.range((range.start, range.end));
+        Self {
+            start_line: start.line + 1,
+            start_column: start.column + 1,
+            end_line: end.line + 1,
+            end_column: end.column + 1,
+        }
+    }
+}
+
+/// Замечание проверки.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    pub file: PathBuf,
+    pub span: Option<Span>,
+}
+
+/// Проверяет комментарии `@viz` и `@VISUAL_META` в содержимом файла `file`
+/// и правила безопасности для языка, определённого по расширению.
+pub fn lint_content(file: &Path, content: &str) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    let index = PositionIndex::new(content);
+    let spans = viz_comment_spans(content);
+    for issue in viz_lint::lint_issues(&parse_viz_comments(content)) {
+        let span = spans.get(issue.node).map(|(line, range)| {
+            let start = index.line_start(*line).unwrap_or(0);
+            Span::of(&index, start + range.start..start + range.end)
+        });
+        findings.push(Finding {
+            rule: issue.rule,
+            severity: issue.severity,
+            message: issue.message,
+            file: file.to_path_buf(),
+            span,
+        });
+    }
+
+    // Первый комментарий каждого идентификатора: к нему привязывается
+    // замечание о кольце наследования.
+    let mut id_spans: Vec<(String, Span)> = Vec::new();
+    for (range, json) in comment_detector::extract_with_ranges(content) {
+        let span = Span::of(&index, range);
+        let mut push = |rule, message| {
+            findings.push(Finding {
+                rule,
+                severity: Severity::Error,
+                message,
+                file: file.to_path_buf(),
+                span: Some(span),
+            })
+        };
+        let Some(json) = json else {
+            push(
+                META_INVALID_JSON,
+                "не удалось распаковать метаданные".into(),
+            );
+            continue;
+        };
+        match serde_json::from_str::<VisualMeta>(&json) {
+            Ok(meta) => {
+                for e in meta::validate(&meta).err().unwrap_or_default() {
+                    push(META_INVALID_FIELD, format!("{}: {}", e.field, e.message));
+                }
+                if !id_spans.iter().any(|(id, _)| *id == meta.id) {
+                    id_spans.push((meta.id, span));
+                }
+            }
+            Err(e) => push(META_INVALID_JSON, e.to_string()),
+        }
+    }
+
+    for cycle in meta::inheritance::extends_cycles(content) {
+        let span = id_spans
+            .iter()
+            .find(|(id, _)| *id == cycle[0])
+            .map(|(_, span)| *span);
+        findings.push(Finding {
+            rule: META_EXTENDS_CYCLE,
+            severity: Severity::Error,
+            message: format!(
+                "цикл наследования extends: {} → {}",
+                cycle.join(" → "),
+                cycle[0]
+            ),
+            file: file.to_path_buf(),
+            span,
+        });
+    }
+
+    let lang = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(lang_from_extension)
+        .and_then(|l| l.parse::<Lang>().ok());
+    for issue in security::check(content, lang) {
+        findings.push(Finding {
+            rule: issue.rule,
+            severity: issue.severity,
+            message: issue.message,
+            file: file.to_path_buf(),
+            span: Some(Span::of(&index, issue.range)),
+        });
+    }
+    findings
+}
+
+/// Проверяет все исходные файлы проекта `root`, в том числе политиками
+/// метаданных и заголовком лицензии из `multicode.toml` и поиском мёртвого
+/// кода ([`dead_code`]). Пути в замечаниях указываются относительно `root`.
+pub fn lint_project(root: &Path) -> Vec<Finding> {
+    let policies = Policies::load(root);
+    let license = LicenseHeader::load(root);
+    let mut dead = DeadCode::default();
+    let mut findings = Vec::new();
+    for path in source_files(root) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let rel = path.strip_prefix(root).unwrap_or(&path);
+        findings.extend(lint_content(rel, &content));
+        findings.extend(policies.check_content(rel, &content));
+        if let Some(license) = &license {
+            findings.extend(license.check_content(rel, &content));
+        }
+        dead.add_file(rel, &content);
+    }
+    findings.extend(dead.finish().findings());
+    findings
+}
+
+/// Отчёт SARIF 2.1.0 с замечаниями `findings`.
+pub fn to_sarif(findings: &[Finding]) -> Value {
+    let rules: Vec<Value> = RULES
+        .iter()
+        .map(|r| {
+            json!({
+                "id": r.id,
+                "shortDescription": { "text": r.description },
+                "defaultConfiguration": { "level": r.severity.as_str() },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = findings
+        .iter()
+        .map(|f| {
+            let uri = f
+                .file
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let mut location = json!({
+                "artifactLocation": { "uri": uri, "uriBaseId": "%SRCROOT%" },
+            });
+            if let Some(span) = f.span {
+                location["region"] = json!({
+                    "startLine": span.start_line,
+                    "startColumn": span.start_column,
+                    "endLine": span.end_line,
+                    "endColumn": span.end_column,
+                });
+            }
+            let mut result = json!({
+                "ruleId": f.rule,
+                "level": f.severity.as_str(),
+                "message": { "text": f.message },
+                "locations": [{ "physicalLocation": location }],
+            });
+            if let Some(index) = RULES.iter().position(|r| r.id == f.rule) {
+                result["ruleIndex"] = json!(index);
+            }
+            result
+        })
+        .collect();
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "multicode",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }]
+    })
+}
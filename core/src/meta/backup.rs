@@ -0,0 +1,213 @@
+//! Резервные копии файлов перед разрушающими операциями с метаданными.
+//!
+//! Перед перезаписью файл копируется в `.multicode/backups/<время>/<путь>`
+//! внутри корня проекта. Старые копии удаляются согласно
+//! [`BackupConfig`](crate::config::BackupConfig), а [`restore`] возвращает
+//! сохранённые версии на место.
+
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+
+use super::{fix_all, remove_all};
+use crate::config::BackupConfig;
+use crate::file_io;
+
+/// Каталог резервных копий относительно корня проекта.
+pub const BACKUP_DIR: &str = ".multicode/backups";
+
+/// Формат имени каталога одной резервной копии.
+const STAMP_FORMAT: &str = "%Y%m%d-%H%M%S%.3f";
+
+/// Одна резервная копия: момент создания и сохранённые файлы.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    /// Имя каталога копии, оно же идентификатор для [`restore`].
+    pub id: String,
+    pub created_at: DateTime<Utc>,
+    /// Пути файлов относительно корня проекта.
+    pub files: Vec<PathBuf>,
+}
+
+fn backups_root(root: &Path) -> PathBuf {
+    root.join(BACKUP_DIR)
+}
+
+/// Путь `path` относительно корня проекта. Пути с `..` или с корнем после
+/// отбрасывания `root` отклоняются: иначе копия попала бы за пределы
+/// каталога резервной копии.
+fn relative(root: &Path, path: &Path) -> io::Result<PathBuf> {
+    let outside = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} находится вне проекта", path.display()),
+        )
+    };
+    let rel = if path.is_absolute() {
+        path.strip_prefix(root).map_err(|_| outside())?
+    } else {
+        path
+    };
+    let escapes = rel.components().any(|c| {
+        matches!(
+            c,
+            Component::ParentDir | Component::RootDir | Component::Prefix(_)
+        )
+    });
+    if escapes {
+        return Err(outside());
+    }
+    Ok(rel.to_path_buf())
+}
+
+/// Копирует файлы `paths` в новую резервную копию и применяет политику
+/// хранения. Возвращает идентификатор созданной копии.
+pub fn backup_files(root: &Path, paths: &[&Path]) -> io::Result<String> {
+    let mut now = Utc::now();
+    let mut id = now.format(STAMP_FORMAT).to_string();
+    // Две операции в одну миллисекунду не должны перезаписать друг друга.
+    while backups_root(root).join(&id).exists() {
+        now += Duration::milliseconds(1);
+        id = now.format(STAMP_FORMAT).to_string();
+    }
+    let dir = backups_root(root).join(&id);
+    for path in paths {
+        let rel = relative(root, path)?;
+        let target = dir.join(&rel);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(root.join(&rel), target)?;
+    }
+    prune(root, &BackupConfig::load(root))?;
+    Ok(id)
+}
+
+/// Список резервных копий проекта, от новых к старым.
+pub fn list_backups(root: &Path) -> io::Result<Vec<Backup>> {
+    let dir = backups_root(root);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut out = Vec::new();
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let id = entry.file_name().to_string_lossy().to_string();
+        let Ok(created) = NaiveDateTime::parse_from_str(&id, STAMP_FORMAT) else {
+            continue;
+        };
+        let mut files: Vec<PathBuf> = walkdir::WalkDir::new(entry.path())
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_type().is_file())
+            .filter_map(|e| e.path().strip_prefix(entry.path()).ok().map(Path::to_path_buf))
+            .collect();
+        files.sort();
+        out.push(Backup {
+            id,
+            created_at: created.and_utc(),
+            files,
+        });
+    }
+    out.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(out)
+}
+
+/// Восстанавливает файлы из резервной копии `id`.
+///
+/// Если `file` задан, восстанавливается только он. Текущие версии
+/// перезаписываемых файлов предварительно сохраняются в новую копию, чтобы
+/// восстановление тоже можно было отменить. Возвращает восстановленные пути.
+pub fn restore(root: &Path, id: &str, file: Option<&Path>) -> io::Result<Vec<PathBuf>> {
+    let backup = list_backups(root)?
+        .into_iter()
+        .find(|b| b.id == id)
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("резервная копия {id} не найдена"))
+        })?;
+    let files: Vec<PathBuf> = match file {
+        Some(f) => {
+            let rel = relative(root, f)?;
+            if !backup.files.contains(&rel) {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{} отсутствует в копии {id}", rel.display()),
+                ));
+            }
+            vec![rel]
+        }
+        None => backup.files.clone(),
+    };
+    // Содержимое читается заранее: новая копия может вытеснить исходную
+    // при очистке по политике хранения.
+    let source = backups_root(root).join(id);
+    let saved = files
+        .iter()
+        .map(|rel| fs::read(source.join(rel)))
+        .collect::<io::Result<Vec<_>>>()?;
+    let existing: Vec<&Path> = files
+        .iter()
+        .filter(|f| root.join(f).exists())
+        .map(PathBuf::as_path)
+        .collect();
+    if !existing.is_empty() {
+        backup_files(root, &existing)?;
+    }
+    for (rel, bytes) in files.iter().zip(saved) {
+        let target = root.join(rel);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(target, bytes)?;
+    }
+    Ok(files)
+}
+
+/// Удаляет копии, выходящие за пределы политики хранения.
+pub fn prune(root: &Path, config: &BackupConfig) -> io::Result<()> {
+    let backups = list_backups(root)?;
+    let cutoff = config
+        .max_age_days
+        .map(|days| Utc::now() - Duration::days(i64::from(days)));
+    for (i, backup) in backups.iter().enumerate() {
+        let too_many = i >= config.keep.max(1);
+        let too_old = cutoff.is_some_and(|c| backup.created_at < c) && i > 0;
+        if too_many || too_old {
+            fs::remove_dir_all(backups_root(root).join(&backup.id))?;
+        }
+    }
+    Ok(())
+}
+
+fn rewrite_with_backup(
+    root: &Path,
+    path: &Path,
+    op: impl FnOnce(&str) -> String,
+) -> io::Result<bool> {
+    let full = root.join(relative(root, path)?);
+    let content = file_io::read_to_string(&full)?;
+    let updated = op(&content);
+    if updated == content {
+        return Ok(false);
+    }
+    backup_files(root, &[full.as_path()])?;
+    file_io::write_preserving(&full, &updated)?;
+    Ok(true)
+}
+
+/// Удаляет из файла все комментарии метаданных, предварительно сохранив копию.
+///
+/// Возвращает `false`, если файл не изменился и копия не создавалась.
+pub fn remove_all_in_file(root: &Path, path: &Path) -> io::Result<bool> {
+    rewrite_with_backup(root, path, remove_all)
+}
+
+/// Исправляет метаданные файла через [`fix_all`], предварительно сохранив копию.
+pub fn fix_all_in_file(root: &Path, path: &Path) -> io::Result<bool> {
+    rewrite_with_backup(root, path, fix_all)
+}
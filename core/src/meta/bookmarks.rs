@@ -0,0 +1,53 @@
+//! Закладки на блоках, хранящиеся как служебный тег в [`VisualMeta`].
+
+use super::{read_all, VisualMeta};
+
+/// Тег, которым помечаются блоки с закладкой.
+pub const BOOKMARK_TAG: &str = "bookmark";
+
+/// Проверяет, содержит ли список тегов отметку закладки.
+pub fn is_bookmarked(tags: &[String]) -> bool {
+    tags.iter().any(|t| t == BOOKMARK_TAG)
+}
+
+/// Переключает закладку в списке тегов и возвращает новое состояние.
+pub fn toggle(tags: &mut Vec<String>) -> bool {
+    if is_bookmarked(tags) {
+        tags.retain(|t| t != BOOKMARK_TAG);
+        false
+    } else {
+        tags.push(BOOKMARK_TAG.to_string());
+        true
+    }
+}
+
+/// Возвращает все метаданные документа, отмеченные закладкой.
+pub fn list(content: &str) -> Vec<VisualMeta> {
+    read_all(content)
+        .into_iter()
+        .filter(|m| is_bookmarked(&m.tags))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_adds_and_removes_tag() {
+        let mut tags = vec!["alpha".to_string()];
+        assert!(toggle(&mut tags));
+        assert!(is_bookmarked(&tags));
+        assert!(!toggle(&mut tags));
+        assert_eq!(tags, vec!["alpha"]);
+    }
+
+    #[test]
+    fn list_returns_only_bookmarked_metas() {
+        let content = "// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"tags\":[\"bookmark\"]}\n\
+                       // @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0}\n";
+        let marked = list(content);
+        assert_eq!(marked.len(), 1);
+        assert_eq!(marked[0].id, "a");
+    }
+}
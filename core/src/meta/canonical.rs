@@ -0,0 +1,123 @@
+//! Каноническая сериализация [`VisualMeta`].
+//!
+//! Ключи объектов сортируются на всех уровнях, поля верхнего уровня со
+//! значениями по умолчанию (пустые списки и словари, `null`) опускаются,
+//! а `-0.0` записывается как `0.0`. Одинаковые метаданные всегда дают
+//! одинаковую строку, поэтому повторное сохранение не создаёт шума в
+//! истории версий.
+
+use serde_json::{Map, Number, Value};
+
+use super::VisualMeta;
+
+/// Поля, которые не учитываются при сравнении содержимого.
+const VOLATILE_FIELDS: &[&str] = &["updated_at"];
+
+/// Сериализует `meta` в канонический JSON.
+pub fn to_canonical_json(meta: &VisualMeta) -> serde_json::Result<String> {
+    serde_json::to_string(&canonical_value(meta)?)
+}
+
+/// Сравнивает метаданные по каноническому виду без учёта `updated_at`.
+pub fn same_content(a: &VisualMeta, b: &VisualMeta) -> bool {
+    changed_fields(a, b).is_empty()
+}
+
+/// Возвращает имена полей верхнего уровня, значения которых различаются
+/// в `old` и `new`, в алфавитном порядке. `updated_at` не учитывается.
+pub fn changed_fields(old: &VisualMeta, new: &VisualMeta) -> Vec<String> {
+    let (Ok(Value::Object(old)), Ok(Value::Object(new))) =
+        (canonical_value(old), canonical_value(new))
+    else {
+        return vec!["*".into()];
+    };
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .filter(|k| !VOLATILE_FIELDS.contains(&k.as_str()))
+        .filter(|k| old.get(*k) != new.get(*k))
+        .cloned()
+        .collect()
+}
+
+fn canonical_value(meta: &VisualMeta) -> serde_json::Result<Value> {
+    let value = serde_json::to_value(meta)?;
+    Ok(match canonicalize(value) {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .filter(|(_, v)| !is_default(v))
+                .collect(),
+        ),
+        other => other,
+    })
+}
+
+fn canonicalize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            let mut sorted = Map::new();
+            for (k, v) in entries {
+                sorted.insert(k, canonicalize(v));
+            }
+            Value::Object(sorted)
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(canonicalize).collect()),
+        Value::Number(n) => match n.as_f64() {
+            Some(f) if f == 0.0 && f.is_sign_negative() => {
+                Value::Number(Number::from_f64(0.0).unwrap_or(n))
+            }
+            _ => Value::Number(n),
+        },
+        other => other,
+    }
+}
+
+fn is_default(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn meta(value: Value) -> VisualMeta {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn sorts_keys_and_omits_defaults() {
+        let mut m = meta(json!({"id": "a", "x": -0.0, "y": 1.5}));
+        m.translations.insert("ru".into(), "б".into());
+        m.translations.insert("en".into(), "a".into());
+        m.extras = Some(json!({"z": 1, "a": {"d": 2, "c": 3}}));
+        let json = to_canonical_json(&m).unwrap();
+        assert!(!json.contains("tags"));
+        assert!(!json.contains("\"ai\""));
+        assert!(json.contains(r#""extras":{"a":{"c":3,"d":2},"z":1}"#));
+        assert!(json.contains(r#""translations":{"en":"a","ru":"б"}"#));
+        assert!(json.contains(r#""x":0.0"#));
+        let back: VisualMeta = serde_json::from_str(&json).unwrap();
+        assert!(same_content(&back, &m));
+    }
+
+    #[test]
+    fn ignores_updated_at() {
+        let a = meta(json!({"id": "a", "x": 0.0, "y": 0.0, "updated_at": "2020-01-01T00:00:00Z"}));
+        let mut b = a.clone();
+        b.updated_at = chrono::Utc::now();
+        assert!(same_content(&a, &b));
+        b.x = 1.0;
+        b.tags.push("t".into());
+        assert!(!same_content(&a, &b));
+        assert_eq!(changed_fields(&a, &b), vec!["tags", "x"]);
+    }
+}
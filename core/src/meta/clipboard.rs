@@ -0,0 +1,191 @@
+//! Буфер обмена блоков.
+//!
+//! [`copy_blocks`] сохраняет фрагмент кода выбранных блоков вместе с их
+//! метаданными, а [`paste_blocks`] вставляет его в другой документ: каждой
+//! записи выдаётся новый идентификатор, ссылки `links` и `extends` между
+//! скопированными блоками переназначаются на новые идентификаторы, ссылки
+//! на внешние блоки сохраняются как есть.
+
+use std::collections::{HashMap, HashSet};
+
+use super::{read_all, remove_all, unique_id, upsert, VisualMeta, MARKER};
+use crate::BlockInfo;
+
+/// Скопированные блоки: исходный код и метаданные.
+#[derive(Debug, Clone, Default)]
+pub struct BlockClipboard {
+    /// Код от начала первого до конца последнего выбранного блока.
+    pub code: String,
+    /// Метаданные блоков, попавших в скопированный фрагмент.
+    pub metas: Vec<VisualMeta>,
+}
+
+impl BlockClipboard {
+    pub fn is_empty(&self) -> bool {
+        self.code.is_empty() && self.metas.is_empty()
+    }
+}
+
+/// Копирует блоки с идентификаторами `selected` из `content`.
+///
+/// Фрагмент охватывает все выбранные блоки; метаданные берутся для всех
+/// блоков из `blocks`, целиком лежащих внутри фрагмента, включая вложенные.
+/// Возвращает `None`, если ни один идентификатор не найден.
+pub fn copy_blocks(
+    content: &str,
+    blocks: &[BlockInfo],
+    selected: &[String],
+) -> Option<BlockClipboard> {
+    let chosen: Vec<&BlockInfo> = blocks
+        .iter()
+        .filter(|b| selected.contains(&b.visual_id))
+        .collect();
+    let start = chosen.iter().map(|b| b.range.0).min()?;
+    let end = chosen.iter().map(|b| b.range.1).max()?;
+    let start = floor_char_boundary(content, start);
+    let end = floor_char_boundary(content, end).max(start);
+
+    let mut code = content[start..end].to_string();
+    if code.contains(MARKER) {
+        code = remove_all(&code);
+    }
+    let inside: HashSet<&str> = blocks
+        .iter()
+        .filter(|b| b.range.0 >= start && b.range.1 <= end)
+        .map(|b| b.visual_id.as_str())
+        .collect();
+    let metas = read_all(content)
+        .into_iter()
+        .filter(|m| inside.contains(m.id.as_str()))
+        .collect();
+    Some(BlockClipboard { code, metas })
+}
+
+/// Вставляет содержимое `clip` в `content` по байтовому смещению `offset`.
+///
+/// Возвращает новый текст и соответствие старых идентификаторов новым.
+pub fn paste_blocks(
+    content: &str,
+    offset: usize,
+    clip: &BlockClipboard,
+) -> (String, HashMap<String, String>) {
+    let mut taken: HashSet<String> = read_all(content).into_iter().map(|m| m.id).collect();
+    let mut ids = HashMap::new();
+    for meta in &clip.metas {
+        let base = unique_id();
+        let mut id = base.clone();
+        let mut n = 1;
+        while !taken.insert(id.clone()) {
+            id = format!("{base}-{n}");
+            n += 1;
+        }
+        ids.insert(meta.id.clone(), id);
+    }
+
+    let offset = floor_char_boundary(content, offset);
+    let mut out = String::with_capacity(content.len() + clip.code.len());
+    out.push_str(&content[..offset]);
+    out.push_str(&clip.code);
+    out.push_str(&content[offset..]);
+
+    for meta in &clip.metas {
+        let mut meta = meta.clone();
+        meta.id = ids[&meta.id].clone();
+        for link in &mut meta.links {
+            if let Some(new) = ids.get(link) {
+                *link = new.clone();
+            }
+        }
+        if let Some(new) = meta.extends.as_ref().and_then(|e| ids.get(e)) {
+            meta.extends = Some(new.clone());
+        }
+        out = upsert(&out, &meta, false);
+    }
+    (out, ids)
+}
+
+fn floor_char_boundary(content: &str, offset: usize) -> usize {
+    let mut offset = offset.min(content.len());
+    while !content.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn meta(id: &str, links: &[&str]) -> VisualMeta {
+        VisualMeta {
+            version: 1,
+            id: id.into(),
+            x: 1.0,
+            y: 2.0,
+            width: None,
+            height: None,
+            tags: vec!["t".into()],
+            links: links.iter().map(|s| s.to_string()).collect(),
+            anchors: vec![],
+            tests: vec![],
+            extends: None,
+            origin: None,
+            translations: HashMap::new(),
+            ai: None,
+            extras: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn block(id: &str, range: (usize, usize)) -> BlockInfo {
+        BlockInfo {
+            visual_id: id.into(),
+            node_id: None,
+            kind: "Function".into(),
+            translations: HashMap::new(),
+            range,
+            anchors: vec![],
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            ports: vec![],
+            ai: None,
+            tags: vec![],
+            links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
+        }
+    }
+
+    #[test]
+    fn paste_remaps_internal_links_only() {
+        let code = "fn a() {}\nfn b() {}\n";
+        let content = upsert(
+            &upsert(code, &meta("a", &["b", "ext"]), false),
+            &meta("b", &[]),
+            false,
+        );
+        let a = content.find("fn a").unwrap();
+        let b = content.find("fn b").unwrap();
+        let blocks = vec![block("a", (a, a + 9)), block("b", (b, b + 9))];
+        let clip = copy_blocks(&content, &blocks, &["a".into(), "b".into()]).unwrap();
+        assert_eq!(clip.code, "fn a() {}\nfn b() {}");
+        assert_eq!(clip.metas.len(), 2);
+
+        let (pasted, ids) = paste_blocks("fn main() {}\n", 13, &clip);
+        assert!(pasted.ends_with("fn main() {}\nfn a() {}\nfn b() {}\n"));
+        let metas = read_all(&pasted);
+        let new_a = metas.iter().find(|m| m.id == ids["a"]).unwrap();
+        assert_ne!(ids["a"], ids["b"]);
+        assert_eq!(new_a.links, vec![ids["b"].clone(), "ext".to_string()]);
+        assert_eq!(new_a.tags, vec!["t".to_string()]);
+    }
+
+    #[test]
+    fn copy_unknown_ids_gives_none() {
+        assert!(copy_blocks("fn a() {}", &[block("a", (0, 9))], &["x".into()]).is_none());
+    }
+}
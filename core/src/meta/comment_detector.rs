@@ -1,51 +1,123 @@
+use std::ops::Range;
+
 use once_cell::sync::Lazy;
-use regex::Regex;
+use regex::{Captures, Regex};
+
+use super::encoding::{self, MetaEncoding};
+use super::MARKER;
 
 static PYTHON_SINGLE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^\s*#\s*@VISUAL_META\s*(?P<json>\{.*\})\s*$").unwrap()
+    Regex::new(r"(?m)^\s*#\s*@VISUAL_META(?:\s*(?P<json>\{.*\})|:zstd\s*(?P<zstd>[A-Za-z0-9+/=]+))\s*$").unwrap()
 });
 
 static SLASH_SINGLE: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?m)^\s*//\s*@VISUAL_META\s*(?P<json>\{.*\})\s*$").unwrap()
+    Regex::new(r"(?m)^\s*//\s*@VISUAL_META(?:\s*(?P<json>\{.*\})|:zstd\s*(?P<zstd>[A-Za-z0-9+/=]+))\s*$").unwrap()
 });
 
 static C_STYLE_MULTI: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?s)/\*\s*@VISUAL_META\s*(?P<json>\{.*?\})\s*\*/").unwrap()
+    Regex::new(r"(?s)/\*\s*@VISUAL_META(?:\s*(?P<json>\{.*?\})|:zstd\s*(?P<zstd>[A-Za-z0-9+/=]+))\s*\*/").unwrap()
 });
 
 static HTML_MULTI: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?s)<!--\s*@VISUAL_META\s*(?P<json>\{.*?\})\s*-->").unwrap()
+    Regex::new(r"(?s)<!--\s*@VISUAL_META(?:\s*(?P<json>\{.*?\})|:zstd\s*(?P<zstd>[A-Za-z0-9+/=]+))\s*-->").unwrap()
 });
 
-pub fn extract_json(content: &str) -> Vec<String> {
+/// JSON комментария: как есть или после распаковки сжатой формы.
+fn payload(caps: &Captures) -> Option<String> {
+    if let Some(m) = caps.name("json") {
+        return Some(m.as_str().to_string());
+    }
+    caps.name("zstd").and_then(|m| encoding::decode(m.as_str()))
+}
+
+/// Комментарии метаданных вместе с их диапазоном в байтах, по порядку.
+/// JSON отсутствует, если сжатую форму не удалось распаковать.
+pub fn extract_with_ranges(content: &str) -> Vec<(Range<usize>, Option<String>)> {
     let mut out = Vec::new();
-    for caps in PYTHON_SINGLE.captures_iter(content) {
-        if let Some(m) = caps.name("json") {
-            out.push(m.as_str().to_string());
+    for re in [&PYTHON_SINGLE, &SLASH_SINGLE, &C_STYLE_MULTI, &HTML_MULTI] {
+        for caps in re.captures_iter(content) {
+            let whole = caps.get(0).expect("group 0");
+            out.push((whole.range(), payload(&caps)));
         }
     }
-    for caps in SLASH_SINGLE.captures_iter(content) {
-        if let Some(m) = caps.name("json") {
-            out.push(m.as_str().to_string());
-        }
+    out.sort_by_key(|(range, _)| range.start);
+    out
+}
+
+pub fn extract_json(content: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    for re in [&PYTHON_SINGLE, &SLASH_SINGLE, &C_STYLE_MULTI, &HTML_MULTI] {
+        out.extend(re.captures_iter(content).filter_map(|caps| payload(&caps)));
     }
-    for caps in C_STYLE_MULTI.captures_iter(content) {
-        if let Some(m) = caps.name("json") {
-            out.push(m.as_str().to_string());
-        }
+    out
+}
+
+/// Замена удалённого комментария: `\s*$` захватывает `\r` перед `\n`, и его
+/// нужно вернуть, чтобы строка с `\r\n` не превратилась в строку с `\n`.
+fn keep_cr(caps: &Captures) -> &'static str {
+    if caps[0].ends_with('\r') {
+        "\r"
+    } else {
+        ""
     }
-    for caps in HTML_MULTI.captures_iter(content) {
-        if let Some(m) = caps.name("json") {
-            out.push(m.as_str().to_string());
-        }
+}
+
+pub fn strip(content: &str) -> String {
+    let mut out = content.to_string();
+    for re in [&PYTHON_SINGLE, &SLASH_SINGLE, &C_STYLE_MULTI, &HTML_MULTI] {
+        out = re.replace_all(&out, keep_cr).into_owned();
     }
     out
 }
 
-pub fn strip(content: &str) -> String {
+/// Перекодирует все комментарии метаданных в `content` в кодировку `target`,
+/// сохраняя стиль комментариев и их расположение.
+pub fn convert(content: &str, target: MetaEncoding) -> String {
+    replace_payloads(content, |json, _| Some((json.to_string(), target)))
+}
+
+/// Заменяет JSON комментариев метаданных результатом `f`, сохраняя стиль,
+/// расположение и кодировку каждого комментария. `None` оставляет
+/// комментарий как есть.
+pub fn rewrite(content: &str, mut f: impl FnMut(&str) -> Option<String>) -> String {
+    replace_payloads(content, |json, encoding| {
+        f(json).map(|json| (json, encoding))
+    })
+}
+
+/// Заменяет JSON и кодировку каждого комментария результатом `f`.
+fn replace_payloads(
+    content: &str,
+    mut f: impl FnMut(&str, MetaEncoding) -> Option<(String, MetaEncoding)>,
+) -> String {
     let mut out = content.to_string();
     for re in [&PYTHON_SINGLE, &SLASH_SINGLE, &C_STYLE_MULTI, &HTML_MULTI] {
-        out = re.replace_all(&out, "").to_string();
+        out = re
+            .replace_all(&out, |caps: &Captures| {
+                let whole = &caps[0];
+                let (Some(json), Some(group)) =
+                    (payload(caps), caps.name("json").or_else(|| caps.name("zstd")))
+                else {
+                    return whole.to_string();
+                };
+                let encoding = if caps.name("zstd").is_some() {
+                    MetaEncoding::Zstd
+                } else {
+                    MetaEncoding::Json
+                };
+                let Some((json, target)) = f(&json, encoding) else {
+                    return whole.to_string();
+                };
+                let start = caps.get(0).map(|m| m.start()).unwrap_or(0);
+                let marker_end = whole.find(MARKER).map(|i| i + MARKER.len()).unwrap_or(0);
+                format!(
+                    "{}{}{}",
+                    &whole[..marker_end],
+                    encoding::encode(&json, target),
+                    &whole[group.end() - start..]
+                )
+            })
+            .into_owned();
     }
     out
 }
@@ -0,0 +1,419 @@
+//! Выгрузка метаданных в CSV и загрузка исправленной таблицы обратно.
+//!
+//! [`export_csv`] сводит записи `@VISUAL_META` проекта в таблицу со
+//! столбцами [`COLUMNS`]: её удобно править в редакторе таблиц. Теги в
+//! ячейке разделяются запятыми, `comment` — это `extras.comment`.
+//! [`plan_import`] сверяет таблицу с файлами и возвращает список правок,
+//! ничего не записывая, а [`apply_import`] записывает их, сохранив
+//! резервную копию. Таблица с ошибками не применяется целиком.
+//!
+//! Строки находятся по `id` и `file`; `kind` только для справки. Пустая
+//! ячейка перевода или комментария удаляет значение, отсутствующий столбец
+//! оставляет поле без изменений.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use chrono::Utc;
+use serde_json::Value;
+
+use super::backup::backup_files;
+use super::tags::stored_metas;
+use super::{canonical, comment_detector, validate, VisualMeta};
+use crate::blocks::{lang_from_extension, parse_blocks, source_files};
+use crate::file_io;
+
+/// Столбцы таблицы в порядке выгрузки.
+pub const COLUMNS: [&str; 7] = ["id", "file", "kind", "tags", "en", "ru", "comment"];
+
+/// Строка таблицы.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvRow {
+    pub id: String,
+    /// Путь файла относительно корня проекта через `/`.
+    pub file: String,
+    /// Вид блока записи; пусто для записи файла.
+    pub kind: String,
+    pub tags: Vec<String>,
+    pub en: String,
+    pub ru: String,
+    pub comment: String,
+}
+
+/// Изменение поля записи при загрузке таблицы.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    pub file: String,
+    pub id: String,
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {}: {:?} → {:?}",
+            self.file, self.id, self.field, self.old, self.new
+        )
+    }
+}
+
+/// Проверенная таблица, готовая к записи.
+#[derive(Debug, Clone, Default)]
+pub struct CsvImport {
+    pub changes: Vec<FieldChange>,
+    /// Новое содержимое изменённых файлов.
+    updates: Vec<(PathBuf, String)>,
+}
+
+fn comment_of(meta: &VisualMeta) -> String {
+    meta.extras
+        .as_ref()
+        .and_then(|e| e.get("comment"))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Путь `path` относительно `root` через `/`.
+pub(super) fn rel_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Строки таблицы для всех записей метаданных проекта `root`, по порядку
+/// файлов и записей в них. Поля берутся из сохранённых записей без
+/// наследования `extends`.
+pub fn export_rows(root: &Path) -> Vec<CsvRow> {
+    let mut rows = Vec::new();
+    for path in source_files(root) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let metas = stored_metas(&content);
+        if metas.is_empty() {
+            continue;
+        }
+        let kinds: HashMap<String, String> = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension)
+            .and_then(|lang| parse_blocks(content.clone(), lang.into()))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|b| (b.visual_id, b.kind))
+            .collect();
+        let file = rel_path(root, &path);
+        for meta in metas {
+            rows.push(CsvRow {
+                kind: kinds.get(&meta.id).cloned().unwrap_or_default(),
+                file: file.clone(),
+                en: meta.translations.get("en").cloned().unwrap_or_default(),
+                ru: meta.translations.get("ru").cloned().unwrap_or_default(),
+                comment: comment_of(&meta),
+                tags: meta.tags,
+                id: meta.id,
+            });
+        }
+    }
+    rows
+}
+
+/// Таблица CSV с метаданными проекта `root`, включая строку заголовка.
+pub fn export_csv(root: &Path) -> String {
+    let mut out = write_record(COLUMNS.iter().copied());
+    for row in export_rows(root) {
+        let tags = row.tags.join(", ");
+        out.push_str(&write_record([
+            row.id.as_str(),
+            &row.file,
+            &row.kind,
+            &tags,
+            &row.en,
+            &row.ru,
+            &row.comment,
+        ]));
+    }
+    out
+}
+
+fn write_record<'a>(fields: impl IntoIterator<Item = &'a str>) -> String {
+    let fields: Vec<String> = fields
+        .into_iter()
+        .map(|f| {
+            if f.contains([',', ';', '"', '\n', '\r']) {
+                format!("\"{}\"", f.replace('"', "\"\""))
+            } else {
+                f.to_string()
+            }
+        })
+        .collect();
+    format!("{}\n", fields.join(","))
+}
+
+/// Разбирает CSV: кавычки по RFC 4180, разделитель `,` или `;`, как его
+/// сохраняют табличные редакторы с русской локалью. Возвращает записи с
+/// номером строки, на которой каждая начинается.
+fn parse_records(data: &str) -> io::Result<Vec<(usize, Vec<String>)>> {
+    let data = data.strip_prefix('\u{feff}').unwrap_or(data);
+    let header = data.lines().next().unwrap_or_default();
+    let sep = if !header.contains(',') && header.contains(';') {
+        ';'
+    } else {
+        ','
+    };
+    let mut records = Vec::new();
+    let (mut record, mut field) = (Vec::new(), String::new());
+    let (mut line, mut start, mut quoted) = (1, 1, false);
+    let mut chars = data.chars().peekable();
+    while let Some(c) = chars.next() {
+        if quoted {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => quoted = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => quoted = true,
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            c if c == sep => record.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(invalid(format!("строка {start}: незакрытые кавычки")));
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    records.retain(|(_, r)| !(r.len() == 1 && r[0].trim().is_empty()));
+    Ok(records)
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Столбцы таблицы и её строки с номерами.
+type Rows = (Vec<&'static str>, Vec<(usize, CsvRow)>);
+
+/// Разбирает таблицу `data` в строки. Заголовок обязателен, столбцы могут
+/// идти в любом порядке, а из обязательных есть только `id` и `file`.
+fn parse_rows(data: &str) -> io::Result<Rows> {
+    let mut records = parse_records(data)?.into_iter();
+    let Some((_, header)) = records.next() else {
+        return Err(invalid("пустая таблица".into()));
+    };
+    let mut columns = Vec::new();
+    for name in &header {
+        let name = name.trim();
+        let Some(column) = COLUMNS.iter().find(|c| **c == name) else {
+            return Err(invalid(format!("неизвестный столбец `{name}`")));
+        };
+        if columns.contains(column) {
+            return Err(invalid(format!("столбец `{name}` повторяется")));
+        }
+        columns.push(*column);
+    }
+    for required in ["id", "file"] {
+        if !columns.contains(&required) {
+            return Err(invalid(format!("нет столбца `{required}`")));
+        }
+    }
+    let mut rows = Vec::new();
+    for (line, record) in records {
+        if record.len() != columns.len() {
+            return Err(invalid(format!(
+                "строка {line}: ячеек {}, а столбцов {}",
+                record.len(),
+                columns.len()
+            )));
+        }
+        let mut row = CsvRow::default();
+        for (column, value) in columns.iter().zip(record) {
+            match *column {
+                "id" => row.id = value.trim().to_string(),
+                "file" => row.file = value.trim().to_string(),
+                "kind" => row.kind = value,
+                "tags" => {
+                    row.tags = value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|t| !t.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }
+                "en" => row.en = value,
+                "ru" => row.ru = value,
+                _ => row.comment = value,
+            }
+        }
+        rows.push((line, row));
+    }
+    Ok((columns, rows))
+}
+
+/// Применяет к `meta` столбцы `columns` строки `row` и записывает изменения
+/// в `changes`.
+fn apply_row(
+    meta: &mut VisualMeta,
+    row: &CsvRow,
+    columns: &[&'static str],
+    changes: &mut Vec<FieldChange>,
+) {
+    let mut change = |field: &'static str, old: String, new: String| {
+        if old != new {
+            changes.push(FieldChange {
+                file: row.file.clone(),
+                id: row.id.clone(),
+                field,
+                old,
+                new,
+            });
+            true
+        } else {
+            false
+        }
+    };
+    if columns.contains(&"tags") && change("tags", meta.tags.join(", "), row.tags.join(", ")) {
+        meta.tags = row.tags.clone();
+    }
+    for (lang, value) in [("en", &row.en), ("ru", &row.ru)] {
+        let old = meta.translations.get(lang).cloned().unwrap_or_default();
+        if columns.contains(&lang) && change(lang, old, value.clone()) {
+            if value.is_empty() {
+                meta.translations.remove(lang);
+            } else {
+                meta.translations.insert(lang.to_string(), value.clone());
+            }
+        }
+    }
+    if columns.contains(&"comment") && change("comment", comment_of(meta), row.comment.clone()) {
+        let mut extras = match meta.extras.take() {
+            Some(Value::Object(obj)) => obj,
+            _ => Default::default(),
+        };
+        if row.comment.is_empty() {
+            extras.remove("comment");
+        } else {
+            extras.insert("comment".into(), Value::String(row.comment.clone()));
+        }
+        meta.extras = (!extras.is_empty()).then_some(Value::Object(extras));
+    }
+}
+
+/// Сверяет таблицу `data` с метаданными проекта `root`, ничего не
+/// записывая. Ошибки всех строк — неизвестные файлы и идентификаторы,
+/// повторы строк и значения, не прошедшие [`validate`], — собираются в
+/// одну ошибку `InvalidData`.
+pub fn plan_import(root: &Path, data: &str) -> io::Result<CsvImport> {
+    let (columns, rows) = parse_rows(data)?;
+    let mut errors = Vec::new();
+    let mut by_file: BTreeMap<String, Vec<(usize, CsvRow)>> = BTreeMap::new();
+    for (line, row) in rows {
+        let entries = by_file.entry(row.file.clone()).or_default();
+        if entries.iter().any(|(_, r)| r.id == row.id) {
+            errors.push(format!("строка {line}: запись {} повторяется", row.id));
+            continue;
+        }
+        entries.push((line, row));
+    }
+    let mut plan = CsvImport::default();
+    for (file, rows) in by_file {
+        let inside = Path::new(&file)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if !inside {
+            for (line, _) in &rows {
+                errors.push(format!("строка {line}: {file}: путь вне проекта"));
+            }
+            continue;
+        }
+        let path = root.join(&file);
+        let content = match file_io::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                for (line, _) in &rows {
+                    errors.push(format!("строка {line}: {file}: {e}"));
+                }
+                continue;
+            }
+        };
+        let metas = stored_metas(&content);
+        let mut edited: HashMap<String, VisualMeta> = HashMap::new();
+        for (line, row) in &rows {
+            let Some(meta) = metas.iter().find(|m| m.id == row.id) else {
+                errors.push(format!("строка {line}: в {file} нет записи {}", row.id));
+                continue;
+            };
+            let mut meta = meta.clone();
+            let before = plan.changes.len();
+            apply_row(&mut meta, row, &columns, &mut plan.changes);
+            if plan.changes.len() == before {
+                continue;
+            }
+            if let Err(e) = validate(&meta) {
+                for e in e {
+                    errors.push(format!("строка {line}: {}: {}", e.field, e.message));
+                }
+                continue;
+            }
+            meta.updated_at = Utc::now();
+            edited.insert(meta.id.clone(), meta);
+        }
+        if edited.is_empty() {
+            continue;
+        }
+        let updated = comment_detector::rewrite(&content, |json| {
+            let meta: VisualMeta = serde_json::from_str(json).ok()?;
+            let meta = edited.remove(&meta.id)?;
+            canonical::to_canonical_json(&meta).ok()
+        });
+        plan.updates.push((path, updated));
+    }
+    if !errors.is_empty() {
+        return Err(invalid(errors.join("\n")));
+    }
+    Ok(plan)
+}
+
+/// Записывает проверенную таблицу `plan` в файлы проекта `root`.
+///
+/// Перед записью изменённые файлы сохраняются одной резервной копией.
+/// Возвращает список изменённых файлов.
+pub fn apply_import(root: &Path, plan: CsvImport) -> io::Result<Vec<PathBuf>> {
+    if plan.updates.is_empty() {
+        return Ok(Vec::new());
+    }
+    let paths: Vec<&Path> = plan.updates.iter().map(|(p, _)| p.as_path()).collect();
+    backup_files(root, &paths)?;
+    for (path, updated) in &plan.updates {
+        file_io::write_preserving(path, updated)?;
+    }
+    Ok(plan.updates.into_iter().map(|(p, _)| p).collect())
+}
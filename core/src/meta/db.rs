@@ -1,9 +1,28 @@
+use crate::analysis::metrics::{BlockMetrics, MeasuredBlock};
 use crate::meta::VisualMeta;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnectOptions;
 use sqlx::{Row, SqlitePool};
+use std::path::{Path, PathBuf};
 
-/// Инициализирует базу данных, создавая таблицу `visual_meta`, если она отсутствует.
+/// Открывает базу данных в файле `path`, создавая файл, его каталог и
+/// таблицы при необходимости.
+pub async fn open(path: &Path) -> Result<SqlitePool, sqlx::Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let options = SqliteConnectOptions::new()
+        .filename(path)
+        .create_if_missing(true);
+    let pool = SqlitePool::connect_with(options).await?;
+    init(&pool).await?;
+    Ok(pool)
+}
+
+/// Инициализирует базу данных, создавая таблицы `visual_meta`,
+/// `meta_history`, `block_embeddings` и `block_metrics`, если они
+/// отсутствуют.
 pub async fn init(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"CREATE TABLE IF NOT EXISTS visual_meta (
@@ -22,6 +41,34 @@ pub async fn init(pool: &SqlitePool) -> Result<(), sqlx::Error> {
     )
     .execute(pool)
     .await?;
+    sqlx::query(
+        r#"CREATE TABLE IF NOT EXISTS block_embeddings (
+            id TEXT PRIMARY KEY,
+            file TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            label TEXT NOT NULL,
+            model TEXT NOT NULL,
+            hash TEXT NOT NULL,
+            vector BLOB NOT NULL
+        )"#,
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query(
+        r#"CREATE TABLE IF NOT EXISTS block_metrics (
+            file TEXT NOT NULL,
+            id TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            name TEXT,
+            loc INTEGER NOT NULL,
+            complexity INTEGER NOT NULL,
+            depth INTEGER NOT NULL,
+            params INTEGER NOT NULL,
+            PRIMARY KEY (file, id)
+        )"#,
+    )
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
@@ -158,3 +205,140 @@ pub async fn list(pool: &SqlitePool) -> Result<Vec<VisualMeta>, sqlx::Error> {
     }
     Ok(metas)
 }
+
+/// Вектор блока для семантического поиска, см. [`crate::search::semantic`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Embedding {
+    /// Ключ блока: файл относительно корня проекта и первая строка кода.
+    pub id: String,
+    /// Файл относительно корня проекта.
+    pub file: String,
+    /// Строка начала блока, с нуля.
+    pub line: usize,
+    pub label: String,
+    /// Модель, построившая вектор.
+    pub model: String,
+    /// Отпечаток текста блока, по которому вектор построен.
+    pub hash: String,
+    pub vector: Vec<f32>,
+}
+
+/// Вставляет или заменяет вектор блока.
+pub async fn upsert_embedding(pool: &SqlitePool, embedding: &Embedding) -> Result<(), sqlx::Error> {
+    let vector: Vec<u8> = embedding
+        .vector
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    sqlx::query(
+        "INSERT OR REPLACE INTO block_embeddings (id, file, line, label, model, hash, vector) \
+         VALUES (?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&embedding.id)
+    .bind(&embedding.file)
+    .bind(embedding.line as i64)
+    .bind(&embedding.label)
+    .bind(&embedding.model)
+    .bind(&embedding.hash)
+    .bind(vector)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Перечисляет все векторы блоков.
+pub async fn list_embeddings(pool: &SqlitePool) -> Result<Vec<Embedding>, sqlx::Error> {
+    let rows =
+        sqlx::query("SELECT id, file, line, label, model, hash, vector FROM block_embeddings")
+            .fetch_all(pool)
+            .await?;
+    let mut out = Vec::new();
+    for row in rows {
+        let line: i64 = row.try_get("line")?;
+        let bytes: Vec<u8> = row.try_get("vector")?;
+        out.push(Embedding {
+            id: row.try_get("id")?,
+            file: row.try_get("file")?,
+            line: line.max(0) as usize,
+            label: row.try_get("label")?,
+            model: row.try_get("model")?,
+            hash: row.try_get("hash")?,
+            vector: bytes
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect(),
+        });
+    }
+    Ok(out)
+}
+
+/// Удаляет вектор блока по идентификатору.
+pub async fn delete_embedding(pool: &SqlitePool, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM block_embeddings WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Заменяет сохранённые метрики блоков на `blocks`.
+pub async fn replace_metrics(
+    pool: &SqlitePool,
+    blocks: &[MeasuredBlock],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM block_metrics")
+        .execute(&mut *tx)
+        .await?;
+    for block in blocks {
+        let m = &block.metrics;
+        sqlx::query(
+            "INSERT OR REPLACE INTO block_metrics \
+             (file, id, line, name, loc, complexity, depth, params) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(block.file.to_string_lossy().into_owned())
+        .bind(&block.id)
+        .bind(block.line as i64)
+        .bind(&block.name)
+        .bind(m.loc as i64)
+        .bind(m.complexity as i64)
+        .bind(m.depth as i64)
+        .bind(m.params as i64)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Перечисляет сохранённые метрики блоков по файлу и строке.
+pub async fn list_metrics(pool: &SqlitePool) -> Result<Vec<MeasuredBlock>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT file, id, line, name, loc, complexity, depth, params \
+         FROM block_metrics ORDER BY file, line",
+    )
+    .fetch_all(pool)
+    .await?;
+    let count = |row: &sqlx::sqlite::SqliteRow, column: &str| -> Result<usize, sqlx::Error> {
+        let value: i64 = row.try_get(column)?;
+        Ok(value.max(0) as usize)
+    };
+    let mut out = Vec::new();
+    for row in rows {
+        let file: String = row.try_get("file")?;
+        out.push(MeasuredBlock {
+            file: PathBuf::from(file),
+            id: row.try_get("id")?,
+            line: count(&row, "line")?,
+            name: row.try_get("name")?,
+            metrics: BlockMetrics {
+                loc: count(&row, "loc")?,
+                complexity: count(&row, "complexity")?,
+                depth: count(&row, "depth")?,
+                params: count(&row, "params")?,
+            },
+        });
+    }
+    Ok(out)
+}
@@ -0,0 +1,109 @@
+//! Компактная кодировка комментариев `@VISUAL_META`.
+//!
+//! Помимо обычного JSON, метаданные могут храниться сжатыми:
+//!
+//! ```text
+//! // @VISUAL_META {"id":"1","x":0.0,"y":0.0}
+//! // @VISUAL_META:zstd KLUv/QBY...
+//! ```
+//!
+//! Во втором случае после маркера следует тег [`ZSTD_TAG`] и JSON, сжатый
+//! zstd и закодированный в base64. Чтение и [`upsert`](super::upsert)
+//! поддерживают обе формы; кодировка новых комментариев задаётся через
+//! [`set_default_encoding`].
+
+use std::sync::RwLock;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// Тег, следующий сразу за маркером в сжатых комментариях.
+pub const ZSTD_TAG: &str = ":zstd";
+
+/// Уровень сжатия zstd: размер комментария важнее скорости.
+const ZSTD_LEVEL: i32 = 19;
+
+/// Способ записи метаданных в комментарий.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetaEncoding {
+    /// Читаемый JSON.
+    #[default]
+    Json,
+    /// JSON, сжатый zstd и закодированный в base64.
+    Zstd,
+}
+
+static DEFAULT_ENCODING: RwLock<MetaEncoding> = RwLock::new(MetaEncoding::Json);
+
+/// Кодировка, используемая для новых комментариев.
+pub fn default_encoding() -> MetaEncoding {
+    DEFAULT_ENCODING.read().map(|e| *e).unwrap_or_default()
+}
+
+/// Устанавливает кодировку для новых комментариев.
+///
+/// Существующие комментарии при обновлении сохраняют свою кодировку.
+pub fn set_default_encoding(encoding: MetaEncoding) {
+    if let Ok(mut e) = DEFAULT_ENCODING.write() {
+        *e = encoding;
+    }
+}
+
+/// Формирует часть комментария после маркера: ` {json}` или `:zstd <base64>`.
+pub fn encode(json: &str, encoding: MetaEncoding) -> String {
+    match encoding {
+        MetaEncoding::Json => format!(" {json}"),
+        MetaEncoding::Zstd => match zstd::encode_all(json.as_bytes(), ZSTD_LEVEL) {
+            Ok(bytes) => format!("{ZSTD_TAG} {}", STANDARD.encode(bytes)),
+            Err(e) => {
+                tracing::error!("не удалось сжать метаданные: {e}");
+                format!(" {json}")
+            }
+        },
+    }
+}
+
+/// Распаковывает base64-строку сжатого комментария в JSON.
+pub fn decode(payload: &str) -> Option<String> {
+    let bytes = STANDARD.decode(payload.trim()).ok()?;
+    let json = zstd::decode_all(bytes.as_slice()).ok()?;
+    String::from_utf8(json).ok()
+}
+
+/// Разбирает часть комментария после маркера и возвращает JSON и кодировку.
+pub fn decode_payload(rest: &str) -> Option<(String, MetaEncoding)> {
+    if let Some(b64) = rest.strip_prefix(ZSTD_TAG) {
+        return decode(b64).map(|json| (json, MetaEncoding::Zstd));
+    }
+    let json = rest.trim();
+    json.starts_with('{')
+        .then(|| (json.to_string(), MetaEncoding::Json))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zstd_roundtrip() {
+        let json = r#"{"id":"1","x":0.0,"y":0.0,"extras":{"data":"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"}}"#;
+        let encoded = encode(json, MetaEncoding::Zstd);
+        assert!(encoded.starts_with(ZSTD_TAG));
+        assert_eq!(
+            decode_payload(&encoded),
+            Some((json.to_string(), MetaEncoding::Zstd))
+        );
+        assert_eq!(
+            decode_payload(&encode(json, MetaEncoding::Json)),
+            Some((json.to_string(), MetaEncoding::Json))
+        );
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(decode_payload(":zstd not-base64!"), None);
+        assert_eq!(decode_payload(" plain text"), None);
+    }
+}
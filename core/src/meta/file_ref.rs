@@ -0,0 +1,153 @@
+//! Блоки-ссылки на файлы проекта.
+//!
+//! Блок `FileRef` не соответствует коду: он существует только как запись
+//! [`VisualMeta`] с тегом [`FILE_REF_TAG`]. Ссылка `links[0]` указывает на
+//! корневую метаданную целевого файла (первую запись `@VISUAL_META` в нём),
+//! а поле `origin` хранит путь относительно корня проекта. Если файл был
+//! перемещён, [`resolve`] находит его по идентификатору корневой записи.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use walkdir::WalkDir;
+
+use super::{read_all, style, unique_id, upsert, VisualMeta, DEFAULT_VERSION};
+use crate::{file_io, i18n, ignore_rules::IgnoreRules, BlockInfo};
+
+/// Тип блока-ссылки на файл.
+pub const FILE_REF_KIND: &str = "FileRef";
+
+/// Тег, которым помечаются метаданные блоков-ссылок.
+pub const FILE_REF_TAG: &str = "file-ref";
+
+/// Является ли запись блоком-ссылкой на файл.
+pub fn is_file_ref(meta: &VisualMeta) -> bool {
+    meta.tags.iter().any(|t| t == FILE_REF_TAG)
+}
+
+/// Корневая метаданная файла: первая запись в `content`.
+pub fn root_meta(content: &str) -> Option<VisualMeta> {
+    read_all(content).into_iter().next()
+}
+
+/// Возвращает идентификатор корневой метаданной `content`, при
+/// необходимости добавляя новую запись в начало файла.
+///
+/// Второй элемент результата содержит обновлённый текст, если запись была
+/// создана.
+pub fn ensure_root_meta(content: &str) -> (String, Option<String>) {
+    if let Some(meta) = root_meta(content) {
+        return (meta.id, None);
+    }
+    let meta = VisualMeta {
+        version: DEFAULT_VERSION,
+        id: unique_id(),
+        x: 0.0,
+        y: 0.0,
+        width: None,
+        height: None,
+        tags: vec![],
+        links: vec![],
+        anchors: vec![],
+        tests: vec![],
+        extends: None,
+        origin: None,
+        translations: HashMap::new(),
+        ai: None,
+        extras: None,
+        updated_at: Utc::now(),
+    };
+    let updated = upsert(content, &meta, false);
+    (meta.id, Some(updated))
+}
+
+/// Создаёт метаданные блока-ссылки на файл `target` с корневой записью
+/// `root_id`, расположенного в точке `(x, y)` холста.
+pub fn file_ref_meta(root: &Path, target: &Path, root_id: &str, x: f64, y: f64) -> VisualMeta {
+    let rel = target.strip_prefix(root).unwrap_or(target);
+    let name = rel
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| rel.display().to_string());
+    VisualMeta {
+        version: DEFAULT_VERSION,
+        id: unique_id(),
+        x,
+        y,
+        width: None,
+        height: None,
+        tags: vec![FILE_REF_TAG.into()],
+        links: vec![root_id.to_string()],
+        anchors: vec![],
+        tests: vec![],
+        extends: None,
+        origin: Some(rel.to_string_lossy().replace('\\', "/")),
+        translations: i18n::languages()
+            .into_iter()
+            .map(|lang| (lang, name.clone()))
+            .collect(),
+        ai: None,
+        extras: None,
+        updated_at: Utc::now(),
+    }
+}
+
+/// Строит блок холста для записи-ссылки. Блок не связан с кодом, поэтому
+/// его диапазон пуст.
+pub fn file_ref_block(meta: &VisualMeta) -> BlockInfo {
+    BlockInfo {
+        visual_id: meta.id.clone(),
+        node_id: None,
+        kind: FILE_REF_KIND.into(),
+        translations: meta.translations.clone(),
+        range: (0, 0),
+        anchors: vec![],
+        x: meta.x,
+        y: meta.y,
+        width: None,
+        height: None,
+        ports: vec![],
+        ai: meta.ai.clone(),
+        tags: meta.tags.clone(),
+        links: meta.links.clone(),
+        color: style::color(meta).map(str::to_string),
+        icon: style::icon(meta).map(str::to_string),
+        doc: None,
+    }
+}
+
+/// Находит файл, на который указывает блок-ссылка `meta`.
+///
+/// Сначала проверяется путь из `origin`; если файла там нет или его
+/// корневая запись не совпадает со ссылкой, проект просматривается в
+/// поиске файла, содержащего запись с идентификатором `links[0]`.
+pub fn resolve(root: &Path, meta: &VisualMeta) -> Option<PathBuf> {
+    let link = meta.links.first();
+    if let Some(origin) = &meta.origin {
+        let path = root.join(origin);
+        if let Ok(content) = file_io::read_to_string(&path) {
+            let matches = match link {
+                Some(id) => read_all(&content).iter().any(|m| &m.id == id),
+                None => true,
+            };
+            if matches {
+                return Some(path);
+            }
+        }
+    }
+    let link = link?;
+    let rules = IgnoreRules::load(root);
+    WalkDir::new(root)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| !rules.is_ignored(e.path(), e.file_type().is_dir()))
+        .filter_map(Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .find(|e| {
+            file_io::read_to_string(e.path())
+                .map(|c| read_all(&c).iter().any(|m| &m.id == link))
+                .unwrap_or(false)
+        })
+        .map(|e| e.into_path())
+}
@@ -0,0 +1,110 @@
+//! Наследование записей метаданных через `extends`.
+//!
+//! Запись с `extends` получает теги, связи и остальные поля базовой записи
+//! того же файла ([`merge_base_meta`](super::merge_base_meta)). Цепочка
+//! баз, замкнутая в кольцо, не даёт ошибки при чтении: слияние просто
+//! останавливается на повторе. [`extends_cycles`] находит такие кольца,
+//! чтобы проверка проекта и редактор могли о них сообщить, а
+//! [`inheritance_chain`] показывает, из каких записей складывается итог.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+
+use super::tags::stored_metas;
+use super::{canonical, comment_detector, VisualMeta};
+
+/// Цепочка наследования записи: она сама и её базы по порядку `extends`.
+#[derive(Debug, Clone, Default)]
+pub struct InheritanceChain {
+    /// Записи в том виде, в каком они сохранены, начиная с исходной.
+    pub entries: Vec<VisualMeta>,
+    /// База, на которую ссылается последняя запись, но которой нет в файле.
+    pub missing: Option<String>,
+    /// База последней записи уже есть в цепочке: наследование замкнуто.
+    pub cycle: Option<String>,
+}
+
+/// Первая запись с каждым идентификатором.
+fn by_id(metas: &[VisualMeta]) -> HashMap<&str, &VisualMeta> {
+    let mut map = HashMap::new();
+    for meta in metas {
+        map.entry(meta.id.as_str()).or_insert(meta);
+    }
+    map
+}
+
+/// Цепочка наследования записи `id` в `content`; пустая, если записи нет.
+pub fn inheritance_chain(content: &str, id: &str) -> InheritanceChain {
+    let metas = stored_metas(content);
+    let map = by_id(&metas);
+    let mut chain = InheritanceChain::default();
+    let mut next = Some(id.to_string());
+    while let Some(id) = next.take() {
+        if chain.entries.iter().any(|m| m.id == id) {
+            chain.cycle = Some(id);
+            break;
+        }
+        let Some(meta) = map.get(id.as_str()) else {
+            if !chain.entries.is_empty() {
+                chain.missing = Some(id);
+            }
+            break;
+        };
+        next = meta.extends.clone();
+        chain.entries.push((*meta).clone());
+    }
+    chain
+}
+
+/// Заменяет базу записи `id` в `content` на `extends`; `None` убирает её.
+/// Стиль, кодировка и место комментария сохраняются.
+pub fn set_extends(content: &str, id: &str, extends: Option<&str>) -> String {
+    let mut found = false;
+    comment_detector::rewrite(content, |json| {
+        if found {
+            return None;
+        }
+        let mut meta: VisualMeta = serde_json::from_str(json).ok()?;
+        if meta.id != id {
+            return None;
+        }
+        found = true;
+        if meta.extends.as_deref() == extends {
+            return None;
+        }
+        meta.extends = extends.map(str::to_string);
+        meta.updated_at = Utc::now();
+        canonical::to_canonical_json(&meta).ok()
+    })
+}
+
+/// Кольца `extends` среди записей `content`. Каждое кольцо перечислено
+/// один раз по порядку ссылок, начиная с наименьшего идентификатора:
+/// последний элемент наследует первый.
+pub fn extends_cycles(content: &str) -> Vec<Vec<String>> {
+    let metas = stored_metas(content);
+    let map = by_id(&metas);
+    let mut done: HashSet<&str> = HashSet::new();
+    let mut cycles = Vec::new();
+    for meta in &metas {
+        let mut path: Vec<&str> = Vec::new();
+        let mut current = Some(meta.id.as_str());
+        while let Some(id) = current {
+            if done.contains(id) {
+                break;
+            }
+            if let Some(pos) = path.iter().position(|p| *p == id) {
+                let mut cycle: Vec<String> = path[pos..].iter().map(|s| s.to_string()).collect();
+                let min = (0..cycle.len()).min_by_key(|&i| &cycle[i]).unwrap_or(0);
+                cycle.rotate_left(min);
+                cycles.push(cycle);
+                break;
+            }
+            path.push(id);
+            current = map.get(id).and_then(|m| m.extends.as_deref());
+        }
+        done.extend(path);
+    }
+    cycles
+}
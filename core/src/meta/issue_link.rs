@@ -0,0 +1,110 @@
+//! Ссылки на задачи во внешних трекерах.
+//!
+//! Помимо идентификаторов блоков `links` может содержать типизированные
+//! ссылки вида `<трекер>:<ключ>`:
+//!
+//! ```text
+//! jira:PROJ-123
+//! gh:owner/repo#42
+//! ```
+//!
+//! Ссылки превращаются в адреса по шаблонам из секции `[issue_links]`
+//! файла `multicode.toml` (см. [`crate::config::IssueLinksConfig`]).
+
+use std::collections::BTreeMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::{ValidationError, VisualMeta};
+
+/// Трекер задач GitHub.
+pub const GITHUB: &str = "gh";
+/// Трекер задач Jira.
+pub const JIRA: &str = "jira";
+
+static SCHEME: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([a-z][a-z0-9_-]*):(.*)$").expect("valid regex"));
+static JIRA_KEY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([A-Z][A-Z0-9_]*)-([0-9]+)$").expect("valid regex"));
+static GITHUB_KEY: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([A-Za-z0-9_.-]+)/([A-Za-z0-9_.-]+)#([0-9]+)$").expect("valid regex")
+});
+
+/// Разобранная ссылка на задачу.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueLink {
+    pub tracker: String,
+    pub key: String,
+}
+
+impl IssueLink {
+    /// Разбирает ссылку `link`.
+    ///
+    /// Возвращает `None` для обычных ссылок на блоки и ошибку для
+    /// типизированной ссылки с неверным ключом.
+    pub fn parse(link: &str) -> Option<Result<Self, String>> {
+        let caps = SCHEME.captures(link.trim())?;
+        let tracker = caps[1].to_string();
+        let key = caps[2].to_string();
+        let valid = match tracker.as_str() {
+            JIRA => JIRA_KEY.is_match(&key),
+            GITHUB => GITHUB_KEY.is_match(&key),
+            _ => !key.is_empty() && !key.chars().any(char::is_whitespace),
+        };
+        Some(if valid {
+            Ok(IssueLink { tracker, key })
+        } else {
+            Err(match tracker.as_str() {
+                JIRA => format!("ссылка '{link}' должна иметь вид jira:PROJ-123"),
+                GITHUB => format!("ссылка '{link}' должна иметь вид gh:owner/repo#42"),
+                _ => format!("ссылка '{link}' должна иметь непустой ключ без пробелов"),
+            })
+        })
+    }
+
+    /// Значения подстановок для шаблона адреса: всегда `{key}`, для Jira
+    /// также `{project}` и `{number}`, для GitHub — `{owner}`, `{repo}` и
+    /// `{number}`.
+    pub fn fields(&self) -> BTreeMap<&'static str, String> {
+        let mut fields = BTreeMap::from([("key", self.key.clone())]);
+        match self.tracker.as_str() {
+            JIRA => {
+                if let Some(caps) = JIRA_KEY.captures(&self.key) {
+                    fields.insert("project", caps[1].to_string());
+                    fields.insert("number", caps[2].to_string());
+                }
+            }
+            GITHUB => {
+                if let Some(caps) = GITHUB_KEY.captures(&self.key) {
+                    fields.insert("owner", caps[1].to_string());
+                    fields.insert("repo", caps[2].to_string());
+                    fields.insert("number", caps[3].to_string());
+                }
+            }
+            _ => {}
+        }
+        fields
+    }
+
+    /// Подставляет поля ссылки в шаблон адреса.
+    pub fn expand(&self, template: &str) -> String {
+        self.fields()
+            .iter()
+            .fold(template.to_string(), |url, (name, value)| {
+                url.replace(&format!("{{{name}}}"), value)
+            })
+    }
+}
+
+/// Проверяет синтаксис типизированных ссылок в `links`.
+pub(super) fn validate(meta: &VisualMeta, errors: &mut Vec<ValidationError>) {
+    for link in &meta.links {
+        if let Some(Err(message)) = IssueLink::parse(link) {
+            errors.push(ValidationError {
+                field: "links".into(),
+                message,
+            });
+        }
+    }
+}
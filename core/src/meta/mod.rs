@@ -4,14 +4,39 @@ use serde::Serialize;
 use tracing::error;
 use std::collections::HashSet;
 use std::sync::Mutex;
-mod comment_detector;
+use crate::anchor;
+use crate::text::{self, LineEnding};
+pub mod backup;
+pub mod bookmarks;
+pub mod canonical;
+pub mod clipboard;
+pub(crate) mod comment_detector;
+pub mod csv;
 #[cfg(feature = "db")]
 pub mod db;
+pub mod encoding;
+pub mod file_ref;
 pub mod id_registry;
+pub mod inheritance;
+pub mod issue_link;
+pub mod pin;
 pub mod query;
+pub mod rename;
+pub mod schema;
+pub mod style;
+pub mod suggest;
+pub mod summary;
+pub mod symbols;
+pub mod tags;
+pub mod team_sync;
+pub mod templates;
+pub mod translation;
 mod types;
 #[cfg(feature = "watch")]
 pub mod watch;
+pub mod waypoints;
+pub use encoding::{set_default_encoding, MetaEncoding};
+pub use rename::rename_id;
 pub use types::{AiNote, VisualMeta, DEFAULT_VERSION};
 
 /// Маркер, используемый для идентификации комментариев с визуальными метаданными в документах.
@@ -32,6 +57,12 @@ fn migrate(meta: &mut VisualMeta) {
     }
 }
 
+/// JSON-схема записи [`VisualMeta`] для проверки содержимого комментариев
+/// `@VISUAL_META` внешними инструментами.
+pub fn schema() -> schemars::schema::RootSchema {
+    schemars::schema_for!(VisualMeta)
+}
+
 /// Структурированная ошибка валидации для [`VisualMeta`].
 #[derive(Debug, Serialize)]
 pub struct ValidationError {
@@ -66,6 +97,15 @@ pub fn validate(meta: &VisualMeta) -> Result<(), Vec<ValidationError>> {
         });
     }
 
+    for (field, value) in [("width", meta.width), ("height", meta.height)] {
+        if value.is_some_and(|v| !v.is_finite() || v <= 0.0) {
+            errors.push(ValidationError {
+                field: field.into(),
+                message: format!("{field} должен быть положительным конечным числом"),
+            });
+        }
+    }
+
     let mut tag_set = HashSet::new();
     for tag in &meta.tags {
         if !tag_set.insert(tag) {
@@ -88,12 +128,23 @@ pub fn validate(meta: &VisualMeta) -> Result<(), Vec<ValidationError>> {
 
     let mut anchor_set = HashSet::new();
     for anc in &meta.anchors {
-        if !anchor_set.insert(anc) {
+        if anc.name.trim().is_empty() {
+            errors.push(ValidationError {
+                field: "anchors".into(),
+                message: "имя anchor не должно быть пустым".into(),
+            });
+        } else if !anchor_set.insert(&anc.name) {
             errors.push(ValidationError {
                 field: "anchors".into(),
                 message: format!("дублирующийся anchor '{anc}'"),
             });
         }
+        if anc.label.as_deref().is_some_and(|l| l.trim().is_empty()) {
+            errors.push(ValidationError {
+                field: "anchors".into(),
+                message: format!("подпись anchor '{anc}' не должна быть пустой"),
+            });
+        }
     }
 
     if let Some(ext) = &meta.extends {
@@ -105,6 +156,13 @@ pub fn validate(meta: &VisualMeta) -> Result<(), Vec<ValidationError>> {
         }
     }
 
+    style::validate(meta, &mut errors);
+    waypoints::validate(meta, &mut errors);
+    pin::validate(meta, &mut errors);
+    issue_link::validate(meta, &mut errors);
+    crate::runconfig::validate(meta, &mut errors);
+    crate::watch::validate(meta, &mut errors);
+
     if errors.is_empty() {
         Ok(())
     } else {
@@ -114,33 +172,82 @@ pub fn validate(meta: &VisualMeta) -> Result<(), Vec<ValidationError>> {
 
 /// Вставляет или обновляет комментарий с визуальными метаданными в `content`.
 ///
-/// Если комментария ещё нет, он будет помещён в начало документа.
-/// `preserve_formatting` сохраняет исходные отступы и суффикс строки
-/// существующего комментария.
+/// Метаданные записываются в каноническом виде (см. [`canonical`]), а
+/// существующий комментарий переписывается, только если его содержимое
+/// (без учёта `updated_at`) действительно изменилось.
+///
+/// Если комментария ещё нет, он будет помещён в начало документа в
+/// кодировке [`encoding::default_encoding`]; существующий комментарий
+/// сохраняет свою кодировку. `preserve_formatting` сохраняет исходные
+/// отступы и суффикс строки существующего комментария.
 pub fn upsert(content: &str, meta: &VisualMeta, preserve_formatting: bool) -> String {
-    let marker = format!("<!-- {} ", MARKER);
+    upsert_if_changed(content, meta, preserve_formatting).0
+}
+
+/// То же, что [`upsert`], но дополнительно сообщает, были ли изменены
+/// метаданные: `false`, если запись с тем же `id` уже содержит те же
+/// значения полей и `updated_at` сохранён прежним.
+pub fn upsert_if_changed(
+    content: &str,
+    meta: &VisualMeta,
+    preserve_formatting: bool,
+) -> (String, bool) {
+    upsert_impl(content, meta, preserve_formatting, true)
+}
+
+/// Вставляет запись, сохраняя её `updated_at` без изменений.
+///
+/// Используется при повторной вставке уже существующих метаданных, например
+/// после перегенерации кода.
+pub(crate) fn reinsert(content: &str, meta: &VisualMeta) -> String {
+    upsert_impl(content, meta, false, false).0
+}
+
+fn upsert_impl(
+    content: &str,
+    meta: &VisualMeta,
+    preserve_formatting: bool,
+    touch: bool,
+) -> (String, bool) {
+    let marker = format!("<!-- {}", MARKER);
     let mut meta = meta.clone();
-    meta.updated_at = Utc::now();
+    if touch {
+        meta.updated_at = Utc::now();
+    }
     if let Err(errs) = validate(&meta) {
         error!("невалидный VisualMeta: {:?}", errs);
-        return content.to_string();
+        return (content.to_string(), false);
     }
-    let serialized = match serde_json::to_string(&meta) {
+    let serialized = match canonical::to_canonical_json(&meta) {
         Ok(s) => s,
         Err(e) => {
             error!("не удалось сериализовать VisualMeta: {e}");
-            return content.to_string();
+            return (content.to_string(), false);
         }
     };
 
+    // BOM и переводы строк каждой строки сохраняются как есть, чтобы
+    // правка метаданных в файле с `\r\n` не превращалась в diff всего файла.
+    // Последняя строка без перевода строки получает преобладающий.
+    let (bom, body) = text::split_bom(content);
+    let eol = LineEnding::detect(body).as_str();
     let mut out = String::new();
     let mut found = false;
-    for line in content.lines() {
+    let mut changed = false;
+    for line in body.split_inclusive('\n') {
+        let (line, ending) = match text::split_line_ending(line) {
+            (line, "") => (line, eol),
+            split => split,
+        };
         let trimmed = line.trim_start();
-        if trimmed.starts_with(&marker) {
-            if let Some(end_idx) = trimmed.find("-->") {
-                let json_part = &trimmed[marker.len()..end_idx].trim();
-                if let Ok(existing) = serde_json::from_str::<VisualMeta>(json_part) {
+        if let Some(rest) = trimmed.strip_prefix(&marker) {
+            if let Some(end_idx) = rest.find("-->") {
+                let existing = encoding::decode_payload(&rest[..end_idx]).and_then(|(json, enc)| {
+                    serde_json::from_str::<VisualMeta>(&json)
+                        .ok()
+                        .map(|m| (m, enc))
+                });
+                if let Some((existing, enc)) = existing {
                     if existing.id == meta.id {
                         let prefix = if preserve_formatting {
                             &line[..line.len() - trimmed.len()]
@@ -148,16 +255,26 @@ pub fn upsert(content: &str, meta: &VisualMeta, preserve_formatting: bool) -> St
                             ""
                         };
                         let suffix = if preserve_formatting {
-                            &trimmed[end_idx + 3..]
+                            &rest[end_idx + 3..]
                         } else {
                             ""
                         };
+                        // Если содержимое не изменилось, сохраняем прежнюю запись,
+                        // чтобы не менять `updated_at` и не создавать лишний diff.
+                        let dirty = canonical::changed_fields(&existing, &meta);
+                        let payload = if dirty.is_empty() {
+                            rest[..end_idx].trim_end().to_string()
+                        } else {
+                            tracing::debug!(id = %meta.id, fields = ?dirty, "метаданные изменены");
+                            changed = true;
+                            encoding::encode(&serialized, enc)
+                        };
                         out.push_str(prefix);
-                        out.push_str(&format!("{}{} -->", marker, serialized));
+                        out.push_str(&format!("{}{} -->", marker, payload));
                         if preserve_formatting {
                             out.push_str(suffix);
                         }
-                        out.push('\n');
+                        out.push_str(ending);
                         found = true;
                         continue;
                     }
@@ -165,14 +282,16 @@ pub fn upsert(content: &str, meta: &VisualMeta, preserve_formatting: bool) -> St
             }
         }
         out.push_str(line);
-        out.push('\n');
+        out.push_str(ending);
     }
 
     if !found {
-        out = format!("{}{} -->\n{}", marker, serialized, out);
+        let payload = encoding::encode(&serialized, encoding::default_encoding());
+        out = format!("{}{} -->{}{}", marker, payload, eol, out);
+        changed = true;
     }
 
-    out
+    (format!("{bom}{out}"), changed)
 }
 
 /// Считывает все комментарии с визуальными метаданными из `content`.
@@ -217,6 +336,9 @@ pub fn read_all(content: &str) -> Vec<VisualMeta> {
 pub fn merge_base_meta(id: &str) -> Option<VisualMeta> {
     fn inner(id: &str, visited: &mut HashSet<String>) -> Option<VisualMeta> {
         if !visited.insert(id.to_string()) {
+            // Кольцо замыкается на уже слитой записи; о нём сообщают
+            // проверки через `inheritance::extends_cycles`.
+            tracing::warn!(id, "цикл наследования extends");
             return id_registry::get(id);
         }
         let mut meta = id_registry::get(id)?;
@@ -249,16 +371,7 @@ pub fn merge_base_meta(id: &str) -> Option<VisualMeta> {
                 acc
             },
         );
-        child.anchors = base
-            .anchors
-            .into_iter()
-            .chain(child.anchors.into_iter())
-            .fold(Vec::new(), |mut acc, anc| {
-                if !acc.contains(&anc) {
-                    acc.push(anc);
-                }
-                acc
-            });
+        child.anchors = anchor::merge_named(&base.anchors, &child.anchors);
         if child.origin.is_none() {
             child.origin = base.origin;
         }
@@ -298,6 +411,11 @@ pub fn remove_all(content: &str) -> String {
     comment_detector::strip(content)
 }
 
+/// Перекодирует все комментарии метаданных в `content` в кодировку `target`.
+pub fn convert_encoding(content: &str, target: MetaEncoding) -> String {
+    comment_detector::convert(content, target)
+}
+
 /// Удобная обёртка, возвращающая все записи метаданных из `content`.
 pub fn list(content: &str) -> Vec<VisualMeta> {
     read_all(content)
@@ -329,7 +447,21 @@ pub fn fix_all(content: &str) -> String {
     out
 }
 
-fn unique_id() -> String {
+/// Идентификаторы, которые повторяются в комментариях метаданных
+/// `content`, по одному разу и в порядке первого повтора. [`fix_all`]
+/// заменяет такие идентификаторы новыми.
+pub fn duplicate_ids(content: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for meta in read_all(content) {
+        if !seen.insert(meta.id.clone()) && !duplicates.contains(&meta.id) {
+            duplicates.push(meta.id);
+        }
+    }
+    duplicates
+}
+
+pub(crate) fn unique_id() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
     let nanos = match SystemTime::now().duration_since(UNIX_EPOCH) {
         Ok(d) => d.as_nanos(),
@@ -356,6 +488,8 @@ fn unique_id() -> String {
             id: "1".into(),
             x: 10.0,
             y: 20.0,
+            width: None,
+            height: None,
             tags: vec!["alpha".into(), "beta".into()],
             links: vec![],
             anchors: vec!["a".into()],
@@ -416,6 +550,8 @@ fn unique_id() -> String {
             id: "p".into(),
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             tags: vec!["base".into()],
             links: vec!["l1".into()],
             anchors: vec!["a1".into()],
@@ -440,6 +576,8 @@ fn unique_id() -> String {
             id: "c".into(),
             x: 1.0,
             y: 1.0,
+            width: None,
+            height: None,
             tags: vec!["child".into()],
             links: vec!["l2".into()],
             anchors: vec!["a2".into()],
@@ -493,6 +631,8 @@ fn unique_id() -> String {
             id: "".into(),
             x: f64::NAN,
             y: 0.0,
+            width: None,
+            height: None,
             tags: vec!["a".into(), "a".into()],
             links: vec!["l".into(), "l".into()],
             anchors: vec!["a".into(), "a".into()],
@@ -521,6 +661,8 @@ fn unique_id() -> String {
               id: "".into(),
               x: f64::NAN,
               y: 0.0,
+              width: None,
+              height: None,
               tags: vec![],
               links: vec![],
               anchors: vec![],
@@ -0,0 +1,69 @@
+//! Закреплённые блоки.
+//!
+//! Закреплённый блок не сдвигается автоматической раскладкой холста, а его
+//! элемент — перестановкой кода при перегенерации по координатам. Признак
+//! хранится в `extras` под ключом [`PINNED_KEY`]:
+//!
+//! ```text
+//! <!-- @VISUAL_META {"id":"1","x":0.0,"y":0.0,"extras":{"pinned":true}} -->
+//! ```
+//!
+//! Закреплённый структурный блок, например модуль, замораживает и все
+//! вложенные в него блоки.
+
+use std::collections::HashSet;
+
+use serde_json::{json, Value};
+
+use super::{ValidationError, VisualMeta};
+
+/// Ключ `extras` с признаком закрепления.
+pub const PINNED_KEY: &str = "pinned";
+
+/// Закреплён ли блок записи `meta`.
+pub fn is_pinned(meta: &VisualMeta) -> bool {
+    meta.extras
+        .as_ref()
+        .and_then(|e| e.get(PINNED_KEY))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Идентификаторы закреплённых записей `metas`.
+pub fn pinned_ids(metas: &[VisualMeta]) -> HashSet<String> {
+    metas
+        .iter()
+        .filter(|m| is_pinned(m))
+        .map(|m| m.id.clone())
+        .collect()
+}
+
+/// Записывает признак закрепления, сохраняя остальные ключи `extras`.
+/// Снятие признака удаляет ключ; пустой объект заменяется на `None`.
+pub fn set_pinned(meta: &mut VisualMeta, pinned: bool) {
+    let mut extras = meta.extras.take().unwrap_or_else(|| json!({}));
+    if let Some(obj) = extras.as_object_mut() {
+        if pinned {
+            obj.insert(PINNED_KEY.into(), Value::Bool(true));
+        } else {
+            obj.remove(PINNED_KEY);
+        }
+        if obj.is_empty() {
+            return;
+        }
+    }
+    meta.extras = Some(extras);
+}
+
+/// Проверяет, что признак закрепления — логическое значение.
+pub(super) fn validate(meta: &VisualMeta, errors: &mut Vec<ValidationError>) {
+    let Some(value) = meta.extras.as_ref().and_then(|e| e.get(PINNED_KEY)) else {
+        return;
+    };
+    if !value.is_boolean() {
+        errors.push(ValidationError {
+            field: "extras.pinned".into(),
+            message: "признак закрепления должен быть true или false".into(),
+        });
+    }
+}
@@ -0,0 +1,105 @@
+//! Переименование идентификаторов метаданных во всём рабочем пространстве.
+//!
+//! На идентификатор записи ссылаются поля `links` и `extends` других
+//! записей, в том числе из других файлов, поэтому правка `id` вручную
+//! молча обрывает связи. [`rename_id`] меняет идентификатор и все ссылки
+//! на него за одну операцию: сначала сохраняется резервная копия
+//! изменяемых файлов, а если запись какого-то файла не удалась, уже
+//! записанные файлы возвращаются из неё.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+
+use super::backup::{backup_files, restore};
+use super::tags::stored_metas;
+use super::{canonical, comment_detector, VisualMeta};
+use crate::blocks::source_files;
+use crate::file_io;
+
+/// Заменяет в `content` идентификатор `old` на `new` в полях `id`, `links`
+/// и `extends`. Стиль, кодировка и место комментариев сохраняются, у
+/// изменённых записей обновляется `updated_at`.
+pub fn replace_id(content: &str, old: &str, new: &str) -> String {
+    comment_detector::rewrite(content, |json| {
+        let mut meta: VisualMeta = serde_json::from_str(json).ok()?;
+        let mut changed = false;
+        if meta.id == old {
+            meta.id = new.to_string();
+            changed = true;
+        }
+        for link in meta.links.iter_mut().filter(|l| *l == old) {
+            *link = new.to_string();
+            changed = true;
+        }
+        if meta.extends.as_deref() == Some(old) {
+            meta.extends = Some(new.to_string());
+            changed = true;
+        }
+        if !changed {
+            return None;
+        }
+        meta.updated_at = Utc::now();
+        canonical::to_canonical_json(&meta).ok()
+    })
+}
+
+/// Переименовывает запись `old` в `new` во всех файлах `root` и исправляет
+/// ссылки на неё.
+///
+/// Ошибка, если `new` пуст или уже занят либо записи `old` нет. Перед
+/// записью изменённые файлы сохраняются одной резервной копией. Возвращает
+/// список изменённых файлов.
+pub fn rename_id(root: &Path, old: &str, new: &str) -> io::Result<Vec<PathBuf>> {
+    let new = new.trim();
+    if new.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "идентификатор не должен быть пустым",
+        ));
+    }
+    let mut sources = Vec::new();
+    for path in source_files(root) {
+        if let Ok(content) = file_io::read_to_string(&path) {
+            sources.push((path, content));
+        }
+    }
+    let ids: Vec<String> = sources
+        .iter()
+        .flat_map(|(_, content)| stored_metas(content))
+        .map(|m| m.id)
+        .collect();
+    if !ids.iter().any(|id| id == old) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("запись {old} не найдена"),
+        ));
+    }
+    if new == old {
+        return Ok(Vec::new());
+    }
+    if ids.iter().any(|id| id == new) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("идентификатор {new} уже занят"),
+        ));
+    }
+    let updates: Vec<(PathBuf, String)> = sources
+        .into_iter()
+        .filter_map(|(path, content)| {
+            let updated = replace_id(&content, old, new);
+            (updated != content).then_some((path, updated))
+        })
+        .collect();
+    let paths: Vec<&Path> = updates.iter().map(|(p, _)| p.as_path()).collect();
+    let backup = backup_files(root, &paths)?;
+    for (path, updated) in &updates {
+        if let Err(e) = file_io::write_preserving(path, updated) {
+            // Половина переименования хуже никакого: ссылки разошлись бы.
+            restore(root, &backup, None)?;
+            return Err(e);
+        }
+    }
+    Ok(updates.into_iter().map(|(p, _)| p).collect())
+}
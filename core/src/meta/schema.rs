@@ -0,0 +1,84 @@
+//! Описание полей [`VisualMeta`](super::VisualMeta), построенное по её JSON-схеме.
+//!
+//! Редакторы метаданных строят по [`fields`] форму с типизированными полями
+//! вместо того, чтобы повторять список полей вручную.
+
+use schemars::schema::{InstanceType, Schema, SingleOrVec};
+
+/// Тип значения поля.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    /// Строка.
+    Text,
+    /// Число.
+    Number,
+    /// Список строк.
+    List,
+    /// Словарь строк.
+    Map,
+    /// Объект с произвольными значениями JSON.
+    Object,
+}
+
+/// Поле записи метаданных.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSpec {
+    pub name: String,
+    pub ty: FieldType,
+    /// Поле обязательно и не может быть пустым.
+    pub required: bool,
+    /// Описание поля из документации [`VisualMeta`](super::VisualMeta).
+    pub description: String,
+}
+
+fn field_type(schema: &Schema) -> FieldType {
+    let Schema::Object(schema) = schema else {
+        return FieldType::Object;
+    };
+    let types: Vec<InstanceType> = match &schema.instance_type {
+        Some(SingleOrVec::Single(ty)) => vec![**ty],
+        Some(SingleOrVec::Vec(types)) => types.clone(),
+        None => Vec::new(),
+    };
+    let ty = types.into_iter().find(|t| *t != InstanceType::Null);
+    match ty {
+        Some(InstanceType::String) => FieldType::Text,
+        Some(InstanceType::Number | InstanceType::Integer) => FieldType::Number,
+        Some(InstanceType::Array) => FieldType::List,
+        Some(InstanceType::Object)
+            if schema
+                .object
+                .as_ref()
+                .and_then(|o| o.additional_properties.as_deref())
+                .is_some_and(|p| field_type(p) == FieldType::Text) =>
+        {
+            FieldType::Map
+        }
+        _ => FieldType::Object,
+    }
+}
+
+/// Поля [`VisualMeta`](super::VisualMeta) в порядке объявления.
+pub fn fields() -> Vec<FieldSpec> {
+    let root = super::schema();
+    let Some(object) = root.schema.object.as_ref() else {
+        return Vec::new();
+    };
+    object
+        .properties
+        .iter()
+        .map(|(name, schema)| FieldSpec {
+            name: name.clone(),
+            ty: field_type(schema),
+            required: object.required.contains(name),
+            description: match schema {
+                Schema::Object(s) => s
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.description.clone())
+                    .unwrap_or_default(),
+                Schema::Bool(_) => String::new(),
+            },
+        })
+        .collect()
+}
@@ -0,0 +1,97 @@
+//! Переопределение цвета и значка блока.
+//!
+//! Значения хранятся в `extras` под ключами [`COLOR_KEY`] и [`ICON_KEY`]:
+//!
+//! ```text
+//! <!-- @VISUAL_META {"id":"1","x":0.0,"y":0.0,"extras":{"color":"#ff8800","icon":"⚠"}} -->
+//! ```
+
+use serde_json::{json, Value};
+
+use super::{ValidationError, VisualMeta};
+
+/// Ключ `extras` с цветом блока в формате `#rrggbb` или `#rgb`.
+pub const COLOR_KEY: &str = "color";
+/// Ключ `extras` со значком блока.
+pub const ICON_KEY: &str = "icon";
+/// Максимальная длина значка в символах.
+pub const MAX_ICON_CHARS: usize = 4;
+
+fn extra<'a>(meta: &'a VisualMeta, key: &str) -> Option<&'a str> {
+    meta.extras.as_ref()?.get(key)?.as_str()
+}
+
+/// Цвет блока, заданный в метаданных.
+pub fn color(meta: &VisualMeta) -> Option<&str> {
+    extra(meta, COLOR_KEY)
+}
+
+/// Значок блока, заданный в метаданных.
+pub fn icon(meta: &VisualMeta) -> Option<&str> {
+    extra(meta, ICON_KEY)
+}
+
+/// Записывает цвет и значок в `extras`, сохраняя остальные ключи.
+/// `None` удаляет ключ; пустой объект `extras` заменяется на `None`.
+pub fn set_style(meta: &mut VisualMeta, color: Option<&str>, icon: Option<&str>) {
+    let mut extras = meta.extras.take().unwrap_or_else(|| json!({}));
+    if let Some(obj) = extras.as_object_mut() {
+        for (key, value) in [(COLOR_KEY, color), (ICON_KEY, icon)] {
+            match value {
+                Some(v) => {
+                    obj.insert(key.into(), Value::String(v.into()));
+                }
+                None => {
+                    obj.remove(key);
+                }
+            }
+        }
+        if obj.is_empty() {
+            return;
+        }
+    }
+    meta.extras = Some(extras);
+}
+
+/// Разбирает цвет `#rrggbb` или `#rgb` в компоненты RGB.
+pub fn parse_color(value: &str) -> Option<(u8, u8, u8)> {
+    let hex = value.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok();
+    match hex.len() {
+        6 => Some((channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?)),
+        3 => {
+            let short = |i: usize| channel(&hex[i..i + 1]).map(|v| v * 17);
+            Some((short(0)?, short(1)?, short(2)?))
+        }
+        _ => None,
+    }
+}
+
+/// Проверяет значения цвета и значка в `extras`.
+pub(super) fn validate(meta: &VisualMeta, errors: &mut Vec<ValidationError>) {
+    let Some(extras) = meta.extras.as_ref() else {
+        return;
+    };
+    if let Some(value) = extras.get(COLOR_KEY) {
+        if value.as_str().and_then(parse_color).is_none() {
+            errors.push(ValidationError {
+                field: "extras.color".into(),
+                message: format!("цвет должен иметь вид #rrggbb или #rgb, получено {value}"),
+            });
+        }
+    }
+    if let Some(value) = extras.get(ICON_KEY) {
+        let len = value.as_str().map(|s| s.trim().chars().count());
+        if !matches!(len, Some(1..=MAX_ICON_CHARS)) {
+            errors.push(ValidationError {
+                field: "extras.icon".into(),
+                message: format!(
+                    "значок должен быть непустой строкой не длиннее {MAX_ICON_CHARS} символов"
+                ),
+            });
+        }
+    }
+}
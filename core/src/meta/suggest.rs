@@ -0,0 +1,518 @@
+//! Предложения тегов и связей для блоков без метаданных.
+//!
+//! Для функций, у которых ещё нет тегов, [`suggest`] предлагает теги из
+//! слов имени функции и из импортов файла, используемых в её теле. Для
+//! функций без связей предлагаются связи с вызываемыми функциями проекта:
+//! граф вызовов строится по блокам `Function/Call` внутри определения.
+//!
+//! Принятое предложение записывается через [`upsert_meta`]. Новая запись
+//! метаданных сдвигает блоки файла, а их идентификаторы зависят от
+//! смещения, поэтому [`accept`] переносит записи и ссылки на них на новые
+//! идентификаторы сдвинутых блоков.
+
+use std::collections::{BTreeSet, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::backup::backup_files;
+use super::rename::replace_id;
+use super::{read_all, VisualMeta, DEFAULT_VERSION};
+use crate::blocks::symbols::{file_symbols, workspace_symbols, CodeSymbolKind};
+use crate::blocks::{lang_from_extension, parse_blocks, source_files, upsert_meta};
+use crate::file_io;
+use crate::text::PositionIndex;
+use crate::BlockInfo;
+
+/// Слова имён, слишком общие для тега.
+const STOP_WORDS: &[&str] = &[
+    "and", "as", "build", "check", "create", "default", "do", "for", "from", "get", "handle",
+    "has", "init", "into", "is", "load", "main", "make", "new", "on", "parse", "process", "read",
+    "run", "save", "set", "the", "to", "try", "update", "with", "write",
+];
+
+/// Корни импортов, не говорящие о назначении кода.
+const STD_ROOTS: &[&str] = &["std", "core", "alloc", "crate", "self", "super"];
+
+/// Сколько тегов из имени предлагать одному блоку.
+const NAME_TAGS: usize = 3;
+
+/// Сколько раз переносить записи на новые идентификаторы блоков.
+const MAX_REKEY_PASSES: usize = 8;
+
+/// Что предлагается добавить блоку.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub enum SuggestionKind {
+    /// Тег, полученный из имени функции.
+    NameTag(String),
+    /// Тег модуля, импорт которого используется в теле функции.
+    ImportTag(String),
+    /// Связь с вызываемой функцией: её идентификатор и имя.
+    Call { target: String, name: String },
+}
+
+impl SuggestionKind {
+    /// Предлагаемый тег, если это предложение тега.
+    pub fn tag(&self) -> Option<&str> {
+        match self {
+            SuggestionKind::NameTag(tag) | SuggestionKind::ImportTag(tag) => Some(tag),
+            SuggestionKind::Call { .. } => None,
+        }
+    }
+}
+
+/// Предложение метаданных для блока.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MetaSuggestion {
+    /// Путь к файлу относительно корня проекта.
+    pub file: PathBuf,
+    /// Идентификатор блока.
+    pub id: String,
+    /// Номер первой строки блока, с нуля.
+    pub line: usize,
+    /// Имя функции.
+    pub name: String,
+    pub kind: SuggestionKind,
+}
+
+impl MetaSuggestion {
+    /// Ключ предложения, не зависящий от положения блока: по нему
+    /// запоминаются отклонённые предложения.
+    pub fn key(&self) -> String {
+        let what = match &self.kind {
+            SuggestionKind::NameTag(tag) | SuggestionKind::ImportTag(tag) => format!("#{tag}"),
+            SuggestionKind::Call { name, .. } => format!("->{name}"),
+        };
+        format!("{}:{}{}", self.file.display(), self.name, what)
+    }
+}
+
+/// Слова идентификатора `name` в нижнем регистре: `readHttpConfig` и
+/// `read_http_config` дают `read`, `http`, `config`.
+pub fn name_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            prev_lower = false;
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Идентификаторы `text`.
+fn identifiers(text: &str) -> BTreeSet<&str> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+/// Импорт файла: тег модуля и имена, под которыми он доступен в коде.
+#[derive(Debug, Clone, PartialEq)]
+struct Import {
+    tag: String,
+    names: Vec<String>,
+}
+
+/// Имя пакета по пути модуля JavaScript: `./net/http` — `http`,
+/// `@scope/pkg` — `pkg`.
+fn js_module_tag(path: &str) -> String {
+    let path = path.trim_end_matches(".js");
+    path.rsplit('/').next().unwrap_or(path).to_string()
+}
+
+/// Импорты исходника `content` на языке `lang`.
+fn imports(content: &str, lang: &str) -> Vec<Import> {
+    let mut out = Vec::new();
+    for line in content.lines().map(str::trim) {
+        let import = match lang {
+            "rust" => line
+                .strip_prefix("pub use ")
+                .or_else(|| line.strip_prefix("use "))
+                .and_then(|rest| {
+                    let rest = rest.trim_end_matches(';').trim_start_matches("::");
+                    let root = rest.split("::").next()?.trim();
+                    let names = identifiers(&rest[root.len()..])
+                        .into_iter()
+                        .filter(|w| *w != "as" && *w != "self")
+                        .map(String::from)
+                        .chain([root.to_string()])
+                        .collect();
+                    Some(Import {
+                        tag: root.to_string(),
+                        names,
+                    })
+                }),
+            "python" => {
+                if let Some(rest) = line.strip_prefix("from ") {
+                    rest.split_once(" import ").map(|(module, names)| Import {
+                        tag: module
+                            .trim_start_matches('.')
+                            .split('.')
+                            .next()
+                            .unwrap_or("")
+                            .into(),
+                        names: identifiers(names)
+                            .into_iter()
+                            .filter(|w| *w != "as")
+                            .map(String::from)
+                            .collect(),
+                    })
+                } else {
+                    line.strip_prefix("import ").map(|rest| {
+                        let module = rest.split([' ', ',']).next().unwrap_or("");
+                        let alias = rest.split(" as ").nth(1).map(str::trim);
+                        let root = module.split('.').next().unwrap_or("");
+                        Import {
+                            tag: root.into(),
+                            names: vec![alias.unwrap_or(root).into()],
+                        }
+                    })
+                }
+            }
+            "javascript" => {
+                let quoted = |s: &str| {
+                    let start = s.find(['\'', '"'])?;
+                    let quote = &s[start..start + 1];
+                    let rest = &s[start + 1..];
+                    Some(rest[..rest.find(quote)?].to_string())
+                };
+                if let Some(rest) = line.strip_prefix("import ") {
+                    rest.split_once(" from ").and_then(|(names, module)| {
+                        Some(Import {
+                            tag: js_module_tag(&quoted(module)?),
+                            names: identifiers(names)
+                                .into_iter()
+                                .filter(|w| *w != "as")
+                                .map(String::from)
+                                .collect(),
+                        })
+                    })
+                } else {
+                    line.find("require(").and_then(|i| {
+                        quoted(&line[i..]).map(|module| Import {
+                            tag: js_module_tag(&module),
+                            names: identifiers(line[..i].split('=').next().unwrap_or(""))
+                                .into_iter()
+                                .filter(|w| !["const", "let", "var"].contains(w))
+                                .map(String::from)
+                                .collect(),
+                        })
+                    })
+                }
+            }
+            _ => None,
+        };
+        if let Some(import) = import {
+            if !import.tag.is_empty() && !STD_ROOTS.contains(&import.tag.as_str()) {
+                out.push(import);
+            }
+        }
+    }
+    out
+}
+
+/// Имя вызываемой функции по тексту вызова: `self.net.send(x)` — `send`.
+fn callee(call: &str) -> Option<&str> {
+    let head = &call[..call.find('(')?];
+    let head = head.trim_end().trim_end_matches('!');
+    let name = head
+        .rsplit(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+        .next()?;
+    (!name.is_empty()).then_some(name)
+}
+
+/// Функции проекта по имени: идентификатор блока, путь и строка.
+pub type CallTargets = HashMap<String, Vec<(String, PathBuf, usize)>>;
+
+/// Функции проекта `root`, на которые могут указывать вызовы.
+pub fn call_targets(root: &Path) -> CallTargets {
+    let mut targets = CallTargets::new();
+    for symbol in workspace_symbols(root) {
+        if symbol.kind != CodeSymbolKind::Function {
+            continue;
+        }
+        let path = symbol.path.strip_prefix(root).unwrap_or(&symbol.path);
+        targets.entry(symbol.name).or_default().push((
+            symbol.visual_id,
+            path.to_path_buf(),
+            symbol.line,
+        ));
+    }
+    targets
+}
+
+/// Предложения для функций файла `file` (путь относительно корня) с
+/// содержимым `content` и блоками `blocks`.
+pub fn suggest_file(
+    file: &Path,
+    content: &str,
+    blocks: &[BlockInfo],
+    targets: &CallTargets,
+) -> Vec<MetaSuggestion> {
+    let Some(lang) = file
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(lang_from_extension)
+    else {
+        return Vec::new();
+    };
+    let imports = imports(content, lang);
+    let stored: HashMap<String, VisualMeta> = read_all(content)
+        .into_iter()
+        .map(|m| (m.id.clone(), m))
+        .collect();
+    let index = PositionIndex::new(content);
+    let calls: Vec<&BlockInfo> = blocks
+        .iter()
+        .filter(|b| b.kind == "Function/Call")
+        .collect();
+    let mut out = Vec::new();
+    for symbol in file_symbols(file, content, blocks) {
+        if symbol.kind != CodeSymbolKind::Function {
+            continue;
+        }
+        let Some(block) = blocks.iter().find(|b| b.visual_id == symbol.visual_id) else {
+            continue;
+        };
+        let meta = stored.get(&block.visual_id);
+        let has_tags = !block.tags.is_empty() || meta.is_some_and(|m| !m.tags.is_empty());
+        let has_links = !block.links.is_empty() || meta.is_some_and(|m| !m.links.is_empty());
+        let body = content.get(block.range.0..block.range.1).unwrap_or("");
+        let mut kinds = Vec::new();
+        if !has_tags {
+            let mut tags = BTreeSet::new();
+            for word in name_words(&symbol.name)
+                .into_iter()
+                .filter(|w| w.len() >= 3 && !STOP_WORDS.contains(&w.as_str()))
+                .take(NAME_TAGS)
+            {
+                if tags.insert(word.clone()) {
+                    kinds.push(SuggestionKind::NameTag(word));
+                }
+            }
+            let used = identifiers(body);
+            for import in &imports {
+                if import.names.iter().any(|n| used.contains(n.as_str()))
+                    && tags.insert(import.tag.clone())
+                {
+                    kinds.push(SuggestionKind::ImportTag(import.tag.clone()));
+                }
+            }
+        }
+        if !has_links {
+            let mut seen = BTreeSet::new();
+            for call in &calls {
+                if call.range.0 < block.range.0 || call.range.1 > block.range.1 {
+                    continue;
+                }
+                let Some(name) = content.get(call.range.0..call.range.1).and_then(callee) else {
+                    continue;
+                };
+                let Some(candidates) = targets.get(name) else {
+                    continue;
+                };
+                // Одноимённые функции в разных файлах: своя важнее, а при
+                // нескольких чужих связь неоднозначна.
+                let target = match candidates.iter().find(|(_, path, _)| path == file) {
+                    Some(local) => local,
+                    None if candidates.len() == 1 => &candidates[0],
+                    None => continue,
+                };
+                if target.0 == block.visual_id || !seen.insert(target.0.clone()) {
+                    continue;
+                }
+                kinds.push(SuggestionKind::Call {
+                    target: target.0.clone(),
+                    name: name.to_string(),
+                });
+            }
+        }
+        out.extend(kinds.into_iter().map(|kind| MetaSuggestion {
+            file: file.to_path_buf(),
+            id: block.visual_id.clone(),
+            line: index.line_of(block.range.0),
+            name: symbol.name.clone(),
+            kind,
+        }));
+    }
+    out
+}
+
+/// Предложения для всех функций проекта `root` без тегов или связей,
+/// упорядоченные по файлу и строке.
+pub fn suggest(root: &Path) -> Vec<MetaSuggestion> {
+    let targets = call_targets(root);
+    let mut out = Vec::new();
+    for path in source_files(root) {
+        let Some(lang) = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension)
+        else {
+            continue;
+        };
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let Some(blocks) = parse_blocks(content.clone(), lang.into()) else {
+            continue;
+        };
+        let file = path.strip_prefix(root).unwrap_or(&path);
+        out.extend(suggest_file(file, &content, &blocks, &targets));
+    }
+    out
+}
+
+/// Ключ блока, не зависящий от его смещения.
+fn block_key(content: &str, block: &BlockInfo) -> (String, String) {
+    let text = content.get(block.range.0..block.range.1).unwrap_or("");
+    (block.kind.clone(), text.trim().to_string())
+}
+
+/// Новые идентификаторы блоков после правки `before` → `after`: блоки
+/// сопоставляются по виду и тексту в порядке следования.
+fn moved_ids(before: &str, after: &str, lang: &str) -> Vec<(String, String)> {
+    let (Some(old), Some(new)) = (
+        parse_blocks(before.to_string(), lang.into()),
+        parse_blocks(after.to_string(), lang.into()),
+    ) else {
+        return Vec::new();
+    };
+    let mut fresh: HashMap<(String, String), Vec<String>> = HashMap::new();
+    for block in new.iter().rev() {
+        fresh
+            .entry(block_key(after, block))
+            .or_default()
+            .push(block.visual_id.clone());
+    }
+    old.iter()
+        .filter_map(|block| {
+            let id = fresh.get_mut(&block_key(before, block))?.pop()?;
+            (id != block.visual_id).then(|| (block.visual_id.clone(), id))
+        })
+        .collect()
+}
+
+/// Применяет предложение `suggestion` к исходнику `content` на языке
+/// `lang`. Возвращает новое содержимое и замены идентификаторов сдвинутых
+/// блоков в порядке применения; `None`, если блока с идентификатором
+/// предложения нет.
+pub fn apply(
+    content: &str,
+    lang: &str,
+    suggestion: &MetaSuggestion,
+) -> Option<(String, Vec<(String, String)>)> {
+    let blocks = parse_blocks(content.to_string(), lang.into())?;
+    let block = blocks.iter().find(|b| b.visual_id == suggestion.id)?;
+    let mut meta = read_all(content)
+        .into_iter()
+        .find(|m| m.id == block.visual_id)
+        .unwrap_or_else(|| VisualMeta {
+            version: DEFAULT_VERSION,
+            id: block.visual_id.clone(),
+            x: block.x,
+            y: block.y,
+            width: None,
+            height: None,
+            tags: Vec::new(),
+            links: Vec::new(),
+            anchors: Vec::new(),
+            tests: Vec::new(),
+            extends: None,
+            origin: None,
+            translations: HashMap::new(),
+            ai: None,
+            extras: None,
+            updated_at: chrono::Utc::now(),
+        });
+    match &suggestion.kind {
+        SuggestionKind::NameTag(tag) | SuggestionKind::ImportTag(tag) => {
+            if !meta.tags.contains(tag) {
+                meta.tags.push(tag.clone());
+            }
+        }
+        SuggestionKind::Call { target, .. } => {
+            if !meta.links.contains(target) {
+                meta.links.push(target.clone());
+            }
+        }
+    }
+    let key = suggestion.file.to_string_lossy().into_owned();
+    let mut before = content.to_string();
+    let mut updated =
+        upsert_meta(content.to_string(), meta, lang.into(), vec![key.clone()]).remove(&key)?;
+    let mut moved = Vec::new();
+    // Новые идентификаторы могут отличаться длиной от прежних, и их запись
+    // снова сдвигает блоки, поэтому замена повторяется до устойчивого
+    // состояния.
+    for _ in 0..MAX_REKEY_PASSES {
+        let pass = moved_ids(&before, &updated, lang);
+        if pass.is_empty() {
+            break;
+        }
+        before = updated.clone();
+        updated = pass
+            .iter()
+            .fold(updated, |acc, (old, new)| replace_id(&acc, old, new));
+        moved.extend(pass);
+    }
+    Some((updated, moved))
+}
+
+/// Принимает предложение: записывает его в файл проекта `root` и
+/// исправляет ссылки других файлов на сдвинутые блоки. Перед записью
+/// изменённые файлы сохраняются в резервную копию. Возвращает изменённые
+/// файлы.
+pub fn accept(root: &Path, suggestion: &MetaSuggestion) -> io::Result<Vec<PathBuf>> {
+    let path = root.join(&suggestion.file);
+    let lang = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .and_then(lang_from_extension)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "неподдерживаемый язык"))?;
+    let content = file_io::read_to_string(&path)?;
+    let (updated, moved) = apply(&content, lang, suggestion).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("блок {} не найден", suggestion.id),
+        )
+    })?;
+    let mut updates = vec![(path.clone(), updated)];
+    if !moved.is_empty() {
+        for other in source_files(root) {
+            if other == path {
+                continue;
+            }
+            let Ok(content) = file_io::read_to_string(&other) else {
+                continue;
+            };
+            let changed = moved.iter().fold(content.clone(), |acc, (old, new)| {
+                replace_id(&acc, old, new)
+            });
+            if changed != content {
+                updates.push((other, changed));
+            }
+        }
+    }
+    let paths: Vec<&Path> = updates.iter().map(|(p, _)| p.as_path()).collect();
+    backup_files(root, &paths)?;
+    for (path, content) in &updates {
+        file_io::write_preserving(path, content)?;
+    }
+    Ok(updates.into_iter().map(|(p, _)| p).collect())
+}
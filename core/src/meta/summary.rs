@@ -0,0 +1,372 @@
+//! Сводки файлов и модулей.
+//!
+//! Сводка файла складывается из описаний его блоков (`ai.description`), а
+//! сводка модуля — из сводок файлов его каталога. Если передан клиент
+//! модели ([`AiClient`]), собранные заметки пересказываются моделью
+//! связным текстом; иначе сводкой служит сам список заметок.
+//!
+//! Сводки хранятся записями `@VISUAL_META` в конце файла, не связанными
+//! с блоками: сводка файла — с идентификатором `summary:<файл>`, сводка
+//! модуля — `module-summary:<каталог>` в его главном файле ([`MODULE_ENTRIES`],
+//! например `mod.rs` или `__init__.py`). Текст сводки лежит в
+//! `ai.description`. Сводки пересоздаются по запросу ([`regenerate`]).
+
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+
+use crate::ai::{AiClient, AiRequest};
+use crate::blocks::parse_dir;
+use crate::file_io;
+use crate::offline_queue::OpError;
+use crate::text::LineEnding;
+use crate::BlockInfo;
+
+use super::backup::backup_files;
+use super::{read_all, upsert, AiNote, VisualMeta, DEFAULT_VERSION};
+
+/// Начало идентификатора сводки файла.
+pub const FILE_PREFIX: &str = "summary:";
+
+/// Начало идентификатора сводки модуля.
+pub const MODULE_PREFIX: &str = "module-summary:";
+
+/// Файлы, в которых хранится сводка модуля-каталога, в порядке выбора.
+pub const MODULE_ENTRIES: &[&str] = &[
+    "mod.rs",
+    "lib.rs",
+    "main.rs",
+    "__init__.py",
+    "index.ts",
+    "index.js",
+];
+
+/// Инструкция модели для сводки файла.
+const FILE_PROMPT: &str = "Below are notes about the parts of one source file. \
+     Summarize in two or three sentences what the file is responsible for. \
+     Answer in the language of the notes.";
+
+/// Инструкция модели для сводки модуля.
+const MODULE_PROMPT: &str = "Below are summaries of the files of one module. \
+     Summarize in two or three sentences what the module is responsible for. \
+     Answer in the language of the summaries.";
+
+/// Уровень сводки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    File,
+    Module,
+}
+
+/// Сводка файла или модуля.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    pub scope: Scope,
+    /// Файл или каталог модуля относительно корня проекта; корень — `.`.
+    pub path: PathBuf,
+    /// Файл, в котором хранится сводка, относительно корня.
+    pub file: PathBuf,
+    pub text: String,
+}
+
+impl Summary {
+    /// Идентификатор записи сводки.
+    pub fn id(&self) -> String {
+        let prefix = match self.scope {
+            Scope::File => FILE_PREFIX,
+            Scope::Module => MODULE_PREFIX,
+        };
+        format!("{prefix}{}", slash_path(&self.path))
+    }
+
+    /// Запись метаданных со сводкой.
+    pub fn to_meta(&self) -> VisualMeta {
+        VisualMeta {
+            version: DEFAULT_VERSION,
+            id: self.id(),
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            tags: Vec::new(),
+            links: Vec::new(),
+            anchors: Vec::new(),
+            tests: Vec::new(),
+            extends: None,
+            origin: None,
+            translations: HashMap::new(),
+            ai: Some(AiNote {
+                description: Some(self.text.clone()),
+                hints: Vec::new(),
+            }),
+            extras: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    /// Сводка из записи метаданных файла `file`; `None`, если запись не
+    /// сводка.
+    pub fn from_meta(meta: &VisualMeta, file: &Path) -> Option<Self> {
+        let (scope, path) = if let Some(path) = meta.id.strip_prefix(FILE_PREFIX) {
+            (Scope::File, path)
+        } else if let Some(path) = meta.id.strip_prefix(MODULE_PREFIX) {
+            (Scope::Module, path)
+        } else {
+            return None;
+        };
+        Some(Self {
+            scope,
+            path: PathBuf::from(path),
+            file: file.to_path_buf(),
+            text: meta.ai.as_ref()?.description.clone()?,
+        })
+    }
+}
+
+/// Путь с `/` в качестве разделителя на любой платформе.
+fn slash_path(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    if path.is_empty() {
+        ".".into()
+    } else {
+        path
+    }
+}
+
+/// Описания блоков `blocks` файла `content` в порядке следования: первая
+/// строка кода блока и описание.
+pub fn block_notes(content: &str, blocks: &[BlockInfo]) -> Vec<(String, String)> {
+    let mut blocks: Vec<_> = blocks.iter().collect();
+    blocks.sort_by_key(|b| b.range.0);
+    blocks
+        .into_iter()
+        .filter_map(|block| {
+            let description = block.ai.as_ref()?.description.as_deref()?.trim();
+            if description.is_empty() {
+                return None;
+            }
+            let label = content
+                .get(block.range.0..block.range.1)?
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string();
+            Some((label, description.to_string()))
+        })
+        .collect()
+}
+
+/// Заметки списком: по строке `- подпись: описание` на заметку.
+/// Многострочное описание начинается со следующей строки с отступом,
+/// так что из списков сводок файлов получается вложенный список модуля.
+pub fn compose(notes: &[(String, String)]) -> String {
+    notes
+        .iter()
+        .map(|(label, description)| {
+            if description.contains('\n') {
+                format!("- {label}:\n  {}", description.replace('\n', "\n  "))
+            } else {
+                format!("- {label}: {description}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Текст сводки из заметок: пересказ моделью или сам список.
+fn condense(
+    client: Option<&AiClient>,
+    prompt: &str,
+    notes: &[(String, String)],
+) -> Result<String, OpError> {
+    let composed = compose(notes);
+    match client {
+        Some(client) => {
+            let request = AiRequest::new(composed).with_system(prompt);
+            Ok(client.complete(&request, |_| {})?.text.trim().to_string())
+        }
+        None => Ok(composed),
+    }
+}
+
+/// Главный файл модуля-каталога `dir` среди файлов `files`.
+fn module_entry<'a>(dir: &Path, files: &'a [PathBuf]) -> Option<&'a PathBuf> {
+    MODULE_ENTRIES
+        .iter()
+        .find_map(|name| files.iter().find(|f| *f == &dir.join(name)))
+}
+
+/// Строит сводки проекта `root`: файлов, в блоках которых есть описания,
+/// и модулей-каталогов с главным файлом, в которых есть сводки файлов.
+/// Сводки упорядочены: сначала файлы, затем модули, по пути.
+pub fn generate(root: &Path, client: Option<&AiClient>) -> Result<Vec<Summary>, OpError> {
+    let mut files = Vec::new();
+    let mut summaries = Vec::new();
+    for (path, blocks) in parse_dir(root) {
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        files.push(rel.clone());
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let notes = block_notes(&content, &blocks);
+        if notes.is_empty() {
+            continue;
+        }
+        summaries.push(Summary {
+            scope: Scope::File,
+            text: condense(client, FILE_PROMPT, &notes)?,
+            path: rel.clone(),
+            file: rel,
+        });
+    }
+    let mut modules: BTreeMap<PathBuf, Vec<(String, String)>> = BTreeMap::new();
+    for summary in &summaries {
+        let dir = summary.path.parent().unwrap_or(Path::new("")).to_path_buf();
+        let name = summary
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        modules
+            .entry(dir)
+            .or_default()
+            .push((name, summary.text.clone()));
+    }
+    for (dir, notes) in modules {
+        let Some(entry) = module_entry(&dir, &files) else {
+            continue;
+        };
+        summaries.push(Summary {
+            scope: Scope::Module,
+            text: condense(client, MODULE_PROMPT, &notes)?,
+            file: entry.clone(),
+            path: if dir.as_os_str().is_empty() {
+                PathBuf::from(".")
+            } else {
+                dir
+            },
+        });
+    }
+    Ok(summaries)
+}
+
+/// Сводки, сохранённые в файлах проекта `root`, в порядке [`generate`].
+pub fn stored(root: &Path) -> Vec<Summary> {
+    let mut summaries: Vec<Summary> = parse_dir(root)
+        .into_iter()
+        .filter_map(|(path, _)| {
+            let content = file_io::read_to_string(&path).ok()?;
+            let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            Some(
+                read_all(&content)
+                    .iter()
+                    .filter_map(|meta| Summary::from_meta(meta, &rel))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .flatten()
+        .collect();
+    summaries.sort_by(|a, b| (a.scope, &a.path).cmp(&(b.scope, &b.path)));
+    summaries
+}
+
+/// Запись — сводка, а не метаданные блока.
+fn is_summary(meta: &VisualMeta) -> bool {
+    meta.id.starts_with(FILE_PREFIX) || meta.id.starts_with(MODULE_PREFIX)
+}
+
+/// Комментарий с записью `meta` в синтаксисе файла `path`.
+fn comment_line(path: &Path, meta: &VisualMeta) -> String {
+    let comment = upsert("", meta, false).trim_end().to_string();
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+    {
+        "py" => format!("# {comment}"),
+        "css" => format!("/* {comment} */"),
+        "html" | "htm" => comment,
+        _ => format!("// {comment}"),
+    }
+}
+
+/// Текст файла `content` (путь `path`) со сводками `own` в конце вместо
+/// прежних. Сводка с тем же текстом сохраняет прежнюю строку, чтобы
+/// повторное создание не меняло файл.
+fn with_summaries(path: &Path, content: &str, own: &[&Summary]) -> String {
+    let mut kept = String::new();
+    let mut previous: HashMap<String, (String, Option<String>)> = HashMap::new();
+    for line in content.split_inclusive('\n') {
+        let metas = if line.contains(super::MARKER) {
+            read_all(line)
+        } else {
+            Vec::new()
+        };
+        match metas.iter().find(|meta| is_summary(meta)) {
+            Some(meta) => {
+                let text = meta.ai.as_ref().and_then(|ai| ai.description.clone());
+                previous.insert(meta.id.clone(), (line.trim_end().to_string(), text));
+            }
+            None => kept.push_str(line),
+        }
+    }
+    if own.is_empty() {
+        return kept;
+    }
+    let eol = LineEnding::detect(content).as_str();
+    if !kept.is_empty() && !kept.ends_with('\n') {
+        kept.push_str(eol);
+    }
+    for summary in own {
+        match previous.get(&summary.id()) {
+            Some((line, Some(text))) if *text == summary.text => kept.push_str(line),
+            _ => kept.push_str(&comment_line(path, &summary.to_meta())),
+        }
+        kept.push_str(eol);
+    }
+    kept
+}
+
+/// Записывает сводки `summaries` в конец их файлов проекта `root` и
+/// удаляет устаревшие. В конце файла запись не сдвигает блоки, от
+/// положения которых зависят их идентификаторы. Изменённые файлы
+/// предварительно копируются в резервную копию. Возвращает число
+/// изменённых файлов.
+pub fn write(root: &Path, summaries: &[Summary]) -> io::Result<usize> {
+    let mut by_file: BTreeMap<PathBuf, Vec<&Summary>> = BTreeMap::new();
+    for summary in summaries {
+        by_file
+            .entry(summary.file.clone())
+            .or_default()
+            .push(summary);
+    }
+    let mut changed = Vec::new();
+    for (path, _) in parse_dir(root) {
+        let rel = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        let content = file_io::read_to_string(&path)?;
+        let own = by_file.get(&rel).map(Vec::as_slice).unwrap_or_default();
+        let updated = with_summaries(&path, &content, own);
+        if updated != content {
+            changed.push((path, updated));
+        }
+    }
+    if changed.is_empty() {
+        return Ok(0);
+    }
+    let paths: Vec<&Path> = changed.iter().map(|(p, _)| p.as_path()).collect();
+    backup_files(root, &paths)?;
+    for (path, updated) in &changed {
+        file_io::write_preserving(path, updated)?;
+    }
+    Ok(changed.len())
+}
+
+/// Пересоздаёт сводки проекта `root` и записывает их в файлы.
+pub fn regenerate(root: &Path, client: Option<&AiClient>) -> Result<Vec<Summary>, String> {
+    let summaries = generate(root, client).map_err(|e| e.to_string())?;
+    write(root, &summaries).map_err(|e| e.to_string())?;
+    Ok(summaries)
+}
@@ -0,0 +1,82 @@
+//! Символы рабочего пространства для автодополнения метаданных.
+//!
+//! [`WorkspaceSymbols`] собирает теги, идентификаторы и языки переводов из
+//! всех комментариев `@VISUAL_META` проекта, чтобы редактор мог дополнять
+//! их при правке `tags`, `links`, `extends` и `translations`. Заодно
+//! считаются входящие ссылки на каждую запись — их показывают подсказки
+//! редактора.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use super::tags::stored_metas;
+use crate::blocks::source_files;
+use crate::file_io;
+use crate::i18n;
+
+/// Вид дополняемого символа.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolKind {
+    /// Тег из `tags`.
+    Tag,
+    /// Идентификатор записи для `links` и `extends`.
+    Id,
+    /// Код языка — ключ `translations`.
+    Language,
+}
+
+/// Теги, идентификаторы и языки, встречающиеся в метаданных проекта.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkspaceSymbols {
+    pub tags: BTreeSet<String>,
+    pub ids: BTreeSet<String>,
+    pub languages: BTreeSet<String>,
+    /// Число ссылок `links` на каждый идентификатор.
+    pub incoming: BTreeMap<String, usize>,
+}
+
+impl WorkspaceSymbols {
+    /// Собирает символы из всех файлов проекта `root`. Языки стандартных
+    /// переводов доступны даже в проекте без метаданных.
+    pub fn collect(root: &Path) -> Self {
+        let mut symbols = Self::default();
+        symbols.languages.extend(i18n::languages());
+        for path in source_files(root) {
+            if let Ok(content) = file_io::read_to_string(&path) {
+                symbols.add_content(&content);
+            }
+        }
+        symbols
+    }
+
+    /// Добавляет символы из метаданных, записанных в `content`.
+    pub fn add_content(&mut self, content: &str) {
+        for meta in stored_metas(content) {
+            self.tags.extend(meta.tags);
+            self.languages.extend(meta.translations.into_keys());
+            for link in meta.links {
+                *self.incoming.entry(link).or_default() += 1;
+            }
+            self.ids.insert(meta.id);
+        }
+    }
+
+    /// Символы вида `kind`, начинающиеся с `prefix` без учёта регистра.
+    pub fn complete(&self, kind: SymbolKind, prefix: &str) -> Vec<&str> {
+        let set = match kind {
+            SymbolKind::Tag => &self.tags,
+            SymbolKind::Id => &self.ids,
+            SymbolKind::Language => &self.languages,
+        };
+        let prefix = prefix.to_lowercase();
+        set.iter()
+            .filter(|s| s.to_lowercase().starts_with(&prefix))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Число ссылок на запись `id` из метаданных проекта.
+    pub fn incoming_links(&self, id: &str) -> usize {
+        self.incoming.get(id).copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,101 @@
+//! Теги во всём рабочем пространстве: подсчёт, переименование и слияние.
+//!
+//! Со временем одни и те же понятия обрастают близкими тегами вроде
+//! `net` и `network`. [`tag_counts`] показывает, какие теги используются,
+//! а [`rename_tag`] и [`merge_tags`] переписывают их во всех файлах
+//! проекта, предварительно сохранив резервную копию изменённых файлов.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::{backup::backup_files, comment_detector, upsert, VisualMeta};
+use crate::blocks::source_files;
+use crate::file_io;
+
+/// Записи метаданных в том виде, в каком они сохранены в файле, без
+/// наследования полей через `extends`.
+pub(super) fn stored_metas(content: &str) -> Vec<VisualMeta> {
+    comment_detector::extract_json(content)
+        .into_iter()
+        .filter_map(|json| serde_json::from_str(&json).ok())
+        .collect()
+}
+
+/// Количество записей метаданных с каждым тегом во всех файлах `root`.
+pub fn tag_counts(root: &Path) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for path in source_files(root) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        for meta in stored_metas(&content) {
+            for tag in meta.tags {
+                *counts.entry(tag).or_insert(0) += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Заменяет в `content` теги `from` на `into`, не допуская повторов.
+pub fn replace_tags(content: &str, from: &[&str], into: &str) -> String {
+    let mut out = content.to_string();
+    for mut meta in stored_metas(content) {
+        if !meta.tags.iter().any(|t| from.contains(&t.as_str())) {
+            continue;
+        }
+        let mut tags = Vec::with_capacity(meta.tags.len());
+        for tag in meta.tags {
+            let tag = if from.contains(&tag.as_str()) {
+                into.to_string()
+            } else {
+                tag
+            };
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        meta.tags = tags;
+        out = upsert(&out, &meta, true);
+    }
+    out
+}
+
+/// Сливает теги `from` в тег `into` во всех файлах `root`.
+///
+/// Перед записью изменённые файлы сохраняются одной резервной копией.
+/// Возвращает список изменённых файлов.
+pub fn merge_tags(root: &Path, from: &[&str], into: &str) -> io::Result<Vec<PathBuf>> {
+    let into = into.trim();
+    if into.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "имя тега не должно быть пустым",
+        ));
+    }
+    let mut updates = Vec::new();
+    for path in source_files(root) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let updated = replace_tags(&content, from, into);
+        if updated != content {
+            updates.push((path, updated));
+        }
+    }
+    if updates.is_empty() {
+        return Ok(Vec::new());
+    }
+    let paths: Vec<&Path> = updates.iter().map(|(p, _)| p.as_path()).collect();
+    backup_files(root, &paths)?;
+    for (path, updated) in &updates {
+        file_io::write_preserving(path, updated)?;
+    }
+    Ok(updates.into_iter().map(|(p, _)| p).collect())
+}
+
+/// Переименовывает тег `from` в `to` во всех файлах `root`.
+pub fn rename_tag(root: &Path, from: &str, to: &str) -> io::Result<Vec<PathBuf>> {
+    merge_tags(root, &[from], to)
+}
@@ -0,0 +1,517 @@
+//! Обмен метаданными с сервером команды.
+//!
+//! Записи `@VISUAL_META` каждого файла хранятся на сервере отдельно от
+//! кода, списком с номером версии файла. Так участники, которые не
+//! работают с git, например дизайнеры, могут менять раскладку блоков, а
+//! разработчики — забирать её в код ([`sync_project`]).
+//!
+//! Для каждого файла запоминается версия и записи на момент последнего
+//! обмена (`.multicode/team_sync.json`). По ним изменения сводятся в три
+//! стороны по полям записи: поле, изменённое только с одной стороны,
+//! берётся оттуда, а изменённое с обеих — по правилу [`ConflictPolicy`].
+//! Сервер принимает записи, только если версия файла не изменилась с
+//! момента чтения; иначе обмен файлом повторяется. Удаление записей не
+//! передаётся: запись, удалённая с одной стороны, возвращается с другой.
+//!
+//! Если сервер недоступен, обмен ставится в очередь
+//! ([`crate::offline_queue`], вид [`QUEUE_KIND`]) и повторяется, когда
+//! связь появится ([`replay`]).
+//!
+//! Сервер задаётся секцией `[team_sync]` файла `multicode.toml`
+//! ([`HttpRemote`], флаг `team-sync`):
+//!
+//! ```toml
+//! [team_sync]
+//! server = "https://multicode.example.com"
+//! project = "shop"          # по умолчанию — имя каталога проекта
+//! token_env = "MULTICODE_TOKEN"
+//! conflict = "remote"       # или "local"
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::backup::backup_files;
+use super::csv::rel_path;
+use super::{canonical, read_all, reinsert, VisualMeta};
+use crate::blocks::source_files;
+use crate::file_io;
+use crate::offline_queue::{OfflineQueue, OpError};
+
+/// Файл состояния обмена относительно корня проекта.
+pub const SYNC_STATE_FILE: &str = ".multicode/team_sync.json";
+
+/// Сколько раз повторяется обмен файлом, который на сервере успели
+/// изменить между чтением и записью.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Вид операции обмена в очереди [`crate::offline_queue`].
+pub const QUEUE_KIND: &str = "team_sync";
+
+/// Чьё значение поля берётся, если его изменили и локально, и на сервере.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Значение с сервера.
+    #[default]
+    Remote,
+    /// Локальное значение.
+    Local,
+}
+
+/// Записи файла на сервере.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RemoteFile {
+    /// Версия файла; `0` — записей на сервере ещё нет.
+    pub version: u64,
+    #[serde(default)]
+    pub metas: Vec<VisualMeta>,
+}
+
+/// Ответ сервера на запись.
+#[derive(Debug, Clone)]
+pub enum PushResult {
+    /// Записи приняты, новая версия файла.
+    Accepted(u64),
+    /// Версия файла на сервере уже другая; текущие записи сервера.
+    Conflict(RemoteFile),
+}
+
+/// Сервер команды. Файлы задаются путём от корня проекта через `/`.
+pub trait MetaRemote {
+    /// Файлы с записями на сервере и их версии.
+    fn list(&self) -> Result<Vec<(String, u64)>, OpError>;
+    /// Записи файла `file`.
+    fn pull(&self, file: &str) -> Result<RemoteFile, OpError>;
+    /// Записывает `metas` файла `file`, если его версия на сервере всё ещё
+    /// `base_version`.
+    fn push(
+        &self,
+        file: &str,
+        base_version: u64,
+        metas: &[VisualMeta],
+    ) -> Result<PushResult, OpError>;
+}
+
+/// Состояние файла на момент последнего обмена.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FileState {
+    pub version: u64,
+    pub base: Vec<VisualMeta>,
+}
+
+/// Состояние обмена проекта.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    #[serde(default)]
+    pub files: BTreeMap<String, FileState>,
+}
+
+impl SyncState {
+    /// Загружает состояние проекта `root`; без файла — пустое.
+    pub fn load(root: &Path) -> io::Result<Self> {
+        let data = match fs::read_to_string(root.join(SYNC_STATE_FILE)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Записывает состояние в файл проекта `root`.
+    pub fn save(&self, root: &Path) -> io::Result<()> {
+        let path = root.join(SYNC_STATE_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, data)
+    }
+}
+
+/// Итог сведения записей файла.
+#[derive(Debug, Clone, Default)]
+pub struct Merged {
+    pub metas: Vec<VisualMeta>,
+    /// Записи, поля которых изменены с обеих сторон.
+    pub conflicts: Vec<String>,
+}
+
+fn fields(meta: &VisualMeta) -> Map<String, Value> {
+    match serde_json::to_value(meta) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+/// Сводит запись `local` и запись `remote` с общим предком `base` по
+/// полям. Возвращает запись и признак конфликта.
+fn merge_meta(
+    base: Option<&VisualMeta>,
+    local: &VisualMeta,
+    remote: &VisualMeta,
+    policy: ConflictPolicy,
+) -> (VisualMeta, bool) {
+    if canonical::same_content(local, remote) {
+        return (local.clone(), false);
+    }
+    if let Some(base) = base {
+        if canonical::same_content(base, local) {
+            return (remote.clone(), false);
+        }
+        if canonical::same_content(base, remote) {
+            return (local.clone(), false);
+        }
+    }
+    let base = base.map(fields).unwrap_or_default();
+    let theirs = fields(remote);
+    let mut out = fields(local);
+    let mut conflict = false;
+    let keys: Vec<String> = out
+        .keys()
+        .chain(theirs.keys())
+        .filter(|k| *k != "updated_at")
+        .cloned()
+        .collect();
+    for key in keys {
+        let (l, r) = (out.get(&key).cloned(), theirs.get(&key));
+        if l.as_ref() == r {
+            continue;
+        }
+        let take_remote = if l.as_ref() == base.get(&key) {
+            true
+        } else if r == base.get(&key) {
+            false
+        } else {
+            conflict = true;
+            policy == ConflictPolicy::Remote
+        };
+        if take_remote {
+            match r {
+                Some(r) => out.insert(key, r.clone()),
+                None => out.remove(&key),
+            };
+        }
+    }
+    let mut merged = serde_json::from_value(Value::Object(out)).unwrap_or_else(|_| local.clone());
+    merged.updated_at = local.updated_at.max(remote.updated_at);
+    (merged, conflict)
+}
+
+/// Сводит локальные записи файла `local` и записи сервера `remote` с
+/// записями последнего обмена `base`. Записи идут в локальном порядке,
+/// новые записи сервера — в конце.
+pub fn merge(
+    base: &[VisualMeta],
+    local: &[VisualMeta],
+    remote: &[VisualMeta],
+    policy: ConflictPolicy,
+) -> Merged {
+    let find = |metas: &'_ [VisualMeta], id: &str| metas.iter().find(|m| m.id == id).cloned();
+    let mut merged = Merged::default();
+    for meta in local {
+        let out = match find(remote, &meta.id) {
+            Some(theirs) => {
+                let base = find(base, &meta.id);
+                let (out, conflict) = merge_meta(base.as_ref(), meta, &theirs, policy);
+                if conflict {
+                    merged.conflicts.push(meta.id.clone());
+                }
+                out
+            }
+            None => meta.clone(),
+        };
+        merged.metas.push(out);
+    }
+    for meta in remote {
+        if !local.iter().any(|m| m.id == meta.id) {
+            merged.metas.push(meta.clone());
+        }
+    }
+    merged
+}
+
+/// Совпадают ли два списка записей без учёта порядка.
+fn same_set(a: &[VisualMeta], b: &[VisualMeta]) -> bool {
+    a.len() == b.len()
+        && a.iter().all(|m| {
+            b.iter()
+                .any(|o| o.id == m.id && canonical::same_content(o, m))
+        })
+}
+
+/// Итог обмена одним файлом.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FileSync {
+    /// Путь файла от корня проекта через `/`.
+    pub file: String,
+    /// Записи отправлены на сервер.
+    pub pushed: bool,
+    /// Сколько записей файла изменено по данным сервера.
+    pub pulled: usize,
+    /// Записи с конфликтующими правками.
+    pub conflicts: Vec<String>,
+}
+
+/// Обменивается записями файла `file` с содержимым `content`. Возвращает
+/// итог и новое содержимое, если файл изменился.
+fn sync_file(
+    file: &str,
+    content: &str,
+    remote: &dyn MetaRemote,
+    state: &mut SyncState,
+    policy: ConflictPolicy,
+) -> Result<(FileSync, Option<String>), OpError> {
+    let local = read_all(content);
+    let base = state.files.get(file).cloned().unwrap_or_default();
+    for _ in 0..MAX_ATTEMPTS {
+        let theirs = remote.pull(file)?;
+        let merged = merge(&base.base, &local, &theirs.metas, policy);
+        let pushed = !same_set(&merged.metas, &theirs.metas);
+        let version = if pushed {
+            match remote.push(file, theirs.version, &merged.metas)? {
+                PushResult::Accepted(version) => version,
+                PushResult::Conflict(_) => continue,
+            }
+        } else {
+            theirs.version
+        };
+        let mut updated = content.to_string();
+        let mut pulled = 0;
+        for meta in &merged.metas {
+            let same = local
+                .iter()
+                .any(|l| l.id == meta.id && canonical::same_content(l, meta));
+            if !same {
+                updated = reinsert(&updated, meta);
+                pulled += 1;
+            }
+        }
+        state.files.insert(
+            file.to_string(),
+            FileState {
+                version,
+                base: merged.metas,
+            },
+        );
+        let report = FileSync {
+            file: file.to_string(),
+            pushed,
+            pulled,
+            conflicts: merged.conflicts,
+        };
+        return Ok((report, (updated != content).then_some(updated)));
+    }
+    Err(format!("{file}: версия на сервере меняется слишком часто").into())
+}
+
+/// Обменивается записями проекта `root` с сервером `remote`: файлами с
+/// записями, файлами, известными серверу, и файлами прошлых обменов.
+/// Файл пропускается, если с прошлого обмена он не изменился ни локально,
+/// ни на сервере. Изменённые файлы перед записью сохраняются одной
+/// резервной копией. Возвращает итоги по файлам, в которых что-то
+/// произошло; при [`OpError::Offline`] обмен стоит повторить позже.
+pub fn sync_project(
+    root: &Path,
+    remote: &dyn MetaRemote,
+    policy: ConflictPolicy,
+) -> Result<Vec<FileSync>, OpError> {
+    let mut state = SyncState::load(root).map_err(|e| e.to_string())?;
+    let versions: BTreeMap<String, u64> = remote.list()?.into_iter().collect();
+    let mut files: BTreeMap<String, PathBuf> = source_files(root)
+        .into_iter()
+        .map(|path| (rel_path(root, &path), path))
+        .collect();
+    let mut reports = Vec::new();
+    let mut updates = Vec::new();
+    for (file, path) in &mut files {
+        let Ok(content) = file_io::read_to_string(&*path) else {
+            continue;
+        };
+        let local = read_all(&content);
+        let known = state.files.get(file);
+        let remote_version = versions.get(file).copied().unwrap_or(0);
+        let unchanged = match known {
+            Some(known) => known.version == remote_version && same_set(&known.base, &local),
+            None => local.is_empty() && remote_version == 0,
+        };
+        if unchanged {
+            continue;
+        }
+        let (report, updated) = sync_file(file, &content, remote, &mut state, policy)?;
+        if let Some(updated) = updated {
+            updates.push((std::mem::take(path), updated));
+        }
+        if report.pushed || report.pulled > 0 || !report.conflicts.is_empty() {
+            reports.push(report);
+        }
+    }
+    if !updates.is_empty() {
+        let paths: Vec<&Path> = updates.iter().map(|(p, _)| p.as_path()).collect();
+        backup_files(root, &paths).map_err(|e| e.to_string())?;
+        for (path, updated) in &updates {
+            file_io::write_preserving(path, updated).map_err(|e| e.to_string())?;
+        }
+    }
+    state.save(root).map_err(|e| e.to_string())?;
+    Ok(reports)
+}
+
+/// Сервер команды по HTTP:
+///
+/// - `GET {server}/team/{project}/files` — `[{"file": ..., "version": n}]`;
+/// - `GET {server}/team/{project}/meta?file=...` — [`RemoteFile`], `404` —
+///   записей нет;
+/// - `PUT {server}/team/{project}/meta` с телом
+///   `{"file": ..., "base_version": n, "metas": [...]}` — `{"version": n}`,
+///   а если версия уже другая, `409` с текущим [`RemoteFile`].
+#[cfg(feature = "team-sync")]
+pub struct HttpRemote {
+    base: String,
+    token: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "team-sync")]
+impl HttpRemote {
+    /// Клиент по секции `[team_sync]` проекта `root`; ошибка, если `server`
+    /// не задан.
+    pub fn from_config(
+        root: &Path,
+        config: &crate::config::TeamSyncConfig,
+    ) -> Result<Self, String> {
+        let server = config
+            .server
+            .as_deref()
+            .ok_or("в секции [team_sync] не задан server")?;
+        let project = match &config.project {
+            Some(project) => project.clone(),
+            None => root
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .ok_or("не удалось определить имя проекта")?,
+        };
+        let token = config
+            .token_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok());
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            base: format!("{}/team/{project}", server.trim_end_matches('/')),
+            token,
+            client,
+        })
+    }
+
+    fn auth(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+    ) -> reqwest::blocking::RequestBuilder {
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+#[cfg(feature = "team-sync")]
+use crate::offline_queue::http_error;
+
+#[cfg(feature = "team-sync")]
+impl MetaRemote for HttpRemote {
+    fn list(&self) -> Result<Vec<(String, u64)>, OpError> {
+        #[derive(Deserialize)]
+        struct Entry {
+            file: String,
+            version: u64,
+        }
+        let entries: Vec<Entry> = self
+            .auth(self.client.get(format!("{}/files", self.base)))
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .map_err(http_error)?;
+        Ok(entries.into_iter().map(|e| (e.file, e.version)).collect())
+    }
+
+    fn pull(&self, file: &str) -> Result<RemoteFile, OpError> {
+        let response = self
+            .auth(self.client.get(format!("{}/meta", self.base)))
+            .query(&[("file", file)])
+            .send()
+            .map_err(http_error)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(RemoteFile::default());
+        }
+        response
+            .error_for_status()
+            .and_then(|r| r.json())
+            .map_err(http_error)
+    }
+
+    fn push(
+        &self,
+        file: &str,
+        base_version: u64,
+        metas: &[VisualMeta],
+    ) -> Result<PushResult, OpError> {
+        let response = self
+            .auth(self.client.put(format!("{}/meta", self.base)))
+            .json(&serde_json::json!({
+                "file": file,
+                "base_version": base_version,
+                "metas": metas,
+            }))
+            .send()
+            .map_err(http_error)?;
+        if response.status() == reqwest::StatusCode::CONFLICT {
+            return response
+                .json()
+                .map(PushResult::Conflict)
+                .map_err(http_error);
+        }
+        let body: Value = response
+            .error_for_status()
+            .and_then(|r| r.json())
+            .map_err(http_error)?;
+        body.get("version")
+            .and_then(Value::as_u64)
+            .map(PushResult::Accepted)
+            .ok_or_else(|| OpError::Failed("в ответе сервера нет поля version".into()))
+    }
+}
+
+/// Ставит обмен проекта `root` с правилом `policy` в очередь
+/// [`crate::offline_queue`].
+pub fn enqueue(root: &Path, policy: ConflictPolicy) -> io::Result<()> {
+    let mut queue = OfflineQueue::load(root)?;
+    queue.push(
+        QUEUE_KIND,
+        serde_json::json!({ "policy": policy }),
+        Utc::now(),
+    );
+    queue.save(root)
+}
+
+/// Повторяет обмен из очереди с параметрами `payload` по секции
+/// `[team_sync]` проекта `root`.
+#[cfg(feature = "team-sync")]
+pub fn replay(root: &Path, payload: &Value) -> Result<(), OpError> {
+    let config = crate::config::TeamSyncConfig::load(root);
+    let policy = payload
+        .get("policy")
+        .and_then(|p| serde_json::from_value(p.clone()).ok())
+        .unwrap_or(config.conflict);
+    let remote = HttpRemote::from_config(root, &config)?;
+    sync_project(root, &remote, policy).map(|_| ())
+}
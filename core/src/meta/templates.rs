@@ -0,0 +1,174 @@
+//! Шаблоны метаданных проекта.
+//!
+//! Шаблоны описываются в `.multicode/templates.toml` в корне проекта:
+//!
+//! ```toml
+//! [[template]]
+//! name = "handler"
+//! tags = ["net", "api"]
+//! translations = { ru = "Обработчик", en = "Handler" }
+//! ai = { hints = ["проверьте таймауты"] }
+//! ```
+//!
+//! Применённый шаблон записывается в файл базовой записью с
+//! идентификатором `template:<имя>`, а запись блока получает её в
+//! `extends`. Теги, переводы и подсказки шаблона добавляются к полям блока
+//! обычным слиянием наследования, а правка шаблона и повторное применение
+//! обновляют базовую запись у всех блоков файла сразу.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use super::backup::backup_files;
+use super::inheritance::set_extends;
+use super::tags::stored_metas;
+use super::{upsert, AiNote, VisualMeta, DEFAULT_VERSION};
+use crate::saved_search::SavedSearch;
+use crate::{file_io, BlockInfo};
+
+/// Файл шаблонов относительно корня проекта.
+pub const TEMPLATES_FILE: &str = ".multicode/templates.toml";
+
+/// Префикс идентификатора базовой записи шаблона.
+pub const TEMPLATE_ID_PREFIX: &str = "template:";
+
+/// Именованный шаблон метаданных.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MetaTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub translations: HashMap<String, String>,
+    /// Описание и подсказки для блоков шаблона.
+    #[serde(default)]
+    pub ai: Option<AiNote>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TemplatesFile {
+    #[serde(default, rename = "template")]
+    templates: Vec<MetaTemplate>,
+}
+
+impl MetaTemplate {
+    /// Идентификатор базовой записи шаблона.
+    pub fn id(&self) -> String {
+        format!("{TEMPLATE_ID_PREFIX}{}", self.name)
+    }
+
+    /// Базовая запись шаблона, которую наследуют блоки.
+    pub fn base_meta(&self) -> VisualMeta {
+        VisualMeta {
+            version: DEFAULT_VERSION,
+            id: self.id(),
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            tags: self.tags.clone(),
+            links: Vec::new(),
+            anchors: Vec::new(),
+            tests: Vec::new(),
+            extends: None,
+            origin: None,
+            translations: self.translations.clone(),
+            ai: self.ai.clone(),
+            extras: None,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Загружает шаблоны проекта `root`; отсутствующий файл даёт пустой список.
+pub fn load(root: &Path) -> io::Result<Vec<MetaTemplate>> {
+    let data = match fs::read_to_string(root.join(TEMPLATES_FILE)) {
+        Ok(data) => data,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    toml::from_str::<TemplatesFile>(&data)
+        .map(|f| f.templates)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+/// Шаблон проекта `root` с именем `name`.
+pub fn find(root: &Path, name: &str) -> io::Result<Option<MetaTemplate>> {
+    Ok(load(root)?.into_iter().find(|t| t.name == name))
+}
+
+/// Подключает `template` к записи `meta` в `content`: записывает или
+/// обновляет базовую запись шаблона и ставит её в `extends` записи.
+/// Существующий комментарий записи сохраняет свой стиль и остальные поля.
+pub fn apply(content: &str, template: &MetaTemplate, meta: &VisualMeta) -> String {
+    let id = template.id();
+    let out = upsert(content, &template.base_meta(), true);
+    if stored_metas(&out).iter().any(|m| m.id == meta.id) {
+        return set_extends(&out, &meta.id, Some(&id));
+    }
+    let mut meta = meta.clone();
+    meta.extends = Some(id);
+    upsert(&out, &meta, true)
+}
+
+/// Новая запись метаданных блока без своего комментария.
+fn block_meta(block: &BlockInfo) -> VisualMeta {
+    VisualMeta {
+        version: DEFAULT_VERSION,
+        id: block.visual_id.clone(),
+        x: block.x,
+        y: block.y,
+        width: block.width,
+        height: block.height,
+        tags: block.tags.clone(),
+        links: block.links.clone(),
+        anchors: Vec::new(),
+        tests: Vec::new(),
+        extends: None,
+        origin: None,
+        translations: block.translations.clone(),
+        ai: None,
+        extras: None,
+        updated_at: Utc::now(),
+    }
+}
+
+/// Применяет `template` ко всем блокам проекта `root`, найденным `search`.
+///
+/// Перед записью изменённые файлы сохраняются одной резервной копией.
+/// Возвращает список изменённых файлов.
+pub fn apply_matching(
+    root: &Path,
+    template: &MetaTemplate,
+    search: &SavedSearch,
+) -> io::Result<Vec<PathBuf>> {
+    let mut hits: BTreeMap<PathBuf, Vec<BlockInfo>> = BTreeMap::new();
+    for hit in search.run(root) {
+        hits.entry(hit.file).or_default().push(hit.block);
+    }
+    let mut updates = Vec::new();
+    for (path, blocks) in hits {
+        let content = file_io::read_to_string(&path)?;
+        let mut updated = content.clone();
+        for block in &blocks {
+            updated = apply(&updated, template, &block_meta(block));
+        }
+        if updated != content {
+            updates.push((path, updated));
+        }
+    }
+    if updates.is_empty() {
+        return Ok(Vec::new());
+    }
+    let paths: Vec<&Path> = updates.iter().map(|(p, _)| p.as_path()).collect();
+    backup_files(root, &paths)?;
+    for (path, updated) in &updates {
+        file_io::write_preserving(path, updated)?;
+    }
+    Ok(updates.into_iter().map(|(p, _)| p).collect())
+}
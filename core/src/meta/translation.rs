@@ -0,0 +1,362 @@
+//! Полнота переводов метаданных и машинный перевод.
+//!
+//! [`coverage`] считает, у какой доли записей `@VISUAL_META` в каждом
+//! каталоге проекта есть перевод на каждый язык. [`plan_fill`] заполняет
+//! недостающие переводы через [`Translator`] — например, HTTP-сервис из
+//! секции `[translate]` ([`HttpTranslator`], флаг `translate`), а
+//! [`apply_fill`] записывает их. Заполненные так языки перечисляются в
+//! `extras.machine_translated`, чтобы их можно было найти и вычитать;
+//! с машинного перевода ничего не переводится повторно.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::backup::backup_files;
+use super::csv::rel_path;
+use super::tags::stored_metas;
+use super::templates::TEMPLATE_ID_PREFIX;
+use super::{canonical, comment_detector, read_all, VisualMeta};
+use crate::blocks::source_files;
+use crate::file_io;
+
+/// Ключ `extras` со списком языков, переведённых машинно.
+pub const MACHINE_TRANSLATED: &str = "machine_translated";
+
+/// Полнота переводов записей одного каталога.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DirCoverage {
+    /// Каталог относительно корня проекта через `/`; `.` — корень.
+    pub dir: String,
+    /// Сколько записей в каталоге.
+    pub total: usize,
+    /// Сколько из них переведено на каждый язык.
+    pub translated: BTreeMap<String, usize>,
+}
+
+impl DirCoverage {
+    /// Доля переведённых на `lang` записей в процентах; 100, если
+    /// записей нет.
+    pub fn percent(&self, lang: &str) -> f64 {
+        if self.total == 0 {
+            return 100.0;
+        }
+        let done = self.translated.get(lang).copied().unwrap_or(0);
+        done as f64 * 100.0 / self.total as f64
+    }
+
+    fn add(&mut self, meta: &VisualMeta, languages: &[String]) {
+        self.total += 1;
+        for lang in languages {
+            let count = self.translated.entry(lang.clone()).or_insert(0);
+            if is_translated(meta, lang) {
+                *count += 1;
+            }
+        }
+    }
+}
+
+/// Отчёт о полноте переводов проекта.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct CoverageReport {
+    pub languages: Vec<String>,
+    /// Каталоги по алфавиту.
+    pub dirs: Vec<DirCoverage>,
+    /// Итог по всему проекту.
+    pub total: DirCoverage,
+}
+
+fn is_translated(meta: &VisualMeta, lang: &str) -> bool {
+    meta.translations
+        .get(lang)
+        .is_some_and(|t| !t.trim().is_empty())
+}
+
+/// Языки `extras.machine_translated` записи.
+pub fn machine_translated(meta: &VisualMeta) -> Vec<String> {
+    meta.extras
+        .as_ref()
+        .and_then(|e| e.get(MACHINE_TRANSLATED))
+        .and_then(Value::as_array)
+        .map(|langs| {
+            langs
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn dir_of(root: &Path, path: &Path) -> String {
+    let parent = path
+        .strip_prefix(root)
+        .unwrap_or(path)
+        .parent()
+        .map(|p| {
+            p.components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/")
+        })
+        .unwrap_or_default();
+    if parent.is_empty() {
+        ".".into()
+    } else {
+        parent
+    }
+}
+
+/// Полнота переводов записей проекта `root` на языки `languages` по
+/// каталогам. Пустой список языков — все языки, встретившиеся в записях.
+/// Переводы, полученные через `extends`, учитываются; базовые записи
+/// шаблонов не считаются.
+pub fn coverage(root: &Path, languages: &[String]) -> CoverageReport {
+    let mut files = Vec::new();
+    for path in source_files(root) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let metas: Vec<VisualMeta> = read_all(&content)
+            .into_iter()
+            .filter(|m| !m.id.starts_with(TEMPLATE_ID_PREFIX))
+            .collect();
+        if !metas.is_empty() {
+            files.push((dir_of(root, &path), metas));
+        }
+    }
+    let languages: Vec<String> = if languages.is_empty() {
+        let seen: BTreeSet<&String> = files
+            .iter()
+            .flat_map(|(_, metas)| metas.iter().flat_map(|m| m.translations.keys()))
+            .collect();
+        seen.into_iter().cloned().collect()
+    } else {
+        languages.to_vec()
+    };
+    let mut dirs: BTreeMap<String, DirCoverage> = BTreeMap::new();
+    let mut total = DirCoverage {
+        dir: ".".into(),
+        ..Default::default()
+    };
+    for (dir, metas) in files {
+        let entry = dirs.entry(dir.clone()).or_insert_with(|| DirCoverage {
+            dir,
+            ..Default::default()
+        });
+        for meta in &metas {
+            entry.add(meta, &languages);
+            total.add(meta, &languages);
+        }
+    }
+    CoverageReport {
+        languages,
+        dirs: dirs.into_values().collect(),
+        total,
+    }
+}
+
+/// Сервис машинного перевода.
+pub trait Translator {
+    /// Переводит `text` с языка `source` на `target`.
+    fn translate(&self, text: &str, source: &str, target: &str) -> Result<String, String>;
+}
+
+/// Машинный перевод через HTTP: `POST` на `endpoint` с телом
+/// `{"text": ..., "source": "en", "target": "ru"}`, ответ —
+/// `{"translation": ...}`.
+#[cfg(feature = "translate")]
+pub struct HttpTranslator {
+    endpoint: String,
+    token: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+#[cfg(feature = "translate")]
+impl HttpTranslator {
+    /// Переводчик по секции `[translate]`; ошибка, если `endpoint` не задан.
+    pub fn from_config(config: &crate::config::TranslateConfig) -> Result<Self, String> {
+        let endpoint = config
+            .endpoint
+            .clone()
+            .ok_or("в секции [translate] не задан endpoint")?;
+        let token = config
+            .token_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok());
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            endpoint,
+            token,
+            client,
+        })
+    }
+}
+
+#[cfg(feature = "translate")]
+impl Translator for HttpTranslator {
+    fn translate(&self, text: &str, source: &str, target: &str) -> Result<String, String> {
+        let mut request = self.client.post(&self.endpoint).json(&serde_json::json!({
+            "text": text,
+            "source": source,
+            "target": target,
+        }));
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        let response = request
+            .send()
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?;
+        let body: Value = response.json().map_err(|e| e.to_string())?;
+        body.get("translation")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| "в ответе сервиса нет поля translation".into())
+    }
+}
+
+/// Перевод, заполненный машинно.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filled {
+    /// Путь файла относительно корня проекта через `/`.
+    pub file: String,
+    pub id: String,
+    pub source: String,
+    pub target: String,
+    pub text: String,
+}
+
+/// Машинные переводы, готовые к записи.
+#[derive(Debug, Clone, Default)]
+pub struct FillPlan {
+    pub filled: Vec<Filled>,
+    /// Новое содержимое изменённых файлов.
+    updates: Vec<(PathBuf, String)>,
+}
+
+/// Текст, с которого переводить запись: перевод на `source`, иначе первый
+/// по алфавиту языков заполненный перевод. Машинные переводы не берутся.
+fn source_text<'a>(meta: &'a VisualMeta, source: Option<&str>) -> Option<(&'a str, &'a str)> {
+    let machine = machine_translated(meta);
+    let mut candidates: Vec<(&String, &String)> = meta
+        .translations
+        .iter()
+        .filter(|(lang, text)| !text.trim().is_empty() && !machine.contains(lang))
+        .collect();
+    candidates.sort();
+    candidates
+        .iter()
+        .find(|(lang, _)| Some(lang.as_str()) == source)
+        .or_else(|| candidates.first())
+        .map(|(lang, text)| (lang.as_str(), text.as_str()))
+}
+
+/// Заполняет в `content` переводы записей на языки `languages`, которых
+/// у записей нет. Поля берутся из сохранённых записей без `extends`.
+/// Возвращает новый текст и заполненные переводы с пустым `file`.
+pub fn fill_missing(
+    content: &str,
+    languages: &[String],
+    source: Option<&str>,
+    translator: &dyn Translator,
+) -> Result<(String, Vec<Filled>), String> {
+    let mut edited: BTreeMap<String, VisualMeta> = BTreeMap::new();
+    let mut filled = Vec::new();
+    for mut meta in stored_metas(content) {
+        if edited.contains_key(&meta.id) {
+            continue;
+        }
+        let Some((from, text)) = source_text(&meta, source) else {
+            continue;
+        };
+        let (from, text) = (from.to_string(), text.to_string());
+        let mut machine = machine_translated(&meta);
+        for target in languages {
+            if *target == from || is_translated(&meta, target) {
+                continue;
+            }
+            let translation = translator.translate(&text, &from, target)?;
+            meta.translations
+                .insert(target.clone(), translation.clone());
+            if !machine.contains(target) {
+                machine.push(target.clone());
+            }
+            filled.push(Filled {
+                file: String::new(),
+                id: meta.id.clone(),
+                source: from.clone(),
+                target: target.clone(),
+                text: translation,
+            });
+        }
+        if machine == machine_translated(&meta) {
+            continue;
+        }
+        let mut extras = match meta.extras.take() {
+            Some(Value::Object(obj)) => obj,
+            _ => Default::default(),
+        };
+        extras.insert(MACHINE_TRANSLATED.into(), Value::from(machine));
+        meta.extras = Some(Value::Object(extras));
+        meta.updated_at = Utc::now();
+        edited.insert(meta.id.clone(), meta);
+    }
+    let updated = comment_detector::rewrite(content, |json| {
+        let meta: VisualMeta = serde_json::from_str(json).ok()?;
+        let meta = edited.remove(&meta.id)?;
+        canonical::to_canonical_json(&meta).ok()
+    });
+    Ok((updated, filled))
+}
+
+/// Заполняет недостающие переводы во всех файлах проекта `root`, ничего
+/// не записывая. Первая ошибка сервиса перевода прерывает работу.
+pub fn plan_fill(
+    root: &Path,
+    languages: &[String],
+    source: Option<&str>,
+    translator: &dyn Translator,
+) -> io::Result<FillPlan> {
+    let mut plan = FillPlan::default();
+    for path in source_files(root) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let (updated, filled) = fill_missing(&content, languages, source, translator)
+            .map_err(|e| io::Error::other(format!("{}: {e}", path.display())))?;
+        if filled.is_empty() {
+            continue;
+        }
+        let file = rel_path(root, &path);
+        plan.filled.extend(filled.into_iter().map(|f| Filled {
+            file: file.clone(),
+            ..f
+        }));
+        plan.updates.push((path, updated));
+    }
+    Ok(plan)
+}
+
+/// Записывает машинные переводы `plan` в файлы проекта `root`.
+///
+/// Перед записью изменённые файлы сохраняются одной резервной копией.
+/// Возвращает список изменённых файлов.
+pub fn apply_fill(root: &Path, plan: FillPlan) -> io::Result<Vec<PathBuf>> {
+    if plan.updates.is_empty() {
+        return Ok(Vec::new());
+    }
+    let paths: Vec<&Path> = plan.updates.iter().map(|(p, _)| p.as_path()).collect();
+    backup_files(root, &paths)?;
+    for (path, updated) in &plan.updates {
+        file_io::write_preserving(path, updated)?;
+    }
+    Ok(plan.updates.into_iter().map(|(p, _)| p).collect())
+}
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 
+use crate::anchor::NamedAnchor;
+
 /// Начальное значение версии схемы метаданных.
 pub const DEFAULT_VERSION: u32 = 1;
 
@@ -35,15 +37,23 @@ pub struct VisualMeta {
     pub x: f64,
     /// Координата Y на холсте.
     pub y: f64,
+    /// Необязательная ширина блока на холсте; без неё блок рисуется
+    /// ширины по умолчанию.
+    #[serde(default)]
+    pub width: Option<f64>,
+    /// Необязательная высота блока на холсте.
+    #[serde(default)]
+    pub height: Option<f64>,
     /// Необязательные теги, связанные с этим блоком.
     #[serde(default)]
     pub tags: Vec<String>,
     /// Необязательные ссылки на другие блоки.
     #[serde(default)]
     pub links: Vec<String>,
-    /// Необязательные anchor'ы, связанные с блоком.
+    /// Необязательные anchor'ы, связанные с блоком: имена или объекты с
+    /// подписью и видом.
     #[serde(default)]
-    pub anchors: Vec<String>,
+    pub anchors: Vec<NamedAnchor>,
     /// Необязательные команды тестов для запуска этого блока.
     #[serde(default)]
     pub tests: Vec<String>,
@@ -1,12 +1,28 @@
 use crate::blocks::parse_blocks;
+use crate::config::{run_hook, HookEvent, HooksConfig};
+use crate::file_io;
+use crate::ignore_rules::IgnoreRules;
+use crate::viz_lint;
 use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
-use std::{env, fs, path::PathBuf, sync::mpsc::channel, thread};
+use std::{
+    env,
+    path::{Path, PathBuf},
+    sync::mpsc::channel,
+    thread,
+};
 use tokio::sync::broadcast::Sender;
+use tracing::warn;
 
 /// Запускает фоновый поток, отслеживающий текущий каталог на изменения
 /// исходных файлов и файлов `.meta.json`. При записи файла соответствующий
 /// исходник разбирается, а полученные блоки отправляются в переданный
 /// канал вещания в виде JSON-строки.
+///
+/// После разбора выполняются хуки `on_parse` и `on_lint_error` из
+/// `multicode.toml` текущего каталога, если они настроены. Хуки запускаются в
+/// отдельных потоках, поэтому медленная команда не задерживает обработку
+/// следующих изменений. Файлы, исключённые правилами `.multicode/ignore`, не
+/// разбираются.
 pub fn spawn(tx: Sender<String>) {
     thread::spawn(move || {
         let (fs_tx, fs_rx) = channel::<Event>();
@@ -16,17 +32,27 @@ pub fn spawn(tx: Sender<String>) {
             }
         })
         .expect("наблюдатель");
-        let path = env::current_dir().expect("текущий каталог");
+        let root = env::current_dir().expect("текущий каталог");
         watcher
-            .watch(&path, RecursiveMode::Recursive)
+            .watch(&root, RecursiveMode::Recursive)
             .expect("наблюдение");
+        let hooks = HooksConfig::load(&root);
+        let rules = IgnoreRules::load(&root);
         while let Ok(event) = fs_rx.recv() {
             if let EventKind::Modify(_) = event.kind {
                 if let Some(path) = event.paths.first() {
-                    if let Some(src_path) = source_path(path) {
+                    if let Some(src_path) =
+                        source_path(path).filter(|p| !rules.is_ignored(p, false))
+                    {
                         if let Some(lang) = language_from_path(&src_path) {
-                            if let Ok(content) = fs::read_to_string(&src_path) {
+                            if let Ok(content) = file_io::read_to_string(&src_path) {
+                                // Проверка нужна только хуку on_lint_error.
+                                let lint_errors = match hooks.on_lint_error {
+                                    Some(_) => viz_lint::lint_str(&content),
+                                    None => Vec::new(),
+                                };
                                 if let Some(blocks) = parse_blocks(content, lang.into()) {
+                                    run_hooks(&hooks, &root, &src_path, blocks.len(), lint_errors);
                                     if let Ok(json) = serde_json::to_string(&blocks) {
                                         let _ = tx.send(json);
                                     }
@@ -40,6 +66,37 @@ pub fn spawn(tx: Sender<String>) {
     });
 }
 
+fn run_hooks(
+    hooks: &HooksConfig,
+    root: &Path,
+    src_path: &Path,
+    blocks: usize,
+    lint_errors: Vec<String>,
+) {
+    let path = src_path.to_string_lossy().to_string();
+    let mut events = vec![HookEvent::Parse {
+        path: path.clone(),
+        blocks,
+    }];
+    if !lint_errors.is_empty() {
+        events.push(HookEvent::LintError {
+            path,
+            errors: lint_errors,
+        });
+    }
+    for event in events {
+        let Some(cmd) = hooks.command_for(&event).map(str::to_string) else {
+            continue;
+        };
+        let root = root.to_path_buf();
+        thread::spawn(move || {
+            if let Err(e) = run_hook(&cmd, &event, &root) {
+                warn!("{e}");
+            }
+        });
+    }
+}
+
 fn language_from_path(path: &PathBuf) -> Option<&'static str> {
     match path.extension().and_then(|s| s.to_str()) {
         Some("rs") => Some("rust"),
@@ -0,0 +1,103 @@
+//! Промежуточные точки связей между блоками.
+//!
+//! Точки, через которые пользователь провёл связь на холсте, хранятся в
+//! `extras` записи блока-источника под ключом [`WAYPOINTS_KEY`], по списку
+//! координат `[x, y]` на каждый блок-приёмник:
+//!
+//! ```text
+//! <!-- @VISUAL_META {"id":"1","x":0.0,"y":0.0,"extras":{"waypoints":{"2":[[200.0,40.0],[200.0,120.0]]}}} -->
+//! ```
+
+use std::collections::BTreeMap;
+
+use serde_json::{json, Map, Value};
+
+use super::{ValidationError, VisualMeta};
+
+/// Ключ `extras` с промежуточными точками связей.
+pub const WAYPOINTS_KEY: &str = "waypoints";
+
+fn point(value: &Value) -> Option<(f64, f64)> {
+    match value.as_array()?.as_slice() {
+        [x, y] => Some((x.as_f64()?, y.as_f64()?)),
+        _ => None,
+    }
+}
+
+fn points(value: &Value) -> Option<Vec<(f64, f64)>> {
+    value.as_array()?.iter().map(point).collect()
+}
+
+/// Промежуточные точки связи записи `meta` с блоком `target`.
+pub fn waypoints(meta: &VisualMeta, target: &str) -> Vec<(f64, f64)> {
+    meta.extras
+        .as_ref()
+        .and_then(|e| e.get(WAYPOINTS_KEY))
+        .and_then(|w| w.get(target))
+        .and_then(points)
+        .unwrap_or_default()
+}
+
+/// Промежуточные точки всех связей записи `meta` по блокам-приёмникам.
+/// Некорректные списки пропускаются.
+pub fn all(meta: &VisualMeta) -> BTreeMap<String, Vec<(f64, f64)>> {
+    let Some(Value::Object(map)) = meta.extras.as_ref().and_then(|e| e.get(WAYPOINTS_KEY)) else {
+        return BTreeMap::new();
+    };
+    map.iter()
+        .filter_map(|(target, value)| Some((target.clone(), points(value)?)))
+        .collect()
+}
+
+/// Записывает промежуточные точки связи с блоком `target`, сохраняя
+/// остальные ключи `extras`. Пустой список удаляет связь из
+/// [`WAYPOINTS_KEY`]; пустые объекты заменяются на `None`.
+pub fn set_waypoints(meta: &mut VisualMeta, target: &str, points: &[(f64, f64)]) {
+    let mut extras = meta.extras.take().unwrap_or_else(|| json!({}));
+    if let Some(obj) = extras.as_object_mut() {
+        let mut waypoints = match obj.remove(WAYPOINTS_KEY) {
+            Some(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        if points.is_empty() {
+            waypoints.remove(target);
+        } else {
+            let list = points.iter().map(|&(x, y)| json!([x, y])).collect();
+            waypoints.insert(target.into(), Value::Array(list));
+        }
+        if !waypoints.is_empty() {
+            obj.insert(WAYPOINTS_KEY.into(), Value::Object(waypoints));
+        }
+        if obj.is_empty() {
+            return;
+        }
+    }
+    meta.extras = Some(extras);
+}
+
+/// Переносит промежуточные точки всех связей из `from` в `to`, заменяя
+/// прежние точки `to`.
+pub fn copy(from: &VisualMeta, to: &mut VisualMeta) {
+    for target in all(to).into_keys() {
+        set_waypoints(to, &target, &[]);
+    }
+    for (target, points) in all(from) {
+        set_waypoints(to, &target, &points);
+    }
+}
+
+/// Проверяет формат промежуточных точек в `extras`.
+pub(super) fn validate(meta: &VisualMeta, errors: &mut Vec<ValidationError>) {
+    let Some(value) = meta.extras.as_ref().and_then(|e| e.get(WAYPOINTS_KEY)) else {
+        return;
+    };
+    let valid = value
+        .as_object()
+        .is_some_and(|map| map.values().all(|v| points(v).is_some()));
+    if !valid {
+        errors.push(ValidationError {
+            field: "extras.waypoints".into(),
+            message: "точки связей должны иметь вид {\"id\": [[x, y], ...]}".into(),
+        });
+    }
+}
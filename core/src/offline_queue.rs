@@ -0,0 +1,248 @@
+//! Очередь операций, ожидающих связи с сервером.
+//!
+//! Операция, которую не удалось выполнить из-за отсутствия связи
+//! ([`OpError::Offline`]), сохраняется в `.multicode/offline_queue.json` и
+//! повторяется позже ([`replay_project`]). Перерыв между попытками растёт
+//! вдвое от [`BASE_BACKOFF`] до [`MAX_BACKOFF`]. Операция, завершившаяся
+//! другой ошибкой ([`OpError::Failed`]), из очереди удаляется. Если связи
+//! по-прежнему нет, остальные операции в этот раз не выполняются.
+//!
+//! Операции выполняются по виду ([`run`]): сейчас это обмен метаданными с
+//! сервером команды ([`crate::meta::team_sync`], флаг `team-sync`).
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Файл очереди относительно корня проекта.
+pub const QUEUE_FILE: &str = ".multicode/offline_queue.json";
+
+/// Перерыв перед первой повторной попыткой.
+pub const BASE_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Наибольший перерыв между попытками.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// Ошибка операции, обращающейся к серверу.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpError {
+    /// Сервер недоступен; операцию стоит повторить позже.
+    Offline(String),
+    /// Сервер отклонил операцию или она не может быть выполнена.
+    Failed(String),
+}
+
+impl fmt::Display for OpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Offline(e) | Self::Failed(e) => f.write_str(e),
+        }
+    }
+}
+
+impl From<String> for OpError {
+    fn from(e: String) -> Self {
+        Self::Failed(e)
+    }
+}
+
+/// Ошибка HTTP-запроса: нет соединения, истекло время ожидания или
+/// сервер временно не отвечает — [`OpError::Offline`].
+#[cfg(any(feature = "team-sync", feature = "ai", feature = "embeddings"))]
+pub(crate) fn http_error(e: reqwest::Error) -> OpError {
+    let unavailable =
+        e.is_connect() || e.is_timeout() || e.status().is_some_and(|s| s.is_server_error());
+    if unavailable {
+        OpError::Offline(e.to_string())
+    } else {
+        OpError::Failed(e.to_string())
+    }
+}
+
+/// Операция в очереди.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingOp {
+    pub id: u64,
+    /// Вид операции, по которому выбирается обработчик.
+    pub kind: String,
+    /// Параметры операции.
+    #[serde(default)]
+    pub payload: Value,
+    pub created_at: DateTime<Utc>,
+    /// Сколько раз операция уже не удалась.
+    #[serde(default)]
+    pub attempts: u32,
+    /// Время следующей попытки.
+    pub next_attempt: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+/// Перерыв после `attempts` неудачных попыток.
+pub fn backoff(attempts: u32) -> Duration {
+    let factor = 1u32 << attempts.saturating_sub(1).min(16);
+    BASE_BACKOFF.saturating_mul(factor).min(MAX_BACKOFF)
+}
+
+/// Состояние очереди для показа пользователю.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueueStatus {
+    pub pending: usize,
+    /// Время ближайшей попытки.
+    pub next_attempt: Option<DateTime<Utc>>,
+    /// Последняя ошибка связи.
+    pub last_error: Option<String>,
+}
+
+/// Итог повтора операций.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Выполненные операции.
+    pub done: usize,
+    /// Операции, отложенные из-за отсутствия связи.
+    pub postponed: usize,
+    /// Удалённые из очереди операции: вид и ошибка.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Очередь операций проекта.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OfflineQueue {
+    #[serde(default)]
+    next_id: u64,
+    #[serde(default)]
+    pub ops: Vec<PendingOp>,
+}
+
+impl OfflineQueue {
+    /// Загружает очередь проекта `root`; без файла очередь пуста.
+    pub fn load(root: &Path) -> io::Result<Self> {
+        let data = match fs::read_to_string(root.join(QUEUE_FILE)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        serde_json::from_str(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Записывает очередь в файл проекта `root`.
+    pub fn save(&self, root: &Path) -> io::Result<()> {
+        let path = root.join(QUEUE_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_string_pretty(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        fs::write(path, data)
+    }
+
+    /// Ставит операцию в очередь; первая попытка — после [`BASE_BACKOFF`].
+    /// Та же операция, уже стоящая в очереди, не повторяется. Возвращает
+    /// идентификатор операции.
+    pub fn push(&mut self, kind: &str, payload: Value, now: DateTime<Utc>) -> u64 {
+        if let Some(op) = self
+            .ops
+            .iter()
+            .find(|op| op.kind == kind && op.payload == payload)
+        {
+            return op.id;
+        }
+        self.next_id += 1;
+        self.ops.push(PendingOp {
+            id: self.next_id,
+            kind: kind.to_string(),
+            payload,
+            created_at: now,
+            attempts: 0,
+            next_attempt: now + backoff(1),
+            last_error: None,
+        });
+        self.next_id
+    }
+
+    /// Состояние очереди.
+    pub fn status(&self) -> QueueStatus {
+        QueueStatus {
+            pending: self.ops.len(),
+            next_attempt: self.ops.iter().map(|op| op.next_attempt).min(),
+            last_error: self
+                .ops
+                .iter()
+                .filter(|op| op.last_error.is_some())
+                .max_by_key(|op| op.next_attempt)
+                .and_then(|op| op.last_error.clone()),
+        }
+    }
+
+    /// Выполняет операции, время которых пришло к `now` (с `force` — все),
+    /// функцией `run`. После первой ошибки связи остальные операции
+    /// откладываются без попытки.
+    pub fn replay(
+        &mut self,
+        now: DateTime<Utc>,
+        force: bool,
+        mut run: impl FnMut(&PendingOp) -> Result<(), OpError>,
+    ) -> ReplayReport {
+        let mut report = ReplayReport::default();
+        let mut offline = false;
+        let mut kept = Vec::new();
+        for mut op in std::mem::take(&mut self.ops) {
+            if offline || (!force && op.next_attempt > now) {
+                kept.push(op);
+                continue;
+            }
+            match run(&op) {
+                Ok(()) => report.done += 1,
+                Err(OpError::Offline(e)) => {
+                    offline = true;
+                    op.attempts += 1;
+                    op.next_attempt = now + backoff(op.attempts);
+                    op.last_error = Some(e);
+                    report.postponed += 1;
+                    kept.push(op);
+                }
+                Err(OpError::Failed(e)) => report.failed.push((op.kind, e)),
+            }
+        }
+        self.ops = kept;
+        report
+    }
+}
+
+/// Выполняет операцию `op` проекта `root` обработчиком её вида.
+pub fn run(root: &Path, op: &PendingOp) -> Result<(), OpError> {
+    match op.kind.as_str() {
+        #[cfg(feature = "team-sync")]
+        crate::meta::team_sync::QUEUE_KIND => crate::meta::team_sync::replay(root, &op.payload),
+        // Без флага обработчика нет, но операция остаётся в очереди для
+        // сборки, в которой он есть.
+        #[cfg(not(feature = "team-sync"))]
+        crate::meta::team_sync::QUEUE_KIND => {
+            let _ = root;
+            Err(OpError::Offline("requires the `team-sync` feature".into()))
+        }
+        kind => Err(OpError::Failed(format!("unknown operation '{kind}'"))),
+    }
+}
+
+/// Повторяет операции очереди проекта `root`, время которых пришло к
+/// `now` (с `force` — все), и сохраняет очередь.
+pub fn replay_project(
+    root: &Path,
+    now: DateTime<Utc>,
+    force: bool,
+) -> io::Result<(ReplayReport, QueueStatus)> {
+    let mut queue = OfflineQueue::load(root)?;
+    if queue.ops.is_empty() {
+        return Ok((ReplayReport::default(), QueueStatus::default()));
+    }
+    let report = queue.replay(now, force, |op| run(root, op));
+    queue.save(root)?;
+    Ok((report, queue.status()))
+}
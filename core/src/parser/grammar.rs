@@ -0,0 +1,139 @@
+//! Сведения о версиях грамматик tree-sitter.
+//!
+//! Версии грамматик закреплены в `core/Cargo.toml` через `=x.y.z` и
+//! продублированы в [`GRAMMAR_VERSIONS`]: обновление грамматики меняет
+//! набор блоков, поэтому должно быть явным. Таблица сверяется с манифестом
+//! в тестах.
+
+use serde::Serialize;
+use tree_sitter::Tree;
+
+use super::{language, Lang};
+
+/// Закреплённые версии крейтов грамматик для каждого языка.
+pub const GRAMMAR_VERSIONS: &[(Lang, &str, &str)] = &[
+    (Lang::Rust, "tree-sitter-rust", "0.23.3"),
+    (Lang::Python, "tree-sitter-python", "0.23.6"),
+    (Lang::JavaScript, "tree-sitter-javascript", "0.23.1"),
+    (Lang::Css, "tree-sitter-css", "0.23.2"),
+    (Lang::Html, "tree-sitter-html", "0.23.2"),
+    (Lang::Go, "tree-sitter-go", "0.23.4"),
+    (Lang::TypeScript, "tree-sitter-typescript", "0.23.2"),
+    (Lang::C, "tree-sitter-c", "0.23.4"),
+    (Lang::Cpp, "tree-sitter-cpp", "0.23.4"),
+    (Lang::Java, "tree-sitter-java", "0.23.5"),
+    (Lang::CSharp, "tree-sitter-c-sharp", "0.23.1"),
+];
+
+/// Версия и возможности грамматики одного языка.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrammarInfo {
+    pub lang: Lang,
+    /// Имя крейта грамматики.
+    pub crate_name: &'static str,
+    /// Закреплённая версия крейта грамматики.
+    pub version: &'static str,
+    /// Версия ABI, с которой сгенерирован парсер.
+    pub abi: usize,
+    /// Количество типов узлов в грамматике.
+    pub node_kinds: usize,
+    /// Количество именованных полей в грамматике.
+    pub fields: usize,
+}
+
+/// Диагностика: сохранённое дерево получено другой грамматикой.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GrammarMismatch {
+    pub lang: Lang,
+    /// Версия ABI текущей грамматики.
+    pub expected_abi: usize,
+    /// Версия ABI грамматики, которой построено дерево.
+    pub found_abi: usize,
+    /// Закреплённая версия текущей грамматики.
+    pub expected_version: &'static str,
+}
+
+impl std::fmt::Display for GrammarMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "tree for {} was produced by another grammar (ABI {}, expected {} of {})",
+            self.lang, self.found_abi, self.expected_abi, self.expected_version
+        )
+    }
+}
+
+/// Возвращает сведения о грамматике языка `lang`.
+pub fn grammar_info_for(lang: Lang) -> GrammarInfo {
+    let (crate_name, version) = GRAMMAR_VERSIONS
+        .iter()
+        .find(|(l, _, _)| *l == lang)
+        .map(|(_, name, version)| (*name, *version))
+        .unwrap_or(("unknown", "unknown"));
+    let language = language(lang);
+    GrammarInfo {
+        lang,
+        crate_name,
+        version,
+        abi: language.version(),
+        node_kinds: language.node_kind_count(),
+        fields: language.field_count(),
+    }
+}
+
+/// Отчёт о версиях и возможностях всех поддерживаемых грамматик.
+pub fn grammar_info() -> Vec<GrammarInfo> {
+    GRAMMAR_VERSIONS
+        .iter()
+        .map(|(lang, _, _)| grammar_info_for(*lang))
+        .collect()
+}
+
+/// Проверяет, что `tree` построено текущей грамматикой языка `lang`.
+///
+/// При расхождении пишет структурированное предупреждение в журнал и
+/// возвращает [`GrammarMismatch`]; такое дерево нельзя использовать для
+/// инкрементального разбора.
+pub fn check_tree(tree: &Tree, lang: Lang) -> Option<GrammarMismatch> {
+    let current = language(lang);
+    let found = tree.language();
+    if *found == current {
+        return None;
+    }
+    let mismatch = GrammarMismatch {
+        lang,
+        expected_abi: current.version(),
+        found_abi: found.version(),
+        expected_version: grammar_info_for(lang).version,
+    };
+    tracing::warn!(
+        lang = %mismatch.lang,
+        expected_abi = mismatch.expected_abi,
+        found_abi = mismatch.found_abi,
+        expected_version = mismatch.expected_version,
+        "сохранённое дерево разбора получено другой грамматикой"
+    );
+    Some(mismatch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse;
+
+    #[test]
+    fn reports_every_language() {
+        let info = grammar_info();
+        assert_eq!(info.len(), GRAMMAR_VERSIONS.len());
+        assert!(info.iter().all(|i| i.abi > 0 && i.node_kinds > 0));
+    }
+
+    #[test]
+    fn detects_tree_from_other_grammar() {
+        let tree = parse("fn main() {}", Lang::Rust, None).unwrap();
+        assert!(check_tree(&tree, Lang::Rust).is_none());
+        let mismatch = check_tree(&tree, Lang::Python).unwrap();
+        assert_eq!(mismatch.lang, Lang::Python);
+        assert_eq!(mismatch.expected_version, "0.23.6");
+    }
+}
@@ -0,0 +1,259 @@
+//! Подсветка синтаксиса по дереву разбора tree-sitter.
+//!
+//! Участки подсветки берутся из запросов `highlights.scm`, которые
+//! поставляются вместе с грамматиками, поэтому подсветка совпадает с
+//! разбором и доступна для всех языков [`Lang`]. [`DocumentHighlights`]
+//! хранит дерево документа в общем хранилище деревьев
+//! ([`get_document_tree`]) и после правки разбирает текст инкрементально,
+//! перестраивая подсветку только изменившихся строк.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+use tree_sitter::{InputEdit, Point, Query, QueryCursor, Tree};
+
+use super::{check_tree, language, parse, Lang};
+use crate::{get_document_tree, remove_document_tree, update_document_tree};
+
+/// Участки одной строки: байтовый диапазон от начала строки и имя захвата
+/// запроса подсветки, например `keyword` или `function.method`.
+pub type LineHighlights = Vec<(Range<usize>, &'static str)>;
+
+/// Собранные запросы подсветки; `None`, если запрос не собрался.
+static QUERIES: Lazy<Mutex<HashMap<Lang, Option<&'static Query>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn query_source(lang: Lang) -> String {
+    match lang {
+        Lang::Rust => tree_sitter_rust::HIGHLIGHTS_QUERY.into(),
+        Lang::Python => tree_sitter_python::HIGHLIGHTS_QUERY.into(),
+        Lang::JavaScript => tree_sitter_javascript::HIGHLIGHT_QUERY.into(),
+        Lang::Css => tree_sitter_css::HIGHLIGHTS_QUERY.into(),
+        Lang::Html => tree_sitter_html::HIGHLIGHTS_QUERY.into(),
+        Lang::Go => tree_sitter_go::HIGHLIGHTS_QUERY.into(),
+        // Запросы TypeScript и C++ только дополняют запросы JavaScript и C.
+        Lang::TypeScript => format!(
+            "{}\n{}",
+            tree_sitter_javascript::HIGHLIGHT_QUERY,
+            tree_sitter_typescript::HIGHLIGHTS_QUERY
+        ),
+        Lang::C => tree_sitter_c::HIGHLIGHT_QUERY.into(),
+        Lang::Cpp => format!(
+            "{}\n{}",
+            tree_sitter_c::HIGHLIGHT_QUERY,
+            tree_sitter_cpp::HIGHLIGHT_QUERY
+        ),
+        Lang::Java => tree_sitter_java::HIGHLIGHTS_QUERY.into(),
+        Lang::CSharp => include_str!("queries/c_sharp_highlights.scm").into(),
+    }
+}
+
+/// Запрос подсветки для `lang`. Запрос собирается при первом обращении и
+/// живёт до конца работы программы.
+pub fn highlight_query(lang: Lang) -> Option<&'static Query> {
+    let mut queries = QUERIES.lock().ok()?;
+    *queries
+        .entry(lang)
+        .or_insert_with(|| match Query::new(&language(lang), &query_source(lang)) {
+            Ok(query) => Some(Box::leak(Box::new(query))),
+            Err(e) => {
+                tracing::error!("запрос подсветки для {lang} не собран: {e}");
+                None
+            }
+        })
+}
+
+/// Подсветка строк `lines` текста `source`, разобранного в `tree`.
+///
+/// Если один узел захвачен несколькими шаблонами, действует последний из
+/// них: запросы грамматик начинаются с общих шаблонов и уточняют их ниже.
+/// Участки вложенных узлов идут после объемлющих и перекрывают их.
+pub fn highlight_lines(
+    tree: &Tree,
+    source: &str,
+    lang: Lang,
+    lines: Range<usize>,
+) -> Vec<LineHighlights> {
+    let starts = line_starts(source);
+    let lines = lines.start.min(starts.len())..lines.end.min(starts.len());
+    let mut result = vec![Vec::new(); lines.len()];
+    let Some(query) = highlight_query(lang) else {
+        return result;
+    };
+    let names = query.capture_names();
+    let mut cursor = QueryCursor::new();
+    cursor.set_point_range(Point::new(lines.start, 0)..Point::new(lines.end, 0));
+    let mut spans: Vec<(Range<usize>, usize, &'static str)> = cursor
+        .captures(query, tree.root_node(), source.as_bytes())
+        .map(|(m, i)| {
+            let capture = m.captures[i];
+            let name = names[capture.index as usize];
+            (capture.node.byte_range(), m.pattern_index, name)
+        })
+        // Служебные захваты нужны только условиям шаблонов.
+        .filter(|(range, _, name)| !name.starts_with('_') && !range.is_empty())
+        .collect();
+    spans.sort_by(|a, b| (a.0.start, b.0.end, b.1).cmp(&(b.0.start, a.0.end, a.1)));
+    spans.dedup_by(|later, first| later.0 == first.0);
+    for (range, _, name) in spans {
+        let first = starts.partition_point(|s| *s <= range.start) - 1;
+        for line in first.max(lines.start)..lines.end {
+            let start = starts[line];
+            if start >= range.end {
+                break;
+            }
+            let end = starts.get(line + 1).map_or(source.len(), |next| next - 1);
+            let span = range.start.max(start) - start..range.end.min(end) - start;
+            if !span.is_empty() {
+                result[line - lines.start].push((span, name));
+            }
+        }
+    }
+    result
+}
+
+/// Смещения начал строк текста.
+fn line_starts(source: &str) -> Vec<usize> {
+    std::iter::once(0)
+        .chain(source.match_indices('\n').map(|(i, _)| i + 1))
+        .collect()
+}
+
+/// Позиция tree-sitter для байтового смещения `offset`.
+fn point_at(source: &str, offset: usize) -> Point {
+    let before = &source.as_bytes()[..offset];
+    let row = before.iter().filter(|b| **b == b'\n').count();
+    let column = offset
+        - before
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |i| i + 1);
+    Point::new(row, column)
+}
+
+/// Подсветка открытого документа.
+///
+/// Дерево разбора хранится под идентификатором документа и удаляется
+/// вместе с подсветкой.
+#[derive(Debug)]
+pub struct DocumentHighlights {
+    id: String,
+    lang: Lang,
+    source: String,
+    lines: Arc<Vec<LineHighlights>>,
+}
+
+impl DocumentHighlights {
+    /// Разбирает и подсвечивает `source` целиком; `None`, если разбор не
+    /// удался.
+    pub fn new(id: impl Into<String>, source: &str, lang: Lang) -> Option<Self> {
+        let id = id.into();
+        let tree = parse(source, lang, None)?;
+        let lines = highlight_lines(&tree, source, lang, 0..usize::MAX);
+        update_document_tree(id.clone(), tree);
+        Some(Self {
+            id,
+            lang,
+            source: source.to_string(),
+            lines: Arc::new(lines),
+        })
+    }
+
+    pub fn lang(&self) -> Lang {
+        self.lang
+    }
+
+    /// Построена ли подсветка для текста `source`.
+    pub fn is_current(&self, source: &str) -> bool {
+        self.source == source
+    }
+
+    /// Подсветка по строкам текста. Копия `Arc` дёшева, поэтому её можно
+    /// передавать в отрисовку на каждом кадре.
+    pub fn lines(&self) -> &Arc<Vec<LineHighlights>> {
+        &self.lines
+    }
+
+    /// Переходит к новому тексту документа. Правка вычисляется по общему
+    /// началу и концу старого и нового текста и переносится в сохранённое
+    /// дерево; подсветка перестраивается для строк правки и строк, где
+    /// изменилась структура дерева. Возвращает `false`, если разбор не
+    /// удался и подсветка устарела.
+    pub fn update(&mut self, source: &str) -> bool {
+        if self.source == source {
+            return true;
+        }
+        let Some(mut tree) =
+            get_document_tree(&self.id).filter(|t| check_tree(t, self.lang).is_none())
+        else {
+            // Дерева нет — документ разбирается заново.
+            let Some(tree) = parse(source, self.lang, None) else {
+                return false;
+            };
+            self.lines = Arc::new(highlight_lines(&tree, source, self.lang, 0..usize::MAX));
+            self.source = source.to_string();
+            update_document_tree(self.id.clone(), tree);
+            return true;
+        };
+        let (old, new) = (self.source.as_bytes(), source.as_bytes());
+        let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let edit = InputEdit {
+            start_byte: prefix,
+            old_end_byte: old.len() - suffix,
+            new_end_byte: new.len() - suffix,
+            start_position: point_at(&self.source, prefix),
+            old_end_position: point_at(&self.source, old.len() - suffix),
+            new_end_position: point_at(source, new.len() - suffix),
+        };
+        tree.edit(&edit);
+        let Some(new_tree) = parse(source, self.lang, Some(&tree)) else {
+            return false;
+        };
+
+        // Строки после правки сдвигаются вместе с текстом.
+        let start = edit.start_position.row;
+        let old_end = edit.old_end_position.row;
+        let new_end = edit.new_end_position.row;
+        let lines = Arc::make_mut(&mut self.lines);
+        let tail = lines.len().min(old_end + 1);
+        lines.splice(start.min(tail)..tail, vec![Vec::new(); new_end + 1 - start]);
+
+        let mut dirty: Vec<Range<usize>> = tree
+            .changed_ranges(&new_tree)
+            .map(|r| r.start_point.row..r.end_point.row + 1)
+            .collect();
+        dirty.push(start..new_end + 1);
+        dirty.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<usize>> = Vec::new();
+        for range in dirty {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        for range in merged {
+            let fresh = highlight_lines(&new_tree, source, self.lang, range.clone());
+            let end = (range.start + fresh.len()).min(lines.len());
+            if range.start < end {
+                lines.splice(range.start..end, fresh);
+            }
+        }
+        self.source = source.to_string();
+        update_document_tree(self.id.clone(), new_tree);
+        true
+    }
+}
+
+impl Drop for DocumentHighlights {
+    fn drop(&mut self) {
+        remove_document_tree(&self.id);
+    }
+}
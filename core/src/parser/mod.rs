@@ -1,9 +1,11 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fmt::{self, Display};
 use std::ops::Range;
 use tree_sitter::{Language, Node, Parser, Tree};
 
+use crate::anchor::{Anchor, AnchorKind};
+
 #[macro_export]
 macro_rules! define_lang_parser {
     ($lang:expr) => {
@@ -26,6 +28,8 @@ pub mod c_sharp;
 pub mod cpp;
 pub mod css;
 pub mod go;
+pub mod grammar;
+pub mod highlight;
 pub mod html;
 pub mod java;
 pub mod javascript;
@@ -35,7 +39,7 @@ pub mod typescript;
 pub mod viz_comments;
 
 /// Поддерживаемые языки для парсинга.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Lang {
     Rust,
     Python,
@@ -90,8 +94,10 @@ impl std::str::FromStr for Lang {
     }
 }
 
+pub use grammar::{check_tree, grammar_info, GrammarInfo, GrammarMismatch};
+
 /// Возвращает [`Language`] tree-sitter из [`Lang`].
-fn language(lang: Lang) -> Language {
+pub(crate) fn language(lang: Lang) -> Language {
     match lang {
         Lang::Rust => rust::language(),
         Lang::Python => python::language(),
@@ -129,7 +135,58 @@ pub struct Block {
     /// Байтовый диапазон узла в исходнике.
     pub range: Range<usize>,
     /// Якоря, указывающие на диапазоны в исходном коде.
-    pub anchors: Vec<(usize, usize)>,
+    pub anchors: Vec<Anchor>,
+}
+
+/// Приводит тип узла tree-sitter к типу блока.
+pub(crate) fn map_kind(kind: &str) -> String {
+    match kind {
+        "+" | "-" | "*" | "/" | "%" | "&&" | "||" | "==" | "!=" | ">" | ">=" | "<" | "<=" => {
+            format!("Op/{kind}")
+        }
+        "?" => "Op/Ternary".into(),
+        "identifier" => "Variable/Get".into(),
+        _ => {
+            let k = kind.to_lowercase();
+            if k.contains("call") && !k.contains("function") {
+                "Function/Call".into()
+            } else if k.contains("return") {
+                "Return".into()
+            } else if k.contains("function") || k.contains("method") {
+                "Function/Define".into()
+            } else {
+                kind.to_string()
+            }
+        }
+    }
+}
+
+/// Якоря узла типа `kind`: сам оператор или переменная, а у определения
+/// функции — её параметры и возвращаемый тип.
+fn node_anchors(node: Node, kind: &str) -> Vec<Anchor> {
+    let range = node.byte_range();
+    if kind.starts_with("Op/") || kind == "Variable/Get" {
+        return vec![Anchor::new(range.start, range.end)];
+    }
+    if kind != "Function/Define" {
+        return Vec::new();
+    }
+    let mut anchors = Vec::new();
+    if let Some(params) = node.child_by_field_name("parameters") {
+        let mut cursor = params.walk();
+        for param in params.named_children(&mut cursor) {
+            if param.kind().contains("comment") {
+                continue;
+            }
+            anchors.push(
+                Anchor::new(param.start_byte(), param.end_byte()).with_kind(AnchorKind::Param),
+            );
+        }
+    }
+    if let Some(ret) = node.child_by_field_name("return_type") {
+        anchors.push(Anchor::new(ret.start_byte(), ret.end_byte()).with_kind(AnchorKind::Returns));
+    }
+    anchors
 }
 
 /// Преобразует AST [`Tree`] в плоский список [`Block`].
@@ -148,28 +205,6 @@ pub fn parse_to_blocks(tree: &Tree, prev: Option<&HashMap<u32, String>>) -> Vec<
         })
         .unwrap_or(0);
 
-    fn map_kind(kind: &str) -> String {
-        match kind {
-            "+" | "-" | "*" | "/" | "%" | "&&" | "||" | "==" | "!=" | ">" | ">=" | "<" | "<=" => {
-                format!("Op/{kind}")
-            }
-            "?" => "Op/Ternary".into(),
-            "identifier" => "Variable/Get".into(),
-            _ => {
-                let k = kind.to_lowercase();
-                if k.contains("call") && !k.contains("function") {
-                    "Function/Call".into()
-                } else if k.contains("return") {
-                    "Return".into()
-                } else if k.contains("function") || k.contains("method") {
-                    "Function/Define".into()
-                } else {
-                    kind.to_string()
-                }
-            }
-        }
-    }
-
     fn walk(
         node: Node,
         blocks: &mut Vec<Block>,
@@ -178,11 +213,7 @@ pub fn parse_to_blocks(tree: &Tree, prev: Option<&HashMap<u32, String>>) -> Vec<
     ) {
         let range = node.byte_range();
         let kind = map_kind(node.kind());
-        let anchors = if kind.starts_with("Op/") || kind == "Variable/Get" {
-            vec![(range.start, range.end)]
-        } else {
-            vec![]
-        };
+        let anchors = node_anchors(node, &kind);
 
         let node_id = node.id() as u32;
         let visual_id = if let Some(map) = prev {
@@ -50,6 +50,17 @@ pub fn parse_viz_comments(content: &str) -> VizDocument {
     doc
 }
 
+/// Положение комментариев `@viz` в `content` в том же порядке, что и
+/// записи [`parse_viz_comments`]: номер строки с нуля и диапазон байтов
+/// комментария в строке.
+pub fn viz_comment_spans(content: &str) -> Vec<(usize, std::ops::Range<usize>)> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| VIZ_RE.find(line).map(|m| (i, m.range())))
+        .collect()
+}
+
 fn parse_entry(params: &str) -> VizEntry {
     let mut entry = VizEntry::default();
     for part in params.split_whitespace() {
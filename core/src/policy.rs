@@ -0,0 +1,340 @@
+//! Политики обязательных метаданных.
+//!
+//! Секции `[[policy]]` файла `multicode.toml` ([`PolicyRule`]) требуют от
+//! блоков в файлах по шаблону путей переводов, тегов или описания,
+//! например «у всех публичных функций в `src/api` есть перевод `en` и хотя
+//! бы один тег». Поля проверяются у записи `@VISUAL_META` блока с учётом
+//! `extends`. Политика может и ограничить метрики блоков
+//! ([`crate::analysis::metrics`]), например «сложность функций не больше
+//! 15». Нарушения попадают в `multicode lint` по правилам
+//! [`REQUIRED_META`] и [`METRIC_LIMIT`], а [`check_project`] собирает отчёт
+//! с итогами по каждой политике для CI.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::analysis::metrics::{block_metrics, BlockMetrics, MetricLimit};
+use crate::blocks::{lang_from_extension, parse_blocks, source_files};
+use crate::config::PolicyRule;
+use crate::file_io;
+use crate::lint::{Finding, Span};
+use crate::meta::{read_all, VisualMeta};
+use crate::parser::Lang;
+use crate::text::PositionIndex;
+use crate::BlockInfo;
+
+/// Блоку не хватает метаданных, которых требует политика.
+pub const REQUIRED_META: &str = "policy/required-meta";
+/// Метрика блока превышает ограничение политики.
+pub const METRIC_LIMIT: &str = "policy/metric-limit";
+
+#[derive(Debug, Clone)]
+struct Policy {
+    rule: PolicyRule,
+    /// `None` — политика относится ко всем файлам.
+    matcher: Option<Gitignore>,
+    /// Разобранные ограничения `rule.metrics`.
+    limits: Vec<MetricLimit>,
+}
+
+/// Политики проекта, готовые к проверке файлов.
+#[derive(Debug, Clone, Default)]
+pub struct Policies {
+    policies: Vec<Policy>,
+}
+
+/// Итог одной политики.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PolicySummary {
+    pub name: String,
+    /// Сколько блоков подпало под политику.
+    pub checked: usize,
+    /// Сколько из них её нарушили.
+    pub failed: usize,
+}
+
+/// Отчёт проверки проекта политиками.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PolicyReport {
+    pub policies: Vec<PolicySummary>,
+    pub findings: Vec<Finding>,
+}
+
+impl Policies {
+    /// Политики проекта `root` из `multicode.toml`.
+    pub fn load(root: &Path) -> Self {
+        Self::new(root, PolicyRule::load(root))
+    }
+
+    /// Собирает политики из правил `rules` для проекта `root`. Правило с
+    /// некорректным шаблоном пути пропускается с предупреждением в журнал.
+    pub fn new(root: &Path, rules: Vec<PolicyRule>) -> Self {
+        let mut policies = Vec::new();
+        for rule in rules {
+            let limits = rule
+                .metrics
+                .iter()
+                .filter_map(|limit| {
+                    limit
+                        .parse()
+                        .map_err(|e| warn!("policy {}: {e}", rule.name))
+                        .ok()
+                })
+                .collect();
+            if rule.paths.is_empty() {
+                policies.push(Policy {
+                    rule,
+                    matcher: None,
+                    limits,
+                });
+                continue;
+            }
+            let mut builder = GitignoreBuilder::new(root);
+            for pattern in &rule.paths {
+                if let Err(e) = builder.add_line(None, pattern) {
+                    warn!("policy {}: {pattern}: {e}", rule.name);
+                }
+            }
+            match builder.build() {
+                Ok(matcher) => policies.push(Policy {
+                    rule,
+                    matcher: Some(matcher),
+                    limits,
+                }),
+                Err(e) => warn!("policy {}: {e}", rule.name),
+            }
+        }
+        Self { policies }
+    }
+
+    /// Нет ни одной политики.
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Пустой отчёт с нулевыми итогами всех политик.
+    pub fn report(&self) -> PolicyReport {
+        PolicyReport {
+            policies: self
+                .policies
+                .iter()
+                .map(|p| PolicySummary {
+                    name: p.rule.name.clone(),
+                    checked: 0,
+                    failed: 0,
+                })
+                .collect(),
+            findings: Vec::new(),
+        }
+    }
+
+    /// Проверяет файл `file` (путь относительно корня проекта) с
+    /// содержимым `content` и добавляет итоги в `report`.
+    pub fn check_file(&self, file: &Path, content: &str, report: &mut PolicyReport) {
+        let applicable: Vec<usize> = (0..self.policies.len())
+            .filter(|&i| self.policies[i].applies_to(file))
+            .collect();
+        if applicable.is_empty() {
+            return;
+        }
+        let Some(lang) = file
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension)
+        else {
+            return;
+        };
+        let blocks = parse_blocks(content.to_string(), lang.into()).unwrap_or_default();
+        let metas: HashMap<String, VisualMeta> = read_all(content)
+            .into_iter()
+            .map(|m| (m.id.clone(), m))
+            .collect();
+        let index = PositionIndex::new(content);
+        // Метрики считаются, только если их ограничивает хотя бы одна
+        // политика.
+        let metrics: Vec<Option<BlockMetrics>> = if applicable
+            .iter()
+            .any(|&i| !self.policies[i].limits.is_empty())
+        {
+            match lang.parse::<Lang>() {
+                Ok(lang) => block_metrics(content, lang, &blocks),
+                Err(_) => vec![None; blocks.len()],
+            }
+        } else {
+            vec![None; blocks.len()]
+        };
+        for i in applicable {
+            let policy = &self.policies[i];
+            // Вложенный узел с тем же началом, например ключевое слово
+            // `function`, — тот же блок.
+            let mut starts = HashSet::new();
+            for (block, metrics) in blocks.iter().zip(&metrics) {
+                if !policy.matches(lang, content, block) || !starts.insert(block.range.0) {
+                    continue;
+                }
+                report.policies[i].checked += 1;
+                let missing = if policy.requires_meta() {
+                    policy.missing(metas.get(&block.visual_id))
+                } else {
+                    Vec::new()
+                };
+                let exceeded = metrics
+                    .as_ref()
+                    .map(|m| policy.exceeded(m))
+                    .unwrap_or_default();
+                if missing.is_empty() && exceeded.is_empty() {
+                    continue;
+                }
+                report.policies[i].failed += 1;
+                let end = content[block.range.0..]
+                    .find('\n')
+                    .map_or(block.range.1, |n| block.range.0 + n)
+                    .min(block.range.1);
+                let span = Some(Span::of(&index, block.range.0..end));
+                for (rule, problems) in [(REQUIRED_META, missing), (METRIC_LIMIT, exceeded)] {
+                    if problems.is_empty() {
+                        continue;
+                    }
+                    report.findings.push(Finding {
+                        rule,
+                        severity: policy.rule.severity,
+                        message: format!("{}: {}", policy.rule.name, problems.join("; ")),
+                        file: file.to_path_buf(),
+                        span,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Замечания политик для одного файла.
+    pub fn check_content(&self, file: &Path, content: &str) -> Vec<Finding> {
+        let mut report = self.report();
+        self.check_file(file, content, &mut report);
+        report.findings
+    }
+}
+
+impl Policy {
+    fn applies_to(&self, file: &Path) -> bool {
+        self.matcher
+            .as_ref()
+            .is_none_or(|m| m.matched_path_or_any_parents(file, false).is_ignore())
+    }
+
+    fn matches(&self, lang: &str, content: &str, block: &BlockInfo) -> bool {
+        let kind = block.kind.to_lowercase();
+        if !self.rule.kinds.is_empty()
+            && !self
+                .rule
+                .kinds
+                .iter()
+                .any(|k| kind.starts_with(&k.to_lowercase()))
+        {
+            return false;
+        }
+        !self.rule.public || is_public(lang, content, block)
+    }
+
+    /// Требует ли политика записи `@VISUAL_META`. Политика только с
+    /// ограничениями метрик записи не требует.
+    fn requires_meta(&self) -> bool {
+        let rule = &self.rule;
+        self.limits.is_empty()
+            || !rule.translations.is_empty()
+            || rule.min_tags > 0
+            || rule.description
+    }
+
+    /// Нарушенные ограничения метрик блока.
+    fn exceeded(&self, metrics: &BlockMetrics) -> Vec<String> {
+        self.limits
+            .iter()
+            .filter_map(|limit| {
+                let value = limit.exceeded(metrics)?;
+                Some(format!(
+                    "{} {value}, допустимо не больше {}",
+                    limit.metric.as_str(),
+                    limit.max
+                ))
+            })
+            .collect()
+    }
+
+    /// Чего не хватает записи `meta` блока.
+    fn missing(&self, meta: Option<&VisualMeta>) -> Vec<String> {
+        let rule = &self.rule;
+        let Some(meta) = meta else {
+            return vec!["нет записи @VISUAL_META".into()];
+        };
+        let mut missing = Vec::new();
+        for lang in &rule.translations {
+            if meta
+                .translations
+                .get(lang)
+                .is_none_or(|t| t.trim().is_empty())
+            {
+                missing.push(format!("нет перевода {lang}"));
+            }
+        }
+        if meta.tags.len() < rule.min_tags {
+            missing.push(format!(
+                "тегов {}, нужно не меньше {}",
+                meta.tags.len(),
+                rule.min_tags
+            ));
+        }
+        let described = meta
+            .ai
+            .as_ref()
+            .and_then(|ai| ai.description.as_deref())
+            .is_some_and(|d| !d.trim().is_empty());
+        if rule.description && !described {
+            missing.push("нет описания ai.description".into());
+        }
+        missing
+    }
+}
+
+/// Виден ли блок снаружи модуля: `pub` в Rust, `export` в JavaScript,
+/// имя без `_` в начале в Python. Для остальных языков блок считается
+/// публичным.
+fn is_public(lang: &str, content: &str, block: &BlockInfo) -> bool {
+    let text = content.get(block.range.0..block.range.1).unwrap_or("");
+    match lang {
+        "rust" => text.starts_with("pub ") || text.starts_with("pub\t"),
+        "javascript" => {
+            let line_start = content[..block.range.0].rfind('\n').map_or(0, |i| i + 1);
+            content[line_start..block.range.0]
+                .trim_start()
+                .starts_with("export")
+        }
+        "python" => text
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .find(|w| !w.is_empty() && !matches!(*w, "async" | "def" | "class"))
+            .is_some_and(|name| !name.starts_with('_')),
+        _ => true,
+    }
+}
+
+/// Проверяет политиками все исходные файлы проекта `root`. Пути в
+/// замечаниях указываются относительно `root`.
+pub fn check_project(root: &Path) -> PolicyReport {
+    let policies = Policies::load(root);
+    let mut report = policies.report();
+    if policies.is_empty() {
+        return report;
+    }
+    for path in source_files(root) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let rel: PathBuf = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        policies.check_file(&rel, &content, &mut report);
+    }
+    report
+}
@@ -0,0 +1,92 @@
+//! Проверка метаданных перед коммитом.
+//!
+//! `multicode install-hooks` записывает в репозиторий хук `pre-commit`
+//! ([`HOOK_SCRIPT`]), который запускает `multicode meta fix --check --staged`
+//! и `multicode lint --staged`. Обе команды проверяют текст файлов из индекса
+//! ([`staged_sources`]), а не рабочей копии, и отменяют коммит, только если
+//! нашлись замечания правил из [`PrecommitConfig::blocking`].
+//!
+//! [`PrecommitConfig::blocking`]: crate::config::PrecommitConfig::blocking
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+use crate::blocks::lang_from_extension;
+use crate::git;
+use crate::ignore_rules::IgnoreRules;
+
+/// Строка, по которой хук `pre-commit` узнаётся как установленный multicode.
+pub const HOOK_MARKER: &str = "# multicode pre-commit hook";
+
+/// Текст хука `pre-commit`. Путь к `multicode` можно задать переменной
+/// окружения `MULTICODE`.
+pub const HOOK_SCRIPT: &str = "#!/bin/sh
+# multicode pre-commit hook
+# Установлен командой `multicode install-hooks`; правила, отменяющие коммит,
+# задаются в секции [precommit] файла multicode.toml.
+MULTICODE=${MULTICODE:-multicode}
+\"$MULTICODE\" meta fix --check --staged || exit 1
+\"$MULTICODE\" lint --staged || exit 1
+";
+
+/// Исходные файлы из индекса git с текстом из индекса. Пути указываются
+/// относительно `root`; файлы, исключённые правилами проекта, пропускаются.
+pub fn staged_sources(root: &Path) -> Result<Vec<(PathBuf, String)>, git2::Error> {
+    let rules = IgnoreRules::load(root);
+    let mut files = git::staged_files(root)?;
+    files.retain(|(path, _)| {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(lang_from_extension)
+            .is_some()
+            && !rules.is_ignored(path, false)
+    });
+    Ok(files)
+}
+
+/// Каталог хуков репозитория с учётом настройки `core.hooksPath`. Рабочие
+/// деревья (`git worktree`) используют хуки основного репозитория.
+fn hooks_dir(repo: &Repository) -> Result<PathBuf, git2::Error> {
+    // Каталог рабочего дерева `.git/worktrees/<имя>` хранит путь к общему
+    // каталогу репозитория в файле `commondir`.
+    let common = fs::read_to_string(repo.path().join("commondir"))
+        .map(|dir| repo.path().join(dir.trim()))
+        .unwrap_or_else(|_| repo.path().to_path_buf());
+    let default = common.join("hooks");
+    let Ok(configured) = repo.config()?.get_path("core.hooksPath") else {
+        return Ok(default);
+    };
+    Ok(match repo.workdir() {
+        Some(workdir) if configured.is_relative() => workdir.join(configured),
+        _ => configured,
+    })
+}
+
+/// Записывает хук `pre-commit` в репозиторий каталога `root` и возвращает
+/// путь к нему. Чужой хук заменяется только при `force`, иначе
+/// возвращается ошибка [`io::ErrorKind::AlreadyExists`].
+pub fn install_hook(root: &Path, force: bool) -> io::Result<PathBuf> {
+    let to_io = |e: git2::Error| io::Error::other(e.message().to_string());
+    let (repo, _) = git::locate(root).map_err(to_io)?;
+    let dir = hooks_dir(&repo).map_err(to_io)?;
+    let path = dir.join("pre-commit");
+    if let Ok(existing) = fs::read_to_string(&path) {
+        if !force && !existing.contains(HOOK_MARKER) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("{} уже существует", path.display()),
+            ));
+        }
+    }
+    fs::create_dir_all(&dir)?;
+    fs::write(&path, HOOK_SCRIPT)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
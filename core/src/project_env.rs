@@ -0,0 +1,373 @@
+//! Переменные окружения проекта.
+//!
+//! Переменные хранятся в `.multicode/env` в корне проекта в формате
+//! dotenv:
+//!
+//! ```text
+//! # комментарий
+//! RUST_LOG=debug
+//! GREETING="hello world"
+//! API_TOKEN=@keychain
+//! ```
+//!
+//! Значение `@keychain` означает секрет: сам он хранится в связке ключей
+//! системы ([`SecretStore`], [`SystemKeychain`]), а в файл, который может
+//! попасть в репозиторий, не записывается. Переменные добавляются к
+//! окружению команд терминала, конфигураций запуска, задач сборки, тестов
+//! и хуков ([`vars`]); собственные переменные конфигурации запуска и
+//! терминала важнее переменных проекта. Комментарии файла при сохранении
+//! не сохраняются.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Файл переменных окружения относительно корня проекта.
+pub const ENV_FILE: &str = ".multicode/env";
+
+/// Значение в файле, означающее секрет из связки ключей.
+pub const KEYCHAIN_MARKER: &str = "@keychain";
+
+/// Переменная окружения проекта.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvVar {
+    pub key: String,
+    /// Значение; для секретов — пустое.
+    pub value: String,
+    /// Значение хранится в связке ключей.
+    pub secret: bool,
+}
+
+impl EnvVar {
+    pub fn new(key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: value.into(),
+            secret: false,
+        }
+    }
+
+    pub fn secret(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            value: String::new(),
+            secret: true,
+        }
+    }
+}
+
+/// Переменные окружения проекта в порядке файла.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectEnv {
+    pub vars: Vec<EnvVar>,
+}
+
+impl ProjectEnv {
+    /// Загружает переменные проекта `root`; отсутствующий файл даёт пустой
+    /// список.
+    pub fn load(root: &Path) -> io::Result<Self> {
+        match fs::read_to_string(root.join(ENV_FILE)) {
+            Ok(data) => Ok(Self::parse(&data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Записывает переменные в файл проекта `root`. Значения секретов в
+    /// файл не попадают.
+    pub fn save(&self, root: &Path) -> io::Result<()> {
+        let path = root.join(ENV_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.to_string())
+    }
+
+    /// Разбирает текст в формате dotenv. Строки без `=` и с пустым именем
+    /// пропускаются; повторное имя заменяет прежнее значение.
+    pub fn parse(data: &str) -> Self {
+        let mut env = Self::default();
+        for line in data.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let key = key.trim();
+            if key.is_empty() {
+                continue;
+            }
+            let value = value.trim();
+            let var = if value == KEYCHAIN_MARKER {
+                EnvVar::secret(key)
+            } else {
+                EnvVar::new(key, unquote(value))
+            };
+            env.set(var);
+        }
+        env
+    }
+
+    /// Добавляет переменную или заменяет переменную с тем же именем.
+    pub fn set(&mut self, var: EnvVar) {
+        match self.vars.iter_mut().find(|v| v.key == var.key) {
+            Some(existing) => *existing = var,
+            None => self.vars.push(var),
+        }
+    }
+
+    /// Удаляет переменную `key`. Возвращает удалённую переменную.
+    pub fn remove(&mut self, key: &str) -> Option<EnvVar> {
+        let pos = self.vars.iter().position(|v| v.key == key)?;
+        Some(self.vars.remove(pos))
+    }
+
+    /// Значения переменных для запуска команд проекта `root`: секреты
+    /// читаются из `secrets`. Секреты, которых нет в хранилище, пропускаются
+    /// и возвращаются вторым элементом вместе с причиной.
+    pub fn resolve(
+        &self,
+        root: &Path,
+        secrets: &dyn SecretStore,
+    ) -> (BTreeMap<String, String>, Vec<(String, String)>) {
+        let mut values = BTreeMap::new();
+        let mut missing = Vec::new();
+        for var in &self.vars {
+            if !var.secret {
+                values.insert(var.key.clone(), var.value.clone());
+                continue;
+            }
+            match secrets.get(root, &var.key) {
+                Ok(Some(value)) => {
+                    values.insert(var.key.clone(), value);
+                }
+                Ok(None) => missing.push((var.key.clone(), "not found".into())),
+                Err(e) => missing.push((var.key.clone(), e.to_string())),
+            }
+        }
+        (values, missing)
+    }
+}
+
+impl std::fmt::Display for ProjectEnv {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for var in &self.vars {
+            if var.secret {
+                writeln!(f, "{}={KEYCHAIN_MARKER}", var.key)?;
+            } else {
+                writeln!(f, "{}={}", var.key, quote(&var.value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Значение без кавычек: в двойных кавычках понимаются `\n`, `\"` и `\\`,
+/// в одинарных текст берётся как есть, а без кавычек отбрасывается
+/// комментарий после ` #`.
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('"') {
+        let mut out = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some(other) => out.push(other),
+                    None => break,
+                },
+                c => out.push(c),
+            }
+        }
+        return out;
+    }
+    if let Some(inner) = value.strip_prefix('\'') {
+        return inner.split('\'').next().unwrap_or_default().to_string();
+    }
+    match value.find(" #") {
+        Some(pos) => value[..pos].trim_end().to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Значение для записи в файл; берётся в кавычки, если иначе оно
+/// прочиталось бы по-другому.
+fn quote(value: &str) -> String {
+    let plain = !value.is_empty()
+        && value != KEYCHAIN_MARKER
+        && !value.starts_with(['"', '\''])
+        && !value.contains(" #")
+        && !value.contains('\n')
+        && value.trim() == value;
+    if plain {
+        return value.to_string();
+    }
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+/// Хранилище секретов проекта.
+pub trait SecretStore {
+    /// Секрет `key` проекта `root`; `None`, если его нет.
+    fn get(&self, root: &Path, key: &str) -> io::Result<Option<String>>;
+    /// Сохраняет секрет `key` проекта `root`.
+    fn set(&self, root: &Path, key: &str, value: &str) -> io::Result<()>;
+    /// Удаляет секрет `key` проекта `root`; отсутствие секрета не ошибка.
+    fn delete(&self, root: &Path, key: &str) -> io::Result<()>;
+}
+
+/// Связка ключей системы: `security` в macOS и `secret-tool` (Secret
+/// Service) в Linux. В других системах операции завершаются ошибкой
+/// [`io::ErrorKind::Unsupported`]. Секреты разных проектов различаются
+/// службой `multicode:<корень проекта>`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemKeychain;
+
+impl SystemKeychain {
+    fn service(root: &Path) -> String {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        format!("multicode:{}", root.display())
+    }
+}
+
+fn run_tool(cmd: &mut Command, input: Option<&str>) -> io::Result<std::process::Output> {
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes())?;
+    }
+    child.wait_with_output()
+}
+
+fn tool_error(output: &std::process::Output) -> io::Error {
+    io::Error::other(String::from_utf8_lossy(&output.stderr).trim().to_string())
+}
+
+fn unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "system keychain is not supported on this platform",
+    )
+}
+
+impl SecretStore for SystemKeychain {
+    fn get(&self, root: &Path, key: &str) -> io::Result<Option<String>> {
+        let service = Self::service(root);
+        let output = if cfg!(target_os = "macos") {
+            run_tool(
+                Command::new("security").args([
+                    "find-generic-password",
+                    "-s",
+                    &service,
+                    "-a",
+                    key,
+                    "-w",
+                ]),
+                None,
+            )?
+        } else if cfg!(unix) {
+            run_tool(
+                Command::new("secret-tool").args(["lookup", "service", &service, "account", key]),
+                None,
+            )?
+        } else {
+            return Err(unsupported());
+        };
+        if !output.status.success() {
+            // Оба инструмента сообщают об отсутствии записи ненулевым кодом.
+            return Ok(None);
+        }
+        let value = String::from_utf8_lossy(&output.stdout);
+        Ok(Some(value.strip_suffix('\n').unwrap_or(&value).to_string()))
+    }
+
+    fn set(&self, root: &Path, key: &str, value: &str) -> io::Result<()> {
+        let service = Self::service(root);
+        let output = if cfg!(target_os = "macos") {
+            run_tool(
+                Command::new("security").args([
+                    "add-generic-password",
+                    "-U",
+                    "-s",
+                    &service,
+                    "-a",
+                    key,
+                    "-w",
+                    value,
+                ]),
+                None,
+            )?
+        } else if cfg!(unix) {
+            let label = format!("{service} {key}");
+            run_tool(
+                Command::new("secret-tool").args([
+                    "store", "--label", &label, "service", &service, "account", key,
+                ]),
+                Some(value),
+            )?
+        } else {
+            return Err(unsupported());
+        };
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(tool_error(&output))
+        }
+    }
+
+    fn delete(&self, root: &Path, key: &str) -> io::Result<()> {
+        let service = Self::service(root);
+        if cfg!(target_os = "macos") {
+            run_tool(
+                Command::new("security").args([
+                    "delete-generic-password",
+                    "-s",
+                    &service,
+                    "-a",
+                    key,
+                ]),
+                None,
+            )?;
+        } else if cfg!(unix) {
+            run_tool(
+                Command::new("secret-tool").args(["clear", "service", &service, "account", key]),
+                None,
+            )?;
+        } else {
+            return Err(unsupported());
+        }
+        Ok(())
+    }
+}
+
+/// Переменные окружения проекта `root` для запуска команд. Ошибки чтения
+/// файла и недоступные секреты записываются в журнал и не мешают запуску.
+pub fn vars(root: &Path) -> BTreeMap<String, String> {
+    let env = match ProjectEnv::load(root) {
+        Ok(env) => env,
+        Err(e) => {
+            tracing::warn!(error = %e, "не удалось прочитать переменные окружения проекта");
+            return BTreeMap::new();
+        }
+    };
+    if env.vars.is_empty() {
+        return BTreeMap::new();
+    }
+    let (values, missing) = env.resolve(root, &SystemKeychain);
+    for (key, reason) in missing {
+        tracing::warn!(key, reason, "секрет проекта недоступен");
+    }
+    values
+}
@@ -0,0 +1,146 @@
+//! Недавно изменённые блоки проекта.
+//!
+//! Список хранится в `.multicode/recent_blocks.toml` в корне проекта, от
+//! последней правки к самой давней:
+//!
+//! ```toml
+//! [[block]]
+//! visual_id = "1234"
+//! file = "src/net.rs"
+//! label = "connect"
+//! line = 12
+//! edited_at = "2024-01-01T12:00:00Z"
+//! ```
+//!
+//! Идентификатор блока меняется вместе с его текстом, поэтому запись
+//! узнаётся по файлу и метке: повторная правка той же функции поднимает
+//! её запись наверх, а не добавляет новую.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::blocks::symbols::{symbol_kind, symbol_name};
+use crate::blocks::BlockLocator;
+use crate::text::PositionIndex;
+use crate::BlockInfo;
+
+/// Файл недавних блоков относительно корня проекта.
+pub const RECENT_BLOCKS_FILE: &str = ".multicode/recent_blocks.toml";
+
+/// Больше записей список не хранит.
+pub const RECENT_BLOCKS_LIMIT: usize = 50;
+
+/// Самая длинная метка блока без имени, в символах.
+const MAX_LABEL_CHARS: usize = 60;
+
+/// Изменённый блок.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentBlock {
+    /// Идентификатор блока на момент правки.
+    pub visual_id: String,
+    /// Путь файла относительно корня проекта.
+    pub file: PathBuf,
+    /// Имя функции или класса, иначе первая строка блока.
+    pub label: String,
+    /// Строка начала блока на момент правки, с нуля.
+    pub line: usize,
+    pub edited_at: DateTime<Utc>,
+}
+
+/// Недавно изменённые блоки, от последней правки к самой давней.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecentBlocks {
+    #[serde(default, rename = "block")]
+    pub entries: Vec<RecentBlock>,
+}
+
+impl RecentBlocks {
+    /// Загружает список проекта `root`; отсутствующий файл даёт пустой список.
+    pub fn load(root: &Path) -> io::Result<Self> {
+        let data = match fs::read_to_string(root.join(RECENT_BLOCKS_FILE)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e),
+        };
+        toml::from_str(&data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+    }
+
+    /// Записывает список в файл проекта `root`.
+    pub fn save(&self, root: &Path) -> io::Result<()> {
+        let path = root.join(RECENT_BLOCKS_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = toml::to_string(self)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        fs::write(path, data)
+    }
+
+    /// Ставит `block` первым, заменяя прежнюю запись того же блока.
+    /// Возвращает `true`, если порядок записей изменился и список стоит
+    /// сохранить; повторная правка первого блока лишь обновляет его запись.
+    pub fn record(&mut self, block: RecentBlock) -> bool {
+        let same = |e: &RecentBlock| e.file == block.file && e.label == block.label;
+        if self.entries.first().is_some_and(same) {
+            self.entries[0] = block;
+            return false;
+        }
+        self.entries.retain(|e| !same(e));
+        self.entries.insert(0, block);
+        self.entries.truncate(RECENT_BLOCKS_LIMIT);
+        true
+    }
+
+    /// Забывает записи файла `file`, например после его удаления.
+    pub fn forget_file(&mut self, file: &Path) -> bool {
+        let len = self.entries.len();
+        self.entries.retain(|e| e.file != file);
+        self.entries.len() != len
+    }
+}
+
+/// Запись о правке по смещению `offset` файла `file`: объемлющая функция
+/// или класс, а вне их — блок верхнего уровня. Правка между блоками
+/// верхнего уровня записи не даёт.
+pub fn edited_block(
+    file: &Path,
+    content: &str,
+    blocks: &[BlockInfo],
+    offset: usize,
+    edited_at: DateTime<Utc>,
+) -> Option<RecentBlock> {
+    let locator = BlockLocator::new(blocks);
+    // Цепочка от самого вложенного блока до корня файла.
+    let chain: Vec<usize> =
+        std::iter::successors(locator.block_at(offset), |&i| locator.parent(i)).collect();
+    let scope = chain
+        .iter()
+        .find(|&&i| symbol_kind(&blocks[i].kind).is_some())
+        .or_else(|| chain.iter().rev().nth(1))?;
+    let block = &blocks[*scope];
+    let header = content.get(block.range.0..block.range.1)?;
+    let label = symbol_kind(&block.kind)
+        .and(symbol_name(header))
+        .map(str::to_string)
+        .unwrap_or_else(|| {
+            header
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .chars()
+                .take(MAX_LABEL_CHARS)
+                .collect()
+        });
+    Some(RecentBlock {
+        visual_id: block.visual_id.clone(),
+        file: file.to_path_buf(),
+        label,
+        line: PositionIndex::new(content).line_of(block.range.0),
+        edited_at,
+    })
+}
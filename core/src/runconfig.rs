@@ -0,0 +1,130 @@
+//! Конфигурации запуска, привязанные к блокам.
+//!
+//! Команды хранятся в `extras` под ключом [`RUN_KEY`] — одним объектом или
+//! списком объектов:
+//!
+//! ```text
+//! <!-- @VISUAL_META {"id":"1","x":0.0,"y":0.0,"extras":{"run":{"name":"tests","command":"cargo test -p core","cwd":"core","env":{"RUST_LOG":"debug"}}}} -->
+//! ```
+//!
+//! Каталог `cwd` задаётся относительно корня проекта. Команда выполняется
+//! системной оболочкой ([`Shell::system`]), поэтому в ней допустимы
+//! конвейеры и перенаправления.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::meta::{ValidationError, VisualMeta};
+use crate::project_env;
+use crate::shell::Shell;
+
+/// Ключ `extras` с конфигурациями запуска блока.
+pub const RUN_KEY: &str = "run";
+
+/// Команда запуска блока.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunConfig {
+    /// Название, по которому конфигурацию выбирают среди нескольких.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Команда оболочки.
+    pub command: String,
+    /// Рабочий каталог относительно корня проекта.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    /// Дополнительные переменные окружения; важнее переменных проекта.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+}
+
+impl RunConfig {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            name: None,
+            command: command.into(),
+            cwd: None,
+            env: BTreeMap::new(),
+        }
+    }
+
+    /// Название конфигурации или сама команда, если название не задано.
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.command)
+    }
+
+    /// Рабочий каталог команды в проекте `root`.
+    pub fn working_dir(&self, root: &Path) -> PathBuf {
+        match &self.cwd {
+            Some(cwd) => root.join(cwd),
+            None => root.to_path_buf(),
+        }
+    }
+
+    /// Процесс оболочки, выполняющий команду в проекте `root` с
+    /// переменными окружения проекта.
+    pub fn command(&self, root: &Path) -> Command {
+        let mut cmd = Shell::system().command(&self.command);
+        cmd.current_dir(self.working_dir(root))
+            .envs(project_env::vars(root))
+            .envs(&self.env);
+        cmd
+    }
+}
+
+fn parse(value: &Value) -> Result<Vec<RunConfig>, String> {
+    let configs = match value {
+        Value::Array(items) => items
+            .iter()
+            .map(|v| RunConfig::deserialize(v).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<_>, _>>()?,
+        Value::Object(_) => vec![RunConfig::deserialize(value).map_err(|e| e.to_string())?],
+        _ => return Err("ожидается объект или список объектов".into()),
+    };
+    Ok(configs)
+}
+
+/// Конфигурации запуска блока с метаданными `block`. Некорректное
+/// значение `extras.run` даёт пустой список, ошибку сообщает
+/// [`crate::meta::validate`].
+pub fn resolve(block: &VisualMeta) -> Vec<RunConfig> {
+    block
+        .extras
+        .as_ref()
+        .and_then(|e| e.get(RUN_KEY))
+        .and_then(|v| parse(v).ok())
+        .unwrap_or_default()
+}
+
+/// Конфигурация с названием `name` или первая, если название не указано.
+pub fn find<'a>(configs: &'a [RunConfig], name: Option<&str>) -> Option<&'a RunConfig> {
+    match name {
+        Some(name) => configs.iter().find(|c| c.name.as_deref() == Some(name)),
+        None => configs.first(),
+    }
+}
+
+/// Проверяет конфигурации запуска в `extras`.
+pub(crate) fn validate(meta: &VisualMeta, errors: &mut Vec<ValidationError>) {
+    let Some(value) = meta.extras.as_ref().and_then(|e| e.get(RUN_KEY)) else {
+        return;
+    };
+    let field = format!("extras.{RUN_KEY}");
+    match parse(value) {
+        Ok(configs) => {
+            if configs.iter().any(|c| c.command.trim().is_empty()) {
+                errors.push(ValidationError {
+                    field,
+                    message: "команда запуска не должна быть пустой".into(),
+                });
+            }
+        }
+        Err(e) => errors.push(ValidationError {
+            field,
+            message: format!("некорректная конфигурация запуска: {e}"),
+        }),
+    }
+}
@@ -0,0 +1,221 @@
+//! Сохранённые поиски и умные фильтры блоков.
+//!
+//! Поиски хранятся в `.multicode/searches.toml` в корне проекта:
+//!
+//! ```toml
+//! [[search]]
+//! name = "network"
+//! text = "connect"
+//! tags = ["net"]
+//! kinds = ["Function"]
+//! ```
+//!
+//! Запрос также можно записать одной строкой: `connect tag:net kind:Function`
+//! (см. [`SavedSearch::parse`]).
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::blocks::{lang_from_extension, parse_blocks, source_files};
+use crate::file_io;
+use crate::BlockInfo;
+
+/// Файл сохранённых поисков относительно корня проекта.
+pub const SAVED_SEARCHES_FILE: &str = ".multicode/searches.toml";
+
+/// Сохранённый поиск: текст и фильтры по тегам и видам блоков.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    /// Подстрока метки, вида или первой строки кода блока (без учёта регистра).
+    #[serde(default)]
+    pub text: String,
+    /// Теги, которые должны быть у блока все одновременно.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Виды блоков; достаточно совпадения с префиксом любого из них.
+    #[serde(default)]
+    pub kinds: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SearchesFile {
+    #[serde(default, rename = "search")]
+    searches: Vec<SavedSearch>,
+}
+
+/// Блок, найденный сохранённым поиском.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub file: PathBuf,
+    /// Номер строки начала блока, с нуля.
+    pub line: usize,
+    pub block: BlockInfo,
+}
+
+impl SavedSearch {
+    /// Разбирает строку запроса: слова `tag:<тег>` и `kind:<вид>` становятся
+    /// фильтрами, остальные слова — текстом поиска.
+    pub fn parse(name: &str, query: &str) -> Self {
+        let mut search = SavedSearch {
+            name: name.trim().to_string(),
+            ..Default::default()
+        };
+        let mut text = Vec::new();
+        for word in query.split_whitespace() {
+            if let Some(tag) = word.strip_prefix("tag:").filter(|t| !t.is_empty()) {
+                search.tags.push(tag.to_string());
+            } else if let Some(kind) = word.strip_prefix("kind:").filter(|k| !k.is_empty()) {
+                search.kinds.push(kind.to_string());
+            } else {
+                text.push(word);
+            }
+        }
+        search.text = text.join(" ");
+        search
+    }
+
+    /// Строка запроса, из которой [`SavedSearch::parse`] восстановит поиск.
+    pub fn query(&self) -> String {
+        let tags = self.tags.iter().map(|t| format!("tag:{t}"));
+        let kinds = self.kinds.iter().map(|k| format!("kind:{k}"));
+        std::iter::once(self.text.clone())
+            .filter(|t| !t.is_empty())
+            .chain(tags)
+            .chain(kinds)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Пустой поиск без текста и фильтров ничего не находит.
+    pub fn is_empty(&self) -> bool {
+        self.text.trim().is_empty() && self.tags.is_empty() && self.kinds.is_empty()
+    }
+
+    /// Подходит ли блок `block` из файла с содержимым `content`.
+    pub fn matches(&self, block: &BlockInfo, content: &str) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        if !self.tags.iter().all(|t| block.tags.contains(t)) {
+            return false;
+        }
+        let kind = block.kind.to_lowercase();
+        if !self.kinds.is_empty()
+            && !self
+                .kinds
+                .iter()
+                .any(|k| kind.starts_with(&k.to_lowercase()))
+        {
+            return false;
+        }
+        let text = self.text.trim().to_lowercase();
+        if text.is_empty() {
+            return true;
+        }
+        let first_line = content
+            .get(block.range.0..block.range.1)
+            .and_then(|s| s.lines().next())
+            .unwrap_or("");
+        std::iter::once(block.kind.as_str())
+            .chain(block.translations.values().map(String::as_str))
+            .chain(std::iter::once(first_line))
+            .any(|s| s.to_lowercase().contains(&text))
+    }
+
+    /// Выполняет поиск по всем поддерживаемым файлам проекта `root`.
+    pub fn run(&self, root: &Path) -> Vec<SearchHit> {
+        let mut hits = Vec::new();
+        if self.is_empty() {
+            return hits;
+        }
+        for file in source_files(root) {
+            let Some(lang) = file
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(lang_from_extension)
+            else {
+                continue;
+            };
+            let Ok(content) = file_io::read_to_string(&file) else {
+                continue;
+            };
+            let blocks = parse_blocks(content.clone(), lang.into()).unwrap_or_default();
+            for block in blocks {
+                if self.matches(&block, &content) {
+                    let line = content
+                        .get(..block.range.0)
+                        .map_or(0, |s| s.matches('\n').count());
+                    hits.push(SearchHit {
+                        file: file.clone(),
+                        line,
+                        block,
+                    });
+                }
+            }
+        }
+        hits
+    }
+}
+
+/// Загружает сохранённые поиски проекта `root`.
+///
+/// Отсутствующий файл даёт пустой список.
+pub fn load(root: &Path) -> io::Result<Vec<SavedSearch>> {
+    let path = root.join(SAVED_SEARCHES_FILE);
+    let data = match fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    toml::from_str::<SearchesFile>(&data)
+        .map(|f| f.searches)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))
+}
+
+/// Записывает список поисков в файл проекта `root`.
+pub fn save_all(root: &Path, searches: &[SavedSearch]) -> io::Result<()> {
+    let path = root.join(SAVED_SEARCHES_FILE);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = SearchesFile {
+        searches: searches.to_vec(),
+    };
+    let data = toml::to_string(&file)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    fs::write(path, data)
+}
+
+/// Добавляет поиск или заменяет сохранённый поиск с тем же именем.
+pub fn save(root: &Path, search: SavedSearch) -> io::Result<Vec<SavedSearch>> {
+    if search.name.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "имя поиска не должно быть пустым",
+        ));
+    }
+    let mut searches = load(root)?;
+    match searches.iter_mut().find(|s| s.name == search.name) {
+        Some(existing) => *existing = search,
+        None => searches.push(search),
+    }
+    save_all(root, &searches)?;
+    Ok(searches)
+}
+
+/// Удаляет сохранённый поиск `name`.
+pub fn remove(root: &Path, name: &str) -> io::Result<Vec<SavedSearch>> {
+    let mut searches = load(root)?;
+    searches.retain(|s| s.name != name);
+    save_all(root, &searches)?;
+    Ok(searches)
+}
+
+/// Находит сохранённый поиск по имени.
+pub fn find(root: &Path, name: &str) -> io::Result<Option<SavedSearch>> {
+    Ok(load(root)?.into_iter().find(|s| s.name == name))
+}
@@ -1,17 +1,24 @@
-use std::{
-    fs,
-    io::{BufRead, BufReader},
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
 use once_cell::sync::Lazy;
 use regex::{Error as RegexError, Regex};
 use walkdir::WalkDir;
 
-use crate::meta::VisualMeta;
+use crate::file_io;
+use crate::ignore_rules::IgnoreRules;
+use crate::meta::{encoding, VisualMeta};
+use crate::text::PositionIndex;
+
+#[cfg(feature = "embeddings")]
+pub mod semantic;
+pub mod replace;
+pub mod structural;
+#[cfg(feature = "embeddings")]
+pub use semantic::semantic;
+pub use structural::structural;
 
 static META_RE: Lazy<Result<Regex, RegexError>> =
-    Lazy::new(|| Regex::new(r"@VISUAL_META\s*(\{.*?\})"));
+    Lazy::new(|| Regex::new(r"@VISUAL_META(?:\s*(\{.*?\})|:zstd\s*([A-Za-z0-9+/=]+))"));
 
 /// File extensions that are searched for metadata.
 const ALLOWED_EXTENSIONS: &[&str] = &["rs", "js", "ts", "tsx", "jsx"]; // extend as needed
@@ -32,21 +39,68 @@ fn validate_query(query: &str) -> Result<(), RegexError> {
     Ok(())
 }
 
+/// JSON из совпадения [`META_RE`], в том числе из сжатого комментария.
+fn captured_json(caps: &regex::Captures) -> Option<String> {
+    match caps.get(1) {
+        Some(m) => Some(m.as_str().to_string()),
+        None => caps.get(2).and_then(|m| encoding::decode(m.as_str())),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SearchResult {
     pub file: PathBuf,
+    /// Строка комментария, с единицы.
     pub line: usize,
+    /// Столбец начала комментария в символах, с единицы.
+    pub column: usize,
     pub meta: VisualMeta,
 }
 
+/// Добавляет в `out` записи метаданных файла `path`, удовлетворяющие `keep`.
+fn scan_file(
+    path: &Path,
+    re: &Regex,
+    keep: impl Fn(&VisualMeta) -> bool,
+    out: &mut Vec<SearchResult>,
+) {
+    let Ok(content) = file_io::read_to_string(path) else {
+        return;
+    };
+    let index = PositionIndex::new(&content);
+    for caps in re.captures_iter(&content) {
+        let Some(json) = captured_json(&caps) else {
+            continue;
+        };
+        let Ok(meta) = serde_json::from_str::<VisualMeta>(&json) else {
+            continue;
+        };
+        if keep(&meta) {
+            let pos = index.position(caps.get(0).map_or(0, |m| m.start()));
+            out.push(SearchResult {
+                file: path.to_path_buf(),
+                line: pos.line + 1,
+                column: pos.column + 1,
+                meta,
+            });
+        }
+    }
+}
+
 /// Рекурсивно ищет в `root` метаданные с идентификатором `query`.
+/// Файлы, исключённые правилами `.multicode/ignore`, пропускаются.
 /// `query` должен быть непустым и состоять только из символов `[a-zA-Z0-9_-]`.
 /// Возвращает ошибку, если `query` не проходит проверку.
 pub fn search_metadata(root: &Path, query: &str) -> Result<Vec<SearchResult>, RegexError> {
     validate_query(query)?;
     let re = META_RE.as_ref().map_err(|e| e.clone())?;
     let mut out = Vec::new();
-    for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+    let rules = IgnoreRules::load(root);
+    for entry in WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| !rules.is_ignored(e.path(), e.file_type().is_dir()))
+        .filter_map(Result::ok)
+    {
         if !entry.file_type().is_file() {
             continue;
         }
@@ -63,25 +117,7 @@ pub fn search_metadata(root: &Path, query: &str) -> Result<Vec<SearchResult>, Re
                 continue;
             }
         }
-        if let Ok(file) = fs::File::open(path) {
-            let reader = BufReader::new(file);
-            for (idx, line) in reader.lines().enumerate() {
-                if let Ok(line) = line {
-                    for caps in re.captures_iter(&line) {
-                        let json = &caps[1];
-                        if let Ok(meta) = serde_json::from_str::<VisualMeta>(json) {
-                            if meta.id == query {
-                                out.push(SearchResult {
-                                    file: path.to_path_buf(),
-                                    line: idx + 1,
-                                    meta,
-                                });
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        scan_file(path, re, |meta| meta.id == query, &mut out);
     }
     Ok(out)
 }
@@ -92,7 +128,12 @@ pub fn search_links(root: &Path, target: &str) -> Result<Vec<SearchResult>, Rege
     validate_query(target)?;
     let re = META_RE.as_ref().map_err(|e| e.clone())?;
     let mut out = Vec::new();
-    for entry in WalkDir::new(root).into_iter().filter_map(Result::ok) {
+    let rules = IgnoreRules::load(root);
+    for entry in WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|e| !rules.is_ignored(e.path(), e.file_type().is_dir()))
+        .filter_map(Result::ok)
+    {
         if !entry.file_type().is_file() {
             continue;
         }
@@ -109,25 +150,12 @@ pub fn search_links(root: &Path, target: &str) -> Result<Vec<SearchResult>, Rege
                 continue;
             }
         }
-        if let Ok(file) = fs::File::open(path) {
-            let reader = BufReader::new(file);
-            for (idx, line) in reader.lines().enumerate() {
-                if let Ok(line) = line {
-                    for caps in re.captures_iter(&line) {
-                        let json = &caps[1];
-                        if let Ok(meta) = serde_json::from_str::<VisualMeta>(json) {
-                            if meta.links.iter().any(|l| l == target) {
-                                out.push(SearchResult {
-                                    file: path.to_path_buf(),
-                                    line: idx + 1,
-                                    meta,
-                                });
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        scan_file(
+            path,
+            re,
+            |meta| meta.links.iter().any(|l| l == target),
+            &mut out,
+        );
     }
     Ok(out)
 }
@@ -159,6 +187,7 @@ mod tests {
         assert_eq!(res.len(), 1);
         assert_eq!(res[0].file, file1);
         assert_eq!(res[0].line, 1);
+        assert_eq!(res[0].column, 4);
 
         let def = goto_definition(dir.path(), "two").unwrap().unwrap();
         assert_eq!(def.file, file2);
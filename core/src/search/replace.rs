@@ -0,0 +1,255 @@
+//! Замена по шаблону структурного поиска.
+//!
+//! Каждый фрагмент, найденный шаблоном [`Pattern`], заменяется текстом
+//! подстановки, в которой `$A` и `$$$A` заменяются значениями
+//! метапеременных: `add($A, $B)` → `add($B, $A)` меняет аргументы местами.
+//! Замена принимается, только если файл после неё разбирается не хуже, чем
+//! до неё; иначе фрагмент остаётся как был и попадает в отклонённые.
+//!
+//! [`preview`] ничего не пишет и возвращает изменения по файлам с
+//! построчной разницей, [`apply`] записывает их, предварительно сохранив
+//! файлы в резервную копию: [`backup::restore`] с её идентификатором
+//! отменяет замену целиком.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tree_sitter::Node;
+
+use super::structural::{Pattern, StructuralError};
+use crate::blocks::{lang_from_extension, source_files};
+use crate::file_io;
+use crate::meta::backup;
+use crate::parser::{parse, Lang};
+
+/// Одна замена в файле.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Edit {
+    /// Байтовый диапазон заменяемого фрагмента в исходном файле.
+    pub range: (usize, usize),
+    /// Номер первой строки фрагмента, с нуля.
+    pub line: usize,
+    /// Исходный текст фрагмента.
+    pub before: String,
+    /// Текст после подстановки метапеременных.
+    pub after: String,
+}
+
+/// Замены в одном файле.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileReplacement {
+    pub file: PathBuf,
+    /// Содержимое файла до замены.
+    pub original: String,
+    /// Содержимое файла после принятых замен.
+    pub replaced: String,
+    /// Принятые замены в порядке следования.
+    pub edits: Vec<Edit>,
+    /// Замены, после которых файл перестал бы разбираться.
+    pub rejected: Vec<Edit>,
+}
+
+/// Метапеременные текста: диапазон вместе с `$`, имя и признак
+/// последовательности. Одиночный `$` без имени метапеременной не считается.
+fn metavariables(text: &str) -> Vec<((usize, usize), &str, bool)> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while let Some(offset) = text[i..].find('$') {
+        let start = i + offset;
+        let multi = text[start..].starts_with("$$$");
+        let name_start = start + if multi { 3 } else { 1 };
+        let name_len = text[name_start..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(text.len() - name_start);
+        let end = name_start + name_len;
+        if multi || name_len > 0 {
+            out.push(((start, end), &text[name_start..end], multi));
+        }
+        i = end.max(start + 1);
+    }
+    out
+}
+
+/// Подставляет значения метапеременных в `template`.
+fn substitute(template: &str, bindings: &BTreeMap<String, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut last = 0;
+    for ((start, end), name, _) in metavariables(template) {
+        out.push_str(&template[last..start]);
+        out.push_str(bindings.get(name).map_or("", String::as_str));
+        last = end;
+    }
+    out.push_str(&template[last..]);
+    out
+}
+
+/// Проверяет, что подстановка использует только запоминаемые
+/// метапеременные шаблона поиска.
+fn check_template(pattern: &str, template: &str) -> Result<(), StructuralError> {
+    let known: Vec<&str> = metavariables(pattern)
+        .into_iter()
+        .map(|(_, name, _)| name)
+        .collect();
+    for ((start, end), name, _) in metavariables(template) {
+        if name.is_empty() || name == "_" || !known.contains(&name) {
+            return Err(StructuralError::UnknownVariable(
+                template[start..end].to_string(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Число ошибочных и пропущенных узлов дерева разбора.
+fn error_count(node: Node) -> usize {
+    if !node.has_error() {
+        return 0;
+    }
+    let own = usize::from(node.is_error() || node.is_missing());
+    let mut cursor = node.walk();
+    let nested: usize = node.children(&mut cursor).map(error_count).sum();
+    own + nested
+}
+
+fn errors(content: &str, lang: Lang) -> usize {
+    parse(content, lang, None).map_or(usize::MAX, |tree| error_count(tree.root_node()))
+}
+
+/// Начало строки, содержащей `offset`, и конец строки, содержащей
+/// `end`, без перевода строки.
+fn line_span(content: &str, offset: usize, end: usize) -> (usize, usize) {
+    let start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[end..].find('\n').map_or(content.len(), |i| end + i);
+    (start, end)
+}
+
+impl FileReplacement {
+    /// Построчная разница: для каждой замены строки до (`-`) и после
+    /// (`+`) под заголовком `@@ <строка> @@`.
+    pub fn diff(&self) -> String {
+        let mut out = String::new();
+        let mut delta = 0isize;
+        for edit in &self.edits {
+            let (start, end) = edit.range;
+            let new_start = start.saturating_add_signed(delta);
+            let new_end = new_start + edit.after.len();
+            delta += edit.after.len() as isize - (end - start) as isize;
+            let old = line_span(&self.original, start, end);
+            let new = line_span(&self.replaced, new_start, new_end);
+            out.push_str(&format!("@@ {} @@\n", edit.line + 1));
+            for line in self.original[old.0..old.1].lines() {
+                out.push_str(&format!("-{line}\n"));
+            }
+            for line in self.replaced[new.0..new.1].lines() {
+                out.push_str(&format!("+{line}\n"));
+            }
+        }
+        out
+    }
+}
+
+/// Заменяет совпадения `pattern` в `content` подстановкой `template`.
+/// Вложенные совпадения внутри заменяемых фрагментов не заменяются.
+pub fn rewrite(file: &Path, content: &str, pattern: &Pattern, template: &str) -> FileReplacement {
+    let mut found = pattern.find(content);
+    found.sort_by_key(|(range, _)| (range.0, std::cmp::Reverse(range.1)));
+    let mut edits: Vec<Edit> = Vec::new();
+    for (range, bindings) in found {
+        if edits.last().is_some_and(|e| range.0 < e.range.1) {
+            continue;
+        }
+        edits.push(Edit {
+            range,
+            line: content[..range.0].matches('\n').count(),
+            before: content[range.0..range.1].to_string(),
+            after: substitute(template, &bindings),
+        });
+    }
+    // Замены проверяются с конца файла: начало при этом не сдвигается,
+    // и диапазоны следующих проверяемых фрагментов остаются верными.
+    let mut replaced = content.to_string();
+    let mut current = errors(&replaced, pattern.lang());
+    let mut accepted = Vec::new();
+    let mut rejected = Vec::new();
+    for edit in edits.into_iter().rev() {
+        if edit.before == edit.after {
+            continue;
+        }
+        let mut candidate = replaced.clone();
+        candidate.replace_range(edit.range.0..edit.range.1, &edit.after);
+        let after = errors(&candidate, pattern.lang());
+        if after <= current {
+            replaced = candidate;
+            current = after;
+            accepted.push(edit);
+        } else {
+            rejected.push(edit);
+        }
+    }
+    accepted.reverse();
+    rejected.reverse();
+    FileReplacement {
+        file: file.to_path_buf(),
+        original: content.to_string(),
+        replaced,
+        edits: accepted,
+        rejected,
+    }
+}
+
+/// Замены `pattern` на `template` во всех файлах языка `lang` в проекте
+/// `root`, без записи. Файлы без совпадений в результат не попадают.
+pub fn preview(
+    root: &Path,
+    pattern: &str,
+    template: &str,
+    lang: &str,
+) -> Result<Vec<FileReplacement>, StructuralError> {
+    check_template(pattern, template)?;
+    let parsed = Pattern::parse(pattern, lang)?;
+    let lang = parsed.lang().to_string();
+    let mut out = Vec::new();
+    for path in source_files(root) {
+        let file_lang = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension);
+        if file_lang != Some(lang.as_str()) {
+            continue;
+        }
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let replacement = rewrite(&path, &content, &parsed, template);
+        if !replacement.edits.is_empty() || !replacement.rejected.is_empty() {
+            out.push(replacement);
+        }
+    }
+    Ok(out)
+}
+
+/// Записывает принятые замены и возвращает идентификатор резервной копии
+/// изменённых файлов. Если файл изменился после [`preview`], ничего не
+/// записывается.
+pub fn apply(root: &Path, replacements: &[FileReplacement]) -> io::Result<String> {
+    let changed: Vec<&FileReplacement> = replacements
+        .iter()
+        .filter(|r| !r.edits.is_empty())
+        .collect();
+    for r in &changed {
+        if file_io::read_to_string(&r.file)? != r.original {
+            return Err(io::Error::other(format!(
+                "{} изменён после предпросмотра",
+                r.file.display()
+            )));
+        }
+    }
+    let paths: Vec<&Path> = changed.iter().map(|r| r.file.as_path()).collect();
+    let id = backup::backup_files(root, &paths)?;
+    for r in changed {
+        file_io::write_preserving(&r.file, &r.replaced)?;
+    }
+    Ok(id)
+}
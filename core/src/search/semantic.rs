@@ -0,0 +1,375 @@
+//! Семантический поиск по блокам (флаг `embeddings`).
+//!
+//! Для определений функций и блоков с описанием строится текст: первая
+//! строка кода, описание, теги и сам код ([`documents`]). Поставщик
+//! ([`Embedder`], например [`HttpEmbedder`] из секции `[embeddings]`)
+//! превращает текст в вектор, а векторы хранятся в таблице
+//! `block_embeddings` базы метаданных ([`crate::meta::db`]).
+//!
+//! [`index`] обновляет векторы только у изменившихся блоков и удаляет
+//! векторы исчезнувших. [`semantic`] находит блоки, ближе всего подходящие
+//! к вопросу на естественном языке, по косинусной близости векторов.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use sqlx::sqlite::SqlitePool;
+use tokio::task;
+
+use crate::blocks::parse_dir;
+use crate::config::EmbeddingsConfig;
+use crate::file_io;
+use crate::meta::db::{self, Embedding};
+use crate::offline_queue::OpError;
+
+/// Сколько символов кода блока попадает в его текст.
+pub const MAX_CODE_CHARS: usize = 2000;
+
+/// Поставщик векторов текста.
+pub trait Embedder: Send + Sync {
+    /// Имя модели; векторы разных моделей не сравниваются.
+    fn model(&self) -> &str;
+    /// Векторы `texts` в том же порядке.
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, OpError>;
+}
+
+/// Ошибка построения или чтения векторов.
+#[derive(Debug)]
+pub enum SemanticError {
+    Db(sqlx::Error),
+    Embed(OpError),
+}
+
+impl fmt::Display for SemanticError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Db(e) => write!(f, "{e}"),
+            Self::Embed(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for SemanticError {}
+
+impl From<sqlx::Error> for SemanticError {
+    fn from(e: sqlx::Error) -> Self {
+        Self::Db(e)
+    }
+}
+
+impl From<OpError> for SemanticError {
+    fn from(e: OpError) -> Self {
+        Self::Embed(e)
+    }
+}
+
+/// Текст блока, по которому строится вектор.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockDoc {
+    /// Файл относительно корня и первая строка кода блока: в отличие от
+    /// идентификатора блока без метаданных, не меняется при правке других
+    /// блоков файла.
+    pub id: String,
+    /// Файл относительно корня проекта.
+    pub file: PathBuf,
+    /// Строка начала блока, с нуля.
+    pub line: usize,
+    /// Первая строка кода блока.
+    pub label: String,
+    pub text: String,
+    /// Отпечаток текста.
+    pub hash: String,
+}
+
+/// Тексты блоков проекта `root`: определения функций и блоки с описанием.
+pub fn documents(root: &Path) -> Vec<BlockDoc> {
+    let mut docs = Vec::new();
+    for (path, blocks) in parse_dir(root) {
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let file = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for block in blocks {
+            let description = block
+                .ai
+                .as_ref()
+                .and_then(|ai| ai.description.as_deref())
+                .filter(|d| !d.trim().is_empty());
+            if block.kind != "Function/Define" && description.is_none() {
+                continue;
+            }
+            let Some(code) = content.get(block.range.0..block.range.1) else {
+                continue;
+            };
+            let label = code.lines().next().unwrap_or_default().trim().to_string();
+            let mut text = label.clone();
+            if let Some(description) = description {
+                text.push('\n');
+                text.push_str(description);
+            }
+            if !block.tags.is_empty() {
+                text.push_str("\ntags: ");
+                text.push_str(&block.tags.join(", "));
+            }
+            text.push('\n');
+            text.extend(code.chars().take(MAX_CODE_CHARS));
+            let mut hasher = DefaultHasher::new();
+            text.hash(&mut hasher);
+            let mut id = format!("{}#{label}", file.display());
+            let repeat = seen.entry(id.clone()).or_default();
+            *repeat += 1;
+            if *repeat > 1 {
+                id.push_str(&format!("#{repeat}"));
+            }
+            docs.push(BlockDoc {
+                id,
+                line: content[..block.range.0].matches('\n').count(),
+                file: file.clone(),
+                label,
+                text,
+                hash: format!("{:016x}", hasher.finish()),
+            });
+        }
+    }
+    docs
+}
+
+/// Итог обновления векторов.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexReport {
+    /// Блоки, для которых построен новый вектор.
+    pub embedded: usize,
+    /// Блоки, вектор которых не изменился.
+    pub unchanged: usize,
+    /// Удалённые векторы исчезнувших блоков.
+    pub removed: usize,
+}
+
+/// Открывает базу метаданных проекта `root` из секции `[embeddings]`,
+/// создавая файл и таблицы при необходимости.
+pub async fn open(root: &Path, config: &EmbeddingsConfig) -> Result<SqlitePool, sqlx::Error> {
+    db::open(&root.join(&config.db)).await
+}
+
+/// Векторы `texts`, построенные вне потока исполнителя.
+async fn embed(embedder: &Arc<dyn Embedder>, texts: Vec<String>) -> Result<Vec<Vec<f32>>, OpError> {
+    let embedder = Arc::clone(embedder);
+    let count = texts.len();
+    let vectors = task::spawn_blocking(move || embedder.embed(&texts))
+        .await
+        .map_err(|e| OpError::Failed(e.to_string()))??;
+    if vectors.len() != count {
+        return Err(OpError::Failed(format!(
+            "expected {count} embeddings, got {}",
+            vectors.len()
+        )));
+    }
+    Ok(vectors)
+}
+
+/// Обновляет векторы блоков проекта `root` в `pool`, отправляя тексты
+/// поставщику пачками по `batch_size`. Векторы неизменившихся блоков той же
+/// модели остаются прежними.
+pub async fn index(
+    root: &Path,
+    pool: &SqlitePool,
+    embedder: Arc<dyn Embedder>,
+    batch_size: usize,
+) -> Result<IndexReport, SemanticError> {
+    let dir = root.to_path_buf();
+    let docs = task::spawn_blocking(move || documents(&dir))
+        .await
+        .map_err(|e| OpError::Failed(e.to_string()))?;
+    let stored: HashMap<String, Embedding> = db::list_embeddings(pool)
+        .await?
+        .into_iter()
+        .map(|e| (e.id.clone(), e))
+        .collect();
+    let model = embedder.model().to_string();
+    let mut report = IndexReport::default();
+    let mut stale = Vec::new();
+    for doc in &docs {
+        match stored.get(&doc.id) {
+            Some(e) if e.model == model && e.hash == doc.hash => {
+                // Блок мог сдвинуться без изменения текста.
+                if e.line != doc.line {
+                    db::upsert_embedding(
+                        pool,
+                        &Embedding {
+                            line: doc.line,
+                            ..e.clone()
+                        },
+                    )
+                    .await?;
+                }
+                report.unchanged += 1;
+            }
+            _ => stale.push(doc),
+        }
+    }
+    for batch in stale.chunks(batch_size.max(1)) {
+        let texts = batch.iter().map(|doc| doc.text.clone()).collect();
+        let vectors = embed(&embedder, texts).await?;
+        for (doc, vector) in batch.iter().zip(vectors) {
+            db::upsert_embedding(
+                pool,
+                &Embedding {
+                    id: doc.id.clone(),
+                    file: doc.file.to_string_lossy().into_owned(),
+                    line: doc.line,
+                    label: doc.label.clone(),
+                    model: model.clone(),
+                    hash: doc.hash.clone(),
+                    vector,
+                },
+            )
+            .await?;
+            report.embedded += 1;
+        }
+    }
+    let current: std::collections::HashSet<&str> = docs.iter().map(|d| d.id.as_str()).collect();
+    for id in stored.keys().filter(|id| !current.contains(id.as_str())) {
+        db::delete_embedding(pool, id).await?;
+        report.removed += 1;
+    }
+    Ok(report)
+}
+
+/// Блок, найденный семантическим поиском.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SemanticHit {
+    /// Файл относительно корня проекта.
+    pub file: PathBuf,
+    /// Строка начала блока, с нуля.
+    pub line: usize,
+    pub label: String,
+    /// Косинусная близость к вопросу, от -1 до 1.
+    pub score: f32,
+}
+
+/// Косинусная близость векторов; `0` для нулевых и разной длины.
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
+
+/// `k` блоков, ближе всего подходящих к вопросу `query`, по убыванию
+/// близости. Учитываются только векторы модели `embedder`.
+pub async fn semantic(
+    pool: &SqlitePool,
+    embedder: Arc<dyn Embedder>,
+    query: &str,
+    k: usize,
+) -> Result<Vec<SemanticHit>, SemanticError> {
+    if query.trim().is_empty() || k == 0 {
+        return Ok(Vec::new());
+    }
+    let query = embed(&embedder, vec![query.to_string()])
+        .await?
+        .pop()
+        .unwrap_or_default();
+    let mut hits: Vec<SemanticHit> = db::list_embeddings(pool)
+        .await?
+        .into_iter()
+        .filter(|e| e.model == embedder.model())
+        .map(|e| SemanticHit {
+            score: cosine(&query, &e.vector),
+            file: PathBuf::from(e.file),
+            line: e.line,
+            label: e.label,
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(k);
+    Ok(hits)
+}
+
+/// OpenAI-совместимый поставщик: `POST {endpoint}` с `{"model", "input"}`,
+/// ответ — `data[].embedding`.
+pub struct HttpEmbedder {
+    endpoint: String,
+    model: String,
+    token: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+impl HttpEmbedder {
+    /// Поставщик по секции `[embeddings]`; ошибка, если `endpoint` не задан.
+    pub fn from_config(config: &EmbeddingsConfig) -> Result<Self, String> {
+        let endpoint = config
+            .endpoint
+            .clone()
+            .ok_or("в секции [embeddings] не задан endpoint")?;
+        let token = config
+            .token_env
+            .as_ref()
+            .and_then(|name| std::env::var(name).ok());
+        let client = reqwest::blocking::Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            endpoint,
+            model: config.model.clone().unwrap_or_default(),
+            token,
+            client,
+        })
+    }
+}
+
+impl Embedder for HttpEmbedder {
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, OpError> {
+        use serde_json::Value;
+
+        use crate::offline_queue::http_error;
+
+        let body = serde_json::json!({ "model": self.model, "input": texts });
+        let mut http = self.client.post(&self.endpoint).json(&body);
+        if let Some(token) = &self.token {
+            http = http.bearer_auth(token);
+        }
+        let response: Value = http
+            .send()
+            .and_then(|r| r.error_for_status())
+            .and_then(|r| r.json())
+            .map_err(http_error)?;
+        let mut data: Vec<&Value> = response
+            .get("data")
+            .and_then(Value::as_array)
+            .ok_or_else(|| OpError::Failed("response has no data".into()))?
+            .iter()
+            .collect();
+        data.sort_by_key(|d| d.get("index").and_then(Value::as_u64).unwrap_or(0));
+        data.into_iter()
+            .map(|d| {
+                d.get("embedding")
+                    .and_then(Value::as_array)
+                    .map(|v| {
+                        v.iter()
+                            .filter_map(Value::as_f64)
+                            .map(|x| x as f32)
+                            .collect()
+                    })
+                    .ok_or_else(|| OpError::Failed("response item has no embedding".into()))
+            })
+            .collect()
+    }
+}
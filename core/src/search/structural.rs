@@ -0,0 +1,323 @@
+//! Структурный поиск по синтаксическому дереву.
+//!
+//! Шаблон — фрагмент кода на языке поиска с метапеременными, как в
+//! ast-grep: `$A` совпадает с любым одним узлом, `$$$A` — с любой
+//! последовательностью соседних узлов, в том числе пустой, а `$_` и `$$$`
+//! совпадают без запоминания. Повтор одной метапеременной требует
+//! одинакового текста: `$A == $A` находит сравнения значения с самим
+//! собой. Пробелы и комментарии на совпадение не влияют.
+//!
+//! ```text
+//! foo($A, $$$REST)        вызовы foo хотя бы с одним аргументом
+//! if $COND { $$$ }        условия без else
+//! let $X = $X;            присваивание переменной самой себе
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tree_sitter::{Node, Tree};
+
+use crate::blocks::{lang_from_extension, parse_blocks, source_files};
+use crate::file_io;
+use crate::parser::{parse, Lang};
+use crate::BlockInfo;
+
+/// Символ, которым заменяется `$` перед разбором шаблона: в отличие от
+/// `$` он допустим в идентификаторах всех поддерживаемых языков.
+const META: &str = "µ";
+
+/// Ошибка разбора шаблона.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StructuralError {
+    /// Язык поиска не поддерживается.
+    UnknownLanguage(String),
+    /// Шаблон не разбирается как код на языке поиска.
+    InvalidPattern(String),
+    /// Подстановка замены использует метапеременную, которой нет в
+    /// шаблоне или которая не запоминается.
+    UnknownVariable(String),
+}
+
+impl fmt::Display for StructuralError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownLanguage(lang) => write!(f, "неизвестный язык: {lang}"),
+            Self::InvalidPattern(pattern) => write!(f, "шаблон не разбирается: {pattern}"),
+            Self::UnknownVariable(name) => write!(f, "неизвестная метапеременная: {name}"),
+        }
+    }
+}
+
+impl std::error::Error for StructuralError {}
+
+/// Найденный по шаблону фрагмент.
+#[derive(Debug, Clone, Serialize)]
+pub struct StructuralMatch {
+    pub file: PathBuf,
+    /// Номер первой строки фрагмента, с нуля.
+    pub line: usize,
+    /// Байтовый диапазон фрагмента в файле.
+    pub range: (usize, usize),
+    /// Текст фрагмента.
+    pub text: String,
+    /// Значения метапеременных по имени без `$`.
+    pub bindings: BTreeMap<String, String>,
+    /// Самый внешний блок, совпадающий с фрагментом.
+    pub block: BlockInfo,
+}
+
+/// Разобранный шаблон структурного поиска.
+pub struct Pattern {
+    lang: Lang,
+    source: String,
+    tree: Tree,
+    /// Диапазон шаблона в `source` с учётом обёртки.
+    range: (usize, usize),
+}
+
+/// Обёртки, в которых шаблон разбирается, если не разбирается сам по
+/// себе: например, инструкция Rust допустима только в теле функции.
+fn wrappers(lang: Lang) -> &'static [(&'static str, &'static str)] {
+    match lang {
+        Lang::Rust => &[("", ""), ("fn __pattern() {\n", "\n}")],
+        Lang::JavaScript | Lang::TypeScript => &[("", ""), ("class __Pattern {\n", "\n}")],
+        Lang::Css => &[("", ""), ("__pattern {\n", "\n}")],
+        _ => &[("", "")],
+    }
+}
+
+/// Метапеременная шаблона: имя и признак последовательности.
+struct MetaVar<'a> {
+    name: &'a str,
+    multi: bool,
+}
+
+impl MetaVar<'_> {
+    /// Запоминается ли значение.
+    fn binds(&self) -> bool {
+        !self.name.is_empty() && self.name != "_"
+    }
+}
+
+fn metavar<'a>(node: Node, source: &'a str) -> Option<MetaVar<'a>> {
+    let text = &source[node.byte_range()];
+    let multi = META.repeat(3);
+    let (name, multi) = match text.strip_prefix(&multi) {
+        Some(name) => (name, true),
+        None => (text.strip_prefix(META)?, false),
+    };
+    name.chars()
+        .all(|c| c.is_alphanumeric() || c == '_')
+        .then_some(MetaVar { name, multi })
+}
+
+/// Дочерние узлы без комментариев и других необязательных узлов.
+fn children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|c| !c.is_extra())
+        .collect()
+}
+
+type Bindings = BTreeMap<String, String>;
+
+/// Запоминает значение метапеременной; повтор требует того же текста.
+fn bind(bindings: &mut Bindings, var: &MetaVar, value: &str) -> bool {
+    if !var.binds() {
+        return true;
+    }
+    match bindings.get(var.name) {
+        Some(bound) => bound == value,
+        None => {
+            bindings.insert(var.name.to_string(), value.to_string());
+            true
+        }
+    }
+}
+
+struct Matcher<'a> {
+    pattern: &'a str,
+    target: &'a str,
+}
+
+impl Matcher<'_> {
+    fn node(&self, p: Node, t: Node, bindings: &mut Bindings) -> bool {
+        if let Some(var) = metavar(p, self.pattern) {
+            return bind(bindings, &var, &self.target[t.byte_range()]);
+        }
+        if p.kind() != t.kind() {
+            return false;
+        }
+        let (pc, tc) = (children(p), children(t));
+        if pc.is_empty() {
+            return tc.is_empty() && self.pattern[p.byte_range()] == self.target[t.byte_range()];
+        }
+        self.sequence(&pc, &tc, bindings)
+    }
+
+    fn sequence(&self, pc: &[Node], tc: &[Node], bindings: &mut Bindings) -> bool {
+        let Some((first, rest)) = pc.split_first() else {
+            return tc.is_empty();
+        };
+        let saved = bindings.clone();
+        if let Some(var) = metavar(*first, self.pattern).filter(|v| v.multi) {
+            for n in 0..=tc.len() {
+                let value = match (tc.first(), n) {
+                    (Some(start), n) if n > 0 => {
+                        &self.target[start.start_byte()..tc[n - 1].end_byte()]
+                    }
+                    _ => "",
+                };
+                if bind(bindings, &var, value) && self.sequence(rest, &tc[n..], bindings) {
+                    return true;
+                }
+                *bindings = saved.clone();
+            }
+            return false;
+        }
+        let Some((head, tail)) = tc.split_first() else {
+            return false;
+        };
+        if self.node(*first, *head, bindings) && self.sequence(rest, tail, bindings) {
+            return true;
+        }
+        *bindings = saved;
+        false
+    }
+}
+
+impl Pattern {
+    /// Разбирает шаблон `pattern` на языке `lang` (`rust`, `python`,
+    /// `javascript`, `css`, `html`).
+    pub fn parse(pattern: &str, lang: &str) -> Result<Self, StructuralError> {
+        let lang: Lang = lang
+            .parse()
+            .map_err(|_| StructuralError::UnknownLanguage(lang.to_string()))?;
+        let text = pattern.trim().replace('$', META);
+        if text.is_empty() {
+            return Err(StructuralError::InvalidPattern(pattern.to_string()));
+        }
+        for (prefix, suffix) in wrappers(lang) {
+            let source = format!("{prefix}{text}{suffix}");
+            let Some(tree) = parse(&source, lang, None) else {
+                continue;
+            };
+            if tree.root_node().has_error() {
+                continue;
+            }
+            let range = (prefix.len(), prefix.len() + text.len());
+            let found = tree
+                .root_node()
+                .descendant_for_byte_range(range.0, range.1)
+                .is_some_and(|n| n.byte_range() == (range.0..range.1));
+            if found {
+                return Ok(Self {
+                    lang,
+                    source,
+                    tree,
+                    range,
+                });
+            }
+        }
+        Err(StructuralError::InvalidPattern(pattern.to_string()))
+    }
+
+    /// Язык шаблона.
+    pub fn lang(&self) -> Lang {
+        self.lang
+    }
+
+    fn root(&self) -> Node<'_> {
+        self.tree
+            .root_node()
+            .descendant_for_byte_range(self.range.0, self.range.1)
+            .unwrap_or_else(|| self.tree.root_node())
+    }
+
+    /// Совпадения шаблона в исходнике `content`: диапазон и значения
+    /// метапеременных, в порядке обхода дерева. Вложенные совпадения
+    /// тоже попадают в результат.
+    pub fn find(&self, content: &str) -> Vec<((usize, usize), Bindings)> {
+        let Some(tree) = parse(content, self.lang, None) else {
+            return Vec::new();
+        };
+        let root = self.root();
+        let any_kind = metavar(root, &self.source).is_some();
+        let matcher = Matcher {
+            pattern: &self.source,
+            target: content,
+        };
+        let mut out = Vec::new();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            if (any_kind || node.kind() == root.kind()) && !node.is_extra() {
+                let mut bindings = Bindings::new();
+                if matcher.node(root, node, &mut bindings) {
+                    out.push(((node.start_byte(), node.end_byte()), bindings));
+                }
+            }
+            let mut cursor = node.walk();
+            let kids: Vec<Node> = node.children(&mut cursor).collect();
+            stack.extend(kids.into_iter().rev());
+        }
+        out
+    }
+}
+
+/// Ищет `pattern` на языке `lang` во всех файлах этого языка в проекте
+/// `root`. Результат упорядочен по файлу и положению.
+pub fn structural(
+    root: &Path,
+    pattern: &str,
+    lang: &str,
+) -> Result<Vec<StructuralMatch>, StructuralError> {
+    let pattern = Pattern::parse(pattern, lang)?;
+    let lang = pattern.lang().to_string();
+    let mut out = Vec::new();
+    for path in source_files(root) {
+        let file_lang = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension);
+        if file_lang != Some(lang.as_str()) {
+            continue;
+        }
+        let Ok(content) = file_io::read_to_string(&path) else {
+            continue;
+        };
+        let found = pattern.find(&content);
+        if found.is_empty() {
+            continue;
+        }
+        let blocks = parse_blocks(content.clone(), lang.clone()).unwrap_or_default();
+        for (range, bindings) in found {
+            // Блок с тем же диапазоном есть у каждого узла, кроме узлов,
+            // которых нет среди блоков; тогда берётся ближайший внешний.
+            let Some(block) = blocks
+                .iter()
+                .find(|b| b.range == range)
+                .or_else(|| {
+                    blocks
+                        .iter()
+                        .filter(|b| b.range.0 <= range.0 && range.1 <= b.range.1)
+                        .min_by_key(|b| b.range.1 - b.range.0)
+                })
+                .cloned()
+            else {
+                continue;
+            };
+            out.push(StructuralMatch {
+                file: path.clone(),
+                line: content[..range.0].matches('\n').count(),
+                range,
+                text: content[range.0..range.1].to_string(),
+                bindings,
+                block,
+            });
+        }
+    }
+    Ok(out)
+}
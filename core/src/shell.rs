@@ -0,0 +1,122 @@
+//! Запуск команд через системную оболочку.
+//!
+//! Хуки, форматтеры и конфигурации запуска блоков задаются строкой
+//! оболочки. В Unix она выполняется `sh -c`, в Windows — интерпретатором
+//! из `%COMSPEC%` (обычно `cmd.exe`) или PowerShell, если он указан явно.
+//! Вид оболочки ([`ShellKind`]) определяет, как передать ей команду и как
+//! экранировать подставляемые в неё пути.
+
+use std::borrow::Cow;
+use std::path::Path;
+use std::process::Command;
+
+/// Вид оболочки.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellKind {
+    /// `sh`, `bash` и другие POSIX-совместимые оболочки.
+    Posix,
+    /// `cmd.exe`.
+    Cmd,
+    /// Windows PowerShell или PowerShell 7 (`pwsh`).
+    PowerShell,
+}
+
+/// Оболочка: программа и её вид.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shell {
+    pub program: String,
+    pub kind: ShellKind,
+}
+
+impl Shell {
+    /// Оболочка `program`; вид определяется по имени файла программы.
+    /// Пути Windows распознаются на любой платформе.
+    pub fn new(program: impl Into<String>) -> Self {
+        let program = program.into();
+        let name = program
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let kind = match name.strip_suffix(".exe").unwrap_or(&name) {
+            "cmd" => ShellKind::Cmd,
+            "powershell" | "pwsh" => ShellKind::PowerShell,
+            _ => ShellKind::Posix,
+        };
+        Self { program, kind }
+    }
+
+    /// Системная оболочка: `sh` в Unix, `%COMSPEC%` или `cmd.exe` в Windows.
+    pub fn system() -> Self {
+        if cfg!(windows) {
+            Self::new(std::env::var("COMSPEC").unwrap_or_else(|_| "cmd.exe".into()))
+        } else {
+            Self::new("sh")
+        }
+    }
+
+    /// Аргументы, с которыми оболочка выполняет строку `line`.
+    pub fn args(&self, line: &str) -> Vec<String> {
+        match self.kind {
+            ShellKind::Posix => vec!["-c".into(), line.into()],
+            ShellKind::Cmd => vec!["/D".into(), "/S".into(), "/C".into(), line.into()],
+            ShellKind::PowerShell => vec![
+                "-NoProfile".into(),
+                "-NonInteractive".into(),
+                "-Command".into(),
+                line.into(),
+            ],
+        }
+    }
+
+    /// Процесс оболочки, выполняющий строку `line`.
+    pub fn command(&self, line: &str) -> Command {
+        let mut cmd = Command::new(&self.program);
+        match self.kind {
+            // cmd.exe разбирает командную строку по своим правилам, а не по
+            // правилам MSVCRT, поэтому строка передаётся без экранирования.
+            #[cfg(windows)]
+            ShellKind::Cmd => {
+                use std::os::windows::process::CommandExt;
+                cmd.raw_arg(format!("/D /S /C \"{line}\""));
+            }
+            _ => {
+                cmd.args(self.args(line));
+            }
+        }
+        cmd
+    }
+
+    /// Экранирует `arg` для подстановки в командную строку оболочки.
+    /// Значения без специальных символов возвращаются как есть.
+    pub fn quote<'a>(&self, arg: &'a str) -> Cow<'a, str> {
+        let safe = |c: char| {
+            c.is_alphanumeric()
+                || matches!(c, '_' | '-' | '.' | '/' | ':' | '+')
+                || match self.kind {
+                    ShellKind::Posix => matches!(c, ',' | '=' | '@' | '%'),
+                    ShellKind::Cmd => matches!(c, '\\' | '@'),
+                    ShellKind::PowerShell => c == '\\',
+                }
+        };
+        if !arg.is_empty() && arg.chars().all(safe) {
+            return Cow::Borrowed(arg);
+        }
+        Cow::Owned(match self.kind {
+            ShellKind::Posix => format!("'{}'", arg.replace('\'', r"'\''")),
+            ShellKind::Cmd => format!("\"{}\"", arg.replace('"', "\"\"")),
+            ShellKind::PowerShell => format!("'{}'", arg.replace('\'', "''")),
+        })
+    }
+
+    /// Экранирует путь `path`, см. [`Shell::quote`].
+    pub fn quote_path(&self, path: &Path) -> String {
+        self.quote(&path.to_string_lossy()).into_owned()
+    }
+}
+
+impl Default for Shell {
+    fn default() -> Self {
+        Self::system()
+    }
+}
@@ -0,0 +1,177 @@
+//! Задачи проекта: комментарии `TODO`/`FIXME` и блоки с тегом `todo`.
+//!
+//! Комментарий вида `// TODO(alice): проверить таймаут` даёт задачу с
+//! исполнителем `alice`. Для блоков с тегом [`TODO_TAG`] текст берётся из
+//! заметки `ai.description` или метки блока, а исполнитель и статус — из
+//! `extras`:
+//!
+//! ```text
+//! <!-- @VISUAL_META {"id":"1","tags":["todo"],"extras":{"assignee":"bob","status":"review"}} -->
+//! ```
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::blocks::{lang_from_extension, parse_blocks, source_files};
+use crate::file_io;
+use crate::meta::{read_all, VisualMeta};
+use crate::BlockInfo;
+
+/// Тег блока, превращающий его в задачу.
+pub const TODO_TAG: &str = "todo";
+/// Ключ `extras` с исполнителем задачи.
+pub const ASSIGNEE_KEY: &str = "assignee";
+/// Ключ `extras` со статусом задачи.
+pub const STATUS_KEY: &str = "status";
+/// Статус задачи, для которой он не указан.
+pub const DEFAULT_STATUS: &str = "open";
+
+static MARKER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?://|#|/\*|<!--|\*)\s*(TODO|FIXME)(?:\(([^)]*)\))?:?\s*(.*)")
+        .expect("valid regex")
+});
+
+/// Источник задачи.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskKind {
+    /// Комментарий `TODO`.
+    Todo,
+    /// Комментарий `FIXME`.
+    Fixme,
+    /// Блок с тегом [`TODO_TAG`].
+    Block,
+}
+
+/// Задача, найденная в исходниках проекта.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Task {
+    pub file: PathBuf,
+    /// Номер строки, с нуля.
+    pub line: usize,
+    /// Идентификатор блока, к которому относится задача.
+    pub block: Option<String>,
+    pub kind: TaskKind,
+    pub text: String,
+    pub assignee: Option<String>,
+    pub status: String,
+    /// Теги блока задачи.
+    pub tags: Vec<String>,
+}
+
+fn extra(meta: &VisualMeta, key: &str) -> Option<String> {
+    let value = meta.extras.as_ref()?.get(key)?.as_str()?.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content.get(..offset).map_or(0, |s| s.matches('\n').count())
+}
+
+/// Наименьший блок, содержащий смещение `offset`, кроме корня файла.
+fn enclosing_block(blocks: &[BlockInfo], offset: usize, len: usize) -> Option<&BlockInfo> {
+    blocks
+        .iter()
+        .filter(|b| b.range.0 <= offset && offset < b.range.1)
+        .filter(|b| !(b.range.0 == 0 && b.range.1 >= len))
+        .min_by_key(|b| b.range.1 - b.range.0)
+}
+
+/// Задачи из содержимого файла `file` на языке `lang`.
+pub fn tasks_in(file: &Path, content: &str, lang: &str) -> Vec<Task> {
+    let blocks = parse_blocks(content.to_string(), lang.into()).unwrap_or_default();
+    let metas = read_all(content);
+    let mut tasks = Vec::new();
+
+    let mut offset = 0;
+    for (line, text) in content.split_inclusive('\n').enumerate() {
+        let start = offset;
+        offset += text.len();
+        if text.contains("@VISUAL_META") {
+            continue;
+        }
+        let Some(caps) = MARKER.captures(text) else {
+            continue;
+        };
+        let kind = if &caps[1] == "FIXME" {
+            TaskKind::Fixme
+        } else {
+            TaskKind::Todo
+        };
+        let body = caps[3]
+            .trim()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->");
+        let block = enclosing_block(
+            &blocks,
+            start + caps.get(0).map_or(0, |m| m.start()),
+            content.len(),
+        );
+        tasks.push(Task {
+            file: file.to_path_buf(),
+            line,
+            block: block.map(|b| b.visual_id.clone()),
+            kind,
+            text: body.trim().to_string(),
+            assignee: caps
+                .get(2)
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|a| !a.is_empty()),
+            status: DEFAULT_STATUS.to_string(),
+            tags: block.map(|b| b.tags.clone()).unwrap_or_default(),
+        });
+    }
+
+    let mut seen = HashSet::new();
+    for block in blocks
+        .iter()
+        .filter(|b| b.tags.iter().any(|t| t == TODO_TAG))
+        .filter(|b| seen.insert(b.visual_id.as_str()))
+    {
+        let meta = metas.iter().find(|m| m.id == block.visual_id);
+        let text = block
+            .ai
+            .as_ref()
+            .and_then(|ai| ai.description.clone())
+            .or_else(|| block.translations.get("en").cloned())
+            .unwrap_or_else(|| block.kind.clone());
+        tasks.push(Task {
+            file: file.to_path_buf(),
+            line: line_of(content, block.range.0),
+            block: Some(block.visual_id.clone()),
+            kind: TaskKind::Block,
+            text,
+            assignee: meta.and_then(|m| extra(m, ASSIGNEE_KEY)),
+            status: meta
+                .and_then(|m| extra(m, STATUS_KEY))
+                .unwrap_or_else(|| DEFAULT_STATUS.to_string()),
+            tags: block.tags.clone(),
+        });
+    }
+    tasks.sort_by_key(|t| t.line);
+    tasks
+}
+
+/// Все задачи проекта `root`, упорядоченные по файлу и строке.
+pub fn collect(root: &Path) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    for file in source_files(root) {
+        let Some(lang) = file
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(lang_from_extension)
+        else {
+            continue;
+        };
+        let Ok(content) = file_io::read_to_string(&file) else {
+            continue;
+        };
+        tasks.extend(tasks_in(&file, &content, lang));
+    }
+    tasks.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    tasks
+}
@@ -0,0 +1,211 @@
+//! Запуск тестов проекта и их результаты по блокам.
+//!
+//! Фреймворк определяется по файлам в корне проекта ([`Framework::detect`]):
+//! `Cargo.toml` — `cargo test`, `pytest.ini`, `pyproject.toml`,
+//! `setup.cfg` или `conftest.py` — pytest, `package.json` — `npm test`.
+//! Вывод разбирается в итоги отдельных тестов ([`parse_results`]), а
+//! [`block_status`] сопоставляет их блокам по полю `tests` метаданных:
+//! запись подходит тесту с тем же именем или именем, оканчивающимся на
+//! `::запись` либо `.запись`.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use crate::meta;
+use crate::project_env;
+use crate::shell::Shell;
+use crate::BlockInfo;
+
+/// Фреймворк тестов проекта.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Framework {
+    Cargo,
+    Pytest,
+    Npm,
+}
+
+impl Framework {
+    /// Фреймворк проекта `root` по его файлам; `None`, если не найден.
+    pub fn detect(root: &Path) -> Option<Self> {
+        let has = |name: &str| root.join(name).is_file();
+        if has("Cargo.toml") {
+            Some(Self::Cargo)
+        } else if ["pytest.ini", "pyproject.toml", "setup.cfg", "conftest.py"]
+            .into_iter()
+            .any(has)
+        {
+            Some(Self::Pytest)
+        } else if has("package.json") {
+            Some(Self::Npm)
+        } else {
+            None
+        }
+    }
+
+    /// Команда оболочки, запускающая все тесты или только подходящие под
+    /// `filter`, с выводом, который понимает [`parse_results`].
+    pub fn command(self, filter: Option<&str>) -> String {
+        let shell = Shell::system();
+        match (self, filter) {
+            (Self::Cargo, None) => "cargo test --no-fail-fast".into(),
+            (Self::Cargo, Some(f)) => {
+                format!("cargo test --no-fail-fast -- {}", shell.quote(f))
+            }
+            (Self::Pytest, None) => "python -m pytest -v".into(),
+            // Полный идентификатор теста передаётся как путь, а имя — как
+            // выражение отбора.
+            (Self::Pytest, Some(f)) if f.contains("::") => {
+                format!("python -m pytest -v {}", shell.quote(f))
+            }
+            (Self::Pytest, Some(f)) => format!("python -m pytest -v -k {}", shell.quote(f)),
+            (Self::Npm, None) => "npm test -- --verbose".into(),
+            (Self::Npm, Some(f)) => format!("npm test -- --verbose -t {}", shell.quote(f)),
+        }
+    }
+}
+
+impl fmt::Display for Framework {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Cargo => "cargo test",
+            Self::Pytest => "pytest",
+            Self::Npm => "npm test",
+        };
+        f.write_str(s)
+    }
+}
+
+/// Итог одного теста.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestOutcome {
+    pub name: String,
+    pub passed: bool,
+}
+
+/// Итоги тестов из вывода `output` фреймворка `framework`. Пропущенные
+/// тесты в итоги не попадают.
+pub fn parse_results(framework: Framework, output: &str) -> Vec<TestOutcome> {
+    output
+        .lines()
+        .filter_map(|line| match framework {
+            Framework::Cargo => parse_cargo(line),
+            Framework::Pytest => parse_pytest(line),
+            Framework::Npm => parse_jest(line),
+        })
+        .collect()
+}
+
+/// `test path::name ... ok` или `... FAILED`.
+fn parse_cargo(line: &str) -> Option<TestOutcome> {
+    let (name, result) = line.strip_prefix("test ")?.split_once(" ... ")?;
+    let passed = match result.trim() {
+        "ok" => true,
+        "FAILED" => false,
+        _ => return None,
+    };
+    Some(TestOutcome {
+        name: name.trim().to_string(),
+        passed,
+    })
+}
+
+/// `tests/test_a.py::test_name PASSED [ 50%]`.
+fn parse_pytest(line: &str) -> Option<TestOutcome> {
+    let mut parts = line.split_whitespace();
+    let name = parts.next().filter(|n| n.contains("::"))?;
+    let passed = match parts.next()? {
+        "PASSED" | "XPASS" => true,
+        "FAILED" | "ERROR" => false,
+        _ => return None,
+    };
+    Some(TestOutcome {
+        name: name.to_string(),
+        passed,
+    })
+}
+
+/// `✓ name (3 ms)` или `✕ name`; в консоли Windows — `√` и `×`.
+fn parse_jest(line: &str) -> Option<TestOutcome> {
+    let line = line.trim();
+    let mut chars = line.chars();
+    let passed = match chars.next()? {
+        '✓' | '√' => true,
+        '✕' | '×' => false,
+        _ => return None,
+    };
+    let name = chars.as_str().trim();
+    let name = match name.rsplit_once(" (") {
+        Some((name, time)) if time.ends_with("ms)") || time.ends_with(" s)") => name,
+        _ => name,
+    };
+    Some(TestOutcome {
+        name: name.to_string(),
+        passed,
+    })
+}
+
+/// Подходит ли тест с именем `name` записи `entry` поля `tests`.
+pub fn matches(name: &str, entry: &str) -> bool {
+    let entry = entry.trim();
+    !entry.is_empty()
+        && (name == entry
+            || name
+                .strip_suffix(entry)
+                .is_some_and(|rest| rest.ends_with("::") || rest.ends_with('.')))
+}
+
+/// Состояние тестов каждого блока `blocks` файла `content` по итогам
+/// `outcomes`: `Some(false)`, если упал хотя бы один тест блока,
+/// `Some(true)`, если все его тесты прошли, и `None`, если итогов для
+/// тестов блока нет.
+pub fn block_status(
+    content: &str,
+    blocks: &[BlockInfo],
+    outcomes: &[TestOutcome],
+) -> Vec<Option<bool>> {
+    let metas = meta::read_all(content);
+    blocks
+        .iter()
+        .map(|b| {
+            let entries = &metas.iter().find(|m| m.id == b.visual_id)?.tests;
+            outcomes
+                .iter()
+                .filter(|o| entries.iter().any(|e| matches(&o.name, e)))
+                .map(|o| o.passed)
+                .reduce(|a, b| a && b)
+        })
+        .collect()
+}
+
+/// Результат запуска тестов.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestRun {
+    pub framework: Framework,
+    /// Отбор, с которым запускались тесты.
+    pub filter: Option<String>,
+    pub outcomes: Vec<TestOutcome>,
+    /// Команда завершилась успешно.
+    pub success: bool,
+}
+
+/// Запускает тесты проекта `root` фреймворком `framework`, все или только
+/// подходящие под `filter`, и разбирает их вывод.
+pub fn run(root: &Path, framework: Framework, filter: Option<&str>) -> io::Result<TestRun> {
+    let output = Shell::system()
+        .command(&framework.command(filter))
+        .current_dir(root)
+        .envs(project_env::vars(root))
+        .output()?;
+    let text = format!(
+        "{}\n{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    Ok(TestRun {
+        framework,
+        filter: filter.map(str::to_string),
+        outcomes: parse_results(framework, &text),
+        success: output.status.success(),
+    })
+}
@@ -0,0 +1,230 @@
+//! Преобразование байтовых смещений в строки и столбцы.
+//!
+//! Диапазоны [`BlockInfo`](crate::BlockInfo) и результаты поиска хранятся
+//! в байтах, редактору нужны строки и столбцы в символах, а LSP — столбцы
+//! в единицах UTF-16. [`PositionIndex`] строится один раз для текста
+//! документа и выполняет все эти преобразования за логарифмическое время.
+//!
+//! Здесь же определяется стиль перевода строк документа ([`LineEnding`]) и
+//! отделяется BOM ([`split_bom`]), чтобы правки метаданных не меняли их.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use once_cell::sync::Lazy;
+
+/// Положение в тексте: строка и столбец с нуля.
+///
+/// Единица столбца зависит от метода, вернувшего положение: символы
+/// Unicode у [`PositionIndex::position`], единицы UTF-16 у
+/// [`PositionIndex::utf16_position`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    pub fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+}
+
+/// Метка порядка байтов, с которой начинаются некоторые файлы Windows.
+pub const BOM: char = '\u{FEFF}';
+
+/// Отделяет BOM от начала `text`: возвращает сам BOM (или пустую строку)
+/// и остальной текст.
+pub fn split_bom(text: &str) -> (&str, &str) {
+    let len = if text.starts_with(BOM) {
+        BOM.len_utf8()
+    } else {
+        0
+    };
+    text.split_at(len)
+}
+
+/// Перевод строки документа.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+
+    /// Преобладающий перевод строки в `text`. В тексте без переводов строк
+    /// и при равенстве выбирается `\n`.
+    pub fn detect(text: &str) -> Self {
+        let total = text.matches('\n').count();
+        let crlf = text.matches("\r\n").count();
+        if crlf * 2 > total {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        }
+    }
+}
+
+/// Делит строку, полученную из [`str::split_inclusive`] по `'\n'`, на
+/// содержимое и перевод строки (`"\n"`, `"\r\n"` или пустую строку).
+pub fn split_line_ending(line: &str) -> (&str, &str) {
+    let body = line
+        .strip_suffix('\n')
+        .map_or(line, |l| l.strip_suffix('\r').unwrap_or(l));
+    line.split_at(body.len())
+}
+
+/// Индекс начал строк документа.
+///
+/// Смещения, попавшие внутрь многобайтового символа, округляются вниз до
+/// его начала, а смещения за концом текста — до конца текста.
+#[derive(Debug, Clone)]
+pub struct PositionIndex {
+    text: String,
+    line_starts: Vec<usize>,
+}
+
+impl PositionIndex {
+    pub fn new(text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            text: text.to_string(),
+            line_starts,
+        }
+    }
+
+    /// Текст, по которому построен индекс.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Количество строк; пустой текст состоит из одной строки.
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Смещение начала строки `line` или `None`, если такой строки нет.
+    pub fn line_start(&self, line: usize) -> Option<usize> {
+        self.line_starts.get(line).copied()
+    }
+
+    /// Текст строки `line` без завершающего перевода строки.
+    pub fn line_text(&self, line: usize) -> Option<&str> {
+        let start = self.line_start(line)?;
+        let end = self.line_end(line);
+        Some(self.text[start..end].trim_end_matches('\r'))
+    }
+
+    /// Номер строки, содержащей смещение `offset`.
+    pub fn line_of(&self, offset: usize) -> usize {
+        let offset = self.clamp(offset);
+        self.line_starts.partition_point(|&s| s <= offset) - 1
+    }
+
+    /// Строка и столбец в символах для смещения `offset`.
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = self.clamp(offset);
+        let line = self.line_of(offset);
+        let start = self.line_starts[line];
+        Position::new(line, self.text[start..offset].chars().count())
+    }
+
+    /// Строка и столбец в единицах UTF-16 для смещения `offset`.
+    pub fn utf16_position(&self, offset: usize) -> Position {
+        let offset = self.clamp(offset);
+        let line = self.line_of(offset);
+        let start = self.line_starts[line];
+        let column = self.text[start..offset].chars().map(char::len_utf16).sum();
+        Position::new(line, column)
+    }
+
+    /// Смещение для положения `pos` со столбцом в символах. Столбец за
+    /// концом строки указывает на её конец, строка за концом текста — на
+    /// конец текста.
+    pub fn offset(&self, pos: Position) -> usize {
+        self.offset_by(pos, |_| 1)
+    }
+
+    /// Смещение для положения `pos` со столбцом в единицах UTF-16.
+    /// Столбец внутри суррогатной пары указывает на начало символа.
+    pub fn utf16_offset(&self, pos: Position) -> usize {
+        self.offset_by(pos, char::len_utf16)
+    }
+
+    /// Начало и конец байтового диапазона, например
+    /// [`BlockInfo::range`](crate::BlockInfo::range), в символах.
+    pub fn range(&self, (start, end): (usize, usize)) -> (Position, Position) {
+        (self.position(start), self.position(end))
+    }
+
+    /// То же, что [`range`](Self::range), но со столбцами в UTF-16.
+    pub fn utf16_range(&self, (start, end): (usize, usize)) -> (Position, Position) {
+        (self.utf16_position(start), self.utf16_position(end))
+    }
+
+    fn offset_by(&self, pos: Position, width: impl Fn(char) -> usize) -> usize {
+        let Some(start) = self.line_start(pos.line) else {
+            return self.text.len();
+        };
+        let end = self.line_end(pos.line);
+        let mut column = 0;
+        for (i, ch) in self.text[start..end].char_indices() {
+            column += width(ch);
+            if column > pos.column {
+                return start + i;
+            }
+        }
+        end
+    }
+
+    /// Конец строки `line` перед переводом строки.
+    fn line_end(&self, line: usize) -> usize {
+        self.line_starts
+            .get(line + 1)
+            .map_or(self.text.len(), |next| next - 1)
+    }
+
+    fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
+
+/// Индексы позиций открытых документов, хранятся рядом с деревьями разбора.
+static POSITION_INDEXES: Lazy<Mutex<HashMap<String, Arc<PositionIndex>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Возвращает индекс позиций документа `id` для текста `text`.
+///
+/// Индекс перестраивается, только если текст изменился с прошлого вызова.
+pub fn position_index(id: &str, text: &str) -> Arc<PositionIndex> {
+    let Ok(mut indexes) = POSITION_INDEXES.lock() else {
+        return Arc::new(PositionIndex::new(text));
+    };
+    if let Some(index) = indexes.get(id) {
+        if index.text() == text {
+            return index.clone();
+        }
+    }
+    let index = Arc::new(PositionIndex::new(text));
+    indexes.insert(id.to_string(), index.clone());
+    index
+}
+
+/// Последний построенный индекс позиций документа `id`.
+pub fn get_position_index(id: &str) -> Option<Arc<PositionIndex>> {
+    POSITION_INDEXES.lock().ok()?.get(id).cloned()
+}
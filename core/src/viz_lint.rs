@@ -1,5 +1,10 @@
-use crate::parser::viz_comments::{load_viz_document, parse_viz_comments, VizDocument};
+use crate::lint::Severity;
+use crate::parser::viz_comments::{
+    load_viz_document, parse_viz_comments, viz_comment_spans, VizDocument,
+};
+use crate::BlockInfo;
 use std::collections::HashSet;
+use std::ops::Range;
 use std::path::Path;
 
 /// Список разрешённых названий операций. Это минимальный набор, используемый в
@@ -25,9 +30,103 @@ pub fn lint_file(path: &Path) -> std::io::Result<Vec<String>> {
     Ok(lint_document(&doc))
 }
 
+/// Операция узла не входит в [`ALLOWED_OPS`].
+pub const UNKNOWN_OP: &str = "viz/unknown-op";
+/// У узла не указана операция.
+pub const MISSING_OP: &str = "viz/missing-op";
+/// Значение `node` не является числом.
+pub const INVALID_NODE: &str = "viz/invalid-node";
+/// Значение `node` повторяется.
+pub const DUPLICATE_NODE: &str = "viz/duplicate-node";
+/// У узла не указан `node`.
+pub const MISSING_NODE: &str = "viz/missing-node";
+/// Вход ссылается на неизвестный узел.
+pub const UNKNOWN_INPUT: &str = "viz/unknown-input";
+/// Выход ссылается на неизвестный узел.
+pub const UNKNOWN_OUTPUT: &str = "viz/unknown-output";
+
+/// Замечание линтера к узлу документа.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    /// Идентификатор правила, например [`UNKNOWN_OP`].
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub message: String,
+    /// Номер узла в [`VizDocument::nodes`].
+    pub node: usize,
+}
+
+/// Замечание линтера с положением комментария `@viz` и блоком, к которому
+/// он относится.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocatedIssue {
+    pub issue: LintIssue,
+    /// Номер строки комментария с нуля.
+    pub line: usize,
+    /// Диапазон байтов комментария в строке.
+    pub range: Range<usize>,
+    /// `visual_id` блока комментария; `None`, если блока нет.
+    pub visual_id: Option<String>,
+}
+
+/// Проверяет комментарии `@viz` в `content` и привязывает замечания к
+/// блокам `blocks` этого текста. Комментарий относится к блоку, который
+/// начинается сразу за ним через пустые строки и другие комментарии, а без
+/// такого — к самому вложенному блоку, внутри которого стоит.
+pub fn lint_blocks(content: &str, blocks: &[BlockInfo]) -> Vec<LocatedIssue> {
+    let mut starts = vec![0];
+    starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+    let spans = viz_comment_spans(content);
+    lint_issues(&parse_viz_comments(content))
+        .into_iter()
+        .filter_map(|issue| {
+            let (line, range) = spans.get(issue.node)?.clone();
+            let start = starts[line];
+            let end = starts.get(line + 1).copied().unwrap_or(content.len());
+            let visual_id = comment_block(content, blocks, start..end).map(|b| b.visual_id.clone());
+            Some(LocatedIssue {
+                issue,
+                line,
+                range,
+                visual_id,
+            })
+        })
+        .collect()
+}
+
+/// Блок комментария в строке `line` (диапазон байтов с переводом строки).
+fn comment_block<'a>(
+    content: &str,
+    blocks: &'a [BlockInfo],
+    line: Range<usize>,
+) -> Option<&'a BlockInfo> {
+    let next = blocks
+        .iter()
+        .filter(|b| b.range.0 >= line.end)
+        .min_by_key(|b| (b.range.0, std::cmp::Reverse(b.range.1)));
+    let next = next.filter(|b| {
+        content[line.end..b.range.0]
+            .lines()
+            .map(str::trim)
+            .all(|l| l.is_empty() || l.starts_with("//") || l.starts_with('#'))
+    });
+    // Узлы самого комментария лежат в его строке и не считаются.
+    next.or_else(|| {
+        blocks
+            .iter()
+            .filter(|b| b.range.0 < line.start && line.start < b.range.1)
+            .min_by_key(|b| b.range.1 - b.range.0)
+    })
+}
+
 /// Выполняет проверку [`VizDocument`].
 fn lint_document(doc: &VizDocument) -> Vec<String> {
-    let mut errors = Vec::new();
+    lint_issues(doc).into_iter().map(|i| i.message).collect()
+}
+
+/// Проверяет [`VizDocument`] и возвращает замечания с правилами и уровнями.
+pub fn lint_issues(doc: &VizDocument) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
     let mut node_ids = HashSet::new();
     let known_ids: HashSet<&str> = doc
         .nodes
@@ -35,38 +134,74 @@ fn lint_document(doc: &VizDocument) -> Vec<String> {
         .filter_map(|n| n.id.as_deref())
         .collect();
 
-    for entry in &doc.nodes {
+    for (node, entry) in doc.nodes.iter().enumerate() {
         let ident = entry.id.as_deref().unwrap_or("<unknown>");
+        let mut push = |rule, severity, message| {
+            issues.push(LintIssue {
+                rule,
+                severity,
+                message,
+                node,
+            })
+        };
 
         match entry.op.as_deref() {
             Some(op) if ALLOWED_OPS.contains(&op) => {}
-            Some(op) => errors.push(format!("узел {ident}: неизвестная операция `{op}`")),
-            None => errors.push(format!("узел {ident}: отсутствует операция")),
+            Some(op) => push(
+                UNKNOWN_OP,
+                Severity::Error,
+                format!("узел {ident}: неизвестная операция `{op}`"),
+            ),
+            None => push(
+                MISSING_OP,
+                Severity::Error,
+                format!("узел {ident}: отсутствует операция"),
+            ),
         }
 
         match entry.node.as_deref() {
             Some(n) => {
                 if n.parse::<u32>().is_err() {
-                    errors.push(format!("узел {ident}: некорректный node `{n}`"));
+                    push(
+                        INVALID_NODE,
+                        Severity::Error,
+                        format!("узел {ident}: некорректный node `{n}`"),
+                    );
                 }
                 if !node_ids.insert(n) {
-                    errors.push(format!("дублирующийся идентификатор узла `{n}`"));
+                    push(
+                        DUPLICATE_NODE,
+                        Severity::Error,
+                        format!("дублирующийся идентификатор узла `{n}`"),
+                    );
                 }
             }
-            None => errors.push(format!("узел {ident}: отсутствует node")),
+            None => push(
+                MISSING_NODE,
+                Severity::Error,
+                format!("узел {ident}: отсутствует node"),
+            ),
         }
 
         for inp in &entry.inputs {
             if !known_ids.contains(inp.as_str()) {
-                errors.push(format!("узел {ident}: неизвестный вход `{inp}`"));
+                push(
+                    UNKNOWN_INPUT,
+                    Severity::Warning,
+                    format!("узел {ident}: неизвестный вход `{inp}`"),
+                );
             }
         }
         for out in &entry.outputs {
             if !known_ids.contains(out.as_str()) {
-                errors.push(format!("узел {ident}: неизвестный выход `{out}`"));
+                push(
+                    UNKNOWN_OUTPUT,
+                    Severity::Warning,
+                    format!("узел {ident}: неизвестный выход `{out}`"),
+                );
             }
         }
     }
 
-    errors
+    issues
 }
@@ -0,0 +1,122 @@
+//! Выражения наблюдения, привязанные к блокам.
+//!
+//! Выражения хранятся в `extras` под ключом [`WATCH_KEY`] — одной строкой
+//! или списком строк:
+//!
+//! ```text
+//! <!-- @VISUAL_META {"id":"1","x":0.0,"y":0.0,"extras":{"watch":["count","user.name"]}} -->
+//! ```
+//!
+//! При каждой остановке отладчика выражения блоков, в которые попадает
+//! строка остановки, вычисляются по переменным кадра: значение выражения —
+//! значение переменной с тем же именем. Поля вложенных значений отладчик
+//! передаёт под составными именами вроде `user.name`.
+
+use serde_json::Value;
+
+use crate::debugger::{line_span, StoppedLocation};
+use crate::meta::{self, ValidationError, VisualMeta};
+use crate::BlockInfo;
+
+/// Ключ `extras` с выражениями наблюдения блока.
+pub const WATCH_KEY: &str = "watch";
+
+/// Значение выражения наблюдения при остановке.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WatchValue {
+    pub expression: String,
+    /// `None` — выражение не вычисляется в кадре остановки.
+    pub value: Option<String>,
+}
+
+fn parse(value: &Value) -> Result<Vec<String>, String> {
+    match value {
+        Value::String(s) => Ok(vec![s.clone()]),
+        Value::Array(items) => items
+            .iter()
+            .map(|v| {
+                v.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| "ожидается строка".to_string())
+            })
+            .collect(),
+        _ => Err("ожидается строка или список строк".into()),
+    }
+}
+
+/// Выражения наблюдения блока с метаданными `block`. Некорректное значение
+/// `extras.watch` даёт пустой список, ошибку сообщает
+/// [`crate::meta::validate`].
+pub fn resolve(block: &VisualMeta) -> Vec<String> {
+    block
+        .extras
+        .as_ref()
+        .and_then(|e| e.get(WATCH_KEY))
+        .and_then(|v| parse(v).ok())
+        .unwrap_or_default()
+}
+
+/// Значение выражения `expression` в кадре остановки `stopped`.
+pub fn evaluate(expression: &str, stopped: &StoppedLocation) -> Option<String> {
+    let expression = expression.trim();
+    stopped
+        .variables
+        .iter()
+        .find(|v| v.name == expression)
+        .map(|v| v.value.clone())
+}
+
+/// Значения выражений наблюдения каждого блока `blocks` файла `content`
+/// при остановке `stopped`. У блоков, в которые строка остановки не
+/// попадает, список пуст.
+pub fn evaluate_at_stop(
+    content: &str,
+    blocks: &[BlockInfo],
+    stopped: &StoppedLocation,
+) -> Vec<Vec<WatchValue>> {
+    let Some((start, end)) = line_span(content, stopped.line) else {
+        return vec![Vec::new(); blocks.len()];
+    };
+    let metas = meta::read_all(content);
+    blocks
+        .iter()
+        .map(|b| {
+            if b.range.0 > start || end > b.range.1 {
+                return Vec::new();
+            }
+            metas
+                .iter()
+                .find(|m| m.id == b.visual_id)
+                .map(resolve)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|expression| WatchValue {
+                    value: evaluate(&expression, stopped),
+                    expression,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Проверяет выражения наблюдения в `extras`.
+pub(crate) fn validate(meta: &VisualMeta, errors: &mut Vec<ValidationError>) {
+    let Some(value) = meta.extras.as_ref().and_then(|e| e.get(WATCH_KEY)) else {
+        return;
+    };
+    let field = format!("extras.{WATCH_KEY}");
+    match parse(value) {
+        Ok(expressions) => {
+            if expressions.iter().any(|e| e.trim().is_empty()) {
+                errors.push(ValidationError {
+                    field,
+                    message: "выражение наблюдения не должно быть пустым".into(),
+                });
+            }
+        }
+        Err(e) => errors.push(ValidationError {
+            field,
+            message: format!("некорректные выражения наблюдения: {e}"),
+        }),
+    }
+}
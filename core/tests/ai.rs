@@ -0,0 +1,210 @@
+use core::ai::{
+    parse_stream_line, AiClient, AiProvider, AiRequest, Limits, Redactor, StreamLine, REDACTED,
+};
+use core::offline_queue::OpError;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Поставщик, отвечающий словами запроса по одному. Запоминает полученные
+/// запросы и наибольшее число одновременных вызовов.
+#[derive(Default)]
+struct Echo {
+    seen: Mutex<Vec<AiRequest>>,
+    active: AtomicUsize,
+    peak: AtomicUsize,
+    delay: Duration,
+}
+
+/// Общий доступ к [`Echo`] из теста и из клиента.
+struct Shared(Arc<Echo>);
+
+impl AiProvider for Shared {
+    fn model(&self) -> &str {
+        "echo"
+    }
+
+    fn complete(
+        &self,
+        request: &AiRequest,
+        on_token: &mut dyn FnMut(&str),
+    ) -> Result<String, OpError> {
+        let active = self.0.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.0.peak.fetch_max(active, Ordering::SeqCst);
+        thread::sleep(self.0.delay);
+        self.0.seen.lock().unwrap().push(request.clone());
+        let mut text = String::new();
+        for word in request.prompt.split_inclusive(' ') {
+            on_token(word);
+            text.push_str(word);
+        }
+        self.0.active.fetch_sub(1, Ordering::SeqCst);
+        if request.prompt == "offline" {
+            return Err(OpError::Offline("connection refused".into()));
+        }
+        Ok(text)
+    }
+}
+
+fn limits(max_concurrent: usize) -> Limits {
+    Limits {
+        max_concurrent,
+        max_requests: None,
+        window: Duration::from_secs(60),
+        cache_size: 16,
+    }
+}
+
+fn client(echo: &Arc<Echo>, redact: &[&str], limits: Limits) -> AiClient {
+    let patterns: Vec<String> = redact.iter().map(|p| p.to_string()).collect();
+    AiClient::new(
+        Box::new(Shared(echo.clone())),
+        Redactor::new(&patterns).unwrap(),
+        limits,
+    )
+}
+
+#[test]
+fn redactor_replaces_group_or_whole_match() {
+    let redactor = Redactor::new(&[
+        r#"password\s*=\s*"([^"]*)""#.to_string(),
+        r"sk-[A-Za-z0-9]{8,}".to_string(),
+    ])
+    .unwrap();
+    let text = r#"let password = "hunter2"; let key = "sk-abcdefgh12";"#;
+    assert_eq!(
+        redactor.redact(text),
+        format!(r#"let password = "{REDACTED}"; let key = "{REDACTED}";"#)
+    );
+    assert_eq!(redactor.redact("fn main() {}"), "fn main() {}");
+    assert!(Redactor::new(&["(".to_string()]).is_err());
+}
+
+#[test]
+fn streams_tokens_and_sends_redacted_request() {
+    let echo = Arc::new(Echo::default());
+    let client = client(&echo, &[r"token=(\w+)"], limits(1));
+    let mut tokens = Vec::new();
+    let request = AiRequest::new("explain token=abc now").with_system("be brief");
+    let response = client
+        .complete(&request, |t| tokens.push(t.to_string()))
+        .unwrap();
+    assert_eq!(tokens, ["explain ", "token=[REDACTED] ", "now"]);
+    assert_eq!(response.text, "explain token=[REDACTED] now");
+    assert!(!response.cached);
+    let seen = echo.seen.lock().unwrap();
+    assert_eq!(seen[0].prompt, "explain token=[REDACTED] now");
+    assert_eq!(seen[0].system.as_deref(), Some("be brief"));
+}
+
+#[test]
+fn repeated_request_comes_from_cache() {
+    let echo = Arc::new(Echo::default());
+    let client = client(&echo, &[r"secret\d"], limits(1));
+    let first = client
+        .complete(&AiRequest::new("a secret1"), |_| {})
+        .unwrap();
+    // Запросы, совпадающие после очистки, совпадают и в кэше.
+    let mut tokens = Vec::new();
+    let second = client
+        .complete(&AiRequest::new("a secret2"), |t| tokens.push(t.to_string()))
+        .unwrap();
+    assert!(second.cached);
+    assert_eq!(second.text, first.text);
+    assert_eq!(tokens, [first.text]);
+    assert_eq!(echo.seen.lock().unwrap().len(), 1);
+
+    let _ = client.complete(&AiRequest::new("other"), |_| {}).unwrap();
+    assert_eq!(echo.seen.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn errors_are_not_cached() {
+    let echo = Arc::new(Echo::default());
+    let client = client(&echo, &[], limits(1));
+    let request = AiRequest::new("offline");
+    assert!(matches!(
+        client.complete(&request, |_| {}),
+        Err(OpError::Offline(_))
+    ));
+    assert!(client.complete(&request, |_| {}).is_err());
+    assert_eq!(echo.seen.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn cache_evicts_oldest_answers() {
+    let echo = Arc::new(Echo::default());
+    let client = client(
+        &echo,
+        &[],
+        Limits {
+            cache_size: 1,
+            ..limits(1)
+        },
+    );
+    for prompt in ["a", "b", "a"] {
+        client.complete(&AiRequest::new(prompt), |_| {}).unwrap();
+    }
+    assert_eq!(echo.seen.lock().unwrap().len(), 3);
+}
+
+#[test]
+fn concurrent_requests_are_limited() {
+    let echo = Arc::new(Echo {
+        delay: Duration::from_millis(30),
+        ..Default::default()
+    });
+    let client = Arc::new(client(&echo, &[], limits(2)));
+    let handles: Vec<_> = (0..6)
+        .map(|i| {
+            let client = client.clone();
+            thread::spawn(move || {
+                client
+                    .complete(&AiRequest::new(format!("q{i}")), |_| {})
+                    .unwrap()
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert_eq!(echo.seen.lock().unwrap().len(), 6);
+    assert_eq!(echo.peak.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn request_rate_is_limited() {
+    let echo = Arc::new(Echo::default());
+    let client = client(
+        &echo,
+        &[],
+        Limits {
+            max_requests: Some(2),
+            window: Duration::from_millis(200),
+            ..limits(4)
+        },
+    );
+    let start = Instant::now();
+    for i in 0..3 {
+        client
+            .complete(&AiRequest::new(format!("q{i}")), |_| {})
+            .unwrap();
+    }
+    assert!(start.elapsed() >= Duration::from_millis(200));
+}
+
+#[test]
+fn parses_stream_lines() {
+    assert_eq!(
+        parse_stream_line(r#"data: {"choices":[{"delta":{"content":"Hi"}}]}"#),
+        StreamLine::Token("Hi".into())
+    );
+    assert_eq!(parse_stream_line("data: [DONE]"), StreamLine::Done);
+    assert_eq!(
+        parse_stream_line(r#"data: {"choices":[{"delta":{"role":"assistant"}}]}"#),
+        StreamLine::Skip
+    );
+    assert_eq!(parse_stream_line(": keep-alive"), StreamLine::Skip);
+    assert_eq!(parse_stream_line(""), StreamLine::Skip);
+}
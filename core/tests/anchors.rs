@@ -0,0 +1,120 @@
+use core::anchor::merge_named;
+use core::meta::{read_all, validate, VisualMeta};
+use core::parser::{parse, parse_to_blocks, Lang};
+use core::{Anchor, AnchorKind, BlockInfo, NamedAnchor};
+use serde_json::json;
+
+#[test]
+fn old_forms_are_still_accepted() {
+    let anchors: Vec<Anchor> =
+        serde_json::from_value(json!([[1, 2], {"range": [3, 4], "kind": "hotspot"}])).unwrap();
+    assert_eq!(anchors[0], (1, 2));
+    assert_eq!(anchors[1], Anchor::new(3, 4).with_kind(AnchorKind::Hotspot));
+
+    let named: Vec<NamedAnchor> = serde_json::from_value(json!([
+        "start",
+        {"name": "n", "label": "Количество", "kind": "param"}
+    ]))
+    .unwrap();
+    assert_eq!(named[0], "start");
+    assert_eq!(named[1].label.as_deref(), Some("Количество"));
+    assert_eq!(named[1].kind, Some(AnchorKind::Param));
+}
+
+#[test]
+fn bare_anchors_keep_compact_form() {
+    let anchors = vec![
+        Anchor::new(1, 2),
+        Anchor::new(3, 4)
+            .with_label("result")
+            .with_kind(AnchorKind::Returns),
+    ];
+    assert_eq!(
+        serde_json::to_value(&anchors).unwrap(),
+        json!([[1, 2], {"range": [3, 4], "label": "result", "kind": "returns"}])
+    );
+    let named = vec![
+        NamedAnchor::new("a"),
+        NamedAnchor::new("b").with_kind(AnchorKind::Hotspot),
+    ];
+    assert_eq!(
+        serde_json::to_value(&named).unwrap(),
+        json!(["a", {"name": "b", "kind": "hotspot"}])
+    );
+
+    let block: BlockInfo = serde_json::from_value(json!({
+        "visual_id": "1", "kind": "Op/+", "translations": {}, "range": [0, 1],
+        "anchors": [[0, 1]], "x": 0.0, "y": 0.0, "ai": null
+    }))
+    .unwrap();
+    assert_eq!(block.anchors, vec![(0, 1)]);
+}
+
+#[test]
+fn validation_checks_names_and_labels() {
+    let meta: VisualMeta = serde_json::from_value(json!({
+        "id": "a", "x": 0.0, "y": 0.0,
+        "anchors": ["n", {"name": "n", "kind": "param"}, {"name": "m", "label": " "}]
+    }))
+    .unwrap();
+    let errors = validate(&meta).unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().all(|e| e.field == "anchors"));
+    assert!(errors[0].message.contains("'n'"));
+    assert!(errors[1].message.contains("'m'"));
+}
+
+#[test]
+fn merge_prefers_child_labels() {
+    let base = vec![
+        NamedAnchor::new("n").with_label("Число"),
+        NamedAnchor::new("r"),
+    ];
+    let child = vec![
+        NamedAnchor::new("r").with_kind(AnchorKind::Returns),
+        NamedAnchor::new("n").with_label("Количество"),
+    ];
+    let merged = merge_named(&base, &child);
+    assert_eq!(merged.len(), 2);
+    assert_eq!(merged[0].label.as_deref(), Some("Количество"));
+    assert_eq!(merged[1].kind, Some(AnchorKind::Returns));
+
+    let content = format!(
+        "// @VISUAL_META {}\n// @VISUAL_META {}\n",
+        json!({"id": "anchors-base", "x": 0, "y": 0,
+               "anchors": [{"name": "n", "kind": "param", "label": "Число"}]}),
+        json!({"id": "anchors-child", "x": 0, "y": 0, "extends": "anchors-base",
+               "anchors": [{"name": "n", "label": "Количество"}, "extra"]}),
+    );
+    let metas = read_all(&content);
+    let child = metas.iter().find(|m| m.id == "anchors-child").unwrap();
+    assert_eq!(
+        child.anchors,
+        vec![
+            NamedAnchor::new("n")
+                .with_kind(AnchorKind::Param)
+                .with_label("Количество"),
+            NamedAnchor::new("extra"),
+        ]
+    );
+}
+
+#[test]
+fn parser_marks_params_and_return_type() {
+    let src = "fn add(a: i32, b: i32) -> i32 { a + b }";
+    let tree = parse(src, Lang::Rust, None).unwrap();
+    let blocks = parse_to_blocks(&tree, None);
+    let func = blocks.iter().find(|b| b.kind == "Function/Define").unwrap();
+    let kinds: Vec<_> = func.anchors.iter().map(|a| a.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            Some(AnchorKind::Param),
+            Some(AnchorKind::Param),
+            Some(AnchorKind::Returns)
+        ]
+    );
+    let text = |a: &Anchor| &src[a.range.0..a.range.1];
+    assert_eq!(text(&func.anchors[0]), "a: i32");
+    assert_eq!(text(&func.anchors[2]), "i32");
+}
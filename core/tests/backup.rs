@@ -0,0 +1,65 @@
+use core::config::PROJECT_CONFIG_FILE;
+use core::meta::backup::{backup_files, list_backups, remove_all_in_file, restore, BACKUP_DIR};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const SRC: &str = "// @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0}\nfn main() {}\n";
+
+#[test]
+fn remove_all_backs_up_and_restore_reverts() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), SRC).unwrap();
+
+    assert!(remove_all_in_file(root, Path::new("src/main.rs")).unwrap());
+    assert!(!fs::read_to_string(root.join("src/main.rs"))
+        .unwrap()
+        .contains("@VISUAL_META"));
+    assert!(!remove_all_in_file(root, Path::new("src/main.rs")).unwrap());
+
+    let backups = list_backups(root).unwrap();
+    assert_eq!(backups.len(), 1);
+    assert_eq!(backups[0].files, vec![Path::new("src/main.rs").to_path_buf()]);
+    assert!(root.join(BACKUP_DIR).join(&backups[0].id).join("src/main.rs").exists());
+
+    let restored = restore(root, &backups[0].id, None).unwrap();
+    assert_eq!(restored.len(), 1);
+    assert_eq!(fs::read_to_string(root.join("src/main.rs")).unwrap(), SRC);
+    // Перезаписанная версия тоже сохранена.
+    assert_eq!(list_backups(root).unwrap().len(), 2);
+}
+
+#[test]
+fn retention_keeps_latest_backups() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join(PROJECT_CONFIG_FILE), "[backups]\nkeep = 2\n").unwrap();
+    for i in 0..4 {
+        fs::write(root.join("a.rs"), format!("{SRC}// {i}\n")).unwrap();
+        remove_all_in_file(root, &root.join("a.rs")).unwrap();
+    }
+    let backups = list_backups(root).unwrap();
+    assert_eq!(backups.len(), 2);
+    let latest = fs::read_to_string(
+        root.join(BACKUP_DIR).join(&backups[0].id).join("a.rs"),
+    )
+    .unwrap();
+    assert!(latest.ends_with("// 3\n"));
+}
+
+#[test]
+fn paths_outside_project_are_rejected() {
+    let dir = tempdir().unwrap();
+    let root = dir.path().join("project");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(dir.path().join("x"), SRC).unwrap();
+    for path in [Path::new("../x"), &root.join("../x"), &dir.path().join("x")] {
+        let err = backup_files(&root, &[path]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        assert!(remove_all_in_file(&root, path).is_err());
+    }
+    assert!(!root.join(BACKUP_DIR).exists());
+    assert_eq!(fs::read_to_string(dir.path().join("x")).unwrap(), SRC);
+}
@@ -0,0 +1,53 @@
+use core::blocks::{block_at, blocks_in_range, BlockLocator};
+use core::parse_blocks;
+
+const SRC: &str = "fn a() {}\n\nfn b() {\n    let x = 1;\n}\n";
+
+#[test]
+fn innermost_block_under_cursor() {
+    let x = SRC.find('x').unwrap();
+    let block = block_at(SRC, "rust", x).unwrap();
+    assert_eq!(block.range, (x, x + 1));
+
+    let fn_b = SRC.find("fn b").unwrap();
+    let blocks = parse_blocks(SRC.to_string(), "rust".into()).unwrap();
+    let locator = BlockLocator::new(&blocks);
+    // Пробел перед `let` лежит в теле функции, но вне инструкций.
+    let i = locator.block_at(fn_b + 9).unwrap();
+    assert_eq!(&SRC[blocks[i].range.0..blocks[i].range.0 + 1], "{");
+
+    // Курсор сразу за блоком относится к нему.
+    let i = locator.block_at(9).unwrap();
+    assert_eq!(blocks[i].range.1, 9);
+}
+
+#[test]
+fn matches_linear_scan() {
+    let blocks = parse_blocks(SRC.to_string(), "rust".into()).unwrap();
+    let locator = BlockLocator::new(&blocks);
+    // На границах соседних блоков допустимы оба ответа, сравниваются
+    // только смещения внутри блоков.
+    for offset in (0..SRC.len()).filter(|o| blocks.iter().all(|b| b.range.1 != *o)) {
+        let expected = blocks
+            .iter()
+            .filter(|b| b.range.0 <= offset && offset < b.range.1)
+            .map(|b| b.range.1 - b.range.0)
+            .min();
+        let found = locator
+            .block_at(offset)
+            .map(|i| blocks[i].range.1 - blocks[i].range.0);
+        assert_eq!(found, expected, "offset {offset}");
+    }
+}
+
+#[test]
+fn selection_maps_to_whole_blocks() {
+    let end = SRC.find("\n\nfn b").unwrap();
+    let found = blocks_in_range(SRC, "rust", 0..end);
+    assert!(!found.is_empty());
+    assert!(found.iter().all(|b| b.range.1 <= end));
+    assert!(found.windows(2).all(|w| w[0].range.0 <= w[1].range.0));
+    assert_eq!(found[0].range, (0, end));
+
+    assert!(blocks_in_range(SRC, "rust", 3..3).is_empty());
+}
@@ -0,0 +1,49 @@
+use core::meta::{read_all, upsert, validate, VisualMeta};
+use core::parse_blocks;
+
+#[test]
+fn size_is_optional_and_omitted_when_unset() {
+    let old = "// @VISUAL_META {\"id\":\"1\",\"x\":1.0,\"y\":2.0}\nfn main() {}\n";
+    let meta = &read_all(old)[0];
+    assert_eq!((meta.width, meta.height), (None, None));
+    assert!(validate(meta).is_ok());
+
+    let out = upsert(old, meta, false);
+    assert!(!out.contains("width"));
+    assert!(!out.contains("height"));
+}
+
+#[test]
+fn size_round_trips_and_reaches_blocks() {
+    let mut meta: VisualMeta = read_all("// @VISUAL_META {\"id\":\"1\",\"x\":0.0,\"y\":0.0}\n")
+        .into_iter()
+        .next()
+        .unwrap();
+    meta.width = Some(200.0);
+    meta.height = Some(80.0);
+    let stored = &read_all(&upsert("fn main() {}\n", &meta, false))[0];
+    assert_eq!((stored.width, stored.height), (Some(200.0), Some(80.0)));
+
+    let code = "fn main() {}\n";
+    let blocks = parse_blocks(code.into(), "rust".into()).unwrap();
+    meta.id = blocks[0].visual_id.clone();
+    // Идентификатор зависит от смещения блока, поэтому комментарий с
+    // метаданными добавляется в конец файла.
+    let comment = upsert("", &meta, false);
+    let blocks = parse_blocks(format!("{code}// {comment}"), "rust".into()).unwrap();
+    let block = blocks.iter().find(|b| b.visual_id == meta.id).unwrap();
+    assert_eq!((block.width, block.height), (Some(200.0), Some(80.0)));
+}
+
+#[test]
+fn size_must_be_positive() {
+    let mut meta = read_all("// @VISUAL_META {\"id\":\"1\",\"x\":0.0,\"y\":0.0,\"width\":-5.0}\n")
+        .into_iter()
+        .next()
+        .unwrap();
+    let errors = validate(&meta).unwrap_err();
+    assert_eq!(errors[0].field, "width");
+    meta.width = Some(10.0);
+    meta.height = Some(f64::NAN);
+    assert_eq!(validate(&meta).unwrap_err()[0].field, "height");
+}
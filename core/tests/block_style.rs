@@ -0,0 +1,51 @@
+use core::blocks::parse_blocks;
+use core::meta::style::{self, parse_color};
+use core::meta::{upsert, validate, VisualMeta};
+use serde_json::json;
+
+fn meta(id: &str, extras: serde_json::Value) -> VisualMeta {
+    serde_json::from_value(json!({"id": id, "x": 0.0, "y": 0.0, "extras": extras})).unwrap()
+}
+
+#[test]
+fn style_is_validated() {
+    assert!(validate(&meta("a", json!({"color": "#fa0", "icon": "⚠"}))).is_ok());
+
+    let errors = validate(&meta("a", json!({"color": "orange", "icon": ""}))).unwrap_err();
+    let fields: Vec<_> = errors.iter().map(|e| e.field.as_str()).collect();
+    assert_eq!(fields, vec!["extras.color", "extras.icon"]);
+
+    let errors = validate(&meta("a", json!({"icon": "слишком"}))).unwrap_err();
+    assert_eq!(errors[0].field, "extras.icon");
+
+    assert_eq!(parse_color("#ff8800"), Some((255, 136, 0)));
+    assert_eq!(parse_color("#fa0"), Some((255, 170, 0)));
+    assert_eq!(parse_color("ff8800"), None);
+}
+
+#[test]
+fn set_style_keeps_other_extras() {
+    let mut m = meta("a", json!({"comment": "важно"}));
+    style::set_style(&mut m, Some("#123456"), None);
+    assert_eq!(style::color(&m), Some("#123456"));
+    assert_eq!(m.extras.as_ref().unwrap()["comment"], "важно");
+
+    let mut m = meta("a", json!({"color": "#123456"}));
+    style::set_style(&mut m, None, None);
+    assert!(m.extras.is_none());
+}
+
+#[test]
+fn style_reaches_block_info() {
+    let code = "fn main() {}\n";
+    let blocks = parse_blocks(code.into(), "rust".into()).unwrap();
+    let id = blocks[0].visual_id.clone();
+    // Идентификатор зависит от смещения блока, поэтому комментарий с
+    // метаданными добавляется в конец файла.
+    let comment = upsert("", &meta(&id, json!({"color": "#ff0000", "icon": "★"})), false);
+    let content = format!("{code}// {comment}");
+    let blocks = parse_blocks(content, "rust".into()).unwrap();
+    let block = blocks.iter().find(|b| b.visual_id == id).unwrap();
+    assert_eq!(block.color.as_deref(), Some("#ff0000"));
+    assert_eq!(block.icon.as_deref(), Some("★"));
+}
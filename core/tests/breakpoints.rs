@@ -0,0 +1,89 @@
+use core::blocks::parse_blocks;
+use core::breakpoints::{self, Breakpoint, Breakpoints};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn source(prefix: &str) -> String {
+    format!(
+        "{prefix}fn work() {{\n    let a = 1;\n    let b = 2;\n}}\n// @VISUAL_META {}\n",
+        json!({"id": "work", "x": 0, "y": 0}),
+    )
+}
+
+fn blocks(content: &str) -> Vec<core::BlockInfo> {
+    let mut blocks = parse_blocks(content.to_string(), "rust".into()).unwrap();
+    for b in &mut blocks {
+        if b.kind == "Function/Define" {
+            b.visual_id = "work".into();
+        }
+    }
+    blocks
+}
+
+#[test]
+fn breakpoints_follow_their_block() {
+    let file = Path::new("src/main.rs");
+    let content = source("");
+    let point = breakpoints::at_line(file, &content, &blocks(&content), 3).unwrap();
+    assert_eq!(
+        point,
+        Breakpoint {
+            file: file.into(),
+            visual_id: "work".into(),
+            offset: 2,
+        }
+    );
+
+    let mut points = Breakpoints::default();
+    assert!(points.toggle(point.clone()));
+    // Строки, вставленные выше блока, сдвигают и точку.
+    let shifted = source("use std::io;\n\n");
+    assert_eq!(points.lines(file, &shifted, &blocks(&shifted)), vec![5]);
+    // Точка за концом блока и точка исчезнувшего блока пропускаются.
+    let short = "fn work() {}\n";
+    assert!(points.lines(file, short, &blocks(short)).is_empty());
+    assert!(points.lines(file, "", &[]).is_empty());
+
+    assert!(!points.toggle(point));
+    assert!(points.entries.is_empty());
+}
+
+#[test]
+fn breakpoints_are_saved_and_resolved_per_project() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    let content = source("\n");
+    fs::write(dir.path().join("src/main.rs"), &content).unwrap();
+    let id = parse_blocks(content, "rust".into())
+        .unwrap()
+        .into_iter()
+        .find(|b| b.kind == "Function/Define")
+        .unwrap()
+        .visual_id;
+
+    let mut points = Breakpoints::default();
+    points.toggle(Breakpoint {
+        file: "src/main.rs".into(),
+        visual_id: id,
+        offset: 1,
+    });
+    points.toggle(Breakpoint {
+        file: "src/gone.rs".into(),
+        visual_id: "work".into(),
+        offset: 0,
+    });
+    points.save(dir.path()).unwrap();
+    let loaded = Breakpoints::load(dir.path()).unwrap();
+    assert_eq!(loaded, points);
+    assert_eq!(
+        Breakpoints::load(&dir.path().join("missing")).unwrap(),
+        Breakpoints::default()
+    );
+
+    assert_eq!(
+        loaded.resolve(dir.path()),
+        vec![(Path::new("src/main.rs").to_path_buf(), 3)]
+    );
+}
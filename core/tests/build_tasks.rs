@@ -0,0 +1,156 @@
+use core::build_tasks::{
+    self, match_problems, BuildTask, BuildTasks, MatcherSpec, Problem, ProblemMatcher,
+    BUILD_TASKS_FILE,
+};
+use core::lint::Severity;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+fn builtin(name: &str) -> Vec<ProblemMatcher> {
+    vec![ProblemMatcher::builtin(name).unwrap()]
+}
+
+#[test]
+fn rustc_matcher_spans_two_lines() {
+    let output = "\
+error[E0425]: cannot find value `x` in this scope
+ --> src/main.rs:2:5
+  |
+2 |     x
+  |     ^ not found in this scope
+
+warning: unused variable: `y`
+  --> src/lib.rs:10:9
+";
+    assert_eq!(
+        match_problems(output, &builtin("rustc")),
+        vec![
+            Problem {
+                file: PathBuf::from("src/main.rs"),
+                line: 2,
+                column: 5,
+                message: "cannot find value `x` in this scope".into(),
+                severity: Severity::Error,
+            },
+            Problem {
+                file: PathBuf::from("src/lib.rs"),
+                line: 10,
+                column: 9,
+                message: "unused variable: `y`".into(),
+                severity: Severity::Warning,
+            },
+        ]
+    );
+}
+
+#[test]
+fn gcc_and_tsc_matchers() {
+    let gcc = match_problems(
+        "main.c:3:5: error: expected ';' before '}' token\nmake: *** Error 1\n",
+        &builtin("gcc"),
+    );
+    assert_eq!(gcc.len(), 1);
+    assert_eq!((gcc[0].line, gcc[0].column), (3, 5));
+    assert_eq!(gcc[0].message, "expected ';' before '}' token");
+
+    let tsc = match_problems(
+        "src/app.ts(7,12): error TS2304: Cannot find name 'x'.\n",
+        &builtin("tsc"),
+    );
+    assert_eq!(tsc[0].file, PathBuf::from("src/app.ts"));
+    assert_eq!((tsc[0].line, tsc[0].column), (7, 12));
+    assert_eq!(tsc[0].message, "TS2304: Cannot find name 'x'.");
+}
+
+#[test]
+fn custom_matcher_uses_default_severity_and_column() {
+    let matcher = ProblemMatcher {
+        pattern: r"^(?P<file>\S+) line (?P<line>\d+): (?P<message>.+)$".into(),
+        severity: Severity::Warning,
+    };
+    let problems = match_problems("lint.py line 4: too long\nok\n", &[matcher]);
+    assert_eq!(
+        problems,
+        vec![Problem {
+            file: PathBuf::from("lint.py"),
+            line: 4,
+            column: 1,
+            message: "too long".into(),
+            severity: Severity::Warning,
+        }]
+    );
+}
+
+#[test]
+fn duplicates_from_several_matchers_are_merged() {
+    let all = BuildTask {
+        name: "build".into(),
+        command: "make".into(),
+        cwd: None,
+        matchers: Vec::new(),
+    }
+    .problem_matchers()
+    .unwrap();
+    let gcc = ProblemMatcher::builtin("gcc").unwrap();
+    let problems = match_problems("a.c:1:1: error: boom\n", &[all, vec![gcc]].concat());
+    assert_eq!(problems.len(), 1);
+}
+
+#[test]
+fn loads_tasks_and_rejects_unknown_matchers() {
+    let dir = tempdir().unwrap();
+    assert_eq!(BuildTasks::load(dir.path()).unwrap(), BuildTasks::default());
+
+    fs::create_dir_all(dir.path().join(".multicode")).unwrap();
+    let file = dir.path().join(BUILD_TASKS_FILE);
+    fs::write(
+        &file,
+        r#"
+[[task]]
+name = "build"
+command = "cargo build"
+cwd = "core"
+matchers = ["rustc", { pattern = '^(?P<file>\S+):(?P<line>\d+) (?P<message>.+)$', severity = "note" }]
+"#,
+    )
+    .unwrap();
+    let tasks = BuildTasks::load(dir.path()).unwrap();
+    let task = tasks.get("build").unwrap();
+    assert_eq!(task.cwd, Some(PathBuf::from("core")));
+    assert_eq!(task.matchers[0], MatcherSpec::Builtin("rustc".into()));
+    let matchers = task.problem_matchers().unwrap();
+    assert_eq!(matchers[1].severity, Severity::Note);
+
+    fs::write(
+        &file,
+        "[[task]]\nname = \"x\"\ncommand = \"x\"\nmatchers = [\"msvc\"]\n",
+    )
+    .unwrap();
+    assert!(BuildTasks::load(dir.path()).is_err());
+    fs::write(
+        &file,
+        "[[task]]\nname = \"x\"\ncommand = \"x\"\nmatchers = [{ pattern = \"(\" }]\n",
+    )
+    .unwrap();
+    assert!(BuildTasks::load(dir.path()).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_resolves_paths_from_task_directory() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("web")).unwrap();
+    let task = BuildTask {
+        name: "check".into(),
+        command: "echo 'src/a.c:2:3: warning: unused' && exit 1".into(),
+        cwd: Some(PathBuf::from("web")),
+        matchers: vec![MatcherSpec::Builtin("gcc".into())],
+    };
+    let run = build_tasks::run(dir.path(), &task).unwrap();
+    assert!(!run.success);
+    assert_eq!(run.task, "check");
+    assert_eq!(run.problems.len(), 1);
+    assert_eq!(run.problems[0].file, PathBuf::from("web/src/a.c"));
+    assert_eq!(run.problems[0].severity, Severity::Warning);
+}
@@ -0,0 +1,63 @@
+use core::meta::{read_all, upsert, upsert_if_changed, VisualMeta};
+use core::upsert_meta;
+use serde_json::json;
+
+fn sample() -> VisualMeta {
+    let mut meta: VisualMeta =
+        serde_json::from_value(json!({"id": "canon", "x": 1.0, "y": 2.0})).unwrap();
+    for (lang, label) in [("ru", "Функция"), ("en", "Function"), ("de", "Funktion")] {
+        meta.translations.insert(lang.into(), label.into());
+    }
+    meta
+}
+
+#[test]
+fn repeated_upsert_does_not_touch_comment() {
+    let first = upsert("fn main() {}\n", &sample(), false);
+    let second = upsert(&first, &sample(), false);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn changed_meta_is_rewritten_canonically() {
+    let first = upsert("fn main() {}\n", &sample(), false);
+    let mut meta = sample();
+    meta.x = 5.0;
+    let second = upsert(&first, &meta, false);
+    assert_ne!(first, second);
+    let line = second.lines().next().unwrap();
+    assert!(line.contains(r#""translations":{"de":"Funktion","en":"Function","ru":"Функция"}"#));
+    assert!(!line.contains("\"tags\""));
+    assert_eq!(read_all(&second)[0].x, 5.0);
+}
+
+#[test]
+fn upsert_if_changed_reports_writes_and_keeps_timestamp() {
+    let (first, written) = upsert_if_changed("fn main() {}\n", &sample(), false);
+    assert!(written);
+    let stamp = read_all(&first)[0].updated_at;
+    let (second, written) = upsert_if_changed(&first, &sample(), false);
+    assert!(!written);
+    assert_eq!(read_all(&second)[0].updated_at, stamp);
+    let mut meta = sample();
+    meta.tags.push("new".into());
+    let (third, written) = upsert_if_changed(&second, &meta, false);
+    assert!(written);
+    assert!(read_all(&third)[0].updated_at >= stamp);
+}
+
+#[test]
+fn upsert_meta_keeps_timestamps_of_untouched_entries() {
+    let mut other = sample();
+    other.id = "other".into();
+    let src = upsert(&upsert("x = 1\n", &sample(), false), &other, false);
+    let before = read_all(&src);
+    let mut edited = before.iter().find(|m| m.id == "canon").cloned().unwrap();
+    edited.y = 10.0;
+    let file = "canonical-test.py".to_string();
+    let out = upsert_meta(src, edited, "python".into(), vec![file.clone()]);
+    let after = read_all(&out[&file]);
+    let stamp = |list: &[VisualMeta], id: &str| list.iter().find(|m| m.id == id).unwrap().updated_at;
+    assert_eq!(stamp(&after, "other"), stamp(&before, "other"));
+    assert!(stamp(&after, "canon") > stamp(&before, "canon"));
+}
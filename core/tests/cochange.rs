@@ -0,0 +1,82 @@
+#![cfg(feature = "git")]
+use core::cochange::{analyze, CoChangeOptions};
+use core::parse_blocks;
+use git2::{Repository, Signature};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn commit_files(repo: &Repository, files: &[(&str, &str)], message: &str) {
+    let mut index = repo.index().unwrap();
+    for (file, content) in files {
+        fs::write(repo.workdir().unwrap().join(file), content).unwrap();
+        index.add_path(Path::new(file)).unwrap();
+    }
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Tester", "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+        .unwrap();
+}
+
+fn server(version: u32) -> String {
+    format!("fn connect() {{\n    let v = {version};\n}}\n\nfn idle() {{\n}}\n")
+}
+
+fn client(version: u32) -> String {
+    format!("fn send() {{\n    let v = {version};\n}}\n")
+}
+
+#[test]
+fn suggests_blocks_changed_together_and_flags_stale_links() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    commit_files(
+        &repo,
+        &[("server.rs", &server(0)), ("client.rs", &client(0))],
+        "initial",
+    );
+    commit_files(
+        &repo,
+        &[("server.rs", &server(1)), ("client.rs", &client(1))],
+        "protocol v1",
+    );
+    commit_files(
+        &repo,
+        &[("server.rs", &server(2)), ("client.rs", &client(2))],
+        "protocol v2",
+    );
+
+    // Связь `idle` → `send`: эти блоки никогда не менялись вместе.
+    let content = server(2);
+    let blocks = parse_blocks(content.clone(), "rust".into()).unwrap();
+    let idle = blocks
+        .iter()
+        .find(|b| content[b.range.0..b.range.1].starts_with("fn idle"))
+        .unwrap();
+    let client_content = client(2);
+    let send = parse_blocks(client_content.clone(), "rust".into())
+        .unwrap()
+        .into_iter()
+        .find(|b| client_content[b.range.0..b.range.1].starts_with("fn send"))
+        .unwrap();
+    let meta = format!(
+        "// <!-- @VISUAL_META {{\"id\":\"{}\",\"x\":0.0,\"y\":0.0,\"links\":[\"{}\"],\"updated_at\":\"2024-01-01T00:00:00Z\"}} -->\n",
+        idle.visual_id, send.visual_id
+    );
+    fs::write(dir.path().join("server.rs"), format!("{content}{meta}")).unwrap();
+
+    let report = analyze(dir.path(), &CoChangeOptions::default()).unwrap();
+    assert_eq!(report.commits, 2);
+    let pairs: Vec<_> = report
+        .suggestions
+        .iter()
+        .map(|s| (s.a.label.as_str(), s.b.label.as_str(), s.together))
+        .collect();
+    assert_eq!(pairs, vec![("fn send() {", "fn connect() {", 2)]);
+    assert_eq!(report.stale_links.len(), 1);
+    assert_eq!(report.stale_links[0].from.label, "fn idle() {");
+    assert_eq!(report.stale_links[0].to.id, send.visual_id);
+}
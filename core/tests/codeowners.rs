@@ -0,0 +1,49 @@
+use core::codeowners::CodeOwners;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const RULES: &str = "\
+# Владельцы по умолчанию
+*           @org/all
+*.rs        @org/rust # комментарий
+/docs/      docs@example.com
+src/gen/
+!src/keep.rs @nobody
+";
+
+#[test]
+fn last_matching_rule_wins() {
+    let root = Path::new("/p");
+    let owners = CodeOwners::parse(root, RULES);
+    let of = |path: &str| owners.owners(Path::new(path)).to_vec();
+    assert_eq!(of("README.md"), ["@org/all"]);
+    assert_eq!(of("src/lib.rs"), ["@org/rust"]);
+    assert_eq!(of("/p/src/lib.rs"), ["@org/rust"]);
+    assert_eq!(of("docs/guide.md"), ["docs@example.com"]);
+    // Шаблон без `/` в начале подходит на любой глубине.
+    assert_eq!(of("sub/docs/guide.md"), ["@org/all"]);
+    // Строка без владельцев снимает назначенных выше.
+    assert!(of("src/gen/out.rs").is_empty());
+    // Отрицания не поддерживаются и пропускаются.
+    assert_eq!(of("src/keep.rs"), ["@org/rust"]);
+    assert!(of("/elsewhere/a.rs").is_empty());
+}
+
+#[test]
+fn file_is_found_in_github_locations() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    assert!(CodeOwners::load(root).is_empty());
+
+    fs::create_dir(root.join("docs")).unwrap();
+    fs::write(root.join("docs/CODEOWNERS"), "* @docs\n").unwrap();
+    assert_eq!(CodeOwners::load(root).owners(Path::new("a.rs")), ["@docs"]);
+
+    fs::create_dir(root.join(".github")).unwrap();
+    fs::write(root.join(".github/CODEOWNERS"), "* @github\n").unwrap();
+    assert_eq!(
+        CodeOwners::load(root).owners(&root.join("a.rs")),
+        ["@github"]
+    );
+}
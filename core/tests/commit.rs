@@ -1,7 +1,6 @@
 #![cfg(feature = "git")]
 use core::git::commit;
 use git2::Repository;
-use std::env;
 use std::fs;
 use tempfile::tempdir;
 
@@ -19,10 +18,7 @@ fn commit_creates_commit() {
     fs::create_dir(dir.path().join("docs")).unwrap();
     fs::write(dir.path().join("backend/file.txt"), "hello").unwrap();
 
-    let prev = env::current_dir().unwrap();
-    env::set_current_dir(dir.path()).unwrap();
-    commit("initial commit").unwrap();
-    env::set_current_dir(prev).unwrap();
+    commit(dir.path(), "initial commit").unwrap();
 
     // Новый репозиторий должен иметь ветку `main`, указывающую на первый
     // коммит, а `HEAD` должен ссылаться на неё.
@@ -44,9 +40,6 @@ fn commit_empty_message_error() {
         cfg.set_str("user.email", "test@example.com").unwrap();
     }
 
-    let prev = env::current_dir().unwrap();
-    env::set_current_dir(dir.path()).unwrap();
-    let err = commit("").unwrap_err();
-    env::set_current_dir(prev).unwrap();
+    let err = commit(dir.path(), "").unwrap_err();
     assert_eq!(err.message(), "сообщение коммита не может быть пустым");
 }
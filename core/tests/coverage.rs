@@ -0,0 +1,123 @@
+use core::blocks::parse_blocks;
+use core::coverage::{
+    self, block_coverage, block_hits, uncovered_tests, BlockCoverage, CoverageReport,
+};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const SOURCE: &str = "fn covered() {\n    let x = 1;\n}\n\nfn missed() {\n    let y = 2;\n}\n";
+
+fn function_blocks(content: &str) -> Vec<core::BlockInfo> {
+    parse_blocks(content.to_string(), "rust".into())
+        .unwrap()
+        .into_iter()
+        .filter(|b| b.kind == "Function/Define")
+        .collect()
+}
+
+#[test]
+fn parses_lcov_and_cobertura() {
+    let lcov = "TN:\nSF:/work/src/lib.rs\nDA:1,3\nDA:2,0\nend_of_record\n";
+    let report = CoverageReport::parse(lcov).unwrap();
+    let lines = report.lines(Path::new("/work/src/lib.rs")).unwrap();
+    assert_eq!(lines.get(&1), Some(&3));
+    assert_eq!(lines.get(&2), Some(&0));
+
+    let xml = r#"<?xml version="1.0"?>
+<coverage>
+  <packages><package name="app"><classes>
+    <class filename="app/util.py">
+      <methods><method name="f"><lines><line number="1" hits="2"/></lines></method></methods>
+      <lines><line number="1" hits="2"/><line number="2" hits="0"/></lines>
+    </class>
+  </classes></package></packages>
+</coverage>"#;
+    let report = CoverageReport::parse(xml).unwrap();
+    // Относительный путь из отчёта находится по окончанию абсолютного.
+    let lines = report.lines(Path::new("/project/app/util.py")).unwrap();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines.get(&1), Some(&2));
+    assert!(report.lines(Path::new("/project/other.py")).is_none());
+    assert!(CoverageReport::parse("<coverage>").is_err());
+}
+
+#[test]
+fn maps_lines_onto_blocks() {
+    let lcov = "SF:src/lib.rs\nDA:1,1\nDA:2,1\nDA:5,1\nDA:6,0\nend_of_record\n";
+    let report = CoverageReport::parse_lcov(lcov);
+    let blocks = function_blocks(SOURCE);
+    let coverage = block_coverage(&report, Path::new("/p/src/lib.rs"), SOURCE, &blocks);
+    assert_eq!(
+        coverage,
+        vec![
+            Some(BlockCoverage {
+                covered: 2,
+                total: 2
+            }),
+            Some(BlockCoverage {
+                covered: 1,
+                total: 2
+            }),
+        ]
+    );
+    assert_eq!(coverage[1].unwrap().percent(), 50);
+
+    let none = block_coverage(&report, Path::new("/p/src/main.rs"), SOURCE, &blocks);
+    assert_eq!(none, vec![None, None]);
+}
+
+#[test]
+fn counts_block_executions() {
+    let lcov = "SF:src/lib.rs\nDA:1,4\nDA:2,40\nDA:5,0\nDA:6,0\nend_of_record\n";
+    let report = CoverageReport::parse_lcov(lcov);
+    let blocks = function_blocks(SOURCE);
+    let path = Path::new("/p/src/lib.rs");
+    assert_eq!(
+        block_hits(&report, path, SOURCE, &blocks),
+        vec![Some(40), Some(0)]
+    );
+    let other = Path::new("/p/src/main.rs");
+    assert_eq!(
+        block_hits(&report, other, SOURCE, &blocks),
+        vec![None, None]
+    );
+}
+
+#[test]
+fn reports_declared_tests_that_never_ran() {
+    let blocks = function_blocks(SOURCE);
+    let id = blocks[1].visual_id.clone();
+    // Комментарий в конце файла не сдвигает блоки и их идентификаторы.
+    let content = format!(
+        "{SOURCE}// @VISUAL_META {{\"id\":\"{id}\",\"x\":0,\"y\":0,\"tests\":[\"cargo test missed\"]}}\n"
+    );
+    let lcov = "SF:lib.rs\nDA:1,1\nDA:2,1\nDA:5,0\nDA:6,0\nend_of_record\n";
+    let report = CoverageReport::parse_lcov(lcov);
+    let coverage = block_coverage(&report, Path::new("lib.rs"), &content, &blocks);
+    assert_eq!(uncovered_tests(&content, &blocks, &coverage), vec![id]);
+}
+
+#[test]
+fn loads_reports_listed_in_config() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    assert_eq!(coverage::load_project(root).unwrap(), None);
+
+    fs::create_dir(root.join("cov")).unwrap();
+    fs::write(root.join("cov/a.info"), "SF:a.rs\nDA:1,1\nend_of_record\n").unwrap();
+    fs::write(
+        root.join("cov/b.info"),
+        "SF:a.rs\nDA:1,2\nDA:2,0\nend_of_record\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("multicode.toml"),
+        "[coverage]\nreports = [\"cov/a.info\", \"cov/b.info\", \"cov/missing.info\"]\n",
+    )
+    .unwrap();
+    let report = coverage::load_project(root).unwrap().unwrap();
+    let lines = report.lines(Path::new("a.rs")).unwrap();
+    assert_eq!(lines.get(&1), Some(&3));
+    assert_eq!(lines.get(&2), Some(&0));
+}
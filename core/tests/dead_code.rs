@@ -0,0 +1,127 @@
+use core::analysis::dead_code::{analyze, clean, ORPHAN_META, UNUSED_FUNCTION};
+use core::lint::lint_project;
+use core::meta::backup::restore;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const RUST: &str = "fn main() {
+    used();
+    S.call();
+}
+
+fn used() {}
+
+fn unused() {
+    unused();
+}
+
+pub fn api() {}
+
+#[test]
+fn check() {}
+
+struct S;
+
+impl Clone for S {
+    fn clone(&self) -> Self {
+        S
+    }
+}
+
+impl S {
+    fn call(&self) {
+        self.method();
+    }
+
+    fn method(&self) {}
+}
+";
+
+const PYTHON: &str = "def helper():
+    return 1
+
+
+def _stale():
+    return helper()
+
+
+@app.route('/')
+def index():
+    pass
+";
+
+fn meta(id: &str, extra: &str) -> String {
+    format!("# @VISUAL_META {{\"id\":\"{id}\",\"x\":0.0,\"y\":0.0{extra}}}\n")
+}
+
+fn project(root: &Path) {
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(root.join("src/main.rs"), RUST).unwrap();
+    let blocks = core::parse_blocks(PYTHON.into(), "python".into()).unwrap();
+    let helper = &blocks
+        .iter()
+        .find(|b| b.kind == "Function/Define")
+        .unwrap()
+        .visual_id;
+    let content = format!(
+        "{PYTHON}{}{}{}{}",
+        meta(helper, ",\"extends\":\"base\""),
+        meta("base", ""),
+        meta("summary:src/tool.py", ""),
+        meta("ghost", ""),
+    );
+    fs::write(root.join("src/tool.py"), content).unwrap();
+}
+
+#[test]
+fn finds_unused_functions_and_orphaned_meta() {
+    let dir = tempdir().unwrap();
+    project(dir.path());
+    let report = analyze(dir.path());
+    let names: Vec<&str> = report.functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["unused", "_stale"]);
+    let unused = &report.functions[0];
+    assert_eq!(unused.file, Path::new("src/main.rs"));
+    assert_eq!(unused.line, 7);
+    assert_eq!(unused.span.start_line, 8);
+    assert_eq!(unused.span.start_column, 4);
+    let orphans: Vec<&str> = report.orphans.iter().map(|o| o.id.as_str()).collect();
+    assert_eq!(orphans, ["ghost"]);
+    assert_eq!(report.orphans[0].span.start_line, 15);
+
+    let rules: Vec<&str> = lint_project(dir.path()).iter().map(|f| f.rule).collect();
+    assert_eq!(rules, [UNUSED_FUNCTION, UNUSED_FUNCTION, ORPHAN_META]);
+}
+
+#[test]
+fn clean_removes_orphans_and_comments_out_dead_code() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    project(root);
+    let before = fs::read_to_string(root.join("src/tool.py")).unwrap();
+
+    let cleaned = clean(root, false).unwrap();
+    assert_eq!(cleaned.changes, 1);
+    assert_eq!(cleaned.files, [Path::new("src/tool.py")]);
+    let after = fs::read_to_string(root.join("src/tool.py")).unwrap();
+    assert!(!after.contains("ghost"));
+    assert_eq!(after.lines().count(), before.lines().count() - 1);
+    assert!(after.contains("def _stale():"));
+
+    let cleaned = clean(root, true).unwrap();
+    assert_eq!(cleaned.changes, 2);
+    let rust = fs::read_to_string(root.join("src/main.rs")).unwrap();
+    assert!(rust.contains("// fn unused() {\n//     unused();\n// }\n"));
+    let python = fs::read_to_string(root.join("src/tool.py")).unwrap();
+    assert!(python.contains("# def _stale():\n#     return helper()\n"));
+    // Единственный вызов helper закомментирован вместе с _stale.
+    let report = analyze(root);
+    assert!(report.orphans.is_empty());
+    let names: Vec<&str> = report.functions.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["helper"]);
+
+    restore(root, &cleaned.backup.unwrap(), None).unwrap();
+    assert_eq!(fs::read_to_string(root.join("src/main.rs")).unwrap(), RUST);
+    assert!(clean(root, false).unwrap().backup.is_none());
+}
@@ -0,0 +1,27 @@
+use core::blocks::parse_blocks;
+use core::debugger::{stopped_block, StoppedLocation};
+
+const SOURCE: &str = "fn first() {\n    let x = 1;\n}\n\nfn second() {\n    let y = 2;\n}\n";
+
+#[test]
+fn stopped_line_maps_to_innermost_block() {
+    let blocks = parse_blocks(SOURCE.to_string(), "rust".into()).unwrap();
+    let kind = |line| stopped_block(SOURCE, &blocks, line).map(|i| blocks[i].kind.as_str());
+    let range = |line| stopped_block(SOURCE, &blocks, line).map(|i| blocks[i].range);
+
+    // Строка оператора попадает в сам оператор, а не в функцию.
+    let (start, end) = range(6).unwrap();
+    assert_eq!(&SOURCE[start..end], "let y = 2;");
+    // Заголовок функции относится к функции.
+    assert_eq!(kind(1), Some("Function/Define"));
+    // Пустая строка между функциями не принадлежит ни одной из них.
+    assert_ne!(kind(4), Some("Function/Define"));
+    assert_eq!(stopped_block(SOURCE, &blocks, 0), None);
+    assert_eq!(stopped_block(SOURCE, &blocks, 42), None);
+
+    let stopped: StoppedLocation = serde_json::from_str(
+        r#"{"file":"src/main.rs","line":2,"variables":[{"name":"x","value":"1"}]}"#,
+    )
+    .unwrap();
+    assert_eq!(stopped.variables[0].name, "x");
+}
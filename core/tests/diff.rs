@@ -3,7 +3,6 @@ use core::git::{commit, diff};
 use git2::{Repository, ErrorCode};
 use tempfile::tempdir;
 use std::fs;
-use std::env;
 
 #[test]
 fn diff_shows_changes() {
@@ -18,12 +17,9 @@ fn diff_shows_changes() {
     let file_path = dir.path().join("backend/file.txt");
     fs::write(&file_path, "line1\n").unwrap();
 
-    let prev = env::current_dir().unwrap();
-    env::set_current_dir(dir.path()).unwrap();
-    commit("initial").unwrap();
+    commit(dir.path(), "initial").unwrap();
     fs::write(&file_path, "line1\nline2\n").unwrap();
-    let out = diff().unwrap();
-    env::set_current_dir(prev).unwrap();
+    let out = diff(dir.path()).unwrap();
 
     assert!(out.contains("line2"));
     assert!(out.contains("backend/file.txt"));
@@ -32,10 +28,6 @@ fn diff_shows_changes() {
 #[test]
 fn diff_errors_outside_repo() {
     let dir = tempdir().unwrap();
-    let prev = env::current_dir().unwrap();
-    env::set_current_dir(dir.path()).unwrap();
-    let err = diff().unwrap_err();
-    env::set_current_dir(prev).unwrap();
+    let err = diff(dir.path()).unwrap_err();
     assert_eq!(err.code(), ErrorCode::NotFound);
 }
-
@@ -0,0 +1,215 @@
+use core::blocks::enrich::{
+    register_stage, stage_names, EnrichContext, EnrichStage, DOCS, NAMES, TAGS,
+};
+use core::blocks::naming::default_name;
+use core::blocks::parse_blocks_with_config;
+use core::config::{EnrichConfig, ParseLimits, PROJECT_CONFIG_FILE};
+use core::parse_blocks;
+use core::BlockInfo;
+use std::fs;
+use tempfile::tempdir;
+
+const SRC: &str = "/// Точка входа.\nfn main() {\n    let x = 1;\n}\n";
+
+fn parse(content: &str, stages: &[&str]) -> core::blocks::ParsedBlocks {
+    let config = EnrichConfig {
+        stages: stages.iter().map(|s| s.to_string()).collect(),
+    };
+    parse_blocks_with_config(
+        content.to_string(),
+        "rust".into(),
+        &ParseLimits::default(),
+        &config,
+    )
+    .unwrap()
+}
+
+fn with_meta(tags: &str) -> (String, String) {
+    let blocks = parse_blocks(SRC.to_string(), "rust".into()).unwrap();
+    let start = SRC.find("fn main").unwrap();
+    let id = blocks
+        .iter()
+        .find(|b| b.range.0 == start)
+        .unwrap()
+        .visual_id
+        .clone();
+    let content = format!(
+        "{SRC}// <!-- @VISUAL_META {{\"id\":\"{id}\",\"x\":0.0,\"y\":0.0,\"tags\":{tags},\"updated_at\":\"2024-01-01T00:00:00Z\"}} -->\n"
+    );
+    (content, id)
+}
+
+#[test]
+fn config_selects_and_orders_stages() {
+    let dir = tempdir().unwrap();
+    assert_eq!(
+        EnrichConfig::load(dir.path()).stages,
+        ["translations", "meta", "names", "docs"]
+    );
+    fs::write(
+        dir.path().join(PROJECT_CONFIG_FILE),
+        "[enrich]\nstages = [\"meta\", \"tags\"]\n",
+    )
+    .unwrap();
+    assert_eq!(EnrichConfig::load(dir.path()).stages, ["meta", "tags"]);
+}
+
+#[test]
+fn default_pipeline_extracts_docs() {
+    let parsed = parse(SRC, &["translations", "meta", "docs"]);
+    let names: Vec<_> = parsed.timings.iter().map(|t| t.stage.as_str()).collect();
+    assert_eq!(names, ["translations", "meta", "docs"]);
+    let start = SRC.find("fn main").unwrap();
+    let main = parsed.blocks.iter().find(|b| b.range.0 == start).unwrap();
+    assert_eq!(main.doc.as_deref(), Some("Точка входа."));
+    assert!(!main.translations.is_empty());
+
+    let parsed = parse(SRC, &["meta"]);
+    let main = parsed.blocks.iter().find(|b| b.range.0 == start).unwrap();
+    assert!(main.doc.is_none());
+    assert!(main.translations.is_empty());
+}
+
+#[test]
+fn tags_are_inherited_by_nested_blocks() {
+    let (content, id) = with_meta("[\"core\"]");
+    let x = content.find('x').unwrap();
+    let nested = |parsed: &core::blocks::ParsedBlocks| {
+        parsed
+            .blocks
+            .iter()
+            .find(|b| b.range == (x, x + 1))
+            .unwrap()
+            .tags
+            .clone()
+    };
+
+    let parsed = parse(&content, &["meta"]);
+    assert!(nested(&parsed).is_empty());
+    let parsed = parse(&content, &["meta", TAGS]);
+    assert_eq!(nested(&parsed), ["core"]);
+    let main = parsed.blocks.iter().find(|b| b.visual_id == id).unwrap();
+    assert_eq!(main.tags, ["core"]);
+}
+
+/// Русские подписи блоков, начинающихся с `prefix`.
+fn labels(parsed: &core::blocks::ParsedBlocks, content: &str, prefix: &str) -> Vec<String> {
+    parsed
+        .blocks
+        .iter()
+        .filter(|b| content[b.range.0..].starts_with(prefix))
+        .map(|b| b.translations["ru"].clone())
+        .collect()
+}
+
+#[test]
+fn names_label_blocks_from_source() {
+    let src = "fn main() {\n    let mut total = 0;\n    if total > 10 && ready() {\n        return;\n    }\n    for i in 0..3 {\n        self.net.send(i);\n    }\n}\n";
+    let parsed = parse(src, &["translations", "meta", NAMES]);
+    assert_eq!(
+        labels(&parsed, src, "fn main"),
+        ["source_file", "main", "fn"]
+    );
+    assert!(labels(&parsed, src, "let mut").contains(&"total".to_string()));
+    assert!(labels(&parsed, src, "if total").contains(&"if total > 10 && ready()".to_string()));
+    assert!(labels(&parsed, src, "for i").contains(&"for i in 0..3".to_string()));
+    assert!(labels(&parsed, src, "self.net.send(").contains(&"self.net.send()".to_string()));
+
+    // Без этапа остаются подписи по виду блока.
+    let parsed = parse(src, &["translations", "meta"]);
+    assert_eq!(labels(&parsed, src, "fn main")[1], "Function/Define");
+}
+
+#[test]
+fn names_keep_translations_from_metadata() {
+    let blocks = parse_blocks(SRC.to_string(), "rust".into()).unwrap();
+    let start = SRC.find("fn main").unwrap();
+    let id = &blocks
+        .iter()
+        .find(|b| b.range.0 == start)
+        .unwrap()
+        .visual_id;
+    let content = format!(
+        "{SRC}// <!-- @VISUAL_META {{\"id\":\"{id}\",\"x\":0.0,\"y\":0.0,\"translations\":{{\"ru\":\"Вход\"}}}} -->\n"
+    );
+    let parsed = parse(&content, &["translations", "meta", NAMES]);
+    let main = parsed.blocks.iter().find(|b| b.range.0 == start).unwrap();
+    assert_eq!(main.translations["ru"], "Вход");
+    assert_eq!(main.translations["en"], "main");
+}
+
+#[test]
+fn default_names_per_language() {
+    assert_eq!(
+        default_name("Condition", "elif y < 0:\n    pass").as_deref(),
+        Some("elif y < 0")
+    );
+    assert_eq!(
+        default_name("Condition", "if (y > 10) { return y; }").as_deref(),
+        Some("if y > 10")
+    );
+    assert_eq!(
+        default_name("Loop", "for x in items:\n    go(x)").as_deref(),
+        Some("for x in items")
+    );
+    assert_eq!(
+        default_name("lexical_declaration", "const y = x + 1;").as_deref(),
+        Some("y")
+    );
+    assert_eq!(
+        default_name("assignment", "name: str = \"\"").as_deref(),
+        Some("name")
+    );
+    assert_eq!(
+        default_name("Function/Define", "def load(path):\n    pass").as_deref(),
+        Some("load")
+    );
+    assert_eq!(
+        default_name("class_declaration", "class C {\n}").as_deref(),
+        Some("C")
+    );
+    assert_eq!(
+        default_name("rule_set", ".btn, a:hover { color: red; }").as_deref(),
+        Some(".btn, a:hover")
+    );
+    assert_eq!(
+        default_name("element", "<div class=\"x\">\n</div>").as_deref(),
+        Some("<div>")
+    );
+    assert_eq!(default_name("block", "{\n    go();\n}"), None);
+    let long = format!("if {} {{\n}}", "a && ".repeat(20) + "b");
+    let name = default_name("Condition", &long).unwrap();
+    assert_eq!(name.chars().count(), 40);
+    assert!(name.ends_with('…'));
+}
+
+struct Upper;
+
+impl EnrichStage for Upper {
+    fn name(&self) -> &str {
+        "test-upper-doc"
+    }
+
+    fn apply(&self, _ctx: &EnrichContext, blocks: &mut [BlockInfo]) {
+        for b in blocks.iter_mut() {
+            b.doc = b.doc.as_ref().map(|d| d.to_uppercase());
+        }
+    }
+}
+
+#[test]
+fn plugins_add_stages() {
+    register_stage(Upper);
+    assert!(stage_names().iter().any(|n| n == "test-upper-doc"));
+
+    let start = SRC.find("fn main").unwrap();
+    let parsed = parse(SRC, &[DOCS, "test-upper-doc", "missing"]);
+    assert_eq!(parsed.timings.len(), 2);
+    let main = parsed.blocks.iter().find(|b| b.range.0 == start).unwrap();
+    assert_eq!(main.doc.as_deref(), Some("ТОЧКА ВХОДА."));
+
+    // Порядок этапов важен: плагин до извлечения документации ничего не меняет.
+    let parsed = parse(SRC, &["test-upper-doc", DOCS]);
+    let main = parsed.blocks.iter().find(|b| b.range.0 == start).unwrap();
+    assert_eq!(main.doc.as_deref(), Some("Точка входа."));
+}
@@ -1,5 +1,7 @@
 #![cfg(feature = "export")]
-use core::export::prepare_for_export;
+use core::export::{prepare_for_export, report};
+use core::meta::{summary, upsert, VisualMeta};
+use core::parse_blocks;
 
 #[test]
 fn remove_python_meta() {
@@ -40,3 +42,33 @@ fn keep_meta_when_requested() {
     assert!(kept.contains("@VISUAL_META"));
     assert!(kept.contains("console.log"));
 }
+
+#[test]
+fn report_includes_summaries_and_block_notes() {
+    let dir = tempfile::tempdir().unwrap();
+    let code = "fn run() {}\n";
+    let id = parse_blocks(code.into(), "rust".into()).unwrap()[0]
+        .visual_id
+        .clone();
+    let meta: VisualMeta = serde_json::from_value(serde_json::json!({
+        "id": id,
+        "x": 0.0,
+        "y": 0.0,
+        "ai": {"description": "Runs <all> & more."},
+    }))
+    .unwrap();
+    std::fs::write(
+        dir.path().join("main.rs"),
+        format!("{code}// {}", upsert("", &meta, false)),
+    )
+    .unwrap();
+    summary::regenerate(dir.path(), None).unwrap();
+
+    let html = report::html(dir.path(), "Demo");
+    assert!(html.contains("<title>Demo</title>"));
+    assert!(html.contains("<h2>Модули</h2>\n<section>\n<h3>.</h3>"));
+    assert!(
+        html.contains("<div class=\"summary\">- fn run() {}: Runs &lt;all&gt; &amp; more.</div>")
+    );
+    assert!(html.contains("<li><code>fn run() {}</code> — Runs &lt;all&gt; &amp; more.</li>"));
+}
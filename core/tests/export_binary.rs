@@ -0,0 +1,65 @@
+#![cfg(feature = "export")]
+use core::export::binary::{decode, encode, export_dir, BlockGraph, SCHEMA_VERSION};
+use core::parse_blocks;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn exported_graph_round_trips() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir(root.join("src")).unwrap();
+    let code = "fn main() {}\n";
+    let blocks = parse_blocks(code.into(), "rust".into()).unwrap();
+    let id = &blocks
+        .iter()
+        .find(|b| code[b.range.0..b.range.1].starts_with("fn main"))
+        .unwrap()
+        .visual_id;
+    let content = format!(
+        "{code}// <!-- @VISUAL_META {{\"id\":\"{id}\",\"x\":1.5,\"y\":-2.0,\"tags\":[\"net\"],\"links\":[\"jira:PROJ-1\"],\"translations\":{{\"ru\":\"Корень\"}},\"ai\":{{\"description\":\"точка входа\"}},\"updated_at\":\"2024-01-01T00:00:00Z\"}} -->\n"
+    );
+    fs::write(root.join("src/main.rs"), content).unwrap();
+    fs::write(root.join("util.py"), "def f():\n    pass\n").unwrap();
+    fs::write(root.join("CODEOWNERS"), "src/ @org/core @alice\n").unwrap();
+
+    let mut out = Vec::new();
+    let count = export_dir(root, &mut out).unwrap();
+    let graph = decode(&out).unwrap();
+    assert_eq!(graph.version, SCHEMA_VERSION);
+    let paths: Vec<&str> = graph.files.iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(paths, vec!["src/main.rs", "util.py"]);
+    assert_eq!(graph.files[0].owners, ["@org/core", "@alice"]);
+    assert!(graph.files[1].owners.is_empty());
+    assert_eq!(
+        graph.files.iter().map(|f| f.blocks.len()).sum::<usize>(),
+        count
+    );
+
+    let block = graph.files[0].blocks.iter().find(|b| &b.id == id).unwrap();
+    assert_eq!((block.x, block.y), (1.5, -2.0));
+    assert_eq!(block.tags, vec!["net"]);
+    assert_eq!(block.links, vec!["jira:PROJ-1"]);
+    assert_eq!(block.translations["ru"], "Корень");
+    assert_eq!(block.description.as_deref(), Some("точка входа"));
+
+    assert_eq!(encode(&graph), out);
+}
+
+#[test]
+fn decoder_skips_unknown_fields_and_rejects_newer_versions() {
+    let mut bytes = encode(&BlockGraph {
+        version: SCHEMA_VERSION,
+        files: Vec::new(),
+    });
+    // Поле 15 типа varint из будущей версии схемы.
+    bytes.extend_from_slice(&[15 << 3, 42]);
+    assert_eq!(decode(&bytes).unwrap().version, SCHEMA_VERSION);
+
+    let newer = encode(&BlockGraph {
+        version: SCHEMA_VERSION + 1,
+        files: Vec::new(),
+    });
+    assert!(decode(&newer).is_err());
+    assert!(decode(&[0x12, 0x05, 0x0a]).is_err());
+}
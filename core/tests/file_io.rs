@@ -0,0 +1,83 @@
+use core::file_io::{self, decode, encode, FileEncoding};
+use core::meta::tags::rename_tag;
+use core::parse_dir;
+use std::fs;
+use tempfile::tempdir;
+
+const SRC: &str = "// Привет, мир\nfn main() {}\n";
+
+fn cp1251() -> FileEncoding {
+    FileEncoding::for_label("cp1251").unwrap()
+}
+
+#[test]
+fn detects_legacy_encodings() {
+    let bytes = encode(SRC, cp1251()).unwrap();
+    assert!(std::str::from_utf8(&bytes).is_err());
+    let file = decode(&bytes);
+    assert_eq!(file.text, SRC);
+    assert_eq!(file.encoding.name(), "windows-1251");
+    assert!(!file.lossy);
+
+    for label in ["utf-16le", "utf-16be"] {
+        let plain = FileEncoding::for_label(label).unwrap();
+        let with_bom = FileEncoding { bom: true, ..plain };
+        for encoding in [plain, with_bom] {
+            let file = decode(&encode(SRC, encoding).unwrap());
+            assert_eq!(file.text, SRC);
+            assert_eq!(file.encoding, encoding);
+        }
+    }
+
+    let bom = FileEncoding {
+        bom: true,
+        ..FileEncoding::UTF8
+    };
+    let bytes = encode(SRC, bom).unwrap();
+    assert_eq!(&bytes[..3], &[0xEF, 0xBB, 0xBF]);
+    assert_eq!(decode(&bytes).encoding, bom);
+    assert_eq!(decode(SRC.as_bytes()).encoding, FileEncoding::UTF8);
+}
+
+#[test]
+fn unmappable_text_is_rejected() {
+    let err = encode("😀", cp1251()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn legacy_files_are_parsed_and_written_back() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("main.rs");
+    file_io::write_text(&path, SRC, cp1251()).unwrap();
+    assert!(fs::read_to_string(&path).is_err());
+
+    let parsed = parse_dir(dir.path());
+    assert_eq!(parsed.len(), 1);
+    assert!(!parsed[0].1.is_empty());
+
+    let edited = SRC.replace("мир", "код");
+    file_io::write_preserving(&path, &edited).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), encode(&edited, cp1251()).unwrap());
+    assert_eq!(file_io::read_to_string(&path).unwrap(), edited);
+
+    let new_file = dir.path().join("new.rs");
+    file_io::write_preserving(&new_file, &edited).unwrap();
+    assert_eq!(fs::read_to_string(&new_file).unwrap(), edited);
+}
+
+#[test]
+fn tag_rename_keeps_file_encoding() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("lib.py");
+    let content =
+        "# Модуль\n# @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"tags\":[\"сеть\"]}\n";
+    file_io::write_text(&path, content, cp1251()).unwrap();
+
+    let changed = rename_tag(dir.path(), "сеть", "network").unwrap();
+    assert_eq!(changed, vec![path.clone()]);
+    let file = file_io::read_text(&path).unwrap();
+    assert_eq!(file.encoding, cp1251());
+    assert!(file.text.contains("network"));
+    assert!(file.text.contains("# Модуль\n"));
+}
@@ -0,0 +1,47 @@
+use core::blocks::parse_blocks;
+use core::meta::file_ref::{
+    ensure_root_meta, file_ref_meta, resolve, FILE_REF_KIND, FILE_REF_TAG,
+};
+use core::meta::upsert;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn file_ref_resolves_by_origin_and_after_move() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join("src")).unwrap();
+    let target = root.join("src/util.rs");
+
+    let (root_id, updated) = ensure_root_meta("fn util() {}\n");
+    let updated = updated.expect("корневая запись должна быть создана");
+    assert_eq!(ensure_root_meta(&updated), (root_id.clone(), None));
+    fs::write(&target, &updated).unwrap();
+
+    let meta = file_ref_meta(root, &target, &root_id, 10.0, 20.0);
+    assert_eq!(meta.origin.as_deref(), Some("src/util.rs"));
+    assert_eq!(meta.links, vec![root_id.clone()]);
+    assert!(meta.tags.contains(&FILE_REF_TAG.to_string()));
+    assert_eq!(resolve(root, &meta), Some(target.clone()));
+
+    fs::create_dir_all(root.join("lib")).unwrap();
+    let moved = root.join("lib/util.rs");
+    fs::rename(&target, &moved).unwrap();
+    assert_eq!(resolve(root, &meta), Some(moved));
+}
+
+#[test]
+fn file_ref_meta_becomes_block() {
+    let root = std::path::Path::new("/project");
+    let meta = file_ref_meta(root, &root.join("src/util.rs"), "r1", 5.0, 7.0);
+    let content = upsert("fn main() {}\n", &meta, false);
+    let blocks = parse_blocks(content, "rust".into()).unwrap();
+    let block = blocks
+        .iter()
+        .find(|b| b.kind == FILE_REF_KIND)
+        .expect("блок FileRef");
+    assert_eq!(block.visual_id, meta.id);
+    assert_eq!((block.x, block.y), (5.0, 7.0));
+    assert_eq!(block.translations.get("en").map(String::as_str), Some("util.rs"));
+    assert_eq!(block.links, vec!["r1".to_string()]);
+}
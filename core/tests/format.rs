@@ -0,0 +1,35 @@
+#![cfg(unix)]
+use core::config::FormattersConfig;
+use core::format::format_code;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const META: &str = "<!-- @VISUAL_META {\"id\":\"1\",\"x\":0.0,\"y\":0.0} -->";
+
+#[test]
+fn formats_code_and_keeps_meta_lines() {
+    let content = format!("{META}\nfn a()  {{}}\n// {META}\n");
+    let out = format_code(&content, "tr -s ' '", Path::new("main.rs")).unwrap();
+    assert_eq!(out, format!("{META}\nfn a() {{}}\n// {META}\n"));
+}
+
+#[test]
+fn reports_failing_formatter() {
+    let err = format_code("x\n", "echo bad >&2; exit 1", Path::new("main.rs")).unwrap_err();
+    assert!(err.contains("bad"));
+}
+
+#[test]
+fn project_config_overrides_commands() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("multicode.toml"),
+        "[formatters]\npython = \"ruff format -\"\nrust = \"\"\n",
+    )
+    .unwrap();
+    let config = FormattersConfig::load(dir.path());
+    assert_eq!(config.command_for("python"), Some("ruff format -"));
+    assert_eq!(config.command_for("rust"), None);
+    assert!(config.command_for("css").unwrap().contains("{file}"));
+}
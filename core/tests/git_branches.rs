@@ -0,0 +1,89 @@
+#![cfg(feature = "git")]
+use core::git::{branch_info, checkout_branch, create_branch, stash, stash_pop, GitError};
+use git2::{Repository, Signature};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn commit_file(repo: &Repository, file: &str, content: &str) {
+    fs::write(repo.workdir().unwrap().join(file), content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(file)).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Tester", "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, file, &tree, &parents)
+        .unwrap();
+}
+
+#[test]
+fn creates_and_switches_branches() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let repo = Repository::init(root).unwrap();
+    commit_file(&repo, "a.txt", "main\n");
+    let main = branch_info(root).unwrap().current.unwrap();
+
+    assert!(matches!(
+        create_branch(root, "bad..name", false),
+        Err(GitError::InvalidBranchName(_))
+    ));
+    create_branch(root, "feature", true).unwrap();
+    assert!(matches!(
+        create_branch(root, "feature", false),
+        Err(GitError::BranchExists(_))
+    ));
+    commit_file(&repo, "a.txt", "feature\n");
+    let info = branch_info(root).unwrap();
+    assert_eq!(info.current.as_deref(), Some("feature"));
+    assert!(info.branches.contains(&main));
+
+    checkout_branch(root, &main).unwrap();
+    assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "main\n");
+    let back = branch_info(root).unwrap();
+    assert_eq!(back.current, Some(main.clone()));
+    assert_ne!(back.head, info.head);
+
+    assert!(matches!(
+        checkout_branch(root, "missing"),
+        Err(GitError::BranchNotFound(_))
+    ));
+
+    // Правка файла, который отличается в ветках, мешает переключению.
+    fs::write(root.join("a.txt"), "local\n").unwrap();
+    match checkout_branch(root, "feature") {
+        Err(GitError::Conflicts(paths)) => assert_eq!(paths, [Path::new("a.txt")]),
+        other => panic!("unexpected {other:?}"),
+    }
+    assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "local\n");
+    assert_eq!(branch_info(root).unwrap().current, Some(main));
+}
+
+#[test]
+fn stash_and_pop_restore_changes() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let repo = Repository::init(root).unwrap();
+    commit_file(&repo, "a.txt", "one\n");
+
+    assert!(matches!(stash(root, None), Err(GitError::NothingToStash)));
+    assert!(matches!(stash_pop(root), Err(GitError::NoStash)));
+
+    fs::write(root.join("a.txt"), "two\n").unwrap();
+    stash(root, Some("wip")).unwrap();
+    assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "one\n");
+    let info = branch_info(root).unwrap();
+    assert_eq!(info.stashes.len(), 1);
+    assert!(info.stashes[0].contains("wip"));
+
+    fs::write(root.join("a.txt"), "three\n").unwrap();
+    assert!(matches!(stash_pop(root), Err(GitError::StashConflict)));
+    assert_eq!(branch_info(root).unwrap().stashes.len(), 1);
+
+    fs::write(root.join("a.txt"), "one\n").unwrap();
+    stash_pop(root).unwrap();
+    assert_eq!(fs::read_to_string(root.join("a.txt")).unwrap(), "two\n");
+    assert!(branch_info(root).unwrap().stashes.is_empty());
+}
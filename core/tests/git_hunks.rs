@@ -0,0 +1,122 @@
+#![cfg(feature = "git")]
+use core::git::{changes, commit_index, head_message, stage, stage_hunks, unstage_hunks};
+use git2::{Repository, Signature};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn commit_file(repo: &Repository, file: &Path, content: &str) {
+    fs::write(repo.workdir().unwrap().join(file), content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(file).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Tester", "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, "initial", &tree, &parents)
+        .unwrap();
+}
+
+fn numbered(lines: &[&str]) -> String {
+    lines.iter().map(|l| format!("{l}\n")).collect()
+}
+
+fn index_content(repo: &Repository, file: &str) -> String {
+    let mut index = repo.index().unwrap();
+    index.read(true).unwrap();
+    let entry = index.get_path(Path::new(file), 0).unwrap();
+    String::from_utf8(repo.find_blob(entry.id).unwrap().content().to_vec()).unwrap()
+}
+
+#[test]
+fn stages_and_unstages_single_hunks() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    let lines: Vec<String> = (1..=20).map(|i| format!("line {i}")).collect();
+    let original: Vec<&str> = lines.iter().map(String::as_str).collect();
+    commit_file(&repo, Path::new("a.txt"), &numbered(&original));
+
+    let mut edited = original.clone();
+    edited[1] = "first change";
+    edited[18] = "second change";
+    let path = dir.path().join("a.txt");
+    fs::write(&path, numbered(&edited)).unwrap();
+
+    let work = changes(dir.path()).unwrap();
+    assert!(work.staged.is_empty());
+    assert_eq!(work.unstaged.len(), 1);
+    let file = &work.unstaged[0];
+    assert_eq!(file.path, path);
+    assert_eq!(file.hunks.len(), 2);
+    assert!(file.hunks[0].header.starts_with("@@ -1,"));
+    assert!(file.hunks[1]
+        .lines
+        .iter()
+        .any(|l| l.origin == '+' && l.content == "second change"));
+
+    stage_hunks(&path, &[1]).unwrap();
+    let mut expected = original.clone();
+    expected[18] = "second change";
+    assert_eq!(index_content(&repo, "a.txt"), numbered(&expected));
+    let work = changes(dir.path()).unwrap();
+    assert_eq!(work.staged[0].hunks.len(), 1);
+    assert_eq!(work.unstaged[0].hunks.len(), 1);
+
+    stage_hunks(&path, &[0]).unwrap();
+    assert_eq!(index_content(&repo, "a.txt"), numbered(&edited));
+    assert_eq!(changes(dir.path()).unwrap().staged[0].hunks.len(), 2);
+
+    unstage_hunks(&path, &[0]).unwrap();
+    assert_eq!(index_content(&repo, "a.txt"), numbered(&expected));
+    assert_eq!(fs::read_to_string(&path).unwrap(), numbered(&edited));
+}
+
+#[test]
+fn stages_hunk_of_untracked_file() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    commit_file(&repo, Path::new("a.txt"), "a\n");
+    let path = dir.path().join("new.txt");
+    fs::write(&path, "new\n").unwrap();
+
+    let work = changes(dir.path()).unwrap();
+    assert_eq!(work.unstaged[0].path, path);
+    stage_hunks(&path, &[0]).unwrap();
+    assert_eq!(index_content(&repo, "new.txt"), "new\n");
+    assert!(changes(dir.path()).unwrap().unstaged.is_empty());
+}
+
+#[test]
+fn commits_index_and_amends() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    commit_file(&repo, Path::new("a.txt"), "a\n");
+    assert!(commit_index(dir.path(), "nothing", false).is_err());
+    assert!(commit_index(dir.path(), "  ", false).is_err());
+
+    repo.config()
+        .unwrap()
+        .set_str("user.name", "Author")
+        .unwrap();
+    let path = dir.path().join("a.txt");
+    fs::write(&path, "b\n").unwrap();
+    stage(&path).unwrap();
+    commit_index(dir.path(), "change a", false).unwrap();
+    assert_eq!(
+        head_message(dir.path()).unwrap().as_deref(),
+        Some("change a")
+    );
+    assert!(changes(dir.path()).unwrap().staged.is_empty());
+
+    repo.config()
+        .unwrap()
+        .set_str("user.name", "Fixer")
+        .unwrap();
+    commit_index(dir.path(), "change a to b", true).unwrap();
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.summary(), Some("change a to b"));
+    assert_eq!(head.author().name(), Some("Author"));
+    assert_eq!(head.committer().name(), Some("Fixer"));
+    assert_eq!(head.parent(0).unwrap().summary(), Some("initial"));
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "git")]
+use core::git::{blame, diff, locate, log, status};
+use core::precommit::install_hook;
+use git2::{Repository, Signature};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn commit_file(repo: &Repository, file: &str, content: &str, author: &str) {
+    let path = repo.workdir().unwrap().join(file);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(file)).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now(author, "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    let message = format!("{author}: {file}");
+    repo.commit(Some("HEAD"), &sig, &sig, &message, &tree, &parents)
+        .unwrap();
+}
+
+/// Надпроект `super` с подмодулем `super/sub`; возвращается репозиторий
+/// подмодуля.
+fn superproject(dir: &Path) -> Repository {
+    let origin = Repository::init(dir.join("origin")).unwrap();
+    commit_file(&origin, "lib.rs", "fn lib() {}\n", "Sub");
+
+    let sup = Repository::init(dir.join("super")).unwrap();
+    commit_file(&sup, "main.rs", "fn main() {}\n", "Super");
+    let url = dir.join("origin").to_string_lossy().to_string();
+    let mut submodule = sup.submodule(&url, Path::new("sub"), true).unwrap();
+    submodule.clone(None).unwrap();
+    submodule.add_finalize().unwrap();
+    submodule.open().unwrap()
+}
+
+#[test]
+fn submodule_files_use_submodule_repository() {
+    let dir = tempdir().unwrap();
+    let sub = superproject(dir.path());
+    let root = dir.path().join("super");
+    let lib = root.join("sub/lib.rs");
+
+    let (repo, rel) = locate(&lib).unwrap();
+    assert_eq!(repo.path(), sub.path());
+    assert_eq!(rel, Path::new("lib.rs"));
+
+    let lines = blame(&lib).unwrap();
+    assert_eq!(lines.len(), 1);
+    assert_eq!(lines[0].author, "Sub");
+    assert_eq!(log(&lib).unwrap().len(), 1);
+    assert!(log(&lib).unwrap()[0].ends_with("Sub: lib.rs"));
+    assert!(log(&root).unwrap()[0].ends_with("Super: main.rs"));
+
+    fs::write(&lib, "fn lib() { 1 }\n").unwrap();
+    assert!(diff(&root.join("sub")).unwrap().contains("fn lib() { 1 }"));
+    assert!(!diff(&root.join("main.rs")).unwrap().contains("fn lib"));
+    let files = status(&root).unwrap();
+    assert!(files[&lib].modified);
+}
+
+#[test]
+fn log_of_path_lists_only_commits_touching_it() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    commit_file(&repo, "a/one.rs", "1\n", "A");
+    commit_file(&repo, "b/two.rs", "2\n", "B");
+    commit_file(&repo, "a/one.rs", "3\n", "C");
+
+    assert_eq!(log(dir.path()).unwrap().len(), 3);
+    let a = log(&dir.path().join("a")).unwrap();
+    assert_eq!(a.len(), 2);
+    assert!(a[0].ends_with("C: a/one.rs"));
+    assert!(a[1].ends_with("A: a/one.rs"));
+}
+
+#[test]
+fn worktree_is_resolved_from_its_own_directory() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::init(dir.path().join("main")).unwrap();
+    commit_file(&repo, "a.rs", "fn a() {}\n", "Main");
+    let wt_path = dir.path().join("wt");
+    repo.worktree("wt", &wt_path, None).unwrap();
+    let wt = Repository::open(&wt_path).unwrap();
+    commit_file(&wt, "a.rs", "fn a() { 2 }\n", "Tree");
+
+    let (found, rel) = locate(&wt_path.join("a.rs")).unwrap();
+    assert!(found.is_worktree());
+    assert_eq!(rel, Path::new("a.rs"));
+    assert_eq!(blame(&wt_path.join("a.rs")).unwrap()[0].author, "Tree");
+    assert_eq!(
+        blame(&dir.path().join("main/a.rs")).unwrap()[0].author,
+        "Main"
+    );
+
+    fs::write(wt_path.join("a.rs"), "changed\n").unwrap();
+    assert!(status(&wt_path).unwrap()[&wt_path.join("a.rs")].modified);
+    assert!(status(&dir.path().join("main")).unwrap().is_empty());
+
+    // Хуки общие для всех рабочих деревьев.
+    let hook = install_hook(&wt_path, false).unwrap();
+    assert_eq!(
+        hook.canonicalize().unwrap(),
+        dir.path()
+            .join("main/.git/hooks/pre-commit")
+            .canonicalize()
+            .unwrap()
+    );
+}
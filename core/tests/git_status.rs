@@ -0,0 +1,84 @@
+#![cfg(feature = "git")]
+use core::git::{discard, stage, status, unstage, FileStatus};
+use git2::{Repository, Signature};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn commit_file(repo: &Repository, file: &Path, content: &str) {
+    fs::write(repo.workdir().unwrap().join(file), content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(file).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Tester", "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, "commit", &tree, &parents)
+        .unwrap();
+}
+
+#[test]
+fn status_reports_modified_untracked_and_staged_files() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    commit_file(&repo, Path::new("a.rs"), "fn a() {}\n");
+    commit_file(&repo, Path::new("clean.rs"), "fn c() {}\n");
+    let a = dir.path().join("a.rs");
+    let new = dir.path().join("src").join("new.rs");
+    fs::write(&a, "fn a() { 1 }\n").unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(&new, "fn new() {}\n").unwrap();
+
+    let statuses = status(dir.path()).unwrap();
+    assert_eq!(statuses.len(), 2);
+    assert!(statuses[&a].modified && !statuses[&a].staged);
+    assert!(statuses[&new].untracked);
+
+    stage(&a).unwrap();
+    stage(&new).unwrap();
+    let statuses = status(dir.path()).unwrap();
+    assert_eq!(
+        statuses[&a],
+        FileStatus {
+            staged: true,
+            ..FileStatus::default()
+        }
+    );
+    assert!(statuses[&new].staged && !statuses[&new].untracked);
+
+    unstage(&a).unwrap();
+    unstage(&new).unwrap();
+    let statuses = status(dir.path()).unwrap();
+    assert!(statuses[&a].modified && !statuses[&a].staged);
+    assert!(statuses[&new].untracked);
+
+    // Ключи строятся от переданного каталога, даже если это подкаталог.
+    let nested = status(&dir.path().join("src")).unwrap();
+    assert_eq!(nested.keys().collect::<Vec<_>>(), vec![&new]);
+}
+
+#[test]
+fn discard_restores_tracked_files_only() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    commit_file(&repo, Path::new("a.rs"), "fn a() {}\n");
+    let a = dir.path().join("a.rs");
+    let new = dir.path().join("new.rs");
+    fs::write(&a, "fn a() { 1 }\n").unwrap();
+    fs::write(&new, "fn new() {}\n").unwrap();
+
+    discard(&a).unwrap();
+    assert_eq!(fs::read_to_string(&a).unwrap(), "fn a() {}\n");
+    assert!(discard(&new).is_err());
+    assert!(new.exists());
+
+    fs::remove_file(&a).unwrap();
+    assert!(status(dir.path()).unwrap()[&a].modified);
+    stage(&a).unwrap();
+    assert!(status(dir.path()).unwrap()[&a].staged);
+    unstage(&a).unwrap();
+    discard(&a).unwrap();
+    assert!(a.exists());
+    assert_eq!(status(dir.path()).unwrap().len(), 1);
+}
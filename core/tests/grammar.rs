@@ -0,0 +1,38 @@
+use core::parser::grammar::GRAMMAR_VERSIONS;
+use core::parser::{check_tree, grammar_info, Lang};
+use core::{get_document_tree, parse_blocks};
+
+#[test]
+fn pinned_versions_match_manifest() {
+    let manifest = include_str!("../Cargo.toml");
+    for (_, name, version) in GRAMMAR_VERSIONS {
+        let line = format!("{name} = \"={version}\"");
+        assert!(
+            manifest.contains(&line),
+            "{name} must be pinned to {version} in Cargo.toml"
+        );
+    }
+}
+
+#[test]
+fn grammar_report_serializes() {
+    let report = serde_json::to_value(grammar_info()).unwrap();
+    let rust = report
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|i| i["lang"] == serde_json::json!(Lang::Rust))
+        .unwrap();
+    assert_eq!(rust["crate_name"], "tree-sitter-rust");
+    assert!(rust["abi"].as_u64().unwrap() >= 13);
+}
+
+#[test]
+fn language_switch_keeps_trees_apart() {
+    parse_blocks("fn grammar_switch() {}\n".into(), "rust".into()).unwrap();
+    parse_blocks("def grammar_switch():\n    pass\n".into(), "python".into()).unwrap();
+    let rust = get_document_tree("current:rust").unwrap();
+    let python = get_document_tree("current:python").unwrap();
+    assert!(check_tree(&rust, Lang::Rust).is_none());
+    assert!(check_tree(&python, Lang::Python).is_none());
+}
@@ -0,0 +1,40 @@
+use core::get_document_tree;
+use core::parser::grammar::GRAMMAR_VERSIONS;
+use core::parser::highlight::{highlight_query, DocumentHighlights};
+use core::parser::Lang;
+
+#[test]
+fn every_language_has_a_highlight_query() {
+    for (lang, _, _) in GRAMMAR_VERSIONS {
+        assert!(highlight_query(*lang).is_some(), "{lang}");
+    }
+}
+
+#[test]
+fn rust_source_is_highlighted_by_line() {
+    let src = "fn main() {\n    let s = \"a\nb\"; // note\n}\n";
+    let doc = DocumentHighlights::new("highlight-rust", src, Lang::Rust).unwrap();
+    let lines = doc.lines();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].contains(&(0..2, "keyword")));
+    assert!(lines[0].contains(&(3..7, "function")));
+    // Многострочная строка делится по строкам.
+    assert!(lines[1].contains(&(12..14, "string")));
+    assert!(lines[2].contains(&(0..2, "string")));
+    assert!(lines[2].contains(&(4..11, "comment")));
+}
+
+#[test]
+fn incremental_update_matches_full_highlighting() {
+    let before = "fn a() {}\n\nfn b() {\n    1\n}\n";
+    let after = "fn a() {}\n\n// fn x\nfn b() {\n    \"s\"\n}\n";
+    let mut doc = DocumentHighlights::new("highlight-update", before, Lang::Rust).unwrap();
+    assert!(doc.update(after));
+    assert!(doc.is_current(after));
+    let fresh = DocumentHighlights::new("highlight-fresh", after, Lang::Rust).unwrap();
+    assert_eq!(doc.lines(), fresh.lines());
+    // Дерево документа живёт, пока жива подсветка.
+    assert!(get_document_tree("highlight-update").is_some());
+    drop(doc);
+    assert!(get_document_tree("highlight-update").is_none());
+}
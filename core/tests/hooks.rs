@@ -0,0 +1,79 @@
+use core::config::{run_hook, HookEvent, HooksConfig, ParseLimits, PROJECT_CONFIG_FILE};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn load_reads_hooks_section() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join(PROJECT_CONFIG_FILE),
+        "[hooks]\non_save = \"echo saved\"\non_lint_error = \"echo lint\"\n",
+    )
+    .unwrap();
+    let hooks = HooksConfig::load(dir.path());
+    assert_eq!(hooks.on_save.as_deref(), Some("echo saved"));
+    assert_eq!(hooks.on_lint_error.as_deref(), Some("echo lint"));
+    assert!(hooks.on_parse.is_none());
+}
+
+#[test]
+fn bad_section_keeps_other_sections() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join(PROJECT_CONFIG_FILE),
+        "[limits]\nmax_nodes = \"много\"\n\n[hooks]\non_save = \"echo saved\"\n",
+    )
+    .unwrap();
+    let hooks = HooksConfig::load(dir.path());
+    assert_eq!(hooks.on_save.as_deref(), Some("echo saved"));
+    let limits = ParseLimits::load(dir.path());
+    assert_eq!(limits.max_nodes, ParseLimits::default().max_nodes);
+}
+
+#[test]
+fn missing_config_yields_no_hooks() {
+    let dir = tempdir().unwrap();
+    let hooks = HooksConfig::load(dir.path());
+    assert_eq!(hooks, HooksConfig::default());
+    let event = HookEvent::Save { path: "a.rs".into() };
+    assert!(hooks.run(&event, dir.path()).unwrap().is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_passes_json_payload_on_stdin() {
+    let dir = tempdir().unwrap();
+    let hooks = HooksConfig {
+        on_save: Some("cat".into()),
+        ..Default::default()
+    };
+    let event = HookEvent::Save {
+        path: "src/main.rs".into(),
+    };
+    let output = hooks.run(&event, dir.path()).unwrap().unwrap();
+    assert!(output.success());
+    let payload: serde_json::Value = serde_json::from_str(&output.stdout).unwrap();
+    assert_eq!(payload["event"], "save");
+    assert_eq!(payload["path"], "src/main.rs");
+}
+
+#[cfg(unix)]
+#[test]
+fn command_events_run_only_directly() {
+    let dir = tempdir().unwrap();
+    let hooks = HooksConfig {
+        on_save: Some("cat".into()),
+        on_parse: Some("cat".into()),
+        on_lint_error: Some("cat".into()),
+    };
+    let event = HookEvent::Command {
+        id: "deploy".into(),
+        path: None,
+    };
+    assert!(hooks.run(&event, dir.path()).unwrap().is_none());
+    let output = run_hook("cat", &event, dir.path()).unwrap();
+    let payload: serde_json::Value = serde_json::from_str(&output.stdout).unwrap();
+    assert_eq!(payload["event"], "command");
+    assert_eq!(payload["id"], "deploy");
+    assert!(payload["path"].is_null());
+}
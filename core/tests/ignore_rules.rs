@@ -0,0 +1,45 @@
+use core::ignore_rules::{IgnoreRules, IGNORE_FILE};
+use core::parse_dir;
+use core::search::search_metadata;
+use std::fs;
+use tempfile::tempdir;
+
+const META_SRC: &str = "// @VISUAL_META {\"id\":\"shared\",\"x\":0.0,\"y\":0.0}\nfn main() {}\n";
+
+fn project() -> tempfile::TempDir {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join(".multicode")).unwrap();
+    fs::write(dir.path().join(IGNORE_FILE), "vendor/\n# build output\ntarget\n").unwrap();
+    fs::create_dir_all(dir.path().join("src")).unwrap();
+    fs::create_dir_all(dir.path().join("vendor/lib")).unwrap();
+    fs::create_dir_all(dir.path().join("target")).unwrap();
+    fs::write(dir.path().join("src/main.rs"), META_SRC).unwrap();
+    fs::write(dir.path().join("vendor/lib/dep.rs"), META_SRC).unwrap();
+    fs::write(dir.path().join("target/gen.rs"), META_SRC).unwrap();
+    dir
+}
+
+#[test]
+fn load_reads_project_ignore_file() {
+    let dir = project();
+    let rules = IgnoreRules::load(dir.path());
+    assert!(rules.is_ignored(&dir.path().join("vendor/lib/dep.rs"), false));
+    assert!(rules.is_ignored(&dir.path().join("target"), true));
+    assert!(!rules.is_ignored(&dir.path().join("src/main.rs"), false));
+}
+
+#[test]
+fn parse_dir_skips_ignored_files() {
+    let dir = project();
+    let parsed = parse_dir(dir.path());
+    let files: Vec<_> = parsed.iter().map(|(p, _)| p.clone()).collect();
+    assert_eq!(files, vec![dir.path().join("src/main.rs")]);
+}
+
+#[test]
+fn search_skips_ignored_files() {
+    let dir = project();
+    let results = search_metadata(dir.path(), "shared").unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].file, dir.path().join("src/main.rs"));
+}
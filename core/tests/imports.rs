@@ -0,0 +1,139 @@
+use core::format::imports::organize_imports;
+use std::path::Path;
+
+#[test]
+fn groups_sorts_and_prunes_rust_uses() {
+    let content = "//! Модуль.
+
+use crate::text::PositionIndex;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+// Нужен для write_all.
+use std::io::Write;
+use std::collections::{hash_map, HashMap};
+use anyhow::{bail, Context};
+#[cfg(test)]
+use std::fs;
+pub use crate::lint::Finding;
+
+fn run(p: &Path, m: HashMap<u8, u8>) -> PositionIndex {
+    fs::read(p).unwrap();
+    todo!()
+}
+";
+    let organized = organize_imports(Path::new("src/lib.rs"), content, true).unwrap();
+    assert_eq!(
+        organized,
+        "//! Модуль.
+
+use std::collections::HashMap;
+#[cfg(test)]
+use std::fs;
+// Нужен для write_all.
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::text::PositionIndex;
+
+pub use crate::lint::Finding;
+
+fn run(p: &Path, m: HashMap<u8, u8>) -> PositionIndex {
+    fs::read(p).unwrap();
+    todo!()
+}
+"
+    );
+    assert_eq!(
+        organize_imports(Path::new("src/lib.rs"), &organized, true),
+        None
+    );
+
+    let kept = organize_imports(Path::new("src/lib.rs"), content, false).unwrap();
+    assert!(kept.contains("use anyhow::{bail, Context};\n"));
+    assert!(kept.contains("use std::collections::{hash_map, HashMap};\n"));
+}
+
+#[test]
+fn rust_order_matches_rustfmt() {
+    let content = "use a::Zeta;\nuse a::beta;\nuse a::{b, c};\nuse a::*;\nuse a::ALPHA;\nuse a;\n\nfn f() { (beta, b, c, a); }\n";
+    assert_eq!(
+        organize_imports(Path::new("x.rs"), content, false).unwrap(),
+        "use a;\nuse a::beta;\nuse a::Zeta;\nuse a::ALPHA;\nuse a::*;\nuse a::{b, c};\n\nfn f() { (beta, b, c, a); }\n"
+    );
+}
+
+#[test]
+fn organizes_python_imports() {
+    let content = "\"\"\"Утилиты.\"\"\"
+from .models import User, Group
+import requests
+import os, sys
+from __future__ import annotations
+from typing import List, Optional
+import json  # noqa
+
+__all__ = [\"helper\"]
+
+
+def helper(items: List[User]) -> None:
+    os.getcwd()
+    requests.get(\"x\")
+";
+    assert_eq!(
+        organize_imports(Path::new("util.py"), content, true).unwrap(),
+        "\"\"\"Утилиты.\"\"\"
+from __future__ import annotations
+
+import json  # noqa
+import os
+from typing import List
+
+import requests
+
+from .models import User
+
+__all__ = [\"helper\"]
+
+
+def helper(items: List[User]) -> None:
+    os.getcwd()
+    requests.get(\"x\")
+"
+    );
+}
+
+#[test]
+fn organizes_javascript_imports() {
+    let content = "import { render, hydrate } from './dom';
+import React, { useState, useMemo } from 'react';
+import * as path from 'node:path';
+import './styles.css';
+import { late } from './late';
+
+export function App() {
+  const [x] = useState(render);
+  return <div>{x}</div>;
+}
+";
+    assert_eq!(
+        organize_imports(Path::new("app.jsx"), content, true).unwrap(),
+        "import React, { useState } from 'react';
+
+import { render } from './dom';
+import './styles.css';
+import { late } from './late';
+
+export function App() {
+  const [x] = useState(render);
+  return <div>{x}</div>;
+}
+"
+    );
+    assert_eq!(
+        organize_imports(Path::new("notes.txt"), content, true),
+        None
+    );
+}
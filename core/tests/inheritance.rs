@@ -0,0 +1,79 @@
+use core::lint::{lint_content, META_EXTENDS_CYCLE};
+use core::meta::inheritance::{extends_cycles, inheritance_chain, set_extends};
+use core::meta::read_all;
+use std::path::Path;
+
+fn meta_line(id: &str, extends: Option<&str>, tags: &[&str]) -> String {
+    let mut value = serde_json::json!({ "id": id, "x": 0.0, "y": 0.0, "tags": tags });
+    if let Some(base) = extends {
+        value["extends"] = base.into();
+    }
+    format!("// @VISUAL_META {value}\n")
+}
+
+#[test]
+fn chain_lists_bases_in_order() {
+    let content = [
+        meta_line("leaf", Some("mid"), &["c"]),
+        meta_line("mid", Some("root"), &["b"]),
+        meta_line("root", None, &["a"]),
+        meta_line("orphan", Some("gone"), &[]),
+    ]
+    .concat();
+
+    let chain = inheritance_chain(&content, "leaf");
+    let ids: Vec<&str> = chain.entries.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, ["leaf", "mid", "root"]);
+    assert_eq!(chain.entries[0].extends.as_deref(), Some("mid"));
+    assert!(chain.missing.is_none() && chain.cycle.is_none());
+
+    let orphan = inheritance_chain(&content, "orphan");
+    assert_eq!(orphan.entries.len(), 1);
+    assert_eq!(orphan.missing.as_deref(), Some("gone"));
+    assert!(inheritance_chain(&content, "unknown").entries.is_empty());
+    assert!(extends_cycles(&content).is_empty());
+
+    let rebased = set_extends(&content, "orphan", Some("root"));
+    assert!(rebased.starts_with(&meta_line("leaf", Some("mid"), &["c"])));
+    assert_eq!(rebased.lines().count(), 4);
+    let chain = inheritance_chain(&rebased, "orphan");
+    assert_eq!(chain.entries.len(), 2);
+    assert!(chain.missing.is_none());
+    let cleared = set_extends(&rebased, "orphan", None);
+    assert!(inheritance_chain(&cleared, "orphan").entries[0]
+        .extends
+        .is_none());
+}
+
+#[test]
+fn cycles_are_reported_once_with_their_ids() {
+    let content = [
+        meta_line("x", Some("c"), &[]),
+        meta_line("c", Some("a"), &[]),
+        meta_line("b", Some("c"), &[]),
+        meta_line("a", Some("b"), &[]),
+        meta_line("self", Some("self"), &[]),
+    ]
+    .concat();
+
+    assert_eq!(
+        extends_cycles(&content),
+        vec![vec!["a", "b", "c"], vec!["self"]]
+    );
+    let chain = inheritance_chain(&content, "x");
+    assert_eq!(chain.entries.len(), 4);
+    assert_eq!(chain.cycle.as_deref(), Some("c"));
+    // Чтение не зависает и не теряет записи кольца.
+    assert_eq!(read_all(&content).len(), 5);
+
+    let findings: Vec<_> = lint_content(Path::new("a.rs"), &content)
+        .into_iter()
+        .filter(|f| f.rule == META_EXTENDS_CYCLE)
+        .collect();
+    assert_eq!(findings.len(), 2);
+    assert_eq!(
+        findings[0].message,
+        "цикл наследования extends: a → b → c → a"
+    );
+    assert_eq!(findings[0].span.unwrap().start_line, 4);
+}
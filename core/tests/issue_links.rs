@@ -0,0 +1,67 @@
+use chrono::Utc;
+use core::config::IssueLinksConfig;
+use core::meta::issue_link::IssueLink;
+use core::meta::{validate, VisualMeta, DEFAULT_VERSION};
+use std::collections::HashMap;
+use std::fs;
+use tempfile::tempdir;
+
+fn meta_with_links(links: &[&str]) -> VisualMeta {
+    VisualMeta {
+        version: DEFAULT_VERSION,
+        id: "1".into(),
+        x: 0.0,
+        y: 0.0,
+        width: None,
+        height: None,
+        tags: vec![],
+        links: links.iter().map(|l| l.to_string()).collect(),
+        anchors: vec![],
+        tests: vec![],
+        extends: None,
+        origin: None,
+        translations: HashMap::new(),
+        ai: None,
+        extras: None,
+        updated_at: Utc::now(),
+    }
+}
+
+#[test]
+fn parses_typed_links_and_ignores_block_ids() {
+    assert!(IssueLink::parse("42").is_none());
+    let jira = IssueLink::parse("jira:PROJ-123").unwrap().unwrap();
+    assert_eq!(jira.tracker, "jira");
+    assert_eq!(jira.fields()["project"], "PROJ");
+    assert!(IssueLink::parse("jira:proj 1").unwrap().is_err());
+    assert!(IssueLink::parse("gh:owner/repo#x").unwrap().is_err());
+}
+
+#[test]
+fn validation_reports_malformed_issue_links() {
+    assert!(validate(&meta_with_links(&["1", "gh:owner/repo#42", "jira:PROJ-1"])).is_ok());
+    let errors = validate(&meta_with_links(&["jira:PROJ"])).unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].field, "links");
+}
+
+#[test]
+fn resolves_links_with_project_templates() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("multicode.toml"),
+        "[issue_links]\njira = \"https://example.atlassian.net/browse/{key}\"\n",
+    )
+    .unwrap();
+    let config = IssueLinksConfig::load(dir.path());
+    assert_eq!(
+        config.resolve("jira:PROJ-123").as_deref(),
+        Some("https://example.atlassian.net/browse/PROJ-123")
+    );
+    assert_eq!(
+        config.resolve("gh:owner/repo#42").as_deref(),
+        Some("https://github.com/owner/repo/issues/42")
+    );
+    assert_eq!(config.resolve("yt:ABC-1"), None);
+    assert_eq!(config.resolve("42"), None);
+}
@@ -0,0 +1,101 @@
+use core::config::LicenseConfig;
+use core::license::{check_project, fix, HeaderState, LicenseHeader, LICENSE_HEADER};
+use core::meta::backup::restore;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn header() -> LicenseHeader {
+    LicenseHeader::new(&LicenseConfig {
+        header: Some("Copyright (c) {year} {owner}\n\nSPDX-License-Identifier: MIT".into()),
+        owner: "Acme".into(),
+        year: Some(2024),
+        on_save: false,
+    })
+    .unwrap()
+}
+
+#[test]
+fn inserts_header_after_shebang_and_meta() {
+    let header = header();
+    let content =
+        "#!/usr/bin/env python3\n# @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0}\nimport os\n";
+    let file = Path::new("tool.py");
+    assert_eq!(header.state(file, content), Some(HeaderState::Missing));
+    let fixed = header.apply(file, content).unwrap();
+    assert_eq!(
+        fixed,
+        "#!/usr/bin/env python3\n# @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0}\n\
+         # Copyright (c) 2024 Acme\n#\n# SPDX-License-Identifier: MIT\n\nimport os\n"
+    );
+    assert_eq!(header.state(file, &fixed), Some(HeaderState::Current));
+    assert_eq!(header.apply(file, &fixed), None);
+
+    let css = header.apply(Path::new("site.css"), "").unwrap();
+    assert_eq!(
+        css,
+        "/* Copyright (c) 2024 Acme */\n/* */\n/* SPDX-License-Identifier: MIT */\n"
+    );
+    assert_eq!(header.apply(Path::new("notes.txt"), ""), None);
+}
+
+#[test]
+fn updates_outdated_header_keeping_its_years() {
+    let header = header();
+    let file = Path::new("src/lib.rs");
+    let current =
+        "// Copyright (c) 2019-2023 Acme\n//\n// SPDX-License-Identifier: MIT\n\nfn a() {}\n";
+    assert_eq!(header.state(file, current), Some(HeaderState::Current));
+
+    let content = "// Copyright 2020 Old Corp\n// Licensed under GPL\n\n//! Модуль.\n";
+    assert_eq!(header.state(file, content), Some(HeaderState::Outdated));
+    assert_eq!(
+        header.apply(file, content).unwrap(),
+        "// Copyright (c) 2020 Acme\n//\n// SPDX-License-Identifier: MIT\n\n//! Модуль.\n"
+    );
+
+    // Обычный комментарий в начале файла заголовком не считается.
+    let content = "// Точка входа.\nfn main() {}\n";
+    assert_eq!(
+        header.apply(file, content).unwrap(),
+        format!("// Copyright (c) 2024 Acme\n//\n// SPDX-License-Identifier: MIT\n\n{content}")
+    );
+}
+
+#[test]
+fn lint_and_fix_project() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("multicode.toml"),
+        "[license]\nheader = \"Copyright {year} {owner}\"\nowner = \"Acme\"\nyear = 2024\n",
+    )
+    .unwrap();
+    fs::write(root.join("a.rs"), "// Copyright 2024 Acme\nfn main() {}\n").unwrap();
+    fs::write(root.join("b.js"), "let b = 1;\n").unwrap();
+
+    let findings = check_project(root);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].rule, LICENSE_HEADER);
+    assert_eq!(findings[0].file, Path::new("b.js"));
+    let rules: Vec<&str> = core::lint::lint_project(root)
+        .iter()
+        .map(|f| f.rule)
+        .collect();
+    assert_eq!(rules, [LICENSE_HEADER]);
+
+    let report = fix(root).unwrap();
+    assert_eq!(report.files, [Path::new("b.js")]);
+    assert_eq!(
+        fs::read_to_string(root.join("b.js")).unwrap(),
+        "// Copyright 2024 Acme\n\nlet b = 1;\n"
+    );
+    assert!(check_project(root).is_empty());
+    assert_eq!(fix(root).unwrap().backup, None);
+
+    restore(root, &report.backup.unwrap(), None).unwrap();
+    assert_eq!(
+        fs::read_to_string(root.join("b.js")).unwrap(),
+        "let b = 1;\n"
+    );
+}
@@ -0,0 +1,39 @@
+use core::blocks::parse_blocks_with_limits;
+use core::config::{ParseLimits, PROJECT_CONFIG_FILE};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn loads_limits_section() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join(PROJECT_CONFIG_FILE),
+        "[limits]\nmax_nodes = 10\n",
+    )
+    .unwrap();
+    let limits = ParseLimits::load(dir.path());
+    assert_eq!(limits.max_nodes, 10);
+    assert_eq!(limits.max_file_size, ParseLimits::default().max_file_size);
+}
+
+#[test]
+fn large_file_gets_simplified_view() {
+    let src = "fn limits_a() { let x = 1; }\nfn limits_b() { let y = 2; }\n".to_string();
+    let limits = ParseLimits {
+        max_file_size: 16,
+        ..ParseLimits::default()
+    };
+    let parsed = parse_blocks_with_limits(src, "rust".into(), &limits).unwrap();
+    assert!(parsed.simplified);
+    assert_eq!(parsed.blocks.len(), 2);
+    assert!(parsed.blocks.iter().all(|b| b.anchors.is_empty()));
+    assert!(parsed.blocks.iter().all(|b| b.kind == "Function/Define"));
+}
+
+#[test]
+fn small_file_is_parsed_fully() {
+    let src = "fn limits_small() { let z = 3; }\n".to_string();
+    let parsed = parse_blocks_with_limits(src, "rust".into(), &ParseLimits::default()).unwrap();
+    assert!(!parsed.simplified);
+    assert!(parsed.blocks.len() > 1);
+}
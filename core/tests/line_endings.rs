@@ -0,0 +1,72 @@
+use core::meta::{fix_all, read_all, remove_all, upsert, VisualMeta};
+use core::text::{split_bom, LineEnding};
+
+fn meta(id: &str, x: f64) -> VisualMeta {
+    serde_json::from_value(serde_json::json!({ "id": id, "x": x, "y": 0.0 })).unwrap()
+}
+
+/// Переводы строк текста по порядку.
+fn endings(text: &str) -> Vec<&str> {
+    text.split_inclusive('\n')
+        .map(|l| if l.ends_with("\r\n") { "\r\n" } else { "\n" })
+        .collect()
+}
+
+#[test]
+fn detects_predominant_line_ending() {
+    assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+    assert_eq!(LineEnding::detect("a\nb\nc\r\n"), LineEnding::Lf);
+    assert_eq!(LineEnding::detect("a"), LineEnding::Lf);
+    assert_eq!(split_bom("\u{FEFF}fn a() {}"), ("\u{FEFF}", "fn a() {}"));
+    assert_eq!(split_bom("fn a() {}"), ("", "fn a() {}"));
+}
+
+#[test]
+fn upsert_keeps_crlf_and_bom() {
+    let content = "\u{FEFF}fn a() {}\r\nfn b() {}\r\n";
+    let inserted = upsert(content, &meta("a", 1.0), false);
+    assert!(inserted.starts_with("\u{FEFF}<!-- @VISUAL_META"));
+    assert!(!inserted.replace("\r\n", "").contains('\n'));
+    assert!(inserted.ends_with("fn a() {}\r\nfn b() {}\r\n"));
+
+    let updated = upsert(&inserted, &meta("a", 2.0), false);
+    assert_eq!(read_all(&updated)[0].x, 2.0);
+    assert_eq!(endings(&updated), endings(&inserted));
+    assert_eq!(updated.matches('\u{FEFF}').count(), 1);
+}
+
+#[test]
+fn mixed_line_endings_survive_round_trip() {
+    let content = "fn a() {}\r\nfn b() {}\nfn c() {}\r\nfn d() {}\r\n";
+    let inserted = upsert(content, &meta("a", 1.0), false);
+    // Новая строка метаданных получает преобладающий перевод строки,
+    // остальные строки не меняются.
+    assert!(inserted.ends_with(content));
+    assert_eq!(
+        endings(&inserted),
+        vec!["\r\n", "\r\n", "\n", "\r\n", "\r\n"]
+    );
+
+    let updated = upsert(&inserted, &meta("a", 2.0), true);
+    assert_eq!(endings(&updated), endings(&inserted));
+    assert!(updated.ends_with(content));
+
+    let fixed = fix_all(&updated);
+    assert_eq!(fixed, updated);
+
+    let removed = remove_all(&updated);
+    assert_eq!(removed, format!("\r\n{content}"));
+}
+
+#[test]
+fn unterminated_last_line_gets_document_line_ending() {
+    let updated = upsert("fn a() {}\r\nfn b() {}", &meta("a", 1.0), false);
+    assert!(updated.ends_with("fn a() {}\r\nfn b() {}\r\n"));
+}
+
+#[test]
+fn remove_all_keeps_crlf_of_line_comments() {
+    let content = "a = 1\r\n# @VISUAL_META {\"id\":\"p\",\"x\":0,\"y\":0}\r\nb = 2\r\n";
+    let removed = remove_all(content);
+    assert_eq!(removed, "a = 1\r\n\r\nb = 2\r\n");
+}
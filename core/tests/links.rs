@@ -21,6 +21,8 @@ fn upsert_preserves_links() {
         id: "1".into(),
         x: 0.0,
         y: 0.0,
+        width: None,
+        height: None,
         tags: vec![],
         links: vec!["l".into()],
         anchors: vec![],
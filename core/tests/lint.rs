@@ -0,0 +1,65 @@
+use core::lint::{
+    lint_content, lint_project, to_sarif, Severity, Span, META_INVALID_FIELD, META_INVALID_JSON,
+};
+use core::viz_lint::{UNKNOWN_INPUT, UNKNOWN_OP};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+#[test]
+fn findings_carry_rules_and_spans() {
+    let content = "fn f() {}\n  // @viz op=foo node=1 id=a in=b\n";
+    let findings = lint_content(Path::new("a.rs"), content);
+    let rules: Vec<_> = findings.iter().map(|f| (f.rule, f.severity)).collect();
+    assert_eq!(
+        rules,
+        vec![
+            (UNKNOWN_OP, Severity::Error),
+            (UNKNOWN_INPUT, Severity::Warning)
+        ]
+    );
+    assert_eq!(
+        findings[0].span,
+        Some(Span {
+            start_line: 2,
+            start_column: 6,
+            end_line: 2,
+            end_column: 34,
+        })
+    );
+}
+
+#[test]
+fn meta_comments_are_checked() {
+    let content = "x = 1\n# @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"tags\":[\"t\",\"t\"]}\n# @VISUAL_META {\"id\":\n";
+    let findings = lint_content(Path::new("a.py"), content);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].rule, META_INVALID_FIELD);
+    assert_eq!(findings[0].span.unwrap().start_line, 2);
+
+    let content = "# @VISUAL_META {\"id\": 1}\n";
+    let findings = lint_content(Path::new("a.py"), content);
+    assert_eq!(findings[0].rule, META_INVALID_JSON);
+}
+
+#[test]
+fn project_lint_emits_sarif() {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/a.rs"), "// @viz op=foo node=1 id=a\n").unwrap();
+    let findings = lint_project(dir.path());
+    assert_eq!(findings.len(), 1);
+
+    let sarif = to_sarif(&findings);
+    assert_eq!(sarif["version"], "2.1.0");
+    let run = &sarif["runs"][0];
+    let result = &run["results"][0];
+    assert_eq!(result["ruleId"], UNKNOWN_OP);
+    assert_eq!(result["level"], "error");
+    let index = result["ruleIndex"].as_u64().unwrap() as usize;
+    assert_eq!(run["tool"]["driver"]["rules"][index]["id"], UNKNOWN_OP);
+    let location = &result["locations"][0]["physicalLocation"];
+    assert_eq!(location["artifactLocation"]["uri"], "src/a.rs");
+    assert_eq!(location["region"]["startLine"], 1);
+    assert_eq!(location["region"]["startColumn"], 4);
+}
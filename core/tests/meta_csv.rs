@@ -0,0 +1,86 @@
+use core::meta::backup::list_backups;
+use core::meta::csv::{apply_import, export_csv, export_rows, plan_import};
+use core::meta::read_all;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn exports_and_imports_edited_table() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir(root.join("src")).unwrap();
+    let a = root.join("src/a.rs");
+    fs::write(
+        &a,
+        "fn a() {}\n// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"tags\":[\"net\",\"io\"],\"translations\":{\"en\":\"Open, then read\"},\"extras\":{\"comment\":\"say \\\"hi\\\"\",\"color\":\"#fff\"}}\n",
+    )
+    .unwrap();
+    let untouched = "def b(): pass\n# @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0}\n";
+    fs::write(root.join("b.py"), untouched).unwrap();
+
+    let rows = export_rows(root);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].file, "src/a.rs");
+    assert_eq!(rows[1].comment, "say \"hi\"");
+    let csv = export_csv(root);
+    assert_eq!(
+        csv,
+        "id,file,kind,tags,en,ru,comment\n\
+         b,b.py,,,,,\n\
+         a,src/a.rs,,\"net, io\",\"Open, then read\",,\"say \"\"hi\"\"\"\n"
+    );
+
+    // Редактор таблиц с русской локалью сохраняет через `;` и с BOM.
+    let edited = "\u{feff}id;file;tags;ru;comment\r\nb;b.py;;;\r\na;src/a.rs;net, ui;Открыть;\r\n";
+    let plan = plan_import(root, edited).unwrap();
+    let changes: Vec<_> = plan
+        .changes
+        .iter()
+        .map(|c| (c.field, c.new.as_str()))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![("tags", "net, ui"), ("ru", "Открыть"), ("comment", "")]
+    );
+    assert!(plan.changes[0]
+        .to_string()
+        .starts_with("src/a.rs a: tags: "));
+
+    let changed = apply_import(root, plan).unwrap();
+    assert_eq!(changed, vec![a.clone()]);
+    let content = fs::read_to_string(&a).unwrap();
+    assert!(content.starts_with("fn a() {}\n// @VISUAL_META {"));
+    let meta = &read_all(&content)[0];
+    assert_eq!(meta.tags, vec!["net", "ui"]);
+    assert_eq!(meta.translations["en"], "Open, then read");
+    assert_eq!(meta.translations["ru"], "Открыть");
+    assert_eq!(meta.extras.as_ref().unwrap()["color"], "#fff");
+    assert!(meta.extras.as_ref().unwrap().get("comment").is_none());
+    assert_eq!(fs::read_to_string(root.join("b.py")).unwrap(), untouched);
+    assert_eq!(list_backups(root).unwrap().len(), 1);
+
+    assert!(plan_import(root, &export_csv(root))
+        .unwrap()
+        .changes
+        .is_empty());
+}
+
+#[test]
+fn rejects_invalid_tables_without_writing() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let content = "fn a() {}\n// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0}\n";
+    fs::write(root.join("a.rs"), content).unwrap();
+
+    let err = |data: &str| plan_import(root, data).unwrap_err().to_string();
+    assert!(err("id,tags\na,x\n").contains("file"));
+    assert!(err("id,file,owner\n").contains("owner"));
+    assert!(err("id,file\na\n").contains("строка 2"));
+    assert!(err("id,file,en\na,a.rs,\"open\n").contains("кавычки"));
+    let message = err("id,file,tags\nmissing,a.rs,x\na,a.rs,\"t, t\"\na,a.rs,y\nc,../a.rs,\n");
+    assert!(message.contains("строка 2: в a.rs нет записи missing"));
+    assert!(message.contains("строка 3: tags"));
+    assert!(message.contains("строка 4: запись a повторяется"));
+    assert!(message.contains("строка 5: ../a.rs: путь вне проекта"));
+    assert_eq!(fs::read_to_string(root.join("a.rs")).unwrap(), content);
+}
@@ -0,0 +1,53 @@
+use core::config::{MetaConfig, PROJECT_CONFIG_FILE};
+use core::meta::encoding::{encode, ZSTD_TAG};
+use core::meta::{convert_encoding, read_all, upsert, MetaEncoding, VisualMeta};
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+fn big_meta(id: &str) -> VisualMeta {
+    let mut meta: VisualMeta = serde_json::from_value(json!({"id": id, "x": 1.0, "y": 2.0})).unwrap();
+    meta.extras = Some(json!({ "payload": "abc".repeat(500) }));
+    meta
+}
+
+#[test]
+fn reads_compressed_comments() {
+    let json = serde_json::to_string(&big_meta("zipped")).unwrap();
+    let src = format!("// @VISUAL_META{}\nfn main() {{}}\n", encode(&json, MetaEncoding::Zstd));
+    assert!(src.len() < json.len());
+    let metas = read_all(&src);
+    assert_eq!(metas.len(), 1);
+    assert_eq!(metas[0].id, "zipped");
+    assert!(metas[0].extras.is_some());
+}
+
+#[test]
+fn upsert_keeps_existing_encoding() {
+    let mut meta = big_meta("keep");
+    let json = serde_json::to_string(&meta).unwrap();
+    let src = format!("<!-- @VISUAL_META{} -->\n", encode(&json, MetaEncoding::Zstd));
+    meta.x = 42.0;
+    let updated = upsert(&src, &meta, false);
+    assert!(updated.contains(ZSTD_TAG));
+    assert_eq!(updated.lines().count(), 1);
+    assert_eq!(read_all(&updated)[0].x, 42.0);
+}
+
+#[test]
+fn convert_roundtrip_preserves_metadata() {
+    let json = serde_json::to_string(&big_meta("conv")).unwrap();
+    let src = format!("# @VISUAL_META {json}\nprint(1)\n");
+    let zipped = convert_encoding(&src, MetaEncoding::Zstd);
+    assert!(zipped.starts_with("# @VISUAL_META:zstd "));
+    assert!(zipped.ends_with("\nprint(1)\n"));
+    let back = convert_encoding(&zipped, MetaEncoding::Json);
+    assert_eq!(back, src);
+}
+
+#[test]
+fn loads_meta_section() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join(PROJECT_CONFIG_FILE), "[meta]\nencoding = \"zstd\"\n").unwrap();
+    assert_eq!(MetaConfig::load(dir.path()).encoding, MetaEncoding::Zstd);
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "git")]
+use core::git::{meta_history, MetaChange};
+use git2::{Repository, Signature};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn commit_file(repo: &Repository, file: &Path, content: &str, message: &str) {
+    fs::write(repo.workdir().unwrap().join(file), content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(file).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = Signature::now("Tester", "test@example.com").unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<_> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+        .unwrap();
+}
+
+fn source(x: f64, tags: &str, note: &str) -> String {
+    format!(
+        "fn a() {{}}\n// <!-- @VISUAL_META {{\"id\":\"a\",\"x\":{x},\"y\":0.0,\"tags\":[{tags}],\"ai\":{{\"description\":\"{note}\"}},\"updated_at\":\"2024-01-01T00:00:00Z\"}} -->\n"
+    )
+}
+
+#[test]
+fn history_records_position_tag_and_note_changes() {
+    let dir = tempdir().unwrap();
+    let repo = Repository::init(dir.path()).unwrap();
+    let file = Path::new("main.rs");
+    commit_file(&repo, file, "fn a() {}\n", "plain");
+    commit_file(&repo, file, &source(0.0, "", "draft"), "add meta");
+    commit_file(&repo, file, &source(0.0, "", "draft"), "no change");
+    commit_file(&repo, file, &source(40.0, "\"net\"", "draft"), "move");
+    commit_file(&repo, file, &source(40.0, "\"net\"", "final"), "note");
+
+    let history = meta_history(&dir.path().join(file), "a").unwrap();
+    let summaries: Vec<_> = history.iter().map(|e| e.summary.as_str()).collect();
+    assert_eq!(summaries, vec!["add meta", "move", "note"]);
+    assert_eq!(history[0].changes, vec![MetaChange::Added]);
+    assert_eq!(
+        history[1].changes,
+        vec![
+            MetaChange::Moved {
+                from: (0.0, 0.0),
+                to: (40.0, 0.0)
+            },
+            MetaChange::Tags {
+                added: vec!["net".into()],
+                removed: vec![]
+            },
+        ]
+    );
+    assert_eq!(
+        history[2].changes,
+        vec![MetaChange::Note {
+            from: Some("draft".into()),
+            to: Some("final".into())
+        }]
+    );
+    assert_eq!(history[2].author, "Tester");
+}
@@ -0,0 +1,45 @@
+use core::meta::schema::{fields, FieldType};
+
+#[test]
+fn fields_follow_visual_meta_schema() {
+    let fields = fields();
+    let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "version",
+            "id",
+            "x",
+            "y",
+            "width",
+            "height",
+            "tags",
+            "links",
+            "anchors",
+            "tests",
+            "extends",
+            "origin",
+            "translations",
+            "ai",
+            "extras",
+            "updated_at"
+        ]
+    );
+    let ty = |name: &str| fields.iter().find(|f| f.name == name).unwrap().ty;
+    assert_eq!(ty("id"), FieldType::Text);
+    assert_eq!(ty("x"), FieldType::Number);
+    assert_eq!(ty("width"), FieldType::Number);
+    assert_eq!(ty("version"), FieldType::Number);
+    assert_eq!(ty("tags"), FieldType::List);
+    assert_eq!(ty("extends"), FieldType::Text);
+    assert_eq!(ty("translations"), FieldType::Map);
+    assert_eq!(ty("ai"), FieldType::Object);
+    assert_eq!(ty("extras"), FieldType::Object);
+    let id = fields.iter().find(|f| f.name == "id").unwrap();
+    assert!(id.required);
+    assert_eq!(
+        id.description,
+        "Идентификатор, связывающий эти метаданные с узлами AST."
+    );
+    assert!(!fields.iter().find(|f| f.name == "tags").unwrap().required);
+}
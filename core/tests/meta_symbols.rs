@@ -0,0 +1,38 @@
+use core::meta::symbols::{SymbolKind, WorkspaceSymbols};
+use core::meta::{upsert, VisualMeta};
+use std::fs;
+use tempfile::tempdir;
+
+fn meta(value: serde_json::Value) -> VisualMeta {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn collects_symbols_from_workspace() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let a = meta(serde_json::json!({
+        "id": "net-client", "x": 0.0, "y": 0.0, "tags": ["net", "io"],
+        "translations": {"fr": "Client"}
+    }));
+    let b = meta(serde_json::json!({
+        "id": "parser", "x": 0.0, "y": 0.0, "tags": ["Network"],
+        "links": ["net-client"]
+    }));
+    fs::write(root.join("a.rs"), upsert("fn a() {}\n", &a, false)).unwrap();
+    fs::write(root.join("b.py"), upsert("def b(): pass\n", &b, false)).unwrap();
+
+    let symbols = WorkspaceSymbols::collect(root);
+    assert_eq!(
+        symbols.complete(SymbolKind::Tag, "ne"),
+        vec!["Network", "net"]
+    );
+    assert_eq!(
+        symbols.complete(SymbolKind::Id, ""),
+        vec!["net-client", "parser"]
+    );
+    assert!(symbols.complete(SymbolKind::Language, "").contains(&"ru"));
+    assert_eq!(symbols.complete(SymbolKind::Language, "f"), vec!["fr"]);
+    assert_eq!(symbols.incoming_links("net-client"), 1);
+    assert_eq!(symbols.incoming_links("parser"), 0);
+}
@@ -0,0 +1,101 @@
+use core::analysis::metrics::{measure, measure_file, BlockMetrics, MetricLimit};
+use core::parser::Lang;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const RUST: &str = "// проверка\n\
+pub fn check(&self, a: i32, b: i32) -> i32 {\n\
+    // комментарий\n\
+    if a > 0 && b > 0 {\n\
+        for i in 0..a {\n\
+            if i == b { return i; }\n\
+        }\n\
+    } else if a < 0 {\n\
+        return -1;\n\
+    }\n\
+\n\
+    match b { 0 => 0, _ => 1 }\n\
+}\n";
+
+const PYTHON: &str = "def scan(self, items, limit):\n    \
+for item in items:\n        if item and item > limit:\n            return item\n        \
+elif item is None:\n            continue\n    return None\n";
+
+#[test]
+fn measures_rust_functions() {
+    let blocks = measure_file(Path::new("lib.rs"), RUST, Lang::Rust);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].name.as_deref(), Some("check"));
+    assert_eq!(blocks[0].line, 1);
+    assert_eq!(
+        blocks[0].metrics,
+        BlockMetrics {
+            loc: 10,
+            complexity: 8,
+            depth: 3,
+            params: 2,
+        }
+    );
+}
+
+#[test]
+fn measures_python_functions() {
+    let blocks = measure_file(Path::new("scan.py"), PYTHON, Lang::Python);
+    assert_eq!(
+        blocks[0].metrics,
+        BlockMetrics {
+            loc: 7,
+            complexity: 5,
+            depth: 2,
+            params: 2,
+        }
+    );
+}
+
+#[test]
+fn measures_project_files() {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/lib.rs"), RUST).unwrap();
+    fs::write(dir.path().join("scan.py"), PYTHON).unwrap();
+    let mut files: Vec<_> = measure(dir.path()).into_iter().map(|b| b.file).collect();
+    files.sort();
+    assert_eq!(files, vec![Path::new("scan.py"), Path::new("src/lib.rs")]);
+}
+
+#[test]
+fn parses_limits() {
+    let limit: MetricLimit = "complexity > 15".parse().unwrap();
+    assert_eq!(limit.to_string(), "complexity > 15");
+    let metrics = BlockMetrics {
+        complexity: 16,
+        params: 5,
+        ..Default::default()
+    };
+    assert_eq!(limit.exceeded(&metrics), Some(16));
+    let params: MetricLimit = "params>=5".parse().unwrap();
+    assert_eq!(params.to_string(), "params > 4");
+    assert_eq!(params.exceeded(&metrics), Some(5));
+    assert!("size > 3".parse::<MetricLimit>().is_err());
+    assert!("loc < 3".parse::<MetricLimit>().is_err());
+}
+
+#[cfg(feature = "db")]
+#[test]
+fn stores_metrics_in_database() {
+    use core::analysis::metrics;
+    use core::meta::db;
+
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("lib.rs"), RUST).unwrap();
+    tokio::runtime::Runtime::new().unwrap().block_on(async {
+        let pool = db::open(&dir.path().join(".multicode/meta.db"))
+            .await
+            .unwrap();
+        assert_eq!(metrics::store(dir.path(), &pool).await.unwrap(), 1);
+        // Повторное сохранение заменяет прежние метрики.
+        assert_eq!(metrics::store(dir.path(), &pool).await.unwrap(), 1);
+        assert_eq!(db::list_metrics(&pool).await.unwrap(), measure(dir.path()));
+    });
+}
@@ -0,0 +1,120 @@
+use chrono::{Duration, TimeZone, Utc};
+use core::offline_queue::{backoff, OfflineQueue, OpError, QueueStatus, BASE_BACKOFF, MAX_BACKOFF};
+use serde_json::json;
+use tempfile::tempdir;
+
+#[test]
+fn backoff_doubles_up_to_limit() {
+    assert_eq!(backoff(1), BASE_BACKOFF);
+    assert_eq!(backoff(2), BASE_BACKOFF * 2);
+    assert_eq!(backoff(3), BASE_BACKOFF * 4);
+    assert_eq!(backoff(100), MAX_BACKOFF);
+}
+
+#[test]
+fn push_skips_duplicate_operations() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut queue = OfflineQueue::default();
+    let a = queue.push("team_sync", json!({ "policy": "remote" }), now);
+    let b = queue.push("team_sync", json!({ "policy": "remote" }), now);
+    let c = queue.push("team_sync", json!({ "policy": "local" }), now);
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_eq!(queue.ops.len(), 2);
+}
+
+#[test]
+fn replay_retries_offline_operations_with_backoff() {
+    let now = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut queue = OfflineQueue::default();
+    queue.push("first", json!(1), now);
+    queue.push("second", json!(2), now);
+
+    // Время попытки ещё не пришло.
+    let report = queue.replay(now, false, |_| panic!("not due"));
+    assert_eq!(report.done, 0);
+    assert_eq!(queue.ops.len(), 2);
+
+    // Связи нет: после первой ошибки вторая операция не выполняется.
+    let later = now + Duration::seconds(10);
+    let mut calls = 0;
+    let report = queue.replay(later, false, |_| {
+        calls += 1;
+        Err(OpError::Offline("connection refused".into()))
+    });
+    assert_eq!(calls, 1);
+    assert_eq!(report.postponed, 1);
+    assert_eq!(queue.ops[0].attempts, 1);
+    assert_eq!(
+        queue.status(),
+        QueueStatus {
+            pending: 2,
+            next_attempt: Some(now + Duration::seconds(5)),
+            last_error: Some("connection refused".into()),
+        }
+    );
+    let report = queue.replay(later, false, |_| Err(OpError::Offline("again".into())));
+    assert_eq!(report.postponed, 1);
+    assert_eq!(queue.ops[0].attempts, 1);
+    assert_eq!(queue.ops[1].attempts, 1);
+    assert_eq!(queue.ops[1].next_attempt, later + BASE_BACKOFF);
+
+    // Связь появилась: операции выполняются по порядку.
+    let mut order = Vec::new();
+    let report = queue.replay(later, true, |op| {
+        order.push(op.kind.clone());
+        Ok(())
+    });
+    assert_eq!(report.done, 2);
+    assert_eq!(order, ["first", "second"]);
+    assert_eq!(queue.status(), QueueStatus::default());
+}
+
+#[test]
+fn replay_drops_failed_operations() {
+    let now = Utc::now();
+    let mut queue = OfflineQueue::default();
+    queue.push("bad", json!(null), now);
+    queue.push("good", json!(null), now);
+    let report = queue.replay(now, true, |op| match op.kind.as_str() {
+        "bad" => Err(OpError::Failed("rejected".into())),
+        _ => Ok(()),
+    });
+    assert_eq!(report.done, 1);
+    assert_eq!(
+        report.failed,
+        vec![("bad".to_string(), "rejected".to_string())]
+    );
+    assert!(queue.ops.is_empty());
+}
+
+#[test]
+fn queue_roundtrips_through_project_file() {
+    let dir = tempdir().unwrap();
+    assert_eq!(
+        OfflineQueue::load(dir.path()).unwrap(),
+        OfflineQueue::default()
+    );
+    let mut queue = OfflineQueue::default();
+    queue.push("team_sync", json!({ "policy": "local" }), Utc::now());
+    queue.save(dir.path()).unwrap();
+    let loaded = OfflineQueue::load(dir.path()).unwrap();
+    assert_eq!(loaded, queue);
+
+    let mut loaded = loaded;
+    let id = loaded.push("other", json!(null), Utc::now());
+    assert_eq!(id, 2);
+}
+
+#[test]
+fn replay_project_drops_unknown_operations() {
+    let dir = tempdir().unwrap();
+    let mut queue = OfflineQueue::default();
+    queue.push("mystery", json!(null), Utc::now());
+    queue.save(dir.path()).unwrap();
+    let (report, status) =
+        core::offline_queue::replay_project(dir.path(), Utc::now(), true).unwrap();
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(status.pending, 0);
+    assert!(OfflineQueue::load(dir.path()).unwrap().ops.is_empty());
+}
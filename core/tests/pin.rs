@@ -0,0 +1,108 @@
+use std::collections::HashSet;
+
+use core::blocks::layout::{auto_layout, frozen};
+use core::meta::pin::{is_pinned, pinned_ids, set_pinned};
+use core::meta::{validate, VisualMeta};
+use core::{parse_blocks, upsert_meta, BlockInfo};
+use serde_json::json;
+
+fn meta(id: &str, x: f64, y: f64) -> VisualMeta {
+    serde_json::from_value(json!({"id": id, "x": x, "y": y})).unwrap()
+}
+
+fn block(id: &str, range: (usize, usize), x: f64, y: f64) -> BlockInfo {
+    BlockInfo {
+        visual_id: id.into(),
+        node_id: None,
+        kind: "Function".into(),
+        translations: Default::default(),
+        range,
+        anchors: vec![],
+        x,
+        y,
+        width: None,
+        height: None,
+        ports: vec![],
+        ai: None,
+        tags: vec![],
+        links: vec![],
+        color: None,
+        icon: None,
+        doc: None,
+    }
+}
+
+#[test]
+fn set_pinned_keeps_other_extras() {
+    let mut m = meta("a", 0.0, 0.0);
+    m.extras = Some(json!({"color": "#123456"}));
+    set_pinned(&mut m, true);
+    assert!(is_pinned(&m));
+    assert!(validate(&m).is_ok());
+    assert_eq!(
+        pinned_ids(&[m.clone(), meta("b", 0.0, 0.0)]),
+        HashSet::from(["a".to_string()])
+    );
+
+    set_pinned(&mut m, false);
+    assert_eq!(m.extras, Some(json!({"color": "#123456"})));
+    let mut m = meta("a", 0.0, 0.0);
+    set_pinned(&mut m, true);
+    set_pinned(&mut m, false);
+    assert!(m.extras.is_none());
+
+    m.extras = Some(json!({"pinned": "yes"}));
+    let errors = validate(&m).unwrap_err();
+    assert_eq!(errors[0].field, "extras.pinned");
+}
+
+#[test]
+fn regeneration_keeps_pinned_items_in_place() {
+    let src = "fn a() {}\n\nfn b() {}\n\nfn c() {}\n";
+    let functions: Vec<BlockInfo> = parse_blocks(src.into(), "rust".into())
+        .unwrap()
+        .into_iter()
+        .filter(|b| b.kind == "Function/Define")
+        .collect();
+    let id = |i: usize| functions[i].visual_id.clone();
+    // Комментарии в конце файла не сдвигают элементы, по смещениям которых
+    // вычислены идентификаторы.
+    let mut a = meta(&id(0), 0.0, 200.0);
+    set_pinned(&mut a, true);
+    let mut content = src.to_string();
+    for m in [a, meta(&id(1), 0.0, 100.0), meta(&id(2), 0.0, 50.0)] {
+        content.push_str(&format!(
+            "// @VISUAL_META {}\n",
+            serde_json::to_string(&m).unwrap()
+        ));
+    }
+    let file = "pin-test.rs".to_string();
+    let out = upsert_meta(
+        content,
+        meta(&id(2), 0.0, 0.0),
+        "rust".into(),
+        vec![file.clone()],
+    );
+    let code = &out[&file];
+    let pos = |name: &str| code.find(name).unwrap();
+    // `a` закреплён первым, остальные упорядочены по y.
+    assert!(pos("fn a") < pos("fn c"));
+    assert!(pos("fn c") < pos("fn b"));
+}
+
+#[test]
+fn auto_layout_skips_frozen_blocks_and_their_cells() {
+    let blocks = vec![
+        block("m", (0, 100), 0.0, 0.0),
+        block("inner", (10, 20), 500.0, 500.0),
+        block("x", (200, 210), 900.0, 900.0),
+        block("y", (300, 310), 900.0, 900.0),
+    ];
+    let frozen = frozen(&blocks, &HashSet::from(["m".to_string()]));
+    assert_eq!(frozen, vec![true, true, false, false]);
+    let positions = auto_layout(&blocks, &frozen, (150.0, 60.0));
+    assert_eq!(
+        positions,
+        vec![(0.0, 0.0), (500.0, 500.0), (150.0, 0.0), (0.0, 60.0)]
+    );
+}
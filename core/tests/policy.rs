@@ -0,0 +1,109 @@
+use core::config::PolicyRule;
+use core::lint::{lint_project, Severity};
+use core::policy::{check_project, Policies, PolicySummary, METRIC_LIMIT, REQUIRED_META};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const CONFIG: &str = r#"
+[[policy]]
+name = "api"
+paths = ["src/api/**"]
+public = true
+translations = ["en"]
+min_tags = 1
+severity = "error"
+"#;
+
+#[test]
+fn public_api_functions_need_translation_and_tag() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("multicode.toml"), CONFIG).unwrap();
+    fs::create_dir_all(root.join("src/api")).unwrap();
+    let api = "pub fn open() {}\nfn helper() {}\npub fn close() {}\n";
+    let blocks = core::parse_blocks(api.into(), "rust".into()).unwrap();
+    let close = blocks
+        .iter()
+        .find(|b| b.kind.starts_with("Function") && api[b.range.0..].starts_with("pub fn close"))
+        .unwrap();
+    let api = format!(
+        "{api}// @VISUAL_META {{\"id\":\"{}\",\"x\":0.0,\"y\":0.0,\"tags\":[\"io\"],\"translations\":{{\"en\":\"Close\"}}}}\n",
+        close.visual_id
+    );
+    fs::write(root.join("src/api/mod.rs"), &api).unwrap();
+    fs::write(root.join("src/main.rs"), "pub fn main() {}\n").unwrap();
+
+    let report = check_project(root);
+    assert_eq!(
+        report.policies,
+        vec![PolicySummary {
+            name: "api".into(),
+            checked: 2,
+            failed: 1,
+        }]
+    );
+    assert_eq!(report.findings.len(), 1);
+    let finding = &report.findings[0];
+    assert_eq!(finding.rule, REQUIRED_META);
+    assert_eq!(finding.severity, Severity::Error);
+    assert_eq!(finding.file, Path::new("src/api/mod.rs"));
+    assert_eq!(finding.span.unwrap().start_line, 1);
+    assert_eq!(finding.message, "api: нет записи @VISUAL_META");
+
+    let lint: Vec<_> = lint_project(root)
+        .into_iter()
+        .filter(|f| f.rule == REQUIRED_META)
+        .collect();
+    assert_eq!(lint, report.findings);
+}
+
+#[test]
+fn reports_each_missing_field() {
+    let rule = PolicyRule {
+        name: "docs".into(),
+        translations: vec!["en".into(), "ru".into()],
+        min_tags: 2,
+        description: true,
+        ..Default::default()
+    };
+    assert_eq!(rule.kinds, vec!["Function"]);
+    assert_eq!(rule.severity, Severity::Warning);
+    let policies = Policies::new(Path::new("/"), vec![rule]);
+    let content = "def _hidden():\n    pass\n";
+    let blocks = core::parse_blocks(content.into(), "python".into()).unwrap();
+    let id = &blocks
+        .iter()
+        .find(|b| b.kind.starts_with("Function"))
+        .unwrap()
+        .visual_id;
+    let content = format!(
+        "{content}# @VISUAL_META {{\"id\":\"{id}\",\"x\":0.0,\"y\":0.0,\"tags\":[\"a\"],\"translations\":{{\"ru\":\"Скрытая\"}}}}\n"
+    );
+    let findings = policies.check_content(Path::new("a.py"), &content);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(
+        findings[0].message,
+        "docs: нет перевода en; тегов 1, нужно не меньше 2; нет описания ai.description"
+    );
+    assert_eq!(findings[0].severity, Severity::Warning);
+}
+
+#[test]
+fn metric_limits_do_not_require_meta() {
+    let rule = PolicyRule {
+        name: "simple".into(),
+        metrics: vec!["complexity > 2".into(), "params >= 3".into()],
+        ..Default::default()
+    };
+    let policies = Policies::new(Path::new("/"), vec![rule]);
+    let content = "def small(a):\n    return a\n\n\ndef branchy(a, b, c):\n    if a:\n        return b\n    elif b:\n        return c\n    return a\n";
+    let findings = policies.check_content(Path::new("a.py"), content);
+    assert_eq!(findings.len(), 1);
+    assert_eq!(findings[0].rule, METRIC_LIMIT);
+    assert_eq!(findings[0].span.unwrap().start_line, 5);
+    assert_eq!(
+        findings[0].message,
+        "simple: complexity 3, допустимо не больше 2; params 3, допустимо не больше 2"
+    );
+}
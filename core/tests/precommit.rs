@@ -0,0 +1,93 @@
+#![cfg(feature = "git")]
+use core::config::PrecommitConfig;
+use core::lint::{META_DUPLICATE_ID, META_INVALID_FIELD, META_INVALID_JSON};
+use core::meta::duplicate_ids;
+use core::precommit::{install_hook, staged_sources, HOOK_MARKER};
+use git2::Repository;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+#[test]
+fn staged_sources_read_index_content() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let repo = Repository::init(root).unwrap();
+    fs::create_dir(root.join("src")).unwrap();
+    fs::write(root.join("src/a.rs"), "fn staged() {}\n").unwrap();
+    fs::write(root.join("notes.txt"), "text\n").unwrap();
+    fs::write(root.join("b.py"), "x = 1\n").unwrap();
+    let mut index = repo.index().unwrap();
+    for file in ["src/a.rs", "notes.txt"] {
+        index.add_path(Path::new(file)).unwrap();
+    }
+    index.write().unwrap();
+    fs::write(root.join("src/a.rs"), "fn unstaged() {}\n").unwrap();
+
+    let files = staged_sources(root).unwrap();
+    assert_eq!(
+        files,
+        [(PathBuf::from("src/a.rs"), "fn staged() {}\n".to_string())]
+    );
+}
+
+#[test]
+fn duplicate_ids_are_listed_once() {
+    let meta = |id: &str| format!("# @VISUAL_META {{\"id\":\"{id}\",\"x\":0.0,\"y\":0.0}}\n");
+    let content = ["a", "b", "a", "a"].map(meta).concat();
+    assert_eq!(duplicate_ids(&content), ["a"]);
+    assert!(duplicate_ids(&meta("a")).is_empty());
+}
+
+#[test]
+fn blocking_rules_come_from_project_config() {
+    let dir = tempdir().unwrap();
+    let config = PrecommitConfig::load(dir.path());
+    assert!(config.is_blocking(META_DUPLICATE_ID));
+    assert!(config.is_blocking(META_INVALID_JSON));
+    assert!(!config.is_blocking(META_INVALID_FIELD));
+
+    fs::write(
+        dir.path().join("multicode.toml"),
+        "[precommit]\nblocking = [\"meta/invalid-field\"]\n",
+    )
+    .unwrap();
+    let config = PrecommitConfig::load(dir.path());
+    assert!(config.is_blocking(META_INVALID_FIELD));
+    assert!(!config.is_blocking(META_DUPLICATE_ID));
+}
+
+#[test]
+fn hook_replaces_only_own_script_without_force() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    Repository::init(root).unwrap();
+    let path = install_hook(root, false).unwrap();
+    assert_eq!(path, root.join(".git/hooks/pre-commit"));
+    let script = fs::read_to_string(&path).unwrap();
+    assert!(script.contains(HOOK_MARKER));
+    assert!(script.contains("meta fix --check --staged"));
+    assert!(script.contains("lint --staged"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o111,
+            0o111
+        );
+    }
+
+    // Повторная установка обновляет собственный хук.
+    install_hook(root, false).unwrap();
+
+    fs::write(&path, "#!/bin/sh\nmake check\n").unwrap();
+    let err = install_hook(root, false).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "#!/bin/sh\nmake check\n"
+    );
+    install_hook(root, true).unwrap();
+    assert!(fs::read_to_string(&path).unwrap().contains(HOOK_MARKER));
+}
@@ -0,0 +1,16 @@
+use core::blocks::{parse_blocks, profile::parse_costs};
+use core::parser::Lang;
+use std::time::Duration;
+
+#[test]
+fn measures_each_block_separately() {
+    let content = "fn a() {\n    let x = 1;\n}\n";
+    let mut blocks = parse_blocks(content.into(), "rust".into()).unwrap();
+    let costs = parse_costs(content, Lang::Rust, &blocks);
+    assert_eq!(costs.len(), blocks.len());
+    assert!(costs.iter().any(|c| *c > Duration::ZERO));
+
+    // У блока без текста разбирать нечего.
+    blocks[0].range = (0, 0);
+    assert_eq!(parse_costs(content, Lang::Rust, &blocks)[0], Duration::ZERO);
+}
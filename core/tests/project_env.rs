@@ -0,0 +1,115 @@
+use core::project_env::{self, EnvVar, ProjectEnv, SecretStore, ENV_FILE};
+use core::runconfig::RunConfig;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::io;
+use std::path::Path;
+use tempfile::tempdir;
+
+/// Хранилище секретов в памяти.
+#[derive(Default)]
+struct MemoryStore(RefCell<HashMap<String, String>>);
+
+impl SecretStore for MemoryStore {
+    fn get(&self, _root: &Path, key: &str) -> io::Result<Option<String>> {
+        Ok(self.0.borrow().get(key).cloned())
+    }
+
+    fn set(&self, _root: &Path, key: &str, value: &str) -> io::Result<()> {
+        self.0.borrow_mut().insert(key.into(), value.into());
+        Ok(())
+    }
+
+    fn delete(&self, _root: &Path, key: &str) -> io::Result<()> {
+        self.0.borrow_mut().remove(key);
+        Ok(())
+    }
+}
+
+#[test]
+fn parses_dotenv_lines() {
+    let env = ProjectEnv::parse(
+        "# comment\n\
+         export RUST_LOG=debug\n\
+         GREETING=\"hello \\\"world\\\"\\n\"\n\
+         RAW='a # b'\n\
+         PLAIN=value # trailing\n\
+         API_TOKEN=@keychain\n\
+         not a var\n\
+         RUST_LOG=info\n",
+    );
+    assert_eq!(
+        env.vars,
+        vec![
+            EnvVar::new("RUST_LOG", "info"),
+            EnvVar::new("GREETING", "hello \"world\"\n"),
+            EnvVar::new("RAW", "a # b"),
+            EnvVar::new("PLAIN", "value"),
+            EnvVar::secret("API_TOKEN"),
+        ]
+    );
+}
+
+#[test]
+fn save_round_trips_without_secret_values() {
+    let dir = tempdir().unwrap();
+    assert_eq!(ProjectEnv::load(dir.path()).unwrap(), ProjectEnv::default());
+
+    let mut env = ProjectEnv::default();
+    env.set(EnvVar::new("A", "1"));
+    env.set(EnvVar::new("B", " spaced # value "));
+    env.set(EnvVar::new("C", "@keychain"));
+    env.set(EnvVar::secret("TOKEN"));
+    env.save(dir.path()).unwrap();
+
+    let text = fs::read_to_string(dir.path().join(ENV_FILE)).unwrap();
+    assert!(text.contains("TOKEN=@keychain\n"));
+    assert!(text.contains("C=\"@keychain\"\n"));
+    assert_eq!(ProjectEnv::load(dir.path()).unwrap(), env);
+
+    assert_eq!(env.remove("A"), Some(EnvVar::new("A", "1")));
+    assert_eq!(env.remove("A"), None);
+}
+
+#[test]
+fn resolve_reads_secrets_from_store() {
+    let dir = tempdir().unwrap();
+    let store = MemoryStore::default();
+    store.set(dir.path(), "TOKEN", "s3cr3t").unwrap();
+    let env = ProjectEnv {
+        vars: vec![
+            EnvVar::new("A", "1"),
+            EnvVar::secret("TOKEN"),
+            EnvVar::secret("MISSING"),
+        ],
+    };
+    let (values, missing) = env.resolve(dir.path(), &store);
+    assert_eq!(values.get("A").map(String::as_str), Some("1"));
+    assert_eq!(values.get("TOKEN").map(String::as_str), Some("s3cr3t"));
+    assert!(!values.contains_key("MISSING"));
+    assert_eq!(missing.len(), 1);
+    assert_eq!(missing[0].0, "MISSING");
+}
+
+#[test]
+fn run_config_gets_project_vars_below_its_own() {
+    let dir = tempdir().unwrap();
+    fs::create_dir_all(dir.path().join(".multicode")).unwrap();
+    fs::write(dir.path().join(ENV_FILE), "SHARED=project\nONLY=project\n").unwrap();
+    assert_eq!(project_env::vars(dir.path()).len(), 2);
+
+    let mut config = RunConfig::new("true");
+    config.env.insert("SHARED".into(), "config".into());
+    let cmd = config.command(dir.path());
+    let envs: HashMap<&OsStr, Option<&OsStr>> = cmd.get_envs().collect();
+    assert_eq!(envs[OsStr::new("ONLY")], Some(OsStr::new("project")));
+    // Позднее значение переопределяет раннее.
+    let shared: Vec<_> = cmd
+        .get_envs()
+        .filter(|(k, _)| *k == "SHARED")
+        .map(|(_, v)| v)
+        .collect();
+    assert_eq!(shared.last().copied().flatten(), Some(OsStr::new("config")));
+}
@@ -0,0 +1,88 @@
+use chrono::{TimeZone, Utc};
+use core::parse_blocks;
+use core::recent_blocks::{edited_block, RecentBlock, RecentBlocks, RECENT_BLOCKS_LIMIT};
+use std::path::{Path, PathBuf};
+use tempfile::tempdir;
+
+const SRC: &str = "struct Point;\n\nfn distance() {\n    let d = 1;\n}\n\nconst LIMIT: u32 = 10;\n";
+
+fn entry(file: &str, label: &str, minute: u32) -> RecentBlock {
+    RecentBlock {
+        visual_id: format!("{label}-{minute}"),
+        file: PathBuf::from(file),
+        label: label.into(),
+        line: 0,
+        edited_at: Utc.with_ymd_and_hms(2024, 1, 1, 12, minute, 0).unwrap(),
+    }
+}
+
+#[test]
+fn edit_inside_function_records_the_function() {
+    let blocks = parse_blocks(SRC.into(), "rust".into()).unwrap();
+    let at = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+    let offset = SRC.find("let d").unwrap();
+    let block = edited_block(Path::new("src/a.rs"), SRC, &blocks, offset, at).unwrap();
+    assert_eq!(block.label, "distance");
+    assert_eq!(block.line, 2);
+    assert_eq!(block.file, PathBuf::from("src/a.rs"));
+    assert_eq!(block.edited_at, at);
+    let function = blocks
+        .iter()
+        .find(|b| b.visual_id == block.visual_id)
+        .unwrap();
+    assert_eq!(function.kind, "Function/Define");
+
+    let offset = SRC.find("Point").unwrap();
+    let block = edited_block(Path::new("src/a.rs"), SRC, &blocks, offset, at).unwrap();
+    assert_eq!(block.label, "Point");
+
+    let offset = SRC.find("10").unwrap();
+    let block = edited_block(Path::new("src/a.rs"), SRC, &blocks, offset, at).unwrap();
+    assert_eq!(block.label, "const LIMIT: u32 = 10;");
+
+    let offset = SRC.find("\n\nfn").unwrap() + 1;
+    assert_eq!(
+        edited_block(Path::new("src/a.rs"), SRC, &blocks, offset, at),
+        None
+    );
+}
+
+#[test]
+fn record_moves_block_to_front_and_keeps_limit() {
+    let mut recent = RecentBlocks::default();
+    assert!(recent.record(entry("a.rs", "load", 0)));
+    assert!(recent.record(entry("b.rs", "save", 1)));
+    // Та же функция с новым идентификатором поднимается наверх.
+    assert!(recent.record(entry("a.rs", "load", 2)));
+    let labels: Vec<&str> = recent.entries.iter().map(|e| e.label.as_str()).collect();
+    assert_eq!(labels, ["load", "save"]);
+    assert_eq!(recent.entries[0].visual_id, "load-2");
+
+    // Повторная правка первого блока порядок не меняет.
+    assert!(!recent.record(entry("a.rs", "load", 3)));
+    assert_eq!(recent.entries[0].visual_id, "load-3");
+
+    for i in 0..RECENT_BLOCKS_LIMIT {
+        recent.record(entry("c.rs", &format!("f{i}"), 4));
+    }
+    assert_eq!(recent.entries.len(), RECENT_BLOCKS_LIMIT);
+    assert!(recent.entries.iter().all(|e| e.file == Path::new("c.rs")));
+
+    assert!(recent.forget_file(Path::new("c.rs")));
+    assert!(recent.entries.is_empty());
+}
+
+#[test]
+fn list_round_trips_through_project_file() {
+    let dir = tempdir().unwrap();
+    assert_eq!(
+        RecentBlocks::load(dir.path()).unwrap(),
+        RecentBlocks::default()
+    );
+
+    let mut recent = RecentBlocks::default();
+    recent.record(entry("src/a.rs", "load", 0));
+    recent.record(entry("src/b.rs", "save", 5));
+    recent.save(dir.path()).unwrap();
+    assert_eq!(RecentBlocks::load(dir.path()).unwrap(), recent);
+}
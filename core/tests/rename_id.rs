@@ -0,0 +1,74 @@
+use core::meta::backup::list_backups;
+use core::meta::rename::replace_id;
+use core::meta::{read_all, rename_id};
+use std::fs;
+use std::io::ErrorKind;
+use tempfile::tempdir;
+
+#[test]
+fn renames_id_and_repairs_references_across_files() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let a = root.join("a.rs");
+    let b = root.join("b.py");
+    let c = root.join("c.rs");
+    fs::write(
+        &a,
+        "fn a() {}\n// @VISUAL_META {\"id\":\"old\",\"x\":0.0,\"y\":0.0,\"tags\":[\"net\"]}\n",
+    )
+    .unwrap();
+    fs::write(
+        &b,
+        "def b(): pass\n# @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0,\"links\":[\"x\",\"old\"],\"extends\":\"old\"}\n",
+    )
+    .unwrap();
+    let untouched = "fn c() {}\n// @VISUAL_META {\"id\":\"c\",\"x\":0.0,\"y\":0.0}\n";
+    fs::write(&c, untouched).unwrap();
+
+    let changed = rename_id(root, "old", "connect").unwrap();
+    assert_eq!(changed, vec![a.clone(), b.clone()]);
+
+    let a_content = fs::read_to_string(&a).unwrap();
+    assert!(a_content.starts_with("fn a() {}\n// @VISUAL_META {"));
+    let renamed = &read_all(&a_content)[0];
+    assert_eq!(renamed.id, "connect");
+    assert_eq!(renamed.tags, vec!["net"]);
+
+    let b_content = fs::read_to_string(&b).unwrap();
+    assert!(b_content.contains("# @VISUAL_META {"));
+    let linked = &read_all(&b_content)[0];
+    assert_eq!(linked.links, vec!["x", "connect"]);
+    // `read_all` разрешает наследование и сбрасывает `extends`.
+    assert!(b_content.contains("\"extends\":\"connect\""));
+
+    assert_eq!(fs::read_to_string(&c).unwrap(), untouched);
+    assert_eq!(list_backups(root).unwrap()[0].files.len(), 2);
+}
+
+#[test]
+fn rejects_missing_taken_and_empty_ids() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let content = "fn a() {}\n// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0}\n// @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0}\n";
+    fs::write(root.join("a.rs"), content).unwrap();
+
+    let kind = |old, new| rename_id(root, old, new).unwrap_err().kind();
+    assert_eq!(kind("missing", "c"), ErrorKind::NotFound);
+    assert_eq!(kind("a", "b"), ErrorKind::AlreadyExists);
+    assert_eq!(kind("a", " "), ErrorKind::InvalidInput);
+    assert!(rename_id(root, "a", "a").unwrap().is_empty());
+    assert_eq!(fs::read_to_string(root.join("a.rs")).unwrap(), content);
+    assert!(list_backups(root).unwrap().is_empty());
+}
+
+#[test]
+fn replace_keeps_compressed_encoding() {
+    let json = "{\"id\":\"b\",\"x\":0.0,\"y\":0.0,\"links\":[\"old\"]}";
+    let compressed = core::meta::convert_encoding(
+        &format!("// @VISUAL_META {json}\n"),
+        core::meta::MetaEncoding::Zstd,
+    );
+    let updated = replace_id(&compressed, "old", "new");
+    assert!(updated.starts_with("// @VISUAL_META:zstd "));
+    assert_eq!(read_all(&updated)[0].links, vec!["new"]);
+}
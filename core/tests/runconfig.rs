@@ -0,0 +1,73 @@
+use core::meta::{read_all, validate, VisualMeta};
+use core::runconfig::{self, RunConfig};
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+fn meta(extras: serde_json::Value) -> VisualMeta {
+    serde_json::from_value(json!({"id": "1", "x": 0.0, "y": 0.0, "extras": extras})).unwrap()
+}
+
+#[test]
+fn resolves_single_and_listed_configs() {
+    let single = meta(json!({"run": {"command": "cargo test", "cwd": "core"}}));
+    let configs = runconfig::resolve(&single);
+    assert_eq!(configs.len(), 1);
+    assert_eq!(configs[0].label(), "cargo test");
+    assert_eq!(configs[0].cwd.as_deref(), Some("core".as_ref()));
+
+    let listed = meta(json!({"run": [
+        {"name": "build", "command": "cargo build"},
+        {"name": "bench", "command": "cargo bench", "env": {"RUST_LOG": "info"}}
+    ]}));
+    let configs = runconfig::resolve(&listed);
+    assert_eq!(
+        runconfig::find(&configs, None).map(RunConfig::label),
+        Some("build")
+    );
+    let bench = runconfig::find(&configs, Some("bench")).unwrap();
+    assert_eq!(bench.env.get("RUST_LOG").map(String::as_str), Some("info"));
+    assert!(runconfig::find(&configs, Some("missing")).is_none());
+
+    assert!(runconfig::resolve(&meta(json!({"color": "#fff"}))).is_empty());
+}
+
+#[test]
+fn invalid_configs_are_reported() {
+    let wrong = meta(json!({"run": "cargo test"}));
+    assert!(runconfig::resolve(&wrong).is_empty());
+    let errors = validate(&wrong).unwrap_err();
+    assert_eq!(errors[0].field, "extras.run");
+
+    let empty = meta(json!({"run": [{"command": " "}]}));
+    let errors = validate(&empty).unwrap_err();
+    assert!(errors[0].message.contains("пустой"));
+
+    assert!(validate(&meta(json!({"run": {"command": "make"}}))).is_ok());
+}
+
+#[test]
+fn configs_are_inherited_through_extends() {
+    let content = format!(
+        "// @VISUAL_META {}\n// @VISUAL_META {}\n",
+        json!({"id": "run-base", "x": 0, "y": 0, "extras": {"run": {"command": "make"}}}),
+        json!({"id": "run-child", "x": 0, "y": 0, "extends": "run-base"}),
+    );
+    let metas = read_all(&content);
+    let child = metas.iter().find(|m| m.id == "run-child").unwrap();
+    assert_eq!(runconfig::resolve(child), vec![RunConfig::new("make")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn command_runs_in_cwd_with_env() {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("sub")).unwrap();
+    let mut config = RunConfig::new("echo \"$GREETING\" > out.txt");
+    config.cwd = Some("sub".into());
+    config.env.insert("GREETING".into(), "привет".into());
+    let status = config.command(dir.path()).status().unwrap();
+    assert!(status.success());
+    let out = fs::read_to_string(dir.path().join("sub/out.txt")).unwrap();
+    assert_eq!(out.trim(), "привет");
+}
@@ -0,0 +1,39 @@
+use core::saved_search::{self, SavedSearch, SAVED_SEARCHES_FILE};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn query_round_trips_through_parse() {
+    let search = SavedSearch::parse("net", "open socket tag:net kind:Function tag:io");
+    assert_eq!(search.text, "open socket");
+    assert_eq!(search.tags, vec!["net", "io"]);
+    assert_eq!(search.kinds, vec!["Function"]);
+    assert_eq!(SavedSearch::parse("net", &search.query()), search);
+    assert!(SavedSearch::parse("empty", "  ").is_empty());
+}
+
+#[test]
+fn searches_are_saved_and_run() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("a.rs"), "fn connect() {}\nfn other() {}\n").unwrap();
+
+    assert!(saved_search::load(root).unwrap().is_empty());
+    saved_search::save(root, SavedSearch::parse("conn", "connect kind:Function")).unwrap();
+    saved_search::save(root, SavedSearch::parse("tagged", "tag:net")).unwrap();
+    let searches =
+        saved_search::save(root, SavedSearch::parse("conn", "fn connect kind:Function")).unwrap();
+    assert_eq!(searches.len(), 2);
+    let data = fs::read_to_string(root.join(SAVED_SEARCHES_FILE)).unwrap();
+    assert!(data.contains("[[search]]"));
+
+    let search = saved_search::find(root, "conn").unwrap().unwrap();
+    let hits = search.run(root);
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].line, 0);
+    assert!(saved_search::find(root, "tagged").unwrap().unwrap().run(root).is_empty());
+
+    let searches = saved_search::remove(root, "tagged").unwrap();
+    assert_eq!(searches.len(), 1);
+    assert!(saved_search::save(root, SavedSearch::parse(" ", "x")).is_err());
+}
@@ -0,0 +1,43 @@
+use core::meta::{schema, VisualMeta};
+use core::{block_schema, parse_blocks};
+use serde_json::Value;
+
+fn properties(schema: &Value) -> Vec<&str> {
+    schema["properties"]
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(String::as_str)
+        .collect()
+}
+
+#[test]
+fn visual_meta_schema_describes_payload() {
+    let schema = serde_json::to_value(schema()).unwrap();
+    assert_eq!(schema["title"], "VisualMeta");
+    assert_eq!(schema["required"], serde_json::json!(["id", "x", "y"]));
+
+    let meta: VisualMeta = serde_json::from_value(serde_json::json!({
+        "id": "a", "x": 1.0, "y": 2.0, "tags": ["net"]
+    }))
+    .unwrap();
+    let payload = serde_json::to_value(&meta).unwrap();
+    let props = properties(&schema);
+    for key in payload.as_object().unwrap().keys() {
+        assert!(props.contains(&key.as_str()), "{key} missing from schema");
+    }
+    assert!(schema["definitions"]["AiNote"].is_object());
+}
+
+#[test]
+fn block_schema_covers_serialized_blocks() {
+    let schema = serde_json::to_value(block_schema()).unwrap();
+    assert_eq!(schema["title"], "BlockInfo");
+    let blocks = parse_blocks("fn main() {}\n".into(), "rust".into()).unwrap();
+    let block = serde_json::to_value(&blocks[0]).unwrap();
+    let props = properties(&schema);
+    for key in block.as_object().unwrap().keys() {
+        assert!(props.contains(&key.as_str()), "{key} missing from schema");
+    }
+    assert!(schema["definitions"]["Port"].is_object());
+}
@@ -0,0 +1,168 @@
+#![cfg(feature = "embeddings")]
+use core::config::EmbeddingsConfig;
+use core::offline_queue::OpError;
+use core::search::semantic::{self, cosine, documents, Embedder};
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::tempdir;
+
+/// Вектор — число вхождений слов-признаков. Считает построенные векторы.
+#[derive(Default)]
+struct Words {
+    embedded: AtomicUsize,
+}
+
+const FEATURES: &[&str] = &["connect", "socket", "parse", "json", "sum"];
+
+impl Embedder for Words {
+    fn model(&self) -> &str {
+        "words"
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, OpError> {
+        self.embedded.fetch_add(texts.len(), Ordering::SeqCst);
+        Ok(texts
+            .iter()
+            .map(|t| {
+                let t = t.to_lowercase();
+                FEATURES
+                    .iter()
+                    .map(|f| t.matches(f).count() as f32)
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+/// Общий доступ к [`Words`] из теста и из индекса.
+struct Shared(Arc<Words>);
+
+impl Embedder for Shared {
+    fn model(&self) -> &str {
+        self.0.model()
+    }
+
+    fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, OpError> {
+        self.0.embed(texts)
+    }
+}
+
+const SOURCE: &str =
+    "fn open_socket() {\n    connect();\n}\n\nfn read_config() {\n    parse_json();\n}\n";
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Runtime::new().unwrap()
+}
+
+#[test]
+fn documents_cover_function_definitions() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), SOURCE).unwrap();
+    let docs = documents(dir.path());
+    let labels: Vec<_> = docs.iter().map(|d| d.label.as_str()).collect();
+    assert_eq!(labels, vec!["fn open_socket() {", "fn read_config() {"]);
+    assert_eq!(docs[1].line, 4);
+    assert!(docs[0].text.contains("connect();"));
+    assert_ne!(docs[0].hash, docs[1].hash);
+}
+
+#[test]
+fn cosine_of_parallel_and_zero_vectors() {
+    assert!((cosine(&[1.0, 2.0], &[2.0, 4.0]) - 1.0).abs() < 1e-6);
+    assert_eq!(cosine(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+    assert_eq!(cosine(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    assert_eq!(cosine(&[1.0], &[1.0, 1.0]), 0.0);
+}
+
+#[test]
+fn semantic_search_ranks_closest_block_first() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), SOURCE).unwrap();
+    let words = Arc::new(Words::default());
+    let embedder: Arc<dyn Embedder> = Arc::new(Shared(Arc::clone(&words)));
+    runtime().block_on(async {
+        let pool = semantic::open(dir.path(), &EmbeddingsConfig::default())
+            .await
+            .unwrap();
+        let report = semantic::index(dir.path(), &pool, Arc::clone(&embedder), 1)
+            .await
+            .unwrap();
+        assert_eq!(report.embedded, 2);
+
+        let hits = semantic::semantic(&pool, Arc::clone(&embedder), "where is json parsed", 1)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].label, "fn read_config() {");
+        assert_eq!(hits[0].file, std::path::Path::new("main.rs"));
+        assert_eq!(hits[0].line, 4);
+
+        let hits = semantic::semantic(&pool, embedder, "socket connect", 5)
+            .await
+            .unwrap();
+        assert_eq!(hits[0].label, "fn open_socket() {");
+        assert!(hits[0].score > hits[1].score);
+    });
+    assert!(dir.path().join(".multicode/meta.db").exists());
+}
+
+#[test]
+fn index_embeds_only_changed_blocks_and_drops_removed() {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("main.rs");
+    fs::write(&file, SOURCE).unwrap();
+    let words = Arc::new(Words::default());
+    let embedder: Arc<dyn Embedder> = Arc::new(Shared(Arc::clone(&words)));
+    runtime().block_on(async {
+        let pool = semantic::open(dir.path(), &EmbeddingsConfig::default())
+            .await
+            .unwrap();
+        semantic::index(dir.path(), &pool, Arc::clone(&embedder), 32)
+            .await
+            .unwrap();
+        assert_eq!(words.embedded.load(Ordering::SeqCst), 2);
+
+        let report = semantic::index(dir.path(), &pool, Arc::clone(&embedder), 32)
+            .await
+            .unwrap();
+        assert_eq!(report.unchanged, 2);
+        assert_eq!(report.embedded, 0);
+        assert_eq!(words.embedded.load(Ordering::SeqCst), 2);
+
+        fs::write(&file, "fn open_socket() {\n    connect(socket);\n}\n").unwrap();
+        let report = semantic::index(dir.path(), &pool, Arc::clone(&embedder), 32)
+            .await
+            .unwrap();
+        assert_eq!(report.embedded, 1);
+        assert_eq!(report.removed, 1);
+        let hits = semantic::semantic(&pool, embedder, "parse json", 5)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+    });
+}
+
+#[test]
+fn embedder_errors_are_reported() {
+    struct Down;
+    impl Embedder for Down {
+        fn model(&self) -> &str {
+            "down"
+        }
+        fn embed(&self, _: &[String]) -> Result<Vec<Vec<f32>>, OpError> {
+            Err(OpError::Offline("connection refused".into()))
+        }
+    }
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("main.rs"), SOURCE).unwrap();
+    runtime().block_on(async {
+        let pool = semantic::open(dir.path(), &EmbeddingsConfig::default())
+            .await
+            .unwrap();
+        let err = semantic::index(dir.path(), &pool, Arc::new(Down), 32)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "connection refused");
+    });
+}
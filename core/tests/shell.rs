@@ -0,0 +1,69 @@
+use core::shell::{Shell, ShellKind};
+use std::path::Path;
+
+#[test]
+fn detects_shell_kind_by_program_name() {
+    assert_eq!(Shell::new("sh").kind, ShellKind::Posix);
+    assert_eq!(Shell::new("/usr/bin/bash").kind, ShellKind::Posix);
+    assert_eq!(
+        Shell::new(r"C:\Windows\System32\cmd.exe").kind,
+        ShellKind::Cmd
+    );
+    assert_eq!(Shell::new("CMD.EXE").kind, ShellKind::Cmd);
+    assert_eq!(Shell::new("powershell.exe").kind, ShellKind::PowerShell);
+    assert_eq!(Shell::new("pwsh").kind, ShellKind::PowerShell);
+    if cfg!(windows) {
+        assert_ne!(Shell::system().kind, ShellKind::Posix);
+    } else {
+        assert_eq!(Shell::system(), Shell::new("sh"));
+    }
+}
+
+#[test]
+fn passes_command_line_in_shell_specific_way() {
+    assert_eq!(Shell::new("sh").args("ls"), vec!["-c", "ls"]);
+    assert_eq!(
+        Shell::new("cmd.exe").args("dir"),
+        vec!["/D", "/S", "/C", "dir"]
+    );
+    assert_eq!(
+        Shell::new("pwsh").args("ls"),
+        vec!["-NoProfile", "-NonInteractive", "-Command", "ls"]
+    );
+}
+
+#[test]
+fn quotes_arguments_for_each_shell() {
+    let sh = Shell::new("sh");
+    assert_eq!(sh.quote("src/main.rs"), "src/main.rs");
+    assert_eq!(sh.quote("my file.rs"), "'my file.rs'");
+    assert_eq!(sh.quote("it's"), r"'it'\''s'");
+    assert_eq!(sh.quote(""), "''");
+
+    let cmd = Shell::new("cmd.exe");
+    assert_eq!(cmd.quote(r"C:\src\main.rs"), r"C:\src\main.rs");
+    assert_eq!(
+        cmd.quote(r"C:\Program Files\a.rs"),
+        r#""C:\Program Files\a.rs""#
+    );
+    assert_eq!(cmd.quote("a&b"), r#""a&b""#);
+    assert_eq!(cmd.quote(r#"say "hi""#), r#""say ""hi""""#);
+
+    let ps = Shell::new("pwsh");
+    assert_eq!(ps.quote(r"C:\src\main.rs"), r"C:\src\main.rs");
+    assert_eq!(ps.quote("a,b"), "'a,b'");
+    assert_eq!(ps.quote("it's"), "'it''s'");
+}
+
+#[cfg(unix)]
+#[test]
+fn quoted_path_survives_posix_shell() {
+    let sh = Shell::new("sh");
+    let path = Path::new("dir with spaces/it's $HOME;.rs");
+    let line = format!("printf %s {}", sh.quote_path(path));
+    let output = sh.command(&line).output().unwrap();
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        path.to_string_lossy()
+    );
+}
@@ -0,0 +1,101 @@
+use core::meta::backup::restore;
+use core::search::replace;
+use core::search::structural::{structural, Pattern, StructuralError};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+const LIB: &str =
+    "fn run(a: i32) -> i32 {\n    let x = add(a, 1);\n    let y = add(x, 2, 3);\n    \
+let z = z;\n    // заметка\n    if a == a { return 0; }\n    x + y\n}\n\n\
+fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n";
+
+#[test]
+fn binds_single_and_sequence_metavariables() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("lib.rs"), LIB).unwrap();
+    fs::write(dir.path().join("other.py"), "add(1, 2)\n").unwrap();
+
+    let found = structural(dir.path(), "add($A, $$$REST)", "rust").unwrap();
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].line, 1);
+    assert_eq!(found[0].text, "add(a, 1)");
+    assert_eq!(found[0].bindings["A"], "a");
+    assert_eq!(found[0].bindings["REST"], "1");
+    assert_eq!(found[1].bindings["REST"], "2, 3");
+    assert_eq!(found[0].block.range, found[0].range);
+    assert!(found.iter().all(|m| m.file.ends_with("lib.rs")));
+}
+
+#[test]
+fn repeated_metavariable_requires_same_text() {
+    let pattern = Pattern::parse("let $X = $X;", "rust").unwrap();
+    let found = pattern.find(LIB);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].1["X"], "z");
+
+    let pattern = Pattern::parse("if $A == $A { $$$ }", "rust").unwrap();
+    assert_eq!(pattern.find(LIB).len(), 1);
+}
+
+#[test]
+fn ignores_whitespace_and_comments() {
+    let pattern = Pattern::parse("fn $NAME($$$ARGS) -> i32 { $$$BODY }", "rust").unwrap();
+    let names: Vec<_> = pattern
+        .find(LIB)
+        .into_iter()
+        .map(|(_, b)| b["NAME"].clone())
+        .collect();
+    assert_eq!(names, vec!["run", "add"]);
+
+    let pattern = Pattern::parse("print($A)", "python").unwrap();
+    assert_eq!(pattern.find("print( x )\nprint(x, y)\n").len(), 1);
+}
+
+#[test]
+fn reports_bad_patterns() {
+    assert!(matches!(
+        Pattern::parse("foo(", "rust"),
+        Err(StructuralError::InvalidPattern(_))
+    ));
+    assert!(matches!(
+        Pattern::parse("foo()", "cobol"),
+        Err(StructuralError::UnknownLanguage(_))
+    ));
+}
+
+#[test]
+fn replaces_with_bindings_and_previews_diff() {
+    let dir = tempdir().unwrap();
+    let file = dir.path().join("lib.rs");
+    fs::write(&file, LIB).unwrap();
+
+    let preview = replace::preview(dir.path(), "add($A, $B)", "add($B, $A)", "rust").unwrap();
+    assert_eq!(preview.len(), 1);
+    let r = &preview[0];
+    assert_eq!(r.edits.len(), 1);
+    assert_eq!(
+        r.diff(),
+        "@@ 2 @@\n-    let x = add(a, 1);\n+    let x = add(1, a);\n"
+    );
+    assert_eq!(fs::read_to_string(&file).unwrap(), LIB);
+
+    let id = replace::apply(dir.path(), &preview).unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), r.replaced);
+    restore(dir.path(), &id, None).unwrap();
+    assert_eq!(fs::read_to_string(&file).unwrap(), LIB);
+}
+
+#[test]
+fn rejects_replacements_that_break_parsing() {
+    let pattern = Pattern::parse("add($A, $B)", "rust").unwrap();
+    let r = replace::rewrite(Path::new("lib.rs"), LIB, &pattern, "add($A, $B");
+    assert!(r.edits.is_empty());
+    assert_eq!(r.rejected.len(), 1);
+    assert_eq!(r.replaced, LIB);
+
+    assert_eq!(
+        replace::preview(Path::new("."), "add($A, $B)", "add($C)", "rust"),
+        Err(StructuralError::UnknownVariable("$C".into()))
+    );
+}
@@ -0,0 +1,121 @@
+use core::meta::read_all;
+use core::meta::suggest::{self, name_words, MetaSuggestion, SuggestionKind};
+use core::parse_blocks;
+use std::fs;
+use tempfile::tempdir;
+
+const NET: &str = "import json\nfrom http.client import HTTPConnection\n\n\
+def send_request(url):\n    conn = HTTPConnection(url)\n    return encode_body(conn)\n\n\
+def encode_body(data):\n    return json.dumps(data)\n";
+
+fn kinds(suggestions: &[MetaSuggestion], name: &str) -> Vec<SuggestionKind> {
+    suggestions
+        .iter()
+        .filter(|s| s.name == name)
+        .map(|s| s.kind.clone())
+        .collect()
+}
+
+#[test]
+fn splits_snake_and_camel_case() {
+    assert_eq!(name_words("readHttpConfig"), vec!["read", "http", "config"]);
+    assert_eq!(name_words("send_request2"), vec!["send", "request2"]);
+}
+
+#[test]
+fn suggests_tags_from_names_and_imports_and_links_from_calls() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("net.py"), NET).unwrap();
+    let suggestions = suggest::suggest(dir.path());
+
+    let encode_id = suggestions
+        .iter()
+        .find(|s| s.name == "encode_body")
+        .unwrap()
+        .id
+        .clone();
+    assert_eq!(
+        kinds(&suggestions, "send_request"),
+        vec![
+            SuggestionKind::NameTag("send".into()),
+            SuggestionKind::NameTag("request".into()),
+            SuggestionKind::ImportTag("http".into()),
+            SuggestionKind::Call {
+                target: encode_id,
+                name: "encode_body".into()
+            },
+        ]
+    );
+    assert_eq!(
+        kinds(&suggestions, "encode_body"),
+        vec![
+            SuggestionKind::NameTag("encode".into()),
+            SuggestionKind::NameTag("body".into()),
+            SuggestionKind::ImportTag("json".into()),
+        ]
+    );
+    assert_eq!(suggestions[0].line, 3);
+}
+
+#[test]
+fn accepted_suggestions_stick_to_their_blocks() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("net.py");
+    fs::write(&path, NET).unwrap();
+    let suggestions = suggest::suggest(dir.path());
+    let link = suggestions
+        .iter()
+        .find(|s| matches!(s.kind, SuggestionKind::Call { .. }))
+        .unwrap();
+    suggest::accept(dir.path(), link).unwrap();
+
+    // Запись сдвинула блоки, но связь указывает на новые идентификаторы.
+    let content = fs::read_to_string(&path).unwrap();
+    let blocks = parse_blocks(content.clone(), "python".into()).unwrap();
+    let defines: Vec<_> = blocks
+        .iter()
+        .filter(|b| b.kind == "Function/Define")
+        .collect();
+    assert_eq!(defines[0].links, vec![defines[1].visual_id.clone()]);
+    assert_eq!(read_all(&content).len(), 1);
+
+    // Блок со связью больше не получает предложений связей, а принятый тег
+    // убирает остальные предложения тегов.
+    let suggestions = suggest::suggest(dir.path());
+    assert!(kinds(&suggestions, "send_request")
+        .iter()
+        .all(|k| k.tag().is_some()));
+    let tag = suggestions
+        .iter()
+        .find(|s| s.name == "encode_body")
+        .unwrap()
+        .clone();
+    suggest::accept(dir.path(), &tag).unwrap();
+    let suggestions = suggest::suggest(dir.path());
+    assert!(kinds(&suggestions, "encode_body").is_empty());
+    let content = fs::read_to_string(&path).unwrap();
+    let blocks = parse_blocks(content, "python".into()).unwrap();
+    let defines: Vec<_> = blocks
+        .iter()
+        .filter(|b| b.kind == "Function/Define")
+        .collect();
+    assert_eq!(defines[0].links, vec![defines[1].visual_id.clone()]);
+    assert_eq!(defines[1].tags, vec!["encode".to_string()]);
+}
+
+#[test]
+fn ambiguous_calls_are_not_linked() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("a.rs"), "fn helper() {}\n").unwrap();
+    fs::write(dir.path().join("b.rs"), "fn helper() {}\n").unwrap();
+    fs::write(
+        dir.path().join("c.rs"),
+        "use serde_json::Value;\n\nfn run_all() {\n    helper();\n}\n",
+    )
+    .unwrap();
+    let suggestions = suggest::suggest(dir.path());
+    assert_eq!(
+        kinds(&suggestions, "run_all"),
+        vec![SuggestionKind::NameTag("all".into())]
+    );
+}
@@ -0,0 +1,156 @@
+use core::ai::{AiClient, AiProvider, AiRequest, Limits, Redactor};
+use core::meta::summary::{self, Scope};
+use core::meta::{upsert, VisualMeta};
+use core::offline_queue::OpError;
+use core::parse_blocks;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tempfile::{tempdir, TempDir};
+
+/// Код `code` с описаниями функций: имя функции и описание.
+fn described(code: &str, notes: &[(&str, &str)]) -> String {
+    let blocks = parse_blocks(code.into(), "rust".into()).unwrap();
+    let mut out = code.to_string();
+    for (name, description) in notes {
+        let start = code.find(&format!("fn {name}")).unwrap();
+        let block = blocks
+            .iter()
+            .find(|b| b.kind == "Function/Define" && b.range.0 == start)
+            .unwrap();
+        let meta: VisualMeta = serde_json::from_value(json!({
+            "id": block.visual_id,
+            "x": 0.0,
+            "y": 0.0,
+            "ai": {"description": description},
+        }))
+        .unwrap();
+        out.push_str(&format!("// {}", upsert("", &meta, false)));
+    }
+    out
+}
+
+fn project() -> TempDir {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("lib.rs"), "fn plain() {}\n").unwrap();
+    fs::write(
+        dir.path().join("src/mod.rs"),
+        described("fn init() {}\n", &[("init", "Sets up the module.")]),
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("src/net.rs"),
+        described(
+            "fn connect() {}\n\nfn close() {}\n",
+            &[("close", "Closes it."), ("connect", "Opens a connection.")],
+        ),
+    )
+    .unwrap();
+    dir
+}
+
+#[test]
+fn file_and_module_summaries_compose_block_notes() {
+    let dir = project();
+    let summaries = summary::generate(dir.path(), None).unwrap();
+    let ids: Vec<_> = summaries.iter().map(|s| s.id()).collect();
+    assert_eq!(
+        ids,
+        vec![
+            "summary:src/mod.rs",
+            "summary:src/net.rs",
+            "module-summary:src"
+        ]
+    );
+    assert_eq!(
+        summaries[1].text,
+        "- fn connect() {}: Opens a connection.\n- fn close() {}: Closes it."
+    );
+    assert_eq!(summaries[2].scope, Scope::Module);
+    assert_eq!(summaries[2].file, Path::new("src/mod.rs"));
+    assert_eq!(
+        summaries[2].text,
+        "- mod.rs: - fn init() {}: Sets up the module.\n\
+         - net.rs:\n  - fn connect() {}: Opens a connection.\n  - fn close() {}: Closes it."
+    );
+}
+
+#[test]
+fn regenerate_stores_summaries_without_moving_blocks() {
+    let dir = project();
+    let net = dir.path().join("src/net.rs");
+    let before = fs::read_to_string(&net).unwrap();
+    let summaries = summary::regenerate(dir.path(), None).unwrap();
+    assert_eq!(summary::stored(dir.path()), summaries);
+
+    let after = fs::read_to_string(&net).unwrap();
+    assert!(after.starts_with(&before));
+    assert!(after
+        .lines()
+        .last()
+        .unwrap()
+        .starts_with("// <!-- @VISUAL_META"));
+    // Описания блоков по-прежнему находят свои блоки.
+    let notes = summary::block_notes(&after, &parse_blocks(after.clone(), "rust".into()).unwrap());
+    assert_eq!(notes.len(), 2);
+
+    // Повторное создание без изменений не трогает файлы.
+    assert_eq!(summary::write(dir.path(), &summaries).unwrap(), 0);
+    assert_eq!(fs::read_to_string(&net).unwrap(), after);
+}
+
+#[test]
+fn stale_summaries_are_removed() {
+    let dir = project();
+    summary::regenerate(dir.path(), None).unwrap();
+    fs::write(dir.path().join("src/net.rs"), "fn connect() {}\n").unwrap();
+    let summaries = summary::regenerate(dir.path(), None).unwrap();
+    let paths: Vec<PathBuf> = summaries.iter().map(|s| s.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![PathBuf::from("src/mod.rs"), PathBuf::from("src")]
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("src/net.rs")).unwrap(),
+        "fn connect() {}\n"
+    );
+    assert_eq!(summary::stored(dir.path()), summaries);
+}
+
+/// Модель, отвечающая числом строк заметок.
+struct Counter;
+
+impl AiProvider for Counter {
+    fn model(&self) -> &str {
+        "counter"
+    }
+
+    fn complete(
+        &self,
+        request: &AiRequest,
+        _on_token: &mut dyn FnMut(&str),
+    ) -> Result<String, OpError> {
+        assert!(request.system.is_some());
+        Ok(format!(" {} notes ", request.prompt.lines().count()))
+    }
+}
+
+#[test]
+fn model_condenses_notes() {
+    let dir = project();
+    let client = AiClient::new(
+        Box::new(Counter),
+        Redactor::default(),
+        Limits {
+            max_concurrent: 1,
+            max_requests: None,
+            window: Duration::from_secs(60),
+            cache_size: 0,
+        },
+    );
+    let summaries = summary::generate(dir.path(), Some(&client)).unwrap();
+    let texts: Vec<_> = summaries.iter().map(|s| s.text.as_str()).collect();
+    assert_eq!(texts, vec!["1 notes", "2 notes", "2 notes"]);
+}
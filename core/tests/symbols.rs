@@ -0,0 +1,67 @@
+use core::blocks::symbols::{symbol_name, workspace_symbols, CodeSymbolKind};
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn names_come_from_block_headers() {
+    assert_eq!(
+        symbol_name("pub(crate) fn load(path: &Path) {"),
+        Some("load")
+    );
+    assert_eq!(symbol_name("async def fetch(url):"), Some("fetch"));
+    assert_eq!(symbol_name("function* gen() {"), Some("gen"));
+    assert_eq!(symbol_name("static async save() {"), Some("save"));
+    assert_eq!(symbol_name("pub struct Config<T> {"), Some("Config"));
+    assert_eq!(symbol_name("function (a) { return a; }"), None);
+    assert_eq!(symbol_name("(a) => a"), None);
+}
+
+#[test]
+fn collects_functions_and_classes_across_workspace() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(root.join("a.rs"), "struct Point;\n\nfn distance() {}\n").unwrap();
+    fs::write(
+        root.join("b.py"),
+        "class Parser:\n    def parse(self):\n        pass\n",
+    )
+    .unwrap();
+    fs::write(root.join("notes.txt"), "fn ignored() {}\n").unwrap();
+
+    let symbols = workspace_symbols(root);
+    let found: Vec<(&str, CodeSymbolKind, usize)> = symbols
+        .iter()
+        .map(|s| (s.name.as_str(), s.kind, s.line))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            ("Point", CodeSymbolKind::Class, 0),
+            ("distance", CodeSymbolKind::Function, 2),
+            ("Parser", CodeSymbolKind::Class, 0),
+            ("parse", CodeSymbolKind::Function, 1),
+        ]
+    );
+    assert_eq!(symbols[0].path, root.join("a.rs"));
+    assert_eq!(symbols[3].path, root.join("b.py"));
+}
+
+#[test]
+fn translated_names_come_from_metadata() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let src = "fn distance() {}\n";
+    fs::write(root.join("a.rs"), src).unwrap();
+    let id = workspace_symbols(root).remove(0).visual_id;
+
+    // Метаданные в конце файла не сдвигают блок и сохраняют его идентификатор.
+    let content = format!(
+        "{src}// @VISUAL_META {{\"id\":\"{id}\",\"x\":0.0,\"y\":0.0,\"translations\":{{\"ru\":\"Расстояние\"}}}}\n"
+    );
+    fs::write(root.join("a.rs"), content).unwrap();
+
+    let symbol = workspace_symbols(root).remove(0);
+    assert_eq!(symbol.name, "distance");
+    assert_eq!(symbol.label("ru"), "Расстояние");
+    assert_eq!(symbol.label("en"), "distance");
+}
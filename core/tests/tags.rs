@@ -1,6 +1,26 @@
-use core::meta::{read_all, upsert, VisualMeta};
 use chrono::Utc;
+use core::meta::backup::list_backups;
+use core::meta::tags::{merge_tags, rename_tag, tag_counts};
+use core::meta::{read_all, upsert, VisualMeta};
 use std::collections::HashMap;
+use std::fs;
+use tempfile::tempdir;
+
+fn meta(id: &str, tags: &[&str]) -> VisualMeta {
+    serde_json::from_value(serde_json::json!({
+        "id": id, "x": 0.0, "y": 0.0, "tags": tags
+    }))
+    .unwrap()
+}
+
+fn tags_of(path: &std::path::Path, id: &str) -> Vec<String> {
+    let content = fs::read_to_string(path).unwrap();
+    read_all(&content)
+        .into_iter()
+        .find(|m| m.id == id)
+        .unwrap()
+        .tags
+}
 
 #[test]
 fn read_tags_from_comment() {
@@ -18,6 +38,8 @@ fn upsert_preserves_tags() {
         id: "1".into(),
         x: 0.0,
         y: 0.0,
+        width: None,
+        height: None,
         tags: vec!["t".into()],
         links: vec![],
         anchors: vec![],
@@ -34,3 +56,41 @@ fn upsert_preserves_tags() {
     let metas = read_all(&updated);
     assert_eq!(metas[0].tags, vec!["t"]);
 }
+
+#[test]
+fn rename_and_merge_tags_across_files() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let a = root.join("a.rs");
+    let b = root.join("b.py");
+    fs::write(&a, upsert("fn a() {}\n", &meta("a", &["net", "io"]), false)).unwrap();
+    let b_content = upsert("def b(): pass\n", &meta("b1", &["network"]), false);
+    fs::write(
+        &b,
+        upsert(&b_content, &meta("b2", &["net", "network"]), false),
+    )
+    .unwrap();
+    fs::write(
+        root.join("notes.txt"),
+        "<!-- @VISUAL_META {\"id\":\"t\",\"x\":0,\"y\":0,\"tags\":[\"net\"]} -->",
+    )
+    .unwrap();
+
+    let counts = tag_counts(root);
+    assert_eq!(counts.get("net"), Some(&2));
+    assert_eq!(counts.get("network"), Some(&2));
+    assert_eq!(counts.get("io"), Some(&1));
+
+    let changed = merge_tags(root, &["net"], "network").unwrap();
+    assert_eq!(changed, vec![a.clone(), b.clone()]);
+    assert_eq!(tags_of(&a, "a"), vec!["network", "io"]);
+    assert_eq!(tags_of(&b, "b2"), vec!["network"]);
+    assert_eq!(tag_counts(root).get("network"), Some(&3));
+    assert_eq!(list_backups(root).unwrap()[0].files.len(), 2);
+
+    let changed = rename_tag(root, "io", "input").unwrap();
+    assert_eq!(changed, vec![a.clone()]);
+    assert_eq!(tags_of(&a, "a"), vec!["network", "input"]);
+    assert!(rename_tag(root, "missing", "x").unwrap().is_empty());
+    assert!(rename_tag(root, "input", " ").is_err());
+}
@@ -0,0 +1,47 @@
+use core::parse_blocks;
+use core::tasks::{collect, tasks_in, TaskKind, DEFAULT_STATUS};
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+#[test]
+fn extracts_comment_markers_with_assignee() {
+    let content = "fn run() {\n    // TODO(alice): check timeout\n    /* FIXME handle errors */\n}\n";
+    let tasks = tasks_in(Path::new("main.rs"), content, "rust");
+    assert_eq!(tasks.len(), 2);
+    assert_eq!(tasks[0].kind, TaskKind::Todo);
+    assert_eq!(tasks[0].line, 1);
+    assert_eq!(tasks[0].text, "check timeout");
+    assert_eq!(tasks[0].assignee.as_deref(), Some("alice"));
+    assert_eq!(tasks[0].status, DEFAULT_STATUS);
+    assert!(tasks[0].block.is_some());
+    assert_eq!(tasks[1].kind, TaskKind::Fixme);
+    assert_eq!(tasks[1].text, "handle errors");
+    assert_eq!(tasks[1].assignee, None);
+}
+
+#[test]
+fn tagged_blocks_become_tasks_with_extras() {
+    let code = "fn connect() {}\n";
+    let blocks = parse_blocks(code.into(), "rust".into()).unwrap();
+    let block = blocks
+        .iter()
+        .find(|b| code[b.range.0..b.range.1].starts_with("fn connect"))
+        .unwrap();
+    let content = format!(
+        "{code}// <!-- @VISUAL_META {{\"id\":\"{}\",\"x\":0.0,\"y\":0.0,\"tags\":[\"todo\",\"net\"],\"ai\":{{\"description\":\"retry on failure\"}},\"extras\":{{\"assignee\":\"bob\",\"status\":\"review\"}},\"updated_at\":\"2024-01-01T00:00:00Z\"}} -->\n",
+        block.visual_id
+    );
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("net.rs"), content).unwrap();
+
+    let tasks = collect(dir.path());
+    assert_eq!(tasks.len(), 1);
+    let task = &tasks[0];
+    assert_eq!(task.kind, TaskKind::Block);
+    assert_eq!(task.block.as_deref(), Some(block.visual_id.as_str()));
+    assert_eq!(task.text, "retry on failure");
+    assert_eq!(task.assignee.as_deref(), Some("bob"));
+    assert_eq!(task.status, "review");
+    assert_eq!(task.tags, vec!["todo", "net"]);
+}
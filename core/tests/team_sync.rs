@@ -0,0 +1,159 @@
+use core::meta::team_sync::{
+    merge, sync_project, ConflictPolicy, MetaRemote, PushResult, RemoteFile, SyncState,
+};
+use core::meta::{read_all, upsert, VisualMeta};
+use core::offline_queue::OpError;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fs;
+use tempfile::tempdir;
+
+fn meta(id: &str, x: f64, y: f64) -> VisualMeta {
+    serde_json::from_value(serde_json::json!({ "id": id, "x": x, "y": y })).unwrap()
+}
+
+fn with_tags(mut m: VisualMeta, tags: &[&str]) -> VisualMeta {
+    m.tags = tags.iter().map(|t| t.to_string()).collect();
+    m
+}
+
+/// Сервер в памяти. `race` раз принимает запись с ответом о конфликте,
+/// как если бы файл успели изменить между чтением и записью.
+#[derive(Default)]
+struct Server {
+    files: RefCell<BTreeMap<String, RemoteFile>>,
+    race: Cell<usize>,
+    pushes: Cell<usize>,
+}
+
+impl Server {
+    fn put(&self, file: &str, metas: Vec<VisualMeta>) {
+        let mut files = self.files.borrow_mut();
+        let entry = files.entry(file.to_string()).or_default();
+        entry.version += 1;
+        entry.metas = metas;
+    }
+
+    fn get(&self, file: &str) -> RemoteFile {
+        self.files.borrow().get(file).cloned().unwrap_or_default()
+    }
+}
+
+impl MetaRemote for Server {
+    fn list(&self) -> Result<Vec<(String, u64)>, OpError> {
+        Ok(self
+            .files
+            .borrow()
+            .iter()
+            .map(|(f, r)| (f.clone(), r.version))
+            .collect())
+    }
+
+    fn pull(&self, file: &str) -> Result<RemoteFile, OpError> {
+        Ok(self.get(file))
+    }
+
+    fn push(
+        &self,
+        file: &str,
+        base_version: u64,
+        metas: &[VisualMeta],
+    ) -> Result<PushResult, OpError> {
+        self.pushes.set(self.pushes.get() + 1);
+        if self.race.get() > 0 {
+            self.race.set(self.race.get() - 1);
+            let mut current = self.get(file).metas;
+            current.push(meta("raced", 9.0, 9.0));
+            self.put(file, current);
+        }
+        let current = self.get(file);
+        if current.version != base_version {
+            return Ok(PushResult::Conflict(current));
+        }
+        self.put(file, metas.to_vec());
+        Ok(PushResult::Accepted(self.get(file).version))
+    }
+}
+
+#[test]
+fn merges_fields_changed_on_one_side() {
+    let base = vec![meta("a", 0.0, 0.0)];
+    // Дизайнер подвинул блок, разработчик добавил тег.
+    let remote = vec![meta("a", 10.0, 20.0)];
+    let local = vec![with_tags(meta("a", 0.0, 0.0), &["net"])];
+    let merged = merge(&base, &local, &remote, ConflictPolicy::Remote);
+    assert!(merged.conflicts.is_empty());
+    assert_eq!((merged.metas[0].x, merged.metas[0].y), (10.0, 20.0));
+    assert_eq!(merged.metas[0].tags, vec!["net"]);
+}
+
+#[test]
+fn conflicting_fields_follow_policy() {
+    let base = vec![meta("a", 0.0, 0.0), meta("b", 0.0, 0.0)];
+    let local = vec![meta("a", 1.0, 0.0), meta("b", 0.0, 0.0)];
+    let remote = vec![meta("a", 2.0, 5.0), meta("c", 3.0, 3.0)];
+
+    let merged = merge(&base, &local, &remote, ConflictPolicy::Remote);
+    assert_eq!(merged.conflicts, vec!["a"]);
+    assert_eq!((merged.metas[0].x, merged.metas[0].y), (2.0, 5.0));
+    // Удаление не передаётся, новые записи сервера идут в конце.
+    let ids: Vec<_> = merged.metas.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, vec!["a", "b", "c"]);
+
+    let merged = merge(&base, &local, &remote, ConflictPolicy::Local);
+    assert_eq!((merged.metas[0].x, merged.metas[0].y), (1.0, 5.0));
+}
+
+#[test]
+fn sync_pushes_pulls_and_remembers_versions() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let path = root.join("a.rs");
+    fs::write(&path, upsert("fn a() {}\n", &meta("a", 0.0, 0.0), false)).unwrap();
+    let server = Server::default();
+
+    // Первый обмен отправляет локальные записи.
+    let reports = sync_project(root, &server, ConflictPolicy::Remote).unwrap();
+    assert_eq!(reports.len(), 1);
+    assert!(reports[0].pushed);
+    assert_eq!(server.get("a.rs").version, 1);
+    let state = SyncState::load(root).unwrap();
+    assert_eq!(state.files["a.rs"].version, 1);
+
+    // Без изменений сервер не запрашивается.
+    assert!(sync_project(root, &server, ConflictPolicy::Remote)
+        .unwrap()
+        .is_empty());
+    assert_eq!(server.pushes.get(), 1);
+
+    // Раскладка с сервера попадает в файл, код не меняется.
+    server.put("a.rs", vec![meta("a", 40.0, 50.0)]);
+    let reports = sync_project(root, &server, ConflictPolicy::Remote).unwrap();
+    assert_eq!(reports[0].pulled, 1);
+    assert!(!reports[0].pushed);
+    let content = fs::read_to_string(&path).unwrap();
+    assert!(content.contains("fn a() {}"));
+    assert_eq!(read_all(&content)[0].x, 40.0);
+}
+
+#[test]
+fn sync_retries_when_version_changes_during_push() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::write(
+        root.join("a.rs"),
+        upsert("fn a() {}\n", &meta("a", 1.0, 1.0), false),
+    )
+    .unwrap();
+    let server = Server::default();
+    server.race.set(1);
+
+    let reports = sync_project(root, &server, ConflictPolicy::Remote).unwrap();
+    assert!(reports[0].pushed);
+    assert_eq!(server.pushes.get(), 2);
+    let remote = server.get("a.rs");
+    let ids: Vec<_> = remote.metas.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(ids, vec!["a", "raced"]);
+    let local = read_all(&fs::read_to_string(root.join("a.rs")).unwrap());
+    assert!(local.iter().any(|m| m.id == "raced"));
+}
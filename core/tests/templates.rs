@@ -0,0 +1,75 @@
+use core::meta::backup::list_backups;
+use core::meta::read_all;
+use core::meta::templates::{self, TEMPLATES_FILE};
+use core::saved_search::SavedSearch;
+use std::fs;
+use tempfile::tempdir;
+
+const TEMPLATES: &str = r#"
+[[template]]
+name = "handler"
+tags = ["net", "api"]
+translations = { ru = "Обработчик" }
+ai = { hints = ["проверьте таймауты"] }
+
+[[template]]
+name = "empty"
+"#;
+
+#[test]
+fn loads_templates_and_applies_them_through_extends() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    assert!(templates::load(root).unwrap().is_empty());
+    fs::create_dir_all(root.join(".multicode")).unwrap();
+    fs::write(root.join(TEMPLATES_FILE), TEMPLATES).unwrap();
+
+    let all = templates::load(root).unwrap();
+    assert_eq!(all.len(), 2);
+    assert!(all[1].tags.is_empty());
+    let handler = templates::find(root, "handler").unwrap().unwrap();
+    assert_eq!(handler.id(), "template:handler");
+    assert!(templates::find(root, "missing").unwrap().is_none());
+
+    let content =
+        "fn a() {}\n// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"tags\":[\"io\"]}\n";
+    let meta = read_all(content).remove(0);
+    let updated = templates::apply(content, &handler, &meta);
+    assert!(updated.contains("// @VISUAL_META {\"extends\":\"template:handler\""));
+    let merged = read_all(&updated)
+        .into_iter()
+        .find(|m| m.id == "a")
+        .unwrap();
+    assert_eq!(merged.tags, vec!["net", "api", "io"]);
+    assert_eq!(merged.translations["ru"], "Обработчик");
+    assert_eq!(merged.ai.unwrap().hints, vec!["проверьте таймауты"]);
+    let again = templates::apply(&updated, &handler, &meta);
+    assert_eq!(again.matches("\"id\":\"template:handler\"").count(), 1);
+
+    fs::write(root.join(TEMPLATES_FILE), "[[template]\n").unwrap();
+    assert!(templates::load(root).is_err());
+}
+
+#[test]
+fn applies_template_to_matching_blocks() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir_all(root.join(".multicode")).unwrap();
+    fs::write(root.join(TEMPLATES_FILE), TEMPLATES).unwrap();
+    let a = root.join("a.rs");
+    fs::write(&a, "fn connect() {}\n").unwrap();
+    let untouched = "fn other() {}\n";
+    fs::write(root.join("b.rs"), untouched).unwrap();
+
+    let handler = templates::find(root, "handler").unwrap().unwrap();
+    let search = SavedSearch::parse("", "connect kind:Function");
+    let changed = templates::apply_matching(root, &handler, &search).unwrap();
+    assert_eq!(changed, vec![a.clone()]);
+
+    let content = fs::read_to_string(&a).unwrap();
+    let metas = read_all(&content);
+    let block = metas.iter().find(|m| m.id != handler.id()).unwrap();
+    assert_eq!(block.tags, vec!["net", "api"]);
+    assert_eq!(fs::read_to_string(root.join("b.rs")).unwrap(), untouched);
+    assert_eq!(list_backups(root).unwrap().len(), 1);
+}
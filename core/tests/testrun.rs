@@ -0,0 +1,95 @@
+use core::blocks::parse_blocks;
+use core::testrun::{self, block_status, parse_results, Framework, TestOutcome};
+use serde_json::json;
+use std::fs;
+use tempfile::tempdir;
+
+fn outcome(name: &str, passed: bool) -> TestOutcome {
+    TestOutcome {
+        name: name.into(),
+        passed,
+    }
+}
+
+#[test]
+fn detects_framework_by_project_files() {
+    let dir = tempdir().unwrap();
+    assert_eq!(Framework::detect(dir.path()), None);
+    fs::write(dir.path().join("package.json"), "{}").unwrap();
+    assert_eq!(Framework::detect(dir.path()), Some(Framework::Npm));
+    fs::write(dir.path().join("conftest.py"), "").unwrap();
+    assert_eq!(Framework::detect(dir.path()), Some(Framework::Pytest));
+    fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+    assert_eq!(Framework::detect(dir.path()), Some(Framework::Cargo));
+
+    assert_eq!(
+        Framework::Cargo.command(Some("net::connects")),
+        "cargo test --no-fail-fast -- net::connects"
+    );
+    assert!(Framework::Pytest
+        .command(Some("test_a.py::test_x"))
+        .ends_with("-v test_a.py::test_x"));
+    assert!(Framework::Pytest
+        .command(Some("test_x"))
+        .ends_with("-k test_x"));
+}
+
+#[test]
+fn parses_framework_output() {
+    let cargo = "running 3 tests\ntest net::tests::connects ... ok\ntest net::tests::fails ... FAILED\ntest slow ... ignored\n";
+    assert_eq!(
+        parse_results(Framework::Cargo, cargo),
+        vec![
+            outcome("net::tests::connects", true),
+            outcome("net::tests::fails", false)
+        ]
+    );
+
+    let pytest = "tests/test_a.py::test_ok PASSED    [ 50%]\ntests/test_a.py::test_bad FAILED   [100%]\ntests/test_a.py::test_skip SKIPPED\n";
+    assert_eq!(
+        parse_results(Framework::Pytest, pytest),
+        vec![
+            outcome("tests/test_a.py::test_ok", true),
+            outcome("tests/test_a.py::test_bad", false)
+        ]
+    );
+
+    let jest = "  math\n    ✓ adds numbers (3 ms)\n    ✕ divides by zero (1 ms)\n    √ subtracts\n";
+    assert_eq!(
+        parse_results(Framework::Npm, jest),
+        vec![
+            outcome("adds numbers", true),
+            outcome("divides by zero", false),
+            outcome("subtracts", true)
+        ]
+    );
+}
+
+#[test]
+fn results_map_to_blocks_through_tests_field() {
+    assert!(testrun::matches("net::tests::connects", "connects"));
+    assert!(testrun::matches("net::tests::connects", "tests::connects"));
+    assert!(!testrun::matches("net::tests::reconnects", "connects"));
+    assert!(testrun::matches("adds numbers", "adds numbers"));
+
+    let content = "fn a() {}\nfn b() {}\nfn c() {}\n";
+    let blocks: Vec<_> = parse_blocks(content.to_string(), "rust".into())
+        .unwrap()
+        .into_iter()
+        .filter(|b| b.kind == "Function/Define")
+        .collect();
+    let content = format!(
+        "{content}// @VISUAL_META {}\n// @VISUAL_META {}\n",
+        json!({"id": blocks[0].visual_id, "x": 0, "y": 0, "tests": ["a_works", "a_edge"]}),
+        json!({"id": blocks[1].visual_id, "x": 0, "y": 0, "tests": ["b_works"]}),
+    );
+    let outcomes = [
+        outcome("tests::a_works", true),
+        outcome("tests::a_edge", false),
+        outcome("tests::b_works", true),
+    ];
+    assert_eq!(
+        block_status(&content, &blocks, &outcomes),
+        vec![Some(false), Some(true), None]
+    );
+}
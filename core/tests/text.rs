@@ -0,0 +1,49 @@
+use core::text::{get_position_index, position_index, Position, PositionIndex};
+
+#[test]
+fn converts_offsets_to_positions() {
+    let text = "fn a() {}\nlet é = \"😀x\";\n";
+    let index = PositionIndex::new(text);
+    assert_eq!(index.line_count(), 3);
+    assert_eq!(index.line_text(1), Some("let é = \"😀x\";"));
+
+    let x = text.find('x').unwrap();
+    assert_eq!(index.position(x), Position::new(1, 10));
+    assert_eq!(index.utf16_position(x), Position::new(1, 11));
+    assert_eq!(index.offset(Position::new(1, 10)), x);
+    assert_eq!(index.utf16_offset(Position::new(1, 11)), x);
+
+    // Смещение внутри символа округляется к его началу.
+    let emoji = text.find('😀').unwrap();
+    assert_eq!(index.position(emoji + 2), index.position(emoji));
+    assert_eq!(index.utf16_offset(Position::new(1, 10)), emoji);
+
+    // Положения за концом строки и текста ограничиваются.
+    assert_eq!(index.offset(Position::new(0, 100)), 9);
+    assert_eq!(index.offset(Position::new(10, 0)), text.len());
+    assert_eq!(index.position(1000), Position::new(2, 0));
+}
+
+#[test]
+fn block_ranges_map_to_positions() {
+    let text = "fn a() {}\n\nfn b() {\n    1\n}\n";
+    let blocks = core::parse_blocks(text.to_string(), "rust".into()).unwrap();
+    let index = PositionIndex::new(text);
+    let start = text.find("fn b").unwrap();
+    let block = blocks.iter().find(|b| b.range.0 == start).unwrap();
+    let (start, end) = index.range(block.range);
+    assert_eq!(start, Position::new(2, 0));
+    assert_eq!(end, Position::new(4, 1));
+}
+
+#[test]
+fn index_is_cached_per_document() {
+    let first = position_index("text-test", "a\nb");
+    let again = position_index("text-test", "a\nb");
+    assert!(std::sync::Arc::ptr_eq(&first, &again));
+
+    let changed = position_index("text-test", "a\nb\nc");
+    assert_eq!(changed.line_count(), 3);
+    let cached = get_position_index("text-test").unwrap();
+    assert!(std::sync::Arc::ptr_eq(&changed, &cached));
+}
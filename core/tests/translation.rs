@@ -0,0 +1,110 @@
+use core::meta::backup::list_backups;
+use core::meta::read_all;
+use core::meta::translation::{
+    apply_fill, coverage, fill_missing, machine_translated, plan_fill, Translator,
+};
+use std::cell::RefCell;
+use std::fs;
+use tempfile::tempdir;
+
+/// Переводчик, помечающий текст языком и запоминающий запросы.
+#[derive(Default)]
+struct Fake {
+    calls: RefCell<Vec<String>>,
+}
+
+impl Translator for Fake {
+    fn translate(&self, text: &str, source: &str, target: &str) -> Result<String, String> {
+        self.calls.borrow_mut().push(format!("{source}>{target}"));
+        if text == "fail" {
+            return Err("service unavailable".into());
+        }
+        Ok(format!("[{target}] {text}"))
+    }
+}
+
+fn langs(list: &[&str]) -> Vec<String> {
+    list.iter().map(|s| s.to_string()).collect()
+}
+
+#[test]
+fn reports_coverage_per_directory_and_language() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    fs::create_dir(root.join("api")).unwrap();
+    fs::write(
+        root.join("api/a.rs"),
+        "// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"translations\":{\"en\":\"Open\",\"ru\":\"Открыть\"}}\n\
+         // @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0,\"translations\":{\"en\":\"Close\",\"ru\":\" \"}}\n",
+    )
+    .unwrap();
+    fs::write(
+        root.join("main.py"),
+        "# @VISUAL_META {\"id\":\"template:t\",\"x\":0.0,\"y\":0.0,\"translations\":{\"de\":\"Haupt\"}}\n\
+         # @VISUAL_META {\"id\":\"m\",\"x\":0.0,\"y\":0.0,\"extends\":\"template:t\"}\n",
+    )
+    .unwrap();
+
+    let report = coverage(root, &[]);
+    assert_eq!(report.languages, langs(&["de", "en", "ru"]));
+    assert_eq!(report.dirs.len(), 2);
+    let (root_dir, api) = (&report.dirs[0], &report.dirs[1]);
+    assert_eq!((root_dir.dir.as_str(), root_dir.total), (".", 1));
+    assert_eq!(root_dir.percent("de"), 100.0);
+    assert_eq!((api.dir.as_str(), api.total), ("api", 2));
+    assert_eq!(api.percent("en"), 100.0);
+    assert_eq!(api.percent("ru"), 50.0);
+    assert_eq!(report.total.total, 3);
+    assert_eq!(report.total.translated["en"], 2);
+
+    let report = coverage(root, &langs(&["ru"]));
+    assert_eq!(report.languages, langs(&["ru"]));
+    assert_eq!(report.total.translated.len(), 1);
+}
+
+#[test]
+fn fills_missing_translations_and_flags_them() {
+    let content = "fn a() {}\n// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"translations\":{\"de\":\"Öffnen\",\"en\":\"Open\"}}\n// @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0}\n";
+    let fake = Fake::default();
+    let (updated, filled) =
+        fill_missing(content, &langs(&["en", "ru"]), Some("en"), &fake).unwrap();
+    assert_eq!(*fake.calls.borrow(), vec!["en>ru"]);
+    assert_eq!(filled.len(), 1);
+    assert_eq!(filled[0].text, "[ru] Open");
+    assert!(updated.starts_with("fn a() {}\n// @VISUAL_META {"));
+    let meta = &read_all(&updated)[0];
+    assert_eq!(meta.translations["ru"], "[ru] Open");
+    assert_eq!(machine_translated(meta), vec!["ru"]);
+
+    // Без `source` берётся первый по алфавиту перевод, но не машинный.
+    let fake = Fake::default();
+    let (again, filled) = fill_missing(&updated, &langs(&["en", "ru", "fr"]), None, &fake).unwrap();
+    assert_eq!(*fake.calls.borrow(), vec!["de>fr"]);
+    assert_eq!(filled.len(), 1);
+    assert_eq!(machine_translated(&read_all(&again)[0]), vec!["ru", "fr"]);
+}
+
+#[test]
+fn project_fill_writes_after_backup_and_stops_on_errors() {
+    let dir = tempdir().unwrap();
+    let root = dir.path();
+    let a = root.join("a.rs");
+    fs::write(
+        &a,
+        "// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"translations\":{\"en\":\"Open\"}}\n",
+    )
+    .unwrap();
+
+    let plan = plan_fill(root, &langs(&["ru"]), None, &Fake::default()).unwrap();
+    assert_eq!(plan.filled[0].file, "a.rs");
+    assert_eq!(apply_fill(root, plan).unwrap(), vec![a.clone()]);
+    assert!(fs::read_to_string(&a).unwrap().contains("[ru] Open"));
+    assert_eq!(list_backups(root).unwrap().len(), 1);
+
+    let broken =
+        "// @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0,\"translations\":{\"en\":\"fail\"}}\n";
+    fs::write(root.join("b.rs"), broken).unwrap();
+    let err = plan_fill(root, &langs(&["de"]), None, &Fake::default()).unwrap_err();
+    assert!(err.to_string().contains("service unavailable"));
+    assert_eq!(fs::read_to_string(root.join("b.rs")).unwrap(), broken);
+}
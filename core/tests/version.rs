@@ -25,6 +25,8 @@ fn upsert_preserves_version() {
         id: "1".into(),
         x: 0.0,
         y: 0.0,
+        width: None,
+        height: None,
         tags: vec![],
         links: vec![],
         anchors: vec![],
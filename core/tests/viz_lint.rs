@@ -13,3 +13,27 @@ fn detects_unknown_op() {
     let errors = lint_str(src);
     assert!(errors.iter().any(|e| e.contains("неизвестная операция")), "{errors:?}");
 }
+
+#[test]
+fn issues_map_to_their_blocks() {
+    use core::viz_lint::{lint_blocks, UNKNOWN_OP};
+
+    let src = "// @viz op=foo node=1 id=a\nfn a() {\n    // @viz op=bar node=2 id=b\n\n    let x = 1;\n}\n// @viz op=baz node=3 id=c\n";
+    let blocks = core::parse_blocks(src.into(), "rust".into()).unwrap();
+    let id = |kind: &str, text: &str| {
+        let start = src.find(text).unwrap();
+        blocks
+            .iter()
+            .find(|b| b.kind == kind && b.range.0 == start)
+            .map(|b| b.visual_id.clone())
+    };
+    let issues = lint_blocks(src, &blocks);
+    assert_eq!(issues.len(), 3);
+    assert!(issues.iter().all(|i| i.issue.rule == UNKNOWN_OP));
+    assert_eq!((issues[0].line, issues[1].line, issues[2].line), (0, 2, 6));
+    // Комментарий относится к следующему за ним элементу, а без такого — к
+    // охватывающему блоку.
+    assert_eq!(issues[0].visual_id, id("Function/Define", "fn a"));
+    assert_eq!(issues[1].visual_id, id("let_declaration", "let x"));
+    assert_eq!(issues[2].visual_id, id("source_file", "//"));
+}
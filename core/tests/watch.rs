@@ -0,0 +1,66 @@
+use core::blocks::parse_blocks;
+use core::debugger::{StoppedLocation, Variable};
+use core::meta::{validate, VisualMeta};
+use core::watch::{self, WatchValue};
+use serde_json::json;
+
+fn meta(extras: serde_json::Value) -> VisualMeta {
+    serde_json::from_value(json!({"id": "1", "x": 0.0, "y": 0.0, "extras": extras})).unwrap()
+}
+
+#[test]
+fn resolves_and_validates_watches() {
+    assert_eq!(watch::resolve(&meta(json!({"watch": "count"}))), ["count"]);
+    assert_eq!(
+        watch::resolve(&meta(json!({"watch": ["a", "user.name"]}))),
+        ["a", "user.name"]
+    );
+
+    let wrong = meta(json!({"watch": [1]}));
+    assert!(watch::resolve(&wrong).is_empty());
+    let errors = validate(&wrong).unwrap_err();
+    assert_eq!(errors[0].field, "extras.watch");
+
+    let empty = meta(json!({"watch": [" "]}));
+    assert!(validate(&empty).unwrap_err()[0].message.contains("пустым"));
+}
+
+#[test]
+fn watches_are_evaluated_in_blocks_containing_the_stop() {
+    let content = format!(
+        "fn first() {{\n    let x = 1;\n}}\n\nfn second() {{\n    let y = 2;\n}}\n// @VISUAL_META {}\n// @VISUAL_META {}\n",
+        json!({"id": "first", "x": 0, "y": 0, "extras": {"watch": ["x", "missing"]}}),
+        json!({"id": "second", "x": 0, "y": 0, "extras": {"watch": "y"}}),
+    );
+    let mut blocks: Vec<_> = parse_blocks(content.clone(), "rust".into())
+        .unwrap()
+        .into_iter()
+        .filter(|b| b.kind == "Function/Define")
+        .collect();
+    blocks[0].visual_id = "first".into();
+    blocks[1].visual_id = "second".into();
+
+    let stopped = StoppedLocation {
+        file: "main.rs".into(),
+        line: 2,
+        variables: vec![Variable {
+            name: "x".into(),
+            value: "1".into(),
+        }],
+    };
+    let values = watch::evaluate_at_stop(&content, &blocks, &stopped);
+    assert_eq!(
+        values[0],
+        vec![
+            WatchValue {
+                expression: "x".into(),
+                value: Some("1".into()),
+            },
+            WatchValue {
+                expression: "missing".into(),
+                value: None,
+            },
+        ]
+    );
+    assert!(values[1].is_empty());
+}
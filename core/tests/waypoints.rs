@@ -0,0 +1,43 @@
+use core::meta::waypoints::{self, copy, set_waypoints};
+use core::meta::{validate, VisualMeta};
+use serde_json::json;
+
+fn meta(id: &str, extras: serde_json::Value) -> VisualMeta {
+    serde_json::from_value(json!({"id": id, "x": 0.0, "y": 0.0, "extras": extras})).unwrap()
+}
+
+#[test]
+fn set_waypoints_keeps_other_extras() {
+    let mut m = meta("a", json!({"color": "#123456"}));
+    set_waypoints(&mut m, "b", &[(200.0, 40.0), (200.0, 120.0)]);
+    assert_eq!(
+        waypoints::waypoints(&m, "b"),
+        vec![(200.0, 40.0), (200.0, 120.0)]
+    );
+    assert!(waypoints::waypoints(&m, "c").is_empty());
+    assert_eq!(m.extras.as_ref().unwrap()["color"], "#123456");
+    assert!(validate(&m).is_ok());
+
+    set_waypoints(&mut m, "b", &[]);
+    assert_eq!(m.extras, Some(json!({"color": "#123456"})));
+
+    let mut m = meta("a", json!({}));
+    set_waypoints(&mut m, "b", &[(1.0, 2.0)]);
+    set_waypoints(&mut m, "b", &[]);
+    assert!(m.extras.is_none());
+}
+
+#[test]
+fn waypoints_are_validated_and_copied() {
+    let errors = validate(&meta("a", json!({"waypoints": {"b": [[1.0]]}}))).unwrap_err();
+    assert_eq!(errors[0].field, "extras.waypoints");
+    assert!(validate(&meta("a", json!({"waypoints": [1, 2]}))).is_err());
+
+    let from = meta("a", json!({"waypoints": {"b": [[1.0, 2.0]]}}));
+    let mut to = meta("a", json!({"icon": "⚠", "waypoints": {"c": [[3.0, 4.0]]}}));
+    copy(&from, &mut to);
+    assert_eq!(
+        to.extras,
+        Some(json!({"icon": "⚠", "waypoints": {"b": [[1.0, 2.0]]}}))
+    );
+}
@@ -2,25 +2,41 @@ use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use iced::futures::stream;
-use iced::{event, subscription, Application, Command, Element, Subscription, Theme};
+use iced::multi_window::Application;
+use iced::theme::Palette;
+use iced::{event, subscription, time, window, Command, Element, Event, Subscription, Theme};
 use tokio::sync::broadcast;
 
 use super::command_palette::COMMANDS;
 use super::command_translations::command_name;
 use super::events::Message;
-use super::{
-    AppTheme, CreateTarget, EditorMode, Language, LogLevel, MulticodeApp, Screen, UserSettings,
-};
+use super::git_screen::GitScreen;
+use super::git_status::{self, GitDecorations};
+use super::input_mode::captured_escape;
+use super::logging::{self, LogBuffer, LogFilter};
+use super::navigation::NavigationHistory;
+use super::notifications::Notifications;
+use super::offline_queue::QUEUE_POLL_INTERVAL;
+use super::recovery::RecoveryStore;
+use super::task_board::TaskGroup;
+use super::{AppTheme, CreateTarget, EditorMode, Language, MulticodeApp, Screen, UserSettings};
+use crate::editor::input_mode::Vim;
 use crate::search::{fuzzy, index::SearchIndex};
 use crate::sync::{ChangeTracker, SyncEngine};
 use crate::visual::palette::{PaletteBlock, DEFAULT_CATEGORY};
 use crate::visual::translations::block_synonyms;
 use lru::LruCache;
+use multicode_core::codeowners::CodeOwners;
+use multicode_core::git::BranchInfo;
 use multicode_core::parse_blocks;
 use multicode_core::parser::Lang;
 
+/// Период проверки файла пользовательской темы на изменения.
+const THEME_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 pub(super) fn build_command_index() -> SearchIndex<&'static str> {
     let mut index = SearchIndex::new();
     for cmd in COMMANDS {
@@ -105,6 +121,12 @@ impl Application for MulticodeApp {
         };
         let cap = NonZeroUsize::new(cache_size).unwrap_or_else(|| NonZeroUsize::new(1).unwrap());
 
+        let recovery = RecoveryStore::open_default().filter(|_| settings.autosave_secs > 0);
+        let recovery_prompt = recovery
+            .as_ref()
+            .map(RecoveryStore::pending)
+            .unwrap_or_default();
+
         let view_mode = settings.last_view_mode;
         let screen = if let Some(path) = settings.last_folders.first().cloned() {
             match settings.editor_mode {
@@ -132,18 +154,23 @@ impl Application for MulticodeApp {
             create_target: CreateTarget::File,
             rename_file_name: String::new(),
             search_query: String::new(),
+            file_search: None,
+            file_search_generation: Default::default(),
+            file_tree_viewport: Default::default(),
             favorites: fav_files,
             query: String::new(),
             show_command_palette: false,
-            log: Vec::new(),
-            min_log_level: LogLevel::Info,
+            log: LogBuffer::new(settings.log_limit),
+            log_filter: LogFilter::default(),
             project_search_results: Vec::new(),
             goto_line: None,
             goto_line_input: String::new(),
             show_goto_line_modal: false,
             show_terminal: false,
             terminal_cmd: String::new(),
-            terminal_child: None,
+            terminal: None,
+            terminal_job: None,
+            terminal_size: crate::terminal::pty::initial_size(),
             show_terminal_help: false,
             sender,
             settings,
@@ -157,10 +184,7 @@ impl Application for MulticodeApp {
             settings_warning: None,
             loading: false,
             diff_error: None,
-            show_meta_dialog: false,
-            meta_tags: String::new(),
-            meta_links: String::new(),
-            meta_comment: String::new(),
+            meta_form: None,
             autocomplete: None,
             show_meta_panel: false,
             tab_drag: None,
@@ -178,7 +202,96 @@ impl Application for MulticodeApp {
             block_index,
             command_cache: RefCell::new(LruCache::new(cap)),
             block_cache: RefCell::new(LruCache::new(cap)),
+            navigation: NavigationHistory::default(),
+            show_bookmarks_panel: false,
+            block_clipboard: None,
+            label_edit: None,
+            canvas_focus: None,
+            theme_file_mtime: None,
+            show_tags_panel: false,
+            workspace_tags: Vec::new(),
+            tag_from: String::new(),
+            tag_into: String::new(),
+            meta_new_id: String::new(),
+            saved_searches: Vec::new(),
+            active_saved_search: None,
+            saved_search_name: String::new(),
+            show_block_history: false,
+            collapsed_lanes: HashSet::new(),
+            show_canvas_filter: false,
+            canvas_filter: String::new(),
+            canvas_filter_hide: false,
+            canvas_filter_current: None,
+            show_inheritance_panel: false,
+            extends_draft: None,
+            block_history: Vec::new(),
+            block_history_id: None,
+            show_cochange_panel: false,
+            cochange: None,
+            show_meta_suggestions: false,
+            meta_suggestions: Vec::new(),
+            rejected_suggestions: HashSet::new(),
+            show_structural_search: false,
+            structural_pattern: String::new(),
+            structural_lang: Lang::Rust,
+            structural_results: Vec::new(),
+            structural_template: String::new(),
+            structural_preview: Vec::new(),
+            structural_backup: None,
+            block_metrics: Default::default(),
+            show_coverage: false,
+            coverage: None,
+            profile_metric: None,
+            parse_costs: Default::default(),
+            debug_stop: None,
+            breakpoints: Default::default(),
+            show_test_panel: false,
+            test_run: None,
+            tests_running: false,
+            build_tasks: Default::default(),
+            build_problems: Vec::new(),
+            dead_code: None,
+            show_env_editor: false,
+            project_env: Default::default(),
+            offline_queue: Default::default(),
+            queue_replaying: false,
+            ai_client: None,
+            ai_answer: None,
+            tasks: Vec::new(),
+            task_group: TaskGroup::default(),
+            issue_links: Default::default(),
+            issue_link_cache: RefCell::new(HashMap::new()),
+            diagnostic_popup: None,
+            focused_diagnostic: None,
+            workspace_symbols: Default::default(),
+            recovery,
+            recovery_written: HashMap::new(),
+            recovery_prompt,
+            git_status: GitDecorations::default(),
+            git_discard_confirm: None,
+            git_screen: GitScreen::default(),
+            git_branches: BranchInfo::default(),
+            new_branch_name: String::new(),
+            code_owners: CodeOwners::default(),
+            show_notifications: false,
+            notifications: Notifications::default(),
+            show_macros_panel: false,
+            macro_recording: None,
+            recorded_macro: Vec::new(),
+            macro_name: String::new(),
+            vim: Vim::default(),
+            modifiers: Default::default(),
+            carets: Default::default(),
+            column_anchor: None,
+            folds: HashMap::new(),
+            editor_scroll: 0.0,
+            canvas_window: None,
+            symbol_search: None,
+            pending_block_focus: None,
+            recent_blocks: Default::default(),
+            recent_blocks_query: None,
         };
+        app.bind_custom_hotkeys();
 
         let cmd = match &app.screen {
             Screen::TextEditor { root }
@@ -188,6 +301,7 @@ impl Application for MulticodeApp {
                 if let Some(entry) = app.settings.default_entry.clone() {
                     cmds.push(app.handle_message(Message::SelectFile(entry)));
                 }
+                cmds.extend(app.restore_panes());
                 Command::batch(cmds)
             }
             _ => Command::none(),
@@ -196,18 +310,31 @@ impl Application for MulticodeApp {
         (app, cmd)
     }
 
-    fn title(&self) -> String {
+    fn title(&self, window: window::Id) -> String {
+        if Some(window) == self.canvas_window {
+            return self.canvas_window_title();
+        }
         String::from("Multicode Desktop")
     }
 
     fn update(&mut self, message: Message) -> Command<Message> {
-        self.handle_message(message)
+        let command = self.handle_message(message);
+        self.sync_panes();
+        command
     }
 
     fn subscription(&self) -> Subscription<Message> {
-        if matches!(
+        let theme_file = if self.settings.editor.theme_file.is_some() {
+            time::every(THEME_POLL_INTERVAL).map(|_| Message::ThemeFileTick)
+        } else {
+            Subscription::none()
+        };
+        let editor = if matches!(
             self.screen,
-            Screen::TextEditor { .. } | Screen::VisualEditor { .. } | Screen::Split { .. }
+            Screen::TextEditor { .. }
+                | Screen::VisualEditor { .. }
+                | Screen::Split { .. }
+                | Screen::Git { .. }
         ) {
             let rx = self.sender.subscribe();
             let core = subscription::run_with_id(
@@ -220,13 +347,71 @@ impl Application for MulticodeApp {
                 }),
             );
             let events = event::listen().map(Message::IcedEvent);
-            Subscription::batch([core, events])
+            let escape = event::listen_with(captured_escape);
+            let git = match self.current_root_path() {
+                Some(root) => git_status::watch(root),
+                None => Subscription::none(),
+            };
+            Subscription::batch([core, events, escape, git])
         } else {
             Subscription::none()
-        }
+        };
+        let log_events = subscription::run_with_id(
+            "log-events",
+            stream::unfold(logging::subscribe(), |mut rx| async {
+                loop {
+                    match rx.recv().await {
+                        Ok(entry) => return Some((Message::LogEvent(entry), rx)),
+                        Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            }),
+        );
+        let autosave = if self.recovery.is_some()
+            && (self.tabs.iter().any(|t| t.dirty) || !self.recovery_written.is_empty())
+        {
+            time::every(Duration::from_secs(self.settings.autosave_secs.max(1)))
+                .map(|_| Message::AutosaveTick)
+        } else {
+            Subscription::none()
+        };
+        let windows = if self.canvas_window.is_some() {
+            event::listen_with(|event, _| match event {
+                Event::Window(id, window::Event::Closed) => Some(Message::WindowClosed(id)),
+                _ => None,
+            })
+        } else {
+            Subscription::none()
+        };
+        let offline_queue = if self.offline_queue.pending > 0 {
+            time::every(QUEUE_POLL_INTERVAL).map(|_| Message::ReplayOfflineQueue(false))
+        } else {
+            Subscription::none()
+        };
+        Subscription::batch([
+            editor,
+            theme_file,
+            log_events,
+            autosave,
+            windows,
+            offline_queue,
+        ])
     }
 
-    fn theme(&self) -> Theme {
+    fn theme(&self, _window: window::Id) -> Theme {
+        if let Some(palette) = self.settings.editor.palette() {
+            return Theme::custom(
+                self.settings.editor.theme.to_string(),
+                Palette {
+                    background: palette.background,
+                    text: palette.foreground,
+                    primary: palette.blocks.loops,
+                    success: palette.blocks.conditional,
+                    danger: palette.blocks.arithmetic,
+                },
+            );
+        }
         match self.settings.theme {
             AppTheme::Light => Theme::Light,
             AppTheme::Dark => Theme::Dark,
@@ -234,7 +419,10 @@ impl Application for MulticodeApp {
         }
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self, window: window::Id) -> Element<'_, Message> {
+        if Some(window) == self.canvas_window {
+            return self.canvas_window_view();
+        }
         self.render()
     }
 }
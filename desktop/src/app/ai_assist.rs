@@ -0,0 +1,188 @@
+//! Описание блока моделью ИИ.
+//!
+//! Команда «Описать блок» отправляет код выбранного на холсте блока модели
+//! из секции `[ai]` проекта ([`AiClient`]) и показывает ответ в панели по
+//! мере получения. Кнопка «Сохранить» записывает ответ в `ai.description`
+//! блока. Без секции `[ai]` команда сообщает, что модель не настроена.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use iced::futures::{stream, StreamExt};
+use iced::widget::{button, column, row, scrollable, text, Space};
+use iced::{Command, Element, Length};
+use multicode_core::ai::{AiClient, AiRequest};
+use multicode_core::config::AiConfig;
+use multicode_core::meta::AiNote;
+use tokio::sync::mpsc;
+use tokio::task;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+/// Инструкция модели для описания блока.
+const DESCRIBE_PROMPT: &str = "Describe in one or two sentences what this code fragment does. \
+     Answer in the language of the identifiers and comments.";
+
+/// Событие потока ответа модели.
+#[derive(Debug, Clone)]
+pub enum AiEvent {
+    /// Очередная часть ответа.
+    Token(String),
+    /// Ответ закончен: взят ли он из кэша, или ошибка.
+    Finished(Result<bool, String>),
+}
+
+/// Ответ модели о блоке.
+#[derive(Debug, Clone, Default)]
+pub struct AiAnswer {
+    pub tab: usize,
+    pub block: String,
+    pub text: String,
+    pub done: bool,
+    pub cached: bool,
+}
+
+impl MulticodeApp {
+    /// Создаёт клиент модели по секции `[ai]` проекта.
+    pub(super) fn load_ai_client(&mut self) {
+        self.ai_client = None;
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        let config = AiConfig::load(&root);
+        if config.endpoint.is_none() {
+            return;
+        }
+        match AiClient::from_config(&config) {
+            Ok(client) => self.ai_client = Some(Arc::new(client)),
+            Err(e) => self
+                .log
+                .push(LogEntry::new(LogMessage::AiError, vec![e], Utc::now())),
+        }
+    }
+
+    /// Просит модель описать блок, выбранный на холсте.
+    pub(super) fn describe_block_with_ai(&mut self) -> Command<Message> {
+        let Some(client) = self.ai_client.clone() else {
+            self.notify(LogEntry::new(
+                LogMessage::AiNotConfigured,
+                Vec::new(),
+                Utc::now(),
+            ));
+            return Command::none();
+        };
+        let (Some(tab_index), Some(index)) = (self.active_tab, self.canvas_focus) else {
+            return Command::none();
+        };
+        let Some(tab) = self.tabs.get(tab_index) else {
+            return Command::none();
+        };
+        let Some(block) = tab.blocks.get(index) else {
+            return Command::none();
+        };
+        let (start, end) = block.range;
+        let Some(code) = tab.content.get(start..end.min(tab.content.len())) else {
+            return Command::none();
+        };
+        let lang = tab.lang.map(|l| format!("{l:?}")).unwrap_or_default();
+        let request =
+            AiRequest::new(format!("```{lang}\n{code}\n```")).with_system(DESCRIBE_PROMPT);
+        self.ai_answer = Some(AiAnswer {
+            tab: tab_index,
+            block: block.visual_id.clone(),
+            ..Default::default()
+        });
+        let (tx, rx) = mpsc::unbounded_channel();
+        let start = async move {
+            // Ответ читается из канала, поэтому завершения задачи не ждём.
+            drop(task::spawn_blocking(move || {
+                let result = client.complete(&request, |token| {
+                    let _ = tx.send(AiEvent::Token(token.to_string()));
+                });
+                let _ = tx.send(AiEvent::Finished(
+                    result.map(|r| r.cached).map_err(|e| e.to_string()),
+                ));
+            }));
+        };
+        let events = stream::once(start)
+            .filter_map(|()| async { None })
+            .chain(stream::unfold(rx, |mut rx| async move {
+                rx.recv().await.map(|event| (event, rx))
+            }));
+        Command::run(events, Message::AiStream)
+    }
+
+    /// Добавляет к ответу очередную часть или завершает его.
+    pub(super) fn ai_event(&mut self, event: AiEvent) {
+        let Some(answer) = self.ai_answer.as_mut() else {
+            return;
+        };
+        match event {
+            AiEvent::Token(token) if !answer.done => answer.text.push_str(&token),
+            AiEvent::Token(_) => {}
+            AiEvent::Finished(Ok(cached)) => {
+                answer.done = true;
+                answer.cached = cached;
+            }
+            AiEvent::Finished(Err(e)) => {
+                self.ai_answer = None;
+                self.log
+                    .push(LogEntry::new(LogMessage::AiError, vec![e], Utc::now()));
+            }
+        }
+    }
+
+    /// Записывает ответ модели в `ai.description` блока.
+    pub(super) fn apply_ai_description(&mut self) {
+        let Some(answer) = self.ai_answer.take() else {
+            return;
+        };
+        let description = answer.text.trim().to_string();
+        let meta = self.tabs.get_mut(answer.tab).and_then(|tab| {
+            let index = tab
+                .blocks
+                .iter()
+                .position(|b| b.visual_id == answer.block)?;
+            let ai = tab.blocks[index].ai.get_or_insert_with(AiNote::default);
+            ai.description = Some(description);
+            let ai = ai.clone();
+            let mut meta = Self::block_meta_with_stored(tab, &tab.blocks[index]);
+            meta.ai = Some(ai);
+            Some(meta)
+        });
+        if let Some(meta) = meta {
+            self.apply_block_meta(answer.tab, meta);
+        }
+    }
+
+    pub fn ai_panel_component(&self) -> Element<'_, Message> {
+        let Some(answer) = &self.ai_answer else {
+            return Space::with_height(Length::Shrink).into();
+        };
+        let status = if !answer.done {
+            "…"
+        } else if answer.cached {
+            "из кэша"
+        } else {
+            ""
+        };
+        let mut save = button("Сохранить");
+        if answer.done {
+            save = save.on_press(Message::ApplyAiDescription);
+        }
+        let header = row![
+            text(format!("Описание блока {}", answer.block)).width(Length::Fill),
+            text(status),
+            save,
+            button("×").on_press(Message::CloseAiPanel),
+        ]
+        .spacing(5);
+        column![
+            header,
+            scrollable(text(&answer.text)).height(Length::Fixed(120.0))
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+use iced::widget::text_editor::Content;
+use iced::{Command, Point, Size};
+use multicode_core::blocks::layout;
+use multicode_core::meta::{pin, read_all, waypoints, VisualMeta};
+use multicode_core::BlockInfo;
+
+use super::events::Message;
+use super::{MulticodeApp, Tab};
+use crate::sync::SyncMessage;
+use crate::visual::canvas::{BLOCK_HEIGHT, BLOCK_WIDTH};
+use crate::visual::change::delta_from_meta;
+use crate::visual::lanes::{lanes, Lane};
+
+/// Промежуток между блоками при автоматической раскладке.
+const LAYOUT_GAP: f32 = 40.0;
+
+impl MulticodeApp {
+    /// Добавляет блок на холст вкладки и записывает `meta` в код.
+    pub(super) fn add_visual_block(&mut self, tab_index: usize, block: BlockInfo, meta: VisualMeta) {
+        if let Some(tab) = self.tabs.get_mut(tab_index) {
+            tab.blocks.push(block);
+        }
+        self.apply_block_meta(tab_index, meta);
+    }
+
+    /// Записывает изменённые метаданные блока в код вкладки через движок
+    /// синхронизации.
+    pub(super) fn apply_block_meta(&mut self, tab_index: usize, meta: VisualMeta) {
+        let delta = delta_from_meta(&meta);
+        if let Some(tab) = self.tabs.get_mut(tab_index) {
+            tab.dirty = true;
+        }
+        if let Some((code, _, _)) = self.sync_engine.handle(SyncMessage::VisualChanged(meta)) {
+            if let Some(tab) = self.tabs.get_mut(tab_index) {
+                tab.content = code.to_string();
+                tab.editor = Content::with_text(&tab.content);
+            }
+        }
+        self.change_tracker.record_visual(delta);
+    }
+
+    /// Метаданные блока `block` вкладки `tab` с промежуточными точками его
+    /// связей и признаком закрепления из записи в коде.
+    pub(super) fn block_meta_with_stored(tab: &Tab, block: &BlockInfo) -> VisualMeta {
+        let mut meta = Self::block_to_meta(block);
+        if let Some(stored) = read_all(&tab.content)
+            .into_iter()
+            .find(|m| m.id == block.visual_id)
+        {
+            waypoints::copy(&stored, &mut meta);
+            pin::set_pinned(&mut meta, pin::is_pinned(&stored));
+        }
+        meta
+    }
+
+    /// Переносит блок `index` активной вкладки в `position`, сохраняя
+    /// промежуточные точки его связей.
+    pub(super) fn move_block(&mut self, index: usize, position: Point) {
+        let Some(i) = self.active_tab else {
+            return;
+        };
+        let meta = self.tabs.get_mut(i).and_then(|tab| {
+            let block = tab.blocks.get_mut(index)?;
+            block.x = position.x as f64;
+            block.y = position.y as f64;
+            let block = &tab.blocks[index];
+            Some(Self::block_meta_with_stored(tab, block))
+        });
+        if let Some(meta) = meta {
+            self.apply_block_meta(i, meta);
+        }
+    }
+
+    /// Задаёт размер блока `index` активной вкладки.
+    pub(super) fn resize_block(&mut self, index: usize, size: Size) {
+        let Some(i) = self.active_tab else {
+            return;
+        };
+        let meta = self.tabs.get_mut(i).and_then(|tab| {
+            let block = tab.blocks.get_mut(index)?;
+            block.width = Some(size.width.round() as f64);
+            block.height = Some(size.height.round() as f64);
+            let block = &tab.blocks[index];
+            Some(Self::block_meta_with_stored(tab, block))
+        });
+        if let Some(meta) = meta {
+            self.apply_block_meta(i, meta);
+        }
+    }
+
+    /// Записывает промежуточные точки связи `connection` активной вкладки в
+    /// метаданные блока-источника.
+    pub(super) fn set_connection_waypoints(&mut self, connection: usize, points: Vec<Point>) {
+        let Some(i) = self.active_tab else {
+            return;
+        };
+        let meta = self.tabs.get(i).and_then(|tab| {
+            let conn = tab.connections.get(connection)?;
+            let from = tab.blocks.get(conn.from.0)?;
+            let to = tab.blocks.get(conn.to.0)?;
+            let mut meta = Self::block_meta_with_stored(tab, from);
+            let points: Vec<(f64, f64)> = points.iter().map(|p| (p.x as f64, p.y as f64)).collect();
+            waypoints::set_waypoints(&mut meta, &to.visual_id, &points);
+            Some(meta)
+        });
+        if let Some(meta) = meta {
+            self.apply_block_meta(i, meta);
+        }
+    }
+
+    /// Закрепляет выбранный на холсте блок или снимает закрепление.
+    pub(super) fn toggle_block_pin(&mut self) {
+        let (Some(i), Some(index)) = (self.active_tab, self.canvas_focus) else {
+            return;
+        };
+        let meta = self.tabs.get(i).and_then(|tab| {
+            let block = tab.blocks.get(index)?;
+            let mut meta = Self::block_meta_with_stored(tab, block);
+            let pinned = pin::is_pinned(&meta);
+            pin::set_pinned(&mut meta, !pinned);
+            Some(meta)
+        });
+        if let Some(meta) = meta {
+            self.apply_block_meta(i, meta);
+        }
+    }
+
+    /// Раскладывает блоки активной вкладки по сетке, не трогая
+    /// замороженные.
+    pub(super) fn auto_layout_blocks(&mut self) {
+        let Some(i) = self.active_tab else {
+            return;
+        };
+        let Some(tab) = self.tabs.get(i) else {
+            return;
+        };
+        let frozen = Self::tab_frozen(tab);
+        let cell = (
+            f64::from(BLOCK_WIDTH + LAYOUT_GAP),
+            f64::from(BLOCK_HEIGHT + LAYOUT_GAP),
+        );
+        let positions = layout::auto_layout(&tab.blocks, &frozen, cell);
+        let metas: Vec<VisualMeta> = positions
+            .into_iter()
+            .enumerate()
+            .filter(|&(index, (x, y))| {
+                let block = &tab.blocks[index];
+                block.x != x || block.y != y
+            })
+            .map(|(index, (x, y))| {
+                let mut block = tab.blocks[index].clone();
+                block.x = x;
+                block.y = y;
+                Self::block_meta_with_stored(tab, &block)
+            })
+            .collect();
+        if let Some(tab) = self.tabs.get_mut(i) {
+            for meta in &metas {
+                if let Some(block) = tab.blocks.iter_mut().find(|b| b.visual_id == meta.id) {
+                    block.x = meta.x;
+                    block.y = meta.y;
+                }
+            }
+        }
+        for meta in metas {
+            self.apply_block_meta(i, meta);
+        }
+    }
+
+    /// Заморожены ли блоки вкладки `tab`: закреплены сами или вложены в
+    /// закреплённый блок.
+    pub(super) fn tab_frozen(tab: &Tab) -> Vec<bool> {
+        let pinned = pin::pinned_ids(&read_all(&tab.content));
+        if pinned.is_empty() {
+            return vec![false; tab.blocks.len()];
+        }
+        layout::frozen(&tab.blocks, &pinned)
+    }
+
+    /// Промежуточные точки каждой связи вкладки `tab` из метаданных
+    /// блоков-источников.
+    pub(super) fn tab_waypoints(tab: &Tab) -> Vec<Vec<Point>> {
+        if tab.connections.is_empty() {
+            return Vec::new();
+        }
+        let metas: HashMap<String, VisualMeta> = read_all(&tab.content)
+            .into_iter()
+            .map(|m| (m.id.clone(), m))
+            .collect();
+        tab.connections
+            .iter()
+            .map(|c| {
+                let from = tab
+                    .blocks
+                    .get(c.from.0)
+                    .and_then(|b| metas.get(&b.visual_id));
+                let to = tab.blocks.get(c.to.0);
+                let (Some(from), Some(to)) = (from, to) else {
+                    return Vec::new();
+                };
+                waypoints::waypoints(from, &to.visual_id)
+                    .into_iter()
+                    .map(|(x, y)| Point::new(x as f32, y as f32))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Группы блоков вкладки `tab` по признаку из настроек с учётом
+    /// свёрнутых пользователем.
+    pub(super) fn tab_lanes(&self, tab: &Tab) -> Vec<Lane> {
+        let file = tab
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut lanes = lanes(&tab.blocks, self.settings.canvas_lanes, &file);
+        for lane in &mut lanes {
+            lane.collapsed = self.collapsed_lanes.contains(&lane.key);
+        }
+        lanes
+    }
+
+    /// Открывает редактор метки блока `index` активной вкладки.
+    pub(super) fn open_label_editor(&mut self, index: usize) {
+        let lang = self.settings.language.code();
+        let label = self
+            .current_file()
+            .and_then(|tab| tab.blocks.get(index))
+            .map(|b| b.translations.get(lang).cloned().unwrap_or_else(|| b.kind.clone()));
+        if let Some(label) = label {
+            self.label_edit = Some((index, label));
+        }
+    }
+
+    /// Сохраняет метку из редактора на текущем языке интерфейса.
+    pub(super) fn save_label(&mut self) {
+        let (Some((index, label)), Some(i)) = (self.label_edit.take(), self.active_tab) else {
+            return;
+        };
+        let lang = self.settings.language.code().to_string();
+        let meta = self.tabs.get_mut(i).and_then(|tab| {
+            let block = tab.blocks.get_mut(index)?;
+            block.translations.insert(lang, label.trim().to_string());
+            Some(Self::block_to_meta(block))
+        });
+        if let Some(meta) = meta {
+            self.apply_block_meta(i, meta);
+        }
+    }
+
+    /// Открывает диалог метаданных для блока `index` активной вкладки.
+    pub(super) fn open_block_meta(&mut self, index: usize) -> Command<Message> {
+        let Some(tab) = self.current_file_mut() else {
+            return Command::none();
+        };
+        let Some(block) = tab.blocks.get(index) else {
+            return Command::none();
+        };
+        let meta = read_all(&tab.content)
+            .into_iter()
+            .find(|m| m.id == block.visual_id)
+            .unwrap_or_else(|| Self::block_to_meta(block));
+        tab.meta = Some(meta);
+        self.handle_message(Message::ShowMetaDialog)
+    }
+}
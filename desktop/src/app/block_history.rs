@@ -0,0 +1,82 @@
+use chrono::DateTime;
+use iced::Command;
+use multicode_core::git::{self, MetaChange, MetaHistoryEntry};
+
+use super::events::Message;
+use super::MulticodeApp;
+
+/// Краткое описание изменения для панели истории блока.
+pub(super) fn describe_change(change: &MetaChange) -> String {
+    match change {
+        MetaChange::Added => "метаданные добавлены".into(),
+        MetaChange::Removed => "метаданные удалены".into(),
+        MetaChange::Moved { from, to } => format!(
+            "перемещён: ({:.0}, {:.0}) → ({:.0}, {:.0})",
+            from.0, from.1, to.0, to.1
+        ),
+        MetaChange::Tags { added, removed } => {
+            let mut parts = Vec::new();
+            if !added.is_empty() {
+                parts.push(format!("+{}", added.join(", +")));
+            }
+            if !removed.is_empty() {
+                parts.push(format!("-{}", removed.join(", -")));
+            }
+            format!("теги: {}", parts.join(" "))
+        }
+        MetaChange::Note { to, .. } => match to {
+            Some(note) => format!("заметка: {note}"),
+            None => "заметка удалена".into(),
+        },
+    }
+}
+
+/// Заголовок точки истории: коммит, дата, автор и сообщение.
+pub(super) fn entry_title(entry: &MetaHistoryEntry) -> String {
+    let date = DateTime::from_timestamp(entry.time, 0)
+        .map(|d| d.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    format!(
+        "{} {} {}: {}",
+        entry.commit, date, entry.author, entry.summary
+    )
+}
+
+impl MulticodeApp {
+    /// Загружает историю метаданных блока в фокусе холста.
+    pub(super) fn load_block_history(&mut self) -> Command<Message> {
+        let target = self.current_file().and_then(|tab| {
+            let block = tab.blocks.get(self.canvas_focus?)?;
+            Some((tab.path.clone(), block.visual_id.clone()))
+        });
+        let Some((path, id)) = target else {
+            self.block_history.clear();
+            self.block_history_id = None;
+            return Command::none();
+        };
+        self.block_history_id = Some(id.clone());
+        Command::perform(
+            async move { git::meta_history(&path, &id).map_err(|e| e.to_string()) },
+            Message::BlockHistoryLoaded,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_tag_and_move_changes() {
+        let tags = MetaChange::Tags {
+            added: vec!["net".into()],
+            removed: vec!["old".into(), "tmp".into()],
+        };
+        assert_eq!(describe_change(&tags), "теги: +net -old, -tmp");
+        let moved = MetaChange::Moved {
+            from: (0.0, 10.0),
+            to: (40.5, 10.0),
+        };
+        assert_eq!(describe_change(&moved), "перемещён: (0, 10) → (40, 10)");
+    }
+}
@@ -0,0 +1,133 @@
+//! Задачи сборки в палитре команд.
+//!
+//! Задачи из `.multicode/tasks.toml` проекта ([`BuildTasks`]) появляются в
+//! палитре как команды `task:<название>`. Ошибки, которые сопоставители
+//! нашли в выводе задачи, становятся замечаниями открытых вкладок: они
+//! видны в панели проверки и подчёркиваются в редакторе. Замечания
+//! последнего запуска сохраняются до следующего запуска любой задачи и
+//! добавляются к вкладкам, открытым позже.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::Utc;
+use iced::Command;
+use multicode_core::build_tasks::{self, BuildTasks, Problem, TaskRun};
+use tokio::task;
+
+use super::command_palette::{CommandAction, CustomCommand};
+use super::events::Message;
+use super::{Diagnostic, Language, LogEntry, LogMessage, MulticodeApp};
+
+/// Префикс идентификаторов команд задач сборки.
+pub(super) const TASK_COMMAND_PREFIX: &str = "task:";
+
+/// Замечание редактора по ошибке `problem` в строке файла `content`.
+/// Подчёркивается слово, с которого начинается ошибка, а если столбец
+/// указывает за конец строки — вся строка.
+fn problem_diagnostic(problem: &Problem, content: &str) -> Diagnostic {
+    let line = problem.line.saturating_sub(1);
+    let text = content.lines().nth(line).unwrap_or_default();
+    let start = text
+        .char_indices()
+        .nth(problem.column.saturating_sub(1))
+        .map_or(text.len(), |(i, _)| i);
+    let end = text[start..]
+        .find(char::is_whitespace)
+        .map_or(text.len(), |n| start + n);
+    let range = if start < end {
+        start..end
+    } else {
+        text.len() - text.trim_start().len()..text.len()
+    };
+    Diagnostic {
+        line,
+        range,
+        message: format!("{}: {}", problem.severity.as_str(), problem.message),
+        fixes: Vec::new(),
+        block: None,
+    }
+}
+
+impl MulticodeApp {
+    /// Загружает задачи сборки проекта.
+    pub(super) fn load_build_tasks(&mut self) {
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        match BuildTasks::load(&root) {
+            Ok(tasks) => self.build_tasks = tasks,
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::BuildTasksError,
+                vec![e.to_string()],
+                Utc::now(),
+            )),
+        }
+    }
+
+    /// Команды палитры, запускающие задачи сборки.
+    pub(super) fn build_task_commands(&self) -> Vec<CustomCommand> {
+        self.build_tasks
+            .tasks
+            .iter()
+            .map(|t| CustomCommand {
+                id: format!("{TASK_COMMAND_PREFIX}{}", t.name),
+                name: format!("Задача: {}", t.name),
+                names: HashMap::from([(Language::English, format!("Task: {}", t.name))]),
+                description: t.command.clone(),
+                descriptions: HashMap::new(),
+                hotkey: String::new(),
+                action: CommandAction::Task(t.name.clone()),
+            })
+            .collect()
+    }
+
+    /// Запускает задачу сборки `name` в фоне.
+    pub(super) fn run_build_task(&mut self, name: &str) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let Some(build_task) = self.build_tasks.get(name).cloned() else {
+            self.notify(LogEntry::new(
+                LogMessage::UnknownCommand,
+                vec![format!("{TASK_COMMAND_PREFIX}{name}")],
+                Utc::now(),
+            ));
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || build_tasks::run(&root, &build_task))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r.map_err(|e| e.to_string()))
+            },
+            Message::BuildTaskFinished,
+        )
+    }
+
+    /// Запоминает ошибки завершившейся задачи и обновляет замечания
+    /// открытых вкладок.
+    pub(super) fn build_task_finished(&mut self, run: TaskRun) {
+        self.notify(LogEntry::new(
+            LogMessage::BuildTaskFinished,
+            vec![run.task, run.problems.len().to_string()],
+            Utc::now(),
+        ));
+        self.build_problems = run.problems;
+        self.refresh_project_diagnostics();
+    }
+
+    /// Замечания файла `path` с содержимым `content` по ошибкам последнего
+    /// запуска задачи сборки.
+    pub(super) fn build_diagnostics(&self, path: &Path, content: &str) -> Vec<Diagnostic> {
+        let Some(root) = self.current_root_path() else {
+            return Vec::new();
+        };
+        self.build_problems
+            .iter()
+            .filter(|p| root.join(&p.file) == path)
+            .map(|p| problem_diagnostic(p, content))
+            .collect()
+    }
+}
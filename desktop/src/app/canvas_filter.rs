@@ -0,0 +1,95 @@
+//! Строка фильтра над холстом.
+//!
+//! Фильтр затеняет на холсте блоки активной вкладки, вид, подпись и теги
+//! которых не подходят строке, или скрывает их совсем. Кнопки и `Enter`
+//! переходят по совпадениям, показывая текущее на холсте.
+
+use iced::widget::{button, checkbox, row, text, text_input, Space};
+use iced::{Element, Length};
+
+use super::events::Message;
+use super::{MulticodeApp, Tab};
+use crate::visual::filter::{self, CanvasFilter};
+
+impl MulticodeApp {
+    /// Фильтр холста для вкладки `tab`; `None`, если строка фильтра скрыта
+    /// или пуста.
+    pub(super) fn tab_canvas_filter(&self, tab: &Tab) -> Option<CanvasFilter> {
+        if !self.show_canvas_filter {
+            return None;
+        }
+        let matches = filter::matches(&tab.blocks, &self.canvas_filter, self.settings.language)?;
+        Some(CanvasFilter {
+            matches,
+            hide: self.canvas_filter_hide,
+            current: self.canvas_filter_current,
+        })
+    }
+
+    pub(super) fn toggle_canvas_filter(&mut self) {
+        self.show_canvas_filter = !self.show_canvas_filter;
+        if !self.show_canvas_filter {
+            self.canvas_filter.clear();
+            self.canvas_filter_current = None;
+        }
+    }
+
+    pub(super) fn canvas_filter_changed(&mut self, value: String) {
+        self.canvas_filter = value;
+        self.canvas_filter_current = None;
+    }
+
+    /// Переходит к следующему или предыдущему совпадению фильтра и
+    /// выбирает его блок.
+    pub(super) fn step_canvas_filter(&mut self, forward: bool) {
+        let Some(tab) = self.current_file() else {
+            return;
+        };
+        let Some(matches) =
+            filter::matches(&tab.blocks, &self.canvas_filter, self.settings.language)
+        else {
+            return;
+        };
+        let next = filter::step(&matches, self.canvas_filter_current, forward);
+        self.canvas_filter_current = next;
+        if next.is_some() {
+            self.canvas_focus = next;
+        }
+    }
+
+    pub fn canvas_filter_component(&self) -> Element<'_, Message> {
+        if !self.show_canvas_filter {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let found = self
+            .current_file()
+            .and_then(|tab| {
+                filter::matches(&tab.blocks, &self.canvas_filter, self.settings.language)
+            })
+            .map(|m| {
+                let total = m.iter().filter(|&&b| b).count();
+                match self.canvas_filter_current {
+                    Some(i) if m.get(i).copied().unwrap_or(false) => {
+                        let n = m[..=i].iter().filter(|&&b| b).count();
+                        format!("{n}/{total}")
+                    }
+                    _ => total.to_string(),
+                }
+            })
+            .unwrap_or_default();
+        row![
+            text_input("Вид, подпись или тег", &self.canvas_filter)
+                .on_input(Message::CanvasFilterChanged)
+                .on_submit(Message::CanvasFilterStep(true))
+                .width(Length::Fill),
+            button("◀").on_press(Message::CanvasFilterStep(false)),
+            button("▶").on_press(Message::CanvasFilterStep(true)),
+            text(found),
+            checkbox("Скрывать остальные", self.canvas_filter_hide)
+                .on_toggle(Message::ToggleCanvasFilterHide),
+            button("×").on_press(Message::ToggleCanvasFilter),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,102 @@
+//! Слои холста.
+//!
+//! Кнопки над холстом показывают и скрывают его слои. Видимость слоёв
+//! запоминается в настройках отдельно для каждого проекта. Щелчок по метке
+//! замечаний блока выбирает его первое замечание в панели проверки.
+
+use iced::widget::{button, row, scrollable, text};
+use iced::{theme, Command, Element};
+
+use super::events::Message;
+use super::{MulticodeApp, Tab};
+use crate::visual::layers::{lint_by_block, CanvasLayers, Layer};
+
+/// Прокрутка панели проверки.
+pub(super) const LINT_SCROLL: &str = "lint-panel";
+
+impl MulticodeApp {
+    /// Видимые слои холста текущего проекта.
+    pub(super) fn canvas_layers(&self) -> CanvasLayers {
+        self.current_root_path()
+            .and_then(|root| self.settings.canvas_layers.get(&root).copied())
+            .unwrap_or_default()
+    }
+
+    /// Показывает или скрывает слой `layer` текущего проекта и сохраняет
+    /// настройки. Проект, у которого видны все слои, из настроек удаляется.
+    pub(super) fn toggle_canvas_layer(&mut self, layer: Layer) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let mut layers = self.canvas_layers();
+        layers.toggle(layer);
+        if layers == CanvasLayers::default() {
+            self.settings.canvas_layers.remove(&root);
+        } else {
+            self.settings.canvas_layers.insert(root, layers);
+        }
+        Command::perform(self.settings.clone().save(), |_| Message::SettingsSaved)
+    }
+
+    /// Номера замечаний проверки вкладки `tab`, приходящихся на каждый её
+    /// блок.
+    pub(super) fn tab_lint(tab: &Tab) -> Vec<Vec<usize>> {
+        let findings: Vec<(Option<&str>, usize, usize)> = tab
+            .diagnostics
+            .iter()
+            .map(|d| (d.block.as_deref(), d.line, d.range.start))
+            .collect();
+        lint_by_block(&tab.blocks, &tab.content, &findings)
+    }
+
+    /// Сообщения замечаний проверки у каждого блока вкладки `tab`.
+    pub(super) fn tab_lint_messages(tab: &Tab) -> Vec<Vec<String>> {
+        Self::tab_lint(tab)
+            .into_iter()
+            .map(|found| {
+                found
+                    .into_iter()
+                    .map(|d| tab.diagnostics[d].message.clone())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Выбирает в панели проверки первое замечание блока `index` активной
+    /// вкладки и прокручивает панель к нему.
+    pub(super) fn focus_block_lint(&mut self, index: usize) -> Command<Message> {
+        let Some(tab) = self.current_file() else {
+            return Command::none();
+        };
+        let total = tab.diagnostics.len();
+        let Some(&first) = Self::tab_lint(tab).get(index).and_then(|f| f.first()) else {
+            return Command::none();
+        };
+        self.focused_diagnostic = Some(first);
+        let y = if total > 1 {
+            first as f32 / (total - 1) as f32
+        } else {
+            0.0
+        };
+        scrollable::snap_to(
+            scrollable::Id::new(LINT_SCROLL),
+            scrollable::RelativeOffset { x: 0.0, y },
+        )
+    }
+
+    pub fn canvas_layers_component(&self) -> Element<'_, Message> {
+        let layers = self.canvas_layers();
+        let buttons = Layer::ALL.into_iter().map(|layer| {
+            let style = if layers.shows(layer) {
+                theme::Button::Primary
+            } else {
+                theme::Button::Secondary
+            };
+            button(text(layer.to_string()))
+                .style(style)
+                .on_press(Message::ToggleCanvasLayer(layer))
+                .into()
+        });
+        row(buttons).spacing(5).into()
+    }
+}
@@ -0,0 +1,92 @@
+//! Отдельное окно холста.
+//!
+//! Схему можно вынести в своё окно системы, например на второй монитор.
+//! Окно показывает холст активной вкладки главного окна и работает с тем
+//! же состоянием приложения: правки блоков в нём проходят через
+//! [`SyncEngine`](crate::sync::SyncEngine) так же, как на холсте главного
+//! окна, а смена вкладки или правка текста сразу видна на схеме. Пока окно
+//! открыто, главное окно вместо холста показывает кнопку возврата схемы.
+
+use iced::widget::{button, column, container, row, text};
+use iced::{window, Command, Element, Length, Size};
+
+use super::events::Message;
+use super::MulticodeApp;
+
+/// Размер окна холста при открытии.
+const CANVAS_WINDOW_SIZE: Size = Size::new(960.0, 720.0);
+
+impl MulticodeApp {
+    pub fn canvas_window(&self) -> Option<window::Id> {
+        self.canvas_window
+    }
+
+    /// Открывает окно холста или закрывает уже открытое.
+    pub(super) fn toggle_canvas_window(&mut self) -> Command<Message> {
+        if let Some(id) = self.canvas_window.take() {
+            return window::close(id);
+        }
+        let (id, spawn) = window::spawn(window::Settings {
+            size: CANVAS_WINDOW_SIZE,
+            ..window::Settings::default()
+        });
+        self.canvas_window = Some(id);
+        spawn
+    }
+
+    /// Учитывает закрытие окна `id`. Вместе с главным окном закрывается и
+    /// окно холста, иначе приложение продолжило бы работать без него.
+    pub(super) fn window_closed(&mut self, id: window::Id) -> Command<Message> {
+        if self.canvas_window == Some(id) {
+            self.canvas_window = None;
+            Command::none()
+        } else if id == window::Id::MAIN {
+            self.canvas_window
+                .take()
+                .map_or(Command::none(), window::close)
+        } else {
+            Command::none()
+        }
+    }
+
+    pub(super) fn canvas_window_title(&self) -> String {
+        match self.current_file().and_then(|f| f.path.file_name()) {
+            Some(name) => format!("Multicode Desktop — схема {}", name.to_string_lossy()),
+            None => String::from("Multicode Desktop — схема"),
+        }
+    }
+
+    /// Содержимое окна холста.
+    pub(super) fn canvas_window_view(&self) -> Element<'_, Message> {
+        let title = self
+            .current_file()
+            .map(|f| self.tab_title(f))
+            .unwrap_or_else(|| "Файл не открыт".into());
+        let header = row![
+            text(title).width(Length::Fill),
+            button("Вернуть в главное окно").on_press(Message::ToggleCanvasWindow)
+        ]
+        .spacing(10);
+        column![header, self.visual_editor_for(self.active_tab)]
+            .spacing(5)
+            .padding(5)
+            .into()
+    }
+
+    /// Заглушка на месте холста главного окна, пока схема вынесена.
+    pub(super) fn detached_canvas_placeholder(&self) -> Element<'_, Message> {
+        container(
+            column![
+                text("Схема открыта в отдельном окне"),
+                button("Вернуть в главное окно").on_press(Message::ToggleCanvasWindow)
+            ]
+            .spacing(10)
+            .align_items(iced::Alignment::Center),
+        )
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .center_x()
+        .center_y()
+        .into()
+    }
+}
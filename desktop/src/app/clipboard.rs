@@ -0,0 +1,129 @@
+use iced::widget::text_editor::Content;
+use multicode_core::blocks::BlockLocator;
+use multicode_core::meta::clipboard::{copy_blocks, paste_blocks, BlockClipboard};
+use multicode_core::BlockInfo;
+
+use super::events::handler::push_with_limit;
+use super::navigation::{block_at_offset, line_of_offset, offset_of_position};
+use super::MulticodeApp;
+
+/// Байтовый диапазон выделенного текста `selection`, примыкающего к
+/// курсору в позиции `cursor` с той или другой стороны.
+pub fn selection_range(content: &str, cursor: usize, selection: &str) -> Option<(usize, usize)> {
+    let len = selection.len();
+    if len == 0 {
+        return None;
+    }
+    if cursor >= len && content.get(cursor - len..cursor) == Some(selection) {
+        return Some((cursor - len, cursor));
+    }
+    if content.get(cursor..cursor + len) == Some(selection) {
+        return Some((cursor, cursor + len));
+    }
+    None
+}
+
+/// Идентификаторы блоков, целиком попадающих в `range`.
+pub fn blocks_in_range(blocks: &[BlockInfo], range: (usize, usize)) -> Vec<String> {
+    BlockLocator::new(blocks)
+        .blocks_in_range(range.0..range.1)
+        .into_iter()
+        .map(|i| blocks[i].visual_id.clone())
+        .collect()
+}
+
+impl MulticodeApp {
+    /// Копирует блоки из выделения активной вкладки во внутренний буфер.
+    ///
+    /// Без выделения копируется блок под курсором. Возвращает количество
+    /// скопированных записей метаданных.
+    pub(super) fn copy_blocks_at_cursor(&mut self) -> Option<usize> {
+        let tab = self.current_file()?;
+        let (line, column) = tab.editor.cursor_position();
+        let cursor = offset_of_position(&tab.content, line, column);
+        let mut ids = tab
+            .editor
+            .selection()
+            .and_then(|s| selection_range(&tab.content, cursor, &s))
+            .map(|range| blocks_in_range(&tab.blocks, range))
+            .unwrap_or_default();
+        if ids.is_empty() {
+            let i = block_at_offset(&tab.blocks, cursor)?;
+            ids.push(tab.blocks[i].visual_id.clone());
+        }
+        let clip = copy_blocks(&tab.content, &tab.blocks, &ids)?;
+        let count = clip.metas.len();
+        self.block_clipboard = Some(clip);
+        Some(count)
+    }
+
+    /// Вставляет содержимое буфера блоков в позицию курсора активной вкладки.
+    ///
+    /// Вставленные метаданные получают новые идентификаторы. Возвращает
+    /// индекс изменённой вкладки и количество вставленных записей.
+    pub(super) fn paste_blocks_at_cursor(&mut self) -> Option<(usize, usize)> {
+        let i = self.active_tab?;
+        let clip = self.block_clipboard.clone()?;
+        let tab = self.tabs.get_mut(i)?;
+        let (line, column) = tab.editor.cursor_position();
+        let cursor = offset_of_position(&tab.content, line, column);
+        let (content, ids) = paste_blocks(&tab.content, cursor, &clip);
+        push_with_limit(&mut tab.undo_stack, tab.content.clone());
+        tab.redo_stack.clear();
+        // Новые комментарии метаданных добавляются в начало файла,
+        // по одной строке на запись.
+        let target = line_of_offset(&tab.content, cursor) + ids.len();
+        tab.content = content;
+        tab.editor = Content::with_text(&tab.content);
+        tab.dirty = true;
+        self.move_cursor_to(target, column);
+        Some((i, ids.len()))
+    }
+
+    pub fn block_clipboard(&self) -> Option<&BlockClipboard> {
+        self.block_clipboard.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn block(id: &str, range: (usize, usize)) -> BlockInfo {
+        BlockInfo {
+            visual_id: id.into(),
+            node_id: None,
+            kind: "Function".into(),
+            translations: HashMap::new(),
+            range,
+            anchors: vec![],
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            ports: vec![],
+            ai: None,
+            tags: vec![],
+            links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
+        }
+    }
+
+    #[test]
+    fn selection_on_either_side_of_cursor() {
+        let content = "fn a() {}\nfn b() {}\n";
+        assert_eq!(selection_range(content, 9, "fn a() {}"), Some((0, 9)));
+        assert_eq!(selection_range(content, 0, "fn a() {}"), Some((0, 9)));
+        assert_eq!(selection_range(content, 5, "zzz"), None);
+    }
+
+    #[test]
+    fn only_whole_blocks_are_selected() {
+        let blocks = vec![block("a", (0, 9)), block("b", (10, 19))];
+        assert_eq!(blocks_in_range(&blocks, (0, 12)), vec!["a".to_string()]);
+        assert!(blocks_in_range(&blocks, (2, 12)).is_empty());
+    }
+}
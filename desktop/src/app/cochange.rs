@@ -0,0 +1,40 @@
+use iced::Command;
+use multicode_core::cochange::{analyze, BlockRef, CoChangeOptions};
+
+use super::events::Message;
+use super::MulticodeApp;
+
+/// Подпись блока в панели совместных изменений.
+pub(super) fn block_label(block: &BlockRef) -> String {
+    format!(
+        "{}:{} {}",
+        block.file.display(),
+        block.line + 1,
+        block.label
+    )
+}
+
+impl MulticodeApp {
+    /// Запускает анализ совместных изменений по истории git проекта.
+    pub(super) fn analyze_cochange(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        self.loading = true;
+        Command::perform(
+            async move { analyze(&root, &CoChangeOptions::default()).map_err(|e| e.to_string()) },
+            Message::CoChangeAnalyzed,
+        )
+    }
+
+    /// Открывает блок из отчёта о совместных изменениях.
+    pub(super) fn open_cochange_block(&mut self, block: BlockRef) -> Command<Message> {
+        let Some(root) = self.cochange.as_ref().map(|r| r.root.clone()) else {
+            return Command::none();
+        };
+        self.handle_message(Message::OpenSearchResult(
+            root.join(&block.file),
+            block.line,
+        ))
+    }
+}
@@ -0,0 +1,67 @@
+//! Метрики блока под курсором для панели метаданных.
+//!
+//! Метрики считаются для всех блоков вкладки один раз на версию разбора.
+//! Панель показывает метрики функции, в которой стоит курсор, а вне
+//! функций — самого вложенного блока с метриками.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use multicode_core::analysis::metrics::{block_metrics, BlockMetrics};
+use multicode_core::blocks::BlockLocator;
+
+use super::navigation::offset_of_position;
+use super::{MulticodeApp, Tab};
+
+/// Метрики блоков по файлам вместе с версией разбора, при которой они
+/// посчитаны.
+pub(super) type BlockMetricsCache = HashMap<PathBuf, (u64, Vec<Option<BlockMetrics>>)>;
+
+const FUNCTION: &str = "Function/Define";
+
+impl MulticodeApp {
+    /// Метрики блоков вкладки `tab` в порядке `tab.blocks`.
+    fn tab_block_metrics(&self, tab: &Tab) -> Vec<Option<BlockMetrics>> {
+        let mut cache = self.block_metrics.borrow_mut();
+        if let Some((version, metrics)) = cache.get(&tab.path) {
+            if *version == tab.analysis_version {
+                return metrics.clone();
+            }
+        }
+        let metrics = match tab.lang {
+            Some(lang) => block_metrics(&tab.content, lang, &tab.blocks),
+            None => vec![None; tab.blocks.len()],
+        };
+        cache.insert(tab.path.clone(), (tab.analysis_version, metrics.clone()));
+        metrics
+    }
+
+    /// Метрики функции под курсором вкладки `tab`, а если курсор вне
+    /// функций — самого вложенного блока под курсором.
+    pub(super) fn cursor_metrics(&self, tab: &Tab) -> Option<BlockMetrics> {
+        let (line, column) = tab.editor.cursor_position();
+        let cursor = offset_of_position(&tab.content, line, column);
+        let metrics = self.tab_block_metrics(tab);
+        let locator = BlockLocator::new(&tab.blocks);
+        let mut innermost = None;
+        let mut current = locator.block_at(cursor);
+        while let Some(i) = current {
+            if let Some(m) = metrics[i] {
+                if tab.blocks[i].kind == FUNCTION {
+                    return Some(m);
+                }
+                innermost = innermost.or(Some(m));
+            }
+            current = locator.parent(i);
+        }
+        innermost
+    }
+}
+
+/// Строка метрик для панели метаданных.
+pub fn metrics_label(metrics: &BlockMetrics) -> String {
+    format!(
+        "Метрики: строк {}, сложность {}, вложенность {}, параметров {}",
+        metrics.loc, metrics.complexity, metrics.depth, metrics.params
+    )
+}
@@ -1,3 +1,11 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use super::Language;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CommandCategory {
     File,
@@ -54,8 +62,286 @@ pub const COMMANDS: &[CommandItem] = &[
         category: CommandCategory::View,
         hotkey: "Ctrl+3",
     },
+    CommandItem {
+        id: "toggle_bookmark",
+        category: CommandCategory::Navigation,
+        hotkey: "Ctrl+Alt+B",
+    },
+    CommandItem {
+        id: "next_bookmark",
+        category: CommandCategory::Navigation,
+        hotkey: "Ctrl+Alt+L",
+    },
+    CommandItem {
+        id: "prev_bookmark",
+        category: CommandCategory::Navigation,
+        hotkey: "Ctrl+Alt+J",
+    },
+    CommandItem {
+        id: "toggle_bookmarks_panel",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+B",
+    },
+    CommandItem {
+        id: "toggle_tags_panel",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+T",
+    },
+    CommandItem {
+        id: "toggle_block_history",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+H",
+    },
+    CommandItem {
+        id: "toggle_inheritance_panel",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+I",
+    },
+    CommandItem {
+        id: "toggle_canvas_filter",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+F",
+    },
+    CommandItem {
+        id: "toggle_block_pin",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+G",
+    },
+    CommandItem {
+        id: "auto_layout_blocks",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+A",
+    },
+    CommandItem {
+        id: "toggle_cochange_panel",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+L",
+    },
+    CommandItem {
+        id: "toggle_notifications",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+N",
+    },
+    CommandItem {
+        id: "toggle_coverage",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+U",
+    },
+    CommandItem {
+        id: "cycle_canvas_profile",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+H",
+    },
+    CommandItem {
+        id: "toggle_breakpoint",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+X",
+    },
+    CommandItem {
+        id: "start_debugging",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+Y",
+    },
+    CommandItem {
+        id: "toggle_test_panel",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+R",
+    },
+    CommandItem {
+        id: "edit_project_env",
+        category: CommandCategory::Settings,
+        hotkey: "Ctrl+Alt+E",
+    },
+    CommandItem {
+        id: "describe_block_ai",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+U",
+    },
+    CommandItem {
+        id: "regenerate_summaries",
+        category: CommandCategory::File,
+        hotkey: "Ctrl+Alt+W",
+    },
+    CommandItem {
+        id: "find_dead_code",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+X",
+    },
+    CommandItem {
+        id: "organize_imports",
+        category: CommandCategory::File,
+        hotkey: "Ctrl+Shift+O",
+    },
+    CommandItem {
+        id: "toggle_meta_suggestions",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+Q",
+    },
+    CommandItem {
+        id: "toggle_structural_search",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+Z",
+    },
+    CommandItem {
+        id: "open_task_board",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+K",
+    },
+    CommandItem {
+        id: "open_git_screen",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+G",
+    },
+    CommandItem {
+        id: "navigate_history_back",
+        category: CommandCategory::Navigation,
+        hotkey: "Alt+ArrowLeft",
+    },
+    CommandItem {
+        id: "navigate_history_forward",
+        category: CommandCategory::Navigation,
+        hotkey: "Alt+ArrowRight",
+    },
+    CommandItem {
+        id: "toggle_sync_recording",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+R",
+    },
+    CommandItem {
+        id: "toggle_macro_recording",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+M",
+    },
+    CommandItem {
+        id: "toggle_macros_panel",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+M",
+    },
+    CommandItem {
+        id: "add_cursor_above",
+        category: CommandCategory::Navigation,
+        hotkey: "Ctrl+Alt+ArrowUp",
+    },
+    CommandItem {
+        id: "add_cursor_below",
+        category: CommandCategory::Navigation,
+        hotkey: "Ctrl+Alt+ArrowDown",
+    },
+    CommandItem {
+        id: "toggle_fold",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+[",
+    },
+    CommandItem {
+        id: "fold_all_except_current",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+F",
+    },
+    CommandItem {
+        id: "unfold_all",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+]",
+    },
+    CommandItem {
+        id: "split_pane_right",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+\\",
+    },
+    CommandItem {
+        id: "split_pane_down",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+\\",
+    },
+    CommandItem {
+        id: "unsplit_pane",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Shift+W",
+    },
+    CommandItem {
+        id: "focus_other_pane",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+O",
+    },
+    CommandItem {
+        id: "toggle_canvas_window",
+        category: CommandCategory::View,
+        hotkey: "Ctrl+Alt+D",
+    },
+    CommandItem {
+        id: "goto_symbol",
+        category: CommandCategory::Navigation,
+        hotkey: "Ctrl+T",
+    },
+    CommandItem {
+        id: "recent_blocks",
+        category: CommandCategory::Navigation,
+        hotkey: "Ctrl+Shift+E",
+    },
+    CommandItem {
+        id: "copy_blocks",
+        category: CommandCategory::File,
+        hotkey: "Ctrl+Alt+C",
+    },
+    CommandItem {
+        id: "paste_blocks",
+        category: CommandCategory::File,
+        hotkey: "Ctrl+Alt+V",
+    },
 ];
 
+/// Что делает пользовательская команда палитры.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandAction {
+    /// Выполнить встроенные команды палитры по порядку.
+    Commands(Vec<String>),
+    /// Выполнить команду оболочки во встроенном терминале.
+    Shell(String),
+    /// Запустить команду как хук: событие `command` передаётся в stdin,
+    /// результат попадает в журнал.
+    Hook(String),
+    /// Запустить задачу сборки проекта с этим названием.
+    Task(String),
+}
+
+/// Команда палитры из пользовательских настроек или добавленная плагином.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CustomCommand {
+    pub id: String,
+    /// Название по умолчанию.
+    pub name: String,
+    /// Названия на других языках.
+    #[serde(default)]
+    pub names: HashMap<Language, String>,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub descriptions: HashMap<Language, String>,
+    /// Сочетание клавиш в формате `Ctrl+Shift+X`; пустое — без сочетания.
+    #[serde(default)]
+    pub hotkey: String,
+    pub action: CommandAction,
+}
+
+static PLUGIN_COMMANDS: Lazy<RwLock<Vec<CustomCommand>>> = Lazy::new(|| RwLock::new(Vec::new()));
+
+/// Добавляет команду плагина в палитру. Команда с тем же идентификатором
+/// заменяется.
+pub fn register_command(cmd: CustomCommand) {
+    if let Ok(mut commands) = PLUGIN_COMMANDS.write() {
+        commands.retain(|c| c.id != cmd.id);
+        commands.push(cmd);
+    }
+}
+
+/// Команды, добавленные плагинами.
+pub fn plugin_commands() -> Vec<CustomCommand> {
+    PLUGIN_COMMANDS
+        .read()
+        .map(|commands| commands.clone())
+        .unwrap_or_default()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use once_cell::sync::Lazy;
 
-use super::command_palette::CommandItem;
+use super::command_palette::{CommandItem, CustomCommand};
 use super::Language;
 
 type Translation = (&'static str, &'static str);
@@ -84,6 +84,465 @@ static COMMAND_TRANSLATIONS: Lazy<HashMap<(&'static str, Language), Translation>
                 "Переключиться в режим разделения",
             ),
         );
+        m.insert(
+            ("toggle_bookmark", English),
+            ("Toggle Bookmark", "Add or remove a bookmark on the current block"),
+        );
+        m.insert(
+            ("toggle_bookmark", Russian),
+            ("Закладка", "Поставить или снять закладку на текущем блоке"),
+        );
+        m.insert(
+            ("next_bookmark", English),
+            ("Next Bookmark", "Jump to the next bookmarked block"),
+        );
+        m.insert(
+            ("next_bookmark", Russian),
+            ("Следующая закладка", "Перейти к следующему блоку с закладкой"),
+        );
+        m.insert(
+            ("prev_bookmark", English),
+            ("Previous Bookmark", "Jump to the previous bookmarked block"),
+        );
+        m.insert(
+            ("prev_bookmark", Russian),
+            ("Предыдущая закладка", "Перейти к предыдущему блоку с закладкой"),
+        );
+        m.insert(
+            ("toggle_bookmarks_panel", English),
+            ("Toggle Bookmarks", "Show or hide the bookmarks panel"),
+        );
+        m.insert(
+            ("toggle_bookmarks_panel", Russian),
+            ("Показать/Скрыть закладки", "Показать или скрыть панель закладок"),
+        );
+        m.insert(
+            ("toggle_tags_panel", English),
+            ("Toggle Tags", "Show workspace tags to rename or merge them"),
+        );
+        m.insert(
+            ("toggle_tags_panel", Russian),
+            ("Показать/Скрыть теги", "Показать теги проекта для переименования и слияния"),
+        );
+        m.insert(
+            ("toggle_block_history", English),
+            ("Block History", "Show how the selected block's metadata changed in git"),
+        );
+        m.insert(
+            ("toggle_block_history", Russian),
+            ("История блока", "Показать изменения метаданных выбранного блока в git"),
+        );
+        m.insert(
+            ("toggle_inheritance_panel", English),
+            (
+                "Inheritance",
+                "Show and edit the extends chain of the selected metadata",
+            ),
+        );
+        m.insert(
+            ("toggle_inheritance_panel", Russian),
+            (
+                "Наследование",
+                "Цепочка extends выбранных метаданных и итог слияния",
+            ),
+        );
+        m.insert(
+            ("toggle_canvas_filter", English),
+            (
+                "Canvas Filter",
+                "Dim or hide canvas blocks that do not match a kind, label or tag",
+            ),
+        );
+        m.insert(
+            ("toggle_canvas_filter", Russian),
+            (
+                "Фильтр холста",
+                "Затенить или скрыть блоки холста не подходящего вида, подписи или тега",
+            ),
+        );
+        m.insert(
+            ("toggle_block_pin", English),
+            (
+                "Pin Block",
+                "Keep the selected block in place during auto-layout and code reordering",
+            ),
+        );
+        m.insert(
+            ("toggle_block_pin", Russian),
+            (
+                "Закрепить блок",
+                "Не двигать выбранный блок при раскладке и перестановке кода",
+            ),
+        );
+        m.insert(
+            ("auto_layout_blocks", English),
+            (
+                "Auto Layout",
+                "Arrange unpinned canvas blocks on a grid in code order",
+            ),
+        );
+        m.insert(
+            ("auto_layout_blocks", Russian),
+            (
+                "Разложить блоки",
+                "Расставить незакреплённые блоки холста по сетке в порядке кода",
+            ),
+        );
+        m.insert(
+            ("toggle_cochange_panel", English),
+            ("Co-changes", "Suggest links between blocks that change together in git"),
+        );
+        m.insert(
+            ("toggle_cochange_panel", Russian),
+            ("Совместные изменения", "Предложить связи между блоками, меняющимися вместе"),
+        );
+        m.insert(
+            ("toggle_notifications", English),
+            ("Notifications", "Show save, lint, hook and watcher events"),
+        );
+        m.insert(
+            ("toggle_notifications", Russian),
+            ("Уведомления", "Показать события сохранения, проверки, хуков"),
+        );
+        m.insert(
+            ("toggle_macro_recording", English),
+            ("Record Macro", "Start or stop recording editor actions"),
+        );
+        m.insert(
+            ("toggle_macro_recording", Russian),
+            ("Запись макроса", "Начать или остановить запись действий"),
+        );
+        m.insert(
+            ("toggle_macros_panel", English),
+            ("Macros", "Name, replay and delete recorded macros"),
+        );
+        m.insert(
+            ("toggle_macros_panel", Russian),
+            ("Макросы", "Сохранить, запустить и удалить макросы"),
+        );
+        m.insert(
+            ("add_cursor_above", English),
+            ("Add Cursor Above", "Add a caret on the line above"),
+        );
+        m.insert(
+            ("add_cursor_above", Russian),
+            ("Каретка выше", "Добавить каретку строкой выше"),
+        );
+        m.insert(
+            ("add_cursor_below", English),
+            ("Add Cursor Below", "Add a caret on the line below"),
+        );
+        m.insert(
+            ("add_cursor_below", Russian),
+            ("Каретка ниже", "Добавить каретку строкой ниже"),
+        );
+        m.insert(
+            ("toggle_fold", English),
+            ("Toggle Fold", "Fold or unfold the block under the caret"),
+        );
+        m.insert(
+            ("toggle_fold", Russian),
+            ("Свернуть блок", "Свернуть или развернуть блок под кареткой"),
+        );
+        m.insert(
+            ("fold_all_except_current", English),
+            ("Fold Others", "Fold every block except the selected one"),
+        );
+        m.insert(
+            ("fold_all_except_current", Russian),
+            ("Свернуть остальные", "Свернуть все блоки, кроме выбранного"),
+        );
+        m.insert(
+            ("unfold_all", English),
+            ("Unfold All", "Unfold every block in the file"),
+        );
+        m.insert(
+            ("unfold_all", Russian),
+            ("Развернуть всё", "Развернуть все блоки файла"),
+        );
+        m.insert(
+            ("split_pane_right", English),
+            ("Split Right", "Open a second editor pane to the right"),
+        );
+        m.insert(
+            ("split_pane_right", Russian),
+            ("Разделить вправо", "Открыть вторую панель редактора справа"),
+        );
+        m.insert(
+            ("split_pane_down", English),
+            ("Split Down", "Open a second editor pane below"),
+        );
+        m.insert(
+            ("split_pane_down", Russian),
+            ("Разделить вниз", "Открыть вторую панель редактора снизу"),
+        );
+        m.insert(
+            ("unsplit_pane", English),
+            ("Join Panes", "Close the second editor pane"),
+        );
+        m.insert(
+            ("unsplit_pane", Russian),
+            ("Объединить панели", "Закрыть вторую панель редактора"),
+        );
+        m.insert(
+            ("focus_other_pane", English),
+            ("Other Pane", "Move focus to the other editor pane"),
+        );
+        m.insert(
+            ("focus_other_pane", Russian),
+            ("Другая панель", "Перейти в другую панель редактора"),
+        );
+        m.insert(
+            ("toggle_canvas_window", English),
+            (
+                "Canvas Window",
+                "Move the canvas to a separate window or back",
+            ),
+        );
+        m.insert(
+            ("toggle_canvas_window", Russian),
+            (
+                "Окно схемы",
+                "Вынести холст в отдельное окно или вернуть обратно",
+            ),
+        );
+        m.insert(
+            ("goto_symbol", English),
+            ("Go to Symbol", "Find a function or class in the project"),
+        );
+        m.insert(
+            ("goto_symbol", Russian),
+            ("Перейти к символу", "Найти функцию или класс в проекте"),
+        );
+        m.insert(
+            ("recent_blocks", English),
+            (
+                "Recent Blocks",
+                "Return to a recently edited function or block",
+            ),
+        );
+        m.insert(
+            ("recent_blocks", Russian),
+            (
+                "Недавние блоки",
+                "Вернуться к недавно изменённой функции или блоку",
+            ),
+        );
+        m.insert(
+            ("toggle_coverage", English),
+            ("Test Coverage", "Color canvas blocks by lcov or Cobertura coverage"),
+        );
+        m.insert(
+            ("toggle_coverage", Russian),
+            ("Покрытие тестами", "Раскрасить блоки холста по покрытию lcov или Cobertura"),
+        );
+        m.insert(
+            ("cycle_canvas_profile", English),
+            (
+                "Canvas Profile",
+                "Color canvas blocks by parse time or execution count",
+            ),
+        );
+        m.insert(
+            ("cycle_canvas_profile", Russian),
+            (
+                "Профиль холста",
+                "Раскрасить блоки холста по времени разбора или числу выполнений",
+            ),
+        );
+        m.insert(
+            ("toggle_breakpoint", English),
+            (
+                "Toggle Breakpoint",
+                "Set or clear a breakpoint on the cursor line",
+            ),
+        );
+        m.insert(
+            ("toggle_breakpoint", Russian),
+            (
+                "Точка останова",
+                "Поставить или снять точку останова на строке курсора",
+            ),
+        );
+        m.insert(
+            ("start_debugging", English),
+            (
+                "Start Debugging",
+                "Start a debug session with the project breakpoints",
+            ),
+        );
+        m.insert(
+            ("start_debugging", Russian),
+            (
+                "Запустить отладку",
+                "Запустить отладку с точками останова проекта",
+            ),
+        );
+        m.insert(
+            ("toggle_test_panel", English),
+            (
+                "Test Runner",
+                "Run project tests and show results per block",
+            ),
+        );
+        m.insert(
+            ("toggle_test_panel", Russian),
+            (
+                "Панель тестов",
+                "Запустить тесты проекта и показать итоги по блокам",
+            ),
+        );
+        m.insert(
+            ("edit_project_env", English),
+            (
+                "Project Environment",
+                "Edit environment variables and secrets of the project",
+            ),
+        );
+        m.insert(
+            ("edit_project_env", Russian),
+            (
+                "Окружение проекта",
+                "Изменить переменные окружения и секреты проекта",
+            ),
+        );
+        m.insert(
+            ("describe_block_ai", English),
+            (
+                "Describe Block with AI",
+                "Ask the configured AI model to describe the selected block",
+            ),
+        );
+        m.insert(
+            ("describe_block_ai", Russian),
+            ("Описать блок", "Попросить модель ИИ описать выбранный блок"),
+        );
+        m.insert(
+            ("regenerate_summaries", English),
+            (
+                "Regenerate Summaries",
+                "Rebuild file and module summaries from block descriptions",
+            ),
+        );
+        m.insert(
+            ("regenerate_summaries", Russian),
+            (
+                "Обновить сводки",
+                "Пересоздать сводки файлов и модулей из описаний блоков",
+            ),
+        );
+        m.insert(
+            ("find_dead_code", English),
+            (
+                "Find Dead Code",
+                "Mark unused functions and meta entries without blocks in open files",
+            ),
+        );
+        m.insert(
+            ("find_dead_code", Russian),
+            (
+                "Найти мёртвый код",
+                "Отметить в открытых файлах неиспользуемые функции и записи без блоков",
+            ),
+        );
+        m.insert(
+            ("organize_imports", English),
+            (
+                "Organize Imports",
+                "Sort and group imports of the current file and remove unused ones",
+            ),
+        );
+        m.insert(
+            ("organize_imports", Russian),
+            (
+                "Упорядочить импорты",
+                "Отсортировать и сгруппировать импорты файла и удалить неиспользуемые",
+            ),
+        );
+        m.insert(
+            ("toggle_meta_suggestions", English),
+            (
+                "Suggest Tags and Links",
+                "Show tags and links proposed for blocks without metadata",
+            ),
+        );
+        m.insert(
+            ("toggle_meta_suggestions", Russian),
+            (
+                "Предложить теги и связи",
+                "Показать теги и связи, предложенные для блоков без метаданных",
+            ),
+        );
+        m.insert(
+            ("toggle_structural_search", English),
+            (
+                "Structural Search",
+                "Find code by a pattern with metavariables like foo($A, $$$REST)",
+            ),
+        );
+        m.insert(
+            ("toggle_structural_search", Russian),
+            (
+                "Структурный поиск",
+                "Найти код по шаблону с метапеременными вроде foo($A, $$$REST)",
+            ),
+        );
+        m.insert(
+            ("open_task_board", English),
+            ("Task Board", "Show TODO/FIXME comments and todo blocks grouped by tag or status"),
+        );
+        m.insert(
+            ("open_task_board", Russian),
+            ("Доска задач", "Показать TODO/FIXME и блоки todo по тегам или статусам"),
+        );
+        m.insert(
+            ("open_git_screen", English),
+            ("Git", "Stage changes by hunk and commit"),
+        );
+        m.insert(
+            ("open_git_screen", Russian),
+            ("Git", "Добавить изменения в индекс по участкам и сделать коммит"),
+        );
+        m.insert(
+            ("navigate_history_back", English),
+            ("Go Back", "Return to the previous location"),
+        );
+        m.insert(
+            ("navigate_history_back", Russian),
+            ("Назад", "Вернуться к предыдущей позиции"),
+        );
+        m.insert(
+            ("navigate_history_forward", English),
+            ("Go Forward", "Go to the next location in history"),
+        );
+        m.insert(
+            ("navigate_history_forward", Russian),
+            ("Вперёд", "Перейти к следующей позиции в истории"),
+        );
+        m.insert(
+            ("toggle_sync_recording", English),
+            ("Record Sync Session", "Start or stop recording synchronization messages"),
+        );
+        m.insert(
+            ("toggle_sync_recording", Russian),
+            ("Запись синхронизации", "Начать или остановить запись сообщений синхронизации"),
+        );
+        m.insert(
+            ("copy_blocks", English),
+            ("Copy Blocks", "Copy selected blocks together with their metadata"),
+        );
+        m.insert(
+            ("copy_blocks", Russian),
+            ("Копировать блоки", "Скопировать выбранные блоки вместе с метаданными"),
+        );
+        m.insert(
+            ("paste_blocks", English),
+            ("Paste Blocks", "Paste copied blocks at the cursor with new ids"),
+        );
+        m.insert(
+            ("paste_blocks", Russian),
+            ("Вставить блоки", "Вставить скопированные блоки в позицию курсора с новыми id"),
+        );
         m
     });
 
@@ -107,6 +566,23 @@ pub fn command_hotkey(cmd: &CommandItem, lang: Language) -> String {
     format_hotkey(cmd.hotkey, lang)
 }
 
+/// Название пользовательской команды на языке `lang` или название по
+/// умолчанию.
+pub fn custom_command_name(cmd: &CustomCommand, lang: Language) -> &str {
+    cmd.names.get(&lang).unwrap_or(&cmd.name)
+}
+
+pub fn custom_command_description(cmd: &CustomCommand, lang: Language) -> &str {
+    cmd.descriptions.get(&lang).unwrap_or(&cmd.description)
+}
+
+pub fn custom_command_hotkey(cmd: &CustomCommand, lang: Language) -> String {
+    if cmd.hotkey.is_empty() {
+        return String::new();
+    }
+    format_hotkey(&cmd.hotkey, lang)
+}
+
 fn format_hotkey(raw: &str, lang: Language) -> String {
     raw.split('+')
         .map(|part| translate_part(part, lang))
@@ -0,0 +1,32 @@
+use iced::Command;
+use multicode_core::coverage::{self, block_coverage};
+
+use super::events::Message;
+use super::{MulticodeApp, Tab};
+
+impl MulticodeApp {
+    /// Загружает отчёты о покрытии проекта из секции `[coverage]`.
+    pub(super) fn load_coverage(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        self.loading = true;
+        Command::perform(
+            async move { coverage::load_project(&root).map_err(|e| e.to_string()) },
+            Message::CoverageLoaded,
+        )
+    }
+
+    /// Доля покрытых строк каждого блока вкладки `tab` для раскраски холста;
+    /// `None`, если покрытие не показывается.
+    pub(super) fn tab_coverage(&self, tab: &Tab) -> Option<Vec<Option<f32>>> {
+        let report = self.coverage.as_ref().filter(|_| self.show_coverage)?;
+        let coverage = block_coverage(report, &tab.path, &tab.content, &tab.blocks);
+        Some(
+            coverage
+                .into_iter()
+                .map(|c| c.map(|c| c.ratio() as f32))
+                .collect(),
+        )
+    }
+}
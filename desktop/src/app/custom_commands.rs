@@ -0,0 +1,108 @@
+//! Пользовательские команды палитры.
+//!
+//! Команды задаются в `custom_commands` пользовательских настроек или
+//! добавляются плагинами через [`register_command`]. Команда выполняет
+//! цепочку встроенных команд, команду оболочки во встроенном терминале или
+//! хук ([`CommandAction`]). Задачи сборки проекта добавляются в конец
+//! списка. Сочетания клавиш команд привязываются при
+//! запуске, если пользователь не переназначил их раньше.
+//!
+//! [`register_command`]: super::command_palette::register_command
+
+use chrono::Utc;
+use iced::Command;
+use multicode_core::config::{run_hook, HookEvent};
+use tokio::task;
+
+use super::command_palette::{plugin_commands, CommandAction, CustomCommand};
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+use crate::search::hotkeys::{HotkeyContext, KeyCombination};
+
+impl MulticodeApp {
+    /// Команды из настроек, затем команды плагинов с другими
+    /// идентификаторами и задачи сборки проекта.
+    pub(super) fn custom_commands(&self) -> Vec<CustomCommand> {
+        let mut commands = self.settings.custom_commands.clone();
+        for cmd in plugin_commands() {
+            if !commands.iter().any(|c| c.id == cmd.id) {
+                commands.push(cmd);
+            }
+        }
+        commands.extend(self.build_task_commands());
+        commands
+    }
+
+    /// Привязывает сочетания пользовательских команд, у которых ещё нет
+    /// привязки. Занятые сочетания пропускаются.
+    pub(super) fn bind_custom_hotkeys(&mut self) {
+        for cmd in self.custom_commands() {
+            let hotkeys = &mut self.settings.hotkeys;
+            if hotkeys.binding(HotkeyContext::Global, &cmd.id).is_some() {
+                continue;
+            }
+            if let Some(combo) = KeyCombination::parse(&cmd.hotkey) {
+                hotkeys.bind(HotkeyContext::Global, cmd.id, combo);
+            }
+        }
+    }
+
+    /// Выполняет пользовательскую команду `id`.
+    pub(super) fn run_custom_command(&mut self, id: &str) -> Command<Message> {
+        let Some(cmd) = self.custom_commands().into_iter().find(|c| c.id == id) else {
+            self.notify(LogEntry::new(
+                LogMessage::UnknownCommand,
+                vec![id.to_string()],
+                Utc::now(),
+            ));
+            return Command::none();
+        };
+        match cmd.action {
+            // Шаги выполняются по порядку, но их фоновые действия могут
+            // завершиться в другом порядке.
+            CommandAction::Commands(steps) => {
+                let mut cmds = Vec::new();
+                for step in steps {
+                    match self.execute_builtin_command(&step) {
+                        Some(c) => cmds.push(c),
+                        None => {
+                            self.notify(LogEntry::new(
+                                LogMessage::UnknownCommand,
+                                vec![step],
+                                Utc::now(),
+                            ));
+                            break;
+                        }
+                    }
+                }
+                Command::batch(cmds)
+            }
+            CommandAction::Shell(line) => {
+                self.show_terminal = true;
+                self.run_in_terminal(&line);
+                Command::none()
+            }
+            CommandAction::Hook(line) => {
+                let Some(root) = self.current_root_path() else {
+                    return Command::none();
+                };
+                let event = HookEvent::Command {
+                    id: cmd.id,
+                    path: self
+                        .current_file()
+                        .map(|f| f.path.to_string_lossy().to_string()),
+                };
+                Command::perform(
+                    async move {
+                        task::spawn_blocking(move || run_hook(&line, &event, &root).map(Some))
+                            .await
+                            .map_err(|e| e.to_string())
+                            .and_then(|r| r)
+                    },
+                    Message::HookFinished,
+                )
+            }
+            CommandAction::Task(name) => self.run_build_task(&name),
+        }
+    }
+}
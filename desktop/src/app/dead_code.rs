@@ -0,0 +1,109 @@
+//! Поиск мёртвого кода в палитре команд.
+//!
+//! Команда «Найти мёртвый код» ищет в фоне неиспользуемые функции и
+//! записи `@VISUAL_META` без блоков ([`dead_code::analyze`]). Найденное
+//! становится замечаниями открытых вкладок, как ошибки задач сборки, и
+//! сохраняется до следующего поиска. Удаляет записи и закомментирует
+//! функции `multicode deadcode clean`.
+
+use std::path::Path;
+
+use chrono::Utc;
+use iced::Command;
+use multicode_core::analysis::dead_code::{self, DeadCodeReport};
+use multicode_core::lint::Finding;
+use tokio::task;
+
+use super::events::Message;
+use super::{Diagnostic, LogEntry, LogMessage, MulticodeApp};
+use crate::editor::meta_integration::{lint_viz, validate_meta_json};
+
+/// Замечание редактора по находке `finding` в файле `content`.
+/// Многострочная находка подчёркивается до конца первой строки.
+fn finding_diagnostic(finding: &Finding, content: &str) -> Option<Diagnostic> {
+    let span = finding.span?;
+    let line = span.start_line.saturating_sub(1);
+    let text = content.lines().nth(line)?;
+    let byte = |column: usize| {
+        text.char_indices()
+            .nth(column.saturating_sub(1))
+            .map_or(text.len(), |(i, _)| i)
+    };
+    let start = byte(span.start_column);
+    let end = if span.end_line == span.start_line {
+        byte(span.end_column)
+    } else {
+        text.len()
+    };
+    Some(Diagnostic {
+        line,
+        range: start..end.max(start),
+        message: format!("{}: {}", finding.severity.as_str(), finding.message),
+        fixes: Vec::new(),
+        block: None,
+    })
+}
+
+impl MulticodeApp {
+    /// Ищет мёртвый код проекта в фоне.
+    pub(super) fn find_dead_code(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || dead_code::analyze(&root))
+                    .await
+                    .unwrap_or_default()
+            },
+            Message::DeadCodeFound,
+        )
+    }
+
+    /// Запоминает итог поиска и обновляет замечания открытых вкладок.
+    pub(super) fn dead_code_found(&mut self, report: DeadCodeReport) {
+        self.notify(LogEntry::new(
+            LogMessage::DeadCodeFound,
+            vec![
+                report.functions.len().to_string(),
+                report.orphans.len().to_string(),
+            ],
+            Utc::now(),
+        ));
+        self.dead_code = Some(report);
+        self.refresh_project_diagnostics();
+    }
+
+    /// Замечания файла `path` с содержимым `content` по последнему поиску
+    /// мёртвого кода.
+    pub(super) fn dead_code_diagnostics(&self, path: &Path, content: &str) -> Vec<Diagnostic> {
+        let (Some(root), Some(report)) = (self.current_root_path(), &self.dead_code) else {
+            return Vec::new();
+        };
+        report
+            .findings()
+            .iter()
+            .filter(|f| root.join(&f.file) == path)
+            .filter_map(|f| finding_diagnostic(f, content))
+            .collect()
+    }
+
+    /// Замечания файла, найденные по всему проекту: ошибки задач сборки и
+    /// мёртвый код.
+    pub(super) fn project_diagnostics(&self, path: &Path, content: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = self.build_diagnostics(path, content);
+        diagnostics.extend(self.dead_code_diagnostics(path, content));
+        diagnostics
+    }
+
+    /// Пересчитывает замечания всех открытых вкладок.
+    pub(super) fn refresh_project_diagnostics(&mut self) {
+        for i in 0..self.tabs.len() {
+            let project = self.project_diagnostics(&self.tabs[i].path, &self.tabs[i].content);
+            let tab = &mut self.tabs[i];
+            tab.diagnostics = validate_meta_json(&tab.content);
+            tab.diagnostics.extend(lint_viz(&tab.content, &tab.blocks));
+            tab.diagnostics.extend(project);
+        }
+    }
+}
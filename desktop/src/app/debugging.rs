@@ -0,0 +1,189 @@
+//! Пошаговое выполнение на холсте.
+//!
+//! Когда отладчик сообщает место остановки, редактор открывает его файл на
+//! строке остановки, холст обводит блок, в котором она находится, а панель
+//! рядом с холстом показывает переменные кадра. Кнопки над холстом делают
+//! шаг или продолжают выполнение; до следующей остановки подсветка
+//! снимается. Справа от блоков, в которые попадает остановка, выводятся
+//! значения их выражений наблюдения. Относительный путь остановки берётся
+//! от корня проекта.
+//!
+//! Точки останова хранятся в проекте привязанными к блокам
+//! ([`Breakpoints`]) и переводятся в строки сохранённых файлов при запуске
+//! отладки.
+
+use chrono::Utc;
+use iced::widget::{button, column, row, scrollable, text, Space};
+use iced::{Command, Element, Length};
+use multicode_core::breakpoints::{self, Breakpoints};
+use multicode_core::debugger::{self, stopped_block, StoppedLocation};
+use multicode_core::watch;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp, Tab};
+
+impl MulticodeApp {
+    /// Запоминает место остановки отладчика и открывает его файл на строке
+    /// остановки.
+    pub(super) fn debug_stopped(&mut self, mut stopped: StoppedLocation) -> Command<Message> {
+        if stopped.file.is_relative() {
+            if let Some(root) = self.current_root_path() {
+                stopped.file = root.join(&stopped.file);
+            }
+        }
+        let path = stopped.file.clone();
+        self.goto_line = Some(stopped.line.saturating_sub(1));
+        self.debug_stop = Some(stopped);
+        self.handle_message(Message::SelectFile(path))
+    }
+
+    /// Делает шаг (`step`) или продолжает выполнение до следующей
+    /// остановки.
+    pub(super) fn debug_resume(&mut self, step: bool) {
+        if step {
+            debugger::debug_step();
+        } else {
+            debugger::debug_continue();
+        }
+        self.debug_stop = None;
+    }
+
+    /// Номер блока вкладки `tab`, в котором остановился отладчик.
+    pub(super) fn tab_stopped_block(&self, tab: &Tab) -> Option<usize> {
+        let stopped = self.debug_stop.as_ref().filter(|s| s.file == tab.path)?;
+        stopped_block(&tab.content, &tab.blocks, stopped.line)
+    }
+
+    /// Значения выражений наблюдения каждого блока вкладки `tab` при
+    /// остановке отладчика в её файле, по строке «выражение = значение».
+    pub(super) fn tab_watches(&self, tab: &Tab) -> Vec<Vec<String>> {
+        let Some(stopped) = self.debug_stop.as_ref().filter(|s| s.file == tab.path) else {
+            return Vec::new();
+        };
+        watch::evaluate_at_stop(&tab.content, &tab.blocks, stopped)
+            .into_iter()
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(|w| format!("{} = {}", w.expression, w.value.as_deref().unwrap_or("?")))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Загружает точки останова проекта.
+    pub(super) fn load_breakpoints(&mut self) {
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        match Breakpoints::load(&root) {
+            Ok(points) => self.breakpoints = points,
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::BreakpointsError,
+                vec![e.to_string()],
+                Utc::now(),
+            )),
+        }
+    }
+
+    /// Ставит или снимает точку останова на строке курсора активной
+    /// вкладки и сохраняет точки проекта.
+    pub(super) fn toggle_breakpoint(&mut self) {
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        let Some(tab) = self.current_file() else {
+            return;
+        };
+        let Ok(file) = tab.path.strip_prefix(&root) else {
+            return;
+        };
+        let line = tab.editor.cursor_position().0 + 1;
+        let Some(point) = breakpoints::at_line(file, &tab.content, &tab.blocks, line) else {
+            return;
+        };
+        self.breakpoints.toggle(point);
+        if let Err(e) = self.breakpoints.save(&root) {
+            self.log.push(LogEntry::new(
+                LogMessage::BreakpointsError,
+                vec![e.to_string()],
+                Utc::now(),
+            ));
+        }
+    }
+
+    /// Блоки вкладки `tab`, в которых стоят точки останова.
+    pub(super) fn tab_breakpoints(&self, tab: &Tab) -> Vec<bool> {
+        let file = self
+            .current_root_path()
+            .and_then(|root| tab.path.strip_prefix(root).ok().map(|p| p.to_path_buf()));
+        let Some(file) = file else {
+            return Vec::new();
+        };
+        tab.blocks
+            .iter()
+            .map(|b| {
+                self.breakpoints
+                    .in_file(&file)
+                    .any(|p| p.visual_id == b.visual_id)
+            })
+            .collect()
+    }
+
+    /// Запускает отладку: точки останова переводятся в строки сохранённых
+    /// файлов, которые и выполняет отлаживаемая программа.
+    pub(super) fn debug_start(&mut self) {
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        self.debug_stop = None;
+        debugger::debug_start(&self.breakpoints.resolve(&root));
+    }
+
+    /// Место остановки и кнопки шага над холстом; без остановки — запуск
+    /// отладки, если в проекте есть точки останова.
+    pub fn debug_bar_component(&self) -> Element<'_, Message> {
+        let Some(stopped) = &self.debug_stop else {
+            if self.breakpoints.entries.is_empty() {
+                return Space::with_height(Length::Shrink).into();
+            }
+            return row![
+                text(format!(
+                    "Точек останова: {}",
+                    self.breakpoints.entries.len()
+                )),
+                button("Запустить отладку").on_press(Message::DebugStart),
+            ]
+            .spacing(5)
+            .into();
+        };
+        let name = stopped
+            .file
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        row![
+            text(format!("Остановка: {name}:{}", stopped.line)),
+            button("Шаг").on_press(Message::DebugStep),
+            button("Продолжить").on_press(Message::DebugContinue),
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    /// Переменные кадра, в котором остановился отладчик.
+    pub fn debug_variables_component(&self) -> Element<'_, Message> {
+        let variables = self
+            .debug_stop
+            .iter()
+            .flat_map(|s| &s.variables)
+            .map(|v| text(format!("{} = {}", v.name, v.value)).into());
+        column![
+            text("Переменные"),
+            scrollable(column(variables).spacing(2)).height(Length::Fill),
+        ]
+        .spacing(5)
+        .width(Length::Fixed(220.0))
+        .into()
+    }
+}
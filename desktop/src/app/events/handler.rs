@@ -1,42 +1,52 @@
 use super::Message;
 use crate::app::io::{pick_file, pick_file_in_dir, pick_folder};
+use crate::app::navigation::{line_of_offset, NavLocation};
+use crate::app::test_runner::merge_runs;
 use crate::app::{
-    diff::DiffView, log_translations::LogMessage, save_log_to_file, Diagnostic, EditorMode,
-    EntryType, LogEntry, MulticodeApp, PendingAction, Screen, Tab, TabDragState, ViewMode,
+    diff::DiffView, log_translations::LogMessage, save_log_to_file, save_log_to_ndjson, EditorMode,
+    EntryType, LogEntry, LogLevel, MulticodeApp, PendingAction, Screen, Tab, TabDragState,
+    ViewMode,
 };
-use crate::components::file_manager::{self, ContextMenu};
+use crate::components::file_manager::{self, ContextMenu, ContextMenuItem};
 use crate::editor::autocomplete::{self, AutocompleteState};
-use crate::editor::meta_integration::{changed_meta_ids, validate_meta_json};
+use crate::editor::markdown_preview::{anchor_line, preview_items, PREVIEW_SCROLL};
+use crate::editor::meta_integration::{changed_meta_ids, lint_viz, validate_meta_json};
+use crate::editor::panes::PaneAxis;
+use crate::editor::sticky_headers::{EDITOR_SCROLL, LINE_HEIGHT};
 use crate::search::hotkeys::{HotkeyContext, KeyCombination};
 use crate::sync::{SyncMessage, TextDelta};
 use crate::visual::canvas::CanvasMessage;
-use crate::visual::change::delta_from_meta;
 use crate::visual::palette::PaletteMessage;
 use chrono::Utc;
+use directories::ProjectDirs;
 use iced::widget::{
     scrollable,
     text_editor::{self, Content},
 };
 use iced::{keyboard, window, Command, Event};
 use multicode_core::{
-    blocks, export, git,
-    meta::{self, VisualMeta, DEFAULT_VERSION},
-    parser::{self, Lang},
-    search, viz_lint, BlockInfo,
+    blocks,
+    codeowners::CodeOwners,
+    config::{HookEvent, LicenseConfig},
+    export, file_io, git,
+    ignore_rules::IgnoreRules,
+    license::LicenseHeader,
+    meta::{self, file_ref::FILE_REF_KIND, style, VisualMeta, DEFAULT_VERSION},
+    parser::{highlight::DocumentHighlights, Lang},
+    project_env::EnvVar,
+    search, BlockInfo,
 };
-use serde_json::json;
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::fs;
-use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command as TokioCommand;
 use tokio::time::{sleep, Duration};
 
 const HISTORY_LIMIT: usize = 100;
 
-fn push_with_limit(stack: &mut VecDeque<String>, value: String) {
+pub(crate) fn push_with_limit(stack: &mut VecDeque<String>, value: String) {
     if stack.len() >= HISTORY_LIMIT {
         stack.pop_front();
     }
@@ -44,12 +54,14 @@ fn push_with_limit(stack: &mut VecDeque<String>, value: String) {
 }
 
 impl MulticodeApp {
-    fn block_to_meta(block: &BlockInfo) -> VisualMeta {
-        VisualMeta {
+    pub(crate) fn block_to_meta(block: &BlockInfo) -> VisualMeta {
+        let mut meta = VisualMeta {
             version: DEFAULT_VERSION,
             id: block.visual_id.clone(),
             x: block.x,
             y: block.y,
+            width: block.width,
+            height: block.height,
             tags: block.tags.clone(),
             links: block.links.clone(),
             anchors: Vec::new(),
@@ -60,38 +72,28 @@ impl MulticodeApp {
             ai: None,
             extras: None,
             updated_at: Utc::now(),
-        }
+        };
+        style::set_style(&mut meta, block.color.as_deref(), block.icon.as_deref());
+        meta
     }
     pub fn handle_message(&mut self, message: Message) -> Command<Message> {
+        self.record_macro_step(&message);
         match message {
             Message::CanvasEvent(event) => {
                 match event {
                     CanvasMessage::BlockDragged { index, position } => {
-                        if let Some(i) = self.active_tab {
-                            let meta = if let Some(tab) = self.tabs.get_mut(i) {
-                                if let Some(block) = tab.blocks.get_mut(index) {
-                                    block.x = position.x as f64;
-                                    block.y = position.y as f64;
-                                    tab.dirty = true;
-                                    Some(Self::block_to_meta(block))
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            };
-                            if let Some(meta) = meta {
-                                let delta = delta_from_meta(&meta);
-                                if let Some((code, _, _)) =
-                                    self.sync_engine.handle(SyncMessage::VisualChanged(meta))
-                                {
-                                    if let Some(tab) = self.tabs.get_mut(i) {
-                                        tab.content = code.to_string();
-                                        tab.editor = Content::with_text(&tab.content);
-                                    }
-                                }
-                                self.change_tracker.record_visual(delta);
-                            }
+                        self.move_block(index, position);
+                    }
+                    CanvasMessage::BlockResized { index, size } => {
+                        self.resize_block(index, size);
+                    }
+                    CanvasMessage::LintClicked(index) => return self.focus_block_lint(index),
+                    CanvasMessage::WaypointsChanged { connection, points } => {
+                        self.set_connection_waypoints(connection, points);
+                    }
+                    CanvasMessage::LaneToggled(key) => {
+                        if !self.collapsed_lanes.remove(&key) {
+                            self.collapsed_lanes.insert(key);
                         }
                     }
                     CanvasMessage::Dropped { position } => {
@@ -100,20 +102,7 @@ impl MulticodeApp {
                             block.y = position.y as f64;
                             if let Some(i) = self.active_tab {
                                 let meta = Self::block_to_meta(&block);
-                                let delta = delta_from_meta(&meta);
-                                if let Some(tab) = self.tabs.get_mut(i) {
-                                    tab.blocks.push(block);
-                                    tab.dirty = true;
-                                }
-                                if let Some((code, _, _)) =
-                                    self.sync_engine.handle(SyncMessage::VisualChanged(meta))
-                                {
-                                    if let Some(tab) = self.tabs.get_mut(i) {
-                                        tab.content = code.to_string();
-                                        tab.editor = Content::with_text(&tab.content);
-                                    }
-                                }
-                                self.change_tracker.record_visual(delta);
+                                self.add_visual_block(i, block, meta);
                             }
                         }
                     }
@@ -129,8 +118,42 @@ impl MulticodeApp {
                             self.palette_query.clear();
                         }
                     }
-                    CanvasMessage::BlockSelected(_)
-                    | CanvasMessage::Pan { .. }
+                    CanvasMessage::BlockSelected(None) => self.canvas_focus = None,
+                    CanvasMessage::BlockSelected(Some(index)) => {
+                        self.canvas_focus = Some(index);
+                        let history = if self.show_block_history {
+                            self.load_block_history()
+                        } else {
+                            Command::none()
+                        };
+                        let target = self.current_file().and_then(|tab| {
+                            tab.blocks.get(index).map(|b| NavLocation {
+                                path: tab.path.clone(),
+                                line: line_of_offset(&tab.content, b.range.0),
+                                visual_id: Some(b.visual_id.clone()),
+                            })
+                        });
+                        if let Some(target) = target {
+                            return Command::batch([history, self.navigate_to(target)]);
+                        }
+                        return history;
+                    }
+                    CanvasMessage::BlockActivated(index) => {
+                        let is_ref = self
+                            .current_file()
+                            .and_then(|tab| tab.blocks.get(index))
+                            .is_some_and(|b| b.kind == FILE_REF_KIND);
+                        if is_ref {
+                            return self.open_file_ref(index);
+                        }
+                        return self.handle_message(Message::CanvasEvent(
+                            CanvasMessage::BlockSelected(Some(index)),
+                        ));
+                    }
+                    CanvasMessage::EditLabel(index) => self.open_label_editor(index),
+                    CanvasMessage::EditMeta(index) => return self.open_block_meta(index),
+                    CanvasMessage::BlockFocused(index) => self.canvas_focus = Some(index),
+                    CanvasMessage::Pan { .. }
                     | CanvasMessage::Zoom { .. } => {}
                 }
                 Command::none()
@@ -233,6 +256,9 @@ impl MulticodeApp {
                         }
                     }
                 }
+                if let Some(cmd) = self.input_mode_key(&key, modifiers) {
+                    return cmd;
+                }
                 if let Some(cmd) =
                     self.settings
                         .hotkeys
@@ -348,7 +374,17 @@ impl MulticodeApp {
                 }
                 Command::none()
             }
-            Message::IcedEvent(Event::Window(_, window::Event::FileDropped(path))) => {
+            Message::IcedEvent(Event::Window(id, window::Event::FileDropped(path))) => {
+                let on_canvas = (self.hotkey_context() == HotkeyContext::VisualEditor
+                    || self.canvas_window == Some(id))
+                    && self.current_file().is_some_and(|t| t.path != path);
+                if on_canvas && path.is_file() {
+                    if let Err(e) = self.drop_file_ref(path) {
+                        self.log
+                            .push(LogEntry::new(LogMessage::ReadError, vec![e], Utc::now()));
+                    }
+                    return Command::none();
+                }
                 if path.is_dir() {
                     return self.handle_message(Message::FolderPicked(Some(path)));
                 } else if path.is_file() {
@@ -356,6 +392,16 @@ impl MulticodeApp {
                 }
                 Command::none()
             }
+            Message::IcedEvent(Event::Window(id, window::Event::Resized { width, .. })) => {
+                if id == window::Id::MAIN {
+                    self.resize_terminal(width as f32);
+                }
+                Command::none()
+            }
+            Message::IcedEvent(Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers))) => {
+                self.modifiers = modifiers;
+                Command::none()
+            }
             Message::IcedEvent(_) => Command::none(),
             Message::ThemeSelected(theme) => {
                 self.settings.theme = theme;
@@ -404,6 +450,52 @@ impl MulticodeApp {
                 }
                 Command::none()
             }
+            Message::ToggleAccessibleMode(val) => {
+                self.settings.accessibility.enabled = val;
+                Command::none()
+            }
+            Message::ToggleHighContrastBlocks(val) => {
+                self.settings.accessibility.high_contrast = val;
+                Command::none()
+            }
+            Message::MinFontSizeChanged(value) => {
+                if let Ok(v) = value.parse() {
+                    self.settings.accessibility.min_font_size = v;
+                }
+                Command::none()
+            }
+            Message::LodStructureChanged(value) => {
+                if let Ok(v) = value.parse() {
+                    self.settings.canvas_lod.structure_below = v;
+                }
+                Command::none()
+            }
+            Message::LodFunctionsChanged(value) => {
+                if let Ok(v) = value.parse() {
+                    self.settings.canvas_lod.functions_below = v;
+                }
+                Command::none()
+            }
+            Message::ToggleCanvasGrid(val) => {
+                self.settings.canvas_grid.show = val;
+                Command::none()
+            }
+            Message::ToggleCanvasSnap(val) => {
+                self.settings.canvas_grid.snap = val;
+                Command::none()
+            }
+            Message::LaneGroupingSelected(grouping) => {
+                self.settings.canvas_lanes = grouping;
+                Command::none()
+            }
+            Message::GridCellChanged(value) => {
+                if let Ok(v) = value.parse::<f32>() {
+                    if v >= 1.0 {
+                        self.settings.canvas_grid.cell = v;
+                    }
+                }
+                Command::none()
+            }
             Message::TabWidthChanged(value) => {
                 if let Ok(v) = value.parse() {
                     self.settings.editor.tab_width = v;
@@ -422,10 +514,84 @@ impl MulticodeApp {
                 self.settings.editor.highlight_current_line = val;
                 Command::none()
             }
+            Message::ToggleStickyHeaders(val) => {
+                self.settings.editor.sticky_headers = val;
+                Command::none()
+            }
+            Message::ToggleInlayHints(val) => {
+                self.settings.editor.inlay_hints = val;
+                Command::none()
+            }
             Message::EditorThemeSelected(theme) => {
                 self.settings.editor.theme = theme;
                 Command::none()
             }
+            Message::InputModeSelected(mode) => {
+                self.settings.editor.input_mode = mode;
+                self.vim = Default::default();
+                Command::none()
+            }
+            Message::EditorEscape => {
+                self.carets.clear();
+                self.vim_escape()
+            }
+            Message::AddCursorAbove => self.add_cursor(false),
+            Message::AddCursorBelow => self.add_cursor(true),
+            Message::ToggleFold(line) => {
+                self.toggle_fold(line);
+                Command::none()
+            }
+            Message::EditorScrolled(offset) => {
+                self.editor_scroll = offset;
+                Command::none()
+            }
+            Message::StickyHeaderPressed(line) => {
+                self.move_cursor_to(line, 0);
+                let view_line = self
+                    .current_file()
+                    .and_then(|f| f.folded.as_ref())
+                    .and_then(|view| view.view_line(line))
+                    .unwrap_or(line);
+                scrollable::scroll_to(
+                    scrollable::Id::new(EDITOR_SCROLL),
+                    scrollable::AbsoluteOffset {
+                        x: 0.0,
+                        y: view_line as f32 * LINE_HEIGHT,
+                    },
+                )
+            }
+            Message::ToggleFoldAtCursor => {
+                self.toggle_fold_at_cursor();
+                Command::none()
+            }
+            Message::FoldAllExceptCurrent => {
+                self.fold_all_except_current();
+                Command::none()
+            }
+            Message::UnfoldAll => {
+                self.unfold_all();
+                Command::none()
+            }
+            Message::ThemeFileChanged(value) => {
+                let value = value.trim();
+                self.settings.editor.theme_file =
+                    (!value.is_empty()).then(|| PathBuf::from(value));
+                self.theme_file_mtime = None;
+                Command::none()
+            }
+            Message::ThemeFileTick => {
+                self.reload_theme_file();
+                Command::none()
+            }
+            Message::AutosaveTick => {
+                self.autosave();
+                Command::none()
+            }
+            Message::RestoreRecovery => self.restore_recovery(),
+            Message::DiscardRecovery => {
+                self.discard_recovery();
+                Command::none()
+            }
             Message::ToggleLineNumbers(value) => {
                 self.settings.show_line_numbers = value;
                 Command::none()
@@ -442,115 +608,117 @@ impl MulticodeApp {
                 self.settings.show_markdown_preview = value;
                 Command::none()
             }
+            Message::PreviewAnchor(anchor) => {
+                let Some(f) = self.current_file() else {
+                    return Command::none();
+                };
+                let Some(line) = anchor_line(&preview_items(&f.content), &anchor) else {
+                    return Command::none();
+                };
+                // Высоты элементов предпросмотра неизвестны, поэтому позиция
+                // якоря оценивается по его строке в исходнике.
+                let y = line as f32 / f.content.lines().count().max(1) as f32;
+                self.move_cursor_to(line, 0);
+                scrollable::snap_to(
+                    scrollable::Id::new(PREVIEW_SCROLL),
+                    scrollable::RelativeOffset { x: 0.0, y },
+                )
+            }
+            Message::SetTabLanguage(lang) => self.set_tab_language(lang),
+            Message::ToggleCanvasWindow => self.toggle_canvas_window(),
+            Message::WindowClosed(id) => self.window_closed(id),
+            Message::OpenSymbolSearch => self.open_symbol_search(),
+            Message::CloseSymbolSearch => {
+                self.symbol_search = None;
+                Command::none()
+            }
+            Message::SymbolsLoaded(symbols) => {
+                self.symbols_loaded(symbols);
+                Command::none()
+            }
+            Message::SymbolQueryChanged(query) => {
+                if let Some(search) = self.symbol_search.as_mut() {
+                    search.query = query;
+                }
+                Command::none()
+            }
+            Message::OpenSymbol(index) => self.open_symbol(index),
+            Message::ToggleRecentBlocks => {
+                self.toggle_recent_blocks();
+                Command::none()
+            }
+            Message::RecentBlocksQueryChanged(query) => {
+                if let Some(q) = self.recent_blocks_query.as_mut() {
+                    *q = query;
+                }
+                Command::none()
+            }
+            Message::OpenRecentBlock(index) => self.open_recent_block(index),
             Message::ToggleMetaPanel => {
                 self.show_meta_panel = !self.show_meta_panel;
                 Command::none()
             }
             Message::ShowMetaDialog => {
-                if let Some(f) = self.current_file() {
-                    let tags = f
-                        .meta
-                        .as_ref()
-                        .map(|m| m.tags.join(", "))
-                        .unwrap_or_default();
-                    let links = f
-                        .meta
-                        .as_ref()
-                        .map(|m| m.links.join(", "))
-                        .unwrap_or_default();
-                    let comment = f
-                        .meta
-                        .as_ref()
-                        .and_then(|m| m.extras.as_ref())
-                        .and_then(|e| e.get("comment"))
-                        .and_then(|c| c.as_str())
-                        .unwrap_or("")
-                        .to_string();
-                    self.meta_tags = tags;
-                    self.meta_links = links;
-                    self.meta_comment = comment;
+                self.show_meta_form();
+                Command::none()
+            }
+            Message::LabelEditChanged(s) => {
+                if let Some((_, label)) = self.label_edit.as_mut() {
+                    *label = s;
                 }
-                self.show_meta_dialog = true;
+                Command::none()
+            }
+            Message::SaveLabel => {
+                self.save_label();
+                Command::none()
+            }
+            Message::CloseLabelEditor => {
+                self.label_edit = None;
                 Command::none()
             }
             Message::CloseMetaDialog => {
-                self.show_meta_dialog = false;
+                self.meta_form = None;
                 Command::none()
             }
-            Message::MetaTagsChanged(s) => {
-                self.meta_tags = s;
+            Message::MetaFieldChanged(index, value) => {
+                self.edit_meta_form(index, |f| f.text = value);
                 Command::none()
             }
-            Message::MetaLinksChanged(s) => {
-                self.meta_links = s;
+            Message::MetaEntryKeyChanged(index, entry, key) => {
+                self.edit_meta_form(index, |f| {
+                    if let Some(e) = f.entries.get_mut(entry) {
+                        e.0 = key;
+                    }
+                });
                 Command::none()
             }
-            Message::MetaCommentChanged(s) => {
-                self.meta_comment = s;
+            Message::MetaEntryValueChanged(index, entry, value) => {
+                self.edit_meta_form(index, |f| {
+                    if let Some(e) = f.entries.get_mut(entry) {
+                        e.1 = value;
+                    }
+                });
                 Command::none()
             }
-            Message::SaveMeta => {
-                let tags_str = self.meta_tags.clone();
-                let links_str = self.meta_links.clone();
-                let comment_str = self.meta_comment.clone();
-                let preserve_formatting = self.settings.sync.preserve_meta_formatting;
-                if let Some(f) = self.current_file_mut() {
-                    let mut meta = f.meta.clone().unwrap_or(VisualMeta {
-                        version: DEFAULT_VERSION,
-                        id: "root".into(),
-                        x: 0.0,
-                        y: 0.0,
-                        tags: Vec::new(),
-                        links: Vec::new(),
-                        anchors: Vec::new(),
-                        tests: Vec::new(),
-                        extends: None,
-                        origin: None,
-                        translations: HashMap::new(),
-                        ai: None,
-                        extras: None,
-                        updated_at: Utc::now(),
-                    });
-                    meta.tags = tags_str
-                        .split(',')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect();
-                    meta.links = links_str
-                        .split(',')
-                        .map(|s| s.trim().to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect();
-                    if comment_str.trim().is_empty() {
-                        if let Some(mut extras) = meta.extras.take() {
-                            if let Some(obj) = extras.as_object_mut() {
-                                obj.remove("comment");
-                                if obj.is_empty() {
-                                    meta.extras = None;
-                                } else {
-                                    meta.extras = Some(extras);
-                                }
-                            }
-                        }
-                    } else {
-                        let mut extras = meta.extras.take().unwrap_or_else(|| json!({}));
-                        if let Some(obj) = extras.as_object_mut() {
-                            obj.insert(
-                                "comment".into(),
-                                serde_json::Value::String(comment_str.clone()),
-                            );
-                        }
-                        meta.extras = Some(extras);
+            Message::AddMetaEntry(index) => {
+                self.edit_meta_form(index, |f| f.entries.push(Default::default()));
+                Command::none()
+            }
+            Message::RemoveMetaEntry(index, entry) => {
+                self.edit_meta_form(index, |f| {
+                    if entry < f.entries.len() {
+                        f.entries.remove(entry);
                     }
-                    meta.updated_at = Utc::now();
-                    f.content = meta::upsert(&f.content, &meta, preserve_formatting);
-                    f.editor = Content::with_text(&f.content);
-                    f.meta = Some(meta);
-                    f.dirty = true;
+                });
+                Command::none()
+            }
+            Message::PickMetaTemplate(index) => {
+                if let Some(form) = self.meta_form.as_mut() {
+                    form.pick_template(index);
                 }
-                self.show_meta_dialog = false;
                 Command::none()
             }
+            Message::SaveMeta => self.save_meta_form(),
             Message::StartCaptureShortcut(id) => {
                 self.shortcut_capture = Some(id);
                 Command::none()
@@ -591,6 +759,18 @@ impl MulticodeApp {
                     }
                 }
             }
+            Message::SplitPane(axis) => self.split_pane(axis),
+            Message::UnsplitPane => self.unsplit_pane(),
+            Message::FocusPane(pane) => {
+                self.focus_pane(pane);
+                Command::none()
+            }
+            Message::PaneMessage(pane, message) => {
+                self.focus_pane(pane);
+                self.handle_message(*message)
+            }
+            Message::SetPaneMode(pane, mode) => self.set_pane_mode(pane, mode),
+            Message::ClosePaneTab(pane, path) => self.close_pane_tab(pane, path),
             Message::SwitchToTextEditor => {
                 if let Some(root) = self.current_root_path() {
                     self.screen = Screen::TextEditor { root: root.clone() };
@@ -678,7 +858,23 @@ impl MulticodeApp {
             Message::FilesLoaded(list) => {
                 self.files = list;
                 self.selected_path = self.files.first().map(|e| e.path.clone());
-                Command::none()
+                self.reload_issue_links();
+                self.load_recent_blocks();
+                self.load_breakpoints();
+                self.load_build_tasks();
+                self.load_project_env();
+                self.load_offline_queue();
+                self.load_ai_client();
+                self.code_owners = self
+                    .current_root_path()
+                    .map(|root| CodeOwners::load(&root))
+                    .unwrap_or_default();
+                Command::batch([
+                    self.load_saved_searches(),
+                    self.load_workspace_symbols(),
+                    self.refresh_git_status(),
+                    self.load_branches(),
+                ])
             }
             Message::FileError(e) => {
                 self.log
@@ -785,75 +981,47 @@ impl MulticodeApp {
                         .take_while(|ch| ch.is_alphanumeric() || *ch == '_')
                         .collect::<Vec<_>>();
                     let prefix: String = prefix.into_iter().rev().collect();
-                    let suggestions = autocomplete::suggestions(&content, &prefix);
+                    let suggestions = match autocomplete::meta_context(&line_text[..column]) {
+                        Some((kind, prefix)) => {
+                            autocomplete::meta_suggestions(&self.workspace_symbols, kind, &prefix)
+                        }
+                        None => autocomplete::suggestions(&content, &prefix),
+                    };
                     if !suggestions.is_empty() {
                         self.autocomplete = Some(AutocompleteState::new(suggestions));
                     }
                 }
                 Command::none()
             }
-            Message::AutoFormat => {
-                if let Some(f) = self.current_file_mut() {
-                    if let Some(lang) = detect_lang(&f.path) {
-                        if parser::parse(&f.content, lang, None).is_some() {
-                            let formatted = f
-                                .content
-                                .lines()
-                                .map(|l| l.trim())
-                                .collect::<Vec<_>>()
-                                .join("\n");
-                            f.content = formatted.clone();
-                            f.editor = Content::with_text(&f.content);
-                            f.dirty = true;
-                        }
-                    }
-                }
-                Command::none()
-            }
+            Message::AutoFormat => self.auto_format(),
+            Message::OrganizeImports => self.organize_tab_imports(),
+            Message::Formatted(path, original, result) => self.formatted(path, original, result),
             Message::OpenSearchResult(path, line) => {
                 self.goto_line = Some(line);
                 return self.handle_message(Message::SelectFile(path));
             }
-            Message::SelectFile(path) => {
-                self.context_menu = None;
-                self.selected_path = Some(path.clone());
-                if let Some(idx) = self.tabs.iter().position(|f| f.path == path) {
-                    self.active_tab = Some(idx);
-                    self.search_results.clear();
-                    self.current_match = None;
-                    if let Some(line) = self.goto_line.take() {
-                        self.perform_search();
-                        if let Some(pos) = self.search_results.iter().position(|(l, _)| *l == line)
-                        {
-                            self.current_match = Some(pos);
-                            self.focus_current_match();
-                        } else {
-                            self.move_cursor_to(line, 0);
-                        }
-                    }
-                    Command::none()
-                } else {
-                    self.search_results.clear();
-                    self.current_match = None;
-                    return Command::perform(
-                        async move {
-                            match fs::read_to_string(&path).await {
-                                Ok(c) => Ok((path, c)),
-                                Err(e) => Err(format!("{}", e)),
-                            }
-                        },
-                        Message::FileLoaded,
-                    );
-                }
-            }
+            Message::SelectFile(path) => self.open_file(path, true),
             Message::FileLoaded(Ok((path, content))) => {
                 let editor = Content::with_text(&content);
-                let blocks = detect_lang(&path)
-                    .and_then(|lang| blocks::parse_blocks(content.clone(), lang.to_string()))
+                let limits = self.parse_limits();
+                let enrich = self.enrich_config();
+                let lang = self.lang_for(&path);
+                let parsed = lang
+                    .and_then(|lang| {
+                        blocks::parse_blocks_with_config(
+                            content.clone(),
+                            lang.to_string(),
+                            &limits,
+                            &enrich,
+                        )
+                    })
                     .unwrap_or_default();
                 let blame_path = path.clone();
                 let meta = meta::read_all(&content).into_iter().next();
-                let diagnostics = validate_meta_json(&content);
+                let mut diagnostics = validate_meta_json(&content);
+                diagnostics.extend(lint_viz(&content, &parsed.blocks));
+                diagnostics.extend(self.project_diagnostics(&path, &content));
+                let highlights = document_highlights(&path, &content, lang);
                 file_manager::emit_open(&path);
                 self.tabs.push(Tab {
                     path,
@@ -862,12 +1030,16 @@ impl MulticodeApp {
                     dirty: false,
                     blame: HashMap::new(),
                     diagnostics,
-                    blocks,
+                    blocks: parsed.blocks,
+                    simplified: parsed.simplified,
                     connections: Vec::new(),
                     meta,
                     undo_stack: VecDeque::new(),
                     redo_stack: VecDeque::new(),
                     analysis_version: 0,
+                    folded: None,
+                    highlights,
+                    lang,
                 });
                 self.active_tab = Some(self.tabs.len() - 1);
                 self.rename_file_name.clear();
@@ -882,6 +1054,7 @@ impl MulticodeApp {
                         self.move_cursor_to(line, 0);
                     }
                 }
+                self.focus_pending_block();
                 return self.handle_message(Message::RunGitBlame(blame_path));
             }
             Message::FileLoaded(Err(e)) => {
@@ -889,55 +1062,15 @@ impl MulticodeApp {
                     .push(LogEntry::new(LogMessage::ReadError, vec![e], Utc::now()));
                 Command::none()
             }
-            Message::FileContentEdited(action) => {
-                if let Some(i) = self.active_tab {
-                    if let Some(f) = self.tabs.get_mut(i) {
-                        let is_edit = action.is_edit();
-                        let old_content = f.content.clone();
-                        if is_edit {
-                            push_with_limit(&mut f.undo_stack, old_content.clone());
-                            f.redo_stack.clear();
-                        }
-                        f.editor.perform(action);
-                        f.content = f.editor.text();
-                        let changed_ids = changed_meta_ids(&old_content, &f.content);
-                        if !changed_ids.is_empty() {
-                            self.change_tracker.record_text(TextDelta {
-                                meta_ids: changed_ids,
-                            });
-                        }
-                        if let Some((_, metas, _)) =
-                            self.sync_engine.handle(SyncMessage::TextChanged(
-                                f.content.clone(),
-                                detect_lang(&f.path).unwrap_or(Lang::Rust),
-                            ))
-                        {
-                            for block in &mut f.blocks {
-                                if let Some(meta) = metas.iter().find(|m| m.id == block.visual_id) {
-                                    block.x = meta.x;
-                                    block.y = meta.y;
-                                    block.tags = meta.tags.clone();
-                                    block.links = meta.links.clone();
-                                    block.translations = meta.translations.clone();
-                                }
-                            }
-                            f.meta = metas.first().cloned();
-                        }
-                        if is_edit {
-                            f.dirty = true;
-                        }
-                        return self.schedule_analysis(i);
-                    }
-                }
-                Command::none()
-            }
+            Message::FileContentEdited(action) => self.edit_with_input_mode(action),
             Message::Undo => {
+                self.carets.clear();
                 if let Some(f) = self.current_file_mut() {
                     if let Some(prev) = f.undo_stack.pop_back() {
                         push_with_limit(&mut f.redo_stack, f.content.clone());
                         f.content = prev;
                         f.editor = Content::with_text(&f.content);
-                        if let Some(lang) = detect_lang(&f.path) {
+                        if let Some(lang) = f.lang {
                             if let Some(bs) =
                                 blocks::parse_blocks(f.content.clone(), lang.to_string())
                             {
@@ -949,15 +1082,20 @@ impl MulticodeApp {
                         f.dirty = true;
                     }
                 }
+                if let Some(i) = self.active_tab {
+                    self.refresh_highlights(i);
+                    self.refresh_folds(i);
+                }
                 Command::none()
             }
             Message::Redo => {
+                self.carets.clear();
                 if let Some(f) = self.current_file_mut() {
                     if let Some(next) = f.redo_stack.pop_back() {
                         push_with_limit(&mut f.undo_stack, f.content.clone());
                         f.content = next;
                         f.editor = Content::with_text(&f.content);
-                        if let Some(lang) = detect_lang(&f.path) {
+                        if let Some(lang) = f.lang {
                             if let Some(bs) =
                                 blocks::parse_blocks(f.content.clone(), lang.to_string())
                             {
@@ -969,13 +1107,21 @@ impl MulticodeApp {
                         f.dirty = true;
                     }
                 }
+                if let Some(i) = self.active_tab {
+                    self.refresh_highlights(i);
+                    self.refresh_folds(i);
+                }
                 Command::none()
             }
-            Message::AnalysisReady(path, version, blocks, diagnostics) => {
-                if let Some(tab) = self.tabs.iter_mut().find(|t| t.path == path) {
+            Message::AnalysisReady(path, version, parsed, mut diagnostics) => {
+                if let Some(i) = self.tabs.iter().position(|t| t.path == path) {
+                    diagnostics.extend(self.project_diagnostics(&path, &self.tabs[i].content));
+                    let tab = &mut self.tabs[i];
                     if tab.analysis_version == version {
-                        tab.blocks = blocks;
+                        tab.blocks = parsed.blocks;
+                        tab.simplified = parsed.simplified;
                         tab.diagnostics = diagnostics;
+                        self.refresh_folds(i);
                     }
                 }
                 Command::none()
@@ -983,6 +1129,11 @@ impl MulticodeApp {
             Message::NewFile => Command::none(),
             Message::SaveFile => {
                 let preserve_formatting = self.settings.sync.preserve_meta_formatting;
+                let license = self
+                    .current_root_path()
+                    .map(|root| LicenseConfig::load(&root))
+                    .filter(|config| config.on_save)
+                    .and_then(|config| LicenseHeader::new(&config));
                 if let Some(f) = self.current_file_mut() {
                     let path = f.path.clone();
                     let mut meta = f.meta.clone().unwrap_or(VisualMeta {
@@ -990,6 +1141,8 @@ impl MulticodeApp {
                         id: "root".into(),
                         x: 0.0,
                         y: 0.0,
+                        width: None,
+                        height: None,
                         tags: Vec::new(),
                         links: Vec::new(),
                         anchors: Vec::new(),
@@ -1003,6 +1156,9 @@ impl MulticodeApp {
                     });
                     meta.updated_at = Utc::now();
                     let content = meta::upsert(&f.content, &meta, preserve_formatting);
+                    let content = license
+                        .and_then(|l| l.apply(&path, &content))
+                        .unwrap_or(content);
                     f.content = content.clone();
                     f.editor = Content::with_text(&f.content);
                     f.undo_stack.clear();
@@ -1010,8 +1166,7 @@ impl MulticodeApp {
                     f.meta = Some(meta);
                     return Command::perform(
                         async move {
-                            fs::write(&path, content)
-                                .await
+                            file_io::write_preserving(&path, &content)
                                 .map_err(|e| format!("{}", e))
                         },
                         Message::FileSaved,
@@ -1039,11 +1194,25 @@ impl MulticodeApp {
                     ));
                 }
                 self.set_dirty(false);
-                Command::none()
+                self.save_recent_blocks();
+                if let Some(path) = self.current_file().map(|f| f.path.clone()) {
+                    self.forget_recovery(&path);
+                }
+                let hook = match self.current_file() {
+                    Some(f) => self.run_hook(HookEvent::Save {
+                        path: f.path.to_string_lossy().to_string(),
+                    }),
+                    None => Command::none(),
+                };
+                Command::batch([
+                    hook,
+                    self.rerun_saved_search(),
+                    self.load_workspace_symbols(),
+                    self.refresh_git_status(),
+                ])
             }
             Message::FileSaved(Err(e)) => {
-                self.log
-                    .push(LogEntry::new(LogMessage::SaveError, vec![e], Utc::now()));
+                self.notify(LogEntry::new(LogMessage::SaveError, vec![e], Utc::now()));
                 Command::none()
             }
             Message::NewFileNameChanged(s) => {
@@ -1132,11 +1301,15 @@ impl MulticodeApp {
                     blame: HashMap::new(),
                     diagnostics: Vec::new(),
                     blocks: Vec::new(),
+                    simplified: false,
                     connections: Vec::new(),
                     meta: None,
                     undo_stack: VecDeque::new(),
                     redo_stack: VecDeque::new(),
                     analysis_version: 0,
+                    folded: None,
+                    highlights: document_highlights(&path, "", self.lang_for(&path)),
+                    lang: self.lang_for(&path),
                 });
                 self.active_tab = Some(self.tabs.len() - 1);
                 return self.load_files(self.current_root_path().unwrap());
@@ -1269,6 +1442,7 @@ impl MulticodeApp {
                     Utc::now(),
                 ));
                 file_manager::emit_delete(&path);
+                self.forget_recent_blocks(&path);
                 if let Some(idx) = self.tabs.iter().position(|f| f.path == path) {
                     self.tabs.remove(idx);
                     if let Some(active) = self.active_tab {
@@ -1295,8 +1469,7 @@ impl MulticodeApp {
                         let content = file.content.clone();
                         return Command::perform(
                             async move {
-                                fs::write(&path, content)
-                                    .await
+                                file_io::write_preserving(&path, &content)
                                     .map(|_| idx)
                                     .map_err(|e| format!("{}", e))
                             },
@@ -1320,7 +1493,8 @@ impl MulticodeApp {
             }
             Message::FileClosed(Ok(idx)) => {
                 if idx < self.tabs.len() {
-                    self.tabs.remove(idx);
+                    let tab = self.tabs.remove(idx);
+                    self.forget_recovery(&tab.path);
                     if let Some(active) = self.active_tab {
                         if active >= idx {
                             if self.tabs.is_empty() {
@@ -1334,8 +1508,7 @@ impl MulticodeApp {
                 Command::none()
             }
             Message::FileClosed(Err(e)) => {
-                self.log
-                    .push(LogEntry::new(LogMessage::SaveError, vec![e], Utc::now()));
+                self.notify(LogEntry::new(LogMessage::SaveError, vec![e], Utc::now()));
                 Command::none()
             }
             Message::StartTabDrag(index) => {
@@ -1384,6 +1557,7 @@ impl MulticodeApp {
             }
             Message::ActivateTab(idx) => {
                 if idx < self.tabs.len() {
+                    self.carets.clear();
                     self.active_tab = Some(idx);
                 }
                 Command::none()
@@ -1435,7 +1609,14 @@ impl MulticodeApp {
                     .push(LogEntry::new(LogMessage::SearchError, vec![e], Utc::now()));
                 Command::none()
             }
+            Message::SavedSearchSelected(name) => self.select_saved_search(name),
+            Message::SavedSearchNameChanged(name) => {
+                self.saved_search_name = name;
+                Command::none()
+            }
+            Message::SaveSearch => self.save_current_search(),
             Message::ProjectSearch(query) => {
+                self.active_saved_search = None;
                 self.search_term = query.clone();
                 self.project_search_results.clear();
                 if let Some(root) = self.current_root_path() {
@@ -1448,6 +1629,11 @@ impl MulticodeApp {
                 self.project_search_results = results;
                 Command::none()
             }
+            Message::AskCodebase(query) => self.ask_codebase(query),
+            Message::AskCodebaseFinished(result) => {
+                self.codebase_answered(result);
+                Command::none()
+            }
             Message::OpenGotoLine => {
                 self.show_goto_line_modal = true;
                 self.goto_line_input.clear();
@@ -1472,9 +1658,12 @@ impl MulticodeApp {
             }
             Message::RunParse => {
                 self.loading = true;
-                let files = self.file_paths();
+                let root = self.current_root_path();
                 Command::perform(
                     async move {
+                        let files = root
+                            .map(|root| MulticodeApp::project_files(&root))
+                            .unwrap_or_default();
                         let mut lines = Vec::new();
                         for path in files {
                             let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
@@ -1489,7 +1678,7 @@ impl MulticodeApp {
                                     continue;
                                 }
                             };
-                            match fs::read_to_string(&path).await {
+                            match file_io::read_to_string(&path) {
                                 Ok(content) => {
                                     match blocks::parse_blocks(content, lang.to_string()) {
                                         Some(b) => lines.push(format!(
@@ -1531,44 +1720,11 @@ impl MulticodeApp {
             Message::RunLint => {
                 if let Some(file) = self.current_file() {
                     let content = file.content.clone();
+                    let blocks = file.blocks.clone();
                     Command::perform(
                         async move {
-                            let errors = viz_lint::lint_str(&content);
-                            let lines: Vec<&str> = content.lines().collect();
-                            let mut id_map = HashMap::new();
-                            for (i, line) in lines.iter().enumerate() {
-                                if let Some(idx) = line.find("@viz") {
-                                    if let Some(id_pos) = line[idx..].find("id=") {
-                                        let rest = &line[idx + id_pos + 3..];
-                                        let id = rest
-                                            .split_whitespace()
-                                            .next()
-                                            .unwrap_or("")
-                                            .split(',')
-                                            .next()
-                                            .unwrap_or("")
-                                            .to_string();
-                                        id_map.insert(id, (i, line.len()));
-                                    }
-                                }
-                            }
-                            let mut diags = Vec::new();
-                            for err in errors {
-                                let ident = err
-                                    .strip_prefix("узел ")
-                                    .and_then(|s| s.split(':').next())
-                                    .unwrap_or("<unknown>")
-                                    .to_string();
-                                let (line_idx, len) = id_map
-                                    .get(&ident)
-                                    .copied()
-                                    .unwrap_or((0, lines.get(0).map(|l| l.len()).unwrap_or(0)));
-                                diags.push(Diagnostic {
-                                    line: line_idx,
-                                    range: 0..len,
-                                    message: err,
-                                });
-                            }
+                            let mut diags = validate_meta_json(&content);
+                            diags.extend(lint_viz(&content, &blocks));
                             diags
                         },
                         Message::LintFinished,
@@ -1577,14 +1733,97 @@ impl MulticodeApp {
                     Command::none()
                 }
             }
+            Message::ToggleDiagnosticPopup(line) => {
+                self.diagnostic_popup = if self.diagnostic_popup == Some(line) {
+                    None
+                } else {
+                    Some(line)
+                };
+                Command::none()
+            }
+            Message::ApplyQuickFix { diagnostic, fix } => {
+                let Some(i) = self.active_tab else {
+                    return Command::none();
+                };
+                let tab = &mut self.tabs[i];
+                let Some(fix) = tab
+                    .diagnostics
+                    .get(diagnostic)
+                    .and_then(|d| d.fixes.get(fix))
+                else {
+                    return Command::none();
+                };
+                let content = fix.apply(&tab.content);
+                if content == tab.content {
+                    return Command::none();
+                }
+                push_with_limit(
+                    &mut tab.undo_stack,
+                    std::mem::replace(&mut tab.content, content),
+                );
+                tab.redo_stack.clear();
+                tab.editor = Content::with_text(&tab.content);
+                tab.dirty = true;
+                tab.diagnostics.clear();
+                self.diagnostic_popup = None;
+                self.schedule_analysis(i)
+            }
             Message::LintFinished(diags) => {
+                let mut hook = Command::none();
+                let level = if diags.is_empty() {
+                    LogLevel::Info
+                } else {
+                    LogLevel::Warning
+                };
+                let path = self
+                    .current_file()
+                    .map(|f| f.path.display().to_string())
+                    .unwrap_or_default();
+                self.notify(
+                    LogEntry::new(
+                        LogMessage::LintFinished,
+                        vec![path, diags.len().to_string()],
+                        Utc::now(),
+                    )
+                    .with_level(level),
+                );
                 if let Some(tab) = self.current_file_mut() {
-                    tab.diagnostics = diags;
+                    if !diags.is_empty() {
+                        let event = HookEvent::LintError {
+                            path: tab.path.to_string_lossy().to_string(),
+                            errors: diags.iter().map(|d| d.message.clone()).collect(),
+                        };
+                        tab.diagnostics = diags;
+                        hook = self.run_hook(event);
+                    } else {
+                        tab.diagnostics = diags;
+                    }
+                }
+                hook
+            }
+            Message::HookFinished(Ok(Some(output))) => {
+                if output.success() {
+                    self.log.push(LogEntry::new(
+                        LogMessage::HookFinished,
+                        vec![output.stdout.trim().to_string()],
+                        Utc::now(),
+                    ));
+                } else {
+                    self.notify(LogEntry::new(
+                        LogMessage::HookFailed,
+                        vec![output.stderr.trim().to_string()],
+                        Utc::now(),
+                    ));
                 }
                 Command::none()
             }
+            Message::HookFinished(Ok(None)) => Command::none(),
+            Message::HookFinished(Err(e)) => {
+                self.notify(LogEntry::new(LogMessage::HookFailed, vec![e], Utc::now()));
+                Command::none()
+            }
             Message::RunGitBlame(path) => {
-                match git::blame(path.to_string_lossy().as_ref()) {
+                match git::blame(&path) {
                     Ok(lines) => {
                         if let Some(tab) = self.tabs.iter_mut().find(|t| t.path == path) {
                             tab.blame = lines.into_iter().map(|b| (b.line, b)).collect();
@@ -1599,9 +1838,12 @@ impl MulticodeApp {
                 Command::none()
             }
             Message::RunGitLog => {
+                let Some(root) = self.current_root_path() else {
+                    return Command::none();
+                };
                 self.loading = true;
                 Command::perform(
-                    async move { git::log().map_err(|e| e.to_string()) },
+                    async move { git::log(&root).map_err(|e| e.to_string()) },
                     Message::GitFinished,
                 )
             }
@@ -1622,15 +1864,47 @@ impl MulticodeApp {
             }
             Message::RunExport => {
                 self.loading = true;
-                let files = self.file_paths();
+                let root = self.current_root_path();
+                let owners = self.code_owners.clone();
                 Command::perform(
                     async move {
+                        let files = root
+                            .as_ref()
+                            .map(|root| MulticodeApp::project_files(root))
+                            .unwrap_or_default();
                         let mut lines = Vec::new();
-                        for path in files {
-                            match fs::read_to_string(&path).await {
-                                Ok(content) => match export::serialize_viz_document(&content) {
-                                    Some(json) => lines.push(format!("{}: {json}", path.display())),
-                                    None => lines
+                        if let Some(root) = &root {
+                            let title = root
+                                .file_name()
+                                .map(|n| n.to_string_lossy().into_owned())
+                                .unwrap_or_default();
+                            let dir = root.join(".multicode");
+                            let report = dir.join("report.html");
+                            match std::fs::create_dir_all(&dir).and_then(|_| {
+                                std::fs::write(&report, export::report::html(root, &title))
+                            }) {
+                                Ok(()) => lines.push(format!("HTML-отчёт: {}", report.display())),
+                                Err(e) => {
+                                    lines.push(format!("{}: ошибка записи {e}", report.display()))
+                                }
+                            }
+                        }
+                        for path in files {
+                            match file_io::read_to_string(&path) {
+                                Ok(content) => match export::serialize_viz_document(&content) {
+                                    Some(json) => {
+                                        let file_owners = owners.owners(&path);
+                                        if file_owners.is_empty() {
+                                            lines.push(format!("{}: {json}", path.display()));
+                                        } else {
+                                            lines.push(format!(
+                                                "{} [{}]: {json}",
+                                                path.display(),
+                                                file_owners.join(", ")
+                                            ));
+                                        }
+                                    }
+                                    None => lines
                                         .push(format!("{}: метаданных не найдено", path.display())),
                                 },
                                 Err(e) => {
@@ -1668,63 +1942,20 @@ impl MulticodeApp {
             }
             Message::RunTerminalCmd(cmd) => {
                 let cmd = cmd.trim().to_string();
-                self.log.push(LogEntry::new(
-                    LogMessage::Command,
-                    vec![cmd.clone()],
-                    Utc::now(),
-                ));
                 self.terminal_cmd.clear();
-                if cmd == ":clear" {
-                    self.log.clear();
-                    return Command::none();
-                }
-                if cmd == ":stop" {
-                    if let Some(mut child) = self.terminal_child.take() {
-                        tokio::spawn(async move {
-                            let _ = child.kill().await;
-                        });
-                    }
-                    return Command::none();
-                }
-                let sender = self.sender.clone();
-                match TokioCommand::new("sh")
-                    .arg("-c")
-                    .arg(cmd.clone())
-                    .stdout(std::process::Stdio::piped())
-                    .stderr(std::process::Stdio::piped())
-                    .spawn()
-                {
-                    Ok(mut child) => {
-                        if let Some(stdout) = child.stdout.take() {
-                            let mut reader = BufReader::new(stdout).lines();
-                            let tx = sender.clone();
-                            tokio::spawn(async move {
-                                while let Ok(Some(line)) = reader.next_line().await {
-                                    let _ = tx.send(line);
-                                }
-                            });
-                        }
-                        if let Some(stderr) = child.stderr.take() {
-                            let mut reader = BufReader::new(stderr).lines();
-                            let tx = sender.clone();
-                            tokio::spawn(async move {
-                                while let Ok(Some(line)) = reader.next_line().await {
-                                    let _ = tx.send(line);
-                                }
-                            });
-                        }
-                        self.terminal_child = Some(child);
-                    }
-                    Err(e) => {
-                        self.log.push(LogEntry::new(
-                            LogMessage::RunError,
-                            vec![e.to_string()],
-                            Utc::now(),
-                        ));
+                match cmd.as_str() {
+                    ":clear" => self.log.clear(),
+                    ":stop" => {
+                        self.log
+                            .push(LogEntry::new(LogMessage::Command, vec![cmd], Utc::now()));
+                        self.stop_terminal();
                     }
+                    // Введённую команду выводит сама оболочка.
+                    _ => self.run_in_terminal(&cmd),
                 }
                 Command::none()
             }
+            Message::RunBlock(index) => self.run_block(index),
             Message::ShowTerminalHelp => {
                 self.show_terminal_help = !self.show_terminal_help;
                 Command::none()
@@ -1737,27 +1968,80 @@ impl MulticodeApp {
                         Utc::now(),
                     )),
                     Err(e) => {
-                        self.log
-                            .push(LogEntry::new(LogMessage::SaveError, vec![e], Utc::now()))
+                        self.notify(LogEntry::new(LogMessage::SaveError, vec![e], Utc::now()))
+                    }
+                }
+                Command::none()
+            }
+            Message::SaveLogNdjson => {
+                let path = PathBuf::from("log.ndjson");
+                match save_log_to_ndjson(&self.log, &path) {
+                    Ok(()) => self.log.push(LogEntry::raw(
+                        format!("log saved to {}", path.display()),
+                        Utc::now(),
+                    )),
+                    Err(e) => {
+                        self.notify(LogEntry::new(LogMessage::SaveError, vec![e], Utc::now()))
                     }
                 }
                 Command::none()
             }
             Message::LogLevelSelected(level) => {
-                self.min_log_level = level;
+                self.log_filter.level = level;
                 Command::none()
             }
+            Message::LogModuleSelected(module) => {
+                self.log_filter.module = module;
+                Command::none()
+            }
+            Message::LogQueryChanged(query) => {
+                self.log_filter.query = query;
+                Command::none()
+            }
+            Message::LogEvent(entry) => {
+                if entry.level >= LogLevel::Warning {
+                    self.notify(entry);
+                } else {
+                    self.log.push(entry);
+                }
+                Command::none()
+            }
+            Message::ToggleNotifications => {
+                self.show_notifications = !self.show_notifications;
+                Command::none()
+            }
+            Message::DismissNotification(id) => {
+                self.notifications.dismiss(id);
+                Command::none()
+            }
+            Message::ClearNotifications => {
+                self.notifications.clear();
+                Command::none()
+            }
+            Message::ToggleMacroRecording => {
+                self.toggle_macro_recording();
+                Command::none()
+            }
+            Message::ToggleMacrosPanel => {
+                self.show_macros_panel = !self.show_macros_panel;
+                Command::none()
+            }
+            Message::MacroNameChanged(name) => {
+                self.macro_name = name;
+                Command::none()
+            }
+            Message::SaveMacro => self.save_macro(),
+            Message::RunMacro { name, all_tabs } => self.run_macro(&name, all_tabs),
+            Message::DeleteMacro(name) => self.delete_macro(&name),
             Message::OpenDiff(left, right, ignore_ws) => {
                 self.loading = true;
                 let left_path = left.clone();
                 let right_path = right.clone();
                 Command::perform(
                     async move {
-                        let left_content = fs::read_to_string(&left_path)
-                            .await
+                        let left_content = file_io::read_to_string(&left_path)
                             .map_err(|e| format!("{}: {}", left_path.display(), e))?;
-                        let right_content = fs::read_to_string(&right_path)
-                            .await
+                        let right_content = file_io::read_to_string(&right_path)
                             .map_err(|e| format!("{}: {}", right_path.display(), e))?;
                         Ok(DiffView::new(left_content, right_content, ignore_ws))
                     },
@@ -1773,8 +2057,7 @@ impl MulticodeApp {
                 };
                 Command::perform(
                     async move {
-                        let current = fs::read_to_string(&path)
-                            .await
+                        let current = file_io::read_to_string(&path)
                             .map_err(|e| format!("{}: {}", path.display(), e))?;
                         let spec = format!("{commit}:{}", rel.to_string_lossy());
                         match TokioCommand::new("git")
@@ -1906,40 +2189,444 @@ impl MulticodeApp {
                 self.settings.recent_commands = self.recent_commands.iter().cloned().collect();
                 let save_cmd =
                     Command::perform(self.settings.clone().save(), |_| Message::SettingsSaved);
-                let action_cmd = match cmd.as_str() {
-                    "open_file" => self.handle_message(Message::PickFile),
-                    "save_file" => self.handle_message(Message::SaveFile),
-                    "toggle_terminal" => self.handle_message(Message::ToggleTerminal),
-                    "goto_line" => self.handle_message(Message::OpenGotoLine),
-                    "open_settings" => self.handle_message(Message::OpenSettings),
-                    "create_file" => self.handle_message(Message::CreateFile),
-                    "rename_file" => self.handle_message(Message::RenameFile),
-                    "delete_file" => self.handle_message(Message::RequestDeleteFile),
-                    "next_diff" => self.handle_message(Message::NextDiff),
-                    "prev_diff" => self.handle_message(Message::PrevDiff),
-                    "toggle_command_palette" => self.handle_message(Message::ToggleCommandPalette),
-                    "switch_to_text_editor" => self.handle_message(Message::SwitchToTextEditor),
-                    "switch_to_visual_editor" => self.handle_message(Message::SwitchToVisualEditor),
-                    "switch_to_split" => {
-                        self.handle_message(Message::SwitchViewMode(ViewMode::Split))
-                    }
-                    _ => Command::none(),
-                };
+                let action_cmd = self.execute_command(&cmd);
                 Command::batch([save_cmd, action_cmd])
             }
+            Message::ToggleSyncRecording => {
+                if let Some(count) = self.sync_engine.stop_recording() {
+                    self.log.push(LogEntry::new(
+                        LogMessage::SyncRecordingStopped,
+                        vec![count.to_string()],
+                        Utc::now(),
+                    ));
+                    return Command::none();
+                }
+                let path = sync_recording_path();
+                match self.sync_engine.start_recording(&path) {
+                    Ok(()) => self.log.push(LogEntry::new(
+                        LogMessage::SyncRecordingStarted,
+                        vec![path.display().to_string()],
+                        Utc::now(),
+                    )),
+                    Err(e) => self.log.push(LogEntry::new(
+                        LogMessage::SaveError,
+                        vec![e.to_string()],
+                        Utc::now(),
+                    )),
+                }
+                Command::none()
+            }
+            Message::CopyBlocks => {
+                if let Some(count) = self.copy_blocks_at_cursor() {
+                    self.log.push(LogEntry::new(
+                        LogMessage::BlocksCopied,
+                        vec![count.to_string()],
+                        Utc::now(),
+                    ));
+                }
+                Command::none()
+            }
+            Message::PasteBlocks => match self.paste_blocks_at_cursor() {
+                Some((i, count)) => {
+                    self.log.push(LogEntry::new(
+                        LogMessage::BlocksPasted,
+                        vec![count.to_string()],
+                        Utc::now(),
+                    ));
+                    self.schedule_analysis(i)
+                }
+                None => Command::none(),
+            },
+            Message::ToggleBookmark => {
+                self.toggle_bookmark_at_cursor();
+                Command::none()
+            }
+            Message::NextBookmark => self.cycle_bookmark(true),
+            Message::PrevBookmark => self.cycle_bookmark(false),
+            Message::ToggleBookmarksPanel => {
+                self.show_bookmarks_panel = !self.show_bookmarks_panel;
+                Command::none()
+            }
+            Message::ToggleBlockHistory => {
+                self.show_block_history = !self.show_block_history;
+                if self.show_block_history {
+                    self.load_block_history()
+                } else {
+                    Command::none()
+                }
+            }
+            Message::ToggleInheritancePanel => {
+                self.show_inheritance_panel = !self.show_inheritance_panel;
+                self.extends_draft = None;
+                Command::none()
+            }
+            Message::ToggleCanvasFilter => {
+                self.toggle_canvas_filter();
+                Command::none()
+            }
+            Message::CanvasFilterChanged(value) => {
+                self.canvas_filter_changed(value);
+                Command::none()
+            }
+            Message::CanvasFilterStep(forward) => {
+                self.step_canvas_filter(forward);
+                Command::none()
+            }
+            Message::ToggleCanvasFilterHide(value) => {
+                self.canvas_filter_hide = value;
+                Command::none()
+            }
+            Message::ToggleCanvasLayer(layer) => self.toggle_canvas_layer(layer),
+            Message::CycleProfile => self.cycle_profile(),
+            Message::DebugStopped(stopped) => self.debug_stopped(stopped),
+            Message::DebugStep => {
+                self.debug_resume(true);
+                Command::none()
+            }
+            Message::DebugContinue => {
+                self.debug_resume(false);
+                Command::none()
+            }
+            Message::ToggleBreakpoint => {
+                self.toggle_breakpoint();
+                Command::none()
+            }
+            Message::DebugStart => {
+                self.debug_start();
+                Command::none()
+            }
+            Message::ToggleTestPanel => {
+                self.show_test_panel = !self.show_test_panel;
+                if self.show_test_panel && self.test_run.is_none() && !self.tests_running {
+                    self.run_tests(None)
+                } else {
+                    Command::none()
+                }
+            }
+            Message::RunTests(filter) => self.run_tests(filter),
+            Message::TestsFinished(Ok(run)) => {
+                self.tests_running = false;
+                self.test_run = Some(merge_runs(self.test_run.take(), run));
+                Command::none()
+            }
+            Message::TestsFinished(Err(e)) => {
+                self.tests_running = false;
+                self.log
+                    .push(LogEntry::new(LogMessage::RunError, vec![e], Utc::now()));
+                Command::none()
+            }
+            Message::BuildTaskFinished(Ok(run)) => {
+                self.build_task_finished(run);
+                Command::none()
+            }
+            Message::BuildTaskFinished(Err(e)) => {
+                self.log
+                    .push(LogEntry::new(LogMessage::RunError, vec![e], Utc::now()));
+                Command::none()
+            }
+            Message::ToggleEnvEditor => {
+                self.show_env_editor = !self.show_env_editor;
+                if self.show_env_editor {
+                    self.load_project_env();
+                }
+                Command::none()
+            }
+            Message::EnvKeyChanged(i, key) => {
+                if let Some(var) = self.project_env.vars.get_mut(i) {
+                    var.key = key;
+                }
+                Command::none()
+            }
+            Message::EnvValueChanged(i, value) => {
+                if let Some(var) = self.project_env.vars.get_mut(i) {
+                    var.value = value;
+                }
+                Command::none()
+            }
+            Message::EnvSecretToggled(i, secret) => {
+                if let Some(var) = self.project_env.vars.get_mut(i) {
+                    var.secret = secret;
+                }
+                Command::none()
+            }
+            Message::AddEnvVar => {
+                self.project_env.vars.push(EnvVar::new("", ""));
+                Command::none()
+            }
+            Message::RemoveEnvVar(i) => {
+                if i < self.project_env.vars.len() {
+                    self.project_env.vars.remove(i);
+                }
+                Command::none()
+            }
+            Message::SaveProjectEnv => self.save_project_env(),
+            Message::ProjectSecretsSaved(Ok(())) => Command::none(),
+            Message::ProjectSecretsSaved(Err(e)) => {
+                self.log.push(LogEntry::new(
+                    LogMessage::ProjectEnvError,
+                    vec![e],
+                    Utc::now(),
+                ));
+                Command::none()
+            }
+            Message::ReplayOfflineQueue(force) => self.replay_offline_queue(force),
+            Message::OfflineQueueReplayed(result) => {
+                self.offline_queue_replayed(result);
+                Command::none()
+            }
+            Message::DescribeBlockWithAi => self.describe_block_with_ai(),
+            Message::AiStream(event) => {
+                self.ai_event(event);
+                Command::none()
+            }
+            Message::ApplyAiDescription => {
+                self.apply_ai_description();
+                Command::none()
+            }
+            Message::CloseAiPanel => {
+                self.ai_answer = None;
+                Command::none()
+            }
+            Message::RegenerateSummaries => self.regenerate_summaries(),
+            Message::SummariesRegenerated(result) => self.summaries_regenerated(result),
+            Message::FindDeadCode => self.find_dead_code(),
+            Message::DeadCodeFound(report) => {
+                self.dead_code_found(report);
+                Command::none()
+            }
+            Message::ProfileThresholdChanged(metric, hot, value) => {
+                if let Ok(v) = value.parse() {
+                    self.settings.canvas_profile.set_threshold(metric, hot, v);
+                }
+                Command::none()
+            }
+            Message::ToggleBlockPin => {
+                self.toggle_block_pin();
+                Command::none()
+            }
+            Message::AutoLayoutBlocks => {
+                self.auto_layout_blocks();
+                Command::none()
+            }
+            Message::ExtendsDraftChanged(value) => {
+                self.extends_draft_changed(value);
+                Command::none()
+            }
+            Message::ApplyExtends => {
+                self.apply_extends();
+                Command::none()
+            }
+            Message::BlockHistoryLoaded(Ok(entries)) => {
+                self.block_history = entries;
+                Command::none()
+            }
+            Message::BlockHistoryLoaded(Err(e)) => {
+                self.block_history.clear();
+                self.log
+                    .push(LogEntry::new(LogMessage::GitError, vec![e], Utc::now()));
+                Command::none()
+            }
+            Message::ToggleCoChangePanel => {
+                self.show_cochange_panel = !self.show_cochange_panel;
+                if self.show_cochange_panel {
+                    self.analyze_cochange()
+                } else {
+                    Command::none()
+                }
+            }
+            Message::AnalyzeCoChange => self.analyze_cochange(),
+            Message::CoChangeAnalyzed(Ok(report)) => {
+                self.loading = false;
+                self.cochange = Some(report);
+                Command::none()
+            }
+            Message::CoChangeAnalyzed(Err(e)) => {
+                self.loading = false;
+                self.log
+                    .push(LogEntry::new(LogMessage::GitError, vec![e], Utc::now()));
+                Command::none()
+            }
+            Message::OpenCoChangeBlock(block) => self.open_cochange_block(block),
+            Message::ToggleMetaSuggestions => {
+                self.show_meta_suggestions = !self.show_meta_suggestions;
+                if self.show_meta_suggestions {
+                    self.find_meta_suggestions()
+                } else {
+                    Command::none()
+                }
+            }
+            Message::FindMetaSuggestions => self.find_meta_suggestions(),
+            Message::MetaSuggestionsFound(suggestions) => {
+                self.meta_suggestions_found(suggestions);
+                Command::none()
+            }
+            Message::AcceptMetaSuggestion(index) => self.accept_meta_suggestion(index),
+            Message::RejectMetaSuggestion(index) => {
+                self.reject_meta_suggestion(index);
+                Command::none()
+            }
+            Message::MetaSuggestionAccepted(result) => self.meta_suggestion_accepted(result),
+            Message::ToggleStructuralSearch => {
+                self.toggle_structural_search();
+                Command::none()
+            }
+            Message::StructuralPatternChanged(pattern) => {
+                self.structural_pattern = pattern;
+                Command::none()
+            }
+            Message::StructuralLangChanged(lang) => {
+                self.structural_lang = lang;
+                Command::none()
+            }
+            Message::RunStructuralSearch => self.run_structural_search(),
+            Message::StructuralSearchFinished(result) => {
+                self.structural_search_finished(result);
+                Command::none()
+            }
+            Message::StructuralTemplateChanged(template) => {
+                self.structural_template = template;
+                self.structural_preview.clear();
+                Command::none()
+            }
+            Message::PreviewStructuralReplace => self.preview_structural_replace(),
+            Message::StructuralReplacePreviewed(result) => {
+                self.structural_replace_previewed(result);
+                Command::none()
+            }
+            Message::ApplyStructuralReplace => self.apply_structural_replace(),
+            Message::StructuralReplaceApplied(result) => self.structural_replace_applied(result),
+            Message::UndoStructuralReplace => self.undo_structural_replace(),
+            Message::StructuralReplaceUndone(result) => self.structural_replace_undone(result),
+            Message::ToggleCoverage => {
+                self.show_coverage = !self.show_coverage;
+                if self.show_coverage {
+                    self.load_coverage()
+                } else {
+                    Command::none()
+                }
+            }
+            Message::CoverageLoaded(Ok(report)) => {
+                self.loading = false;
+                if report.is_none() {
+                    self.show_coverage = false;
+                    self.log.push(LogEntry::new(
+                        LogMessage::CoverageMissing,
+                        vec![],
+                        Utc::now(),
+                    ));
+                }
+                self.coverage = report;
+                Command::none()
+            }
+            Message::CoverageLoaded(Err(e)) => {
+                self.loading = false;
+                self.show_coverage = false;
+                self.log.push(LogEntry::new(
+                    LogMessage::CoverageError,
+                    vec![e],
+                    Utc::now(),
+                ));
+                Command::none()
+            }
+            Message::WorkspaceSymbolsLoaded(symbols) => {
+                self.workspace_symbols = symbols;
+                Command::none()
+            }
+            Message::OpenExternalLink(url) => self.open_external_link(url),
+            Message::OpenTaskBoard => self.open_task_board(),
+            Message::CloseTaskBoard => {
+                self.close_task_board();
+                Command::none()
+            }
+            Message::TasksLoaded(tasks) => {
+                self.tasks = tasks;
+                Command::none()
+            }
+            Message::TaskGroupChanged(group) => {
+                self.task_group = group;
+                Command::none()
+            }
+            Message::OpenTask(task) => self.open_task(task),
+            Message::ToggleTagsPanel => {
+                self.show_tags_panel = !self.show_tags_panel;
+                if self.show_tags_panel {
+                    self.load_tags()
+                } else {
+                    Command::none()
+                }
+            }
+            Message::TagsLoaded(tags) => {
+                self.workspace_tags = tags;
+                Command::none()
+            }
+            Message::SelectTag(tag) => {
+                if self.tag_from.trim().is_empty() {
+                    self.tag_from = tag;
+                } else {
+                    self.tag_from = format!("{}, {tag}", self.tag_from.trim_end());
+                }
+                Command::none()
+            }
+            Message::TagFromChanged(value) => {
+                self.tag_from = value;
+                Command::none()
+            }
+            Message::TagIntoChanged(value) => {
+                self.tag_into = value;
+                Command::none()
+            }
+            Message::MergeTags => self.merge_tags(),
+            Message::TagsMerged(result) => self.tags_merged(result),
+            Message::MetaNewIdChanged(value) => {
+                self.meta_new_id = value;
+                Command::none()
+            }
+            Message::RenameMetaId => self.rename_meta_id(),
+            Message::MetaIdRenamed(old, new, result) => self.meta_id_renamed(old, new, result),
+            Message::GotoLocation(target) => self.navigate_to(target),
+            Message::HistoryBack => {
+                let current = self.current_location();
+                match self.navigation.back(current) {
+                    Some(target) => self.jump_to(target),
+                    None => Command::none(),
+                }
+            }
+            Message::HistoryForward => {
+                let current = self.current_location();
+                match self.navigation.forward(current) {
+                    Some(target) => self.jump_to(target),
+                    None => Command::none(),
+                }
+            }
             Message::ToggleDir(path) => {
                 self.selected_path = Some(path.clone());
-                if !self.expanded_dirs.remove(&path) {
-                    self.expanded_dirs.insert(path);
+                if self.expanded_dirs.remove(&path) {
+                    return Command::none();
+                }
+                self.expanded_dirs.insert(path.clone());
+                self.load_dir_if_needed(path)
+            }
+            Message::DirLoaded(dir, children) => {
+                if let Some(entry) = file_manager::find_entry_mut(&mut self.files, &dir) {
+                    entry.children = children;
+                    entry.loaded = true;
                 }
                 Command::none()
             }
+            Message::FileTreeScrolled(viewport) => {
+                self.file_tree_viewport = viewport;
+                Command::none()
+            }
+            Message::FileTreeSearched(Some(search)) => {
+                if search.query == self.search_query.trim() {
+                    self.file_search = Some(search);
+                }
+                Command::none()
+            }
+            Message::FileTreeSearched(None) => Command::none(),
             Message::NavigateUp => {
-                let entries = file_manager::flatten_visible_paths(
-                    &self.files,
-                    &self.expanded_dirs,
-                    &self.search_query,
-                );
+                let (entries, expanded) = self.file_tree_view();
+                let entries = file_manager::flatten_visible_paths(entries, expanded);
                 if entries.is_empty() {
                     return Command::none();
                 }
@@ -1953,11 +2640,8 @@ impl MulticodeApp {
                 Command::none()
             }
             Message::NavigateDown => {
-                let entries = file_manager::flatten_visible_paths(
-                    &self.files,
-                    &self.expanded_dirs,
-                    &self.search_query,
-                );
+                let (entries, expanded) = self.file_tree_view();
+                let entries = file_manager::flatten_visible_paths(entries, expanded);
                 if entries.is_empty() {
                     return Command::none();
                 }
@@ -1976,11 +2660,13 @@ impl MulticodeApp {
             }
             Message::NavigateInto => {
                 if let Some(path) = self.selected_path.clone() {
-                    if let Some(entry) = file_manager::find_entry(&self.files, &path) {
+                    let (entries, _) = self.file_tree_view();
+                    if let Some(entry) = file_manager::find_entry(entries, &path) {
                         match entry.ty {
                             EntryType::Dir => {
                                 if !self.expanded_dirs.contains(&path) {
-                                    self.expanded_dirs.insert(path);
+                                    self.expanded_dirs.insert(path.clone());
+                                    return self.load_dir_if_needed(path);
                                 } else if let Some(child) = entry.children.first() {
                                     self.selected_path = Some(child.path.clone());
                                 }
@@ -2005,7 +2691,7 @@ impl MulticodeApp {
             }
             Message::SearchChanged(q) => {
                 self.search_query = q;
-                Command::none()
+                self.search_file_tree()
             }
             Message::AddFavorite(path) => {
                 if !self.favorites.contains(&path) {
@@ -2030,6 +2716,77 @@ impl MulticodeApp {
                 self.context_menu = None;
                 Command::none()
             }
+            Message::ContextMenuAction(item) => {
+                let Some(menu) = self.context_menu.take() else {
+                    return Command::none();
+                };
+                match item {
+                    ContextMenuItem::Open => self.handle_message(Message::SelectFile(menu.path)),
+                    item => self.git_action(item, menu.path),
+                }
+            }
+            Message::GitStatusRefresh => Command::batch([
+                self.refresh_git_status(),
+                self.load_git_changes(),
+                self.load_branches(),
+            ]),
+            Message::GitStatusLoaded(result) => {
+                // Вне репозитория git метки просто не показываются.
+                self.git_status = result.unwrap_or_default();
+                Command::none()
+            }
+            Message::GitActionDone(result) => self.git_action_done(result),
+            Message::ConfirmGitDiscard => match self.git_discard_confirm.clone() {
+                Some(path) => self.git_action(ContextMenuItem::Discard, path),
+                None => Command::none(),
+            },
+            Message::CancelGitDiscard => {
+                self.git_discard_confirm = None;
+                Command::none()
+            }
+            Message::OpenGitScreen => self.open_git_screen(),
+            Message::CloseGitScreen => {
+                self.close_git_screen();
+                Command::none()
+            }
+            Message::GitChangesLoaded(Ok(changes)) => {
+                self.git_screen.set_changes(changes);
+                Command::none()
+            }
+            Message::GitChangesLoaded(Err(e)) => {
+                self.git_screen.set_changes(Default::default());
+                self.log
+                    .push(LogEntry::new(LogMessage::GitError, vec![e], Utc::now()));
+                Command::none()
+            }
+            Message::ToggleHunk(hunk) => {
+                self.git_screen.toggle(hunk);
+                Command::none()
+            }
+            Message::StageSelectedHunks => self.apply_selected_hunks(false),
+            Message::UnstageSelectedHunks => self.apply_selected_hunks(true),
+            Message::StageFile(path) => self.git_action(ContextMenuItem::Stage, path),
+            Message::UnstageFile(path) => self.git_action(ContextMenuItem::Unstage, path),
+            Message::CommitMessageChanged(message) => {
+                self.git_screen.message = message;
+                Command::none()
+            }
+            Message::CommitAmendToggled(amend) => {
+                self.set_commit_amend(amend);
+                Command::none()
+            }
+            Message::CommitChanges => self.commit_changes(),
+            Message::CommitDone(result) => self.commit_done(result),
+            Message::BranchesLoaded(result) => self.branches_loaded(result),
+            Message::CheckoutBranch(name) => self.checkout_branch(name),
+            Message::NewBranchNameChanged(name) => {
+                self.new_branch_name = name;
+                Command::none()
+            }
+            Message::CreateBranch => self.create_branch(),
+            Message::StashChanges => self.stash_changes(),
+            Message::PopStash => self.pop_stash(),
+            Message::BranchOpDone(result) => self.branch_op_done(result),
             Message::ConfirmDiscard => {
                 self.set_dirty(false);
                 if let Some(action) = self.pending_action.take() {
@@ -2037,7 +2794,7 @@ impl MulticodeApp {
                         PendingAction::Select(path) => {
                             return Command::perform(
                                 async move {
-                                    match fs::read_to_string(&path).await {
+                                    match file_io::read_to_string(&path) {
                                         Ok(c) => Ok((path, c)),
                                         Err(e) => Err(format!("{}", e)),
                                     }
@@ -2068,12 +2825,12 @@ impl MulticodeApp {
                 if let Ok(blocks) = serde_json::from_str::<Vec<BlockInfo>>(&ev) {
                     if let Some(f) = self.current_file_mut() {
                         f.blocks = blocks.clone();
-                        if let Ok(content) = std::fs::read_to_string(&f.path) {
+                        if let Ok(content) = file_io::read_to_string(&f.path) {
                             f.content = content.clone();
                             f.editor = Content::with_text(&f.content);
                         }
                     }
-                    self.log.push(LogEntry::new(
+                    self.notify(LogEntry::new(
                         LogMessage::BlocksUpdated,
                         vec![blocks.len().to_string()],
                         Utc::now(),
@@ -2081,7 +2838,7 @@ impl MulticodeApp {
                 } else {
                     self.log.push(LogEntry::raw(ev, Utc::now()));
                 }
-                Command::none()
+                self.rerun_saved_search()
             }
             Message::SaveSettings => {
                 let mut set = HashSet::new();
@@ -2101,6 +2858,124 @@ impl MulticodeApp {
 }
 
 impl MulticodeApp {
+    /// Выполняет встроенную команду палитры или, если такой нет,
+    /// пользовательскую команду с идентификатором `id`.
+    pub(crate) fn execute_command(&mut self, id: &str) -> Command<Message> {
+        match self.execute_builtin_command(id) {
+            Some(cmd) => cmd,
+            None => self.run_custom_command(id),
+        }
+    }
+
+    /// Выполняет встроенную команду; `None`, если команды `id` нет.
+    pub(crate) fn execute_builtin_command(&mut self, id: &str) -> Option<Command<Message>> {
+        let cmd = match id {
+            "open_file" => self.handle_message(Message::PickFile),
+            "save_file" => self.handle_message(Message::SaveFile),
+            "toggle_terminal" => self.handle_message(Message::ToggleTerminal),
+            "goto_line" => self.handle_message(Message::OpenGotoLine),
+            "open_settings" => self.handle_message(Message::OpenSettings),
+            "create_file" => self.handle_message(Message::CreateFile),
+            "rename_file" => self.handle_message(Message::RenameFile),
+            "delete_file" => self.handle_message(Message::RequestDeleteFile),
+            "next_diff" => self.handle_message(Message::NextDiff),
+            "prev_diff" => self.handle_message(Message::PrevDiff),
+            "toggle_command_palette" => self.handle_message(Message::ToggleCommandPalette),
+            "switch_to_text_editor" => self.handle_message(Message::SwitchToTextEditor),
+            "switch_to_visual_editor" => self.handle_message(Message::SwitchToVisualEditor),
+            "switch_to_split" => self.handle_message(Message::SwitchViewMode(ViewMode::Split)),
+            "toggle_bookmark" => self.handle_message(Message::ToggleBookmark),
+            "next_bookmark" => self.handle_message(Message::NextBookmark),
+            "prev_bookmark" => self.handle_message(Message::PrevBookmark),
+            "toggle_bookmarks_panel" => self.handle_message(Message::ToggleBookmarksPanel),
+            "toggle_tags_panel" => self.handle_message(Message::ToggleTagsPanel),
+            "toggle_block_history" => self.handle_message(Message::ToggleBlockHistory),
+            "toggle_inheritance_panel" => self.handle_message(Message::ToggleInheritancePanel),
+            "toggle_canvas_filter" => self.handle_message(Message::ToggleCanvasFilter),
+            "toggle_block_pin" => self.handle_message(Message::ToggleBlockPin),
+            "auto_layout_blocks" => self.handle_message(Message::AutoLayoutBlocks),
+            "toggle_cochange_panel" => self.handle_message(Message::ToggleCoChangePanel),
+            "toggle_notifications" => self.handle_message(Message::ToggleNotifications),
+            "toggle_macro_recording" => self.handle_message(Message::ToggleMacroRecording),
+            "toggle_macros_panel" => self.handle_message(Message::ToggleMacrosPanel),
+            "add_cursor_above" => self.handle_message(Message::AddCursorAbove),
+            "add_cursor_below" => self.handle_message(Message::AddCursorBelow),
+            "toggle_fold" => self.handle_message(Message::ToggleFoldAtCursor),
+            "fold_all_except_current" => self.handle_message(Message::FoldAllExceptCurrent),
+            "unfold_all" => self.handle_message(Message::UnfoldAll),
+            "toggle_coverage" => self.handle_message(Message::ToggleCoverage),
+            "cycle_canvas_profile" => self.handle_message(Message::CycleProfile),
+            "toggle_breakpoint" => self.handle_message(Message::ToggleBreakpoint),
+            "start_debugging" => self.handle_message(Message::DebugStart),
+            "toggle_test_panel" => self.handle_message(Message::ToggleTestPanel),
+            "edit_project_env" => self.handle_message(Message::ToggleEnvEditor),
+            "describe_block_ai" => self.handle_message(Message::DescribeBlockWithAi),
+            "regenerate_summaries" => self.handle_message(Message::RegenerateSummaries),
+            "find_dead_code" => self.handle_message(Message::FindDeadCode),
+            "organize_imports" => self.handle_message(Message::OrganizeImports),
+            "toggle_meta_suggestions" => self.handle_message(Message::ToggleMetaSuggestions),
+            "toggle_structural_search" => self.handle_message(Message::ToggleStructuralSearch),
+            "open_task_board" => self.handle_message(Message::OpenTaskBoard),
+            "open_git_screen" => self.handle_message(Message::OpenGitScreen),
+            "navigate_history_back" => self.handle_message(Message::HistoryBack),
+            "navigate_history_forward" => self.handle_message(Message::HistoryForward),
+            "toggle_sync_recording" => self.handle_message(Message::ToggleSyncRecording),
+            "split_pane_right" => self.handle_message(Message::SplitPane(PaneAxis::Horizontal)),
+            "split_pane_down" => self.handle_message(Message::SplitPane(PaneAxis::Vertical)),
+            "unsplit_pane" => self.handle_message(Message::UnsplitPane),
+            "focus_other_pane" => match self.pane_layout().map(|l| 1 - l.focused.min(1)) {
+                Some(pane) => self.handle_message(Message::FocusPane(pane)),
+                None => Command::none(),
+            },
+            "toggle_canvas_window" => self.handle_message(Message::ToggleCanvasWindow),
+            "goto_symbol" => self.handle_message(Message::OpenSymbolSearch),
+            "recent_blocks" => self.handle_message(Message::ToggleRecentBlocks),
+            "copy_blocks" => self.handle_message(Message::CopyBlocks),
+            "paste_blocks" => self.handle_message(Message::PasteBlocks),
+            _ => return None,
+        };
+        Some(cmd)
+    }
+
+    /// Открывает файл `path` или переключается на его вкладку. С `record`
+    /// текущая позиция запоминается в истории навигации; переходы
+    /// «назад/вперёд» открывают файл без записи в историю.
+    pub(crate) fn open_file(&mut self, path: PathBuf, record: bool) -> Command<Message> {
+        if record {
+            if let Some(from) = self.current_location() {
+                if from.path != path {
+                    self.navigation.record(from);
+                }
+            }
+        }
+        self.context_menu = None;
+        self.selected_path = Some(path.clone());
+        self.search_results.clear();
+        self.current_match = None;
+        if let Some(idx) = self.tabs.iter().position(|f| f.path == path) {
+            self.active_tab = Some(idx);
+            if let Some(line) = self.goto_line.take() {
+                self.perform_search();
+                if let Some(pos) = self.search_results.iter().position(|(l, _)| *l == line) {
+                    self.current_match = Some(pos);
+                    self.focus_current_match();
+                } else {
+                    self.move_cursor_to(line, 0);
+                }
+            }
+            return Command::none();
+        }
+        Command::perform(
+            async move {
+                match file_io::read_to_string(&path) {
+                    Ok(c) => Ok((path, c)),
+                    Err(e) => Err(format!("{}", e)),
+                }
+            },
+            Message::FileLoaded,
+        )
+    }
+
     fn perform_search(&mut self) {
         self.search_results.clear();
         let term = self.search_term.clone();
@@ -2137,7 +3012,11 @@ impl MulticodeApp {
         }
     }
 
-    fn move_cursor_to(&mut self, line: usize, column: usize) {
+    pub(crate) fn move_cursor_to(&mut self, line: usize, column: usize) {
+        if self.current_file().is_some_and(|f| f.folded.is_some()) {
+            self.reveal_position(line, column);
+            return;
+        }
         if let Some(f) = self.current_file_mut() {
             f.editor.perform(text_editor::Action::Move(
                 text_editor::Motion::DocumentStart,
@@ -2164,23 +3043,108 @@ impl MulticodeApp {
         }
     }
 
-    fn schedule_analysis(&mut self, tab_index: usize) -> Command<Message> {
+    /// Выполняет действие редактора в активной вкладке.
+    pub(crate) fn perform_edit(&mut self, action: text_editor::Action) -> Command<Message> {
+        if let Some(command) = self.folded_action(&action) {
+            return command;
+        }
+        if let Some(command) = self.multi_cursor_action(&action) {
+            return command;
+        }
+        if let Some(i) = self.active_tab {
+            if let Some(f) = self.tabs.get_mut(i) {
+                let is_edit = action.is_edit();
+                let old_content = f.content.clone();
+                if is_edit {
+                    push_with_limit(&mut f.undo_stack, old_content.clone());
+                    f.redo_stack.clear();
+                }
+                f.editor.perform(action);
+                return self.content_changed(i, old_content, is_edit);
+            }
+        }
+        Command::none()
+    }
+
+    /// Обновляет вкладку `i` после изменения её редактора: текст, журнал
+    /// изменений метаданных, синхронизацию и анализ.
+    pub(crate) fn content_changed(
+        &mut self,
+        i: usize,
+        old_content: String,
+        is_edit: bool,
+    ) -> Command<Message> {
+        if let Some(f) = self.tabs.get_mut(i) {
+            f.content = f.editor.text();
+            let changed_ids = changed_meta_ids(&old_content, &f.content);
+            if !changed_ids.is_empty() {
+                self.change_tracker.record_text(TextDelta {
+                    meta_ids: changed_ids,
+                });
+            }
+            if let Some((_, metas, _)) = self.sync_engine.handle(SyncMessage::TextChanged(
+                f.content.clone(),
+                f.lang.unwrap_or(Lang::Rust),
+            )) {
+                for block in &mut f.blocks {
+                    if let Some(meta) = metas.iter().find(|m| m.id == block.visual_id) {
+                        block.x = meta.x;
+                        block.y = meta.y;
+                        block.tags = meta.tags.clone();
+                        block.links = meta.links.clone();
+                        block.translations = meta.translations.clone();
+                    }
+                }
+                f.meta = metas.first().cloned();
+            }
+            if is_edit {
+                f.dirty = true;
+                self.record_edited_block(i, &old_content);
+            }
+            return self.schedule_analysis(i);
+        }
+        Command::none()
+    }
+
+    /// Переносит правки вкладки `i` в её подсветку tree-sitter.
+    pub(crate) fn refresh_highlights(&mut self, i: usize) {
+        if let Some(tab) = self.tabs.get_mut(i) {
+            if let Some(highlights) = &mut tab.highlights {
+                highlights.update(&tab.content);
+            }
+        }
+    }
+
+    pub(crate) fn schedule_analysis(&mut self, tab_index: usize) -> Command<Message> {
+        // Подсветка обновляется сразу, разбор на блоки — после паузы.
+        self.refresh_highlights(tab_index);
         if let Some(tab) = self.tabs.get_mut(tab_index) {
             tab.analysis_version = tab.analysis_version.wrapping_add(1);
             let version = tab.analysis_version;
             let path = tab.path.clone();
+            let lang = tab.lang;
             let content = tab.content.clone();
+            let limits = self.parse_limits();
+            let enrich = self.enrich_config();
             return Command::perform(
                 async move {
                     sleep(Duration::from_millis(300)).await;
-                    let diagnostics = validate_meta_json(&content);
-                    let blocks = detect_lang(&path)
-                        .and_then(|lang| blocks::parse_blocks(content.clone(), lang.to_string()))
+                    let mut diagnostics = validate_meta_json(&content);
+                    let parsed = lang
+                        .and_then(|lang| {
+                            blocks::parse_blocks_with_config(
+                                content.clone(),
+                                lang.to_string(),
+                                &limits,
+                                &enrich,
+                            )
+                        })
                         .unwrap_or_default();
-                    (path, version, blocks, diagnostics)
+                    diagnostics.extend(lint_viz(&content, &parsed.blocks));
+                    (path, version, parsed, diagnostics)
                 },
-                |(path, version, blocks, diagnostics)| {
-                    Message::AnalysisReady(path, version, blocks, diagnostics)
+                |(path, version, parsed, diagnostics)| {
+                    Message::AnalysisReady(path, version, parsed, diagnostics)
                 },
             );
         }
@@ -2189,6 +3153,7 @@ impl MulticodeApp {
 }
 
 async fn project_search(root: PathBuf, query: String) -> Vec<(PathBuf, usize, String)> {
+    let rules = IgnoreRules::load(&root);
     let mut stack = vec![root];
     let mut results = Vec::new();
     while let Some(dir) = stack.pop() {
@@ -2202,10 +3167,13 @@ async fn project_search(root: PathBuf, query: String) -> Vec<(PathBuf, usize, St
                 Ok(t) => t,
                 Err(_) => continue,
             };
+            if rules.is_ignored(&path, ty.is_dir()) {
+                continue;
+            }
             if ty.is_dir() {
                 stack.push(path);
             } else if ty.is_file() {
-                if let Ok(content) = fs::read_to_string(&path).await {
+                if let Ok(content) = file_io::read_to_string(&path) {
                     for (i, line) in content.lines().enumerate() {
                         if line.contains(&query) {
                             results.push((path.clone(), i, line.to_string()));
@@ -2218,7 +3186,15 @@ async fn project_search(root: PathBuf, query: String) -> Vec<(PathBuf, usize, St
     results
 }
 
-fn detect_lang(path: &Path) -> Option<Lang> {
+/// Путь к новому файлу записи сессии синхронизации в каталоге данных приложения.
+fn sync_recording_path() -> PathBuf {
+    let dir = ProjectDirs::from("com", "multicode", "multicode")
+        .map(|p| p.data_dir().join("recordings"))
+        .unwrap_or_else(|| PathBuf::from("recordings"));
+    dir.join(format!("sync-{}.jsonl", Utc::now().format("%Y%m%d-%H%M%S")))
+}
+
+pub(crate) fn detect_lang(path: &Path) -> Option<Lang> {
     match path.extension().and_then(|e| e.to_str())? {
         "rs" => Some(Lang::Rust),
         "py" => Some(Lang::Python),
@@ -2227,15 +3203,28 @@ fn detect_lang(path: &Path) -> Option<Lang> {
         "css" => Some(Lang::Css),
         "html" => Some(Lang::Html),
         "go" => Some(Lang::Go),
+        "c" | "h" => Some(Lang::C),
+        "cpp" | "cc" | "cxx" | "hpp" => Some(Lang::Cpp),
+        "java" => Some(Lang::Java),
+        "cs" => Some(Lang::CSharp),
         _ => None,
     }
 }
 
+/// Подсветка tree-sitter для файла `path` на языке `lang`.
+pub(crate) fn document_highlights(
+    path: &Path,
+    content: &str,
+    lang: Option<Lang>,
+) -> Option<DocumentHighlights> {
+    DocumentHighlights::new(path.display().to_string(), content, lang?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::sync::ConflictResolutionMode;
-    use iced::Application;
+    use iced::multi_window::Application;
 
     #[test]
     fn updates_sync_engine_after_messages() {
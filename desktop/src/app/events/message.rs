@@ -1,14 +1,44 @@
-use iced::{widget::text_editor, Event};
+use iced::{widget::text_editor, window, Event};
 use std::path::PathBuf;
 
+use crate::app::ai_assist::AiEvent;
 use crate::app::diff::DiffView;
-use crate::app::{AppTheme, CreateTarget, Diagnostic, FileEntry, Language, LogLevel, ViewMode};
+use crate::app::git_branches::BranchOpResult;
+use crate::app::git_screen::HunkRef;
+use crate::app::git_status::GitDecorations;
+use crate::app::navigation::NavLocation;
+use crate::app::task_board::TaskGroup;
+use crate::app::{
+    AppTheme, CreateTarget, Diagnostic, FileEntry, Language, LogEntry, LogLevel, ViewMode,
+};
+use crate::components::file_manager::{ContextMenuItem, TreeSearch, TreeViewport};
+use crate::editor::input_mode::InputMode;
+use crate::editor::panes::{PaneAxis, PaneMode};
 use crate::editor::EditorTheme;
 use crate::visual::canvas::CanvasMessage;
+use crate::visual::lanes::LaneGrouping;
+use crate::visual::layers::Layer;
+use crate::visual::profile::ProfileMetric;
 use crate::visual::palette::PaletteMessage;
 use crate::sync::SyncMessage;
 use crate::sync::ConflictResolutionMode;
-use multicode_core::BlockInfo;
+use multicode_core::analysis::dead_code::DeadCodeReport;
+use multicode_core::blocks::symbols::CodeSymbol;
+use multicode_core::build_tasks::TaskRun;
+use multicode_core::blocks::ParsedBlocks;
+use multicode_core::config::HookOutput;
+use multicode_core::cochange::{BlockRef, CoChangeReport};
+use multicode_core::coverage::CoverageReport;
+use multicode_core::debugger::StoppedLocation;
+use multicode_core::git::{BranchInfo, MetaHistoryEntry, WorkingChanges};
+use multicode_core::meta::suggest::MetaSuggestion;
+use multicode_core::meta::symbols::WorkspaceSymbols;
+use multicode_core::offline_queue::{QueueStatus, ReplayReport};
+use multicode_core::parser::Lang;
+use multicode_core::search::replace::FileReplacement;
+use multicode_core::search::structural::StructuralMatch;
+use multicode_core::tasks::Task;
+use multicode_core::testrun::TestRun;
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -25,7 +55,7 @@ pub enum Message {
     FileContentEdited(text_editor::Action),
     Undo,
     Redo,
-    AnalysisReady(PathBuf, u64, Vec<BlockInfo>, Vec<Diagnostic>),
+    AnalysisReady(PathBuf, u64, ParsedBlocks, Vec<Diagnostic>),
     SearchTermChanged(String),
     ReplaceTermChanged(String),
     Find,
@@ -36,6 +66,8 @@ pub enum Message {
     ToggleSearchPanel,
     AutoComplete,
     AutoFormat,
+    OrganizeImports,
+    Formatted(PathBuf, String, Result<String, String>),
     NewFile,
     SaveFile,
     FileSaved(Result<(), String>),
@@ -65,7 +97,13 @@ pub enum Message {
     RunSearch,
     SearchFinished(Result<Vec<String>, String>),
     ProjectSearch(String),
+    SavedSearchSelected(String),
+    SavedSearchNameChanged(String),
+    SaveSearch,
     ProjectSearchFinished(Vec<(PathBuf, usize, String)>),
+    /// Найти блоки проекта по смыслу вопроса
+    AskCodebase(String),
+    AskCodebaseFinished(Result<Vec<(PathBuf, usize, String)>, String>),
     OpenGotoLine,
     GotoLineInputChanged(String),
     ConfirmGotoLine,
@@ -75,6 +113,10 @@ pub enum Message {
     ParseFinished(Result<Vec<String>, String>),
     RunLint,
     LintFinished(Vec<Diagnostic>),
+    ToggleDiagnosticPopup(usize),
+    /// Применить исправление `fix` диагностики `diagnostic` текущего файла.
+    ApplyQuickFix { diagnostic: usize, fix: usize },
+    HookFinished(Result<Option<HookOutput>, String>),
     RunGitBlame(PathBuf),
     RunGitLog,
     GitFinished(Result<Vec<String>, String>),
@@ -88,11 +130,41 @@ pub enum Message {
     CloseSettings,
     OpenProjectPicker,
     ToggleDir(PathBuf),
+    /// Прочитано содержимое раскрытого каталога.
+    DirLoaded(PathBuf, Vec<FileEntry>),
+    FileTreeScrolled(TreeViewport),
+    /// Завершён обход проекта для фильтра дерева; `None` — обход отменён.
+    FileTreeSearched(Option<TreeSearch>),
     SearchChanged(String),
     AddFavorite(PathBuf),
     RemoveFavorite(PathBuf),
     ShowContextMenu(PathBuf),
     CloseContextMenu,
+    ContextMenuAction(ContextMenuItem),
+    GitStatusRefresh,
+    GitStatusLoaded(Result<GitDecorations, String>),
+    GitActionDone(Result<PathBuf, String>),
+    ConfirmGitDiscard,
+    CancelGitDiscard,
+    OpenGitScreen,
+    CloseGitScreen,
+    GitChangesLoaded(Result<WorkingChanges, String>),
+    ToggleHunk(HunkRef),
+    StageSelectedHunks,
+    UnstageSelectedHunks,
+    StageFile(PathBuf),
+    UnstageFile(PathBuf),
+    CommitMessageChanged(String),
+    CommitAmendToggled(bool),
+    CommitChanges,
+    CommitDone(Result<String, String>),
+    BranchesLoaded(Result<BranchInfo, String>),
+    CheckoutBranch(String),
+    NewBranchNameChanged(String),
+    CreateBranch,
+    StashChanges,
+    PopStash,
+    BranchOpDone(BranchOpResult),
     ConfirmDiscard,
     CancelDiscard,
     ThemeSelected(AppTheme),
@@ -102,10 +174,51 @@ pub enum Message {
     CacheSizeChanged(String),
     FontSizeChanged(String),
     TabWidthChanged(String),
+    ToggleAccessibleMode(bool),
+    ToggleHighContrastBlocks(bool),
+    MinFontSizeChanged(String),
+    /// Порог масштаба, ниже которого видны только структурные блоки
+    LodStructureChanged(String),
+    /// Порог масштаба, ниже которого скрываются инструкции
+    LodFunctionsChanged(String),
+    /// Показ сетки холста
+    ToggleCanvasGrid(bool),
+    /// Привязка перетаскиваемых блоков к сетке и соседям
+    ToggleCanvasSnap(bool),
+    /// Размер ячейки сетки холста
+    GridCellChanged(String),
+    /// Признак группировки блоков холста
+    LaneGroupingSelected(LaneGrouping),
     ToggleAutoIndent(bool),
     ToggleLineWrapping(bool),
     ToggleHighlightCurrentLine(bool),
+    ToggleStickyHeaders(bool),
+    ToggleInlayHints(bool),
     EditorThemeSelected(EditorTheme),
+    InputModeSelected(InputMode),
+    /// `Escape`, поглощённый редактором.
+    EditorEscape,
+    /// Добавить каретку строкой выше.
+    AddCursorAbove,
+    /// Добавить каретку строкой ниже.
+    AddCursorBelow,
+    /// Свернуть или развернуть блок с заголовком в этой строке.
+    ToggleFold(usize),
+    ToggleFoldAtCursor,
+    /// Прокрутка редактора кода, пикселей от начала.
+    EditorScrolled(f32),
+    /// Щелчок по закреплённому заголовку в этой строке.
+    StickyHeaderPressed(usize),
+    FoldAllExceptCurrent,
+    UnfoldAll,
+    ThemeFileChanged(String),
+    ThemeFileTick,
+    /// Записать снимки несохранённых вкладок.
+    AutosaveTick,
+    /// Восстановить вкладки из снимков прошлого запуска.
+    RestoreRecovery,
+    /// Отказаться от восстановления и удалить снимки.
+    DiscardRecovery,
     ToggleLineNumbers(bool),
     ToggleStatusBar(bool),
     ToggleToolbar(bool),
@@ -113,11 +226,35 @@ pub enum Message {
     ConflictResolutionModeSelected(ConflictResolutionMode),
     TogglePreserveMetaFormatting(bool),
     ToggleMetaPanel,
+    /// Язык активной вкладки; `None` — определять по расширению.
+    SetTabLanguage(Option<Lang>),
+    /// Вынести холст в отдельное окно или вернуть его в главное.
+    ToggleCanvasWindow,
+    WindowClosed(window::Id),
+    /// Окно «Перейти к символу».
+    OpenSymbolSearch,
+    CloseSymbolSearch,
+    SymbolsLoaded(Vec<CodeSymbol>),
+    SymbolQueryChanged(String),
+    /// Переход к символу окна по его индексу.
+    OpenSymbol(usize),
+    /// Окно недавно изменённых блоков.
+    ToggleRecentBlocks,
+    RecentBlocksQueryChanged(String),
+    /// Переход к недавнему блоку по его индексу в списке проекта.
+    OpenRecentBlock(usize),
     ShowMetaDialog,
     CloseMetaDialog,
-    MetaTagsChanged(String),
-    MetaLinksChanged(String),
-    MetaCommentChanged(String),
+    /// Новое значение поля формы метаданных.
+    MetaFieldChanged(usize, String),
+    /// Новый ключ записи `entry` поля-словаря формы метаданных.
+    MetaEntryKeyChanged(usize, usize, String),
+    /// Новое значение записи `entry` поля-словаря формы метаданных.
+    MetaEntryValueChanged(usize, usize, String),
+    AddMetaEntry(usize),
+    RemoveMetaEntry(usize, usize),
+    /// Подстановка шаблона проекта по индексу в `extends` формы метаданных.
+    PickMetaTemplate(usize),
     SaveMeta,
     StartCaptureShortcut(String),
     SwitchToTextEditor,
@@ -125,9 +262,32 @@ pub enum Message {
     ToggleTerminal,
     TerminalCmdChanged(String),
     RunTerminalCmd(String),
+    /// Запустить конфигурацию запуска выбранного блока по индексу.
+    RunBlock(usize),
     ShowTerminalHelp,
     SaveLog,
+    /// Сохранить журнал в NDJSON.
+    SaveLogNdjson,
     LogLevelSelected(LogLevel),
+    /// Показывать записи только этого модуля; `None` — всех.
+    LogModuleSelected(Option<String>),
+    LogQueryChanged(String),
+    /// Запись из события `tracing`.
+    LogEvent(LogEntry),
+    ToggleNotifications,
+    /// Закрыть уведомление с этим идентификатором.
+    DismissNotification(u64),
+    ClearNotifications,
+    ToggleMacroRecording,
+    ToggleMacrosPanel,
+    MacroNameChanged(String),
+    SaveMacro,
+    /// Воспроизвести макрос в текущей вкладке или во всех открытых.
+    RunMacro {
+        name: String,
+        all_tabs: bool,
+    },
+    DeleteMacro(String),
     OpenDiff(PathBuf, PathBuf, bool),
     OpenGitDiff(PathBuf, String, bool),
     DiffLoaded(Result<DiffView, String>),
@@ -140,12 +300,152 @@ pub enum Message {
     /// Execute a command by its identifier
     ExecuteCommand(String),
     SwitchViewMode(ViewMode),
+    /// Разделить редактор на две панели или сменить направление разделения.
+    SplitPane(PaneAxis),
+    UnsplitPane,
+    FocusPane(usize),
+    /// Сообщение из панели: панель сначала получает фокус.
+    PaneMessage(usize, Box<Message>),
+    SetPaneMode(usize, PaneMode),
+    ClosePaneTab(usize, PathBuf),
     FileError(String),
     NavigateUp,
     NavigateDown,
     NavigateInto,
     NavigateBack,
+    ToggleBookmark,
+    NextBookmark,
+    PrevBookmark,
+    ToggleBookmarksPanel,
+    ToggleTagsPanel,
+    ToggleBlockHistory,
+    ToggleInheritancePanel,
+    /// Показать или скрыть строку фильтра холста
+    ToggleCanvasFilter,
+    CanvasFilterChanged(String),
+    /// Переход к следующему (`true`) или предыдущему совпадению фильтра
+    CanvasFilterStep(bool),
+    ToggleCanvasFilterHide(bool),
+    /// Показать или скрыть слой холста в текущем проекте
+    ToggleCanvasLayer(Layer),
+    /// Переключить меру профиля холста по кругу
+    CycleProfile,
+    /// Порог «горячий» (`true`) или «тёплый» меры профиля
+    ProfileThresholdChanged(ProfileMetric, bool, String),
+    /// Отладчик остановился
+    DebugStopped(StoppedLocation),
+    /// Шаг отладчика с места остановки
+    DebugStep,
+    /// Продолжить выполнение до следующей остановки
+    DebugContinue,
+    /// Поставить или снять точку останова на строке курсора
+    ToggleBreakpoint,
+    /// Запустить отладку с точками останова проекта
+    DebugStart,
+    ToggleTestPanel,
+    /// Запустить тесты проекта: все или подходящие под отбор
+    RunTests(Option<String>),
+    TestsFinished(Result<TestRun, String>),
+    BuildTaskFinished(Result<TaskRun, String>),
+    ToggleEnvEditor,
+    /// Изменить имя переменной окружения проекта
+    EnvKeyChanged(usize, String),
+    /// Изменить значение переменной окружения проекта
+    EnvValueChanged(usize, String),
+    /// Пометить переменную окружения секретом или снять пометку
+    EnvSecretToggled(usize, bool),
+    AddEnvVar,
+    RemoveEnvVar(usize),
+    SaveProjectEnv,
+    ProjectSecretsSaved(Result<(), String>),
+    /// Повторить операции очереди, ожидающие связи: все или только те,
+    /// время которых пришло
+    ReplayOfflineQueue(bool),
+    OfflineQueueReplayed(Result<(ReplayReport, QueueStatus), String>),
+    /// Попросить модель описать блок, выбранный на холсте
+    DescribeBlockWithAi,
+    AiStream(AiEvent),
+    /// Записать ответ модели в описание блока
+    ApplyAiDescription,
+    CloseAiPanel,
+    /// Пересоздать сводки файлов и модулей из описаний блоков
+    RegenerateSummaries,
+    SummariesRegenerated(Result<usize, String>),
+    FindDeadCode,
+    DeadCodeFound(DeadCodeReport),
+    /// Закрепить выбранный блок или снять закрепление
+    ToggleBlockPin,
+    /// Разложить незакреплённые блоки холста по сетке
+    AutoLayoutBlocks,
+    ExtendsDraftChanged(String),
+    ApplyExtends,
+    BlockHistoryLoaded(Result<Vec<MetaHistoryEntry>, String>),
+    ToggleCoChangePanel,
+    AnalyzeCoChange,
+    CoChangeAnalyzed(Result<CoChangeReport, String>),
+    OpenCoChangeBlock(BlockRef),
+    /// Показать или скрыть предложения тегов и связей
+    ToggleMetaSuggestions,
+    FindMetaSuggestions,
+    MetaSuggestionsFound(Vec<MetaSuggestion>),
+    AcceptMetaSuggestion(usize),
+    RejectMetaSuggestion(usize),
+    MetaSuggestionAccepted(Result<(), String>),
+    /// Показать или скрыть панель структурного поиска
+    ToggleStructuralSearch,
+    StructuralPatternChanged(String),
+    StructuralLangChanged(Lang),
+    RunStructuralSearch,
+    StructuralSearchFinished(Result<Vec<StructuralMatch>, String>),
+    StructuralTemplateChanged(String),
+    /// Показать разницу по файлам для замены по шаблону
+    PreviewStructuralReplace,
+    StructuralReplacePreviewed(Result<Vec<FileReplacement>, String>),
+    ApplyStructuralReplace,
+    /// Резервная копия записанной замены и число замен
+    StructuralReplaceApplied(Result<(String, usize), String>),
+    UndoStructuralReplace,
+    StructuralReplaceUndone(Result<(), String>),
+    ToggleCoverage,
+    CoverageLoaded(Result<Option<CoverageReport>, String>),
+    OpenTaskBoard,
+    CloseTaskBoard,
+    TasksLoaded(Vec<Task>),
+    TaskGroupChanged(TaskGroup),
+    OpenTask(Task),
+    OpenExternalLink(String),
+    /// Переход к якорю предпросмотра Markdown.
+    PreviewAnchor(String),
+    WorkspaceSymbolsLoaded(WorkspaceSymbols),
+    TagsLoaded(Vec<(String, usize)>),
+    SelectTag(String),
+    TagFromChanged(String),
+    TagIntoChanged(String),
+    MergeTags,
+    TagsMerged(Result<Vec<PathBuf>, String>),
+    MetaNewIdChanged(String),
+    RenameMetaId,
+    /// Результат переименования записи: прежний и новый идентификаторы и
+    /// изменённые файлы.
+    MetaIdRenamed(String, String, Result<Vec<PathBuf>, String>),
+    GotoLocation(NavLocation),
+    /// Переход назад по истории навигации
+    HistoryBack,
+    /// Переход вперёд по истории навигации
+    HistoryForward,
     CanvasEvent(CanvasMessage),
     PaletteEvent(PaletteMessage),
     Sync(SyncMessage),
+    /// Включить или выключить запись сессии синхронизации
+    ToggleSyncRecording,
+    /// Скопировать выбранные блоки вместе с метаданными
+    CopyBlocks,
+    /// Вставить скопированные блоки в позицию курсора
+    PasteBlocks,
+    /// Изменён текст в редакторе метки блока
+    LabelEditChanged(String),
+    /// Сохранить метку блока
+    SaveLabel,
+    /// Закрыть редактор метки без сохранения
+    CloseLabelEditor,
 }
@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use iced::widget::text_editor::Content;
+use iced::Command;
+use multicode_core::file_io;
+use multicode_core::meta::{
+    file_ref::{ensure_root_meta, file_ref_block, file_ref_meta, resolve},
+    read_all,
+};
+
+use super::events::Message;
+use super::navigation::NavLocation;
+use super::{LogEntry, LogMessage, MulticodeApp};
+use crate::visual::geometry::block_size;
+
+/// Вертикальный отступ между блоком-ссылкой и блоками над ним.
+const FILE_REF_GAP: f64 = 20.0;
+
+impl MulticodeApp {
+    /// Создаёт на холсте активной вкладки блок-ссылку на файл `path`.
+    ///
+    /// Если у целевого файла ещё нет метаданных, в него добавляется
+    /// корневая запись: открытая вкладка изменяется в памяти, иначе файл
+    /// перезаписывается на диске.
+    pub(super) fn drop_file_ref(&mut self, path: PathBuf) -> Result<(), String> {
+        let (Some(i), Some(root)) = (self.active_tab, self.current_root_path()) else {
+            return Ok(());
+        };
+        let root_id = match self.tabs.iter_mut().find(|t| t.path == path) {
+            Some(target) => {
+                let (id, updated) = ensure_root_meta(&target.content);
+                if let Some(updated) = updated {
+                    target.content = updated;
+                    target.editor = Content::with_text(&target.content);
+                    target.dirty = true;
+                }
+                id
+            }
+            None => {
+                let content = file_io::read_to_string(&path).map_err(|e| e.to_string())?;
+                let (id, updated) = ensure_root_meta(&content);
+                if let Some(updated) = updated {
+                    file_io::write_preserving(&path, &updated).map_err(|e| e.to_string())?;
+                }
+                id
+            }
+        };
+        let y = self.tabs[i]
+            .blocks
+            .iter()
+            .map(|b| b.y + f64::from(block_size(b).height) + FILE_REF_GAP)
+            .fold(0.0, f64::max);
+        let meta = file_ref_meta(&root, &path, &root_id, 0.0, y);
+        self.add_visual_block(i, file_ref_block(&meta), meta);
+        Ok(())
+    }
+
+    /// Открывает файл, на который указывает блок-ссылка `index`.
+    pub(super) fn open_file_ref(&mut self, index: usize) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let Some(meta) = self.current_file().and_then(|tab| {
+            let block = tab.blocks.get(index)?;
+            read_all(&tab.content)
+                .into_iter()
+                .find(|m| m.id == block.visual_id)
+                .or_else(|| Some(Self::block_to_meta(block)))
+        }) else {
+            return Command::none();
+        };
+        match resolve(&root, &meta) {
+            Some(path) => self.navigate_to(NavLocation {
+                path,
+                line: 0,
+                visual_id: None,
+            }),
+            None => {
+                self.log.push(LogEntry::new(
+                    LogMessage::FileRefNotFound,
+                    vec![meta.origin.unwrap_or(meta.id)],
+                    Utc::now(),
+                ));
+                Command::none()
+            }
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Сворачивание блоков во вкладках редактора.
+//!
+//! Свёрнутые блоки запоминаются по идентификаторам для каждого файла на
+//! время сеанса: после повторного открытия файла или нового разбора они
+//! сворачиваются снова. Пока во вкладке есть свёрнутые блоки, редактор
+//! показывает представление [`FoldedView`], а содержимое вкладки
+//! (`Tab::editor`) остаётся полным текстом с той же позицией каретки —
+//! остальной код работает с ним как обычно.
+
+use std::collections::HashSet;
+
+use iced::widget::text_editor::Action;
+use iced::Command;
+
+use super::events::handler::push_with_limit;
+use super::events::Message;
+use super::MulticodeApp;
+use crate::editor::folding::{fold_regions, hidden_ranges, FoldRegion, FoldedView};
+use crate::editor::multi_cursor::{content_with_cursor, cursor_offset, line_column, offset_of};
+
+impl MulticodeApp {
+    /// Сворачиваемые блоки текущей вкладки.
+    pub fn fold_regions(&self) -> Vec<FoldRegion> {
+        self.current_file()
+            .map(|f| fold_regions(&f.content, &f.blocks))
+            .unwrap_or_default()
+    }
+
+    /// Перестраивает представление вкладки `i` по свёрнутым блокам её файла.
+    pub(super) fn refresh_folds(&mut self, i: usize) {
+        let Some(f) = self.tabs.get_mut(i) else {
+            return;
+        };
+        let folded = self.folds.get(&f.path).cloned().unwrap_or_default();
+        let hidden = hidden_ranges(&f.content, &fold_regions(&f.content, &f.blocks), &folded);
+        let unchanged = f
+            .folded
+            .as_ref()
+            .is_some_and(|v| v.is_current(&f.content) && v.hidden() == hidden.as_slice());
+        if !unchanged {
+            let cursor = cursor_offset(&f.content, f.editor.cursor_position());
+            f.folded = FoldedView::new(&f.content, hidden, cursor);
+        }
+        if f.folded.is_some() && Some(i) == self.active_tab {
+            self.carets.clear();
+        }
+    }
+
+    fn set_folds(&mut self, folded: HashSet<String>) {
+        let Some(i) = self.active_tab else {
+            return;
+        };
+        let path = self.tabs[i].path.clone();
+        if folded.is_empty() {
+            self.folds.remove(&path);
+        } else {
+            self.folds.insert(path, folded);
+        }
+        self.refresh_folds(i);
+    }
+
+    /// Сворачивает или разворачивает блок с заголовком в строке `line`, а
+    /// если такого нет — ближайший блок, содержащий строку.
+    pub(super) fn toggle_fold(&mut self, line: usize) {
+        let Some(path) = self.current_file().map(|f| f.path.clone()) else {
+            return;
+        };
+        let regions = self.fold_regions();
+        let mut folded = self.folds.get(&path).cloned().unwrap_or_default();
+        let target = regions
+            .iter()
+            .find(|r| r.header == line && folded.contains(&r.id))
+            .or_else(|| regions.iter().find(|r| r.header == line))
+            .or_else(|| regions.iter().rfind(|r| r.header <= line && line <= r.end));
+        if let Some(region) = target {
+            if !folded.remove(&region.id) {
+                folded.insert(region.id.clone());
+            }
+            self.set_folds(folded);
+        }
+    }
+
+    /// Сворачивает или разворачивает блок под кареткой.
+    pub(super) fn toggle_fold_at_cursor(&mut self) {
+        if let Some(f) = self.current_file() {
+            let cursor = cursor_offset(&f.content, f.editor.cursor_position());
+            let (line, _) = line_column(&f.content, cursor);
+            self.toggle_fold(line);
+        }
+    }
+
+    /// Сворачивает все блоки, кроме текущего: выбранного на холсте, а без
+    /// выбора — ближайшего блока под кареткой. Объемлющие и вложенные в
+    /// него блоки остаются развёрнутыми.
+    pub(super) fn fold_all_except_current(&mut self) {
+        let Some(f) = self.current_file() else {
+            return;
+        };
+        let regions = self.fold_regions();
+        let current = match self.canvas_focus.and_then(|i| f.blocks.get(i)) {
+            Some(block) => regions.iter().find(|r| r.id == block.visual_id),
+            None => {
+                let cursor = cursor_offset(&f.content, f.editor.cursor_position());
+                let (line, _) = line_column(&f.content, cursor);
+                regions.iter().rfind(|r| r.header <= line && line <= r.end)
+            }
+        };
+        let related = |r: &FoldRegion| {
+            current.is_some_and(|c| {
+                (r.header <= c.header && c.end <= r.end) || (c.header <= r.header && r.end <= c.end)
+            })
+        };
+        let folded = regions
+            .iter()
+            .filter(|r| !related(r))
+            .map(|r| r.id.clone())
+            .collect();
+        self.set_folds(folded);
+    }
+
+    pub(super) fn unfold_all(&mut self) {
+        self.set_folds(HashSet::new());
+    }
+
+    /// Разворачивает блоки, скрывающие позицию `line`:`column` полного
+    /// текста, и ставит туда каретку.
+    pub(super) fn reveal_position(&mut self, line: usize, column: usize) {
+        let Some(i) = self.active_tab else {
+            return;
+        };
+        let Some(f) = self.tabs.get_mut(i) else {
+            return;
+        };
+        let offset = offset_of(&f.content, line, column);
+        f.editor = content_with_cursor(&f.content, offset);
+        if let (Some(view), Some(folded)) = (&f.folded, self.folds.get_mut(&f.path)) {
+            for (id, range) in view.hidden() {
+                if range.start < offset && offset <= range.end {
+                    folded.remove(id);
+                }
+            }
+        }
+        self.refresh_folds(i);
+    }
+
+    /// Действие редактора в свёрнутой вкладке; `None`, если вкладка не
+    /// свёрнута.
+    pub(crate) fn folded_action(&mut self, action: &Action) -> Option<Command<Message>> {
+        let i = self.active_tab?;
+        let f = self.tabs.get_mut(i)?;
+        let view = f.folded.as_mut()?;
+        if !view.is_current(&f.content) {
+            // Текст изменился в обход представления — редактор показывает
+            // полный текст до следующего разбора.
+            f.folded = None;
+            return None;
+        }
+        let before = view.cursor();
+        match view.perform(action.clone()) {
+            Ok(()) if action.is_edit() => {
+                if view.source() == f.content {
+                    return Some(Command::none());
+                }
+                let old_content = f.content.clone();
+                push_with_limit(&mut f.undo_stack, old_content.clone());
+                f.redo_stack.clear();
+                f.editor = content_with_cursor(view.source(), view.cursor());
+                Some(self.content_changed(i, old_content, true))
+            }
+            Ok(()) => {
+                if !matches!(action, Action::Scroll { .. }) {
+                    f.editor = content_with_cursor(&f.content, view.cursor());
+                }
+                Some(Command::none())
+            }
+            Err(touched) => {
+                f.editor = content_with_cursor(&f.content, before);
+                f.folded = None;
+                if let Some(folded) = self.folds.get_mut(&f.path) {
+                    for id in touched {
+                        folded.remove(&id);
+                    }
+                }
+                self.refresh_folds(i);
+                Some(Command::none())
+            }
+        }
+    }
+}
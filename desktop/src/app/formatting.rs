@@ -0,0 +1,122 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use iced::widget::text_editor::Content;
+use iced::Command;
+use multicode_core::config::{FormattersConfig, ImportsConfig};
+use multicode_core::format::format_code;
+use multicode_core::format::imports::organize_imports;
+
+use super::events::handler::push_with_limit;
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+impl MulticodeApp {
+    /// Запускает форматтер языка активной вкладки из `[formatters]`.
+    pub(super) fn auto_format(&mut self) -> Command<Message> {
+        let Some(tab) = self.current_file() else {
+            return Command::none();
+        };
+        let Some(lang) = tab.lang else {
+            return Command::none();
+        };
+        let config = self
+            .current_root_path()
+            .map(|root| FormattersConfig::load(&root))
+            .unwrap_or_default();
+        let Some(command) = config.command_for(&lang.to_string()).map(str::to_string) else {
+            self.log.push(LogEntry::new(
+                LogMessage::FormatError,
+                vec![format!("форматтер для {lang} не настроен")],
+                Utc::now(),
+            ));
+            return Command::none();
+        };
+        let imports = self.imports_config();
+        let path = tab.path.clone();
+        let original = tab.content.clone();
+        Command::perform(
+            async move {
+                let result = tokio::task::spawn_blocking({
+                    let path = path.clone();
+                    let original = original.clone();
+                    // Импорты упорядочиваются до форматтера: его вывод
+                    // окончательный, и обе правки отменяются одним шагом.
+                    move || {
+                        let organized = imports
+                            .on_format
+                            .then(|| organize_imports(&path, &original, imports.remove_unused))
+                            .flatten();
+                        format_code(organized.as_ref().unwrap_or(&original), &command, &path)
+                    }
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r);
+                (path, original, result)
+            },
+            |(path, original, result)| Message::Formatted(path, original, result),
+        )
+    }
+
+    fn imports_config(&self) -> ImportsConfig {
+        self.current_root_path()
+            .map(|root| ImportsConfig::load(&root))
+            .unwrap_or_default()
+    }
+
+    /// Упорядочивает импорты активной вкладки одной правкой, которую можно
+    /// отменить.
+    pub(super) fn organize_tab_imports(&mut self) -> Command<Message> {
+        let config = self.imports_config();
+        let Some(i) = self.active_tab else {
+            return Command::none();
+        };
+        let Some(tab) = self.tabs.get_mut(i) else {
+            return Command::none();
+        };
+        let Some(organized) = organize_imports(&tab.path, &tab.content, config.remove_unused)
+        else {
+            return Command::none();
+        };
+        let original = std::mem::replace(&mut tab.content, organized);
+        push_with_limit(&mut tab.undo_stack, original);
+        tab.redo_stack.clear();
+        tab.editor = Content::with_text(&tab.content);
+        tab.dirty = true;
+        self.schedule_analysis(i)
+    }
+
+    /// Применяет результат форматтера одной правкой, которую можно отменить.
+    ///
+    /// Если код вкладки успел измениться во время форматирования,
+    /// результат отбрасывается.
+    pub(super) fn formatted(
+        &mut self,
+        path: PathBuf,
+        original: String,
+        result: Result<String, String>,
+    ) -> Command<Message> {
+        let formatted = match result {
+            Ok(formatted) => formatted,
+            Err(e) => {
+                self.log
+                    .push(LogEntry::new(LogMessage::FormatError, vec![e], Utc::now()));
+                return Command::none();
+            }
+        };
+        let Some(i) = self.tabs.iter().position(|t| t.path == path) else {
+            return Command::none();
+        };
+        let tab = &mut self.tabs[i];
+        if tab.content != original || formatted == original {
+            return Command::none();
+        }
+        push_with_limit(&mut tab.undo_stack, original);
+        tab.redo_stack.clear();
+        tab.content = formatted;
+        tab.editor = Content::with_text(&tab.content);
+        tab.dirty = true;
+        self.schedule_analysis(i)
+    }
+}
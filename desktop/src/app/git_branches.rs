@@ -0,0 +1,173 @@
+//! Ветки и отложенные изменения git.
+//!
+//! Главное меню показывает текущую ветку проекта, позволяет переключиться
+//! на другую, создать новую и отложить изменения или вернуть отложенные
+//! ([`multicode_core::git::branch_info`]). Когда меняется `HEAD` — из
+//! приложения или снаружи, что замечает наблюдатель состояния git, — вкладки
+//! без несохранённых правок перечитываются с диска и разбираются заново.
+
+use std::path::Path;
+
+use chrono::Utc;
+use iced::widget::text_editor::Content;
+use iced::Command;
+use multicode_core::git::{self, BranchInfo, GitError};
+use multicode_core::{file_io, meta};
+use tokio::task;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+/// Результат операции с ветками: запись журнала и её аргументы.
+pub type BranchOpResult = Result<(LogMessage, Vec<String>), String>;
+
+impl MulticodeApp {
+    /// Перечитывает ветки проекта в фоне.
+    pub(super) fn load_branches(&self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || git::branch_info(&root).map_err(|e| e.to_string()))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r)
+            },
+            Message::BranchesLoaded,
+        )
+    }
+
+    pub(super) fn branches_loaded(
+        &mut self,
+        result: Result<BranchInfo, String>,
+    ) -> Command<Message> {
+        // Вне репозитория git ветки просто не показываются.
+        let info = result.unwrap_or_default();
+        let head_changed =
+            (&info.current, &info.head) != (&self.git_branches.current, &self.git_branches.head);
+        self.git_branches = info;
+        if !head_changed {
+            return Command::none();
+        }
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        Command::batch([self.reload_clean_tabs(&root), self.load_files(root)])
+    }
+
+    pub(super) fn checkout_branch(&mut self, name: String) -> Command<Message> {
+        self.run_branch_op(move |root| {
+            git::checkout_branch(root, &name)?;
+            Ok((LogMessage::BranchSwitched, vec![name]))
+        })
+    }
+
+    /// Создаёт ветку с введённым именем и переключается на неё.
+    pub(super) fn create_branch(&mut self) -> Command<Message> {
+        let name = self.new_branch_name.trim().to_string();
+        if name.is_empty() {
+            return Command::none();
+        }
+        self.new_branch_name.clear();
+        self.run_branch_op(move |root| {
+            git::create_branch(root, &name, true)?;
+            Ok((LogMessage::BranchCreated, vec![name]))
+        })
+    }
+
+    pub(super) fn stash_changes(&mut self) -> Command<Message> {
+        self.run_branch_op(|root| {
+            git::stash(root, None)?;
+            Ok((LogMessage::ChangesStashed, Vec::new()))
+        })
+    }
+
+    pub(super) fn pop_stash(&mut self) -> Command<Message> {
+        self.run_branch_op(|root| {
+            git::stash_pop(root)?;
+            Ok((LogMessage::StashApplied, Vec::new()))
+        })
+    }
+
+    fn run_branch_op<F>(&self, op: F) -> Command<Message>
+    where
+        F: FnOnce(&Path) -> Result<(LogMessage, Vec<String>), GitError> + Send + 'static,
+    {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || op(&root).map_err(|e| e.to_string()))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r)
+            },
+            Message::BranchOpDone,
+        )
+    }
+
+    /// Завершение операции с ветками. Отложенные изменения меняют рабочую
+    /// копию без смены `HEAD`, поэтому вкладки перечитываются сразу.
+    pub(super) fn branch_op_done(&mut self, result: BranchOpResult) -> Command<Message> {
+        match result {
+            Ok((message, args)) => {
+                self.log.push(LogEntry::new(message, args, Utc::now()));
+                let Some(root) = self.current_root_path() else {
+                    return Command::none();
+                };
+                Command::batch([
+                    self.reload_clean_tabs(&root),
+                    self.load_branches(),
+                    self.refresh_git_status(),
+                    self.load_git_changes(),
+                ])
+            }
+            Err(e) => {
+                self.log
+                    .push(LogEntry::new(LogMessage::GitError, vec![e], Utc::now()));
+                Command::none()
+            }
+        }
+    }
+
+    /// Перечитывает с диска вкладки без несохранённых правок внутри `path`
+    /// и заново разбирает их блоки. Вкладки файлов, которых больше нет на
+    /// диске, закрываются.
+    pub(super) fn reload_clean_tabs(&mut self, path: &Path) -> Command<Message> {
+        let mut cmds = Vec::new();
+        let mut index = 0;
+        while index < self.tabs.len() {
+            let tab = &mut self.tabs[index];
+            if tab.dirty || !tab.path.starts_with(path) {
+                index += 1;
+                continue;
+            }
+            match file_io::read_to_string(&tab.path) {
+                Ok(content) => {
+                    if content != tab.content {
+                        tab.editor = Content::with_text(&content);
+                        tab.meta = meta::read_all(&content).into_iter().next();
+                        tab.content = content;
+                        tab.undo_stack.clear();
+                        tab.redo_stack.clear();
+                        cmds.push(self.schedule_analysis(index));
+                    }
+                    index += 1;
+                }
+                Err(_) if !tab.path.exists() => {
+                    self.tabs.remove(index);
+                    self.active_tab = match self.active_tab {
+                        _ if self.tabs.is_empty() => None,
+                        Some(active) if active > index => Some(active - 1),
+                        Some(active) => Some(active.min(self.tabs.len() - 1)),
+                        None => None,
+                    };
+                }
+                Err(_) => index += 1,
+            }
+        }
+        Command::batch(cmds)
+    }
+}
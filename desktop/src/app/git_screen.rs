@@ -0,0 +1,269 @@
+//! Экран git: изменения по участкам и коммит.
+//!
+//! Экран показывает неиндексированные и индексированные изменения проекта
+//! ([`multicode_core::git::changes`]). Отмеченные участки добавляются в
+//! индекс или убираются из него, после чего индекс можно закоммитить или
+//! исправить им последний коммит.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+
+use chrono::Utc;
+use iced::Command;
+use multicode_core::git::{self, Hunk, WorkingChanges};
+use tokio::task;
+
+use super::events::Message;
+use super::{EditorMode, LogEntry, LogMessage, MulticodeApp, Screen};
+
+/// Участок на экране git. Заголовок участка сохраняется, чтобы после
+/// обновления изменений отметка не перешла на другой участок.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HunkRef {
+    /// Участок из индексированных изменений.
+    pub staged: bool,
+    pub path: PathBuf,
+    pub index: usize,
+    pub header: String,
+}
+
+/// Состояние экрана git.
+#[derive(Debug, Clone, Default)]
+pub struct GitScreen {
+    pub changes: WorkingChanges,
+    /// Отмеченные участки.
+    pub selected: BTreeSet<HunkRef>,
+    /// Сообщение коммита.
+    pub message: String,
+    /// Исправить последний коммит вместо создания нового.
+    pub amend: bool,
+}
+
+impl GitScreen {
+    pub fn hunk(&self, r: &HunkRef) -> Option<&Hunk> {
+        let files = if r.staged {
+            &self.changes.staged
+        } else {
+            &self.changes.unstaged
+        };
+        files
+            .iter()
+            .find(|f| f.path == r.path)
+            .and_then(|f| f.hunks.get(r.index))
+    }
+
+    /// Заменяет изменения, оставляя отмеченными только сохранившиеся участки.
+    pub fn set_changes(&mut self, changes: WorkingChanges) {
+        self.changes = changes;
+        let selected = std::mem::take(&mut self.selected);
+        self.selected = selected
+            .into_iter()
+            .filter(|r| self.hunk(r).is_some_and(|h| h.header == r.header))
+            .collect();
+    }
+
+    pub fn toggle(&mut self, r: HunkRef) {
+        if !self.selected.remove(&r) {
+            self.selected.insert(r);
+        }
+    }
+
+    /// Номера отмеченных участков по файлам для одной стороны.
+    pub fn selection(&self, staged: bool) -> BTreeMap<PathBuf, Vec<usize>> {
+        let mut files: BTreeMap<PathBuf, Vec<usize>> = BTreeMap::new();
+        for r in self.selected.iter().filter(|r| r.staged == staged) {
+            files.entry(r.path.clone()).or_default().push(r.index);
+        }
+        files
+    }
+}
+
+impl MulticodeApp {
+    /// Открывает экран git проекта.
+    pub(super) fn open_git_screen(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        self.screen = Screen::Git { root };
+        self.load_git_changes()
+    }
+
+    /// Возвращается к редактору в текущем режиме.
+    pub(super) fn close_git_screen(&mut self) {
+        if let Screen::Git { root } = &self.screen {
+            let root = root.clone();
+            self.screen = match self.settings.editor_mode {
+                EditorMode::Text => Screen::TextEditor { root },
+                EditorMode::Visual => Screen::VisualEditor { root },
+                EditorMode::Split => Screen::Split { root },
+            };
+        }
+    }
+
+    /// Перечитывает изменения в фоне, если открыт экран git.
+    pub(super) fn load_git_changes(&self) -> Command<Message> {
+        let Screen::Git { root } = &self.screen else {
+            return Command::none();
+        };
+        let root = root.clone();
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    git::changes(&root).map_err(|e| e.message().to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r)
+            },
+            Message::GitChangesLoaded,
+        )
+    }
+
+    /// Добавляет отмеченные неиндексированные участки в индекс или убирает
+    /// отмеченные индексированные.
+    pub(super) fn apply_selected_hunks(&mut self, staged: bool) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let files = self.git_screen.selection(staged);
+        if files.is_empty() {
+            return Command::none();
+        }
+        self.git_screen.selected.retain(|r| r.staged != staged);
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    for (path, hunks) in files {
+                        let result = if staged {
+                            git::unstage_hunks(&path, &hunks)
+                        } else {
+                            git::stage_hunks(&path, &hunks)
+                        };
+                        result.map_err(|e| format!("{}: {}", path.display(), e.message()))?;
+                    }
+                    Ok(root)
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r)
+            },
+            Message::GitActionDone,
+        )
+    }
+
+    /// Включает исправление последнего коммита; пустое сообщение
+    /// заполняется сообщением этого коммита.
+    pub(super) fn set_commit_amend(&mut self, amend: bool) {
+        self.git_screen.amend = amend;
+        if !amend || !self.git_screen.message.trim().is_empty() {
+            return;
+        }
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        if let Ok(Some(message)) = git::head_message(&root) {
+            self.git_screen.message = message.trim_end().to_string();
+        }
+    }
+
+    /// Коммитит индекс с введённым сообщением.
+    pub(super) fn commit_changes(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let message = self.git_screen.message.trim().to_string();
+        let amend = self.git_screen.amend;
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    git::commit_index(&root, &message, amend)
+                        .map(|_| message)
+                        .map_err(|e| e.message().to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r)
+            },
+            Message::CommitDone,
+        )
+    }
+
+    pub(super) fn commit_done(&mut self, result: Result<String, String>) -> Command<Message> {
+        match result {
+            Ok(message) => {
+                let summary = message.lines().next().unwrap_or_default().to_string();
+                self.log.push(LogEntry::new(
+                    LogMessage::Committed,
+                    vec![summary],
+                    Utc::now(),
+                ));
+                self.git_screen.message.clear();
+                self.git_screen.amend = false;
+                Command::batch([self.load_git_changes(), self.refresh_git_status()])
+            }
+            Err(e) => {
+                self.log
+                    .push(LogEntry::new(LogMessage::GitError, vec![e], Utc::now()));
+                Command::none()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multicode_core::git::FileChanges;
+
+    fn hunk(header: &str) -> Hunk {
+        Hunk {
+            header: header.into(),
+            lines: Vec::new(),
+        }
+    }
+
+    fn changes(unstaged: Vec<Hunk>, staged: Vec<Hunk>) -> WorkingChanges {
+        let file = |hunks| FileChanges {
+            path: PathBuf::from("a.rs"),
+            hunks,
+            binary: false,
+        };
+        WorkingChanges {
+            unstaged: vec![file(unstaged)],
+            staged: vec![file(staged)],
+        }
+    }
+
+    fn hunk_ref(staged: bool, index: usize, header: &str) -> HunkRef {
+        HunkRef {
+            staged,
+            path: PathBuf::from("a.rs"),
+            index,
+            header: header.into(),
+        }
+    }
+
+    #[test]
+    fn selection_survives_only_unchanged_hunks() {
+        let mut screen = GitScreen::default();
+        screen.set_changes(changes(
+            vec![hunk("@@ -1 +1 @@"), hunk("@@ -9 +9 @@")],
+            vec![hunk("@@ -5 +5 @@")],
+        ));
+        screen.toggle(hunk_ref(false, 0, "@@ -1 +1 @@"));
+        screen.toggle(hunk_ref(false, 1, "@@ -9 +9 @@"));
+        screen.toggle(hunk_ref(true, 0, "@@ -5 +5 @@"));
+        assert_eq!(screen.selection(false)[&PathBuf::from("a.rs")], vec![0, 1]);
+        assert_eq!(screen.selection(true)[&PathBuf::from("a.rs")], vec![0]);
+
+        screen.toggle(hunk_ref(true, 0, "@@ -5 +5 @@"));
+        assert!(screen.selection(true).is_empty());
+
+        // Первый участок добавлен в индекс: второй стал первым.
+        screen.set_changes(changes(
+            vec![hunk("@@ -9 +9 @@")],
+            vec![hunk("@@ -1 +1 @@")],
+        ));
+        assert!(screen.selected.is_empty());
+    }
+}
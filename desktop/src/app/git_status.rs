@@ -0,0 +1,323 @@
+//! Состояние файлов проекта в git.
+//!
+//! Результат `git status` ([`multicode_core::git::status`]) хранится в
+//! [`GitDecorations`] и отображается метками у записей дерева файлов и
+//! заголовков вкладок. Каталог получает объединённую метку своих файлов.
+//! Состояние перечитывается при изменениях в каталоге проекта, которые
+//! сообщает наблюдатель [`watch`], а также после сохранения файлов и
+//! действий контекстного меню.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc as std_mpsc;
+use std::thread;
+use std::time::Duration;
+
+use chrono::Utc;
+use iced::futures::stream;
+use iced::{subscription, Color, Command, Subscription};
+use multicode_core::git::{self, FileStatus};
+use notify::{recommended_watcher, Event, RecursiveMode, Watcher};
+use tokio::sync::mpsc;
+use tokio::task;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp, Tab};
+use crate::components::file_manager::ContextMenuItem;
+
+/// Сколько ждать затишья в событиях файловой системы перед обновлением.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Состояния файлов и каталогов проекта.
+#[derive(Debug, Clone, Default)]
+pub struct GitDecorations {
+    files: HashMap<PathBuf, FileStatus>,
+    dirs: HashMap<PathBuf, FileStatus>,
+}
+
+impl GitDecorations {
+    /// Состояния файлов `files` каталога `root`; состояния каталогов между
+    /// файлом и `root` объединяются из их файлов.
+    pub fn new(root: &Path, files: HashMap<PathBuf, FileStatus>) -> Self {
+        let mut dirs: HashMap<PathBuf, FileStatus> = HashMap::new();
+        for (path, status) in &files {
+            for dir in path.ancestors().skip(1) {
+                if !dir.starts_with(root) || dir == root {
+                    break;
+                }
+                let merged = dirs.get(dir).copied().unwrap_or_default().merge(*status);
+                dirs.insert(dir.to_path_buf(), merged);
+            }
+        }
+        Self { files, dirs }
+    }
+
+    pub fn get(&self, path: &Path) -> Option<FileStatus> {
+        self.files
+            .get(path)
+            .or_else(|| self.dirs.get(path))
+            .copied()
+    }
+
+    /// Метка файла или каталога `path`, если у него есть изменения.
+    pub fn marker(&self, path: &Path) -> Option<String> {
+        self.get(path).map(marker).filter(|m| !m.is_empty())
+    }
+}
+
+/// Буквы состояния: `!` — конфликт, `S` — изменения в индексе,
+/// `M` — изменения в рабочей копии, `U` — файл не отслеживается.
+pub fn marker(status: FileStatus) -> String {
+    [
+        (status.conflicted, '!'),
+        (status.staged, 'S'),
+        (status.modified, 'M'),
+        (status.untracked, 'U'),
+    ]
+    .into_iter()
+    .filter(|(set, _)| *set)
+    .map(|(_, c)| c)
+    .collect()
+}
+
+/// Цвет метки: конфликт важнее остальных состояний, изменения в рабочей
+/// копии — индексированных.
+pub fn marker_color(status: FileStatus) -> Color {
+    if status.conflicted {
+        Color::from_rgb(0.85, 0.2, 0.2)
+    } else if status.modified {
+        Color::from_rgb(0.9, 0.6, 0.1)
+    } else if status.staged {
+        Color::from_rgb(0.2, 0.6, 0.3)
+    } else {
+        Color::from_rgb(0.3, 0.5, 0.9)
+    }
+}
+
+/// Пункты git контекстного меню для файла с состоянием `status`.
+pub fn menu_items(status: Option<FileStatus>) -> Vec<ContextMenuItem> {
+    let Some(status) = status else {
+        return Vec::new();
+    };
+    let mut items = Vec::new();
+    if status.modified || status.untracked {
+        items.push(ContextMenuItem::Stage);
+    }
+    if status.staged {
+        items.push(ContextMenuItem::Unstage);
+    }
+    if status.modified {
+        items.push(ContextMenuItem::Discard);
+    }
+    items
+}
+
+/// Изменения в каталоге `root`, кроме служебных файлов `.git`, не
+/// влияющих на состояние. События объединяются, пока идут чаще
+/// [`WATCH_DEBOUNCE`].
+pub fn watch(root: PathBuf) -> Subscription<Message> {
+    subscription::run_with_id(
+        ("git-status", root.clone()),
+        stream::unfold(None, move |state| {
+            let root = root.clone();
+            async move {
+                let (watcher, mut rx) = match state {
+                    Some(state) => state,
+                    None => start_watch(&root)?,
+                };
+                rx.recv().await?;
+                Some((Message::GitStatusRefresh, Some((watcher, rx))))
+            }
+        }),
+    )
+}
+
+fn start_watch(root: &Path) -> Option<(notify::RecommendedWatcher, mpsc::UnboundedReceiver<()>)> {
+    let (fs_tx, fs_rx) = std_mpsc::channel::<Event>();
+    let mut watcher = recommended_watcher(move |res: notify::Result<Event>| {
+        if let Ok(event) = res {
+            let _ = fs_tx.send(event);
+        }
+    })
+    .ok()?;
+    watcher.watch(root, RecursiveMode::Recursive).ok()?;
+    let (tx, rx) = mpsc::unbounded_channel();
+    thread::spawn(move || {
+        while let Ok(event) = fs_rx.recv() {
+            if !event.paths.iter().any(|p| affects_status(p)) {
+                continue;
+            }
+            while fs_rx.recv_timeout(WATCH_DEBOUNCE).is_ok() {}
+            if tx.send(()).is_err() {
+                break;
+            }
+        }
+    });
+    Some((watcher, rx))
+}
+
+/// Внутри `.git` состояние меняют только индекс и `HEAD`; остальные файлы
+/// (объекты, журналы, блокировки) пропускаются.
+fn affects_status(path: &Path) -> bool {
+    let mut components = path.components().map(|c| c.as_os_str());
+    if !components.any(|c| c == ".git") {
+        return true;
+    }
+    matches!(
+        components.next().and_then(|c| c.to_str()),
+        Some("index" | "HEAD")
+    ) && components.next().is_none()
+}
+
+impl MulticodeApp {
+    /// Заголовок вкладки: имя файла и метка его состояния в git.
+    pub(super) fn tab_title(&self, tab: &Tab) -> String {
+        let name = tab
+            .path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        match self.git_status.marker(&tab.path) {
+            Some(marker) => format!("{name} {marker}"),
+            None => name,
+        }
+    }
+
+    /// Пункты контекстного меню дерева для `path`.
+    pub(super) fn context_menu_items(&self, path: &Path) -> Vec<ContextMenuItem> {
+        let mut items = Vec::new();
+        if !path.is_dir() {
+            items.push(ContextMenuItem::Open);
+        }
+        items.extend(menu_items(self.git_status.get(path)));
+        items
+    }
+
+    /// Перечитывает состояние git проекта в фоне.
+    pub(super) fn refresh_git_status(&self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    let files = git::status(&root).map_err(|e| e.message().to_string())?;
+                    Ok(GitDecorations::new(&root, files))
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r)
+            },
+            Message::GitStatusLoaded,
+        )
+    }
+
+    /// Выполняет git-действие контекстного меню для `path`. Отмена
+    /// изменений сначала запрашивает подтверждение.
+    pub(super) fn git_action(&mut self, item: ContextMenuItem, path: PathBuf) -> Command<Message> {
+        match item {
+            ContextMenuItem::Stage | ContextMenuItem::Unstage => {}
+            ContextMenuItem::Discard if self.git_discard_confirm.as_ref() != Some(&path) => {
+                self.git_discard_confirm = Some(path);
+                return Command::none();
+            }
+            ContextMenuItem::Discard => self.git_discard_confirm = None,
+            _ => return Command::none(),
+        }
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    let result = match item {
+                        ContextMenuItem::Stage => git::stage(&path),
+                        ContextMenuItem::Unstage => git::unstage(&path),
+                        _ => git::discard(&path),
+                    };
+                    result
+                        .map(|_| path.clone())
+                        .map_err(|e| format!("{}: {}", path.display(), e.message()))
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r)
+            },
+            Message::GitActionDone,
+        )
+    }
+
+    /// Завершение git-действия: вкладки без правок внутри `path`
+    /// перечитываются с диска, ведь отмена изменений переписывает файлы.
+    pub(super) fn git_action_done(&mut self, result: Result<PathBuf, String>) -> Command<Message> {
+        match result {
+            Ok(path) => Command::batch([
+                self.reload_clean_tabs(&path),
+                self.refresh_git_status(),
+                self.load_git_changes(),
+            ]),
+            Err(e) => {
+                self.log
+                    .push(LogEntry::new(LogMessage::GitError, vec![e], Utc::now()));
+                Command::none()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(modified: bool, staged: bool, untracked: bool) -> FileStatus {
+        FileStatus {
+            modified,
+            staged,
+            untracked,
+            conflicted: false,
+        }
+    }
+
+    #[test]
+    fn directories_merge_statuses_of_their_files() {
+        let root = Path::new("/p");
+        let files = HashMap::from([
+            (root.join("src/a.rs"), status(true, false, false)),
+            (root.join("src/deep/b.rs"), status(false, true, false)),
+            (root.join("new.rs"), status(false, false, true)),
+        ]);
+        let git = GitDecorations::new(root, files);
+        assert_eq!(git.marker(&root.join("src/a.rs")).as_deref(), Some("M"));
+        assert_eq!(git.marker(&root.join("src/deep")).as_deref(), Some("S"));
+        assert_eq!(git.marker(&root.join("src")).as_deref(), Some("SM"));
+        assert_eq!(git.marker(&root.join("new.rs")).as_deref(), Some("U"));
+        assert_eq!(git.marker(root), None);
+        assert_eq!(git.marker(&root.join("clean.rs")), None);
+    }
+
+    #[test]
+    fn menu_offers_actions_matching_status() {
+        let items = |s| {
+            menu_items(Some(s))
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert!(menu_items(None).is_empty());
+        assert_eq!(items(status(false, false, true)), ["Добавить в индекс"]);
+        assert_eq!(
+            items(status(true, true, false)),
+            [
+                "Добавить в индекс",
+                "Убрать из индекса",
+                "Отменить изменения"
+            ]
+        );
+    }
+
+    #[test]
+    fn git_internals_do_not_trigger_refresh() {
+        assert!(affects_status(Path::new("/p/src/a.rs")));
+        assert!(affects_status(Path::new("/p/.git/index")));
+        assert!(affects_status(Path::new("/p/.git/HEAD")));
+        assert!(!affects_status(Path::new("/p/.git/objects/ab/cd")));
+        assert!(!affects_status(Path::new("/p/.git/index.lock")));
+    }
+}
@@ -0,0 +1,166 @@
+//! Панель наследования метаданных.
+//!
+//! Показывает цепочку `extends` записи выбранного блока (или файла, если
+//! у блока нет своей записи), позволяет сменить её базу и заранее видеть
+//! итог слияния. Замкнутая цепочка отмечается на панели, а применить
+//! правку, которая замкнула бы её, нельзя.
+
+use iced::widget::{button, column, row, scrollable, text, text_input, Space};
+use iced::{theme, Color, Element, Length};
+use multicode_core::meta::inheritance::{inheritance_chain, set_extends, InheritanceChain};
+use multicode_core::meta::read_all;
+
+use super::events::Message;
+use super::MulticodeApp;
+
+/// Текст `content`, в котором база записи `id` заменена на `extends`;
+/// пустая строка убирает базу. `None`, если записи `id` нет.
+pub fn with_extends(content: &str, id: &str, extends: &str) -> Option<String> {
+    if inheritance_chain(content, id).entries.is_empty() {
+        return None;
+    }
+    let extends = extends.trim();
+    Some(set_extends(
+        content,
+        id,
+        (!extends.is_empty()).then_some(extends),
+    ))
+}
+
+/// Черновик правки панели для открытой записи.
+struct Draft {
+    id: String,
+    /// Поле `extends` в поле ввода.
+    extends: String,
+    /// Текст файла с применённым черновиком.
+    content: String,
+    chain: InheritanceChain,
+    changed: bool,
+}
+
+impl MulticodeApp {
+    /// Запись, цепочку которой показывает панель: выбранный блок, если у
+    /// него есть метаданные, иначе корневая запись файла.
+    fn inheritance_target(&self) -> Option<String> {
+        let tab = self.current_file()?;
+        self.canvas_focus
+            .and_then(|i| tab.blocks.get(i))
+            .map(|b| b.visual_id.clone())
+            .filter(|id| !inheritance_chain(&tab.content, id).entries.is_empty())
+            .or_else(|| tab.meta.as_ref().map(|m| m.id.clone()))
+    }
+
+    fn inheritance_draft(&self) -> Option<Draft> {
+        let id = self.inheritance_target()?;
+        let tab = self.current_file()?;
+        let stored = inheritance_chain(&tab.content, &id)
+            .entries
+            .first()?
+            .extends
+            .clone()
+            .unwrap_or_default();
+        let extends = match &self.extends_draft {
+            Some((draft_id, extends)) if *draft_id == id => extends.clone(),
+            _ => stored.clone(),
+        };
+        let content = with_extends(&tab.content, &id, &extends)?;
+        Some(Draft {
+            chain: inheritance_chain(&content, &id),
+            changed: extends.trim() != stored,
+            id,
+            extends,
+            content,
+        })
+    }
+
+    pub(super) fn extends_draft_changed(&mut self, value: String) {
+        if let Some(id) = self.inheritance_target() {
+            self.extends_draft = Some((id, value));
+        }
+    }
+
+    /// Записывает новую базу в текст вкладки. Правка, замыкающая цепочку,
+    /// не применяется.
+    pub(super) fn apply_extends(&mut self) {
+        let Some(draft) = self.inheritance_draft() else {
+            return;
+        };
+        if !draft.changed || draft.chain.cycle.is_some() {
+            return;
+        }
+        if let Some(f) = self.current_file_mut() {
+            f.content = draft.content;
+            f.editor = iced::widget::text_editor::Content::with_text(&f.content);
+            f.meta = read_all(&f.content).into_iter().next();
+            f.dirty = true;
+        }
+        self.extends_draft = None;
+    }
+
+    pub fn inheritance_panel_component(&self) -> Element<'_, Message> {
+        if !self.show_inheritance_panel {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let error = |s: String| text(s).style(theme::Text::Color(Color::from_rgb(0.8, 0.0, 0.0)));
+        let Some(draft) = self.inheritance_draft() else {
+            return column![
+                row![
+                    text("Наследование").width(Length::Fill),
+                    button("×").on_press(Message::ToggleInheritancePanel),
+                ]
+                .spacing(5),
+                text("Метаданных нет"),
+            ]
+            .spacing(5)
+            .into();
+        };
+        let header = row![
+            text(format!("Наследование {}", draft.id)).width(Length::Fill),
+            button("×").on_press(Message::ToggleInheritancePanel),
+        ]
+        .spacing(5);
+        let mut chain = column![].spacing(2);
+        for (depth, entry) in draft.chain.entries.iter().enumerate() {
+            let tags = if entry.tags.is_empty() {
+                "-".to_string()
+            } else {
+                entry.tags.join(", ")
+            };
+            chain = chain.push(text(format!(
+                "{}{}  теги: {tags}",
+                "  ".repeat(depth),
+                entry.id
+            )));
+        }
+        if let Some(id) = &draft.chain.missing {
+            chain = chain.push(error(format!("{id} — нет в файле")));
+        }
+        if let Some(id) = &draft.chain.cycle {
+            chain = chain.push(error(format!("↻ {id} — цикл наследования")));
+        }
+        let can_apply = draft.changed && draft.chain.cycle.is_none();
+        let edit = row![
+            text("extends"),
+            text_input("база", &draft.extends)
+                .on_input(Message::ExtendsDraftChanged)
+                .on_submit(Message::ApplyExtends)
+                .width(Length::Fill),
+            button("Применить").on_press_maybe(can_apply.then_some(Message::ApplyExtends)),
+        ]
+        .spacing(5);
+        let merged = read_all(&draft.content)
+            .into_iter()
+            .find(|m| m.id == draft.id)
+            .and_then(|m| serde_json::to_string_pretty(&m).ok())
+            .unwrap_or_default();
+        column![
+            header,
+            chain,
+            edit,
+            text("Итог слияния"),
+            scrollable(text(merged)).height(Length::Fixed(160.0)),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
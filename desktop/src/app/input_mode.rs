@@ -0,0 +1,129 @@
+//! Режимы ввода Vim и Emacs в редакторе кода.
+//!
+//! Правки из редактора ([`Message::FileContentEdited`]) и нажатия, которые
+//! редактор не обработал, проходят через режим ввода из настроек
+//! ([`InputMode`]). Сочетания Emacs с `Ctrl` редактор пропускает, они
+//! приходят событиями клавиатуры и важнее сочетаний приложения; сочетания
+//! с `Alt` редактор вводит как текст, поэтому они распознаются по
+//! последним нажатым модификаторам. `Escape` редактор поглощает, снимая с
+//! себя фокус, — для Vim он приходит отдельным сообщением
+//! [`Message::EditorEscape`], а команды обычного режима после этого
+//! принимаются и без фокуса.
+
+use iced::keyboard::{self, key::Named, Key, Modifiers};
+use iced::widget::text_editor::{Action, Edit};
+use iced::{event, Command, Event};
+
+use super::events::Message;
+use super::{MulticodeApp, Screen};
+use crate::editor::input_mode::{delete_lines, emacs_command, InputCommand, InputMode, VimMode};
+
+/// `Escape`, поглощённый виджетом, в `event::listen` не попадает.
+pub(super) fn captured_escape(event: Event, status: event::Status) -> Option<Message> {
+    match (event, status) {
+        (
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key: Key::Named(Named::Escape),
+                ..
+            }),
+            event::Status::Captured,
+        ) => Some(Message::EditorEscape),
+        _ => None,
+    }
+}
+
+impl MulticodeApp {
+    /// Правка из редактора с учётом режима ввода.
+    pub(super) fn edit_with_input_mode(&mut self, action: Action) -> Command<Message> {
+        let commands = match self.settings.editor.input_mode {
+            InputMode::Standard => None,
+            InputMode::Vim => self.vim.action(&action),
+            InputMode::Emacs => match &action {
+                Action::Edit(Edit::Insert(c)) if self.modifiers.alt() => {
+                    emacs_command(&c.to_string(), self.modifiers)
+                }
+                _ => None,
+            },
+        };
+        match commands {
+            Some(commands) => self.apply_input_commands(commands),
+            None => self.perform_edit(action),
+        }
+    }
+
+    /// Нажатие, которое не обработал редактор; `None`, если режим ввода
+    /// его не использует.
+    pub(super) fn input_mode_key(
+        &mut self,
+        key: &Key,
+        modifiers: Modifiers,
+    ) -> Option<Command<Message>> {
+        if self.current_file().is_none()
+            || !matches!(
+                self.screen,
+                Screen::TextEditor { .. } | Screen::Split { .. }
+            )
+        {
+            return None;
+        }
+        match self.settings.editor.input_mode {
+            InputMode::Standard => None,
+            InputMode::Emacs => {
+                let Key::Character(c) = key else {
+                    return None;
+                };
+                let commands = emacs_command(c.as_str(), modifiers)?;
+                Some(self.apply_input_commands(commands))
+            }
+            InputMode::Vim if modifiers.control() || modifiers.alt() => {
+                let redo = modifiers.control()
+                    && !modifiers.alt()
+                    && self.vim.mode == VimMode::Normal
+                    && matches!(key, Key::Character(c) if c.as_str() == "r");
+                redo.then(|| self.apply_input_commands(vec![InputCommand::Redo]))
+            }
+            InputMode::Vim => {
+                let action = match key {
+                    Key::Named(Named::Escape) => return Some(self.vim_escape()),
+                    Key::Named(Named::Space) => Action::Edit(Edit::Insert(' ')),
+                    Key::Named(Named::Enter) => Action::Edit(Edit::Enter),
+                    Key::Named(Named::Backspace) => Action::Edit(Edit::Backspace),
+                    Key::Character(c) => Action::Edit(Edit::Insert(c.chars().next()?)),
+                    _ => return None,
+                };
+                Some(self.edit_with_input_mode(action))
+            }
+        }
+    }
+
+    /// Выход Vim в обычный режим.
+    pub(super) fn vim_escape(&mut self) -> Command<Message> {
+        if self.settings.editor.input_mode != InputMode::Vim {
+            return Command::none();
+        }
+        let commands = self.vim.escape();
+        self.apply_input_commands(commands)
+    }
+
+    fn apply_input_commands(&mut self, commands: Vec<InputCommand>) -> Command<Message> {
+        let mut cmds = Vec::new();
+        for command in commands {
+            match command {
+                InputCommand::Action(action) => cmds.push(self.perform_edit(action)),
+                InputCommand::DeleteLines(count) => {
+                    let actions = self
+                        .current_file()
+                        .map(|f| {
+                            let (line, _) = f.editor.cursor_position();
+                            delete_lines(line, f.editor.line_count(), count)
+                        })
+                        .unwrap_or_default();
+                    cmds.extend(actions.into_iter().map(|a| self.perform_edit(a)));
+                }
+                InputCommand::Undo => cmds.push(self.handle_message(Message::Undo)),
+                InputCommand::Redo => cmds.push(self.handle_message(Message::Redo)),
+            }
+        }
+        Command::batch(cmds)
+    }
+}
@@ -1,11 +1,17 @@
 use iced::Command;
+use multicode_core::config::{HookEvent, HooksConfig, MetaConfig};
+use multicode_core::file_io;
+use multicode_core::ignore_rules::IgnoreRules;
 use multicode_core::meta;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 use tokio::task;
 
 use super::{EntryType, FileEntry, MulticodeApp};
 use crate::app::events::Message;
+use crate::components::file_manager::{self, TreeSearch};
 
 pub fn pick_folder() -> impl std::future::Future<Output = Option<PathBuf>> {
     async {
@@ -34,55 +40,201 @@ pub fn pick_file_in_dir(dir: PathBuf) -> impl std::future::Future<Output = Optio
     }
 }
 
+/// Сколько совпадений показывает поиск по дереву файлов.
+pub const MAX_TREE_SEARCH_RESULTS: usize = 500;
+
+fn has_meta(path: &Path, tabs_meta: &HashMap<PathBuf, bool>) -> bool {
+    tabs_meta.get(path).copied().unwrap_or_else(|| {
+        file_io::read_to_string(path)
+            .ok()
+            .map(|c| !meta::read_all(&c).is_empty())
+            .unwrap_or(false)
+    })
+}
+
+/// Читает один уровень каталога `dir`. Вложенные каталоги не читаются,
+/// кроме перечисленных в `expanded`: их содержимое загружается сразу,
+/// чтобы раскрытые каталоги не схлопывались при обновлении дерева.
+pub fn read_dir_entries(
+    dir: &Path,
+    expanded: &HashSet<PathBuf>,
+    tabs_meta: &HashMap<PathBuf, bool>,
+    rules: &IgnoreRules,
+) -> Vec<FileEntry> {
+    let mut entries = Vec::new();
+    let Ok(read) = std::fs::read_dir(dir) else {
+        return entries;
+    };
+    let mut read: Vec<_> = read.flatten().collect();
+    read.sort_by_key(|e| e.path());
+    for entry in read {
+        let Ok(ft) = entry.file_type() else {
+            continue;
+        };
+        let path = entry.path();
+        if rules.is_ignored(&path, ft.is_dir()) {
+            continue;
+        }
+        if ft.is_dir() {
+            let loaded = expanded.contains(&path);
+            let children = if loaded {
+                read_dir_entries(&path, expanded, tabs_meta, rules)
+            } else {
+                Vec::new()
+            };
+            entries.push(FileEntry {
+                path,
+                ty: EntryType::Dir,
+                has_meta: false,
+                children,
+                loaded,
+            });
+        } else if ft.is_file() {
+            entries.push(FileEntry {
+                has_meta: has_meta(&path, tabs_meta),
+                path,
+                ty: EntryType::File,
+                children: Vec::new(),
+                loaded: true,
+            });
+        }
+    }
+    entries
+}
+
+/// Ищет в `root` файлы и каталоги, имя которых содержит `query` без учёта
+/// регистра. Обход идёт в ширину, поэтому при превышении `limit` первыми
+/// остаются совпадения ближе к корню. Возвращает `None`, если `cancelled`
+/// сообщил об отмене.
+pub fn search_tree(
+    root: &Path,
+    query: &str,
+    limit: usize,
+    tabs_meta: &HashMap<PathBuf, bool>,
+    cancelled: impl Fn() -> bool,
+) -> Option<TreeSearch> {
+    let q = query.to_lowercase();
+    let rules = IgnoreRules::load(root);
+    let mut matches = Vec::new();
+    let mut truncated = false;
+    let mut queue = VecDeque::from([root.to_path_buf()]);
+    'walk: while let Some(dir) = queue.pop_front() {
+        if cancelled() {
+            return None;
+        }
+        let Ok(read) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut read: Vec<_> = read.flatten().collect();
+        read.sort_by_key(|e| e.path());
+        for entry in read {
+            let Ok(ft) = entry.file_type() else {
+                continue;
+            };
+            let path = entry.path();
+            if rules.is_ignored(&path, ft.is_dir()) {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().to_lowercase();
+            if name.contains(&q) {
+                if matches.len() == limit {
+                    truncated = true;
+                    break 'walk;
+                }
+                matches.push((path.clone(), ft.is_dir()));
+            }
+            if ft.is_dir() {
+                queue.push_back(path);
+            }
+        }
+    }
+    let mut entries = Vec::new();
+    for (path, is_dir) in matches {
+        let Ok(rel) = path.strip_prefix(root) else {
+            continue;
+        };
+        insert_match(&mut entries, root, rel, is_dir, tabs_meta);
+    }
+    Some(TreeSearch {
+        query: query.to_string(),
+        entries,
+        truncated,
+        complete: true,
+    })
+}
+
+/// Добавляет совпадение `rel` в дерево результатов вместе с его
+/// родительскими каталогами.
+fn insert_match(
+    entries: &mut Vec<FileEntry>,
+    base: &Path,
+    rel: &Path,
+    is_dir: bool,
+    tabs_meta: &HashMap<PathBuf, bool>,
+) {
+    let mut components = rel.components();
+    let Some(first) = components.next() else {
+        return;
+    };
+    let path = base.join(first);
+    let rest = components.as_path();
+    if rest.as_os_str().is_empty() {
+        if !entries.iter().any(|e| e.path == path) {
+            let entry = if is_dir {
+                FileEntry {
+                    path,
+                    ty: EntryType::Dir,
+                    has_meta: false,
+                    children: Vec::new(),
+                    loaded: false,
+                }
+            } else {
+                FileEntry {
+                    has_meta: has_meta(&path, tabs_meta),
+                    path,
+                    ty: EntryType::File,
+                    children: Vec::new(),
+                    loaded: true,
+                }
+            };
+            entries.push(entry);
+        }
+        return;
+    }
+    let idx = match entries.iter().position(|e| e.path == path) {
+        Some(idx) => idx,
+        None => {
+            entries.push(FileEntry {
+                path: path.clone(),
+                ty: EntryType::Dir,
+                has_meta: false,
+                children: Vec::new(),
+                loaded: false,
+            });
+            entries.len() - 1
+        }
+    };
+    insert_match(&mut entries[idx].children, &path, rest, is_dir, tabs_meta);
+}
+
 impl MulticodeApp {
-    pub fn load_files(&self, root: PathBuf) -> Command<Message> {
-        let tabs_meta: HashMap<PathBuf, bool> = self
-            .tabs
+    fn tabs_meta(&self) -> HashMap<PathBuf, bool> {
+        self.tabs
             .iter()
             .map(|t| (t.path.clone(), t.meta.is_some()))
-            .collect();
+            .collect()
+    }
+
+    /// Загружает верхний уровень проекта и раскрытые каталоги.
+    pub fn load_files(&self, root: PathBuf) -> Command<Message> {
+        meta::set_default_encoding(MetaConfig::load(&root).encoding);
+        let tabs_meta = self.tabs_meta();
+        let expanded = self.expanded_dirs.clone();
         Command::perform(
             async move {
-                let tabs_meta = tabs_meta;
                 task::spawn_blocking(move || {
-                    fn visit(dir: &Path, tabs_meta: &HashMap<PathBuf, bool>) -> Vec<FileEntry> {
-                        let mut entries = Vec::new();
-                        if let Ok(read) = std::fs::read_dir(dir) {
-                            let mut read: Vec<_> = read.flatten().collect();
-                            read.sort_by_key(|e| e.path());
-                            for entry in read {
-                                if let Ok(ft) = entry.file_type() {
-                                    let path = entry.path();
-                                    if ft.is_dir() {
-                                        let children = visit(&path, tabs_meta);
-                                        entries.push(FileEntry {
-                                            path,
-                                            ty: EntryType::Dir,
-                                            has_meta: false,
-                                            children,
-                                        });
-                                    } else if ft.is_file() {
-                                        let has_meta =
-                                            tabs_meta.get(&path).copied().unwrap_or_else(|| {
-                                                std::fs::read_to_string(&path)
-                                                    .ok()
-                                                    .map(|c| !meta::read_all(&c).is_empty())
-                                                    .unwrap_or(false)
-                                            });
-                                        entries.push(FileEntry {
-                                            path,
-                                            ty: EntryType::File,
-                                            has_meta,
-                                            children: Vec::new(),
-                                        });
-                                    }
-                                }
-                            }
-                        }
-                        entries
-                    }
-
-                    visit(&root, &tabs_meta)
+                    let rules = IgnoreRules::load(&root);
+                    read_dir_entries(&root, &expanded, &tabs_meta, &rules)
                 })
                 .await
                 .map_err(|e| e.to_string())
@@ -94,6 +246,104 @@ impl MulticodeApp {
         )
     }
 
+    /// Читает содержимое каталога `dir` при первом раскрытии.
+    pub fn load_dir(&self, dir: PathBuf) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let tabs_meta = self.tabs_meta();
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    let rules = IgnoreRules::load(&root);
+                    let children = read_dir_entries(&dir, &HashSet::new(), &tabs_meta, &rules);
+                    (dir, children)
+                })
+                .await
+                .map_err(|e| e.to_string())
+            },
+            |res| match res {
+                Ok((dir, children)) => Message::DirLoaded(dir, children),
+                Err(e) => Message::FileError(e),
+            },
+        )
+    }
+
+    /// Загружает каталог `dir`, если он ещё не прочитан.
+    pub(super) fn load_dir_if_needed(&self, dir: PathBuf) -> Command<Message> {
+        match file_manager::find_entry(&self.files, &dir) {
+            Some(entry) if matches!(entry.ty, EntryType::Dir) && !entry.loaded => {
+                self.load_dir(dir)
+            }
+            _ => Command::none(),
+        }
+    }
+
+    /// Запускает поиск по дереву файлов для текущей строки фильтра. Пока
+    /// обход идёт, показываются совпадения среди загруженных каталогов;
+    /// предыдущий обход отменяется.
+    pub fn search_file_tree(&mut self) -> Command<Message> {
+        let generation = self.file_search_generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let query = self.search_query.trim().to_string();
+        if query.is_empty() {
+            self.file_search = None;
+            return Command::none();
+        }
+        self.file_search = Some(TreeSearch {
+            entries: file_manager::filter_entries(&self.files, &query),
+            query: query.clone(),
+            truncated: false,
+            complete: false,
+        });
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let tabs_meta = self.tabs_meta();
+        let current = Arc::clone(&self.file_search_generation);
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    search_tree(&root, &query, MAX_TREE_SEARCH_RESULTS, &tabs_meta, || {
+                        current.load(Ordering::SeqCst) != generation
+                    })
+                })
+                .await
+                .ok()
+                .flatten()
+            },
+            Message::FileTreeSearched,
+        )
+    }
+
+    /// Записи дерева для отображения и раскрытые каталоги; при активном
+    /// поиске показываются его результаты, раскрытые целиком.
+    pub fn file_tree_view(&self) -> (&[FileEntry], Option<&HashSet<PathBuf>>) {
+        match &self.file_search {
+            Some(search) => (&search.entries, None),
+            None => (&self.files, Some(&self.expanded_dirs)),
+        }
+    }
+
+    /// Запускает хук из `multicode.toml` проекта для события `event`.
+    pub fn run_hook(&self, event: HookEvent) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let hooks = HooksConfig::load(&root);
+        if hooks.command_for(&event).is_none() {
+            return Command::none();
+        }
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || hooks.run(&event, &root))
+                    .await
+                    .map_err(|e| e.to_string())
+                    .and_then(|r| r)
+            },
+            Message::HookFinished,
+        )
+    }
+
     pub fn collect_files(entries: &[FileEntry], out: &mut Vec<PathBuf>) {
         for entry in entries {
             match entry.ty {
@@ -103,9 +353,33 @@ impl MulticodeApp {
         }
     }
 
-    pub fn file_paths(&self) -> Vec<PathBuf> {
+    /// Все файлы проекта `root`, кроме исключённых правилами
+    /// `.multicode/ignore`. Дерево файлов загружается лениво, поэтому
+    /// полный список собирается обходом диска.
+    pub fn project_files(root: &Path) -> Vec<PathBuf> {
+        let rules = IgnoreRules::load(root);
         let mut out = Vec::new();
-        Self::collect_files(&self.files, &mut out);
+        let mut stack = vec![root.to_path_buf()];
+        while let Some(dir) = stack.pop() {
+            let Ok(read) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in read.flatten() {
+                let Ok(ft) = entry.file_type() else {
+                    continue;
+                };
+                let path = entry.path();
+                if rules.is_ignored(&path, ft.is_dir()) {
+                    continue;
+                }
+                if ft.is_dir() {
+                    stack.push(path);
+                } else if ft.is_file() {
+                    out.push(path);
+                }
+            }
+        }
+        out.sort();
         out
     }
 }
@@ -122,6 +396,7 @@ mod tests {
                 ty: EntryType::File,
                 has_meta: false,
                 children: vec![],
+                loaded: true,
             },
             FileEntry {
                 path: PathBuf::from("dir"),
@@ -132,7 +407,9 @@ mod tests {
                     ty: EntryType::File,
                     has_meta: false,
                     children: vec![],
+                    loaded: true,
                 }],
+                loaded: true,
             },
         ];
         let mut out = Vec::new();
@@ -141,4 +418,92 @@ mod tests {
         assert!(out.contains(&PathBuf::from("a.txt")));
         assert!(out.contains(&PathBuf::from("dir/b.txt")));
     }
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/deep/nested")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::create_dir_all(root.join(".multicode")).unwrap();
+        std::fs::write(root.join(".multicode/ignore"), "target/\n").unwrap();
+        std::fs::write(root.join("main.rs"), "").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.join("src/deep/nested/main_test.rs"), "").unwrap();
+        std::fs::write(root.join("target/main.rs"), "").unwrap();
+        dir
+    }
+
+    #[test]
+    fn read_dir_entries_loads_only_expanded_dirs() {
+        let dir = project();
+        let root = dir.path();
+        let rules = IgnoreRules::load(root);
+        let tabs_meta = HashMap::new();
+
+        let entries = read_dir_entries(root, &HashSet::new(), &tabs_meta, &rules);
+        let names: Vec<_> = entries
+            .iter()
+            .map(|e| e.path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                PathBuf::from(".multicode"),
+                PathBuf::from("main.rs"),
+                PathBuf::from("src")
+            ]
+        );
+        let src = &entries[2];
+        assert!(!src.loaded);
+        assert!(src.children.is_empty());
+
+        let expanded = HashSet::from([root.join("src")]);
+        let entries = read_dir_entries(root, &expanded, &tabs_meta, &rules);
+        let src = &entries[2];
+        assert!(src.loaded);
+        assert_eq!(src.children.len(), 2);
+        assert!(!src.children[0].loaded);
+    }
+
+    #[test]
+    fn search_tree_walks_unloaded_dirs_with_cap() {
+        let dir = project();
+        let root = dir.path();
+        let tabs_meta = HashMap::new();
+
+        let search = search_tree(root, "MAIN", 10, &tabs_meta, || false).unwrap();
+        assert!(search.complete);
+        assert!(!search.truncated);
+        let paths: Vec<_> = file_manager::visible_rows(&search.entries, None)
+            .into_iter()
+            .map(|r| r.entry.path.strip_prefix(root).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                PathBuf::from("main.rs"),
+                PathBuf::from("src"),
+                PathBuf::from("src/deep"),
+                PathBuf::from("src/deep/nested"),
+                PathBuf::from("src/deep/nested/main_test.rs"),
+            ]
+        );
+
+        // Обход в ширину: при ограничении остаётся совпадение у корня.
+        let search = search_tree(root, "main", 1, &tabs_meta, || false).unwrap();
+        assert!(search.truncated);
+        assert_eq!(search.entries.len(), 1);
+        assert_eq!(search.entries[0].path, root.join("main.rs"));
+
+        assert!(search_tree(root, "main", 10, &tabs_meta, || true).is_none());
+    }
+
+    #[test]
+    fn project_files_skips_ignored() {
+        let dir = project();
+        let root = dir.path();
+        let files = MulticodeApp::project_files(root);
+        assert!(files.contains(&root.join("src/deep/nested/main_test.rs")));
+        assert!(!files.contains(&root.join("target/main.rs")));
+    }
 }
@@ -0,0 +1,118 @@
+#[cfg(not(target_os = "windows"))]
+use std::process::Command as Process;
+
+use chrono::Utc;
+use iced::Command;
+use multicode_core::config::IssueLinksConfig;
+use multicode_core::meta::issue_link::IssueLink;
+#[cfg(target_os = "windows")]
+use multicode_core::shell::Shell;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+/// Результат разбора ссылки на задачу, который кэшируется для панели мета.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IssueLinkStatus {
+    /// Адрес задачи во внешнем трекере.
+    Url(String),
+    /// Для трекера не настроен шаблон в `[issue_links]`.
+    UnknownTracker,
+    /// Ключ ссылки не соответствует формату трекера.
+    Invalid(String),
+}
+
+/// Определяет статус ссылки; `None` для ссылок на блоки.
+pub(super) fn link_status(config: &IssueLinksConfig, link: &str) -> Option<IssueLinkStatus> {
+    Some(match IssueLink::parse(link)? {
+        Err(e) => IssueLinkStatus::Invalid(e),
+        Ok(_) => match config.resolve(link) {
+            Some(url) => IssueLinkStatus::Url(url),
+            None => IssueLinkStatus::UnknownTracker,
+        },
+    })
+}
+
+/// Открывает адрес в системном браузере.
+fn open_url(url: &str) -> std::io::Result<()> {
+    // В адресах встречаются `&` и `%`, поэтому для cmd.exe адрес
+    // экранируется, а не передаётся отдельным аргументом.
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let shell = Shell::system();
+        shell.command(&format!("start \"\" {}", shell.quote(url)))
+    };
+    #[cfg(target_os = "macos")]
+    let mut cmd = {
+        let mut cmd = Process::new("open");
+        cmd.arg(url);
+        cmd
+    };
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut cmd = {
+        let mut cmd = Process::new("xdg-open");
+        cmd.arg(url);
+        cmd
+    };
+    cmd.spawn().map(|_| ())
+}
+
+impl MulticodeApp {
+    /// Перечитывает шаблоны ссылок проекта и сбрасывает кэш статусов.
+    pub(super) fn reload_issue_links(&mut self) {
+        self.issue_links = self
+            .current_root_path()
+            .map(|root| IssueLinksConfig::load(&root))
+            .unwrap_or_default();
+        self.issue_link_cache.borrow_mut().clear();
+    }
+
+    /// Статус ссылки из кэша; при первом обращении ссылка разбирается.
+    pub(super) fn issue_link_status(&self, link: &str) -> Option<IssueLinkStatus> {
+        if let Some(status) = self.issue_link_cache.borrow().get(link) {
+            return status.clone();
+        }
+        let status = link_status(&self.issue_links, link);
+        self.issue_link_cache
+            .borrow_mut()
+            .insert(link.to_string(), status.clone());
+        status
+    }
+
+    /// Открывает ссылку на задачу во внешнем браузере.
+    pub(super) fn open_external_link(&mut self, url: String) -> Command<Message> {
+        if let Err(e) = open_url(&url) {
+            self.log.push(LogEntry::new(
+                LogMessage::OpenLinkError,
+                vec![url, e.to_string()],
+                Utc::now(),
+            ));
+        }
+        Command::none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statuses_distinguish_block_ids_and_trackers() {
+        let config = IssueLinksConfig::default();
+        assert_eq!(link_status(&config, "42"), None);
+        assert_eq!(
+            link_status(&config, "gh:owner/repo#42"),
+            Some(IssueLinkStatus::Url(
+                "https://github.com/owner/repo/issues/42".into()
+            ))
+        );
+        assert_eq!(
+            link_status(&config, "jira:PROJ-1"),
+            Some(IssueLinkStatus::UnknownTracker)
+        );
+        assert!(matches!(
+            link_status(&config, "jira:oops"),
+            Some(IssueLinkStatus::Invalid(_))
+        ));
+    }
+}
@@ -27,6 +27,56 @@ pub enum LogMessage {
     Command,
     RunError,
     BlocksUpdated,
+    HookFinished,
+    HookFailed,
+    SyncRecordingStarted,
+    SyncRecordingStopped,
+    BlocksCopied,
+    BlocksPasted,
+    FileRefNotFound,
+    ThemeLoaded,
+    ThemeLoadError,
+    TagsMerged,
+    TagsMergeError,
+    MetaIdRenamed,
+    MetaIdRenameError,
+    MetaTemplatesError,
+    SearchSaved,
+    SavedSearchError,
+    OpenLinkError,
+    FormatError,
+    CoverageMissing,
+    CoverageError,
+    AutosaveError,
+    TabsRecovered,
+    Committed,
+    BranchSwitched,
+    BranchCreated,
+    ChangesStashed,
+    StashApplied,
+    LintFinished,
+    UnknownCommand,
+    MacroSaved,
+    RecentBlocksError,
+    BreakpointsError,
+    BuildTasksError,
+    BuildTaskFinished,
+    ProjectEnvError,
+    ProjectEnvSaved,
+    OfflineQueueError,
+    OfflineOpFailed,
+    OfflineQueueReplayed,
+    AiError,
+    AiNotConfigured,
+    SemanticSearchError,
+    EmbeddingsNotConfigured,
+    SummariesRegenerated,
+    SummaryError,
+    MetaSuggestionError,
+    StructuralError,
+    StructuralReplaced,
+    StructuralReplaceUndone,
+    DeadCodeFound,
     Raw,
 }
 
@@ -35,9 +85,13 @@ impl LogMessage {
         use LogMessage::*;
         match self {
             FileError | ReadError | SaveError | CreateError | DirCreateError | RenameError
-            | DeleteError | SearchError | ParseError | GitError | ExportError | RunError => {
-                LogLevel::Error
-            }
+            | DeleteError | SearchError | ParseError | GitError | ExportError | RunError
+            | HookFailed | FileRefNotFound | ThemeLoadError | MetaTemplatesError
+            | TagsMergeError | MetaIdRenameError | SavedSearchError | OpenLinkError | FormatError
+            | CoverageError | AutosaveError | UnknownCommand | RecentBlocksError
+            | BreakpointsError | BuildTasksError | ProjectEnvError | OfflineQueueError
+            | OfflineOpFailed | AiError | SemanticSearchError | SummaryError
+            | MetaSuggestionError | StructuralError => LogLevel::Error,
             _ => LogLevel::Info,
         }
     }
@@ -186,7 +240,387 @@ pub fn format_log(entry: &LogEntry, lang: Language) -> String {
             Language::Spanish => format!("bloques actualizados: {}", arg0(0)),
             Language::German => format!("Blöcke aktualisiert: {}", arg0(0)),
         },
+        HookFinished => match lang {
+            Language::English => format!("hook finished: {}", arg0(0)),
+            Language::Russian => format!("хук выполнен: {}", arg0(0)),
+            Language::Spanish => format!("hook completado: {}", arg0(0)),
+            Language::German => format!("Hook ausgeführt: {}", arg0(0)),
+        },
+        HookFailed => match lang {
+            Language::English => format!("hook failed: {}", arg0(0)),
+            Language::Russian => format!("ошибка хука: {}", arg0(0)),
+            Language::Spanish => format!("error del hook: {}", arg0(0)),
+            Language::German => format!("Hook-Fehler: {}", arg0(0)),
+        },
+        SyncRecordingStarted => match lang {
+            Language::English => format!("sync recording started: {}", arg0(0)),
+            Language::Russian => format!("запись синхронизации начата: {}", arg0(0)),
+            Language::Spanish => format!("grabación de sincronización iniciada: {}", arg0(0)),
+            Language::German => format!("Sync-Aufzeichnung gestartet: {}", arg0(0)),
+        },
+        SyncRecordingStopped => match lang {
+            Language::English => format!("sync recording stopped, messages: {}", arg0(0)),
+            Language::Russian => format!("запись синхронизации остановлена, сообщений: {}", arg0(0)),
+            Language::Spanish => format!("grabación de sincronización detenida, mensajes: {}", arg0(0)),
+            Language::German => format!("Sync-Aufzeichnung beendet, Nachrichten: {}", arg0(0)),
+        },
+        BlocksCopied => match lang {
+            Language::English => format!("blocks copied, metadata entries: {}", arg0(0)),
+            Language::Russian => format!("блоки скопированы, записей метаданных: {}", arg0(0)),
+            Language::Spanish => format!("bloques copiados, entradas de metadatos: {}", arg0(0)),
+            Language::German => format!("Blöcke kopiert, Metadateneinträge: {}", arg0(0)),
+        },
+        BlocksPasted => match lang {
+            Language::English => format!("blocks pasted, metadata entries: {}", arg0(0)),
+            Language::Russian => format!("блоки вставлены, записей метаданных: {}", arg0(0)),
+            Language::Spanish => format!("bloques pegados, entradas de metadatos: {}", arg0(0)),
+            Language::German => format!("Blöcke eingefügt, Metadateneinträge: {}", arg0(0)),
+        },
+        FileRefNotFound => match lang {
+            Language::English => format!("referenced file not found: {}", arg0(0)),
+            Language::Russian => format!("файл по ссылке не найден: {}", arg0(0)),
+            Language::Spanish => format!("archivo referenciado no encontrado: {}", arg0(0)),
+            Language::German => format!("referenzierte Datei nicht gefunden: {}", arg0(0)),
+        },
+        ThemeLoaded => match lang {
+            Language::English => format!("theme loaded: {}", arg0(0)),
+            Language::Russian => format!("тема загружена: {}", arg0(0)),
+            Language::Spanish => format!("tema cargado: {}", arg0(0)),
+            Language::German => format!("Theme geladen: {}", arg0(0)),
+        },
+        ThemeLoadError => match lang {
+            Language::English => format!("theme load error: {}", arg0(0)),
+            Language::Russian => format!("ошибка загрузки темы: {}", arg0(0)),
+            Language::Spanish => format!("error al cargar el tema: {}", arg0(0)),
+            Language::German => format!("Fehler beim Laden des Themes: {}", arg0(0)),
+        },
+        TagsMerged => match lang {
+            Language::English => {
+                format!("tags {} renamed to {}, files: {}", arg0(0), arg0(1), arg0(2))
+            }
+            Language::Russian => {
+                format!("теги {} переименованы в {}, файлов: {}", arg0(0), arg0(1), arg0(2))
+            }
+            Language::Spanish => {
+                format!("etiquetas {} renombradas a {}, archivos: {}", arg0(0), arg0(1), arg0(2))
+            }
+            Language::German => {
+                format!("Tags {} umbenannt in {}, Dateien: {}", arg0(0), arg0(1), arg0(2))
+            }
+        },
+        TagsMergeError => match lang {
+            Language::English => format!("tag rename error: {}", arg0(0)),
+            Language::Russian => format!("ошибка переименования тегов: {}", arg0(0)),
+            Language::Spanish => format!("error al renombrar etiquetas: {}", arg0(0)),
+            Language::German => format!("Fehler beim Umbenennen von Tags: {}", arg0(0)),
+        },
+        MetaIdRenamed => match lang {
+            Language::English => {
+                format!("id {} renamed to {}, files: {}", arg0(0), arg0(1), arg0(2))
+            }
+            Language::Russian => {
+                format!("id {} переименован в {}, файлов: {}", arg0(0), arg0(1), arg0(2))
+            }
+            Language::Spanish => {
+                format!("id {} renombrado a {}, archivos: {}", arg0(0), arg0(1), arg0(2))
+            }
+            Language::German => {
+                format!("ID {} umbenannt in {}, Dateien: {}", arg0(0), arg0(1), arg0(2))
+            }
+        },
+        MetaIdRenameError => match lang {
+            Language::English => format!("id rename error: {}", arg0(0)),
+            Language::Russian => format!("ошибка переименования id: {}", arg0(0)),
+            Language::Spanish => format!("error al renombrar el id: {}", arg0(0)),
+            Language::German => format!("Fehler beim Umbenennen der ID: {}", arg0(0)),
+        },
+        MetaTemplatesError => match lang {
+            Language::English => format!("meta templates error: {}", arg0(0)),
+            Language::Russian => format!("ошибка шаблонов метаданных: {}", arg0(0)),
+            Language::Spanish => format!("error de plantillas de metadatos: {}", arg0(0)),
+            Language::German => format!("Fehler in Metadaten-Vorlagen: {}", arg0(0)),
+        },
+        SearchSaved => match lang {
+            Language::English => format!("search saved: {}", arg0(0)),
+            Language::Russian => format!("поиск сохранён: {}", arg0(0)),
+            Language::Spanish => format!("búsqueda guardada: {}", arg0(0)),
+            Language::German => format!("Suche gespeichert: {}", arg0(0)),
+        },
+        FormatError => match lang {
+            Language::English => format!("format error: {}", arg0(0)),
+            Language::Russian => format!("ошибка форматирования: {}", arg0(0)),
+            Language::Spanish => format!("error de formato: {}", arg0(0)),
+            Language::German => format!("Formatierungsfehler: {}", arg0(0)),
+        },
+        OpenLinkError => match lang {
+            Language::English => format!("failed to open {}: {}", arg0(0), arg0(1)),
+            Language::Russian => format!("не удалось открыть {}: {}", arg0(0), arg0(1)),
+            Language::Spanish => format!("no se pudo abrir {}: {}", arg0(0), arg0(1)),
+            Language::German => format!("{} konnte nicht geöffnet werden: {}", arg0(0), arg0(1)),
+        },
+        SavedSearchError => match lang {
+            Language::English => format!("saved search error: {}", arg0(0)),
+            Language::Russian => format!("ошибка сохранённого поиска: {}", arg0(0)),
+            Language::Spanish => format!("error de búsqueda guardada: {}", arg0(0)),
+            Language::German => format!("Fehler der gespeicherten Suche: {}", arg0(0)),
+        },
+        CoverageMissing => match lang {
+            Language::English => {
+                "no coverage report found, see [coverage] in multicode.toml".into()
+            }
+            Language::Russian => {
+                "отчёт о покрытии не найден, см. [coverage] в multicode.toml".into()
+            }
+            Language::Spanish => {
+                "no se encontró el informe de cobertura, ver [coverage] en multicode.toml".into()
+            }
+            Language::German => {
+                "kein Abdeckungsbericht gefunden, siehe [coverage] in multicode.toml".into()
+            }
+        },
+        CoverageError => match lang {
+            Language::English => format!("failed to read coverage report: {}", arg0(0)),
+            Language::Russian => format!("не удалось прочитать отчёт о покрытии: {}", arg0(0)),
+            Language::Spanish => format!("no se pudo leer el informe de cobertura: {}", arg0(0)),
+            Language::German => format!("Abdeckungsbericht nicht lesbar: {}", arg0(0)),
+        },
+        AutosaveError => match lang {
+            Language::English => format!("autosave error: {}", arg0(0)),
+            Language::Russian => format!("ошибка автосохранения: {}", arg0(0)),
+            Language::Spanish => format!("error de autoguardado: {}", arg0(0)),
+            Language::German => format!("Fehler beim automatischen Speichern: {}", arg0(0)),
+        },
+        TabsRecovered => match lang {
+            Language::English => format!("unsaved changes restored, files: {}", arg0(0)),
+            Language::Russian => {
+                format!("несохранённые изменения восстановлены, файлов: {}", arg0(0))
+            }
+            Language::Spanish => format!("cambios no guardados restaurados, archivos: {}", arg0(0)),
+            Language::German => format!(
+                "ungespeicherte Änderungen wiederhergestellt, Dateien: {}",
+                arg0(0)
+            ),
+        },
+        Committed => match lang {
+            Language::English => format!("commit created: {}", arg0(0)),
+            Language::Russian => format!("коммит создан: {}", arg0(0)),
+            Language::Spanish => format!("commit creado: {}", arg0(0)),
+            Language::German => format!("Commit erstellt: {}", arg0(0)),
+        },
+        BranchSwitched => match lang {
+            Language::English => format!("switched to branch {}", arg0(0)),
+            Language::Russian => format!("переключено на ветку {}", arg0(0)),
+            Language::Spanish => format!("cambiado a la rama {}", arg0(0)),
+            Language::German => format!("zu Branch {} gewechselt", arg0(0)),
+        },
+        BranchCreated => match lang {
+            Language::English => format!("branch created: {}", arg0(0)),
+            Language::Russian => format!("ветка создана: {}", arg0(0)),
+            Language::Spanish => format!("rama creada: {}", arg0(0)),
+            Language::German => format!("Branch erstellt: {}", arg0(0)),
+        },
+        ChangesStashed => match lang {
+            Language::English => "changes stashed".into(),
+            Language::Russian => "изменения отложены".into(),
+            Language::Spanish => "cambios guardados en stash".into(),
+            Language::German => "Änderungen gestasht".into(),
+        },
+        StashApplied => match lang {
+            Language::English => "stashed changes restored".into(),
+            Language::Russian => "отложенные изменения возвращены".into(),
+            Language::Spanish => "cambios del stash restaurados".into(),
+            Language::German => "gestashte Änderungen wiederhergestellt".into(),
+        },
+        LintFinished => match lang {
+            Language::English => format!("lint finished for {}: {} problems", arg0(0), arg0(1)),
+            Language::Russian => format!("проверка {} завершена: проблем {}", arg0(0), arg0(1)),
+            Language::Spanish => {
+                format!("análisis de {} terminado: {} problemas", arg0(0), arg0(1))
+            }
+            Language::German => format!("Prüfung von {} beendet: {} Probleme", arg0(0), arg0(1)),
+        },
+        UnknownCommand => match lang {
+            Language::English => format!("unknown command: {}", arg0(0)),
+            Language::Russian => format!("неизвестная команда: {}", arg0(0)),
+            Language::Spanish => format!("comando desconocido: {}", arg0(0)),
+            Language::German => format!("unbekannter Befehl: {}", arg0(0)),
+        },
+        MacroSaved => match lang {
+            Language::English => format!("macro {} saved: {} steps", arg0(0), arg0(1)),
+            Language::Russian => format!("макрос {} сохранён: шагов {}", arg0(0), arg0(1)),
+            Language::Spanish => format!("macro {} guardada: {} pasos", arg0(0), arg0(1)),
+            Language::German => format!("Makro {} gespeichert: {} Schritte", arg0(0), arg0(1)),
+        },
+        RecentBlocksError => match lang {
+            Language::English => format!("recent blocks error: {}", arg0(0)),
+            Language::Russian => format!("ошибка списка недавних блоков: {}", arg0(0)),
+            Language::Spanish => format!("error de bloques recientes: {}", arg0(0)),
+            Language::German => format!("Fehler der zuletzt bearbeiteten Blöcke: {}", arg0(0)),
+        },
+        BreakpointsError => match lang {
+            Language::English => format!("breakpoints error: {}", arg0(0)),
+            Language::Russian => format!("ошибка точек останова: {}", arg0(0)),
+            Language::Spanish => format!("error de puntos de interrupción: {}", arg0(0)),
+            Language::German => format!("Fehler der Haltepunkte: {}", arg0(0)),
+        },
+        BuildTasksError => match lang {
+            Language::English => format!("build tasks error: {}", arg0(0)),
+            Language::Russian => format!("ошибка задач сборки: {}", arg0(0)),
+            Language::Spanish => format!("error de tareas de compilación: {}", arg0(0)),
+            Language::German => format!("Fehler der Build-Aufgaben: {}", arg0(0)),
+        },
+        BuildTaskFinished => match lang {
+            Language::English => format!("task {} finished: {} problems", arg0(0), arg0(1)),
+            Language::Russian => format!("задача {} завершена: ошибок {}", arg0(0), arg0(1)),
+            Language::Spanish => format!("tarea {} terminada: {} problemas", arg0(0), arg0(1)),
+            Language::German => format!("Aufgabe {} beendet: {} Probleme", arg0(0), arg0(1)),
+        },
+        ProjectEnvError => match lang {
+            Language::English => format!("project environment error: {}", arg0(0)),
+            Language::Russian => format!("ошибка окружения проекта: {}", arg0(0)),
+            Language::Spanish => format!("error del entorno del proyecto: {}", arg0(0)),
+            Language::German => format!("Fehler der Projektumgebung: {}", arg0(0)),
+        },
+        ProjectEnvSaved => match lang {
+            Language::English => format!("project environment saved: {} variables", arg0(0)),
+            Language::Russian => format!("окружение проекта сохранено: переменных {}", arg0(0)),
+            Language::Spanish => format!("entorno del proyecto guardado: {} variables", arg0(0)),
+            Language::German => format!("Projektumgebung gespeichert: {} Variablen", arg0(0)),
+        },
+        OfflineQueueError => match lang {
+            Language::English => format!("offline queue error: {}", arg0(0)),
+            Language::Russian => format!("ошибка очереди операций: {}", arg0(0)),
+            Language::Spanish => format!("error de la cola sin conexión: {}", arg0(0)),
+            Language::German => format!("Fehler der Offline-Warteschlange: {}", arg0(0)),
+        },
+        OfflineOpFailed => match lang {
+            Language::English => format!("queued operation {} failed: {}", arg0(0), arg0(1)),
+            Language::Russian => {
+                format!("операция {} из очереди не выполнена: {}", arg0(0), arg0(1))
+            }
+            Language::Spanish => format!("la operación en cola {} falló: {}", arg0(0), arg0(1)),
+            Language::German => format!(
+                "Operation {} aus der Warteschlange fehlgeschlagen: {}",
+                arg0(0),
+                arg0(1)
+            ),
+        },
+        OfflineQueueReplayed => match lang {
+            Language::English => format!("queued operations completed: {}", arg0(0)),
+            Language::Russian => format!("выполнено операций из очереди: {}", arg0(0)),
+            Language::Spanish => format!("operaciones en cola completadas: {}", arg0(0)),
+            Language::German => {
+                format!("Operationen aus der Warteschlange ausgeführt: {}", arg0(0))
+            }
+        },
+        AiError => match lang {
+            Language::English => format!("AI model error: {}", arg0(0)),
+            Language::Russian => format!("ошибка модели ИИ: {}", arg0(0)),
+            Language::Spanish => format!("error del modelo de IA: {}", arg0(0)),
+            Language::German => format!("Fehler des KI-Modells: {}", arg0(0)),
+        },
+        AiNotConfigured => match lang {
+            Language::English => {
+                "AI model is not configured: add an [ai] section to multicode.toml".into()
+            }
+            Language::Russian => {
+                "модель ИИ не настроена: добавьте секцию [ai] в multicode.toml".into()
+            }
+            Language::Spanish => {
+                "el modelo de IA no está configurado: añada la sección [ai] a multicode.toml".into()
+            }
+            Language::German => {
+                "KI-Modell ist nicht konfiguriert: Abschnitt [ai] in multicode.toml hinzufügen".into()
+            }
+        },
+        SemanticSearchError => match lang {
+            Language::English => format!("semantic search error: {}", arg0(0)),
+            Language::Russian => format!("ошибка поиска по смыслу: {}", arg0(0)),
+            Language::Spanish => format!("error de búsqueda semántica: {}", arg0(0)),
+            Language::German => format!("Fehler der semantischen Suche: {}", arg0(0)),
+        },
+        EmbeddingsNotConfigured => match lang {
+            Language::English => {
+                "semantic search is not configured: add an [embeddings] section to multicode.toml"
+                    .into()
+            }
+            Language::Russian => {
+                "поиск по смыслу не настроен: добавьте секцию [embeddings] в multicode.toml".into()
+            }
+            Language::Spanish => {
+                "la búsqueda semántica no está configurada: añada la sección [embeddings] a \
+                 multicode.toml"
+                    .into()
+            }
+            Language::German => {
+                "Semantische Suche ist nicht konfiguriert: Abschnitt [embeddings] in \
+                 multicode.toml hinzufügen"
+                    .into()
+            }
+        },
+        SummariesRegenerated => match lang {
+            Language::English => format!("summaries regenerated: {}", arg0(0)),
+            Language::Russian => format!("сводки обновлены: {}", arg0(0)),
+            Language::Spanish => format!("resúmenes regenerados: {}", arg0(0)),
+            Language::German => format!("Zusammenfassungen neu erstellt: {}", arg0(0)),
+        },
+        SummaryError => match lang {
+            Language::English => format!("summary error: {}", arg0(0)),
+            Language::Russian => format!("ошибка сводок: {}", arg0(0)),
+            Language::Spanish => format!("error de resúmenes: {}", arg0(0)),
+            Language::German => format!("Fehler der Zusammenfassungen: {}", arg0(0)),
+        },
+        MetaSuggestionError => match lang {
+            Language::English => format!("failed to apply suggestion: {}", arg0(0)),
+            Language::Russian => format!("не удалось применить предложение: {}", arg0(0)),
+            Language::Spanish => format!("no se pudo aplicar la sugerencia: {}", arg0(0)),
+            Language::German => format!("Vorschlag konnte nicht übernommen werden: {}", arg0(0)),
+        },
+        StructuralReplaced => match lang {
+            Language::English => format!("replacements applied: {}", arg0(0)),
+            Language::Russian => format!("выполнено замен: {}", arg0(0)),
+            Language::Spanish => format!("reemplazos aplicados: {}", arg0(0)),
+            Language::German => format!("Ersetzungen angewendet: {}", arg0(0)),
+        },
+        StructuralReplaceUndone => match lang {
+            Language::English => "replacement undone".into(),
+            Language::Russian => "замена отменена".into(),
+            Language::Spanish => "reemplazo deshecho".into(),
+            Language::German => "Ersetzung rückgängig gemacht".into(),
+        },
+        StructuralError => match lang {
+            Language::English => format!("structural search error: {}", arg0(0)),
+            Language::Russian => format!("ошибка структурного поиска: {}", arg0(0)),
+            Language::Spanish => format!("error de búsqueda estructural: {}", arg0(0)),
+            Language::German => format!("Fehler der Struktursuche: {}", arg0(0)),
+        },
+        DeadCodeFound => match lang {
+            Language::English => format!(
+                "dead code: {} unused functions, {} orphaned meta entries",
+                arg0(0),
+                arg0(1)
+            ),
+            Language::Russian => format!(
+                "мёртвый код: неиспользуемых функций {}, записей без блоков {}",
+                arg0(0),
+                arg0(1)
+            ),
+            Language::Spanish => format!(
+                "código muerto: {} funciones sin usar, {} metadatos huérfanos",
+                arg0(0),
+                arg0(1)
+            ),
+            Language::German => format!(
+                "toter Code: {} unbenutzte Funktionen, {} verwaiste Metadaten",
+                arg0(0),
+                arg0(1)
+            ),
+        },
         Raw => arg0(0),
     };
-    format!("[{}] {}", entry.timestamp.format("%H:%M:%S"), message)
+    let time = entry.timestamp.format("%H:%M:%S");
+    match &entry.module {
+        Some(module) => format!("[{time}] {module}: {message}"),
+        None => format!("[{time}] {message}"),
+    }
 }
@@ -0,0 +1,334 @@
+//! Журнал приложения.
+//!
+//! Записи хранятся в [`LogBuffer`], который отбрасывает самые старые записи
+//! сверх лимита. События `tracing` из `core` и `desktop` попадают в журнал
+//! через слой [`layer`]: модуль события становится модулем записи, по
+//! которому панель лога фильтрует записи вместе с уровнем и поиском
+//! ([`LogFilter`]).
+
+use std::fmt;
+use std::ops::Deref;
+
+use chrono::Utc;
+use once_cell::sync::Lazy;
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::layer::{Context, Layer};
+
+use super::{format_log, Language, LogEntry, LogLevel};
+
+/// Число записей журнала по умолчанию.
+pub const DEFAULT_LOG_LIMIT: usize = 1000;
+
+/// Записи журнала с ограничением по количеству.
+#[derive(Debug, Clone)]
+pub struct LogBuffer {
+    entries: Vec<LogEntry>,
+    limit: usize,
+}
+
+impl LogBuffer {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            entries: Vec::new(),
+            limit: limit.max(1),
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Меняет лимит, сразу отбрасывая лишние старые записи.
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit.max(1);
+        self.rotate();
+    }
+
+    pub fn push(&mut self, entry: LogEntry) {
+        self.entries.push(entry);
+        self.rotate();
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Модули записей, без повторов и по алфавиту.
+    pub fn modules(&self) -> Vec<String> {
+        let mut modules: Vec<String> = self
+            .entries
+            .iter()
+            .filter_map(|e| e.module.clone())
+            .collect();
+        modules.sort();
+        modules.dedup();
+        modules
+    }
+
+    fn rotate(&mut self) {
+        if self.entries.len() > self.limit {
+            let extra = self.entries.len() - self.limit;
+            self.entries.drain(..extra);
+        }
+    }
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_LIMIT)
+    }
+}
+
+impl Deref for LogBuffer {
+    type Target = [LogEntry];
+
+    fn deref(&self) -> &[LogEntry] {
+        &self.entries
+    }
+}
+
+impl Extend<LogEntry> for LogBuffer {
+    fn extend<I: IntoIterator<Item = LogEntry>>(&mut self, iter: I) {
+        self.entries.extend(iter);
+        self.rotate();
+    }
+}
+
+/// Выбор модуля в панели лога; `None` — все модули.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModuleChoice(pub Option<String>);
+
+impl fmt::Display for ModuleChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Some(module) => f.write_str(module),
+            None => f.write_str("*"),
+        }
+    }
+}
+
+/// Фильтр панели лога.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Минимальный уровень записей.
+    pub level: LogLevel,
+    /// Модуль; вложенные модули тоже подходят.
+    pub module: Option<String>,
+    /// Подстрока текста записи, без учёта регистра.
+    pub query: String,
+}
+
+impl Default for LogFilter {
+    fn default() -> Self {
+        Self {
+            level: LogLevel::Info,
+            module: None,
+            query: String::new(),
+        }
+    }
+}
+
+impl LogFilter {
+    pub fn matches(&self, entry: &LogEntry, lang: Language) -> bool {
+        if entry.level < self.level {
+            return false;
+        }
+        if let Some(module) = &self.module {
+            let Some(own) = entry.module.as_deref() else {
+                return false;
+            };
+            let nested = own
+                .strip_prefix(module.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"));
+            if !nested {
+                return false;
+            }
+        }
+        let query = self.query.trim();
+        query.is_empty()
+            || format_log(entry, lang)
+                .to_lowercase()
+                .contains(&query.to_lowercase())
+    }
+}
+
+static EVENTS: Lazy<broadcast::Sender<LogEntry>> = Lazy::new(|| broadcast::channel(256).0);
+
+/// Подписка на записи из событий `tracing`.
+pub fn subscribe() -> broadcast::Receiver<LogEntry> {
+    EVENTS.subscribe()
+}
+
+/// Уровень журнала для уровня `tracing`.
+pub fn level_of(level: Level) -> LogLevel {
+    match level {
+        Level::ERROR => LogLevel::Error,
+        Level::WARN => LogLevel::Warning,
+        Level::INFO => LogLevel::Info,
+        _ => LogLevel::Debug,
+    }
+}
+
+/// Собирает сообщение и поля события в одну строку: `сообщение ключ=значение`.
+#[derive(Default)]
+struct EventText {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl Visit for EventText {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.push(format!("{}={value}", field.name()));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else {
+            self.fields.push(format!("{}={value:?}", field.name()));
+        }
+    }
+}
+
+impl EventText {
+    fn finish(self) -> String {
+        std::iter::once(self.message)
+            .chain(self.fields)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Запись журнала для события `tracing`.
+pub fn entry_for(event: &Event<'_>) -> LogEntry {
+    let meta = event.metadata();
+    let mut text = EventText::default();
+    event.record(&mut text);
+    LogEntry::raw(text.finish(), Utc::now())
+        .with_level(level_of(*meta.level()))
+        .with_module(meta.target())
+}
+
+struct LogLayer;
+
+impl<S: Subscriber> Layer<S> for LogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        // Без подписчиков (например, до открытия редактора) событие
+        // просто отбрасывается.
+        let _ = EVENTS.send(entry_for(event));
+    }
+}
+
+/// Слой `tracing`, передающий события `core` и `desktop` уровня DEBUG и
+/// выше в панель лога.
+pub fn layer<S>() -> impl Layer<S>
+where
+    S: Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+{
+    let targets = Targets::new()
+        .with_target("core", Level::DEBUG)
+        .with_target("desktop", Level::DEBUG);
+    LogLayer.with_filter(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::LogMessage;
+    use tracing_subscriber::prelude::*;
+
+    fn entry(text: &str, level: LogLevel, module: Option<&str>) -> LogEntry {
+        let entry = LogEntry::raw(text.into(), Utc::now()).with_level(level);
+        match module {
+            Some(module) => entry.with_module(module),
+            None => entry,
+        }
+    }
+
+    #[test]
+    fn buffer_drops_oldest_entries() {
+        let mut log = LogBuffer::new(2);
+        for text in ["a", "b", "c"] {
+            log.push(entry(text, LogLevel::Info, None));
+        }
+        let args: Vec<&str> = log.iter().map(|e| e.args[0].as_str()).collect();
+        assert_eq!(args, ["b", "c"]);
+        log.set_limit(1);
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].args[0], "c");
+    }
+
+    #[test]
+    fn filter_by_level_module_and_query() {
+        let meta = entry("meta parsed", LogLevel::Debug, Some("core::meta::db"));
+        let git = entry("Git failed", LogLevel::Error, Some("core::git"));
+        let plain = LogEntry::new(LogMessage::FileSaved, vec![], Utc::now());
+
+        let mut filter = LogFilter::default();
+        assert!(!filter.matches(&meta, Language::English));
+        assert!(filter.matches(&git, Language::English));
+        assert!(filter.matches(&plain, Language::English));
+
+        filter.level = LogLevel::Debug;
+        filter.module = Some("core::meta".into());
+        assert!(filter.matches(&meta, Language::English));
+        assert!(!filter.matches(&git, Language::English));
+        assert!(!filter.matches(&plain, Language::English));
+
+        filter.module = Some("core::me".into());
+        assert!(!filter.matches(&meta, Language::English));
+
+        filter.module = None;
+        filter.query = "git FAIL".into();
+        assert!(filter.matches(&git, Language::English));
+        assert!(!filter.matches(&meta, Language::English));
+    }
+
+    #[test]
+    fn tracing_events_become_entries() {
+        let mut rx = subscribe();
+        let subscriber = tracing_subscriber::registry().with(layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::warn!(target: "core::config", command = "make", "хук выполнен");
+            tracing::info!(target: "other", "skipped");
+        });
+        let entries: Vec<LogEntry> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        let hook = entries
+            .iter()
+            .find(|e| e.module.as_deref() == Some("core::config"))
+            .expect("hook event");
+        assert_eq!(hook.level, LogLevel::Warning);
+        assert_eq!(hook.args, ["хук выполнен command=make"]);
+        assert!(entries.iter().all(|e| e.module.as_deref() != Some("other")));
+    }
+
+    #[test]
+    fn ndjson_export_has_one_record_per_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.ndjson");
+        let entries = [
+            LogEntry::new(LogMessage::FileSaved, vec![], Utc::now()),
+            entry("x", LogLevel::Debug, Some("core::meta")),
+        ];
+        crate::app::save_log_to_ndjson(&entries, &path).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        let records: Vec<serde_json::Value> = text
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["message_key"], "FileSaved");
+        assert!(records[0]["module"].is_null());
+        assert_eq!(records[1]["level"], "Debug");
+        assert_eq!(records[1]["module"], "core::meta");
+        assert!(records[1]["timestamp"].is_string());
+    }
+}
@@ -0,0 +1,343 @@
+//! Запись и воспроизведение макросов.
+//!
+//! Пока идёт запись, повторяемые сообщения редактора — правки текста и
+//! перемещения курсора, перетаскивание блоков холста и команды палитры —
+//! сохраняются шагами [`MacroStep`]. Щелчки мышью и прокрутка зависят от
+//! раскладки окна и не записываются. Записанный макрос получает имя и
+//! хранится в пользовательских настройках; воспроизвести его можно в
+//! текущей вкладке или по очереди во всех открытых.
+
+use std::sync::Arc;
+
+use chrono::Utc;
+use iced::widget::text_editor::{Action, Edit, Motion};
+use iced::{Command, Point};
+use serde::{Deserialize, Serialize};
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+use crate::visual::canvas::CanvasMessage;
+
+/// Команды управления макросами; в макрос они не записываются.
+const MACRO_COMMANDS: &[&str] = &["toggle_macro_recording", "toggle_macros_panel"];
+
+/// Перемещение курсора, как [`Motion`] редактора.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MacroMotion {
+    Left,
+    Right,
+    Up,
+    Down,
+    WordLeft,
+    WordRight,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    DocumentStart,
+    DocumentEnd,
+}
+
+impl From<Motion> for MacroMotion {
+    fn from(motion: Motion) -> Self {
+        match motion {
+            Motion::Left => Self::Left,
+            Motion::Right => Self::Right,
+            Motion::Up => Self::Up,
+            Motion::Down => Self::Down,
+            Motion::WordLeft => Self::WordLeft,
+            Motion::WordRight => Self::WordRight,
+            Motion::Home => Self::Home,
+            Motion::End => Self::End,
+            Motion::PageUp => Self::PageUp,
+            Motion::PageDown => Self::PageDown,
+            Motion::DocumentStart => Self::DocumentStart,
+            Motion::DocumentEnd => Self::DocumentEnd,
+        }
+    }
+}
+
+impl From<MacroMotion> for Motion {
+    fn from(motion: MacroMotion) -> Self {
+        match motion {
+            MacroMotion::Left => Self::Left,
+            MacroMotion::Right => Self::Right,
+            MacroMotion::Up => Self::Up,
+            MacroMotion::Down => Self::Down,
+            MacroMotion::WordLeft => Self::WordLeft,
+            MacroMotion::WordRight => Self::WordRight,
+            MacroMotion::Home => Self::Home,
+            MacroMotion::End => Self::End,
+            MacroMotion::PageUp => Self::PageUp,
+            MacroMotion::PageDown => Self::PageDown,
+            MacroMotion::DocumentStart => Self::DocumentStart,
+            MacroMotion::DocumentEnd => Self::DocumentEnd,
+        }
+    }
+}
+
+/// Шаг макроса: сообщение редактора в виде, пригодном для сохранения.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MacroStep {
+    /// Вставка текста в позиции курсора.
+    Insert(String),
+    Enter,
+    Backspace,
+    Delete,
+    /// Перемещение курсора; `select` расширяет выделение.
+    Move {
+        motion: MacroMotion,
+        select: bool,
+    },
+    SelectWord,
+    SelectLine,
+    /// Перемещение блока холста с номером `index`.
+    MoveBlock {
+        index: usize,
+        x: f32,
+        y: f32,
+    },
+    /// Команда палитры.
+    Command(String),
+}
+
+impl MacroStep {
+    /// Шаг для сообщения; `None`, если сообщение не записывается.
+    pub fn from_message(message: &Message) -> Option<Self> {
+        let step = match message {
+            Message::FileContentEdited(action) => match action {
+                Action::Edit(Edit::Insert(c)) => Self::Insert(c.to_string()),
+                Action::Edit(Edit::Paste(text)) => Self::Insert(text.to_string()),
+                Action::Edit(Edit::Enter) => Self::Enter,
+                Action::Edit(Edit::Backspace) => Self::Backspace,
+                Action::Edit(Edit::Delete) => Self::Delete,
+                Action::Move(motion) => Self::Move {
+                    motion: (*motion).into(),
+                    select: false,
+                },
+                Action::Select(motion) => Self::Move {
+                    motion: (*motion).into(),
+                    select: true,
+                },
+                Action::SelectWord => Self::SelectWord,
+                Action::SelectLine => Self::SelectLine,
+                Action::Click(_) | Action::Drag(_) | Action::Scroll { .. } => return None,
+            },
+            Message::CanvasEvent(CanvasMessage::BlockDragged { index, position }) => {
+                Self::MoveBlock {
+                    index: *index,
+                    x: position.x,
+                    y: position.y,
+                }
+            }
+            Message::ExecuteCommand(id) if !MACRO_COMMANDS.contains(&id.as_str()) => {
+                Self::Command(id.clone())
+            }
+            _ => return None,
+        };
+        Some(step)
+    }
+
+    pub fn to_message(&self) -> Message {
+        let edit = |edit| Message::FileContentEdited(Action::Edit(edit));
+        match self {
+            Self::Insert(text) => edit(Edit::Paste(Arc::new(text.clone()))),
+            Self::Enter => edit(Edit::Enter),
+            Self::Backspace => edit(Edit::Backspace),
+            Self::Delete => edit(Edit::Delete),
+            Self::Move { motion, select } => Message::FileContentEdited(if *select {
+                Action::Select((*motion).into())
+            } else {
+                Action::Move((*motion).into())
+            }),
+            Self::SelectWord => Message::FileContentEdited(Action::SelectWord),
+            Self::SelectLine => Message::FileContentEdited(Action::SelectLine),
+            Self::MoveBlock { index, x, y } => Message::CanvasEvent(CanvasMessage::BlockDragged {
+                index: *index,
+                position: Point::new(*x, *y),
+            }),
+            Self::Command(id) => Message::ExecuteCommand(id.clone()),
+        }
+    }
+}
+
+/// Добавляет шаг к записи. Ввод подряд объединяется в одну вставку, а
+/// перетаскивание одного блока — в одно перемещение в конечную точку.
+pub fn push_step(steps: &mut Vec<MacroStep>, step: MacroStep) {
+    match (steps.last_mut(), step) {
+        (Some(MacroStep::Insert(text)), MacroStep::Insert(more)) => text.push_str(&more),
+        (
+            Some(MacroStep::MoveBlock { index, x, y }),
+            MacroStep::MoveBlock {
+                index: i,
+                x: nx,
+                y: ny,
+            },
+        ) if *index == i => {
+            *x = nx;
+            *y = ny;
+        }
+        (_, step) => steps.push(step),
+    }
+}
+
+/// Именованный макрос из пользовательских настроек.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Macro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+impl MulticodeApp {
+    /// Записывает шаг для сообщения, если идёт запись макроса.
+    pub(super) fn record_macro_step(&mut self, message: &Message) {
+        let Some(steps) = &mut self.macro_recording else {
+            return;
+        };
+        if let Some(step) = MacroStep::from_message(message) {
+            push_step(steps, step);
+        }
+    }
+
+    /// Начинает запись или останавливает её; записанные шаги ждут имени.
+    pub(super) fn toggle_macro_recording(&mut self) {
+        match self.macro_recording.take() {
+            Some(steps) => self.recorded_macro = steps,
+            None => {
+                self.macro_recording = Some(Vec::new());
+                self.show_macros_panel = true;
+            }
+        }
+    }
+
+    /// Сохраняет записанный макрос под введённым именем, заменяя макрос с
+    /// тем же именем.
+    pub(super) fn save_macro(&mut self) -> Command<Message> {
+        let name = self.macro_name.trim().to_string();
+        if name.is_empty() || self.recorded_macro.is_empty() {
+            return Command::none();
+        }
+        let steps = std::mem::take(&mut self.recorded_macro);
+        self.log.push(LogEntry::new(
+            LogMessage::MacroSaved,
+            vec![name.clone(), steps.len().to_string()],
+            Utc::now(),
+        ));
+        self.settings.macros.retain(|m| m.name != name);
+        self.settings.macros.push(Macro { name, steps });
+        self.macro_name.clear();
+        Command::perform(self.settings.clone().save(), |_| Message::SettingsSaved)
+    }
+
+    pub(super) fn delete_macro(&mut self, name: &str) -> Command<Message> {
+        self.settings.macros.retain(|m| m.name != name);
+        Command::perform(self.settings.clone().save(), |_| Message::SettingsSaved)
+    }
+
+    /// Воспроизводит макрос в текущей вкладке или во всех открытых
+    /// вкладках по очереди. Воспроизведение не попадает в идущую запись.
+    pub(super) fn run_macro(&mut self, name: &str, all_tabs: bool) -> Command<Message> {
+        let Some(steps) = self
+            .settings
+            .macros
+            .iter()
+            .find(|m| m.name == name)
+            .map(|m| m.steps.clone())
+        else {
+            return Command::none();
+        };
+        let recording = self.macro_recording.take();
+        let mut cmds = Vec::new();
+        if all_tabs {
+            let active = self.active_tab;
+            for i in 0..self.tabs.len() {
+                cmds.push(self.handle_message(Message::ActivateTab(i)));
+                cmds.extend(steps.iter().map(|s| self.handle_message(s.to_message())));
+            }
+            if let Some(i) = active {
+                cmds.push(self.handle_message(Message::ActivateTab(i)));
+            }
+        } else {
+            cmds.extend(steps.iter().map(|s| self.handle_message(s.to_message())));
+        }
+        self.macro_recording = recording;
+        Command::batch(cmds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(action: Action) -> Message {
+        Message::FileContentEdited(action)
+    }
+
+    #[test]
+    fn typing_and_drags_are_merged() {
+        let mut steps = Vec::new();
+        let messages = [
+            edit(Action::Move(Motion::DocumentStart)),
+            edit(Action::Edit(Edit::Insert('a'))),
+            edit(Action::Edit(Edit::Insert('b'))),
+            edit(Action::Click(Point::ORIGIN)),
+            edit(Action::Edit(Edit::Paste(Arc::new("cd".into())))),
+            Message::CanvasEvent(CanvasMessage::BlockDragged {
+                index: 1,
+                position: Point::new(1.0, 2.0),
+            }),
+            Message::CanvasEvent(CanvasMessage::BlockDragged {
+                index: 1,
+                position: Point::new(5.0, 6.0),
+            }),
+            Message::ExecuteCommand("toggle_macro_recording".into()),
+            Message::ExecuteCommand("save_file".into()),
+        ];
+        for message in &messages {
+            if let Some(step) = MacroStep::from_message(message) {
+                push_step(&mut steps, step);
+            }
+        }
+        assert_eq!(
+            steps,
+            [
+                MacroStep::Move {
+                    motion: MacroMotion::DocumentStart,
+                    select: false
+                },
+                MacroStep::Insert("abcd".into()),
+                MacroStep::MoveBlock {
+                    index: 1,
+                    x: 5.0,
+                    y: 6.0
+                },
+                MacroStep::Command("save_file".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn steps_survive_settings_round_trip() {
+        let steps = vec![
+            MacroStep::Move {
+                motion: MacroMotion::End,
+                select: true,
+            },
+            MacroStep::Backspace,
+            MacroStep::Command("save_file".into()),
+        ];
+        let json = serde_json::to_string(&Macro {
+            name: "m".into(),
+            steps: steps.clone(),
+        })
+        .unwrap();
+        let parsed: Macro = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.steps, steps);
+        assert!(matches!(
+            parsed.steps[0].to_message(),
+            Message::FileContentEdited(Action::Select(Motion::End))
+        ));
+    }
+}
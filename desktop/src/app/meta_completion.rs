@@ -0,0 +1,47 @@
+use iced::widget::{button, text, Row};
+use iced::{Command, Element};
+use multicode_core::meta::symbols::{SymbolKind, WorkspaceSymbols};
+
+use super::events::Message;
+use super::MulticodeApp;
+use crate::editor::autocomplete::complete_list;
+
+/// Сколько вариантов показывать под полем диалога метаданных.
+const MAX_DIALOG_SUGGESTIONS: usize = 6;
+
+impl MulticodeApp {
+    /// Собирает теги, идентификаторы и языки метаданных проекта для
+    /// автодополнения.
+    pub(super) fn load_workspace_symbols(&self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move { WorkspaceSymbols::collect(&root) },
+            Message::WorkspaceSymbolsLoaded,
+        )
+    }
+
+    /// Кнопки завершения последнего элемента поля `value` диалога
+    /// метаданных. Нажатие подставляет в поле завершённое значение.
+    pub(super) fn meta_completion_component(
+        &self,
+        value: &str,
+        kind: SymbolKind,
+        on_select: impl Fn(String) -> Message,
+    ) -> Element<'_, Message> {
+        complete_list(value, &self.workspace_symbols, kind)
+            .into_iter()
+            .take(MAX_DIALOG_SUGGESTIONS)
+            .fold(Row::new().spacing(5), |r, completed| {
+                let label = completed
+                    .rsplit(',')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string();
+                r.push(button(text(label)).on_press(on_select(completed)))
+            })
+            .into()
+    }
+}
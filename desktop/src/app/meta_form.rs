@@ -0,0 +1,526 @@
+use chrono::Utc;
+use iced::widget::{button, column, container, row, scrollable, text, text_input, Column};
+use iced::{theme, Color, Command, Element, Length};
+use multicode_core::meta::schema::{self, FieldSpec, FieldType};
+use multicode_core::meta::symbols::SymbolKind;
+use multicode_core::meta::templates::{self, MetaTemplate};
+use multicode_core::meta::{self, VisualMeta};
+use serde_json::{Map, Value};
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+/// Поля, которые приложение заполняет само и не показывает в форме.
+const MANAGED_FIELDS: &[&str] = &["version", "updated_at"];
+
+/// Поле формы метаданных.
+#[derive(Debug, Clone)]
+pub struct FormField {
+    pub spec: FieldSpec,
+    /// Значение строкового, числового или списочного поля; элементы списка
+    /// разделяются запятыми.
+    pub text: String,
+    /// Пары ключ — значение словаря или объекта.
+    pub entries: Vec<(String, String)>,
+    pub error: Option<String>,
+    /// Элементы-объекты исходного списка, например якоря с подписью. В
+    /// тексте показывается только их `name`, а при сохранении объект с тем
+    /// же именем возвращается целиком.
+    named_items: Vec<Value>,
+}
+
+/// Имя элемента-объекта списка.
+fn item_name(item: &Value) -> Option<&str> {
+    item.get("name").and_then(Value::as_str)
+}
+
+impl FormField {
+    fn new(spec: FieldSpec, value: Option<&Value>) -> Self {
+        let mut field = Self {
+            spec,
+            text: String::new(),
+            entries: Vec::new(),
+            error: None,
+            named_items: Vec::new(),
+        };
+        match (field.spec.ty, value) {
+            (_, None | Some(Value::Null)) => {}
+            (FieldType::Text, Some(Value::String(s))) => field.text = s.clone(),
+            (FieldType::List, Some(Value::Array(items))) => {
+                field.text = items
+                    .iter()
+                    .map(|v| {
+                        v.as_str()
+                            .or_else(|| item_name(v))
+                            .map_or_else(|| v.to_string(), str::to_string)
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                field.named_items = items
+                    .iter()
+                    .filter(|v| item_name(v).is_some())
+                    .cloned()
+                    .collect();
+            }
+            (FieldType::Map | FieldType::Object, Some(Value::Object(obj))) => {
+                field.entries = obj
+                    .iter()
+                    .map(|(k, v)| {
+                        let v = v.as_str().map_or_else(|| v.to_string(), str::to_string);
+                        (k.clone(), v)
+                    })
+                    .collect();
+            }
+            (_, Some(v)) => field.text = v.to_string(),
+        }
+        field
+    }
+
+    /// Значение поля в JSON; `None` — поле не задано.
+    fn value(&self) -> Result<Option<Value>, String> {
+        match self.spec.ty {
+            FieldType::Text | FieldType::Number if self.text.trim().is_empty() => {
+                if self.spec.required {
+                    Err("обязательное поле".into())
+                } else {
+                    Ok(None)
+                }
+            }
+            FieldType::Text => Ok(Some(Value::String(self.text.trim().to_string()))),
+            FieldType::Number => serde_json::from_str::<serde_json::Number>(self.text.trim())
+                .map(|n| Some(Value::Number(n)))
+                .map_err(|_| "ожидается число".into()),
+            FieldType::List => Ok(Some(Value::Array(
+                self.text
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| {
+                        self.named_items
+                            .iter()
+                            .find(|v| item_name(v) == Some(s))
+                            .cloned()
+                            .unwrap_or_else(|| Value::String(s.to_string()))
+                    })
+                    .collect(),
+            ))),
+            FieldType::Map | FieldType::Object => {
+                let mut obj = Map::new();
+                for (key, value) in &self.entries {
+                    let key = key.trim();
+                    if key.is_empty() {
+                        if value.trim().is_empty() {
+                            continue;
+                        }
+                        return Err("ключ не должен быть пустым".into());
+                    }
+                    let value = if self.spec.ty == FieldType::Map {
+                        Value::String(value.clone())
+                    } else {
+                        // Значение, не являющееся JSON, сохраняется строкой.
+                        serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.clone()))
+                    };
+                    if obj.insert(key.to_string(), value).is_some() {
+                        return Err(format!("повторяющийся ключ '{key}'"));
+                    }
+                }
+                if obj.is_empty() && self.spec.ty == FieldType::Object {
+                    Ok(None)
+                } else {
+                    Ok(Some(Value::Object(obj)))
+                }
+            }
+        }
+    }
+}
+
+/// Форма редактирования [`VisualMeta`], построенная по схеме метаданных.
+#[derive(Debug, Clone)]
+pub struct MetaForm {
+    pub fields: Vec<FormField>,
+    /// Ошибка, не относящаяся к отдельному полю.
+    pub error: Option<String>,
+    /// Шаблоны проекта из `.multicode/templates.toml`.
+    pub templates: Vec<MetaTemplate>,
+    /// Исходная запись: из неё берутся поля, которых нет в форме.
+    base: VisualMeta,
+}
+
+impl MetaForm {
+    pub fn new(meta: VisualMeta) -> Self {
+        let value = serde_json::to_value(&meta).unwrap_or_default();
+        let fields = schema::fields()
+            .into_iter()
+            .filter(|spec| !MANAGED_FIELDS.contains(&spec.name.as_str()))
+            .map(|spec| {
+                let value = value.get(&spec.name);
+                FormField::new(spec, value)
+            })
+            .collect();
+        Self {
+            fields,
+            error: None,
+            templates: Vec::new(),
+            base: meta,
+        }
+    }
+
+    /// Подставляет шаблон `index` в поле `extends` и проверяет форму.
+    pub fn pick_template(&mut self, index: usize) {
+        let Some(id) = self.templates.get(index).map(MetaTemplate::id) else {
+            return;
+        };
+        if let Some(field) = self.fields.iter_mut().find(|f| f.spec.name == "extends") {
+            field.text = id;
+        }
+        self.validate();
+    }
+
+    /// Базовая запись шаблона, который наследует `meta`.
+    fn template_base(&self, meta: &VisualMeta) -> Option<VisualMeta> {
+        let extends = meta.extends.as_deref()?;
+        self.templates
+            .iter()
+            .find(|t| t.id() == extends)
+            .map(MetaTemplate::base_meta)
+    }
+
+    /// Проверяет форму и отмечает ошибки у полей. Возвращает запись, если
+    /// ошибок нет.
+    pub fn validate(&mut self) -> Option<VisualMeta> {
+        self.error = None;
+        let mut obj = match serde_json::to_value(&self.base) {
+            Ok(Value::Object(obj)) => obj,
+            _ => Map::new(),
+        };
+        for field in &mut self.fields {
+            field.error = None;
+            match field.value() {
+                Ok(Some(value)) => {
+                    obj.insert(field.spec.name.clone(), value);
+                }
+                Ok(None) => {
+                    obj.remove(&field.spec.name);
+                }
+                Err(e) => field.error = Some(e),
+            }
+        }
+        if !self.is_valid() {
+            return None;
+        }
+        let meta = match serde_json::from_value::<VisualMeta>(Value::Object(obj)) {
+            Ok(meta) => meta,
+            Err(e) => {
+                self.error = Some(e.to_string());
+                return None;
+            }
+        };
+        if let Err(errors) = meta::validate(&meta) {
+            for e in errors {
+                // Ошибки вложенных полей вроде `extras.color` относятся к
+                // полю верхнего уровня.
+                let name = e.field.split('.').next().unwrap_or_default();
+                let target = match self.fields.iter_mut().find(|f| f.spec.name == name) {
+                    Some(field) => &mut field.error,
+                    None => &mut self.error,
+                };
+                let message = match target.take() {
+                    Some(prev) => format!("{prev}; {}", e.message),
+                    None => e.message,
+                };
+                *target = Some(message);
+            }
+            return None;
+        }
+        Some(meta)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none() && self.fields.iter().all(|f| f.error.is_none())
+    }
+}
+
+impl MulticodeApp {
+    /// Открывает форму для метаданных текущего файла или выбранного блока.
+    pub(super) fn show_meta_form(&mut self) {
+        let Some(f) = self.current_file() else {
+            return;
+        };
+        let meta = f.meta.clone().unwrap_or_else(|| VisualMeta {
+            version: meta::DEFAULT_VERSION,
+            id: "root".into(),
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            tags: Vec::new(),
+            links: Vec::new(),
+            anchors: Vec::new(),
+            tests: Vec::new(),
+            extends: None,
+            origin: None,
+            translations: Default::default(),
+            ai: None,
+            extras: None,
+            updated_at: Utc::now(),
+        });
+        let mut form = MetaForm::new(meta);
+        if let Some(root) = self.current_root_path() {
+            match templates::load(&root) {
+                Ok(list) => form.templates = list,
+                Err(e) => self.log.push(LogEntry::new(
+                    LogMessage::MetaTemplatesError,
+                    vec![e.to_string()],
+                    Utc::now(),
+                )),
+            }
+        }
+        form.validate();
+        self.meta_form = Some(form);
+    }
+
+    /// Применяет `edit` к полю `index` формы и сразу проверяет форму.
+    pub(super) fn edit_meta_form(&mut self, index: usize, edit: impl FnOnce(&mut FormField)) {
+        if let Some(form) = self.meta_form.as_mut() {
+            if let Some(field) = form.fields.get_mut(index) {
+                edit(field);
+            }
+            form.validate();
+        }
+    }
+
+    /// Записывает метаданные из формы в файл. Форма с ошибками остаётся
+    /// открытой, а файл не меняется. Если запись наследует шаблон проекта,
+    /// вместе с ней записывается и базовая запись шаблона.
+    pub(super) fn save_meta_form(&mut self) -> Command<Message> {
+        let Some(form) = self.meta_form.as_mut() else {
+            return Command::none();
+        };
+        let Some(mut meta) = form.validate() else {
+            return Command::none();
+        };
+        let template = form.template_base(&meta);
+        let preserve_formatting = self.settings.sync.preserve_meta_formatting;
+        if let Some(f) = self.current_file_mut() {
+            meta.updated_at = Utc::now();
+            if let Some(base) = template {
+                f.content = meta::upsert(&f.content, &base, preserve_formatting);
+            }
+            f.content = meta::upsert(&f.content, &meta, preserve_formatting);
+            f.editor = iced::widget::text_editor::Content::with_text(&f.content);
+            f.meta = Some(meta);
+            f.dirty = true;
+        }
+        self.meta_form = None;
+        Command::none()
+    }
+
+    fn meta_field_component<'a>(
+        &'a self,
+        index: usize,
+        field: &'a FormField,
+    ) -> Column<'a, Message> {
+        let label = if field.spec.required {
+            format!("{} *", field.spec.name)
+        } else {
+            field.spec.name.clone()
+        };
+        let mut col = column![text(label)].spacing(3);
+        match field.spec.ty {
+            FieldType::Text | FieldType::Number | FieldType::List => {
+                col = col.push(
+                    text_input(&field.spec.description, &field.text)
+                        .on_input(move |s| Message::MetaFieldChanged(index, s)),
+                );
+                let kind = match field.spec.name.as_str() {
+                    "tags" => Some(SymbolKind::Tag),
+                    "links" => Some(SymbolKind::Id),
+                    _ => None,
+                };
+                if let Some(kind) = kind {
+                    col = col.push(self.meta_completion_component(&field.text, kind, move |s| {
+                        Message::MetaFieldChanged(index, s)
+                    }));
+                }
+            }
+            FieldType::Map | FieldType::Object => {
+                for (entry, (key, value)) in field.entries.iter().enumerate() {
+                    col = col.push(
+                        row![
+                            text_input("ключ", key)
+                                .on_input(move |s| Message::MetaEntryKeyChanged(index, entry, s))
+                                .width(Length::FillPortion(1)),
+                            text_input("значение", value)
+                                .on_input(move |s| Message::MetaEntryValueChanged(index, entry, s))
+                                .width(Length::FillPortion(2)),
+                            button("×").on_press(Message::RemoveMetaEntry(index, entry)),
+                        ]
+                        .spacing(5),
+                    );
+                }
+                col = col.push(button("+").on_press(Message::AddMetaEntry(index)));
+            }
+        }
+        if let Some(error) = &field.error {
+            col = col.push(text(error).style(theme::Text::Color(Color::from_rgb(0.8, 0.0, 0.0))));
+        }
+        col
+    }
+
+    /// Модальная форма редактирования метаданных.
+    pub(super) fn meta_form_component(&self) -> Element<'_, Message> {
+        let Some(form) = &self.meta_form else {
+            return column![].into();
+        };
+        let fields = form
+            .fields
+            .iter()
+            .enumerate()
+            .fold(column![].spacing(8), |col, (i, field)| {
+                col.push(self.meta_field_component(i, field))
+            });
+        let mut content = column![].spacing(5);
+        if !form.templates.is_empty() {
+            let picker = form.templates.iter().enumerate().fold(
+                row![text("Шаблон:")].spacing(5),
+                |row, (i, template)| {
+                    row.push(button(text(&template.name)).on_press(Message::PickMetaTemplate(i)))
+                },
+            );
+            content = content.push(picker);
+        }
+        content = content.push(scrollable(fields).height(Length::Fixed(400.0)));
+        if let Some(error) = &form.error {
+            content =
+                content.push(text(error).style(theme::Text::Color(Color::from_rgb(0.8, 0.0, 0.0))));
+        }
+        content = content.push(
+            row![
+                button("Сохранить").on_press_maybe(form.is_valid().then_some(Message::SaveMeta)),
+                button("Отмена").on_press(Message::CloseMetaDialog)
+            ]
+            .spacing(5),
+        );
+        container(content)
+            .width(Length::Fixed(450.0))
+            .padding(10)
+            .into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multicode_core::AnchorKind;
+
+    fn meta(value: Value) -> VisualMeta {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn field<'a>(form: &'a mut MetaForm, name: &str) -> &'a mut FormField {
+        form.fields
+            .iter_mut()
+            .find(|f| f.spec.name == name)
+            .unwrap()
+    }
+
+    #[test]
+    fn form_round_trips_meta() {
+        let mut form = MetaForm::new(meta(serde_json::json!({
+            "id": "a", "x": 1.5, "y": 2.0, "tags": ["net", "io"],
+            "translations": {"ru": "Клиент"}, "extras": {"comment": "hi", "n": 3}
+        })));
+        assert_eq!(field(&mut form, "tags").text, "net, io");
+        assert_eq!(
+            field(&mut form, "extras").entries,
+            vec![
+                ("comment".to_string(), "hi".to_string()),
+                ("n".to_string(), "3".to_string())
+            ]
+        );
+        field(&mut form, "tags").text = "net, ui,".into();
+        let meta = form.validate().unwrap();
+        assert_eq!(meta.tags, vec!["net", "ui"]);
+        assert_eq!(meta.x, 1.5);
+        assert_eq!(meta.translations["ru"], "Клиент");
+        assert_eq!(meta.extras.unwrap()["n"], 3);
+    }
+
+    #[test]
+    fn form_reports_errors_per_field() {
+        let mut form = MetaForm::new(meta(serde_json::json!({"id": "a", "x": 0.0, "y": 0.0})));
+        field(&mut form, "id").text = " ".into();
+        field(&mut form, "x").text = "abc".into();
+        assert!(form.validate().is_none());
+        assert_eq!(
+            field(&mut form, "id").error.as_deref(),
+            Some("обязательное поле")
+        );
+        assert_eq!(
+            field(&mut form, "x").error.as_deref(),
+            Some("ожидается число")
+        );
+
+        field(&mut form, "id").text = "a".into();
+        field(&mut form, "x").text = "0".into();
+        field(&mut form, "tags").text = "net, net".into();
+        field(&mut form, "extras").entries = vec![("color".into(), "зелёный".into())];
+        assert!(form.validate().is_none());
+        assert!(field(&mut form, "tags")
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("net"));
+        assert!(field(&mut form, "extras").error.is_some());
+        assert!(field(&mut form, "x").error.is_none());
+    }
+
+    #[test]
+    fn form_keeps_labelled_anchors() {
+        let mut form = MetaForm::new(meta(serde_json::json!({
+            "id": "a", "x": 0.0, "y": 0.0,
+            "anchors": ["start", {"name": "n", "kind": "param", "label": "Количество"}]
+        })));
+        assert_eq!(field(&mut form, "anchors").text, "start, n");
+        field(&mut form, "anchors").text = "n, end".into();
+        let meta = form.validate().unwrap();
+        assert_eq!(meta.anchors[0].label.as_deref(), Some("Количество"));
+        assert_eq!(meta.anchors[0].kind, Some(AnchorKind::Param));
+        assert_eq!(meta.anchors[1], "end");
+    }
+
+    #[test]
+    fn form_rejects_duplicate_keys() {
+        let mut form = MetaForm::new(meta(serde_json::json!({"id": "a", "x": 0.0, "y": 0.0})));
+        field(&mut form, "translations").entries =
+            vec![("ru".into(), "А".into()), ("ru".into(), "Б".into())];
+        assert!(form.validate().is_none());
+        assert_eq!(
+            field(&mut form, "translations").error.as_deref(),
+            Some("повторяющийся ключ 'ru'")
+        );
+    }
+
+    #[test]
+    fn template_picker_sets_extends() {
+        let mut form = MetaForm::new(meta(serde_json::json!({"id": "a", "x": 0.0, "y": 0.0})));
+        form.templates = vec![MetaTemplate {
+            name: "handler".into(),
+            tags: vec!["net".into()],
+            ..Default::default()
+        }];
+        form.pick_template(1);
+        assert!(field(&mut form, "extends").text.is_empty());
+
+        form.pick_template(0);
+        assert_eq!(field(&mut form, "extends").text, "template:handler");
+        let meta = form.validate().unwrap();
+        let base = form.template_base(&meta).unwrap();
+        assert_eq!(base.id, "template:handler");
+        assert_eq!(base.tags, vec!["net"]);
+
+        field(&mut form, "extends").text = "other".into();
+        let meta = form.validate().unwrap();
+        assert!(form.template_base(&meta).is_none());
+    }
+}
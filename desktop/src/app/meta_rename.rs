@@ -0,0 +1,77 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use iced::widget::text_editor::Content;
+use iced::Command;
+use multicode_core::meta::{read_all, rename::replace_id, rename_id};
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+impl MulticodeApp {
+    /// Переименовывает запись метаданных текущего файла в идентификатор из
+    /// поля панели метаданных и исправляет ссылки на неё во всём проекте.
+    pub(super) fn rename_meta_id(&mut self) -> Command<Message> {
+        let new = self.meta_new_id.trim().to_string();
+        let (Some(root), Some(old)) = (
+            self.current_root_path(),
+            self.current_file()
+                .and_then(|f| f.meta.as_ref())
+                .map(|m| m.id.clone()),
+        ) else {
+            return Command::none();
+        };
+        if new.is_empty() || new == old {
+            return Command::none();
+        }
+        Command::perform(
+            async move {
+                let result = rename_id(&root, &old, &new).map_err(|e| e.to_string());
+                (old, new, result)
+            },
+            |(old, new, result)| Message::MetaIdRenamed(old, new, result),
+        )
+    }
+
+    /// Применяет переименование к открытым вкладкам.
+    ///
+    /// Как и при слиянии тегов, файлы на диске уже изменены, а вкладки могут
+    /// содержать несохранённые правки, поэтому идентификатор заменяется и в
+    /// их тексте.
+    pub(super) fn meta_id_renamed(
+        &mut self,
+        old: String,
+        new: String,
+        result: Result<Vec<PathBuf>, String>,
+    ) -> Command<Message> {
+        let changed = match result {
+            Ok(changed) => changed,
+            Err(e) => {
+                self.log.push(LogEntry::new(
+                    LogMessage::MetaIdRenameError,
+                    vec![e],
+                    Utc::now(),
+                ));
+                return Command::none();
+            }
+        };
+        let mut commands = Vec::new();
+        for i in 0..self.tabs.len() {
+            let tab = &mut self.tabs[i];
+            let updated = replace_id(&tab.content, &old, &new);
+            if updated != tab.content {
+                tab.content = updated;
+                tab.editor = Content::with_text(&tab.content);
+                tab.meta = read_all(&tab.content).into_iter().next();
+                commands.push(self.schedule_analysis(i));
+            }
+        }
+        self.log.push(LogEntry::new(
+            LogMessage::MetaIdRenamed,
+            vec![old, new, changed.len().to_string()],
+            Utc::now(),
+        ));
+        self.meta_new_id.clear();
+        Command::batch(commands)
+    }
+}
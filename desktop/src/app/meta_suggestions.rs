@@ -0,0 +1,144 @@
+//! Предложения тегов и связей.
+//!
+//! Панель «Предложения» показывает теги и связи, которые движок
+//! [`suggest`] предлагает функциям проекта без метаданных. Принятое
+//! предложение записывается в файл, отклонённое скрывается до конца сеанса
+//! ([`pending_meta`]).
+
+use chrono::Utc;
+use iced::widget::{button, column, row, scrollable, text, Space};
+use iced::{Command, Element, Length};
+use multicode_core::meta::suggest::{self, MetaSuggestion, SuggestionKind};
+use tokio::task;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+use crate::visual::suggestions::pending_meta;
+
+/// Что предлагается блоку, для панели.
+fn suggestion_label(suggestion: &MetaSuggestion) -> String {
+    match &suggestion.kind {
+        SuggestionKind::NameTag(tag) => format!("тег #{tag} (имя)"),
+        SuggestionKind::ImportTag(tag) => format!("тег #{tag} (импорт)"),
+        SuggestionKind::Call { name, .. } => format!("связь → {name} (вызов)"),
+    }
+}
+
+impl MulticodeApp {
+    /// Ищет предложения для функций проекта.
+    pub(super) fn find_meta_suggestions(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || suggest::suggest(&root))
+                    .await
+                    .unwrap_or_default()
+            },
+            Message::MetaSuggestionsFound,
+        )
+    }
+
+    /// Показывает найденные предложения, кроме отклонённых.
+    pub(super) fn meta_suggestions_found(&mut self, suggestions: Vec<MetaSuggestion>) {
+        self.meta_suggestions = pending_meta(suggestions, &self.rejected_suggestions);
+    }
+
+    /// Записывает предложение `index` в файл проекта.
+    pub(super) fn accept_meta_suggestion(&mut self, index: usize) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        if index >= self.meta_suggestions.len() {
+            return Command::none();
+        }
+        let suggestion = self.meta_suggestions.remove(index);
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    suggest::accept(&root, &suggestion)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            },
+            Message::MetaSuggestionAccepted,
+        )
+    }
+
+    /// Скрывает предложение `index` и запоминает отказ.
+    pub(super) fn reject_meta_suggestion(&mut self, index: usize) {
+        if index < self.meta_suggestions.len() {
+            let suggestion = self.meta_suggestions.remove(index);
+            self.rejected_suggestions.insert(suggestion.key());
+        }
+    }
+
+    /// Перечитывает изменённые файлы и обновляет предложения: запись
+    /// сдвигает блоки файла и меняет их идентификаторы.
+    pub(super) fn meta_suggestion_accepted(
+        &mut self,
+        result: Result<(), String>,
+    ) -> Command<Message> {
+        if let Err(e) = result {
+            self.log.push(LogEntry::new(
+                LogMessage::MetaSuggestionError,
+                vec![e],
+                Utc::now(),
+            ));
+        }
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        Command::batch([self.reload_clean_tabs(&root), self.find_meta_suggestions()])
+    }
+
+    pub fn meta_suggestions_component(&self) -> Element<'_, Message> {
+        if !self.show_meta_suggestions {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let header = row![
+            text("Предложения").width(Length::Fill),
+            button("Обновить").on_press(Message::FindMetaSuggestions),
+            button("×").on_press(Message::ToggleMetaSuggestions),
+        ]
+        .spacing(5);
+        let items: Vec<Element<Message>> = if self.meta_suggestions.is_empty() {
+            vec![text("Предложений нет").into()]
+        } else {
+            self.meta_suggestions
+                .iter()
+                .enumerate()
+                .map(|(i, s)| {
+                    row![
+                        button(text(format!(
+                            "{}:{} {}",
+                            s.file.display(),
+                            s.line + 1,
+                            s.name
+                        )))
+                        .on_press(Message::OpenSearchResult(
+                            self.current_root_path()
+                                .map(|root| root.join(&s.file))
+                                .unwrap_or_else(|| s.file.clone()),
+                            s.line,
+                        )),
+                        text(suggestion_label(s)).width(Length::Fill),
+                        button("✓").on_press(Message::AcceptMetaSuggestion(i)),
+                        button("×").on_press(Message::RejectMetaSuggestion(i)),
+                    ]
+                    .spacing(5)
+                    .into()
+                })
+                .collect()
+        };
+        column![
+            header,
+            scrollable(column(items).spacing(5)).height(Length::Fixed(160.0))
+        ]
+        .spacing(5)
+        .into()
+    }
+}
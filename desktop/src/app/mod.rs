@@ -2,32 +2,86 @@ pub mod command_palette;
 pub mod command_translations;
 pub mod diff;
 pub mod events;
+pub mod git_status;
 pub mod io;
+mod issue_links;
+mod macros;
 pub mod log_translations;
+pub mod logging;
+pub mod navigation;
 pub mod search_translations;
 pub mod settings_translations;
 pub mod ui;
 
 mod actions;
+mod ai_assist;
+mod block_edit;
+mod block_history;
+mod build_tasks;
+mod canvas_filter;
+mod canvas_layers;
+mod canvas_window;
+mod clipboard;
+mod cochange;
+mod code_metrics;
+mod coverage;
+mod custom_commands;
+mod dead_code;
+mod debugging;
+mod file_refs;
+mod folding;
+mod formatting;
+mod git_branches;
+mod git_screen;
+mod inheritance;
+mod input_mode;
+mod meta_completion;
+mod meta_form;
+mod meta_rename;
+mod meta_suggestions;
+mod multi_cursor;
+mod notifications;
+mod offline_queue;
+mod panes;
+mod profile;
+mod project_env;
+mod recent_blocks;
+mod recovery;
+mod run_block;
+mod saved_searches;
+mod semantic_search;
 mod state;
+mod structural_search;
+mod summaries;
+mod symbol_search;
+mod tab_language;
+mod tags;
+mod task_board;
+mod terminal;
+mod test_runner;
+mod themes;
 mod view;
 
 pub use crate::visual::translations::Language;
+use crate::visual::accessibility::DEFAULT_FONT_SIZE;
 pub use log_translations::{format_log, LogMessage};
 pub use state::{
     AppTheme, CreateTarget, Diagnostic, EditorMode, EntryType, FileEntry, LogEntry, LogLevel,
-    MulticodeApp, PendingAction, Screen, Tab, TabDragState, UserSettings, ViewMode,
+    MulticodeApp, PendingAction, QuickFix, Screen, Tab, TabDragState, TextEdit, UserSettings,
+    ViewMode,
 };
 
-use iced::Application;
+use iced::multi_window::Application;
 use iced::Settings;
 use std::path::PathBuf;
 
 pub fn run(path: Option<PathBuf>) -> iced::Result {
     let settings = UserSettings::load();
     let flags = path.or_else(|| settings.last_folders.first().cloned());
+    let text_size = settings.accessibility.font_size(DEFAULT_FONT_SIZE);
     MulticodeApp::run(Settings {
         flags,
+        default_text_size: f32::from(text_size).into(),
         ..Settings::default()
     })
 }
@@ -38,16 +92,34 @@ pub fn save_log_to_file<P: AsRef<std::path::Path>>(
     path: P,
 ) -> Result<(), String> {
     use std::fs::File;
-    let items: Vec<serde_json::Value> = entries
-        .iter()
-        .map(|e| {
-            serde_json::json!({
-                "level": e.level.to_string(),
-                "message_key": format!("{:?}", e.message_key),
-                "args": e.args,
-            })
-        })
-        .collect();
+    let items: Vec<serde_json::Value> = entries.iter().map(log_record).collect();
     let file = File::create(path.as_ref()).map_err(|e| e.to_string())?;
     serde_json::to_writer_pretty(file, &items).map_err(|e| e.to_string())
 }
+
+/// Save log entries as NDJSON: one JSON object per line with the same
+/// fields as [`save_log_to_file`] plus `timestamp` (RFC 3339) and `module`.
+pub fn save_log_to_ndjson<P: AsRef<std::path::Path>>(
+    entries: &[LogEntry],
+    path: P,
+) -> Result<(), String> {
+    use std::io::Write;
+    let file = std::fs::File::create(path.as_ref()).map_err(|e| e.to_string())?;
+    let mut out = std::io::BufWriter::new(file);
+    for entry in entries {
+        let mut record = log_record(entry);
+        record["timestamp"] = entry.timestamp.to_rfc3339().into();
+        record["module"] = entry.module.clone().into();
+        serde_json::to_writer(&mut out, &record).map_err(|e| e.to_string())?;
+        writeln!(out).map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())
+}
+
+fn log_record(e: &LogEntry) -> serde_json::Value {
+    serde_json::json!({
+        "level": e.level.to_string(),
+        "message_key": format!("{:?}", e.message_key),
+        "args": e.args,
+    })
+}
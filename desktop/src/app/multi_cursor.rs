@@ -0,0 +1,103 @@
+//! Несколько кареток и прямоугольное выделение во вкладке редактора.
+//!
+//! Действия редактора проходят здесь до того, как их выполнит виджет.
+//! `Ctrl`+щелчок добавляет каретку, `Alt`+перетаскивание выделяет
+//! прямоугольник, а `Ctrl+Alt+↑`/`Ctrl+Alt+↓` — их редактор выполняет как
+//! перемещение — добавляют каретку строкой выше или ниже. Пока кареток
+//! несколько, правка применяется ко всем сразу: в историю отмены она
+//! попадает одним шагом, а в синхронизацию уходит одним изменением текста.
+
+use std::ops::Range;
+
+use iced::widget::text_editor::{Action, Motion};
+use iced::Command;
+
+use super::events::handler::push_with_limit;
+use super::events::Message;
+use super::MulticodeApp;
+use crate::editor::multi_cursor::{content_with_cursor, cursor_offset, highlights, line_column};
+
+impl MulticodeApp {
+    /// Подсветка дополнительных кареток в текущей вкладке.
+    pub fn caret_highlights(&self) -> Vec<(usize, Range<usize>)> {
+        match self.current_file() {
+            Some(f) if self.carets.is_active() => highlights(&f.content, self.carets.carets()),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Добавляет каретку строкой выше или ниже крайней.
+    pub(super) fn add_cursor(&mut self, down: bool) -> Command<Message> {
+        if let Some(f) = self.current_file() {
+            let primary = cursor_offset(&f.content, f.editor.cursor_position());
+            let text = f.content.clone();
+            self.carets.add_vertical(&text, primary, down);
+        }
+        Command::none()
+    }
+
+    /// Действие редактора с учётом кареток; `None`, если его выполняет
+    /// сам редактор.
+    pub(crate) fn multi_cursor_action(&mut self, action: &Action) -> Option<Command<Message>> {
+        let i = self.active_tab?;
+        let (control, alt) = (self.modifiers.control(), self.modifiers.alt());
+        let f = self.tabs.get_mut(i)?;
+        match action {
+            Action::Move(motion @ (Motion::Up | Motion::Down)) if control && alt => {
+                Some(self.add_cursor(*motion == Motion::Down))
+            }
+            Action::Click(_) if control => {
+                let old = cursor_offset(&f.content, f.editor.cursor_position());
+                f.editor.perform(action.clone());
+                let new = cursor_offset(&f.content, f.editor.cursor_position());
+                self.carets.add(old, new);
+                Some(Command::none())
+            }
+            Action::Click(_) if alt => {
+                f.editor.perform(action.clone());
+                let (line, byte) = f.editor.cursor_position();
+                self.column_anchor = Some(line_column(
+                    &f.content,
+                    cursor_offset(&f.content, (line, byte)),
+                ));
+                self.carets.clear();
+                Some(Command::none())
+            }
+            // Прямоугольник строится по каретке под указателем; щелчок
+            // вместо перетаскивания не оставляет обычного выделения.
+            Action::Drag(point) if self.column_anchor.is_some() => {
+                f.editor.perform(Action::Click(*point));
+                let to = line_column(
+                    &f.content,
+                    cursor_offset(&f.content, f.editor.cursor_position()),
+                );
+                let from = self.column_anchor?;
+                self.carets.column_select(&f.content, from, to);
+                Some(Command::none())
+            }
+            Action::Click(_) | Action::Drag(_) | Action::SelectWord | Action::SelectLine => {
+                self.column_anchor = None;
+                self.carets.clear();
+                None
+            }
+            _ if !self.carets.is_active() => None,
+            Action::Edit(edit) => {
+                let old_content = f.content.clone();
+                let text = self.carets.edit(&old_content, edit);
+                let primary = self.carets.primary()?.position;
+                push_with_limit(&mut f.undo_stack, old_content.clone());
+                f.redo_stack.clear();
+                f.editor = content_with_cursor(&text, primary);
+                Some(self.content_changed(i, old_content, true))
+            }
+            Action::Move(motion) | Action::Select(motion) => {
+                let select = matches!(action, Action::Select(_));
+                if !self.carets.motion(&f.content, *motion, select) {
+                    self.carets.clear();
+                }
+                None
+            }
+            Action::Scroll { .. } => None,
+        }
+    }
+}
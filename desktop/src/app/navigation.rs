@@ -0,0 +1,304 @@
+use std::path::PathBuf;
+
+use iced::widget::text_editor::Content;
+use iced::Command;
+use multicode_core::blocks::BlockLocator;
+use multicode_core::meta::bookmarks;
+use multicode_core::BlockInfo;
+
+use super::events::Message;
+use super::{MulticodeApp, Tab};
+use crate::sync::SyncMessage;
+use crate::visual::change::delta_from_meta;
+
+/// Максимальное количество позиций в истории навигации.
+const NAV_HISTORY_LIMIT: usize = 100;
+
+/// Позиция в проекте: файл, строка и, при наличии, блок.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavLocation {
+    pub path: PathBuf,
+    pub line: usize,
+    pub visual_id: Option<String>,
+}
+
+/// История переходов «назад/вперёд» между файлами и блоками.
+#[derive(Debug, Default)]
+pub struct NavigationHistory {
+    back: Vec<NavLocation>,
+    forward: Vec<NavLocation>,
+}
+
+impl NavigationHistory {
+    /// Запоминает позицию, с которой выполняется переход.
+    ///
+    /// Новый переход очищает стек «вперёд», как в IDE.
+    pub fn record(&mut self, from: NavLocation) {
+        if self.back.last() != Some(&from) {
+            if self.back.len() >= NAV_HISTORY_LIMIT {
+                self.back.remove(0);
+            }
+            self.back.push(from);
+        }
+        self.forward.clear();
+    }
+
+    /// Возвращает предыдущую позицию, сохраняя `current` для перехода вперёд.
+    pub fn back(&mut self, current: Option<NavLocation>) -> Option<NavLocation> {
+        let target = self.back.pop()?;
+        if let Some(cur) = current {
+            self.forward.push(cur);
+        }
+        Some(target)
+    }
+
+    /// Возвращает следующую позицию, сохраняя `current` для перехода назад.
+    pub fn forward(&mut self, current: Option<NavLocation>) -> Option<NavLocation> {
+        let target = self.forward.pop()?;
+        if let Some(cur) = current {
+            self.back.push(cur);
+        }
+        Some(target)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
+
+/// Закладка на блоке открытого файла.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bookmark {
+    pub location: NavLocation,
+    pub label: String,
+}
+
+/// Номер строки (с нуля) для байтового смещения в `content`.
+pub fn line_of_offset(content: &str, offset: usize) -> usize {
+    let end = offset.min(content.len());
+    content.as_bytes()[..end].iter().filter(|b| **b == b'\n').count()
+}
+
+/// Байтовое смещение для строки и столбца (в символах) в `content`.
+pub fn offset_of_position(content: &str, line: usize, column: usize) -> usize {
+    let mut offset = 0;
+    for (i, l) in content.split('\n').enumerate() {
+        if i == line {
+            return offset
+                + l.char_indices()
+                    .nth(column)
+                    .map(|(b, _)| b)
+                    .unwrap_or(l.len());
+        }
+        offset += l.len() + 1;
+    }
+    content.len()
+}
+
+/// Индекс самого вложенного блока, содержащего смещение `offset`.
+pub fn block_at_offset(blocks: &[BlockInfo], offset: usize) -> Option<usize> {
+    BlockLocator::new(blocks).block_at(offset)
+}
+
+/// Собирает закладки всех открытых вкладок, упорядоченные по файлу и строке.
+pub fn collect_bookmarks(tabs: &[Tab]) -> Vec<Bookmark> {
+    let mut list: Vec<Bookmark> = tabs
+        .iter()
+        .flat_map(|tab| {
+            tab.blocks
+                .iter()
+                .filter(|b| bookmarks::is_bookmarked(&b.tags))
+                .map(move |b| Bookmark {
+                    location: NavLocation {
+                        path: tab.path.clone(),
+                        line: line_of_offset(&tab.content, b.range.0),
+                        visual_id: Some(b.visual_id.clone()),
+                    },
+                    label: b
+                        .translations
+                        .get("en")
+                        .cloned()
+                        .unwrap_or_else(|| b.kind.clone()),
+                })
+        })
+        .collect();
+    list.sort_by(|a, b| {
+        (&a.location.path, a.location.line).cmp(&(&b.location.path, b.location.line))
+    });
+    list
+}
+
+/// Выбирает следующую (или предыдущую) закладку относительно `current`
+/// с переходом по кругу.
+pub fn adjacent_bookmark<'a>(
+    list: &'a [Bookmark],
+    current: Option<&NavLocation>,
+    forward: bool,
+) -> Option<&'a Bookmark> {
+    let key = |l: &NavLocation| (l.path.clone(), l.line);
+    let Some(cur) = current.map(key) else {
+        return if forward { list.first() } else { list.last() };
+    };
+    if forward {
+        list.iter()
+            .find(|b| key(&b.location) > cur)
+            .or_else(|| list.first())
+    } else {
+        list.iter()
+            .rev()
+            .find(|b| key(&b.location) < cur)
+            .or_else(|| list.last())
+    }
+}
+
+impl MulticodeApp {
+    /// Текущая позиция курсора в активной вкладке.
+    pub fn current_location(&self) -> Option<NavLocation> {
+        let tab = self.current_file()?;
+        let (line, column) = tab.editor.cursor_position();
+        let offset = offset_of_position(&tab.content, line, column);
+        let visual_id =
+            block_at_offset(&tab.blocks, offset).map(|i| tab.blocks[i].visual_id.clone());
+        Some(NavLocation {
+            path: tab.path.clone(),
+            line,
+            visual_id,
+        })
+    }
+
+    pub fn bookmarks(&self) -> Vec<Bookmark> {
+        collect_bookmarks(&self.tabs)
+    }
+
+    /// Переходит к позиции, запоминая текущую в истории навигации.
+    pub(super) fn navigate_to(&mut self, target: NavLocation) -> Command<Message> {
+        if let Some(from) = self.current_location() {
+            if from != target {
+                self.navigation.record(from);
+            }
+        }
+        self.jump_to(target)
+    }
+
+    /// Переходит к позиции без изменения истории навигации.
+    pub(super) fn jump_to(&mut self, target: NavLocation) -> Command<Message> {
+        self.goto_line = Some(target.line);
+        if let Some(idx) = self.tabs.iter().position(|t| t.path == target.path) {
+            self.active_tab = Some(idx);
+            self.goto_line = None;
+            self.move_cursor_to(target.line, 0);
+            return Command::none();
+        }
+        self.open_file(target.path, false)
+    }
+
+    /// Переключает закладку на блоке под курсором активной вкладки.
+    pub(super) fn toggle_bookmark_at_cursor(&mut self) {
+        let Some(i) = self.active_tab else {
+            return;
+        };
+        let Some(loc) = self.current_location() else {
+            return;
+        };
+        let Some(visual_id) = loc.visual_id else {
+            return;
+        };
+        let meta = self.tabs.get_mut(i).and_then(|tab| {
+            let block = tab.blocks.iter_mut().find(|b| b.visual_id == visual_id)?;
+            bookmarks::toggle(&mut block.tags);
+            tab.dirty = true;
+            Some(Self::block_to_meta(block))
+        });
+        if let Some(meta) = meta {
+            let delta = delta_from_meta(&meta);
+            if let Some((code, _, _)) = self.sync_engine.handle(SyncMessage::VisualChanged(meta)) {
+                if let Some(tab) = self.tabs.get_mut(i) {
+                    tab.content = code.to_string();
+                    tab.editor = Content::with_text(&tab.content);
+                }
+            }
+            self.change_tracker.record_visual(delta);
+        }
+    }
+
+    /// Переходит к следующей или предыдущей закладке.
+    pub(super) fn cycle_bookmark(&mut self, forward: bool) -> Command<Message> {
+        let list = self.bookmarks();
+        let current = self.current_location();
+        match adjacent_bookmark(&list, current.as_ref(), forward) {
+            Some(bm) => {
+                let target = bm.location.clone();
+                self.navigate_to(target)
+            }
+            None => Command::none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loc(path: &str, line: usize) -> NavLocation {
+        NavLocation {
+            path: PathBuf::from(path),
+            line,
+            visual_id: None,
+        }
+    }
+
+    #[test]
+    fn history_back_and_forward() {
+        let mut history = NavigationHistory::default();
+        history.record(loc("a.rs", 1));
+        history.record(loc("b.rs", 5));
+        assert_eq!(history.back(Some(loc("c.rs", 0))), Some(loc("b.rs", 5)));
+        assert!(history.can_go_forward());
+        assert_eq!(history.forward(Some(loc("b.rs", 5))), Some(loc("c.rs", 0)));
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back(None), Some(loc("b.rs", 5)));
+        assert_eq!(history.back(None), Some(loc("a.rs", 1)));
+        assert_eq!(history.back(None), None);
+    }
+
+    #[test]
+    fn record_clears_forward_and_skips_duplicates() {
+        let mut history = NavigationHistory::default();
+        history.record(loc("a.rs", 1));
+        history.record(loc("a.rs", 1));
+        let _ = history.back(Some(loc("b.rs", 2)));
+        history.record(loc("c.rs", 3));
+        assert!(!history.can_go_forward());
+        assert_eq!(history.back(None), Some(loc("c.rs", 3)));
+        assert_eq!(history.back(None), None);
+    }
+
+    #[test]
+    fn adjacent_bookmark_wraps_around() {
+        let list: Vec<Bookmark> = [("a.rs", 2), ("a.rs", 10), ("b.rs", 0)]
+            .iter()
+            .map(|(p, l)| Bookmark {
+                location: loc(p, *l),
+                label: String::new(),
+            })
+            .collect();
+        let next = adjacent_bookmark(&list, Some(&loc("a.rs", 5)), true).unwrap();
+        assert_eq!(next.location, loc("a.rs", 10));
+        let next = adjacent_bookmark(&list, Some(&loc("b.rs", 0)), true).unwrap();
+        assert_eq!(next.location, loc("a.rs", 2));
+        let prev = adjacent_bookmark(&list, Some(&loc("a.rs", 2)), false).unwrap();
+        assert_eq!(prev.location, loc("b.rs", 0));
+    }
+
+    #[test]
+    fn offsets_and_lines_roundtrip() {
+        let content = "fn a() {}\nfn b() {\n    1\n}\n";
+        let offset = offset_of_position(content, 2, 4);
+        assert_eq!(&content[offset..offset + 1], "1");
+        assert_eq!(line_of_offset(content, offset), 2);
+    }
+}
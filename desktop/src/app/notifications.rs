@@ -0,0 +1,139 @@
+//! Центр уведомлений.
+//!
+//! Фоновые события, которые легко пропустить среди строк журнала, —
+//! обновление блоков наблюдателем, завершение проверки, ошибки сохранения и
+//! хуков, предупреждения модулей `core` — кроме журнала попадают в
+//! [`Notifications`]. Панель уведомлений показывает их по уровню важности,
+//! каждое уведомление закрывается отдельно. Повтор ещё не закрытого
+//! уведомления не добавляет новое, а увеличивает его счётчик.
+
+use std::ops::Deref;
+
+use iced::Color;
+
+use super::{LogEntry, LogLevel, MulticodeApp};
+
+/// Сколько уведомлений хранится; самые старые отбрасываются.
+pub const NOTIFICATION_LIMIT: usize = 50;
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub id: u64,
+    pub entry: LogEntry,
+    /// Сколько раз событие произошло, пока уведомление не закрыто.
+    pub count: usize,
+}
+
+/// Незакрытые уведомления, новые первыми.
+#[derive(Debug, Clone, Default)]
+pub struct Notifications {
+    items: Vec<Notification>,
+    next_id: u64,
+}
+
+impl Notifications {
+    /// Добавляет уведомление первым. Уведомление о том же событии
+    /// поднимается наверх с увеличенным счётчиком.
+    pub fn push(&mut self, entry: LogEntry) {
+        let count = self
+            .items
+            .iter()
+            .position(|n| n.entry.message_key == entry.message_key && n.entry.args == entry.args)
+            .map_or(1, |i| self.items.remove(i).count + 1);
+        self.next_id += 1;
+        self.items.insert(
+            0,
+            Notification {
+                id: self.next_id,
+                entry,
+                count,
+            },
+        );
+        self.items.truncate(NOTIFICATION_LIMIT);
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.items.retain(|n| n.id != id);
+    }
+
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    /// Наибольшая важность среди уведомлений.
+    pub fn severity(&self) -> Option<LogLevel> {
+        self.items.iter().map(|n| n.entry.level).max()
+    }
+}
+
+impl Deref for Notifications {
+    type Target = [Notification];
+
+    fn deref(&self) -> &[Notification] {
+        &self.items
+    }
+}
+
+/// Цвет уровня важности в панели уведомлений.
+pub fn severity_color(level: LogLevel) -> Color {
+    match level {
+        LogLevel::Error => Color::from_rgb(0.85, 0.2, 0.2),
+        LogLevel::Warning => Color::from_rgb(0.9, 0.6, 0.1),
+        LogLevel::Info => Color::from_rgb(0.3, 0.5, 0.9),
+        LogLevel::Debug => Color::from_rgb(0.5, 0.5, 0.5),
+    }
+}
+
+impl MulticodeApp {
+    /// Записывает событие в журнал и показывает его в центре уведомлений.
+    pub(super) fn notify(&mut self, entry: LogEntry) {
+        self.notifications.push(entry.clone());
+        self.log.push(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::LogMessage;
+    use chrono::Utc;
+
+    fn save_error(path: &str) -> LogEntry {
+        LogEntry::new(LogMessage::SaveError, vec![path.into()], Utc::now())
+    }
+
+    #[test]
+    fn repeated_events_are_merged() {
+        let mut notifications = Notifications::default();
+        notifications.push(save_error("a.rs"));
+        notifications.push(LogEntry::new(LogMessage::FileSaved, vec![], Utc::now()));
+        notifications.push(save_error("a.rs"));
+        notifications.push(save_error("b.rs"));
+        let items: Vec<(&str, usize)> = notifications
+            .iter()
+            .map(|n| (n.entry.args.first().map_or("", String::as_str), n.count))
+            .collect();
+        assert_eq!(items, [("b.rs", 1), ("a.rs", 2), ("", 1)]);
+        assert_eq!(notifications.severity(), Some(LogLevel::Error));
+
+        let id = notifications[1].id;
+        notifications.dismiss(id);
+        notifications.push(save_error("a.rs"));
+        assert_eq!(notifications[0].count, 1);
+        notifications.clear();
+        assert_eq!(notifications.severity(), None);
+    }
+
+    #[test]
+    fn oldest_notifications_are_dropped() {
+        let mut notifications = Notifications::default();
+        for i in 0..NOTIFICATION_LIMIT + 5 {
+            notifications.push(save_error(&i.to_string()));
+        }
+        assert_eq!(notifications.len(), NOTIFICATION_LIMIT);
+        assert_eq!(
+            notifications[0].entry.args[0],
+            (NOTIFICATION_LIMIT + 4).to_string()
+        );
+    }
+}
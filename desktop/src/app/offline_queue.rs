@@ -0,0 +1,123 @@
+//! Очередь операций, ожидающих связи с сервером, в строке состояния.
+//!
+//! Пока в очереди проекта есть операции ([`OfflineQueue`]), приложение раз
+//! в [`QUEUE_POLL_INTERVAL`] повторяет те, время которых пришло, а строка
+//! состояния показывает их число и время следующей попытки. Нажатие на
+//! индикатор повторяет все операции сразу.
+
+use std::time::Duration;
+
+use chrono::Utc;
+use iced::widget::{button, text};
+use iced::{theme, Command, Element};
+use multicode_core::offline_queue::{self, OfflineQueue, QueueStatus, ReplayReport};
+use tokio::task;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+/// Как часто проверяется очередь, пока она не пуста.
+pub(super) const QUEUE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+impl MulticodeApp {
+    /// Загружает состояние очереди проекта.
+    pub(super) fn load_offline_queue(&mut self) {
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        match OfflineQueue::load(&root) {
+            Ok(queue) => self.offline_queue = queue.status(),
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::OfflineQueueError,
+                vec![e.to_string()],
+                Utc::now(),
+            )),
+        }
+    }
+
+    /// Повторяет в фоне операции очереди, время которых пришло, или, с
+    /// `force`, все операции.
+    pub(super) fn replay_offline_queue(&mut self, force: bool) -> Command<Message> {
+        if self.queue_replaying || self.offline_queue.pending == 0 {
+            return Command::none();
+        }
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        self.queue_replaying = true;
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    offline_queue::replay_project(&root, Utc::now(), force)
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r.map_err(|e| e.to_string()))
+            },
+            Message::OfflineQueueReplayed,
+        )
+    }
+
+    pub(super) fn offline_queue_replayed(
+        &mut self,
+        result: Result<(ReplayReport, QueueStatus), String>,
+    ) {
+        self.queue_replaying = false;
+        let (report, status) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                self.log.push(LogEntry::new(
+                    LogMessage::OfflineQueueError,
+                    vec![e],
+                    Utc::now(),
+                ));
+                return;
+            }
+        };
+        self.offline_queue = status;
+        for (kind, e) in report.failed {
+            self.log.push(LogEntry::new(
+                LogMessage::OfflineOpFailed,
+                vec![kind, e],
+                Utc::now(),
+            ));
+        }
+        if report.done > 0 {
+            self.notify(LogEntry::new(
+                LogMessage::OfflineQueueReplayed,
+                vec![report.done.to_string()],
+                Utc::now(),
+            ));
+        }
+    }
+
+    /// Текст индикатора очереди; `None`, если очередь пуста.
+    pub(super) fn offline_queue_label(&self) -> Option<String> {
+        let status = &self.offline_queue;
+        if status.pending == 0 {
+            return None;
+        }
+        let mut label = format!("offline queue {}", status.pending);
+        if self.queue_replaying {
+            label.push_str(" | retrying");
+        } else if let Some(next) = status.next_attempt {
+            let secs = (next - Utc::now()).num_seconds();
+            if secs > 0 {
+                label.push_str(&format!(" | retry in {secs}s"));
+            }
+        }
+        Some(label)
+    }
+
+    /// Индикатор очереди в строке состояния.
+    pub(super) fn offline_queue_indicator(&self) -> Option<Element<'_, Message>> {
+        let label = self.offline_queue_label()?;
+        Some(
+            button(text(label))
+                .padding(0)
+                .style(theme::Button::Text)
+                .on_press(Message::ReplayOfflineQueue(true))
+                .into(),
+        )
+    }
+}
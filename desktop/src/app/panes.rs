@@ -0,0 +1,217 @@
+//! Панели редактора.
+//!
+//! Раскладка хранится в настройках ([`UserSettings::panes`]), поэтому
+//! переживает перезапуск: при открытии проекта файлы панелей открываются
+//! снова. Активная вкладка приложения всегда показывается в панели в
+//! фокусе; сообщения из другой панели приходят обёрнутыми в
+//! [`Message::PaneMessage`] и сначала переводят фокус в неё.
+//!
+//! [`UserSettings::panes`]: super::UserSettings::panes
+
+use std::path::PathBuf;
+
+use iced::widget::{button, column, container, row, text, Column, Row};
+use iced::{theme, Command, Element, Length};
+
+use super::events::Message;
+use super::MulticodeApp;
+use crate::editor::panes::{PaneAxis, PaneLayout, PaneMode};
+use crate::editor::CodeEditor;
+
+impl MulticodeApp {
+    pub fn pane_layout(&self) -> Option<&PaneLayout> {
+        self.settings.panes.as_ref()
+    }
+
+    fn tab_index(&self, path: &std::path::Path) -> Option<usize> {
+        self.tabs.iter().position(|t| t.path == path)
+    }
+
+    /// Переносит смену активной вкладки в раскладку. Вызывается после
+    /// каждого сообщения.
+    pub(super) fn sync_panes(&mut self) {
+        let Some(path) = self.current_file().map(|f| f.path.clone()) else {
+            return;
+        };
+        let Some(layout) = self.settings.panes.as_mut() else {
+            return;
+        };
+        if let Some(pos) = layout.restoring.iter().position(|p| *p == path) {
+            // Восстановленные файлы уже разложены по панелям; когда открыт
+            // последний, активной становится вкладка панели в фокусе.
+            layout.restoring.remove(pos);
+            if layout.restoring.is_empty() {
+                let focused = layout.focused;
+                self.focus_pane(focused);
+            }
+        } else if layout.focused_pane().active.as_ref() != Some(&path) {
+            layout.activate(&path);
+        }
+    }
+
+    /// Команды открытия файлов сохранённой раскладки.
+    pub(super) fn restore_panes(&mut self) -> Vec<Command<Message>> {
+        let Some(layout) = self.settings.panes.as_mut() else {
+            return Vec::new();
+        };
+        let paths: Vec<PathBuf> = layout
+            .restore_order()
+            .into_iter()
+            .filter(|p| p.is_file())
+            .collect();
+        layout.restoring = paths.clone();
+        paths
+            .into_iter()
+            .map(|p| self.handle_message(Message::SelectFile(p)))
+            .collect()
+    }
+
+    pub(super) fn split_pane(&mut self, axis: PaneAxis) -> Command<Message> {
+        match self.settings.panes.as_mut() {
+            Some(layout) => layout.axis = axis,
+            None => {
+                let tabs = self.tabs.iter().map(|t| t.path.clone()).collect();
+                let active = self.current_file().map(|f| f.path.clone());
+                self.settings.panes = Some(PaneLayout::split(axis, tabs, active));
+            }
+        }
+        // Панели живут в текстовом редакторе.
+        if matches!(self.screen, super::Screen::TextEditor { .. }) {
+            self.handle_message(Message::SaveSettings)
+        } else {
+            self.handle_message(Message::SwitchViewMode(super::ViewMode::Code))
+        }
+    }
+
+    pub(super) fn unsplit_pane(&mut self) -> Command<Message> {
+        if self.settings.panes.take().is_none() {
+            return Command::none();
+        }
+        self.handle_message(Message::SaveSettings)
+    }
+
+    /// Переводит фокус в панель `pane` и делает её файл активной вкладкой.
+    pub(super) fn focus_pane(&mut self, pane: usize) {
+        let Some(layout) = self.settings.panes.as_mut().filter(|_| pane < 2) else {
+            return;
+        };
+        layout.focused = pane;
+        let active = layout.panes[pane].active.clone();
+        let index = active.and_then(|p| self.tab_index(&p));
+        if index != self.active_tab {
+            self.active_tab = index;
+            self.search_results.clear();
+            self.current_match = None;
+            self.carets.clear();
+        }
+    }
+
+    pub(super) fn set_pane_mode(&mut self, pane: usize, mode: PaneMode) -> Command<Message> {
+        let Some(p) = self
+            .settings
+            .panes
+            .as_mut()
+            .and_then(|l| l.panes.get_mut(pane))
+        else {
+            return Command::none();
+        };
+        p.mode = mode;
+        self.handle_message(Message::SaveSettings)
+    }
+
+    /// Убирает файл из панели; вкладка закрывается, если файл не открыт в
+    /// другой панели.
+    pub(super) fn close_pane_tab(&mut self, pane: usize, path: PathBuf) -> Command<Message> {
+        let Some(layout) = self.settings.panes.as_mut() else {
+            return Command::none();
+        };
+        let unused = layout.close(pane, &path);
+        let close = match self.tab_index(&path) {
+            Some(i) if unused => self.handle_message(Message::CloseFile(i)),
+            _ => Command::none(),
+        };
+        self.focus_pane(pane);
+        Command::batch([close, self.handle_message(Message::SaveSettings)])
+    }
+
+    fn pane_component(&self, layout: &PaneLayout, pane: usize) -> Element<'_, Message> {
+        let p = &layout.panes[pane];
+        let focused = pane == layout.focused;
+        let tabs = p.tabs.iter().map(|path| {
+            let title = match self.tab_index(path) {
+                Some(i) => self.tab_title(&self.tabs[i]),
+                None => path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            };
+            let title = if p.active.as_ref() == Some(path) {
+                format!("[{title}]")
+            } else {
+                title
+            };
+            row![
+                button(text(title))
+                    .style(theme::Button::Text)
+                    .on_press(Message::SelectFile(path.clone())),
+                button(text("x")).on_press(Message::ClosePaneTab(pane, path.clone()))
+            ]
+            .spacing(5)
+            .into()
+        });
+        let mut header = Row::with_children(tabs.collect::<Vec<_>>()).spacing(5);
+        header = header.push(container(text("")).width(Length::Fill));
+        for (label, mode) in [("Текст", PaneMode::Text), ("Схема", PaneMode::Visual)] {
+            let b = button(text(label));
+            header = header.push(if p.mode == mode {
+                b
+            } else {
+                b.on_press(Message::SetPaneMode(pane, mode))
+            });
+        }
+        if pane == 1 {
+            header = header.push(button(text("Объединить")).on_press(Message::UnsplitPane));
+        }
+        let header = container(header).width(Length::Fill).padding(2);
+        let header = if focused {
+            header.style(theme::Container::Box)
+        } else {
+            header
+        };
+
+        let index = p.active.as_ref().and_then(|path| self.tab_index(path));
+        let body: Element<Message> = match (focused, p.mode, index) {
+            (true, PaneMode::Text, _) => CodeEditor::new(self).view(),
+            (true, PaneMode::Visual, _) => self.visual_editor_component(),
+            (false, PaneMode::Text, Some(i)) => CodeEditor::for_tab(self, i).view(),
+            (false, PaneMode::Visual, Some(i)) => self.visual_editor_for(Some(i)),
+            (false, _, None) => {
+                container(button(text("Выбрать панель")).on_press(Message::FocusPane(pane)))
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .center_x()
+                    .center_y()
+                    .into()
+            }
+        };
+        let content: Element<Message> = column![header, body]
+            .spacing(5)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into();
+        if focused {
+            content
+        } else {
+            content.map(move |m| Message::PaneMessage(pane, Box::new(m)))
+        }
+    }
+
+    /// Две панели редактора по раскладке `layout`.
+    pub(super) fn panes_component(&self, layout: &PaneLayout) -> Element<'_, Message> {
+        let panes = [0, 1].map(|i| self.pane_component(layout, i));
+        match layout.axis {
+            PaneAxis::Horizontal => Row::with_children(panes).spacing(10).into(),
+            PaneAxis::Vertical => Column::with_children(panes).spacing(10).into(),
+        }
+    }
+}
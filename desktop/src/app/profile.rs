@@ -0,0 +1,111 @@
+//! Профиль холста.
+//!
+//! Команда профиля по кругу включает раскраску блоков по времени разбора,
+//! затем по числу выполнений из отчёта о покрытии и выключает её. Время
+//! разбора измеряется один раз на версию разбора вкладки, а отчёт о
+//! покрытии загружается при первом включении числа выполнений.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use iced::widget::{row, text, Space};
+use iced::{Command, Element, Length};
+use multicode_core::blocks::profile::parse_costs;
+use multicode_core::coverage::block_hits;
+
+use super::events::Message;
+use super::{MulticodeApp, Tab};
+use crate::visual::profile::{format_value, heat, heat_color, parse_value, ProfileMetric};
+
+/// Время разбора блоков по файлам вместе с версией разбора, при которой оно
+/// измерено.
+pub(super) type ParseCosts = HashMap<PathBuf, (u64, HashMap<String, Duration>)>;
+
+impl MulticodeApp {
+    /// Переключает профиль холста: выключен → время разбора → число
+    /// выполнений → выключен.
+    pub(super) fn cycle_profile(&mut self) -> Command<Message> {
+        self.profile_metric = match self.profile_metric {
+            None => Some(ProfileMetric::ParseTime),
+            Some(ProfileMetric::ParseTime) => Some(ProfileMetric::Executions),
+            Some(ProfileMetric::Executions) => None,
+        };
+        if self.profile_metric == Some(ProfileMetric::Executions) && self.coverage.is_none() {
+            return self.load_coverage();
+        }
+        Command::none()
+    }
+
+    /// Время разбора блоков вкладки `tab` по `visual_id`. Измерение
+    /// повторяется, только когда у вкладки сменилась версия разбора.
+    fn tab_parse_costs(&self, tab: &Tab) -> HashMap<String, Duration> {
+        let mut cache = self.parse_costs.borrow_mut();
+        if let Some((version, costs)) = cache.get(&tab.path) {
+            if *version == tab.analysis_version {
+                return costs.clone();
+            }
+        }
+        let costs: HashMap<String, Duration> = match tab.lang {
+            Some(lang) => tab
+                .blocks
+                .iter()
+                .map(|b| b.visual_id.clone())
+                .zip(parse_costs(&tab.content, lang, &tab.blocks))
+                .collect(),
+            None => HashMap::new(),
+        };
+        cache.insert(tab.path.clone(), (tab.analysis_version, costs.clone()));
+        costs
+    }
+
+    /// Нагрев и подпись стоимости каждого блока вкладки `tab`; `None`, если
+    /// профиль выключен.
+    pub(super) fn tab_profile(&self, tab: &Tab) -> Option<Vec<Option<(f32, String)>>> {
+        let metric = self.profile_metric?;
+        let values: Vec<Option<u64>> = match metric {
+            ProfileMetric::ParseTime => {
+                let costs = self.tab_parse_costs(tab);
+                tab.blocks
+                    .iter()
+                    .map(|b| costs.get(&b.visual_id).copied().map(parse_value))
+                    .collect()
+            }
+            ProfileMetric::Executions => match &self.coverage {
+                Some(report) => block_hits(report, &tab.path, &tab.content, &tab.blocks),
+                None => vec![None; tab.blocks.len()],
+            },
+        };
+        let thresholds = self.settings.canvas_profile.thresholds(metric);
+        Some(
+            values
+                .into_iter()
+                .map(|v| v.map(|v| (heat(v, thresholds), format_value(metric, v))))
+                .collect(),
+        )
+    }
+
+    /// Легенда профиля над холстом: мера и цвета порогов.
+    pub fn profile_legend_component(&self) -> Element<'_, Message> {
+        let Some(metric) = self.profile_metric else {
+            return Space::with_height(Length::Shrink).into();
+        };
+        let (warm, hot) = self.settings.canvas_profile.thresholds(metric);
+        let swatch = |h: f32| text("■").style(heat_color(h));
+        row![
+            text(format!("Профиль: {metric}")),
+            swatch(0.0),
+            text(format!("< {}", format_value(metric, warm))),
+            swatch(0.5),
+            text(format!(
+                "{} – {}",
+                format_value(metric, warm),
+                format_value(metric, hot)
+            )),
+            swatch(1.0),
+            text(format!("≥ {}", format_value(metric, hot))),
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,136 @@
+//! Редактор переменных окружения проекта.
+//!
+//! Редактор правит копию переменных из `.multicode/env` ([`ProjectEnv`])
+//! и записывает её по кнопке «Сохранить». Значение секрета в редакторе не
+//! показывается: введённое значение при сохранении уходит в связку ключей
+//! системы, а пустое оставляет прежний секрет. Секреты, удалённые из
+//! списка, удаляются и из связки ключей.
+
+use chrono::Utc;
+use iced::widget::{button, checkbox, column, row, scrollable, text, text_input, Space};
+use iced::{Command, Element, Length};
+use multicode_core::project_env::{EnvVar, ProjectEnv, SecretStore, SystemKeychain};
+use tokio::task;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+impl MulticodeApp {
+    /// Загружает переменные окружения проекта в редактор.
+    pub(super) fn load_project_env(&mut self) {
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        match ProjectEnv::load(&root) {
+            Ok(env) => self.project_env = env,
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::ProjectEnvError,
+                vec![e.to_string()],
+                Utc::now(),
+            )),
+        }
+    }
+
+    /// Записывает переменные редактора в проект, а значения секретов — в
+    /// связку ключей.
+    pub(super) fn save_project_env(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let mut env = ProjectEnv::default();
+        let mut secrets = Vec::new();
+        for var in &self.project_env.vars {
+            let key = var.key.trim();
+            if key.is_empty() {
+                continue;
+            }
+            if var.secret {
+                if !var.value.is_empty() {
+                    secrets.push((key.to_string(), Some(var.value.clone())));
+                }
+                env.set(EnvVar::secret(key));
+            } else {
+                env.set(EnvVar::new(key, var.value.clone()));
+            }
+        }
+        let previous = ProjectEnv::load(&root).unwrap_or_default();
+        secrets.extend(
+            previous
+                .vars
+                .into_iter()
+                .filter(|old| old.secret && !env.vars.iter().any(|v| v.secret && v.key == old.key))
+                .map(|old| (old.key, None)),
+        );
+        if let Err(e) = env.save(&root) {
+            self.log.push(LogEntry::new(
+                LogMessage::ProjectEnvError,
+                vec![e.to_string()],
+                Utc::now(),
+            ));
+            return Command::none();
+        }
+        self.notify(LogEntry::new(
+            LogMessage::ProjectEnvSaved,
+            vec![env.vars.len().to_string()],
+            Utc::now(),
+        ));
+        self.project_env = env;
+        if secrets.is_empty() {
+            return Command::none();
+        }
+        // Связка ключей может запросить разблокировку, поэтому секреты
+        // записываются в фоне.
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    secrets
+                        .into_iter()
+                        .try_for_each(|(key, value)| match value {
+                            Some(value) => SystemKeychain.set(&root, &key, &value),
+                            None => SystemKeychain.delete(&root, &key),
+                        })
+                })
+                .await
+                .map_err(|e| e.to_string())
+                .and_then(|r| r.map_err(|e| e.to_string()))
+            },
+            Message::ProjectSecretsSaved,
+        )
+    }
+
+    pub fn env_editor_component(&self) -> Element<'_, Message> {
+        if !self.show_env_editor {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let header = row![
+            text("Окружение проекта").width(Length::Fill),
+            button("Добавить").on_press(Message::AddEnvVar),
+            button("Сохранить").on_press(Message::SaveProjectEnv),
+            button("×").on_press(Message::ToggleEnvEditor),
+        ]
+        .spacing(5);
+        let rows = self.project_env.vars.iter().enumerate().map(|(i, var)| {
+            let value = if var.secret {
+                text_input("в связке ключей", &var.value).secure(true)
+            } else {
+                text_input("значение", &var.value)
+            };
+            row![
+                text_input("ИМЯ", &var.key)
+                    .on_input(move |s| Message::EnvKeyChanged(i, s))
+                    .width(Length::Fixed(180.0)),
+                value.on_input(move |s| Message::EnvValueChanged(i, s)),
+                checkbox("секрет", var.secret).on_toggle(move |b| Message::EnvSecretToggled(i, b)),
+                button("×").on_press(Message::RemoveEnvVar(i)),
+            ]
+            .spacing(5)
+            .into()
+        });
+        column![
+            header,
+            scrollable(column(rows).spacing(5)).height(Length::Fixed(160.0))
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,173 @@
+//! Недавно изменённые блоки.
+//!
+//! Каждая правка текста записывает в список проекта ([`RecentBlocks`])
+//! объемлющую функцию или класс. Окно «Недавние блоки» показывает их от
+//! последней правки к давней и возвращает к выбранному блоку. Список
+//! сохраняется в проекте, когда в нём появляется новый первый блок, и при
+//! сохранении файла.
+
+use chrono::Utc;
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{Command, Element, Length};
+use multicode_core::recent_blocks::{edited_block, RecentBlock, RecentBlocks};
+
+use super::events::Message;
+use super::navigation::{offset_of_position, NavLocation};
+use super::{Language, LogEntry, LogMessage, MulticodeApp};
+use crate::modal::Modal;
+
+/// Записи недавних блоков, подходящие под `query`: подстрока метки или
+/// пути файла без учёта регистра.
+pub fn filter_recent<'a>(recent: &'a RecentBlocks, query: &str) -> Vec<(usize, &'a RecentBlock)> {
+    let query = query.trim().to_lowercase();
+    recent
+        .entries
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| {
+            e.label.to_lowercase().contains(&query)
+                || e.file.to_string_lossy().to_lowercase().contains(&query)
+        })
+        .collect()
+}
+
+impl MulticodeApp {
+    pub fn recent_blocks(&self) -> &RecentBlocks {
+        &self.recent_blocks
+    }
+
+    /// Загружает недавние блоки проекта.
+    pub(super) fn load_recent_blocks(&mut self) {
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        match RecentBlocks::load(&root) {
+            Ok(recent) => self.recent_blocks = recent,
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::RecentBlocksError,
+                vec![e.to_string()],
+                Utc::now(),
+            )),
+        }
+    }
+
+    pub(super) fn save_recent_blocks(&mut self) {
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        if let Err(e) = self.recent_blocks.save(&root) {
+            self.log.push(LogEntry::new(
+                LogMessage::RecentBlocksError,
+                vec![e.to_string()],
+                Utc::now(),
+            ));
+        }
+    }
+
+    /// Записывает блок под курсором вкладки `i` после правки. Блоки вкладки
+    /// ещё относятся к тексту до правки `old_content`.
+    pub(super) fn record_edited_block(&mut self, i: usize, old_content: &str) {
+        let Some(root) = self.current_root_path() else {
+            return;
+        };
+        let Some(tab) = self.tabs.get(i) else {
+            return;
+        };
+        let Ok(file) = tab.path.strip_prefix(&root) else {
+            return;
+        };
+        let (line, column) = tab.editor.cursor_position();
+        let offset = offset_of_position(&tab.content, line, column);
+        let Some(block) = edited_block(file, old_content, &tab.blocks, offset, Utc::now()) else {
+            return;
+        };
+        if self.recent_blocks.record(block) {
+            self.save_recent_blocks();
+        }
+    }
+
+    /// Забывает недавние блоки удалённого файла `path`.
+    pub(super) fn forget_recent_blocks(&mut self, path: &std::path::Path) {
+        let forgotten = self
+            .current_root_path()
+            .and_then(|root| path.strip_prefix(root).ok().map(|p| p.to_path_buf()))
+            .is_some_and(|file| self.recent_blocks.forget_file(&file));
+        if forgotten {
+            self.save_recent_blocks();
+        }
+    }
+
+    pub(super) fn toggle_recent_blocks(&mut self) {
+        self.recent_blocks_query = match self.recent_blocks_query {
+            Some(_) => None,
+            None => Some(String::new()),
+        };
+    }
+
+    /// Переходит к недавнему блоку `index` и закрывает окно. Если блок с
+    /// тех пор изменился, переход идёт к строке, где он был.
+    pub(super) fn open_recent_block(&mut self, index: usize) -> Command<Message> {
+        self.recent_blocks_query = None;
+        let (Some(root), Some(entry)) = (
+            self.current_root_path(),
+            self.recent_blocks.entries.get(index).cloned(),
+        ) else {
+            return Command::none();
+        };
+        let path = root.join(&entry.file);
+        self.pending_block_focus = Some((path.clone(), entry.visual_id.clone()));
+        let command = self.navigate_to(NavLocation {
+            path,
+            line: entry.line,
+            visual_id: Some(entry.visual_id),
+        });
+        self.focus_pending_block();
+        command
+    }
+
+    pub fn recent_blocks_modal<'a>(&self, content: Element<'a, Message>) -> Element<'a, Message> {
+        let Some(query) = &self.recent_blocks_query else {
+            return content;
+        };
+        let placeholder = if self.settings.language == Language::Russian {
+            "блок или файл"
+        } else {
+            "block or file"
+        };
+        let matches = filter_recent(&self.recent_blocks, query);
+        let mut input = text_input(placeholder, query).on_input(Message::RecentBlocksQueryChanged);
+        if let Some((first, _)) = matches.first() {
+            input = input.on_submit(Message::OpenRecentBlock(*first));
+        }
+        let items = matches
+            .into_iter()
+            .fold(column![], |col, (i, entry)| {
+                let edited = entry
+                    .edited_at
+                    .with_timezone(&chrono::Local)
+                    .format("%d.%m %H:%M")
+                    .to_string();
+                let content = row![
+                    text(&entry.label).width(Length::Fill),
+                    text(format!("{}:{}", entry.file.display(), entry.line + 1)),
+                    text(edited)
+                ]
+                .spacing(10);
+                col.push(
+                    button(content)
+                        .width(Length::Fill)
+                        .on_press(Message::OpenRecentBlock(i)),
+                )
+            })
+            .spacing(5);
+        let modal_content = container(
+            column![input, scrollable(items).height(Length::Shrink)]
+                .spacing(10)
+                .width(Length::Fixed(600.0)),
+        )
+        .padding(10);
+        Modal::new(content, modal_content)
+            .on_blur(Message::ToggleRecentBlocks)
+            .into()
+    }
+}
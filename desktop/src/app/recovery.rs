@@ -0,0 +1,314 @@
+//! Автосохранение несохранённых вкладок и восстановление после сбоя.
+//!
+//! Раз в `autosave_secs` секунд текст изменённых вкладок и положения их
+//! блоков записываются в каталог восстановления ([`RecoveryStore`]), по
+//! файлу на вкладку. После сохранения или закрытия вкладки её снимок
+//! удаляется, поэтому при штатной работе каталог пуст. Если приложение
+//! завершилось аварийно, при следующем запуске оставшиеся снимки
+//! предлагается восстановить.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use iced::widget::text_editor::Content;
+use iced::Command;
+use serde::{Deserialize, Serialize};
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp, Screen, Tab};
+
+/// Интервал автосохранения по умолчанию, секунд.
+pub const DEFAULT_AUTOSAVE_SECS: u64 = 30;
+
+/// Положение блока на холсте.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockPosition {
+    pub id: String,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Снимок несохранённой вкладки.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecoverySnapshot {
+    pub path: PathBuf,
+    pub content: String,
+    #[serde(default)]
+    pub positions: Vec<BlockPosition>,
+    pub saved_at: DateTime<Utc>,
+}
+
+impl RecoverySnapshot {
+    pub fn of(tab: &Tab) -> Self {
+        Self {
+            path: tab.path.clone(),
+            content: tab.content.clone(),
+            positions: tab
+                .blocks
+                .iter()
+                .map(|b| BlockPosition {
+                    id: b.visual_id.clone(),
+                    x: b.x,
+                    y: b.y,
+                })
+                .collect(),
+            saved_at: Utc::now(),
+        }
+    }
+
+    /// Отпечаток текста и положений блоков, чтобы не перезаписывать
+    /// неизменившийся снимок.
+    fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.content.hash(&mut hasher);
+        for p in &self.positions {
+            p.id.hash(&mut hasher);
+            p.x.to_bits().hash(&mut hasher);
+            p.y.to_bits().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Применяет снимок к вкладке и помечает её изменённой.
+    fn apply(&self, tab: &mut Tab) {
+        tab.content = self.content.clone();
+        tab.editor = Content::with_text(&tab.content);
+        for block in &mut tab.blocks {
+            if let Some(p) = self.positions.iter().find(|p| p.id == block.visual_id) {
+                block.x = p.x;
+                block.y = p.y;
+            }
+        }
+        tab.dirty = true;
+    }
+}
+
+/// Каталог со снимками несохранённых вкладок.
+#[derive(Debug, Clone)]
+pub struct RecoveryStore {
+    dir: PathBuf,
+}
+
+impl RecoveryStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Каталог `recovery` в данных приложения.
+    pub fn open_default() -> Option<Self> {
+        ProjectDirs::from("com", "multicode", "multicode")
+            .map(|p| Self::new(p.data_dir().join("recovery")))
+    }
+
+    fn file_for(&self, path: &Path) -> PathBuf {
+        // FNV-1a: имя файла не должно зависеть от версии компилятора.
+        let hash = path
+            .to_string_lossy()
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+                (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+            });
+        self.dir.join(format!("{hash:016x}.json"))
+    }
+
+    /// Записывает снимок через временный файл, чтобы сбой во время записи
+    /// не испортил предыдущий снимок.
+    pub fn save(&self, snapshot: &RecoverySnapshot) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let file = self.file_for(&snapshot.path);
+        let tmp = file.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec(snapshot)?)?;
+        fs::rename(tmp, file)
+    }
+
+    pub fn remove(&self, path: &Path) -> io::Result<()> {
+        match fs::remove_file(self.file_for(path)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Снимки, текст которых отличается от файла на диске, по порядку
+    /// путей. Снимки, совпадающие с файлом, и повреждённые снимки удаляются.
+    pub fn pending(&self) -> Vec<RecoverySnapshot> {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut snapshots = Vec::new();
+        for entry in entries.flatten() {
+            let file = entry.path();
+            if file.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let snapshot = fs::read(&file)
+                .ok()
+                .and_then(|data| serde_json::from_slice::<RecoverySnapshot>(&data).ok());
+            match snapshot {
+                Some(s) if fs::read_to_string(&s.path).ok().as_ref() != Some(&s.content) => {
+                    snapshots.push(s)
+                }
+                _ => {
+                    let _ = fs::remove_file(&file);
+                }
+            }
+        }
+        snapshots.sort_by(|a, b| a.path.cmp(&b.path));
+        snapshots
+    }
+}
+
+impl MulticodeApp {
+    /// Записывает снимки изменённых вкладок и удаляет снимки вкладок,
+    /// которые с прошлого раза сохранены или закрыты.
+    pub(super) fn autosave(&mut self) {
+        let Some(store) = &self.recovery else {
+            return;
+        };
+        // Пока пользователь не ответил на предложение восстановления,
+        // снимки прошлого запуска не трогаем.
+        if !self.recovery_prompt.is_empty() {
+            return;
+        }
+        let mut written = HashMap::new();
+        let mut errors = Vec::new();
+        for tab in self.tabs.iter().filter(|t| t.dirty) {
+            let snapshot = RecoverySnapshot::of(tab);
+            let fingerprint = snapshot.fingerprint();
+            if self.recovery_written.get(&tab.path) != Some(&fingerprint) {
+                if let Err(e) = store.save(&snapshot) {
+                    errors.push(format!("{}: {e}", tab.path.display()));
+                    continue;
+                }
+            }
+            written.insert(tab.path.clone(), fingerprint);
+        }
+        for path in self.recovery_written.keys() {
+            if !written.contains_key(path) {
+                let _ = store.remove(path);
+            }
+        }
+        self.recovery_written = written;
+        for e in errors {
+            self.notify(LogEntry::new(
+                LogMessage::AutosaveError,
+                vec![e],
+                Utc::now(),
+            ));
+        }
+    }
+
+    /// Удаляет снимок вкладки `path` после её сохранения.
+    pub(super) fn forget_recovery(&mut self, path: &Path) {
+        self.recovery_written.remove(path);
+        if let Some(store) = &self.recovery {
+            let _ = store.remove(path);
+        }
+    }
+
+    /// Открывает вкладки из снимков прошлого запуска.
+    pub(super) fn restore_recovery(&mut self) -> Command<Message> {
+        let snapshots = std::mem::take(&mut self.recovery_prompt);
+        let mut cmds = Vec::new();
+        if let (Screen::ProjectPicker, Some(first)) = (&self.screen, snapshots.first()) {
+            let root = first
+                .path
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from("."));
+            self.screen = Screen::TextEditor { root: root.clone() };
+            cmds.push(self.load_files(root));
+        }
+        for snapshot in &snapshots {
+            if !self.tabs.iter().any(|t| t.path == snapshot.path) {
+                cmds.push(self.handle_message(Message::FileLoaded(Ok((
+                    snapshot.path.clone(),
+                    snapshot.content.clone(),
+                )))));
+            }
+            if let Some(tab) = self.tabs.iter_mut().find(|t| t.path == snapshot.path) {
+                snapshot.apply(tab);
+            }
+        }
+        self.log.push(LogEntry::new(
+            LogMessage::TabsRecovered,
+            vec![snapshots.len().to_string()],
+            Utc::now(),
+        ));
+        // Снимки остаются на диске до сохранения вкладок: следующий тик
+        // автосохранения перезапишет их текущим состоянием.
+        Command::batch(cmds)
+    }
+
+    /// Отказ от восстановления: снимки прошлого запуска удаляются.
+    pub(super) fn discard_recovery(&mut self) {
+        let snapshots = std::mem::take(&mut self.recovery_prompt);
+        if let Some(store) = &self.recovery {
+            for snapshot in snapshots {
+                let _ = store.remove(&snapshot.path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(path: &Path, content: &str) -> RecoverySnapshot {
+        RecoverySnapshot {
+            path: path.to_path_buf(),
+            content: content.into(),
+            positions: vec![BlockPosition {
+                id: "1".into(),
+                x: 10.0,
+                y: 20.0,
+            }],
+            saved_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn pending_skips_snapshots_matching_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let recovery = dir.path().join("recovery");
+        let store = RecoveryStore::new(&recovery);
+        let changed = dir.path().join("a.rs");
+        let saved = dir.path().join("b.rs");
+        std::fs::write(&changed, "fn a() {}").unwrap();
+        std::fs::write(&saved, "fn b() {}").unwrap();
+
+        store.save(&snapshot(&changed, "fn a() { 1 }")).unwrap();
+        store.save(&snapshot(&saved, "fn b() {}")).unwrap();
+        std::fs::write(recovery.join("broken.json"), "{").unwrap();
+
+        let pending = store.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].path, changed);
+        assert_eq!(pending[0].content, "fn a() { 1 }");
+        assert_eq!(pending[0].positions[0].x, 10.0);
+        // Совпадающий с диском и повреждённый снимки удалены.
+        assert_eq!(std::fs::read_dir(&recovery).unwrap().count(), 1);
+
+        store.remove(&changed).unwrap();
+        store.remove(&changed).unwrap();
+        assert!(store.pending().is_empty());
+    }
+
+    #[test]
+    fn snapshot_of_missing_file_is_pending() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = RecoveryStore::new(dir.path());
+        let path = dir.path().join("new.rs");
+        store.save(&snapshot(&path, "fn new() {}")).unwrap();
+        store.save(&snapshot(&path, "fn new() { 2 }")).unwrap();
+        let pending = store.pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].content, "fn new() { 2 }");
+    }
+}
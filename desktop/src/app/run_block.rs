@@ -0,0 +1,50 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use iced::Command;
+use multicode_core::meta::read_all;
+use multicode_core::runconfig::{self, RunConfig};
+
+use super::events::Message;
+use super::log_translations::LogMessage;
+use super::{LogEntry, MulticodeApp};
+use crate::terminal::pty;
+
+impl MulticodeApp {
+    /// Конфигурации запуска блока, выбранного на холсте, а если выбора
+    /// нет — блока, метаданные которого открыты на панели.
+    pub(super) fn focused_run_configs(&self) -> Vec<RunConfig> {
+        let Some(tab) = self.current_file() else {
+            return Vec::new();
+        };
+        let focused = self
+            .canvas_focus
+            .and_then(|i| tab.blocks.get(i))
+            .and_then(|block| {
+                read_all(&tab.content)
+                    .into_iter()
+                    .find(|m| m.id == block.visual_id)
+            });
+        focused
+            .or_else(|| tab.meta.clone())
+            .map(|meta| runconfig::resolve(&meta))
+            .unwrap_or_default()
+    }
+
+    /// Запускает в терминале конфигурацию `index` выбранного блока.
+    pub(super) fn run_block(&mut self, index: usize) -> Command<Message> {
+        let Some(config) = self.focused_run_configs().into_iter().nth(index) else {
+            return Command::none();
+        };
+        let root = self
+            .current_root_path()
+            .unwrap_or_else(|| PathBuf::from("."));
+        self.log.push(LogEntry::new(
+            LogMessage::Command,
+            vec![config.command.clone()],
+            Utc::now(),
+        ));
+        self.spawn_terminal_job(pty::from_std(&config.command(&root)));
+        Command::none()
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use iced::Command;
+use multicode_core::saved_search::{self, SavedSearch};
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+/// Результаты поиска в формате панели результатов: файл, строка, описание.
+async fn run_search(root: PathBuf, search: SavedSearch) -> Vec<(PathBuf, usize, String)> {
+    search
+        .run(&root)
+        .into_iter()
+        .map(|hit| {
+            let label = hit
+                .block
+                .translations
+                .get("ru")
+                .cloned()
+                .unwrap_or_else(|| hit.block.kind.clone());
+            (hit.file, hit.line, label)
+        })
+        .collect()
+}
+
+impl MulticodeApp {
+    /// Загружает сохранённые поиски проекта.
+    pub(super) fn load_saved_searches(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        match saved_search::load(&root) {
+            Ok(searches) => self.saved_searches = searches,
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::SavedSearchError,
+                vec![e.to_string()],
+                Utc::now(),
+            )),
+        }
+        Command::none()
+    }
+
+    /// Сохраняет текущую строку поиска под именем из поля ввода.
+    pub(super) fn save_current_search(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let search = SavedSearch::parse(&self.saved_search_name, &self.query);
+        if search.name.is_empty() || search.is_empty() {
+            return Command::none();
+        }
+        let name = search.name.clone();
+        match saved_search::save(&root, search) {
+            Ok(searches) => {
+                self.saved_searches = searches;
+                self.saved_search_name.clear();
+                self.log.push(LogEntry::new(
+                    LogMessage::SearchSaved,
+                    vec![name.clone()],
+                    Utc::now(),
+                ));
+                self.active_saved_search = Some(name);
+                self.rerun_saved_search()
+            }
+            Err(e) => {
+                self.log.push(LogEntry::new(
+                    LogMessage::SavedSearchError,
+                    vec![e.to_string()],
+                    Utc::now(),
+                ));
+                Command::none()
+            }
+        }
+    }
+
+    /// Выбирает сохранённый поиск, подставляет его запрос и выполняет его.
+    pub(super) fn select_saved_search(&mut self, name: String) -> Command<Message> {
+        if let Some(search) = self.saved_searches.iter().find(|s| s.name == name) {
+            self.query = search.query();
+            self.active_saved_search = Some(name);
+        }
+        self.rerun_saved_search()
+    }
+
+    /// Повторяет активный сохранённый поиск, чтобы результаты следовали за
+    /// изменениями файлов.
+    pub(super) fn rerun_saved_search(&self) -> Command<Message> {
+        let (Some(root), Some(name)) = (self.current_root_path(), &self.active_saved_search) else {
+            return Command::none();
+        };
+        let Some(search) = self
+            .saved_searches
+            .iter()
+            .find(|s| &s.name == name)
+            .cloned()
+        else {
+            return Command::none();
+        };
+        Command::perform(run_search(root, search), Message::ProjectSearchFinished)
+    }
+}
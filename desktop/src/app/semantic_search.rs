@@ -0,0 +1,82 @@
+//! Вопросы к коду проекта.
+//!
+//! Кнопка «Спросить код» рядом с поиском по проекту ищет блоки не по
+//! тексту, а по смыслу запроса ([`semantic`]): перед поиском векторы
+//! изменившихся блоков обновляются. Найденные блоки показываются среди
+//! результатов поиска по проекту. Без секции `[embeddings]` команда
+//! сообщает, что поиск не настроен.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::Utc;
+use iced::Command;
+use multicode_core::config::EmbeddingsConfig;
+use multicode_core::search::semantic::{self, HttpEmbedder};
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+/// Сколько блоков показывать в ответе.
+const ASK_RESULTS: usize = 20;
+
+/// Обновляет векторы блоков проекта `root` и ищет ближайшие к `query`.
+async fn ask(
+    root: PathBuf,
+    config: EmbeddingsConfig,
+    query: String,
+) -> Result<Vec<(PathBuf, usize, String)>, String> {
+    let embedder = Arc::new(HttpEmbedder::from_config(&config)?);
+    let pool = semantic::open(&root, &config)
+        .await
+        .map_err(|e| e.to_string())?;
+    semantic::index(&root, &pool, embedder.clone(), config.batch_size)
+        .await
+        .map_err(|e| e.to_string())?;
+    let hits = semantic::semantic(&pool, embedder, &query, ASK_RESULTS)
+        .await
+        .map_err(|e| e.to_string())?;
+    Ok(hits
+        .into_iter()
+        .map(|hit| (root.join(hit.file), hit.line, hit.label))
+        .collect())
+}
+
+impl MulticodeApp {
+    /// Ищет блоки проекта, подходящие к вопросу `query`.
+    pub(super) fn ask_codebase(&mut self, query: String) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        if query.trim().is_empty() {
+            return Command::none();
+        }
+        let config = EmbeddingsConfig::load(&root);
+        if config.endpoint.is_none() {
+            self.notify(LogEntry::new(
+                LogMessage::EmbeddingsNotConfigured,
+                Vec::new(),
+                Utc::now(),
+            ));
+            return Command::none();
+        }
+        self.active_saved_search = None;
+        self.project_search_results.clear();
+        Command::perform(ask(root, config, query), Message::AskCodebaseFinished)
+    }
+
+    /// Показывает найденные блоки среди результатов поиска.
+    pub(super) fn codebase_answered(
+        &mut self,
+        result: Result<Vec<(PathBuf, usize, String)>, String>,
+    ) {
+        match result {
+            Ok(results) => self.project_search_results = results,
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::SemanticSearchError,
+                vec![e],
+                Utc::now(),
+            )),
+        }
+    }
+}
@@ -7,27 +7,81 @@ use crate::search::{
 use crate::visual::connections::Connection;
 use chrono::{DateTime, Utc};
 use directories::ProjectDirs;
-use iced::{widget::text_editor, Color};
+use iced::{keyboard, widget::text_editor, window, Color};
 use lru::LruCache;
-use multicode_core::{git, meta::VisualMeta, BlockInfo};
+use multicode_core::{
+    ai::AiClient,
+    analysis::dead_code::DeadCodeReport,
+    breakpoints::Breakpoints,
+    build_tasks::{BuildTasks, Problem},
+    cochange::CoChangeReport,
+    codeowners::CodeOwners,
+    coverage::CoverageReport,
+    config::{EnrichConfig, IssueLinksConfig, ParseLimits},
+    debugger::StoppedLocation,
+    git::{self, BranchInfo, MetaHistoryEntry},
+    meta::clipboard::BlockClipboard,
+    meta::suggest::MetaSuggestion,
+    meta::symbols::WorkspaceSymbols,
+    meta::VisualMeta,
+    offline_queue::QueueStatus,
+    parser::{highlight::DocumentHighlights, Lang},
+    project_env::ProjectEnv,
+    recent_blocks::RecentBlocks,
+    saved_search::SavedSearch,
+    search::replace::FileReplacement,
+    search::structural::StructuralMatch,
+    tasks::Task,
+    testrun::TestRun,
+    BlockInfo,
+};
+use portable_pty::PtySize;
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::fmt;
 use std::hash::Hash;
 use std::ops::Range;
 use std::path::PathBuf;
-use tokio::{fs, process::Child, sync::broadcast};
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::{fs, sync::broadcast};
 
 use super::actions::{build_block_index, build_command_index};
-use super::command_palette::COMMANDS;
+use super::ai_assist::AiAnswer;
+use super::code_metrics::BlockMetricsCache;
+use super::command_palette::{CustomCommand, COMMANDS};
 use super::command_translations::command_name;
 use super::log_translations::LogMessage;
+use super::profile::ParseCosts;
 use crate::app::diff::DiffView;
-use crate::components::file_manager::ContextMenu;
+use crate::app::git_screen::GitScreen;
+use crate::app::git_status::GitDecorations;
+use crate::app::navigation::NavigationHistory;
+use crate::app::issue_links::IssueLinkStatus;
+use crate::app::logging::{LogBuffer, LogFilter, DEFAULT_LOG_LIMIT};
+use crate::app::macros::{Macro, MacroStep};
+use crate::app::meta_form::MetaForm;
+use crate::app::notifications::Notifications;
+use crate::app::recovery::{RecoverySnapshot, RecoveryStore, DEFAULT_AUTOSAVE_SECS};
+use crate::app::symbol_search::SymbolSearch;
+use crate::app::task_board::TaskGroup;
+use crate::components::file_manager::{ContextMenu, TreeSearch, TreeViewport};
+use crate::editor::folding::FoldedView;
+use crate::editor::input_mode::Vim;
+use crate::editor::multi_cursor::MultiCursor;
+use crate::editor::panes::PaneLayout;
 use crate::editor::{AutocompleteState, EditorSettings};
 use crate::sync::{ChangeTracker, SyncEngine, SyncSettings};
+use crate::terminal::PtySession;
+use crate::visual::accessibility::AccessibilitySettings;
+use crate::visual::grid::GridSettings;
+use crate::visual::lanes::LaneGrouping;
+use crate::visual::layers::CanvasLayers;
+use crate::visual::lod::LodSettings;
 use crate::visual::palette::PaletteBlock;
+use crate::visual::profile::{ProfileMetric, ProfileSettings};
 use crate::visual::translations::Language;
 
 mod serde_color {
@@ -52,6 +106,7 @@ mod serde_color {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum LogLevel {
+    Debug,
     Info,
     Warning,
     Error,
@@ -60,6 +115,7 @@ pub enum LogLevel {
 impl fmt::Display for LogLevel {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
+            LogLevel::Debug => write!(f, "Debug"),
             LogLevel::Info => write!(f, "Info"),
             LogLevel::Warning => write!(f, "Warning"),
             LogLevel::Error => write!(f, "Error"),
@@ -68,7 +124,12 @@ impl fmt::Display for LogLevel {
 }
 
 impl LogLevel {
-    pub const ALL: [LogLevel; 3] = [LogLevel::Info, LogLevel::Warning, LogLevel::Error];
+    pub const ALL: [LogLevel; 4] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warning,
+        LogLevel::Error,
+    ];
 }
 
 #[derive(Debug, Clone)]
@@ -77,6 +138,8 @@ pub struct LogEntry {
     pub message_key: LogMessage,
     pub args: Vec<String>,
     pub timestamp: DateTime<Utc>,
+    /// Модуль-источник записи, например `core::meta`, для событий `tracing`.
+    pub module: Option<String>,
 }
 
 impl LogEntry {
@@ -87,6 +150,7 @@ impl LogEntry {
             message_key,
             args,
             timestamp,
+            module: None,
         }
     }
 
@@ -96,8 +160,19 @@ impl LogEntry {
             message_key: LogMessage::Raw,
             args: vec![message],
             timestamp,
+            module: None,
         }
     }
+
+    pub fn with_level(mut self, level: LogLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    pub fn with_module(mut self, module: impl Into<String>) -> Self {
+        self.module = Some(module.into());
+        self
+    }
 }
 
 fn cached_search<T, F>(
@@ -156,13 +231,19 @@ pub struct MulticodeApp {
     pub(super) rename_file_name: String,
     /// фильтр файлового менеджера
     pub(super) search_query: String,
+    /// результаты фильтра по всему проекту
+    pub(super) file_search: Option<TreeSearch>,
+    /// номер текущего обхода для фильтра; устаревшие обходы прерываются
+    pub(super) file_search_generation: Arc<AtomicU64>,
+    /// прокрутка дерева файлов
+    pub(super) file_tree_viewport: TreeViewport,
     /// избранные файлы и директории
     pub(super) favorites: Vec<PathBuf>,
     pub(super) query: String,
     pub(super) show_command_palette: bool,
-    pub(super) log: Vec<LogEntry>,
-    /// минимальный уровень отображаемых записей журнала
-    pub(super) min_log_level: LogLevel,
+    pub(super) log: LogBuffer,
+    /// фильтр панели журнала: уровень, модуль и строка поиска
+    pub(super) log_filter: LogFilter,
     /// результаты поиска по проекту
     pub(super) project_search_results: Vec<(PathBuf, usize, String)>,
     /// строка для перехода после открытия файла
@@ -173,7 +254,11 @@ pub struct MulticodeApp {
     pub(super) show_goto_line_modal: bool,
     pub(super) show_terminal: bool,
     pub(super) terminal_cmd: String,
-    pub(super) terminal_child: Option<Child>,
+    /// Оболочка проекта во встроенном терминале.
+    pub(super) terminal: Option<PtySession>,
+    /// Процесс, запущенный кнопкой запуска блока.
+    pub(super) terminal_job: Option<PtySession>,
+    pub(super) terminal_size: PtySize,
     pub(super) show_terminal_help: bool,
     pub(super) sender: broadcast::Sender<String>,
     pub(super) settings: UserSettings,
@@ -190,11 +275,13 @@ pub struct MulticodeApp {
     pub(super) settings_warning: Option<String>,
     pub(super) loading: bool,
     pub(super) diff_error: Option<String>,
-    pub(super) show_meta_dialog: bool,
-    pub(super) meta_tags: String,
-    pub(super) meta_links: String,
-    pub(super) meta_comment: String,
+    /// открытая форма метаданных
+    pub(super) meta_form: Option<MetaForm>,
     pub(super) autocomplete: Option<AutocompleteState>,
+    /// Строка, для которой открыт список диагностик с быстрыми исправлениями.
+    pub(super) diagnostic_popup: Option<usize>,
+    /// Замечание панели проверки, выбранное щелчком по метке блока на холсте.
+    pub(super) focused_diagnostic: Option<usize>,
     pub(super) show_meta_panel: bool,
     pub(super) tab_drag: Option<TabDragState>,
     pub(super) palette: Vec<PaletteBlock>,
@@ -215,6 +302,176 @@ pub struct MulticodeApp {
     pub(super) block_index: Option<SearchIndex<usize>>,
     pub(super) command_cache: RefCell<LruCache<String, Vec<&'static str>>>,
     pub(super) block_cache: RefCell<LruCache<String, Vec<usize>>>,
+    /// история переходов по файлам и блокам
+    pub(super) navigation: NavigationHistory,
+    /// отображать панель закладок
+    pub(super) show_bookmarks_panel: bool,
+    /// внутренний буфер обмена блоков
+    pub(super) block_clipboard: Option<BlockClipboard>,
+    /// редактируемая метка блока холста: индекс блока и текст
+    pub(super) label_edit: Option<(usize, String)>,
+    /// блок холста, выбранный мышью или клавиатурой
+    pub(super) canvas_focus: Option<usize>,
+    /// время изменения загруженного файла темы
+    pub(super) theme_file_mtime: Option<SystemTime>,
+    /// отображать панель тегов рабочего пространства
+    pub(super) show_tags_panel: bool,
+    /// теги рабочего пространства с числом использований
+    pub(super) workspace_tags: Vec<(String, usize)>,
+    /// переименуемые теги через запятую
+    pub(super) tag_from: String,
+    /// новое имя тега
+    pub(super) tag_into: String,
+    /// новый идентификатор записи метаданных текущего файла
+    pub(super) meta_new_id: String,
+    /// сохранённые поиски проекта
+    pub(super) saved_searches: Vec<SavedSearch>,
+    /// сохранённый поиск, результаты которого обновляются при изменениях
+    pub(super) active_saved_search: Option<String>,
+    /// имя для сохранения текущего поиска
+    pub(super) saved_search_name: String,
+    /// показывать панель истории блока
+    pub(super) show_block_history: bool,
+    /// ключи свёрнутых групп блоков на холсте
+    pub(super) collapsed_lanes: HashSet<String>,
+    /// показывать строку фильтра над холстом
+    pub(super) show_canvas_filter: bool,
+    /// строка фильтра блоков холста
+    pub(super) canvas_filter: String,
+    /// скрывать неподходящие фильтру блоки вместо затенения
+    pub(super) canvas_filter_hide: bool,
+    /// совпадение фильтра, к которому перешёл пользователь
+    pub(super) canvas_filter_current: Option<usize>,
+    /// показывать панель наследования метаданных
+    pub(super) show_inheritance_panel: bool,
+    /// несохранённая база `extends`: идентификатор записи и введённое значение
+    pub(super) extends_draft: Option<(String, String)>,
+    /// история метаданных блока из git
+    pub(super) block_history: Vec<MetaHistoryEntry>,
+    /// идентификатор блока, история которого показана
+    pub(super) block_history_id: Option<String>,
+    /// показывать панель совместных изменений
+    pub(super) show_cochange_panel: bool,
+    /// последний отчёт о совместных изменениях блоков
+    pub(super) cochange: Option<CoChangeReport>,
+    /// показывать панель предложений тегов и связей
+    pub(super) show_meta_suggestions: bool,
+    /// предложения тегов и связей для блоков без метаданных
+    pub(super) meta_suggestions: Vec<MetaSuggestion>,
+    /// ключи отклонённых предложений, см. [`MetaSuggestion::key`]
+    pub(super) rejected_suggestions: HashSet<String>,
+    /// показывать панель структурного поиска
+    pub(super) show_structural_search: bool,
+    /// шаблон структурного поиска, например `foo($A, $$$REST)`
+    pub(super) structural_pattern: String,
+    /// язык шаблона структурного поиска
+    pub(super) structural_lang: Lang,
+    /// результаты последнего структурного поиска
+    pub(super) structural_results: Vec<StructuralMatch>,
+    /// подстановка для замены по шаблону, например `bar($A)`
+    pub(super) structural_template: String,
+    /// предпросмотр замены по файлам
+    pub(super) structural_preview: Vec<FileReplacement>,
+    /// резервная копия последней замены, которой её можно отменить
+    pub(super) structural_backup: Option<String>,
+    /// раскрашивать блоки холста по покрытию тестами
+    pub(super) show_coverage: bool,
+    /// отчёт о покрытии проекта
+    pub(super) coverage: Option<CoverageReport>,
+    /// мера профиля холста; `None`, если профиль выключен
+    pub(super) profile_metric: Option<ProfileMetric>,
+    /// кэш времени разбора блоков для профиля холста
+    pub(super) parse_costs: RefCell<ParseCosts>,
+    /// кэш метрик блоков для панели метаданных
+    pub(super) block_metrics: RefCell<BlockMetricsCache>,
+    /// место, где остановился отладчик
+    pub(super) debug_stop: Option<StoppedLocation>,
+    /// точки останова проекта
+    pub(super) breakpoints: Breakpoints,
+    /// показывать панель тестов
+    pub(super) show_test_panel: bool,
+    /// итоги последнего запуска тестов
+    pub(super) test_run: Option<TestRun>,
+    /// тесты выполняются
+    pub(super) tests_running: bool,
+    /// задачи сборки проекта
+    pub(super) build_tasks: BuildTasks,
+    /// ошибки последнего запуска задачи сборки
+    pub(super) build_problems: Vec<Problem>,
+    /// итог последнего поиска мёртвого кода
+    pub(super) dead_code: Option<DeadCodeReport>,
+    /// показывать редактор окружения проекта
+    pub(super) show_env_editor: bool,
+    /// переменные окружения проекта в редакторе
+    pub(super) project_env: ProjectEnv,
+    /// Состояние очереди операций, ожидающих связи с сервером.
+    pub(super) offline_queue: QueueStatus,
+    pub(super) queue_replaying: bool,
+    /// Клиент модели из секции `[ai]` проекта.
+    pub(super) ai_client: Option<Arc<AiClient>>,
+    pub(super) ai_answer: Option<AiAnswer>,
+    /// задачи проекта для доски задач
+    pub(super) tasks: Vec<Task>,
+    /// группировка задач на доске
+    pub(super) task_group: TaskGroup,
+    /// шаблоны адресов для ссылок на задачи
+    pub(super) issue_links: IssueLinksConfig,
+    /// кэш статусов ссылок на задачи; `None` — ссылка на блок
+    pub(super) issue_link_cache: RefCell<HashMap<String, Option<IssueLinkStatus>>>,
+    /// теги, идентификаторы и языки метаданных проекта для автодополнения
+    pub(super) workspace_symbols: WorkspaceSymbols,
+    /// каталог снимков несохранённых вкладок; `None` — автосохранение выключено
+    pub(super) recovery: Option<RecoveryStore>,
+    /// отпечатки снимков, записанных в этом запуске
+    pub(super) recovery_written: HashMap<PathBuf, u64>,
+    /// снимки прошлого запуска, которые предлагается восстановить
+    pub(super) recovery_prompt: Vec<RecoverySnapshot>,
+    /// состояние файлов проекта в git
+    pub(super) git_status: GitDecorations,
+    /// путь, отмену изменений которого нужно подтвердить
+    pub(super) git_discard_confirm: Option<PathBuf>,
+    /// изменения и коммит на экране git
+    pub(super) git_screen: GitScreen,
+    /// ветки и отложенные изменения проекта
+    pub(super) git_branches: BranchInfo,
+    /// имя новой ветки в главном меню
+    pub(super) new_branch_name: String,
+    /// владельцы файлов проекта из CODEOWNERS
+    pub(super) code_owners: CodeOwners,
+    /// показывать панель уведомлений
+    pub(super) show_notifications: bool,
+    /// незакрытые уведомления о фоновых событиях
+    pub(super) notifications: Notifications,
+    /// показывать панель макросов
+    pub(super) show_macros_panel: bool,
+    /// шаги записываемого макроса; `None`, если запись не идёт
+    pub(super) macro_recording: Option<Vec<MacroStep>>,
+    /// шаги последней записи, ещё не сохранённые под именем
+    pub(super) recorded_macro: Vec<MacroStep>,
+    /// имя для сохранения записанного макроса
+    pub(super) macro_name: String,
+    /// состояние модального ввода Vim
+    pub(super) vim: Vim,
+    /// последние нажатые модификаторы
+    pub(super) modifiers: keyboard::Modifiers,
+    /// дополнительные каретки текущей вкладки
+    pub(super) carets: MultiCursor,
+    /// угол прямоугольного выделения: строка и колонка
+    pub(super) column_anchor: Option<(usize, usize)>,
+    /// свёрнутые блоки каждого файла за время сеанса
+    pub(super) folds: HashMap<PathBuf, HashSet<String>>,
+    /// прокрутка редактора кода в пикселях
+    pub(super) editor_scroll: f32,
+    /// отдельное окно холста, если схема вынесена из главного окна
+    pub(super) canvas_window: Option<window::Id>,
+    /// окно «Перейти к символу», если открыто
+    pub(super) symbol_search: Option<SymbolSearch>,
+    /// блок символа, который нужно выделить после открытия его файла
+    pub(super) pending_block_focus: Option<(PathBuf, String)>,
+    /// недавно изменённые блоки проекта
+    pub(super) recent_blocks: RecentBlocks,
+    /// строка поиска окна недавних блоков, если оно открыто
+    pub(super) recent_blocks_query: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -225,6 +482,8 @@ pub enum Screen {
     Split { root: PathBuf },
     Diff(DiffView),
     Settings,
+    TaskBoard { root: PathBuf },
+    Git { root: PathBuf },
 }
 
 #[derive(Debug, Clone)]
@@ -245,6 +504,8 @@ pub struct FileEntry {
     pub ty: EntryType,
     pub has_meta: bool,
     pub children: Vec<FileEntry>,
+    /// Содержимое каталога прочитано. Каталоги читаются при раскрытии.
+    pub loaded: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -252,6 +513,49 @@ pub struct Diagnostic {
     pub line: usize,
     pub range: Range<usize>,
     pub message: String,
+    /// Быстрые исправления, предложенные правилом проверки.
+    pub fixes: Vec<QuickFix>,
+    /// `visual_id` блока замечания; `None` — блок ищется по положению.
+    pub block: Option<String>,
+}
+
+/// Замена диапазона `range` (в байтах) строки `line` на `replacement`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub line: usize,
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// Быстрое исправление: набор непересекающихся правок текста.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickFix {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+impl QuickFix {
+    /// Применяет правки к `content`. Правки за пределами текста пропускаются.
+    pub fn apply(&self, content: &str) -> String {
+        let mut starts = vec![0];
+        starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+        let mut edits: Vec<(Range<usize>, &str)> = self
+            .edits
+            .iter()
+            .filter_map(|e| {
+                let start = starts.get(e.line)? + e.range.start;
+                let end = starts.get(e.line)? + e.range.end;
+                content.get(start..end)?;
+                Some((start..end, e.replacement.as_str()))
+            })
+            .collect();
+        edits.sort_by_key(|(r, _)| std::cmp::Reverse(r.start));
+        let mut out = content.to_string();
+        for (range, replacement) in edits {
+            out.replace_range(range, replacement);
+        }
+        out
+    }
 }
 
 #[derive(Debug)]
@@ -263,11 +567,19 @@ pub struct Tab {
     pub blame: HashMap<usize, git::BlameLine>,
     pub diagnostics: Vec<Diagnostic>,
     pub blocks: Vec<BlockInfo>,
+    /// Файл слишком велик, блоки построены в упрощённом виде.
+    pub simplified: bool,
     pub connections: Vec<Connection>,
     pub meta: Option<VisualMeta>,
     pub undo_stack: VecDeque<String>,
     pub redo_stack: VecDeque<String>,
     pub analysis_version: u64,
+    /// Представление со свёрнутыми блоками, см. [`FoldedView`].
+    pub folded: Option<FoldedView>,
+    /// Подсветка tree-sitter; `None`, если язык файла не поддерживается.
+    pub highlights: Option<DocumentHighlights>,
+    /// Язык разбора: выбранный вручную или определённый по расширению.
+    pub lang: Option<Lang>,
 }
 
 #[derive(Debug)]
@@ -351,6 +663,14 @@ fn default_true() -> bool {
     true
 }
 
+fn default_autosave_secs() -> u64 {
+    DEFAULT_AUTOSAVE_SECS
+}
+
+fn default_log_limit() -> usize {
+    DEFAULT_LOG_LIMIT
+}
+
 fn default_view_mode() -> ViewMode {
     ViewMode::Code
 }
@@ -405,6 +725,42 @@ pub struct UserSettings {
     pub recent_commands: Vec<String>,
     #[serde(default)]
     pub sync: SyncSettings,
+    /// пороги масштаба для уровней детализации холста
+    #[serde(default)]
+    pub canvas_lod: LodSettings,
+    /// сетка холста и привязка блоков при перетаскивании
+    #[serde(default)]
+    pub canvas_grid: GridSettings,
+    /// признак, по которому блоки холста собираются в группы
+    #[serde(default)]
+    pub canvas_lanes: LaneGrouping,
+    /// видимые слои холста по корневым каталогам проектов
+    #[serde(default)]
+    pub canvas_layers: BTreeMap<PathBuf, CanvasLayers>,
+    /// пороги раскраски профиля холста
+    #[serde(default)]
+    pub canvas_profile: ProfileSettings,
+    /// режим доступности, контрастная палитра и минимальный шрифт
+    #[serde(default)]
+    pub accessibility: AccessibilitySettings,
+    /// сколько последних записей хранит журнал
+    #[serde(default = "default_log_limit")]
+    pub log_limit: usize,
+    /// интервал автосохранения несохранённых вкладок, секунд; 0 — выключено
+    #[serde(default = "default_autosave_secs")]
+    pub autosave_secs: u64,
+    /// пользовательские команды палитры
+    #[serde(default)]
+    pub custom_commands: Vec<CustomCommand>,
+    /// записанные макросы
+    #[serde(default)]
+    pub macros: Vec<Macro>,
+    /// раскладка панелей редактора; `None` — редактор не разделён
+    #[serde(default)]
+    pub panes: Option<PaneLayout>,
+    /// языки, выбранные вручную для отдельных файлов
+    #[serde(default)]
+    pub file_languages: BTreeMap<PathBuf, Lang>,
 }
 
 impl Default for UserSettings {
@@ -430,6 +786,18 @@ impl Default for UserSettings {
             block_favorites: Vec::new(),
             recent_commands: Vec::new(),
             sync: SyncSettings::default(),
+            canvas_lod: LodSettings::default(),
+            canvas_grid: GridSettings::default(),
+            canvas_lanes: LaneGrouping::default(),
+            canvas_layers: BTreeMap::new(),
+            canvas_profile: ProfileSettings::default(),
+            accessibility: AccessibilitySettings::default(),
+            log_limit: DEFAULT_LOG_LIMIT,
+            autosave_secs: DEFAULT_AUTOSAVE_SECS,
+            custom_commands: Vec::new(),
+            macros: Vec::new(),
+            panes: None,
+            file_languages: BTreeMap::new(),
         }
     }
 }
@@ -479,6 +847,14 @@ impl MulticodeApp {
         self.view_mode
     }
 
+    pub fn active_tab_index(&self) -> Option<usize> {
+        self.active_tab
+    }
+
+    pub fn tab(&self, index: usize) -> Option<&Tab> {
+        self.tabs.get(index)
+    }
+
     pub fn current_file(&self) -> Option<&Tab> {
         self.active_tab.and_then(|i| self.tabs.get(i))
     }
@@ -509,11 +885,27 @@ impl MulticodeApp {
     }
 
     /// Возвращает путь к корню проекта, если он выбран
+    /// Пороги упрощённого разбора из `multicode.toml` текущего проекта.
+    pub fn parse_limits(&self) -> ParseLimits {
+        self.current_root_path()
+            .map(|root| ParseLimits::load(&root))
+            .unwrap_or_default()
+    }
+
+    /// Этапы обогащения блоков из `multicode.toml` текущего проекта.
+    pub fn enrich_config(&self) -> EnrichConfig {
+        self.current_root_path()
+            .map(|root| EnrichConfig::load(&root))
+            .unwrap_or_default()
+    }
+
     pub fn current_root_path(&self) -> Option<PathBuf> {
         match &self.screen {
             Screen::TextEditor { root }
             | Screen::VisualEditor { root }
-            | Screen::Split { root } => Some(root.clone()),
+            | Screen::Split { root }
+            | Screen::TaskBoard { root }
+            | Screen::Git { root } => Some(root.clone()),
             Screen::Diff(_) => self.settings.last_folders.first().cloned(),
             Screen::ProjectPicker => None,
             Screen::Settings => self.settings.last_folders.first().cloned(),
@@ -541,10 +933,22 @@ impl MulticodeApp {
         &self.search_results
     }
 
+    pub fn editor_scroll(&self) -> f32 {
+        self.editor_scroll
+    }
+
+    pub fn workspace_symbols(&self) -> &WorkspaceSymbols {
+        &self.workspace_symbols
+    }
+
     pub fn autocomplete(&self) -> Option<&AutocompleteState> {
         self.autocomplete.as_ref()
     }
 
+    pub fn diagnostic_popup(&self) -> Option<usize> {
+        self.diagnostic_popup
+    }
+
     pub fn settings(&self) -> &UserSettings {
         &self.settings
     }
@@ -651,18 +1055,23 @@ mod tests {
             create_target: CreateTarget::File,
             rename_file_name: String::new(),
             search_query: String::new(),
+            file_search: None,
+            file_search_generation: Default::default(),
+            file_tree_viewport: Default::default(),
             favorites: Vec::new(),
             query: String::new(),
             show_command_palette: false,
-            log: Vec::new(),
-            min_log_level: LogLevel::Info,
+            log: crate::app::logging::LogBuffer::default(),
+            log_filter: crate::app::logging::LogFilter::default(),
             project_search_results: Vec::new(),
             goto_line: None,
             goto_line_input: String::new(),
             show_goto_line_modal: false,
             show_terminal: false,
             terminal_cmd: String::new(),
-            terminal_child: None,
+            terminal: None,
+            terminal_job: None,
+            terminal_size: crate::terminal::pty::initial_size(),
             show_terminal_help: false,
             sender,
             settings: UserSettings::default(),
@@ -676,10 +1085,7 @@ mod tests {
             settings_warning: None,
             loading: false,
             diff_error: None,
-            show_meta_dialog: false,
-            meta_tags: String::new(),
-            meta_links: String::new(),
-            meta_comment: String::new(),
+            meta_form: None,
             autocomplete: None,
             show_meta_panel: false,
             tab_drag: None,
@@ -697,6 +1103,94 @@ mod tests {
             block_index: None,
             command_cache: RefCell::new(LruCache::new(NonZeroUsize::new(1).unwrap())),
             block_cache: RefCell::new(LruCache::new(NonZeroUsize::new(1).unwrap())),
+            navigation: NavigationHistory::default(),
+            show_bookmarks_panel: false,
+            block_clipboard: None,
+            label_edit: None,
+            canvas_focus: None,
+            theme_file_mtime: None,
+            show_tags_panel: false,
+            workspace_tags: Vec::new(),
+            tag_from: String::new(),
+            tag_into: String::new(),
+            meta_new_id: String::new(),
+            saved_searches: Vec::new(),
+            active_saved_search: None,
+            saved_search_name: String::new(),
+            show_block_history: false,
+            collapsed_lanes: HashSet::new(),
+            show_canvas_filter: false,
+            canvas_filter: String::new(),
+            canvas_filter_hide: false,
+            canvas_filter_current: None,
+            show_inheritance_panel: false,
+            extends_draft: None,
+            block_history: Vec::new(),
+            block_history_id: None,
+            show_cochange_panel: false,
+            cochange: None,
+            show_meta_suggestions: false,
+            meta_suggestions: Vec::new(),
+            rejected_suggestions: HashSet::new(),
+            show_structural_search: false,
+            structural_pattern: String::new(),
+            structural_lang: Lang::Rust,
+            structural_results: Vec::new(),
+            structural_template: String::new(),
+            structural_preview: Vec::new(),
+            structural_backup: None,
+            block_metrics: Default::default(),
+            show_coverage: false,
+            coverage: None,
+            profile_metric: None,
+            parse_costs: Default::default(),
+            debug_stop: None,
+            breakpoints: Default::default(),
+            show_test_panel: false,
+            test_run: None,
+            tests_running: false,
+            build_tasks: Default::default(),
+            build_problems: Vec::new(),
+            dead_code: None,
+            show_env_editor: false,
+            project_env: Default::default(),
+            offline_queue: Default::default(),
+            queue_replaying: false,
+            ai_client: None,
+            ai_answer: None,
+            tasks: Vec::new(),
+            task_group: TaskGroup::default(),
+            issue_links: Default::default(),
+            issue_link_cache: RefCell::new(HashMap::new()),
+            diagnostic_popup: None,
+            focused_diagnostic: None,
+            workspace_symbols: WorkspaceSymbols::default(),
+            recovery: None,
+            recovery_written: HashMap::new(),
+            recovery_prompt: Vec::new(),
+            git_status: Default::default(),
+            git_discard_confirm: None,
+            git_screen: Default::default(),
+            git_branches: Default::default(),
+            new_branch_name: String::new(),
+            code_owners: Default::default(),
+            show_notifications: false,
+            notifications: Default::default(),
+            show_macros_panel: false,
+            macro_recording: None,
+            recorded_macro: Vec::new(),
+            macro_name: String::new(),
+            vim: Vim::default(),
+            modifiers: Default::default(),
+            carets: Default::default(),
+            column_anchor: None,
+            folds: HashMap::new(),
+            editor_scroll: 0.0,
+            canvas_window: None,
+            symbol_search: None,
+            pending_block_focus: None,
+            recent_blocks: Default::default(),
+            recent_blocks_query: None,
         }
     }
 
@@ -0,0 +1,326 @@
+//! Структурный поиск.
+//!
+//! Панель «Структурный поиск» ищет в файлах проекта фрагменты кода по
+//! шаблону с метапеременными ([`structural`]), например `foo($A, $$$REST)`.
+//! Язык шаблона по умолчанию берётся из активной вкладки. У каждого
+//! результата показываются значения метапеременных; щелчок открывает
+//! фрагмент в редакторе.
+//!
+//! В режиме замены найденные фрагменты заменяются подстановкой
+//! ([`replace`]): сначала показывается разница по файлам, затем замена
+//! записывается с резервной копией, и её можно отменить кнопкой
+//! «Отменить замену».
+
+use chrono::Utc;
+use iced::widget::{button, column, pick_list, row, scrollable, text, text_input, Space};
+use iced::{Command, Element, Length};
+use multicode_core::meta::backup;
+use multicode_core::parser::Lang;
+use multicode_core::search::replace::{self, FileReplacement};
+use multicode_core::search::structural::{structural, StructuralMatch};
+use tokio::task;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+/// Языки, файлы которых просматривает структурный поиск.
+const LANGS: [Lang; 5] = [
+    Lang::Rust,
+    Lang::Python,
+    Lang::JavaScript,
+    Lang::Css,
+    Lang::Html,
+];
+
+/// Значения метапеременных в одну строку: `$A = x, $B = y`.
+fn bindings_label(found: &StructuralMatch) -> String {
+    found
+        .bindings
+        .iter()
+        .map(|(name, value)| {
+            let value = value.split_whitespace().collect::<Vec<_>>().join(" ");
+            format!("${name} = {value}")
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl MulticodeApp {
+    /// Показывает или скрывает панель; при открытии берёт язык активной
+    /// вкладки, если поиск его поддерживает.
+    pub(super) fn toggle_structural_search(&mut self) {
+        self.show_structural_search = !self.show_structural_search;
+        if !self.show_structural_search {
+            return;
+        }
+        let lang = self
+            .active_tab
+            .and_then(|i| self.tabs.get(i))
+            .and_then(|tab| tab.lang);
+        if let Some(lang) = lang.filter(|l| LANGS.contains(l)) {
+            self.structural_lang = lang;
+        }
+    }
+
+    /// Ищет шаблон из панели в файлах проекта.
+    pub(super) fn run_structural_search(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let pattern = self.structural_pattern.clone();
+        if pattern.trim().is_empty() {
+            return Command::none();
+        }
+        let lang = self.structural_lang.to_string();
+        self.structural_results.clear();
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    structural(&root, &pattern, &lang).map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            },
+            Message::StructuralSearchFinished,
+        )
+    }
+
+    /// Показывает результаты поиска или пишет ошибку шаблона в журнал.
+    pub(super) fn structural_search_finished(
+        &mut self,
+        result: Result<Vec<StructuralMatch>, String>,
+    ) {
+        match result {
+            Ok(found) => self.structural_results = found,
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::StructuralError,
+                vec![e],
+                Utc::now(),
+            )),
+        }
+    }
+
+    /// Строит замену по шаблону и подстановке из панели, не записывая её.
+    pub(super) fn preview_structural_replace(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let pattern = self.structural_pattern.clone();
+        if pattern.trim().is_empty() {
+            return Command::none();
+        }
+        let template = self.structural_template.clone();
+        let lang = self.structural_lang.to_string();
+        self.structural_preview.clear();
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    replace::preview(&root, &pattern, &template, &lang).map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            },
+            Message::StructuralReplacePreviewed,
+        )
+    }
+
+    pub(super) fn structural_replace_previewed(
+        &mut self,
+        result: Result<Vec<FileReplacement>, String>,
+    ) {
+        match result {
+            Ok(preview) => self.structural_preview = preview,
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::StructuralError,
+                vec![e],
+                Utc::now(),
+            )),
+        }
+    }
+
+    /// Записывает показанную замену.
+    pub(super) fn apply_structural_replace(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let preview = std::mem::take(&mut self.structural_preview);
+        let edits: usize = preview.iter().map(|r| r.edits.len()).sum();
+        if edits == 0 {
+            return Command::none();
+        }
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    replace::apply(&root, &preview)
+                        .map(|id| (id, edits))
+                        .map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            },
+            Message::StructuralReplaceApplied,
+        )
+    }
+
+    /// Запоминает резервную копию и перечитывает изменённые вкладки.
+    pub(super) fn structural_replace_applied(
+        &mut self,
+        result: Result<(String, usize), String>,
+    ) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        match result {
+            Ok((id, edits)) => {
+                self.log.push(LogEntry::new(
+                    LogMessage::StructuralReplaced,
+                    vec![edits.to_string()],
+                    Utc::now(),
+                ));
+                self.structural_backup = Some(id);
+                self.structural_results.clear();
+                self.reload_clean_tabs(&root)
+            }
+            Err(e) => {
+                self.log.push(LogEntry::new(
+                    LogMessage::StructuralError,
+                    vec![e],
+                    Utc::now(),
+                ));
+                Command::none()
+            }
+        }
+    }
+
+    /// Возвращает файлы из резервной копии последней замены.
+    pub(super) fn undo_structural_replace(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let Some(id) = self.structural_backup.take() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    backup::restore(&root, &id, None)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            },
+            Message::StructuralReplaceUndone,
+        )
+    }
+
+    pub(super) fn structural_replace_undone(
+        &mut self,
+        result: Result<(), String>,
+    ) -> Command<Message> {
+        let (message, args) = match result {
+            Ok(()) => (LogMessage::StructuralReplaceUndone, Vec::new()),
+            Err(e) => (LogMessage::StructuralError, vec![e]),
+        };
+        self.log.push(LogEntry::new(message, args, Utc::now()));
+        match self.current_root_path() {
+            Some(root) => self.reload_clean_tabs(&root),
+            None => Command::none(),
+        }
+    }
+
+    /// Разница по файлам для предпросмотра замены.
+    fn structural_preview_items(&self) -> Vec<Element<'_, Message>> {
+        let root = self.current_root_path();
+        self.structural_preview
+            .iter()
+            .map(|r| {
+                let path = root
+                    .as_deref()
+                    .and_then(|root| r.file.strip_prefix(root).ok())
+                    .unwrap_or(&r.file);
+                let mut title = format!("{}: замен {}", path.display(), r.edits.len());
+                if !r.rejected.is_empty() {
+                    title.push_str(&format!(", не разбираются {}", r.rejected.len()));
+                }
+                column![text(title), text(r.diff())].spacing(2).into()
+            })
+            .collect()
+    }
+
+    pub fn structural_search_component(&self) -> Element<'_, Message> {
+        if !self.show_structural_search {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let header = row![
+            text_input(
+                "Шаблон, например foo($A, $$$REST)",
+                &self.structural_pattern
+            )
+            .on_input(Message::StructuralPatternChanged)
+            .on_submit(Message::RunStructuralSearch)
+            .width(Length::Fill),
+            pick_list(
+                &LANGS[..],
+                Some(self.structural_lang),
+                Message::StructuralLangChanged
+            ),
+            button("Найти").on_press(Message::RunStructuralSearch),
+            button("×").on_press(Message::ToggleStructuralSearch),
+        ]
+        .spacing(5);
+        let mut replace_row = row![
+            text_input("Замена, например bar($A)", &self.structural_template)
+                .on_input(Message::StructuralTemplateChanged)
+                .on_submit(Message::PreviewStructuralReplace)
+                .width(Length::Fill),
+            button("Предпросмотр").on_press(Message::PreviewStructuralReplace),
+        ]
+        .spacing(5);
+        if self.structural_preview.iter().any(|r| !r.edits.is_empty()) {
+            replace_row =
+                replace_row.push(button("Заменить").on_press(Message::ApplyStructuralReplace));
+        }
+        if self.structural_backup.is_some() {
+            replace_row = replace_row
+                .push(button("Отменить замену").on_press(Message::UndoStructuralReplace));
+        }
+        if !self.structural_preview.is_empty() {
+            return column![
+                header,
+                replace_row,
+                scrollable(column(self.structural_preview_items()).spacing(5))
+                    .height(Length::Fixed(160.0))
+            ]
+            .spacing(5)
+            .into();
+        }
+        let root = self.current_root_path();
+        let items: Vec<Element<Message>> = self
+            .structural_results
+            .iter()
+            .map(|m| {
+                let path = root
+                    .as_deref()
+                    .and_then(|root| m.file.strip_prefix(root).ok())
+                    .unwrap_or(&m.file);
+                let first = m.text.lines().next().unwrap_or_default();
+                row![
+                    button(text(format!("{}:{}: {first}", path.display(), m.line + 1)))
+                        .on_press(Message::OpenSearchResult(m.file.clone(), m.line)),
+                    text(bindings_label(m)).width(Length::Fill),
+                ]
+                .spacing(5)
+                .into()
+            })
+            .collect();
+        column![
+            header,
+            replace_row,
+            text(format!("Найдено: {}", self.structural_results.len())),
+            scrollable(column(items).spacing(5)).height(Length::Fixed(160.0))
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,59 @@
+//! Сводки файлов и модулей.
+//!
+//! Команда «Обновить сводки» пересоздаёт сводки проекта из описаний блоков
+//! ([`summary::regenerate`]); если настроена модель ИИ (секция `[ai]`),
+//! заметки пересказываются ею. Открытые вкладки без несохранённых правок
+//! перечитываются с диска. Сводки попадают в HTML-отчёт экспорта.
+
+use chrono::Utc;
+use iced::Command;
+use multicode_core::meta::summary;
+use tokio::task;
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+impl MulticodeApp {
+    /// Пересоздаёт сводки проекта в фоне.
+    pub(super) fn regenerate_summaries(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let client = self.ai_client.clone();
+        Command::perform(
+            async move {
+                task::spawn_blocking(move || {
+                    summary::regenerate(&root, client.as_deref()).map(|s| s.len())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            },
+            Message::SummariesRegenerated,
+        )
+    }
+
+    /// Сообщает о пересозданных сводках и перечитывает изменённые файлы.
+    pub(super) fn summaries_regenerated(
+        &mut self,
+        result: Result<usize, String>,
+    ) -> Command<Message> {
+        match result {
+            Ok(count) => {
+                self.notify(LogEntry::new(
+                    LogMessage::SummariesRegenerated,
+                    vec![count.to_string()],
+                    Utc::now(),
+                ));
+                match self.current_root_path() {
+                    Some(root) => self.reload_clean_tabs(&root),
+                    None => Command::none(),
+                }
+            }
+            Err(e) => {
+                self.log
+                    .push(LogEntry::new(LogMessage::SummaryError, vec![e], Utc::now()));
+                Command::none()
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+//! Переход к символу.
+//!
+//! Окно «Перейти к символу» ищет функции и классы всего проекта по имени из
+//! исходника и по переводам имени из метаданных. Символы файлов на диске
+//! собирает ядро ([`workspace_symbols`]), а для открытых вкладок они
+//! строятся по текущему, возможно несохранённому, тексту. Выбранный символ
+//! открывает свой файл на строке заголовка и фокусирует блок на холсте.
+
+use std::path::PathBuf;
+
+use iced::widget::{button, column, container, row, scrollable, text, text_input};
+use iced::{Command, Element, Length};
+use multicode_core::blocks::symbols::{
+    file_symbols, workspace_symbols, CodeSymbol, CodeSymbolKind,
+};
+
+use super::events::Message;
+use super::navigation::NavLocation;
+use super::{Language, MulticodeApp};
+use crate::modal::Modal;
+use crate::search::fuzzy;
+
+/// Больше строк окно не показывает.
+const MAX_SYMBOL_RESULTS: usize = 50;
+
+/// Состояние окна «Перейти к символу».
+#[derive(Debug, Clone, Default)]
+pub struct SymbolSearch {
+    pub query: String,
+    /// Символы проекта по файлам и строкам; пусто, пока идёт сбор.
+    pub symbols: Vec<CodeSymbol>,
+}
+
+/// Индексы символов `symbols`, подходящих под `query`, от лучшего к худшему.
+/// Имя, содержащее запрос целиком, выше нечёткого совпадения; пустой запрос
+/// оставляет все символы по порядку.
+pub fn rank_symbols(query: &str, symbols: &[CodeSymbol], lang: &str) -> Vec<usize> {
+    let query = query.trim();
+    if query.is_empty() {
+        return (0..symbols.len()).collect();
+    }
+    let lower = query.to_lowercase();
+    let n = query.chars().count().clamp(1, 3);
+    let score = |name: &str| {
+        let exact = if name.to_lowercase().contains(&lower) {
+            1.0
+        } else {
+            0.0
+        };
+        exact + fuzzy::similarity(query, name, n)
+    };
+    let mut scored: Vec<(usize, f32)> = symbols
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (i, score(&s.name).max(score(s.label(lang)))))
+        .filter(|(_, score)| *score > 0.0)
+        .collect();
+    scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    scored.into_iter().map(|(i, _)| i).collect()
+}
+
+impl MulticodeApp {
+    pub fn symbol_search(&self) -> Option<&SymbolSearch> {
+        self.symbol_search.as_ref()
+    }
+
+    /// Открывает окно и запускает сбор символов проекта.
+    pub(super) fn open_symbol_search(&mut self) -> Command<Message> {
+        self.symbol_search = Some(SymbolSearch::default());
+        let Some(root) = self.current_root_path() else {
+            return self.handle_message(Message::SymbolsLoaded(Vec::new()));
+        };
+        Command::perform(
+            async move { workspace_symbols(&root) },
+            Message::SymbolsLoaded,
+        )
+    }
+
+    /// Принимает символы с диска; символы открытых вкладок берутся из их
+    /// текущего текста.
+    pub(super) fn symbols_loaded(&mut self, mut symbols: Vec<CodeSymbol>) {
+        let Some(search) = self.symbol_search.as_mut() else {
+            return;
+        };
+        symbols.retain(|s| !self.tabs.iter().any(|t| t.path == s.path));
+        symbols.extend(
+            self.tabs
+                .iter()
+                .flat_map(|t| file_symbols(&t.path, &t.content, &t.blocks)),
+        );
+        symbols.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        search.symbols = symbols;
+    }
+
+    /// Переходит к символу `index` окна и закрывает окно.
+    pub(super) fn open_symbol(&mut self, index: usize) -> Command<Message> {
+        let Some(symbol) = self
+            .symbol_search
+            .take()
+            .and_then(|mut s| (index < s.symbols.len()).then(|| s.symbols.swap_remove(index)))
+        else {
+            return Command::none();
+        };
+        self.pending_block_focus = Some((symbol.path.clone(), symbol.visual_id.clone()));
+        let command = self.navigate_to(NavLocation {
+            path: symbol.path,
+            line: symbol.line,
+            visual_id: Some(symbol.visual_id),
+        });
+        self.focus_pending_block();
+        command
+    }
+
+    /// Фокусирует блок символа, как только его файл стал активной вкладкой.
+    pub(super) fn focus_pending_block(&mut self) {
+        let Some((path, id)) = &self.pending_block_focus else {
+            return;
+        };
+        let Some(tab) = self.current_file().filter(|t| &t.path == path) else {
+            return;
+        };
+        if let Some(i) = tab.blocks.iter().position(|b| &b.visual_id == id) {
+            self.canvas_focus = Some(i);
+        }
+        self.pending_block_focus = None;
+    }
+
+    pub fn symbol_search_modal<'a>(&self, content: Element<'a, Message>) -> Element<'a, Message> {
+        let Some(search) = &self.symbol_search else {
+            return content;
+        };
+        let lang = self.settings.language;
+        let russian = lang == Language::Russian;
+        let placeholder = if russian { "символ" } else { "symbol" };
+        let ranked = rank_symbols(&search.query, &search.symbols, lang.code());
+        let first = ranked.first().copied();
+        let mut input =
+            text_input(placeholder, &search.query).on_input(Message::SymbolQueryChanged);
+        if let Some(first) = first {
+            input = input.on_submit(Message::OpenSymbol(first));
+        }
+        let root = self.current_root_path();
+        let items = ranked
+            .into_iter()
+            .take(MAX_SYMBOL_RESULTS)
+            .fold(column![], |col, i| {
+                let symbol = &search.symbols[i];
+                let kind = match (symbol.kind, russian) {
+                    (CodeSymbolKind::Function, true) => "функция",
+                    (CodeSymbolKind::Class, true) => "класс",
+                    (CodeSymbolKind::Function, false) => "function",
+                    (CodeSymbolKind::Class, false) => "class",
+                };
+                let path = root
+                    .as_deref()
+                    .and_then(|r| symbol.path.strip_prefix(r).ok())
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| symbol.path.clone());
+                let location = format!("{}:{}", path.display(), symbol.line + 1);
+                let content = row![
+                    text(symbol.label(lang.code())).width(Length::Fill),
+                    text(kind),
+                    text(location)
+                ]
+                .spacing(10);
+                col.push(
+                    button(content)
+                        .width(Length::Fill)
+                        .on_press(Message::OpenSymbol(i)),
+                )
+            })
+            .spacing(5);
+        let modal_content = container(
+            column![input, scrollable(items).height(Length::Shrink)]
+                .spacing(10)
+                .width(Length::Fixed(600.0)),
+        )
+        .padding(10);
+        Modal::new(content, modal_content)
+            .on_blur(Message::CloseSymbolSearch)
+            .into()
+    }
+}
@@ -0,0 +1,92 @@
+//! Язык вкладки.
+//!
+//! Обычно язык определяется по расширению файла ([`detect_lang`]). Для
+//! файлов с незнакомым или неподходящим расширением язык выбирается в
+//! строке состояния; выбор запоминается в настройках для этого файла
+//! ([`UserSettings::file_languages`]), после чего вкладка заново
+//! разбирается на блоки и подсвечивается.
+//!
+//! [`UserSettings::file_languages`]: super::UserSettings::file_languages
+
+use std::path::Path;
+
+use iced::widget::{button, pick_list, row};
+use iced::{Command, Element};
+use multicode_core::blocks;
+use multicode_core::parser::grammar::GRAMMAR_VERSIONS;
+use multicode_core::parser::Lang;
+
+use super::events::handler::{detect_lang, document_highlights};
+use super::events::Message;
+use super::MulticodeApp;
+
+impl MulticodeApp {
+    /// Язык файла `path`: выбранный вручную или определённый по расширению.
+    pub(crate) fn lang_for(&self, path: &Path) -> Option<Lang> {
+        self.settings
+            .file_languages
+            .get(path)
+            .copied()
+            .or_else(|| detect_lang(path))
+    }
+
+    /// Задаёт язык активной вкладки; `None` возвращает определение по
+    /// расширению.
+    pub(super) fn set_tab_language(&mut self, lang: Option<Lang>) -> Command<Message> {
+        let Some(i) = self.active_tab else {
+            return Command::none();
+        };
+        let path = self.tabs[i].path.clone();
+        match lang {
+            Some(lang) => self.settings.file_languages.insert(path.clone(), lang),
+            None => self.settings.file_languages.remove(&path),
+        };
+        let lang = self.lang_for(&path);
+        let limits = self.parse_limits();
+        let enrich = self.enrich_config();
+        let tab = &mut self.tabs[i];
+        if tab.lang != lang {
+            tab.lang = lang;
+            // Старая подсветка удаляет дерево документа при сбросе, поэтому
+            // она освобождается до построения новой под тем же именем.
+            tab.highlights = None;
+            tab.highlights = document_highlights(&path, &tab.content, lang);
+            let parsed = lang
+                .and_then(|lang| {
+                    blocks::parse_blocks_with_config(
+                        tab.content.clone(),
+                        lang.to_string(),
+                        &limits,
+                        &enrich,
+                    )
+                })
+                .unwrap_or_default();
+            tab.blocks = parsed.blocks;
+            tab.simplified = parsed.simplified;
+            // Незавершённый разбор по старому языку больше не нужен.
+            tab.analysis_version = tab.analysis_version.wrapping_add(1);
+            self.refresh_folds(i);
+        }
+        self.handle_message(Message::SaveSettings)
+    }
+
+    /// Выбор языка в строке состояния.
+    pub(super) fn language_picker(&self, path: &Path, lang: Option<Lang>) -> Element<'_, Message> {
+        let langs: Vec<Lang> = GRAMMAR_VERSIONS.iter().map(|(lang, _, _)| *lang).collect();
+        let picker = pick_list(langs, lang, |l| Message::SetTabLanguage(Some(l)))
+            .placeholder("язык не определён")
+            .padding([0, 5]);
+        if self.settings.file_languages.contains_key(path) {
+            row![
+                picker,
+                button("авто")
+                    .padding([0, 5])
+                    .on_press(Message::SetTabLanguage(None))
+            ]
+            .spacing(5)
+            .into()
+        } else {
+            picker.into()
+        }
+    }
+}
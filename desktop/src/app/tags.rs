@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use iced::widget::text_editor::Content;
+use iced::Command;
+use multicode_core::meta::tags::{merge_tags, replace_tags, tag_counts};
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp};
+
+/// Теги из строки ввода, разделённые запятыми.
+fn split_tags(input: &str) -> Vec<String> {
+    input
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+impl MulticodeApp {
+    /// Загружает теги рабочего пространства с числом использований.
+    pub(super) fn load_tags(&self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        Command::perform(
+            async move { tag_counts(&root).into_iter().collect() },
+            Message::TagsLoaded,
+        )
+    }
+
+    /// Переименовывает теги из поля «из» в тег из поля «в» во всех файлах
+    /// проекта. Несколько исходных тегов сливаются в один.
+    pub(super) fn merge_tags(&mut self) -> Command<Message> {
+        let from = split_tags(&self.tag_from);
+        let into = self.tag_into.trim().to_string();
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        if from.is_empty() || into.is_empty() {
+            return Command::none();
+        }
+        Command::perform(
+            async move {
+                let from: Vec<&str> = from.iter().map(String::as_str).collect();
+                merge_tags(&root, &from, &into).map_err(|e| e.to_string())
+            },
+            Message::TagsMerged,
+        )
+    }
+
+    /// Применяет переименование к открытым вкладкам и обновляет список тегов.
+    ///
+    /// Файлы на диске уже изменены, а в памяти вкладок могут быть
+    /// несохранённые правки, поэтому теги заменяются и в их содержимом.
+    pub(super) fn tags_merged(
+        &mut self,
+        result: Result<Vec<PathBuf>, String>,
+    ) -> Command<Message> {
+        let changed = match result {
+            Ok(changed) => changed,
+            Err(e) => {
+                self.log
+                    .push(LogEntry::new(LogMessage::TagsMergeError, vec![e], Utc::now()));
+                return Command::none();
+            }
+        };
+        let from_list = split_tags(&self.tag_from);
+        let from: Vec<&str> = from_list.iter().map(String::as_str).collect();
+        let into = self.tag_into.trim().to_string();
+        let mut commands = Vec::new();
+        for i in 0..self.tabs.len() {
+            let tab = &mut self.tabs[i];
+            let updated = replace_tags(&tab.content, &from, &into);
+            if updated != tab.content {
+                tab.content = updated;
+                tab.editor = Content::with_text(&tab.content);
+                commands.push(self.schedule_analysis(i));
+            }
+        }
+        self.log.push(LogEntry::new(
+            LogMessage::TagsMerged,
+            vec![from_list.join(", "), into, changed.len().to_string()],
+            Utc::now(),
+        ));
+        self.tag_from.clear();
+        self.tag_into.clear();
+        commands.push(self.load_tags());
+        Command::batch(commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_tags;
+
+    #[test]
+    fn splits_comma_separated_tags() {
+        assert_eq!(split_tags(" net, network ,,"), vec!["net", "network"]);
+        assert!(split_tags(" ").is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use iced::Command;
+use multicode_core::tasks::{self, Task};
+
+use super::events::Message;
+use super::{EditorMode, MulticodeApp, Screen};
+
+/// Колонка доски для задач без тегов.
+pub(super) const UNTAGGED: &str = "без тега";
+
+/// Признак группировки задач на доске.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TaskGroup {
+    #[default]
+    Status,
+    Tag,
+}
+
+/// Раскладывает задачи по колонкам доски. При группировке по тегам задача
+/// с несколькими тегами попадает в колонку каждого тега.
+pub(super) fn group_tasks(tasks: &[Task], group: TaskGroup) -> BTreeMap<String, Vec<&Task>> {
+    let mut columns: BTreeMap<String, Vec<&Task>> = BTreeMap::new();
+    for task in tasks {
+        match group {
+            TaskGroup::Status => columns.entry(task.status.clone()).or_default().push(task),
+            TaskGroup::Tag if task.tags.is_empty() => {
+                columns.entry(UNTAGGED.to_string()).or_default().push(task)
+            }
+            TaskGroup::Tag => {
+                for tag in &task.tags {
+                    columns.entry(tag.clone()).or_default().push(task);
+                }
+            }
+        }
+    }
+    columns
+}
+
+impl MulticodeApp {
+    /// Открывает доску задач проекта и собирает задачи.
+    pub(super) fn open_task_board(&mut self) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        self.screen = Screen::TaskBoard { root: root.clone() };
+        self.load_tasks(root)
+    }
+
+    /// Собирает задачи проекта в фоне.
+    pub(super) fn load_tasks(&self, root: PathBuf) -> Command<Message> {
+        Command::perform(async move { tasks::collect(&root) }, Message::TasksLoaded)
+    }
+
+    /// Возвращается к редактору в текущем режиме.
+    pub(super) fn close_task_board(&mut self) {
+        if let Screen::TaskBoard { root } = &self.screen {
+            let root = root.clone();
+            self.screen = match self.settings.editor_mode {
+                EditorMode::Text => Screen::TextEditor { root },
+                EditorMode::Visual => Screen::VisualEditor { root },
+                EditorMode::Split => Screen::Split { root },
+            };
+        }
+    }
+
+    /// Закрывает доску и переходит к строке задачи.
+    pub(super) fn open_task(&mut self, task: Task) -> Command<Message> {
+        self.close_task_board();
+        self.handle_message(Message::OpenSearchResult(task.file, task.line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use multicode_core::tasks::TaskKind;
+
+    fn task(status: &str, tags: &[&str]) -> Task {
+        Task {
+            file: PathBuf::from("main.rs"),
+            line: 0,
+            block: None,
+            kind: TaskKind::Todo,
+            text: String::new(),
+            assignee: None,
+            status: status.into(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn groups_by_status_and_tags() {
+        let tasks = vec![task("open", &["net", "ui"]), task("review", &[])];
+        let by_status = group_tasks(&tasks, TaskGroup::Status);
+        assert_eq!(by_status.keys().collect::<Vec<_>>(), vec!["open", "review"]);
+        let by_tag = group_tasks(&tasks, TaskGroup::Tag);
+        assert_eq!(
+            by_tag.keys().collect::<Vec<_>>(),
+            vec!["net", "ui", UNTAGGED]
+        );
+        assert_eq!(by_tag["net"].len(), 1);
+    }
+}
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use iced::font::Weight;
+use iced::widget::{row, text};
+use iced::{Element, Font};
+use portable_pty::CommandBuilder;
+
+use super::events::Message;
+use super::log_translations::LogMessage;
+use super::{LogEntry, MulticodeApp};
+use crate::terminal::{ansi, pty, PtySession};
+
+impl MulticodeApp {
+    fn terminal_root(&self) -> PathBuf {
+        self.current_root_path()
+            .unwrap_or_else(|| PathBuf::from("."))
+    }
+
+    fn terminal_error(&mut self, error: String) {
+        self.log
+            .push(LogEntry::new(LogMessage::RunError, vec![error], Utc::now()));
+    }
+
+    /// Передаёт строку оболочке проекта. Оболочка запускается при первой
+    /// команде и перезапускается, если она завершилась или открыт другой
+    /// проект.
+    pub(super) fn run_in_terminal(&mut self, line: &str) {
+        let root = self.terminal_root();
+        let alive = self
+            .terminal
+            .as_mut()
+            .is_some_and(|s| s.root() == root && s.is_running());
+        if !alive {
+            self.terminal = None;
+            match PtySession::shell(&root, self.terminal_size, self.sender.clone()) {
+                Ok(session) => self.terminal = Some(session),
+                Err(e) => return self.terminal_error(e),
+            }
+        }
+        if let Some(session) = self.terminal.as_mut() {
+            if let Err(e) = session.write_line(line) {
+                self.terminal_error(e.to_string());
+            }
+        }
+    }
+
+    /// Запускает отдельный процесс в псевдотерминале, завершая предыдущий.
+    pub(super) fn spawn_terminal_job(&mut self, cmd: CommandBuilder) {
+        self.terminal_job = None;
+        let root = self.terminal_root();
+        match PtySession::spawn(&root, cmd, self.terminal_size, self.sender.clone()) {
+            Ok(session) => self.terminal_job = Some(session),
+            Err(e) => self.terminal_error(e),
+        }
+    }
+
+    /// Останавливает запущенный процесс блока, а если его нет — прерывает
+    /// текущую команду оболочки.
+    pub(super) fn stop_terminal(&mut self) {
+        if let Some(mut job) = self.terminal_job.take() {
+            if job.is_running() {
+                job.kill();
+                return;
+            }
+        }
+        if let Some(session) = self.terminal.as_mut() {
+            if let Err(e) = session.interrupt() {
+                self.terminal_error(e.to_string());
+            }
+        }
+    }
+
+    /// Подгоняет размер псевдотерминалов под ширину окна.
+    pub(super) fn resize_terminal(&mut self, width: f32) {
+        let size = pty::size_for(width, crate::terminal::OUTPUT_HEIGHT);
+        if size == self.terminal_size {
+            return;
+        }
+        self.terminal_size = size;
+        for session in self.terminal.iter().chain(&self.terminal_job) {
+            let _ = session.resize(size);
+        }
+    }
+}
+
+/// Строка лога с цветами и жирностью из последовательностей ANSI.
+pub(super) fn ansi_line<'a>(line: &str) -> Element<'a, Message> {
+    let spans = ansi::parse(line).into_iter().map(|span| {
+        let mut t = text(span.text);
+        if let Some(fg) = span.style.fg {
+            t = t.style(fg.to_color());
+        }
+        if span.style.bold {
+            t = t.font(Font {
+                weight: Weight::Bold,
+                ..Font::DEFAULT
+            });
+        }
+        t.into()
+    });
+    row(spans.collect::<Vec<Element<Message>>>()).into()
+}
@@ -0,0 +1,135 @@
+//! Панель тестов.
+//!
+//! Панель запускает тесты фреймворком, найденным в корне проекта, и
+//! показывает итоги по блокам активной вкладки, у которых в метаданных
+//! заполнено поле `tests`. Повторный запуск отдельного теста обновляет
+//! только его итог, а итоги остальных тестов сохраняются.
+
+use chrono::Utc;
+use iced::widget::{button, column, row, scrollable, text, Space};
+use iced::{Command, Element, Length};
+use multicode_core::blocks::symbols::symbol_name;
+use multicode_core::meta::read_all;
+use multicode_core::testrun::{self, block_status, Framework, TestRun};
+
+use super::events::Message;
+use super::{LogEntry, LogMessage, MulticodeApp, Tab};
+use crate::visual::canvas::test_status_color;
+
+/// Объединяет итоги нового запуска `run` с прежними `previous`: итоги
+/// перезапущенных тестов заменяются, остальные остаются.
+pub(super) fn merge_runs(previous: Option<TestRun>, mut run: TestRun) -> TestRun {
+    let Some(previous) = previous.filter(|p| p.framework == run.framework) else {
+        return run;
+    };
+    if run.filter.is_none() {
+        return run;
+    }
+    let mut outcomes: Vec<_> = previous
+        .outcomes
+        .into_iter()
+        .filter(|o| !run.outcomes.iter().any(|n| n.name == o.name))
+        .collect();
+    outcomes.append(&mut run.outcomes);
+    run.outcomes = outcomes;
+    run
+}
+
+impl MulticodeApp {
+    /// Запускает тесты проекта: все или только подходящие под `filter`.
+    pub(super) fn run_tests(&mut self, filter: Option<String>) -> Command<Message> {
+        let Some(root) = self.current_root_path() else {
+            return Command::none();
+        };
+        let Some(framework) = Framework::detect(&root) else {
+            self.log.push(LogEntry::new(
+                LogMessage::RunError,
+                vec!["фреймворк тестов не найден".into()],
+                Utc::now(),
+            ));
+            return Command::none();
+        };
+        self.tests_running = true;
+        Command::perform(
+            async move { testrun::run(&root, framework, filter.as_deref()) },
+            |result| Message::TestsFinished(result.map_err(|e| e.to_string())),
+        )
+    }
+
+    /// Состояние тестов каждого блока вкладки `tab` по последнему запуску.
+    pub(super) fn tab_test_status(&self, tab: &Tab) -> Vec<Option<bool>> {
+        match &self.test_run {
+            Some(run) => block_status(&tab.content, &tab.blocks, &run.outcomes),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn test_panel_component(&self) -> Element<'_, Message> {
+        if !self.show_test_panel {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let title = match &self.test_run {
+            Some(run) => format!("Тесты ({})", run.framework),
+            None => "Тесты".into(),
+        };
+        let mut run_all = button("Запустить все");
+        if !self.tests_running {
+            run_all = run_all.on_press(Message::RunTests(None));
+        }
+        let header = row![
+            text(title).width(Length::Fill),
+            run_all,
+            button("×").on_press(Message::ToggleTestPanel),
+        ]
+        .spacing(5);
+
+        let mut items: Vec<Element<Message>> = Vec::new();
+        if self.tests_running {
+            items.push(text("Тесты выполняются...").into());
+        }
+        if let Some(run) = &self.test_run {
+            let failed = run.outcomes.iter().filter(|o| !o.passed).count();
+            items.push(
+                text(format!(
+                    "Прошло: {}, упало: {failed}",
+                    run.outcomes.len() - failed
+                ))
+                .into(),
+            );
+        }
+        if let Some(tab) = self.current_file() {
+            let metas = read_all(&tab.content);
+            let status = self.tab_test_status(tab);
+            for (i, block) in tab.blocks.iter().enumerate() {
+                let Some(meta) = metas
+                    .iter()
+                    .find(|m| m.id == block.visual_id && !m.tests.is_empty())
+                else {
+                    continue;
+                };
+                let source = tab
+                    .content
+                    .get(block.range.0..block.range.1)
+                    .unwrap_or_default();
+                let label = symbol_name(source).unwrap_or(&block.kind).to_string();
+                let state = status.get(i).copied().flatten();
+                let mut line =
+                    row![text("●").style(test_status_color(state)), text(label)].spacing(5);
+                for entry in &meta.tests {
+                    let mut rerun = button(text(format!("↻ {entry}")));
+                    if !self.tests_running {
+                        rerun = rerun.on_press(Message::RunTests(Some(entry.clone())));
+                    }
+                    line = line.push(rerun);
+                }
+                items.push(line.into());
+            }
+        }
+        column![
+            header,
+            scrollable(column(items).spacing(5)).height(Length::Fixed(160.0))
+        ]
+        .spacing(5)
+        .into()
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::UNIX_EPOCH;
+
+use chrono::Utc;
+
+use super::{LogEntry, LogMessage, MulticodeApp};
+use crate::editor::{CustomTheme, EditorTheme};
+
+impl MulticodeApp {
+    /// Перечитывает файл пользовательской темы, если он изменился с момента
+    /// последней загрузки, и переключает редактор на тему `Custom`.
+    ///
+    /// Недоступный файл считается неизменённым до появления, поэтому ошибка
+    /// чтения попадает в журнал один раз.
+    pub(super) fn reload_theme_file(&mut self) {
+        let Some(path) = self.settings.editor.theme_file.clone() else {
+            return;
+        };
+        let mtime = std::fs::metadata(&path)
+            .and_then(|m| m.modified())
+            .unwrap_or(UNIX_EPOCH);
+        if self.theme_file_mtime == Some(mtime) {
+            return;
+        }
+        self.theme_file_mtime = Some(mtime);
+        let name = path.display().to_string();
+        match CustomTheme::load(&path) {
+            Ok(theme) => {
+                self.settings.editor.custom_theme = theme;
+                self.settings.editor.theme = EditorTheme::Custom;
+                self.log
+                    .push(LogEntry::new(LogMessage::ThemeLoaded, vec![name], Utc::now()));
+            }
+            Err(e) => self.log.push(LogEntry::new(
+                LogMessage::ThemeLoadError,
+                vec![format!("{name}: {e}")],
+                Utc::now(),
+            )),
+        }
+    }
+}
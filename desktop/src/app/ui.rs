@@ -6,18 +6,35 @@ use iced::widget::{
 };
 use iced::{Element, Length};
 
+use crate::app::block_history::{describe_change, entry_title};
+use crate::app::canvas_layers::LINT_SCROLL;
+use crate::app::cochange::block_label;
+use crate::app::code_metrics::metrics_label;
 use crate::app::diff::DiffView;
 use crate::app::events::Message;
+use crate::app::git_screen::HunkRef;
+use crate::app::issue_links::IssueLinkStatus;
+use crate::app::notifications::severity_color;
+use crate::app::task_board::{group_tasks, TaskGroup};
+use crate::app::terminal::ansi_line;
 use crate::app::{
     command_palette::COMMANDS,
-    command_translations::{command_description, command_hotkey, command_name},
+    command_translations::{
+        command_description, command_hotkey, command_name, custom_command_description,
+        custom_command_hotkey, custom_command_name,
+    },
     format_log,
+    logging::ModuleChoice,
     search_translations::{search_text, SearchText},
     Language, LogLevel, MulticodeApp,
 };
+use crate::editor::input_mode::InputMode;
 use crate::modal::Modal;
 use crate::search::fuzzy;
 use crate::search::hotkeys::HotkeyContext;
+use crate::terminal::OUTPUT_HEIGHT;
+use crate::visual::accessibility::{describe_block, DEFAULT_FONT_SIZE};
+use crate::visual::blocks::BlockColors;
 use crate::visual::canvas::{CanvasMessage, VisualCanvas};
 use crate::visual::connections::Connection;
 use crate::visual::palette::{BlockPalette, PaletteMessage};
@@ -72,9 +89,30 @@ impl MulticodeApp {
             let items = file
                 .diagnostics
                 .iter()
-                .map(|d| text(format!("{}: {}", d.line + 1, d.message)).into())
+                .enumerate()
+                .map(|(i, d)| {
+                    let marker = if self.focused_diagnostic == Some(i) {
+                        "▶ "
+                    } else {
+                        ""
+                    };
+                    d.fixes
+                        .iter()
+                        .enumerate()
+                        .fold(
+                            row![text(format!("{marker}{}: {}", d.line + 1, d.message))].spacing(5),
+                            |r, (j, f)| {
+                                r.push(button(text(&f.title)).on_press(Message::ApplyQuickFix {
+                                    diagnostic: i,
+                                    fix: j,
+                                }))
+                            },
+                        )
+                        .into()
+                })
                 .collect::<Vec<Element<Message>>>();
             scrollable(column(items))
+                .id(scrollable::Id::new(LINT_SCROLL))
                 .height(Length::Fixed(100.0))
                 .into()
         } else {
@@ -82,6 +120,472 @@ impl MulticodeApp {
         }
     }
 
+    pub fn bookmarks_panel_component(&self) -> Element<'_, Message> {
+        if !self.show_bookmarks_panel {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let bookmarks = self.bookmarks();
+        let nav = row![
+            button("←").on_press_maybe(
+                self.navigation
+                    .can_go_back()
+                    .then_some(Message::HistoryBack)
+            ),
+            button("→").on_press_maybe(
+                self.navigation
+                    .can_go_forward()
+                    .then_some(Message::HistoryForward)
+            ),
+            text("Закладки").width(Length::Fill),
+            button("×").on_press(Message::ToggleBookmarksPanel),
+        ]
+        .spacing(5);
+        let items: Vec<Element<Message>> = if bookmarks.is_empty() {
+            vec![text("Закладок нет").into()]
+        } else {
+            bookmarks
+                .into_iter()
+                .map(|bm| {
+                    let name = bm
+                        .location
+                        .path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let label = format!("{}:{} {}", name, bm.location.line + 1, bm.label);
+                    button(text(label))
+                        .on_press(Message::GotoLocation(bm.location))
+                        .into()
+                })
+                .collect()
+        };
+        column![nav, scrollable(column(items)).height(Length::Fixed(120.0))]
+            .spacing(5)
+            .into()
+    }
+
+    pub fn tags_panel_component(&self) -> Element<'_, Message> {
+        if !self.show_tags_panel {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let header = row![
+            text("Теги").width(Length::Fill),
+            button("×").on_press(Message::ToggleTagsPanel),
+        ]
+        .spacing(5);
+        let items: Vec<Element<Message>> = if self.workspace_tags.is_empty() {
+            vec![text("Тегов нет").into()]
+        } else {
+            self.workspace_tags
+                .iter()
+                .map(|(tag, count)| {
+                    button(text(format!("{tag} ({count})")))
+                        .on_press(Message::SelectTag(tag.clone()))
+                        .into()
+                })
+                .collect()
+        };
+        let rename = row![
+            text_input("Теги через запятую", &self.tag_from)
+                .on_input(Message::TagFromChanged)
+                .width(Length::Fill),
+            text("→"),
+            text_input("Новый тег", &self.tag_into)
+                .on_input(Message::TagIntoChanged)
+                .on_submit(Message::MergeTags)
+                .width(Length::Fill),
+            button(text("Переименовать / слить")).on_press(Message::MergeTags),
+        ]
+        .spacing(5);
+        column![
+            header,
+            scrollable(column(items)).height(Length::Fixed(120.0)),
+            rename
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    pub fn block_history_component(&self) -> Element<'_, Message> {
+        if !self.show_block_history {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let title = match &self.block_history_id {
+            Some(id) => format!("История блока {id}"),
+            None => "История блока".to_string(),
+        };
+        let header = row![
+            text(title).width(Length::Fill),
+            button("×").on_press(Message::ToggleBlockHistory),
+        ]
+        .spacing(5);
+        let items: Vec<Element<Message>> = if self.block_history_id.is_none() {
+            vec![text("Выберите блок на холсте").into()]
+        } else if self.block_history.is_empty() {
+            vec![text("Изменений в истории git нет").into()]
+        } else {
+            self.block_history
+                .iter()
+                .rev()
+                .map(|entry| {
+                    let changes = entry
+                        .changes
+                        .iter()
+                        .map(|c| text(format!("  {}", describe_change(c))).into())
+                        .collect::<Vec<Element<Message>>>();
+                    column![text(entry_title(entry)), column(changes)].into()
+                })
+                .collect()
+        };
+        column![header, scrollable(column(items).spacing(5)).height(Length::Fixed(160.0))]
+            .spacing(5)
+            .into()
+    }
+
+    pub fn cochange_component(&self) -> Element<'_, Message> {
+        if !self.show_cochange_panel {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let header = row![
+            text("Совместные изменения").width(Length::Fill),
+            button("Обновить").on_press(Message::AnalyzeCoChange),
+            button("×").on_press(Message::ToggleCoChangePanel),
+        ]
+        .spacing(5);
+        let Some(report) = &self.cochange else {
+            return column![header, text("Анализ истории git...")]
+                .spacing(5)
+                .into();
+        };
+        let mut items: Vec<Element<Message>> = vec![text(format!(
+            "Коммитов учтено: {}",
+            report.commits
+        ))
+        .into()];
+        if report.suggestions.is_empty() && report.stale_links.is_empty() {
+            items.push(text("Предложений нет").into());
+        }
+        for s in &report.suggestions {
+            items.push(
+                row![
+                    button(text(block_label(&s.a)))
+                        .on_press(Message::OpenCoChangeBlock(s.a.clone())),
+                    text("↔"),
+                    button(text(block_label(&s.b)))
+                        .on_press(Message::OpenCoChangeBlock(s.b.clone())),
+                    text(format!(
+                        "{} коммитов, {:.0}%",
+                        s.together,
+                        s.confidence * 100.0
+                    )),
+                ]
+                .spacing(5)
+                .into(),
+            );
+        }
+        for link in &report.stale_links {
+            items.push(
+                row![
+                    text("Связь без общих изменений:"),
+                    button(text(block_label(&link.from)))
+                        .on_press(Message::OpenCoChangeBlock(link.from.clone())),
+                    text("→"),
+                    button(text(block_label(&link.to)))
+                        .on_press(Message::OpenCoChangeBlock(link.to.clone())),
+                ]
+                .spacing(5)
+                .into(),
+            );
+        }
+        column![header, scrollable(column(items).spacing(5)).height(Length::Fixed(160.0))]
+            .spacing(5)
+            .into()
+    }
+
+    pub fn notifications_component(&self) -> Element<'_, Message> {
+        if !self.show_notifications {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let header = row![
+            text("Уведомления").width(Length::Fill),
+            button("Очистить").on_press_maybe(
+                (!self.notifications.is_empty()).then_some(Message::ClearNotifications)
+            ),
+            button("×").on_press(Message::ToggleNotifications),
+        ]
+        .spacing(5);
+        if self.notifications.is_empty() {
+            return column![header, text("Уведомлений нет")].spacing(5).into();
+        }
+        let items = self.notifications.iter().map(|n| {
+            let mut message = format_log(&n.entry, self.settings.language);
+            if n.count > 1 {
+                message.push_str(&format!(" (×{})", n.count));
+            }
+            row![
+                text(n.entry.level.to_string()).style(severity_color(n.entry.level)),
+                text(message).width(Length::Fill),
+                button("×").on_press(Message::DismissNotification(n.id)),
+            ]
+            .spacing(5)
+            .into()
+        });
+        column![
+            header,
+            scrollable(column(items.collect::<Vec<Element<Message>>>()).spacing(5))
+                .height(Length::Fixed(160.0))
+        ]
+        .spacing(5)
+        .into()
+    }
+
+    pub fn macros_component(&self) -> Element<'_, Message> {
+        if !self.show_macros_panel {
+            return Space::with_height(Length::Shrink).into();
+        }
+        let record_label = match &self.macro_recording {
+            Some(steps) => format!("Остановить запись ({})", steps.len()),
+            None => "Записать".to_string(),
+        };
+        let header = row![
+            text("Макросы").width(Length::Fill),
+            button(text(record_label)).on_press(Message::ToggleMacroRecording),
+            button("×").on_press(Message::ToggleMacrosPanel),
+        ]
+        .spacing(5);
+        let mut content = column![header].spacing(5);
+        if !self.recorded_macro.is_empty() {
+            let can_save = !self.macro_name.trim().is_empty();
+            content = content.push(
+                row![
+                    text(format!("Записано шагов: {}", self.recorded_macro.len())),
+                    text_input("Имя макроса", &self.macro_name)
+                        .on_input(Message::MacroNameChanged)
+                        .on_submit(Message::SaveMacro),
+                    button("Сохранить").on_press_maybe(can_save.then_some(Message::SaveMacro)),
+                ]
+                .spacing(5),
+            );
+        }
+        if self.settings.macros.is_empty() {
+            return content.push(text("Макросов нет")).into();
+        }
+        let items = self.settings.macros.iter().map(|m| {
+            let run = |all_tabs| Message::RunMacro {
+                name: m.name.clone(),
+                all_tabs,
+            };
+            row![
+                text(format!("{} ({})", m.name, m.steps.len())).width(Length::Fill),
+                button("Запустить").on_press(run(false)),
+                button("Во всех вкладках").on_press(run(true)),
+                button("Удалить").on_press(Message::DeleteMacro(m.name.clone())),
+            ]
+            .spacing(5)
+            .into()
+        });
+        content
+            .push(
+                scrollable(column(items.collect::<Vec<Element<Message>>>()).spacing(5))
+                    .height(Length::Fixed(160.0)),
+            )
+            .into()
+    }
+
+    pub fn task_board_component(&self) -> Element<'_, Message> {
+        let group_button = |label: &'static str, group: TaskGroup| {
+            button(text(label)).on_press_maybe(
+                (self.task_group != group).then_some(Message::TaskGroupChanged(group)),
+            )
+        };
+        let header = row![
+            text(format!("Задачи: {}", self.tasks.len())).width(Length::Fill),
+            group_button("По статусу", TaskGroup::Status),
+            group_button("По тегам", TaskGroup::Tag),
+            button("Закрыть").on_press(Message::CloseTaskBoard),
+        ]
+        .spacing(5);
+        let root = self.current_root_path().unwrap_or_default();
+        let columns: Vec<Element<Message>> = group_tasks(&self.tasks, self.task_group)
+            .into_iter()
+            .map(|(name, tasks)| {
+                let cards: Vec<Element<Message>> = tasks
+                    .into_iter()
+                    .map(|task| {
+                        let file = task.file.strip_prefix(&root).unwrap_or(&task.file);
+                        let assignee = task
+                            .assignee
+                            .as_ref()
+                            .map(|a| format!(" @{a}"))
+                            .unwrap_or_default();
+                        let card = column![
+                            text(&task.text),
+                            text(format!("{}:{}{}", file.display(), task.line + 1, assignee))
+                                .size(12),
+                        ];
+                        button(card)
+                            .width(Length::Fill)
+                            .on_press(Message::OpenTask(task.clone()))
+                            .into()
+                    })
+                    .collect();
+                column![
+                    text(format!("{name} ({})", cards.len())),
+                    scrollable(column(cards).spacing(5)),
+                ]
+                .spacing(5)
+                .width(Length::Fixed(240.0))
+                .into()
+            })
+            .collect();
+        let board: Element<Message> = if columns.is_empty() {
+            text("Задач нет").into()
+        } else {
+            scrollable(row(columns).spacing(10))
+                .direction(scrollable::Direction::Horizontal(
+                    scrollable::Properties::default(),
+                ))
+                .into()
+        };
+        column![header, board].spacing(10).into()
+    }
+
+    pub fn git_screen_component(&self) -> Element<'_, Message> {
+        let screen = &self.git_screen;
+        let header = row![
+            text("Git").width(Length::Fill),
+            button("Обновить").on_press(Message::GitStatusRefresh),
+            button("Закрыть").on_press(Message::CloseGitScreen),
+        ]
+        .spacing(5);
+        let changes = row![
+            self.git_changes_column(false),
+            self.git_changes_column(true)
+        ]
+        .spacing(10)
+        .height(Length::Fill);
+        let can_commit = !screen.message.trim().is_empty()
+            && (screen.amend || !screen.changes.staged.is_empty());
+        let commit_label = if screen.amend {
+            "Исправить коммит"
+        } else {
+            "Коммит"
+        };
+        let commit = row![
+            text_input("Сообщение коммита", &screen.message)
+                .on_input(Message::CommitMessageChanged)
+                .width(Length::Fill),
+            checkbox("Исправить последний коммит", screen.amend)
+                .on_toggle(Message::CommitAmendToggled),
+            button(commit_label).on_press_maybe(can_commit.then_some(Message::CommitChanges)),
+        ]
+        .spacing(5)
+        .align_items(iced::Alignment::Center);
+        column![header, changes, commit].spacing(10).into()
+    }
+
+    /// Колонка неиндексированных (`staged == false`) или индексированных
+    /// изменений экрана git.
+    fn git_changes_column(&self, staged: bool) -> Element<'_, Message> {
+        let screen = &self.git_screen;
+        let files = if staged {
+            &screen.changes.staged
+        } else {
+            &screen.changes.unstaged
+        };
+        let root = self.current_root_path().unwrap_or_default();
+        let mut list = column![].spacing(5);
+        for file in files {
+            let name = file.path.strip_prefix(&root).unwrap_or(&file.path);
+            let file_action = if staged {
+                button(text("Убрать файл").size(12))
+                    .on_press(Message::UnstageFile(file.path.clone()))
+            } else {
+                button(text("Добавить файл").size(12))
+                    .on_press(Message::StageFile(file.path.clone()))
+            };
+            list = list.push(
+                row![
+                    text(name.display().to_string()).width(Length::Fill),
+                    file_action
+                ]
+                .spacing(5),
+            );
+            if file.binary {
+                list = list.push(text("двоичный файл").size(12));
+            }
+            for (index, hunk) in file.hunks.iter().enumerate() {
+                let hunk_ref = HunkRef {
+                    staged,
+                    path: file.path.clone(),
+                    index,
+                    header: hunk.header.clone(),
+                };
+                let checked = screen.selected.contains(&hunk_ref);
+                list = list.push(
+                    checkbox(hunk.header.clone(), checked)
+                        .on_toggle(move |_| Message::ToggleHunk(hunk_ref.clone())),
+                );
+                let lines = hunk.lines.iter().fold(column![], |lines, line| {
+                    let content = text(format!("{}{}", line.origin, line.content))
+                        .font(iced::Font::MONOSPACE)
+                        .size(12);
+                    lines.push(match line.origin {
+                        '+' => content.style(iced::Color::from_rgb(0.2, 0.6, 0.3)),
+                        '-' => content.style(iced::Color::from_rgb(0.8, 0.2, 0.2)),
+                        _ => content,
+                    })
+                });
+                list = list.push(lines);
+            }
+        }
+        let (title, action_label, action) = if staged {
+            (
+                "В индексе",
+                "Убрать выбранные",
+                Message::UnstageSelectedHunks,
+            )
+        } else {
+            (
+                "Не в индексе",
+                "Добавить выбранные",
+                Message::StageSelectedHunks,
+            )
+        };
+        let has_selection = screen.selected.iter().any(|r| r.staged == staged);
+        column![
+            row![
+                text(format!("{title} ({})", files.len())).width(Length::Fill),
+                button(action_label).on_press_maybe(has_selection.then_some(action)),
+            ]
+            .spacing(5),
+            scrollable(list),
+        ]
+        .spacing(5)
+        .width(Length::FillPortion(1))
+        .into()
+    }
+
+    pub fn saved_search_component(&self) -> Element<'_, Message> {
+        let names: Vec<String> = self.saved_searches.iter().map(|s| s.name.clone()).collect();
+        let picker = pick_list(
+            names,
+            self.active_saved_search.clone(),
+            Message::SavedSearchSelected,
+        )
+        .placeholder("Сохранённые поиски");
+        row![
+            picker,
+            text_input("Имя поиска", &self.saved_search_name)
+                .on_input(Message::SavedSearchNameChanged)
+                .on_submit(Message::SaveSearch)
+                .width(Length::Fill),
+            button(text("Сохранить поиск")).on_press(Message::SaveSearch),
+        ]
+        .spacing(5)
+        .into()
+    }
+
     pub fn toolbar(&self) -> Element<Message> {
         if self.settings.show_toolbar {
             let open_icon = Svg::new(Handle::from_memory(OPEN_ICON))
@@ -100,6 +604,15 @@ impl MulticodeApp {
             let new_btn = button("Новый").on_press(Message::NewFile);
             let palette_btn = button("Командная палитра").on_press(Message::ToggleCommandPalette);
             let settings_btn = button("Настройки").on_press(Message::OpenSettings);
+            let notifications_label = text(format!("Уведомления: {}", self.notifications.len()));
+            let notifications_label = match self.notifications.severity() {
+                Some(level) if level >= LogLevel::Warning => {
+                    notifications_label.style(severity_color(level))
+                }
+                _ => notifications_label,
+            };
+            let notifications_btn =
+                button(notifications_label).on_press(Message::ToggleNotifications);
             row![
                 new_btn,
                 palette_btn,
@@ -109,7 +622,8 @@ impl MulticodeApp {
                 button(format_icon).on_press(Message::AutoFormat),
                 button(auto_icon).on_press(Message::AutoComplete),
                 lint_btn,
-                button("Meta").on_press(Message::ToggleMetaPanel)
+                button("Meta").on_press(Message::ToggleMetaPanel),
+                notifications_btn
             ]
             .spacing(5)
             .into()
@@ -143,14 +657,8 @@ impl MulticodeApp {
             .iter()
             .enumerate()
             .map(|(i, f)| {
-                let name = f
-                    .path
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("")
-                    .to_string();
                 let tab = row![
-                    text(name),
+                    text(self.tab_title(f)),
                     button(text("x")).on_press(Message::CloseFile(i))
                 ]
                 .spacing(5);
@@ -170,18 +678,65 @@ impl MulticodeApp {
         column![toggle, diff.view()].spacing(5).into()
     }
 
+    /// Диалог редактирования метки блока холста.
+    pub fn label_editor_component<'a>(&self, label: &'a str) -> Element<'a, Message> {
+        container(
+            column![
+                text_input("Метка", label)
+                    .on_input(Message::LabelEditChanged)
+                    .on_submit(Message::SaveLabel),
+                row![
+                    button("Сохранить").on_press(Message::SaveLabel),
+                    button("Отмена").on_press(Message::CloseLabelEditor)
+                ]
+                .spacing(5),
+            ]
+            .spacing(5),
+        )
+        .width(Length::Fixed(300.0))
+        .padding(10)
+        .into()
+    }
+
     pub fn meta_panel_component(&self) -> Element<Message> {
         if let Some(file) = self.current_file() {
+            let owners = self.code_owners.owners(&file.path);
+            let owners = text(format!(
+                "Владельцы: {}",
+                if owners.is_empty() {
+                    "-".into()
+                } else {
+                    owners.join(", ")
+                }
+            ));
+            let metrics = text(
+                self.cursor_metrics(file)
+                    .map_or_else(|| "Метрики: -".into(), |m| metrics_label(&m)),
+            );
             if let Some(meta) = &file.meta {
                 let tags = if meta.tags.is_empty() {
                     "-".into()
                 } else {
                     meta.tags.join(", ")
                 };
-                let links = if meta.links.is_empty() {
-                    "-".into()
+                let links: Element<Message> = if meta.links.is_empty() {
+                    text("Связи: -").into()
                 } else {
-                    meta.links.join(", ")
+                    let items: Vec<Element<Message>> = meta
+                        .links
+                        .iter()
+                        .map(|link| match self.issue_link_status(link) {
+                            Some(IssueLinkStatus::Url(url)) => button(text(link))
+                                .on_press(Message::OpenExternalLink(url))
+                                .into(),
+                            Some(IssueLinkStatus::UnknownTracker) => {
+                                text(format!("{link} (трекер не настроен)")).into()
+                            }
+                            Some(IssueLinkStatus::Invalid(e)) => text(e).into(),
+                            None => text(link).into(),
+                        })
+                        .collect();
+                    column![text("Связи:"), column(items).spacing(2)].into()
                 };
                 let comment = meta
                     .extras
@@ -190,10 +745,24 @@ impl MulticodeApp {
                     .and_then(|c| c.as_str())
                     .unwrap_or("")
                     .to_string();
+                let rename = row![
+                    text_input(&meta.id, &self.meta_new_id)
+                        .on_input(Message::MetaNewIdChanged)
+                        .on_submit(Message::RenameMetaId)
+                        .width(Length::Fill),
+                    button("Переименовать id").on_press_maybe(
+                        (!self.meta_new_id.trim().is_empty()).then_some(Message::RenameMetaId)
+                    ),
+                ]
+                .spacing(5);
                 column![
                     text("Мета"),
+                    text(format!("ID: {}", meta.id)),
+                    rename,
                     text(format!("Теги: {}", tags)),
-                    text(format!("Связи: {}", links)),
+                    owners,
+                    metrics,
+                    links,
                     text(format!(
                         "Комментарий: {}",
                         if comment.is_empty() {
@@ -210,6 +779,8 @@ impl MulticodeApp {
             } else {
                 column![
                     text("Мета отсутствует"),
+                    owners,
+                    metrics,
                     button("Создать").on_press(Message::ShowMetaDialog)
                 ]
                 .spacing(5)
@@ -221,25 +792,93 @@ impl MulticodeApp {
         }
     }
 
+    /// Список текстовых описаний блоков для режима доступности.
+    fn block_descriptions_component<'a>(&self, blocks: &'a [BlockInfo]) -> Element<'a, Message> {
+        let lang = self.settings.language;
+        let items = blocks.iter().enumerate().fold(column![], |col, (i, b)| {
+            let label = describe_block(b, i, blocks.len(), lang);
+            col.push(
+                button(text(label))
+                    .on_press(Message::CanvasEvent(CanvasMessage::BlockSelected(Some(i)))),
+            )
+        });
+        scrollable(items.spacing(2))
+            .width(Length::FillPortion(1))
+            .into()
+    }
+
     pub fn visual_editor_component(&self) -> Element<Message> {
-        let blocks: &[BlockInfo] = self
-            .current_file()
-            .map(|f| f.blocks.as_slice())
-            .unwrap_or(&[]);
-        let connections: &[Connection] = self
-            .current_file()
-            .map(|f| f.connections.as_slice())
-            .unwrap_or(&[]);
-        let canvas_widget = Canvas::new(VisualCanvas::new(
-            blocks,
-            connections,
-            self.settings.language,
-        ))
+        if self.canvas_window.is_some() {
+            return self.detached_canvas_placeholder();
+        }
+        self.visual_editor_for(self.active_tab)
+    }
+
+    /// Холст вкладки `tab`; панель метаданных показывается только для
+    /// активной вкладки.
+    pub fn visual_editor_for(&self, tab: Option<usize>) -> Element<'_, Message> {
+        let file = tab.and_then(|i| self.tabs.get(i));
+        let blocks: &[BlockInfo] = file.map(|f| f.blocks.as_slice()).unwrap_or(&[]);
+        let connections: &[Connection] = file.map(|f| f.connections.as_slice()).unwrap_or(&[]);
+        let a11y = self.settings.accessibility;
+        let palette = self.settings.editor.palette();
+        let colors = if a11y.high_contrast {
+            Some(BlockColors::high_contrast())
+        } else {
+            palette.as_ref().map(|p| p.blocks)
+        };
+        let filter = file
+            .filter(|_| tab == self.active_tab)
+            .and_then(|t| self.tab_canvas_filter(t));
+        let canvas_widget = Canvas::new(
+            VisualCanvas::new(blocks, connections, self.settings.language)
+                .with_lod(self.settings.canvas_lod)
+                .with_grid(self.settings.canvas_grid)
+                .with_waypoints(file.map(Self::tab_waypoints).unwrap_or_default())
+                .with_lanes(file.map(|t| self.tab_lanes(t)).unwrap_or_default())
+                .with_filter(filter)
+                .with_frozen(file.map(Self::tab_frozen).unwrap_or_default())
+                .with_layers(self.canvas_layers())
+                .with_lint(file.map(Self::tab_lint_messages).unwrap_or_default())
+                .with_colors(colors)
+                .with_background(palette.map(|p| (p.canvas_background, p.canvas_grid)))
+                .with_font_size(a11y.font_size(DEFAULT_FONT_SIZE))
+                .with_coverage(file.and_then(|t| self.tab_coverage(t)))
+                .with_profile(file.and_then(|t| self.tab_profile(t)))
+                .with_stopped(file.and_then(|t| self.tab_stopped_block(t)))
+                .with_watches(file.map(|t| self.tab_watches(t)).unwrap_or_default())
+                .with_breakpoints(file.map(|t| self.tab_breakpoints(t)).unwrap_or_default())
+                .with_tests(file.map(|t| self.tab_test_status(t)).unwrap_or_default()),
+        )
         .width(Length::Fill)
         .height(Length::Fill);
         let canvas: Element<CanvasMessage> = canvas_widget.into();
-        let canvas = canvas.map(Message::CanvasEvent);
-        if self.show_meta_panel {
+        let mut canvas = canvas.map(Message::CanvasEvent);
+        if a11y.enabled {
+            canvas = row![
+                container(canvas).width(Length::FillPortion(3)),
+                self.block_descriptions_component(blocks)
+            ]
+            .spacing(5)
+            .into();
+        }
+        if tab == self.active_tab {
+            canvas = column![
+                self.canvas_layers_component(),
+                self.canvas_filter_component(),
+                self.profile_legend_component(),
+                self.debug_bar_component(),
+                canvas
+            ]
+            .spacing(5)
+            .into();
+            if self.debug_stop.is_some() {
+                canvas = row![canvas, self.debug_variables_component()]
+                    .spacing(5)
+                    .into();
+            }
+        }
+        if self.show_meta_panel && tab == self.active_tab {
             row![
                 container(canvas).width(Length::FillPortion(3)),
                 self.meta_panel_component()
@@ -259,17 +898,47 @@ impl MulticodeApp {
             let (line, column) = file.editor.cursor_position();
             let path = file.path.to_string_lossy().to_string();
             let dirty = if file.dirty { "*" } else { "" };
-            let info = format!("{}:{} | blocks {}", line + 1, column + 1, file.blocks.len());
-            container(row![text(path).width(Length::Fill), text(info), text(dirty)].spacing(10))
-                .width(Length::Fill)
-                .padding(5)
-                .into()
+            let mut info = format!("{}:{} | blocks {}", line + 1, column + 1, file.blocks.len());
+            if file.simplified {
+                info.push_str(" | simplified view");
+            }
+            if self.settings.editor.input_mode == InputMode::Vim {
+                let status = format!(" | {} {}", self.vim.mode, self.vim.pending());
+                info.push_str(status.trim_end());
+            }
+            let focused = self
+                .canvas_focus
+                .and_then(|i| file.blocks.get(i).map(|b| (i, b)));
+            if let (true, Some((i, block))) = (self.settings.accessibility.enabled, focused) {
+                info.push_str(" | ");
+                info.push_str(&describe_block(
+                    block,
+                    i,
+                    file.blocks.len(),
+                    self.settings.language,
+                ));
+            }
+            let lang = self.language_picker(&file.path, file.lang);
+            container(
+                row![text(path).width(Length::Fill), lang]
+                    .push_maybe(self.offline_queue_indicator())
+                    .push(text(info))
+                    .push(text(dirty))
+                    .spacing(10),
+            )
+            .width(Length::Fill)
+            .padding(5)
+            .into()
         } else {
             let root = self.current_root();
-            container(row![text(root).width(Length::Fill)].spacing(10))
-                .width(Length::Fill)
-                .padding(5)
-                .into()
+            container(
+                row![text(root).width(Length::Fill)]
+                    .push_maybe(self.offline_queue_indicator())
+                    .spacing(10),
+            )
+            .width(Length::Fill)
+            .padding(5)
+            .into()
         }
     }
 
@@ -280,38 +949,61 @@ impl MulticodeApp {
         let output = scrollable(column(
             self.log
                 .iter()
-                .filter(|e| e.level >= self.min_log_level)
-                .map(|e| text(format_log(e, self.settings.language)).into())
+                .filter(|e| self.log_filter.matches(e, self.settings.language))
+                .map(|e| ansi_line(&format_log(e, self.settings.language)))
                 .collect::<Vec<Element<Message>>>(),
         ))
-        .height(Length::Fixed(150.0));
+        .height(Length::Fixed(OUTPUT_HEIGHT));
         let input = text_input("cmd", &self.terminal_cmd)
             .on_input(Message::TerminalCmdChanged)
             .on_submit(Message::RunTerminalCmd(self.terminal_cmd.clone()));
         let clear_btn = button("Очистить").on_press(Message::RunTerminalCmd(":clear".into()));
         let stop_btn = button("Stop").on_press(Message::RunTerminalCmd(":stop".into()));
         let help_btn = button("Справка").on_press(Message::ShowTerminalHelp);
+        let run_btns = row(self
+            .focused_run_configs()
+            .iter()
+            .enumerate()
+            .map(|(i, config)| {
+                button(text(format!("▶ {}", config.label())))
+                    .on_press(Message::RunBlock(i))
+                    .into()
+            })
+            .collect::<Vec<Element<Message>>>())
+        .spacing(5);
         let lang_pick = pick_list(
             &Language::ALL[..],
             Some(self.settings.language),
             Message::LanguageSelected,
         );
         let save_log_btn = button("Сохранить лог").on_press(Message::SaveLog);
+        let save_ndjson_btn = button("NDJSON").on_press(Message::SaveLogNdjson);
         let level_pick = pick_list(
             &LogLevel::ALL[..],
-            Some(self.min_log_level),
+            Some(self.log_filter.level),
             Message::LogLevelSelected,
         );
+        let modules: Vec<ModuleChoice> = std::iter::once(None)
+            .chain(self.log.modules().into_iter().map(Some))
+            .map(ModuleChoice)
+            .collect();
+        let module_pick = pick_list(
+            modules,
+            Some(ModuleChoice(self.log_filter.module.clone())),
+            |choice| Message::LogModuleSelected(choice.0),
+        );
+        let log_search = text_input("поиск в логе", &self.log_filter.query)
+            .on_input(Message::LogQueryChanged)
+            .width(Length::Fixed(160.0));
         column![
             output,
+            row![input, clear_btn, stop_btn, run_btns, help_btn, lang_pick].spacing(5),
             row![
-                input,
-                clear_btn,
-                stop_btn,
-                help_btn,
-                lang_pick,
+                log_search,
+                module_pick,
+                level_pick,
                 save_log_btn,
-                level_pick
+                save_ndjson_btn
             ]
             .spacing(5)
         ]
@@ -354,54 +1046,63 @@ impl MulticodeApp {
         } else {
             None
         };
+        let lang = self.settings.language;
+        let score = |name: &str, trigrams: Option<&fuzzy::TrigramSet>| {
+            if self.query.is_empty() {
+                0.0
+            } else if n == 3 {
+                match (query_trigrams.as_ref(), trigrams) {
+                    (Some(qt), Some(ct)) => fuzzy::trigram_similarity(qt, ct),
+                    _ => 0.0,
+                }
+            } else {
+                fuzzy::similarity(&self.query, name, n)
+            }
+        };
         let candidates = self.search_commands(&self.query);
-        let mut items: Vec<_> = candidates
+        let mut items: Vec<(String, String, String, String, f32)> = candidates
             .iter()
             .filter_map(|id| COMMANDS.iter().find(|c| c.id == *id))
             .map(|cmd| {
-                let name = command_name(cmd, self.settings.language);
-                let desc = command_description(cmd, self.settings.language);
-                let score = if self.query.is_empty() {
-                    0.0
-                } else if n == 3 {
-                    let cached = self.command_trigrams.get(cmd.id);
-                    if let (Some(qt), Some(ct)) = (query_trigrams.as_ref(), cached) {
-                        fuzzy::trigram_similarity(qt, ct)
-                    } else {
-                        0.0
-                    }
-                } else {
-                    fuzzy::similarity(&self.query, &name, n)
-                };
-                (cmd, name, desc, score)
+                let name = command_name(cmd, lang);
+                (
+                    cmd.id.to_string(),
+                    name.to_string(),
+                    command_description(cmd, lang).to_string(),
+                    command_hotkey(cmd, lang),
+                    score(name, self.command_trigrams.get(cmd.id)),
+                )
             })
             .collect();
+        // Пользовательских команд немного, поэтому они не индексируются.
+        items.extend(self.custom_commands().iter().map(|cmd| {
+            let name = custom_command_name(cmd, lang);
+            (
+                cmd.id.clone(),
+                name.to_string(),
+                custom_command_description(cmd, lang).to_string(),
+                custom_command_hotkey(cmd, lang),
+                score(name, Some(&fuzzy::trigram_set(name))),
+            )
+        }));
 
+        let uses = |id: &str| freq.get(id).copied().unwrap_or(0);
         if self.query.is_empty() {
-            items.sort_by(|a, b| {
-                let fa = freq.get(a.0.id).copied().unwrap_or(0);
-                let fb = freq.get(b.0.id).copied().unwrap_or(0);
-                fb.cmp(&fa)
-            });
+            items.sort_by_key(|item| std::cmp::Reverse(uses(&item.0)));
         } else {
-            items.retain(|(_, _, _, s)| *s > 0.0);
+            items.retain(|item| item.4 > 0.0);
             items.sort_by(|a, b| {
-                b.3.partial_cmp(&a.3)
+                b.4.partial_cmp(&a.4)
                     .unwrap_or(std::cmp::Ordering::Equal)
-                    .then_with(|| {
-                        let fa = freq.get(a.0.id).copied().unwrap_or(0);
-                        let fb = freq.get(b.0.id).copied().unwrap_or(0);
-                        fb.cmp(&fa)
-                    })
+                    .then_with(|| uses(&b.0).cmp(&uses(&a.0)))
             });
         }
 
         let items = items
             .into_iter()
-            .fold(column![], |col, (cmd, name, desc, _)| {
-                let hk = command_hotkey(cmd, self.settings.language);
-                let content = row![column![text(name), text(desc).size(14)], text(hk)].spacing(10);
-                col.push(button(content).on_press(Message::ExecuteCommand(cmd.id.to_string())))
+            .fold(column![], |col, (id, name, desc, hk, _)| {
+                let content = row![column![text(name), text(desc).size(self.settings.accessibility.font_size(14))], text(hk)].spacing(10);
+                col.push(button(content).on_press(Message::ExecuteCommand(id)))
             })
             .spacing(5);
 
@@ -411,6 +1112,63 @@ impl MulticodeApp {
             .into()
     }
 
+    pub fn recovery_modal<'a>(&self, content: Element<'a, Message>) -> Element<'a, Message> {
+        if self.recovery_prompt.is_empty() {
+            return content;
+        }
+        let files = self
+            .recovery_prompt
+            .iter()
+            .fold(column![], |col, s| {
+                col.push(text(format!(
+                    "{} ({})",
+                    s.path.display(),
+                    s.saved_at
+                        .with_timezone(&chrono::Local)
+                        .format("%d.%m %H:%M")
+                )))
+            })
+            .spacing(5);
+        let modal_content = container(
+            column![
+                text("Восстановить несохранённые изменения?"),
+                scrollable(files).height(Length::Shrink),
+                row![
+                    button("Восстановить").on_press(Message::RestoreRecovery),
+                    button("Отклонить").on_press(Message::DiscardRecovery)
+                ]
+                .spacing(10)
+            ]
+            .spacing(10),
+        )
+        .padding(10);
+        Modal::new(content, modal_content).into()
+    }
+
+    pub fn git_discard_modal<'a>(&self, content: Element<'a, Message>) -> Element<'a, Message> {
+        let Some(path) = &self.git_discard_confirm else {
+            return content;
+        };
+        let modal_content = container(
+            column![
+                text(format!(
+                    "Отменить изменения в {}? Их нельзя будет вернуть.",
+                    path.display()
+                )),
+                row![
+                    button("Отменить изменения").on_press(Message::ConfirmGitDiscard),
+                    button("Нет").on_press(Message::CancelGitDiscard)
+                ]
+                .spacing(10)
+            ]
+            .spacing(10),
+        )
+        .padding(10);
+        Modal::new(content, modal_content)
+            .on_blur(Message::CancelGitDiscard)
+            .into()
+    }
+
     pub fn goto_line_modal<'a>(&self, content: Element<'a, Message>) -> Element<'a, Message> {
         if !self.show_goto_line_modal {
             return content;
@@ -465,21 +1223,29 @@ impl MulticodeApp {
     }
 
     pub fn shortcuts_settings_component(&self) -> Element<Message> {
+        let lang = self.settings.language;
+        let custom = self.custom_commands();
         let items = COMMANDS
             .iter()
-            .map(|cmd| {
-                let label = if self.shortcut_capture.as_deref() == Some(cmd.id) {
+            .map(|cmd| (cmd.id, command_name(cmd, lang)))
+            .chain(
+                custom
+                    .iter()
+                    .map(|cmd| (cmd.id.as_str(), custom_command_name(cmd, lang))),
+            )
+            .map(|(id, name)| {
+                let label = if self.shortcut_capture.as_deref() == Some(id) {
                     String::from("...")
                 } else {
                     self.settings
                         .hotkeys
-                        .binding(HotkeyContext::Global, cmd.id)
+                        .binding(HotkeyContext::Global, id)
                         .map(|h| h.to_string())
                         .unwrap_or_else(|| String::from("-"))
                 };
                 row![
-                    text(command_name(cmd, self.settings.language)),
-                    button(text(label)).on_press(Message::StartCaptureShortcut(cmd.id.to_string()))
+                    text(name),
+                    button(text(label)).on_press(Message::StartCaptureShortcut(id.to_string()))
                 ]
                 .spacing(10)
                 .into()
@@ -491,10 +1257,14 @@ impl MulticodeApp {
 
 #[cfg(test)]
 mod tests {
-    use super::super::{CreateTarget, LogLevel, MulticodeApp, Screen, UserSettings, ViewMode};
+    use super::super::{CreateTarget, MulticodeApp, Screen, Tab, UserSettings, ViewMode};
+    use crate::app::navigation::NavigationHistory;
     use crate::app::command_palette::COMMANDS;
+    use crate::app::events::handler::detect_lang;
+    use crate::app::task_board::TaskGroup;
     use crate::components::file_manager::ContextMenu;
     use crate::sync::{ChangeTracker, SyncEngine, SyncSettings};
+    use iced::widget::text_editor::Content;
     use lru::LruCache;
     use multicode_core::parser::Lang;
     use std::cell::RefCell;
@@ -510,6 +1280,27 @@ mod tests {
     }
 
     #[cfg(test)]
+    fn tab(path: &str, content: &str) -> Tab {
+        Tab {
+            path: PathBuf::from(path),
+            content: content.into(),
+            editor: Content::with_text(content),
+            dirty: false,
+            blame: HashMap::new(),
+            diagnostics: Vec::new(),
+            blocks: Vec::new(),
+            simplified: false,
+            connections: Vec::new(),
+            meta: None,
+            undo_stack: VecDeque::new(),
+            redo_stack: VecDeque::new(),
+            analysis_version: 0,
+            folded: None,
+            highlights: None,
+            lang: detect_lang(&PathBuf::from(path)),
+        }
+    }
+
     fn build_app(screen: Screen) -> MulticodeApp {
         let (sender, _) = broadcast::channel(1);
         let view_mode = match screen {
@@ -533,18 +1324,23 @@ mod tests {
             create_target: CreateTarget::File,
             rename_file_name: String::new(),
             search_query: String::new(),
+            file_search: None,
+            file_search_generation: Default::default(),
+            file_tree_viewport: Default::default(),
             favorites: Vec::new(),
             query: String::new(),
             show_command_palette: false,
-            log: Vec::new(),
-            min_log_level: LogLevel::Info,
+            log: crate::app::logging::LogBuffer::default(),
+            log_filter: crate::app::logging::LogFilter::default(),
             project_search_results: Vec::new(),
             goto_line: None,
             goto_line_input: String::new(),
             show_goto_line_modal: false,
             show_terminal: false,
             terminal_cmd: String::new(),
-            terminal_child: None,
+            terminal: None,
+            terminal_job: None,
+            terminal_size: crate::terminal::pty::initial_size(),
             show_terminal_help: false,
             sender,
             settings: UserSettings::default(),
@@ -558,10 +1354,7 @@ mod tests {
             settings_warning: None,
             loading: false,
             diff_error: None,
-            show_meta_dialog: false,
-            meta_tags: String::new(),
-            meta_links: String::new(),
-            meta_comment: String::new(),
+            meta_form: None,
             autocomplete: None,
             show_meta_panel: false,
             tab_drag: None,
@@ -579,6 +1372,94 @@ mod tests {
             block_index: None,
             command_cache: RefCell::new(LruCache::new(NonZeroUsize::new(1).unwrap())),
             block_cache: RefCell::new(LruCache::new(NonZeroUsize::new(1).unwrap())),
+            navigation: NavigationHistory::default(),
+            show_bookmarks_panel: false,
+            block_clipboard: None,
+            label_edit: None,
+            canvas_focus: None,
+            theme_file_mtime: None,
+            show_tags_panel: false,
+            workspace_tags: Vec::new(),
+            tag_from: String::new(),
+            tag_into: String::new(),
+            meta_new_id: String::new(),
+            saved_searches: Vec::new(),
+            active_saved_search: None,
+            saved_search_name: String::new(),
+            show_block_history: false,
+            collapsed_lanes: HashSet::new(),
+            show_canvas_filter: false,
+            canvas_filter: String::new(),
+            canvas_filter_hide: false,
+            canvas_filter_current: None,
+            show_inheritance_panel: false,
+            extends_draft: None,
+            block_history: Vec::new(),
+            block_history_id: None,
+            show_cochange_panel: false,
+            cochange: None,
+            show_meta_suggestions: false,
+            meta_suggestions: Vec::new(),
+            rejected_suggestions: HashSet::new(),
+            show_structural_search: false,
+            structural_pattern: String::new(),
+            structural_lang: Lang::Rust,
+            structural_results: Vec::new(),
+            structural_template: String::new(),
+            structural_preview: Vec::new(),
+            structural_backup: None,
+            block_metrics: Default::default(),
+            show_coverage: false,
+            coverage: None,
+            profile_metric: None,
+            parse_costs: Default::default(),
+            debug_stop: None,
+            breakpoints: Default::default(),
+            show_test_panel: false,
+            test_run: None,
+            tests_running: false,
+            build_tasks: Default::default(),
+            build_problems: Vec::new(),
+            dead_code: None,
+            show_env_editor: false,
+            project_env: Default::default(),
+            offline_queue: Default::default(),
+            queue_replaying: false,
+            ai_client: None,
+            ai_answer: None,
+            tasks: Vec::new(),
+            task_group: TaskGroup::default(),
+            issue_links: Default::default(),
+            issue_link_cache: RefCell::new(HashMap::new()),
+            diagnostic_popup: None,
+            focused_diagnostic: None,
+            workspace_symbols: Default::default(),
+            recovery: None,
+            recovery_written: Default::default(),
+            recovery_prompt: Vec::new(),
+            git_status: Default::default(),
+            git_discard_confirm: None,
+            git_screen: Default::default(),
+            git_branches: Default::default(),
+            new_branch_name: String::new(),
+            code_owners: Default::default(),
+            show_notifications: false,
+            notifications: Default::default(),
+            show_macros_panel: false,
+            macro_recording: None,
+            recorded_macro: Vec::new(),
+            macro_name: String::new(),
+            vim: Default::default(),
+            modifiers: Default::default(),
+            carets: Default::default(),
+            column_anchor: None,
+            folds: HashMap::new(),
+            editor_scroll: 0.0,
+            canvas_window: None,
+            symbol_search: None,
+            pending_block_focus: None,
+            recent_blocks: Default::default(),
+            recent_blocks_query: None,
         }
     }
 
@@ -616,6 +1497,48 @@ mod tests {
         assert!(app.goto_line_input.is_empty());
     }
 
+    #[test]
+    fn autosave_and_restore_dirty_tab() {
+        use crate::app::events::Message;
+        use crate::app::recovery::RecoveryStore;
+
+        let dir = tempfile::tempdir().unwrap();
+        let recovery = dir.path().join("recovery");
+        let store = RecoveryStore::new(&recovery);
+        let path = dir.path().join("a.rs");
+        std::fs::write(&path, "fn a() {}\n").unwrap();
+
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+        app.recovery = Some(store.clone());
+        let _ = app.handle_message(Message::FileLoaded(Ok((
+            path.clone(),
+            "fn a() {}\n".into(),
+        ))));
+        app.tabs[0].content = "fn a() { 1 }\n".into();
+        app.tabs[0].dirty = true;
+        let _ = app.handle_message(Message::AutosaveTick);
+        assert_eq!(store.pending().len(), 1);
+
+        // Новый запуск после сбоя.
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+        app.recovery = Some(store.clone());
+        app.recovery_prompt = store.pending();
+        let _ = app.handle_message(Message::RestoreRecovery);
+        assert!(app.recovery_prompt.is_empty());
+        assert_eq!(app.tabs.len(), 1);
+        assert_eq!(app.tabs[0].content, "fn a() { 1 }\n");
+        assert!(app.tabs[0].dirty);
+
+        // После сохранения вкладки снимок удаляется.
+        let _ = app.handle_message(Message::FileSaved(Ok(())));
+        assert!(!app.tabs[0].dirty);
+        assert!(std::fs::read_dir(&recovery).unwrap().next().is_none());
+    }
+
     #[test]
     fn command_palette_orders_by_frequency() {
         use iced::widget::Space;
@@ -659,4 +1582,1320 @@ mod tests {
         assert_eq!(items[1].0, "toggle_terminal");
         assert_eq!(items[2].0, "save_file");
     }
+
+    #[test]
+    fn custom_commands_chain_builtin_commands() {
+        use crate::app::command_palette::{register_command, CommandAction, CustomCommand};
+        use crate::app::command_translations::custom_command_name;
+        use crate::app::events::Message;
+        use crate::app::Language;
+        use crate::search::hotkeys::HotkeyContext;
+        use iced::widget::Space;
+        use iced::Length;
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        app.settings.custom_commands = serde_json::from_str(
+            r#"[{
+                "id": "panels",
+                "name": "Panels",
+                "names": {"Russian": "Панели"},
+                "hotkey": "Ctrl+Alt+P",
+                "action": {"commands": ["toggle_notifications", "toggle_tags_panel"]}
+            }]"#,
+        )
+        .unwrap();
+        register_command(CustomCommand {
+            id: "plugin_terminal".into(),
+            name: "Plugin terminal".into(),
+            names: Default::default(),
+            description: String::new(),
+            descriptions: Default::default(),
+            hotkey: String::new(),
+            action: CommandAction::Commands(vec!["toggle_terminal".into(), "missing".into()]),
+        });
+        app.bind_custom_hotkeys();
+        assert!(app
+            .settings
+            .hotkeys
+            .binding(HotkeyContext::Global, "panels")
+            .is_some());
+
+        let _ = app.handle_message(Message::ExecuteCommand("panels".into()));
+        assert!(app.show_notifications);
+        assert!(app.show_tags_panel);
+        assert_eq!(app.command_counts.get("panels"), Some(&1));
+
+        // Шаги после неизвестной команды не выполняются.
+        let _ = app.handle_message(Message::ExecuteCommand("plugin_terminal".into()));
+        assert!(app.show_terminal);
+        assert_eq!(app.notifications[0].entry.args, ["missing"]);
+
+        app.settings.language = Language::Russian;
+        app.show_command_palette = true;
+        app.query = "Пан".into();
+        let _ = app.command_palette_modal(Space::with_height(Length::Shrink).into());
+        let names: Vec<String> = app
+            .custom_commands()
+            .iter()
+            .map(|c| custom_command_name(c, app.settings.language).to_string())
+            .collect();
+        assert!(names.contains(&"Панели".to_string()));
+        assert!(names.contains(&"Plugin terminal".to_string()));
+    }
+
+    #[test]
+    fn recorded_macro_replays_over_all_tabs() {
+        use crate::app::events::Message;
+        use iced::widget::text_editor::{Action, Edit, Motion};
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        app.tabs = vec![tab("a.rs", "a"), tab("b.rs", "b")];
+        app.active_tab = Some(0);
+
+        let _ = app.handle_message(Message::ExecuteCommand("toggle_macro_recording".into()));
+        assert!(app.show_macros_panel);
+        for action in [
+            Action::Move(Motion::DocumentEnd),
+            Action::Edit(Edit::Insert('!')),
+            Action::Edit(Edit::Insert('?')),
+        ] {
+            let _ = app.handle_message(Message::FileContentEdited(action));
+        }
+        let _ = app.handle_message(Message::ExecuteCommand("toggle_macro_recording".into()));
+        assert!(app.macro_recording.is_none());
+        assert_eq!(app.recorded_macro.len(), 2);
+
+        let _ = app.handle_message(Message::MacroNameChanged("bang".into()));
+        let _ = app.handle_message(Message::SaveMacro);
+        assert!(app.recorded_macro.is_empty());
+        assert_eq!(app.settings.macros[0].name, "bang");
+
+        let _ = app.handle_message(Message::RunMacro {
+            name: "bang".into(),
+            all_tabs: true,
+        });
+        assert_eq!(app.tabs[0].content.trim_end(), "a!?!?");
+        assert_eq!(app.tabs[1].content.trim_end(), "b!?");
+        assert_eq!(app.active_tab, Some(0));
+
+        let _ = app.handle_message(Message::DeleteMacro("bang".into()));
+        assert!(app.settings.macros.is_empty());
+    }
+
+    #[test]
+    fn input_modes_translate_editor_keys() {
+        use crate::app::events::Message;
+        use crate::editor::input_mode::{InputMode, VimMode};
+        use iced::keyboard::{self, Modifiers};
+        use iced::widget::text_editor::{Action, Edit};
+        use iced::Event;
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        app.tabs = vec![tab("a.rs", "abc")];
+        app.active_tab = Some(0);
+        let typed = |c| Message::FileContentEdited(Action::Edit(Edit::Insert(c)));
+
+        let _ = app.handle_message(Message::InputModeSelected(InputMode::Vim));
+        for c in ['2', 'x', 'i', 'z'] {
+            let _ = app.handle_message(typed(c));
+        }
+        assert_eq!(app.tabs[0].content.trim_end(), "zc");
+        assert_eq!(app.vim.mode, VimMode::Insert);
+        let _ = app.handle_message(Message::EditorEscape);
+        assert_eq!(app.vim.mode, VimMode::Normal);
+        let _ = app.handle_message(typed('u'));
+        assert_eq!(app.tabs[0].content.trim_end(), "c");
+
+        // Alt+> у Emacs редактор вводит как символ.
+        let _ = app.handle_message(Message::InputModeSelected(InputMode::Emacs));
+        let _ = app.handle_message(Message::IcedEvent(Event::Keyboard(
+            keyboard::Event::ModifiersChanged(Modifiers::ALT),
+        )));
+        let _ = app.handle_message(typed('>'));
+        let _ = app.handle_message(Message::IcedEvent(Event::Keyboard(
+            keyboard::Event::ModifiersChanged(Modifiers::empty()),
+        )));
+        let _ = app.handle_message(typed('!'));
+        assert_eq!(app.tabs[0].content.trim_end(), "c!");
+    }
+
+    #[test]
+    fn typing_with_several_carets_is_one_undo_step() {
+        use crate::app::events::Message;
+        use iced::widget::text_editor::{Action, Edit};
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        app.tabs = vec![tab("a.rs", "ab\ncd\nef")];
+        app.active_tab = Some(0);
+        for _ in 0..2 {
+            let _ = app.handle_message(Message::ExecuteCommand("add_cursor_below".into()));
+        }
+        assert_eq!(app.caret_highlights(), [(0, 0..1), (1, 0..1), (2, 0..1)]);
+
+        let _ = app.handle_message(Message::FileContentEdited(Action::Edit(Edit::Insert('x'))));
+        assert_eq!(app.tabs[0].content.trim_end(), "xab\nxcd\nxef");
+        assert_eq!(app.tabs[0].undo_stack.len(), 1);
+        assert_eq!(app.tabs[0].editor.cursor_position(), (2, 1));
+
+        let _ = app.handle_message(Message::Undo);
+        assert_eq!(app.tabs[0].content, "ab\ncd\nef");
+        assert!(app.caret_highlights().is_empty());
+    }
+
+    #[test]
+    fn folding_keeps_edits_in_the_full_text() {
+        use crate::app::events::Message;
+        use iced::widget::text_editor::{Action, Edit, Motion};
+        use multicode_core::BlockInfo;
+
+        let block = |id: &str, range| BlockInfo {
+            visual_id: id.into(),
+            node_id: None,
+            kind: "Function".into(),
+            translations: HashMap::new(),
+            range,
+            anchors: vec![],
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            ports: vec![],
+            ai: None,
+            tags: vec![],
+            links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
+        };
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        let text = "fn a() {\n    1\n}\nfn b() {\n    2\n}\n";
+        app.tabs = vec![tab("a.rs", text)];
+        app.tabs[0].blocks = vec![block("a", (0, 16)), block("b", (17, 33))];
+        app.active_tab = Some(0);
+        app.canvas_focus = Some(1);
+
+        let _ = app.handle_message(Message::ExecuteCommand("fold_all_except_current".into()));
+        let view = app.tabs[0].folded.as_ref().unwrap();
+        assert_eq!(view.editor.text(), "fn a() {\nfn b() {\n    2\n}\n");
+
+        for action in [
+            Action::Move(Motion::DocumentEnd),
+            Action::Edit(Edit::Insert('x')),
+        ] {
+            let _ = app.handle_message(Message::FileContentEdited(action));
+        }
+        assert_eq!(app.tabs[0].content, format!("{}x\n", text.trim_end()));
+        assert_eq!(app.tabs[0].undo_stack.len(), 1);
+        assert!(app.tabs[0].folded.is_some());
+
+        let _ = app.handle_message(Message::ToggleFold(0));
+        assert!(app.tabs[0].folded.is_none());
+        assert!(app.folds.is_empty());
+    }
+
+    #[test]
+    fn panes_edit_their_own_files() {
+        use crate::app::events::Message;
+        use crate::editor::panes::PaneAxis;
+        use iced::multi_window::Application;
+        use iced::widget::text_editor::{Action, Edit};
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        app.tabs = vec![tab("a.rs", "a"), tab("b.rs", "b")];
+        app.active_tab = Some(0);
+        let _ = app.update(Message::SplitPane(PaneAxis::Horizontal));
+        let _ = app.update(Message::SelectFile("b.rs".into()));
+        let layout = app.pane_layout().unwrap();
+        assert_eq!(layout.panes[1].active, Some("b.rs".into()));
+        assert_eq!(layout.panes[0].active, Some("a.rs".into()));
+
+        // Правка из первой панели переводит фокус в неё и меняет её файл.
+        let edit = Message::FileContentEdited(Action::Edit(Edit::Insert('x')));
+        let _ = app.update(Message::PaneMessage(0, Box::new(edit)));
+        assert_eq!(app.pane_layout().unwrap().focused, 0);
+        assert_eq!(app.tabs[0].content.trim_end(), "xa");
+        assert_eq!(app.tabs[1].content, "b");
+
+        let _ = app.update(Message::ExecuteCommand("focus_other_pane".into()));
+        assert_eq!(app.current_file().unwrap().path, PathBuf::from("b.rs"));
+        let _ = app.update(Message::UnsplitPane);
+        assert!(app.pane_layout().is_none());
+    }
+
+    #[test]
+    fn canvas_window_follows_its_lifecycle() {
+        use crate::app::events::Message;
+        use iced::multi_window::Application;
+        use iced::window;
+
+        let mut app = build_app(Screen::VisualEditor {
+            root: PathBuf::new(),
+        });
+        app.tabs = vec![tab("a.rs", "fn a() {}")];
+        app.active_tab = Some(0);
+        let _ = app.handle_message(Message::ExecuteCommand("toggle_canvas_window".into()));
+        let id = app.canvas_window().unwrap();
+        assert_eq!(app.title(id), "Multicode Desktop — схема a.rs");
+        assert_eq!(app.title(window::Id::MAIN), "Multicode Desktop");
+
+        // Закрытое пользователем окно забывается.
+        let _ = app.handle_message(Message::WindowClosed(id));
+        assert!(app.canvas_window().is_none());
+
+        // С главным окном закрывается и окно холста.
+        let _ = app.handle_message(Message::ToggleCanvasWindow);
+        assert!(app.canvas_window().is_some());
+        let _ = app.handle_message(Message::WindowClosed(window::Id::MAIN));
+        assert!(app.canvas_window().is_none());
+    }
+
+    #[test]
+    fn tab_language_override_reparses_file() {
+        use crate::app::events::Message;
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        app.tabs = vec![tab("script.rs.txt", "fn main() {}\n")];
+        app.active_tab = Some(0);
+        assert_eq!(app.tabs[0].lang, None);
+        assert!(app.tabs[0].blocks.is_empty());
+
+        let _ = app.handle_message(Message::SetTabLanguage(Some(Lang::Rust)));
+        let tab = &app.tabs[0];
+        assert_eq!(tab.lang, Some(Lang::Rust));
+        assert!(tab.highlights.is_some());
+        assert!(!tab.blocks.is_empty());
+        assert_eq!(
+            app.lang_for(&PathBuf::from("script.rs.txt")),
+            Some(Lang::Rust)
+        );
+
+        let _ = app.handle_message(Message::SetTabLanguage(None));
+        assert_eq!(app.tabs[0].lang, None);
+        assert!(app.tabs[0].highlights.is_none());
+        assert!(app.tabs[0].blocks.is_empty());
+        assert!(app.settings.file_languages.is_empty());
+    }
+
+    #[test]
+    fn symbol_search_opens_file_and_focuses_block() {
+        use crate::app::events::Message;
+        use crate::app::symbol_search::rank_symbols;
+        use crate::app::Language;
+        use multicode_core::blocks::symbols::{file_symbols, CodeSymbolKind};
+        use multicode_core::parse_blocks;
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        app.settings.language = Language::Russian;
+        let mut a = tab("a.rs", "struct Point;\n");
+        let mut b = tab("b.rs", "fn main() {}\n\nfn distance() {}\n");
+        a.blocks = parse_blocks(a.content.clone(), "rust".into()).unwrap();
+        b.blocks = parse_blocks(b.content.clone(), "rust".into()).unwrap();
+        app.tabs = vec![b];
+        app.active_tab = Some(0);
+
+        let _ = app.handle_message(Message::ExecuteCommand("goto_symbol".into()));
+        // С диска пришёл и устаревший символ открытой вкладки: её символы
+        // берутся из текущего текста.
+        let mut from_disk = file_symbols(&a.path, &a.content, &a.blocks);
+        from_disk.extend(file_symbols(
+            &PathBuf::from("b.rs"),
+            "fn old() {}\n",
+            &parse_blocks("fn old() {}\n".into(), "rust".into()).unwrap(),
+        ));
+        let _ = app.handle_message(Message::SymbolsLoaded(from_disk));
+        let search = app.symbol_search().unwrap();
+        let names: Vec<(&str, CodeSymbolKind)> = search
+            .symbols
+            .iter()
+            .map(|s| (s.name.as_str(), s.kind))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Point", CodeSymbolKind::Class),
+                ("main", CodeSymbolKind::Function),
+                ("distance", CodeSymbolKind::Function),
+            ]
+        );
+
+        // Переведённое имя ищется так же, как имя из исходника.
+        app.symbol_search.as_mut().unwrap().symbols[0]
+            .translations
+            .insert("ru".into(), "Точка".into());
+        let _ = app.handle_message(Message::SymbolQueryChanged("точк".into()));
+        let search = app.symbol_search().unwrap();
+        assert_eq!(rank_symbols(&search.query, &search.symbols, "ru"), vec![0]);
+        assert_eq!(rank_symbols("dist", &search.symbols, "ru"), vec![2]);
+
+        // Файл символа ещё не открыт: блок выделяется после загрузки.
+        let id = search.symbols[0].visual_id.clone();
+        let _ = app.handle_message(Message::OpenSymbol(0));
+        assert!(app.symbol_search().is_none());
+        assert_eq!(app.canvas_focus, None);
+        app.tabs.push(a);
+        app.active_tab = Some(1);
+        app.focus_pending_block();
+        assert!(app.pending_block_focus.is_none());
+        let focused = app
+            .canvas_focus
+            .map(|i| app.tabs[1].blocks[i].visual_id.clone());
+        assert_eq!(focused, Some(id));
+    }
+
+    #[test]
+    fn edited_blocks_are_listed_most_recent_first() {
+        use crate::app::events::Message;
+        use crate::app::recent_blocks::filter_recent;
+        use iced::widget::text_editor::{Action, Edit};
+        use multicode_core::parse_blocks;
+        use multicode_core::recent_blocks::RecentBlocks;
+
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let mut app = build_app(Screen::TextEditor { root: root.clone() });
+        let open = |name: &str, content: &str| {
+            let mut t = tab(root.join(name).to_str().unwrap(), content);
+            t.blocks = parse_blocks(content.into(), "rust".into()).unwrap();
+            t
+        };
+        app.tabs = vec![
+            open("a.rs", "fn load() {}\n"),
+            open("b.rs", "struct Point;\n"),
+        ];
+        let edit = Message::FileContentEdited(Action::Edit(Edit::Insert(' ')));
+        for i in [0, 1] {
+            app.active_tab = Some(i);
+            let _ = app.handle_message(edit.clone());
+        }
+        let labels: Vec<&str> = app
+            .recent_blocks()
+            .entries
+            .iter()
+            .map(|e| e.label.as_str())
+            .collect();
+        assert_eq!(labels, ["Point", "load"]);
+        assert_eq!(app.recent_blocks().entries[1].file, PathBuf::from("a.rs"));
+        assert_eq!(&RecentBlocks::load(&root).unwrap(), app.recent_blocks());
+
+        let _ = app.handle_message(Message::ExecuteCommand("recent_blocks".into()));
+        let _ = app.handle_message(Message::RecentBlocksQueryChanged("LO".into()));
+        let query = app.recent_blocks_query.clone().unwrap();
+        let matches = filter_recent(app.recent_blocks(), &query);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0, 1);
+
+        // Файл блока закрыт: переход открывает его и выделит блок после
+        // загрузки.
+        app.tabs.clear();
+        app.active_tab = None;
+        let id = app.recent_blocks().entries[1].visual_id.clone();
+        let _ = app.handle_message(Message::OpenRecentBlock(1));
+        assert!(app.recent_blocks_query.is_none());
+        assert_eq!(app.pending_block_focus, Some((root.join("a.rs"), id)));
+    }
+
+    #[test]
+    fn inheritance_panel_edits_extends_and_refuses_cycles() {
+        use crate::app::events::Message;
+        use multicode_core::meta::inheritance::inheritance_chain;
+        use multicode_core::meta::read_all;
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::from("/tmp"),
+        });
+        let content = "fn a() {}\n// @VISUAL_META {\"id\":\"leaf\",\"x\":0.0,\"y\":0.0}\n// @VISUAL_META {\"id\":\"base\",\"x\":0.0,\"y\":0.0,\"tags\":[\"net\"]}\n";
+        let mut t = tab("/tmp/a.rs", content);
+        t.meta = read_all(content).into_iter().next();
+        app.tabs = vec![t];
+        app.active_tab = Some(0);
+
+        let _ = app.handle_message(Message::ExecuteCommand("toggle_inheritance_panel".into()));
+        assert!(app.show_inheritance_panel);
+        let _ = app.handle_message(Message::ExtendsDraftChanged("base".into()));
+        let _ = app.handle_message(Message::ApplyExtends);
+        let tab = &app.tabs[0];
+        assert!(tab.dirty);
+        assert_eq!(tab.meta.as_ref().unwrap().tags, vec!["net"]);
+        let ids: Vec<String> = inheritance_chain(&tab.content, "leaf")
+            .entries
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, ["leaf", "base"]);
+        assert!(app.extends_draft.is_none());
+
+        // База, ссылающаяся на запись, замкнула бы кольцо.
+        let before = app.tabs[0].content.clone();
+        app.tabs[0].meta = read_all(&before).into_iter().nth(1);
+        let _ = app.handle_message(Message::ExtendsDraftChanged("leaf".into()));
+        let _ = app.handle_message(Message::ApplyExtends);
+        assert_eq!(app.tabs[0].content, before);
+    }
+
+    #[test]
+    fn auto_layout_keeps_pinned_blocks() {
+        use crate::app::events::Message;
+        use multicode_core::meta::{pin, read_all};
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::from("/tmp"),
+        });
+        let content = "fn a() {}\nfn b() {}\n";
+        let mut t = tab("/tmp/a.rs", content);
+        t.blocks = multicode_core::parse_blocks(content.into(), "rust".into())
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.kind == "Function/Define")
+            .collect();
+        t.blocks[0].x = 500.0;
+        t.blocks[1].x = 500.0;
+        let pinned = t.blocks[0].visual_id.clone();
+        app.tabs = vec![t];
+        app.active_tab = Some(0);
+        app.canvas_focus = Some(0);
+
+        let _ = app.handle_message(Message::ExecuteCommand("toggle_block_pin".into()));
+        let metas = read_all(&app.tabs[0].content);
+        assert!(metas.iter().any(|m| m.id == pinned && pin::is_pinned(m)));
+        assert_eq!(MulticodeApp::tab_frozen(&app.tabs[0]), vec![true, false]);
+
+        let _ = app.handle_message(Message::ExecuteCommand("auto_layout_blocks".into()));
+        let tab = &app.tabs[0];
+        assert_eq!((tab.blocks[0].x, tab.blocks[0].y), (500.0, 0.0));
+        assert_eq!((tab.blocks[1].x, tab.blocks[1].y), (0.0, 0.0));
+        // Перенос блока не снимает закрепление.
+        let metas = read_all(&tab.content);
+        assert!(metas.iter().any(|m| m.id == pinned && pin::is_pinned(m)));
+    }
+
+    #[test]
+    fn ai_description_streams_into_panel_and_saves() {
+        use crate::app::ai_assist::AiEvent;
+        use crate::app::events::Message;
+        use crate::app::LogMessage;
+        use multicode_core::ai::{AiClient, AiProvider, AiRequest, Limits, Redactor};
+        use multicode_core::meta::read_all;
+        use multicode_core::offline_queue::OpError;
+
+        struct Silent;
+
+        impl AiProvider for Silent {
+            fn model(&self) -> &str {
+                "silent"
+            }
+
+            fn complete(
+                &self,
+                _request: &AiRequest,
+                _on_token: &mut dyn FnMut(&str),
+            ) -> Result<String, OpError> {
+                Ok(String::new())
+            }
+        }
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::from("/tmp"),
+        });
+        let content = "fn a() {}\n";
+        let mut t = tab("/tmp/a.rs", content);
+        t.blocks = multicode_core::parse_blocks(content.into(), "rust".into())
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.kind == "Function/Define")
+            .collect();
+        let id = t.blocks[0].visual_id.clone();
+        app.tabs = vec![t];
+        app.active_tab = Some(0);
+        app.canvas_focus = Some(0);
+
+        // Без секции [ai] команда только сообщает об этом.
+        let _ = app.handle_message(Message::ExecuteCommand("describe_block_ai".into()));
+        assert!(app.ai_answer.is_none());
+        assert_eq!(
+            app.log.last().unwrap().message_key,
+            LogMessage::AiNotConfigured
+        );
+
+        app.ai_client = Some(std::sync::Arc::new(AiClient::new(
+            Box::new(Silent),
+            Redactor::default(),
+            Limits {
+                max_concurrent: 1,
+                max_requests: None,
+                window: std::time::Duration::from_secs(60),
+                cache_size: 0,
+            },
+        )));
+        let _ = app.handle_message(Message::DescribeBlockWithAi);
+        assert_eq!(app.ai_answer.as_ref().unwrap().block, id);
+        for token in ["Does ", "nothing."] {
+            let _ = app.handle_message(Message::AiStream(AiEvent::Token(token.into())));
+        }
+        let answer = app.ai_answer.as_ref().unwrap();
+        assert_eq!(answer.text, "Does nothing.");
+        assert!(!answer.done);
+        let _ = app.handle_message(Message::AiStream(AiEvent::Finished(Ok(false))));
+        assert!(app.ai_answer.as_ref().unwrap().done);
+
+        let _ = app.handle_message(Message::ApplyAiDescription);
+        assert!(app.ai_answer.is_none());
+        let meta = read_all(&app.tabs[0].content)
+            .into_iter()
+            .find(|m| m.id == id)
+            .unwrap();
+        assert_eq!(
+            meta.ai.and_then(|ai| ai.description).as_deref(),
+            Some("Does nothing.")
+        );
+
+        // Ошибка модели закрывает панель и попадает в журнал.
+        let _ = app.handle_message(Message::DescribeBlockWithAi);
+        let _ = app.handle_message(Message::AiStream(AiEvent::Finished(Err("quota".into()))));
+        assert!(app.ai_answer.is_none());
+        assert_eq!(app.log.last().unwrap().message_key, LogMessage::AiError);
+    }
+
+    #[test]
+    fn ask_codebase_fills_project_search_results() {
+        use crate::app::events::Message;
+        use crate::app::LogMessage;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+
+        // Без секции [embeddings] поиск только сообщает об этом.
+        let _ = app.handle_message(Message::AskCodebase("where is json parsed".into()));
+        assert_eq!(
+            app.log.last().unwrap().message_key,
+            LogMessage::EmbeddingsNotConfigured
+        );
+
+        let file = dir.path().join("main.rs");
+        let _ = app.handle_message(Message::AskCodebaseFinished(Ok(vec![(
+            file.clone(),
+            4,
+            "fn read_config() {".into(),
+        )])));
+        assert_eq!(
+            app.project_search_results,
+            vec![(file, 4, "fn read_config() {".to_string())]
+        );
+
+        let _ = app.handle_message(Message::AskCodebaseFinished(Err("offline".into())));
+        assert_eq!(
+            app.log.last().unwrap().message_key,
+            LogMessage::SemanticSearchError
+        );
+    }
+
+    #[test]
+    fn rejected_meta_suggestions_stay_hidden() {
+        use crate::app::events::Message;
+        use crate::app::LogMessage;
+        use multicode_core::meta::suggest;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("net.py"),
+            "def send_request(url):\n    return url\n",
+        )
+        .unwrap();
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+        let found = suggest::suggest(dir.path());
+        assert_eq!(found.len(), 2);
+
+        let _ = app.handle_message(Message::MetaSuggestionsFound(found.clone()));
+        let _ = app.handle_message(Message::RejectMetaSuggestion(0));
+        assert_eq!(app.meta_suggestions, found[1..].to_vec());
+        let _ = app.handle_message(Message::MetaSuggestionsFound(found.clone()));
+        assert_eq!(app.meta_suggestions, found[1..].to_vec());
+
+        let _ = app.handle_message(Message::MetaSuggestionAccepted(Err("denied".into())));
+        assert_eq!(
+            app.log.last().unwrap().message_key,
+            LogMessage::MetaSuggestionError
+        );
+    }
+
+    #[test]
+    fn structural_search_shows_matches_and_errors() {
+        use crate::app::events::Message;
+        use crate::app::LogMessage;
+        use multicode_core::search::structural::structural;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.py"), "print(a)\nprint(a, b)\n").unwrap();
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+        let _ = app.handle_message(Message::ToggleStructuralSearch);
+        assert!(app.show_structural_search);
+        let _ = app.handle_message(Message::StructuralLangChanged(Lang::Python));
+        let _ = app.handle_message(Message::StructuralPatternChanged("print($A)".into()));
+        assert_eq!(app.structural_pattern, "print($A)");
+
+        let found = structural(dir.path(), "print($A)", "python").unwrap();
+        let _ = app.handle_message(Message::StructuralSearchFinished(Ok(found)));
+        assert_eq!(app.structural_results.len(), 1);
+        assert_eq!(app.structural_results[0].bindings["A"], "a");
+
+        let _ = app.handle_message(Message::StructuralSearchFinished(Err("bad".into())));
+        assert_eq!(
+            app.log.last().unwrap().message_key,
+            LogMessage::StructuralError
+        );
+    }
+
+    #[test]
+    fn structural_replace_keeps_backup_for_undo() {
+        use crate::app::events::Message;
+        use crate::app::LogMessage;
+        use multicode_core::search::replace;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.py"), "print(a)\n").unwrap();
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+        let preview = replace::preview(dir.path(), "print($A)", "log($A)", "python").unwrap();
+        let _ = app.handle_message(Message::StructuralReplacePreviewed(Ok(preview)));
+        assert_eq!(app.structural_preview.len(), 1);
+        let _ = app.handle_message(Message::StructuralTemplateChanged("warn($A)".into()));
+        assert!(app.structural_preview.is_empty());
+
+        let _ = app.handle_message(Message::StructuralReplaceApplied(Ok(("b1".into(), 1))));
+        assert_eq!(app.structural_backup.as_deref(), Some("b1"));
+        let entry = app.log.last().unwrap();
+        assert_eq!(entry.message_key, LogMessage::StructuralReplaced);
+        assert_eq!(entry.args, vec!["1".to_string()]);
+
+        let _ = app.handle_message(Message::UndoStructuralReplace);
+        assert_eq!(app.structural_backup, None);
+    }
+
+    #[test]
+    fn regenerate_summaries_reports_result() {
+        use crate::app::events::Message;
+        use crate::app::LogMessage;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+
+        let _ = app.handle_message(Message::SummariesRegenerated(Ok(2)));
+        let entry = app.log.last().unwrap();
+        assert_eq!(entry.message_key, LogMessage::SummariesRegenerated);
+        assert_eq!(entry.args, vec!["2".to_string()]);
+
+        let _ = app.handle_message(Message::SummariesRegenerated(Err("denied".into())));
+        assert_eq!(
+            app.log.last().unwrap().message_key,
+            LogMessage::SummaryError
+        );
+    }
+
+    #[test]
+    fn canvas_layers_are_kept_per_project() {
+        use crate::app::events::Message;
+        use crate::visual::layers::{CanvasLayers, Layer};
+
+        let mut app = build_app(Screen::VisualEditor {
+            root: PathBuf::from("/tmp/a"),
+        });
+        let _ = app.handle_message(Message::ToggleCanvasLayer(Layer::Ai));
+        assert!(!app.canvas_layers().shows(Layer::Ai));
+        assert!(app.canvas_layers().shows(Layer::Links));
+
+        app.screen = Screen::VisualEditor {
+            root: PathBuf::from("/tmp/b"),
+        };
+        assert_eq!(app.canvas_layers(), CanvasLayers::default());
+
+        // Проект, у которого снова видны все слои, не хранится в настройках.
+        app.screen = Screen::VisualEditor {
+            root: PathBuf::from("/tmp/a"),
+        };
+        let _ = app.handle_message(Message::ToggleCanvasLayer(Layer::Ai));
+        assert!(app.settings.canvas_layers.is_empty());
+    }
+
+    #[test]
+    fn lint_badge_focuses_panel_entry() {
+        use crate::app::events::Message;
+        use crate::editor::meta_integration::lint_viz;
+        use crate::visual::canvas::CanvasMessage;
+
+        let mut app = build_app(Screen::VisualEditor {
+            root: PathBuf::from("/tmp"),
+        });
+        let content = "fn a() {}\n// @viz op=foo node=1 id=x\n// @viz node=2 id=y\nfn b() {}\n";
+        let mut t = tab("/tmp/a.rs", content);
+        t.blocks = multicode_core::parse_blocks(content.into(), "rust".into())
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.kind == "Function/Define")
+            .collect();
+        t.diagnostics = lint_viz(content, &t.blocks);
+        app.tabs = vec![t];
+        app.active_tab = Some(0);
+
+        // Оба замечания относятся к функции `b`, перед которой стоят.
+        let messages = MulticodeApp::tab_lint_messages(&app.tabs[0]);
+        assert!(messages[0].is_empty());
+        assert_eq!(messages[1].len(), 2);
+        assert!(messages[1][0].contains("неизвестная операция"));
+
+        let _ = app.handle_message(Message::CanvasEvent(CanvasMessage::LintClicked(1)));
+        assert_eq!(app.focused_diagnostic, Some(0));
+    }
+
+    #[test]
+    fn debugger_stop_highlights_block() {
+        use crate::app::events::Message;
+        use multicode_core::debugger::{StoppedLocation, Variable};
+
+        let mut app = build_app(Screen::VisualEditor {
+            root: PathBuf::from("/tmp"),
+        });
+        let content = concat!(
+            "fn a() {}\nfn b() {\n    let x = 1;\n}\n",
+            "// @VISUAL_META {\"id\":\"b\",\"x\":0,\"y\":0,\"extras\":{\"watch\":[\"x\",\"y\"]}}\n",
+        );
+        let mut t = tab("/tmp/d.rs", content);
+        t.blocks = multicode_core::parse_blocks(content.into(), "rust".into())
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.kind == "Function/Define")
+            .collect();
+        t.blocks[1].visual_id = "b".into();
+
+        // Файл остановки ещё не открыт: редактор загрузит его сам.
+        let _ = app.handle_message(Message::DebugStopped(StoppedLocation {
+            file: PathBuf::from("d.rs"),
+            line: 3,
+            variables: vec![Variable {
+                name: "x".into(),
+                value: "1".into(),
+            }],
+        }));
+        assert_eq!(app.selected_path, Some(PathBuf::from("/tmp/d.rs")));
+        app.tabs = vec![t];
+        assert_eq!(app.tab_stopped_block(&app.tabs[0]), Some(1));
+        assert_eq!(
+            app.tab_watches(&app.tabs[0]),
+            vec![vec![], vec!["x = 1".to_string(), "y = ?".to_string()]]
+        );
+
+        let _ = app.handle_message(Message::DebugStep);
+        assert!(app.debug_stop.is_none());
+        assert_eq!(app.tab_stopped_block(&app.tabs[0]), None);
+        assert!(app.tab_watches(&app.tabs[0]).is_empty());
+    }
+
+    #[test]
+    fn breakpoints_are_kept_in_the_project() {
+        use crate::app::events::Message;
+        use multicode_core::breakpoints::Breakpoints;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = build_app(Screen::VisualEditor {
+            root: dir.path().to_path_buf(),
+        });
+        let content = "fn a() {\n    let x = 1;\n}\n";
+        let mut t = tab(dir.path().join("a.rs").to_str().unwrap(), content);
+        t.blocks = multicode_core::parse_blocks(content.into(), "rust".into())
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.kind == "Function/Define")
+            .collect();
+        app.tabs = vec![t];
+        app.active_tab = Some(0);
+
+        let _ = app.handle_message(Message::ToggleBreakpoint);
+        assert_eq!(app.tab_breakpoints(&app.tabs[0]), vec![true]);
+        let saved = Breakpoints::load(dir.path()).unwrap();
+        assert_eq!(saved.entries[0].file, PathBuf::from("a.rs"));
+        assert_eq!(saved.entries[0].offset, 0);
+
+        let _ = app.handle_message(Message::ToggleBreakpoint);
+        assert_eq!(app.tab_breakpoints(&app.tabs[0]), vec![false]);
+        assert!(Breakpoints::load(dir.path()).unwrap().entries.is_empty());
+    }
+
+    #[test]
+    fn test_rerun_updates_block_status() {
+        use crate::app::events::Message;
+        use multicode_core::testrun::{Framework, TestOutcome, TestRun};
+
+        let mut app = build_app(Screen::VisualEditor {
+            root: PathBuf::from("/tmp"),
+        });
+        let code = "fn a() {}\nfn b() {}\n";
+        let mut blocks: Vec<_> = multicode_core::parse_blocks(code.into(), "rust".into())
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.kind == "Function/Define")
+            .collect();
+        blocks[0].visual_id = "a".into();
+        blocks[1].visual_id = "b".into();
+        let content = format!(
+            "{code}// @VISUAL_META {{\"id\":\"a\",\"x\":0,\"y\":0,\"tests\":[\"a_works\"]}}\n"
+        );
+        let mut t = tab("/tmp/t.rs", &content);
+        t.blocks = blocks;
+        app.tabs = vec![t];
+        app.active_tab = Some(0);
+
+        let run = |filter: Option<&str>, outcomes: &[(&str, bool)]| TestRun {
+            framework: Framework::Cargo,
+            filter: filter.map(str::to_string),
+            outcomes: outcomes
+                .iter()
+                .map(|&(name, passed)| TestOutcome {
+                    name: name.into(),
+                    passed,
+                })
+                .collect(),
+            success: true,
+        };
+        let _ = app.handle_message(Message::TestsFinished(Ok(run(
+            None,
+            &[("tests::a_works", false), ("tests::other", true)],
+        ))));
+        assert_eq!(app.tab_test_status(&app.tabs[0]), vec![Some(false), None]);
+
+        // Повторный запуск одного теста не теряет итоги остальных.
+        let _ = app.handle_message(Message::TestsFinished(Ok(run(
+            Some("a_works"),
+            &[("tests::a_works", true)],
+        ))));
+        assert_eq!(app.tab_test_status(&app.tabs[0]), vec![Some(true), None]);
+        assert_eq!(app.test_run.as_ref().unwrap().outcomes.len(), 2);
+        assert!(!app.tests_running);
+    }
+
+    #[test]
+    fn build_task_problems_become_diagnostics() {
+        use crate::app::events::Message;
+        use multicode_core::build_tasks::{Problem, TaskRun, BUILD_TASKS_FILE};
+        use multicode_core::lint::Severity;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".multicode")).unwrap();
+        std::fs::write(
+            dir.path().join(BUILD_TASKS_FILE),
+            "[[task]]\nname = \"build\"\ncommand = \"cargo build\"\nmatchers = [\"rustc\"]\n",
+        )
+        .unwrap();
+        let mut app = build_app(Screen::VisualEditor {
+            root: dir.path().to_path_buf(),
+        });
+        app.load_build_tasks();
+        let cmd = app
+            .custom_commands()
+            .into_iter()
+            .find(|c| c.id == "task:build")
+            .unwrap();
+        assert_eq!(
+            cmd.action,
+            crate::app::command_palette::CommandAction::Task("build".into())
+        );
+
+        let path = dir.path().join("src/main.rs");
+        app.tabs = vec![tab(path.to_str().unwrap(), "fn main() {\n    x + 1;\n}\n")];
+        app.active_tab = Some(0);
+        let problem = |line| Problem {
+            file: PathBuf::from("src/main.rs"),
+            line,
+            column: 5,
+            message: "cannot find value `x`".into(),
+            severity: Severity::Error,
+        };
+        let _ = app.handle_message(Message::BuildTaskFinished(Ok(TaskRun {
+            task: "build".into(),
+            problems: vec![problem(2)],
+            success: false,
+        })));
+        let d = &app.tabs[0].diagnostics;
+        assert_eq!(d.len(), 1);
+        assert_eq!((d[0].line, d[0].range.clone()), (1, 4..5));
+        assert_eq!(d[0].message, "error: cannot find value `x`");
+
+        // Следующий запуск заменяет ошибки предыдущего.
+        let _ = app.handle_message(Message::BuildTaskFinished(Ok(TaskRun {
+            task: "build".into(),
+            problems: Vec::new(),
+            success: true,
+        })));
+        assert!(app.tabs[0].diagnostics.is_empty());
+    }
+
+    #[test]
+    fn project_env_editor_saves_vars_for_terminal() {
+        use crate::app::events::Message;
+        use crate::terminal::pty::shell_command;
+        use multicode_core::config::TerminalConfig;
+        use multicode_core::project_env::ENV_FILE;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = build_app(Screen::VisualEditor {
+            root: dir.path().to_path_buf(),
+        });
+        let _ = app.handle_message(Message::ToggleEnvEditor);
+        assert!(app.show_env_editor);
+        assert!(app.project_env.vars.is_empty());
+        let _ = app.handle_message(Message::AddEnvVar);
+        let _ = app.handle_message(Message::EnvKeyChanged(0, "RUST_LOG".into()));
+        let _ = app.handle_message(Message::EnvValueChanged(0, "debug".into()));
+        // Строка без имени не сохраняется.
+        let _ = app.handle_message(Message::AddEnvVar);
+        let _ = app.handle_message(Message::SaveProjectEnv);
+
+        let saved = std::fs::read_to_string(dir.path().join(ENV_FILE)).unwrap();
+        assert_eq!(saved, "RUST_LOG=debug\n");
+        assert_eq!(app.project_env.vars.len(), 1);
+        let cmd = shell_command(dir.path(), &TerminalConfig::default());
+        assert_eq!(
+            cmd.get_env("RUST_LOG").and_then(|v| v.to_str()),
+            Some("debug")
+        );
+
+        let _ = app.handle_message(Message::RemoveEnvVar(0));
+        let _ = app.handle_message(Message::SaveProjectEnv);
+        assert!(app.project_env.vars.is_empty());
+    }
+
+    #[test]
+    fn offline_queue_status_shows_in_status_bar() {
+        use crate::app::events::Message;
+        use crate::app::LogMessage;
+        use multicode_core::offline_queue::{OfflineQueue, QueueStatus, ReplayReport};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = build_app(Screen::VisualEditor {
+            root: dir.path().to_path_buf(),
+        });
+        let _ = app.handle_message(Message::FilesLoaded(Vec::new()));
+        assert_eq!(app.offline_queue_label(), None);
+
+        let mut queue = OfflineQueue::default();
+        queue.push("team_sync", serde_json::json!({}), chrono::Utc::now());
+        queue.save(dir.path()).unwrap();
+        let _ = app.handle_message(Message::FilesLoaded(Vec::new()));
+        assert_eq!(app.offline_queue.pending, 1);
+        let label = app.offline_queue_label().unwrap();
+        assert!(label.starts_with("offline queue 1 | retry in"), "{label}");
+
+        let _ = app.handle_message(Message::ReplayOfflineQueue(true));
+        assert!(app.queue_replaying);
+        assert_eq!(
+            app.offline_queue_label().as_deref(),
+            Some("offline queue 1 | retrying")
+        );
+        let report = ReplayReport {
+            failed: vec![("team_sync".into(), "rejected".into())],
+            ..Default::default()
+        };
+        let _ = app.handle_message(Message::OfflineQueueReplayed(Ok((
+            report,
+            QueueStatus::default(),
+        ))));
+        assert!(!app.queue_replaying);
+        assert_eq!(app.offline_queue_label(), None);
+        assert_eq!(
+            app.log.last().unwrap().message_key,
+            LogMessage::OfflineOpFailed
+        );
+    }
+
+    #[test]
+    fn profile_cycles_through_metrics() {
+        use crate::app::events::Message;
+        use crate::visual::profile::{heat, ProfileMetric};
+        use multicode_core::coverage::CoverageReport;
+
+        let mut app = build_app(Screen::VisualEditor {
+            root: PathBuf::from("/tmp"),
+        });
+        let content = "fn a() {}\nfn b() {}\n";
+        let mut t = tab("/tmp/p.rs", content);
+        t.blocks = multicode_core::parse_blocks(content.into(), "rust".into())
+            .unwrap()
+            .into_iter()
+            .filter(|b| b.kind == "Function/Define")
+            .collect();
+        app.tabs = vec![t];
+        app.active_tab = Some(0);
+        assert!(app.tab_profile(&app.tabs[0]).is_none());
+
+        let _ = app.handle_message(Message::CycleProfile);
+        assert_eq!(app.profile_metric, Some(ProfileMetric::ParseTime));
+        let profile = app.tab_profile(&app.tabs[0]).unwrap();
+        assert_eq!(profile.len(), 2);
+        assert!(profile.iter().flatten().all(|(_, label)| label.ends_with("мкс")));
+
+        app.coverage = Some(CoverageReport::parse_lcov(
+            "SF:/tmp/p.rs\nDA:1,40\nend_of_record\n",
+        ));
+        let _ = app.handle_message(Message::CycleProfile);
+        let profile = app.tab_profile(&app.tabs[0]).unwrap();
+        assert_eq!(profile[0], Some((heat(40, (10, 1000)), "×40".into())));
+        assert_eq!(profile[1], None);
+
+        let _ = app.handle_message(Message::ProfileThresholdChanged(
+            ProfileMetric::Executions,
+            true,
+            "40".into(),
+        ));
+        let profile = app.tab_profile(&app.tabs[0]).unwrap();
+        assert_eq!(profile[0], Some((1.0, "×40".into())));
+
+        let _ = app.handle_message(Message::CycleProfile);
+        assert!(app.profile_metric.is_none());
+    }
+
+    #[test]
+    fn canvas_filter_steps_through_matches() {
+        use crate::app::events::Message;
+        use multicode_core::BlockInfo;
+
+        let block = |id: &str, tags: &[&str]| BlockInfo {
+            visual_id: id.into(),
+            node_id: None,
+            kind: "Function".into(),
+            translations: HashMap::new(),
+            range: (0, 0),
+            anchors: vec![],
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            ports: vec![],
+            ai: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
+        };
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        let mut t = tab("/tmp/a.rs", "");
+        t.blocks = vec![block("a", &["net"]), block("b", &[]), block("c", &["net"])];
+        app.tabs = vec![t];
+        app.active_tab = Some(0);
+
+        let _ = app.handle_message(Message::ExecuteCommand("toggle_canvas_filter".into()));
+        assert!(app.show_canvas_filter);
+        let _ = app.handle_message(Message::CanvasFilterChanged("net".into()));
+        let _ = app.handle_message(Message::CanvasFilterStep(true));
+        let _ = app.handle_message(Message::CanvasFilterStep(true));
+        assert_eq!(app.canvas_filter_current, Some(2));
+        assert_eq!(app.canvas_focus, Some(2));
+        let filter = app.tab_canvas_filter(&app.tabs[0]).unwrap();
+        assert_eq!(filter.matches, vec![true, false, true]);
+
+        let _ = app.handle_message(Message::ToggleCanvasFilter);
+        assert!(app.canvas_filter.is_empty());
+        assert!(app.tab_canvas_filter(&app.tabs[0]).is_none());
+    }
+
+    #[test]
+    fn renamed_meta_id_updates_open_tabs() {
+        use crate::app::events::Message;
+        use crate::app::LogMessage;
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::from("/tmp"),
+        });
+        app.tabs = vec![
+            tab(
+                "/tmp/a.rs",
+                "fn a() {}\n// @VISUAL_META {\"id\":\"old\",\"x\":0.0,\"y\":0.0}\n",
+            ),
+            tab(
+                "/tmp/b.rs",
+                "fn b() {}\n// @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0,\"links\":[\"old\"]}\n",
+            ),
+        ];
+        app.meta_new_id = "connect".into();
+        let _ = app.handle_message(Message::MetaIdRenamed(
+            "old".into(),
+            "connect".into(),
+            Ok(vec![PathBuf::from("/tmp/a.rs"), PathBuf::from("/tmp/b.rs")]),
+        ));
+        assert_eq!(app.tabs[0].meta.as_ref().unwrap().id, "connect");
+        assert_eq!(app.tabs[1].meta.as_ref().unwrap().links, vec!["connect"]);
+        assert!(app.tabs[1].content.contains("\"links\":[\"connect\"]"));
+        assert!(app.meta_new_id.is_empty());
+
+        let _ = app.handle_message(Message::MetaIdRenamed(
+            "connect".into(),
+            "b".into(),
+            Err("taken".into()),
+        ));
+        assert_eq!(app.tabs[0].meta.as_ref().unwrap().id, "connect");
+        assert_eq!(
+            app.log.last().unwrap().message_key,
+            LogMessage::MetaIdRenameError
+        );
+    }
+
+    #[test]
+    fn meta_panel_shows_metrics_of_function_at_cursor() {
+        use crate::app::code_metrics::metrics_label;
+        use iced::widget::text_editor::{Action, Motion};
+        use multicode_core::analysis::metrics::BlockMetrics;
+
+        let mut app = build_app(Screen::TextEditor {
+            root: PathBuf::new(),
+        });
+        let content =
+            "fn first() {}\n\nfn outer(a: i32) {\n    if a > 0 {\n        let x = 1;\n    }\n}";
+        let mut t = tab("a.rs", content);
+        t.blocks = multicode_core::parse_blocks(content.into(), "rust".into()).unwrap();
+        t.editor.perform(Action::Move(Motion::DocumentEnd));
+        app.tabs = vec![t];
+        app.active_tab = Some(0);
+        let outer = BlockMetrics {
+            loc: 5,
+            complexity: 2,
+            depth: 1,
+            params: 1,
+        };
+        assert_eq!(app.cursor_metrics(&app.tabs[0]), Some(outer));
+        assert_eq!(
+            metrics_label(&outer),
+            "Метрики: строк 5, сложность 2, вложенность 1, параметров 1"
+        );
+
+        app.tabs[0]
+            .editor
+            .perform(Action::Move(Motion::DocumentStart));
+        assert_eq!(app.cursor_metrics(&app.tabs[0]).map(|m| m.loc), Some(1));
+    }
+
+    #[test]
+    fn dead_code_becomes_diagnostics() {
+        use crate::app::events::Message;
+        use crate::app::LogMessage;
+        use multicode_core::analysis::dead_code::analyze;
+
+        let dir = tempfile::tempdir().unwrap();
+        let content = "fn main() {}\n\nfn lost() {}\n";
+        std::fs::write(dir.path().join("main.rs"), content).unwrap();
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+        let path = dir.path().join("main.rs");
+        app.tabs = vec![tab(path.to_str().unwrap(), content)];
+        app.active_tab = Some(0);
+
+        let _ = app.handle_message(Message::DeadCodeFound(analyze(dir.path())));
+        let d = &app.tabs[0].diagnostics;
+        assert_eq!(d.len(), 1);
+        assert_eq!((d[0].line, d[0].range.clone()), (2, 3..7));
+        assert_eq!(d[0].message, "warning: функция lost нигде не используется");
+        assert_eq!(
+            app.log.last().unwrap().message_key,
+            LogMessage::DeadCodeFound
+        );
+    }
+
+    #[test]
+    fn save_inserts_license_header() {
+        use crate::app::events::Message;
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("multicode.toml"),
+            "[license]\nheader = \"Copyright {year} {owner}\"\nowner = \"Acme\"\nyear = 2024\non_save = true\n",
+        )
+        .unwrap();
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+        let path = dir.path().join("main.rs");
+        app.tabs = vec![tab(path.to_str().unwrap(), "fn main() {}\n")];
+        app.active_tab = Some(0);
+
+        let _ = app.handle_message(Message::SaveFile);
+        let content = &app.tabs[0].content;
+        // Запись @VISUAL_META в начале файла остаётся первой строкой.
+        let (meta, rest) = content.split_once('\n').unwrap();
+        assert!(meta.contains("@VISUAL_META"));
+        assert_eq!(rest, "// Copyright 2024 Acme\n\nfn main() {}\n");
+    }
+
+    #[test]
+    fn history_back_to_closed_file_keeps_forward() {
+        use crate::app::events::Message;
+        use crate::app::navigation::NavLocation;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+        let closed = dir.path().join("closed.rs");
+        std::fs::write(&closed, "fn closed() {}\n").unwrap();
+        let main = dir.path().join("main.rs");
+        app.tabs = vec![tab(main.to_str().unwrap(), "fn main() {}\n")];
+        app.active_tab = Some(0);
+        app.navigation.record(NavLocation {
+            path: closed.clone(),
+            line: 0,
+            visual_id: None,
+        });
+
+        let _ = app.handle_message(Message::HistoryBack);
+        assert_eq!(app.selected_path, Some(closed));
+        assert!(!app.navigation.can_go_back());
+        assert!(app.navigation.can_go_forward());
+    }
+
+    #[test]
+    fn organize_imports_is_one_undoable_edit() {
+        use crate::app::events::Message;
+
+        let content =
+            "use std::fs;\nuse std::collections::HashMap;\n\nfn f(m: HashMap<u8, u8>) {}\n";
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = build_app(Screen::TextEditor {
+            root: dir.path().to_path_buf(),
+        });
+        app.tabs = vec![tab(dir.path().join("main.rs").to_str().unwrap(), content)];
+        app.active_tab = Some(0);
+
+        let _ = app.handle_message(Message::OrganizeImports);
+        assert_eq!(
+            app.tabs[0].content,
+            "use std::collections::HashMap;\n\nfn f(m: HashMap<u8, u8>) {}\n"
+        );
+        assert!(app.tabs[0].dirty);
+
+        let _ = app.handle_message(Message::Undo);
+        assert_eq!(app.tabs[0].content, content);
+    }
 }
@@ -12,7 +12,10 @@ use super::events::Message;
 use super::{settings_translations::{settings_text, SettingsText}, AppTheme, CreateTarget, Language, MulticodeApp, Screen, ViewMode};
 use crate::sync::ConflictResolutionMode;
 use crate::search::hotkeys::HotkeyContext;
+use crate::editor::input_mode::InputMode;
 use crate::editor::{CodeEditor, EditorTheme, THEME_SET};
+use crate::visual::lanes::LaneGrouping;
+use crate::visual::profile::ProfileMetric;
 use crate::components::file_manager;
 
 const TERMINAL_HELP: &str = include_str!("../../assets/terminal-help.md");
@@ -74,9 +77,9 @@ impl MulticodeApp {
                     .iter()
                     .enumerate()
                     .map(|(i, f)| {
-                        let name = f.path.file_name().unwrap().to_string_lossy().to_string();
                         row![
-                            button(text(name)).on_press(Message::SelectFile(f.path.clone())),
+                            button(text(self.tab_title(f)))
+                                .on_press(Message::SelectFile(f.path.clone())),
                             button(text("x")).on_press(Message::CloseFile(i))
                         ]
                         .spacing(5)
@@ -111,7 +114,10 @@ impl MulticodeApp {
                     Space::with_width(Length::Shrink).into()
                 };
 
-                let editor: Element<_> = CodeEditor::new(self).view();
+                let editor: Element<_> = match self.pane_layout() {
+                    Some(layout) => self.panes_component(layout),
+                    None => CodeEditor::new(self).view(),
+                };
 
                 let search_panel = self.search_panel_component();
 
@@ -120,6 +126,18 @@ impl MulticodeApp {
                     editor,
                     self.project_search_component(),
                     self.lint_panel_component(),
+                    self.bookmarks_panel_component(),
+                    self.tags_panel_component(),
+                    self.block_history_component(),
+                    self.inheritance_panel_component(),
+                    self.cochange_component(),
+                    self.meta_suggestions_component(),
+                    self.structural_search_component(),
+                    self.test_panel_component(),
+                    self.env_editor_component(),
+                    self.ai_panel_component(),
+                    self.notifications_component(),
+                    self.macros_component(),
                     self.terminal_component(),
                 ]
                 .spacing(10);
@@ -160,29 +178,12 @@ impl MulticodeApp {
                         .on_blur(Message::ShowTerminalHelp)
                         .into();
                 }
-                if self.show_meta_dialog {
-                    let modal_content = container(
-                        column![
-                            text_input("Теги", &self.meta_tags).on_input(Message::MetaTagsChanged),
-                            text_input("Связи", &self.meta_links)
-                                .on_input(Message::MetaLinksChanged),
-                            text_input("Комментарий", &self.meta_comment)
-                                .on_input(Message::MetaCommentChanged),
-                            row![
-                                button("Сохранить").on_press(Message::SaveMeta),
-                                button("Отмена").on_press(Message::CloseMetaDialog)
-                            ]
-                            .spacing(5),
-                        ]
-                        .spacing(5),
-                    )
-                    .width(Length::Fixed(400.0))
-                    .padding(10);
-                    content = Modal::new(content, modal_content)
+                if self.meta_form.is_some() {
+                    content = Modal::new(content, self.meta_form_component())
                         .on_blur(Message::CloseMetaDialog)
                         .into();
                 }
-                (Some(tabs.into()), content)
+                (self.pane_layout().is_none().then(|| tabs.into()), content)
             }
             Screen::Split { .. } => {
                 let sidebar = self.sidebar();
@@ -231,6 +232,18 @@ impl MulticodeApp {
                     editor,
                     self.project_search_component(),
                     self.lint_panel_component(),
+                    self.bookmarks_panel_component(),
+                    self.tags_panel_component(),
+                    self.block_history_component(),
+                    self.inheritance_panel_component(),
+                    self.cochange_component(),
+                    self.meta_suggestions_component(),
+                    self.structural_search_component(),
+                    self.test_panel_component(),
+                    self.env_editor_component(),
+                    self.ai_panel_component(),
+                    self.notifications_component(),
+                    self.macros_component(),
                     self.terminal_component(),
                 ]
                 .spacing(10);
@@ -271,28 +284,16 @@ impl MulticodeApp {
                         .on_blur(Message::ShowTerminalHelp)
                         .into();
                 }
-                if self.show_meta_dialog {
-                    let modal_content = container(
-                        column![
-                            text_input("Теги", &self.meta_tags).on_input(Message::MetaTagsChanged),
-                            text_input("Связи", &self.meta_links)
-                                .on_input(Message::MetaLinksChanged),
-                            text_input("Комментарий", &self.meta_comment)
-                                .on_input(Message::MetaCommentChanged),
-                            row![
-                                button("Сохранить").on_press(Message::SaveMeta),
-                                button("Отмена").on_press(Message::CloseMetaDialog)
-                            ]
-                            .spacing(5),
-                        ]
-                        .spacing(5),
-                    )
-                    .width(Length::Fixed(400.0))
-                    .padding(10);
-                    content = Modal::new(content, modal_content)
+                if self.meta_form.is_some() {
+                    content = Modal::new(content, self.meta_form_component())
                         .on_blur(Message::CloseMetaDialog)
                         .into();
                 }
+                if let Some((_, label)) = &self.label_edit {
+                    content = Modal::new(content, self.label_editor_component(label))
+                        .on_blur(Message::CloseLabelEditor)
+                        .into();
+                }
 
                 (Some(tabs.into()), content)
             }
@@ -331,8 +332,12 @@ impl MulticodeApp {
 
                 let content = column![
                     text_input("поиск", &self.query).on_input(Message::QueryChanged),
+                    self.saved_search_component(),
                     editor,
                     self.project_search_component(),
+                    self.lint_panel_component(),
+                    self.notifications_component(),
+                    self.macros_component(),
                     self.terminal_component(),
                 ]
                 .spacing(10);
@@ -373,28 +378,16 @@ impl MulticodeApp {
                         .on_blur(Message::ShowTerminalHelp)
                         .into();
                 }
-                if self.show_meta_dialog {
-                    let modal_content = container(
-                        column![
-                            text_input("Теги", &self.meta_tags).on_input(Message::MetaTagsChanged),
-                            text_input("Связи", &self.meta_links)
-                                .on_input(Message::MetaLinksChanged),
-                            text_input("Комментарий", &self.meta_comment)
-                                .on_input(Message::MetaCommentChanged),
-                            row![
-                                button("Сохранить").on_press(Message::SaveMeta),
-                                button("Отмена").on_press(Message::CloseMetaDialog)
-                            ]
-                            .spacing(5)
-                        ]
-                        .spacing(5),
-                    )
-                    .width(Length::Fixed(400.0))
-                    .padding(10);
-                    content = Modal::new(content, modal_content)
+                if self.meta_form.is_some() {
+                    content = Modal::new(content, self.meta_form_component())
                         .on_blur(Message::CloseMetaDialog)
                         .into();
                 }
+                if let Some((_, label)) = &self.label_edit {
+                    content = Modal::new(content, self.label_editor_component(label))
+                        .on_blur(Message::CloseLabelEditor)
+                        .into();
+                }
                 (Some(tabs), content)
             }
             Screen::Settings => {
@@ -596,6 +589,115 @@ impl MulticodeApp {
                             .on_toggle(Message::ToggleHighlightCurrentLine),
                     ]
                     .spacing(10),
+                    row![
+                        text("Закреплённые заголовки"),
+                        checkbox("", self.settings.editor.sticky_headers)
+                            .on_toggle(Message::ToggleStickyHeaders),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Подсказки блоков"),
+                        checkbox("", self.settings.editor.inlay_hints)
+                            .on_toggle(Message::ToggleInlayHints),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Режим доступности"),
+                        checkbox("", self.settings.accessibility.enabled)
+                            .on_toggle(Message::ToggleAccessibleMode),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Контрастные цвета блоков"),
+                        checkbox("", self.settings.accessibility.high_contrast)
+                            .on_toggle(Message::ToggleHighContrastBlocks),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Минимальный размер шрифта"),
+                        text_input(
+                            "",
+                            &self.settings.accessibility.min_font_size.to_string()
+                        )
+                        .on_input(Message::MinFontSizeChanged)
+                        .width(Length::Fixed(50.0)),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Холст: только структура ниже масштаба"),
+                        text_input(
+                            "",
+                            &self.settings.canvas_lod.structure_below.to_string()
+                        )
+                        .on_input(Message::LodStructureChanged)
+                        .width(Length::Fixed(50.0)),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Холст: без инструкций ниже масштаба"),
+                        text_input(
+                            "",
+                            &self.settings.canvas_lod.functions_below.to_string()
+                        )
+                        .on_input(Message::LodFunctionsChanged)
+                        .width(Length::Fixed(50.0)),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Холст: группы блоков"),
+                        pick_list(
+                            &LaneGrouping::ALL[..],
+                            Some(self.settings.canvas_lanes),
+                            Message::LaneGroupingSelected
+                        ),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Холст: сетка"),
+                        checkbox("", self.settings.canvas_grid.show)
+                            .on_toggle(Message::ToggleCanvasGrid),
+                        text("привязка к сетке и соседям"),
+                        checkbox("", self.settings.canvas_grid.snap)
+                            .on_toggle(Message::ToggleCanvasSnap),
+                        text("ячейка"),
+                        text_input("", &self.settings.canvas_grid.cell.to_string())
+                            .on_input(Message::GridCellChanged)
+                            .width(Length::Fixed(50.0)),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Профиль: разбор, мкс"),
+                        text_input("", &self.settings.canvas_profile.parse_warm_us.to_string())
+                            .on_input(|v| Message::ProfileThresholdChanged(
+                                ProfileMetric::ParseTime,
+                                false,
+                                v
+                            ))
+                            .width(Length::Fixed(60.0)),
+                        text_input("", &self.settings.canvas_profile.parse_hot_us.to_string())
+                            .on_input(|v| Message::ProfileThresholdChanged(
+                                ProfileMetric::ParseTime,
+                                true,
+                                v
+                            ))
+                            .width(Length::Fixed(60.0)),
+                        text("выполнения"),
+                        text_input("", &self.settings.canvas_profile.exec_warm.to_string())
+                            .on_input(|v| Message::ProfileThresholdChanged(
+                                ProfileMetric::Executions,
+                                false,
+                                v
+                            ))
+                            .width(Length::Fixed(60.0)),
+                        text_input("", &self.settings.canvas_profile.exec_hot.to_string())
+                            .on_input(|v| Message::ProfileThresholdChanged(
+                                ProfileMetric::Executions,
+                                true,
+                                v
+                            ))
+                            .width(Length::Fixed(60.0)),
+                    ]
+                    .spacing(10),
                     row![
                         text("Тема редактора"),
                         pick_list(
@@ -605,6 +707,31 @@ impl MulticodeApp {
                         ),
                     ]
                     .spacing(10),
+                    row![
+                        text("Режим ввода"),
+                        pick_list(
+                            &InputMode::ALL[..],
+                            Some(self.settings.editor.input_mode),
+                            Message::InputModeSelected
+                        ),
+                    ]
+                    .spacing(10),
+                    row![
+                        text("Файл темы (JSON/TOML)"),
+                        text_input(
+                            "",
+                            &self
+                                .settings
+                                .editor
+                                .theme_file
+                                .as_ref()
+                                .map(|p| p.display().to_string())
+                                .unwrap_or_default()
+                        )
+                        .on_input(Message::ThemeFileChanged)
+                        .width(Length::Fixed(300.0)),
+                    ]
+                    .spacing(10),
                     row![
                         text("Создать файл"),
                         button(text(create_label))
@@ -661,6 +788,22 @@ impl MulticodeApp {
                 let content = row![self.sidebar(), content].spacing(10).into();
                 (None, content)
             }
+            Screen::TaskBoard { .. } => {
+                let board = container(self.task_board_component())
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+                let content = column![board, self.status_bar_component()].spacing(10);
+                let content = row![self.sidebar(), content].spacing(10).into();
+                (None, content)
+            }
+            Screen::Git { .. } => {
+                let git = container(self.git_screen_component())
+                    .width(Length::Fill)
+                    .height(Length::Fill);
+                let content = column![git, self.status_bar_component()].spacing(10);
+                let content = row![self.sidebar(), content].spacing(10).into();
+                (None, content)
+            }
             Screen::Diff(diff) => {
                 let diff_view = container(self.diff_component(diff))
                     .width(Length::Fill)
@@ -683,6 +826,10 @@ impl MulticodeApp {
         let content = self.loading_overlay(page);
         let content = self.command_palette_modal(content);
         let content = self.goto_line_modal(content);
+        let content = self.symbol_search_modal(content);
+        let content = self.recent_blocks_modal(content);
+        let content = self.recovery_modal(content);
+        let content = self.git_discard_modal(content);
         let content = self.block_palette_modal(content);
         self.error_modal(content)
     }
@@ -713,16 +860,50 @@ impl MulticodeApp {
         } else {
             "Open another project"
         };
-        row![
+        let mut menu = row![
             button("Разбор").on_press(Message::RunParse),
             button("Поиск").on_press(Message::ProjectSearch(self.query.clone())),
+            button("Спросить код").on_press(Message::AskCodebase(self.query.clone())),
+            button("Git").on_press(Message::OpenGitScreen),
             button("Журнал Git").on_press(Message::RunGitLog),
-            button("Экспорт").on_press(Message::RunExport),
-            button(open_other_label).on_press(Message::OpenProjectPicker),
-            button("Терминал").on_press(Message::ToggleTerminal),
-            button(settings_label).on_press(Message::OpenSettings),
         ]
-        .spacing(10)
+        .spacing(10);
+        // Вне репозитория git веток нет, и меню веток не показывается.
+        if !self.git_branches.branches.is_empty() {
+            menu = menu.push(self.branch_menu());
+        }
+        menu.push(button("Экспорт").on_press(Message::RunExport))
+            .push(button(open_other_label).on_press(Message::OpenProjectPicker))
+            .push(button("Терминал").on_press(Message::ToggleTerminal))
+            .push(button(settings_label).on_press(Message::OpenSettings))
+            .into()
+    }
+
+    /// Выбор ветки, создание новой и отложенные изменения.
+    fn branch_menu(&self) -> Element<'_, Message> {
+        let branches = &self.git_branches;
+        let stashes = branches.stashes.len();
+        let pop = button(text(format!("Вернуть отложенное ({stashes})")));
+        let pop = if stashes > 0 {
+            pop.on_press(Message::PopStash)
+        } else {
+            pop
+        };
+        row![
+            pick_list(
+                branches.branches.clone(),
+                branches.current.clone(),
+                Message::CheckoutBranch
+            )
+            .placeholder("HEAD отсоединён"),
+            text_input("Новая ветка", &self.new_branch_name)
+                .on_input(Message::NewBranchNameChanged)
+                .on_submit(Message::CreateBranch)
+                .width(Length::Fixed(140.0)),
+            button("Отложить").on_press(Message::StashChanges),
+            pop,
+        ]
+        .spacing(5)
         .into()
     }
 
@@ -868,14 +1049,47 @@ impl MulticodeApp {
 
     fn sidebar(&self) -> Element<Message> {
         let search = text_input("поиск", &self.search_query).on_input(Message::SearchChanged);
+        let (entries, expanded) = self.file_tree_view();
         let tree = file_manager::file_tree(
-            &self.files,
-            &self.expanded_dirs,
-            &self.search_query,
+            entries,
+            expanded,
             &self.favorites,
             &self.selected_path,
+            &self.git_status,
+            self.file_tree_viewport,
         );
-        column![search, container(tree).width(200)]
+        let status: Element<_> = match &self.file_search {
+            Some(s) if !s.complete => text("поиск…").size(12).into(),
+            Some(s) if s.truncated => text(format!(
+                "показаны первые {}",
+                super::io::MAX_TREE_SEARCH_RESULTS
+            ))
+            .size(12)
+            .into(),
+            _ => Space::with_height(Length::Shrink).into(),
+        };
+        let menu: Element<_> = match &self.context_menu {
+            Some(menu) => self
+                .context_menu_items(&menu.path)
+                .into_iter()
+                .fold(column![], |col, item| {
+                    col.push(
+                        button(text(item.to_string()))
+                            .width(Length::Fill)
+                            .on_press(Message::ContextMenuAction(item)),
+                    )
+                })
+                .push(
+                    button(text("Закрыть"))
+                        .width(Length::Fill)
+                        .on_press(Message::CloseContextMenu),
+                )
+                .spacing(2)
+                .width(200)
+                .into(),
+            None => Space::with_height(Length::Shrink).into(),
+        };
+        column![search, status, menu, container(tree).width(200)]
             .spacing(5)
             .into()
     }
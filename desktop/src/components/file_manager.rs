@@ -1,4 +1,5 @@
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use std::path::{Path, PathBuf};
 
 use iced::widget::overlay::menu;
@@ -10,6 +11,7 @@ use once_cell::sync::Lazy;
 use std::sync::Mutex;
 
 use crate::app::events::Message;
+use crate::app::git_status::{self, GitDecorations};
 use crate::app::{EntryType, FileEntry};
 
 /// Trait implemented by plugins that want to react to file manager events.
@@ -89,11 +91,17 @@ impl ContextMenu {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ContextMenuItem {
     Open,
     Rename,
     Delete,
+    /// `git add` для файла или каталога.
+    Stage,
+    /// Убрать изменения из индекса git.
+    Unstage,
+    /// Вернуть файл к версии из индекса git.
+    Discard,
 }
 
 impl ToString for ContextMenuItem {
@@ -102,6 +110,9 @@ impl ToString for ContextMenuItem {
             ContextMenuItem::Open => "Открыть".into(),
             ContextMenuItem::Rename => "Переименовать".into(),
             ContextMenuItem::Delete => "Удалить".into(),
+            ContextMenuItem::Stage => "Добавить в индекс".into(),
+            ContextMenuItem::Unstage => "Убрать из индекса".into(),
+            ContextMenuItem::Discard => "Отменить изменения".into(),
         }
     }
 }
@@ -120,7 +131,85 @@ static EXT_ICON_MAP: Lazy<HashMap<&'static str, &'static [u8]>> = Lazy::new(|| {
     m
 });
 
-fn filter_entries(entries: &[FileEntry], query: &str) -> Vec<FileEntry> {
+/// Высота строки дерева файлов, пикселей.
+pub const ROW_HEIGHT: f32 = 20.0;
+/// Сколько строк дерева рисуется сверх видимых сверху и снизу.
+const OVERSCAN: usize = 10;
+
+/// Результат поиска по дереву файлов: совпавшие файлы и каталоги вместе с
+/// их родительскими каталогами.
+#[derive(Debug, Clone, Default)]
+pub struct TreeSearch {
+    pub query: String,
+    pub entries: Vec<FileEntry>,
+    /// Найдено больше записей, чем показывается.
+    pub truncated: bool,
+    /// Обход проекта завершён; до этого показываются совпадения среди уже
+    /// загруженных каталогов.
+    pub complete: bool,
+}
+
+/// Прокрутка дерева файлов: по ней рисуются только видимые строки.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TreeViewport {
+    pub offset: f32,
+    pub height: f32,
+}
+
+impl Default for TreeViewport {
+    fn default() -> Self {
+        Self {
+            offset: 0.0,
+            height: 800.0,
+        }
+    }
+}
+
+impl TreeViewport {
+    /// Строки из `total`, попадающие в видимую область с запасом.
+    pub fn window(&self, total: usize) -> Range<usize> {
+        let first = (self.offset.max(0.0) / ROW_HEIGHT) as usize;
+        let count = (self.height.max(0.0) / ROW_HEIGHT).ceil() as usize;
+        let start = first.saturating_sub(OVERSCAN).min(total);
+        let end = (first + count + OVERSCAN).min(total);
+        start..end
+    }
+}
+
+/// Видимая строка дерева.
+#[derive(Debug, Clone, Copy)]
+pub struct TreeRow<'a> {
+    pub entry: &'a FileEntry,
+    pub depth: u16,
+}
+
+/// Строки дерева сверху вниз. `expanded` — раскрытые каталоги, `None` —
+/// раскрыты все (так показываются результаты поиска).
+pub fn visible_rows<'a>(
+    entries: &'a [FileEntry],
+    expanded: Option<&HashSet<PathBuf>>,
+) -> Vec<TreeRow<'a>> {
+    fn walk<'a>(
+        entries: &'a [FileEntry],
+        depth: u16,
+        expanded: Option<&HashSet<PathBuf>>,
+        out: &mut Vec<TreeRow<'a>>,
+    ) {
+        for entry in entries {
+            out.push(TreeRow { entry, depth });
+            let open = expanded.is_none_or(|set| set.contains(&entry.path));
+            if matches!(entry.ty, EntryType::Dir) && open {
+                walk(&entry.children, depth + 1, expanded, out);
+            }
+        }
+    }
+    let mut out = Vec::new();
+    walk(entries, 0, expanded, &mut out);
+    out
+}
+
+/// Совпадения с `query` среди уже загруженных записей.
+pub fn filter_entries(entries: &[FileEntry], query: &str) -> Vec<FileEntry> {
     if query.is_empty() {
         return entries.to_vec();
     }
@@ -148,6 +237,7 @@ fn filter_entries(entries: &[FileEntry], query: &str) -> Vec<FileEntry> {
                             ty: EntryType::Dir,
                             has_meta: false,
                             children,
+                            loaded: true,
                         })
                     } else {
                         None
@@ -158,166 +248,179 @@ fn filter_entries(entries: &[FileEntry], query: &str) -> Vec<FileEntry> {
         .collect()
 }
 
-pub fn view_entries(
-    entries: &[FileEntry],
-    depth: u16,
-    expanded_dirs: &HashSet<PathBuf>,
+fn error_row(
+    indent: Space,
+    fav_button: Element<'static, Message>,
+    message: String,
+) -> Element<'static, Message> {
+    row![
+        indent,
+        fav_button,
+        button(text("Ошибка")).on_press(Message::FileError(message)),
+    ]
+    .spacing(5)
+    .align_items(Alignment::Center)
+    .height(Length::Fixed(ROW_HEIGHT))
+    .into()
+}
+
+/// Одна строка дерева: файл или заголовок каталога.
+pub fn view_row(
+    row: TreeRow<'_>,
+    expanded: bool,
     favorites: &[PathBuf],
     selected: &Option<PathBuf>,
+    git: &GitDecorations,
 ) -> Element<'static, Message> {
-    let mut rows = Vec::new();
-    for entry in entries {
-        let indent = Space::with_width(Length::Fixed((depth * 20) as f32));
-        let is_fav = favorites.contains(&entry.path);
-        let fav_icon = if is_fav { "★" } else { "☆" };
-        let fav_button = button(text(fav_icon))
-            .padding(0)
-            .width(Length::Fixed(20.0))
-            .on_press(if is_fav {
-                Message::RemoveFavorite(entry.path.clone())
-            } else {
-                Message::AddFavorite(entry.path.clone())
-            });
-        match &entry.ty {
-            EntryType::File => {
-                let name = if let Some(name) = entry.path.file_name() {
-                    let name = name.to_string_lossy().to_string();
-                    if entry.has_meta {
-                        format!("{} ◆", name)
-                    } else {
-                        name
-                    }
-                } else {
-                    rows.push(
-                        row![
-                            indent,
-                            fav_button,
-                            button(text("Ошибка"))
-                                .on_press(Message::FileError(format!(
-                                    "не удалось получить имя файла: {}",
-                                    entry.path.display()
-                                ))),
-                        ]
-                        .spacing(5)
-                        .align_items(Alignment::Center)
-                        .height(Length::Fixed(20.0))
-                        .into(),
-                    );
-                    continue;
-                };
-                let ext = entry
-                    .path
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("");
-                let icon = Svg::new(Handle::from_memory(
-                    EXT_ICON_MAP.get(ext).copied().unwrap_or(FILE_ICON),
-                ))
-                .width(Length::Fixed(16.0))
-                .height(Length::Fixed(16.0));
-                let content = row![icon, text(name)]
-                    .spacing(5)
-                    .align_items(Alignment::Center);
-                let mut btn = button(content).on_press(Message::SelectFile(entry.path.clone()));
-                if selected.as_ref() == Some(&entry.path) {
-                    btn = btn.style(theme::Button::Primary);
-                }
-                let row = row![
+    let entry = row.entry;
+    let git_marker: Element<'static, Message> = match git.get(&entry.path) {
+        Some(status) if !status.is_clean() => text(git_status::marker(status))
+            .size(12)
+            .style(git_status::marker_color(status))
+            .into(),
+        _ => Space::with_width(Length::Shrink).into(),
+    };
+    let indent = Space::with_width(Length::Fixed((row.depth * 20) as f32));
+    let is_fav = favorites.contains(&entry.path);
+    let fav_icon = if is_fav { "★" } else { "☆" };
+    let fav_button: Element<'static, Message> = button(text(fav_icon))
+        .padding(0)
+        .width(Length::Fixed(20.0))
+        .on_press(if is_fav {
+            Message::RemoveFavorite(entry.path.clone())
+        } else {
+            Message::AddFavorite(entry.path.clone())
+        })
+        .into();
+    let content = match &entry.ty {
+        EntryType::File => {
+            let Some(name) = entry.path.file_name() else {
+                return error_row(
                     indent,
                     fav_button,
-                    MouseArea::new(btn)
-                        .on_right_press(Message::ShowContextMenu(entry.path.clone())),
-                ]
+                    format!("не удалось получить имя файла: {}", entry.path.display()),
+                );
+            };
+            let name = name.to_string_lossy().to_string();
+            let name = if entry.has_meta {
+                format!("{} ◆", name)
+            } else {
+                name
+            };
+            let ext = entry
+                .path
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("");
+            let icon = Svg::new(Handle::from_memory(
+                EXT_ICON_MAP.get(ext).copied().unwrap_or(FILE_ICON),
+            ))
+            .width(Length::Fixed(16.0))
+            .height(Length::Fixed(16.0));
+            let content = row![icon, text(name), git_marker]
                 .spacing(5)
-                .align_items(Alignment::Center)
-                .height(Length::Fixed(20.0))
-                .into();
-                rows.push(row);
-            }
-            EntryType::Dir => {
-                let name = if let Some(name) = entry.path.file_name() {
-                    name.to_string_lossy().to_string()
-                } else {
-                    rows.push(
-                        row![
-                            indent,
-                            fav_button,
-                            button(text("Ошибка"))
-                                .on_press(Message::FileError(format!(
-                                    "не удалось получить имя каталога: {}",
-                                    entry.path.display()
-                                ))),
-                        ]
-                        .spacing(5)
-                        .align_items(Alignment::Center)
-                        .height(Length::Fixed(20.0))
-                        .into(),
-                    );
-                    continue;
-                };
-                let expanded = expanded_dirs.contains(&entry.path);
-                let icon = if expanded { "▼" } else { "▶" };
-                let content = row![text(icon), text(name)]
-                    .spacing(5)
-                    .align_items(Alignment::Center);
-                let mut btn = button(content).on_press(Message::ToggleDir(entry.path.clone()));
-                if selected.as_ref() == Some(&entry.path) {
-                    btn = btn.style(theme::Button::Primary);
-                }
-                let header = row![
+                .align_items(Alignment::Center);
+            button(content).on_press(Message::SelectFile(entry.path.clone()))
+        }
+        EntryType::Dir => {
+            let Some(name) = entry.path.file_name() else {
+                return error_row(
                     indent,
                     fav_button,
-                    MouseArea::new(btn)
-                        .on_right_press(Message::ShowContextMenu(entry.path.clone())),
-                ]
-                .spacing(5)
-                .align_items(Alignment::Center)
-                .height(Length::Fixed(20.0))
-                .into();
-                rows.push(header);
-                if expanded {
-                    rows.push(view_entries(
-                        &entry.children,
-                        depth + 1,
-                        expanded_dirs,
-                        favorites,
-                        selected,
-                    ));
-                }
-            }
+                    format!("не удалось получить имя каталога: {}", entry.path.display()),
+                );
+            };
+            let icon = if expanded { "▼" } else { "▶" };
+            let content = row![
+                text(icon),
+                text(name.to_string_lossy().to_string()),
+                git_marker
+            ]
+            .spacing(5)
+            .align_items(Alignment::Center);
+            button(content).on_press(Message::ToggleDir(entry.path.clone()))
         }
-    }
+    };
+    let btn = if selected.as_ref() == Some(&entry.path) {
+        content.style(theme::Button::Primary)
+    } else {
+        content
+    };
+    row![
+        indent,
+        fav_button,
+        MouseArea::new(btn).on_right_press(Message::ShowContextMenu(entry.path.clone())),
+    ]
+    .spacing(5)
+    .align_items(Alignment::Center)
+    .height(Length::Fixed(ROW_HEIGHT))
+    .into()
+}
+
+/// Все строки дерева без виртуализации.
+pub fn view_entries(
+    entries: &[FileEntry],
+    expanded_dirs: &HashSet<PathBuf>,
+    favorites: &[PathBuf],
+    selected: &Option<PathBuf>,
+    git: &GitDecorations,
+) -> Element<'static, Message> {
+    let rows = visible_rows(entries, Some(expanded_dirs))
+        .into_iter()
+        .map(|r| {
+            view_row(
+                r,
+                expanded_dirs.contains(&r.entry.path),
+                favorites,
+                selected,
+                git,
+            )
+        })
+        .collect::<Vec<_>>();
     column(rows).into()
 }
 
+/// Дерево файлов в области прокрутки. Рисуются только строки, попадающие
+/// в `viewport`, остальные заменяются пустым местом той же высоты, поэтому
+/// каталоги с тысячами записей не замедляют интерфейс.
 pub fn file_tree(
     entries: &[FileEntry],
-    expanded_dirs: &HashSet<PathBuf>,
-    search_query: &str,
+    expanded: Option<&HashSet<PathBuf>>,
     favorites: &[PathBuf],
     selected: &Option<PathBuf>,
+    git: &GitDecorations,
+    viewport: TreeViewport,
 ) -> Element<'static, Message> {
-    let filtered = filter_entries(entries, search_query);
-    scrollable(view_entries(&filtered, 0, expanded_dirs, favorites, selected)).into()
+    let rows = visible_rows(entries, expanded);
+    let window = viewport.window(rows.len());
+    let above = window.start as f32 * ROW_HEIGHT;
+    let below = (rows.len() - window.end) as f32 * ROW_HEIGHT;
+    let mut items: Vec<Element<'static, Message>> = Vec::with_capacity(window.len() + 2);
+    items.push(Space::with_height(Length::Fixed(above)).into());
+    for r in &rows[window] {
+        let open = expanded.is_none_or(|set| set.contains(&r.entry.path));
+        items.push(view_row(*r, open, favorites, selected, git));
+    }
+    items.push(Space::with_height(Length::Fixed(below)).into());
+    scrollable(column(items))
+        .on_scroll(|v| {
+            Message::FileTreeScrolled(TreeViewport {
+                offset: v.absolute_offset().y,
+                height: v.bounds().height,
+            })
+        })
+        .into()
 }
 
+/// Пути видимых строк дерева сверху вниз, см. [`visible_rows`].
 pub fn flatten_visible_paths(
     entries: &[FileEntry],
-    expanded_dirs: &HashSet<PathBuf>,
-    search_query: &str,
+    expanded: Option<&HashSet<PathBuf>>,
 ) -> Vec<PathBuf> {
-    fn flatten(entries: &[FileEntry], expanded: &HashSet<PathBuf>, out: &mut Vec<PathBuf>) {
-        for e in entries {
-            out.push(e.path.clone());
-            if matches!(e.ty, EntryType::Dir) && expanded.contains(&e.path) {
-                flatten(&e.children, expanded, out);
-            }
-        }
-    }
-    let filtered = filter_entries(entries, search_query);
-    let mut out = Vec::new();
-    flatten(&filtered, expanded_dirs, &mut out);
-    out
+    visible_rows(entries, expanded)
+        .into_iter()
+        .map(|r| r.entry.path.clone())
+        .collect()
 }
 
 pub fn find_entry<'a>(entries: &'a [FileEntry], path: &Path) -> Option<&'a FileEntry> {
@@ -333,3 +436,58 @@ pub fn find_entry<'a>(entries: &'a [FileEntry], path: &Path) -> Option<&'a FileE
     }
     None
 }
+
+pub fn find_entry_mut<'a>(entries: &'a mut [FileEntry], path: &Path) -> Option<&'a mut FileEntry> {
+    for e in entries {
+        if e.path == path {
+            return Some(e);
+        }
+        if matches!(e.ty, EntryType::Dir) && path.starts_with(&e.path) {
+            return find_entry_mut(&mut e.children, path);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, ty: EntryType, children: Vec<FileEntry>) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(path),
+            ty,
+            has_meta: false,
+            children,
+            loaded: true,
+        }
+    }
+
+    #[test]
+    fn visible_rows_follow_expanded_dirs() {
+        let tree = vec![
+            entry(
+                "a",
+                EntryType::Dir,
+                vec![entry("a/b.rs", EntryType::File, vec![])],
+            ),
+            entry("c.rs", EntryType::File, vec![]),
+        ];
+        let collapsed = flatten_visible_paths(&tree, Some(&HashSet::new()));
+        assert_eq!(collapsed, [PathBuf::from("a"), PathBuf::from("c.rs")]);
+        let all = visible_rows(&tree, None);
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[1].depth, 1);
+    }
+
+    #[test]
+    fn viewport_window_covers_visible_rows() {
+        let viewport = TreeViewport {
+            offset: ROW_HEIGHT * 100.0,
+            height: ROW_HEIGHT * 30.0,
+        };
+        assert_eq!(viewport.window(10_000), 90..140);
+        assert_eq!(viewport.window(120), 90..120);
+        assert_eq!(TreeViewport::default().window(5), 0..5);
+    }
+}
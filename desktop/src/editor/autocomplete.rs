@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 
+use multicode_core::meta::symbols::{SymbolKind, WorkspaceSymbols};
 use once_cell::sync::Lazy;
 
 pub static KEYWORDS: &[&str] = &[
@@ -88,3 +89,98 @@ pub fn suggestions(content: &str, prefix: &str) -> Vec<Suggestion> {
     items
 }
 
+/// Ключ записи перед позицией `end` строки `json`: `"tags": ` → `tags`.
+fn key_before(json: &str, end: usize) -> Option<&str> {
+    let s = json[..end].trim_end().strip_suffix(':')?.trim_end();
+    let s = s.strip_suffix('"')?;
+    Some(&s[s.rfind('"')? + 1..])
+}
+
+/// Определяет, дополняется ли значение внутри комментария `@VISUAL_META`.
+///
+/// `before` — текст строки до курсора. Возвращает вид символа и уже
+/// введённую часть строки JSON, в которой стоит курсор.
+pub fn meta_context(before: &str) -> Option<(SymbolKind, String)> {
+    let json = &before[before.rfind("@VISUAL_META")? + "@VISUAL_META".len()..];
+    let mut stack = Vec::new();
+    let mut string_start = None;
+    let mut escaped = false;
+    for (i, c) in json.char_indices() {
+        if string_start.is_some() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => string_start = None,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => string_start = Some(i),
+            '[' | '{' => stack.push((c, i)),
+            ']' | '}' => {
+                stack.pop();
+            }
+            _ => {}
+        }
+    }
+    let start = string_start?;
+    let prefix = json[start + 1..].to_string();
+    let kind = match json[..start].trim_end().chars().last()? {
+        ':' => match key_before(json, json[..start].trim_end().len())? {
+            "extends" => SymbolKind::Id,
+            _ => return None,
+        },
+        '[' | '{' | ',' => {
+            let &(open, pos) = stack.last()?;
+            match (open, key_before(json, pos)?) {
+                ('[', "tags") => SymbolKind::Tag,
+                ('[', "links") => SymbolKind::Id,
+                ('{', "translations") => SymbolKind::Language,
+                _ => return None,
+            }
+        }
+        _ => return None,
+    };
+    Some((kind, prefix))
+}
+
+/// Подсказки символов рабочего пространства для значения метаданных.
+/// Вставляется только недостающая часть символа.
+pub fn meta_suggestions(
+    symbols: &WorkspaceSymbols,
+    kind: SymbolKind,
+    prefix: &str,
+) -> Vec<Suggestion> {
+    symbols
+        .complete(kind, prefix)
+        .into_iter()
+        .filter(|s| *s != prefix)
+        .map(|s| Suggestion {
+            label: s.to_string(),
+            insert: s.chars().skip(prefix.chars().count()).collect(),
+        })
+        .collect()
+}
+
+/// Варианты завершения последнего элемента списка через запятую, как в
+/// полях «Теги» и «Связи» диалога метаданных. Уже указанные значения не
+/// предлагаются.
+pub fn complete_list(value: &str, symbols: &WorkspaceSymbols, kind: SymbolKind) -> Vec<String> {
+    let (head, last) = match value.rfind(',') {
+        Some(i) => (&value[..=i], &value[i + 1..]),
+        None => ("", value),
+    };
+    let prefix = last.trim_start();
+    if prefix.is_empty() {
+        return Vec::new();
+    }
+    let present: HashSet<&str> = head.split(',').map(str::trim).collect();
+    let sep = if head.is_empty() { "" } else { " " };
+    symbols
+        .complete(kind, prefix)
+        .into_iter()
+        .filter(|s| *s != prefix && !present.contains(s))
+        .map(|s| format!("{}{sep}{s}", head.trim_end()))
+        .collect()
+}
@@ -0,0 +1,46 @@
+use super::autocomplete::{complete_list, meta_context, meta_suggestions};
+use multicode_core::meta::symbols::{SymbolKind, WorkspaceSymbols};
+
+fn symbols() -> WorkspaceSymbols {
+    let mut symbols = WorkspaceSymbols::default();
+    symbols.add_content(
+        "// @VISUAL_META {\"id\":\"net-client\",\"x\":0.0,\"y\":0.0,\"tags\":[\"net\",\"network\"],\"translations\":{\"en\":\"Client\"}}\n",
+    );
+    symbols
+}
+
+#[test]
+fn detects_meta_field_under_cursor() {
+    let line = "// @VISUAL_META {\"id\":\"a\",\"tags\":[\"io\",\"ne";
+    assert_eq!(meta_context(line), Some((SymbolKind::Tag, "ne".into())));
+    let line = "// @VISUAL_META {\"id\":\"a\",\"links\":[\"";
+    assert_eq!(meta_context(line), Some((SymbolKind::Id, String::new())));
+    let line = "// @VISUAL_META {\"id\":\"a\",\"extends\":\"net";
+    assert_eq!(meta_context(line), Some((SymbolKind::Id, "net".into())));
+    let line = "// @VISUAL_META {\"id\":\"a\",\"translations\":{\"ru\":\"Клиент\",\"e";
+    assert_eq!(meta_context(line), Some((SymbolKind::Language, "e".into())));
+    assert_eq!(meta_context("// @VISUAL_META {\"id\":\"ne"), None);
+    assert_eq!(meta_context("let s = \"ne"), None);
+}
+
+#[test]
+fn meta_suggestions_insert_rest_of_symbol() {
+    let items = meta_suggestions(&symbols(), SymbolKind::Tag, "ne");
+    let labels: Vec<_> = items.iter().map(|s| s.label.as_str()).collect();
+    assert_eq!(labels, vec!["net", "network"]);
+    assert_eq!(items[1].insert, "twork");
+}
+
+#[test]
+fn completes_last_list_item() {
+    let symbols = symbols();
+    assert_eq!(
+        complete_list("io, ne", &symbols, SymbolKind::Tag),
+        vec!["io, net", "io, network"]
+    );
+    assert_eq!(
+        complete_list("net, ne", &symbols, SymbolKind::Tag),
+        vec!["net, network"]
+    );
+    assert!(complete_list("io, ", &symbols, SymbolKind::Tag).is_empty());
+}
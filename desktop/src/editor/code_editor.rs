@@ -1,77 +1,51 @@
 use chrono::DateTime;
 use iced::advanced::text::highlighter;
 use iced::widget::{
-    button, column, container, row, scrollable, text, text_editor,
+    button, column, container, mouse_area, row, scrollable, text, text_editor,
     tooltip::{self, Tooltip},
-    Column,
 };
 use iced::{theme, Alignment, Color, Element, Length};
+use std::collections::{BTreeMap, HashSet};
+use std::ops::Range;
 
 use crate::app::events::Message;
 use crate::app::MulticodeApp;
 
+use super::folding::fold_regions;
+use super::inlay_hints::inlay_hints;
+use super::markdown_preview::{markdown_preview, PREVIEW_SCROLL};
+use super::sticky_headers::{sticky_headers, top_line, EDITOR_SCROLL};
 use super::syntax_highlighter::{SyntaxHighlighter, SyntaxSettings, SyntaxColors};
 
-pub(super) fn markdown_preview(content: &str) -> Column<'static, Message> {
-    use pulldown_cmark::{Event, HeadingLevel, Parser, Tag};
-
-    let parser = Parser::new(content);
-    let mut elements: Vec<Element<Message>> = Vec::new();
-    let mut buf = String::new();
-    let mut heading = None;
-
-    for event in parser {
-        match event {
-            Event::Start(Tag::Heading(level, _, _)) => {
-                buf.clear();
-                heading = Some(level);
-            }
-            Event::End(Tag::Heading(_, _, _)) => {
-                let size = match heading.unwrap_or(HeadingLevel::H1) {
-                    HeadingLevel::H1 => 30.0,
-                    HeadingLevel::H2 => 26.0,
-                    HeadingLevel::H3 => 22.0,
-                    _ => 20.0,
-                };
-                elements.push(text(buf.clone()).size(size).into());
-                buf.clear();
-                heading = None;
-            }
-            Event::Start(Tag::Paragraph) => buf.clear(),
-            Event::End(Tag::Paragraph) => {
-                elements.push(text(buf.clone()).into());
-                buf.clear();
-            }
-            Event::Start(Tag::Item) => buf.push_str("• "),
-            Event::End(Tag::Item) => {
-                elements.push(text(buf.clone()).into());
-                buf.clear();
-            }
-            Event::Text(t) | Event::Code(t) => buf.push_str(&t),
-            Event::SoftBreak | Event::HardBreak => buf.push('\n'),
-            _ => {}
-        }
-    }
-
-    if !buf.is_empty() {
-        elements.push(text(buf).into());
-    }
-
-    column(elements).spacing(5)
-}
-
-
 pub struct CodeEditor<'a> {
     app: &'a MulticodeApp,
+    tab: Option<usize>,
+    focused: bool,
 }
 
 impl<'a> CodeEditor<'a> {
     pub fn new(app: &'a MulticodeApp) -> Self {
-        Self { app }
+        Self {
+            app,
+            tab: app.active_tab_index(),
+            focused: true,
+        }
+    }
+
+    /// Редактор вкладки `tab` в панели без фокуса. Поиск, каретки,
+    /// прокрутка и всплывающие окна относятся к панели в фокусе, поэтому
+    /// здесь они не показываются.
+    pub fn for_tab(app: &'a MulticodeApp, tab: usize) -> Self {
+        Self {
+            app,
+            tab: Some(tab),
+            focused: false,
+        }
     }
 
     pub fn view(self) -> Element<'a, Message> {
-        if let Some(file) = self.app.current_file() {
+        let focused = self.focused;
+        if let Some(file) = self.tab.and_then(|i| self.app.tab(i)) {
             let ext = file
                 .path
                 .extension()
@@ -83,30 +57,106 @@ impl<'a> CodeEditor<'a> {
                 self.app.settings().match_color,
                 self.app.settings().diagnostic_color,
             );
+            // Свёрнутая вкладка показывает текст без скрытых строк, поэтому
+            // подсветка переносится на строки представления.
+            let folded = file.folded.as_ref().filter(|v| v.is_current(&file.content));
+            let visible = |lines: Vec<(usize, Range<usize>)>| match folded {
+                Some(view) => lines
+                    .into_iter()
+                    .filter_map(|(line, range)| Some((view.view_line(line)?, range)))
+                    .collect(),
+                None => lines,
+            };
+            let semantic = file
+                .highlights
+                .as_ref()
+                .filter(|h| h.is_current(&file.content));
             let settings = SyntaxSettings {
                 extension: ext.clone(),
-                matches: self.app.search_results().to_vec(),
-                diagnostics: file
-                    .diagnostics
-                    .iter()
-                    .map(|d| (d.line, d.range.clone()))
-                    .collect(),
+                matches: if focused {
+                    visible(self.app.search_results().to_vec())
+                } else {
+                    Vec::new()
+                },
+                diagnostics: visible(
+                    file.diagnostics
+                        .iter()
+                        .map(|d| (d.line, d.range.clone()))
+                        .collect(),
+                ),
+                carets: if focused {
+                    self.app.caret_highlights()
+                } else {
+                    Vec::new()
+                },
+                semantic: semantic.map(|h| h.lines().clone()),
+                semantic_lines: folded.filter(|_| semantic.is_some()).map(|view| {
+                    (0..view.editor.line_count())
+                        .map(|line| view.full_line(line))
+                        .collect()
+                }),
                 theme: self.app.settings().syntect_theme.clone(),
                 colors,
             };
-            let editor = text_editor(&file.editor)
+            let content = folded.map_or(&file.editor, |v| &v.editor);
+            let editor = text_editor(content)
                 .highlight::<SyntaxHighlighter>(settings, |c, _| highlighter::Format {
                     color: Some(*c),
                     font: None,
                 })
                 .on_action(Message::FileContentEdited);
 
-            let editor_view: Element<'a, Message> = if self.app.settings().show_line_numbers {
+            let show_numbers = self.app.settings().show_line_numbers;
+            let headers: HashSet<usize> = fold_regions(&file.content, &file.blocks)
+                .iter()
+                .map(|r| r.header)
+                .collect();
+            // Закреплённым заголовкам нужна прокрутка редактора, поэтому с
+            // ними редактор всегда помещается в прокручиваемую колонку.
+            let sticky = self.app.settings().editor.sticky_headers;
+            let hints = if self.app.settings().editor.inlay_hints {
+                inlay_hints(&file.content, &file.blocks, self.app.workspace_symbols())
+            } else {
+                BTreeMap::new()
+            };
+            let gutter = show_numbers
+                || sticky
+                || !hints.is_empty()
+                || !file.diagnostics.is_empty()
+                || !headers.is_empty();
+            let editor_view: Element<'a, Message> = if gutter {
                 let lines = column(
-                    (1..=file.editor.line_count())
-                        .map(|i| {
-                            let ln = text(i.to_string());
-                            if let Some(info) = file.blame.get(&i) {
+                    (0..content.line_count())
+                        .map(|v| {
+                            let i = folded.map_or(v, |view| view.full_line(v)) + 1;
+                            let messages: Vec<&str> = file
+                                .diagnostics
+                                .iter()
+                                .filter(|d| d.line + 1 == i)
+                                .map(|d| d.message.as_str())
+                                .collect();
+                            let ln = text(match (show_numbers, messages.is_empty()) {
+                                (true, true) => i.to_string(),
+                                (true, false) => format!("{i} ~"),
+                                (false, true) => String::new(),
+                                (false, false) => "~".to_string(),
+                            });
+                            let number: Element<Message> = if !messages.is_empty() {
+                                // Подчеркнуть диапазон в `text_editor` нельзя,
+                                // поэтому строка с ошибкой помечается в колонке
+                                // номеров, а сам диапазон выделяется цветом.
+                                let marker = mouse_area(ln.style(theme::Text::Color(
+                                    self.app.settings().diagnostic_color,
+                                )))
+                                .on_press(Message::ToggleDiagnosticPopup(i - 1));
+                                Tooltip::new(
+                                    marker,
+                                    text(messages.join("\n")),
+                                    tooltip::Position::FollowCursor,
+                                )
+                                .style(theme::Container::Box)
+                                .into()
+                            } else if let Some(info) = file.blame.get(&i) {
                                 let tooltip_text =
                                     if let Some(dt) = DateTime::from_timestamp(info.time, 0) {
                                         format!("{} – {}", info.author, dt.format("%Y-%m-%d"))
@@ -121,26 +171,95 @@ impl<'a> CodeEditor<'a> {
                                 .into()
                             } else {
                                 ln.into()
-                            }
+                            };
+                            let fold: Element<Message> = if headers.contains(&(i - 1)) {
+                                let is_folded = folded.is_some_and(|view| view.is_folded(i - 1));
+                                mouse_area(text(if is_folded { "▸" } else { "▾" }))
+                                    .on_press(Message::ToggleFold(i - 1))
+                                    .into()
+                            } else {
+                                text("").into()
+                            };
+                            row![container(fold).width(Length::Fixed(12.0)), number].into()
+                        })
+                        .collect::<Vec<Element<Message>>>(),
+                );
+
+                // Подсказки идут колонкой справа от текста, строка в строку.
+                let hint_color = theme::Text::Color(Color::from_rgb(0.5, 0.5, 0.5));
+                let hint_lines = column(
+                    (0..content.line_count())
+                        .map(|v| {
+                            let line = folded.map_or(v, |view| view.full_line(v));
+                            let hint = hints.get(&line).cloned().unwrap_or_default();
+                            text(hint).style(hint_color).into()
                         })
                         .collect::<Vec<Element<Message>>>(),
                 );
 
-                scrollable(
+                let scroll = scrollable(
                     row![
                         container(lines).width(Length::Shrink),
-                        editor.height(Length::Shrink)
+                        editor.height(Length::Shrink),
+                        container(hint_lines).width(Length::Shrink)
                     ]
                     .spacing(5),
                 )
-                .height(Length::Fill)
-                .into()
+                .height(Length::Fill);
+                if focused {
+                    scroll
+                        .id(scrollable::Id::new(EDITOR_SCROLL))
+                        .on_scroll(|v| Message::EditorScrolled(v.absolute_offset().y))
+                        .into()
+                } else {
+                    scroll.into()
+                }
             } else {
                 editor.height(Length::Fill).into()
             };
 
-            let mut editor_column = column![editor_view];
-            if let Some(ac) = self.app.autocomplete() {
+            let mut editor_column = column![];
+            if sticky && focused {
+                let top = top_line(self.app.editor_scroll());
+                let top = folded.map_or(top, |view| view.full_line(top));
+                let pinned = sticky_headers(&file.content, &file.blocks, top);
+                if !pinned.is_empty() {
+                    let items = pinned.into_iter().map(|(line, header)| {
+                        mouse_area(text(header))
+                            .on_press(Message::StickyHeaderPressed(line))
+                            .into()
+                    });
+                    editor_column = editor_column.push(
+                        container(column(items.collect::<Vec<Element<Message>>>()))
+                            .width(Length::Fill)
+                            .padding([0, 5])
+                            .style(theme::Container::Box),
+                    );
+                }
+            }
+            editor_column = editor_column.push(editor_view);
+            if let Some(line) = self.app.diagnostic_popup().filter(|_| focused) {
+                let items = file
+                    .diagnostics
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, d)| d.line == line)
+                    .fold(column![].spacing(2), |col, (i, d)| {
+                        let fixes = d.fixes.iter().enumerate().fold(
+                            row![text(&d.message)].spacing(5),
+                            |r, (j, f)| {
+                                r.push(button(text(&f.title)).on_press(Message::ApplyQuickFix {
+                                    diagnostic: i,
+                                    fix: j,
+                                }))
+                            },
+                        );
+                        col.push(fixes)
+                    });
+                let popup = container(items).padding(5).style(theme::Container::Box);
+                editor_column = editor_column.push(popup);
+            }
+            if let Some(ac) = self.app.autocomplete().filter(|_| focused) {
                 let items = ac.suggestions.iter().enumerate().fold(
                     column![]
                         .spacing(2),
@@ -159,11 +278,15 @@ impl<'a> CodeEditor<'a> {
                     .style(theme::Container::Box);
                 editor_column = editor_column.push(popup);
             }
-            if self.app.settings().show_markdown_preview && ext == "md" {
-                let preview =
+            let markdown = matches!(ext.as_str(), "md" | "markdown");
+            if self.app.settings().show_markdown_preview && markdown {
+                let mut preview =
                     scrollable(markdown_preview(&file.content)).width(Length::FillPortion(1));
+                if focused {
+                    preview = preview.id(scrollable::Id::new(PREVIEW_SCROLL));
+                }
                 let main = row![editor_column.width(Length::FillPortion(1)), preview].spacing(5);
-                if self.app.show_meta_panel() {
+                if self.app.show_meta_panel() && focused {
                     row![
                         main.width(Length::FillPortion(3)),
                         self.app.meta_panel_component()
@@ -174,7 +297,7 @@ impl<'a> CodeEditor<'a> {
                     main.into()
                 }
             } else {
-                if self.app.show_meta_panel() {
+                if self.app.show_meta_panel() && focused {
                     row![
                         editor_column.width(Length::FillPortion(3)),
                         self.app.meta_panel_component()
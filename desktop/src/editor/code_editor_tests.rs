@@ -1,11 +1,12 @@
 use super::{
-    code_editor::markdown_preview,
+    markdown_preview::markdown_preview,
     syntax_highlighter::{SyntaxColors, SyntaxHighlighter, SyntaxSettings},
 };
 use iced::{
     advanced::{text::highlighter::Highlighter, Widget},
     Color,
 };
+use multicode_core::parser::{highlight::DocumentHighlights, Lang};
 
 #[test]
 fn markdown_preview_renders_heading() {
@@ -25,6 +26,9 @@ fn syntect_highlighter_returns_matches_and_diagnostics() {
         extension: String::from("rs"),
         matches: vec![(0, 0..4), (1, 5..9)],
         diagnostics: vec![(0, 4..5), (1, 0..4)],
+        carets: vec![],
+        semantic: None,
+        semantic_lines: None,
         theme: String::new(),
         colors: SyntaxColors {
             match_color: Color::from_rgb(1.0, 0.0, 0.0),
@@ -50,6 +54,9 @@ fn syntax_highlighter_highlights_meta_comments() {
         extension: String::from("rs"),
         matches: vec![],
         diagnostics: vec![],
+        carets: vec![],
+        semantic: None,
+        semantic_lines: None,
         theme: String::new(),
         colors: SyntaxColors {
             match_color: Color::BLACK,
@@ -63,3 +70,30 @@ fn syntax_highlighter_highlights_meta_comments() {
         .collect();
     assert!(line.iter().any(|(_, c)| *c == settings.colors.meta_color));
 }
+
+#[test]
+fn semantic_highlighting_follows_tree_sitter_captures() {
+    let doc = DocumentHighlights::new("semantic-test", "fn main() {}\n// x\n", Lang::Rust).unwrap();
+    let settings = SyntaxSettings {
+        extension: String::from("rs"),
+        matches: vec![],
+        diagnostics: vec![],
+        carets: vec![],
+        semantic: Some(doc.lines().clone()),
+        // Свёрнутое представление: первая строка редактора — вторая строка
+        // полного текста.
+        semantic_lines: Some(vec![1]),
+        theme: String::from("InspiredGitHub"),
+        colors: SyntaxColors {
+            match_color: Color::BLACK,
+            diagnostic_color: Color::BLACK,
+            meta_color: Color::BLACK,
+        },
+    };
+    let mut highlighter = SyntaxHighlighter::new(&settings);
+    let line: Vec<_> = highlighter.highlight_line("// x").collect();
+    let (plain, comment) = (line[0].clone(), line.last().unwrap().clone());
+    assert_eq!(plain.0, 0..4);
+    assert_eq!(comment.0, 0..4);
+    assert_ne!(plain.1, comment.1);
+}
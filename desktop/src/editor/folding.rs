@@ -0,0 +1,272 @@
+//! Сворачивание кода по структуре блоков.
+//!
+//! Свернуть можно многострочный блок функции, класса или цикла
+//! ([`is_foldable`]): заголовок блока остаётся на месте, а остальные его
+//! строки скрываются. Виджет iced не умеет прятать строки, поэтому
+//! свёрнутая вкладка показывает в редакторе представление без скрытых
+//! участков ([`FoldedView`]) и переводит позиции между ним и полным
+//! текстом. Правка в представлении переносится в полный текст; правка,
+//! которая задела бы скрытый текст, не выполняется, а разворачивает его.
+
+use std::collections::HashSet;
+use std::ops::Range;
+
+use iced::widget::text_editor::{Action, Content};
+use multicode_core::BlockInfo;
+
+use super::multi_cursor::{content_with_cursor, cursor_offset, offset_of};
+
+/// Блок функции, класса или цикла.
+pub fn is_foldable(kind: &str) -> bool {
+    let kind = kind.to_lowercase();
+    (kind.starts_with("function") && kind != "function/call")
+        || kind == "loop"
+        || ["class", "impl", "trait", "interface"]
+            .iter()
+            .any(|k| kind.contains(k))
+}
+
+/// Сворачиваемый блок: идентификатор, строка заголовка и последняя строка.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoldRegion {
+    pub id: String,
+    pub header: usize,
+    pub end: usize,
+}
+
+/// Сворачиваемые блоки текста по порядку заголовков; объемлющий блок идёт
+/// раньше вложенного с тем же заголовком.
+pub fn fold_regions<'a>(
+    text: &str,
+    blocks: impl IntoIterator<Item = &'a BlockInfo>,
+) -> Vec<FoldRegion> {
+    let newlines: Vec<usize> = text
+        .bytes()
+        .enumerate()
+        .filter(|(_, b)| *b == b'\n')
+        .map(|(i, _)| i)
+        .collect();
+    let line_of = |offset: usize| newlines.partition_point(|n| *n < offset);
+    let mut regions: Vec<FoldRegion> = blocks
+        .into_iter()
+        .filter(|b| is_foldable(&b.kind) && b.range.1 > b.range.0)
+        .map(|b| FoldRegion {
+            id: b.visual_id.clone(),
+            header: line_of(b.range.0.min(text.len())),
+            end: line_of((b.range.1 - 1).min(text.len())),
+        })
+        .filter(|r| r.end > r.header)
+        .collect();
+    regions.sort_by(|a, b| a.header.cmp(&b.header).then(b.end.cmp(&a.end)));
+    regions
+}
+
+/// Скрытые участки полного текста для свёрнутых блоков `folded`: от конца
+/// строки заголовка до конца последней строки блока, в символах.
+/// Вложенные участки поглощаются объемлющими.
+pub fn hidden_ranges(
+    text: &str,
+    regions: &[FoldRegion],
+    folded: &HashSet<String>,
+) -> Vec<(String, Range<usize>)> {
+    let mut hidden: Vec<(String, Range<usize>)> = Vec::new();
+    for region in regions.iter().filter(|r| folded.contains(&r.id)) {
+        let range =
+            offset_of(text, region.header, usize::MAX)..offset_of(text, region.end, usize::MAX);
+        match hidden.last_mut() {
+            Some((_, last)) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => hidden.push((region.id.clone(), range)),
+        }
+    }
+    hidden
+}
+
+/// Представление свёрнутой вкладки.
+#[derive(Debug)]
+pub struct FoldedView {
+    /// Содержимое, которое показывает редактор.
+    pub editor: Content,
+    /// Полный текст, из которого построено представление.
+    source: String,
+    /// Скрытые участки полного текста и блоки, которым они принадлежат.
+    hidden: Vec<(String, Range<usize>)>,
+    /// Текст представления.
+    text: String,
+    /// Номер строки полного текста для каждой строки представления.
+    lines: Vec<usize>,
+    /// Строки заголовков свёрнутых блоков.
+    headers: Vec<usize>,
+}
+
+impl FoldedView {
+    /// Представление `text` без участков `hidden` с кареткой в позиции
+    /// полного текста `cursor`; `None`, если скрывать нечего.
+    pub fn new(text: &str, hidden: Vec<(String, Range<usize>)>, cursor: usize) -> Option<Self> {
+        if hidden.is_empty() {
+            return None;
+        }
+        let mut view = Self {
+            editor: Content::new(),
+            source: text.to_string(),
+            hidden,
+            text: String::new(),
+            lines: Vec::new(),
+            headers: Vec::new(),
+        };
+        view.index();
+        view.editor = content_with_cursor(&view.text, view.to_view(cursor));
+        Some(view)
+    }
+
+    fn index(&mut self) {
+        let chars: Vec<char> = self.source.chars().collect();
+        let mut text = String::with_capacity(self.source.len());
+        let mut lines = vec![0];
+        let mut headers = Vec::with_capacity(self.hidden.len());
+        let mut line = 0;
+        let mut prev = 0;
+        for (_, range) in &self.hidden {
+            for c in &chars[prev..range.start] {
+                text.push(*c);
+                if *c == '\n' {
+                    line += 1;
+                    lines.push(line);
+                }
+            }
+            headers.push(line);
+            line += chars[range.clone()].iter().filter(|c| **c == '\n').count();
+            prev = range.end;
+        }
+        for c in &chars[prev..] {
+            text.push(*c);
+            if *c == '\n' {
+                line += 1;
+                lines.push(line);
+            }
+        }
+        self.text = text;
+        self.lines = lines;
+        self.headers = headers;
+    }
+
+    /// Построено ли представление из текста `text`.
+    pub fn is_current(&self, text: &str) -> bool {
+        self.source == text
+    }
+
+    /// Полный текст с правками, сделанными в представлении.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn hidden(&self) -> &[(String, Range<usize>)] {
+        &self.hidden
+    }
+
+    /// Свёрнут ли блок с заголовком в строке `line` полного текста.
+    pub fn is_folded(&self, line: usize) -> bool {
+        self.headers.contains(&line)
+    }
+
+    /// Строка полного текста для строки представления.
+    pub fn full_line(&self, view_line: usize) -> usize {
+        self.lines.get(view_line).copied().unwrap_or(view_line)
+    }
+
+    /// Строка представления для строки полного текста; `None`, если строка
+    /// скрыта.
+    pub fn view_line(&self, line: usize) -> Option<usize> {
+        self.lines.binary_search(&line).ok()
+    }
+
+    /// Позиция полного текста для позиции представления. Позиция на
+    /// границе скрытого участка остаётся перед ним.
+    pub fn to_full(&self, offset: usize) -> usize {
+        let mut full = offset;
+        for (_, range) in &self.hidden {
+            if range.start < full {
+                full += range.len();
+            } else {
+                break;
+            }
+        }
+        full
+    }
+
+    /// Позиция представления для позиции полного текста; позиция внутри
+    /// скрытого участка переносится в конец заголовка.
+    pub fn to_view(&self, offset: usize) -> usize {
+        let mut shift = 0;
+        for (_, range) in &self.hidden {
+            if range.end <= offset {
+                shift += range.len();
+            } else if range.start < offset {
+                return range.start - shift;
+            } else {
+                break;
+            }
+        }
+        offset - shift
+    }
+
+    /// Позиция каретки представления в полном тексте.
+    pub fn cursor(&self) -> usize {
+        self.to_full(cursor_offset(&self.text, self.editor.cursor_position()))
+    }
+
+    /// Выполняет действие в представлении и переносит правку в полный
+    /// текст. Если правка задела бы скрытый текст, возвращает блоки,
+    /// которые нужно развернуть; представление после этого непригодно.
+    pub fn perform(&mut self, action: Action) -> Result<(), Vec<String>> {
+        if !action.is_edit() {
+            self.editor.perform(action);
+            return Ok(());
+        }
+        let old: Vec<char> = self.editor.text().chars().collect();
+        self.editor.perform(action);
+        let new: Vec<char> = self.editor.text().chars().collect();
+        if old == new {
+            return Ok(());
+        }
+        let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let start = self.to_full(prefix);
+        let end = self.to_full(old.len() - suffix);
+        let touched: Vec<String> = self
+            .hidden
+            .iter()
+            .filter(|(_, r)| r.start < end && r.end > start)
+            .map(|(id, _)| id.clone())
+            .collect();
+        if !touched.is_empty() {
+            return Err(touched);
+        }
+        let inserted: String = new[prefix..new.len() - suffix].iter().collect();
+        let delta = inserted.chars().count() as isize - (end - start) as isize;
+        let chars: Vec<char> = self.source.chars().collect();
+        let end = end.min(chars.len());
+        self.source = chars[..start]
+            .iter()
+            .copied()
+            .chain(inserted.chars())
+            .chain(chars[end..].iter().copied())
+            .collect();
+        // Как и `Content::text`, полный текст заканчивается переводом строки.
+        if !self.source.ends_with('\n') {
+            self.source.push('\n');
+        }
+        for (_, range) in &mut self.hidden {
+            if range.start >= end {
+                range.start = range.start.saturating_add_signed(delta);
+                range.end = range.end.saturating_add_signed(delta);
+            }
+        }
+        self.index();
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{HashMap, HashSet};
+
+use super::folding::{fold_regions, hidden_ranges, FoldRegion, FoldedView};
+use super::multi_cursor::offset_of;
+use iced::widget::text_editor::{Action, Edit};
+use multicode_core::BlockInfo;
+
+const TEXT: &str = "fn a() {\n    for x in y {\n        z();\n    }\n}\nfn b() {}\n";
+
+fn block(id: &str, kind: &str, range: (usize, usize)) -> BlockInfo {
+    BlockInfo {
+        visual_id: id.into(),
+        node_id: None,
+        kind: kind.into(),
+        translations: HashMap::new(),
+        range,
+        anchors: vec![],
+        x: 0.0,
+        y: 0.0,
+        width: None,
+        height: None,
+        ports: vec![],
+        ai: None,
+        tags: vec![],
+        links: vec![],
+        color: None,
+        icon: None,
+        doc: None,
+    }
+}
+
+fn blocks() -> Vec<BlockInfo> {
+    vec![
+        block("a", "Function", (0, 46)),
+        block("loop", "Loop", (13, 44)),
+        block("b", "Function", (47, 56)),
+        block("call", "Function/Call", (30, 34)),
+    ]
+}
+
+fn folded_loop(cursor: usize) -> FoldedView {
+    let regions = fold_regions(TEXT, &blocks());
+    let hidden = hidden_ranges(TEXT, &regions, &HashSet::from(["loop".to_string()]));
+    FoldedView::new(TEXT, hidden, cursor).unwrap()
+}
+
+#[test]
+fn only_multiline_functions_and_loops_fold() {
+    assert_eq!(
+        fold_regions(TEXT, &blocks()),
+        [
+            FoldRegion {
+                id: "a".into(),
+                header: 0,
+                end: 4
+            },
+            FoldRegion {
+                id: "loop".into(),
+                header: 1,
+                end: 3
+            },
+        ]
+    );
+    let regions = fold_regions(TEXT, &blocks());
+    // Вложенный свёрнутый блок поглощается объемлющим.
+    let all = HashSet::from(["a".to_string(), "loop".to_string()]);
+    assert_eq!(
+        hidden_ranges(TEXT, &regions, &all),
+        [("a".to_string(), 8..46)]
+    );
+}
+
+#[test]
+fn folded_view_hides_the_body_and_maps_positions() {
+    let view = folded_loop(offset_of(TEXT, 2, 3));
+    assert_eq!(
+        view.editor.text(),
+        "fn a() {\n    for x in y {\n}\nfn b() {}\n"
+    );
+    assert!(view.is_folded(1));
+    assert_eq!(view.full_line(2), 4);
+    assert_eq!(view.view_line(3), None);
+    assert_eq!(view.view_line(5), Some(3));
+    // Каретка из скрытой строки переходит в конец заголовка.
+    assert_eq!(view.cursor(), offset_of(TEXT, 1, usize::MAX));
+}
+
+#[test]
+fn edits_keep_hidden_text_or_unfold_it() {
+    let mut view = folded_loop(offset_of(TEXT, 1, usize::MAX));
+    assert_eq!(view.perform(Action::Edit(Edit::Insert('!'))), Ok(()));
+    assert!(view
+        .source()
+        .starts_with("fn a() {\n    for x in y {!\n        z();"));
+    assert_eq!(view.hidden()[0].1, 26..45);
+
+    // Delete в конце заголовка соединил бы его со скрытой строкой.
+    let mut view = folded_loop(offset_of(TEXT, 1, usize::MAX));
+    assert_eq!(
+        view.perform(Action::Edit(Edit::Delete)),
+        Err(vec!["loop".to_string()])
+    );
+    let mut view = folded_loop(offset_of(TEXT, 4, 0));
+    assert_eq!(
+        view.perform(Action::Edit(Edit::Backspace)),
+        Err(vec!["loop".to_string()])
+    );
+}
@@ -0,0 +1,42 @@
+//! Подсказки блоков в конце строк.
+//!
+//! В строке, где начинается блок с метаданными или связями, редактор
+//! показывает идентификатор блока и число входящих и исходящих ссылок —
+//! так при правке текста видно, на какой код ссылаются схемы. Входящие
+//! ссылки считаются по метаданным всего проекта ([`WorkspaceSymbols`]).
+
+use std::collections::BTreeMap;
+
+use multicode_core::meta::symbols::WorkspaceSymbols;
+use multicode_core::BlockInfo;
+
+/// Текст подсказки: идентификатор и ссылки `←входящие →исходящие`.
+pub fn hint(id: &str, incoming: usize, outgoing: usize) -> String {
+    format!("#{id} ←{incoming} →{outgoing}")
+}
+
+/// Подсказки по строкам текста. Подсказку получает блок, у которого есть
+/// метаданные или хотя бы одна ссылка; подсказки блоков, начинающихся в
+/// одной строке, объединяются от внешнего блока к внутреннему.
+pub fn inlay_hints(
+    text: &str,
+    blocks: &[BlockInfo],
+    symbols: &WorkspaceSymbols,
+) -> BTreeMap<usize, String> {
+    let newlines: Vec<usize> = text.match_indices('\n').map(|(i, _)| i).collect();
+    let mut hints: BTreeMap<usize, String> = BTreeMap::new();
+    for block in blocks {
+        let incoming = symbols.incoming_links(&block.visual_id);
+        let outgoing = block.links.len();
+        if incoming == 0 && outgoing == 0 && !symbols.ids.contains(&block.visual_id) {
+            continue;
+        }
+        let line = newlines.partition_point(|n| *n < block.range.0.min(text.len()));
+        let entry = hints.entry(line).or_default();
+        if !entry.is_empty() {
+            entry.push_str("  ");
+        }
+        entry.push_str(&hint(&block.visual_id, incoming, outgoing));
+    }
+    hints
+}
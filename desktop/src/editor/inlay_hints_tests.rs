@@ -0,0 +1,45 @@
+use std::collections::HashMap;
+
+use super::inlay_hints::{hint, inlay_hints};
+use multicode_core::meta::symbols::WorkspaceSymbols;
+use multicode_core::BlockInfo;
+
+const TEXT: &str = "fn a() {\n    b();\n}\nfn b() {}\n";
+
+fn block(id: &str, range: (usize, usize), links: &[&str]) -> BlockInfo {
+    BlockInfo {
+        visual_id: id.into(),
+        node_id: None,
+        kind: "Function".into(),
+        translations: HashMap::new(),
+        range,
+        anchors: vec![],
+        x: 0.0,
+        y: 0.0,
+        width: None,
+        height: None,
+        ports: vec![],
+        ai: None,
+        tags: vec![],
+        links: links.iter().map(|l| l.to_string()).collect(),
+        color: None,
+        icon: None,
+        doc: None,
+    }
+}
+
+#[test]
+fn hints_mark_blocks_with_metadata_or_links() {
+    let mut symbols = WorkspaceSymbols::default();
+    symbols.add_content("// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"links\":[\"b\"]}\n");
+    let blocks = vec![
+        block("a", (0, 19), &["b"]),
+        block("call", (13, 16), &[]),
+        block("b", (20, 29), &[]),
+        block("plain", (20, 29), &[]),
+    ];
+    let hints = inlay_hints(TEXT, &blocks, &symbols);
+    assert_eq!(hints.len(), 2);
+    assert_eq!(hints[&0], hint("a", 0, 1));
+    assert_eq!(hints[&3], "#b ←1 →0");
+}
@@ -0,0 +1,279 @@
+//! Эмуляция привязок Vim и Emacs в редакторе кода.
+//!
+//! Редактор iced понимает только стандартные клавиши, поэтому режим ввода
+//! ([`InputMode`]) переводит нажатия в действия редактора до того, как они
+//! изменят текст. Vim ([`Vim`]) модальный: в обычном режиме и режиме
+//! выделения символы — это команды с необязательным счётчиком, в режиме
+//! вставки ввод идёт как обычно. Пресет Emacs ([`emacs_command`]) связывает
+//! сочетания с `Ctrl` и `Alt` с перемещениями и удалением.
+
+use std::fmt;
+
+use iced::keyboard::Modifiers;
+use iced::widget::text_editor::{Action, Edit, Motion};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum InputMode {
+    #[default]
+    Standard,
+    Vim,
+    Emacs,
+}
+
+impl InputMode {
+    pub const ALL: [InputMode; 3] = [InputMode::Standard, InputMode::Vim, InputMode::Emacs];
+}
+
+impl fmt::Display for InputMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputMode::Standard => write!(f, "Standard"),
+            InputMode::Vim => write!(f, "Vim"),
+            InputMode::Emacs => write!(f, "Emacs"),
+        }
+    }
+}
+
+/// Команда режима ввода для редактора.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputCommand {
+    Action(Action),
+    /// Удалить строки, начиная со строки курсора; см. [`delete_lines`].
+    DeleteLines(usize),
+    Undo,
+    Redo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VimMode {
+    #[default]
+    Normal,
+    Insert,
+    Visual,
+}
+
+impl fmt::Display for VimMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VimMode::Normal => write!(f, "NORMAL"),
+            VimMode::Insert => write!(f, "INSERT"),
+            VimMode::Visual => write!(f, "VISUAL"),
+        }
+    }
+}
+
+/// Состояние модального ввода Vim.
+#[derive(Debug, Clone, Default)]
+pub struct Vim {
+    pub mode: VimMode,
+    count: Option<usize>,
+    /// Первая клавиша двухсимвольной команды (`dd`, `gg`).
+    pending: Option<char>,
+}
+
+fn action(action: Action) -> InputCommand {
+    InputCommand::Action(action)
+}
+
+fn edit(edit: Edit) -> InputCommand {
+    InputCommand::Action(Action::Edit(edit))
+}
+
+fn move_to(motion: Motion) -> InputCommand {
+    InputCommand::Action(Action::Move(motion))
+}
+
+fn repeat(commands: Vec<InputCommand>, count: usize) -> Vec<InputCommand> {
+    std::iter::repeat_n(commands, count).flatten().collect()
+}
+
+impl Vim {
+    /// Набранные счётчик и первая клавиша команды для строки состояния.
+    pub fn pending(&self) -> String {
+        let count = self.count.map(|n| n.to_string()).unwrap_or_default();
+        format!(
+            "{count}{}",
+            self.pending.map(String::from).unwrap_or_default()
+        )
+    }
+
+    /// Действие редактора в обычном режиме или режиме выделения. `None` —
+    /// действие выполняется как есть.
+    pub fn action(&mut self, action: &Action) -> Option<Vec<InputCommand>> {
+        if self.mode == VimMode::Insert {
+            return None;
+        }
+        match action {
+            Action::Edit(Edit::Insert(c)) => Some(self.key(*c)),
+            Action::Edit(Edit::Enter) => Some(self.key('j')),
+            Action::Edit(Edit::Backspace) => Some(self.key('h')),
+            Action::Edit(Edit::Delete) => Some(self.key('x')),
+            Action::Edit(Edit::Paste(_)) => Some(Vec::new()),
+            Action::Move(motion) if self.mode == VimMode::Visual => {
+                Some(vec![self.motion(*motion)])
+            }
+            _ => None,
+        }
+    }
+
+    /// Выход в обычный режим; выделение снимается.
+    pub fn escape(&mut self) -> Vec<InputCommand> {
+        self.count = None;
+        self.pending = None;
+        match std::mem::take(&mut self.mode) {
+            VimMode::Visual => vec![move_to(Motion::Left)],
+            VimMode::Normal | VimMode::Insert => Vec::new(),
+        }
+    }
+
+    /// Клавиша `c` в обычном режиме или режиме выделения.
+    pub fn key(&mut self, c: char) -> Vec<InputCommand> {
+        if let Some(first) = self.pending.take() {
+            let count = self.take_count();
+            return match (first, c) {
+                ('g', 'g') => vec![self.motion(Motion::DocumentStart)],
+                ('d', 'd') => vec![InputCommand::DeleteLines(count)],
+                _ => Vec::new(),
+            };
+        }
+        if let Some(digit) = c.to_digit(10) {
+            if digit != 0 || self.count.is_some() {
+                let count = self.count.unwrap_or(0);
+                self.count = Some(count.saturating_mul(10).saturating_add(digit as usize));
+                return Vec::new();
+            }
+        }
+        let visual = self.mode == VimMode::Visual;
+        if c == 'g' || (c == 'd' && !visual) {
+            self.pending = Some(c);
+            return Vec::new();
+        }
+        let explicit = self.count.is_some();
+        let count = self.take_count();
+        let motion = match c {
+            'h' => Some(Motion::Left),
+            'l' | ' ' => Some(Motion::Right),
+            'j' => Some(Motion::Down),
+            'k' => Some(Motion::Up),
+            'w' => Some(Motion::WordRight),
+            'b' => Some(Motion::WordLeft),
+            '0' => Some(Motion::Home),
+            '$' => Some(Motion::End),
+            _ => None,
+        };
+        if let Some(motion) = motion {
+            return repeat(vec![self.motion(motion)], count);
+        }
+        match c {
+            // `5G` — на пятую строку, `G` — в конец документа.
+            'G' if explicit => {
+                let mut commands = vec![self.motion(Motion::DocumentStart)];
+                commands.extend(repeat(vec![self.motion(Motion::Down)], count - 1));
+                commands
+            }
+            'G' => vec![self.motion(Motion::DocumentEnd)],
+            'x' | 'd' if visual => {
+                self.mode = VimMode::Normal;
+                vec![edit(Edit::Delete)]
+            }
+            'x' => repeat(vec![edit(Edit::Delete)], count),
+            'X' => repeat(vec![edit(Edit::Backspace)], count),
+            'i' => self.insert(Vec::new()),
+            'a' => self.insert(vec![move_to(Motion::Right)]),
+            'I' => self.insert(vec![move_to(Motion::Home)]),
+            'A' => self.insert(vec![move_to(Motion::End)]),
+            'o' => self.insert(vec![move_to(Motion::End), edit(Edit::Enter)]),
+            'O' => self.insert(vec![
+                move_to(Motion::Home),
+                edit(Edit::Enter),
+                move_to(Motion::Up),
+            ]),
+            'v' if visual => self.escape(),
+            'v' => {
+                self.mode = VimMode::Visual;
+                Vec::new()
+            }
+            'u' => repeat(vec![InputCommand::Undo], count),
+            _ => Vec::new(),
+        }
+    }
+
+    fn take_count(&mut self) -> usize {
+        self.count.take().unwrap_or(1).max(1)
+    }
+
+    fn motion(&self, motion: Motion) -> InputCommand {
+        if self.mode == VimMode::Visual {
+            action(Action::Select(motion))
+        } else {
+            move_to(motion)
+        }
+    }
+
+    fn insert(&mut self, commands: Vec<InputCommand>) -> Vec<InputCommand> {
+        self.mode = VimMode::Insert;
+        commands
+    }
+}
+
+/// Действия, удаляющие `count` строк, начиная со строки `line` документа
+/// из `line_count` строк. Удаление до конца документа захватывает перевод
+/// строки перед первой удаляемой строкой, чтобы не оставлять пустую.
+pub fn delete_lines(line: usize, line_count: usize, count: usize) -> Vec<Action> {
+    if line + count < line_count {
+        let mut actions = vec![Action::Move(Motion::Home)];
+        actions.extend(std::iter::repeat_n(Action::Select(Motion::Down), count));
+        actions.push(Action::Edit(Edit::Delete));
+        return actions;
+    }
+    let start = if line == 0 {
+        vec![Action::Move(Motion::DocumentStart)]
+    } else {
+        vec![Action::Move(Motion::Up), Action::Move(Motion::End)]
+    };
+    start
+        .into_iter()
+        .chain([
+            Action::Select(Motion::DocumentEnd),
+            Action::Edit(Edit::Delete),
+        ])
+        .collect()
+}
+
+/// Команды пресета Emacs для сочетания `modifiers` + `key`; `None`, если
+/// сочетание пресету не принадлежит.
+pub fn emacs_command(key: &str, modifiers: Modifiers) -> Option<Vec<InputCommand>> {
+    let commands = if modifiers.control() && !modifiers.alt() {
+        match key {
+            "f" => vec![move_to(Motion::Right)],
+            "b" => vec![move_to(Motion::Left)],
+            "n" => vec![move_to(Motion::Down)],
+            "p" => vec![move_to(Motion::Up)],
+            "a" => vec![move_to(Motion::Home)],
+            "e" => vec![move_to(Motion::End)],
+            "d" => vec![edit(Edit::Delete)],
+            "h" => vec![edit(Edit::Backspace)],
+            // В конце строки выделение пусто и удаляется перевод строки.
+            "k" => vec![action(Action::Select(Motion::End)), edit(Edit::Delete)],
+            "/" => vec![InputCommand::Undo],
+            _ => return None,
+        }
+    } else if modifiers.alt() && !modifiers.control() {
+        match key {
+            "f" => vec![move_to(Motion::WordRight)],
+            "b" => vec![move_to(Motion::WordLeft)],
+            "d" => vec![
+                action(Action::Select(Motion::WordRight)),
+                edit(Edit::Delete),
+            ],
+            "v" => vec![move_to(Motion::PageUp)],
+            "<" => vec![move_to(Motion::DocumentStart)],
+            ">" => vec![move_to(Motion::DocumentEnd)],
+            _ => return None,
+        }
+    } else {
+        return None;
+    };
+    Some(commands)
+}
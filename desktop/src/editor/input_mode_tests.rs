@@ -0,0 +1,104 @@
+use super::input_mode::{delete_lines, emacs_command, InputCommand, Vim, VimMode};
+use iced::keyboard::Modifiers;
+use iced::widget::text_editor::{Action, Content, Edit, Motion};
+
+fn keys(vim: &mut Vim, keys: &str) -> Vec<InputCommand> {
+    keys.chars().flat_map(|c| vim.key(c)).collect()
+}
+
+#[test]
+fn normal_mode_keys_become_motions_with_counts() {
+    let mut vim = Vim::default();
+    assert_eq!(
+        keys(&mut vim, "3jw"),
+        [
+            InputCommand::Action(Action::Move(Motion::Down)),
+            InputCommand::Action(Action::Move(Motion::Down)),
+            InputCommand::Action(Action::Move(Motion::Down)),
+            InputCommand::Action(Action::Move(Motion::WordRight)),
+        ]
+    );
+    assert_eq!(
+        keys(&mut vim, "0gg"),
+        [
+            InputCommand::Action(Action::Move(Motion::Home)),
+            InputCommand::Action(Action::Move(Motion::DocumentStart)),
+        ]
+    );
+    assert_eq!(vim.pending(), "");
+    keys(&mut vim, "12d");
+    assert_eq!(vim.pending(), "12d");
+    assert_eq!(keys(&mut vim, "d"), [InputCommand::DeleteLines(12)]);
+    assert_eq!(vim.mode, VimMode::Normal);
+}
+
+#[test]
+fn typing_is_intercepted_only_outside_insert_mode() {
+    let mut vim = Vim::default();
+    let typed = Action::Edit(Edit::Insert('a'));
+    assert_eq!(
+        vim.action(&typed),
+        Some(vec![InputCommand::Action(Action::Move(Motion::Right))])
+    );
+    assert_eq!(vim.mode, VimMode::Insert);
+    assert_eq!(vim.action(&typed), None);
+    assert!(vim.escape().is_empty());
+    assert_eq!(vim.mode, VimMode::Normal);
+    assert_eq!(vim.action(&Action::Move(Motion::Left)), None);
+
+    keys(&mut vim, "v");
+    assert_eq!(
+        vim.action(&Action::Move(Motion::End)),
+        Some(vec![InputCommand::Action(Action::Select(Motion::End))])
+    );
+    assert_eq!(
+        keys(&mut vim, "d"),
+        [InputCommand::Action(Action::Edit(Edit::Delete))]
+    );
+    assert_eq!(vim.mode, VimMode::Normal);
+}
+
+#[test]
+fn delete_lines_takes_a_newline_with_each_line() {
+    assert_eq!(
+        delete_lines(1, 4, 2),
+        [
+            Action::Move(Motion::Home),
+            Action::Select(Motion::Down),
+            Action::Select(Motion::Down),
+            Action::Edit(Edit::Delete),
+        ]
+    );
+    // Последние строки удаляются вместе с переводом строки перед ними.
+    assert_eq!(
+        delete_lines(2, 3, 1),
+        [
+            Action::Move(Motion::Up),
+            Action::Move(Motion::End),
+            Action::Select(Motion::DocumentEnd),
+            Action::Edit(Edit::Delete),
+        ]
+    );
+    let mut content: Content = Content::with_text("one\ntwo");
+    for action in delete_lines(0, 2, 5) {
+        content.perform(action);
+    }
+    assert_eq!(content.text().trim_end(), "");
+}
+
+#[test]
+fn emacs_preset_maps_control_and_alt_keys() {
+    assert_eq!(
+        emacs_command("k", Modifiers::CTRL),
+        Some(vec![
+            InputCommand::Action(Action::Select(Motion::End)),
+            InputCommand::Action(Action::Edit(Edit::Delete)),
+        ])
+    );
+    assert_eq!(
+        emacs_command("f", Modifiers::ALT),
+        Some(vec![InputCommand::Action(Action::Move(Motion::WordRight))])
+    );
+    assert_eq!(emacs_command("q", Modifiers::CTRL), None);
+    assert_eq!(emacs_command("f", Modifiers::empty()), None);
+}
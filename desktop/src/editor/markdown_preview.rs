@@ -0,0 +1,314 @@
+//! Предпросмотр Markdown.
+//!
+//! Текст разбирается pulldown-cmark в список элементов ([`preview_items`]),
+//! которые затем отрисовываются колонкой ([`markdown_preview`]).
+//! Комментарии `@VISUAL_META` показываются карточками блоков. Ссылки на
+//! заголовки и блоки документа — `[текст](#id)`, `[текст](id)` и `links`
+//! карточек — становятся внутренними якорями: щелчок по ним прокручивает
+//! предпросмотр и ставит каретку на строку цели.
+
+use std::collections::HashSet;
+
+use iced::widget::{button, column, container, horizontal_rule, row, text, Column};
+use iced::{theme, Element, Font, Length};
+use multicode_core::meta;
+use pulldown_cmark::{Event, Options, Parser, Tag};
+
+use crate::app::events::Message;
+
+/// Прокрутка предпросмотра, см. [`iced::widget::scrollable::Id`].
+pub const PREVIEW_SCROLL: &str = "markdown-preview";
+
+/// Цель ссылки.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Link {
+    /// Заголовок или блок этого документа.
+    Anchor(String),
+    /// Внешний адрес, открывается в браузере.
+    External(String),
+    /// Якорь, которого нет в документе.
+    Missing(String),
+}
+
+/// Участок текста, возможно со ссылкой.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub link: Option<Link>,
+}
+
+/// Карточка блока из комментария `@VISUAL_META`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaCard {
+    pub id: String,
+    /// Русский или первый попавшийся перевод метки блока.
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub links: Vec<Link>,
+}
+
+/// Элемент предпросмотра.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewItem {
+    Heading {
+        level: usize,
+        text: String,
+        anchor: String,
+    },
+    Paragraph(Vec<Span>),
+    ListItem(Vec<Span>),
+    Code(String),
+    Rule,
+    Meta(MetaCard),
+}
+
+/// Якорь заголовка: строчные буквы и цифры, пробелы заменены дефисами.
+pub fn slug(text: &str) -> String {
+    text.trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            c if c.is_whitespace() => Some('-'),
+            _ => None,
+        })
+        .collect()
+}
+
+fn push_text(spans: &mut Vec<Span>, text: &str, link: Option<&str>) {
+    let link = link.map(|l| Link::External(l.to_string()));
+    match spans.last_mut() {
+        Some(last) if last.link == link => last.text.push_str(text),
+        _ => spans.push(Span {
+            text: text.to_string(),
+            link,
+        }),
+    }
+}
+
+fn meta_card(meta: meta::VisualMeta) -> MetaCard {
+    let title = meta
+        .translations
+        .get("ru")
+        .or_else(|| meta.translations.values().next())
+        .cloned();
+    MetaCard {
+        id: meta.id,
+        title,
+        tags: meta.tags,
+        links: meta.links.into_iter().map(Link::Anchor).collect(),
+    }
+}
+
+/// Элементы предпросмотра `content` со строкой исходника, с которой каждый
+/// начинается.
+pub fn preview_items(content: &str) -> Vec<(usize, PreviewItem)> {
+    let newlines: Vec<usize> = content.match_indices('\n').map(|(i, _)| i).collect();
+    let line_of = |offset: usize| newlines.partition_point(|n| *n < offset);
+
+    let mut items = Vec::new();
+    let mut spans: Vec<Span> = Vec::new();
+    let mut start = 0;
+    let mut link: Option<String> = None;
+    let mut code: Option<String> = None;
+    let mut items_open = 0usize;
+
+    for (event, range) in Parser::new_ext(content, Options::all()).into_offset_iter() {
+        match event {
+            Event::Start(Tag::Heading(..)) | Event::Start(Tag::Paragraph) if items_open == 0 => {
+                spans.clear();
+                start = line_of(range.start);
+            }
+            Event::End(Tag::Heading(level, id, _)) => {
+                let text: String = spans.drain(..).map(|s| s.text).collect();
+                let anchor = id.map_or_else(|| slug(&text), str::to_string);
+                items.push((
+                    start,
+                    PreviewItem::Heading {
+                        level: level as usize,
+                        text,
+                        anchor,
+                    },
+                ));
+            }
+            Event::End(Tag::Paragraph) if items_open == 0 => {
+                items.push((start, PreviewItem::Paragraph(std::mem::take(&mut spans))));
+            }
+            Event::Start(Tag::Item) => {
+                // Вложенный список завершает текст объемлющего пункта.
+                if items_open > 0 && !spans.is_empty() {
+                    items.push((start, PreviewItem::ListItem(std::mem::take(&mut spans))));
+                }
+                spans.clear();
+                push_text(&mut spans, &format!("{}• ", "  ".repeat(items_open)), None);
+                start = line_of(range.start);
+                items_open += 1;
+            }
+            Event::End(Tag::Item) => {
+                items_open -= 1;
+                if !spans.is_empty() {
+                    items.push((start, PreviewItem::ListItem(std::mem::take(&mut spans))));
+                }
+            }
+            Event::Start(Tag::CodeBlock(_)) => code = Some(String::new()),
+            Event::End(Tag::CodeBlock(_)) => {
+                let text = code.take().unwrap_or_default();
+                items.push((line_of(range.start), PreviewItem::Code(text)));
+            }
+            Event::Start(Tag::Link(_, dest, _)) => link = Some(dest.to_string()),
+            Event::End(Tag::Link(..)) => link = None,
+            Event::Text(t) => match &mut code {
+                Some(code) => code.push_str(&t),
+                None => push_text(&mut spans, &t, link.as_deref()),
+            },
+            Event::Code(t) => push_text(&mut spans, &t, link.as_deref()),
+            Event::SoftBreak => push_text(&mut spans, " ", link.as_deref()),
+            Event::HardBreak => push_text(&mut spans, "\n", link.as_deref()),
+            Event::End(Tag::TableCell) => push_text(&mut spans, "  ", None),
+            Event::End(Tag::TableHead) | Event::End(Tag::TableRow) => {
+                let row = std::mem::take(&mut spans);
+                items.push((line_of(range.start), PreviewItem::Paragraph(row)));
+            }
+            Event::Rule => items.push((line_of(range.start), PreviewItem::Rule)),
+            Event::Html(html) if html.contains("@VISUAL_META") => {
+                for meta in meta::read_all(&html) {
+                    items.push((line_of(range.start), PreviewItem::Meta(meta_card(meta))));
+                }
+            }
+            _ => {}
+        }
+    }
+    if !spans.is_empty() {
+        items.push((start, PreviewItem::Paragraph(spans)));
+    }
+    resolve_links(&mut items);
+    items
+}
+
+/// Якоря элементов: заголовки и карточки блоков.
+fn anchor_of(item: &PreviewItem) -> Option<&str> {
+    match item {
+        PreviewItem::Heading { anchor, .. } => Some(anchor),
+        PreviewItem::Meta(card) => Some(&card.id),
+        _ => None,
+    }
+}
+
+fn resolve_links(items: &mut [(usize, PreviewItem)]) {
+    let anchors: HashSet<String> = items
+        .iter()
+        .filter_map(|(_, item)| anchor_of(item).map(str::to_string))
+        .collect();
+    let resolve = |link: &mut Link| {
+        let (Link::External(target) | Link::Anchor(target)) = link else {
+            return;
+        };
+        let anchor = target.strip_prefix('#').unwrap_or(target);
+        *link = if anchors.contains(anchor) {
+            Link::Anchor(anchor.to_string())
+        } else if target.contains(':') {
+            Link::External(target.clone())
+        } else {
+            Link::Missing(anchor.to_string())
+        };
+    };
+    for (_, item) in items {
+        match item {
+            PreviewItem::Paragraph(spans) | PreviewItem::ListItem(spans) => spans
+                .iter_mut()
+                .filter_map(|s| s.link.as_mut())
+                .for_each(resolve),
+            PreviewItem::Meta(card) => card.links.iter_mut().for_each(resolve),
+            _ => {}
+        }
+    }
+}
+
+/// Строка исходника, с которой начинается якорь `anchor`.
+pub fn anchor_line(items: &[(usize, PreviewItem)], anchor: &str) -> Option<usize> {
+    items
+        .iter()
+        .find(|(_, item)| anchor_of(item) == Some(anchor))
+        .map(|(line, _)| *line)
+}
+
+fn link_button(label: &str, link: &Link) -> Element<'static, Message> {
+    match link {
+        Link::Anchor(anchor) => button(text(label))
+            .style(theme::Button::Text)
+            .padding(0)
+            .on_press(Message::PreviewAnchor(anchor.clone()))
+            .into(),
+        Link::External(url) => button(text(label))
+            .style(theme::Button::Text)
+            .padding(0)
+            .on_press(Message::OpenExternalLink(url.clone()))
+            .into(),
+        Link::Missing(anchor) => text(format!("{label} (нет #{anchor})")).into(),
+    }
+}
+
+/// Абзац: текст целиком, а под ним — ссылки из него. Виджеты iced 0.12 не
+/// умеют кнопки внутри переносимого текста.
+fn spans_view(spans: &[Span]) -> Element<'static, Message> {
+    let body = text(spans.iter().map(|s| s.text.as_str()).collect::<String>());
+    let links: Vec<Element<Message>> = spans
+        .iter()
+        .filter_map(|s| Some(link_button(s.text.trim(), s.link.as_ref()?)))
+        .collect();
+    if links.is_empty() {
+        body.into()
+    } else {
+        column![body, row(links).spacing(10)].spacing(2).into()
+    }
+}
+
+fn card_view(card: &MetaCard) -> Element<'static, Message> {
+    let mut body =
+        column![text(card.title.clone().unwrap_or_else(|| card.id.clone())).size(18)].spacing(4);
+    if card.title.is_some() {
+        body = body.push(text(format!("#{}", card.id)).size(12));
+    }
+    if !card.tags.is_empty() {
+        body = body.push(text(format!("Теги: {}", card.tags.join(", "))));
+    }
+    if !card.links.is_empty() {
+        let mut links: Vec<Element<Message>> = vec![text("Связи:").into()];
+        links.extend(card.links.iter().map(|link| {
+            let (Link::Anchor(id) | Link::External(id) | Link::Missing(id)) = link;
+            link_button(id, link)
+        }));
+        body = body.push(row(links).spacing(10));
+    }
+    container(body)
+        .padding(8)
+        .width(Length::Fill)
+        .style(theme::Container::Box)
+        .into()
+}
+
+/// Отрисовывает предпросмотр `content`.
+pub fn markdown_preview(content: &str) -> Column<'static, Message> {
+    let elements = preview_items(content)
+        .into_iter()
+        .map(|(_, item)| match item {
+            PreviewItem::Heading { level, text: t, .. } => {
+                let size = match level {
+                    1 => 30.0,
+                    2 => 26.0,
+                    3 => 22.0,
+                    _ => 20.0,
+                };
+                text(t).size(size).into()
+            }
+            PreviewItem::Paragraph(spans) | PreviewItem::ListItem(spans) => spans_view(&spans),
+            PreviewItem::Code(code) => container(text(code).font(Font::MONOSPACE))
+                .padding(5)
+                .width(Length::Fill)
+                .style(theme::Container::Box)
+                .into(),
+            PreviewItem::Rule => horizontal_rule(1).into(),
+            PreviewItem::Meta(card) => card_view(&card),
+        });
+    column(elements.collect::<Vec<Element<Message>>>()).spacing(5)
+}
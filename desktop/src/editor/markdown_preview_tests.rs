@@ -0,0 +1,68 @@
+use super::markdown_preview::{anchor_line, preview_items, Link, MetaCard, PreviewItem, Span};
+
+const DOC: &str = "\
+# Обзор
+
+См. [клиент](#client), [сервер](#server) и [сайт](https://example.com).
+
+<!-- @VISUAL_META {\"id\":\"client\",\"x\":0.0,\"y\":0.0,\"links\":[\"parser\",\"обзор\"],\"translations\":{\"ru\":\"Клиент\"}} -->
+
+- один
+- два
+";
+
+#[test]
+fn headings_lists_and_meta_cards_become_items() {
+    let items = preview_items(DOC);
+    assert_eq!(
+        items[0],
+        (
+            0,
+            PreviewItem::Heading {
+                level: 1,
+                text: "Обзор".into(),
+                anchor: "обзор".into(),
+            }
+        )
+    );
+    assert_eq!(
+        items[2],
+        (
+            4,
+            PreviewItem::Meta(MetaCard {
+                id: "client".into(),
+                title: Some("Клиент".into()),
+                tags: vec![],
+                links: vec![Link::Missing("parser".into()), Link::Anchor("обзор".into())],
+            })
+        )
+    );
+    assert_eq!(items.len(), 5);
+    assert_eq!(
+        items[4].1,
+        PreviewItem::ListItem(vec![Span {
+            text: "• два".into(),
+            link: None
+        }])
+    );
+}
+
+#[test]
+fn links_resolve_to_anchors_in_the_document() {
+    let items = preview_items(DOC);
+    let PreviewItem::Paragraph(spans) = &items[1].1 else {
+        panic!("ожидался абзац: {:?}", items[1]);
+    };
+    let links: Vec<&Link> = spans.iter().filter_map(|s| s.link.as_ref()).collect();
+    assert_eq!(
+        links,
+        [
+            &Link::Anchor("client".into()),
+            &Link::Missing("server".into()),
+            &Link::External("https://example.com".into()),
+        ]
+    );
+    assert_eq!(anchor_line(&items, "client"), Some(4));
+    assert_eq!(anchor_line(&items, "обзор"), Some(0));
+    assert_eq!(anchor_line(&items, "server"), None);
+}
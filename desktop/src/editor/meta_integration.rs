@@ -4,8 +4,9 @@ use std::collections::{HashMap, HashSet};
 use std::ops::Range;
 
 use multicode_core::meta::{self, VisualMeta};
+use multicode_core::{viz_lint, BlockInfo};
 
-use crate::app::Diagnostic;
+use crate::app::{Diagnostic, QuickFix, TextEdit};
 
 static META_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"@VISUAL_META\s*(\{.*?\})").unwrap());
@@ -70,15 +71,30 @@ pub fn update_meta_comment(content: &str, meta: &VisualMeta) -> String {
 /// Validate JSON inside `@VISUAL_META` comments and produce diagnostics.
 pub fn validate_meta_json(content: &str) -> Vec<Diagnostic> {
     let mut diags = Vec::new();
+    // First comment of every id: extends cycle diagnostics point at it.
+    let mut positions: HashMap<String, (usize, Range<usize>)> = HashMap::new();
     for (line, range, json) in find_meta_comments(content) {
         match serde_json::from_str::<VisualMeta>(&json) {
             Ok(meta) => {
+                positions
+                    .entry(meta.id.clone())
+                    .or_insert((line, range.clone()));
                 if let Err(errors) = meta::validate(&meta) {
                     for e in errors {
+                        let fixes: Vec<QuickFix> = duplicate_fix(&meta, &e, line, &range, &json)
+                            .into_iter()
+                            .collect();
+                        // Исправление указывает на массив с повтором точнее,
+                        // чем весь комментарий.
+                        let range = fixes
+                            .first()
+                            .map_or(range.clone(), |f| f.edits[0].range.clone());
                         diags.push(Diagnostic {
                             line,
-                            range: range.clone(),
+                            range,
                             message: format!("{}: {}", e.field, e.message),
+                            fixes,
+                            block: Some(meta.id.clone()),
                         });
                     }
                 }
@@ -87,8 +103,77 @@ pub fn validate_meta_json(content: &str) -> Vec<Diagnostic> {
                 line,
                 range,
                 message: e.to_string(),
+                fixes: Vec::new(),
+                block: None,
             }),
         }
     }
+    for cycle in meta::inheritance::extends_cycles(content) {
+        let message = format!(
+            "цикл наследования extends: {} → {}",
+            cycle.join(" → "),
+            cycle[0]
+        );
+        for id in &cycle {
+            if let Some((line, range)) = positions.get(id) {
+                diags.push(Diagnostic {
+                    line: *line,
+                    range: range.clone(),
+                    message: message.clone(),
+                    fixes: Vec::new(),
+                    block: Some(id.clone()),
+                });
+            }
+        }
+    }
     diags
 }
+
+/// Замечания к комментариям `@viz`, привязанные к блокам `blocks`.
+pub fn lint_viz(content: &str, blocks: &[BlockInfo]) -> Vec<Diagnostic> {
+    viz_lint::lint_blocks(content, blocks)
+        .into_iter()
+        .map(|located| Diagnostic {
+            line: located.line,
+            range: located.range,
+            message: located.issue.message,
+            fixes: Vec::new(),
+            block: located.visual_id,
+        })
+        .collect()
+}
+
+/// Быстрое исправление повторяющегося тега или ссылки: в массиве поля
+/// ошибки остаётся только первое вхождение значения.
+fn duplicate_fix(
+    meta: &VisualMeta,
+    error: &meta::ValidationError,
+    line: usize,
+    range: &Range<usize>,
+    json: &str,
+) -> Option<QuickFix> {
+    let (values, title) = match error.field.as_str() {
+        "tags" => (&meta.tags, "Удалить повтор тега"),
+        "links" => (&meta.links, "Удалить повтор ссылки"),
+        _ => return None,
+    };
+    let value = values.iter().enumerate().find_map(|(i, v)| {
+        (values[..i].contains(v) && error.message.contains(&format!("'{v}'"))).then_some(v)
+    })?;
+    let array = Regex::new(&format!(r#""{}"\s*:\s*(\[[^\]]*\])"#, error.field)).ok()?;
+    let m = array.captures(json)?.get(1)?;
+    let items: Vec<String> = serde_json::from_str(m.as_str()).ok()?;
+    let mut seen = false;
+    let kept: Vec<&String> = items
+        .iter()
+        .filter(|i| *i != value || !std::mem::replace(&mut seen, true))
+        .collect();
+    Some(QuickFix {
+        title: format!("{title} '{value}'"),
+        edits: vec![TextEdit {
+            line,
+            range: range.start + m.start()..range.start + m.end(),
+            replacement: serde_json::to_string(&kept).ok()?,
+        }],
+    })
+}
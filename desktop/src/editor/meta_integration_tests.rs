@@ -1,4 +1,4 @@
-use super::meta_integration::{changed_meta_ids, find_meta_comments};
+use super::meta_integration::{changed_meta_ids, find_meta_comments, validate_meta_json};
 
 #[test]
 fn detects_new_meta_id() {
@@ -44,3 +44,34 @@ fn ignores_text_after_closing_brace() {
     assert_eq!(comments.len(), 1);
     assert_eq!(comments[0].2, "{\"id\":\"one\"}");
 }
+
+#[test]
+fn duplicate_tag_has_quick_fix() {
+    let content = "fn a() {}\n// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"tags\":[\"x\",\"y\",\"x\"]}\n";
+    let diags = validate_meta_json(content);
+    assert_eq!(diags.len(), 1);
+    let fix = &diags[0].fixes[0];
+    assert_eq!(fix.title, "Удалить повтор тега 'x'");
+    assert_eq!(
+        fix.apply(content),
+        "fn a() {}\n// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"tags\":[\"x\",\"y\"]}\n"
+    );
+    assert!(validate_meta_json(&fix.apply(content)).is_empty());
+}
+
+#[test]
+fn invalid_link_has_no_quick_fix() {
+    let content = "// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"links\":[\"jira:bad\"]}";
+    let diags = validate_meta_json(content);
+    assert_eq!(diags.len(), 1);
+    assert!(diags[0].fixes.is_empty());
+}
+
+#[test]
+fn extends_cycle_is_reported_on_each_member() {
+    let content = "// @VISUAL_META {\"id\":\"a\",\"x\":0.0,\"y\":0.0,\"extends\":\"b\"}\n// @VISUAL_META {\"id\":\"b\",\"x\":0.0,\"y\":0.0,\"extends\":\"a\"}\n";
+    let diags = validate_meta_json(content);
+    let lines: Vec<usize> = diags.iter().map(|d| d.line).collect();
+    assert_eq!(lines, [0, 1]);
+    assert_eq!(diags[0].message, "цикл наследования extends: a → b → a");
+}
@@ -1,15 +1,38 @@
 pub mod autocomplete;
 pub mod code_editor;
+pub mod folding;
+pub mod inlay_hints;
+pub mod input_mode;
+pub mod markdown_preview;
 pub mod meta_integration;
+pub mod multi_cursor;
+pub mod panes;
 pub mod syntax_highlighter;
 pub mod settings;
+pub mod sticky_headers;
 
 pub use autocomplete::{suggestions, AutocompleteState, Suggestion};
 pub use code_editor::CodeEditor;
 pub use syntax_highlighter::THEME_SET;
 pub use settings::{EditorSettings, EditorTheme, CustomTheme};
 
+#[cfg(test)]
+mod autocomplete_tests;
 #[cfg(test)]
 mod code_editor_tests;
 #[cfg(test)]
+mod folding_tests;
+#[cfg(test)]
+mod inlay_hints_tests;
+#[cfg(test)]
+mod input_mode_tests;
+#[cfg(test)]
+mod markdown_preview_tests;
+#[cfg(test)]
 mod meta_integration_tests;
+#[cfg(test)]
+mod multi_cursor_tests;
+#[cfg(test)]
+mod panes_tests;
+#[cfg(test)]
+mod sticky_headers_tests;
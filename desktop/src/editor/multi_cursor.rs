@@ -0,0 +1,331 @@
+//! Несколько кареток и прямоугольное выделение в редакторе кода.
+//!
+//! Виджет iced держит одну каретку, поэтому каретки хранятся отдельно
+//! ([`MultiCursor`]) позициями в символах текста. Правка применяется ко
+//! всем кареткам сразу и даёт новый текст целиком — в историю он попадает
+//! одним шагом и одним изменением уходит в синхронизацию. Каретки
+//! подсвечиваются в редакторе цветом совпадений поиска.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use iced::widget::text_editor::{Action, Content, Edit, Motion};
+
+/// Каретка: позиция в символах и начало выделения, если оно есть.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Caret {
+    pub position: usize,
+    pub anchor: Option<usize>,
+}
+
+impl Caret {
+    pub fn new(position: usize) -> Self {
+        Self {
+            position,
+            anchor: None,
+        }
+    }
+
+    /// Выделенный диапазон; без выделения — пустой диапазон в позиции.
+    pub fn range(&self) -> Range<usize> {
+        let anchor = self.anchor.unwrap_or(self.position);
+        anchor.min(self.position)..anchor.max(self.position)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MultiCursor {
+    carets: Vec<Caret>,
+}
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Строка и колонка символа с номером `offset`.
+pub fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let mut line = 0;
+    let mut column = 0;
+    for c in text.chars().take(offset) {
+        if c == '\n' {
+            line += 1;
+            column = 0;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
+
+/// Номер символа в колонке `column` строки `line`; колонка за концом
+/// строки прижимается к её концу.
+pub fn offset_of(text: &str, line: usize, column: usize) -> usize {
+    let mut offset = 0;
+    for (i, l) in text.split('\n').enumerate() {
+        let len = l.chars().count();
+        if i == line {
+            return offset + column.min(len);
+        }
+        offset += len + 1;
+    }
+    text.chars().count()
+}
+
+/// Номер символа под кареткой редактора: [`Content::cursor_position`]
+/// даёт колонку в байтах.
+pub fn cursor_offset(text: &str, (line, byte): (usize, usize)) -> usize {
+    let column = text
+        .split('\n')
+        .nth(line)
+        .map_or(0, |l| l[..byte.min(l.len())].chars().count());
+    offset_of(text, line, column)
+}
+
+/// Содержимое редактора с текстом `text` и кареткой перед символом
+/// `offset`. Каретка ставится вставкой, а не перемещением: перемещения
+/// требуют разметки строк, которой у нового содержимого ещё нет.
+pub fn content_with_cursor(text: &str, offset: usize) -> Content {
+    let split = text
+        .char_indices()
+        .nth(offset)
+        .map_or(text.len(), |(i, _)| i);
+    let mut content = Content::with_text(&text[split..]);
+    content.perform(Action::Move(Motion::DocumentStart));
+    content.perform(Action::Edit(Edit::Paste(Arc::new(
+        text[..split].to_string(),
+    ))));
+    content
+}
+
+/// Подсветка кареток: строка и байтовый диапазон. Каретка без выделения
+/// подсвечивает символ после себя, а в конце строки — символ перед собой.
+pub fn highlights(text: &str, carets: &[Caret]) -> Vec<(usize, Range<usize>)> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let byte = |line: usize, column: usize| {
+        lines[line]
+            .char_indices()
+            .nth(column)
+            .map_or(lines[line].len(), |(i, _)| i)
+    };
+    let mut result = Vec::new();
+    for caret in carets {
+        let range = caret.range();
+        let (start_line, start_col) = line_column(text, range.start);
+        let (end_line, end_col) = line_column(text, range.end);
+        if range.is_empty() {
+            let len = lines[start_line].chars().count();
+            let column = if start_col < len {
+                start_col
+            } else if len > 0 {
+                len - 1
+            } else {
+                continue;
+            };
+            result.push((
+                start_line,
+                byte(start_line, column)..byte(start_line, column + 1),
+            ));
+            continue;
+        }
+        for (line, text) in lines.iter().enumerate().take(end_line + 1).skip(start_line) {
+            let from = if line == start_line { start_col } else { 0 };
+            let to = if line == end_line {
+                end_col
+            } else {
+                text.chars().count()
+            };
+            if from < to {
+                result.push((line, byte(line, from)..byte(line, to)));
+            }
+        }
+    }
+    result
+}
+
+impl MultiCursor {
+    /// Кареток больше одной.
+    pub fn is_active(&self) -> bool {
+        self.carets.len() > 1
+    }
+
+    pub fn carets(&self) -> &[Caret] {
+        &self.carets
+    }
+
+    pub fn clear(&mut self) {
+        self.carets.clear();
+    }
+
+    /// Основная каретка — последняя по тексту; за ней следует каретка
+    /// редактора.
+    pub fn primary(&self) -> Option<Caret> {
+        self.carets.last().copied()
+    }
+
+    /// Заменяет каретки, упорядочивая их и отбрасывая пересекающиеся.
+    pub fn set(&mut self, mut carets: Vec<Caret>) {
+        carets.sort_by_key(|c| (c.range().start, c.range().end));
+        let mut result: Vec<Caret> = Vec::with_capacity(carets.len());
+        for caret in carets {
+            match result.last() {
+                Some(last) if caret.range().start < last.range().end => {}
+                Some(last) if *last == caret => {}
+                Some(last) if last.range().is_empty() && caret.range() == last.range() => {}
+                _ => result.push(caret),
+            }
+        }
+        self.carets = result;
+    }
+
+    /// Добавляет каретку в позиции `position`; если кареток ещё нет, первой
+    /// становится каретка редактора в позиции `primary`.
+    pub fn add(&mut self, primary: usize, position: usize) {
+        let mut carets = std::mem::take(&mut self.carets);
+        if carets.is_empty() {
+            carets.push(Caret::new(primary));
+        }
+        carets.push(Caret::new(position));
+        self.set(carets);
+    }
+
+    /// Добавляет каретку строкой выше первой или ниже последней каретки в
+    /// той же колонке. `false`, если такой строки нет.
+    pub fn add_vertical(&mut self, text: &str, primary: usize, down: bool) -> bool {
+        let mut carets = std::mem::take(&mut self.carets);
+        if carets.is_empty() {
+            carets.push(Caret::new(primary));
+        }
+        let edge = if down { carets.last() } else { carets.first() };
+        let (line, column) = line_column(text, edge.map_or(primary, |c| c.position));
+        let lines = text.split('\n').count();
+        let target = if down {
+            (line + 1 < lines).then_some(line + 1)
+        } else {
+            line.checked_sub(1)
+        };
+        if let Some(target) = target {
+            carets.push(Caret::new(offset_of(text, target, column)));
+        }
+        self.set(carets);
+        target.is_some()
+    }
+
+    /// Прямоугольное выделение между углами `from` и `to` (строка,
+    /// колонка): по каретке на строку с выделением между колонками углов.
+    pub fn column_select(&mut self, text: &str, from: (usize, usize), to: (usize, usize)) {
+        let lines = from.0.min(to.0)..=from.0.max(to.0);
+        let carets = lines
+            .map(|line| {
+                let anchor = offset_of(text, line, from.1);
+                let position = offset_of(text, line, to.1);
+                Caret {
+                    position,
+                    anchor: (anchor != position).then_some(anchor),
+                }
+            })
+            .collect();
+        self.set(carets);
+    }
+
+    /// Применяет правку во всех каретках и возвращает новый текст. Вставка
+    /// из нескольких строк по числу кареток раздаётся по строке на каретку.
+    pub fn edit(&mut self, text: &str, edit: &Edit) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let pasted: Vec<&str> = match edit {
+            Edit::Paste(s) => s.trim_end_matches('\n').split('\n').collect(),
+            _ => Vec::new(),
+        };
+        let distribute = pasted.len() > 1 && pasted.len() == self.carets.len();
+        let mut out = Vec::with_capacity(chars.len());
+        let mut prev = 0;
+        let mut carets = Vec::with_capacity(self.carets.len());
+        for (i, caret) in self.carets.iter().enumerate() {
+            let range = caret.range();
+            let (start, end, insert): (usize, usize, String) = match edit {
+                Edit::Insert(c) => (range.start, range.end, c.to_string()),
+                Edit::Paste(_) if distribute => (range.start, range.end, pasted[i].to_string()),
+                Edit::Paste(s) => (range.start, range.end, s.to_string()),
+                Edit::Enter => (range.start, range.end, "\n".into()),
+                Edit::Backspace if range.is_empty() => {
+                    (range.start.saturating_sub(1), range.end, String::new())
+                }
+                Edit::Delete if range.is_empty() => {
+                    (range.start, (range.end + 1).min(chars.len()), String::new())
+                }
+                Edit::Backspace | Edit::Delete => (range.start, range.end, String::new()),
+            };
+            let start = start.max(prev);
+            out.extend_from_slice(&chars[prev..start]);
+            out.extend(insert.chars());
+            carets.push(Caret::new(out.len()));
+            prev = end.max(start);
+        }
+        out.extend_from_slice(&chars[prev.min(chars.len())..]);
+        self.set(carets);
+        out.into_iter().collect()
+    }
+
+    /// Перемещает все каретки; `select` расширяет их выделения. `false`,
+    /// если перемещение каретками не поддерживается.
+    pub fn motion(&mut self, text: &str, motion: Motion, select: bool) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        let len = chars.len();
+        let moved = |caret: &Caret| -> Option<usize> {
+            let p = caret.position;
+            let (line, column) = line_column(text, p);
+            Some(match motion {
+                Motion::Left if !select && caret.anchor.is_some() => caret.range().start,
+                Motion::Right if !select && caret.anchor.is_some() => caret.range().end,
+                Motion::Left => p.saturating_sub(1),
+                Motion::Right => (p + 1).min(len),
+                Motion::Home => offset_of(text, line, 0),
+                Motion::End => offset_of(text, line, usize::MAX),
+                Motion::Up => match line.checked_sub(1) {
+                    Some(up) => offset_of(text, up, column),
+                    None => p,
+                },
+                Motion::Down if line + 1 < text.split('\n').count() => {
+                    offset_of(text, line + 1, column)
+                }
+                Motion::Down => p,
+                Motion::WordLeft => {
+                    let mut i = p;
+                    while i > 0 && !is_word(chars[i - 1]) {
+                        i -= 1;
+                    }
+                    while i > 0 && is_word(chars[i - 1]) {
+                        i -= 1;
+                    }
+                    i
+                }
+                Motion::WordRight => {
+                    let mut i = p;
+                    while i < len && !is_word(chars[i]) {
+                        i += 1;
+                    }
+                    while i < len && is_word(chars[i]) {
+                        i += 1;
+                    }
+                    i
+                }
+                Motion::PageUp | Motion::PageDown | Motion::DocumentStart | Motion::DocumentEnd => {
+                    return None
+                }
+            })
+        };
+        let mut carets = Vec::with_capacity(self.carets.len());
+        for caret in &self.carets {
+            let Some(position) = moved(caret) else {
+                return false;
+            };
+            let anchor = if select {
+                Some(caret.anchor.unwrap_or(caret.position)).filter(|a| *a != position)
+            } else {
+                None
+            };
+            carets.push(Caret { position, anchor });
+        }
+        self.set(carets);
+        true
+    }
+}
@@ -0,0 +1,71 @@
+use std::sync::Arc;
+
+use super::multi_cursor::{content_with_cursor, highlights, Caret, MultiCursor};
+use iced::widget::text_editor::{Content, Edit, Motion};
+
+fn carets(positions: &[usize]) -> MultiCursor {
+    let mut cursor = MultiCursor::default();
+    cursor.set(positions.iter().map(|p| Caret::new(*p)).collect());
+    cursor
+}
+
+#[test]
+fn edits_apply_at_every_caret() {
+    let text = "ab\ncd\nef";
+    let mut cursor = carets(&[1, 4, 7]);
+    let text = cursor.edit(text, &Edit::Insert('x'));
+    assert_eq!(text, "axb\ncxd\nexf");
+    let text = cursor.edit(&text, &Edit::Backspace);
+    assert_eq!(text, "ab\ncd\nef");
+    assert_eq!(
+        cursor.carets(),
+        [Caret::new(1), Caret::new(4), Caret::new(7)]
+    );
+
+    // Строки вставки раздаются по кареткам.
+    let text = cursor.edit(&text, &Edit::Paste(Arc::new("1\n2\n3\n".into())));
+    assert_eq!(text, "a1b\nc2d\ne3f");
+}
+
+#[test]
+fn motions_move_all_carets_and_merge_overlaps() {
+    let text = "one two\nthree";
+    let mut cursor = carets(&[0, 8]);
+    assert!(cursor.motion(text, Motion::WordRight, true));
+    assert_eq!(cursor.carets()[0].range(), 0..3);
+    assert_eq!(cursor.carets()[1].range(), 8..13);
+    assert!(cursor.motion(text, Motion::Up, false));
+    assert_eq!(cursor.carets(), [Caret::new(3), Caret::new(5)]);
+    assert!(cursor.motion(text, Motion::Home, false));
+    assert!(!cursor.is_active());
+    assert!(!cursor.motion(text, Motion::PageDown, false));
+}
+
+#[test]
+fn column_selection_clamps_to_short_lines() {
+    let text = "abcdef\nab\nabcdef";
+    let mut cursor = MultiCursor::default();
+    cursor.column_select(text, (0, 1), (2, 4));
+    let ranges: Vec<_> = cursor.carets().iter().map(Caret::range).collect();
+    assert_eq!(ranges, [1..4, 8..9, 11..14]);
+    assert_eq!(
+        highlights(text, cursor.carets()),
+        [(0, 1..4), (1, 1..2), (2, 1..4)]
+    );
+    assert_eq!(cursor.edit(text, &Edit::Delete), "aef\na\naef");
+
+    let mut cursor = MultiCursor::default();
+    assert!(cursor.add_vertical(text, 8, true));
+    assert!(cursor.add_vertical(text, 8, false));
+    assert_eq!(
+        cursor.carets(),
+        [Caret::new(1), Caret::new(8), Caret::new(11)]
+    );
+}
+
+#[test]
+fn rebuilt_content_keeps_the_cursor() {
+    let content: Content = content_with_cursor("ab\nсd", 4);
+    assert_eq!(content.text().trim_end(), "ab\nсd");
+    assert_eq!(content.cursor_position(), (1, 2));
+}
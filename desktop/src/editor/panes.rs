@@ -0,0 +1,153 @@
+//! Разделение редактора на панели.
+//!
+//! Открытые файлы по-прежнему лежат во вкладках приложения, а панели лишь
+//! раскладывают их: у каждой панели свой список файлов, активный файл и
+//! режим (текст или схема). Редактируется всегда файл панели в фокусе —
+//! он же активная вкладка приложения. Раскладка сохраняется в настройках
+//! и восстанавливается при следующем запуске.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Направление разделения.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaneAxis {
+    /// Панели рядом, слева и справа.
+    Horizontal,
+    /// Панели друг под другом.
+    Vertical,
+}
+
+/// Что показывает панель.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PaneMode {
+    #[default]
+    Text,
+    Visual,
+}
+
+/// Панель: свои вкладки, активный файл и режим.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pane {
+    #[serde(default)]
+    pub tabs: Vec<PathBuf>,
+    #[serde(default)]
+    pub active: Option<PathBuf>,
+    #[serde(default)]
+    pub mode: PaneMode,
+}
+
+impl Pane {
+    fn open(&mut self, path: &Path) {
+        if !self.tabs.iter().any(|p| p == path) {
+            self.tabs.push(path.to_path_buf());
+        }
+        self.active = Some(path.to_path_buf());
+    }
+
+    fn close(&mut self, path: &Path) {
+        let Some(pos) = self.tabs.iter().position(|p| p == path) else {
+            return;
+        };
+        self.tabs.remove(pos);
+        if self.active.as_deref() == Some(path) {
+            self.active = self
+                .tabs
+                .get(pos.min(self.tabs.len().saturating_sub(1)))
+                .cloned();
+        }
+    }
+}
+
+/// Две панели редактора.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaneLayout {
+    pub axis: PaneAxis,
+    pub panes: [Pane; 2],
+    /// Панель в фокусе, `0` или `1`.
+    #[serde(default)]
+    pub focused: usize,
+    /// Файлы, которые ещё открываются при восстановлении раскладки.
+    #[serde(skip)]
+    pub restoring: Vec<PathBuf>,
+}
+
+impl PaneLayout {
+    /// Разделяет редактор: первая панель получает открытые файлы `tabs`,
+    /// вторая — только `active`, и фокус переходит во вторую.
+    pub fn split(axis: PaneAxis, tabs: Vec<PathBuf>, active: Option<PathBuf>) -> Self {
+        let second = Pane {
+            tabs: active.iter().cloned().collect(),
+            active: active.clone(),
+            mode: PaneMode::Text,
+        };
+        Self {
+            axis,
+            panes: [
+                Pane {
+                    tabs,
+                    active,
+                    mode: PaneMode::Text,
+                },
+                second,
+            ],
+            focused: 1,
+            restoring: Vec::new(),
+        }
+    }
+
+    pub fn focused_pane(&self) -> &Pane {
+        &self.panes[self.focused.min(1)]
+    }
+
+    /// Панель, в которой открыт файл `path`; при совпадении — панель в
+    /// фокусе.
+    pub fn pane_of(&self, path: &Path) -> Option<usize> {
+        let focused = self.focused.min(1);
+        [focused, 1 - focused]
+            .into_iter()
+            .find(|&i| self.panes[i].tabs.iter().any(|p| p == path))
+    }
+
+    /// Учитывает, что активным стал файл `path`: если его уже показывает
+    /// другая панель, фокус переходит туда, иначе файл открывается в
+    /// панели в фокусе.
+    pub fn activate(&mut self, path: &Path) {
+        let focused = self.focused.min(1);
+        let other = 1 - focused;
+        self.focused = if self.panes[other].active.as_deref() == Some(path) {
+            other
+        } else {
+            focused
+        };
+        self.panes[self.focused].open(path);
+    }
+
+    /// Убирает файл `path` из панели `pane`. Возвращает `true`, если файл
+    /// больше не открыт ни в одной панели и его вкладку можно закрыть.
+    pub fn close(&mut self, pane: usize, path: &Path) -> bool {
+        if let Some(p) = self.panes.get_mut(pane) {
+            p.close(path);
+        }
+        self.pane_of(path).is_none()
+    }
+
+    /// Файлы всех панелей в порядке открытия при восстановлении: активный
+    /// файл панели в фокусе идёт последним, чтобы стать активной вкладкой.
+    pub fn restore_order(&self) -> Vec<PathBuf> {
+        let focused = self.focused.min(1);
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for pane in [&self.panes[1 - focused], &self.panes[focused]] {
+            let others = pane
+                .tabs
+                .iter()
+                .filter(|p| Some(*p) != pane.active.as_ref());
+            for path in others.chain(pane.active.iter()) {
+                paths.retain(|p| p != path);
+                paths.push(path.clone());
+            }
+        }
+        paths
+    }
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use super::panes::{PaneAxis, PaneLayout, PaneMode};
+
+fn paths(names: &[&str]) -> Vec<PathBuf> {
+    names.iter().map(PathBuf::from).collect()
+}
+
+#[test]
+fn split_moves_focus_to_second_pane() {
+    let layout = PaneLayout::split(
+        PaneAxis::Horizontal,
+        paths(&["a.rs", "b.rs"]),
+        Some("b.rs".into()),
+    );
+    assert_eq!(layout.focused, 1);
+    assert_eq!(layout.panes[0].tabs, paths(&["a.rs", "b.rs"]));
+    assert_eq!(layout.panes[1].tabs, paths(&["b.rs"]));
+    assert_eq!(layout.panes[1].mode, PaneMode::Text);
+}
+
+#[test]
+fn activating_file_opens_it_in_focused_pane_or_focuses_its_pane() {
+    let mut layout = PaneLayout::split(
+        PaneAxis::Vertical,
+        paths(&["a.rs", "b.rs"]),
+        Some("b.rs".into()),
+    );
+    layout.activate("c.rs".as_ref());
+    assert_eq!(layout.panes[1].tabs, paths(&["b.rs", "c.rs"]));
+    assert_eq!(layout.panes[1].active, Some("c.rs".into()));
+
+    // Файл показан в первой панели — фокус переходит туда.
+    layout.focused = 0;
+    layout.activate("a.rs".as_ref());
+    layout.focused = 1;
+    layout.activate("a.rs".as_ref());
+    assert_eq!(layout.focused, 0);
+    assert_eq!(layout.panes[1].active, Some("c.rs".into()));
+
+    // Файл лишь есть среди вкладок первой панели — он открывается во второй.
+    layout.focused = 1;
+    layout.activate("b.rs".as_ref());
+    assert_eq!(layout.focused, 1);
+    assert_eq!(layout.panes[1].active, Some("b.rs".into()));
+}
+
+#[test]
+fn closing_reports_whether_file_is_still_shown() {
+    let mut layout = PaneLayout::split(
+        PaneAxis::Horizontal,
+        paths(&["a.rs", "b.rs"]),
+        Some("b.rs".into()),
+    );
+    assert!(!layout.close(1, "b.rs".as_ref()));
+    assert!(layout.panes[1].tabs.is_empty());
+    assert_eq!(layout.panes[1].active, None);
+    assert!(layout.close(0, "b.rs".as_ref()));
+    assert_eq!(layout.panes[0].active, Some("a.rs".into()));
+}
+
+#[test]
+fn restore_opens_focused_pane_active_file_last() {
+    let mut layout = PaneLayout::split(
+        PaneAxis::Horizontal,
+        paths(&["a.rs", "b.rs"]),
+        Some("a.rs".into()),
+    );
+    layout.activate("c.rs".as_ref());
+    layout.activate("a.rs".as_ref());
+    layout.activate("c.rs".as_ref());
+    assert_eq!(layout.restore_order(), paths(&["b.rs", "a.rs", "c.rs"]));
+}
@@ -1,8 +1,12 @@
 use iced::Color;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::path::{Path, PathBuf};
 
-mod serde_color {
+use super::input_mode::InputMode;
+use crate::visual::blocks::BlockColors;
+
+pub(crate) mod serde_color {
     use iced::Color;
     use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
@@ -27,16 +31,32 @@ pub enum EditorTheme {
     Light,
     Dark,
     HighContrast,
+    Solarized,
+    Nord,
     Custom,
 }
 
 impl EditorTheme {
-    pub const ALL: [EditorTheme; 4] = [
+    pub const ALL: [EditorTheme; 6] = [
         EditorTheme::Light,
         EditorTheme::Dark,
         EditorTheme::HighContrast,
+        EditorTheme::Solarized,
+        EditorTheme::Nord,
         EditorTheme::Custom,
     ];
+
+    /// Полная палитра темы: встроенный пресет или пользовательская тема
+    /// `custom`. Для светлой, тёмной и контрастной тем возвращает `None`,
+    /// и используются стандартные цвета iced.
+    pub fn colors(&self, custom: &CustomTheme) -> Option<CustomTheme> {
+        match self {
+            EditorTheme::Light | EditorTheme::Dark | EditorTheme::HighContrast => None,
+            EditorTheme::Solarized => Some(CustomTheme::solarized()),
+            EditorTheme::Nord => Some(CustomTheme::nord()),
+            EditorTheme::Custom => Some(custom.clone()),
+        }
+    }
 }
 
 impl Default for EditorTheme {
@@ -51,12 +71,20 @@ impl fmt::Display for EditorTheme {
             EditorTheme::Light => write!(f, "Light"),
             EditorTheme::Dark => write!(f, "Dark"),
             EditorTheme::HighContrast => write!(f, "High Contrast"),
+            EditorTheme::Solarized => write!(f, "Solarized"),
+            EditorTheme::Nord => write!(f, "Nord"),
             EditorTheme::Custom => write!(f, "Custom"),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Цвета пользовательской темы.
+///
+/// Загружается из файла JSON или TOML; отсутствующие в файле поля берутся
+/// из значений по умолчанию. Цвета задаются массивами `[r, g, b]` в
+/// диапазоне `0.0..=1.0`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CustomTheme {
     #[serde(with = "serde_color")]
     pub background: Color,
@@ -64,6 +92,14 @@ pub struct CustomTheme {
     pub foreground: Color,
     #[serde(with = "serde_color")]
     pub current_line: Color,
+    /// Фон холста визуального редактора.
+    #[serde(with = "serde_color")]
+    pub canvas_background: Color,
+    /// Линии сетки холста.
+    #[serde(with = "serde_color")]
+    pub canvas_grid: Color,
+    /// Цвета блоков по категориям.
+    pub blocks: BlockColors,
 }
 
 impl Default for CustomTheme {
@@ -72,6 +108,67 @@ impl Default for CustomTheme {
             background: Color::from_rgb(1.0, 1.0, 1.0),
             foreground: Color::from_rgb(0.0, 0.0, 0.0),
             current_line: Color::from_rgb(0.9, 0.9, 0.9),
+            canvas_background: Color::from_rgb(1.0, 1.0, 1.0),
+            canvas_grid: Color::from_rgb(0.9, 0.9, 0.9),
+            blocks: BlockColors::default(),
+        }
+    }
+}
+
+fn hex(rgb: u32) -> Color {
+    Color::from_rgb8((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+}
+
+impl CustomTheme {
+    /// Тёмный вариант палитры Solarized.
+    pub fn solarized() -> Self {
+        Self {
+            background: hex(0x002b36),
+            foreground: hex(0x839496),
+            current_line: hex(0x073642),
+            canvas_background: hex(0x002b36),
+            canvas_grid: hex(0x073642),
+            blocks: BlockColors {
+                arithmetic: hex(0xdc322f),
+                conditional: hex(0x859900),
+                loops: hex(0x268bd2),
+                variables: hex(0xb58900),
+                functions: hex(0xd33682),
+            },
+        }
+    }
+
+    /// Палитра Nord.
+    pub fn nord() -> Self {
+        Self {
+            background: hex(0x2e3440),
+            foreground: hex(0xd8dee9),
+            current_line: hex(0x3b4252),
+            canvas_background: hex(0x2e3440),
+            canvas_grid: hex(0x3b4252),
+            blocks: BlockColors {
+                arithmetic: hex(0xbf616a),
+                conditional: hex(0xa3be8c),
+                loops: hex(0x81a1c1),
+                variables: hex(0xebcb8b),
+                functions: hex(0xb48ead),
+            },
+        }
+    }
+
+    /// Загружает тему из файла; формат определяется расширением
+    /// (`.toml`, иначе JSON).
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&data, path.extension().and_then(|e| e.to_str()) == Some("toml"))
+    }
+
+    /// Разбирает тему из строки в формате TOML или JSON.
+    pub fn parse(data: &str, toml: bool) -> Result<Self, String> {
+        if toml {
+            toml::from_str(data).map_err(|e| e.to_string())
+        } else {
+            serde_json::from_str(data).map_err(|e| e.to_string())
         }
     }
 }
@@ -88,10 +185,22 @@ pub struct EditorSettings {
     pub line_wrapping: bool,
     #[serde(default = "default_true")]
     pub highlight_current_line: bool,
+    /// Закреплять над редактором заголовки объемлющих функций и классов.
+    #[serde(default = "default_true")]
+    pub sticky_headers: bool,
+    /// Показывать в конце строк идентификаторы блоков и число их связей.
+    #[serde(default = "default_true")]
+    pub inlay_hints: bool,
     #[serde(default)]
     pub theme: EditorTheme,
     #[serde(default)]
     pub custom_theme: CustomTheme,
+    /// Файл пользовательской темы, перечитываемый при изменении.
+    #[serde(default)]
+    pub theme_file: Option<PathBuf>,
+    /// Привязки клавиш редактора: стандартные, Vim или Emacs.
+    #[serde(default)]
+    pub input_mode: InputMode,
 }
 
 fn default_font_size() -> u16 {
@@ -114,8 +223,54 @@ impl Default for EditorSettings {
             auto_indent: true,
             line_wrapping: false,
             highlight_current_line: true,
+            sticky_headers: true,
+            inlay_hints: true,
             theme: EditorTheme::Light,
             custom_theme: CustomTheme::default(),
+            theme_file: None,
+            input_mode: InputMode::default(),
         }
     }
 }
+
+impl EditorSettings {
+    /// Палитра выбранной темы, см. [`EditorTheme::colors`].
+    pub fn palette(&self) -> Option<CustomTheme> {
+        self.theme.colors(&self.custom_theme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_partial_theme_files() {
+        let toml = r#"
+            background = [0.0, 0.0, 0.0]
+            canvas_grid = [0.5, 0.5, 0.5]
+
+            [blocks]
+            loops = [0.0, 0.0, 1.0]
+        "#;
+        let theme = CustomTheme::parse(toml, true).unwrap();
+        assert_eq!(theme.background, Color::from_rgb(0.0, 0.0, 0.0));
+        assert_eq!(theme.canvas_grid, Color::from_rgb(0.5, 0.5, 0.5));
+        assert_eq!(theme.blocks.loops, Color::from_rgb(0.0, 0.0, 1.0));
+        assert_eq!(theme.foreground, CustomTheme::default().foreground);
+        assert_eq!(theme.blocks.functions, BlockColors::default().functions);
+
+        let json = r#"{"canvas_background": [0.1, 0.2, 0.3]}"#;
+        let theme = CustomTheme::parse(json, false).unwrap();
+        assert_eq!(theme.canvas_background, Color::from_rgb(0.1, 0.2, 0.3));
+        assert!(CustomTheme::parse("background = 1", true).is_err());
+    }
+
+    #[test]
+    fn presets_provide_palettes() {
+        let custom = CustomTheme::default();
+        assert_eq!(EditorTheme::Light.colors(&custom), None);
+        assert_eq!(EditorTheme::Nord.colors(&custom), Some(CustomTheme::nord()));
+        assert_eq!(EditorTheme::Custom.colors(&custom), Some(custom));
+    }
+}
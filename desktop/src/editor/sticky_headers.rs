@@ -0,0 +1,51 @@
+//! Закреплённые заголовки объемлющих блоков.
+//!
+//! Пока верхняя видимая строка редактора лежит внутри функции или класса,
+//! а строка с их заголовком уже прокручена, заголовок показывается над
+//! редактором. Границы блоков берутся из разбора вкладки, а верхняя строка
+//! — из прокрутки редактора ([`top_line`]).
+
+use multicode_core::BlockInfo;
+
+use super::folding::{fold_regions, is_foldable};
+
+/// Прокрутка редактора кода, см. [`iced::widget::scrollable::Id`].
+pub const EDITOR_SCROLL: &str = "code-editor";
+
+/// Высота строки редактора: размер шрифта по умолчанию и межстрочный
+/// интервал `1.3`.
+pub const LINE_HEIGHT: f32 = 16.0 * 1.3;
+
+/// Отступ текста от края редактора.
+const PADDING: f32 = 5.0;
+
+/// Больше заголовков не закрепляется, чтобы они не закрывали текст.
+pub const MAX_HEADERS: usize = 3;
+
+/// Верхняя видимая строка при прокрутке на `offset` пикселей.
+pub fn top_line(offset: f32) -> usize {
+    ((offset - PADDING).max(0.0) / LINE_HEIGHT) as usize
+}
+
+/// Функция или класс; циклы заголовков не закрепляют.
+pub fn is_scope(kind: &str) -> bool {
+    is_foldable(kind) && !kind.eq_ignore_ascii_case("loop")
+}
+
+/// Заголовки блоков, содержащих строку `top` и начатых выше неё: номер
+/// строки и её текст, от внешнего блока к внутреннему.
+pub fn sticky_headers(text: &str, blocks: &[BlockInfo], top: usize) -> Vec<(usize, String)> {
+    let mut headers: Vec<usize> = fold_regions(text, blocks.iter().filter(|b| is_scope(&b.kind)))
+        .into_iter()
+        .filter(|r| r.header < top && top <= r.end)
+        .map(|r| r.header)
+        .collect();
+    headers.dedup();
+    let skip = headers.len().saturating_sub(MAX_HEADERS);
+    let lines: Vec<&str> = text.lines().collect();
+    headers
+        .into_iter()
+        .skip(skip)
+        .filter_map(|line| Some((line, lines.get(line)?.trim_end().to_string())))
+        .collect()
+}
@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use super::sticky_headers::{sticky_headers, top_line, LINE_HEIGHT};
+use multicode_core::BlockInfo;
+
+const TEXT: &str =
+    "impl A {\n    fn a() {\n        for x in y {\n            z();\n        }\n    }\n}\n";
+
+fn block(id: &str, kind: &str, range: (usize, usize)) -> BlockInfo {
+    BlockInfo {
+        visual_id: id.into(),
+        node_id: None,
+        kind: kind.into(),
+        translations: HashMap::new(),
+        range,
+        anchors: vec![],
+        x: 0.0,
+        y: 0.0,
+        width: None,
+        height: None,
+        ports: vec![],
+        ai: None,
+        tags: vec![],
+        links: vec![],
+        color: None,
+        icon: None,
+        doc: None,
+    }
+}
+
+fn blocks() -> Vec<BlockInfo> {
+    vec![
+        block("impl", "Impl", (0, TEXT.len() - 1)),
+        block("a", "Function", (9, 75)),
+        block("loop", "Loop", (22, 69)),
+    ]
+}
+
+#[test]
+fn enclosing_scopes_are_pinned_without_loops() {
+    let blocks = blocks();
+    assert!(sticky_headers(TEXT, &blocks, 0).is_empty());
+    assert_eq!(
+        sticky_headers(TEXT, &blocks, 1),
+        [(0, "impl A {".to_string())]
+    );
+    assert_eq!(
+        sticky_headers(TEXT, &blocks, 3),
+        [(0, "impl A {".to_string()), (1, "    fn a() {".to_string())]
+    );
+    assert_eq!(
+        sticky_headers(TEXT, &blocks, 6),
+        [(0, "impl A {".to_string())]
+    );
+    assert!(sticky_headers(TEXT, &blocks, 7).is_empty());
+}
+
+#[test]
+fn only_innermost_headers_are_kept() {
+    let mut text = String::new();
+    let mut blocks = Vec::new();
+    for depth in 0..5 {
+        let start = text.len();
+        text.push_str(&format!("{}fn f{depth}() {{\n", " ".repeat(depth * 4)));
+        blocks.push(block(&format!("f{depth}"), "Function", (start, 0)));
+    }
+    text.push_str("body\n");
+    for (depth, b) in blocks.iter_mut().enumerate().rev() {
+        text.push_str(&format!("{}}}\n", " ".repeat(depth * 4)));
+        b.range.1 = text.len() - 1;
+    }
+    let headers: Vec<usize> = sticky_headers(&text, &blocks, 5)
+        .into_iter()
+        .map(|(line, _)| line)
+        .collect();
+    assert_eq!(headers, [2, 3, 4]);
+}
+
+#[test]
+fn top_line_follows_scroll_offset() {
+    assert_eq!(top_line(0.0), 0);
+    assert_eq!(top_line(5.0 + LINE_HEIGHT * 2.5), 2);
+}
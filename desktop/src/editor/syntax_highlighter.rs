@@ -1,13 +1,15 @@
 use std::ops::Range;
+use std::sync::Arc;
 
 use iced::advanced::text::highlighter::Highlighter;
 use iced::Color;
+use multicode_core::parser::highlight::LineHighlights;
 use std::collections::HashMap;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use syntect::easy::HighlightLines;
-use syntect::highlighting::{Theme, ThemeSet};
-use syntect::parsing::SyntaxSet;
+use syntect::highlighting::{self, Theme, ThemeSet};
+use syntect::parsing::{Scope, SyntaxSet};
 
 pub static SYNTAX_SET: Lazy<SyntaxSet> = Lazy::new(SyntaxSet::load_defaults_newlines);
 pub static THEME_SET: Lazy<ThemeSet> = Lazy::new(ThemeSet::load_defaults);
@@ -42,6 +44,14 @@ pub struct SyntaxSettings {
     pub extension: String,
     pub matches: Vec<(usize, Range<usize>)>,
     pub diagnostics: Vec<(usize, Range<usize>)>,
+    /// Дополнительные каретки и их выделения.
+    pub carets: Vec<(usize, Range<usize>)>,
+    /// Подсветка tree-sitter по строкам полного текста; без неё текст
+    /// подсвечивается syntect по расширению файла.
+    pub semantic: Option<Arc<Vec<LineHighlights>>>,
+    /// Строка полного текста для каждой строки редактора, если редактор
+    /// показывает свёрнутое представление.
+    pub semantic_lines: Option<Vec<usize>>,
     pub theme: String,
     pub colors: SyntaxColors,
 }
@@ -49,10 +59,53 @@ pub struct SyntaxSettings {
 pub struct SyntaxHighlighter {
     settings: SyntaxSettings,
     highlighter: HighlightLines<'static>,
+    /// Цвета темы для захватов tree-sitter.
+    styles: highlighting::Highlighter<'static>,
+    capture_colors: HashMap<&'static str, Color>,
     current_line: usize,
     cache: HashMap<usize, Vec<(Range<usize>, Color)>>,
 }
 
+fn to_color(color: highlighting::Color) -> Color {
+    Color::from_rgb(
+        color.r as f32 / 255.0,
+        color.g as f32 / 255.0,
+        color.b as f32 / 255.0,
+    )
+}
+
+/// Область TextMate, по которой в теме syntect ищется цвет захвата
+/// tree-sitter, например `function.method` → `entity.name.function`.
+fn capture_scope(capture: &str) -> &'static str {
+    match capture {
+        "variable.builtin" => "variable.language",
+        "variable.parameter" => "variable.parameter",
+        "function.builtin" => "support.function",
+        "type.builtin" => "storage.type",
+        "constant.builtin" => "constant.language",
+        "string.escape" | "escape" => "constant.character.escape",
+        _ => match capture.split('.').next().unwrap_or(capture) {
+            "comment" => "comment",
+            "string" => "string",
+            "number" => "constant.numeric",
+            "boolean" => "constant.language",
+            "constant" => "constant.other",
+            "keyword" => "keyword",
+            "operator" => "keyword.operator",
+            "function" => "entity.name.function",
+            "type" | "constructor" => "entity.name.type",
+            "property" => "variable.other.member",
+            "attribute" => "entity.other.attribute-name",
+            "tag" => "entity.name.tag",
+            "label" => "entity.name.label",
+            "module" => "entity.name.namespace",
+            "punctuation" => "punctuation",
+            "variable" => "variable",
+            _ => "source",
+        },
+    }
+}
+
 fn load_highlighting(
     extension: &str,
     theme: &str,
@@ -80,6 +133,8 @@ impl Highlighter for SyntaxHighlighter {
         Self {
             settings: settings.clone(),
             highlighter: HighlightLines::new(syntax, theme),
+            styles: highlighting::Highlighter::new(theme),
+            capture_colors: HashMap::new(),
             current_line: 0,
             cache: HashMap::new(),
         }
@@ -88,6 +143,10 @@ impl Highlighter for SyntaxHighlighter {
     fn update(&mut self, new_settings: &Self::Settings) {
         let (syntax, theme) = load_highlighting(&new_settings.extension, &new_settings.theme);
         self.highlighter = HighlightLines::new(syntax, theme);
+        if new_settings.theme != self.settings.theme {
+            self.styles = highlighting::Highlighter::new(theme);
+            self.capture_colors.clear();
+        }
         self.settings = new_settings.clone();
         self.current_line = 0;
         self.cache.clear();
@@ -100,17 +159,32 @@ impl Highlighter for SyntaxHighlighter {
     fn highlight_line(&mut self, line: &str) -> Self::Iterator<'_> {
         let mut res = if let Some(cached) = self.cache.get(&self.current_line) {
             cached.clone()
+        } else if let Some(lines) = self.settings.semantic.clone() {
+            let full = match &self.settings.semantic_lines {
+                Some(map) => map.get(self.current_line).copied(),
+                None => Some(self.current_line),
+            };
+            let mut tmp = Vec::new();
+            if !line.is_empty() {
+                tmp.push((0..line.len(), self.capture_color("")));
+            }
+            for (range, capture) in full.and_then(|l| lines.get(l)).into_iter().flatten() {
+                // Подсветка могла отстать от текста — чужие границы пропускаются.
+                if range.end <= line.len()
+                    && line.is_char_boundary(range.start)
+                    && line.is_char_boundary(range.end)
+                {
+                    tmp.push((range.clone(), self.capture_color(capture)));
+                }
+            }
+            tmp
         } else {
             let mut tmp = Vec::new();
             if let Ok(ranges) = self.highlighter.highlight_line(line, &SYNTAX_SET) {
                 let mut start = 0;
                 for (style, text) in ranges {
                     let len = text.len();
-                    let color = Color::from_rgb(
-                        style.foreground.r as f32 / 255.0,
-                        style.foreground.g as f32 / 255.0,
-                        style.foreground.b as f32 / 255.0,
-                    );
+                    let color = to_color(style.foreground);
                     tmp.push((start..start + len, color));
                     start += len;
                 }
@@ -125,6 +199,11 @@ impl Highlighter for SyntaxHighlighter {
                 res.push((range.clone(), self.settings.colors.match_color));
             }
         }
+        for (line_idx, range) in &self.settings.carets {
+            if *line_idx == self.current_line {
+                res.push((range.clone(), self.settings.colors.match_color));
+            }
+        }
         for (line_idx, range) in &self.settings.diagnostics {
             if *line_idx == self.current_line {
                 res.push((range.clone(), self.settings.colors.diagnostic_color));
@@ -141,3 +220,17 @@ impl Highlighter for SyntaxHighlighter {
         self.current_line
     }
 }
+
+impl SyntaxHighlighter {
+    /// Цвет темы для захвата tree-sitter; пустое имя — цвет обычного текста.
+    fn capture_color(&mut self, capture: &'static str) -> Color {
+        let styles = &self.styles;
+        *self.capture_colors.entry(capture).or_insert_with(|| {
+            let scopes: Vec<Scope> = ["source", capture_scope(capture)]
+                .iter()
+                .filter_map(|s| Scope::new(s).ok())
+                .collect();
+            to_color(styles.style_for_stack(&scopes).foreground)
+        })
+    }
+}
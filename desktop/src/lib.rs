@@ -6,3 +6,4 @@ pub mod visual;
 pub mod ui;
 pub mod search;
 pub mod sync;
+pub mod terminal;
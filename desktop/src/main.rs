@@ -1,10 +1,15 @@
+use desktop::app::logging;
 use desktop::ui::MainUI;
 use iced::{Application, Settings};
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{fmt, EnvFilter};
 
 pub fn main() -> iced::Result {
     let filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("desktop=debug"));
-    tracing_subscriber::fmt().with_env_filter(filter).init();
+    tracing_subscriber::registry()
+        .with(fmt::layer().with_filter(filter))
+        .with(logging::layer())
+        .init();
     MainUI::run(Settings::default())
 }
@@ -65,6 +65,8 @@ mod tests {
             id: id.into(),
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             tags: vec![],
             links: vec![],
             anchors: vec![],
@@ -157,6 +157,8 @@ mod tests {
             id: id.to_string(),
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             tags: vec![],
             links: vec![],
             anchors: vec![],
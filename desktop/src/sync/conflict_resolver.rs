@@ -1,4 +1,5 @@
 use indexmap::IndexSet;
+use multicode_core::anchor;
 use multicode_core::meta::{AiNote, VisualMeta};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -61,7 +62,10 @@ impl ConflictResolver {
         use ConflictType::*;
         use ResolutionOption::*;
 
-        let movement = text.x != visual.x || text.y != visual.y;
+        let movement = text.x != visual.x
+            || text.y != visual.y
+            || text.width != visual.width
+            || text.height != visual.height;
         let meta_diff = text.tags != visual.tags
             || text.links != visual.links
             || text.anchors != visual.anchors
@@ -84,11 +88,13 @@ impl ConflictResolver {
             if movement {
                 resolved.x = visual.x;
                 resolved.y = visual.y;
+                resolved.width = visual.width;
+                resolved.height = visual.height;
             }
             if meta_diff {
                 resolved.tags = merge_strings(&text.tags, &visual.tags);
                 resolved.links = merge_strings(&text.links, &visual.links);
-                resolved.anchors = merge_strings(&text.anchors, &visual.anchors);
+                resolved.anchors = anchor::merge_named(&text.anchors, &visual.anchors);
                 resolved.tests = merge_strings(&text.tests, &visual.tests);
                 resolved.ai = merge_ai(&text.ai, &visual.ai);
                 resolved.extras = merge_json(&text.extras, &visual.extras);
@@ -178,6 +184,8 @@ mod tests {
             id: id.into(),
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             tags: vec![],
             links: vec![],
             anchors: vec![],
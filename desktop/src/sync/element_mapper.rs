@@ -162,6 +162,8 @@ mod tests {
             id: id.to_string(),
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             tags: Vec::new(),
             links: Vec::new(),
             anchors: Vec::new(),
@@ -29,13 +29,17 @@ use super::conflict_resolver::{
     ConflictResolver, ConflictType, ResolutionOption, ResolutionPolicy, SyncConflict,
 };
 use super::element_mapper::ElementMapper;
+use super::recorder::SessionRecorder;
 use super::settings::SyncSettings;
 use super::{
     generate_with_extensions, init_extensions, parse_with_extensions, resolve_with_extensions,
 };
 use multicode_core::meta::{self, VisualMeta, DEFAULT_VERSION};
 use multicode_core::parser::Lang;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
 
 /// Состояние синхронизации между текстовым и визуальным представлениями.
 #[derive(Debug, Clone, Default)]
@@ -58,7 +62,7 @@ pub struct SyncDiagnostics {
 }
 
 /// Сообщения для движка синхронизации.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SyncMessage {
     /// Текст был изменён, необходимо перечитать метаданные. Принимает язык исходного кода.
     TextChanged(String, Lang),
@@ -94,6 +98,8 @@ pub struct SyncEngine {
     last_metas: Vec<VisualMeta>,
     /// Conflicts detected during the last synchronization cycle.
     last_conflicts: Vec<SyncConflict>,
+    /// Активная запись сессии синхронизации, если она включена.
+    recorder: Option<SessionRecorder>,
 }
 
 impl SyncEngine {
@@ -112,6 +118,7 @@ impl SyncEngine {
             last_diagnostics: SyncDiagnostics::default(),
             last_metas: Vec::new(),
             last_conflicts: Vec::new(),
+            recorder: None,
         }
     }
 
@@ -121,9 +128,36 @@ impl SyncEngine {
         self.preserve_meta_formatting = settings.preserve_meta_formatting;
     }
 
+    /// Начинает запись всех входящих [`SyncMessage`] в файл `path`.
+    ///
+    /// Запись можно воспроизвести через [`super::recorder::replay`].
+    pub fn start_recording(&mut self, path: &Path) -> io::Result<()> {
+        self.recorder = Some(SessionRecorder::create(path)?);
+        Ok(())
+    }
+
+    /// Останавливает запись сессии и возвращает количество записанных сообщений.
+    pub fn stop_recording(&mut self) -> Option<usize> {
+        self.recorder.take().map(|r| r.len())
+    }
+
+    /// Возвращает `true`, если сессия синхронизации записывается.
+    pub fn is_recording(&self) -> bool {
+        self.recorder.is_some()
+    }
+
     /// Обрабатывает входящее сообщение синхронизации.
     /// Возвращает обновлённый текст, список метаданных и диагностические данные.
     pub fn handle(&mut self, msg: SyncMessage) -> Option<(&str, &[VisualMeta], &SyncDiagnostics)> {
+        if let Some(recorder) = self.recorder.as_mut() {
+            if let Err(e) = recorder.record(&msg) {
+                tracing::warn!("не удалось записать сообщение синхронизации: {e}");
+            }
+        }
+        self.process(msg)
+    }
+
+    fn process(&mut self, msg: SyncMessage) -> Option<(&str, &[VisualMeta], &SyncDiagnostics)> {
         self.last_conflicts.clear();
         match msg {
             SyncMessage::TextChanged(code, lang) => {
@@ -245,7 +279,7 @@ impl SyncEngine {
                 if let Some(mut meta) = self.state.metas.get(&from).cloned() {
                     if !meta.links.contains(&to) {
                         meta.links.push(to);
-                        return self.handle(SyncMessage::VisualChanged(meta));
+                        return self.process(SyncMessage::VisualChanged(meta));
                     }
                 }
                 None
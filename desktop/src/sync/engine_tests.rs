@@ -1,6 +1,6 @@
 use super::{ConflictResolutionMode, ResolutionOption, SyncEngine, SyncMessage, SyncSettings};
 use chrono::Utc;
-use multicode_core::meta::{self, VisualMeta, DEFAULT_VERSION};
+use multicode_core::meta::{self, style, VisualMeta, DEFAULT_VERSION};
 use multicode_core::parser::Lang;
 use std::collections::HashMap;
 use tracing_test::traced_test;
@@ -11,6 +11,8 @@ fn make_meta(id: &str, version: u32) -> VisualMeta {
         id: id.to_string(),
         x: 1.0,
         y: 2.0,
+        width: None,
+        height: None,
         tags: Vec::new(),
         links: Vec::new(),
         anchors: Vec::new(),
@@ -94,6 +96,27 @@ fn visual_changed_does_not_duplicate_meta() {
     );
 }
 
+#[test]
+fn visual_changed_preserves_block_style() {
+    let mut engine = SyncEngine::new(Lang::Rust, SyncSettings::default());
+    let mut meta = make_meta("styled", DEFAULT_VERSION);
+    style::set_style(&mut meta, Some("#ff8800"), Some("!"));
+    let code = meta::upsert("fn main() {}\n", &meta, false);
+    let _ = engine.handle(SyncMessage::TextChanged(code, Lang::Rust));
+
+    let mut moved = make_meta("styled", DEFAULT_VERSION + 1);
+    moved.x = 50.0;
+    let _ = engine.handle(SyncMessage::VisualChanged(moved));
+    let _ = engine.handle(SyncMessage::ConnectionAdded("styled".into(), "other".into()));
+
+    let metas = meta::read_all(&engine.state().code);
+    let stored = metas.iter().find(|m| m.id == "styled").unwrap();
+    assert_eq!(stored.x, 50.0);
+    assert_eq!(stored.links, vec!["other".to_string()]);
+    assert_eq!(style::color(stored), Some("#ff8800"));
+    assert_eq!(style::icon(stored), Some("!"));
+}
+
 #[test]
 fn visual_changed_zeros_version_defaults_to_constant() {
     let mut engine = SyncEngine::new(Lang::Rust, SyncSettings::default());
@@ -40,6 +40,8 @@ fn make_meta(id: &str, version: u32) -> VisualMeta {
         id: id.to_string(),
         x: 0.0,
         y: 0.0,
+        width: None,
+        height: None,
         tags: Vec::new(),
         links: Vec::new(),
         anchors: Vec::new(),
@@ -1,10 +1,10 @@
 use crate::components::file_manager::FileManagerPlugin;
 use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
-use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::{mpsc::{channel, SyncSender}, Mutex};
 use std::thread;
 
+use multicode_core::file_io;
 use multicode_core::parser::Lang;
 
 use super::SyncMessage;
@@ -47,7 +47,7 @@ impl FileWatcher {
             while let Ok(event) = evt_rx.recv() {
                 match event.kind {
                     EventKind::Modify(_) => {
-                        if let Ok(code) = fs::read_to_string(&path) {
+                        if let Ok(code) = file_io::read_to_string(&path) {
                             if let Some(lang) = lang_from_path(&path) {
                                 let _ = tx.send(Some(SyncMessage::TextChanged(code, lang)));
                             } else {
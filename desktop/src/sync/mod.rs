@@ -28,6 +28,7 @@ pub mod conflict_resolver;
 pub mod element_mapper;
 pub mod engine;
 pub mod file_watcher;
+pub mod recorder;
 pub mod settings;
 
 use once_cell::sync::Lazy;
@@ -50,6 +51,7 @@ pub use conflict_resolver::{
 pub use element_mapper::ElementMapper;
 pub use engine::{SyncDiagnostics, SyncEngine, SyncMessage, SyncState};
 pub use file_watcher::FileWatcher;
+pub use recorder::{load_session, replay, RecordedEvent, SessionRecorder};
 pub use settings::{ConflictResolutionMode, SyncSettings};
 
 /// Расширение механизма синхронизации.
@@ -213,3 +215,5 @@ mod async_manager_tests;
 mod engine_tests;
 #[cfg(test)]
 mod extension_tests;
+#[cfg(test)]
+mod recorder_tests;
@@ -0,0 +1,106 @@
+//! Запись и воспроизведение сессий синхронизации.
+//!
+//! [`SessionRecorder`] сохраняет каждое [`SyncMessage`], поступившее в
+//! [`SyncEngine`], вместе с меткой времени в файл формата JSON Lines. Такой
+//! файл пользователь может приложить к отчёту об ошибке, а разработчик —
+//! воспроизвести его через [`replay`] на чистом движке в тесте и получить то
+//! же рассогласование текста и блок-схемы.
+//!
+//! # Пример
+//! ```rust,no_run
+//! use desktop::sync::{load_session, replay, SyncEngine, SyncSettings};
+//! use multicode_core::parser::Lang;
+//!
+//! let events = load_session("session.jsonl".as_ref()).unwrap();
+//! let mut engine = SyncEngine::new(Lang::Rust, SyncSettings::default());
+//! let code = replay(&mut engine, &events);
+//! println!("{code:?}");
+//! ```
+
+use super::{SyncEngine, SyncMessage};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Одна запись сессии: сообщение и время его получения движком.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    pub timestamp: DateTime<Utc>,
+    pub message: SyncMessage,
+}
+
+/// Пишет сообщения синхронизации в файл по мере их поступления.
+#[derive(Debug)]
+pub struct SessionRecorder {
+    writer: BufWriter<File>,
+    count: usize,
+}
+
+impl SessionRecorder {
+    /// Создаёт (или перезаписывает) файл записи по пути `path`.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            count: 0,
+        })
+    }
+
+    /// Добавляет сообщение в запись.
+    ///
+    /// Каждая строка сбрасывается на диск сразу, чтобы запись сохранилась даже
+    /// при аварийном завершении приложения.
+    pub fn record(&mut self, message: &SyncMessage) -> io::Result<()> {
+        let event = RecordedEvent {
+            timestamp: Utc::now(),
+            message: message.clone(),
+        };
+        serde_json::to_writer(&mut self.writer, &event)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Количество записанных сообщений.
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+}
+
+/// Загружает записанную сессию. Пустые строки пропускаются.
+pub fn load_session(path: &Path) -> io::Result<Vec<RecordedEvent>> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut events = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let event = serde_json::from_str(&line)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        events.push(event);
+    }
+    Ok(events)
+}
+
+/// Воспроизводит записанные сообщения на движке `engine`.
+///
+/// Возвращает текст, полученный после последнего обработанного сообщения.
+pub fn replay(engine: &mut SyncEngine, events: &[RecordedEvent]) -> Option<String> {
+    let mut code = None;
+    for event in events {
+        if let Some((c, _, _)) = engine.handle(event.message.clone()) {
+            code = Some(c.to_string());
+        }
+    }
+    code
+}
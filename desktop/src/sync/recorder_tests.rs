@@ -0,0 +1,73 @@
+use super::{load_session, replay, SyncEngine, SyncMessage, SyncSettings};
+use chrono::Utc;
+use multicode_core::meta::{self, VisualMeta, DEFAULT_VERSION};
+use multicode_core::parser::Lang;
+use std::collections::HashMap;
+use tempfile::tempdir;
+
+fn make_meta(id: &str, x: f64) -> VisualMeta {
+    VisualMeta {
+        version: DEFAULT_VERSION,
+        id: id.to_string(),
+        x,
+        y: 0.0,
+        width: None,
+        height: None,
+        tags: Vec::new(),
+        links: Vec::new(),
+        anchors: Vec::new(),
+        tests: Vec::new(),
+        extends: None,
+        origin: None,
+        translations: HashMap::new(),
+        ai: None,
+        extras: None,
+        updated_at: Utc::now(),
+    }
+}
+
+fn positions(code: &str) -> Vec<(String, f64)> {
+    let mut list: Vec<_> = meta::read_all(code)
+        .into_iter()
+        .map(|m| (m.id, m.x))
+        .collect();
+    list.sort_by(|a, b| a.0.cmp(&b.0));
+    list
+}
+
+#[test]
+fn recorded_session_replays_to_same_state() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+
+    let mut engine = SyncEngine::new(Lang::Rust, SyncSettings::default());
+    engine.start_recording(&path).unwrap();
+    assert!(engine.is_recording());
+    let code = meta::upsert("fn main() {}\n", &make_meta("a", 1.0), false);
+    engine.handle(SyncMessage::TextChanged(code, Lang::Rust));
+    engine.handle(SyncMessage::VisualChanged(make_meta("a", 5.0)));
+    engine.handle(SyncMessage::VisualChanged(make_meta("b", 7.0)));
+    assert_eq!(engine.stop_recording(), Some(3));
+    let expected = positions(&engine.state().code);
+
+    let events = load_session(&path).unwrap();
+    assert_eq!(events.len(), 3);
+    assert!(matches!(events[0].message, SyncMessage::TextChanged(_, Lang::Rust)));
+
+    let mut fresh = SyncEngine::new(Lang::Rust, SyncSettings::default());
+    let replayed = replay(&mut fresh, &events).unwrap();
+    assert_eq!(positions(&replayed), expected);
+}
+
+#[test]
+fn connection_added_is_recorded_once() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("session.jsonl");
+    let mut engine = SyncEngine::new(Lang::Rust, SyncSettings::default());
+    let code = meta::upsert("", &make_meta("a", 0.0), false);
+    engine.handle(SyncMessage::TextChanged(code, Lang::Rust));
+    engine.start_recording(&path).unwrap();
+    engine.handle(SyncMessage::ConnectionAdded("a".into(), "b".into()));
+    assert_eq!(engine.stop_recording(), Some(1));
+    assert_eq!(load_session(&path).unwrap().len(), 1);
+}
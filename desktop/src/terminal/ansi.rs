@@ -0,0 +1,178 @@
+//! Разбор управляющих последовательностей ANSI в строках вывода.
+//!
+//! Учитываются только атрибуты SGR (`ESC [ … m`): цвета текста и фона из
+//! 16 стандартных, 256-цветной палитры и RGB, а также жирность. Остальные
+//! последовательности CSI и OSC отбрасываются. Стиль не переносится между
+//! строками: каждая строка лога разбирается независимо.
+
+use iced::Color;
+
+/// Цвет из последовательности SGR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnsiColor {
+    /// Номер в 256-цветной палитре xterm; 0–15 — стандартные цвета.
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// Стандартные цвета в палитре xterm.
+const BASIC: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+impl AnsiColor {
+    /// Компоненты RGB цвета.
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            AnsiColor::Rgb(r, g, b) => (r, g, b),
+            AnsiColor::Indexed(i @ 0..=15) => BASIC[i as usize],
+            AnsiColor::Indexed(i @ 16..=231) => {
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                let i = i - 16;
+                (level(i / 36), level(i / 6 % 6), level(i % 6))
+            }
+            AnsiColor::Indexed(i) => {
+                let v = 8 + (i - 232) * 10;
+                (v, v, v)
+            }
+        }
+    }
+
+    pub fn to_color(self) -> Color {
+        let (r, g, b) = self.rgb();
+        Color::from_rgb8(r, g, b)
+    }
+}
+
+/// Атрибуты отрезка текста.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Style {
+    pub fg: Option<AnsiColor>,
+    pub bg: Option<AnsiColor>,
+    pub bold: bool,
+}
+
+impl Style {
+    fn apply_sgr(&mut self, params: &str) {
+        let codes: Vec<u16> = params.split(';').map(|p| p.parse().unwrap_or(0)).collect();
+        let mut iter = codes.into_iter();
+        while let Some(code) = iter.next() {
+            match code {
+                0 => *self = Style::default(),
+                1 => self.bold = true,
+                22 => self.bold = false,
+                30..=37 => self.fg = Some(AnsiColor::Indexed((code - 30) as u8)),
+                90..=97 => self.fg = Some(AnsiColor::Indexed((code - 90 + 8) as u8)),
+                39 => self.fg = None,
+                40..=47 => self.bg = Some(AnsiColor::Indexed((code - 40) as u8)),
+                100..=107 => self.bg = Some(AnsiColor::Indexed((code - 100 + 8) as u8)),
+                49 => self.bg = None,
+                38 | 48 => {
+                    let color = match iter.next() {
+                        Some(5) => iter.next().map(|i| AnsiColor::Indexed(i as u8)),
+                        Some(2) => match (iter.next(), iter.next(), iter.next()) {
+                            (Some(r), Some(g), Some(b)) => {
+                                Some(AnsiColor::Rgb(r as u8, g as u8, b as u8))
+                            }
+                            _ => None,
+                        },
+                        _ => None,
+                    };
+                    if code == 38 {
+                        self.fg = color;
+                    } else {
+                        self.bg = color;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Отрезок строки с одинаковым стилем.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+/// Разбивает строку на отрезки по стилю, удаляя управляющие
+/// последовательности и непечатаемые символы, кроме табуляции.
+pub fn parse(line: &str) -> Vec<Span> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut style = Style::default();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    let mut params = String::new();
+                    let mut fin = None;
+                    for c in chars.by_ref() {
+                        if ('\x40'..='\x7e').contains(&c) {
+                            fin = Some(c);
+                            break;
+                        }
+                        params.push(c);
+                    }
+                    if fin == Some('m') {
+                        let mut next = style;
+                        next.apply_sgr(&params);
+                        if next != style && !current.is_empty() {
+                            spans.push(Span {
+                                text: std::mem::take(&mut current),
+                                style,
+                            });
+                        }
+                        style = next;
+                    }
+                }
+                Some(']') => {
+                    // OSC заканчивается BEL или ESC \.
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\t' => current.push(c),
+            c if c.is_control() => {}
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        spans.push(Span {
+            text: current,
+            style,
+        });
+    }
+    spans
+}
+
+/// Текст строки без управляющих последовательностей.
+pub fn strip(line: &str) -> String {
+    parse(line).into_iter().map(|s| s.text).collect()
+}
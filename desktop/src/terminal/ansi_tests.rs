@@ -0,0 +1,49 @@
+use super::ansi::{parse, strip, AnsiColor, Span, Style};
+
+fn span(text: &str, style: Style) -> Span {
+    Span {
+        text: text.into(),
+        style,
+    }
+}
+
+#[test]
+fn splits_line_by_sgr_colors() {
+    let spans = parse("ok \x1b[1;32mpassed\x1b[0m in \x1b[38;5;208m3s\x1b[39m");
+    let green = Style {
+        fg: Some(AnsiColor::Indexed(2)),
+        bold: true,
+        ..Style::default()
+    };
+    let orange = Style {
+        fg: Some(AnsiColor::Indexed(208)),
+        ..Style::default()
+    };
+    assert_eq!(
+        spans,
+        vec![
+            span("ok ", Style::default()),
+            span("passed", green),
+            span(" in ", Style::default()),
+            span("3s", orange),
+        ]
+    );
+}
+
+#[test]
+fn drops_other_escape_sequences() {
+    let line = "\x1b]0;title\x07\x1b[2K\x1b[?25lready\x1b[48;2;10;20;30m!\x08";
+    assert_eq!(strip(line), "ready!");
+    let spans = parse(line);
+    assert_eq!(spans[1].style.bg, Some(AnsiColor::Rgb(10, 20, 30)));
+    assert_eq!(strip("a\tb"), "a\tb");
+}
+
+#[test]
+fn maps_palette_indices_to_rgb() {
+    assert_eq!(AnsiColor::Indexed(1).rgb(), (205, 0, 0));
+    assert_eq!(AnsiColor::Indexed(16).rgb(), (0, 0, 0));
+    assert_eq!(AnsiColor::Indexed(196).rgb(), (255, 0, 0));
+    assert_eq!(AnsiColor::Indexed(232).rgb(), (8, 8, 8));
+    assert_eq!(AnsiColor::Indexed(255).rgb(), (238, 238, 238));
+}
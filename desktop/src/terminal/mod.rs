@@ -0,0 +1,19 @@
+//! Встроенный терминал.
+//!
+//! Команды выполняются в оболочке проекта, запущенной в псевдотерминале
+//! ([`pty`]), поэтому интерактивные программы и цветной вывод работают
+//! так же, как в обычном терминале, в том числе в Windows (ConPTY).
+//! Цвета вывода разбираются модулем [`ansi`].
+
+pub mod ansi;
+pub mod pty;
+
+pub use pty::{LineBuffer, PtySession};
+
+/// Высота области вывода терминала, пикселей.
+pub const OUTPUT_HEIGHT: f32 = 150.0;
+
+#[cfg(test)]
+mod ansi_tests;
+#[cfg(test)]
+mod pty_tests;
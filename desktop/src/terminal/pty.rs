@@ -0,0 +1,225 @@
+//! Процессы встроенного терминала в псевдотерминале.
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use multicode_core::config::TerminalConfig;
+use multicode_core::project_env;
+use portable_pty::{native_pty_system, Child, CommandBuilder, MasterPty, PtySize};
+use tokio::sync::broadcast;
+
+/// Ширина символа в логе терминала, пикселей.
+pub const CELL_WIDTH: f32 = 8.0;
+/// Высота строки в логе терминала, пикселей.
+pub const CELL_HEIGHT: f32 = 20.0;
+
+/// Размер псевдотерминала для области вывода `width`×`height` пикселей.
+pub fn size_for(width: f32, height: f32) -> PtySize {
+    let cells = |pixels: f32, cell: f32| ((pixels / cell) as u16).max(1);
+    PtySize {
+        rows: cells(height, CELL_HEIGHT),
+        cols: cells(width, CELL_WIDTH),
+        pixel_width: width as u16,
+        pixel_height: height as u16,
+    }
+}
+
+/// Размер псевдотерминала до первого изменения размера окна.
+pub fn initial_size() -> PtySize {
+    size_for(800.0, super::OUTPUT_HEIGHT)
+}
+
+/// Собирает вывод процесса в строки.
+///
+/// Перевод строки `\r\n` считается одним, а `\r` внутри строки
+/// возвращает курсор в начало, поэтому от строк индикаторов прогресса
+/// остаётся только последнее состояние.
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Добавляет байты вывода и возвращает завершённые строки.
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+        let mut lines = Vec::new();
+        while let Some(pos) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=pos).collect();
+            lines.push(Self::finish(&line[..pos]));
+        }
+        lines
+    }
+
+    /// Незавершённая строка, например приглашение оболочки.
+    pub fn take_pending(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let line = std::mem::take(&mut self.pending);
+        Some(Self::finish(&line))
+    }
+
+    fn finish(line: &[u8]) -> String {
+        let text = String::from_utf8_lossy(line);
+        let text = text.trim_end_matches('\r');
+        text.rsplit('\r').next().unwrap_or_default().to_string()
+    }
+}
+
+/// Процесс, запущенный в псевдотерминале. Вывод построчно отправляется
+/// в канал `output`; при удалении сеанса процесс завершается.
+pub struct PtySession {
+    root: PathBuf,
+    master: Box<dyn MasterPty + Send>,
+    writer: Box<dyn Write + Send>,
+    child: Box<dyn Child + Send + Sync>,
+}
+
+impl fmt::Debug for PtySession {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PtySession")
+            .field("root", &self.root)
+            .field("pid", &self.child.process_id())
+            .finish()
+    }
+}
+
+impl PtySession {
+    /// Запускает `cmd` в новом псевдотерминале размера `size`.
+    pub fn spawn(
+        root: &Path,
+        cmd: CommandBuilder,
+        size: PtySize,
+        output: broadcast::Sender<String>,
+    ) -> Result<Self, String> {
+        let pair = native_pty_system()
+            .openpty(size)
+            .map_err(|e| e.to_string())?;
+        let child = pair.slave.spawn_command(cmd).map_err(|e| e.to_string())?;
+        // Дескриптор подчинённой стороны больше не нужен: без него чтение
+        // завершится, когда процесс закроет терминал.
+        drop(pair.slave);
+        let mut reader = pair.master.try_clone_reader().map_err(|e| e.to_string())?;
+        let writer = pair.master.take_writer().map_err(|e| e.to_string())?;
+        std::thread::spawn(move || {
+            let mut lines = LineBuffer::default();
+            let mut buf = [0u8; 4096];
+            while let Ok(n) = reader.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                for line in lines.push(&buf[..n]) {
+                    let _ = output.send(line);
+                }
+                // Вывод приостановился — вероятно, процесс ждёт ввода
+                // после приглашения без перевода строки.
+                if n < buf.len() {
+                    if let Some(line) = lines.take_pending() {
+                        let _ = output.send(line);
+                    }
+                }
+            }
+            if let Some(line) = lines.take_pending() {
+                let _ = output.send(line);
+            }
+        });
+        Ok(Self {
+            root: root.to_path_buf(),
+            master: pair.master,
+            writer,
+            child,
+        })
+    }
+
+    /// Запускает оболочку проекта `root` по секции `[terminal]`.
+    pub fn shell(
+        root: &Path,
+        size: PtySize,
+        output: broadcast::Sender<String>,
+    ) -> Result<Self, String> {
+        Self::spawn(
+            root,
+            shell_command(root, &TerminalConfig::load(root)),
+            size,
+            output,
+        )
+    }
+
+    /// Корень проекта, в котором запущен процесс.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Отправляет строку ввода, как при нажатии Enter.
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        self.writer.write_all(line.as_bytes())?;
+        self.writer.write_all(b"\r")?;
+        self.writer.flush()
+    }
+
+    /// Прерывает текущую команду, как Ctrl+C.
+    pub fn interrupt(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"\x03")?;
+        self.writer.flush()
+    }
+
+    /// Сообщает процессу новый размер терминала.
+    pub fn resize(&self, size: PtySize) -> Result<(), String> {
+        self.master.resize(size).map_err(|e| e.to_string())
+    }
+
+    /// Процесс ещё не завершился.
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Завершает процесс.
+    pub fn kill(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+impl Drop for PtySession {
+    fn drop(&mut self) {
+        if self.is_running() {
+            self.kill();
+        }
+    }
+}
+
+/// Команда оболочки проекта `root` с настройками `config` и переменными
+/// окружения проекта; переменные из `config` важнее.
+pub fn shell_command(root: &Path, config: &TerminalConfig) -> CommandBuilder {
+    let mut cmd = match &config.shell {
+        Some(shell) => CommandBuilder::new(shell),
+        None => CommandBuilder::new_default_prog(),
+    };
+    cmd.args(&config.args);
+    for (key, value) in project_env::vars(root) {
+        cmd.env(key, value);
+    }
+    for (key, value) in &config.env {
+        cmd.env(key, value);
+    }
+    cmd.cwd(root);
+    cmd
+}
+
+/// Переносит программу, аргументы, каталог и переменные окружения
+/// из [`std::process::Command`].
+pub fn from_std(command: &std::process::Command) -> CommandBuilder {
+    let mut cmd = CommandBuilder::new(command.get_program());
+    cmd.args(command.get_args());
+    if let Some(dir) = command.get_current_dir() {
+        cmd.cwd(dir);
+    }
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => cmd.env(key, value),
+            None => cmd.env_remove(key),
+        }
+    }
+    cmd
+}
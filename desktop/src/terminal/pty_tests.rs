@@ -0,0 +1,89 @@
+use super::pty::{from_std, shell_command, size_for, LineBuffer};
+use multicode_core::config::TerminalConfig;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::path::Path;
+
+fn argv(cmd: &portable_pty::CommandBuilder) -> Vec<&str> {
+    cmd.get_argv().iter().map(|a| a.to_str().unwrap()).collect()
+}
+
+#[test]
+fn line_buffer_joins_chunks_and_handles_carriage_return() {
+    let mut lines = LineBuffer::default();
+    assert!(lines.push(b"comp").is_empty());
+    assert_eq!(
+        lines.push(b"iling\r\n 10%\r 50%\r100%\nrest"),
+        vec!["compiling".to_string(), "100%".to_string()]
+    );
+    assert_eq!(lines.take_pending().as_deref(), Some("rest"));
+    assert_eq!(lines.take_pending(), None);
+    // Многобайтовый символ, разрезанный между чтениями.
+    let bytes = "привет\n".as_bytes();
+    assert!(lines.push(&bytes[..3]).is_empty());
+    assert_eq!(lines.push(&bytes[3..]), vec!["привет".to_string()]);
+}
+
+#[test]
+fn size_is_measured_in_cells() {
+    let size = size_for(800.0, 150.0);
+    assert_eq!((size.cols, size.rows), (100, 7));
+    let size = size_for(0.0, 0.0);
+    assert_eq!((size.cols, size.rows), (1, 1));
+}
+
+#[test]
+fn shell_command_uses_project_config() {
+    let config = TerminalConfig {
+        shell: Some("bash".into()),
+        args: vec!["--login".into()],
+        env: BTreeMap::from([("RUST_BACKTRACE".into(), "1".into())]),
+    };
+    let cmd = shell_command(Path::new("/project"), &config);
+    assert_eq!(argv(&cmd), vec!["bash", "--login"]);
+    assert_eq!(cmd.get_env("RUST_BACKTRACE"), Some(OsStr::new("1")));
+    assert_eq!(
+        cmd.get_cwd().map(|c| c.as_os_str()),
+        Some(OsStr::new("/project"))
+    );
+    assert!(shell_command(Path::new("/project"), &TerminalConfig::default()).is_default_prog());
+}
+
+#[test]
+fn converts_std_command() {
+    let mut std_cmd = std::process::Command::new("sh");
+    std_cmd
+        .arg("-c")
+        .arg("make")
+        .current_dir("/work")
+        .env("A", "b");
+    let cmd = from_std(&std_cmd);
+    assert_eq!(argv(&cmd), vec!["sh", "-c", "make"]);
+    assert_eq!(
+        cmd.get_cwd().map(|c| c.as_os_str()),
+        Some(OsStr::new("/work"))
+    );
+    assert_eq!(cmd.get_env("A"), Some(OsStr::new("b")));
+}
+
+#[cfg(unix)]
+#[test]
+fn session_streams_colored_output() {
+    use super::PtySession;
+    use std::time::{Duration, Instant};
+    use tokio::sync::broadcast;
+
+    let (tx, mut rx) = broadcast::channel(16);
+    let mut cmd = portable_pty::CommandBuilder::new("sh");
+    cmd.args(["-c", "printf '\\033[31mred\\033[0m\\n'"]);
+    let _session = PtySession::spawn(Path::new("."), cmd, size_for(400.0, 100.0), tx).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let line = loop {
+        match rx.try_recv() {
+            Ok(line) if !line.is_empty() => break line,
+            _ if Instant::now() > deadline => panic!("no output"),
+            _ => std::thread::sleep(Duration::from_millis(10)),
+        }
+    };
+    assert_eq!(line, "\x1b[31mred\x1b[0m");
+}
@@ -5,7 +5,7 @@ use crate::visual::canvas::CanvasMessage;
 use crate::visual::connections::{Connection, DataType};
 use chrono::Utc;
 use iced::widget::text_editor;
-use multicode_core::meta::{VisualMeta, DEFAULT_VERSION};
+use multicode_core::meta::{style, VisualMeta, DEFAULT_VERSION};
 use multicode_core::{export, search, BlockInfo};
 use std::{path::Path, process::Command};
 
@@ -200,10 +200,15 @@ fn handle_sync_message(state: &mut MainUI, msg: SyncMessage) {
                 anchors: Vec::new(),
                 x: m.x,
                 y: m.y,
+                width: None,
+                height: None,
                 ports: Vec::new(),
                 ai: None,
                 tags: m.tags.clone(),
                 links: m.links.clone(),
+                color: style::color(m).map(str::to_string),
+                icon: style::icon(m).map(str::to_string),
+                doc: None,
             })
             .collect();
 
@@ -245,11 +250,13 @@ fn show_conflict_dialog(state: &mut MainUI) {
 }
 
 fn block_to_meta(block: &BlockInfo) -> VisualMeta {
-    VisualMeta {
+    let mut meta = VisualMeta {
         version: DEFAULT_VERSION,
         id: block.visual_id.clone(),
         x: block.x,
         y: block.y,
+        width: block.width,
+        height: block.height,
         tags: block.tags.clone(),
         links: block.links.clone(),
         anchors: Vec::new(),
@@ -260,7 +267,9 @@ fn block_to_meta(block: &BlockInfo) -> VisualMeta {
         ai: None,
         extras: None,
         updated_at: Utc::now(),
-    }
+    };
+    style::set_style(&mut meta, block.color.as_deref(), block.icon.as_deref());
+    meta
 }
 
 #[cfg(test)]
@@ -284,6 +293,8 @@ mod tests {
             id: "b".into(),
             x: 10.0,
             y: 20.0,
+            width: None,
+            height: None,
             tags: Vec::new(),
             links: Vec::new(),
             anchors: Vec::new(),
@@ -301,6 +312,8 @@ mod tests {
             id: "a".into(),
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             tags: Vec::new(),
             links: vec!["b".into()],
             anchors: Vec::new(),
@@ -0,0 +1,115 @@
+//! Режим доступности визуального редактора.
+//!
+//! iced 0.12 не предоставляет дерева доступности, поэтому текстовые
+//! описания блоков выводятся обычными текстовыми виджетами: в строке
+//! состояния для блока в фокусе и в списке блоков рядом с холстом.
+
+use multicode_core::BlockInfo;
+use serde::{Deserialize, Serialize};
+
+use super::translations::{translate_kind, Language};
+
+/// Размер шрифта iced по умолчанию.
+pub const DEFAULT_FONT_SIZE: u16 = 16;
+
+/// Параметры доступности.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessibilitySettings {
+    /// Показывать текстовые описания блоков.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Использовать контрастную палитру блоков на холсте.
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Минимальный размер шрифта холста и панелей.
+    #[serde(default = "default_min_font_size")]
+    pub min_font_size: u16,
+}
+
+fn default_min_font_size() -> u16 {
+    12
+}
+
+impl Default for AccessibilitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            high_contrast: false,
+            min_font_size: default_min_font_size(),
+        }
+    }
+}
+
+impl AccessibilitySettings {
+    /// Размер шрифта не меньше настроенного минимума.
+    pub fn font_size(&self, size: u16) -> u16 {
+        size.max(self.min_font_size)
+    }
+}
+
+/// Текстовое описание блока: вид, переведённая метка и порядковый номер.
+pub fn describe_block(block: &BlockInfo, index: usize, total: usize, lang: Language) -> String {
+    let kind = translate_kind(&block.kind, lang).unwrap_or(&block.kind);
+    let label = block
+        .translations
+        .get(lang.code())
+        .filter(|l| l.as_str() != kind)
+        .map(|l| format!(" «{l}»"))
+        .unwrap_or_default();
+    let n = index + 1;
+    let position = match lang {
+        Language::English => format!("block {n} of {total}"),
+        Language::Russian => format!("блок {n} из {total}"),
+        Language::Spanish => format!("bloque {n} de {total}"),
+        Language::German => format!("Block {n} von {total}"),
+    };
+    format!("{kind}{label}, {position}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn block(kind: &str, label: Option<&str>) -> BlockInfo {
+        BlockInfo {
+            visual_id: "1".into(),
+            node_id: None,
+            kind: kind.into(),
+            translations: label
+                .map(|l| HashMap::from([("ru".to_string(), l.to_string())]))
+                .unwrap_or_default(),
+            range: (0, 0),
+            anchors: vec![],
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            ports: vec![],
+            ai: None,
+            tags: vec![],
+            links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
+        }
+    }
+
+    #[test]
+    fn describes_kind_label_and_position() {
+        let text = describe_block(&block("struct_item", Some("Точка")), 2, 5, Language::Russian);
+        assert_eq!(text, "struct_item «Точка», блок 3 из 5");
+        let text = describe_block(&block("struct_item", None), 0, 1, Language::English);
+        assert_eq!(text, "struct_item, block 1 of 1");
+    }
+
+    #[test]
+    fn font_size_respects_minimum() {
+        let settings = AccessibilitySettings {
+            min_font_size: 18,
+            ..Default::default()
+        };
+        assert_eq!(settings.font_size(14), 18);
+        assert_eq!(settings.font_size(20), 20);
+    }
+}
@@ -1,4 +1,7 @@
 use iced::Color;
+use serde::{Deserialize, Serialize};
+
+use crate::editor::settings::serde_color;
 
 pub mod arithmetic;
 pub mod conditional;
@@ -21,12 +24,18 @@ pub enum BlockType {
     Function(FunctionBlock),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BlockColors {
+    #[serde(with = "serde_color")]
     pub arithmetic: Color,
+    #[serde(with = "serde_color")]
     pub conditional: Color,
+    #[serde(with = "serde_color")]
     pub loops: Color,
+    #[serde(with = "serde_color")]
     pub variables: Color,
+    #[serde(with = "serde_color")]
     pub functions: Color,
 }
 
@@ -41,3 +50,35 @@ impl Default for BlockColors {
         }
     }
 }
+
+impl BlockColors {
+    /// Насыщенные цвета с высоким контрастом к чёрному тексту меток.
+    pub fn high_contrast() -> Self {
+        Self {
+            arithmetic: Color::from_rgb(1.0, 0.6, 0.0),
+            conditional: Color::from_rgb(0.0, 1.0, 0.0),
+            loops: Color::from_rgb(0.0, 1.0, 1.0),
+            variables: Color::from_rgb(1.0, 1.0, 0.0),
+            functions: Color::from_rgb(1.0, 0.5, 1.0),
+        }
+    }
+
+    /// Цвет блока вида `kind` или `None`, если вид не относится ни к одной
+    /// из категорий.
+    pub fn for_kind(&self, kind: &str) -> Option<Color> {
+        let k = kind.to_lowercase();
+        if k.starts_with("op/") {
+            Some(self.arithmetic)
+        } else if k.contains("condition") || k.starts_with("if") || k.contains("else") {
+            Some(self.conditional)
+        } else if k.contains("loop") || k.contains("for") || k.contains("while") {
+            Some(self.loops)
+        } else if k.starts_with("variable") {
+            Some(self.variables)
+        } else if k.starts_with("function") || k == "return" {
+            Some(self.functions)
+        } else {
+            None
+        }
+    }
+}
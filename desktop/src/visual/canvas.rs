@@ -1,27 +1,72 @@
 use iced::widget::canvas::{self, Event, Frame, Geometry, Path, Program, Stroke, Text};
 use iced::{
     keyboard::{self, key},
-    mouse, Point, Rectangle, Renderer, Theme, Vector,
+    mouse, Color, Point, Rectangle, Renderer, Theme, Vector,
 };
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
 
 use crate::visual::connection_draw::{
-    draw_connections, draw_drag, prepare_connections, ConnectionDrag, PreparedConnection,
-    PORT_RADIUS,
+    block_rect, draw_connections, draw_drag, find_route, find_waypoint, insert_waypoint,
+    prepare_connections, ConnectionDrag, PreparedConnection, PORT_RADIUS,
 };
+use crate::visual::accessibility::DEFAULT_FONT_SIZE;
+use crate::visual::blocks::BlockColors;
 use crate::visual::connections::{Connection, DataType};
+use crate::visual::focus::{self, Direction};
+use crate::visual::filter::CanvasFilter;
+use crate::visual::geometry::{
+    block_size, is_output, on_resize_handle, port_point, resized, wrap_label, LABEL_PADDING,
+    RESIZE_HANDLE,
+};
+use crate::visual::grid::{GridSettings, Guide};
+use crate::visual::lanes::Lane;
+use crate::visual::layers::{ai_note, CanvasLayers, Layer};
+use crate::visual::lod::{is_visible, DetailLevel, LodSettings};
+use crate::visual::profile::heat_color;
 use crate::visual::translations::{translate_kind, Language};
+use multicode_core::meta::{file_ref::FILE_REF_KIND, style};
 use multicode_core::BlockInfo;
 
 pub const BLOCK_WIDTH: f32 = 120.0;
 pub const BLOCK_HEIGHT: f32 = 40.0;
 
+/// Максимальный интервал между нажатиями, считающимися двойным щелчком.
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Шаг панорамирования клавишами `h`, `j`, `k`, `l` в пикселях экрана.
+const PAN_STEP: f32 = 40.0;
+
+/// Отступ рамки группы от её блоков.
+const LANE_PADDING: f32 = 10.0;
+/// Высота заголовка группы.
+const LANE_HEADER: f32 = 20.0;
+/// Радиус метки с числом замечаний проверки в левом верхнем углу блока.
+const LINT_BADGE: f32 = 7.0;
+
 #[derive(Debug, Clone)]
 pub enum CanvasMessage {
     Pan { delta: Vector },
     Zoom { factor: f32, center: Point },
     BlockSelected(Option<usize>),
+    /// Двойной щелчок по блоку.
+    BlockActivated(usize),
+    /// Фокус клавиатуры перешёл на блок.
+    BlockFocused(usize),
+    /// Запрошено редактирование метки блока (`Enter`).
+    EditLabel(usize),
+    /// Запрошено редактирование метаданных блока (`M`).
+    EditMeta(usize),
     BlockDragged { index: usize, position: Point },
+    /// Размер блока изменён перетаскиванием его правого нижнего угла.
+    BlockResized { index: usize, size: iced::Size },
+    /// Изменены промежуточные точки связи `connection`.
+    WaypointsChanged { connection: usize, points: Vec<Point> },
+    /// Щелчок по заголовку группы с ключом.
+    LaneToggled(String),
+    /// Щелчок по метке замечаний проверки блока.
+    LintClicked(usize),
     Dropped { position: Point },
     TogglePalette,
     ConnectionCreated(Connection),
@@ -31,40 +76,95 @@ pub struct VisualCanvas<'a> {
     blocks: &'a [BlockInfo],
     connections: &'a [Connection],
     language: Language,
+    lod: LodSettings,
+    grid: GridSettings,
+    colors: Option<BlockColors>,
+    background: Option<(Color, Color)>,
+    font_size: u16,
+    /// Доля покрытых тестами строк каждого блока; `None` у блока — строк
+    /// блока нет в отчёте.
+    coverage: Option<Vec<Option<f32>>>,
+    /// Нагрев и подпись стоимости каждого блока для профиля; `None` у
+    /// блока — стоимость не измерена.
+    profile: Option<Vec<Option<(f32, String)>>>,
+    /// Промежуточные точки каждой связи.
+    waypoints: Vec<Vec<Point>>,
+    lanes: Vec<Lane>,
+    filter: Option<CanvasFilter>,
+    /// Закреплённые блоки и вложенные в них, которые не двигает раскладка.
+    frozen: Vec<bool>,
+    /// Блоки свёрнутых групп и скрытые фильтром.
+    hidden: HashSet<String>,
+    layers: CanvasLayers,
+    /// Сообщения замечаний проверки каждого блока.
+    lint: Vec<Vec<String>>,
+    /// Блок, в котором остановился отладчик.
+    stopped: Option<usize>,
+    /// Значения выражений наблюдения каждого блока при остановке.
+    watches: Vec<Vec<String>>,
+    /// Блоки с точками останова.
+    breakpoints: Vec<bool>,
+    /// Состояние тестов каждого блока: прошли, упали или итогов нет.
+    tests: Vec<Option<bool>>,
 }
 
 pub struct State {
-    offset: Vector,
+    offset: Cell<Vector>,
     scale: f32,
     selected: Option<usize>,
+    /// Блок, на котором находится фокус клавиатуры.
+    focused: Option<usize>,
+    /// Холст получил фокус щелчком и обрабатывает клавиши навигации.
+    has_focus: bool,
     drag: Option<Drag>,
+    /// Блок, размер которого меняется перетаскиванием угла.
+    resize: Option<usize>,
     connection: Option<ConnectionDrag>,
+    /// Перетаскиваемая промежуточная точка: индекс связи и номер точки.
+    waypoint_drag: Option<(usize, usize)>,
+    modifiers: keyboard::Modifiers,
     panning: bool,
     last_cursor: Point,
+    last_click: Option<(usize, Instant)>,
     connections: RefCell<Vec<PreparedConnection>>,
-    last_blocks: RefCell<Vec<(f64, f64)>>,
+    /// Позиции и размеры блоков и их видимость при последней подготовке
+    /// связей.
+    last_blocks: RefCell<Vec<(Rectangle, bool)>>,
     last_connections: RefCell<Vec<Connection>>,
+    last_waypoints: RefCell<Vec<Vec<Point>>>,
+    /// Совпадение фильтра, которое уже показано.
+    revealed: Cell<Option<usize>>,
 }
 
 #[derive(Debug, Clone)]
 struct Drag {
     index: usize,
     grab: Vector,
+    /// Направляющие выравнивания по соседям для текущей позиции.
+    guides: Vec<Guide>,
 }
 
 impl Default for State {
     fn default() -> Self {
         Self {
-            offset: Vector::new(0.0, 0.0),
+            offset: Cell::new(Vector::new(0.0, 0.0)),
             scale: 1.0,
             selected: None,
+            focused: None,
+            has_focus: false,
             drag: None,
+            resize: None,
             connection: None,
+            waypoint_drag: None,
+            modifiers: keyboard::Modifiers::default(),
             panning: false,
             last_cursor: Point::ORIGIN,
+            last_click: None,
             connections: RefCell::new(Vec::new()),
             last_blocks: RefCell::new(Vec::new()),
             last_connections: RefCell::new(Vec::new()),
+            last_waypoints: RefCell::new(Vec::new()),
+            revealed: Cell::new(None),
         }
     }
 }
@@ -75,28 +175,516 @@ impl<'a> VisualCanvas<'a> {
             blocks,
             connections,
             language,
+            lod: LodSettings::default(),
+            grid: GridSettings::default(),
+            colors: None,
+            background: None,
+            font_size: DEFAULT_FONT_SIZE,
+            coverage: None,
+            profile: None,
+            waypoints: Vec::new(),
+            lanes: Vec::new(),
+            filter: None,
+            frozen: Vec::new(),
+            hidden: HashSet::new(),
+            layers: CanvasLayers::default(),
+            lint: Vec::new(),
+            stopped: None,
+            watches: Vec::new(),
+            breakpoints: Vec::new(),
+            tests: Vec::new(),
+        }
+    }
+
+    /// Задаёт видимые слои холста.
+    pub fn with_layers(mut self, layers: CanvasLayers) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Выделяет блок `stopped`, в котором остановился отладчик.
+    pub fn with_stopped(mut self, stopped: Option<usize>) -> Self {
+        self.stopped = stopped;
+        self
+    }
+
+    /// Подписывает справа от блоков значения их выражений наблюдения:
+    /// `watches[i]` — строки блока `i`.
+    pub fn with_watches(mut self, watches: Vec<Vec<String>>) -> Self {
+        self.watches = watches;
+        self
+    }
+
+    /// Отмечает блоки с точками останова: `breakpoints[i]` — есть ли точка
+    /// в блоке `i`.
+    pub fn with_breakpoints(mut self, breakpoints: Vec<bool>) -> Self {
+        self.breakpoints = breakpoints;
+        self
+    }
+
+    /// Отмечает блоки по итогам их тестов: `tests[i]` — прошли ли тесты
+    /// блока `i`.
+    pub fn with_tests(mut self, tests: Vec<Option<bool>>) -> Self {
+        self.tests = tests;
+        self
+    }
+
+    /// Обводит блоки с замечаниями проверки и помечает их числом
+    /// замечаний: `lint[i]` — сообщения блока `i`, показываемые при
+    /// наведении.
+    pub fn with_lint(mut self, lint: Vec<Vec<String>>) -> Self {
+        self.lint = lint;
+        self
+    }
+
+    /// Заливает холст цветом `background.0` и рисует сетку цветом
+    /// `background.1`.
+    pub fn with_background(mut self, background: Option<(Color, Color)>) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Раскрашивает блоки по категориям палитрой `colors`.
+    pub fn with_colors(mut self, colors: Option<BlockColors>) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Задаёт размер шрифта меток блоков.
+    pub fn with_font_size(mut self, size: u16) -> Self {
+        self.font_size = size;
+        self
+    }
+
+    /// Раскрашивает блоки по покрытию тестами вместо категорий и подписывает
+    /// процент покрытых строк.
+    pub fn with_coverage(mut self, coverage: Option<Vec<Option<f32>>>) -> Self {
+        self.coverage = coverage;
+        self
+    }
+
+    /// Раскрашивает блоки по нагреву профиля вместо покрытия и категорий и
+    /// подписывает их стоимость.
+    pub fn with_profile(mut self, profile: Option<Vec<Option<(f32, String)>>>) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Задаёт пороги масштаба для уровней детализации.
+    pub fn with_lod(mut self, lod: LodSettings) -> Self {
+        self.lod = lod;
+        self
+    }
+
+    /// Отмечает замороженные блоки: `frozen[i]` относится к блоку `i`.
+    pub fn with_frozen(mut self, frozen: Vec<bool>) -> Self {
+        self.frozen = frozen;
+        self
+    }
+
+    /// Задаёт промежуточные точки связей; `waypoints[i]` относится к связи `i`.
+    pub fn with_waypoints(mut self, waypoints: Vec<Vec<Point>>) -> Self {
+        self.waypoints = waypoints;
+        self
+    }
+
+    /// Рисует группы блоков `lanes` и скрывает блоки свёрнутых групп.
+    pub fn with_lanes(mut self, lanes: Vec<Lane>) -> Self {
+        let hidden = lanes
+            .iter()
+            .filter(|l| l.collapsed)
+            .flat_map(|l| &l.members)
+            .filter_map(|&i| self.blocks.get(i))
+            .map(|b| b.visual_id.clone());
+        self.hidden.extend(hidden);
+        self.lanes = lanes;
+        self
+    }
+
+    /// Затеняет или скрывает блоки, не подходящие фильтру `filter`, и
+    /// показывает его текущее совпадение.
+    pub fn with_filter(mut self, filter: Option<CanvasFilter>) -> Self {
+        if let Some(filter) = filter.as_ref().filter(|f| f.hide) {
+            let hidden = self
+                .blocks
+                .iter()
+                .zip(&filter.matches)
+                .filter(|(_, matched)| !**matched)
+                .map(|(b, _)| b.visual_id.clone());
+            self.hidden.extend(hidden);
+        }
+        self.filter = filter;
+        self
+    }
+
+    /// Подходит ли блок `index` фильтру; без фильтра подходят все.
+    fn matches_filter(&self, index: usize) -> bool {
+        self.filter
+            .as_ref()
+            .is_none_or(|f| f.matches.get(index).copied().unwrap_or(true))
+    }
+
+    /// Сообщения замечаний проверки блока `index`; пусто, если слой
+    /// проверки скрыт.
+    fn lint_of(&self, index: usize) -> &[String] {
+        if !self.layers.shows(Layer::Lint) {
+            return &[];
+        }
+        self.lint.get(index).map_or(&[], Vec::as_slice)
+    }
+
+    /// Виден ли блок на уровне `level` и не свёрнута ли его группа.
+    fn shown(&self, block: &BlockInfo, level: DetailLevel) -> bool {
+        is_visible(block, level) && !self.hidden.contains(&block.visual_id)
+    }
+
+    /// Рамка группы `lane` вокруг её видимых на уровне `level` блоков;
+    /// у свёрнутой группы — только заголовок. `None`, если блоков не видно.
+    fn lane_rect(&self, lane: &Lane, level: DetailLevel) -> Option<Rectangle> {
+        let members: Vec<&BlockInfo> = lane
+            .members
+            .iter()
+            .filter_map(|&i| self.blocks.get(i))
+            .filter(|b| is_visible(b, level))
+            .collect();
+        let min_x = members.iter().map(|b| b.x as f32).reduce(f32::min)?;
+        let min_y = members.iter().map(|b| b.y as f32).reduce(f32::min)?;
+        if lane.collapsed {
+            return Some(Rectangle::new(
+                Point::new(min_x, min_y),
+                iced::Size::new(BLOCK_WIDTH, LANE_HEADER),
+            ));
+        }
+        let max_x = members
+            .iter()
+            .map(|b| b.x as f32 + block_size(b).width)
+            .reduce(f32::max)?;
+        let max_y = members
+            .iter()
+            .map(|b| b.y as f32 + block_size(b).height)
+            .reduce(f32::max)?;
+        Some(Rectangle::new(
+            Point::new(min_x - LANE_PADDING, min_y - LANE_PADDING - LANE_HEADER),
+            iced::Size::new(
+                max_x - min_x + LANE_PADDING * 2.0,
+                max_y - min_y + LANE_PADDING * 2.0 + LANE_HEADER,
+            ),
+        ))
+    }
+
+    /// Группа, заголовок которой находится в точке холста `pos`.
+    fn lane_at(&self, pos: Point, level: DetailLevel) -> Option<&Lane> {
+        self.lanes.iter().find(|lane| {
+            self.lane_rect(lane, level).is_some_and(|r| {
+                Rectangle::new(r.position(), iced::Size::new(r.width, LANE_HEADER)).contains(pos)
+            })
+        })
+    }
+
+    fn draw_lanes(&self, frame: &mut Frame, level: DetailLevel) {
+        for lane in &self.lanes {
+            let Some(rect) = self.lane_rect(lane, level) else {
+                continue;
+            };
+            let path = Path::rectangle(rect.position(), rect.size());
+            frame.fill(&path, Color::from_rgba(0.5, 0.6, 0.8, 0.15));
+            frame.stroke(
+                &path,
+                Stroke::default()
+                    .with_color(Color::from_rgba(0.3, 0.4, 0.6, 0.8))
+                    .with_width(1.0),
+            );
+            let title = if lane.collapsed {
+                format!("▸ {} ({})", lane.title, lane.members.len())
+            } else {
+                format!("▾ {}", lane.title)
+            };
+            frame.fill_text(Text {
+                content: title,
+                position: Point::new(rect.x + 4.0, rect.y + LANE_HEADER / 2.0),
+                color: Color::BLACK,
+                size: iced::Pixels(f32::from(self.font_size)),
+                vertical_alignment: iced::alignment::Vertical::Center,
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Задаёт сетку холста и привязку перетаскиваемых блоков.
+    pub fn with_grid(mut self, grid: GridSettings) -> Self {
+        self.grid = grid;
+        self
+    }
+
+    /// Обрабатывает клавиши навигации по холсту, когда он в фокусе.
+    fn handle_key(
+        &self,
+        state: &mut State,
+        key: &keyboard::Key,
+        level: DetailLevel,
+        bounds: Rectangle,
+    ) -> Option<(canvas::event::Status, Option<CanvasMessage>)> {
+        let visible = |b: &BlockInfo| self.shown(b, level);
+        let captured = canvas::event::Status::Captured;
+        let direction = match key {
+            keyboard::Key::Named(key::Named::ArrowLeft) => Some(Direction::Left),
+            keyboard::Key::Named(key::Named::ArrowRight) => Some(Direction::Right),
+            keyboard::Key::Named(key::Named::ArrowUp) => Some(Direction::Up),
+            keyboard::Key::Named(key::Named::ArrowDown) => Some(Direction::Down),
+            _ => None,
+        };
+        if let Some(dir) = direction {
+            let next = match state.focused.filter(|i| self.blocks.get(*i).is_some_and(visible)) {
+                Some(from) => focus::neighbor(self.blocks, from, dir, visible),
+                None => focus::first(self.blocks, visible),
+            };
+            let Some(next) = next else {
+                return Some((captured, None));
+            };
+            state.focused = Some(next);
+            state.reveal(&self.blocks[next], bounds);
+            return Some((captured, Some(CanvasMessage::BlockFocused(next))));
+        }
+        match key {
+            keyboard::Key::Named(key::Named::Enter) => {
+                let i = state.focused?;
+                Some((captured, Some(CanvasMessage::EditLabel(i))))
+            }
+            keyboard::Key::Named(key::Named::Escape) => {
+                state.focused = None;
+                Some((captured, None))
+            }
+            keyboard::Key::Character(c) => {
+                let delta = match c.as_str() {
+                    "m" | "M" => {
+                        let i = state.focused?;
+                        return Some((captured, Some(CanvasMessage::EditMeta(i))));
+                    }
+                    "h" => Vector::new(PAN_STEP, 0.0),
+                    "l" => Vector::new(-PAN_STEP, 0.0),
+                    "k" => Vector::new(0.0, PAN_STEP),
+                    "j" => Vector::new(0.0, -PAN_STEP),
+                    _ => return None,
+                };
+                state.offset.set(state.offset.get() + delta);
+                Some((captured, Some(CanvasMessage::Pan { delta })))
+            }
+            _ => None,
+        }
+    }
+
+    /// Рисует справа от блока подсказку с его якорями.
+    /// Рисует подсказку `tip` справа от блока.
+    fn draw_tooltip(&self, frame: &mut Frame, block: &BlockInfo, tip: &str) {
+        let size = f32::from(self.font_size);
+        let line_height = size * 1.3;
+        let longest = tip.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let origin = Point::new(
+            block.x as f32 + block_size(block).width + 8.0,
+            block.y as f32,
+        );
+        let area = iced::Size::new(
+            longest as f32 * size * 0.6 + 10.0,
+            tip.lines().count() as f32 * line_height + 10.0,
+        );
+        frame.fill_rectangle(origin, area, Color::from_rgb(1.0, 1.0, 0.9));
+        frame.stroke(&Path::rectangle(origin, area), Stroke::default());
+        for (i, line) in tip.lines().enumerate() {
+            frame.fill_text(Text {
+                content: line.to_string(),
+                position: Point::new(origin.x + 5.0, origin.y + 5.0 + i as f32 * line_height),
+                color: Color::BLACK,
+                size: iced::Pixels(size),
+                ..Default::default()
+            });
+        }
+    }
+
+    /// Подготавливает маршруты связей, если с прошлого раза изменились
+    /// блоки, связи, промежуточные точки или уровень детализации.
+    fn update_connections(&self, state: &State, level: DetailLevel) {
+        let current_blocks: Vec<(Rectangle, bool)> = self
+            .blocks
+            .iter()
+            .map(|b| (block_rect(b), self.shown(b, level)))
+            .collect();
+        let mut last_blocks = state.last_blocks.borrow_mut();
+        let mut last_connections = state.last_connections.borrow_mut();
+        let mut last_waypoints = state.last_waypoints.borrow_mut();
+        if *last_blocks != current_blocks
+            || *last_connections != self.connections
+            || *last_waypoints != self.waypoints
+        {
+            let prepared =
+                prepare_connections(self.blocks, self.connections, &self.waypoints, |b| {
+                    self.shown(b, level)
+                });
+            *state.connections.borrow_mut() = prepared;
+            *last_blocks = current_blocks;
+            *last_connections = self.connections.to_vec();
+            *last_waypoints = self.waypoints.clone();
+        }
+    }
+
+    /// Правка промежуточных точек щелчком с `Shift` или началом их
+    /// перетаскивания в точке холста `pos`: щелчок по точке удаляет её, по
+    /// маршруту — добавляет новую.
+    fn press_waypoint(&self, state: &mut State, pos: Point) -> Option<CanvasMessage> {
+        let connections = state.connections.borrow();
+        if let Some((connection, index)) = find_waypoint(&connections, pos, state.scale) {
+            if !state.modifiers.shift() {
+                drop(connections);
+                state.waypoint_drag = Some((connection, index));
+                return None;
+            }
+            let mut points = self.waypoints.get(connection).cloned().unwrap_or_default();
+            if index < points.len() {
+                points.remove(index);
+            }
+            return Some(CanvasMessage::WaypointsChanged { connection, points });
+        }
+        if !state.modifiers.shift() {
+            return None;
+        }
+        let route = find_route(&connections, pos, state.scale)?;
+        let point = if self.grid.snap {
+            Point::new(self.grid.round(pos.x), self.grid.round(pos.y))
+        } else {
+            pos
+        };
+        Some(CanvasMessage::WaypointsChanged {
+            connection: route.index,
+            points: insert_waypoint(route, point),
+        })
+    }
+}
+
+/// Рисует сетку в экранных координатах с учётом смещения и масштаба.
+/// При сильном отдалении, когда линии сливаются, сетка не рисуется.
+fn draw_grid(frame: &mut Frame, bounds: Rectangle, state: &State, cell: f32, color: Color) {
+    let step = cell * state.scale;
+    if step < 4.0 {
+        return;
+    }
+    let stroke = Stroke::default().with_color(color).with_width(1.0);
+    let mut x = state.offset.get().x.rem_euclid(step);
+    while x < bounds.width {
+        frame.stroke(
+            &Path::line(Point::new(x, 0.0), Point::new(x, bounds.height)),
+            stroke.clone(),
+        );
+        x += step;
+    }
+    let mut y = state.offset.get().y.rem_euclid(step);
+    while y < bounds.height {
+        frame.stroke(
+            &Path::line(Point::new(0.0, y), Point::new(bounds.width, y)),
+            stroke.clone(),
+        );
+        y += step;
+    }
+}
+
+/// Рисует направляющие выравнивания через всю видимую область холста.
+/// Вызывается после перехода к координатам холста.
+fn draw_guides(frame: &mut Frame, bounds: Rectangle, state: &State, guides: &[Guide]) {
+    let top_left = Point::new(-state.offset.get().x / state.scale, -state.offset.get().y / state.scale);
+    let bottom_right = Point::new(
+        (bounds.width - state.offset.get().x) / state.scale,
+        (bounds.height - state.offset.get().y) / state.scale,
+    );
+    let stroke = Stroke::default()
+        .with_color(Color::from_rgb(0.9, 0.2, 0.6))
+        .with_width(1.0 / state.scale);
+    for guide in guides {
+        let line = match *guide {
+            Guide::Vertical(x) => {
+                Path::line(Point::new(x, top_left.y), Point::new(x, bottom_right.y))
+            }
+            Guide::Horizontal(y) => {
+                Path::line(Point::new(top_left.x, y), Point::new(bottom_right.x, y))
+            }
+        };
+        frame.stroke(&line, stroke.clone());
+    }
+}
+
+/// Цвет блока с долей покрытых строк `ratio`: от красного при нуле до
+/// зелёного при полном покрытии. Блоки вне отчёта серые.
+pub fn coverage_color(ratio: Option<f32>) -> Color {
+    match ratio {
+        Some(r) => {
+            let r = r.clamp(0.0, 1.0);
+            Color::from_rgb(0.85 - 0.55 * r, 0.3 + 0.45 * r, 0.3)
         }
+        None => Color::from_rgb(0.6, 0.6, 0.6),
     }
 }
 
+/// Цвет итога тестов блока: зелёный — прошли, красный — упали, серый —
+/// итогов нет.
+pub fn test_status_color(status: Option<bool>) -> Color {
+    match status {
+        Some(true) => Color::from_rgb(0.2, 0.7, 0.3),
+        Some(false) => Color::from_rgb(0.85, 0.15, 0.15),
+        None => Color::from_rgb(0.6, 0.6, 0.6),
+    }
+}
+
+/// Текст подсказки с якорями блока, по строке на якорь: подпись или
+/// диапазон, перед ними вид в квадратных скобках.
+pub fn anchor_tooltip(block: &BlockInfo) -> Option<String> {
+    if block.anchors.is_empty() {
+        return None;
+    }
+    let lines: Vec<String> = block
+        .anchors
+        .iter()
+        .map(|a| {
+            let text = a
+                .label
+                .clone()
+                .unwrap_or_else(|| format!("{}..{}", a.range.0, a.range.1));
+            match a.kind {
+                Some(kind) => format!("[{kind}] {text}"),
+                None => text,
+            }
+        })
+        .collect();
+    Some(lines.join("\n"))
+}
+
+/// Находится ли точка холста `pos` на метке замечаний блока.
+fn on_lint_badge(block: &BlockInfo, pos: Point) -> bool {
+    let dx = pos.x - block.x as f32;
+    let dy = pos.y - block.y as f32;
+    dx * dx + dy * dy <= LINT_BADGE * LINT_BADGE
+}
+
 fn contains(block: &BlockInfo, pos: Point) -> bool {
+    let size = block_size(block);
     pos.x >= block.x as f32
-        && pos.x <= block.x as f32 + BLOCK_WIDTH
+        && pos.x <= block.x as f32 + size.width
         && pos.y >= block.y as f32
-        && pos.y <= block.y as f32 + BLOCK_HEIGHT
+        && pos.y <= block.y as f32 + size.height
 }
 
-fn find_port(blocks: &[BlockInfo], pos: Point, output: bool) -> Option<(usize, usize, Point)> {
+fn find_port(
+    blocks: &[BlockInfo],
+    pos: Point,
+    output: bool,
+    visible: impl Fn(&BlockInfo) -> bool,
+) -> Option<(usize, usize, Point)> {
     for (bi, block) in blocks.iter().enumerate() {
+        if !visible(block) {
+            continue;
+        }
         for (pi, port) in block.ports.iter().enumerate() {
-            let port_pos = Point::new((block.x + port.x) as f32, (block.y + port.y) as f32);
+            let port_pos = port_point(block, port);
             let dx = pos.x - port_pos.x;
             let dy = pos.y - port_pos.y;
-            if dx * dx + dy * dy <= PORT_RADIUS * PORT_RADIUS {
-                let is_output = port.x >= (BLOCK_WIDTH - PORT_RADIUS * 2.0) as f64;
-                if is_output == output {
-                    return Some((bi, pi, port_pos));
-                }
+            if dx * dx + dy * dy <= PORT_RADIUS * PORT_RADIUS && is_output(port) == output {
+                return Some((bi, pi, port_pos));
             }
         }
     }
@@ -104,15 +692,18 @@ fn find_port(blocks: &[BlockInfo], pos: Point, output: bool) -> Option<(usize, u
 }
 
 impl State {
-    fn update_connections(&self, blocks: &[BlockInfo], connections: &[Connection]) {
-        let current_blocks: Vec<(f64, f64)> = blocks.iter().map(|b| (b.x, b.y)).collect();
-        let mut last_blocks = self.last_blocks.borrow_mut();
-        let mut last_connections = self.last_connections.borrow_mut();
-        if *last_blocks != current_blocks || *last_connections != connections {
-            let prepared = prepare_connections(blocks, connections);
-            *self.connections.borrow_mut() = prepared;
-            *last_blocks = current_blocks;
-            *last_connections = connections.to_vec();
+    /// Сдвигает вид так, чтобы блок `block` оказался внутри `bounds`.
+    fn reveal(&self, block: &BlockInfo, bounds: Rectangle) {
+        let x = block.x as f32 * self.scale + self.offset.get().x;
+        let y = block.y as f32 * self.scale + self.offset.get().y;
+        let size = block_size(block);
+        let w = size.width * self.scale;
+        let h = size.height * self.scale;
+        if x < 0.0 || y < 0.0 || x + w > bounds.width || y + h > bounds.height {
+            self.offset.set(Vector::new(
+                bounds.width / 2.0 - (block.x as f32 + size.width / 2.0) * self.scale,
+                bounds.height / 2.0 - (block.y as f32 + size.height / 2.0) * self.scale,
+            ));
         }
     }
 }
@@ -127,15 +718,24 @@ impl<'a> Program<CanvasMessage> for VisualCanvas<'a> {
         bounds: Rectangle,
         cursor: mouse::Cursor,
     ) -> (canvas::event::Status, Option<CanvasMessage>) {
-        state.update_connections(self.blocks, self.connections);
+        let level = self.lod.level(state.scale);
+        self.update_connections(state, level);
         match event {
-            Event::Keyboard(keyboard::Event::KeyPressed { key, .. }) => {
+            Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+                state.modifiers = modifiers;
+            }
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) => {
                 if key == keyboard::Key::Named(key::Named::Space) {
                     return (
                         canvas::event::Status::Captured,
                         Some(CanvasMessage::TogglePalette),
                     );
                 }
+                if state.has_focus && !modifiers.control() && !modifiers.alt() {
+                    if let Some(result) = self.handle_key(state, &key, level, bounds) {
+                        return result;
+                    }
+                }
             }
             Event::Mouse(mouse::Event::WheelScrolled { delta }) => {
                 if let Some(pos) = cursor.position_in(bounds) {
@@ -155,16 +755,30 @@ impl<'a> Program<CanvasMessage> for VisualCanvas<'a> {
                 }
             }
             Event::Mouse(mouse::Event::ButtonPressed(button)) => {
+                state.has_focus = cursor.position_in(bounds).is_some();
                 if let Some(pos) = cursor.position_in(bounds) {
                     match button {
                         mouse::Button::Left => {
                             let canvas_pos = Point::new(
-                                (pos.x - state.offset.x) / state.scale,
-                                (pos.y - state.offset.y) / state.scale,
+                                (pos.x - state.offset.get().x) / state.scale,
+                                (pos.y - state.offset.get().y) / state.scale,
                             );
+                            let badge = self.blocks.iter().enumerate().position(|(i, b)| {
+                                self.shown(b, level)
+                                    && !self.lint_of(i).is_empty()
+                                    && on_lint_badge(b, canvas_pos)
+                            });
+                            if let Some(index) = badge {
+                                return (
+                                    canvas::event::Status::Captured,
+                                    Some(CanvasMessage::LintClicked(index)),
+                                );
+                            }
                             if state.connection.is_none() {
                                 if let Some((b, p, start)) =
-                                    find_port(self.blocks, canvas_pos, true)
+                                    find_port(self.blocks, canvas_pos, true, |b| {
+                                        self.shown(b, level)
+                                    })
                                 {
                                     state.connection = Some(ConnectionDrag {
                                         from_block: b,
@@ -176,19 +790,60 @@ impl<'a> Program<CanvasMessage> for VisualCanvas<'a> {
                                     });
                                     return (canvas::event::Status::Captured, None);
                                 }
+                                if self.layers.shows(Layer::Links) {
+                                    let message = self.press_waypoint(state, canvas_pos);
+                                    if message.is_some() || state.waypoint_drag.is_some() {
+                                        return (canvas::event::Status::Captured, message);
+                                    }
+                                }
+                            }
+                            if let Some(idx) = self.blocks.iter().position(|b| {
+                                self.shown(b, level) && on_resize_handle(b, canvas_pos)
+                            }) {
+                                state.selected = Some(idx);
+                                state.resize = Some(idx);
+                                return (
+                                    canvas::event::Status::Captured,
+                                    Some(CanvasMessage::BlockSelected(Some(idx))),
+                                );
+                            }
+                            if let Some(lane) = self.lane_at(canvas_pos, level) {
+                                return (
+                                    canvas::event::Status::Captured,
+                                    Some(CanvasMessage::LaneToggled(lane.key.clone())),
+                                );
                             }
                             if let Some((idx, block)) = self
                                 .blocks
                                 .iter()
                                 .enumerate()
-                                .find(|(_, b)| contains(b, canvas_pos))
+                                .find(|(_, b)| self.shown(b, level) && contains(b, canvas_pos))
                             {
                                 state.selected = Some(idx);
+                                state.focused = Some(idx);
+                                let now = Instant::now();
+                                let double = matches!(
+                                    state.last_click,
+                                    Some((last, at)) if last == idx && now - at <= DOUBLE_CLICK
+                                );
+                                if double {
+                                    state.last_click = None;
+                                    state.drag = None;
+                                    return (
+                                        canvas::event::Status::Captured,
+                                        Some(CanvasMessage::BlockActivated(idx)),
+                                    );
+                                }
+                                state.last_click = Some((idx, now));
                                 let grab = Vector::new(
                                     canvas_pos.x - block.x as f32,
                                     canvas_pos.y - block.y as f32,
                                 );
-                                state.drag = Some(Drag { index: idx, grab });
+                                state.drag = Some(Drag {
+                                    index: idx,
+                                    grab,
+                                    guides: Vec::new(),
+                                });
                                 return (
                                     canvas::event::Status::Captured,
                                     Some(CanvasMessage::BlockSelected(Some(idx))),
@@ -228,31 +883,40 @@ impl<'a> Program<CanvasMessage> for VisualCanvas<'a> {
                             return (canvas::event::Status::Captured, None);
                         }
                     }
+                    if state.waypoint_drag.take().is_some() || state.resize.take().is_some() {
+                        return (canvas::event::Status::Captured, None);
+                    }
                     if let Some(drag) = state.drag.take() {
                         if let Some(pos) = cursor.position_in(bounds) {
                             let canvas_pos = Point::new(
-                                (pos.x - state.offset.x) / state.scale,
-                                (pos.y - state.offset.y) / state.scale,
+                                (pos.x - state.offset.get().x) / state.scale,
+                                (pos.y - state.offset.get().y) / state.scale,
                             );
                             let new_pos =
                                 Point::new(canvas_pos.x - drag.grab.x, canvas_pos.y - drag.grab.y);
+                            let snapped = self
+                                .grid
+                                .snap(self.blocks, drag.index, new_pos, |b| self.shown(b, level));
                             return (
                                 canvas::event::Status::Captured,
                                 Some(CanvasMessage::BlockDragged {
                                     index: drag.index,
-                                    position: new_pos,
+                                    position: snapped.position,
                                 }),
                             );
                         }
                     } else if let Some(pos) = cursor.position_in(bounds) {
                         let canvas_pos = Point::new(
-                            (pos.x - state.offset.x) / state.scale,
-                            (pos.y - state.offset.y) / state.scale,
+                            (pos.x - state.offset.get().x) / state.scale,
+                            (pos.y - state.offset.get().y) / state.scale,
                         );
+                        let snapped = self.grid.snap(self.blocks, usize::MAX, canvas_pos, |b| {
+                            self.shown(b, level)
+                        });
                         return (
                             canvas::event::Status::Captured,
                             Some(CanvasMessage::Dropped {
-                                position: canvas_pos,
+                                position: snapped.position,
                             }),
                         );
                     }
@@ -270,7 +934,7 @@ impl<'a> Program<CanvasMessage> for VisualCanvas<'a> {
                     if state.panning {
                         let delta = pos - state.last_cursor;
                         state.last_cursor = pos;
-                        state.offset = state.offset + delta;
+                        state.offset.set(state.offset.get() + delta);
                         return (
                             canvas::event::Status::Captured,
                             Some(CanvasMessage::Pan { delta }),
@@ -278,26 +942,74 @@ impl<'a> Program<CanvasMessage> for VisualCanvas<'a> {
                     }
                     if let Some(conn) = state.connection.as_mut() {
                         let canvas_pos = Point::new(
-                            (pos.x - state.offset.x) / state.scale,
-                            (pos.y - state.offset.y) / state.scale,
+                            (pos.x - state.offset.get().x) / state.scale,
+                            (pos.y - state.offset.get().y) / state.scale,
                         );
                         conn.current = canvas_pos;
                         conn.hover =
-                            find_port(self.blocks, canvas_pos, false).map(|(b, p, _)| (b, p));
+                            find_port(self.blocks, canvas_pos, false, |b| self.shown(b, level))
+                                .map(|(b, p, _)| (b, p));
                         return (canvas::event::Status::Captured, None);
                     }
-                    if let Some(drag) = state.drag.as_ref() {
+                    if let Some((connection, index)) = state.waypoint_drag {
+                        let canvas_pos = Point::new(
+                            (pos.x - state.offset.get().x) / state.scale,
+                            (pos.y - state.offset.get().y) / state.scale,
+                        );
+                        let mut points =
+                            self.waypoints.get(connection).cloned().unwrap_or_default();
+                        let Some(point) = points.get_mut(index) else {
+                            state.waypoint_drag = None;
+                            return (canvas::event::Status::Captured, None);
+                        };
+                        *point = if self.grid.snap {
+                            Point::new(self.grid.round(canvas_pos.x), self.grid.round(canvas_pos.y))
+                        } else {
+                            canvas_pos
+                        };
+                        return (
+                            canvas::event::Status::Captured,
+                            Some(CanvasMessage::WaypointsChanged { connection, points }),
+                        );
+                    }
+                    if let Some(index) = state.resize {
                         let canvas_pos = Point::new(
-                            (pos.x - state.offset.x) / state.scale,
-                            (pos.y - state.offset.y) / state.scale,
+                            (pos.x - state.offset.get().x) / state.scale,
+                            (pos.y - state.offset.get().y) / state.scale,
+                        );
+                        let Some(block) = self.blocks.get(index) else {
+                            state.resize = None;
+                            return (canvas::event::Status::Captured, None);
+                        };
+                        let corner = if self.grid.snap {
+                            Point::new(self.grid.round(canvas_pos.x), self.grid.round(canvas_pos.y))
+                        } else {
+                            canvas_pos
+                        };
+                        return (
+                            canvas::event::Status::Captured,
+                            Some(CanvasMessage::BlockResized {
+                                index,
+                                size: resized(block, corner),
+                            }),
+                        );
+                    }
+                    if let Some(drag) = state.drag.as_mut() {
+                        let canvas_pos = Point::new(
+                            (pos.x - state.offset.get().x) / state.scale,
+                            (pos.y - state.offset.get().y) / state.scale,
                         );
                         let new_pos =
                             Point::new(canvas_pos.x - drag.grab.x, canvas_pos.y - drag.grab.y);
+                        let snapped = self
+                            .grid
+                            .snap(self.blocks, drag.index, new_pos, |b| self.shown(b, level));
+                        drag.guides = snapped.guides;
                         return (
                             canvas::event::Status::Captured,
                             Some(CanvasMessage::BlockDragged {
                                 index: drag.index,
-                                position: new_pos,
+                                position: snapped.position,
                             }),
                         );
                     }
@@ -314,47 +1026,276 @@ impl<'a> Program<CanvasMessage> for VisualCanvas<'a> {
         renderer: &Renderer,
         _theme: &Theme,
         bounds: Rectangle,
-        _cursor: mouse::Cursor,
+        cursor: mouse::Cursor,
     ) -> Vec<Geometry> {
-        state.update_connections(self.blocks, self.connections);
+        let level = self.lod.level(state.scale);
+        self.update_connections(state, level);
+
+        let current = self.filter.as_ref().and_then(|f| f.current);
+        if state.revealed.get() != current {
+            if let Some(block) = current.and_then(|i| self.blocks.get(i)) {
+                state.reveal(block, bounds);
+            }
+            state.revealed.set(current);
+        }
 
         let mut frame = Frame::new(renderer, bounds.size());
 
-        frame.translate(state.offset);
+        if let Some((background, grid)) = self.background {
+            frame.fill_rectangle(Point::ORIGIN, bounds.size(), background);
+            if self.grid.show {
+                draw_grid(&mut frame, bounds, state, self.grid.step(), grid);
+            }
+        }
+
+        frame.translate(state.offset.get());
         frame.scale(state.scale);
 
-        let connections = state.connections.borrow();
-        draw_connections(&mut frame, &connections, state.scale);
-        drop(connections);
+        self.draw_lanes(&mut frame, level);
+
+        if self.layers.shows(Layer::Links) {
+            let connections = state.connections.borrow();
+            draw_connections(&mut frame, &connections, state.scale);
+        }
 
         if let Some(conn) = state.connection.as_ref() {
             draw_drag(&mut frame, conn, self.blocks, state.scale);
         }
 
+        if let Some(drag) = state.drag.as_ref() {
+            draw_guides(&mut frame, bounds, state, &drag.guides);
+        }
+
         for (i, block) in self.blocks.iter().enumerate() {
-            let rect = Path::rectangle(
-                Point::new(block.x as f32, block.y as f32),
-                iced::Size::new(BLOCK_WIDTH, BLOCK_HEIGHT),
-            );
+            if !self.shown(block, level) {
+                continue;
+            }
+            let size = block_size(block);
+            let rect = Path::rectangle(Point::new(block.x as f32, block.y as f32), size);
+            let coverage = self.coverage.as_ref().map(|c| c.get(i).copied().flatten());
+            let profile = self.profile.as_ref().map(|p| p.get(i).cloned().flatten());
             let color = if state.selected == Some(i) {
                 iced::Color::from_rgb(0.8, 0.3, 0.3)
+            } else if let Some(cost) = &profile {
+                match cost {
+                    Some((h, _)) => heat_color(*h),
+                    None => iced::Color::from_rgb(0.6, 0.6, 0.6),
+                }
+            } else if let Some(ratio) = coverage {
+                coverage_color(ratio)
+            } else if let Some((r, g, b)) = block.color.as_deref().and_then(style::parse_color) {
+                iced::Color::from_rgb8(r, g, b)
+            } else if block.kind == FILE_REF_KIND {
+                iced::Color::from_rgb(0.3, 0.6, 0.4)
             } else {
-                iced::Color::from_rgb(0.3, 0.3, 0.7)
+                self.colors
+                    .and_then(|c| c.for_kind(&block.kind))
+                    .unwrap_or(iced::Color::from_rgb(0.3, 0.3, 0.7))
             };
-            frame.fill(&rect, color);
-            frame.stroke(&rect, Stroke::default());
+            let alpha = if self.matches_filter(i) { 1.0 } else { 0.25 };
+            if self.layers.shows(Layer::Blocks) {
+                frame.fill(&rect, Color { a: alpha, ..color });
+            }
+            if self.stopped == Some(i) {
+                frame.stroke(
+                    &rect,
+                    Stroke::default()
+                        .with_width(4.0)
+                        .with_color(iced::Color::from_rgb(0.1, 0.75, 0.2)),
+                );
+            } else if current == Some(i) {
+                frame.stroke(
+                    &rect,
+                    Stroke::default()
+                        .with_width(3.0)
+                        .with_color(iced::Color::from_rgb(0.1, 0.8, 0.9)),
+                );
+            } else if state.focused == Some(i) {
+                frame.stroke(
+                    &rect,
+                    Stroke::default()
+                        .with_width(3.0)
+                        .with_color(iced::Color::from_rgb(1.0, 0.8, 0.2)),
+                );
+            } else if !self.lint_of(i).is_empty() {
+                frame.stroke(
+                    &rect,
+                    Stroke::default()
+                        .with_width(2.0)
+                        .with_color(iced::Color::from_rgb(0.85, 0.15, 0.15)),
+                );
+            } else {
+                frame.stroke(&rect, Stroke::default());
+            }
             let label = block
                 .translations
                 .get(self.language.code())
                 .cloned()
                 .or_else(|| translate_kind(&block.kind, self.language).map(|s| s.to_string()))
                 .unwrap_or_else(|| block.kind.clone());
-            frame.fill_text(Text {
-                content: label,
-                position: Point::new(block.x as f32 + 5.0, block.y as f32 + 20.0),
-                color: iced::Color::BLACK,
-                ..Default::default()
-            });
+            let label = match &block.icon {
+                Some(icon) => format!("{icon} {label}"),
+                None => label,
+            };
+            let font_size = f32::from(self.font_size);
+            let lines = if self.layers.shows(Layer::Blocks) {
+                wrap_label(&label, size, font_size)
+            } else {
+                Vec::new()
+            };
+            // Строки подписи центрируются по высоте блока.
+            let top = block.y as f32
+                + ((size.height - lines.len() as f32 * font_size * 1.2) / 2.0).max(LABEL_PADDING);
+            for (row, line) in lines.into_iter().enumerate() {
+                frame.fill_text(Text {
+                    content: line,
+                    position: Point::new(
+                        block.x as f32 + LABEL_PADDING,
+                        top + row as f32 * font_size * 1.2,
+                    ),
+                    color: Color {
+                        a: alpha,
+                        ..iced::Color::BLACK
+                    },
+                    size: iced::Pixels(font_size),
+                    ..Default::default()
+                });
+            }
+            if state.selected == Some(i) {
+                let corner = Point::new(block.x as f32 + size.width, block.y as f32 + size.height);
+                let mut handle = canvas::path::Builder::new();
+                handle.move_to(corner);
+                handle.line_to(Point::new(corner.x - RESIZE_HANDLE, corner.y));
+                handle.line_to(Point::new(corner.x, corner.y - RESIZE_HANDLE));
+                handle.close();
+                frame.fill(&handle.build(), iced::Color::from_rgb(0.2, 0.2, 0.2));
+            }
+            if self.frozen.get(i).copied().unwrap_or(false) {
+                let pin = Path::circle(
+                    Point::new(block.x as f32 + size.width - 6.0, block.y as f32 + 6.0),
+                    3.0,
+                );
+                frame.fill(
+                    &pin,
+                    Color {
+                        a: alpha,
+                        ..iced::Color::from_rgb(0.2, 0.2, 0.2)
+                    },
+                );
+            }
+            let corner_text = match (profile, coverage) {
+                (Some(cost), _) => cost.map(|(_, label)| label),
+                (None, Some(Some(ratio))) => Some(format!("{}%", (ratio * 100.0).floor())),
+                _ => None,
+            };
+            if let Some(content) = corner_text {
+                frame.fill_text(Text {
+                    content,
+                    position: Point::new(
+                        block.x as f32 + size.width - 5.0,
+                        block.y as f32 + size.height - 5.0,
+                    ),
+                    color: iced::Color::BLACK,
+                    size: iced::Pixels(f32::from(self.font_size) * 0.8),
+                    horizontal_alignment: iced::alignment::Horizontal::Right,
+                    vertical_alignment: iced::alignment::Vertical::Bottom,
+                    ..Default::default()
+                });
+            }
+            if let Some(note) = ai_note(block).filter(|_| self.layers.shows(Layer::Ai)) {
+                // Заметка ИИ занимает одну строку под блоком.
+                let note_size = f32::from(self.font_size) * 0.8;
+                let line = iced::Size::new(size.width, note_size * 1.2 + LABEL_PADDING * 2.0);
+                if let Some(note) = wrap_label(note, line, note_size).into_iter().next() {
+                    frame.fill_text(Text {
+                        content: note,
+                        position: Point::new(
+                            block.x as f32 + LABEL_PADDING,
+                            block.y as f32 + size.height + 2.0,
+                        ),
+                        color: Color {
+                            a: alpha,
+                            ..iced::Color::from_rgb(0.4, 0.2, 0.6)
+                        },
+                        size: iced::Pixels(note_size),
+                        ..Default::default()
+                    });
+                }
+            }
+            if self.breakpoints.get(i).copied().unwrap_or(false) {
+                // Точка останова — красный кружок на левом краю блока.
+                let dot = Path::circle(
+                    Point::new(block.x as f32, block.y as f32 + size.height / 2.0),
+                    4.0,
+                );
+                frame.fill(&dot, iced::Color::from_rgb(0.85, 0.1, 0.1));
+            }
+            if let Some(passed) = self.tests.get(i).copied().flatten() {
+                // Итог тестов — зелёный или красный кружок левее метки
+                // закрепления.
+                let dot = Path::circle(
+                    Point::new(block.x as f32 + size.width - 16.0, block.y as f32 + 6.0),
+                    3.0,
+                );
+                frame.fill(&dot, test_status_color(Some(passed)));
+            }
+            // Значения наблюдения идут столбцом справа от блока.
+            let watch_size = f32::from(self.font_size) * 0.8;
+            for (row, watch) in self.watches.get(i).into_iter().flatten().enumerate() {
+                frame.fill_text(Text {
+                    content: watch.clone(),
+                    position: Point::new(
+                        block.x as f32 + size.width + LABEL_PADDING,
+                        block.y as f32 + row as f32 * watch_size * 1.2,
+                    ),
+                    color: Color {
+                        a: alpha,
+                        ..iced::Color::from_rgb(0.1, 0.5, 0.2)
+                    },
+                    size: iced::Pixels(watch_size),
+                    ..Default::default()
+                });
+            }
+            let lint = self.lint_of(i).len();
+            if lint > 0 {
+                let center = Point::new(block.x as f32, block.y as f32);
+                frame.fill(
+                    &Path::circle(center, LINT_BADGE),
+                    Color {
+                        a: alpha,
+                        ..iced::Color::from_rgb(0.85, 0.15, 0.15)
+                    },
+                );
+                frame.fill_text(Text {
+                    content: lint.to_string(),
+                    position: center,
+                    color: iced::Color::WHITE,
+                    size: iced::Pixels(9.0),
+                    horizontal_alignment: iced::alignment::Horizontal::Center,
+                    vertical_alignment: iced::alignment::Vertical::Center,
+                    ..Default::default()
+                });
+            }
+        }
+
+        let hovered = cursor.position_in(bounds).and_then(|pos| {
+            let canvas_pos = Point::new(
+                (pos.x - state.offset.get().x) / state.scale,
+                (pos.y - state.offset.get().y) / state.scale,
+            );
+            self.blocks
+                .iter()
+                .position(|b| self.shown(b, level) && contains(b, canvas_pos))
+        });
+        if let Some(i) = hovered {
+            let block = &self.blocks[i];
+            let tip: Vec<String> = anchor_tooltip(block)
+                .into_iter()
+                .chain(self.lint_of(i).iter().map(|m| format!("⚠ {m}")))
+                .collect();
+            if !tip.is_empty() {
+                self.draw_tooltip(&mut frame, block, &tip.join("\n"));
+            }
         }
 
         vec![frame.into_geometry()]
@@ -1,12 +1,19 @@
 use iced::widget::canvas::{Frame, Path, Stroke};
-use iced::{Color, Point, Vector};
+use iced::{Color, Point, Rectangle, Size, Vector};
 use multicode_core::BlockInfo;
 
 use crate::visual::connections::{Connection, DataType};
+use crate::visual::geometry::{block_size, port_point};
 
 pub const PORT_RADIUS: f32 = 5.0;
 const ARROW_LENGTH: f32 = 10.0;
 const ARROW_WIDTH: f32 = 6.0;
+/// Длина прямого участка связи у порта.
+const STUB: f32 = 15.0;
+/// Зазор между обходящей связью и блоком.
+const MARGIN: f32 = 10.0;
+/// Половина стороны маркера промежуточной точки в пикселях экрана.
+pub const WAYPOINT_HANDLE: f32 = 4.0;
 
 #[derive(Debug, Clone)]
 pub struct ConnectionDrag {
@@ -20,36 +27,173 @@ pub struct ConnectionDrag {
 
 #[derive(Clone)]
 pub struct PreparedConnection {
-    pub start: Point,
-    pub end: Point,
+    /// Индекс связи в исходном списке.
+    pub index: usize,
+    /// Ломаная из горизонтальных и вертикальных отрезков от порта-источника
+    /// до порта-приёмника.
+    pub route: Vec<Point>,
+    /// Промежуточные точки, заданные пользователем.
+    pub waypoints: Vec<Point>,
     pub color: Color,
 }
 
+/// Прямоугольник блока на холсте.
+pub fn block_rect(block: &BlockInfo) -> Rectangle {
+    Rectangle::new(
+        Point::new(block.x as f32, block.y as f32),
+        block_size(block),
+    )
+}
+
+/// Проходит ли горизонтальный или вертикальный отрезок `a`–`b` через
+/// внутренность прямоугольника `rect`. Касание границы не считается.
+fn crosses(a: Point, b: Point, rect: &Rectangle) -> bool {
+    let (x0, x1) = (a.x.min(b.x), a.x.max(b.x));
+    let (y0, y1) = (a.y.min(b.y), a.y.max(b.y));
+    x1 > rect.x && x0 < rect.x + rect.width && y1 > rect.y && y0 < rect.y + rect.height
+}
+
+fn is_clear(points: &[Point], obstacles: &[Rectangle]) -> bool {
+    points
+        .windows(2)
+        .all(|w| obstacles.iter().all(|r| !crosses(w[0], w[1], r)))
+}
+
+/// Убирает повторяющиеся точки и точки посреди прямого отрезка.
+fn simplify(points: Vec<Point>) -> Vec<Point> {
+    let mut out: Vec<Point> = Vec::with_capacity(points.len());
+    for p in points {
+        if out.last() == Some(&p) {
+            continue;
+        }
+        if out.len() >= 2 {
+            let a = out[out.len() - 2];
+            let b = out[out.len() - 1];
+            if (a.x == b.x && b.x == p.x) || (a.y == b.y && b.y == p.y) {
+                out.pop();
+            }
+        }
+        out.push(p);
+    }
+    out
+}
+
+/// Добавляет к `points` переход из последней точки в `to` горизонтальным
+/// и вертикальным отрезками.
+fn elbow(points: &mut Vec<Point>, to: Point) {
+    if let Some(&from) = points.last() {
+        if from.x != to.x && from.y != to.y {
+            points.push(Point::new(to.x, from.y));
+        }
+    }
+    points.push(to);
+}
+
+/// Ортогональный маршрут связи от выхода `start` к входу `end`.
+///
+/// Связь выходит из порта вправо и входит в порт слева. Заданные
+/// `waypoints` проходятся по порядку; без них выбирается ближайший к
+/// прямому маршрут, не пересекающий блоки `obstacles`, а если такого нет —
+/// маршрут через середину.
+pub fn route(start: Point, end: Point, waypoints: &[Point], obstacles: &[Rectangle]) -> Vec<Point> {
+    let a = Point::new(start.x + STUB, start.y);
+    let b = Point::new(end.x - STUB, end.y);
+    let mut middle = vec![a];
+    if waypoints.is_empty() {
+        middle = auto_route(a, b, obstacles);
+    } else {
+        for &w in waypoints {
+            elbow(&mut middle, w);
+        }
+        elbow(&mut middle, b);
+    }
+    let mut points = vec![start];
+    points.extend(middle);
+    points.push(end);
+    simplify(points)
+}
+
+fn by_distance(mut values: Vec<f32>, target: f32) -> Vec<f32> {
+    values.sort_by(|x, y| (x - target).abs().total_cmp(&(y - target).abs()));
+    values
+}
+
+/// Маршрут между концами участков у портов `a` и `b`.
+fn auto_route(a: Point, b: Point, obstacles: &[Rectangle]) -> Vec<Point> {
+    let mid_x = (a.x + b.x) / 2.0;
+    let mid_y = (a.y + b.y) / 2.0;
+    let through_x = |x: f32| vec![a, Point::new(x, a.y), Point::new(x, b.y), b];
+    let through_y = |y: f32| vec![a, Point::new(a.x, y), Point::new(b.x, y), b];
+    if b.x >= a.x {
+        let mut xs = vec![mid_x];
+        for r in obstacles {
+            xs.push(r.x - MARGIN);
+            xs.push(r.x + r.width + MARGIN);
+        }
+        xs.retain(|x| (a.x..=b.x).contains(x));
+        for x in by_distance(xs, mid_x) {
+            let candidate = through_x(x);
+            if is_clear(&candidate, obstacles) {
+                return candidate;
+            }
+        }
+    }
+    let mut ys = vec![mid_y];
+    for r in obstacles {
+        ys.push(r.y - MARGIN);
+        ys.push(r.y + r.height + MARGIN);
+    }
+    for y in by_distance(ys, mid_y) {
+        let candidate = through_y(y);
+        if is_clear(&candidate, obstacles) {
+            return candidate;
+        }
+    }
+    if b.x >= a.x {
+        through_x(mid_x)
+    } else {
+        through_y(mid_y)
+    }
+}
+
+/// Подготавливает к рисованию связи `connections`, у которых видны оба
+/// блока. `waypoints[i]` — промежуточные точки связи `i`; видимые блоки
+/// служат препятствиями для маршрутов.
 pub fn prepare_connections(
     blocks: &[BlockInfo],
     connections: &[Connection],
+    waypoints: &[Vec<Point>],
+    visible: impl Fn(&BlockInfo) -> bool,
 ) -> Vec<PreparedConnection> {
+    let obstacles: Vec<Rectangle> = blocks
+        .iter()
+        .filter(|b| visible(b))
+        .map(block_rect)
+        .collect();
     let mut prepared = Vec::new();
-    for c in connections {
+    for (index, c) in connections.iter().enumerate() {
         if let (Some(from_block), Some(to_block)) = (blocks.get(c.from.0), blocks.get(c.to.0)) {
+            if !visible(from_block) || !visible(to_block) {
+                continue;
+            }
             if let (Some(from_port), Some(to_port)) =
                 (from_block.ports.get(c.from.1), to_block.ports.get(c.to.1))
             {
-                let start = Point::new(
-                    (from_block.x + from_port.x) as f32,
-                    (from_block.y + from_port.y) as f32,
-                );
-                let end = Point::new(
-                    (to_block.x + to_port.x) as f32,
-                    (to_block.y + to_port.y) as f32,
-                );
+                let start = port_point(from_block, from_port);
+                let end = port_point(to_block, to_port);
                 let color = match c.data_type {
                     DataType::Number => Color::from_rgb(0.0, 0.0, 0.8),
                     DataType::Boolean => Color::from_rgb(0.0, 0.6, 0.0),
                     DataType::Text => Color::from_rgb(1.0, 0.5, 0.0),
                     DataType::Any => Color::from_rgb(0.5, 0.5, 0.5),
                 };
-                prepared.push(PreparedConnection { start, end, color });
+                let waypoints = waypoints.get(index).cloned().unwrap_or_default();
+                prepared.push(PreparedConnection {
+                    index,
+                    route: route(start, end, &waypoints, &obstacles),
+                    waypoints,
+                    color,
+                });
             }
         }
     }
@@ -86,21 +230,87 @@ fn draw_arrow(frame: &mut Frame, start: Point, end: Point, color: Color, scale:
 
 pub fn draw_connections(frame: &mut Frame, connections: &[PreparedConnection], scale: f32) {
     for connection in connections.iter() {
-        let path = Path::line(connection.start, connection.end);
+        let (Some(&first), Some(&end)) = (connection.route.first(), connection.route.last()) else {
+            continue;
+        };
+        let path = Path::new(|p| {
+            p.move_to(first);
+            for &point in &connection.route[1..] {
+                p.line_to(point);
+            }
+        });
         let stroke = Stroke::default()
             .with_color(connection.color)
             .with_width(2.0);
         frame.stroke(&path, stroke);
-        draw_arrow(
-            frame,
-            connection.start,
-            connection.end,
-            connection.color,
-            scale,
-        );
+        let before = connection.route[connection.route.len().saturating_sub(2)];
+        draw_arrow(frame, before, end, connection.color, scale);
+        let half = WAYPOINT_HANDLE / scale;
+        for &w in &connection.waypoints {
+            frame.fill_rectangle(
+                Point::new(w.x - half, w.y - half),
+                Size::new(half * 2.0, half * 2.0),
+                connection.color,
+            );
+        }
     }
 }
 
+/// Промежуточная точка под курсором `pos`: индекс связи в исходном списке
+/// и номер точки.
+pub fn find_waypoint(
+    connections: &[PreparedConnection],
+    pos: Point,
+    scale: f32,
+) -> Option<(usize, usize)> {
+    let reach = (WAYPOINT_HANDLE + 2.0) / scale;
+    connections.iter().find_map(|c| {
+        c.waypoints
+            .iter()
+            .position(|w| (w.x - pos.x).abs() <= reach && (w.y - pos.y).abs() <= reach)
+            .map(|i| (c.index, i))
+    })
+}
+
+/// Связь, маршрут которой проходит рядом с `pos`.
+pub fn find_route(
+    connections: &[PreparedConnection],
+    pos: Point,
+    scale: f32,
+) -> Option<&PreparedConnection> {
+    let reach = 4.0 / scale;
+    connections.iter().find(|c| {
+        c.route.windows(2).any(|w| {
+            let (x0, x1) = (w[0].x.min(w[1].x), w[0].x.max(w[1].x));
+            let (y0, y1) = (w[0].y.min(w[1].y), w[0].y.max(w[1].y));
+            pos.x >= x0 - reach && pos.x <= x1 + reach && pos.y >= y0 - reach && pos.y <= y1 + reach
+        })
+    })
+}
+
+/// Промежуточные точки `connection` с добавленной точкой `pos`, вставленной
+/// туда, где она меньше всего удлиняет путь через точки.
+pub fn insert_waypoint(connection: &PreparedConnection, pos: Point) -> Vec<Point> {
+    let (Some(&start), Some(&end)) = (connection.route.first(), connection.route.last()) else {
+        return vec![pos];
+    };
+    let mut chain = vec![start];
+    chain.extend(&connection.waypoints);
+    chain.push(end);
+    let dist = |a: Point, b: Point| a.distance(b);
+    let slot = (0..chain.len() - 1)
+        .min_by(|&i, &j| {
+            let cost = |k: usize| {
+                dist(chain[k], pos) + dist(pos, chain[k + 1]) - dist(chain[k], chain[k + 1])
+            };
+            cost(i).total_cmp(&cost(j))
+        })
+        .unwrap_or(0);
+    let mut waypoints = connection.waypoints.clone();
+    waypoints.insert(slot, pos);
+    waypoints
+}
+
 pub fn draw_drag(frame: &mut Frame, conn: &ConnectionDrag, blocks: &[BlockInfo], scale: f32) {
     let path = Path::line(conn.start, conn.current);
     let stroke = Stroke::default()
@@ -117,10 +327,61 @@ pub fn draw_drag(frame: &mut Frame, conn: &ConnectionDrag, blocks: &[BlockInfo],
     if let Some((b, p)) = conn.hover {
         if let Some(block) = blocks.get(b) {
             if let Some(port) = block.ports.get(p) {
-                let point = Point::new((block.x + port.x) as f32, (block.y + port.y) as f32);
+                let point = port_point(block, port);
                 let circle = Path::circle(point, PORT_RADIUS);
                 frame.fill(&circle, Color::from_rgba(0.0, 1.0, 0.0, 0.5));
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn orthogonal(points: &[Point]) -> bool {
+        points
+            .windows(2)
+            .all(|w| w[0].x == w[1].x || w[0].y == w[1].y)
+    }
+
+    #[test]
+    fn route_goes_around_blocks() {
+        let start = Point::new(120.0, 20.0);
+        let end = Point::new(400.0, 20.0);
+        let obstacle = Rectangle::new(Point::new(200.0, 0.0), Size::new(120.0, 40.0));
+        let points = route(start, end, &[], &[obstacle]);
+        assert_eq!(points.first(), Some(&start));
+        assert_eq!(points.last(), Some(&end));
+        assert!(orthogonal(&points));
+        assert!(is_clear(&points, &[obstacle]));
+
+        let straight = route(start, end, &[], &[]);
+        assert_eq!(straight, vec![start, end]);
+    }
+
+    #[test]
+    fn route_passes_waypoints_in_order() {
+        let start = Point::new(0.0, 0.0);
+        let end = Point::new(300.0, 0.0);
+        let waypoints = [Point::new(100.0, 80.0), Point::new(200.0, -40.0)];
+        let points = route(start, end, &waypoints, &[]);
+        assert!(orthogonal(&points));
+        let at = |w: &Point| points.iter().position(|p| p == w).unwrap();
+        assert!(at(&waypoints[0]) < at(&waypoints[1]));
+    }
+
+    #[test]
+    fn inserted_waypoint_keeps_path_order() {
+        let connection = PreparedConnection {
+            index: 0,
+            route: vec![Point::new(0.0, 0.0), Point::new(300.0, 0.0)],
+            waypoints: vec![Point::new(100.0, 50.0), Point::new(200.0, 50.0)],
+            color: Color::BLACK,
+        };
+        let points = insert_waypoint(&connection, Point::new(150.0, 50.0));
+        assert_eq!(points[1], Point::new(150.0, 50.0));
+        let points = insert_waypoint(&connection, Point::new(250.0, 30.0));
+        assert_eq!(points[2], Point::new(250.0, 30.0));
+    }
+}
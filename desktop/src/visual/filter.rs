@@ -0,0 +1,133 @@
+//! Фильтр блоков холста.
+//!
+//! Строка фильтра сопоставляется с видом, подписью и тегами каждого блока:
+//! блок подходит, если каждое слово строки входит хотя бы в одно из них.
+//! Неподходящие блоки холст затеняет или скрывает, а по подходящим можно
+//! переходить вперёд и назад.
+
+use multicode_core::BlockInfo;
+
+use crate::visual::translations::{translate_kind, Language};
+
+/// Фильтр, который применяет холст.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CanvasFilter {
+    /// Подходит ли фильтру каждый блок.
+    pub matches: Vec<bool>,
+    /// Скрывать неподходящие блоки вместо затенения.
+    pub hide: bool,
+    /// Совпадение, к которому перешёл пользователь.
+    pub current: Option<usize>,
+}
+
+/// Подходит ли блок строке `query` на языке подписей `language`.
+pub fn block_matches(block: &BlockInfo, query: &str, language: Language) -> bool {
+    let mut fields = vec![block.kind.to_lowercase()];
+    if let Some(label) = block.translations.get(language.code()) {
+        fields.push(label.to_lowercase());
+    }
+    if let Some(kind) = translate_kind(&block.kind, language) {
+        fields.push(kind.to_lowercase());
+    }
+    fields.extend(block.tags.iter().map(|t| t.to_lowercase()));
+    query
+        .to_lowercase()
+        .split_whitespace()
+        .all(|word| fields.iter().any(|f| f.contains(word)))
+}
+
+/// Совпадения строки `query` по блокам; `None` для пустой строки.
+pub fn matches(blocks: &[BlockInfo], query: &str, language: Language) -> Option<Vec<bool>> {
+    if query.trim().is_empty() {
+        return None;
+    }
+    Some(
+        blocks
+            .iter()
+            .map(|b| block_matches(b, query, language))
+            .collect(),
+    )
+}
+
+/// Следующее после `current` совпадение по кругу, при `forward == false` —
+/// предыдущее. Без текущего берётся первое или последнее совпадение.
+pub fn step(matches: &[bool], current: Option<usize>, forward: bool) -> Option<usize> {
+    let found: Vec<usize> = (0..matches.len()).filter(|&i| matches[i]).collect();
+    let (first, last) = (*found.first()?, *found.last()?);
+    let Some(current) = current else {
+        return Some(if forward { first } else { last });
+    };
+    if forward {
+        Some(found.into_iter().find(|&i| i > current).unwrap_or(first))
+    } else {
+        Some(
+            found
+                .into_iter()
+                .rev()
+                .find(|&i| i < current)
+                .unwrap_or(last),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn block(kind: &str, label: &str, tags: &[&str]) -> BlockInfo {
+        BlockInfo {
+            visual_id: label.into(),
+            node_id: None,
+            kind: kind.into(),
+            translations: HashMap::from([("ru".to_string(), label.to_string())]),
+            range: (0, 0),
+            anchors: vec![],
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            ports: vec![],
+            ai: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
+        }
+    }
+
+    #[test]
+    fn matches_kind_label_and_tags() {
+        let blocks = vec![
+            block("Function", "Загрузка", &["net"]),
+            block("Loop", "Цикл", &[]),
+            block("Function", "Сохранение", &["db"]),
+        ];
+        let ru = Language::Russian;
+        assert_eq!(matches(&blocks, "", ru), None);
+        assert_eq!(
+            matches(&blocks, "function", ru),
+            Some(vec![true, false, true])
+        );
+        assert_eq!(
+            matches(&blocks, "загруз", ru),
+            Some(vec![true, false, false])
+        );
+        assert_eq!(
+            matches(&blocks, "function DB", ru),
+            Some(vec![false, false, true])
+        );
+    }
+
+    #[test]
+    fn steps_through_matches_in_a_ring() {
+        let m = [false, true, false, true];
+        assert_eq!(step(&m, None, true), Some(1));
+        assert_eq!(step(&m, None, false), Some(3));
+        assert_eq!(step(&m, Some(1), true), Some(3));
+        assert_eq!(step(&m, Some(3), true), Some(1));
+        assert_eq!(step(&m, Some(1), false), Some(3));
+        assert_eq!(step(&[false, false], None, true), None);
+    }
+}
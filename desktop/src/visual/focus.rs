@@ -0,0 +1,119 @@
+//! Пространственное перемещение фокуса между блоками холста.
+
+use iced::Point;
+use multicode_core::BlockInfo;
+
+use crate::visual::geometry::block_size;
+
+/// Направление перемещения фокуса.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Центр блока в координатах холста.
+pub fn center(block: &BlockInfo) -> Point {
+    let size = block_size(block);
+    Point::new(
+        block.x as f32 + size.width / 2.0,
+        block.y as f32 + size.height / 2.0,
+    )
+}
+
+/// Ближайший к блоку `from` блок в направлении `dir` среди тех, для
+/// которых `visible` возвращает `true`.
+///
+/// Смещение поперёк направления штрафуется сильнее, чем вдоль него, чтобы
+/// фокус двигался по «строкам» и «столбцам» схемы.
+pub fn neighbor(
+    blocks: &[BlockInfo],
+    from: usize,
+    dir: Direction,
+    visible: impl Fn(&BlockInfo) -> bool,
+) -> Option<usize> {
+    let origin = center(blocks.get(from)?);
+    blocks
+        .iter()
+        .enumerate()
+        .filter(|(i, b)| *i != from && visible(b))
+        .filter_map(|(i, b)| {
+            let c = center(b);
+            let (dx, dy) = (c.x - origin.x, c.y - origin.y);
+            let (along, across) = match dir {
+                Direction::Left => (-dx, dy.abs()),
+                Direction::Right => (dx, dy.abs()),
+                Direction::Up => (-dy, dx.abs()),
+                Direction::Down => (dy, dx.abs()),
+            };
+            (along > 0.0).then_some((i, along + across * 2.0))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(i, _)| i)
+}
+
+/// Блок, получающий фокус первым: верхний левый из видимых.
+pub fn first(blocks: &[BlockInfo], visible: impl Fn(&BlockInfo) -> bool) -> Option<usize> {
+    blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| visible(b))
+        .min_by(|a, b| (a.1.y, a.1.x).partial_cmp(&(b.1.y, b.1.x)).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn block(x: f64, y: f64) -> BlockInfo {
+        BlockInfo {
+            visual_id: format!("{x}:{y}"),
+            node_id: None,
+            kind: "Function".into(),
+            translations: HashMap::new(),
+            range: (0, 0),
+            anchors: vec![],
+            x,
+            y,
+            width: None,
+            height: None,
+            ports: vec![],
+            ai: None,
+            tags: vec![],
+            links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
+        }
+    }
+
+    #[test]
+    fn moves_to_nearest_block_in_direction() {
+        // 0 1
+        // 2 3
+        let blocks = vec![
+            block(0.0, 0.0),
+            block(200.0, 0.0),
+            block(0.0, 100.0),
+            block(200.0, 100.0),
+        ];
+        let all = |_: &BlockInfo| true;
+        assert_eq!(neighbor(&blocks, 0, Direction::Right, all), Some(1));
+        assert_eq!(neighbor(&blocks, 0, Direction::Down, all), Some(2));
+        assert_eq!(neighbor(&blocks, 3, Direction::Left, all), Some(2));
+        assert_eq!(neighbor(&blocks, 3, Direction::Up, all), Some(1));
+        assert_eq!(neighbor(&blocks, 0, Direction::Left, all), None);
+        assert_eq!(first(&blocks, all), Some(0));
+    }
+
+    #[test]
+    fn skips_hidden_blocks() {
+        let blocks = vec![block(0.0, 0.0), block(200.0, 0.0), block(400.0, 0.0)];
+        let visible = |b: &BlockInfo| b.x != 200.0;
+        assert_eq!(neighbor(&blocks, 0, Direction::Right, visible), Some(2));
+    }
+}
@@ -0,0 +1,154 @@
+//! Размеры блоков на холсте.
+//!
+//! Ширина и высота блока берутся из метаданных, а без них — размеры по
+//! умолчанию. Порты правого края сдвигаются вместе с ним, а подпись
+//! переносится по словам в пределах блока.
+
+use iced::{Point, Size};
+use multicode_core::{BlockInfo, Port};
+
+use crate::visual::canvas::{BLOCK_HEIGHT, BLOCK_WIDTH};
+use crate::visual::connection_draw::PORT_RADIUS;
+
+/// Наименьшая ширина блока при изменении размера.
+pub const MIN_WIDTH: f32 = 40.0;
+/// Наименьшая высота блока при изменении размера.
+pub const MIN_HEIGHT: f32 = 20.0;
+/// Размер уголка, за который тянут размер блока.
+pub const RESIZE_HANDLE: f32 = 8.0;
+/// Доля размера шрифта, которую в среднем занимает символ.
+const CHAR_WIDTH: f32 = 0.6;
+/// Отступ подписи от краёв блока.
+pub const LABEL_PADDING: f32 = 5.0;
+
+/// Размер блока: из метаданных или по умолчанию, не меньше наименьшего.
+pub fn block_size(block: &BlockInfo) -> Size {
+    Size::new(
+        block
+            .width
+            .map_or(BLOCK_WIDTH, |w| (w as f32).max(MIN_WIDTH)),
+        block
+            .height
+            .map_or(BLOCK_HEIGHT, |h| (h as f32).max(MIN_HEIGHT)),
+    )
+}
+
+/// Размер блока, который получится, если потянуть его правый нижний угол в
+/// точку холста `pos`.
+pub fn resized(block: &BlockInfo, pos: Point) -> Size {
+    Size::new(
+        (pos.x - block.x as f32).max(MIN_WIDTH),
+        (pos.y - block.y as f32).max(MIN_HEIGHT),
+    )
+}
+
+/// Находится ли точка холста `pos` на уголке изменения размера блока.
+pub fn on_resize_handle(block: &BlockInfo, pos: Point) -> bool {
+    let size = block_size(block);
+    let corner = Point::new(block.x as f32 + size.width, block.y as f32 + size.height);
+    (corner.x - RESIZE_HANDLE..=corner.x).contains(&pos.x)
+        && (corner.y - RESIZE_HANDLE..=corner.y).contains(&pos.y)
+}
+
+/// Выходной ли порт: выходные порты стоят у правого края блока.
+pub fn is_output(port: &Port) -> bool {
+    port.x >= (BLOCK_WIDTH - PORT_RADIUS * 2.0) as f64
+}
+
+/// Точка порта на холсте. Выходные порты остаются у правого края блока
+/// при изменении его ширины.
+pub fn port_point(block: &BlockInfo, port: &Port) -> Point {
+    let shift = if is_output(port) {
+        block_size(block).width - BLOCK_WIDTH
+    } else {
+        0.0
+    };
+    Point::new((block.x + port.x) as f32 + shift, (block.y + port.y) as f32)
+}
+
+/// Разбивает подпись `label` на строки, помещающиеся в блок размера `size`
+/// при шрифте `font_size`. Слова длиннее строки режутся, а строки, не
+/// поместившиеся по высоте, отбрасываются с многоточием в последней.
+pub fn wrap_label(label: &str, size: Size, font_size: f32) -> Vec<String> {
+    let columns = (((size.width - LABEL_PADDING * 2.0) / (font_size * CHAR_WIDTH)) as usize).max(1);
+    let rows = (((size.height - LABEL_PADDING * 2.0) / (font_size * 1.2)) as usize).max(1);
+
+    let mut lines: Vec<String> = Vec::new();
+    let mut line = String::new();
+    for word in label.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let line_len = line.chars().count();
+        if line_len > 0 && line_len + 1 + word.len() <= columns {
+            line.push(' ');
+            line.extend(word);
+            continue;
+        }
+        if line_len > 0 {
+            lines.push(std::mem::take(&mut line));
+        }
+        while word.len() > columns {
+            lines.push(word.drain(..columns).collect());
+        }
+        line = word.into_iter().collect();
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    if lines.len() > rows {
+        lines.truncate(rows);
+        if let Some(last) = lines.last_mut() {
+            let mut chars: Vec<char> = last.chars().collect();
+            chars.truncate(columns.saturating_sub(1));
+            chars.push('…');
+            *last = chars.into_iter().collect();
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_words_and_cuts_overflow() {
+        // 10 символов в строке и 2 строки при шрифте 10.
+        let size = Size::new(70.0, 35.0);
+        assert_eq!(
+            wrap_label("load user data", size, 10.0),
+            vec!["load user", "data"]
+        );
+        assert_eq!(
+            wrap_label("abcdefghijklmn", size, 10.0),
+            vec!["abcdefghij", "klmn"]
+        );
+        assert_eq!(
+            wrap_label("one two three four five", size, 10.0),
+            vec!["one two", "three fou…"]
+        );
+    }
+
+    #[test]
+    fn output_ports_follow_the_right_edge() {
+        let mut block: BlockInfo = serde_json::from_value(serde_json::json!({
+            "visual_id": "a",
+            "kind": "Function",
+            "translations": {},
+            "range": [0, 0],
+            "x": 10.0,
+            "y": 0.0,
+            "ai": null,
+            "width": 200.0
+        }))
+        .unwrap();
+        assert_eq!(block_size(&block), Size::new(200.0, BLOCK_HEIGHT));
+        let output = Port {
+            x: BLOCK_WIDTH as f64,
+            y: 20.0,
+        };
+        assert_eq!(port_point(&block, &output), Point::new(210.0, 20.0));
+        block.width = Some(1.0);
+        assert_eq!(block_size(&block).width, MIN_WIDTH);
+    }
+}
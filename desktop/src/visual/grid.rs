@@ -0,0 +1,225 @@
+//! Сетка холста и привязка блоков при перетаскивании.
+//!
+//! При включённой привязке позиция перетаскиваемого блока округляется до
+//! ячейки сетки, а край или центр, оказавшийся рядом с краем или центром
+//! соседнего блока, выравнивается по нему. Выровненные линии показываются
+//! на холсте направляющими, пока блок перетаскивается.
+
+use iced::{Point, Size};
+use multicode_core::BlockInfo;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::canvas::{BLOCK_HEIGHT, BLOCK_WIDTH};
+use crate::visual::geometry::block_size;
+
+/// Расстояние в координатах холста, на котором край блока притягивается к
+/// краю соседа.
+pub const ALIGN_DISTANCE: f32 = 6.0;
+
+/// Настройки сетки холста.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GridSettings {
+    /// Рисовать сетку.
+    #[serde(default = "default_true")]
+    pub show: bool,
+    /// Привязывать перетаскиваемые блоки к сетке и краям соседей.
+    #[serde(default)]
+    pub snap: bool,
+    /// Размер ячейки в координатах холста.
+    #[serde(default = "default_cell")]
+    pub cell: f32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_cell() -> f32 {
+    40.0
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            show: true,
+            snap: false,
+            cell: default_cell(),
+        }
+    }
+}
+
+/// Направляющая выравнивания по соседнему блоку.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Guide {
+    /// Вертикальная линия с координатой `x`.
+    Vertical(f32),
+    /// Горизонтальная линия с координатой `y`.
+    Horizontal(f32),
+}
+
+/// Позиция блока после привязки и направляющие, по которым он выровнен.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapped {
+    pub position: Point,
+    pub guides: Vec<Guide>,
+}
+
+impl GridSettings {
+    /// Размер ячейки не меньше одной единицы холста.
+    pub fn step(&self) -> f32 {
+        self.cell.max(1.0)
+    }
+
+    /// Округляет `value` до ближайшей линии сетки.
+    pub fn round(&self, value: f32) -> f32 {
+        (value / self.step()).round() * self.step()
+    }
+
+    /// Привязывает позицию `position` блока `index` из `blocks`. Без
+    /// привязки позиция возвращается как есть. Соседи учитываются только
+    /// среди блоков, для которых `visible` возвращает `true`.
+    pub fn snap(
+        &self,
+        blocks: &[BlockInfo],
+        index: usize,
+        position: Point,
+        visible: impl Fn(&BlockInfo) -> bool,
+    ) -> Snapped {
+        if !self.snap {
+            return Snapped {
+                position,
+                guides: Vec::new(),
+            };
+        }
+        let others: Vec<&BlockInfo> = blocks
+            .iter()
+            .enumerate()
+            .filter(|(i, b)| *i != index && visible(b))
+            .map(|(_, b)| b)
+            .collect();
+        let xs: Vec<f32> = others
+            .iter()
+            .flat_map(|b| edges(b.x as f32, block_size(b).width))
+            .collect();
+        let ys: Vec<f32> = others
+            .iter()
+            .flat_map(|b| edges(b.y as f32, block_size(b).height))
+            .collect();
+        let size = blocks
+            .get(index)
+            .map_or(Size::new(BLOCK_WIDTH, BLOCK_HEIGHT), block_size);
+        let x = align(position.x, size.width, &xs).unwrap_or_else(|| self.round(position.x));
+        let y = align(position.y, size.height, &ys).unwrap_or_else(|| self.round(position.y));
+        let mut guides = Vec::new();
+        for edge in edges(x, size.width) {
+            if xs.iter().any(|&o| (o - edge).abs() < 0.5) {
+                guides.push(Guide::Vertical(edge));
+            }
+        }
+        for edge in edges(y, size.height) {
+            if ys.iter().any(|&o| (o - edge).abs() < 0.5) {
+                guides.push(Guide::Horizontal(edge));
+            }
+        }
+        Snapped {
+            position: Point::new(x, y),
+            guides,
+        }
+    }
+}
+
+/// Начало, центр и конец отрезка `start..start + size`.
+fn edges(start: f32, size: f32) -> [f32; 3] {
+    [start, start + size / 2.0, start + size]
+}
+
+/// Начало отрезка длины `size`, при котором один из его краёв совпадает с
+/// ближайшей линией `lines` не дальше [`ALIGN_DISTANCE`].
+fn align(start: f32, size: f32, lines: &[f32]) -> Option<f32> {
+    let mut best: Option<(f32, f32)> = None;
+    for (edge, shift) in edges(start, size).into_iter().zip([0.0, size / 2.0, size]) {
+        for &line in lines {
+            let distance = (line - edge).abs();
+            if distance <= ALIGN_DISTANCE && best.is_none_or(|(d, _)| distance < d) {
+                best = Some((distance, line - shift));
+            }
+        }
+    }
+    best.map(|(_, start)| start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn block(x: f64, y: f64) -> BlockInfo {
+        BlockInfo {
+            visual_id: format!("{x}:{y}"),
+            node_id: None,
+            kind: "Function".into(),
+            translations: HashMap::new(),
+            range: (0, 0),
+            anchors: vec![],
+            x,
+            y,
+            width: None,
+            height: None,
+            ports: vec![],
+            ai: None,
+            tags: vec![],
+            links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
+        }
+    }
+
+    fn snapping() -> GridSettings {
+        GridSettings {
+            snap: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn rounds_to_cell() {
+        let grid = GridSettings {
+            cell: 20.0,
+            ..snapping()
+        };
+        let snapped = grid.snap(&[block(0.0, 0.0)], 0, Point::new(33.0, 47.0), |_| true);
+        assert_eq!(snapped.position, Point::new(40.0, 40.0));
+        assert!(snapped.guides.is_empty());
+    }
+
+    #[test]
+    fn disabled_keeps_position() {
+        let grid = GridSettings::default();
+        let snapped = grid.snap(&[block(0.0, 0.0)], 0, Point::new(33.0, 47.0), |_| true);
+        assert_eq!(snapped.position, Point::new(33.0, 47.0));
+    }
+
+    #[test]
+    fn aligns_to_neighbour_edges() {
+        let blocks = [block(0.0, 0.0), block(203.0, 300.0)];
+        let snapped = snapping().snap(&blocks, 0, Point::new(205.0, 117.0), |_| true);
+        // Левый край выровнен по соседу, верх — по сетке.
+        assert_eq!(snapped.position, Point::new(203.0, 120.0));
+        assert_eq!(
+            snapped.guides,
+            vec![
+                Guide::Vertical(203.0),
+                Guide::Vertical(263.0),
+                Guide::Vertical(323.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn hidden_neighbours_are_ignored() {
+        let blocks = [block(0.0, 0.0), block(203.0, 300.0)];
+        let snapped = snapping().snap(&blocks, 0, Point::new(205.0, 117.0), |b| b.x == 0.0);
+        assert_eq!(snapped.position, Point::new(200.0, 120.0));
+    }
+}
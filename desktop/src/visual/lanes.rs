@@ -0,0 +1,204 @@
+//! Группы блоков на холсте.
+//!
+//! Блоки объединяются в контейнеры по файлу, модулю или тегу. Состав
+//! групп вычисляется заново из блоков при каждом обновлении холста, поэтому
+//! смена тега или перенос кода сразу переносит блок в другую группу.
+//! Свёрнутая группа показывается одной плашкой с числом блоков.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use multicode_core::meta::file_ref::{FILE_REF_KIND, FILE_REF_TAG};
+use multicode_core::BlockInfo;
+use serde::{Deserialize, Serialize};
+
+use crate::visual::lod::{kind_level, DetailLevel};
+
+/// Признак, по которому блоки собираются в группы.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LaneGrouping {
+    /// Без групп.
+    #[default]
+    None,
+    /// Блоки файла и блоки-ссылки на каждый другой файл.
+    File,
+    /// Блоки внутри одного модуля, класса или другого структурного блока.
+    Module,
+    /// Блоки с одинаковым первым тегом.
+    Tag,
+}
+
+impl LaneGrouping {
+    pub const ALL: [LaneGrouping; 4] = [
+        LaneGrouping::None,
+        LaneGrouping::File,
+        LaneGrouping::Module,
+        LaneGrouping::Tag,
+    ];
+}
+
+impl fmt::Display for LaneGrouping {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            LaneGrouping::None => "нет",
+            LaneGrouping::File => "по файлу",
+            LaneGrouping::Module => "по модулю",
+            LaneGrouping::Tag => "по тегу",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Группа блоков холста.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lane {
+    /// Ключ группы, по которому запоминается её свёрнутость.
+    pub key: String,
+    pub title: String,
+    /// Индексы блоков группы.
+    pub members: Vec<usize>,
+    pub collapsed: bool,
+}
+
+/// Подпись блока: перевод на любой язык или вид.
+fn label(block: &BlockInfo) -> String {
+    let mut translations: Vec<(&String, &String)> = block.translations.iter().collect();
+    translations.sort();
+    translations
+        .first()
+        .map(|(_, text)| text.to_string())
+        .unwrap_or_else(|| block.kind.clone())
+}
+
+/// Ближайший структурный блок, внутри которого лежит `index`. Сам
+/// структурный блок открывает свою группу.
+fn container(blocks: &[BlockInfo], index: usize) -> Option<usize> {
+    let block = &blocks[index];
+    let is_structure =
+        |b: &BlockInfo| b.kind != FILE_REF_KIND && kind_level(&b.kind) == DetailLevel::Structure;
+    if is_structure(block) {
+        return Some(index);
+    }
+    blocks
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| {
+            is_structure(c)
+                && c.range != block.range
+                && c.range.0 <= block.range.0
+                && block.range.1 <= c.range.1
+        })
+        .min_by_key(|(_, c)| c.range.1 - c.range.0)
+        .map(|(i, _)| i)
+}
+
+/// Группы блоков `blocks` по признаку `grouping`. `file` — имя файла
+/// вкладки, им подписывается группа блоков кода. Блоки без группы, например
+/// без тегов при группировке по тегу, в группы не входят. Группы
+/// возвращаются развёрнутыми в порядке ключей.
+pub fn lanes(blocks: &[BlockInfo], grouping: LaneGrouping, file: &str) -> Vec<Lane> {
+    let mut groups: BTreeMap<String, Lane> = BTreeMap::new();
+    for (i, block) in blocks.iter().enumerate() {
+        let group = match grouping {
+            LaneGrouping::None => None,
+            LaneGrouping::File if block.kind == FILE_REF_KIND => {
+                let name = label(block);
+                Some((format!("file:{name}"), name))
+            }
+            LaneGrouping::File => Some((format!("file:{file}"), file.to_string())),
+            LaneGrouping::Module => container(blocks, i).map(|c| {
+                let c = &blocks[c];
+                (format!("module:{}", c.visual_id), label(c))
+            }),
+            LaneGrouping::Tag => block
+                .tags
+                .iter()
+                .find(|t| *t != FILE_REF_TAG)
+                .map(|tag| (format!("tag:{tag}"), format!("#{tag}"))),
+        };
+        let Some((key, title)) = group else {
+            continue;
+        };
+        groups
+            .entry(key.clone())
+            .or_insert_with(|| Lane {
+                key,
+                title,
+                members: Vec::new(),
+                collapsed: false,
+            })
+            .members
+            .push(i);
+    }
+    groups.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn block(id: &str, kind: &str, range: (usize, usize), tags: &[&str]) -> BlockInfo {
+        BlockInfo {
+            visual_id: id.into(),
+            node_id: None,
+            kind: kind.into(),
+            translations: HashMap::new(),
+            range,
+            anchors: vec![],
+            x: 0.0,
+            y: 0.0,
+            width: None,
+            height: None,
+            ports: vec![],
+            ai: None,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
+        }
+    }
+
+    fn members(lanes: &[Lane]) -> Vec<(&str, Vec<usize>)> {
+        lanes
+            .iter()
+            .map(|l| (l.key.as_str(), l.members.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn groups_by_module() {
+        let blocks = vec![
+            block("m", "mod_item", (0, 100), &[]),
+            block("f", "Function", (10, 40), &[]),
+            block("c", "Function/Call", (20, 30), &[]),
+            block("top", "Function", (120, 140), &[]),
+        ];
+        let lanes = lanes(&blocks, LaneGrouping::Module, "a.rs");
+        assert_eq!(members(&lanes), vec![("module:m", vec![0, 1, 2])]);
+        assert_eq!(lanes[0].title, "mod_item");
+    }
+
+    #[test]
+    fn groups_by_tag_and_file() {
+        let mut link = block("r", FILE_REF_KIND, (0, 0), &[FILE_REF_TAG]);
+        link.translations.insert("ru".into(), "b.rs".into());
+        let blocks = vec![
+            block("a", "Function", (0, 10), &["net", "api"]),
+            block("b", "Function", (20, 30), &[]),
+            block("c", "Function", (40, 50), &["net"]),
+            link,
+        ];
+        let by_tag = lanes(&blocks, LaneGrouping::Tag, "a.rs");
+        assert_eq!(members(&by_tag), vec![("tag:net", vec![0, 2])]);
+        assert_eq!(by_tag[0].title, "#net");
+
+        let by_file = lanes(&blocks, LaneGrouping::File, "a.rs");
+        assert_eq!(
+            members(&by_file),
+            vec![("file:a.rs", vec![0, 1, 2]), ("file:b.rs", vec![3])]
+        );
+        assert!(lanes(&blocks, LaneGrouping::None, "a.rs").is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+//! Слои холста.
+//!
+//! Холст состоит из слоёв: блоки кода, связи, заметки ИИ и замечания
+//! проверки. Каждый слой можно скрыть, чтобы разгрузить схему. Скрытый слой
+//! блоков оставляет от блоков только рамки, чтобы связи и пометки
+//! оставались привязанными к своим местам.
+
+use std::fmt;
+
+use multicode_core::BlockInfo;
+use serde::{Deserialize, Serialize};
+
+/// Слой холста.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// Заливка и подписи блоков кода.
+    Blocks,
+    /// Связи между портами блоков.
+    Links,
+    /// Заметки ИИ под блоками.
+    Ai,
+    /// Число замечаний проверки у блоков.
+    Lint,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 4] = [Layer::Blocks, Layer::Links, Layer::Ai, Layer::Lint];
+}
+
+impl fmt::Display for Layer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Layer::Blocks => "Блоки",
+            Layer::Links => "Связи",
+            Layer::Ai => "ИИ",
+            Layer::Lint => "Проверка",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Видимость слоёв холста.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanvasLayers {
+    #[serde(default = "default_true")]
+    pub blocks: bool,
+    #[serde(default = "default_true")]
+    pub links: bool,
+    #[serde(default = "default_true")]
+    pub ai: bool,
+    #[serde(default = "default_true")]
+    pub lint: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for CanvasLayers {
+    fn default() -> Self {
+        Self {
+            blocks: true,
+            links: true,
+            ai: true,
+            lint: true,
+        }
+    }
+}
+
+impl CanvasLayers {
+    /// Виден ли слой `layer`.
+    pub fn shows(&self, layer: Layer) -> bool {
+        match layer {
+            Layer::Blocks => self.blocks,
+            Layer::Links => self.links,
+            Layer::Ai => self.ai,
+            Layer::Lint => self.lint,
+        }
+    }
+
+    /// Показывает скрытый слой `layer` или скрывает видимый.
+    pub fn toggle(&mut self, layer: Layer) {
+        let flag = match layer {
+            Layer::Blocks => &mut self.blocks,
+            Layer::Links => &mut self.links,
+            Layer::Ai => &mut self.ai,
+            Layer::Lint => &mut self.lint,
+        };
+        *flag = !*flag;
+    }
+}
+
+/// Текст заметки ИИ блока: описание или, без него, первая подсказка.
+pub fn ai_note(block: &BlockInfo) -> Option<&str> {
+    let ai = block.ai.as_ref()?;
+    ai.description
+        .as_deref()
+        .or_else(|| ai.hints.first().map(String::as_str))
+        .filter(|s| !s.trim().is_empty())
+}
+
+/// Номера замечаний проверки `findings`, приходящихся на каждый блок
+/// `blocks` файла `content`. Замечание задано `visual_id` блока, если он
+/// известен, и позицией `(строка, байт в строке)` с нуля. Замечание без
+/// блока относится к самому вложенному блоку, в который попадает позиция.
+pub fn lint_by_block(
+    blocks: &[BlockInfo],
+    content: &str,
+    findings: &[(Option<&str>, usize, usize)],
+) -> Vec<Vec<usize>> {
+    let mut starts = vec![0];
+    starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+    let mut found = vec![Vec::new(); blocks.len()];
+    for (n, &(id, line, column)) in findings.iter().enumerate() {
+        let owner = match id {
+            Some(id) => blocks.iter().position(|b| b.visual_id == id),
+            None => starts.get(line).and_then(|start| {
+                let offset = start + column;
+                blocks
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| (b.range.0..b.range.1).contains(&offset))
+                    .min_by_key(|(_, b)| b.range.1 - b.range.0)
+                    .map(|(i, _)| i)
+            }),
+        };
+        if let Some(index) = owner {
+            found[index].push(n);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(range: (usize, usize)) -> BlockInfo {
+        serde_json::from_value(serde_json::json!({
+            "visual_id": format!("{}-{}", range.0, range.1),
+            "kind": "Function",
+            "translations": {},
+            "range": [range.0, range.1],
+            "x": 0.0,
+            "y": 0.0,
+            "ai": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn lint_findings_go_to_their_blocks() {
+        let content = "mod a {\n    fn b() {}\n}\nfn c() {}\n";
+        let blocks = vec![block((0, 23)), block((12, 21)), block((24, 33))];
+        let findings = [
+            (None, 0, 0),
+            (None, 1, 4),
+            (Some("24-33"), 0, 0),
+            (None, 1, 6),
+            (None, 9, 0),
+            (Some("missing"), 3, 0),
+        ];
+        assert_eq!(
+            lint_by_block(&blocks, content, &findings),
+            vec![vec![0], vec![1, 3], vec![2]]
+        );
+    }
+
+    #[test]
+    fn layers_toggle_and_default_to_visible() {
+        let mut layers: CanvasLayers = serde_json::from_str(r#"{"ai":false}"#).unwrap();
+        assert!(layers.shows(Layer::Blocks) && !layers.shows(Layer::Ai));
+        layers.toggle(Layer::Ai);
+        assert_eq!(layers, CanvasLayers::default());
+    }
+}
@@ -0,0 +1,114 @@
+//! Уровни детализации холста в зависимости от масштаба.
+//!
+//! При сильном отдалении видны только структурные блоки (модули, классы,
+//! типы), на среднем масштабе добавляются функции, а при приближении
+//! показываются все инструкции и операторы.
+
+use multicode_core::meta::file_ref::FILE_REF_KIND;
+use multicode_core::BlockInfo;
+use serde::{Deserialize, Serialize};
+
+/// Уровень детализации. Порядок вариантов соответствует росту детализации.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DetailLevel {
+    /// Модули, классы, структуры и другие объявления верхнего уровня.
+    Structure,
+    /// Дополнительно функции и методы.
+    Functions,
+    /// Все блоки, включая инструкции и операторы.
+    Statements,
+}
+
+/// Пороги масштаба для переключения уровней детализации.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LodSettings {
+    /// Ниже этого масштаба видны только структурные блоки.
+    #[serde(default = "default_structure_below")]
+    pub structure_below: f32,
+    /// Ниже этого масштаба скрываются инструкции и операторы.
+    #[serde(default = "default_functions_below")]
+    pub functions_below: f32,
+}
+
+fn default_structure_below() -> f32 {
+    0.4
+}
+
+fn default_functions_below() -> f32 {
+    0.8
+}
+
+impl Default for LodSettings {
+    fn default() -> Self {
+        Self {
+            structure_below: default_structure_below(),
+            functions_below: default_functions_below(),
+        }
+    }
+}
+
+impl LodSettings {
+    /// Уровень детализации для масштаба `scale`.
+    pub fn level(&self, scale: f32) -> DetailLevel {
+        if scale < self.structure_below {
+            DetailLevel::Structure
+        } else if scale < self.functions_below {
+            DetailLevel::Functions
+        } else {
+            DetailLevel::Statements
+        }
+    }
+}
+
+/// Минимальный уровень детализации, на котором виден блок вида `kind`.
+pub fn kind_level(kind: &str) -> DetailLevel {
+    if kind == FILE_REF_KIND {
+        return DetailLevel::Structure;
+    }
+    let k = kind.to_lowercase();
+    if k.contains("expression") {
+        return DetailLevel::Statements;
+    }
+    const STRUCTURE: &[&str] = &[
+        "mod", "module", "class", "struct", "enum", "trait", "impl", "interface", "namespace",
+        "program", "source_file",
+    ];
+    if STRUCTURE.iter().any(|s| k.contains(s)) {
+        DetailLevel::Structure
+    } else if k.starts_with("function/define") || k == "function" || k.contains("method") {
+        DetailLevel::Functions
+    } else {
+        DetailLevel::Statements
+    }
+}
+
+/// Виден ли блок на уровне `level`.
+pub fn is_visible(block: &BlockInfo, level: DetailLevel) -> bool {
+    kind_level(&block.kind) <= level
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds_select_level() {
+        let lod = LodSettings::default();
+        assert_eq!(lod.level(0.2), DetailLevel::Structure);
+        assert_eq!(lod.level(0.5), DetailLevel::Functions);
+        assert_eq!(lod.level(1.0), DetailLevel::Statements);
+    }
+
+    #[test]
+    fn taxonomy_orders_kinds() {
+        assert_eq!(kind_level("struct_item"), DetailLevel::Structure);
+        assert_eq!(kind_level("class_definition"), DetailLevel::Structure);
+        assert_eq!(kind_level(FILE_REF_KIND), DetailLevel::Structure);
+        assert_eq!(kind_level("Function/Define"), DetailLevel::Functions);
+        assert_eq!(kind_level("Function"), DetailLevel::Functions);
+        assert_eq!(kind_level("Function/Call"), DetailLevel::Statements);
+        assert_eq!(kind_level("Op/+"), DetailLevel::Statements);
+        assert_eq!(kind_level("Loop"), DetailLevel::Statements);
+        assert_eq!(kind_level("struct_expression"), DetailLevel::Statements);
+    }
+}
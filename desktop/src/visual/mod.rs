@@ -1,9 +1,18 @@
+pub mod accessibility;
 pub mod blocks;
 pub mod canvas;
 pub mod change;
 pub mod connection_draw;
 pub mod connections;
+pub mod filter;
+pub mod focus;
+pub mod geometry;
+pub mod grid;
+pub mod lanes;
+pub mod layers;
+pub mod lod;
 pub mod palette;
+pub mod profile;
 pub mod serialization;
 pub mod suggestions;
 pub mod translations;
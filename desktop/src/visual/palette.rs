@@ -291,10 +291,15 @@ mod tests {
             anchors: vec![],
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             ports: vec![],
             ai: None,
             tags: vec![],
             links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
         })
     }
 
@@ -324,10 +329,15 @@ mod tests {
             anchors: vec![],
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             ports: vec![],
             ai: None,
             tags: tags.into_iter().map(|s| s.to_string()).collect(),
             links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
         })
     }
 
@@ -0,0 +1,150 @@
+//! Профиль холста: раскраска блоков по измеренной стоимости.
+//!
+//! Блок окрашивается по времени разбора его текста или по числу выполнений
+//! из отчёта о покрытии. Ниже порога «тёплый» блок зелёный, от него до
+//! порога «горячий» цвет переходит в оранжевый, а с порога «горячий» блок
+//! красный.
+
+use std::fmt;
+use std::time::Duration;
+
+use iced::Color;
+use serde::{Deserialize, Serialize};
+
+/// Мера стоимости блока.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProfileMetric {
+    /// Время разбора текста блока.
+    ParseTime,
+    /// Число выполнений по отчёту о покрытии.
+    Executions,
+}
+
+impl fmt::Display for ProfileMetric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            ProfileMetric::ParseTime => "время разбора",
+            ProfileMetric::Executions => "число выполнений",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Пороги раскраски профиля.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileSettings {
+    /// Время разбора «тёплого» блока, мкс.
+    #[serde(default = "default_parse_warm")]
+    pub parse_warm_us: u64,
+    /// Время разбора «горячего» блока, мкс.
+    #[serde(default = "default_parse_hot")]
+    pub parse_hot_us: u64,
+    /// Число выполнений «тёплого» блока.
+    #[serde(default = "default_exec_warm")]
+    pub exec_warm: u64,
+    /// Число выполнений «горячего» блока.
+    #[serde(default = "default_exec_hot")]
+    pub exec_hot: u64,
+}
+
+fn default_parse_warm() -> u64 {
+    100
+}
+
+fn default_parse_hot() -> u64 {
+    1000
+}
+
+fn default_exec_warm() -> u64 {
+    10
+}
+
+fn default_exec_hot() -> u64 {
+    1000
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self {
+            parse_warm_us: default_parse_warm(),
+            parse_hot_us: default_parse_hot(),
+            exec_warm: default_exec_warm(),
+            exec_hot: default_exec_hot(),
+        }
+    }
+}
+
+impl ProfileSettings {
+    /// Пороги «тёплый» и «горячий» меры `metric`.
+    pub fn thresholds(&self, metric: ProfileMetric) -> (u64, u64) {
+        match metric {
+            ProfileMetric::ParseTime => (self.parse_warm_us, self.parse_hot_us),
+            ProfileMetric::Executions => (self.exec_warm, self.exec_hot),
+        }
+    }
+
+    /// Задаёт порог «горячий» (`hot`) или «тёплый» меры `metric`.
+    pub fn set_threshold(&mut self, metric: ProfileMetric, hot: bool, value: u64) {
+        let threshold = match (metric, hot) {
+            (ProfileMetric::ParseTime, false) => &mut self.parse_warm_us,
+            (ProfileMetric::ParseTime, true) => &mut self.parse_hot_us,
+            (ProfileMetric::Executions, false) => &mut self.exec_warm,
+            (ProfileMetric::Executions, true) => &mut self.exec_hot,
+        };
+        *threshold = value;
+    }
+}
+
+/// Значение меры `metric` для подписи блока и легенды.
+pub fn format_value(metric: ProfileMetric, value: u64) -> String {
+    match metric {
+        ProfileMetric::ParseTime => format!("{value} мкс"),
+        ProfileMetric::Executions => format!("×{value}"),
+    }
+}
+
+/// Значение времени разбора `cost` в единицах меры.
+pub fn parse_value(cost: Duration) -> u64 {
+    cost.as_micros() as u64
+}
+
+/// Нагрев блока со значением `value` при порогах `thresholds`: 0 ниже
+/// порога «тёплый», 1 с порога «горячий», между ними — по линейной шкале.
+pub fn heat(value: u64, thresholds: (u64, u64)) -> f32 {
+    let (warm, hot) = thresholds;
+    if value < warm {
+        0.0
+    } else if value >= hot || hot <= warm {
+        1.0
+    } else {
+        (value - warm) as f32 / (hot - warm) as f32
+    }
+}
+
+/// Цвет блока с нагревом `heat`: зелёный, не достигший порога «тёплый»,
+/// от жёлтого к оранжевому между порогами и красный с порога «горячий».
+pub fn heat_color(heat: f32) -> Color {
+    if heat <= 0.0 {
+        Color::from_rgb(0.4, 0.75, 0.4)
+    } else if heat >= 1.0 {
+        Color::from_rgb(0.9, 0.2, 0.2)
+    } else {
+        Color::from_rgb(0.95, 0.85 - 0.4 * heat, 0.3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heat_follows_thresholds() {
+        assert_eq!(heat(5, (10, 110)), 0.0);
+        assert_eq!(heat(60, (10, 110)), 0.5);
+        assert_eq!(heat(110, (10, 110)), 1.0);
+        // Пороги наоборот не делят шкалу.
+        assert_eq!(heat(10, (10, 5)), 1.0);
+        assert_eq!(parse_value(Duration::from_millis(2)), 2000);
+        assert_eq!(format_value(ProfileMetric::Executions, 3), "×3");
+    }
+}
@@ -18,10 +18,15 @@ fn serialize_roundtrip() {
             anchors: vec![],
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             ports: vec![],
             ai: None,
             tags: vec![],
             links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
         },
         BlockInfo {
             visual_id: "b".into(),
@@ -32,10 +37,15 @@ fn serialize_roundtrip() {
             anchors: vec![],
             x: 1.0,
             y: 1.0,
+            width: None,
+            height: None,
             ports: vec![],
             ai: None,
             tags: vec![],
             links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
         },
     ];
 
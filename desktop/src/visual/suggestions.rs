@@ -1,4 +1,5 @@
 use super::palette::{PaletteBlock, DEFAULT_CATEGORY};
+use multicode_core::meta::suggest::MetaSuggestion;
 use std::collections::HashSet;
 
 /// Maximum number of suggestions to return.
@@ -69,6 +70,20 @@ pub fn suggest_blocks(
         .unwrap_or_default()
 }
 
+/// Metadata suggestions from the core engine that the user has not rejected.
+///
+/// Rejections are remembered by [`MetaSuggestion::key`], so a rejected tag or
+/// link stays hidden after the block moves.
+pub fn pending_meta(
+    suggestions: Vec<MetaSuggestion>,
+    rejected: &HashSet<String>,
+) -> Vec<MetaSuggestion> {
+    suggestions
+        .into_iter()
+        .filter(|s| !rejected.contains(&s.key()))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,10 +102,15 @@ mod tests {
             anchors: vec![],
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             ports: vec![],
             ai: None,
             tags: vec![],
             links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
         })
     }
 
@@ -106,10 +126,15 @@ mod tests {
             anchors: vec![],
             x: 0.0,
             y: 0.0,
+            width: None,
+            height: None,
             ports: vec![],
             ai: None,
             tags: tags.iter().map(|t| t.to_string()).collect(),
             links: vec![],
+            color: None,
+            icon: None,
+            doc: None,
         })
     }
 
@@ -167,6 +192,26 @@ mod tests {
         assert!(res.is_empty());
     }
 
+    #[test]
+    fn rejected_meta_suggestions_are_hidden() {
+        use multicode_core::meta::suggest::SuggestionKind;
+        use std::path::PathBuf;
+
+        let suggestion = |id: &str, tag: &str| MetaSuggestion {
+            file: PathBuf::from("net.py"),
+            id: id.into(),
+            line: 0,
+            name: "send_request".into(),
+            kind: SuggestionKind::NameTag(tag.into()),
+        };
+        let rejected: HashSet<String> = [suggestion("1", "send").key()].into();
+        let pending = pending_meta(
+            vec![suggestion("2", "send"), suggestion("2", "request")],
+            &rejected,
+        );
+        assert_eq!(pending, vec![suggestion("2", "request")]);
+    }
+
     #[test]
     fn respects_limit() {
         // Create more suggestions than the limit and ensure the result is truncated.
@@ -156,6 +156,126 @@ fn format_log_translations() {
             en: "blocks updated: 3",
             ru: "обновлено блоков: 3",
         },
+        Case {
+            key: LogMessage::HookFinished,
+            args: vec!["ok"],
+            en: "hook finished: ok",
+            ru: "хук выполнен: ok",
+        },
+        Case {
+            key: LogMessage::HookFailed,
+            args: vec!["exit 1"],
+            en: "hook failed: exit 1",
+            ru: "ошибка хука: exit 1",
+        },
+        Case {
+            key: LogMessage::SyncRecordingStopped,
+            args: vec!["4"],
+            en: "sync recording stopped, messages: 4",
+            ru: "запись синхронизации остановлена, сообщений: 4",
+        },
+        Case {
+            key: LogMessage::BlocksPasted,
+            args: vec!["2"],
+            en: "blocks pasted, metadata entries: 2",
+            ru: "блоки вставлены, записей метаданных: 2",
+        },
+        Case {
+            key: LogMessage::FileRefNotFound,
+            args: vec!["src/a.rs"],
+            en: "referenced file not found: src/a.rs",
+            ru: "файл по ссылке не найден: src/a.rs",
+        },
+        Case {
+            key: LogMessage::ThemeLoadError,
+            args: vec!["bad color"],
+            en: "theme load error: bad color",
+            ru: "ошибка загрузки темы: bad color",
+        },
+        Case {
+            key: LogMessage::TagsMerged,
+            args: vec!["net", "network", "3"],
+            en: "tags net renamed to network, files: 3",
+            ru: "теги net переименованы в network, файлов: 3",
+        },
+        Case {
+            key: LogMessage::SearchSaved,
+            args: vec!["net"],
+            en: "search saved: net",
+            ru: "поиск сохранён: net",
+        },
+        Case {
+            key: LogMessage::FormatError,
+            args: vec!["exit 1"],
+            en: "format error: exit 1",
+            ru: "ошибка форматирования: exit 1",
+        },
+        Case {
+            key: LogMessage::OpenLinkError,
+            args: vec!["https://example.com", "not found"],
+            en: "failed to open https://example.com: not found",
+            ru: "не удалось открыть https://example.com: not found",
+        },
+        Case {
+            key: LogMessage::AutosaveError,
+            args: vec!["disk full"],
+            en: "autosave error: disk full",
+            ru: "ошибка автосохранения: disk full",
+        },
+        Case {
+            key: LogMessage::TabsRecovered,
+            args: vec!["2"],
+            en: "unsaved changes restored, files: 2",
+            ru: "несохранённые изменения восстановлены, файлов: 2",
+        },
+        Case {
+            key: LogMessage::Committed,
+            args: vec!["fix parser"],
+            en: "commit created: fix parser",
+            ru: "коммит создан: fix parser",
+        },
+        Case {
+            key: LogMessage::BranchSwitched,
+            args: vec!["main"],
+            en: "switched to branch main",
+            ru: "переключено на ветку main",
+        },
+        Case {
+            key: LogMessage::BranchCreated,
+            args: vec!["feature"],
+            en: "branch created: feature",
+            ru: "ветка создана: feature",
+        },
+        Case {
+            key: LogMessage::ChangesStashed,
+            args: vec![],
+            en: "changes stashed",
+            ru: "изменения отложены",
+        },
+        Case {
+            key: LogMessage::StashApplied,
+            args: vec![],
+            en: "stashed changes restored",
+            ru: "отложенные изменения возвращены",
+        },
+        Case {
+            key: LogMessage::LintFinished,
+            args: vec!["a.rs", "2"],
+            en: "lint finished for a.rs: 2 problems",
+            ru: "проверка a.rs завершена: проблем 2",
+        },
+        Case {
+            key: LogMessage::UnknownCommand,
+            args: vec!["deploy"],
+            en: "unknown command: deploy",
+            ru: "неизвестная команда: deploy",
+        },
+        Case {
+            key: LogMessage::MacroSaved,
+            args: vec!["cleanup", "3"],
+            en: "macro cleanup saved: 3 steps",
+            ru: "макрос cleanup сохранён: шагов 3",
+        },
         Case {
             key: LogMessage::Raw,
             args: vec!["raw"],
@@ -184,3 +304,13 @@ fn format_log_translations() {
         );
     }
 }
+
+#[test]
+fn format_log_shows_module() {
+    let ts = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let entry = LogEntry::raw("хук выполнен".into(), ts).with_module("core::config");
+    assert_eq!(
+        "[00:00:00] core::config: хук выполнен",
+        format_log(&entry, Language::English)
+    );
+}
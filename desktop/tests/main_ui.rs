@@ -14,10 +14,15 @@ fn sample_block() -> BlockInfo {
         anchors: Vec::new(),
         x: 0.0,
         y: 0.0,
+        width: None,
+        height: None,
         ports: Vec::new(),
         ai: None,
         tags: Vec::new(),
         links: Vec::new(),
+        color: None,
+        icon: None,
+        doc: None,
     }
 }
 
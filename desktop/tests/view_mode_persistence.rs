@@ -1,5 +1,5 @@
 use desktop::app::{events::Message, MulticodeApp, ViewMode};
-use iced::Application;
+use iced::multi_window::Application;
 use tempfile::tempdir;
 
 #[test]